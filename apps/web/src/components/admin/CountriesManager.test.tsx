@@ -153,4 +153,28 @@ describe('CountriesManager', () => {
       expect(screen.getByText('DE')).toBeTruthy();
     });
   });
+
+  it('loads the report of customers outside coverage', async () => {
+    mockRequest.mockResolvedValueOnce({
+      items: [
+        { country: 'DE', nameEn: 'Germany', isSupported: false, hasMapCoverage: false, customerCount: 4, accountCount: 2, ungeocodedCount: 3 },
+        { country: 'XX', nameEn: null, isSupported: null, hasMapCoverage: null, customerCount: 1, accountCount: 1, ungeocodedCount: 1 },
+      ],
+    });
+    render(<CountriesManager />);
+    fireEvent.click(screen.getByText('countries_coverage_load_btn'));
+    await waitFor(() => {
+      expect(mockRequest).toHaveBeenCalledWith('sazinka.admin.countries.coverage', expect.anything());
+      expect(screen.getByText('Germany')).toBeTruthy();
+      expect(screen.getByText('countries_coverage_unsupported')).toBeTruthy();
+      expect(screen.getByText('countries_coverage_unknown')).toBeTruthy();
+    });
+  });
+
+  it('shows empty state when all customers are covered', async () => {
+    mockRequest.mockResolvedValueOnce({ items: [] });
+    render(<CountriesManager />);
+    fireEvent.click(screen.getByText('countries_coverage_load_btn'));
+    await waitFor(() => expect(screen.getByText('countries_coverage_empty')).toBeTruthy());
+  });
 });
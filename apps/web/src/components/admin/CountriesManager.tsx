@@ -26,6 +26,16 @@ interface SyncResult {
   updated: number;
 }
 
+interface CoverageGap {
+  country: string;
+  nameEn: string | null;
+  isSupported: boolean | null;
+  hasMapCoverage: boolean | null;
+  customerCount: number;
+  accountCount: number;
+  ungeocodedCount: number;
+}
+
 type ApiEnvelope<T> = { payload?: T };
 
 const unwrap = <T,>(r: ApiEnvelope<T> | T): T => {
@@ -46,6 +56,8 @@ export function CountriesManager() {
   const [error, setError] = useState<string | null>(null);
   const [search, setSearch] = useState('');
   const [loaded, setLoaded] = useState(false);
+  const [coverageGaps, setCoverageGaps] = useState<CoverageGap[] | null>(null);
+  const [coverageLoading, setCoverageLoading] = useState(false);
 
   const loadCountries = useCallback(async () => {
     setLoading(true);
@@ -65,6 +77,23 @@ export function CountriesManager() {
     }
   }, [request, t]);
 
+  const loadCoverage = useCallback(async () => {
+    setCoverageLoading(true);
+    setError(null);
+    try {
+      const resp = await request<unknown, ApiEnvelope<{ items: CoverageGap[] }>>(
+        'sazinka.admin.countries.coverage',
+        createRequest(getToken(), {})
+      );
+      const data = unwrap(resp);
+      setCoverageGaps(data.items ?? []);
+    } catch (e) {
+      setError(t('countries_coverage_error'));
+    } finally {
+      setCoverageLoading(false);
+    }
+  }, [request, t]);
+
   const handleSync = async () => {
     setSyncing(true);
     setSyncResult(null);
@@ -92,6 +121,7 @@ export function CountriesManager() {
       );
       const updated = unwrap(resp);
       setCountries(prev => prev.map(c => c.code === code ? updated : c));
+      if (coverageGaps !== null) await loadCoverage();
     } catch (e) {
       setError(t('countries_error_update'));
     }
@@ -104,6 +134,12 @@ export function CountriesManager() {
     return c.nameEn;
   };
 
+  const gapReason = (g: CoverageGap) => {
+    if (g.isSupported === null) return t('countries_coverage_unknown');
+    if (!g.isSupported) return t('countries_coverage_unsupported');
+    return t('countries_coverage_no_map');
+  };
+
   const filtered = countries.filter(c => {
     const q = search.trim().toLowerCase();
     if (!q) return true;
@@ -224,6 +260,48 @@ export function CountriesManager() {
       {!loaded && !loading && (
         <div className={styles.hint}>{t('countries_load_hint')}</div>
       )}
+
+      <div className={styles.header}>
+        <h3>{t('countries_coverage_title')}</h3>
+        <div className={styles.headerActions}>
+          <button type="button" className={styles.loadBtn} onClick={loadCoverage} disabled={coverageLoading}>
+            {coverageLoading ? t('countries_loading') : t('countries_coverage_load_btn')}
+          </button>
+        </div>
+      </div>
+
+      {coverageGaps !== null && coverageGaps.length === 0 && (
+        <div className={styles.empty}>{t('countries_coverage_empty')}</div>
+      )}
+
+      {coverageGaps !== null && coverageGaps.length > 0 && (
+        <div className={styles.tableWrapper}>
+          <table className={styles.table}>
+            <thead>
+              <tr>
+                <th>{t('countries_col_code')}</th>
+                <th>{t('countries_col_name')}</th>
+                <th>{t('countries_coverage_col_reason')}</th>
+                <th>{t('countries_coverage_col_customers')}</th>
+                <th>{t('countries_coverage_col_accounts')}</th>
+                <th>{t('countries_coverage_col_ungeocoded')}</th>
+              </tr>
+            </thead>
+            <tbody>
+              {coverageGaps.map((g) => (
+                <tr key={g.country}>
+                  <td className={styles.codeCell}>{g.country}</td>
+                  <td>{g.nameEn ?? '—'}</td>
+                  <td>{gapReason(g)}</td>
+                  <td>{g.customerCount}</td>
+                  <td>{g.accountCount}</td>
+                  <td>{g.ungeocodedCount}</td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      )}
     </section>
   );
 }
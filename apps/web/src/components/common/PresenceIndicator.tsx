@@ -0,0 +1,36 @@
+import { useTranslation } from 'react-i18next';
+import { Users } from 'lucide-react';
+import type { PresenceViewer } from '@/services/presenceService';
+import styles from './PresenceIndicator.module.css';
+
+interface PresenceIndicatorProps {
+  /** Other people on the entity, editors first (see usePresence) */
+  viewers: PresenceViewer[];
+}
+
+/**
+ * "Jana is editing this" banner; renders nothing when nobody else is here.
+ */
+export function PresenceIndicator({ viewers }: PresenceIndicatorProps) {
+  const { t } = useTranslation('common');
+
+  if (viewers.length === 0) return null;
+
+  // The same person may have the entity open in several tabs
+  const names = new Map<string, PresenceViewer>();
+  for (const viewer of viewers) {
+    const known = names.get(viewer.userId);
+    if (!known || (viewer.editing && !known.editing)) names.set(viewer.userId, viewer);
+  }
+  const [first, ...rest] = [...names.values()];
+
+  return (
+    <div className={styles.presence} data-editing={first.editing} role="status">
+      <Users size={14} />
+      <span>
+        {t(first.editing ? 'presence.editing' : 'presence.viewing', { name: first.name })}
+        {rest.length > 0 && ` ${t('presence.others', { count: rest.length })}`}
+      </span>
+    </div>
+  );
+}
@@ -1,4 +1,5 @@
 export { CollapseButton } from './CollapseButton';
 export { JobStatusTimeline } from './JobStatusTimeline';
+export { PresenceIndicator } from './PresenceIndicator';
 export { SplitView, ThreePanelLayout } from './SplitView';
 export { TimeInput } from './TimeInput';
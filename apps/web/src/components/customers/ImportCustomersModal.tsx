@@ -9,6 +9,10 @@ import { X as XIcon } from 'lucide-react';
 import { submitCustomerImportJob } from '../../services/importJobService';
 import { useActiveJobsStore } from '../../stores/activeJobsStore';
 import { getToken } from '@/utils/auth';
+import { readCsvFile } from '@/utils/csvFile';
+import type { CsvFileContent } from '@/utils/csvFile';
+import { SOURCE_SYSTEMS } from '@shared/import';
+import type { SourceSystem } from '@shared/import';
 import styles from './ImportCustomersModal.module.css';
 
 interface ImportCustomersModalProps {
@@ -23,7 +27,8 @@ interface CsvPreview {
   totalRows: number;
   sampleRows: string[][];
   headers: string[];
-  csvContent: string;
+  /** Raw file for the worker, which detects the encoding */
+  csvFile: CsvFileContent;
 }
 
 export function ImportCustomersModal({ 
@@ -37,16 +42,19 @@ export function ImportCustomersModal({
   const [preview, setPreview] = useState<CsvPreview | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [submittedJobId, setSubmittedJobId] = useState<string | null>(null);
+  const [sourceSystem, setSourceSystem] = useState<SourceSystem>('sazinka');
   
   const addJob = useActiveJobsStore((s) => s.addJob);
 
   const parseCSVPreview = useCallback(async (file: File): Promise<CsvPreview> => {
+    const csvFile = await readCsvFile(file).catch(() => {
+      throw new Error(t('customer_error_read'));
+    });
+
     return new Promise((resolve, reject) => {
-      const reader = new FileReader();
-      
-      reader.onload = (e) => {
+      const parse = () => {
         try {
-          const text = e.target?.result as string;
+          const text = csvFile.text;
           const lines = text.split(/\r?\n/).filter(line => line.trim());
           
           if (lines.length < 2) {
@@ -85,15 +93,14 @@ export function ImportCustomersModal({
             totalRows,
             sampleRows,
             headers,
-            csvContent: text,
+            csvFile,
           });
         } catch (err) {
           reject(err);
         }
       };
       
-      reader.onerror = () => reject(new Error(t('customer_error_read')));
-      reader.readAsText(file, 'utf-8');
+      parse();
     });
   }, [t]);
 
@@ -152,8 +159,10 @@ export function ImportCustomersModal({
     
     try {
       const result = await submitCustomerImportJob(
-        preview.csvContent,
-        preview.filename
+        preview.csvFile,
+        preview.filename,
+        undefined,
+        sourceSystem
       );
       
       // Add job to active jobs store for immediate tracking
@@ -178,7 +187,7 @@ export function ImportCustomersModal({
       setError(err instanceof Error ? err.message : t('customer_error_submit'));
       setState('error');
     }
-  }, [preview, addJob]);
+  }, [preview, sourceSystem, addJob]);
 
   const handleClose = useCallback(() => {
     setState('idle');
@@ -253,6 +262,14 @@ export function ImportCustomersModal({
                   <span className={styles.filename}>{preview.filename}</span>
                   <span className={styles.rowCount}>{t('customer_row_count', { count: preview.totalRows })}</span>
                 </div>
+                <label className={styles.sourceSystem}>
+                  {t('source_system_label')}
+                  <select value={sourceSystem} onChange={(e) => setSourceSystem(e.target.value as SourceSystem)}>
+                    {SOURCE_SYSTEMS.map((source) => (
+                      <option key={source} value={source}>{t(`source_system_${source}`)}</option>
+                    ))}
+                  </select>
+                </label>
               </div>
               
               <div className={styles.previewTable}>
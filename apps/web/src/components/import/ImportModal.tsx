@@ -20,6 +20,10 @@ import {
 import { useActiveJobsStore } from '../../stores/activeJobsStore';
 import type { JobType } from '../../stores/activeJobsStore';
 import { getToken } from '@/utils/auth';
+import { readCsvFile } from '@/utils/csvFile';
+import type { CsvFileContent } from '@/utils/csvFile';
+import { SOURCE_SYSTEMS } from '@shared/import';
+import type { SourceSystem } from '@shared/import';
 import styles from './ImportModal.module.css';
 
 export type ImportEntityType = 'device' | 'revision' | 'communication' | 'work_log' | 'notes' | 'zip';
@@ -76,6 +80,7 @@ interface FilePreview {
   headers?: string[];       // For CSV files
   fileSize: number;
   content: string;          // Raw content (text for CSV, base64 for ZIP)
+  csvFile?: CsvFileContent; // CSV bytes for the worker, which detects the encoding
   isZip: boolean;
 }
 
@@ -87,16 +92,19 @@ export function ImportModal({ isOpen, onClose, entityType, onComplete }: ImportM
   const [preview, setPreview] = useState<FilePreview | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [submittedJobId, setSubmittedJobId] = useState<string | null>(null);
+  const [sourceSystem, setSourceSystem] = useState<SourceSystem>('sazinka');
 
   const addJob = useActiveJobsStore((s) => s.addJob);
 
   const parseCSVPreview = useCallback(async (file: File): Promise<FilePreview> => {
-    return new Promise((resolve, reject) => {
-      const reader = new FileReader();
+    const csvFile = await readCsvFile(file).catch(() => {
+      throw new Error(t('modal_error_read'));
+    });
 
-      reader.onload = (e) => {
+    return new Promise((resolve, reject) => {
+      const parse = () => {
         try {
-          const text = e.target?.result as string;
+          const text = csvFile.text;
           const lines = text.split(/\r?\n/).filter(line => line.trim());
 
           if (lines.length < 2) {
@@ -137,6 +145,7 @@ export function ImportModal({ isOpen, onClose, entityType, onComplete }: ImportM
             headers,
             fileSize: file.size,
             content: text,
+            csvFile,
             isZip: false,
           });
         } catch (err) {
@@ -144,8 +153,7 @@ export function ImportModal({ isOpen, onClose, entityType, onComplete }: ImportM
         }
       };
 
-      reader.onerror = () => reject(new Error(t('modal_error_read')));
-      reader.readAsText(file, 'utf-8');
+      parse();
     });
   }, [t]);
 
@@ -261,16 +269,16 @@ export function ImportModal({ isOpen, onClose, entityType, onComplete }: ImportM
 
       switch (entityType) {
         case 'device':
-          result = await submitDeviceImportJob(preview.content, preview.filename);
+          result = await submitDeviceImportJob(preview.csvFile ?? preview.content, preview.filename, undefined, sourceSystem);
           break;
         case 'revision':
-          result = await submitRevisionImportJob(preview.content, preview.filename);
+          result = await submitRevisionImportJob(preview.csvFile ?? preview.content, preview.filename);
           break;
         case 'communication':
-          result = await submitCommunicationImportJob(preview.content, preview.filename);
+          result = await submitCommunicationImportJob(preview.csvFile ?? preview.content, preview.filename);
           break;
         case 'work_log':
-          result = await submitWorkLogImportJob(preview.content, preview.filename);
+          result = await submitWorkLogImportJob(preview.csvFile ?? preview.content, preview.filename);
           break;
         case 'notes':
           result = await submitNotesImportJob(preview.content, preview.filename);
@@ -310,7 +318,7 @@ export function ImportModal({ isOpen, onClose, entityType, onComplete }: ImportM
       setState('error');
       submittingRef.current = false;
     }
-  }, [preview, entityType, addJob, onComplete]);
+  }, [preview, entityType, sourceSystem, addJob, onComplete]);
 
   const handleClose = useCallback(() => {
     setState('idle');
@@ -398,6 +406,16 @@ export function ImportModal({ isOpen, onClose, entityType, onComplete }: ImportM
                     </span>
                   )}
                 </div>
+                {entityType === 'device' && (
+                  <label className={styles.sourceSystem}>
+                    {t('source_system_label')}
+                    <select value={sourceSystem} onChange={(e) => setSourceSystem(e.target.value as SourceSystem)}>
+                      {SOURCE_SYSTEMS.map((source) => (
+                        <option key={source} value={source}>{t(`source_system_${source}`)}</option>
+                      ))}
+                    </select>
+                  </label>
+                )}
               </div>
 
               {/* CSV preview table */}
@@ -26,6 +26,8 @@ const ISSUE_CODE_KEYS: Record<ImportIssueCode, string> = {
   INVALID_RESULT: 'report_code_invalid_result',
   DB_ERROR: 'report_code_db_error',
   PARSE_ERROR: 'report_code_parse_error',
+  QUOTA_EXCEEDED: 'report_code_quota_exceeded',
+  ENCODING_ERROR: 'report_code_encoding_error',
   UNKNOWN: 'report_code_unknown',
 };
 
@@ -166,6 +168,7 @@ export function ImportReportDialog({ report, onClose }: ImportReportDialogProps)
         <div className={styles.metaRow}>
           <span>{t('report_date')} {formatDate(report.importedAt)}</span>
           <span>{t('report_duration')} {formatDuration(report.durationMs)}</span>
+          {report.encoding && <span>{t('report_encoding')} {report.encoding}</span>}
         </div>
 
         {/* Issue summary (aggregated by code) */}
@@ -0,0 +1,67 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { render, screen, fireEvent, waitFor } from '@testing-library/react';
+import { BreakLocationPicker } from './BreakLocationPicker';
+import * as routeService from '../../services/routeService';
+
+vi.mock('../../services/routeService', () => ({
+  suggestBreakLocations: vi.fn(),
+}));
+
+const FROM = { lat: 49.19, lng: 16.6 };
+const TO = { lat: 49.28, lng: 17.0 };
+
+describe('BreakLocationPicker', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('shows the previous stop when no place is chosen', () => {
+    render(<BreakLocationPicker value={null} from={FROM} to={TO} onChange={vi.fn()} />);
+    expect(screen.getByText('break_location_previous_stop')).toBeInTheDocument();
+  });
+
+  it('selects a suggested place without the detour', async () => {
+    vi.mocked(routeService.suggestBreakLocations).mockResolvedValue({
+      enabled: true,
+      suggestions: [
+        { name: 'Pizzerie Roma', kind: 'restaurant', lat: 49.236, lng: 16.804, address: 'Hlavní 12', detourKm: 0.4 },
+      ],
+    });
+    const onChange = vi.fn();
+    render(<BreakLocationPicker value={null} from={FROM} to={TO} onChange={onChange} />);
+
+    fireEvent.click(screen.getByText('break_location_suggest'));
+    await waitFor(() => expect(screen.getByText(/Pizzerie Roma/)).toBeInTheDocument());
+    fireEvent.click(screen.getByText(/Pizzerie Roma/));
+
+    expect(routeService.suggestBreakLocations).toHaveBeenCalledWith(FROM, TO);
+    expect(onChange).toHaveBeenCalledWith({
+      name: 'Pizzerie Roma', kind: 'restaurant', lat: 49.236, lng: 16.804, address: 'Hlavní 12',
+    });
+  });
+
+  it('explains when suggestions are turned off', async () => {
+    vi.mocked(routeService.suggestBreakLocations).mockResolvedValue({ enabled: false, suggestions: [] });
+    render(<BreakLocationPicker value={null} from={FROM} to={null} onChange={vi.fn()} />);
+
+    fireEvent.click(screen.getByText('break_location_suggest'));
+
+    await waitFor(() => expect(screen.getByText('break_location_disabled')).toBeInTheDocument());
+  });
+
+  it('clears a chosen place', () => {
+    const onChange = vi.fn();
+    render(
+      <BreakLocationPicker
+        value={{ name: 'Shell', kind: 'fuel', lat: 49.2, lng: 16.7, address: null }}
+        from={FROM}
+        to={TO}
+        onChange={onChange}
+      />,
+    );
+
+    fireEvent.click(screen.getByText('break_location_clear'));
+
+    expect(onChange).toHaveBeenCalledWith(null);
+  });
+});
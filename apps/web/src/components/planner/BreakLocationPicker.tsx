@@ -0,0 +1,94 @@
+/**
+ * BreakLocationPicker - chooses where a break is held
+ *
+ * Without a chosen place the break is at the previous stop. On request the
+ * backend suggests restaurants and fuel stations near the leg the break is on.
+ */
+
+import { useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import type { Coordinates } from '@shared/route';
+import * as routeService from '../../services/routeService';
+import type { BreakLocation, BreakSuggestion } from '../../services/routeService';
+import styles from './BreakLocationPicker.module.css';
+
+interface BreakLocationPickerProps {
+  value: BreakLocation | null | undefined;
+  /** Stop the break follows */
+  from: Coordinates | null;
+  /** Stop after the break; null when the break ends the route */
+  to: Coordinates | null;
+  onChange: (location: BreakLocation | null) => void;
+}
+
+type LookupState = 'idle' | 'loading' | 'done' | 'disabled' | 'error';
+
+export function BreakLocationPicker({ value, from, to, onChange }: BreakLocationPickerProps) {
+  const { t } = useTranslation('planner');
+  const [state, setState] = useState<LookupState>('idle');
+  const [suggestions, setSuggestions] = useState<BreakSuggestion[]>([]);
+
+  const handleSuggest = async () => {
+    if (!from) return;
+    setState('loading');
+    try {
+      const result = await routeService.suggestBreakLocations(from, to);
+      setSuggestions(result.suggestions);
+      setState(result.enabled ? 'done' : 'disabled');
+    } catch {
+      setState('error');
+    }
+  };
+
+  const handleSelect = ({ name, kind, lat, lng, address }: BreakSuggestion) => {
+    onChange({ name, kind, lat, lng, address });
+    setSuggestions([]);
+    setState('idle');
+  };
+
+  return (
+    <div className={styles.picker} data-testid="break-location-picker">
+      <div className={styles.current}>
+        <span className={styles.label}>{t('break_location_label')}</span>
+        <span className={styles.value} title={value?.address ?? undefined}>
+          {value ? value.name : t('break_location_previous_stop')}
+        </span>
+        {value && (
+          <button type="button" className={styles.linkButton} onClick={() => onChange(null)}>
+            {t('break_location_clear')}
+          </button>
+        )}
+        <button
+          type="button"
+          className={styles.linkButton}
+          onClick={handleSuggest}
+          disabled={!from || state === 'loading'}
+        >
+          {state === 'loading' ? t('break_location_loading') : t('break_location_suggest')}
+        </button>
+      </div>
+
+      {state === 'disabled' && <p className={styles.hint}>{t('break_location_disabled')}</p>}
+      {state === 'error' && <p className={styles.hint}>{t('break_location_error')}</p>}
+      {state === 'done' && suggestions.length === 0 && (
+        <p className={styles.hint}>{t('break_location_none')}</p>
+      )}
+      {state === 'done' && suggestions.length > 0 && (
+        <ul className={styles.list}>
+          {suggestions.map((s) => (
+            <li key={`${s.name}-${s.lat}-${s.lng}`}>
+              <button type="button" className={styles.suggestion} onClick={() => handleSelect(s)}>
+                <span className={styles.suggestionName}>
+                  {s.kind === 'fuel' ? '⛽' : '🍽'} {s.name}
+                </span>
+                <span className={styles.suggestionMeta}>
+                  {t('break_location_detour', { km: s.detourKm.toFixed(1) })}
+                </span>
+              </button>
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+}
@@ -11,6 +11,7 @@ import { useTranslation } from 'react-i18next';
 import { MapPin, AlertTriangle } from 'lucide-react';
 import type { LastVisitCommentData } from '@/hooks/useLastVisitComment';
 import { StopCommentBlock } from './StopCommentBlock';
+import { BreakLocationPicker } from './BreakLocationPicker';
 import {
   DndContext,
   closestCenter,
@@ -23,12 +24,13 @@ import {
   SortableContext,
   verticalListSortingStrategy,
 } from '@dnd-kit/sortable';
-import type { SavedRouteStop } from '../../services/routeService';
+import type { BreakLocation, SavedRouteStop } from '../../services/routeService';
 import type { RouteWarning } from '@shared/route';
 import { reorderStops, needsScheduledTimeWarning } from './reorderStops';
 import { ScheduledTimeWarning } from './ScheduledTimeWarning';
 import { resolveBackendMessage } from '@/i18n/resolveBackendMessage';
 import { TimeInput } from '../common/TimeInput';
+import { colocatedWithPrevious } from '../../utils/colocation';
 import styles from './RouteDetailTimeline.module.css';
 
 interface RouteDetailTimelineProps {
@@ -43,6 +45,8 @@ interface RouteDetailTimelineProps {
   // Editing actions (optional - Inbox and Planner can both provide these)
   onRemoveStop?: (stopId: string) => void;
   onUpdateBreak?: (stopId: string, patch: { breakTimeStart?: string; breakDurationMinutes?: number }) => void;
+  /** Choose (or with null clear) the place of a break stop */
+  onUpdateBreakLocation?: (stopId: string, location: BreakLocation | null) => void;
   onUpdateTravelDuration?: (stopId: string, minutes: number) => void;
   onResetTravelDuration?: (stopId: string) => void;
   onUpdateServiceDuration?: (stopId: string, minutes: number) => void;
@@ -152,6 +156,7 @@ export function RouteDetailTimeline({
   onReorder,
   onRemoveStop,
   onUpdateBreak,
+  onUpdateBreakLocation,
   onUpdateTravelDuration,
   onResetTravelDuration,
   onUpdateServiceDuration,
@@ -217,6 +222,24 @@ export function RouteDetailTimeline({
     );
   }
 
+  // Ends of the leg a break is on: the nearest stops with coordinates, else the depot
+  const breakLegEnd = (index: number, step: -1 | 1) => {
+    for (let i = index + step; i >= 0 && i < stops.length; i += step) {
+      const s = stops[i];
+      if (s.customerLat != null && s.customerLng != null) return { lat: s.customerLat, lng: s.customerLng };
+    }
+    return depot ? { lat: depot.lat, lng: depot.lng } : null;
+  };
+
+  // Units of one building follow each other as one physical stop
+  const sameBuilding = colocatedWithPrevious(
+    stops.map((s) =>
+      s.stopType === 'customer' && s.customerLat != null && s.customerLng != null
+        ? { lat: s.customerLat, lng: s.customerLng }
+        : null,
+    ),
+  );
+
   const mapSegmentIndexByStopIndex: Array<number | null> = (() => {
     let mapIndex = 0;
     return stops.map((stop) => {
@@ -378,7 +401,19 @@ export function RouteDetailTimeline({
                       </div>
                     </div>
                   </div>
-                  
+                  {onUpdateBreakLocation ? (
+                    <BreakLocationPicker
+                      value={stop.breakLocation}
+                      from={breakLegEnd(index, -1)}
+                      to={breakLegEnd(index, 1)}
+                      onChange={(location) => onUpdateBreakLocation(stop.id, location)}
+                    />
+                  ) : stop.breakLocation && (
+                    <div className={styles.breakMetaRow}>
+                      <span className={styles.breakFieldLabel}>{t('break_location_label')}</span>
+                      <span title={stop.breakLocation.address ?? undefined}>{stop.breakLocation.name}</span>
+                    </div>
+                  )}
                 </div>
                 {onRemoveStop && (
                   <button
@@ -450,7 +485,10 @@ export function RouteDetailTimeline({
                   </div>
                 )}
                 
-                  <div className={styles.stopAddress}>{stop.address}</div>
+                  <div className={styles.stopAddress}>
+                    {sameBuilding[index] && `${t('timeline_same_building')} · `}
+                    {stop.address}
+                  </div>
 
                   {/* Last visit comment — shown only on selected stop */}
                   {isSelected && lastVisitComment && (
@@ -126,4 +126,16 @@ describe('RouteSummaryActions', () => {
     fireEvent.mouseDown(document.body);
     expect(screen.queryByRole('menu')).toBeNull();
   });
+
+  // #16 — navigation unit formats are offered in the dropdown
+  it('selecting Garmin or TomTom calls onExport with the itinerary target', () => {
+    const onExport = vi.fn();
+    render(<RouteSummaryActions onExport={onExport} />);
+    fireEvent.click(screen.getByRole('button', { name: /actions_export_more/i }));
+    fireEvent.click(screen.getByRole('menuitem', { name: /actions_export_garmin/i }));
+    expect(onExport).toHaveBeenCalledWith('garmin_gpx');
+    fireEvent.click(screen.getByRole('button', { name: /actions_export_more/i }));
+    fireEvent.click(screen.getByRole('menuitem', { name: /actions_export_tomtom/i }));
+    expect(onExport).toHaveBeenCalledWith('tomtom_itn');
+  });
 });
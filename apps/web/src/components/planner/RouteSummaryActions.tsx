@@ -2,7 +2,7 @@ import { useState, useRef, useEffect, useCallback } from 'react';
 import { useTranslation } from 'react-i18next';
 import styles from './RouteSummaryActions.module.css';
 
-export type ExportTarget = 'google_maps' | 'mapy_cz';
+export type ExportTarget = 'google_maps' | 'mapy_cz' | 'garmin_gpx' | 'tomtom_itn';
 
 export interface RouteSummaryActionsProps {
   onOptimize?: () => void;
@@ -127,6 +127,22 @@ export function RouteSummaryActions({
               >
                 {t('actions_export_mapycz')}
               </button>
+              <button
+                type="button"
+                className={styles.exportOption}
+                role="menuitem"
+                onClick={() => handleExport('garmin_gpx')}
+              >
+                {t('actions_export_garmin')}
+              </button>
+              <button
+                type="button"
+                className={styles.exportOption}
+                role="menuitem"
+                onClick={() => handleExport('tomtom_itn')}
+              >
+                {t('actions_export_tomtom')}
+              </button>
             </div>
           )}
         </div>
@@ -0,0 +1,75 @@
+import { useEffect, useRef, useState } from 'react';
+import {
+  leavePresence,
+  sendPresenceHeartbeat,
+  type PresenceEntityType,
+  type PresenceViewer,
+} from '@/services/presenceService';
+import { useNatsStore } from '@/stores/natsStore';
+
+const DEFAULT_HEARTBEAT_SECONDS = 15;
+
+/** One presence session per browser tab */
+let tabSessionId: string | null = null;
+
+function getTabSessionId(): string {
+  if (!tabSessionId) {
+    tabSessionId = crypto.randomUUID();
+  }
+  return tabSessionId;
+}
+
+/**
+ * Keep this tab registered on an entity and return the other people
+ * viewing or editing it. Leaves the entity on unmount.
+ */
+export function usePresence(
+  entityType: PresenceEntityType,
+  entityId: string | null | undefined,
+  editing = false
+): PresenceViewer[] {
+  const isConnected = useNatsStore((s) => s.isConnected);
+  const [viewers, setViewers] = useState<PresenceViewer[]>([]);
+
+  // Editing changes often; the next heartbeat picks it up
+  const editingRef = useRef(editing);
+  editingRef.current = editing;
+
+  useEffect(() => {
+    if (!entityId || !isConnected) {
+      setViewers([]);
+      return;
+    }
+
+    const sessionId = getTabSessionId();
+    let cancelled = false;
+    let timer: ReturnType<typeof setTimeout> | undefined;
+
+    const beat = async () => {
+      let nextSeconds = DEFAULT_HEARTBEAT_SECONDS;
+      try {
+        const result = await sendPresenceHeartbeat({
+          sessionId,
+          entityType,
+          entityId,
+          editing: editingRef.current,
+        });
+        if (!cancelled) setViewers(result.viewers);
+        nextSeconds = result.heartbeatSeconds || DEFAULT_HEARTBEAT_SECONDS;
+      } catch {
+        // Presence is advisory; keep trying on the next beat
+      }
+      if (!cancelled) timer = setTimeout(beat, nextSeconds * 1000);
+    };
+    void beat();
+
+    return () => {
+      cancelled = true;
+      if (timer) clearTimeout(timer);
+      setViewers([]);
+      leavePresence(sessionId, { entityType, entityId }).catch(() => {});
+    };
+  }, [entityType, entityId, isConnected]);
+
+  return viewers;
+}
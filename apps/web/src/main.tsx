@@ -9,6 +9,7 @@ import { useAuthStore } from './stores/authStore';
 import { useNatsStore } from './stores/natsStore';
 import { useTokenRefresh } from './hooks/useTokenRefresh';
 import { clearChunkReloadCounter } from './lib/lazyWithRetry';
+import { initDemoModeFromUrl } from './utils/demoMode';
 import './i18n'; // Initialize i18n before rendering
 import './index.css';
 
@@ -18,6 +19,8 @@ window.addEventListener('vite:preloadError', () => {
   window.location.reload();
 });
 
+initDemoModeFromUrl();
+
 // Create router instance
 const router = createRouter({ routeTree });
 
@@ -50,10 +50,13 @@ interface AdminDbStatus {
   tables?: { name: string; rows: number; size: string }[];
 }
 interface AdminServiceAvailability { available: boolean; url?: string; version?: string }
+interface AdminIndexFreshness { ageDays?: number | null; maxAgeDays: number; stale: boolean }
+interface AdminNominatimStatus extends AdminServiceAvailability { indexFreshness?: AdminIndexFreshness | null }
 interface AdminJetStreamStatus {
   available: boolean;
-  streams?: { messages?: number }[];
+  streams?: { name: string; messages?: number; usagePercent?: number | null }[];
   consumers?: { pending?: number }[];
+  unmatchedOverrides?: string[];
 }
 interface AdminGeocodeStatus {
   available: boolean;
@@ -205,12 +208,14 @@ export function Admin() {
     } catch { const idx = newServices.findIndex(s => s.name === 'Valhalla'); if (idx >= 0) newServices[idx] = { ...newServices[idx], status: 'unknown', lastCheck: new Date().toISOString(), details: 'Could not check' }; }
 
     try {
-      const response = await request<unknown, ApiEnvelope<AdminServiceAvailability> | AdminServiceAvailability>('sazinka.admin.nominatim.status', createRequest(getToken(), {}));
+      const response = await request<unknown, ApiEnvelope<AdminNominatimStatus> | AdminNominatimStatus>('sazinka.admin.nominatim.status', createRequest(getToken(), {}));
       const r = unwrapPayload(response);
       const idx = newServices.findIndex(s => s.name === 'Nominatim');
       if (idx >= 0) {
         const isConfigured = r.url && r.url !== 'Not configured';
-        newServices[idx] = { ...newServices[idx], status: r.available ? 'running' : (isConfigured ? 'starting' : 'stopped'), lastCheck: new Date().toISOString(), details: r.available ? `${r.url}${r.version ? ` v${r.version}` : ''}` : (isConfigured ? 'Importing data / starting up...' : 'Not configured') };
+        const freshness = r.indexFreshness;
+        const staleNote = freshness?.stale ? ` — ⚠ data ${freshness.ageDays} days old (limit ${freshness.maxAgeDays})` : '';
+        newServices[idx] = { ...newServices[idx], status: r.available ? 'running' : (isConfigured ? 'starting' : 'stopped'), lastCheck: new Date().toISOString(), details: r.available ? `${r.url}${r.version ? ` v${r.version}` : ''}${staleNote}` : (isConfigured ? 'Importing data / starting up...' : 'Not configured') };
       }
     } catch { const idx = newServices.findIndex(s => s.name === 'Nominatim'); if (idx >= 0) newServices[idx] = { ...newServices[idx], status: 'unknown', lastCheck: new Date().toISOString(), details: 'Could not check' }; }
 
@@ -219,9 +224,13 @@ export function Admin() {
       const r = unwrapPayload(response);
       const idx = newServices.findIndex(s => s.name === 'JetStream');
       if (idx >= 0) {
-        const streamInfo = r.streams?.[0];
+        const streams = r.streams ?? [];
         const consumerInfo = r.consumers?.[0];
-        const details = streamInfo ? `${streamInfo.messages} msgs, ${consumerInfo?.pending || 0} pending` : (r.available ? 'Enabled' : 'Disabled');
+        const totalMessages = streams.reduce((sum, s) => sum + (s.messages || 0), 0);
+        const fullest = streams.reduce<(typeof streams)[number] | undefined>((max, s) => ((s.usagePercent ?? 0) > (max?.usagePercent ?? 0) ? s : max), undefined);
+        const fullestNote = fullest?.usagePercent ? `, fullest ${fullest.name} ${Math.round(fullest.usagePercent)}%` : '';
+        const overridesNote = r.unmatchedOverrides?.length ? ` — ⚠ unknown streams in limits: ${r.unmatchedOverrides.join(', ')}` : '';
+        const details = streams.length ? `${streams.length} streams, ${totalMessages} msgs, ${consumerInfo?.pending || 0} pending${fullestNote}${overridesNote}` : (r.available ? 'Enabled' : 'Disabled');
         newServices[idx] = { ...newServices[idx], status: r.available ? 'running' : 'stopped', lastCheck: new Date().toISOString(), details };
       }
     } catch { const idx = newServices.findIndex(s => s.name === 'JetStream'); if (idx >= 0) newServices[idx] = { ...newServices[idx], status: 'unknown', lastCheck: new Date().toISOString(), details: 'Could not check' }; }
@@ -24,6 +24,8 @@ import { ScheduleDialog, type ScheduleTarget, type Crew } from '../components/co
 import type { SlotSuggestion } from '../components/planner/SlotSuggestions';
 import { DeviceList } from '../components/devices';
 import { CustomerTimeline } from '../components/timeline';
+import { PresenceIndicator } from '../components/common';
+import { usePresence } from '../hooks/usePresence';
 import { useNatsStore } from '../stores/natsStore';
 import { useTranslation } from 'react-i18next';
 import { formatDate } from '../i18n/formatters';
@@ -58,6 +60,9 @@ export function CustomerDetail() {
   // Edit drawer (replaces full-page form)
   const [isEditDrawerOpen, setIsEditDrawerOpen] = useState(searchParams?.edit ?? false);
   const [isSubmitting, setIsSubmitting] = useState(false);
+
+  // Who else has this customer open (editing = edit drawer open)
+  const presenceViewers = usePresence('customer', customerId, isEditDrawerOpen);
   
   // Active tab
   const [activeTab, setActiveTab] = useState<TabId>(searchParams?.tab || 'devices');
@@ -439,6 +444,8 @@ export function CustomerDetail() {
         onDelete={handleDeleteClick}
       />
 
+      <PresenceIndicator viewers={presenceViewers} />
+
       {/* Error banner */}
       {error && (
         <div className={styles.errorBanner}>
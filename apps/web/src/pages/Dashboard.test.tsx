@@ -32,12 +32,18 @@ vi.mock('../services/calendarService', () => ({
   listCalendarItems: vi.fn(),
 }));
 
+vi.mock('../services/customerService', () => ({
+  getGeocodeIndexStatus: vi.fn(),
+}));
+
 import { getRevisionStats } from '../services/revisionService';
 import { listCalendarItems } from '../services/calendarService';
+import { getGeocodeIndexStatus } from '../services/customerService';
 
 describe('Dashboard', () => {
   beforeEach(() => {
     vi.clearAllMocks();
+    vi.mocked(getGeocodeIndexStatus).mockResolvedValue(null);
   });
 
   it('should render dashboard title', async () => {
@@ -216,4 +222,49 @@ describe('Dashboard', () => {
       expect(screen.getByText('new_customer')).toBeInTheDocument();
     });
   });
+
+  it('should warn when the geocoding index is stale', async () => {
+    vi.mocked(getRevisionStats).mockResolvedValue({
+      overdue: 0,
+      dueThisWeek: 0,
+      scheduledToday: 0,
+      completedThisMonth: 0,
+    });
+    vi.mocked(listCalendarItems).mockResolvedValue({ items: [] });
+    vi.mocked(getGeocodeIndexStatus).mockResolvedValue({
+      checkedAt: '2025-01-01T00:00:00Z',
+      dataUpdated: '2024-05-01T00:00:00Z',
+      ageDays: 245,
+      maxAgeDays: 180,
+      stale: true,
+    });
+
+    render(<Dashboard />);
+
+    await waitFor(() => {
+      expect(screen.getByRole('alert')).toBeInTheDocument();
+    });
+  });
+
+  it('should not warn when the geocoding index is fresh', async () => {
+    vi.mocked(getRevisionStats).mockResolvedValue({
+      overdue: 0,
+      dueThisWeek: 0,
+      scheduledToday: 0,
+      completedThisMonth: 0,
+    });
+    vi.mocked(listCalendarItems).mockResolvedValue({ items: [] });
+    vi.mocked(getGeocodeIndexStatus).mockResolvedValue({
+      checkedAt: '2025-01-01T00:00:00Z',
+      dataUpdated: '2024-12-01T00:00:00Z',
+      ageDays: 31,
+      maxAgeDays: 180,
+      stale: false,
+    });
+
+    render(<Dashboard />);
+
+    await waitFor(() => expect(getGeocodeIndexStatus).toHaveBeenCalled());
+    expect(screen.queryByRole('alert')).not.toBeInTheDocument();
+  });
 });
@@ -5,7 +5,8 @@ import type { CalendarItem } from '@shared/calendar';
 import { useNatsStore } from '@/stores/natsStore';
 import { getRevisionStats, type RevisionStats } from '../services/revisionService';
 import { listCalendarItems } from '../services/calendarService';
-import { Phone } from 'lucide-react';
+import { getGeocodeIndexStatus, type GeocodeIndexStatus } from '../services/customerService';
+import { AlertTriangle, Phone } from 'lucide-react';
 import styles from './Dashboard.module.css';
 
 function getStatusLabel(status: CalendarItem['status'], t: (key: string) => string): string {
@@ -42,6 +43,7 @@ export function Dashboard() {
   const [weekItems, setWeekItems] = useState<CalendarItem[]>([]);
   const [isLoading, setIsLoading] = useState(true);
   const [statsError, setStatsError] = useState<string | null>(null);
+  const [geocodeIndex, setGeocodeIndex] = useState<GeocodeIndexStatus | null>(null);
 
   const loadStats = useCallback(async () => {
     if (!isConnected) {
@@ -87,6 +89,14 @@ export function Dashboard() {
     loadStats();
   }, [loadStats]);
 
+  // The geocoding index is checked by the worker every few hours
+  useEffect(() => {
+    if (!isConnected) return;
+    getGeocodeIndexStatus()
+      .then(setGeocodeIndex)
+      .catch((err) => console.warn('Failed to load geocoding index status:', err));
+  }, [isConnected]);
+
   // Refresh stats every 30 seconds when connected
   useEffect(() => {
     if (!isConnected) return;
@@ -136,6 +146,13 @@ export function Dashboard() {
     <div className={styles.dashboard}>
       <h1>{t('title')}</h1>
 
+      {geocodeIndex?.stale && (
+        <div className={styles.staleIndexBanner} role="alert">
+          <AlertTriangle size={16} />
+          <span>{t('geocode_index_stale', { days: geocodeIndex.ageDays })}</span>
+        </div>
+      )}
+
       {/* Call Queue CTA - prominent banner */}
       {totalToCall > 0 && (
         <Link to="/queue" className={styles.callQueueBanner}>
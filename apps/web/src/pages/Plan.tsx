@@ -20,11 +20,14 @@ import type { RouteWarning } from '@shared/route';
 import { validateBreak } from '../utils/breakUtils';
 import { logger } from '../utils/logger';
 import { calculateMetrics } from '../utils/routeMetrics';
-import { buildGoogleMapsUrl, buildMapyCzUrl } from '../utils/routeExport';
+import { buildGoogleMapsUrl, buildMapyCzUrl, buildGpxRoute, buildTomTomItinerary } from '../utils/routeExport';
 import type { ExportTarget } from '../components/planner/RouteSummaryActions';
 import { buildPrintHtml } from '../utils/routePrint';
+import { colocatedWithPrevious } from '../utils/colocation';
 import { RouteListPanel, RouteDetailTimeline, RouteMapPanel, type RouteMetrics, PlanningTimeline, TimelineViewToggle, type TimelineView, RouteSummaryStats, RouteSummaryActions, ArrivalBufferBar } from '../components/planner';
 import { useLastVisitComment } from '../hooks/useLastVisitComment';
+import { usePresence } from '../hooks/usePresence';
+import { PresenceIndicator } from '../components/common';
 import { PlannerFilters } from '../components/shared/PlannerFilters';
 import { AlertTriangle } from 'lucide-react';
 import styles from './Plan.module.css';
@@ -122,6 +125,10 @@ function PlanInner() {
 
   const [selectedRouteStops, setSelectedRouteStops] = useState<SavedRouteStop[]>([]);
 
+  // Other dispatchers on the selected route (editing while it is being re-optimized)
+  const [isOptimizing, setIsOptimizing] = useState(false);
+  const routePresence = usePresence('route', selectedRouteId, isOptimizing);
+
   const { value: _uppTimelineView, setValue: setUppTimelineView } =
     usePersistentControl<string>(PLAN_PROFILE_ID, 'timelineView');
   const timelineView: TimelineView = (TIMELINE_VIEWS as readonly string[]).includes(_uppTimelineView)
@@ -135,7 +142,6 @@ function PlanInner() {
   const [isLoadingSettings, setIsLoadingSettings] = useState(true);
   const [isLoadingRoutes, setIsLoadingRoutes] = useState(false);
   const [isLoadingStops, setIsLoadingStops] = useState(false);
-  const [isOptimizing, setIsOptimizing] = useState(false);
   const [error, setError] = useState<string | null>(null);
 
   // --- Resizable layout ---
@@ -882,6 +888,14 @@ function PlanInner() {
       ? metrics.travelTimeMin + metrics.serviceTimeMin
       : null;
 
+    const sameBuilding = colocatedWithPrevious(
+      selectedRouteStops.map((s) =>
+        s.stopType === 'customer' && s.customerLat != null && s.customerLng != null
+          ? { lat: s.customerLat, lng: s.customerLng }
+          : null,
+      ),
+    );
+
     const html = buildPrintHtml({
       title: routeTitle || t('print_route_title_fallback'),
       mapImageDataUrl: dataUrl,
@@ -901,6 +915,8 @@ function PlanInner() {
         colEtd: t('print_col_etd'),
         colService: t('print_col_service'),
         generated: t('print_label_generated'),
+        breakAt: t('print_label_break_at'),
+        sameBuilding: t('print_label_same_building'),
       },
       stops: selectedRouteStops.map((s, i) => {
         const durMin = s.overrideServiceDurationMinutes ?? s.serviceDurationMinutes;
@@ -912,6 +928,8 @@ function PlanInner() {
           etd: s.estimatedDeparture?.slice(0, 5) ?? null,
           serviceDuration: durMin != null ? `${durMin} min` : null,
           stopType: s.stopType as 'customer' | 'break',
+          breakLocation: s.breakLocation ?? null,
+          sameBuildingAsPrevious: sameBuilding[i],
         };
       }),
       depot: selectedRouteDepot ? { name: selectedRouteDepot.name } : null,
@@ -939,35 +957,72 @@ function PlanInner() {
     win.onload = () => { win.print(); };
   }, [selectedRouteStops, selectedRouteDepot, selectedRoute, depotDeparture, actualRouteEnd, metrics, actions, t]);
 
-  // ─── Export to Google Maps handler ───────────────────────────────────────
+  // ─── Export handler (map links, navigation unit files) ────────────────────
 
   const handleExport = useCallback((target: ExportTarget) => {
-    const exportParams = {
-      depot: selectedRouteDepot
-        ? { lat: selectedRouteDepot.lat, lng: selectedRouteDepot.lng }
-        : null,
-      stops: selectedRouteStops.map((s) => ({
-        customerLat: s.customerLat,
-        customerLng: s.customerLng,
-        stopType: s.stopType as 'customer' | 'break',
-      })),
-    };
+    let warnings: string[];
+
+    if (target === 'garmin_gpx' || target === 'tomtom_itn') {
+      const routeName = [selectedRoute?.date, selectedRouteDepot?.name]
+        .filter(Boolean)
+        .join(' ') || t('print_route_title_fallback');
+      const itineraryParams = {
+        name: routeName,
+        depot: selectedRouteDepot
+          ? { lat: selectedRouteDepot.lat, lng: selectedRouteDepot.lng, name: selectedRouteDepot.name }
+          : null,
+        stops: selectedRouteStops.map((s) => ({
+          customerLat: s.customerLat,
+          customerLng: s.customerLng,
+          stopType: s.stopType as 'customer' | 'break',
+          customerName: s.customerName,
+          customerPhone: s.customerPhone,
+          address: s.address,
+        })),
+      };
+      const isGpx = target === 'garmin_gpx';
+      const result = isGpx ? buildGpxRoute(itineraryParams) : buildTomTomItinerary(itineraryParams);
+
+      if (result.content) {
+        const blob = new Blob([result.content], {
+          type: isGpx ? 'application/gpx+xml' : 'text/plain',
+        });
+        const url = URL.createObjectURL(blob);
+        const link = document.createElement('a');
+        link.href = url;
+        link.download = `route-${selectedRoute?.date ?? 'export'}.${isGpx ? 'gpx' : 'itn'}`;
+        document.body.appendChild(link);
+        link.click();
+        document.body.removeChild(link);
+        URL.revokeObjectURL(url);
+      }
+      warnings = result.warnings.map((w) => w === 'TRUNCATED'
+        ? t('export_itinerary_warning_truncated')
+        : t(`export_gmaps_warning_${w.toLowerCase()}`));
+    } else {
+      const exportParams = {
+        depot: selectedRouteDepot
+          ? { lat: selectedRouteDepot.lat, lng: selectedRouteDepot.lng }
+          : null,
+        stops: selectedRouteStops.map((s) => ({
+          customerLat: s.customerLat,
+          customerLng: s.customerLng,
+          stopType: s.stopType as 'customer' | 'break',
+        })),
+      };
 
-    const result = target === 'mapy_cz'
-      ? buildMapyCzUrl(exportParams)
-      : buildGoogleMapsUrl(exportParams);
+      const result = target === 'mapy_cz'
+        ? buildMapyCzUrl(exportParams)
+        : buildGoogleMapsUrl(exportParams);
 
-    if (result.url) {
-      window.open(result.url, '_blank', 'noopener,noreferrer');
+      if (result.url) {
+        window.open(result.url, '_blank', 'noopener,noreferrer');
+      }
+      warnings = result.warnings.map((w) => t(`export_gmaps_warning_${w.toLowerCase()}`));
     }
 
-    if (result.warnings.length > 0) {
-      const msgs = result.warnings.map((w) => t(`export_gmaps_warning_${w.toLowerCase()}`));
-      setExportWarning(msgs.join(' '));
-    } else {
-      setExportWarning(null);
-    }
-  }, [selectedRouteStops, selectedRouteDepot, t]);
+    setExportWarning(warnings.length > 0 ? warnings.join(' ') : null);
+  }, [selectedRouteStops, selectedRouteDepot, selectedRoute, t]);
 
   const handleOptimizeRoute = useCallback(async () => {
     if (!selectedRoute || selectedRouteStops.length < 2) return;
@@ -1111,6 +1166,8 @@ function PlanInner() {
           onDepotChange={handleDepotFilterChange}
         />
 
+        <PresenceIndicator viewers={routePresence} />
+
         {/* Error */}
         {error && (
           <div className={styles.error}>
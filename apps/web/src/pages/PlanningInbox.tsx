@@ -35,7 +35,7 @@ import { SplitView, ThreePanelLayout } from '../components/common';
 import { SplitLayout, LayoutManager, DetachButton, MapPanelShell } from '../components/layout';
 import { useLayoutMode } from '../hooks/useLayoutMode';
 import { Map as MapIcon } from 'lucide-react';
-import type { SavedRouteStop } from '../services/routeService';
+import type { BreakLocation, SavedRouteStop } from '../services/routeService';
 import { recalculateRoute, type RecalcStopInput } from '../services/routeService';
 import type { BreakSettings } from '@shared/settings';
 import { insertStopAtPosition, findChronologicalPosition } from '../components/planner/insertStop';
@@ -122,6 +122,7 @@ const DEFAULT_BREAK_SETTINGS: BreakSettings = {
   breakLatestTime: '13:00',
   breakMinKm: 40,
   breakMaxKm: 120,
+  breakLocationSuggestions: true,
 };
 
 // Format minutes as "Xh Ymm"
@@ -351,6 +352,7 @@ function PlanningInboxInner() {
         stopType: s.stopType,
         breakDurationMinutes: s.stopType === 'break' ? (s.breakDurationMinutes ?? 45) : undefined,
         breakTimeStart: s.stopType === 'break' ? (s.breakTimeStart ?? s.estimatedArrival ?? '12:00') : undefined,
+        breakLocation: s.stopType === 'break' ? (s.breakLocation ?? undefined) : undefined,
         status: s.status === 'unassigned' ? 'unassigned' : undefined,
         serviceDurationMinutes: s.serviceDurationMinutes ?? undefined,
         overrideServiceDurationMinutes: s.overrideServiceDurationMinutes ?? undefined,
@@ -2381,6 +2383,15 @@ function PlanningInboxInner() {
     incrementRouteVersion();
   }, [incrementRouteVersion, triggerRecalculate]);
 
+  // Route building: choose where a break is held (does not affect timing)
+  const handleUpdateBreakLocation = useCallback((stopId: string, location: BreakLocation | null) => {
+    setRouteStops((prev) =>
+      prev.map((s) => (s.id === stopId && s.stopType === 'break' ? { ...s, breakLocation: location } : s))
+    );
+    setHasChanges(true);
+    incrementRouteVersion();
+  }, [incrementRouteVersion]);
+
   // Override: update travel duration for a stop
   const handleUpdateTravelDuration = useCallback((stopId: string, minutes: number) => {
     setRouteStops((prev) => {
@@ -2721,6 +2732,7 @@ function PlanningInboxInner() {
               onReorder={handleReorder}
               onRemoveStop={handleRemoveFromRoute}
               onUpdateBreak={handleUpdateBreak}
+              onUpdateBreakLocation={handleUpdateBreakLocation}
               onUpdateTravelDuration={handleUpdateTravelDuration}
               onResetTravelDuration={handleResetTravelDuration}
               onUpdateServiceDuration={handleUpdateServiceDuration}
@@ -2870,6 +2882,7 @@ function PlanningInboxInner() {
           onReorder={handleReorder}
           onRemoveStop={handleRemoveFromRoute}
           onUpdateBreak={handleUpdateBreak}
+          onUpdateBreakLocation={handleUpdateBreakLocation}
           onUpdateTravelDuration={handleUpdateTravelDuration}
           onResetTravelDuration={handleResetTravelDuration}
           onUpdateServiceDuration={handleUpdateServiceDuration}
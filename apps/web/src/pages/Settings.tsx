@@ -41,6 +41,7 @@ const DEFAULT_BREAK_SETTINGS: BreakSettings = {
   breakLatestTime: '13:00',
   breakMinKm: 40,
   breakMaxKm: 120,
+  breakLocationSuggestions: true,
 };
 
 export function Settings() {
@@ -1170,6 +1171,7 @@ function BreakSettingsForm({ data, saving, onSave }: BreakSettingsFormProps) {
     breakLatestTime: resolvedData.breakLatestTime,
     breakMinKm: resolvedData.breakMinKm,
     breakMaxKm: resolvedData.breakMaxKm,
+    breakLocationSuggestions: resolvedData.breakLocationSuggestions,
   });
 
   const handleSubmit = (e: React.FormEvent) => {
@@ -1278,6 +1280,18 @@ function BreakSettingsForm({ data, saving, onSave }: BreakSettingsFormProps) {
             <span>{t('break_driving_rule')}</span>
           </label>
         </div>
+
+        <div className={styles.formGroup}>
+          <label className={styles.checkboxLabel}>
+            <input
+              type="checkbox"
+              checked={formData.breakLocationSuggestions}
+              onChange={(e) => setFormData({ ...formData, breakLocationSuggestions: e.target.checked })}
+            />
+            <span>{t('break_location_suggestions')}</span>
+          </label>
+          <p className={styles.hint}>{t('break_location_suggestions_hint')}</p>
+        </div>
       </div>
 
       <div className={styles.formActions}>
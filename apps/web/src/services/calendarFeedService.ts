@@ -0,0 +1,83 @@
+/**
+ * Calendar feed service
+ *
+ * iCal subscriptions of the company work calendar: routes and visits of all
+ * (or selected) crews, colored per crew, with an optional capacity overlay.
+ * The subscription URL carries the token and is only returned on creation.
+ */
+
+import { useNatsStore } from '../stores/natsStore';
+import { createRequest, type SuccessResponse, type ErrorResponse } from '@shared/messages';
+import { getToken } from '@/utils/auth';
+
+type NatsResponse<T> = SuccessResponse<T> | ErrorResponse;
+
+function isErrorResponse(response: NatsResponse<unknown>): response is ErrorResponse {
+  return 'error' in response;
+}
+
+export interface CalendarFeed {
+  id: string;
+  name: string;
+  /** Crews in the feed; empty means every crew of the company */
+  crewIds: string[];
+  includeCapacity: boolean;
+  createdAt: string;
+  lastFetchedAt: string | null;
+  revokedAt: string | null;
+}
+
+export interface CreateCalendarFeedInput {
+  name: string;
+  crewIds?: string[];
+  includeCapacity?: boolean;
+}
+
+async function call<TReq, TRes>(
+  subject: string,
+  payload: TReq,
+  deps = { request: useNatsStore.getState().request }
+): Promise<TRes> {
+  const req = createRequest(getToken(), payload);
+  const response = await deps.request<typeof req, NatsResponse<TRes>>(subject, req);
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
+
+/**
+ * Create a feed; the subscription URL is shown only in this response
+ */
+export async function createCalendarFeed(
+  input: CreateCalendarFeedInput,
+  deps = { request: useNatsStore.getState().request }
+): Promise<{ feed: CalendarFeed; url: string }> {
+  return call('sazinka.calendar_feed.create', input, deps);
+}
+
+/**
+ * Feeds of the company, revoked ones included
+ */
+export async function listCalendarFeeds(
+  deps = { request: useNatsStore.getState().request }
+): Promise<CalendarFeed[]> {
+  const result = await call<Record<string, never>, { feeds: CalendarFeed[] }>(
+    'sazinka.calendar_feed.list',
+    {},
+    deps
+  );
+  return result.feeds;
+}
+
+/**
+ * Revoke a feed; its URL stops working immediately
+ */
+export async function revokeCalendarFeed(
+  id: string,
+  deps = { request: useNatsStore.getState().request }
+): Promise<void> {
+  await call('sazinka.calendar_feed.revoke', { id }, deps);
+}
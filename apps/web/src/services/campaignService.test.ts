@@ -0,0 +1,64 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { createCampaign, deleteCampaign, listCampaigns, listCampaignSends } from './campaignService';
+
+vi.mock('@/utils/auth', () => ({
+  getToken: () => 'test-token',
+}));
+
+const mockRequest = vi.fn();
+
+vi.mock('../stores/natsStore', () => ({
+  useNatsStore: {
+    getState: () => ({
+      request: mockRequest,
+    }),
+  },
+}));
+
+describe('campaignService', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('creates a campaign', async () => {
+    mockRequest.mockResolvedValue({ payload: { id: 'campaign-1', name: 'Topná sezóna' } });
+
+    const result = await createCampaign(
+      {
+        name: 'Topná sezóna',
+        triggerType: 'last_revision_age',
+        monthsSinceRevision: 10,
+        activeFromMonth: 9,
+        activeToMonth: 11,
+        channel: 'email',
+        subject: 'Čas na kontrolu',
+        body: 'Dobrý den, {{customerName}}',
+      },
+      { request: mockRequest },
+    );
+
+    const [subject, payload] = mockRequest.mock.calls[0];
+    expect(subject).toBe('sazinka.campaign.create');
+    expect(payload.payload.monthsSinceRevision).toBe(10);
+    expect(result.id).toBe('campaign-1');
+  });
+
+  it('unwraps the campaign and send lists', async () => {
+    mockRequest.mockResolvedValueOnce({ payload: { campaigns: [{ id: 'campaign-1', sentCount: 3 }] } });
+    mockRequest.mockResolvedValueOnce({ payload: { sends: [{ id: 'send-1', campaignId: 'campaign-1' }] } });
+
+    const campaigns = await listCampaigns({ request: mockRequest });
+    const sends = await listCampaignSends({ campaignId: 'campaign-1' }, { request: mockRequest });
+
+    expect(campaigns[0].sentCount).toBe(3);
+    expect(sends).toHaveLength(1);
+    expect(mockRequest.mock.calls[1][0]).toBe('sazinka.campaign.sends.list');
+    expect(mockRequest.mock.calls[1][1].payload).toEqual({ campaignId: 'campaign-1' });
+  });
+
+  it('throws the backend error', async () => {
+    mockRequest.mockResolvedValue({ error: { code: 'NOT_FOUND', message: 'Campaign not found' } });
+
+    await expect(deleteCampaign('missing', { request: mockRequest })).rejects.toThrow('Campaign not found');
+  });
+});
@@ -0,0 +1,154 @@
+/**
+ * Campaign service
+ *
+ * Campaigns queue an e-mail or SMS to customers matching a trigger (e.g. last
+ * revision older than N months, customer anniversary) once a day in their
+ * active months.
+ */
+
+import { useNatsStore } from '../stores/natsStore';
+import { createRequest, type SuccessResponse, type ErrorResponse } from '@shared/messages';
+import { getToken } from '@/utils/auth';
+
+type NatsResponse<T> = SuccessResponse<T> | ErrorResponse;
+
+function isErrorResponse(response: NatsResponse<unknown>): response is ErrorResponse {
+  return 'error' in response;
+}
+
+export type CampaignTriggerType = 'last_revision_age' | 'customer_anniversary';
+export type CampaignChannel = 'email' | 'sms';
+
+export interface Campaign {
+  id: string;
+  userId: string;
+  name: string;
+  triggerType: CampaignTriggerType;
+  monthsSinceRevision: number | null;
+  /** 1-12; a range such as 10–2 wraps the year end */
+  activeFromMonth: number | null;
+  activeToMonth: number | null;
+  channel: CampaignChannel;
+  subject: string | null;
+  /** Placeholders: {{customerName}}, {{lastRevisionDate}}, {{companyName}}, {{phone}}, {{email}} */
+  body: string;
+  excludeScheduled: boolean;
+  excludeContactedDays: number | null;
+  /** Days before a customer may receive the campaign again; null = once */
+  repeatAfterDays: number | null;
+  enabled: boolean;
+  lastRunOn: string | null;
+  createdAt: string;
+  updatedAt: string;
+  sentCount: number;
+  failedCount: number;
+  lastSentAt: string | null;
+}
+
+export interface CampaignSend {
+  id: string;
+  campaignId: string;
+  customerId: string | null;
+  customerName: string | null;
+  channel: CampaignChannel;
+  recipient: string;
+  jobId: string | null;
+  error: string | null;
+  createdAt: string;
+}
+
+export interface CreateCampaignRequest {
+  name: string;
+  triggerType: CampaignTriggerType;
+  monthsSinceRevision?: number | null;
+  activeFromMonth?: number | null;
+  activeToMonth?: number | null;
+  channel: CampaignChannel;
+  subject?: string | null;
+  body: string;
+  excludeScheduled?: boolean;
+  excludeContactedDays?: number | null;
+  repeatAfterDays?: number | null;
+  enabled?: boolean;
+}
+
+/** Full replacement; trigger type and channel cannot be changed */
+export interface UpdateCampaignRequest {
+  id: string;
+  name: string;
+  monthsSinceRevision: number | null;
+  activeFromMonth: number | null;
+  activeToMonth: number | null;
+  subject: string | null;
+  body: string;
+  excludeScheduled: boolean;
+  excludeContactedDays: number | null;
+  repeatAfterDays: number | null;
+  enabled: boolean;
+}
+
+async function call<TReq, TRes>(
+  subject: string,
+  payload: TReq,
+  deps = { request: useNatsStore.getState().request }
+): Promise<TRes> {
+  const req = createRequest(getToken(), payload);
+  const response = await deps.request<typeof req, NatsResponse<TRes>>(subject, req);
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
+
+/**
+ * List campaigns with their send statistics
+ */
+export async function listCampaigns(
+  deps = { request: useNatsStore.getState().request }
+): Promise<Campaign[]> {
+  const result = await call<Record<string, never>, { campaigns: Campaign[] }>('sazinka.campaign.list', {}, deps);
+  return result.campaigns;
+}
+
+/**
+ * Create a campaign
+ */
+export async function createCampaign(
+  request: CreateCampaignRequest,
+  deps = { request: useNatsStore.getState().request }
+): Promise<Campaign> {
+  return call<CreateCampaignRequest, Campaign>('sazinka.campaign.create', request, deps);
+}
+
+/**
+ * Update a campaign
+ */
+export async function updateCampaign(
+  request: UpdateCampaignRequest,
+  deps = { request: useNatsStore.getState().request }
+): Promise<Campaign> {
+  return call<UpdateCampaignRequest, Campaign>('sazinka.campaign.update', request, deps);
+}
+
+/**
+ * Delete a campaign and its send log
+ */
+export async function deleteCampaign(
+  id: string,
+  deps = { request: useNatsStore.getState().request }
+): Promise<void> {
+  await call<{ id: string }, { deleted: boolean }>('sazinka.campaign.delete', { id }, deps);
+}
+
+/**
+ * Messages queued by campaigns, newest first
+ */
+export async function listCampaignSends(
+  request: { campaignId?: string; limit?: number } = {},
+  deps = { request: useNatsStore.getState().request }
+): Promise<CampaignSend[]> {
+  const result = await call<typeof request, { sends: CampaignSend[] }>('sazinka.campaign.sends.list', request, deps);
+  return result.sends;
+}
@@ -393,6 +393,38 @@ export async function submitReverseGeocodeJob(
   return response.payload;
 }
 
+// ==========================================================================
+// Geocoding index freshness
+// ==========================================================================
+
+export interface GeocodeIndexStatus {
+  checkedAt: string;
+  dataUpdated: string | null;
+  ageDays: number | null;
+  maxAgeDays: number;
+  stale: boolean;
+}
+
+/**
+ * Age of the geocoding data; null until the worker has checked it
+ */
+export async function getGeocodeIndexStatus(
+  deps: CustomerServiceDeps = getDefaultDeps()
+): Promise<GeocodeIndexStatus | null> {
+  const request = createRequest(getToken(), {});
+
+  const response = await deps.request<typeof request, NatsResponse<GeocodeIndexStatus | null>>(
+    'sazinka.geocode.index.status',
+    request
+  );
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
+
 export type ReverseGeocodeJobStatus =
   | { type: 'queued'; position: number }
   | { type: 'processing' }
@@ -1,4 +1,10 @@
-import type { Device, CreateDeviceRequest } from '@shared/device';
+import type {
+  Device,
+  CreateDeviceRequest,
+  DeviceTransfer,
+  TransferDeviceRequest,
+  TransferDeviceResponse,
+} from '@shared/device';
 import type { SuccessResponse, ErrorResponse } from '@shared/messages';
 import { createRequest } from '@shared/messages';
 import { useNatsStore } from '../stores/natsStore';
@@ -162,3 +168,45 @@ export async function deleteDevice(
 
   return response.payload.deleted;
 }
+
+/**
+ * Move a device (and its open revisions) to another customer
+ */
+export async function transferDevice(
+  data: TransferDeviceRequest,
+  deps: DeviceServiceDeps = getDefaultDeps()
+): Promise<TransferDeviceResponse> {
+  const request = createRequest(getToken(), data);
+
+  const response = await deps.request<typeof request, NatsResponse<TransferDeviceResponse>>(
+    'sazinka.device.transfer',
+    request
+  );
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
+
+/**
+ * Transfer history of a device
+ */
+export async function listDeviceTransfers(
+  deviceId: string,
+  deps: DeviceServiceDeps = getDefaultDeps()
+): Promise<DeviceTransfer[]> {
+  const request = createRequest(getToken(), { deviceId });
+
+  const response = await deps.request<typeof request, NatsResponse<{ transfers: DeviceTransfer[] }>>(
+    'sazinka.device.transfers',
+    request
+  );
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload.transfers;
+}
@@ -156,6 +156,26 @@ describe('submitCustomerImportJob', () => {
     expect(p.payload.csvContent).toBe('name;city\nJan;Praha');
     expect(p.payload.filename).toBe('customers.csv');
   });
+
+  it('sends raw file bytes for encoding detection', async () => {
+    let capturedPayload: unknown;
+    const deps: ImportJobServiceDeps = {
+      request: vi.fn(async (_subject, payload) => {
+        capturedPayload = payload;
+        return {
+          id: 'test-id',
+          timestamp: new Date().toISOString(),
+          payload: { jobId: 'job-123', message: 'import:job_queued' },
+        };
+      }),
+    };
+
+    await submitCustomerImportJob({ text: 'Říčany', base64: '2O3oYW55' }, 'customers.csv', deps);
+
+    const p = capturedPayload as { payload: { csvContent: string; csvBase64?: string } };
+    expect(p.payload.csvBase64).toBe('2O3oYW55');
+    expect(p.payload.csvContent).toBe('');
+  });
 });
 
 describe('submitZipImportJob', () => {
@@ -16,11 +16,15 @@ import type {
   NotesImportJobSubmitResponse,
   ZipImportJobRequest,
   ZipImportJobSubmitResponse,
+  ImportUploadBeginResponse,
+  ImportUploadCommitResponse,
+  SourceSystem,
 } from '@shared/import';
 import type { SuccessResponse, ErrorResponse } from '@shared/messages';
 import { createRequest } from '@shared/messages';
 import { useNatsStore } from '../stores/natsStore';
 import { getToken } from '@/utils/auth';
+import type { CsvFileContent } from '@/utils/csvFile';
 
 // NATS subjects for all import types
 const SUBJECTS = {
@@ -54,6 +58,12 @@ const SUBJECTS = {
   },
 } as const;
 
+const UPLOAD_SUBJECTS = {
+  begin: 'sazinka.import.upload.begin',
+  chunk: 'sazinka.import.upload.chunk',
+  commit: 'sazinka.import.upload.commit',
+} as const;
+
 /**
  * Dependencies for import job service
  */
@@ -82,6 +92,61 @@ function isErrorResponse(response: NatsResponse<unknown>): response is ErrorResp
   return 'error' in response;
 }
 
+/**
+ * CSV part of an import request. Raw bytes go as base64 so the worker can
+ * detect the file encoding; plain text is sent as is (already UTF-8).
+ */
+function csvFields(csv: string | CsvFileContent): { csvContent: string; csvBase64?: string } {
+  return typeof csv === 'string' ? { csvContent: csv } : { csvContent: '', csvBase64: csv.base64 };
+}
+
+async function call<TReq, TRes>(subject: string, payload: TReq, deps: ImportJobServiceDeps): Promise<TRes> {
+  const request = createRequest(getToken(), payload);
+  const response = await deps.request<typeof request, NatsResponse<TRes>>(subject, request);
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+  return response.payload;
+}
+
+function toBase64(bytes: Uint8Array): string {
+  let binary = '';
+  for (let i = 0; i < bytes.length; i += 0x8000) {
+    binary += String.fromCharCode(...bytes.subarray(i, i + 0x8000));
+  }
+  return btoa(binary);
+}
+
+// =============================================================================
+// CHUNKED UPLOAD
+// =============================================================================
+
+/**
+ * Send a file too large for one NATS message in chunks.
+ * Returns the uploadId to pass to an import request instead of its content.
+ */
+export async function uploadImportFile(
+  bytes: Uint8Array,
+  filename: string,
+  deps: ImportJobServiceDeps = getDefaultDeps(),
+  onProgress?: (sent: number, total: number) => void
+): Promise<string> {
+  const { uploadId, maxChunkSize } = await call<unknown, ImportUploadBeginResponse>(
+    UPLOAD_SUBJECTS.begin,
+    { filename, totalSize: bytes.length },
+    deps
+  );
+
+  for (let index = 0, offset = 0; offset < bytes.length; index++, offset += maxChunkSize) {
+    const chunk = bytes.subarray(offset, offset + maxChunkSize);
+    await call(UPLOAD_SUBJECTS.chunk, { uploadId, index, dataBase64: toBase64(chunk) }, deps);
+    onProgress?.(offset + chunk.length, bytes.length);
+  }
+
+  await call<unknown, ImportUploadCommitResponse>(UPLOAD_SUBJECTS.commit, { uploadId }, deps);
+  return uploadId;
+}
+
 // =============================================================================
 // CUSTOMER IMPORT
 // =============================================================================
@@ -91,13 +156,15 @@ function isErrorResponse(response: NatsResponse<unknown>): response is ErrorResp
  * Returns immediately after the job is queued
  */
 export async function submitCustomerImportJob(
-  csvContent: string,
+  csv: string | CsvFileContent,
   filename: string,
-  deps: ImportJobServiceDeps = getDefaultDeps()
+  deps: ImportJobServiceDeps = getDefaultDeps(),
+  sourceSystem: SourceSystem = 'sazinka'
 ): Promise<CustomerImportJobSubmitResponse> {
   const payload: CustomerImportJobRequest = {
-    csvContent,
+    ...csvFields(csv),
     filename,
+    sourceSystem,
   };
   
   const request = createRequest(getToken(), payload);
@@ -122,13 +189,15 @@ export async function submitCustomerImportJob(
  * Submit a device import job
  */
 export async function submitDeviceImportJob(
-  csvContent: string,
+  csv: string | CsvFileContent,
   filename: string,
-  deps: ImportJobServiceDeps = getDefaultDeps()
+  deps: ImportJobServiceDeps = getDefaultDeps(),
+  sourceSystem: SourceSystem = 'sazinka'
 ): Promise<DeviceImportJobSubmitResponse> {
   const payload: DeviceImportJobRequest = {
-    csvContent,
+    ...csvFields(csv),
     filename,
+    sourceSystem,
   };
   
   const request = createRequest(getToken(), payload);
@@ -153,12 +222,12 @@ export async function submitDeviceImportJob(
  * Submit a revision import job
  */
 export async function submitRevisionImportJob(
-  csvContent: string,
+  csv: string | CsvFileContent,
   filename: string,
   deps: ImportJobServiceDeps = getDefaultDeps()
 ): Promise<RevisionImportJobSubmitResponse> {
   const payload: RevisionImportJobRequest = {
-    csvContent,
+    ...csvFields(csv),
     filename,
   };
   
@@ -184,12 +253,12 @@ export async function submitRevisionImportJob(
  * Submit a communication import job
  */
 export async function submitCommunicationImportJob(
-  csvContent: string,
+  csv: string | CsvFileContent,
   filename: string,
   deps: ImportJobServiceDeps = getDefaultDeps()
 ): Promise<CommunicationImportJobSubmitResponse> {
   const payload: CommunicationImportJobRequest = {
-    csvContent,
+    ...csvFields(csv),
     filename,
   };
   
@@ -215,12 +284,12 @@ export async function submitCommunicationImportJob(
  * Submit a work log import job
  */
 export async function submitWorkLogImportJob(
-  csvContent: string,
+  csv: string | CsvFileContent,
   filename: string,
   deps: ImportJobServiceDeps = getDefaultDeps()
 ): Promise<WorkLogImportJobSubmitResponse> {
   const payload: WorkLogImportJobRequest = {
-    csvContent,
+    ...csvFields(csv),
     filename,
   };
   
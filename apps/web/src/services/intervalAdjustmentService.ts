@@ -0,0 +1,118 @@
+/**
+ * Interval adjustment service
+ *
+ * Bulk change of the revision interval of a device category after a
+ * regulation change. A preview stores the adjustment as a draft with its
+ * per-device plan; applying the draft runs as a background job.
+ */
+
+import { useNatsStore } from '../stores/natsStore';
+import { createRequest, type SuccessResponse, type ErrorResponse } from '@shared/messages';
+import { getToken } from '@/utils/auth';
+
+type NatsResponse<T> = SuccessResponse<T> | ErrorResponse;
+
+function isErrorResponse(response: NatsResponse<unknown>): response is ErrorResponse {
+  return 'error' in response;
+}
+
+export type IntervalAdjustmentAction =
+  | 'move'
+  | 'create'
+  | 'unchanged'
+  | 'keep_scheduled'
+  | 'no_anchor';
+
+export interface IntervalAdjustmentSummary {
+  devices: number;
+  moved: number;
+  created: number;
+  unchanged: number;
+  keptScheduled: number;
+  noAnchor: number;
+}
+
+export interface IntervalAdjustment {
+  id: string;
+  deviceTypeConfigId: string;
+  newIntervalMonths: number;
+  onlyIntervalMonths: number | null;
+  updateTypeDefault: boolean;
+  regulationNote: string | null;
+  status: 'draft' | 'running' | 'applied' | 'failed';
+  previewSummary: IntervalAdjustmentSummary;
+  appliedSummary: IntervalAdjustmentSummary | null;
+  error: string | null;
+  createdBy: string | null;
+  createdAt: string;
+  appliedAt: string | null;
+}
+
+export interface IntervalAdjustmentItem {
+  deviceId: string;
+  customerId: string;
+  customerName: string | null;
+  deviceName: string | null;
+  oldIntervalMonths: number;
+  anchorDate: string | null;
+  revisionId: string | null;
+  oldDueDate: string | null;
+  newDueDate: string | null;
+  action: IntervalAdjustmentAction;
+}
+
+export interface PreviewIntervalAdjustmentInput {
+  deviceTypeConfigId: string;
+  newIntervalMonths: number;
+  /** Only devices currently on this interval */
+  onlyIntervalMonths?: number;
+  /** Also change the category default (default true) */
+  updateTypeDefault?: boolean;
+  regulationNote?: string;
+}
+
+async function call<TReq, TRes>(
+  subject: string,
+  payload: TReq,
+  deps = { request: useNatsStore.getState().request }
+): Promise<TRes> {
+  const req = createRequest(getToken(), payload);
+  const response = await deps.request<typeof req, NatsResponse<TRes>>(subject, req);
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
+
+/**
+ * Dry run; the returned adjustment is a draft until applied
+ */
+export async function previewIntervalAdjustment(
+  input: PreviewIntervalAdjustmentInput,
+  deps = { request: useNatsStore.getState().request }
+): Promise<{ adjustment: IntervalAdjustment; items: IntervalAdjustmentItem[]; itemsTruncated: boolean }> {
+  return call('sazinka.interval_adjustment.preview', input, deps);
+}
+
+/**
+ * Start the job; progress arrives on sazinka.job.interval_adjustment.status.{jobId}
+ */
+export async function applyIntervalAdjustment(
+  id: string,
+  deps = { request: useNatsStore.getState().request }
+): Promise<{ jobId: string }> {
+  return call('sazinka.interval_adjustment.apply', { id }, deps);
+}
+
+export async function listIntervalAdjustments(
+  deps = { request: useNatsStore.getState().request }
+): Promise<IntervalAdjustment[]> {
+  const result = await call<Record<string, never>, { adjustments: IntervalAdjustment[] }>(
+    'sazinka.interval_adjustment.list',
+    {},
+    deps
+  );
+  return result.adjustments;
+}
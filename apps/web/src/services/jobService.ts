@@ -28,7 +28,7 @@ function isErrorResponse(response: NatsResponse<unknown>): response is ErrorResp
 export interface JobHistoryEntry {
   id: string;
   jobType: string;
-  status: 'completed' | 'failed' | 'cancelled';
+  status: 'running' | 'completed' | 'failed' | 'cancelled';
   startedAt: string;
   completedAt: string;
   durationMs: number;
@@ -40,6 +40,7 @@ export interface JobHistoryEntry {
 /** Response from jobs.history endpoint */
 export interface JobHistoryResponse {
   jobs: JobHistoryEntry[];
+  /** Jobs matching the filters across all pages */
   total: number;
 }
 
@@ -52,9 +53,16 @@ export interface JobActionResponse {
 
 /** Request for job history */
 export interface ListJobHistoryRequest {
+  /** Page size (default 50, at most 200) */
   limit?: number;
+  offset?: number;
+  /** Exact type ('import.customer') or family ('import') */
   jobType?: string;
   status?: string;
+  /** Finished at or after (ISO timestamp) */
+  from?: string;
+  /** Finished before (ISO timestamp) */
+  to?: string;
 }
 
 /** List job history */
@@ -0,0 +1,98 @@
+/**
+ * Notification service
+ *
+ * In-app notifications of the account and the owner's subscription
+ * preferences: per category, notifications can be switched off or
+ * collected into an hourly or daily summary.
+ */
+
+import { useNatsStore } from '../stores/natsStore';
+import { createRequest, type SuccessResponse, type ErrorResponse } from '@shared/messages';
+import { getToken } from '@/utils/auth';
+
+type NatsResponse<T> = SuccessResponse<T> | ErrorResponse;
+
+function isErrorResponse(response: NatsResponse<unknown>): response is ErrorResponse {
+  return 'error' in response;
+}
+
+export type NotificationCategory = 'import' | 'route' | 'reminder' | 'system';
+export type NotificationDelivery = 'immediate' | 'hourly' | 'daily';
+
+export interface AppNotification {
+  id: string;
+  kind: string;
+  title: string;
+  body: string | null;
+  entityType: string | null;
+  entityId: string | null;
+  createdAt: string;
+  readAt: string | null;
+}
+
+export interface NotificationPreference {
+  category: NotificationCategory;
+  enabled: boolean;
+  delivery: NotificationDelivery;
+  /** True while the category uses the default (enabled, immediate) */
+  isDefault: boolean;
+}
+
+async function call<TReq, TRes>(
+  subject: string,
+  payload: TReq,
+  deps = { request: useNatsStore.getState().request }
+): Promise<TRes> {
+  const req = createRequest(getToken(), payload);
+  const response = await deps.request<typeof req, NatsResponse<TRes>>(subject, req);
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
+
+/**
+ * Notifications, newest first
+ */
+export async function listNotifications(
+  options: { unreadOnly?: boolean; limit?: number; offset?: number } = {},
+  deps = { request: useNatsStore.getState().request }
+): Promise<{ items: AppNotification[]; unreadCount: number }> {
+  return call('sazinka.notification.list', options, deps);
+}
+
+/**
+ * Mark notifications read (all unread ones when no ids are given)
+ */
+export async function markNotificationsRead(
+  ids?: string[],
+  deps = { request: useNatsStore.getState().request }
+): Promise<{ updated: number; unreadCount: number }> {
+  return call('sazinka.notification.read', ids ? { ids } : {}, deps);
+}
+
+/**
+ * Subscription preference of every category
+ */
+export async function listNotificationPreferences(
+  deps = { request: useNatsStore.getState().request }
+): Promise<NotificationPreference[]> {
+  const result = await call<Record<string, never>, { preferences: NotificationPreference[] }>(
+    'sazinka.notification.preferences.list',
+    {},
+    deps
+  );
+  return result.preferences;
+}
+
+/**
+ * Change how one category is delivered (company owners only)
+ */
+export async function setNotificationPreference(
+  preference: { category: NotificationCategory; enabled: boolean; delivery?: NotificationDelivery },
+  deps = { request: useNatsStore.getState().request }
+): Promise<NotificationPreference> {
+  return call('sazinka.notification.preferences.set', preference, deps);
+}
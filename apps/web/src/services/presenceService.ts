@@ -0,0 +1,91 @@
+/**
+ * Presence service
+ *
+ * Each open tab sends a heartbeat for the entity on screen and gets back
+ * who else has it open, so the UI can warn before two people edit the
+ * same route or customer at once.
+ */
+
+import { useNatsStore } from '../stores/natsStore';
+import { createRequest, type SuccessResponse, type ErrorResponse } from '@shared/messages';
+import { getToken } from '@/utils/auth';
+
+type NatsResponse<T> = SuccessResponse<T> | ErrorResponse;
+
+function isErrorResponse(response: NatsResponse<unknown>): response is ErrorResponse {
+  return 'error' in response;
+}
+
+export type PresenceEntityType = 'route' | 'customer' | 'visit' | 'revision' | 'device';
+
+export interface PresenceViewer {
+  userId: string;
+  name: string;
+  /** Has unsaved changes open */
+  editing: boolean;
+  since: string;
+}
+
+export interface PresenceResponse {
+  /** Other sessions on the entity, editors first */
+  viewers: PresenceViewer[];
+  heartbeatSeconds: number;
+}
+
+export interface PresenceHeartbeatRequest {
+  sessionId: string;
+  entityType: PresenceEntityType;
+  entityId: string;
+  editing?: boolean;
+}
+
+async function call<TReq, TRes>(
+  subject: string,
+  payload: TReq,
+  deps = { request: useNatsStore.getState().request }
+): Promise<TRes> {
+  const req = createRequest(getToken(), payload);
+  const response = await deps.request<typeof req, NatsResponse<TRes>>(subject, req);
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
+
+/**
+ * Report this tab on an entity; returns everyone else on it
+ */
+export async function sendPresenceHeartbeat(
+  request: PresenceHeartbeatRequest,
+  deps = { request: useNatsStore.getState().request }
+): Promise<PresenceResponse> {
+  return call<PresenceHeartbeatRequest, PresenceResponse>('sazinka.presence.heartbeat', request, deps);
+}
+
+/**
+ * Leave an entity, or every entity of the session when none is given
+ */
+export async function leavePresence(
+  sessionId: string,
+  entity?: { entityType: PresenceEntityType; entityId: string },
+  deps = { request: useNatsStore.getState().request }
+): Promise<void> {
+  await call('sazinka.presence.leave', { sessionId, ...entity }, deps);
+}
+
+/**
+ * Everyone on an entity, without joining it
+ */
+export async function listPresence(
+  entityType: PresenceEntityType,
+  entityId: string,
+  deps = { request: useNatsStore.getState().request }
+): Promise<PresenceResponse> {
+  return call<{ entityType: PresenceEntityType; entityId: string }, PresenceResponse>(
+    'sazinka.presence.list',
+    { entityType, entityId },
+    deps
+  );
+}
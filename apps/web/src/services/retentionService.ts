@@ -0,0 +1,106 @@
+/**
+ * Retention service
+ *
+ * Revision reports, completed visits and communication are kept for a
+ * retention period per entity type; deleting them earlier fails with
+ * RETENTION_ACTIVE. Expired records can be removed by a daily sweep.
+ */
+
+import { useNatsStore } from '../stores/natsStore';
+import { createRequest, type SuccessResponse, type ErrorResponse } from '@shared/messages';
+import { getToken } from '@/utils/auth';
+
+type NatsResponse<T> = SuccessResponse<T> | ErrorResponse;
+
+function isErrorResponse(response: NatsResponse<unknown>): response is ErrorResponse {
+  return 'error' in response;
+}
+
+export type RetentionEntityType = 'revision' | 'visit' | 'communication';
+
+export interface RetentionPolicy {
+  entityType: RetentionEntityType;
+  retentionMonths: number;
+  deleteAfterExpiry: boolean;
+  /** True while the account uses the built-in period */
+  isDefault: boolean;
+  updatedAt: string | null;
+}
+
+export interface SetRetentionPolicyRequest {
+  entityType: RetentionEntityType;
+  /** Revision reports: at least 60 months; others at least 12 */
+  retentionMonths: number;
+  deleteAfterExpiry?: boolean;
+}
+
+export interface RetentionReportEntry {
+  policy: RetentionPolicy;
+  retainedCount: number;
+  expiredCount: number;
+  nextExpiry: string | null;
+  deletedLastYear: number;
+}
+
+export interface RetentionDeletion {
+  id: string;
+  entityType: RetentionEntityType;
+  entityId: string;
+  referenceDate: string;
+  retentionMonths: number;
+  deletedAt: string;
+}
+
+export interface RetentionReport {
+  generatedAt: string;
+  entries: RetentionReportEntry[];
+  recentDeletions: RetentionDeletion[];
+}
+
+async function call<TReq, TRes>(
+  subject: string,
+  payload: TReq,
+  deps = { request: useNatsStore.getState().request }
+): Promise<TRes> {
+  const req = createRequest(getToken(), payload);
+  const response = await deps.request<typeof req, NatsResponse<TRes>>(subject, req);
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
+
+/**
+ * Retention policy of every entity type
+ */
+export async function listRetentionPolicies(
+  deps = { request: useNatsStore.getState().request }
+): Promise<RetentionPolicy[]> {
+  const result = await call<Record<string, never>, { policies: RetentionPolicy[] }>(
+    'sazinka.retention.policy.list',
+    {},
+    deps
+  );
+  return result.policies;
+}
+
+/**
+ * Set the retention period of an entity type
+ */
+export async function setRetentionPolicy(
+  request: SetRetentionPolicyRequest,
+  deps = { request: useNatsStore.getState().request }
+): Promise<RetentionPolicy> {
+  return call<SetRetentionPolicyRequest, RetentionPolicy>('sazinka.retention.policy.set', request, deps);
+}
+
+/**
+ * Retention report for audits
+ */
+export async function getRetentionReport(
+  deps = { request: useNatsStore.getState().request }
+): Promise<RetentionReport> {
+  return call<Record<string, never>, RetentionReport>('sazinka.retention.report', {}, deps);
+}
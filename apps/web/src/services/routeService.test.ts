@@ -1,5 +1,5 @@
 import { describe, it, expect, vi, beforeEach } from 'vitest';
-import { submitRoutePlanJob, type CustomerTimeWindow, type RoutePlanJobRequest } from './routeService';
+import { submitRoutePlanJob, suggestBreakLocations, type CustomerTimeWindow, type RoutePlanJobRequest } from './routeService';
 
 vi.mock('@/utils/auth', () => ({
   getToken: () => 'test-token',
@@ -114,6 +114,34 @@ describe('routeService', () => {
     });
   });
 
+  describe('suggestBreakLocations', () => {
+    it('sends the leg ends and returns the suggestions', async () => {
+      const suggestion = {
+        name: 'Pizzerie Roma', kind: 'restaurant', lat: 49.236, lng: 16.804, address: 'Hlavní 12', detourKm: 0.4,
+      };
+      mockRequest.mockResolvedValue({ payload: { enabled: true, suggestions: [suggestion] } });
+
+      const result = await suggestBreakLocations(
+        { lat: 49.19, lng: 16.6 },
+        { lat: 49.28, lng: 17.0 },
+        { request: mockRequest },
+      );
+
+      const [subject, payload] = mockRequest.mock.calls[0];
+      expect(subject).toBe('sazinka.route.break.suggest');
+      expect(payload.payload).toEqual({ from: { lat: 49.19, lng: 16.6 }, to: { lat: 49.28, lng: 17.0 } });
+      expect(result.suggestions).toEqual([suggestion]);
+    });
+
+    it('throws on error response', async () => {
+      mockRequest.mockResolvedValue({ error: { code: 'GEOCODING_ERROR', message: 'Nominatim down' } });
+
+      await expect(
+        suggestBreakLocations({ lat: 49.19, lng: 16.6 }, null, { request: mockRequest }),
+      ).rejects.toThrow('Nominatim down');
+    });
+  });
+
   describe('CustomerTimeWindow type', () => {
     it('has expected shape', () => {
       const tw: CustomerTimeWindow = {
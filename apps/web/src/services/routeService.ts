@@ -22,6 +22,26 @@ function isErrorResponse(response: NatsResponse<unknown>): response is ErrorResp
   return 'error' in response;
 }
 
+/** Place a break is held at; without one the break is at the previous stop */
+export interface BreakLocation {
+  name: string;
+  kind: 'restaurant' | 'fuel';
+  lat: number;
+  lng: number;
+  address: string | null;
+}
+
+export interface BreakSuggestion extends BreakLocation {
+  /** Extra straight-line distance compared to driving the leg directly */
+  detourKm: number;
+}
+
+export interface BreakSuggestResponse {
+  /** false when break location suggestions are turned off in settings */
+  enabled: boolean;
+  suggestions: BreakSuggestion[];
+}
+
 export interface SaveRouteStop {
   customerId?: string;
   revisionId?: string;
@@ -41,6 +61,8 @@ export interface SaveRouteStop {
   overrideServiceDurationMinutes?: number | null;
   /** Manual override for travel duration */
   overrideTravelDurationMinutes?: number | null;
+  /** Chosen place of a break stop */
+  breakLocation?: BreakLocation;
 }
 
 export interface SaveRouteRequest {
@@ -108,6 +130,7 @@ export interface SavedRouteStop {
   // Break stop fields (only for stopType === 'break')
   breakDurationMinutes?: number | null;
   breakTimeStart?: string | null; // HH:MM format
+  breakLocation?: BreakLocation | null;
   /** Per-stop service duration in minutes */
   serviceDurationMinutes?: number | null;
   /** Manual override for service duration */
@@ -171,6 +194,28 @@ export async function getRoute(
   return response.payload;
 }
 
+/**
+ * Suggest restaurants and fuel stations for a break between two stops
+ * (`to` is null when the break ends the route)
+ */
+export async function suggestBreakLocations(
+  from: Coordinates,
+  to: Coordinates | null,
+  deps = { request: useNatsStore.getState().request }
+): Promise<BreakSuggestResponse> {
+  const req = createRequest(getToken(), { from, to });
+  const response = await deps.request<typeof req, NatsResponse<BreakSuggestResponse>>(
+    'sazinka.route.break.suggest',
+    req,
+  );
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
+
 /**
  * Delete a route by ID
  */
@@ -268,6 +313,109 @@ export async function listRoutes(
   return response.payload;
 }
 
+// ==========================================================================
+// Plan like last year
+// ==========================================================================
+
+/** Last year's route mapped onto the planned week */
+export interface HistoryRouteProposal {
+  sourceRouteId: string;
+  sourceDate: string;
+  /** Same weekday in the planned week */
+  date: string;
+  crewId: string | null;
+  crewName: string | null;
+  /** Customers whose revisions are due again */
+  customerIds: string[];
+  /** Customers of the old route with nothing due, left out */
+  notDueCustomerIds: string[];
+  plan: RoutePlanResponse | null;
+  /** NO_DUE_CUSTOMERS, NO_DEPOT or the solver error code */
+  skippedReason: string | null;
+}
+
+export interface RoutePlanFromHistoryResponse {
+  sourceWeekStart: string;
+  sourceWeekEnd: string;
+  proposals: HistoryRouteProposal[];
+}
+
+/**
+ * Propose the routes of the week of `date` from the routes driven in the
+ * same week last year. Nothing is saved.
+ */
+export async function planFromHistory(
+  date: string,
+  crewId?: string | null,
+  deps = { request: useNatsStore.getState().request }
+): Promise<RoutePlanFromHistoryResponse> {
+  const req = createRequest(getToken(), { date, crewId: crewId ?? null });
+  const response = await deps.request<typeof req, NatsResponse<RoutePlanFromHistoryResponse>>(
+    'sazinka.route.plan.from_history',
+    req,
+  );
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
+
+// ==========================================================================
+// Multi-day planning
+// ==========================================================================
+
+export interface RoutePlanMultidayRequest {
+  startDate: string;
+  /** Inclusive, at most 31 days after startDate */
+  endDate: string;
+  crewId?: string | null;
+  /** Revisions to plan; all unscheduled revisions due by endDate when empty */
+  revisionIds?: string[];
+  /** Start of every day; the crew's home depot or the primary depot if absent */
+  startLocation?: { lat: number; lng: number } | null;
+  includeWeekends?: boolean;
+}
+
+export interface MultidayRouteDay {
+  date: string;
+  customerIds: string[];
+  revisionIds: string[];
+  /** Customers planned after their revision's due date */
+  lateCustomerIds: string[];
+  plan: RoutePlanResponse | null;
+  skippedReason: string | null;
+}
+
+export interface RoutePlanMultidayResponse {
+  days: MultidayRouteDay[];
+  unassignedCustomerIds: string[];
+  missingCoordinatesCustomerIds: string[];
+}
+
+/**
+ * Spread due revisions over the working days of a range and optimize each
+ * day. Nothing is saved.
+ */
+export async function planMultiday(
+  request: RoutePlanMultidayRequest,
+  deps = { request: useNatsStore.getState().request }
+): Promise<RoutePlanMultidayResponse> {
+  const req = createRequest(getToken(), request);
+  const response = await deps.request<typeof req, NatsResponse<RoutePlanMultidayResponse>>(
+    'sazinka.route.plan.multiday',
+    req,
+    120000,
+  );
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
+
 // ==========================================================================
 // Quick route recalculation (ETA/ETD after insert or reorder)
 // ==========================================================================
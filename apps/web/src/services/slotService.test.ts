@@ -0,0 +1,55 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { getSlotCalendar } from './slotService';
+
+vi.mock('@/utils/auth', () => ({
+  getToken: () => 'test-token',
+}));
+
+const mockRequest = vi.fn();
+
+vi.mock('../stores/natsStore', () => ({
+  useNatsStore: {
+    getState: () => ({
+      request: mockRequest,
+    }),
+  },
+}));
+
+describe('slotService', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('requests the capacity calendar for a date range', async () => {
+    const day = {
+      date: '2026-05-04',
+      crewId: 'crew-1',
+      crewName: 'Posádka 1',
+      visitCount: 1,
+      capacityMinutes: 480,
+      usedMinutes: 60,
+      freeMinutes: 420,
+      loadPercent: 13,
+      blocks: [{ start: '08:00:00', end: '09:00:00', usedMinutes: 60, freeMinutes: 0 }],
+    };
+    mockRequest.mockResolvedValue({ payload: { days: [day] } });
+
+    const result = await getSlotCalendar(
+      { dateFrom: '2026-05-04', dateTo: '2026-05-08', crewId: 'crew-1' },
+      { request: mockRequest },
+    );
+
+    const [subject, payload] = mockRequest.mock.calls[0];
+    expect(subject).toBe('sazinka.slots.calendar');
+    expect(payload.payload).toEqual({ dateFrom: '2026-05-04', dateTo: '2026-05-08', crewId: 'crew-1' });
+    expect(result.days).toEqual([day]);
+  });
+
+  it('throws the backend error', async () => {
+    mockRequest.mockResolvedValue({ error: { code: 'INVALID_REQUEST', message: 'Date range is limited to 62 days' } });
+
+    await expect(
+      getSlotCalendar({ dateFrom: '2026-05-01', dateTo: '2026-09-01' }, { request: mockRequest }),
+    ).rejects.toThrow('Date range is limited to 62 days');
+  });
+});
@@ -0,0 +1,73 @@
+/**
+ * Slot availability service
+ *
+ * Free and used crew capacity per day and time block, for availability
+ * calendars that would otherwise request suggestions day by day.
+ */
+
+import { useNatsStore } from '../stores/natsStore';
+import { createRequest, type SuccessResponse, type ErrorResponse } from '@shared/messages';
+import { getToken } from '@/utils/auth';
+
+type NatsResponse<T> = SuccessResponse<T> | ErrorResponse;
+
+function isErrorResponse(response: NatsResponse<unknown>): response is ErrorResponse {
+  return 'error' in response;
+}
+
+export interface SlotCalendarRequest {
+  /** YYYY-MM-DD */
+  dateFrom: string;
+  /** YYYY-MM-DD, at most 62 days after dateFrom */
+  dateTo: string;
+  /** All active crews when omitted */
+  crewId?: string;
+  /** Block length, 15–240 minutes (default 60) */
+  blockMinutes?: number;
+}
+
+export interface CalendarBlock {
+  /** HH:MM:SS */
+  start: string;
+  end: string;
+  usedMinutes: number;
+  freeMinutes: number;
+}
+
+export interface CrewCalendarDay {
+  date: string;
+  crewId: string;
+  crewName: string;
+  visitCount: number;
+  /** Working hours of the crew */
+  capacityMinutes: number;
+  /** Visits and the driving to them */
+  usedMinutes: number;
+  freeMinutes: number;
+  loadPercent: number;
+  blocks: CalendarBlock[];
+}
+
+export interface SlotCalendarResponse {
+  days: CrewCalendarDay[];
+}
+
+/**
+ * Get crew capacity per day and time block for a date range
+ */
+export async function getSlotCalendar(
+  request: SlotCalendarRequest,
+  deps = { request: useNatsStore.getState().request }
+): Promise<SlotCalendarResponse> {
+  const req = createRequest(getToken(), request);
+  const response = await deps.request<typeof req, NatsResponse<SlotCalendarResponse>>(
+    'sazinka.slots.calendar',
+    req,
+  );
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
@@ -0,0 +1,117 @@
+/**
+ * Webhook service
+ *
+ * HTTPS endpoints of the account that receive signed callbacks when
+ * customers, revisions, visits or routes change. Managed by the company
+ * owner; the signing secret is only returned when an endpoint is created.
+ */
+
+import { useNatsStore } from '../stores/natsStore';
+import { createRequest, type SuccessResponse, type ErrorResponse } from '@shared/messages';
+import { getToken } from '@/utils/auth';
+
+type NatsResponse<T> = SuccessResponse<T> | ErrorResponse;
+
+function isErrorResponse(response: NatsResponse<unknown>): response is ErrorResponse {
+  return 'error' in response;
+}
+
+export type WebhookPayloadFormat = 'envelope' | 'flat';
+export type WebhookDeliveryStatus = 'pending' | 'delivered' | 'failed';
+
+export interface Webhook {
+  id: string;
+  url: string;
+  /** Subscribed event types; empty means every event */
+  eventTypes: string[];
+  format: WebhookPayloadFormat;
+  description: string | null;
+  isActive: boolean;
+  createdAt: string;
+  lastDeliveryStatus: WebhookDeliveryStatus | null;
+  lastDeliveryAt: string | null;
+}
+
+export interface WebhookEventType {
+  eventType: string;
+  entity: string;
+  description: string;
+  samplePayload: unknown;
+}
+
+export interface CreateWebhookInput {
+  url: string;
+  eventTypes?: string[];
+  format?: WebhookPayloadFormat;
+  description?: string;
+}
+
+async function call<TReq, TRes>(
+  subject: string,
+  payload: TReq,
+  deps = { request: useNatsStore.getState().request }
+): Promise<TRes> {
+  const req = createRequest(getToken(), payload);
+  const response = await deps.request<typeof req, NatsResponse<TRes>>(subject, req);
+
+  if (isErrorResponse(response)) {
+    throw new Error(response.error.message);
+  }
+
+  return response.payload;
+}
+
+/**
+ * Catalog of events with sample payloads in the given format
+ */
+export async function listWebhookEventTypes(
+  format?: WebhookPayloadFormat,
+  deps = { request: useNatsStore.getState().request }
+): Promise<WebhookEventType[]> {
+  const result = await call<{ format?: WebhookPayloadFormat }, { eventTypes: WebhookEventType[] }>(
+    'sazinka.webhook.event_types',
+    format ? { format } : {},
+    deps
+  );
+  return result.eventTypes;
+}
+
+/**
+ * Register an endpoint; the secret is shown only in this response
+ */
+export async function createWebhook(
+  input: CreateWebhookInput,
+  deps = { request: useNatsStore.getState().request }
+): Promise<{ webhook: Webhook; secret: string }> {
+  return call('sazinka.webhook.create', input, deps);
+}
+
+/**
+ * Endpoints of the account with their latest delivery
+ */
+export async function listWebhooks(
+  deps = { request: useNatsStore.getState().request }
+): Promise<Webhook[]> {
+  const result = await call<Record<string, never>, { webhooks: Webhook[] }>('sazinka.webhook.list', {}, deps);
+  return result.webhooks;
+}
+
+/**
+ * Remove an endpoint and its delivery log
+ */
+export async function deleteWebhook(
+  id: string,
+  deps = { request: useNatsStore.getState().request }
+): Promise<void> {
+  await call('sazinka.webhook.delete', { id }, deps);
+}
+
+/**
+ * Send a test delivery to an endpoint
+ */
+export async function testWebhook(
+  id: string,
+  deps = { request: useNatsStore.getState().request }
+): Promise<{ deliveryId: string }> {
+  return call('sazinka.webhook.test', { id }, deps);
+}
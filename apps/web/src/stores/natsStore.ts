@@ -18,6 +18,48 @@ interface NatsState {
 
 const codec = JSONCodec();
 
+/** HTTP gateway of a standalone worker; when set, NATS is not used */
+const gatewayUrl = import.meta.env.VITE_GATEWAY_URL?.replace(/\/$/, '');
+
+async function gatewayRequest<TReq, TRes>(subject: string, payload: TReq, timeoutMs: number): Promise<TRes> {
+  const controller = new AbortController();
+  const timer = setTimeout(() => controller.abort(), timeoutMs);
+  try {
+    const response = await fetch(`${gatewayUrl}/api/${subject}`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(payload),
+      signal: controller.signal,
+    });
+    if (response.status === 503) {
+      throw new Error(i18n.t('common:errors.server_unavailable'));
+    }
+    if (response.status === 504) {
+      throw new Error(i18n.t('common:errors.request_timeout'));
+    }
+    return (await response.json()) as TRes;
+  } catch (error) {
+    if (error instanceof DOMException && error.name === 'AbortError') {
+      throw new Error(i18n.t('common:errors.request_timeout'));
+    }
+    throw error;
+  } finally {
+    clearTimeout(timer);
+  }
+}
+
+function gatewaySubscribe<T>(subject: string, callback: (msg: T) => void): () => void {
+  const events = new EventSource(`${gatewayUrl}/api/events?subject=${encodeURIComponent(subject)}`);
+  events.onmessage = (event) => {
+    try {
+      callback(JSON.parse(event.data) as T);
+    } catch (error) {
+      logger.error('Failed to decode message:', error);
+    }
+  };
+  return () => events.close();
+}
+
 export const useNatsStore = create<NatsState>((set, get) => ({
   connection: null,
   isConnected: false,
@@ -30,6 +72,22 @@ export const useNatsStore = create<NatsState>((set, get) => ({
 
     set({ isConnecting: true, error: null });
 
+    if (gatewayUrl) {
+      try {
+        const response = await fetch(`${gatewayUrl}/health`);
+        if (!response.ok) {
+          throw new Error(i18n.t('common:errors.server_unavailable'));
+        }
+        set({ isConnected: true, isConnecting: false });
+      } catch (error) {
+        set({
+          isConnecting: false,
+          error: error instanceof Error ? error.message : i18n.t('common:errors.failed_to_connect'),
+        });
+      }
+      return;
+    }
+
     try {
       const nc = await connect({
         servers: url,
@@ -68,6 +126,10 @@ export const useNatsStore = create<NatsState>((set, get) => ({
   },
 
   disconnect: async () => {
+    if (gatewayUrl) {
+      set({ isConnected: false });
+      return;
+    }
     const { connection } = get();
     if (connection) {
       await connection.close();
@@ -80,6 +142,13 @@ export const useNatsStore = create<NatsState>((set, get) => ({
     payload: TReq,
     timeoutMs = 10000
   ): Promise<TRes> => {
+    if (gatewayUrl) {
+      if (!get().isConnected) {
+        throw new Error(i18n.t('common:errors.not_connected'));
+      }
+      return gatewayRequest<TReq, TRes>(subject, payload, timeoutMs);
+    }
+
     const { connection, isConnected } = get();
 
     if (!connection || !isConnected) {
@@ -114,6 +183,13 @@ export const useNatsStore = create<NatsState>((set, get) => ({
     subject: string,
     callback: (msg: T) => void
   ): Promise<() => void> => {
+    if (gatewayUrl) {
+      if (!get().isConnected) {
+        throw new Error(i18n.t('common:errors.not_connected'));
+      }
+      return gatewaySubscribe(subject, callback);
+    }
+
     const { connection, isConnected } = get();
 
     if (!connection || !isConnected) {
@@ -18,6 +18,7 @@ const DEFAULT_BREAK_SETTINGS: BreakSettings = {
   breakLatestTime: '12:30',
   breakMinKm: 10,
   breakMaxKm: 80,
+  breakLocationSuggestions: true,
 };
 
 function makeCustomerStop(overrides: Partial<SavedRouteStop> = {}): SavedRouteStop {
@@ -0,0 +1,25 @@
+import { describe, it, expect } from 'vitest';
+import { colocatedWithPrevious, distanceMeters } from './colocation';
+
+const BLOCK = { lat: 49.2101, lng: 16.6302 };
+// About 10 m away: another entrance of the same building
+const BLOCK_ENTRANCE = { lat: 49.21019, lng: 16.63023 };
+const VILLA = { lat: 49.22, lng: 16.65 };
+
+describe('distanceMeters', () => {
+  it('measures short distances in metres', () => {
+    const d = distanceMeters(BLOCK, BLOCK_ENTRANCE);
+    expect(d).toBeGreaterThan(5);
+    expect(d).toBeLessThan(15);
+  });
+});
+
+describe('colocatedWithPrevious', () => {
+  it('marks consecutive stops in the same building', () => {
+    expect(colocatedWithPrevious([BLOCK, BLOCK_ENTRANCE, BLOCK, VILLA])).toEqual([false, true, true, false]);
+  });
+
+  it('never groups across a stop without coordinates', () => {
+    expect(colocatedWithPrevious([BLOCK, null, BLOCK])).toEqual([false, false, false]);
+  });
+});
@@ -0,0 +1,35 @@
+/**
+ * Co-located stops
+ *
+ * Units of one apartment building share (almost) the same coordinates. The
+ * worker routes them as one physical stop; the UI lists them together.
+ */
+
+/** Stops closer than this are in the same building (matches the worker) */
+export const COLOCATION_RADIUS_M = 25;
+
+export interface LatLng {
+  lat: number;
+  lng: number;
+}
+
+/** Great-circle distance in metres */
+export function distanceMeters(a: LatLng, b: LatLng): number {
+  const toRad = (deg: number) => (deg * Math.PI) / 180;
+  const dLat = toRad(b.lat - a.lat);
+  const dLng = toRad(b.lng - a.lng);
+  const h =
+    Math.sin(dLat / 2) ** 2 + Math.cos(toRad(a.lat)) * Math.cos(toRad(b.lat)) * Math.sin(dLng / 2) ** 2;
+  return 2 * 6_371_000 * Math.asin(Math.sqrt(h));
+}
+
+/**
+ * For each stop, whether it is in the same building as the stop right
+ * before it. Stops without coordinates (breaks) are never co-located.
+ */
+export function colocatedWithPrevious(points: Array<LatLng | null>): boolean[] {
+  return points.map((point, i) => {
+    const previous = i > 0 ? points[i - 1] : null;
+    return point != null && previous != null && distanceMeters(previous, point) <= COLOCATION_RADIUS_M;
+  });
+}
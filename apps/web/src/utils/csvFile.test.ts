@@ -0,0 +1,22 @@
+import { describe, it, expect } from 'vitest';
+import { bytesToBase64, decodeCsvPreview } from './csvFile';
+
+describe('decodeCsvPreview', () => {
+  it('keeps valid UTF-8', () => {
+    const bytes = new TextEncoder().encode('jméno;město\nNovák;Říčany');
+    expect(decodeCsvPreview(bytes)).toBe('jméno;město\nNovák;Říčany');
+  });
+
+  it('falls back to Windows-1250', () => {
+    // "Říčany" in Windows-1250
+    const bytes = new Uint8Array([0xd8, 0xed, 0xe8, 0x61, 0x6e, 0x79]);
+    expect(decodeCsvPreview(bytes)).toBe('Říčany');
+  });
+});
+
+describe('bytesToBase64', () => {
+  it('encodes raw bytes', () => {
+    expect(bytesToBase64(new Uint8Array([0xd8, 0xed, 0xe8]))).toBe('2O3o');
+    expect(bytesToBase64(new Uint8Array([]))).toBe('');
+  });
+});
@@ -0,0 +1,39 @@
+/**
+ * Reading CSV files for import.
+ *
+ * Czech exports from Excel and accounting software are usually Windows-1250,
+ * which `readAsText(file, 'utf-8')` silently mangles. The raw bytes are sent
+ * to the worker (base64), which detects the encoding itself; the text here
+ * is only a best-effort decode for the preview table.
+ */
+
+export interface CsvFileContent {
+  /** Text for the preview: UTF-8 when valid, otherwise Windows-1250 */
+  text: string;
+  /** Raw file bytes, base64 encoded */
+  base64: string;
+}
+
+export function decodeCsvPreview(bytes: Uint8Array): string {
+  try {
+    return new TextDecoder('utf-8', { fatal: true }).decode(bytes);
+  } catch {
+    return new TextDecoder('windows-1250').decode(bytes);
+  }
+}
+
+export function bytesToBase64(bytes: Uint8Array): string {
+  let binary = '';
+  const chunk = 0x8000;
+  for (let i = 0; i < bytes.length; i += chunk) {
+    binary += String.fromCharCode(...bytes.subarray(i, i + chunk));
+  }
+  return btoa(binary);
+}
+
+export function readCsvFile(file: File): Promise<CsvFileContent> {
+  return file.arrayBuffer().then((buffer) => {
+    const bytes = new Uint8Array(buffer);
+    return { text: decodeCsvPreview(bytes), base64: bytesToBase64(bytes) };
+  });
+}
@@ -0,0 +1,26 @@
+import { describe, it, expect, beforeEach } from 'vitest';
+import { createRequest } from '@shared/messages';
+import { initDemoModeFromUrl, isDemoMode, setDemoMode } from './demoMode';
+
+describe('demoMode', () => {
+  beforeEach(() => {
+    sessionStorage.clear();
+  });
+
+  it('marks requests while on', () => {
+    expect(createRequest('t', {}).demoMode).toBeUndefined();
+    setDemoMode(true);
+    expect(createRequest('t', {}).demoMode).toBe(true);
+    setDemoMode(false);
+    expect(isDemoMode()).toBe(false);
+  });
+
+  it('follows the demo query parameter', () => {
+    initDemoModeFromUrl('?demo=1');
+    expect(isDemoMode()).toBe(true);
+    initDemoModeFromUrl('?tab=plan');
+    expect(isDemoMode()).toBe(true);
+    initDemoModeFromUrl('?demo=0');
+    expect(isDemoMode()).toBe(false);
+  });
+});
@@ -0,0 +1,30 @@
+/**
+ * Demo mode for screenshots and training videos.
+ *
+ * While on, every request carries `demoMode` and the worker replaces
+ * customer names, contacts and addresses with stable pseudonyms. The flag
+ * lives in sessionStorage, so it covers one browser tab and ends with it.
+ * Open the app with `?demo=1` to turn it on, `?demo=0` to turn it off.
+ */
+
+import { DEMO_MODE_STORAGE_KEY } from '@shared/messages';
+
+export function isDemoMode(): boolean {
+  return sessionStorage.getItem(DEMO_MODE_STORAGE_KEY) === '1';
+}
+
+export function setDemoMode(enabled: boolean): void {
+  if (enabled) {
+    sessionStorage.setItem(DEMO_MODE_STORAGE_KEY, '1');
+  } else {
+    sessionStorage.removeItem(DEMO_MODE_STORAGE_KEY);
+  }
+}
+
+/** Apply a `demo` query parameter of the current URL */
+export function initDemoModeFromUrl(search: string = window.location.search): void {
+  const demo = new URLSearchParams(search).get('demo');
+  if (demo === '1' || demo === '0') {
+    setDemoMode(demo === '1');
+  }
+}
@@ -1,5 +1,13 @@
 import { describe, it, expect } from 'vitest';
-import { buildGoogleMapsUrl, MAX_WAYPOINTS, buildMapyCzUrl, MAPY_CZ_MAX_WAYPOINTS } from './routeExport';
+import {
+  buildGoogleMapsUrl,
+  MAX_WAYPOINTS,
+  buildMapyCzUrl,
+  MAPY_CZ_MAX_WAYPOINTS,
+  buildGpxRoute,
+  buildTomTomItinerary,
+  ITINERARY_MAX_WAYPOINTS,
+} from './routeExport';
 
 // ---------------------------------------------------------------------------
 // Helpers
@@ -295,3 +303,89 @@ describe('buildMapyCzUrl', () => {
     expect(r.url).toContain('waypoints=0,0');
   });
 });
+
+function makeNamedStop(lat: number, lng: number, name: string, phone: string | null = null) {
+  return {
+    customerLat: lat,
+    customerLng: lng,
+    stopType: 'customer' as const,
+    customerName: name,
+    customerPhone: phone,
+    address: `${name} street 1`,
+  };
+}
+
+const NAMED_DEPOT = { lat: 49.0, lng: 17.0, name: 'Depot' };
+
+describe('buildGpxRoute', () => {
+
+  // #1
+  it('depot + stops → ordered route points starting and ending at the depot', () => {
+    const r = buildGpxRoute({
+      name: 'Route',
+      depot: NAMED_DEPOT,
+      stops: [makeNamedStop(49.1, 16.1, 'Novák', '+420 601 123 456'), makeNamedStop(49.2, 16.2, 'Dvořák')],
+    });
+    const names = [...r.content.matchAll(/<rtept[^>]*>\s*<name>([^<]*)<\/name>/g)].map((m) => m[1]);
+    expect(names).toEqual(['Depot', '1. Novák', '2. Dvořák', 'Depot']);
+    expect(r.content).toContain('<cmt>+420 601 123 456</cmt>');
+    expect(r.content).toContain('<desc>Novák street 1</desc>');
+    expect(r.includedStopCount).toBe(2);
+  });
+
+  // #2
+  it('escapes XML in names and keeps 6 decimal precision', () => {
+    const r = buildGpxRoute({
+      name: 'A & B',
+      depot: null,
+      stops: [makeNamedStop(49.123456789, 16.987654321, 'Tom <& Jerry>')],
+    });
+    expect(r.content).toContain('<name>A &amp; B</name>');
+    expect(r.content).toContain('1. Tom &lt;&amp; Jerry&gt;');
+    expect(r.content).toContain('lat="49.123457" lon="16.987654"');
+  });
+
+  // #3
+  it('no located stops → empty content + NO_STOPS warning', () => {
+    const r = buildGpxRoute({ name: 'Route', depot: NAMED_DEPOT, stops: [] });
+    expect(r.content).toBe('');
+    expect(r.warnings).toContain('NO_STOPS');
+  });
+});
+
+describe('buildTomTomItinerary', () => {
+
+  // #1
+  it('writes integer 1e-5 coordinates with departure, via and destination flags', () => {
+    const r = buildTomTomItinerary({
+      name: 'Route',
+      depot: NAMED_DEPOT,
+      stops: [makeNamedStop(49.123456, 16.654321, 'Novak')],
+    });
+    const lines = r.content.trimEnd().split('\r\n');
+    expect(lines).toEqual([
+      '1700000|4900000|Depot|4|',
+      '1665432|4912346|1. Novak|0|',
+      '1700000|4900000|Depot|2|',
+    ]);
+  });
+
+  // #2
+  it('puts the phone into the description and strips diacritics and separators', () => {
+    const r = buildTomTomItinerary({
+      name: 'Route',
+      depot: null,
+      stops: [makeNamedStop(49.1, 16.1, 'Dvořák | Šťastný', '+420 601 123 456'), makeNamedStop(49.2, 16.2, 'B')],
+    });
+    expect(r.content).toContain('|1. Dvorak / Stastny (+420 601 123 456)|4|');
+  });
+
+  // #3
+  it('caps the itinerary at ITINERARY_MAX_WAYPOINTS stops', () => {
+    const stops = Array.from({ length: ITINERARY_MAX_WAYPOINTS + 5 }, (_, i) => makeNamedStop(49 + i * 0.01, 16, `S${i}`));
+    const r = buildTomTomItinerary({ name: 'Route', depot: NAMED_DEPOT, stops });
+    expect(r.includedStopCount).toBe(ITINERARY_MAX_WAYPOINTS);
+    expect(r.validStopCount).toBe(ITINERARY_MAX_WAYPOINTS + 5);
+    expect(r.warnings).toContain('TRUNCATED');
+  });
+});
@@ -1,5 +1,5 @@
 /**
- * Route export utilities for Google Maps and Mapy.cz.
+ * Route export utilities for Google Maps, Mapy.cz and navigation units.
  *
  * Google Maps Directions URL:
  *   https://www.google.com/maps/dir/?api=1&origin=…&destination=…&waypoints=…&travelmode=driving
@@ -8,10 +8,22 @@
  * Mapy.cz Route URL:
  *   https://mapy.com/fnc/v1/route?start=…&end=…&waypoints=…&routeType=car_fast_traffic
  *   Coordinates: lng,lat (reversed!) — waypoints semicolon-separated — max 15 waypoints
+ *
+ * Garmin (GPX 1.1 route):
+ *   <rte> with ordered <rtept lat lon> — name, address in <desc>, phone in <cmt>
+ *   Coordinates: 6 decimals. Garmin units import GPX routes directly; GPI is a
+ *   binary POI format, not an itinerary, so it is not produced.
+ *
+ * TomTom itinerary (.itn):
+ *   One line per waypoint: `lng*100000|lat*100000|description|flag|`
+ *   Coordinates: integers in 1e-5 degrees — flags 4 = departure, 2 = destination, 0 = via
+ *   Units read the file as Latin-1, so descriptions are transliterated to ASCII.
  */
 
 export const MAX_WAYPOINTS = 23;
 export const MAPY_CZ_MAX_WAYPOINTS = 15;
+/** TomTom units accept at most 48 itinerary points; Garmin route limits are similar */
+export const ITINERARY_MAX_WAYPOINTS = 48;
 
 export type GoogleMapsWarningCode =
   | 'TRUNCATED'
@@ -49,13 +61,14 @@ function lngLat(lat: number, lng: number): string {
   return `${roundCoord(lng)},${roundCoord(lat)}`;
 }
 
-type ValidStop = { customerLat: number; customerLng: number };
+type ExportStop = GoogleMapsExportParams['stops'][number];
+type LocatedStop<S extends ExportStop> = S & { customerLat: number; customerLng: number };
 
-function filterAndWarn(
-  params: GoogleMapsExportParams,
+function filterAndWarn<S extends ExportStop>(
+  depot: { lat: number; lng: number } | null,
+  stops: S[],
   maxWaypoints: number,
-): { validStops: ValidStop[]; includedStops: ValidStop[]; warnings: GoogleMapsWarningCode[] } | { earlyReturn: GoogleMapsExportResult } {
-  const { depot, stops } = params;
+): { validStops: LocatedStop<S>[]; includedStops: LocatedStop<S>[]; warnings: GoogleMapsWarningCode[] } {
   const warnings: GoogleMapsWarningCode[] = [];
 
   if (!depot) warnings.push('NO_DEPOT');
@@ -65,12 +78,11 @@ function filterAndWarn(
 
   if (rawCustomerCount === 0) {
     warnings.push('NO_STOPS');
-    return { earlyReturn: { url: '', warnings, includedStopCount: 0, validStopCount: 0 } };
+    return { validStops: [], includedStops: [], warnings };
   }
 
   const validStops = customerStops.filter(
-    (s): s is typeof s & { customerLat: number; customerLng: number } =>
-      s.customerLat !== null && s.customerLng !== null,
+    (s): s is LocatedStop<S> => s.customerLat !== null && s.customerLng !== null,
   );
 
   if (validStops.length < rawCustomerCount) {
@@ -79,7 +91,7 @@ function filterAndWarn(
 
   if (validStops.length === 0) {
     warnings.push('NO_STOPS');
-    return { earlyReturn: { url: '', warnings, includedStopCount: 0, validStopCount: 0 } };
+    return { validStops: [], includedStops: [], warnings };
   }
 
   let includedStops = validStops;
@@ -92,11 +104,12 @@ function filterAndWarn(
 }
 
 export function buildGoogleMapsUrl(params: GoogleMapsExportParams): GoogleMapsExportResult {
-  const result = filterAndWarn(params, MAX_WAYPOINTS);
-  if ('earlyReturn' in result) return result.earlyReturn;
-
-  const { validStops, includedStops, warnings } = result;
   const { depot } = params;
+  const { validStops, includedStops, warnings } = filterAndWarn(depot, params.stops, MAX_WAYPOINTS);
+  if (includedStops.length === 0) {
+    return { url: '', warnings, includedStopCount: 0, validStopCount: 0 };
+  }
+
   const parts: string[] = [];
 
   if (depot) {
@@ -125,11 +138,12 @@ export function buildGoogleMapsUrl(params: GoogleMapsExportParams): GoogleMapsEx
 }
 
 export function buildMapyCzUrl(params: GoogleMapsExportParams): GoogleMapsExportResult {
-  const result = filterAndWarn(params, MAPY_CZ_MAX_WAYPOINTS);
-  if ('earlyReturn' in result) return result.earlyReturn;
-
-  const { validStops, includedStops, warnings } = result;
   const { depot } = params;
+  const { validStops, includedStops, warnings } = filterAndWarn(depot, params.stops, MAPY_CZ_MAX_WAYPOINTS);
+  if (includedStops.length === 0) {
+    return { url: '', warnings, includedStopCount: 0, validStopCount: 0 };
+  }
+
   const parts: string[] = [];
 
   if (depot) {
@@ -156,3 +170,135 @@ export function buildMapyCzUrl(params: GoogleMapsExportParams): GoogleMapsExport
     validStopCount: validStops.length,
   };
 }
+
+// ---------------------------------------------------------------------------
+// Itinerary files for navigation units
+// ---------------------------------------------------------------------------
+
+export interface ItineraryExportParams {
+  /** Route name shown on the device */
+  name: string;
+  depot: { lat: number; lng: number; name: string } | null;
+  stops: Array<{
+    customerLat: number | null;
+    customerLng: number | null;
+    stopType: 'customer' | 'break';
+    customerName: string | null;
+    customerPhone: string | null;
+    address: string | null;
+  }>;
+}
+
+export interface ItineraryExportResult {
+  /** File content; empty when there is nothing to export */
+  content: string;
+  warnings: GoogleMapsWarningCode[];
+  includedStopCount: number;
+  validStopCount: number;
+}
+
+interface ItineraryPoint {
+  lat: number;
+  lng: number;
+  name: string;
+  address: string | null;
+  phone: string | null;
+}
+
+function itineraryPoints(params: ItineraryExportParams): {
+  points: ItineraryPoint[];
+  warnings: GoogleMapsWarningCode[];
+  includedStopCount: number;
+  validStopCount: number;
+} {
+  const { depot } = params;
+  const { validStops, includedStops, warnings } = filterAndWarn(depot, params.stops, ITINERARY_MAX_WAYPOINTS);
+  if (includedStops.length === 0) {
+    return { points: [], warnings, includedStopCount: 0, validStopCount: 0 };
+  }
+
+  const points: ItineraryPoint[] = includedStops.map((s, i) => ({
+    lat: s.customerLat,
+    lng: s.customerLng,
+    name: `${i + 1}. ${s.customerName?.trim() || s.address?.trim() || '?'}`,
+    address: s.address?.trim() || null,
+    phone: s.customerPhone?.trim() || null,
+  }));
+
+  if (depot) {
+    const depotPoint = { lat: depot.lat, lng: depot.lng, name: depot.name, address: null, phone: null };
+    points.unshift(depotPoint);
+    points.push(depotPoint);
+  }
+
+  return { points, warnings, includedStopCount: includedStops.length, validStopCount: validStops.length };
+}
+
+function escapeXml(value: string): string {
+  return value
+    .replace(/&/g, '&amp;')
+    .replace(/</g, '&lt;')
+    .replace(/>/g, '&gt;')
+    .replace(/"/g, '&quot;')
+    .replace(/'/g, '&apos;');
+}
+
+/** GPX 1.1 route for Garmin units (and any GPX-capable app) */
+export function buildGpxRoute(params: ItineraryExportParams): ItineraryExportResult {
+  const { points, warnings, includedStopCount, validStopCount } = itineraryPoints(params);
+  if (points.length === 0) {
+    return { content: '', warnings, includedStopCount, validStopCount };
+  }
+
+  const rtepts = points.map((p) => {
+    const lines = [`    <rtept lat="${roundCoord(p.lat)}" lon="${roundCoord(p.lng)}">`];
+    lines.push(`      <name>${escapeXml(p.name)}</name>`);
+    if (p.phone) lines.push(`      <cmt>${escapeXml(p.phone)}</cmt>`);
+    if (p.address) lines.push(`      <desc>${escapeXml(p.address)}</desc>`);
+    lines.push('    </rtept>');
+    return lines.join('\n');
+  });
+
+  const content = [
+    '<?xml version="1.0" encoding="UTF-8"?>',
+    '<gpx version="1.1" creator="Sazinka" xmlns="http://www.topografix.com/GPX/1/1">',
+    '  <rte>',
+    `    <name>${escapeXml(params.name)}</name>`,
+    ...rtepts,
+    '  </rte>',
+    '</gpx>',
+    '',
+  ].join('\n');
+
+  return { content, warnings, includedStopCount, validStopCount };
+}
+
+/** ASCII-only description without the ITN field separator */
+function itnText(value: string): string {
+  return value
+    .normalize('NFD')
+    .replace(/[\u0300-\u036f]/g, '')
+    .replace(/[^\x20-\x7e]/g, '')
+    .replace(/\|/g, '/')
+    .trim();
+}
+
+function itnCoord(n: number): number {
+  return Math.round(n * 100000);
+}
+
+/** TomTom itinerary; the phone number is appended to the description, ITN has no notes field */
+export function buildTomTomItinerary(params: ItineraryExportParams): ItineraryExportResult {
+  const { points, warnings, includedStopCount, validStopCount } = itineraryPoints(params);
+  if (points.length === 0) {
+    return { content: '', warnings, includedStopCount, validStopCount };
+  }
+
+  const lines = points.map((p, i) => {
+    const description = itnText(p.phone ? `${p.name} (${p.phone})` : p.name);
+    const flag = i === 0 ? 4 : i === points.length - 1 ? 2 : 0;
+    return `${itnCoord(p.lng)}|${itnCoord(p.lat)}|${description}|${flag}|`;
+  });
+
+  return { content: lines.join('\r\n') + '\r\n', warnings, includedStopCount, validStopCount };
+}
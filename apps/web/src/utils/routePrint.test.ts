@@ -288,4 +288,38 @@ describe('buildPrintHtml', () => {
     const html = buildPrintHtml(params);
     expect(html).toContain('45 min');
   });
+
+  // #26 — breaks with a chosen place are listed with it
+  it('lists a break with its chosen location', () => {
+    const params: PrintRouteParams = {
+      ...BASE_PARAMS,
+      stops: [
+        makeStop(1, 'Customer A'),
+        { ...makeBreakStop(2), breakLocation: { name: 'Pizzerie <Roma>', address: 'Hlavní 12, Rousínov' } },
+        makeStop(3, 'Customer B'),
+      ],
+    };
+    const html = buildPrintHtml(params);
+    expect(html).toContain('class="break-row"');
+    expect(html).toContain('Break: Pizzerie &lt;Roma&gt;');
+    expect(html).toContain('Hlavní 12, Rousínov');
+    expect(html).toContain('12:00');
+  });
+
+  // #27 — units of one building are listed together under the first one
+  it('lists units in the same building under the first one', () => {
+    const params: PrintRouteParams = {
+      ...BASE_PARAMS,
+      stops: [
+        makeStop(1, 'Byt 12', 'Kounicova 5, Brno'),
+        { ...makeStop(2, 'Byt 14', 'Kounicova 5, Brno'), sameBuildingAsPrevious: true },
+        makeStop(3, 'Vila', 'Palackého 1, Brno'),
+      ],
+    };
+    const html = buildPrintHtml(params);
+    expect(html).toContain('class="unit-row"');
+    expect(html).toContain('↳ Byt 14');
+    expect(html).toContain('Same building');
+    expect(html.split('Kounicova 5, Brno').length - 1).toBe(1);
+  });
 });
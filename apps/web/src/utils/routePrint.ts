@@ -21,6 +21,8 @@ export interface PrintLabels {
   colEtd: string;
   colService: string;
   generated: string;
+  breakAt: string;
+  sameBuilding: string;
 }
 
 const DEFAULT_LABELS: PrintLabels = {
@@ -39,6 +41,8 @@ const DEFAULT_LABELS: PrintLabels = {
   colEtd: 'ETD',
   colService: 'Service',
   generated: 'Generated',
+  breakAt: 'Break',
+  sameBuilding: 'Same building',
 };
 
 export interface PrintRouteParams {
@@ -52,6 +56,10 @@ export interface PrintRouteParams {
     etd: string | null;
     serviceDuration: string | null;
     stopType: 'customer' | 'break';
+    /** Chosen place of a break; breaks without one are not listed */
+    breakLocation?: { name: string; address: string | null } | null;
+    /** Unit in the same building as the previous stop; listed under it */
+    sameBuildingAsPrevious?: boolean;
   }>;
   depot: { name: string } | null;
   depotDeparture: string | null;
@@ -84,7 +92,7 @@ export function buildPrintHtml(params: PrintRouteParams): string {
   const { title, mapImageDataUrl, stops, depot, depotDeparture, returnTime, stats } = params;
   const l: PrintLabels = { ...DEFAULT_LABELS, ...params.labels };
 
-  const customerStops = stops.filter(s => s.stopType === 'customer');
+  const listedStops = stops.filter(s => s.stopType === 'customer' || s.breakLocation);
 
   const mapSection = mapImageDataUrl
     ? `<div class="map-section"><img src="${escapeHtml(mapImageDataUrl)}" alt="Route map" class="map-img" /></div>`
@@ -101,9 +109,27 @@ export function buildPrintHtml(params: PrintRouteParams): string {
   const distRow = `<tr><td class="label">${escapeHtml(l.distance)}</td><td>${dash(stats.distance)}</td></tr>`;
   const stopsRow = `<tr><td class="label">${escapeHtml(l.stops)}</td><td>${stats.stopCount}</td></tr>`;
 
-  const stopRows = customerStops
-    .map(
-      s => `<tr>
+  const stopRows = listedStops
+    .map(s =>
+      s.breakLocation
+        ? `<tr class="break-row">
+      <td></td>
+      <td>${escapeHtml(l.breakAt)}: ${escapeHtml(s.breakLocation.name)}</td>
+      <td>${dash(s.breakLocation.address)}</td>
+      <td>${dash(s.eta)}</td>
+      <td>${dash(s.etd)}</td>
+      <td>${dash(s.serviceDuration)}</td>
+    </tr>`
+        : s.sameBuildingAsPrevious
+        ? `<tr class="unit-row">
+      <td>${s.order}</td>
+      <td>↳ ${escapeHtml(s.name)}</td>
+      <td>${escapeHtml(l.sameBuilding)}</td>
+      <td>${dash(s.eta)}</td>
+      <td>${dash(s.etd)}</td>
+      <td>${dash(s.serviceDuration)}</td>
+    </tr>`
+        : `<tr>
       <td>${s.order}</td>
       <td>${escapeHtml(s.name)}</td>
       <td>${escapeHtml(s.address)}</td>
@@ -132,6 +158,8 @@ export function buildPrintHtml(params: PrintRouteParams): string {
     .stops-table { width: 100%; border-collapse: collapse; font-size: 0.85rem; }
     .stops-table th { background: #f0f0f0; border-bottom: 2px solid #999; text-align: left; padding: 4px 6px; }
     .stops-table td { border-bottom: 1px solid #ddd; padding: 3px 6px; vertical-align: top; }
+    .stops-table tr.break-row td { font-style: italic; color: #555; }
+    .stops-table tr.unit-row td { border-top: none; color: #333; }
     footer { margin-top: 20px; font-size: 0.75rem; color: #888; }
     @media print { body { margin: 0; } }
   </style>
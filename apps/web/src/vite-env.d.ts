@@ -12,6 +12,7 @@ interface ImportMetaEnv {
   readonly VITE_NATS_WS_URL: string;
   readonly VITE_NATS_USER: string;
   readonly VITE_NATS_PASS: string;
+  readonly VITE_GATEWAY_URL?: string;
 }
 
 interface ImportMeta {
@@ -3,6 +3,17 @@
 export type CommunicationType = 'email_sent' | 'email_received' | 'call' | 'note' | 'sms';
 export type CommunicationDirection = 'outbound' | 'inbound';
 export type EmailStatus = 'sent' | 'delivered' | 'opened' | 'bounced' | 'failed';
+/** 'manual' for entries typed in by users, the rest are written by the system */
+export type CommunicationEntryType =
+  | 'manual'
+  | 'reminder_email'
+  | 'reminder_sms'
+  | 'confirmation_email'
+  | 'confirmation_sms'
+  | 'campaign_email'
+  | 'campaign_sms'
+  | 'route_confirmed'
+  | 'visit_completed';
 
 export interface Communication {
   id: string;
@@ -21,6 +32,10 @@ export interface Communication {
   
   emailStatus?: EmailStatus | null;
   durationMinutes?: number | null;
+
+  entryType: CommunicationEntryType;
+  /** Structured facts of an automatic entry (recipient, route, visit result, ...) */
+  details?: Record<string, unknown> | null;
   
   followUpDate?: string | null;
   followUpCompleted: boolean;
@@ -53,6 +68,8 @@ export interface ListCommunicationsRequest {
   customerId?: string;
   revisionId?: string;
   commType?: CommunicationType;
+  /** Only these entry types; omitted or empty means all */
+  entryTypes?: CommunicationEntryType[];
   followUpPending?: boolean;
   limit?: number;
   offset?: number;
@@ -4,6 +4,10 @@ export type CustomerType = 'person' | 'company';
 
 export type GeocodeStatus = 'pending' | 'success' | 'failed';
 
+export type AcquisitionSource = 'web_form' | 'phone' | 'email' | 'referral' | 'campaign' | 'other';
+
+export type LeadStatus = 'new' | 'contacted' | 'quoted' | 'won' | 'lost';
+
 export interface Customer {
   id: string;
   userId: string;
@@ -23,6 +27,14 @@ export interface Customer {
   lng?: number;
   geocodeStatus: GeocodeStatus;  // Geocoding status: pending, success, failed
   notes?: string;
+  acquisitionSource?: AcquisitionSource | null;
+  /** Customer who recommended this one (referrals only) */
+  referrerCustomerId?: string | null;
+  /** Set for customers that came in as leads (web form) */
+  leadStatus?: LeadStatus | null;
+  /** Why the lead was lost (leadStatus 'lost' only) */
+  leadLostReason?: string | null;
+  leadStatusChangedAt?: string | null;
   createdAt: string;
   updatedAt: string;
 }
@@ -43,6 +55,9 @@ export interface CreateCustomerRequest {
   lat?: number;
   lng?: number;
   notes?: string;
+  acquisitionSource?: AcquisitionSource;
+  /** Only with acquisitionSource 'referral' */
+  referrerCustomerId?: string;
 }
 
 export interface UpdateCustomerRequest {
@@ -62,6 +77,9 @@ export interface UpdateCustomerRequest {
   lat?: number;
   lng?: number;
   notes?: string;
+  /** Empty string clears the source; changing the source replaces the referrer */
+  acquisitionSource?: AcquisitionSource | '';
+  referrerCustomerId?: string;
 }
 
 export interface Coordinates {
@@ -100,6 +118,8 @@ export type ImportIssueCode =
   | 'INVALID_RESULT'        // result string not recognized
   | 'DB_ERROR'              // unexpected database error
   | 'PARSE_ERROR'           // CSV row can't be parsed
+  | 'QUOTA_EXCEEDED'        // account plan customer limit reached
+  | 'ENCODING_ERROR'        // characters not decodable from the file encoding
   | 'UNKNOWN';              // catch-all
 
 export interface ImportIssue {
@@ -125,6 +145,8 @@ export interface ImportReport {
   importedCount: number;
   updatedCount: number;
   skippedCount: number;
+  /** Encoding the file was decoded from, e.g. "windows-1250" */
+  encoding?: string;
   issues: ImportIssue[];
 }
 
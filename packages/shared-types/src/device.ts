@@ -56,3 +56,31 @@ export interface CreateDeviceRequest {
   /** Custom field values keyed by fieldId */
   customFields?: DeviceFieldValue[];
 }
+
+export interface TransferDeviceRequest {
+  deviceId: string;
+  toCustomerId: string;
+  /** Move upcoming/scheduled revisions with the device (default true) */
+  moveOpenRevisions?: boolean;
+  siteId?: string;
+  note?: string;
+}
+
+export interface DeviceTransfer {
+  id: string;
+  deviceId: string;
+  fromCustomerId: string | null;
+  toCustomerId: string | null;
+  movedRevisionIds: string[];
+  keptRevisionCount: number;
+  note: string | null;
+  transferredBy: string | null;
+  transferredAt: string;
+}
+
+export interface TransferDeviceResponse {
+  transfer: DeviceTransfer;
+  device: Device;
+  /** Moved revisions with an agreed appointment that must be arranged again */
+  rescheduleNeeded: string[];
+}
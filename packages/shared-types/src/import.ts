@@ -14,8 +14,22 @@ export type { ImportIssue, ImportIssueLevel, ImportIssueCode, ImportReport, Impo
 /**
  * Request to submit a customer import job
  */
+/**
+ * Application that produced the import file. Exports of Kominík SW and
+ * REVIZEX are mapped to our columns by the worker.
+ */
+export type SourceSystem = 'sazinka' | 'kominik_sw' | 'revizex';
+
+export const SOURCE_SYSTEMS: SourceSystem[] = ['sazinka', 'kominik_sw', 'revizex'];
+
 export interface CustomerImportJobRequest {
   csvContent: string;
+  /** Raw file bytes (base64); the worker detects the encoding and ignores csvContent */
+  csvBase64?: string;
+  /** File sent with uploadImportFile; replaces the inline content */
+  uploadId?: string;
+  /** Export format of the file, defaults to our own */
+  sourceSystem?: SourceSystem;
   filename: string;
 }
 
@@ -76,6 +90,12 @@ export interface GeocodeJobStatusUpdate {
 
 export interface DeviceImportJobRequest {
   csvContent: string;
+  /** Raw file bytes (base64); the worker detects the encoding and ignores csvContent */
+  csvBase64?: string;
+  /** File sent with uploadImportFile; replaces the inline content */
+  uploadId?: string;
+  /** Export format of the file, defaults to our own */
+  sourceSystem?: SourceSystem;
   filename: string;
 }
 
@@ -104,14 +124,28 @@ export interface DeviceImportJobSubmitResponse {
 
 export interface RevisionImportJobRequest {
   csvContent: string;
+  /** Raw file bytes (base64); the worker detects the encoding and ignores csvContent */
+  csvBase64?: string;
+  /** File sent with uploadImportFile; replaces the inline content */
+  uploadId?: string;
   filename: string;
+  /** Link completed revisions to visits on the same customer and date */
+  linkVisits?: boolean;
+  /** When linking, create a completed visit for revisions without one */
+  createMissingVisits?: boolean;
+}
+
+export interface RevisionLinkSummary {
+  linked: number;
+  visitsCreated: number;
+  unmatched: number;
 }
 
 export type RevisionImportJobStatus =
   | { type: 'queued'; position: number }
   | { type: 'parsing'; progress: number }
   | { type: 'importing'; processed: number; total: number; succeeded: number; failed: number }
-  | { type: 'completed'; total: number; succeeded: number; failed: number; report: ImportReport }
+  | { type: 'completed'; total: number; succeeded: number; failed: number; report: ImportReport; linking?: RevisionLinkSummary }
   | { type: 'failed'; error: string }
   | { type: 'cancelled'; processed: number; total: number };
 
@@ -132,6 +166,10 @@ export interface RevisionImportJobSubmitResponse {
 
 export interface CommunicationImportJobRequest {
   csvContent: string;
+  /** Raw file bytes (base64); the worker detects the encoding and ignores csvContent */
+  csvBase64?: string;
+  /** File sent with uploadImportFile; replaces the inline content */
+  uploadId?: string;
   filename: string;
 }
 
@@ -160,6 +198,10 @@ export interface CommunicationImportJobSubmitResponse {
 
 export interface WorkLogImportJobRequest {
   csvContent: string;
+  /** Raw file bytes (base64); the worker detects the encoding and ignores csvContent */
+  csvBase64?: string;
+  /** File sent with uploadImportFile; replaces the inline content */
+  uploadId?: string;
   filename: string;
 }
 
@@ -223,8 +265,10 @@ export interface ZipImportFileInfo {
 }
 
 export interface ZipImportJobRequest {
-  /** Base64 encoded ZIP content */
+  /** Base64 encoded ZIP content; empty when uploadId is set */
   zipContentBase64: string;
+  /** File sent with uploadImportFile; replaces the inline content */
+  uploadId?: string;
   filename: string;
 }
 
@@ -707,3 +751,17 @@ export const VISIT_RESULT_ALIASES: Record<string, string> = {
   'customer_absent': 'customer_absent',
   'rescheduled': 'rescheduled',
 };
+
+// Chunked upload of files too large for one NATS message
+
+export interface ImportUploadBeginResponse {
+  uploadId: string;
+  /** Largest chunk the worker accepts, in bytes before base64 */
+  maxChunkSize: number;
+}
+
+export interface ImportUploadCommitResponse {
+  uploadId: string;
+  size: number;
+  sha256: string;
+}
@@ -4,6 +4,8 @@ export interface Request<T> {
   id: string;
   timestamp: string;
   token?: string;   // JWT access token
+  /** Screenshot session: the worker pseudonymizes customer data */
+  demoMode?: boolean;
   payload: T;
 }
 
@@ -36,16 +38,31 @@ export interface ListResponse<T> {
   offset: number;
 }
 
+/** sessionStorage key of the demo mode flag (per browser tab) */
+export const DEMO_MODE_STORAGE_KEY = 'sazinka.demoMode';
+
+function isDemoMode(): boolean {
+  try {
+    return globalThis.sessionStorage?.getItem(DEMO_MODE_STORAGE_KEY) === '1';
+  } catch {
+    return false;
+  }
+}
+
 /**
  * Create a NATS request with JWT token authentication.
  * @param token - JWT access token
  * @param payload - The request payload
  */
 export function createRequest<T>(token: string | undefined, payload: T): Request<T> {
-  return {
+  const request: Request<T> = {
     id: crypto.randomUUID(),
     timestamp: new Date().toISOString(),
     token,
     payload,
   };
+  if (isDemoMode()) {
+    request.demoMode = true;
+  }
+  return request;
 }
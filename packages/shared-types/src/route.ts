@@ -53,10 +53,28 @@ export interface RouteStop {
   status: StopStatus;
   actualArrival?: string;
   actualDeparture?: string;
+  /** Dispatcher instructions for this stop */
+  notes?: string | null;
+  /** Checklist for the technician */
+  tasks?: RouteStopTask[];
 }
 
 export type StopStatus = 'pending' | 'arrived' | 'completed' | 'skipped';
 
+export interface RouteStopTask {
+  text: string;
+  done: boolean;
+}
+
+/** Payload of sazinka.route.stop.note.update (and route.update stopNotes) */
+export interface UpdateRouteStopNotesRequest {
+  stopId: string;
+  /** Omit to keep, empty string to clear */
+  notes?: string | null;
+  /** Omit to keep */
+  tasks?: RouteStopTask[];
+}
+
 export interface OptimizeRouteRequest {
   date: string;
   revisionIds: string[];
@@ -97,6 +115,16 @@ export interface RoutePlanRequest {
   date: string;
   /** Working hours (optional, defaults to 08:00-17:00) */
   workingHours?: WorkingHours;
+  /** Existing appointments kept at their time; customerIds are planned around them */
+  fixedStops?: FixedRouteStop[];
+}
+
+export interface FixedRouteStop {
+  customerId: string;
+  /** Agreed arrival time (HH:MM) */
+  arrivalTime: string;
+  /** Defaults to the planning service duration */
+  serviceDurationMinutes?: number;
 }
 
 export interface RoutePlanResponse {
@@ -160,3 +188,18 @@ export interface PlannedRouteStop {
   /** Manual override for travel duration from previous stop (replaces matrix value) */
   overrideTravelDurationMinutes?: number | null;
 }
+
+/** Edit lock of a route (sazinka.route.lock); saves by others fail with ROUTE_LOCKED */
+export interface RouteLock {
+  routeId: string;
+  holderId: string;
+  holderName: string | null;
+  acquiredAt: string;
+  expiresAt: string;
+}
+
+export interface RouteLockRequest {
+  routeId: string;
+  /** Lock lifetime in seconds (30-3600, default 300); renew before expiry */
+  ttlSeconds?: number;
+}
@@ -60,6 +60,8 @@ export interface BreakSettings {
   breakLatestTime: string;     // "HH:MM"
   breakMinKm: number;
   breakMaxKm: number;
+  /** Suggest restaurants and fuel stations near the route for breaks */
+  breakLocationSuggestions: boolean;
 }
 
 // Business/Personal info
@@ -164,6 +166,7 @@ export interface UpdateBreakSettingsRequest {
   breakLatestTime?: string;
   breakMinKm?: number;
   breakMaxKm?: number;
+  breakLocationSuggestions?: boolean;
 }
 
 export interface UpdatePreferencesRequest {
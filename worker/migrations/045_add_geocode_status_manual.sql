@@ -0,0 +1,6 @@
+-- Migration 045: Add 'manual' geocode status
+--
+-- Customers imported with coordinates taken from map pins (KML/KMZ) are
+-- marked 'manual' so they are never overwritten by batch geocoding.
+
+ALTER TYPE geocode_status_enum ADD VALUE IF NOT EXISTS 'manual';
@@ -0,0 +1,59 @@
+-- Migration 046: Generic REST connector for third-party CRM sync
+--
+-- A connector pushes customer/visit changes to an external REST API in
+-- batches and periodically pulls customer updates back. Every run is logged
+-- in crm_sync_log together with any field-level conflicts it detected.
+
+-- ============================================================
+-- SCHEMA: connector configuration
+-- ============================================================
+
+CREATE TABLE crm_connectors (
+    id                     UUID         PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id                UUID         NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name                   VARCHAR(100) NOT NULL,
+    base_url               TEXT         NOT NULL,
+    push_path              TEXT         NOT NULL DEFAULT '/sync',
+    pull_path              TEXT,                   -- NULL = push-only connector
+    auth_header_name       VARCHAR(100),           -- e.g. 'Authorization'
+    auth_header_value      TEXT,                   -- e.g. 'Bearer …'
+    field_mapping          JSONB        NOT NULL DEFAULT '{}'::jsonb,
+                                                   -- local field name → remote field name
+    batch_size             INTEGER      NOT NULL DEFAULT 50,
+    pull_interval_minutes  INTEGER      NOT NULL DEFAULT 60,
+    enabled                BOOLEAN      NOT NULL DEFAULT TRUE,
+    last_push_at           TIMESTAMPTZ,
+    last_pull_at           TIMESTAMPTZ,
+    created_at             TIMESTAMPTZ  NOT NULL DEFAULT NOW(),
+    updated_at             TIMESTAMPTZ  NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT crm_connectors_batch_size_range    CHECK (batch_size BETWEEN 1 AND 500),
+    CONSTRAINT crm_connectors_pull_interval_range CHECK (pull_interval_minutes BETWEEN 5 AND 10080)
+);
+
+CREATE INDEX idx_crm_connectors_user    ON crm_connectors(user_id);
+CREATE INDEX idx_crm_connectors_enabled ON crm_connectors(enabled) WHERE enabled = TRUE;
+
+-- ============================================================
+-- SCHEMA: sync run log
+-- ============================================================
+
+CREATE TABLE crm_sync_log (
+    id               UUID        PRIMARY KEY DEFAULT uuid_generate_v4(),
+    connector_id     UUID        NOT NULL REFERENCES crm_connectors(id) ON DELETE CASCADE,
+    user_id          UUID        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    direction        VARCHAR(10) NOT NULL,  -- 'push', 'pull'
+    status           VARCHAR(10) NOT NULL,  -- 'success', 'partial', 'failed'
+    items_total      INTEGER     NOT NULL DEFAULT 0,
+    items_succeeded  INTEGER     NOT NULL DEFAULT 0,
+    items_failed     INTEGER     NOT NULL DEFAULT 0,
+    conflicts        JSONB       NOT NULL DEFAULT '[]'::jsonb,
+    error            TEXT,
+    started_at       TIMESTAMPTZ NOT NULL,
+    finished_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT crm_sync_log_direction_valid CHECK (direction IN ('push', 'pull')),
+    CONSTRAINT crm_sync_log_status_valid    CHECK (status IN ('success', 'partial', 'failed'))
+);
+
+CREATE INDEX idx_crm_sync_log_connector ON crm_sync_log(connector_id, started_at DESC);
@@ -0,0 +1,16 @@
+-- Migration 047: Billing fields on visit work items
+--
+-- Work items become the billable unit: a net amount in minor currency units
+-- (haléře), the VAT rate applied and how the customer paid. All columns are
+-- optional — unbilled work stays NULL and is left out of VAT summaries.
+
+ALTER TABLE visit_work_items
+    ADD COLUMN amount_minor    BIGINT,
+    ADD COLUMN vat_rate        INTEGER,
+    ADD COLUMN payment_method  VARCHAR(20);
+
+ALTER TABLE visit_work_items
+    ADD CONSTRAINT visit_work_items_amount_non_negative CHECK (amount_minor IS NULL OR amount_minor >= 0),
+    ADD CONSTRAINT visit_work_items_vat_rate_range      CHECK (vat_rate IS NULL OR vat_rate BETWEEN 0 AND 100),
+    ADD CONSTRAINT visit_work_items_payment_method_valid
+        CHECK (payment_method IS NULL OR payment_method IN ('cash', 'card', 'transfer', 'other'));
@@ -0,0 +1,26 @@
+-- Migration 048: Postgres backup of queued JetStream jobs
+--
+-- JetStream is the only place queued jobs live; purging a stream silently
+-- drops pending imports. Each submitted job is mirrored here together with
+-- its stream sequence so startup reconciliation can spot jobs whose message
+-- disappeared before a worker picked it up. Payloads are kept only when they
+-- are small enough to be resubmitted; larger uploads keep metadata only.
+
+CREATE TABLE job_backups (
+    id              UUID PRIMARY KEY,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    job_type        VARCHAR(50) NOT NULL,
+    stream          VARCHAR(100) NOT NULL,
+    subject         VARCHAR(200) NOT NULL,
+    stream_sequence BIGINT NOT NULL,
+    filename        TEXT,
+    payload_bytes   INTEGER NOT NULL,
+    payload         BYTEA,
+    status          VARCHAR(20) NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'started', 'lost')),
+    submitted_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_job_backups_status ON job_backups(status);
+CREATE INDEX idx_job_backups_user_status ON job_backups(user_id, status, submitted_at DESC);
@@ -0,0 +1,26 @@
+-- Migration 049: Admin user management
+--
+-- Lets operators disable accounts, force password resets and change roles
+-- over NATS instead of editing the database. Every admin action is written
+-- to admin_audit_log.
+
+ALTER TABLE users
+    ADD COLUMN disabled_at               TIMESTAMPTZ,
+    ADD COLUMN last_login_at             TIMESTAMPTZ,
+    ADD COLUMN password_reset_token_hash TEXT,
+    ADD COLUMN password_reset_expires    TIMESTAMPTZ;
+
+CREATE INDEX idx_users_password_reset_token ON users(password_reset_token_hash)
+    WHERE password_reset_token_hash IS NOT NULL;
+
+CREATE TABLE admin_audit_log (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    admin_id        UUID REFERENCES users(id) ON DELETE SET NULL,
+    action          VARCHAR(50) NOT NULL,
+    target_user_id  UUID REFERENCES users(id) ON DELETE SET NULL,
+    details         JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_admin_audit_log_created ON admin_audit_log(created_at DESC);
+CREATE INDEX idx_admin_audit_log_target ON admin_audit_log(target_user_id, created_at DESC);
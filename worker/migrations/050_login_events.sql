@@ -0,0 +1,18 @@
+-- Migration 050: Login history
+--
+-- One row per login attempt against an existing account. Backs the
+-- sazinka.auth.logins.list endpoint and new-device detection, which
+-- compares the user agent against earlier successful logins.
+
+CREATE TABLE login_events (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    success         BOOLEAN NOT NULL,
+    failure_reason  VARCHAR(32),
+    ip_address      VARCHAR(64),
+    user_agent      TEXT,
+    new_device      BOOLEAN NOT NULL DEFAULT false,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_login_events_user ON login_events(user_id, created_at DESC);
@@ -0,0 +1,18 @@
+-- Migration 051: Account plans and usage counters
+--
+-- Plan limits live in code (types/quota.rs). Existing accounts start on the
+-- 'unlimited' plan so nothing changes until an admin assigns a tier.
+-- Monthly metrics (route plans, SMS) are counted in usage_counters; the
+-- customer limit is checked against the live customer count.
+
+ALTER TABLE users
+    ADD COLUMN plan VARCHAR(20) NOT NULL DEFAULT 'unlimited';
+
+CREATE TABLE usage_counters (
+    user_id       UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    metric        VARCHAR(32) NOT NULL,
+    period_start  DATE NOT NULL,
+    used          BIGINT NOT NULL DEFAULT 0,
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, metric, period_start)
+);
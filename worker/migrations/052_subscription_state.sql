@@ -0,0 +1,15 @@
+-- Migration 052: Subscription state of accounts
+--
+-- Driven by an external billing system through the admin subscription
+-- handlers. Past-due and cancelled accounts are read-only. Existing accounts
+-- start as 'active' so nothing changes until billing reports otherwise.
+
+ALTER TABLE users
+    ADD COLUMN subscription_status VARCHAR(20) NOT NULL DEFAULT 'active'
+        CHECK (subscription_status IN ('trial', 'active', 'past_due', 'cancelled')),
+    ADD COLUMN subscription_changed_at TIMESTAMPTZ,
+    -- Customer id in the billing system (e.g. Stripe cus_...)
+    ADD COLUMN billing_customer_ref TEXT;
+
+CREATE UNIQUE INDEX idx_users_billing_customer_ref
+    ON users(billing_customer_ref) WHERE billing_customer_ref IS NOT NULL;
@@ -0,0 +1,44 @@
+-- Migration 053: Customer reschedule requests
+--
+-- Dispatchers share a tokenized link for an upcoming visit; the end customer
+-- uses it to ask for a different date. The request keeps the slots the slot
+-- engine suggested at the time and the dispatcher's accept/decline outcome.
+
+CREATE TABLE visit_reschedule_links (
+    token_hash  TEXT PRIMARY KEY,
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    visit_id    UUID NOT NULL REFERENCES visits(id) ON DELETE CASCADE,
+    expires_at  TIMESTAMPTZ NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_visit_reschedule_links_visit ON visit_reschedule_links(visit_id);
+
+CREATE TABLE reschedule_requests (
+    id                    UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id               UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    visit_id              UUID NOT NULL REFERENCES visits(id) ON DELETE CASCADE,
+    customer_id           UUID NOT NULL REFERENCES customers(id) ON DELETE CASCADE,
+    original_date         DATE NOT NULL,
+    original_time_start   TIME,
+    original_time_end     TIME,
+    requested_date        DATE NOT NULL,
+    preferred_time_start  TIME,
+    preferred_time_end    TIME,
+    customer_note         TEXT,
+    suggested_slots       JSONB NOT NULL DEFAULT '[]',
+    status                VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'accepted', 'declined')),
+    decided_by            UUID REFERENCES users(id) ON DELETE SET NULL,
+    decided_at            TIMESTAMPTZ,
+    decision_note         TEXT,
+    new_date              DATE,
+    new_time_start        TIME,
+    new_time_end          TIME,
+    created_at            TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One open request per visit
+CREATE UNIQUE INDEX idx_reschedule_requests_pending
+    ON reschedule_requests(visit_id) WHERE status = 'pending';
+CREATE INDEX idx_reschedule_requests_user ON reschedule_requests(user_id, status, created_at DESC);
@@ -0,0 +1,34 @@
+-- Migration 054: Service sites and structured device locations
+--
+-- A customer may have several service addresses (sites) besides the billing
+-- address on the customer row. Devices point at a site and carry a location
+-- within it (building, floor, room) plus an optional GPS override.
+-- Route planning uses device GPS, then site coordinates, then the customer.
+
+CREATE TABLE customer_sites (
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    customer_id  UUID NOT NULL REFERENCES customers(id) ON DELETE CASCADE,
+    name         VARCHAR(255) NOT NULL,
+    street       VARCHAR(255),
+    city         VARCHAR(255),
+    postal_code  VARCHAR(20),
+    country      VARCHAR(2),
+    lat          DOUBLE PRECISION,
+    lng          DOUBLE PRECISION,
+    notes        TEXT,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_customer_sites_customer ON customer_sites(customer_id);
+
+ALTER TABLE devices
+    ADD COLUMN site_id UUID REFERENCES customer_sites(id) ON DELETE SET NULL,
+    ADD COLUMN building VARCHAR(100),
+    ADD COLUMN floor VARCHAR(50),
+    ADD COLUMN room VARCHAR(100),
+    ADD COLUMN location_lat DOUBLE PRECISION,
+    ADD COLUMN location_lng DOUBLE PRECISION;
+
+CREATE INDEX idx_devices_site ON devices(site_id) WHERE site_id IS NOT NULL;
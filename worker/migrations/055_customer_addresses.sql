@@ -0,0 +1,74 @@
+-- Migration 055: Typed customer addresses
+--
+-- Sites become the customer's address book: each address is a billing,
+-- service or mailing address, at most one of each type is primary, and
+-- addresses are geocoded on their own.
+--
+-- The single-address columns on customers are migrated into a primary
+-- billing address. Imports, CRM sync and the customer form still write
+-- those columns, so a trigger keeps the primary billing address in step.
+-- Planning prefers the device, its site, then the primary service address.
+
+ALTER TABLE customer_sites
+    ADD COLUMN address_type VARCHAR(20) NOT NULL DEFAULT 'service'
+        CHECK (address_type IN ('billing', 'service', 'mailing')),
+    ADD COLUMN is_primary BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN geocode_status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (geocode_status IN ('pending', 'success', 'failed', 'manual'));
+
+UPDATE customer_sites SET geocode_status = 'success'
+WHERE lat IS NOT NULL AND lng IS NOT NULL;
+
+CREATE UNIQUE INDEX idx_customer_sites_primary
+    ON customer_sites(customer_id, address_type) WHERE is_primary;
+
+CREATE INDEX idx_customer_sites_geocode
+    ON customer_sites(user_id, geocode_status) WHERE geocode_status = 'pending';
+
+CREATE OR REPLACE FUNCTION sync_customer_billing_address()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.street IS NULL AND NEW.city IS NULL AND NEW.postal_code IS NULL THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO customer_sites (
+        user_id, customer_id, name, street, city, postal_code, country,
+        lat, lng, address_type, is_primary, geocode_status
+    )
+    VALUES (
+        NEW.user_id, NEW.id, 'Billing address', NEW.street, NEW.city, NEW.postal_code,
+        TRIM(NEW.country), NEW.lat, NEW.lng, 'billing', TRUE,
+        COALESCE(NEW.geocode_status::text, 'pending')
+    )
+    ON CONFLICT (customer_id, address_type) WHERE is_primary DO UPDATE SET
+        street = EXCLUDED.street,
+        city = EXCLUDED.city,
+        postal_code = EXCLUDED.postal_code,
+        country = EXCLUDED.country,
+        lat = EXCLUDED.lat,
+        lng = EXCLUDED.lng,
+        geocode_status = EXCLUDED.geocode_status,
+        updated_at = NOW();
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_customers_billing_address
+    AFTER INSERT OR UPDATE OF street, city, postal_code, country, lat, lng, geocode_status
+    ON customers
+    FOR EACH ROW EXECUTE FUNCTION sync_customer_billing_address();
+
+-- Backfill from the existing single-address columns
+INSERT INTO customer_sites (
+    user_id, customer_id, name, street, city, postal_code, country,
+    lat, lng, address_type, is_primary, geocode_status
+)
+SELECT
+    c.user_id, c.id, 'Billing address', c.street, c.city, c.postal_code,
+    TRIM(c.country), c.lat, c.lng, 'billing', TRUE,
+    COALESCE(c.geocode_status::text, 'pending')
+FROM customers c
+WHERE c.street IS NOT NULL OR c.city IS NOT NULL OR c.postal_code IS NOT NULL
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,13 @@
+-- Migration 056: Customer hierarchy
+--
+-- A customer may be a branch of a parent company (one level: HQ and its
+-- branches). Revisions and reports can be aggregated over the group and
+-- accounting exports can invoice branch work to the parent.
+
+ALTER TABLE customers
+    ADD COLUMN parent_customer_id UUID REFERENCES customers(id) ON DELETE SET NULL,
+    ADD CONSTRAINT chk_customers_parent_not_self
+        CHECK (parent_customer_id IS NULL OR parent_customer_id <> id);
+
+CREATE INDEX idx_customers_parent ON customers(parent_customer_id)
+    WHERE parent_customer_id IS NOT NULL;
@@ -0,0 +1,55 @@
+-- Migration 057: Revision document numbering series
+--
+-- Inspection reports carry sequential legal numbers (e.g. 2025/0001).
+-- The series is configured in settings; numbers are allocated atomically
+-- when a revision is completed, recorded in a register and never reused.
+-- A deleted revision voids its number instead of leaving a silent gap.
+
+ALTER TABLE users
+    ADD COLUMN revision_numbering_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN revision_number_prefix VARCHAR(20) NOT NULL DEFAULT '',
+    ADD COLUMN revision_number_padding SMALLINT NOT NULL DEFAULT 4
+        CHECK (revision_number_padding BETWEEN 1 AND 10),
+    ADD COLUMN revision_number_yearly_reset BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- Last issued sequence per series period (period_year 0 = never resets)
+CREATE TABLE revision_number_counters (
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    period_year  INTEGER NOT NULL,
+    last_number  INTEGER NOT NULL,
+    PRIMARY KEY (user_id, period_year)
+);
+
+-- Register of every issued number
+CREATE TABLE revision_document_numbers (
+    id               UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id          UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    period_year      INTEGER NOT NULL,
+    sequence         INTEGER NOT NULL,
+    document_number  VARCHAR(50) NOT NULL,
+    revision_id      UUID REFERENCES revisions(id) ON DELETE SET NULL,
+    issued_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    voided_at        TIMESTAMPTZ,
+    void_reason      TEXT,
+    UNIQUE (user_id, period_year, sequence),
+    UNIQUE (user_id, document_number)
+);
+
+ALTER TABLE revisions ADD COLUMN document_number VARCHAR(50);
+
+CREATE UNIQUE INDEX idx_revisions_document_number
+    ON revisions(user_id, document_number) WHERE document_number IS NOT NULL;
+
+CREATE OR REPLACE FUNCTION void_revision_document_number()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE revision_document_numbers
+    SET voided_at = NOW(), void_reason = 'revision deleted'
+    WHERE revision_id = OLD.id AND voided_at IS NULL;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_revisions_void_document_number
+    BEFORE DELETE ON revisions
+    FOR EACH ROW EXECUTE FUNCTION void_revision_document_number();
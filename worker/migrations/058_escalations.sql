@@ -0,0 +1,53 @@
+-- Migration 058: Deadline escalations and notification center
+--
+-- Overdue revisions and missed visits used to sit silently in the lists.
+-- Escalation rules (per account) raise them into the notification center,
+-- optionally by e-mail. Each rule escalates an item once; the escalation
+-- log is both the deduplication key and the audit trail.
+
+-- In-app notifications of an account (owner and its workers)
+CREATE TABLE notifications (
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind         VARCHAR(50) NOT NULL,
+    title        TEXT NOT NULL,
+    body         TEXT,
+    entity_type  VARCHAR(20),
+    entity_id    UUID,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    read_at      TIMESTAMPTZ
+);
+
+CREATE INDEX idx_notifications_user ON notifications(user_id, created_at DESC);
+CREATE INDEX idx_notifications_unread ON notifications(user_id) WHERE read_at IS NULL;
+
+CREATE TABLE escalation_rules (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    rule_type       VARCHAR(30) NOT NULL
+        CHECK (rule_type IN ('revision_overdue', 'visit_missed')),
+    -- Days past the due / scheduled date before the item escalates
+    threshold_days  INTEGER NOT NULL DEFAULT 0
+        CHECK (threshold_days BETWEEN 0 AND 365),
+    notify_email    BOOLEAN NOT NULL DEFAULT FALSE,
+    enabled         BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, rule_type, threshold_days)
+);
+
+CREATE TABLE escalation_log (
+    id               UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id          UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    rule_id          UUID NOT NULL REFERENCES escalation_rules(id) ON DELETE CASCADE,
+    entity_type      VARCHAR(20) NOT NULL,
+    entity_id        UUID NOT NULL,
+    customer_id      UUID REFERENCES customers(id) ON DELETE SET NULL,
+    notification_id  UUID REFERENCES notifications(id) ON DELETE SET NULL,
+    emailed_at       TIMESTAMPTZ,
+    email_error      TEXT,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (rule_id, entity_id)
+);
+
+CREATE INDEX idx_escalation_log_user ON escalation_log(user_id, created_at DESC);
@@ -0,0 +1,24 @@
+-- Migration 059: Multi-language customer-facing content
+--
+-- Customers get an optional communication language (NULL = account default).
+-- The account keeps one version of each customer template per language;
+-- sending picks the customer's language and falls back to the account's
+-- own template when no version exists for it.
+
+ALTER TABLE customers ADD COLUMN language VARCHAR(10);
+
+CREATE TABLE customer_template_translations (
+    id             UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id        UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    template_kind  VARCHAR(30) NOT NULL
+        CHECK (template_kind IN (
+            'email_confirmation', 'email_reminder', 'sms_confirmation', 'sms_reminder'
+        )),
+    language       VARCHAR(10) NOT NULL,
+    -- E-mail subject; NULL for SMS or to keep the built-in subject
+    subject        TEXT,
+    body           TEXT NOT NULL,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, template_kind, language)
+);
@@ -0,0 +1,17 @@
+-- Migration 060: Currency of accounts and billed work items
+--
+-- Amounts were implicitly CZK. Each account now has a currency used for new
+-- billed work, and every billed work item stores the currency of its amount.
+-- Existing amounts are taken as CZK.
+
+ALTER TABLE users
+    ADD COLUMN currency VARCHAR(3) NOT NULL DEFAULT 'CZK'
+        CHECK (currency ~ '^[A-Z]{3}$');
+
+ALTER TABLE visit_work_items ADD COLUMN currency VARCHAR(3);
+
+UPDATE visit_work_items SET currency = 'CZK' WHERE amount_minor IS NOT NULL;
+
+ALTER TABLE visit_work_items
+    ADD CONSTRAINT visit_work_items_currency_valid CHECK (currency IS NULL OR currency ~ '^[A-Z]{3}$'),
+    ADD CONSTRAINT visit_work_items_amount_has_currency CHECK (amount_minor IS NULL OR currency IS NOT NULL);
@@ -0,0 +1,33 @@
+-- Migration 061: Service coverage by postal code
+--
+-- The account lists the postal codes (or postal code prefixes) it serves.
+-- Depending on coverage_mode, customers outside the list are accepted
+-- silently ('off'), accepted with a warning ('warn') or refused ('reject').
+-- Every out-of-coverage address is logged so demand from areas the
+-- company does not serve yet can be reported.
+
+ALTER TABLE users ADD COLUMN coverage_mode VARCHAR(10) NOT NULL DEFAULT 'off'
+    CHECK (coverage_mode IN ('off', 'warn', 'reject'));
+
+CREATE TABLE service_coverage_areas (
+    id          UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Postal code or prefix without spaces ("602" covers 602 00 … 602 99)
+    pattern     VARCHAR(10) NOT NULL,
+    label       VARCHAR(255),
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, pattern)
+);
+
+CREATE TABLE coverage_misses (
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    postal_code  VARCHAR(10) NOT NULL,
+    city         VARCHAR(255),
+    -- 'create' (single customer) or 'import'
+    source       VARCHAR(10) NOT NULL CHECK (source IN ('create', 'import')),
+    rejected     BOOLEAN NOT NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_coverage_misses_user ON coverage_misses(user_id, created_at);
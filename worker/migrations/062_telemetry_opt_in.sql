@@ -0,0 +1,8 @@
+-- Migration 062: Anonymous telemetry opt-in
+--
+-- Accounts may opt in to sending anonymized error signatures and aggregate
+-- usage counts to the telemetry endpoint configured for the deployment
+-- (TELEMETRY_ENDPOINT). Nothing is collected for accounts that have not
+-- opted in.
+
+ALTER TABLE users ADD COLUMN telemetry_opt_in BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,12 @@
+-- Migration 063: 'review' geocode status
+--
+-- Geocoding is restricted to the account's country. Results that still
+-- land outside the country's bounds (a street of the same name across the
+-- border) keep their coordinates but are marked 'review' until a user
+-- confirms or moves the pin.
+
+ALTER TYPE geocode_status_enum ADD VALUE IF NOT EXISTS 'review';
+
+ALTER TABLE customer_sites DROP CONSTRAINT IF EXISTS customer_sites_geocode_status_check;
+ALTER TABLE customer_sites ADD CONSTRAINT customer_sites_geocode_status_check
+    CHECK (geocode_status IN ('pending', 'success', 'failed', 'manual', 'review'));
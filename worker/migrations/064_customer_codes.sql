@@ -0,0 +1,43 @@
+-- Migration 064: Customer codes
+--
+-- Every customer gets a human-friendly code (e.g. CUS-2025-0001) generated
+-- from a per-account pattern when it is created or imported. Codes are
+-- unique per account and let imports reference customers without relying
+-- on exact name matches. An empty pattern turns generation off.
+
+ALTER TABLE users
+    ADD COLUMN customer_code_pattern VARCHAR(40) NOT NULL DEFAULT 'CUS-{YYYY}-{SEQ:4}';
+
+-- Last issued sequence per period (period_year 0 = pattern without a year)
+CREATE TABLE customer_code_counters (
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    period_year  INTEGER NOT NULL,
+    last_number  INTEGER NOT NULL,
+    PRIMARY KEY (user_id, period_year)
+);
+
+ALTER TABLE customers ADD COLUMN customer_code VARCHAR(50);
+
+CREATE UNIQUE INDEX idx_customers_customer_code
+    ON customers(user_id, customer_code) WHERE customer_code IS NOT NULL;
+
+-- Backfill existing customers with the default pattern, numbered by creation
+WITH numbered AS (
+    SELECT
+        id,
+        EXTRACT(YEAR FROM created_at)::int AS period_year,
+        ROW_NUMBER() OVER (
+            PARTITION BY user_id, EXTRACT(YEAR FROM created_at)
+            ORDER BY created_at, id
+        ) AS seq
+    FROM customers
+)
+UPDATE customers c
+SET customer_code = 'CUS-' || n.period_year || '-' || LPAD(n.seq::text, 4, '0')
+FROM numbered n
+WHERE c.id = n.id;
+
+INSERT INTO customer_code_counters (user_id, period_year, last_number)
+SELECT user_id, EXTRACT(YEAR FROM created_at)::int, COUNT(*)
+FROM customers
+GROUP BY user_id, EXTRACT(YEAR FROM created_at);
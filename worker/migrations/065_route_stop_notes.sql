@@ -0,0 +1,12 @@
+-- Migration 065: Route stop notes and task checklists
+--
+-- Dispatchers attach instructions ("bring 2 m flue liner") and a short
+-- checklist to a single stop. Tasks are stored as a JSON array of
+-- {"text": ..., "done": ...} objects.
+
+ALTER TABLE route_stops
+    ADD COLUMN IF NOT EXISTS notes TEXT,
+    ADD COLUMN IF NOT EXISTS tasks JSONB NOT NULL DEFAULT '[]'::jsonb;
+
+ALTER TABLE route_stops
+    ADD CONSTRAINT route_stops_notes_max_length CHECK (notes IS NULL OR length(notes) <= 2000);
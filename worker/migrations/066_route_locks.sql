@@ -0,0 +1,16 @@
+-- Migration 066: Route edit locks
+--
+-- A dispatcher opening a route for editing takes a short-lived lock so a
+-- second dispatcher cannot save over their stop order. Locks expire on
+-- their own when the holder's browser goes away; holders renew them while
+-- the edit session is open.
+
+CREATE TABLE IF NOT EXISTS route_locks (
+    route_id    UUID PRIMARY KEY REFERENCES routes(id) ON DELETE CASCADE,
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    holder_id   UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    acquired_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at  TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_route_locks_holder ON route_locks(holder_id);
@@ -0,0 +1,10 @@
+-- Migration 067: Travel time correction factor
+--
+-- Comparing planned leg durations with the actual arrival/departure times of
+-- past routes shows systematic routing bias (e.g. congested town centres).
+-- A user may store the measured factor; route planning then scales the
+-- routing engine's travel times by it. NULL = no correction.
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS travel_time_factor DOUBLE PRECISION
+        CHECK (travel_time_factor IS NULL OR travel_time_factor BETWEEN 0.5 AND 2.0);
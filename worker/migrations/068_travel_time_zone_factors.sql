@@ -0,0 +1,20 @@
+-- Migration 068: Learned travel time factors per zone and time of day
+--
+-- Routing bias is not uniform: town centres at rush hour are much slower
+-- than the routing engine expects, open roads at midday are not. Factors
+-- are learned from actual-vs-planned leg times per grid cell (0.1°) and
+-- time band and applied on top of the routing durations. Rows exist only
+-- while the user has travel time correction enabled.
+
+CREATE TABLE IF NOT EXISTS travel_time_factors (
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    zone_lat    INTEGER NOT NULL,
+    zone_lng    INTEGER NOT NULL,
+    time_band   VARCHAR(20) NOT NULL
+        CHECK (time_band IN ('morning_peak', 'midday', 'afternoon_peak', 'off_peak')),
+    factor      DOUBLE PRECISION NOT NULL CHECK (factor BETWEEN 0.5 AND 2.0),
+    raw_ratio   DOUBLE PRECISION NOT NULL,
+    legs        INTEGER NOT NULL,
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, zone_lat, zone_lng, time_band)
+);
@@ -0,0 +1,13 @@
+-- Migration 069: Break location suggestions
+--
+-- Breaks used to be placed at the previous stop's location. The planner can
+-- now suggest a restaurant or fuel station near the route for the break;
+-- the chosen place is stored on the break stop and printed on the route
+-- sheet. Users who do not want break places looked up can turn it off.
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS break_location_suggestions BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- {"name", "kind", "lat", "lng", "address"}; NULL = at the previous stop
+ALTER TABLE route_stops
+    ADD COLUMN IF NOT EXISTS break_location JSONB;
@@ -0,0 +1,51 @@
+-- Migration 070: Campaign triggers
+--
+-- Campaigns send an e-mail or SMS offer to customers matching a trigger,
+-- e.g. "heating-season checkup to customers whose last revision was more
+-- than 10 months ago". A scheduler evaluates each enabled campaign once a
+-- day (only in its active months) and queues the messages through the
+-- e-mail / SMS job pipelines. The send log is both the deduplication key
+-- and the source of the per-campaign statistics.
+
+CREATE TABLE campaigns (
+    id                     UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id                UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name                   VARCHAR(200) NOT NULL,
+    trigger_type           VARCHAR(30) NOT NULL
+        CHECK (trigger_type IN ('last_revision_age', 'customer_anniversary')),
+    -- last_revision_age: months since the last completed revision
+    months_since_revision  INTEGER CHECK (months_since_revision BETWEEN 1 AND 120),
+    -- Months (1-12) the campaign runs in; a range may wrap the year end
+    active_from_month      INTEGER CHECK (active_from_month BETWEEN 1 AND 12),
+    active_to_month        INTEGER CHECK (active_to_month BETWEEN 1 AND 12),
+    channel                VARCHAR(10) NOT NULL CHECK (channel IN ('email', 'sms')),
+    subject                TEXT,
+    body                   TEXT NOT NULL,
+    -- Exclusion rules
+    exclude_scheduled      BOOLEAN NOT NULL DEFAULT TRUE,
+    exclude_contacted_days INTEGER CHECK (exclude_contacted_days BETWEEN 1 AND 365),
+    -- Days before a customer may receive the campaign again; NULL = once
+    repeat_after_days      INTEGER CHECK (repeat_after_days BETWEEN 1 AND 3650),
+    enabled                BOOLEAN NOT NULL DEFAULT TRUE,
+    last_run_on            DATE,
+    created_at             TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at             TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_campaigns_user ON campaigns(user_id);
+
+CREATE TABLE campaign_sends (
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    campaign_id  UUID NOT NULL REFERENCES campaigns(id) ON DELETE CASCADE,
+    customer_id  UUID REFERENCES customers(id) ON DELETE SET NULL,
+    channel      VARCHAR(10) NOT NULL,
+    recipient    TEXT NOT NULL,
+    -- E-mail / SMS job the message was queued as
+    job_id       UUID,
+    error        TEXT,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_campaign_sends_campaign ON campaign_sends(campaign_id, customer_id, created_at DESC);
+CREATE INDEX idx_campaign_sends_user ON campaign_sends(user_id, created_at DESC);
@@ -0,0 +1,13 @@
+-- Migration 071: Customer acquisition source
+--
+-- Where a customer came from (web form, phone, referral, ...) so owners can
+-- see which marketing brings jobs and revenue. Referrals may link the
+-- customer who recommended them. Existing customers stay unknown (NULL).
+
+ALTER TABLE customers
+    ADD COLUMN acquisition_source VARCHAR(20)
+        CHECK (acquisition_source IN ('web_form', 'phone', 'email', 'referral', 'campaign', 'other')),
+    ADD COLUMN referrer_customer_id UUID REFERENCES customers(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_customers_acquisition_source ON customers(user_id, acquisition_source);
+CREATE INDEX idx_customers_referrer ON customers(referrer_customer_id) WHERE referrer_customer_id IS NOT NULL;
@@ -0,0 +1,23 @@
+-- Migration 072: Web lead intake
+--
+-- Website contact/booking forms post to a public intake endpoint with the
+-- account's intake key instead of e-mailing the office. A submission that
+-- passes the spam checks (honeypot, rate limit, optional captcha) becomes a
+-- customer in the "new" lead state and the dispatcher is notified.
+
+CREATE TABLE lead_intake_settings (
+    user_id          UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    -- Public key embedded in the website form; rotating it cuts off old forms
+    intake_key       VARCHAR(64) NOT NULL UNIQUE,
+    enabled          BOOLEAN NOT NULL DEFAULT TRUE,
+    captcha_provider VARCHAR(20) CHECK (captcha_provider IN ('turnstile', 'hcaptcha', 'recaptcha')),
+    captcha_secret   TEXT,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Lead state of customers that came in as leads; NULL for regular customers
+ALTER TABLE customers
+    ADD COLUMN lead_status VARCHAR(20) CONSTRAINT customers_lead_status_check CHECK (lead_status IN ('new'));
+
+CREATE INDEX idx_customers_lead_status ON customers(user_id, lead_status) WHERE lead_status IS NOT NULL;
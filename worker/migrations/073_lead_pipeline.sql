@@ -0,0 +1,36 @@
+-- Migration 073: Lead pipeline
+--
+-- Leads move new → contacted → quoted → won, or to lost (with a reason) at
+-- any open stage. Every transition is logged so the conversion funnel can
+-- be counted per period and acquisition source, also for leads that later
+-- moved on or were lost.
+
+ALTER TABLE customers DROP CONSTRAINT IF EXISTS customers_lead_status_check;
+ALTER TABLE customers ADD CONSTRAINT customers_lead_status_check
+    CHECK (lead_status IN ('new', 'contacted', 'quoted', 'won', 'lost'));
+
+ALTER TABLE customers
+    ADD COLUMN lead_lost_reason TEXT,
+    ADD COLUMN lead_status_changed_at TIMESTAMPTZ;
+
+UPDATE customers SET lead_status_changed_at = created_at WHERE lead_status IS NOT NULL;
+
+CREATE TABLE lead_status_changes (
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    customer_id  UUID NOT NULL REFERENCES customers(id) ON DELETE CASCADE,
+    -- NULL when a customer became a lead
+    from_status  VARCHAR(20),
+    to_status    VARCHAR(20) NOT NULL,
+    reason       TEXT,
+    changed_by   UUID REFERENCES users(id) ON DELETE SET NULL,
+    changed_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_lead_status_changes_customer ON lead_status_changes(customer_id, changed_at);
+
+-- Leads created by the web intake before the log existed
+INSERT INTO lead_status_changes (user_id, customer_id, from_status, to_status, changed_at)
+SELECT user_id, id, NULL, lead_status, created_at
+FROM customers
+WHERE lead_status IS NOT NULL;
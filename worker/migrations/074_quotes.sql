@@ -0,0 +1,75 @@
+-- Migration 074: Quotes
+--
+-- Quotes (offers) list priced items, usually picked from the account's
+-- service catalog, and are valid until a date. Sending a quote creates a
+-- tokenized link the customer opens without logging in to accept or
+-- decline it. An accepted quote becomes a work order: an open planned
+-- action with the quoted items, waiting in the dispatcher's inbox.
+-- Quote numbers (e.g. N2026-0001) are allocated per year and never reused.
+
+CREATE TABLE catalog_items (
+    id                UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id           UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name              VARCHAR(200) NOT NULL,
+    description       TEXT,
+    -- Unit of quantity shown on quotes ("ks", "h", "km")
+    unit              VARCHAR(20) NOT NULL DEFAULT 'ks',
+    unit_price_minor  BIGINT NOT NULL CHECK (unit_price_minor >= 0),
+    vat_rate          INTEGER NOT NULL CHECK (vat_rate BETWEEN 0 AND 100),
+    currency          VARCHAR(3) NOT NULL CHECK (currency ~ '^[A-Z]{3}$'),
+    -- Work type of the work order line the item becomes
+    work_type         work_type,
+    is_active         BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_catalog_items_user ON catalog_items(user_id, is_active);
+
+CREATE TABLE quote_number_counters (
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    period_year  INTEGER NOT NULL,
+    last_number  INTEGER NOT NULL,
+    PRIMARY KEY (user_id, period_year)
+);
+
+CREATE TABLE quotes (
+    id                  UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id             UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    customer_id         UUID NOT NULL REFERENCES customers(id) ON DELETE CASCADE,
+    quote_number        VARCHAR(30) NOT NULL,
+    title               VARCHAR(200) NOT NULL,
+    note                TEXT,
+    currency            VARCHAR(3) NOT NULL CHECK (currency ~ '^[A-Z]{3}$'),
+    valid_until         DATE NOT NULL,
+    status              VARCHAR(20) NOT NULL DEFAULT 'draft'
+        CHECK (status IN ('draft', 'sent', 'accepted', 'declined', 'expired')),
+    -- SHA-256 of the acceptance link token; set when the quote is sent
+    accept_token_hash   VARCHAR(64) UNIQUE,
+    sent_at             TIMESTAMPTZ,
+    decided_at          TIMESTAMPTZ,
+    decline_reason      TEXT,
+    -- Work order created on acceptance
+    planned_action_id   UUID REFERENCES planned_actions(id) ON DELETE SET NULL,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, quote_number)
+);
+
+CREATE INDEX idx_quotes_user_status ON quotes(user_id, status);
+CREATE INDEX idx_quotes_customer ON quotes(customer_id);
+
+CREATE TABLE quote_items (
+    id                UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    quote_id          UUID NOT NULL REFERENCES quotes(id) ON DELETE CASCADE,
+    position          INTEGER NOT NULL,
+    catalog_item_id   UUID REFERENCES catalog_items(id) ON DELETE SET NULL,
+    name              VARCHAR(200) NOT NULL,
+    unit              VARCHAR(20) NOT NULL,
+    quantity          DOUBLE PRECISION NOT NULL CHECK (quantity > 0),
+    unit_price_minor  BIGINT NOT NULL CHECK (unit_price_minor >= 0),
+    vat_rate          INTEGER NOT NULL CHECK (vat_rate BETWEEN 0 AND 100),
+    work_type         work_type
+);
+
+CREATE INDEX idx_quote_items_quote ON quote_items(quote_id, position);
@@ -0,0 +1,56 @@
+-- Migration 075: Spare parts inventory
+--
+-- Parts are catalog items kept in stock at depots (warehouses) or on crew
+-- vehicles. Materials recorded on a work item are taken from the stock of
+-- the crew doing the work once the work item is completed. Stock may go
+-- negative: the part was used, it just wasn't booked in. Every change is a
+-- movement, so levels can be audited and consumption reported.
+
+CREATE TABLE stock_levels (
+    id                UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id           UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    catalog_item_id   UUID NOT NULL REFERENCES catalog_items(id) ON DELETE CASCADE,
+    -- Exactly one location: a crew vehicle or a depot
+    crew_id           UUID REFERENCES crews(id) ON DELETE CASCADE,
+    depot_id          UUID REFERENCES depots(id) ON DELETE CASCADE,
+    quantity          DOUBLE PRECISION NOT NULL DEFAULT 0,
+    -- Reorder point; 0 = only alert when the stock goes negative
+    min_quantity      DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK (min_quantity >= 0),
+    -- Set once a low-stock notification went out, cleared by restocking
+    low_alerted       BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((crew_id IS NULL) <> (depot_id IS NULL))
+);
+
+CREATE UNIQUE INDEX idx_stock_levels_crew ON stock_levels(catalog_item_id, crew_id) WHERE crew_id IS NOT NULL;
+CREATE UNIQUE INDEX idx_stock_levels_depot ON stock_levels(catalog_item_id, depot_id) WHERE depot_id IS NOT NULL;
+CREATE INDEX idx_stock_levels_user ON stock_levels(user_id);
+
+CREATE TABLE stock_movements (
+    id                UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id           UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    stock_level_id    UUID NOT NULL REFERENCES stock_levels(id) ON DELETE CASCADE,
+    -- Positive for stock in, negative for stock out
+    change            DOUBLE PRECISION NOT NULL,
+    reason            VARCHAR(20) NOT NULL
+        CHECK (reason IN ('restock', 'consumption', 'adjustment')),
+    work_item_id      UUID REFERENCES visit_work_items(id) ON DELETE SET NULL,
+    note              TEXT,
+    created_by        UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_stock_movements_level ON stock_movements(stock_level_id, created_at);
+
+-- Materials used on a work item; consumed_at is set once taken from stock
+CREATE TABLE work_item_materials (
+    id                UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    work_item_id      UUID NOT NULL REFERENCES visit_work_items(id) ON DELETE CASCADE,
+    catalog_item_id   UUID NOT NULL REFERENCES catalog_items(id) ON DELETE RESTRICT,
+    quantity          DOUBLE PRECISION NOT NULL CHECK (quantity > 0),
+    stock_level_id    UUID REFERENCES stock_levels(id) ON DELETE SET NULL,
+    consumed_at       TIMESTAMPTZ,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_work_item_materials_item ON work_item_materials(work_item_id);
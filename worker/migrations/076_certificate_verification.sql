@@ -0,0 +1,17 @@
+-- Migration 076: Revision certificate verification
+--
+-- Every issued report number gets a short random verification code printed
+-- on the report. Anyone holding the report can check the number and code
+-- on the public verification page, which shows only whether the
+-- certificate is valid, when it was issued and for what type of device.
+
+ALTER TABLE revision_document_numbers ADD COLUMN verification_code VARCHAR(16);
+
+UPDATE revision_document_numbers
+SET verification_code = upper(substr(md5(random()::text || id::text), 1, 8))
+WHERE verification_code IS NULL;
+
+ALTER TABLE revision_document_numbers ALTER COLUMN verification_code SET NOT NULL;
+
+-- Verification looks numbers up across accounts
+CREATE INDEX idx_revision_document_numbers_number ON revision_document_numbers(document_number);
@@ -0,0 +1,34 @@
+-- Migration 077: Document retention policies
+--
+-- Revision reports, visit records and customer communication must be kept
+-- for a minimum period. Each account may set the period per entity type
+-- (types without a row use the built-in defaults). Records still inside
+-- their period cannot be deleted; with delete_after_expiry a daily sweep
+-- removes them once the period has passed. Every sweep deletion is logged
+-- for the retention report handed to auditors.
+
+CREATE TABLE retention_policies (
+    user_id             UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    entity_type         VARCHAR(20) NOT NULL
+        CHECK (entity_type IN ('revision', 'visit', 'communication')),
+    retention_months    INTEGER NOT NULL CHECK (retention_months BETWEEN 1 AND 1200),
+    delete_after_expiry BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, entity_type)
+);
+
+CREATE TABLE retention_deletions (
+    id               UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id          UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    entity_type      VARCHAR(20) NOT NULL,
+    -- The record is gone; only its id and dates are kept
+    entity_id        UUID NOT NULL,
+    -- Completion / visit / contact date the period was counted from
+    reference_date   DATE NOT NULL,
+    retention_months INTEGER NOT NULL,
+    deleted_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_retention_deletions_user ON retention_deletions(user_id, deleted_at DESC);
+CREATE INDEX idx_revisions_retention ON revisions(user_id, completed_at) WHERE completed_at IS NOT NULL;
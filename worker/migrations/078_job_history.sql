@@ -0,0 +1,26 @@
+-- Migration 078: Job history
+--
+-- Lifecycle of background jobs (imports, exports, route planning,
+-- geocoding) for the Jobs page. Previously kept in worker memory with a
+-- JSON file copy, so it was lost with the container. A job is written as
+-- `running` when a worker picks it up and updated in place with its final
+-- status; rows still `running` at startup belong to a job the previous
+-- process never finished.
+
+CREATE TABLE job_history (
+    id           UUID PRIMARY KEY,
+    user_id      UUID NOT NULL,
+    job_type     VARCHAR(50) NOT NULL,
+    status       VARCHAR(20) NOT NULL
+        CHECK (status IN ('running', 'completed', 'failed', 'cancelled')),
+    started_at   TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ NOT NULL,
+    duration_ms  BIGINT NOT NULL DEFAULT 0,
+    error        TEXT,
+    details      TEXT,
+    report       JSONB,
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_job_history_user ON job_history(user_id, completed_at DESC);
+CREATE INDEX idx_job_history_running ON job_history(status) WHERE status = 'running';
@@ -0,0 +1,33 @@
+-- Migration 079: Notification subscription preferences
+--
+-- Users choose per category (import, route, reminder, system) whether they
+-- want notifications at all and whether each one arrives immediately or in
+-- an hourly / daily summary. Categories without a row are delivered
+-- immediately. Notifications held back for a summary wait in
+-- notification_digest_items until their delivery time.
+
+CREATE TABLE notification_preferences (
+    user_id    UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    category   VARCHAR(20) NOT NULL
+        CHECK (category IN ('import', 'route', 'reminder', 'system')),
+    enabled    BOOLEAN NOT NULL DEFAULT TRUE,
+    delivery   VARCHAR(20) NOT NULL DEFAULT 'immediate'
+        CHECK (delivery IN ('immediate', 'hourly', 'daily')),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, category)
+);
+
+CREATE TABLE notification_digest_items (
+    id            UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id       UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    category      VARCHAR(20) NOT NULL,
+    kind          VARCHAR(30) NOT NULL,
+    title         TEXT NOT NULL,
+    body          TEXT,
+    entity_type   VARCHAR(30),
+    entity_id     UUID,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deliver_after TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_notification_digest_due ON notification_digest_items(deliver_after);
@@ -0,0 +1,4 @@
+-- Revert migration 080: Webhooks
+
+DROP TABLE webhook_deliveries;
+DROP TABLE webhooks;
@@ -0,0 +1,44 @@
+-- Migration 080: Webhooks
+--
+-- Account owners register HTTPS endpoints that receive signed JSON
+-- callbacks when customers, revisions, visits or routes are created,
+-- updated or deleted. Every callback is a row in webhook_deliveries: it is
+-- queued on JetStream, retried with backoff and kept as an audit trail of
+-- what was sent where and how the endpoint answered.
+
+CREATE TABLE webhooks (
+    id          UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    url         TEXT NOT NULL,
+    -- HMAC-SHA256 key of the X-Sazinka-Signature header
+    secret      TEXT NOT NULL,
+    -- Subscribed event types; empty means every event
+    event_types TEXT[] NOT NULL DEFAULT '{}',
+    format      VARCHAR(20) NOT NULL DEFAULT 'envelope'
+        CHECK (format IN ('envelope', 'flat')),
+    description TEXT,
+    is_active   BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhooks_user ON webhooks(user_id) WHERE is_active;
+
+CREATE TABLE webhook_deliveries (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    webhook_id      UUID NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    event_id        UUID NOT NULL,
+    event_type      VARCHAR(50) NOT NULL,
+    payload         JSONB NOT NULL,
+    status          VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    response_status INTEGER,
+    last_error      TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at    TIMESTAMPTZ,
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhook_deliveries_webhook ON webhook_deliveries(webhook_id, created_at DESC);
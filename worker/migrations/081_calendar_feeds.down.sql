@@ -0,0 +1,3 @@
+-- Revert migration 081: Calendar feeds
+
+DROP TABLE calendar_feeds;
@@ -0,0 +1,22 @@
+-- Migration 081: Calendar feeds
+--
+-- iCal subscriptions of the work calendar. A feed covers the routes and
+-- visits of the whole company or of selected crews, optionally with a
+-- daily capacity overlay per crew. Calendar apps fetch it by a secret
+-- token in the URL; only the token's SHA-256 is stored, and a revoked
+-- feed stops answering at once.
+
+CREATE TABLE calendar_feeds (
+    id               UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id          UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name             VARCHAR(100) NOT NULL,
+    token_hash       VARCHAR(64) NOT NULL UNIQUE,
+    -- Crews in the feed; empty means every crew of the company
+    crew_ids         UUID[] NOT NULL DEFAULT '{}',
+    include_capacity BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_fetched_at  TIMESTAMPTZ,
+    revoked_at       TIMESTAMPTZ
+);
+
+CREATE INDEX idx_calendar_feeds_user ON calendar_feeds(user_id, created_at DESC);
@@ -0,0 +1,10 @@
+-- Revert migration 082: Automatic communication entries
+--
+-- System-written entries are kept as plain entries; only their type and
+-- structured details are lost.
+
+DROP INDEX idx_communications_entry_type;
+
+ALTER TABLE communications
+    DROP COLUMN details,
+    DROP COLUMN entry_type;
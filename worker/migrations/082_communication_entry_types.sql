@@ -0,0 +1,19 @@
+-- Migration 082: Automatic communication entries
+--
+-- Reminder/confirmation/campaign sends, route confirmations and visit
+-- completions are written by the system next to the manual entries;
+-- entry_type tells them apart and details keeps their structured facts.
+
+ALTER TABLE communications
+    ADD COLUMN entry_type VARCHAR(30) NOT NULL DEFAULT 'manual'
+        CHECK (entry_type IN (
+            'manual',
+            'reminder_email', 'reminder_sms',
+            'confirmation_email', 'confirmation_sms',
+            'campaign_email', 'campaign_sms',
+            'route_confirmed', 'visit_completed'
+        )),
+    ADD COLUMN details JSONB;
+
+CREATE INDEX idx_communications_entry_type
+    ON communications(user_id, entry_type, created_at DESC);
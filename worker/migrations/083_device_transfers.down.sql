@@ -0,0 +1,6 @@
+-- Revert migration 083: Device transfers
+--
+-- Transferred devices stay with their new customer; only the audit trail
+-- is lost.
+
+DROP TABLE device_transfers;
@@ -0,0 +1,22 @@
+-- Migration 083: Device transfers
+--
+-- When a property is sold its devices move to the new owner. The device
+-- row is reassigned, open revisions may follow it, and completed ones stay
+-- with the original customer as their history. Every move is recorded
+-- here as the audit trail.
+
+CREATE TABLE device_transfers (
+    id                  UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id             UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    device_id           UUID NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    from_customer_id    UUID REFERENCES customers(id) ON DELETE SET NULL,
+    to_customer_id      UUID REFERENCES customers(id) ON DELETE SET NULL,
+    moved_revision_ids  UUID[] NOT NULL DEFAULT '{}',
+    kept_revision_count INTEGER NOT NULL DEFAULT 0,
+    note                TEXT,
+    transferred_by      UUID REFERENCES users(id) ON DELETE SET NULL,
+    transferred_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_device_transfers_device ON device_transfers(device_id, transferred_at DESC);
+CREATE INDEX idx_device_transfers_user ON device_transfers(user_id, transferred_at DESC);
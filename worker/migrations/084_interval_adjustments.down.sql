@@ -0,0 +1,6 @@
+-- Revert migration 084: Bulk revision interval adjustments
+--
+-- Applied adjustments stay applied to devices and revisions; only their
+-- drafts and summaries are lost.
+
+DROP TABLE interval_adjustments;
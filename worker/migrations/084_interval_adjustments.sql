@@ -0,0 +1,28 @@
+-- Migration 084: Bulk revision interval adjustments
+--
+-- When a regulation changes the inspection interval of a device category,
+-- every matching device gets the new interval and its pending revision is
+-- moved (or created) to the recalculated due date. An adjustment is first
+-- stored as a draft together with its dry-run summary; applying it runs as a
+-- background job and records what was changed.
+
+CREATE TABLE interval_adjustments (
+    id                    UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id               UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    device_type_config_id UUID NOT NULL REFERENCES device_type_configs(id) ON DELETE CASCADE,
+    new_interval_months   INTEGER NOT NULL CHECK (new_interval_months BETWEEN 1 AND 240),
+    -- Only devices currently on this interval; NULL = every device of the category
+    only_interval_months  INTEGER,
+    update_type_default   BOOLEAN NOT NULL DEFAULT TRUE,
+    regulation_note       TEXT,
+    status                VARCHAR(20) NOT NULL DEFAULT 'draft'
+                          CHECK (status IN ('draft', 'running', 'applied', 'failed')),
+    preview_summary       JSONB NOT NULL DEFAULT '{}',
+    applied_summary       JSONB,
+    error                 TEXT,
+    created_by            UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at            TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    applied_at            TIMESTAMPTZ
+);
+
+CREATE INDEX idx_interval_adjustments_user ON interval_adjustments(user_id, created_at DESC);
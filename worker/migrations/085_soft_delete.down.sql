@@ -0,0 +1,9 @@
+-- Revert migration 085: Soft-delete for customers and devices
+--
+-- Soft-deleted devices come back into lists; customers.deleted_at predates
+-- this migration and stays.
+
+DROP INDEX IF EXISTS idx_devices_deleted_at;
+DROP INDEX IF EXISTS idx_customers_deleted_at;
+
+ALTER TABLE devices DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,11 @@
+-- Migration 085: Soft-delete for customers and devices
+--
+-- Deleting a customer or a device only stamps deleted_at; lists leave the
+-- row out and it can be restored until the purge job removes it once the
+-- retention window has passed. customers.deleted_at exists since 028.
+
+ALTER TABLE devices
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_customers_deleted_at ON customers(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_devices_deleted_at ON devices(deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,7 @@
+-- Revert migration 086: Automation rules
+--
+-- Rules and their execution log are lost; actions already taken (sent
+-- e-mails, created tasks) stay.
+
+DROP TABLE automation_runs;
+DROP TABLE automation_rules;
@@ -0,0 +1,39 @@
+-- Migration 086: Automation rules
+--
+-- User-configured "when <trigger> AND <conditions> THEN <actions>" rules,
+-- e.g. "when a revision becomes overdue and the customer has an e-mail,
+-- send this message and create a call-back task". Rules run on entity
+-- change events and on an hourly scan of overdue revisions. Conditions and
+-- actions are a small JSON DSL validated by the worker.
+
+CREATE TABLE automation_rules (
+    id          UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name        VARCHAR(200) NOT NULL,
+    trigger     VARCHAR(50) NOT NULL,
+    conditions  JSONB NOT NULL DEFAULT '[]',
+    actions     JSONB NOT NULL,
+    enabled     BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_automation_rules_trigger ON automation_rules(user_id, trigger) WHERE enabled;
+
+-- Execution log. The overdue scan also logs revisions whose conditions did
+-- not hold ('skipped'), so each revision is evaluated once per rule.
+CREATE TABLE automation_runs (
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    rule_id      UUID NOT NULL REFERENCES automation_rules(id) ON DELETE CASCADE,
+    trigger      VARCHAR(50) NOT NULL,
+    entity_id    UUID,
+    customer_id  UUID REFERENCES customers(id) ON DELETE SET NULL,
+    status       VARCHAR(20) NOT NULL CHECK (status IN ('succeeded', 'failed', 'skipped')),
+    actions_run  INTEGER NOT NULL DEFAULT 0,
+    error        TEXT,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_automation_runs_user ON automation_runs(user_id, created_at DESC);
+CREATE INDEX idx_automation_runs_rule_entity ON automation_runs(rule_id, entity_id);
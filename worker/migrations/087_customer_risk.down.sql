@@ -0,0 +1,11 @@
+-- Revert migration 087: Customer risk scoring
+--
+-- Incidents, custom weights and the score audit are lost.
+
+DROP TABLE customer_risk_score_changes;
+DROP TABLE customer_risk_incidents;
+DROP TABLE customer_risk_settings;
+
+ALTER TABLE customers
+    DROP COLUMN risk_flagged,
+    DROP COLUMN risk_score;
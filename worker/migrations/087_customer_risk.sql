@@ -0,0 +1,51 @@
+-- Migration 087: Customer risk scoring
+--
+-- An internal 0-100 score per customer from no-shows (visits with result
+-- 'customer_absent') and recorded payment/access incidents within a
+-- lookback window. Each category adds its weight per occurrence; the
+-- weights, window and flag threshold are configurable per account.
+-- Crews only see the flag on route stops, never the score.
+
+ALTER TABLE customers
+    ADD COLUMN risk_score   INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN risk_flagged BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Missing row = defaults (see CustomerRiskSettings::default)
+CREATE TABLE customer_risk_settings (
+    user_id              UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    no_show_weight       INTEGER NOT NULL DEFAULT 15 CHECK (no_show_weight BETWEEN 0 AND 100),
+    late_payment_weight  INTEGER NOT NULL DEFAULT 10 CHECK (late_payment_weight BETWEEN 0 AND 100),
+    unpaid_weight        INTEGER NOT NULL DEFAULT 30 CHECK (unpaid_weight BETWEEN 0 AND 100),
+    dispute_weight       INTEGER NOT NULL DEFAULT 20 CHECK (dispute_weight BETWEEN 0 AND 100),
+    access_denied_weight INTEGER NOT NULL DEFAULT 10 CHECK (access_denied_weight BETWEEN 0 AND 100),
+    lookback_months      INTEGER NOT NULL DEFAULT 24 CHECK (lookback_months BETWEEN 1 AND 120),
+    flag_threshold       INTEGER NOT NULL DEFAULT 40 CHECK (flag_threshold BETWEEN 1 AND 100),
+    updated_at           TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE customer_risk_incidents (
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    customer_id  UUID NOT NULL REFERENCES customers(id) ON DELETE CASCADE,
+    kind         VARCHAR(20) NOT NULL CHECK (kind IN ('late_payment', 'unpaid', 'dispute', 'access_denied')),
+    note         TEXT,
+    occurred_on  DATE NOT NULL DEFAULT CURRENT_DATE,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_customer_risk_incidents_customer ON customer_risk_incidents(customer_id, occurred_on);
+
+-- Audit of score changes
+CREATE TABLE customer_risk_score_changes (
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    customer_id  UUID NOT NULL REFERENCES customers(id) ON DELETE CASCADE,
+    old_score    INTEGER NOT NULL,
+    new_score    INTEGER NOT NULL,
+    flagged      BOOLEAN NOT NULL,
+    reason       VARCHAR(30) NOT NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_customer_risk_score_changes_customer ON customer_risk_score_changes(customer_id, created_at DESC);
+CREATE INDEX idx_customer_risk_score_changes_user ON customer_risk_score_changes(user_id, created_at DESC);
@@ -0,0 +1,5 @@
+-- Revert migration 088: Weather and travel conditions per visit
+--
+-- Recorded conditions are lost; the weather outcome report starts empty.
+
+DROP TABLE visit_conditions;
@@ -0,0 +1,23 @@
+-- Migration 088: Weather and travel conditions per visit
+--
+-- Recorded when a visit is completed or cancelled: the day's weather at the
+-- customer (from WEATHER_API_URL, an Open-Meteo compatible API) and how late
+-- the crew arrived against the planned stop time. Weather columns stay NULL
+-- when no API is configured or the day is out of its range. Feeds the
+-- weather outcome report (sazinka.report.weather_outcomes).
+
+CREATE TABLE visit_conditions (
+    visit_id               UUID PRIMARY KEY REFERENCES visits(id) ON DELETE CASCADE,
+    user_id                UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    condition              VARCHAR(10) CHECK (condition IN ('clear', 'cloudy', 'fog', 'rain', 'snow', 'storm')),
+    weather_code           INTEGER,
+    temperature_min_c      DOUBLE PRECISION,
+    temperature_max_c      DOUBLE PRECISION,
+    precipitation_mm       DOUBLE PRECISION,
+    snowfall_cm            DOUBLE PRECISION,
+    wind_max_kmh           DOUBLE PRECISION,
+    arrival_delay_minutes  INTEGER,
+    recorded_at            TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_visit_conditions_user ON visit_conditions(user_id);
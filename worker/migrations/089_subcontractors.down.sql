@@ -0,0 +1,8 @@
+-- Revert migration 089: Subcontractors
+--
+-- Subcontractor logins stay as users with role 'subcontractor' but see
+-- nothing; crews lose their subcontractor link.
+
+DROP TABLE subcontractor_rates;
+ALTER TABLE crews DROP COLUMN subcontractor_id;
+DROP TABLE subcontractors;
@@ -0,0 +1,40 @@
+-- Migration 089: Subcontractors
+--
+-- External companies some of the work is passed to. A subcontractor owns
+-- crews of the account; routes and visits of those crews are its assigned
+-- work. Its optional login (users.role = 'subcontractor', owner_id = the
+-- account) sees only that work, without prices or customer financial data.
+-- Rates are agreed per visit type, 'default' covering the other types, and
+-- price the settlement of completed subcontracted visits.
+
+CREATE TABLE subcontractors (
+    id               UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id          UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name             VARCHAR(255) NOT NULL,
+    company_id       VARCHAR(20),
+    email            VARCHAR(255),
+    phone            VARCHAR(20),
+    is_active        BOOLEAN NOT NULL DEFAULT TRUE,
+    account_user_id  UUID UNIQUE REFERENCES users(id) ON DELETE SET NULL,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_subcontractors_user ON subcontractors(user_id);
+
+CREATE TRIGGER trg_subcontractors_updated_at
+    BEFORE UPDATE ON subcontractors
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE crews
+    ADD COLUMN subcontractor_id UUID REFERENCES subcontractors(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_crews_subcontractor ON crews(subcontractor_id) WHERE subcontractor_id IS NOT NULL;
+
+CREATE TABLE subcontractor_rates (
+    subcontractor_id  UUID NOT NULL REFERENCES subcontractors(id) ON DELETE CASCADE,
+    visit_type        VARCHAR(30) NOT NULL,
+    amount_minor      BIGINT NOT NULL CHECK (amount_minor >= 0),
+    currency          CHAR(3) NOT NULL,
+    PRIMARY KEY (subcontractor_id, visit_type)
+);
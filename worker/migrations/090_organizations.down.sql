@@ -0,0 +1,8 @@
+-- Revert migration 090: Organizations and team membership
+--
+-- Members keep their worker logins and data access; admin rights and
+-- pending invitations are lost.
+
+DROP TABLE organization_invitations;
+DROP TABLE organization_members;
+DROP TABLE organizations;
@@ -0,0 +1,57 @@
+-- Migration 090: Organizations and team membership
+--
+-- An organization is the team around one owner account (role 'customer').
+-- Its data stays keyed on the owner's user_id: members are logins with
+-- role 'worker' and owner_id = the owner, so every query already scoped by
+-- AuthInfo::data_user_id() shares the owner's customers, devices and routes.
+-- Members are 'admin' (manage the team) or 'member'; colleagues join through
+-- an emailed invitation. Existing owners and their workers are backfilled.
+
+CREATE TABLE organizations (
+    id             UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    owner_user_id  UUID NOT NULL UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+    name           VARCHAR(255) NOT NULL,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at     TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER trg_organizations_updated_at
+    BEFORE UPDATE ON organizations
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE organization_members (
+    organization_id  UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    user_id          UUID NOT NULL UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+    role             VARCHAR(10) NOT NULL CHECK (role IN ('owner', 'admin', 'member')),
+    joined_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (organization_id, user_id)
+);
+
+CREATE TABLE organization_invitations (
+    id               UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    organization_id  UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    email            VARCHAR(255) NOT NULL,
+    role             VARCHAR(10) NOT NULL CHECK (role IN ('admin', 'member')),
+    token_hash       VARCHAR(64) NOT NULL UNIQUE,
+    invited_by       UUID REFERENCES users(id) ON DELETE SET NULL,
+    expires_at       TIMESTAMPTZ NOT NULL,
+    accepted_at      TIMESTAMPTZ,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_organization_invitations_org ON organization_invitations(organization_id);
+
+INSERT INTO organizations (owner_user_id, name)
+SELECT id, COALESCE(NULLIF(business_name, ''), name)
+FROM users
+WHERE role = 'customer';
+
+INSERT INTO organization_members (organization_id, user_id, role)
+SELECT id, owner_user_id, 'owner'
+FROM organizations;
+
+INSERT INTO organization_members (organization_id, user_id, role)
+SELECT o.id, u.id, 'member'
+FROM users u
+JOIN organizations o ON o.owner_user_id = u.owner_id
+WHERE u.role = 'worker';
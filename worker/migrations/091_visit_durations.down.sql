@@ -0,0 +1,6 @@
+-- Revert migration 091: Travel and on-site time per visit
+--
+-- Recorded and overridden durations are lost; the workload report and the
+-- learned service durations start empty.
+
+DROP TABLE visit_durations;
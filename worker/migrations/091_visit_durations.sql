@@ -0,0 +1,20 @@
+-- Migration 091: Travel and on-site time per visit
+--
+-- Derived from the visit's progress events: on-site time runs from
+-- "arrived" (actual_arrival) to "completed" (actual_departure), travel time
+-- from the crew's previous departure that day to the arrival. Either value
+-- can be overridden by hand; an overridden value is kept when the events
+-- are recorded again. Feeds the learned service durations and the workload
+-- report (sazinka.report.workload).
+
+CREATE TABLE visit_durations (
+    visit_id            UUID PRIMARY KEY REFERENCES visits(id) ON DELETE CASCADE,
+    user_id             UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    travel_minutes      INTEGER CHECK (travel_minutes >= 0),
+    on_site_minutes     INTEGER CHECK (on_site_minutes >= 0),
+    travel_overridden   BOOLEAN NOT NULL DEFAULT FALSE,
+    on_site_overridden  BOOLEAN NOT NULL DEFAULT FALSE,
+    recorded_at         TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_visit_durations_user ON visit_durations(user_id);
@@ -0,0 +1,8 @@
+-- Revert migration 092: Worker instance of running jobs
+--
+-- A starting worker fails every running job again, including those of
+-- other live workers.
+
+DROP INDEX idx_job_history_running;
+ALTER TABLE job_history DROP COLUMN worker_instance;
+CREATE INDEX idx_job_history_running ON job_history(status) WHERE status = 'running';
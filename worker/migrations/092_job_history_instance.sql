@@ -0,0 +1,12 @@
+-- Migration 092: Worker instance of running jobs
+--
+-- Several workers share job_history, so a starting worker may only fail the
+-- `running` rows it left behind itself. Each row records the instance
+-- (WORKER_INSTANCE_ID, else the host name) that picked the job up; rows
+-- written before this migration have none and are failed by the first
+-- worker that starts.
+
+ALTER TABLE job_history ADD COLUMN worker_instance VARCHAR(255);
+
+DROP INDEX idx_job_history_running;
+CREATE INDEX idx_job_history_running ON job_history(worker_instance) WHERE status = 'running';
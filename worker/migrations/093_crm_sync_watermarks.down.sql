@@ -0,0 +1,7 @@
+-- Revert migration 093: Separate CRM pull watermark from the run schedule
+--
+-- The scheduler falls back to last_pull_at, and customers written by a pull
+-- are pushed back to the remote again.
+
+DROP TABLE crm_pulled_customers;
+ALTER TABLE crm_connectors DROP COLUMN last_run_at;
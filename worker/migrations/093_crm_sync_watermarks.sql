@@ -0,0 +1,20 @@
+-- Migration 093: Separate CRM pull watermark from the run schedule
+--
+-- last_pull_at is the `updatedSince` watermark sent to the remote and only
+-- advances (to the pull start) after a clean pull. The scheduler uses the
+-- new last_run_at instead, so a failing connector is retried on its normal
+-- interval rather than on every tick.
+--
+-- crm_pulled_customers remembers the customer version each connector wrote
+-- during a pull, so the next push does not send it straight back and the
+-- next pull does not treat it as a local change.
+
+ALTER TABLE crm_connectors ADD COLUMN last_run_at TIMESTAMPTZ;
+UPDATE crm_connectors SET last_run_at = last_pull_at;
+
+CREATE TABLE crm_pulled_customers (
+    connector_id  UUID        NOT NULL REFERENCES crm_connectors(id) ON DELETE CASCADE,
+    customer_id   UUID        NOT NULL REFERENCES customers(id) ON DELETE CASCADE,
+    updated_at    TIMESTAMPTZ NOT NULL,  -- customers.updated_at written by the pull
+    PRIMARY KEY (connector_id, customer_id)
+);
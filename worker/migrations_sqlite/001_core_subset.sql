@@ -0,0 +1,129 @@
+-- =============================================================================
+-- Sazinka - SQLite schema subset (trial/demo backend, `sqlite` feature)
+-- =============================================================================
+-- Only the tables behind the repository traits (db::repo). Column names and
+-- meaning follow the Postgres schema in ../migrations; differences:
+--   * UUIDs are 16-byte BLOBs, dates/times/timestamps ISO 8601 TEXT
+--   * enums are TEXT with CHECK constraints
+--   * no triggers — writers set updated_at themselves
+-- =============================================================================
+
+CREATE TABLE users (
+    id          BLOB PRIMARY KEY,
+    email       TEXT NOT NULL UNIQUE,
+    name        TEXT,
+    created_at  TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE TABLE crews (
+    id                  BLOB PRIMARY KEY,
+    user_id             BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name                TEXT NOT NULL,
+    working_hours_start TEXT DEFAULT '08:00:00',
+    working_hours_end   TEXT DEFAULT '17:00:00',
+    is_active           INTEGER NOT NULL DEFAULT 1,
+    created_at          TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at          TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_crews_user ON crews(user_id);
+
+CREATE TABLE customers (
+    id                 BLOB PRIMARY KEY,
+    user_id            BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    customer_type      TEXT NOT NULL DEFAULT 'person' CHECK (customer_type IN ('person', 'company')),
+    name               TEXT,
+    contact_person     TEXT,
+    ico                TEXT,
+    dic                TEXT,
+    email              TEXT,
+    phone              TEXT,
+    phone_raw          TEXT,
+    street             TEXT,
+    city               TEXT,
+    postal_code        TEXT,
+    country            TEXT DEFAULT 'CZ',
+    lat                REAL,
+    lng                REAL,
+    geocode_status     TEXT NOT NULL DEFAULT 'pending' CHECK (geocode_status IN ('pending', 'success', 'failed')),
+    notes              TEXT,
+    is_anonymized      INTEGER NOT NULL DEFAULT 0,
+    is_abandoned       INTEGER NOT NULL DEFAULT 0,
+    deleted_at         TEXT,
+    parent_customer_id BLOB REFERENCES customers(id) ON DELETE SET NULL,
+    language           TEXT,
+    customer_code      TEXT,
+    created_at         TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at         TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_customers_user ON customers(user_id);
+
+CREATE TABLE revisions (
+    id                        BLOB PRIMARY KEY,
+    device_id                 BLOB NOT NULL,
+    customer_id               BLOB NOT NULL REFERENCES customers(id) ON DELETE CASCADE,
+    user_id                   BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status                    TEXT NOT NULL DEFAULT 'upcoming'
+                              CHECK (status IN ('upcoming', 'scheduled', 'confirmed', 'completed', 'cancelled')),
+    due_date                  TEXT NOT NULL,
+    scheduled_date            TEXT,
+    scheduled_time_start      TEXT,
+    scheduled_time_end        TEXT,
+    completed_at              TEXT,
+    duration_minutes          INTEGER,
+    result                    TEXT,
+    findings                  TEXT,
+    fulfilled_by_work_item_id BLOB,
+    assigned_crew_id          BLOB REFERENCES crews(id) ON DELETE SET NULL,
+    route_order               INTEGER,
+    document_number           TEXT,
+    created_at                TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at                TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_revisions_user_scheduled ON revisions(user_id, scheduled_date);
+
+CREATE TABLE routes (
+    id                               BLOB PRIMARY KEY,
+    user_id                          BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    crew_id                          BLOB REFERENCES crews(id) ON DELETE SET NULL,
+    depot_id                         BLOB,
+    date                             TEXT NOT NULL,
+    status                           TEXT NOT NULL DEFAULT 'draft',
+    total_distance_km                REAL,
+    total_duration_minutes           INTEGER,
+    optimization_score               INTEGER,
+    arrival_buffer_percent           REAL NOT NULL DEFAULT 10.0,
+    arrival_buffer_fixed_minutes     REAL NOT NULL DEFAULT 0.0,
+    return_to_depot_distance_km      REAL,
+    return_to_depot_duration_minutes INTEGER,
+    created_at                       TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at                       TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    UNIQUE (user_id, date, crew_id)
+);
+
+CREATE TABLE route_stops (
+    id                                BLOB PRIMARY KEY,
+    route_id                          BLOB NOT NULL REFERENCES routes(id) ON DELETE CASCADE,
+    customer_id                       BLOB REFERENCES customers(id) ON DELETE CASCADE,
+    visit_id                          BLOB,
+    revision_id                       BLOB REFERENCES revisions(id) ON DELETE SET NULL,
+    stop_order                        INTEGER NOT NULL,
+    estimated_arrival                 TEXT,
+    estimated_departure               TEXT,
+    distance_from_previous_km         REAL,
+    duration_from_previous_minutes    INTEGER,
+    status                            TEXT NOT NULL DEFAULT 'pending',
+    stop_type                         TEXT NOT NULL DEFAULT 'customer' CHECK (stop_type IN ('customer', 'break')),
+    break_duration_minutes            INTEGER,
+    break_time_start                  TEXT,
+    service_duration_minutes          INTEGER,
+    override_service_duration_minutes INTEGER,
+    override_travel_duration_minutes  INTEGER,
+    notes                             TEXT,
+    tasks                             TEXT NOT NULL DEFAULT '[]',
+    UNIQUE (route_id, stop_order)
+);
+
+CREATE INDEX idx_route_stops_route ON route_stops(route_id);
@@ -0,0 +1,5 @@
+-- Customer acquisition source (Postgres migration 071)
+
+ALTER TABLE customers ADD COLUMN acquisition_source TEXT
+    CHECK (acquisition_source IN ('web_form', 'phone', 'email', 'referral', 'campaign', 'other'));
+ALTER TABLE customers ADD COLUMN referrer_customer_id BLOB REFERENCES customers(id) ON DELETE SET NULL;
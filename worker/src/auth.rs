@@ -9,6 +9,7 @@ use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation}
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::types::organization::OrganizationMembership;
 use crate::types::Request;
 
 /// JWT claims
@@ -18,11 +19,18 @@ pub struct Claims {
     pub sub: String,
     /// User email
     pub email: String,
-    /// User role (admin, customer, worker)
+    /// User role (admin, customer, worker, subcontractor)
     pub role: String,
-    /// Owner ID (for workers - the customer who created them)
+    /// Owner ID (for workers and subcontractors - the customer who created them)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub owner_id: Option<String>,
+    /// Organization of the user; tokens issued before teams existed have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_id: Option<String>,
+    /// Role in the organization (owner, admin, member), for the UI only:
+    /// team management re-checks it in the database
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_role: Option<String>,
     #[serde(default)]
     pub permissions: Vec<String>,
     /// BCP-47 locale code (e.g. "en", "cs"). Available immediately on login.
@@ -46,13 +54,16 @@ fn default_locale() -> String {
 pub struct AuthInfo {
     pub user_id: Uuid,
     pub role: String,
-    /// For workers, the customer's user_id (used for data queries)
+    /// For workers and subcontractors, the customer's user_id (workers query its data)
     pub owner_id: Option<Uuid>,
+    /// Whether the user's email was verified when the token was issued
+    pub email_verified: bool,
 }
 
 impl AuthInfo {
     /// Returns the user_id to use for data queries.
     /// Workers use their owner's user_id so they see the same data as their customer.
+    /// Subcontractors keep their own, so they only see assigned work (handlers::subcontractor).
     pub fn data_user_id(&self) -> Uuid {
         if self.role == "worker" {
             self.owner_id.unwrap_or(self.user_id)
@@ -60,6 +71,13 @@ impl AuthInfo {
             self.user_id
         }
     }
+
+    /// Customer accounts with an unverified email have limited permissions:
+    /// nothing that sends email on their behalf until they confirm the address.
+    /// Workers are created by their customer and admins are trusted.
+    pub fn requires_email_verification(&self) -> bool {
+        self.role == "customer" && !self.email_verified
+    }
 }
 
 /// Generate a JWT access token
@@ -71,6 +89,7 @@ pub fn generate_token(
     permissions: &[String],
     locale: &str,
     email_verified: bool,
+    org: Option<&OrganizationMembership>,
     secret: &str,
 ) -> Result<String> {
     let now = chrono::Utc::now().timestamp() as usize;
@@ -81,6 +100,8 @@ pub fn generate_token(
         email: email.to_string(),
         role: role.to_string(),
         owner_id: owner_id.map(|id| id.to_string()),
+        org_id: org.map(|m| m.organization_id.to_string()),
+        org_role: org.map(|m| m.role.clone()),
         permissions: permissions.to_vec(),
         locale: locale.to_string(),
         email_verified,
@@ -150,6 +171,7 @@ pub fn extract_auth<T>(request: &Request<T>, jwt_secret: &str) -> Result<AuthInf
             user_id,
             role: claims.role,
             owner_id,
+            email_verified: claims.email_verified,
         });
     }
 
@@ -207,7 +229,7 @@ mod tests {
     #[test]
     fn test_generate_and_validate_token() {
         let user_id = Uuid::new_v4();
-        let token = generate_token(user_id, "test@example.com", "customer", None, &["*".to_string()], "en", true, TEST_SECRET).unwrap();
+        let token = generate_token(user_id, "test@example.com", "customer", None, &["*".to_string()], "en", true, None, TEST_SECRET).unwrap();
 
         let claims = validate_token(&token, TEST_SECRET).unwrap();
         assert_eq!(claims.sub, user_id.to_string());
@@ -222,7 +244,7 @@ mod tests {
     fn test_generate_token_with_owner_id() {
         let user_id = Uuid::new_v4();
         let owner_id = Uuid::new_v4();
-        let token = generate_token(user_id, "worker@example.com", "worker", Some(owner_id), &["page:inbox".to_string()], "cs", true, TEST_SECRET).unwrap();
+        let token = generate_token(user_id, "worker@example.com", "worker", Some(owner_id), &["page:inbox".to_string()], "cs", true, None, TEST_SECRET).unwrap();
 
         let claims = validate_token(&token, TEST_SECRET).unwrap();
         assert_eq!(claims.sub, user_id.to_string());
@@ -234,7 +256,7 @@ mod tests {
     #[test]
     fn test_validate_token_wrong_secret() {
         let user_id = Uuid::new_v4();
-        let token = generate_token(user_id, "test@example.com", "customer", None, &["*".to_string()], "en", true, TEST_SECRET).unwrap();
+        let token = generate_token(user_id, "test@example.com", "customer", None, &["*".to_string()], "en", true, None, TEST_SECRET).unwrap();
 
         let result = validate_token(&token, "wrong-secret");
         assert!(result.is_err());
@@ -251,7 +273,7 @@ mod tests {
         let user_id = Uuid::new_v4();
         
         for role in &["admin", "customer", "worker"] {
-            let token = generate_token(user_id, "test@example.com", role, None, &["*".to_string()], "en", true, TEST_SECRET).unwrap();
+            let token = generate_token(user_id, "test@example.com", role, None, &["*".to_string()], "en", true, None, TEST_SECRET).unwrap();
             let claims = validate_token(&token, TEST_SECRET).unwrap();
             assert_eq!(claims.role, *role);
         }
@@ -264,6 +286,9 @@ mod tests {
             id: Uuid::new_v4(),
             timestamp: Utc::now(),
             token,
+            client_ip: None,
+            user_agent: None,
+            demo_mode: false,
             payload: T::default(),
         }
     }
@@ -271,7 +296,7 @@ mod tests {
     #[test]
     fn test_extract_auth_with_valid_token() {
         let user_id = Uuid::new_v4();
-        let token = generate_token(user_id, "test@example.com", "admin", None, &["*".to_string()], "en", true, TEST_SECRET).unwrap();
+        let token = generate_token(user_id, "test@example.com", "admin", None, &["*".to_string()], "en", true, None, TEST_SECRET).unwrap();
         
         let request = make_request_with_token::<serde_json::Value>(Some(token));
         let auth = extract_auth(&request, TEST_SECRET).unwrap();
@@ -285,7 +310,7 @@ mod tests {
     fn test_extract_auth_with_worker_token() {
         let user_id = Uuid::new_v4();
         let owner_id = Uuid::new_v4();
-        let token = generate_token(user_id, "worker@example.com", "worker", Some(owner_id), &["page:planner".to_string()], "en", true, TEST_SECRET).unwrap();
+        let token = generate_token(user_id, "worker@example.com", "worker", Some(owner_id), &["page:planner".to_string()], "en", true, None, TEST_SECRET).unwrap();
         
         let request = make_request_with_token::<serde_json::Value>(Some(token));
         let auth = extract_auth(&request, TEST_SECRET).unwrap();
@@ -300,7 +325,7 @@ mod tests {
     #[test]
     fn test_extract_auth_data_user_id_for_customer() {
         let user_id = Uuid::new_v4();
-        let token = generate_token(user_id, "customer@example.com", "customer", None, &["*".to_string()], "en", true, TEST_SECRET).unwrap();
+        let token = generate_token(user_id, "customer@example.com", "customer", None, &["*".to_string()], "en", true, None, TEST_SECRET).unwrap();
         
         let request = make_request_with_token::<serde_json::Value>(Some(token));
         let auth = extract_auth(&request, TEST_SECRET).unwrap();
@@ -309,6 +334,57 @@ mod tests {
         assert_eq!(auth.data_user_id(), user_id);
     }
 
+    #[test]
+    fn test_extract_auth_data_user_id_for_subcontractor() {
+        let user_id = Uuid::new_v4();
+        let owner_id = Uuid::new_v4();
+        let token = generate_token(user_id, "sub@example.com", "subcontractor", Some(owner_id), &[], "en", true, None, TEST_SECRET).unwrap();
+
+        let request = make_request_with_token::<serde_json::Value>(Some(token));
+        let auth = extract_auth(&request, TEST_SECRET).unwrap();
+
+        // Subcontractors must not see the owner's data through generic handlers
+        assert_eq!(auth.owner_id, Some(owner_id));
+        assert_eq!(auth.data_user_id(), user_id);
+    }
+
+    #[test]
+    fn test_token_with_organization_claims() {
+        let org = OrganizationMembership { organization_id: Uuid::new_v4(), role: "admin".to_string() };
+        let token = generate_token(Uuid::new_v4(), "w@example.com", "worker", Some(Uuid::new_v4()), &[], "en", true, Some(&org), TEST_SECRET).unwrap();
+
+        let claims = validate_token(&token, TEST_SECRET).unwrap();
+        assert_eq!(claims.org_id, Some(org.organization_id.to_string()));
+        assert_eq!(claims.org_role.as_deref(), Some("admin"));
+
+        let token = generate_token(Uuid::new_v4(), "c@example.com", "customer", None, &[], "en", true, None, TEST_SECRET).unwrap();
+        let claims = validate_token(&token, TEST_SECRET).unwrap();
+        assert_eq!(claims.org_id, None);
+        assert_eq!(claims.org_role, None);
+    }
+
+    #[test]
+    fn test_extract_auth_unverified_customer_is_limited() {
+        let user_id = Uuid::new_v4();
+        let token = generate_token(user_id, "new@example.com", "customer", None, &[], "en", false, None, TEST_SECRET).unwrap();
+
+        let request = make_request_with_token::<serde_json::Value>(Some(token));
+        let auth = extract_auth(&request, TEST_SECRET).unwrap();
+
+        assert!(!auth.email_verified);
+        assert!(auth.requires_email_verification());
+    }
+
+    #[test]
+    fn test_extract_auth_unverified_worker_is_not_limited() {
+        let token = generate_token(Uuid::new_v4(), "w@example.com", "worker", Some(Uuid::new_v4()), &[], "en", false, None, TEST_SECRET).unwrap();
+
+        let request = make_request_with_token::<serde_json::Value>(Some(token));
+        let auth = extract_auth(&request, TEST_SECRET).unwrap();
+
+        assert!(!auth.requires_email_verification());
+    }
+
     #[test]
     fn test_extract_auth_no_token_fails() {
         // Without legacy user_id fallback, no token means UNAUTHORIZED
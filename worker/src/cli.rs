@@ -1,6 +1,11 @@
 //! CLI argument parsing for the sazinka-worker binary.
 
+use std::path::PathBuf;
+
 use clap::{Parser, Subcommand};
+use uuid::Uuid;
+
+use crate::types::account_transfer::ConflictPolicy;
 
 #[derive(Parser)]
 #[command(name = "sazinka-worker", about = "Sazinka CRM backend worker")]
@@ -14,13 +19,77 @@ pub enum Command {
     /// Start the worker server (default if no subcommand given)
     Serve,
     /// Run database migrations and exit
-    Migrate,
+    Migrate {
+        /// Print pending migrations, migration table fixes and a schema diff
+        /// without changing anything
+        #[arg(long, conflicts_with = "reconcile")]
+        dry_run: bool,
+        /// Allow rewriting _sqlx_migrations: delete orphaned records and
+        /// replace mismatched checksums
+        #[arg(long)]
+        reconcile: bool,
+        /// Revert applied migrations newer than VERSION (0 = all) with their
+        /// down migrations; with --dry-run only report what would happen
+        #[arg(long, value_name = "VERSION", conflicts_with = "reconcile")]
+        rollback: Option<i64>,
+        /// Roll back even when the down migrations delete data
+        #[arg(long, requires = "rollback")]
+        force: bool,
+    },
     /// Create or update an admin user interactively
     CreateAdmin {
         /// Admin email address
         #[arg(long)]
         email: String,
     },
+    /// Create an encrypted backup now and upload it to the backup storage
+    Backup {
+        /// Back up only this account (owner user id) instead of the whole database
+        #[arg(long)]
+        user: Option<Uuid>,
+        /// Write the backup to this file instead of uploading it
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// List backups in the backup storage
+    ListBackups,
+    /// Restore a backup; only rows missing from the database are inserted
+    Restore {
+        /// Object key of the backup in the storage (see list-backups)
+        #[arg(long, required_unless_present = "file", conflicts_with = "file")]
+        object: Option<String>,
+        /// Local backup file
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Export an account for transfer to another Sazinka instance
+    ExportAccount {
+        /// Account owner user id
+        #[arg(long)]
+        user: Uuid,
+        /// Transfer archive to write
+        #[arg(long)]
+        output: PathBuf,
+        /// Base64 transfer key shared with the target instance (default: TRANSFER_KEY)
+        #[arg(long)]
+        key: Option<String>,
+    },
+    /// Import an account exported by another Sazinka instance under new ids
+    ImportAccount {
+        /// Transfer archive to read
+        #[arg(long)]
+        file: PathBuf,
+        /// Base64 transfer key (default: TRANSFER_KEY)
+        #[arg(long)]
+        key: Option<String>,
+        /// Merge into this existing account owner instead of creating a new account
+        #[arg(long, value_name = "USER_ID")]
+        into: Option<Uuid>,
+        /// Users whose email already exists here: fail, or skip them and keep
+        /// their data in the account
+        #[arg(long, default_value = "fail")]
+        on_conflict: ConflictPolicy,
+    },
 }
 
 #[cfg(test)]
@@ -31,7 +100,24 @@ mod tests {
     #[test]
     fn test_cli_migrate_command_parses() {
         let cli = Cli::parse_from(["sazinka-worker", "migrate"]);
-        assert!(matches!(cli.command, Some(Command::Migrate)));
+        assert!(matches!(cli.command, Some(Command::Migrate { dry_run: false, reconcile: false, rollback: None, force: false })));
+    }
+
+    #[test]
+    fn test_cli_migrate_dry_run_excludes_reconcile() {
+        let cli = Cli::parse_from(["sazinka-worker", "migrate", "--dry-run"]);
+        assert!(matches!(cli.command, Some(Command::Migrate { dry_run: true, reconcile: false, .. })));
+
+        assert!(Cli::try_parse_from(["sazinka-worker", "migrate", "--dry-run", "--reconcile"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_migrate_rollback_parses() {
+        let cli = Cli::parse_from(["sazinka-worker", "migrate", "--rollback", "82", "--force"]);
+        assert!(matches!(cli.command, Some(Command::Migrate { rollback: Some(82), force: true, .. })));
+
+        assert!(Cli::try_parse_from(["sazinka-worker", "migrate", "--force"]).is_err());
+        assert!(Cli::try_parse_from(["sazinka-worker", "migrate", "--rollback", "82", "--reconcile"]).is_err());
     }
 
     #[test]
@@ -40,6 +126,47 @@ mod tests {
         assert!(cli.command.is_none());
     }
 
+    #[test]
+    fn test_cli_backup_command_parses() {
+        let user = Uuid::new_v4();
+        let cli = Cli::parse_from(["sazinka-worker", "backup", "--user", &user.to_string()]);
+        assert!(matches!(cli.command, Some(Command::Backup { user: Some(u), output: None }) if u == user));
+    }
+
+    #[test]
+    fn test_cli_restore_requires_one_source() {
+        let cli = Cli::parse_from(["sazinka-worker", "restore", "--file", "backup.szbk"]);
+        assert!(matches!(cli.command, Some(Command::Restore { object: None, file: Some(_) })));
+
+        assert!(Cli::try_parse_from(["sazinka-worker", "restore"]).is_err());
+        assert!(Cli::try_parse_from(["sazinka-worker", "restore", "--file", "a", "--object", "b"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_import_account_parses() {
+        let into = Uuid::new_v4();
+        let cli = Cli::parse_from([
+            "sazinka-worker", "import-account", "--file", "account.szbk", "--into", &into.to_string(), "--on-conflict", "skip",
+        ]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::ImportAccount { into: Some(u), on_conflict: ConflictPolicy::Skip, key: None, .. }) if u == into
+        ));
+
+        let cli = Cli::parse_from(["sazinka-worker", "import-account", "--file", "account.szbk"]);
+        assert!(matches!(cli.command, Some(Command::ImportAccount { into: None, on_conflict: ConflictPolicy::Fail, .. })));
+        assert!(Cli::try_parse_from(["sazinka-worker", "import-account", "--file", "a", "--on-conflict", "merge"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_export_account_requires_user_and_output() {
+        let user = Uuid::new_v4();
+        let cli = Cli::parse_from(["sazinka-worker", "export-account", "--user", &user.to_string(), "--output", "a.szbk"]);
+        assert!(matches!(cli.command, Some(Command::ExportAccount { user: u, .. }) if u == user));
+
+        assert!(Cli::try_parse_from(["sazinka-worker", "export-account", "--output", "a.szbk"]).is_err());
+    }
+
     #[test]
     fn test_cli_serve_command_parses() {
         let cli = Cli::parse_from(["sazinka-worker", "serve"]);
@@ -1,21 +1,50 @@
 //! Configuration management
 
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+
 use anyhow::{self, Context, Result};
+use base64::Engine;
+
+use crate::services::geocode_freshness;
+use crate::transport::TransportMode;
 
 /// Application configuration
 #[derive(Debug, Clone)]
 pub struct Config {
     /// NATS server URL
     pub nats_url: String,
+
+    /// NATS server, or embedded broker with the HTTP gateway (WORKER_MODE=standalone)
+    pub transport_mode: TransportMode,
+    /// Listen address of the HTTP gateway in standalone mode
+    pub gateway_addr: SocketAddr,
+    /// Public base URL calendar apps fetch feeds from (`{url}/calendar/{token}.ics`)
+    pub calendar_feed_url: String,
+    /// Listen address serving only calendar feeds over HTTP in NATS mode.
+    /// None → feeds are served by the standalone gateway only.
+    pub calendar_feed_addr: Option<SocketAddr>,
+    /// Listen address serving only the health probes over HTTP in NATS mode
+    /// (`/health`, `/healthz`, `/readyz`). None → no probe listener.
+    pub health_addr: Option<SocketAddr>,
     
     /// PostgreSQL connection string
     pub database_url: String,
     
     /// Nominatim API URL (for geocoding)
     pub nominatim_url: String,
+    /// Age of the Nominatim data after which the index is reported as stale
+    pub nominatim_max_data_age_days: i64,
     
     /// Valhalla routing engine URL (optional, falls back to mock if unavailable)
     pub valhalla_url: Option<String>,
+
+    /// Raster tile URL template for static map snapshots ({z}/{x}/{y} placeholders)
+    pub map_tile_url: String,
+    /// Attribution printed under static map snapshots
+    pub map_tile_attribution: String,
     
     /// JWT secret key for token signing/validation
     pub jwt_secret: String,
@@ -32,6 +61,291 @@ pub struct Config {
     pub ses_from_name: Option<String>,
     /// Optional SES configuration set name for open/click/bounce tracking.
     pub ses_configuration_set: Option<String>,
+
+    /// Endpoint receiving anonymous telemetry of opted-in accounts.
+    /// None → telemetry is never sent.
+    pub telemetry_endpoint: Option<String>,
+
+    /// Encrypted data backups. None → backups disabled.
+    pub backup: Option<BackupConfig>,
+
+    /// Object storage receiving finished exports. None → exports are only
+    /// downloaded through the worker.
+    pub export_storage: Option<ExportStorageConfig>,
+
+    /// Placement and limits of the job streams
+    pub jetstream: JetStreamConfig,
+}
+
+/// Encrypted backups (BACKUP_* variables)
+#[derive(Clone)]
+pub struct BackupConfig {
+    /// AES-256-GCM key of the archives
+    pub key: [u8; 32],
+    /// Off-site object storage. None → no scheduled backups, CLI writes files only.
+    pub storage: Option<BackupStorageConfig>,
+    /// How often the scheduler creates a full backup
+    pub interval: Duration,
+    /// Full backups kept in the storage; older ones are deleted
+    pub retention: usize,
+}
+
+/// S3-compatible bucket receiving the archives
+#[derive(Debug, Clone)]
+pub struct BackupStorageConfig {
+    pub bucket: String,
+    /// Key prefix of the archives, e.g. "backups/"
+    pub prefix: String,
+    pub region: String,
+    /// Custom endpoint of S3-compatible storage (MinIO, Backblaze B2, ...)
+    pub endpoint: Option<String>,
+}
+
+// The key never ends up in logs
+impl fmt::Debug for BackupConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackupConfig")
+            .field("key", &"<redacted>")
+            .field("storage", &self.storage)
+            .field("interval", &self.interval)
+            .field("retention", &self.retention)
+            .finish()
+    }
+}
+
+impl BackupConfig {
+    /// Read BACKUP_* variables; None when no encryption key is set
+    pub fn from_env() -> Result<Option<Self>> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+
+        let Some(encoded_key) = var("BACKUP_ENCRYPTION_KEY") else {
+            if var("BACKUP_S3_BUCKET").is_some() {
+                tracing::warn!("BACKUP_S3_BUCKET is set but BACKUP_ENCRYPTION_KEY is not — backups disabled");
+            }
+            return Ok(None);
+        };
+        let key = parse_backup_key(&encoded_key)?;
+
+        let storage = var("BACKUP_S3_BUCKET").map(|bucket| {
+            let mut prefix = var("BACKUP_S3_PREFIX").unwrap_or_else(|| "backups/".to_string());
+            if !prefix.is_empty() && !prefix.ends_with('/') {
+                prefix.push('/');
+            }
+            BackupStorageConfig {
+                bucket,
+                prefix,
+                region: var("BACKUP_S3_REGION").unwrap_or_else(|| "eu-central-1".to_string()),
+                endpoint: var("BACKUP_S3_ENDPOINT"),
+            }
+        });
+
+        let interval_hours: u64 = var("BACKUP_INTERVAL_HOURS")
+            .map(|v| v.parse())
+            .transpose()
+            .context("BACKUP_INTERVAL_HOURS must be a whole number of hours")?
+            .unwrap_or(24);
+        if interval_hours == 0 {
+            anyhow::bail!("BACKUP_INTERVAL_HOURS must be at least 1");
+        }
+        let retention: usize = var("BACKUP_RETENTION")
+            .map(|v| v.parse())
+            .transpose()
+            .context("BACKUP_RETENTION must be a whole number")?
+            .unwrap_or(14);
+        if retention == 0 {
+            anyhow::bail!("BACKUP_RETENTION must be at least 1");
+        }
+
+        Ok(Some(Self {
+            key,
+            storage,
+            interval: Duration::from_secs(interval_hours * 3600),
+            retention,
+        }))
+    }
+}
+
+/// S3-compatible bucket receiving finished exports (EXPORT_S3_* variables)
+#[derive(Debug, Clone)]
+pub struct ExportStorageConfig {
+    pub bucket: String,
+    /// Key prefix of the exports, e.g. "exports/"
+    pub prefix: String,
+    pub region: String,
+    /// Custom endpoint of S3-compatible storage (MinIO, Backblaze B2, ...)
+    pub endpoint: Option<String>,
+    /// How long download links of uploaded exports stay valid
+    pub link_ttl: Duration,
+}
+
+/// Presigned links can't outlive a week
+const MAX_EXPORT_LINK_TTL_HOURS: u64 = 7 * 24;
+
+impl ExportStorageConfig {
+    /// Read EXPORT_S3_* variables; None when no bucket is set
+    pub fn from_env() -> Result<Option<Self>> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+
+        let Some(bucket) = var("EXPORT_S3_BUCKET") else {
+            return Ok(None);
+        };
+        let mut prefix = var("EXPORT_S3_PREFIX").unwrap_or_else(|| "exports/".to_string());
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+        let ttl_hours: u64 = var("EXPORT_LINK_TTL_HOURS")
+            .map(|v| v.parse())
+            .transpose()
+            .context("EXPORT_LINK_TTL_HOURS must be a whole number of hours")?
+            .unwrap_or(72);
+        if ttl_hours == 0 || ttl_hours > MAX_EXPORT_LINK_TTL_HOURS {
+            anyhow::bail!("EXPORT_LINK_TTL_HOURS must be between 1 and {}", MAX_EXPORT_LINK_TTL_HOURS);
+        }
+
+        Ok(Some(Self {
+            bucket,
+            prefix,
+            region: var("EXPORT_S3_REGION").unwrap_or_else(|| "eu-central-1".to_string()),
+            endpoint: var("EXPORT_S3_ENDPOINT"),
+            link_ttl: Duration::from_secs(ttl_hours * 3600),
+        }))
+    }
+}
+
+/// Most replicas a JetStream stream can have
+const MAX_STREAM_REPLICAS: usize = 5;
+/// Smallest max_bytes a stream may be given; one large import must still fit
+const MIN_STREAM_MAX_BYTES: i64 = 1024 * 1024;
+
+/// Stream placement and limits (JETSTREAM_* variables). Applied to every
+/// job stream when it is created and reconciled with existing streams at
+/// startup; streams without an override keep the processors' defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JetStreamConfig {
+    /// Replicas of every stream; None keeps the processors' default (1)
+    pub replicas: Option<usize>,
+    pub placement_cluster: Option<String>,
+    pub placement_tags: Vec<String>,
+    /// Limit overrides keyed by stream name
+    pub stream_limits: BTreeMap<String, StreamLimitOverride>,
+}
+
+/// Limits of one stream; None keeps the processor's default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamLimitOverride {
+    pub max_messages: Option<i64>,
+    pub max_bytes: Option<i64>,
+}
+
+impl JetStreamConfig {
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+
+        let replicas = var("JETSTREAM_REPLICAS")
+            .map(|v| v.trim().parse::<usize>())
+            .transpose()
+            .context("JETSTREAM_REPLICAS must be a whole number")?;
+        if replicas.is_some_and(|r| !(1..=MAX_STREAM_REPLICAS).contains(&r)) {
+            anyhow::bail!("JETSTREAM_REPLICAS must be between 1 and {}", MAX_STREAM_REPLICAS);
+        }
+        let placement_tags = var("JETSTREAM_PLACEMENT_TAGS")
+            .map(|tags| {
+                tags.split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let stream_limits = match var("JETSTREAM_STREAM_LIMITS") {
+            Some(spec) => parse_stream_limits(&spec).context("Invalid JETSTREAM_STREAM_LIMITS")?,
+            None => BTreeMap::new(),
+        };
+
+        Ok(Self {
+            replicas,
+            placement_cluster: var("JETSTREAM_PLACEMENT_CLUSTER").map(|c| c.trim().to_string()),
+            placement_tags,
+            stream_limits,
+        })
+    }
+}
+
+/// Parse `STREAM:max_messages=N,max_bytes=SIZE;STREAM2:...`, sizes in bytes
+/// or with a KB/MB/GB suffix (powers of 1024)
+fn parse_stream_limits(spec: &str) -> Result<BTreeMap<String, StreamLimitOverride>> {
+    let mut limits = BTreeMap::new();
+    for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let (stream, settings) = entry
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("'{}' must look like STREAM:max_messages=N,max_bytes=SIZE", entry))?;
+        let stream = stream.trim();
+        if stream.is_empty() {
+            anyhow::bail!("'{}' has no stream name", entry);
+        }
+        let mut limit = StreamLimitOverride::default();
+        for setting in settings.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("'{}' of {} must be key=value", setting, stream))?;
+            match key.trim() {
+                "max_messages" => {
+                    let max: i64 = value
+                        .trim()
+                        .parse()
+                        .with_context(|| format!("max_messages of {} must be a whole number", stream))?;
+                    if max < 1 {
+                        anyhow::bail!("max_messages of {} must be at least 1", stream);
+                    }
+                    limit.max_messages = Some(max);
+                }
+                "max_bytes" => {
+                    let max = parse_size(value)
+                        .with_context(|| format!("max_bytes of {} must be a size like 500MB", stream))?;
+                    if max < MIN_STREAM_MAX_BYTES {
+                        anyhow::bail!("max_bytes of {} must be at least 1MB", stream);
+                    }
+                    limit.max_bytes = Some(max);
+                }
+                other => anyhow::bail!("Unknown limit '{}' of {} (expected max_messages or max_bytes)", other, stream),
+            }
+        }
+        if limits.insert(stream.to_string(), limit).is_some() {
+            anyhow::bail!("{} is listed twice", stream);
+        }
+    }
+    Ok(limits)
+}
+
+/// Bytes of a size like `1048576`, `512KB`, `200MB` or `2GB`
+fn parse_size(value: &str) -> Result<i64> {
+    let value = value.trim().to_ascii_uppercase();
+    let value = value.strip_suffix("IB").map(|v| format!("{}B", v)).unwrap_or(value);
+    let (number, multiplier) = if let Some(n) = value.strip_suffix("GB") {
+        (n, 1024 * 1024 * 1024)
+    } else if let Some(n) = value.strip_suffix("MB") {
+        (n, 1024 * 1024)
+    } else if let Some(n) = value.strip_suffix("KB") {
+        (n, 1024)
+    } else {
+        (value.strip_suffix('B').unwrap_or(&value), 1)
+    };
+    let number: i64 = number.trim().parse()?;
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| anyhow::anyhow!("size is too large"))
+}
+
+/// Decode a base64 key of exactly 32 bytes
+fn parse_backup_key(encoded: &str) -> Result<[u8; 32]> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .context("BACKUP_ENCRYPTION_KEY must be base64 — generate one with: openssl rand -base64 32")?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        anyhow::anyhow!(
+            "BACKUP_ENCRYPTION_KEY must be 32 bytes (current: {} bytes) — generate one with: openssl rand -base64 32",
+            bytes.len()
+        )
+    })
 }
 
 impl Config {
@@ -41,14 +355,48 @@ impl Config {
         let nats_url = std::env::var("NATS_URL")
             .unwrap_or_else(|_| "nats://localhost:4222".to_string());
 
+        let transport_mode = std::env::var("WORKER_MODE")
+            .unwrap_or_default()
+            .parse::<TransportMode>()?;
+        let gateway_addr = std::env::var("GATEWAY_ADDR")
+            .unwrap_or_else(|_| "0.0.0.0:8080".to_string())
+            .parse::<SocketAddr>()
+            .context("GATEWAY_ADDR must be an address like 0.0.0.0:8080")?;
+        let calendar_feed_url = std::env::var("CALENDAR_FEED_URL")
+            .unwrap_or_else(|_| "http://localhost:8080".to_string());
+        let calendar_feed_addr = std::env::var("CALENDAR_FEED_ADDR")
+            .ok()
+            .filter(|addr| !addr.trim().is_empty())
+            .map(|addr| addr.parse::<SocketAddr>())
+            .transpose()
+            .context("CALENDAR_FEED_ADDR must be an address like 0.0.0.0:8081")?;
+        let health_addr = std::env::var("HEALTH_ADDR")
+            .ok()
+            .filter(|addr| !addr.trim().is_empty())
+            .map(|addr| addr.parse::<SocketAddr>())
+            .transpose()
+            .context("HEALTH_ADDR must be an address like 0.0.0.0:8082")?;
+
         let database_url = std::env::var("DATABASE_URL")
             .context("DATABASE_URL must be set")?;
 
         let nominatim_url = std::env::var("NOMINATIM_URL")
             .unwrap_or_else(|_| "https://nominatim.openstreetmap.org".to_string());
 
+        let nominatim_max_data_age_days: i64 = std::env::var("NOMINATIM_MAX_DATA_AGE_DAYS")
+            .map(|v| v.parse())
+            .ok()
+            .transpose()
+            .context("NOMINATIM_MAX_DATA_AGE_DAYS must be a whole number of days")?
+            .unwrap_or(geocode_freshness::DEFAULT_MAX_AGE_DAYS);
+
         let valhalla_url = std::env::var("VALHALLA_URL").ok();
 
+        let map_tile_url = std::env::var("MAP_TILE_URL")
+            .unwrap_or_else(|_| "https://tile.openstreetmap.org/{z}/{x}/{y}.png".to_string());
+        let map_tile_attribution = std::env::var("MAP_TILE_ATTRIBUTION")
+            .unwrap_or_else(|_| "© OpenStreetMap contributors".to_string());
+
         let jwt_secret = std::env::var("JWT_SECRET")
             .context("JWT_SECRET must be set — generate one with: openssl rand -base64 48")?;
 
@@ -74,17 +422,37 @@ impl Config {
         let ses_from_name = std::env::var("SES_FROM_NAME").ok();
         let ses_configuration_set = std::env::var("SES_CONFIGURATION_SET").ok();
 
+        let telemetry_endpoint = std::env::var("TELEMETRY_ENDPOINT")
+            .ok()
+            .filter(|url| !url.trim().is_empty());
+
+        let backup = BackupConfig::from_env()?;
+        let export_storage = ExportStorageConfig::from_env()?;
+        let jetstream = JetStreamConfig::from_env()?;
+
         Ok(Self {
             nats_url,
+            transport_mode,
+            gateway_addr,
+            calendar_feed_url,
+            calendar_feed_addr,
+            health_addr,
             database_url,
             nominatim_url,
+            nominatim_max_data_age_days,
             valhalla_url,
+            map_tile_url,
+            map_tile_attribution,
             jwt_secret,
             app_base_url,
             ses_region,
             ses_from_email,
             ses_from_name,
             ses_configuration_set,
+            telemetry_endpoint,
+            backup,
+            export_storage,
+            jetstream,
         })
     }
 }
@@ -189,4 +557,57 @@ mod tests {
         std::env::remove_var("SES_CONFIGURATION_SET");
         std::env::remove_var("JWT_SECRET");
     }
+
+    #[test]
+    fn test_parse_backup_key() {
+        let key = parse_backup_key("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=").unwrap();
+        assert_eq!(key[0], 0);
+        assert_eq!(key[31], 31);
+        assert!(parse_backup_key("AAEC").is_err());
+        assert!(parse_backup_key("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_parse_stream_limits() {
+        let limits =
+            parse_stream_limits("SAZINKA_EXPORT_JOBS:max_messages=2000,max_bytes=500MB; SAZINKA_EMAIL_JOBS:max_bytes=1GiB")
+                .unwrap();
+        assert_eq!(
+            limits["SAZINKA_EXPORT_JOBS"],
+            StreamLimitOverride { max_messages: Some(2000), max_bytes: Some(500 * 1024 * 1024) }
+        );
+        assert_eq!(limits["SAZINKA_EMAIL_JOBS"].max_messages, None);
+        assert_eq!(limits["SAZINKA_EMAIL_JOBS"].max_bytes, Some(1024 * 1024 * 1024));
+        assert!(parse_stream_limits("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_stream_limits_rejects_invalid() {
+        assert!(parse_stream_limits("SAZINKA_JOBS").is_err());
+        assert!(parse_stream_limits("SAZINKA_JOBS:max_messages=0").is_err());
+        assert!(parse_stream_limits("SAZINKA_JOBS:max_bytes=10KB").is_err());
+        assert!(parse_stream_limits("SAZINKA_JOBS:max_age=1h").is_err());
+        assert!(parse_stream_limits("SAZINKA_JOBS:max_messages=5;SAZINKA_JOBS:max_messages=6").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1048576").unwrap(), 1024 * 1024);
+        assert_eq!(parse_size("512kb").unwrap(), 512 * 1024);
+        assert_eq!(parse_size("2 GB").unwrap(), 2 * 1024 * 1024 * 1024);
+        assert!(parse_size("lots").is_err());
+    }
+
+    #[test]
+    fn test_backup_config_debug_redacts_key() {
+        let config = BackupConfig {
+            key: [7; 32],
+            storage: None,
+            interval: Duration::from_secs(3600),
+            retention: 3,
+        };
+        let debug = format!("{:?}", config);
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains("7, 7"));
+    }
 }
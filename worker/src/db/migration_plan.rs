@@ -0,0 +1,753 @@
+//! Migration plan and pre-flight schema diff
+//!
+//! Before migrating, the worker compares `_sqlx_migrations` with the
+//! migrations compiled into the binary: pending migrations, applied
+//! migrations whose stored checksum differs (CRLF/LF checkouts) and applied
+//! versions whose file no longer exists (orphans). Fixing the last two
+//! rewrites the migration table and is only done when reconciliation is
+//! explicitly allowed.
+//!
+//! The pre-flight diff reads the statements of the pending migrations and
+//! checks each schema change against the live database, so a dry run shows
+//! what is created, what already exists and what drops data.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+use anyhow::Result;
+use sqlx::migrate::Migrator;
+use sqlx::PgPool;
+
+/// State of the migration table against the compiled migrations
+#[derive(Debug, Clone, Default)]
+pub struct MigrationPlan {
+    pub applied: Vec<i64>,
+    /// (version, description) of migrations that would run, in order
+    pub pending: Vec<(i64, String)>,
+    /// (version, description) of applied migrations whose checksum would be updated
+    pub checksum_fixes: Vec<(i64, String)>,
+    /// Applied versions with no compiled migration; their records would be deleted
+    pub orphans: Vec<i64>,
+}
+
+impl MigrationPlan {
+    /// Whether migrating would rewrite `_sqlx_migrations`
+    pub fn needs_reconcile(&self) -> bool {
+        !self.checksum_fixes.is_empty() || !self.orphans.is_empty()
+    }
+}
+
+pub async fn migration_table_exists(pool: &PgPool) -> Result<bool> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_name = '_sqlx_migrations')"
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(exists)
+}
+
+/// Compare `_sqlx_migrations` with the compiled migrations. Read-only.
+pub async fn plan(pool: &PgPool, migrator: &Migrator) -> Result<MigrationPlan> {
+    let stored: Vec<(i64, Vec<u8>)> = if migration_table_exists(pool).await? {
+        sqlx::query_as("SELECT version, checksum FROM _sqlx_migrations ORDER BY version")
+            .fetch_all(pool)
+            .await?
+    } else {
+        Vec::new()
+    };
+    let stored: HashMap<i64, Vec<u8>> = stored.into_iter().collect();
+
+    let mut plan = MigrationPlan::default();
+    let mut compiled = HashSet::new();
+    for migration in migrator.iter().filter(|m| !m.migration_type.is_down_migration()) {
+        compiled.insert(migration.version);
+        match stored.get(&migration.version) {
+            None => plan.pending.push((migration.version, migration.description.to_string())),
+            Some(checksum) if checksum.as_slice() != &*migration.checksum => {
+                plan.checksum_fixes.push((migration.version, migration.description.to_string()))
+            }
+            Some(_) => {}
+        }
+    }
+    plan.applied = stored.keys().copied().collect();
+    plan.applied.sort_unstable();
+    plan.orphans = plan.applied.iter().copied().filter(|v| !compiled.contains(v)).collect();
+
+    Ok(plan)
+}
+
+/// What a statement of a pending migration does to the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    CreateTable { table: String, columns: Vec<String>, if_not_exists: bool },
+    DropTable { table: String },
+    AddColumn { table: String, column: String, if_not_exists: bool },
+    DropColumn { table: String, column: String },
+    AlterColumn { table: String, column: String },
+    CreateIndex { index: String, table: String, if_not_exists: bool },
+    DropIndex { index: String },
+    Truncate { table: String },
+    DeleteRows { table: String },
+    UpdateRows { table: String },
+    /// Any other statement, by its leading keywords
+    Other { head: String },
+}
+
+impl SchemaChange {
+    fn describe(&self) -> String {
+        match self {
+            SchemaChange::CreateTable { table, .. } => format!("create table {}", table),
+            SchemaChange::DropTable { table } => format!("drop table {}", table),
+            SchemaChange::AddColumn { table, column, .. } => format!("add column {}.{}", table, column),
+            SchemaChange::DropColumn { table, column } => format!("drop column {}.{}", table, column),
+            SchemaChange::AlterColumn { table, column } => format!("alter column {}.{}", table, column),
+            SchemaChange::CreateIndex { index, table, .. } => format!("create index {} on {}", index, table),
+            SchemaChange::DropIndex { index } => format!("drop index {}", index),
+            SchemaChange::Truncate { table } => format!("truncate {}", table),
+            SchemaChange::DeleteRows { table } => format!("delete rows from {}", table),
+            SchemaChange::UpdateRows { table } => format!("update rows of {}", table),
+            SchemaChange::Other { head } => head.to_lowercase(),
+        }
+    }
+}
+
+/// Split SQL into statements at top-level semicolons, skipping comments,
+/// string literals and dollar-quoted function bodies
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                current.push(' ');
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+                current.push(' ');
+                continue;
+            }
+            '\'' | '"' => {
+                current.push(c);
+                i += 1;
+                while i < chars.len() {
+                    current.push(chars[i]);
+                    i += 1;
+                    if chars[i - 1] == c {
+                        break;
+                    }
+                }
+                continue;
+            }
+            '$' => {
+                // $tag$ ... $tag$
+                let tag_end = chars[i + 1..]
+                    .iter()
+                    .position(|ch| !(ch.is_alphanumeric() || *ch == '_'))
+                    .map(|p| i + 1 + p);
+                if let Some(end) = tag_end.filter(|&end| chars[end] == '$') {
+                    let tag: String = chars[i..=end].iter().collect();
+                    let body_start = end + 1;
+                    let rest: String = chars[body_start..].iter().collect();
+                    let close = rest.find(&tag).map(|p| rest[..p].chars().count() + tag.chars().count());
+                    let consumed = close.unwrap_or(rest.chars().count());
+                    current.push_str(&tag);
+                    current.extend(&chars[body_start..body_start + consumed]);
+                    i = body_start + consumed;
+                    continue;
+                }
+                current.push(c);
+            }
+            ';' => {
+                if !current.trim().is_empty() {
+                    statements.push(current.trim().to_string());
+                }
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+        i += 1;
+    }
+    if !current.trim().is_empty() {
+        statements.push(current.trim().to_string());
+    }
+    statements
+}
+
+/// Unquoted, schema-less, lowercase identifier
+fn ident(token: &str) -> String {
+    let token = token.trim_end_matches(['(', ',']);
+    let name = token.rsplit('.').next().unwrap_or(token);
+    name.trim_matches('"').to_lowercase()
+}
+
+/// Split at commas outside parentheses
+fn split_top_level(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut current = String::new();
+    for c in text.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    parts
+}
+
+/// Removes the leading keywords when the tokens start with them
+fn eat(tokens: &mut &[String], keywords: &[&str]) -> bool {
+    let matches = tokens.len() >= keywords.len()
+        && tokens.iter().zip(keywords).all(|(t, k)| t.eq_ignore_ascii_case(k));
+    if matches {
+        *tokens = &tokens[keywords.len()..];
+    }
+    matches
+}
+
+/// Keywords starting a table constraint rather than a column
+const CONSTRAINT_KEYWORDS: [&str; 7] = ["CONSTRAINT", "PRIMARY", "UNIQUE", "FOREIGN", "CHECK", "EXCLUDE", "LIKE"];
+
+fn alter_table_actions(table: &str, actions: &str) -> Vec<SchemaChange> {
+    split_top_level(actions)
+        .iter()
+        .map(|action| {
+            let owned: Vec<String> = action.split_whitespace().map(str::to_string).collect();
+            let mut t: &[String] = &owned;
+            let table = table.to_string();
+            let is_constraint = |t: &[String]| {
+                t.first().is_some_and(|k| CONSTRAINT_KEYWORDS.iter().any(|c| k.eq_ignore_ascii_case(c)))
+            };
+            if eat(&mut t, &["ADD"]) && !is_constraint(t) {
+                eat(&mut t, &["COLUMN"]);
+                let if_not_exists = eat(&mut t, &["IF", "NOT", "EXISTS"]);
+                let column = t.first().map(|c| ident(c)).unwrap_or_default();
+                return SchemaChange::AddColumn { table, column, if_not_exists };
+            }
+            let mut t: &[String] = &owned;
+            if eat(&mut t, &["DROP"]) && !is_constraint(t) {
+                eat(&mut t, &["COLUMN"]);
+                eat(&mut t, &["IF", "EXISTS"]);
+                return SchemaChange::DropColumn { table, column: t.first().map(|c| ident(c)).unwrap_or_default() };
+            }
+            let mut t: &[String] = &owned;
+            if eat(&mut t, &["ALTER"]) {
+                eat(&mut t, &["COLUMN"]);
+                return SchemaChange::AlterColumn { table, column: t.first().map(|c| ident(c)).unwrap_or_default() };
+            }
+            let head: Vec<&str> = owned.iter().take(2).map(String::as_str).collect();
+            SchemaChange::Other { head: format!("ALTER TABLE {} {}", table, head.join(" ")) }
+        })
+        .collect()
+}
+
+/// Column names defined in the body of a CREATE TABLE statement
+fn table_columns(statement: &str) -> Vec<String> {
+    let (Some(start), Some(end)) = (statement.find('('), statement.rfind(')')) else {
+        return Vec::new();
+    };
+    if end <= start {
+        return Vec::new();
+    }
+    split_top_level(&statement[start + 1..end])
+        .iter()
+        .filter_map(|part| part.split_whitespace().next())
+        .filter(|first| !CONSTRAINT_KEYWORDS.iter().any(|k| first.eq_ignore_ascii_case(k)))
+        .map(ident)
+        .collect()
+}
+
+/// Schema changes of one statement
+pub fn classify(statement: &str) -> Vec<SchemaChange> {
+    let owned: Vec<String> = statement.split_whitespace().map(str::to_string).collect();
+    let mut t: &[String] = &owned;
+    let name = |t: &[String]| t.first().map(|n| ident(n)).unwrap_or_default();
+
+    if eat(&mut t, &["CREATE", "TABLE"]) {
+        let if_not_exists = eat(&mut t, &["IF", "NOT", "EXISTS"]);
+        let table = t.first().map(|n| ident(n.split('(').next().unwrap_or(n))).unwrap_or_default();
+        return vec![SchemaChange::CreateTable { table, columns: table_columns(statement), if_not_exists }];
+    }
+    let mut t: &[String] = &owned;
+    if eat(&mut t, &["CREATE"]) {
+        eat(&mut t, &["UNIQUE"]);
+        if eat(&mut t, &["INDEX"]) {
+            eat(&mut t, &["CONCURRENTLY"]);
+            let if_not_exists = eat(&mut t, &["IF", "NOT", "EXISTS"]);
+            let index = if t.first().is_some_and(|k| k.eq_ignore_ascii_case("ON")) { String::new() } else { name(t) };
+            let table = t
+                .iter()
+                .position(|k| k.eq_ignore_ascii_case("ON"))
+                .map(|p| {
+                    let mut rest = &t[p + 1..];
+                    eat(&mut rest, &["ONLY"]);
+                    name(rest)
+                })
+                .unwrap_or_default();
+            return vec![SchemaChange::CreateIndex { index, table, if_not_exists }];
+        }
+    }
+    let mut t: &[String] = &owned;
+    if eat(&mut t, &["DROP", "TABLE"]) {
+        eat(&mut t, &["IF", "EXISTS"]);
+        let names = t.iter().take_while(|k| !k.eq_ignore_ascii_case("CASCADE") && !k.eq_ignore_ascii_case("RESTRICT"));
+        return names
+            .flat_map(|n| n.split(','))
+            .filter(|n| !n.is_empty())
+            .map(|n| SchemaChange::DropTable { table: ident(n) })
+            .collect();
+    }
+    let mut t: &[String] = &owned;
+    if eat(&mut t, &["DROP", "INDEX"]) {
+        eat(&mut t, &["CONCURRENTLY"]);
+        eat(&mut t, &["IF", "EXISTS"]);
+        return vec![SchemaChange::DropIndex { index: name(t) }];
+    }
+    let mut t: &[String] = &owned;
+    if eat(&mut t, &["ALTER", "TABLE"]) {
+        eat(&mut t, &["IF", "EXISTS"]);
+        eat(&mut t, &["ONLY"]);
+        let table = name(t);
+        let actions = t.get(1..).map(|rest| rest.join(" ")).unwrap_or_default();
+        return alter_table_actions(&table, &actions);
+    }
+    let mut t: &[String] = &owned;
+    if eat(&mut t, &["TRUNCATE"]) {
+        eat(&mut t, &["TABLE"]);
+        return vec![SchemaChange::Truncate { table: name(t) }];
+    }
+    let mut t: &[String] = &owned;
+    if eat(&mut t, &["DELETE", "FROM"]) {
+        return vec![SchemaChange::DeleteRows { table: name(t) }];
+    }
+    let mut t: &[String] = &owned;
+    if eat(&mut t, &["UPDATE"]) {
+        return vec![SchemaChange::UpdateRows { table: name(t) }];
+    }
+
+    let head: Vec<String> = owned.iter().take(3).map(|k| k.to_uppercase()).collect();
+    vec![SchemaChange::Other { head: head.join(" ") }]
+}
+
+/// Tables, columns and indexes of the live database
+#[derive(Debug, Clone, Default)]
+pub struct SchemaSnapshot {
+    pub columns: HashMap<String, HashSet<String>>,
+    pub indexes: HashSet<String>,
+}
+
+impl SchemaSnapshot {
+    pub async fn load(pool: &PgPool) -> Result<Self> {
+        let columns: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT table_name::text, column_name::text FROM information_schema.columns
+            WHERE table_schema = current_schema()
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+        let indexes: Vec<String> = sqlx::query_scalar(
+            "SELECT indexname::text FROM pg_indexes WHERE schemaname = current_schema()"
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut snapshot = SchemaSnapshot { indexes: indexes.into_iter().collect(), ..Default::default() };
+        for (table, column) in columns {
+            snapshot.columns.entry(table).or_default().insert(column);
+        }
+        Ok(snapshot)
+    }
+
+    fn has_table(&self, table: &str) -> bool {
+        self.columns.contains_key(table)
+    }
+
+    fn has_column(&self, table: &str, column: &str) -> bool {
+        self.columns.get(table).is_some_and(|c| c.contains(column))
+    }
+}
+
+/// How a schema change relates to the live database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeStatus {
+    /// The change applies cleanly
+    Applies,
+    /// The object is already in the requested state and the statement is guarded
+    NoOp,
+    /// The statement will fail against the current schema
+    Conflict,
+    /// Existing data is removed or rewritten
+    Destructive,
+}
+
+/// One line of the pre-flight diff
+#[derive(Debug, Clone)]
+pub struct DiffEntry {
+    pub version: i64,
+    pub change: SchemaChange,
+    pub status: ChangeStatus,
+    pub note: Option<&'static str>,
+}
+
+/// Check a change against the schema and record its effect there, so later
+/// statements of the same run see the tables it creates
+fn check_change(snapshot: &mut SchemaSnapshot, change: &SchemaChange) -> (ChangeStatus, Option<&'static str>) {
+    use ChangeStatus::*;
+    match change {
+        SchemaChange::CreateTable { table, columns, if_not_exists } => {
+            if snapshot.has_table(table) {
+                return if *if_not_exists { (NoOp, Some("already exists")) } else { (Conflict, Some("table already exists")) };
+            }
+            snapshot.columns.insert(table.clone(), columns.iter().cloned().collect());
+            (Applies, None)
+        }
+        SchemaChange::DropTable { table } => {
+            if snapshot.columns.remove(table).is_some() {
+                (Destructive, Some("drops the table and its rows"))
+            } else {
+                (NoOp, Some("table does not exist"))
+            }
+        }
+        SchemaChange::AddColumn { table, column, if_not_exists } => {
+            if !snapshot.has_table(table) {
+                (Conflict, Some("table does not exist"))
+            } else if snapshot.has_column(table, column) {
+                if *if_not_exists { (NoOp, Some("already exists")) } else { (Conflict, Some("column already exists")) }
+            } else {
+                snapshot.columns.entry(table.clone()).or_default().insert(column.clone());
+                (Applies, None)
+            }
+        }
+        SchemaChange::DropColumn { table, column } => {
+            if snapshot.columns.get_mut(table).is_some_and(|c| c.remove(column)) {
+                (Destructive, Some("drops the column and its values"))
+            } else {
+                (NoOp, Some("column does not exist"))
+            }
+        }
+        SchemaChange::AlterColumn { table, column } => {
+            if snapshot.has_column(table, column) {
+                (Applies, None)
+            } else if !snapshot.has_table(table) {
+                (Conflict, Some("table does not exist"))
+            } else {
+                (Conflict, Some("column does not exist"))
+            }
+        }
+        SchemaChange::CreateIndex { index, if_not_exists, .. } => {
+            if !index.is_empty() && snapshot.indexes.contains(index) {
+                if *if_not_exists { (NoOp, Some("already exists")) } else { (Conflict, Some("index already exists")) }
+            } else {
+                snapshot.indexes.insert(index.clone());
+                (Applies, None)
+            }
+        }
+        SchemaChange::DropIndex { index } => {
+            if snapshot.indexes.remove(index) { (Applies, None) } else { (NoOp, Some("index does not exist")) }
+        }
+        SchemaChange::Truncate { .. } | SchemaChange::DeleteRows { .. } => (Destructive, Some("removes rows")),
+        SchemaChange::UpdateRows { .. } => (Applies, Some("rewrites rows")),
+        SchemaChange::Other { .. } => (Applies, None),
+    }
+}
+
+/// Schema changes of the pending migrations, checked in order against the live schema
+pub fn schema_diff(migrator: &Migrator, plan: &MigrationPlan, mut snapshot: SchemaSnapshot) -> Vec<DiffEntry> {
+    let pending: HashSet<i64> = plan.pending.iter().map(|(v, _)| *v).collect();
+    let mut entries = Vec::new();
+    for migration in migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration() && pending.contains(&m.version))
+    {
+        for statement in split_statements(&migration.sql) {
+            for change in classify(&statement) {
+                let (status, note) = check_change(&mut snapshot, &change);
+                entries.push(DiffEntry { version: migration.version, change, status, note });
+            }
+        }
+    }
+    entries
+}
+
+/// Human-readable dry-run report
+pub fn report(plan: &MigrationPlan, diff: &[DiffEntry]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Applied migrations: {}", plan.applied.len());
+
+    let _ = writeln!(out, "\nPending migrations ({}):", plan.pending.len());
+    for (version, description) in &plan.pending {
+        let _ = writeln!(out, "  {:03} {}", version, description);
+    }
+
+    let _ = writeln!(out, "\nChecksum fixes ({}), require --reconcile:", plan.checksum_fixes.len());
+    for (version, description) in &plan.checksum_fixes {
+        let _ = writeln!(out, "  {:03} {}: stored checksum would be replaced", version, description);
+    }
+
+    let _ = writeln!(out, "\nOrphaned migration records ({}), require --reconcile:", plan.orphans.len());
+    for version in &plan.orphans {
+        let _ = writeln!(out, "  {:03}: record would be deleted (no migration file)", version);
+    }
+
+    let _ = writeln!(out, "\nSchema diff of pending migrations:");
+    if diff.is_empty() {
+        let _ = writeln!(out, "  (no changes)");
+    }
+    for entry in diff {
+        let marker = match entry.status {
+            ChangeStatus::Applies => "+",
+            ChangeStatus::NoOp => "=",
+            ChangeStatus::Conflict => "!",
+            ChangeStatus::Destructive => "-",
+        };
+        let note = entry.note.map(|n| format!(" ({})", n)).unwrap_or_default();
+        let _ = writeln!(out, "  {} {:03} {}{}", marker, entry.version, entry.change.describe(), note);
+    }
+
+    let conflicts = diff.iter().filter(|e| e.status == ChangeStatus::Conflict).count();
+    let destructive = diff.iter().filter(|e| e.status == ChangeStatus::Destructive).count();
+    let _ = writeln!(out, "\n{} conflicting and {} destructive changes", conflicts, destructive);
+    out
+}
+
+/// One applied migration a rollback reverts
+#[derive(Debug, Clone)]
+pub struct RollbackStep {
+    pub version: i64,
+    pub description: String,
+    /// Whether the migration has a `.down.sql` counterpart
+    pub reversible: bool,
+    /// Data its down migration deletes, e.g. "drop table webhooks (12 rows)"
+    pub data_loss: Vec<String>,
+}
+
+/// Applied migrations newer than the target, newest first
+#[derive(Debug, Clone)]
+pub struct RollbackPlan {
+    pub target: i64,
+    pub steps: Vec<RollbackStep>,
+}
+
+impl RollbackPlan {
+    /// Versions that cannot be reverted
+    pub fn irreversible(&self) -> Vec<i64> {
+        self.steps.iter().filter(|s| !s.reversible).map(|s| s.version).collect()
+    }
+
+    pub fn loses_data(&self) -> bool {
+        self.steps.iter().any(|s| !s.data_loss.is_empty())
+    }
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Rows a destructive change would remove from the live database
+async fn affected_rows(pool: &PgPool, change: &SchemaChange) -> Result<i64> {
+    let sql = match change {
+        SchemaChange::DropTable { table } | SchemaChange::Truncate { table } | SchemaChange::DeleteRows { table } => {
+            format!("SELECT COUNT(*) FROM {}", quote_ident(table))
+        }
+        SchemaChange::DropColumn { table, column } => format!(
+            "SELECT COUNT(*) FROM {} WHERE {} IS NOT NULL",
+            quote_ident(table),
+            quote_ident(column)
+        ),
+        _ => return Ok(0),
+    };
+    Ok(sqlx::query_scalar(&sql).fetch_one(pool).await?)
+}
+
+/// What rolling back to `target` would revert and which data it would
+/// delete. `target` stays applied; 0 reverts everything. Read-only.
+pub async fn plan_rollback(pool: &PgPool, migrator: &Migrator, target: i64) -> Result<RollbackPlan> {
+    let plan = plan(pool, migrator).await?;
+    if target != 0 && !plan.applied.contains(&target) {
+        anyhow::bail!("Migration {} is not applied", target);
+    }
+
+    let mut snapshot = SchemaSnapshot::load(pool).await?;
+    let mut steps = Vec::new();
+    for &version in plan.applied.iter().rev().filter(|&&v| v > target) {
+        let up = migrator.iter().find(|m| m.version == version && !m.migration_type.is_down_migration());
+        let down = migrator.iter().find(|m| m.version == version && m.migration_type.is_down_migration());
+        let mut step = RollbackStep {
+            version,
+            description: up.or(down).map(|m| m.description.to_string()).unwrap_or_default(),
+            reversible: down.is_some(),
+            data_loss: Vec::new(),
+        };
+        for change in down.map(|m| split_statements(&m.sql)).unwrap_or_default().iter().flat_map(|s| classify(s)) {
+            // Rows are counted before the change is recorded in the snapshot
+            let exists = match &change {
+                SchemaChange::DropColumn { table, column } => snapshot.has_column(table, column),
+                SchemaChange::DropTable { table } | SchemaChange::Truncate { table } | SchemaChange::DeleteRows { table } => {
+                    snapshot.has_table(table)
+                }
+                _ => false,
+            };
+            let rows = if exists { affected_rows(pool, &change).await? } else { 0 };
+            let (status, _) = check_change(&mut snapshot, &change);
+            if status == ChangeStatus::Destructive && rows > 0 {
+                step.data_loss.push(format!("{} ({} rows)", change.describe(), rows));
+            }
+        }
+        steps.push(step);
+    }
+
+    Ok(RollbackPlan { target, steps })
+}
+
+/// Human-readable rollback report
+pub fn rollback_report(plan: &RollbackPlan) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Rollback to {:03}: {} migrations to revert", plan.target, plan.steps.len());
+    for step in &plan.steps {
+        let marker = if step.reversible { " " } else { "!" };
+        let _ = writeln!(out, "{} {:03} {}", marker, step.version, step.description);
+        if !step.reversible {
+            let _ = writeln!(out, "      no down migration");
+        }
+        for loss in &step.data_loss {
+            let _ = writeln!(out, "      - {}", loss);
+        }
+    }
+    if !plan.irreversible().is_empty() {
+        let _ = writeln!(out, "\nIrreversible migrations {:?}; restore a backup instead", plan.irreversible());
+    } else if plan.loses_data() {
+        let _ = writeln!(out, "\nThe rollback deletes data; it needs --force");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statements_split_outside_strings_comments_and_bodies() {
+        let sql = r#"
+            -- comment; not a statement
+            CREATE TABLE a (note TEXT DEFAULT 'x;y');
+            /* block; comment */
+            CREATE FUNCTION f() RETURNS trigger AS $$ BEGIN NEW.a := 1; RETURN NEW; END; $$ LANGUAGE plpgsql;
+            ALTER TABLE a ADD COLUMN b INT
+        "#;
+        let statements = split_statements(sql);
+        assert_eq!(statements.len(), 3);
+        assert!(statements[0].contains("'x;y'"));
+        assert!(statements[1].ends_with("LANGUAGE plpgsql"));
+    }
+
+    #[test]
+    fn statements_are_classified() {
+        assert_eq!(
+            classify("CREATE TABLE IF NOT EXISTS public.\"devices\"(id UUID, code TEXT, PRIMARY KEY (id, code))"),
+            vec![SchemaChange::CreateTable {
+                table: "devices".into(),
+                columns: vec!["id".into(), "code".into()],
+                if_not_exists: true,
+            }]
+        );
+        assert_eq!(
+            classify("ALTER TABLE devices ADD COLUMN IF NOT EXISTS code TEXT, DROP COLUMN old_code, ADD CONSTRAINT x CHECK (a > 0)"),
+            vec![
+                SchemaChange::AddColumn { table: "devices".into(), column: "code".into(), if_not_exists: true },
+                SchemaChange::DropColumn { table: "devices".into(), column: "old_code".into() },
+                SchemaChange::Other { head: "ALTER TABLE devices ADD CONSTRAINT".into() },
+            ]
+        );
+        assert_eq!(
+            classify("CREATE UNIQUE INDEX idx_a ON ONLY devices (code)"),
+            vec![SchemaChange::CreateIndex { index: "idx_a".into(), table: "devices".into(), if_not_exists: false }]
+        );
+        assert_eq!(
+            classify("DROP TABLE IF EXISTS a, b CASCADE"),
+            vec![SchemaChange::DropTable { table: "a".into() }, SchemaChange::DropTable { table: "b".into() }]
+        );
+        assert_eq!(classify("DELETE FROM revisions WHERE x"), vec![SchemaChange::DeleteRows { table: "revisions".into() }]);
+    }
+
+    #[test]
+    fn changes_are_checked_in_order_against_the_schema() {
+        let mut snapshot = SchemaSnapshot::default();
+        snapshot.columns.insert("devices".into(), ["id".to_string(), "code".to_string()].into_iter().collect());
+
+        let add_existing = SchemaChange::AddColumn { table: "devices".into(), column: "code".into(), if_not_exists: false };
+        assert_eq!(check_change(&mut snapshot, &add_existing).0, ChangeStatus::Conflict);
+
+        let create = SchemaChange::CreateTable { table: "tags".into(), columns: vec!["id".into()], if_not_exists: false };
+        assert_eq!(check_change(&mut snapshot, &create).0, ChangeStatus::Applies);
+        let alter = SchemaChange::AlterColumn { table: "tags".into(), column: "id".into() };
+        assert_eq!(check_change(&mut snapshot, &alter).0, ChangeStatus::Applies);
+        let add_new = SchemaChange::AddColumn { table: "tags".into(), column: "name".into(), if_not_exists: false };
+        assert_eq!(check_change(&mut snapshot, &add_new).0, ChangeStatus::Applies);
+
+        let drop = SchemaChange::DropColumn { table: "devices".into(), column: "code".into() };
+        assert_eq!(check_change(&mut snapshot, &drop).0, ChangeStatus::Destructive);
+        assert_eq!(check_change(&mut snapshot, &drop).0, ChangeStatus::NoOp);
+    }
+
+    #[test]
+    fn rollback_reports_irreversible_and_destructive_steps() {
+        let step = |version, reversible, data_loss: Vec<String>| RollbackStep {
+            version,
+            description: format!("m{}", version),
+            reversible,
+            data_loss,
+        };
+        let plan = RollbackPlan {
+            target: 81,
+            steps: vec![step(83, true, vec!["drop table device_transfers (3 rows)".into()]), step(82, true, vec![])],
+        };
+        assert!(plan.irreversible().is_empty());
+        assert!(plan.loses_data());
+        assert!(rollback_report(&plan).contains("needs --force"));
+
+        let plan = RollbackPlan { target: 0, steps: vec![step(1, false, vec![])] };
+        assert_eq!(plan.irreversible(), vec![1]);
+        assert!(rollback_report(&plan).contains("restore a backup"));
+    }
+
+    #[test]
+    fn repo_down_migrations_match_their_versions() {
+        let migrator = sqlx::migrate!("./migrations");
+        for down in migrator.iter().filter(|m| m.migration_type.is_down_migration()) {
+            assert!(
+                migrator.iter().any(|m| m.version == down.version && !m.migration_type.is_down_migration()),
+                "down migration {} has no up migration",
+                down.version
+            );
+            assert!(!split_statements(&down.sql).is_empty());
+        }
+    }
+
+    #[test]
+    fn reconcile_is_needed_only_for_table_rewrites() {
+        let mut plan = MigrationPlan { pending: vec![(85, "x".into())], ..Default::default() };
+        assert!(!plan.needs_reconcile());
+        plan.orphans.push(14);
+        assert!(plan.needs_reconcile());
+        assert!(report(&plan, &[]).contains("014: record would be deleted"));
+    }
+}
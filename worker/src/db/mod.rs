@@ -1,6 +1,10 @@
 //! Database module
 
+pub mod migration_plan;
 pub mod queries;
+pub mod repo;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 
 use anyhow::Result;
 use sqlx::postgres::PgPoolOptions;
@@ -17,13 +21,20 @@ pub async fn create_pool(database_url: &str) -> Result<PgPool> {
     Ok(pool)
 }
 
+/// Whether startup may rewrite `_sqlx_migrations` (`MIGRATE_RECONCILE=true`)
+pub fn reconcile_from_env() -> bool {
+    std::env::var("MIGRATE_RECONCILE").is_ok_and(|v| v.eq_ignore_ascii_case("true") || v == "1")
+}
+
 /// Run database migrations.
 ///
-/// Before running, synchronizes `_sqlx_migrations` with the compiled
-/// migration list:
+/// Before running, compares `_sqlx_migrations` with the compiled migration
+/// list. Synchronizing it rewrites the table, so it only happens with
+/// `reconcile`; otherwise the worker refuses to migrate and points to
+/// `migrate --dry-run`:
 /// 1. Removes orphaned records (applied versions whose files no longer exist).
 /// 2. Fixes checksum mismatches (CRLF/LF differences across platforms).
-pub async fn run_migrations(pool: &PgPool) -> Result<()> {
+pub async fn run_migrations(pool: &PgPool, reconcile: bool) -> Result<()> {
     info!("Running database migrations...");
 
     let migrator = sqlx::migrate!("./migrations");
@@ -38,22 +49,76 @@ pub async fn run_migrations(pool: &PgPool) -> Result<()> {
     let applied_versions = get_applied_versions(pool).await?;
     info!("DB applied migration versions: {:?}", applied_versions);
 
-    remove_orphaned_migrations(pool, &compiled_versions, &applied_versions).await?;
-    fix_migration_checksums(pool, &migrator).await?;
+    let plan = migration_plan::plan(pool, &migrator).await?;
+    if plan.needs_reconcile() {
+        if !reconcile {
+            anyhow::bail!(
+                "_sqlx_migrations needs reconciliation ({} checksum mismatches, orphaned versions {:?}); \
+                 review it with `sazinka-worker migrate --dry-run` and apply it with \
+                 `sazinka-worker migrate --reconcile` or MIGRATE_RECONCILE=true",
+                plan.checksum_fixes.len(),
+                plan.orphans
+            );
+        }
+        remove_orphaned_migrations(pool, &compiled_versions, &applied_versions).await?;
+        fix_migration_checksums(pool, &migrator).await?;
+    }
     migrator.run(pool).await?;
 
     info!("Database migrations complete");
     Ok(())
 }
 
-async fn get_applied_versions(pool: &PgPool) -> Result<Vec<i64>> {
-    let table_exists: bool = sqlx::query_scalar(
-        "SELECT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_name = '_sqlx_migrations')"
-    )
-    .fetch_one(pool)
-    .await?;
+/// Report what `run_migrations` would do, without changing anything
+pub async fn dry_run_migrations(pool: &PgPool) -> Result<String> {
+    let migrator = sqlx::migrate!("./migrations");
+    let plan = migration_plan::plan(pool, &migrator).await?;
+    let snapshot = migration_plan::SchemaSnapshot::load(pool).await?;
+    let diff = migration_plan::schema_diff(&migrator, &plan, snapshot);
+    Ok(migration_plan::report(&plan, &diff))
+}
+
+/// Revert the applied migrations newer than `target` with their down
+/// migrations. Refuses when one of them has none, or when the rollback
+/// would delete data and `force` is not set.
+pub async fn rollback_migrations(pool: &PgPool, target: i64, force: bool) -> Result<()> {
+    let migrator = sqlx::migrate!("./migrations");
+    let plan = migration_plan::plan_rollback(pool, &migrator, target).await?;
+    if plan.steps.is_empty() {
+        info!("No migrations newer than {} are applied", target);
+        return Ok(());
+    }
+
+    let irreversible = plan.irreversible();
+    if !irreversible.is_empty() {
+        anyhow::bail!("Migrations {:?} have no down migration; restore a backup instead", irreversible);
+    }
+    if plan.loses_data() && !force {
+        anyhow::bail!(
+            "Rolling back to {} would delete data; re-run with --force to accept:\n{}",
+            target,
+            migration_plan::rollback_report(&plan)
+        );
+    }
+
+    for step in &plan.steps {
+        warn!("Reverting migration {} ({})", step.version, step.description);
+    }
+    migrator.undo(pool, target).await?;
+
+    info!("Rolled back to migration {}", target);
+    Ok(())
+}
 
-    if !table_exists {
+/// Report what `rollback_migrations` would revert, without changing anything
+pub async fn dry_run_rollback(pool: &PgPool, target: i64) -> Result<String> {
+    let migrator = sqlx::migrate!("./migrations");
+    let plan = migration_plan::plan_rollback(pool, &migrator, target).await?;
+    Ok(migration_plan::rollback_report(&plan))
+}
+
+async fn get_applied_versions(pool: &PgPool) -> Result<Vec<i64>> {
+    if !migration_plan::migration_table_exists(pool).await? {
         return Ok(vec![]);
     }
 
@@ -93,13 +158,7 @@ async fn remove_orphaned_migrations(
 /// embedded in the current binary. Handles CRLF/LF line-ending differences
 /// across platforms.
 async fn fix_migration_checksums(pool: &PgPool, migrator: &sqlx::migrate::Migrator) -> Result<()> {
-    let table_exists: bool = sqlx::query_scalar(
-        "SELECT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_name = '_sqlx_migrations')"
-    )
-    .fetch_one(pool)
-    .await?;
-
-    if !table_exists {
+    if !migration_plan::migration_table_exists(pool).await? {
         return Ok(());
     }
 
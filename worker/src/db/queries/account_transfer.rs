@@ -0,0 +1,28 @@
+//! Account transfer queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Email and owner of a user (owner is None for account owners)
+pub async fn get_user_email_and_owner(pool: &PgPool, user_id: Uuid) -> Result<Option<(String, Option<Uuid>)>> {
+    let row: Option<(String, Option<Uuid>)> = sqlx::query_as("SELECT email, owner_id FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row)
+}
+
+/// Existing users with any of `emails` (compared case-insensitively):
+/// (email, id, owner_id)
+pub async fn find_users_by_email(pool: &PgPool, emails: &[String]) -> Result<Vec<(String, Uuid, Option<Uuid>)>> {
+    let lowered: Vec<String> = emails.iter().map(|e| e.to_lowercase()).collect();
+    let rows: Vec<(String, Uuid, Option<Uuid>)> =
+        sqlx::query_as("SELECT email, id, owner_id FROM users WHERE LOWER(email) = ANY($1)")
+            .bind(&lowered)
+            .fetch_all(pool)
+            .await?;
+
+    Ok(rows)
+}
@@ -0,0 +1,211 @@
+#![allow(dead_code)]
+//! Admin user management database queries
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::admin_user::{AdminAuditEntry, AdminUserSummary, ListAdminUsersRequest};
+
+/// Default page size for the user list
+const DEFAULT_USER_LIMIT: i64 = 50;
+/// Upper bound for page size
+const MAX_USER_LIMIT: i64 = 500;
+
+/// Filter shared by the list and count queries
+/// ($1 search, $2 role, $3 include disabled, $4 email verified)
+const USER_FILTER: &str = r#"
+    ($1::text IS NULL
+        OR u.email ILIKE '%' || $1 || '%'
+        OR u.name ILIKE '%' || $1 || '%'
+        OR u.business_name ILIKE '%' || $1 || '%')
+    AND ($2::text IS NULL OR u.role = $2)
+    AND ($3 OR u.disabled_at IS NULL)
+    AND ($4::boolean IS NULL OR u.email_verified = $4)
+"#;
+
+/// List users with activity and usage figures
+pub async fn list_users(pool: &PgPool, req: &ListAdminUsersRequest) -> Result<(Vec<AdminUserSummary>, i64)> {
+    let search = req.search.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    let include_disabled = req.include_disabled.unwrap_or(true);
+    let limit = req.limit.unwrap_or(DEFAULT_USER_LIMIT).clamp(1, MAX_USER_LIMIT);
+    let offset = req.offset.unwrap_or(0).max(0);
+
+    let query = format!(
+        r#"
+        SELECT
+            u.id, u.email, u.name, u.business_name, u.role, u.owner_id, u.plan,
+            u.email_verified, u.disabled_at, u.last_login_at,
+            GREATEST(
+                u.last_login_at,
+                (SELECT MAX(c.updated_at) FROM customers c WHERE c.user_id = u.id),
+                (SELECT MAX(v.updated_at) FROM visits v WHERE v.user_id = u.id)
+            ) AS last_activity_at,
+            (SELECT COUNT(*) FROM customers c WHERE c.user_id = u.id) AS customer_count,
+            (SELECT COUNT(*) FROM visits v
+              WHERE v.user_id = u.id AND v.created_at > NOW() - INTERVAL '30 days') AS visit_count_30d,
+            (SELECT COUNT(*) FROM users w WHERE w.owner_id = u.id) AS worker_count,
+            u.created_at
+        FROM users u
+        WHERE {}
+        ORDER BY u.created_at DESC
+        LIMIT $5 OFFSET $6
+        "#,
+        USER_FILTER
+    );
+
+    let items = sqlx::query_as::<_, AdminUserSummary>(&query)
+        .bind(search)
+        .bind(&req.role)
+        .bind(include_disabled)
+        .bind(req.email_verified)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+    let count_query = format!("SELECT COUNT(*) FROM users u WHERE {}", USER_FILTER);
+    let (total,): (i64,) = sqlx::query_as(&count_query)
+        .bind(search)
+        .bind(&req.role)
+        .bind(include_disabled)
+        .bind(req.email_verified)
+        .fetch_one(pool)
+        .await?;
+
+    Ok((items, total))
+}
+
+/// Disable or re-enable an account. Returns false if the user does not exist.
+pub async fn set_disabled(pool: &PgPool, user_id: Uuid, disabled: bool) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE users
+        SET disabled_at = CASE WHEN $2 THEN COALESCE(disabled_at, NOW()) ELSE NULL END,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(disabled)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Change a user's role, returning the previous one
+pub async fn set_role(pool: &PgPool, user_id: Uuid, role: &str) -> Result<Option<String>> {
+    let previous: Option<(String,)> = sqlx::query_as(
+        r#"
+        UPDATE users u
+        SET role = $2, updated_at = NOW()
+        FROM (SELECT id, role FROM users WHERE id = $1 FOR UPDATE) old
+        WHERE u.id = old.id
+        RETURNING old.role
+        "#,
+    )
+    .bind(user_id)
+    .bind(role)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(previous.map(|(r,)| r))
+}
+
+/// Store a password reset token hash. Returns (email, locale) of the user.
+pub async fn set_password_reset_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    token_hash: &str,
+    expires: DateTime<Utc>,
+) -> Result<Option<(String, String)>> {
+    let row: Option<(String, String)> = sqlx::query_as(
+        r#"
+        UPDATE users
+        SET password_reset_token_hash = $2, password_reset_expires = $3
+        WHERE id = $1
+        RETURNING email, locale
+        "#,
+    )
+    .bind(user_id)
+    .bind(token_hash)
+    .bind(expires)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row)
+}
+
+/// Issue a fresh email verification token. Returns (email, locale) of the user,
+/// or None if the user does not exist or is already verified.
+pub async fn set_verification_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    token_hash: &str,
+    expires: DateTime<Utc>,
+) -> Result<Option<(String, String)>> {
+    let row: Option<(String, String)> = sqlx::query_as(
+        r#"
+        UPDATE users
+        SET verification_token_hash = $2, verification_expires = $3
+        WHERE id = $1 AND email_verified = false
+        RETURNING email, locale
+        "#,
+    )
+    .bind(user_id)
+    .bind(token_hash)
+    .bind(expires)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row)
+}
+
+/// Append an audit log entry
+pub async fn insert_audit(
+    pool: &PgPool,
+    admin_id: Uuid,
+    action: &str,
+    target_user_id: Option<Uuid>,
+    details: serde_json::Value,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO admin_audit_log (admin_id, action, target_user_id, details)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(admin_id)
+    .bind(action)
+    .bind(target_user_id)
+    .bind(details)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// List audit log entries, newest first
+pub async fn list_audit(pool: &PgPool, target_user_id: Option<Uuid>, limit: i64) -> Result<Vec<AdminAuditEntry>> {
+    let items = sqlx::query_as::<_, AdminAuditEntry>(
+        r#"
+        SELECT
+            l.id, l.admin_id, a.email AS admin_email, l.action,
+            l.target_user_id, t.email AS target_email, l.details, l.created_at
+        FROM admin_audit_log l
+        LEFT JOIN users a ON a.id = l.admin_id
+        LEFT JOIN users t ON t.id = l.target_user_id
+        WHERE ($1::uuid IS NULL OR l.target_user_id = $1)
+        ORDER BY l.created_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(target_user_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
@@ -0,0 +1,224 @@
+#![allow(dead_code)]
+//! Automation rule database queries
+
+use anyhow::Result;
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::automation::{
+    AutomationRule, AutomationRun, CreateAutomationRuleRequest, UpdateAutomationRuleRequest,
+};
+use crate::types::RevisionStatus;
+
+const RULE_COLUMNS: &str = r#"
+    id, user_id, name, trigger, conditions, actions, enabled, created_at, updated_at
+"#;
+
+/// Most overdue revisions a rule evaluates per scan; the rest follow on the next scan
+const OVERDUE_BATCH_LIMIT: i64 = 200;
+
+/// Create a rule
+pub async fn create_rule(
+    pool: &PgPool,
+    user_id: Uuid,
+    req: &CreateAutomationRuleRequest,
+) -> Result<AutomationRule> {
+    let query = format!(
+        r#"
+        INSERT INTO automation_rules (user_id, name, trigger, conditions, actions, enabled)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {}
+        "#,
+        RULE_COLUMNS
+    );
+
+    let rule = sqlx::query_as::<_, AutomationRule>(&query)
+        .bind(user_id)
+        .bind(req.name.trim())
+        .bind(&req.trigger)
+        .bind(Json(&req.conditions))
+        .bind(Json(&req.actions))
+        .bind(req.enabled.unwrap_or(true))
+        .fetch_one(pool)
+        .await?;
+
+    Ok(rule)
+}
+
+/// List rules of a user
+pub async fn list_rules(pool: &PgPool, user_id: Uuid) -> Result<Vec<AutomationRule>> {
+    let query = format!(
+        "SELECT {} FROM automation_rules WHERE user_id = $1 ORDER BY trigger, name, id",
+        RULE_COLUMNS
+    );
+
+    let rules = sqlx::query_as::<_, AutomationRule>(&query)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rules)
+}
+
+/// Enabled rules of a user for a trigger
+pub async fn list_enabled_for_trigger(pool: &PgPool, user_id: Uuid, trigger: &str) -> Result<Vec<AutomationRule>> {
+    let query = format!(
+        "SELECT {} FROM automation_rules WHERE user_id = $1 AND trigger = $2 AND enabled ORDER BY created_at, id",
+        RULE_COLUMNS
+    );
+
+    let rules = sqlx::query_as::<_, AutomationRule>(&query)
+        .bind(user_id)
+        .bind(trigger)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rules)
+}
+
+/// Enabled rules of all users for a trigger, for the scheduler
+pub async fn list_all_enabled_for_trigger(pool: &PgPool, trigger: &str) -> Result<Vec<AutomationRule>> {
+    let query = format!(
+        "SELECT {} FROM automation_rules WHERE trigger = $1 AND enabled ORDER BY user_id, created_at, id",
+        RULE_COLUMNS
+    );
+
+    let rules = sqlx::query_as::<_, AutomationRule>(&query)
+        .bind(trigger)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rules)
+}
+
+/// Update a rule (only provided fields change)
+pub async fn update_rule(
+    pool: &PgPool,
+    user_id: Uuid,
+    req: &UpdateAutomationRuleRequest,
+) -> Result<Option<AutomationRule>> {
+    let query = format!(
+        r#"
+        UPDATE automation_rules
+        SET
+            name = COALESCE($3, name),
+            trigger = COALESCE($4, trigger),
+            conditions = COALESCE($5, conditions),
+            actions = COALESCE($6, actions),
+            enabled = COALESCE($7, enabled),
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING {}
+        "#,
+        RULE_COLUMNS
+    );
+
+    let rule = sqlx::query_as::<_, AutomationRule>(&query)
+        .bind(req.id)
+        .bind(user_id)
+        .bind(req.name.as_deref().map(str::trim))
+        .bind(&req.trigger)
+        .bind(req.conditions.as_ref().map(Json))
+        .bind(req.actions.as_ref().map(Json))
+        .bind(req.enabled)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(rule)
+}
+
+/// Delete a rule (its runs cascade)
+pub async fn delete_rule(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM automation_rules WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Log one execution of a rule
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_run(
+    pool: &PgPool,
+    rule: &AutomationRule,
+    trigger: &str,
+    entity_id: Option<Uuid>,
+    customer_id: Option<Uuid>,
+    status: &str,
+    actions_run: i32,
+    error: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO automation_runs (user_id, rule_id, trigger, entity_id, customer_id, status, actions_run, error)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+    )
+    .bind(rule.user_id)
+    .bind(rule.id)
+    .bind(trigger)
+    .bind(entity_id)
+    .bind(customer_id)
+    .bind(status)
+    .bind(actions_run)
+    .bind(error)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Overdue revisions the rule has not evaluated yet, oldest due first
+pub async fn list_unprocessed_overdue_revisions(pool: &PgPool, rule: &AutomationRule) -> Result<Vec<Uuid>> {
+    let query = format!(
+        r#"
+        SELECT r.id
+        FROM revisions r
+        JOIN customers c ON c.id = r.customer_id
+        WHERE r.user_id = $2
+          AND r.status NOT IN ('{completed}', '{cancelled}')
+          AND r.due_date < CURRENT_DATE
+          AND c.is_anonymized = FALSE AND c.deleted_at IS NULL
+          AND NOT EXISTS (SELECT 1 FROM automation_runs ar WHERE ar.rule_id = $1 AND ar.entity_id = r.id)
+        ORDER BY r.due_date, r.id
+        LIMIT $3
+        "#,
+        completed = RevisionStatus::Completed.as_str(),
+        cancelled = RevisionStatus::Cancelled.as_str(),
+    );
+
+    let ids: Vec<Uuid> = sqlx::query_scalar(&query)
+        .bind(rule.id)
+        .bind(rule.user_id)
+        .bind(OVERDUE_BATCH_LIMIT)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(ids)
+}
+
+/// Execution log of a user, newest first
+pub async fn list_runs(pool: &PgPool, user_id: Uuid, rule_id: Option<Uuid>, limit: i64) -> Result<Vec<AutomationRun>> {
+    let runs = sqlx::query_as::<_, AutomationRun>(
+        r#"
+        SELECT
+            ar.id, ar.rule_id, r.name AS rule_name, ar.trigger, ar.entity_id, ar.customer_id,
+            ar.status, ar.actions_run, ar.error, ar.created_at
+        FROM automation_runs ar
+        JOIN automation_rules r ON r.id = ar.rule_id
+        WHERE ar.user_id = $1 AND ($2::uuid IS NULL OR ar.rule_id = $2)
+        ORDER BY ar.created_at DESC, ar.id
+        LIMIT $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(rule_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(runs)
+}
@@ -0,0 +1,272 @@
+#![allow(dead_code)]
+//! Data backup queries
+//!
+//! Backups are schema-agnostic: tables, columns and keys are read from the
+//! catalog, rows travel as JSON (`to_jsonb` / `jsonb_populate_recordset`),
+//! so new migrations need no changes here.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde_json::Value;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::types::backup::{ColumnSchema, ForeignKeySchema, TableSchema};
+
+/// Migration bookkeeping is recreated by `migrate`, never restored
+const EXCLUDED_TABLES: &[&str] = &["_sqlx_migrations"];
+
+/// Tables of the public schema with their writable columns and keys
+pub async fn load_schema(pool: &PgPool) -> Result<Vec<TableSchema>> {
+    let tables: Vec<(String,)> = sqlx::query_as(
+        r#"
+        SELECT table_name::text
+        FROM information_schema.tables
+        WHERE table_schema = 'public' AND table_type = 'BASE TABLE'
+        ORDER BY table_name
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let columns: Vec<(String, String, bool)> = sqlx::query_as(
+        r#"
+        SELECT table_name::text, column_name::text, is_nullable = 'YES'
+        FROM information_schema.columns
+        WHERE table_schema = 'public' AND is_generated = 'NEVER'
+        ORDER BY table_name, ordinal_position
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let primary_keys: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT cl.relname::text, a.attname::text
+        FROM pg_constraint c
+        JOIN pg_class cl ON cl.oid = c.conrelid
+        CROSS JOIN LATERAL unnest(c.conkey) WITH ORDINALITY AS k(attnum, ord)
+        JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.attnum
+        WHERE c.contype = 'p' AND c.connamespace = 'public'::regnamespace
+        ORDER BY cl.relname, k.ord
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let foreign_keys: Vec<(String, String, String, String)> = sqlx::query_as(
+        r#"
+        SELECT cl.relname::text, a.attname::text, pcl.relname::text, pa.attname::text
+        FROM pg_constraint c
+        JOIN pg_class cl ON cl.oid = c.conrelid
+        JOIN pg_class pcl ON pcl.oid = c.confrelid
+        JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = c.conkey[1]
+        JOIN pg_attribute pa ON pa.attrelid = c.confrelid AND pa.attnum = c.confkey[1]
+        WHERE c.contype = 'f' AND c.connamespace = 'public'::regnamespace
+          AND array_length(c.conkey, 1) = 1
+        ORDER BY cl.relname, c.conname
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut schemas: Vec<TableSchema> = tables
+        .into_iter()
+        .filter(|(name,)| !EXCLUDED_TABLES.contains(&name.as_str()))
+        .map(|(name,)| TableSchema { name, columns: vec![], primary_key: vec![], foreign_keys: vec![] })
+        .collect();
+    let index: HashMap<String, usize> = schemas.iter().enumerate().map(|(i, t)| (t.name.clone(), i)).collect();
+
+    for (table, name, nullable) in columns {
+        if let Some(&i) = index.get(&table) {
+            schemas[i].columns.push(ColumnSchema { name, nullable });
+        }
+    }
+    for (table, column) in primary_keys {
+        if let Some(&i) = index.get(&table) {
+            schemas[i].primary_key.push(column);
+        }
+    }
+    for (table, column, parent_table, parent_column) in foreign_keys {
+        if let Some(&i) = index.get(&table) {
+            schemas[i].foreign_keys.push(ForeignKeySchema { column, parent_table, parent_column });
+        }
+    }
+
+    Ok(schemas)
+}
+
+/// Latest applied migration
+pub async fn schema_version(pool: &PgPool) -> Result<i64> {
+    let (version,): (i64,) = sqlx::query_as("SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations WHERE success")
+        .fetch_one(pool)
+        .await?;
+
+    Ok(version)
+}
+
+/// Quote an identifier read from the catalog
+pub fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// WHERE condition selecting one account's rows of `table` (`$1` = account
+/// owner), or None when the table holds no account data.
+///
+/// Tables with `user_id` belong to the owner or one of its workers; other
+/// tables are reached through a foreign key to a table that has a condition
+/// (route stops through routes, role permissions through roles, ...).
+pub fn account_filter(schemas: &[TableSchema], table: &str) -> Option<String> {
+    account_filter_inner(schemas, table, &mut vec![])
+}
+
+fn account_filter_inner(schemas: &[TableSchema], table: &str, visiting: &mut Vec<String>) -> Option<String> {
+    const ACCOUNT_USERS: &str = "SELECT id FROM users WHERE id = $1 OR owner_id = $1";
+
+    if table == "users" {
+        return Some("(id = $1 OR owner_id = $1)".to_string());
+    }
+    let schema = schemas.iter().find(|t| t.name == table)?;
+    if schema.has_column("user_id") {
+        return Some(format!("user_id IN ({})", ACCOUNT_USERS));
+    }
+    if visiting.iter().any(|t| t == table) {
+        return None;
+    }
+
+    visiting.push(table.to_string());
+    let filter = schema.foreign_keys.iter().filter(|fk| fk.parent_table != table).find_map(|fk| {
+        account_filter_inner(schemas, &fk.parent_table, visiting).map(|parent_filter| {
+            format!(
+                "{} IN (SELECT {} FROM {} WHERE {})",
+                quote_ident(&fk.column),
+                quote_ident(&fk.parent_column),
+                quote_ident(&fk.parent_table),
+                parent_filter
+            )
+        })
+    });
+    visiting.pop();
+    filter
+}
+
+/// All rows of a table (or those matching `filter`, bound to `user_id`) as a JSON array
+pub async fn dump_table(pool: &PgPool, table: &str, filter: Option<&str>, user_id: Option<Uuid>) -> Result<Value> {
+    let query = format!(
+        "SELECT COALESCE(jsonb_agg(to_jsonb(t)), '[]'::jsonb) FROM {} t WHERE {}",
+        quote_ident(table),
+        filter.unwrap_or("TRUE")
+    );
+    let mut q = sqlx::query_as::<_, (Value,)>(&query);
+    if let Some(user_id) = user_id {
+        q = q.bind(user_id);
+    }
+    let (rows,) = q.fetch_one(pool).await?;
+
+    Ok(rows)
+}
+
+/// Insert dumped rows, keeping rows that already exist. `deferred` columns
+/// are inserted as NULL and filled by [`fill_deferred_column`] once the rows
+/// they point to exist.
+pub async fn insert_rows(
+    tx: &mut Transaction<'_, Postgres>,
+    table: &TableSchema,
+    rows: &Value,
+    deferred: &[String],
+) -> Result<u64> {
+    let columns: Vec<String> = table.columns.iter().map(|c| quote_ident(&c.name)).collect();
+    let values: Vec<String> = table
+        .columns
+        .iter()
+        .map(|c| if deferred.contains(&c.name) { "NULL".to_string() } else { format!("s.{}", quote_ident(&c.name)) })
+        .collect();
+    let query = format!(
+        "INSERT INTO {table} ({}) SELECT {} FROM jsonb_populate_recordset(NULL::{table}, $1) s ON CONFLICT DO NOTHING",
+        columns.join(", "),
+        values.join(", "),
+        table = quote_ident(&table.name),
+    );
+    let result = sqlx::query(&query).bind(rows).execute(&mut **tx).await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Set a deferred column of restored rows that are still missing it
+pub async fn fill_deferred_column(
+    tx: &mut Transaction<'_, Postgres>,
+    table: &TableSchema,
+    rows: &Value,
+    column: &str,
+) -> Result<u64> {
+    let key_match: Vec<String> = table
+        .primary_key
+        .iter()
+        .map(|k| format!("t.{k} = s.{k}", k = quote_ident(k)))
+        .collect();
+    let query = format!(
+        "UPDATE {table} t SET {col} = s.{col} FROM jsonb_populate_recordset(NULL::{table}, $1) s \
+         WHERE {} AND t.{col} IS NULL",
+        key_match.join(" AND "),
+        table = quote_ident(&table.name),
+        col = quote_ident(column),
+    );
+    let result = sqlx::query(&query).bind(rows).execute(&mut **tx).await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(name: &str, columns: &[&str], foreign_keys: &[(&str, &str)]) -> TableSchema {
+        TableSchema {
+            name: name.to_string(),
+            columns: columns.iter().map(|c| ColumnSchema { name: c.to_string(), nullable: true }).collect(),
+            primary_key: vec!["id".to_string()],
+            foreign_keys: foreign_keys
+                .iter()
+                .map(|(column, parent)| ForeignKeySchema {
+                    column: column.to_string(),
+                    parent_table: parent.to_string(),
+                    parent_column: "id".to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_account_filter() {
+        let schemas = vec![
+            table("users", &["id", "owner_id"], &[("owner_id", "users")]),
+            table("routes", &["id", "user_id"], &[("user_id", "users")]),
+            table("route_stops", &["id", "route_id"], &[("route_id", "routes")]),
+            table("countries", &["code"], &[]),
+        ];
+
+        assert_eq!(account_filter(&schemas, "users").as_deref(), Some("(id = $1 OR owner_id = $1)"));
+        assert!(account_filter(&schemas, "routes").unwrap().starts_with("user_id IN ("));
+        assert_eq!(
+            account_filter(&schemas, "route_stops").unwrap(),
+            format!("\"route_id\" IN (SELECT \"id\" FROM \"routes\" WHERE {})", account_filter(&schemas, "routes").unwrap())
+        );
+        assert!(account_filter(&schemas, "countries").is_none());
+    }
+
+    #[test]
+    fn test_account_filter_survives_cycles() {
+        let schemas = vec![
+            table("a", &["id", "b_id"], &[("b_id", "b")]),
+            table("b", &["id", "a_id"], &[("a_id", "a")]),
+        ];
+        assert!(account_filter(&schemas, "a").is_none());
+    }
+
+    #[test]
+    fn test_quote_ident() {
+        assert_eq!(quote_ident("users"), "\"users\"");
+        assert_eq!(quote_ident("we\"ird"), "\"we\"\"ird\"");
+    }
+}
@@ -0,0 +1,92 @@
+#![allow(dead_code)]
+//! Calendar feed queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::calendar_feed::{CalendarFeed, ResolvedCalendarFeed};
+
+/// Create a feed for a token hash
+pub async fn create_feed(
+    pool: &PgPool,
+    user_id: Uuid,
+    name: &str,
+    token_hash: &str,
+    crew_ids: &[Uuid],
+    include_capacity: bool,
+) -> Result<CalendarFeed> {
+    let feed = sqlx::query_as::<_, CalendarFeed>(
+        r#"
+        INSERT INTO calendar_feeds (user_id, name, token_hash, crew_ids, include_capacity)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, name, crew_ids, include_capacity, created_at, last_fetched_at, revoked_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(name)
+    .bind(token_hash)
+    .bind(crew_ids)
+    .bind(include_capacity)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(feed)
+}
+
+/// Number of feeds of a user that were not revoked
+pub async fn count_active_feeds(pool: &PgPool, user_id: Uuid) -> Result<i64> {
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM calendar_feeds WHERE user_id = $1 AND revoked_at IS NULL")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+
+    Ok(count)
+}
+
+/// List a user's feeds, revoked ones included, newest first
+pub async fn list_feeds(pool: &PgPool, user_id: Uuid) -> Result<Vec<CalendarFeed>> {
+    let feeds = sqlx::query_as::<_, CalendarFeed>(
+        r#"
+        SELECT id, name, crew_ids, include_capacity, created_at, last_fetched_at, revoked_at
+        FROM calendar_feeds
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(feeds)
+}
+
+/// Revoke a feed; false when it does not exist or was revoked already
+pub async fn revoke_feed(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE calendar_feeds SET revoked_at = NOW() WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Resolve an active feed by token hash, recording the fetch
+pub async fn resolve_feed(pool: &PgPool, token_hash: &str) -> Result<Option<ResolvedCalendarFeed>> {
+    let feed = sqlx::query_as::<_, ResolvedCalendarFeed>(
+        r#"
+        UPDATE calendar_feeds SET last_fetched_at = NOW()
+        WHERE token_hash = $1 AND revoked_at IS NULL
+        RETURNING id, user_id, name, crew_ids, include_capacity
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(feed)
+}
@@ -0,0 +1,300 @@
+#![allow(dead_code)]
+//! Campaign trigger database queries
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::campaign::{
+    Campaign, CampaignRecipient, CampaignSend, CreateCampaignRequest, UpdateCampaignRequest,
+    CAMPAIGN_CHANNEL_SMS, CAMPAIGN_TRIGGER_CUSTOMER_ANNIVERSARY,
+};
+use crate::types::RevisionStatus;
+
+/// Campaign columns with send statistics; the campaign row is aliased `c`
+const CAMPAIGN_COLUMNS: &str = r#"
+    c.id, c.user_id, c.name, c.trigger_type, c.months_since_revision,
+    c.active_from_month, c.active_to_month, c.channel, c.subject, c.body,
+    c.exclude_scheduled, c.exclude_contacted_days, c.repeat_after_days,
+    c.enabled, c.last_run_on, c.created_at, c.updated_at,
+    (SELECT COUNT(*) FROM campaign_sends s WHERE s.campaign_id = c.id AND s.error IS NULL) AS sent_count,
+    (SELECT COUNT(*) FROM campaign_sends s WHERE s.campaign_id = c.id AND s.error IS NOT NULL) AS failed_count,
+    (SELECT MAX(s.created_at) FROM campaign_sends s WHERE s.campaign_id = c.id AND s.error IS NULL) AS last_sent_at
+"#;
+
+/// Create a campaign
+pub async fn create_campaign(pool: &PgPool, user_id: Uuid, req: &CreateCampaignRequest) -> Result<Campaign> {
+    let query = format!(
+        r#"
+        WITH c AS (
+            INSERT INTO campaigns (
+                user_id, name, trigger_type, months_since_revision, active_from_month, active_to_month,
+                channel, subject, body, exclude_scheduled, exclude_contacted_days, repeat_after_days, enabled
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING *
+        )
+        SELECT {} FROM c
+        "#,
+        CAMPAIGN_COLUMNS
+    );
+
+    let campaign = sqlx::query_as::<_, Campaign>(&query)
+        .bind(user_id)
+        .bind(req.name.trim())
+        .bind(&req.trigger_type)
+        .bind(req.months_since_revision)
+        .bind(req.active_from_month)
+        .bind(req.active_to_month)
+        .bind(&req.channel)
+        .bind(&req.subject)
+        .bind(&req.body)
+        .bind(req.exclude_scheduled.unwrap_or(true))
+        .bind(req.exclude_contacted_days)
+        .bind(req.repeat_after_days)
+        .bind(req.enabled.unwrap_or(true))
+        .fetch_one(pool)
+        .await?;
+
+    Ok(campaign)
+}
+
+/// Get a campaign of a user
+pub async fn get_campaign(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<Option<Campaign>> {
+    let query = format!("SELECT {} FROM campaigns c WHERE c.id = $1 AND c.user_id = $2", CAMPAIGN_COLUMNS);
+
+    let campaign = sqlx::query_as::<_, Campaign>(&query)
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(campaign)
+}
+
+/// List campaigns of a user
+pub async fn list_campaigns(pool: &PgPool, user_id: Uuid) -> Result<Vec<Campaign>> {
+    let query = format!(
+        "SELECT {} FROM campaigns c WHERE c.user_id = $1 ORDER BY c.name, c.created_at",
+        CAMPAIGN_COLUMNS
+    );
+
+    let campaigns = sqlx::query_as::<_, Campaign>(&query)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(campaigns)
+}
+
+/// Enabled campaigns of all users not yet run today, for the scheduler
+pub async fn list_due_campaigns(pool: &PgPool) -> Result<Vec<Campaign>> {
+    let query = format!(
+        r#"
+        SELECT {} FROM campaigns c
+        WHERE c.enabled AND (c.last_run_on IS NULL OR c.last_run_on < CURRENT_DATE)
+        ORDER BY c.user_id, c.created_at
+        "#,
+        CAMPAIGN_COLUMNS
+    );
+
+    let campaigns = sqlx::query_as::<_, Campaign>(&query).fetch_all(pool).await?;
+
+    Ok(campaigns)
+}
+
+/// Update a campaign
+pub async fn update_campaign(pool: &PgPool, user_id: Uuid, req: &UpdateCampaignRequest) -> Result<Option<Campaign>> {
+    let query = format!(
+        r#"
+        WITH c AS (
+            UPDATE campaigns
+            SET
+                name = $3,
+                months_since_revision = $4,
+                active_from_month = $5,
+                active_to_month = $6,
+                subject = $7,
+                body = $8,
+                exclude_scheduled = $9,
+                exclude_contacted_days = $10,
+                repeat_after_days = $11,
+                enabled = $12,
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2
+            RETURNING *
+        )
+        SELECT {} FROM c
+        "#,
+        CAMPAIGN_COLUMNS
+    );
+
+    let campaign = sqlx::query_as::<_, Campaign>(&query)
+        .bind(req.id)
+        .bind(user_id)
+        .bind(req.name.trim())
+        .bind(req.months_since_revision)
+        .bind(req.active_from_month)
+        .bind(req.active_to_month)
+        .bind(&req.subject)
+        .bind(&req.body)
+        .bind(req.exclude_scheduled)
+        .bind(req.exclude_contacted_days)
+        .bind(req.repeat_after_days)
+        .bind(req.enabled)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(campaign)
+}
+
+/// Delete a campaign (its send log cascades)
+pub async fn delete_campaign(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM campaigns WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Customers a campaign newly matches, after its exclusion rules.
+///
+/// Anonymized, abandoned and deleted customers and customers without a
+/// contact for the channel never match; a customer with a successful send
+/// matches again only after `repeat_after_days`.
+pub async fn match_recipients(pool: &PgPool, campaign: &Campaign, limit: i64) -> Result<Vec<CampaignRecipient>> {
+    let contact = if campaign.channel == CAMPAIGN_CHANNEL_SMS { "c.phone" } else { "c.email" };
+    let anniversary = campaign.trigger_type == CAMPAIGN_TRIGGER_CUSTOMER_ANNIVERSARY;
+    let trigger = if anniversary {
+        r#"
+              AND c.created_at::date <= (CURRENT_DATE - INTERVAL '1 year')::date
+              AND EXTRACT(MONTH FROM c.created_at) = EXTRACT(MONTH FROM CURRENT_DATE)
+              AND EXTRACT(DAY FROM c.created_at) = EXTRACT(DAY FROM CURRENT_DATE)
+        "#
+    } else {
+        "AND lr.last_date < (CURRENT_DATE - make_interval(months => $7::int))::date"
+    };
+
+    let query = format!(
+        r#"
+        WITH last_rev AS (
+            SELECT customer_id, MAX(COALESCE(completed_at::date, scheduled_date, due_date)) AS last_date
+            FROM revisions
+            WHERE user_id = $1 AND status = '{completed}'
+            GROUP BY customer_id
+        )
+        SELECT
+            c.id AS customer_id, c.name AS customer_name,
+            TRIM({contact}) AS contact, lr.last_date AS last_revision_date
+        FROM customers c
+        LEFT JOIN last_rev lr ON lr.customer_id = c.id
+        WHERE c.user_id = $1
+          AND c.is_anonymized = FALSE AND c.is_abandoned = FALSE AND c.deleted_at IS NULL
+          AND NULLIF(TRIM({contact}), '') IS NOT NULL
+          {trigger}
+          AND (NOT $3 OR NOT EXISTS (
+              SELECT 1 FROM revisions r
+              WHERE r.customer_id = c.id AND r.status IN ('{scheduled}', '{confirmed}')
+          ))
+          AND ($4::int IS NULL OR NOT EXISTS (
+              SELECT 1 FROM communications cm
+              WHERE cm.customer_id = c.id AND cm.direction = 'outbound'
+                AND cm.created_at > NOW() - make_interval(days => $4::int)
+          ))
+          AND NOT EXISTS (
+              SELECT 1 FROM campaign_sends s
+              WHERE s.campaign_id = $2 AND s.customer_id = c.id AND s.error IS NULL
+                AND ($5::int IS NULL OR s.created_at > NOW() - make_interval(days => $5::int))
+          )
+        ORDER BY lr.last_date NULLS LAST, c.id
+        LIMIT $6
+        "#,
+        completed = RevisionStatus::Completed.as_str(),
+        scheduled = RevisionStatus::Scheduled.as_str(),
+        confirmed = RevisionStatus::Confirmed.as_str(),
+    );
+
+    let mut query = sqlx::query_as::<_, CampaignRecipient>(&query)
+        .bind(campaign.user_id)
+        .bind(campaign.id)
+        .bind(campaign.exclude_scheduled)
+        .bind(campaign.exclude_contacted_days)
+        .bind(campaign.repeat_after_days)
+        .bind(limit);
+    if !anniversary {
+        query = query.bind(campaign.months_since_revision);
+    }
+    let recipients = query.fetch_all(pool).await?;
+
+    Ok(recipients)
+}
+
+/// Log a message queued for (or failed to queue to) a customer
+pub async fn record_send(
+    pool: &PgPool,
+    campaign: &Campaign,
+    customer_id: Uuid,
+    recipient: &str,
+    job_id: Option<Uuid>,
+    error: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO campaign_sends (user_id, campaign_id, customer_id, channel, recipient, job_id, error)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(campaign.user_id)
+    .bind(campaign.id)
+    .bind(customer_id)
+    .bind(&campaign.channel)
+    .bind(recipient)
+    .bind(job_id)
+    .bind(error)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Remember that a campaign was evaluated on `date`
+pub async fn set_last_run(pool: &PgPool, id: Uuid, date: NaiveDate) -> Result<()> {
+    sqlx::query("UPDATE campaigns SET last_run_on = $2 WHERE id = $1")
+        .bind(id)
+        .bind(date)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Send log of a user, newest first
+pub async fn list_sends(
+    pool: &PgPool,
+    user_id: Uuid,
+    campaign_id: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<CampaignSend>> {
+    let sends = sqlx::query_as::<_, CampaignSend>(
+        r#"
+        SELECT
+            s.id, s.campaign_id, s.customer_id, cu.name AS customer_name,
+            s.channel, s.recipient, s.job_id, s.error, s.created_at
+        FROM campaign_sends s
+        LEFT JOIN customers cu ON cu.id = s.customer_id
+        WHERE s.user_id = $1 AND ($2::uuid IS NULL OR s.campaign_id = $2)
+        ORDER BY s.created_at DESC, s.id
+        LIMIT $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(campaign_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sends)
+}
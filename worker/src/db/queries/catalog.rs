@@ -0,0 +1,96 @@
+#![allow(dead_code)]
+//! Service catalog queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::catalog::{CatalogItem, CreateCatalogItemRequest, UpdateCatalogItemRequest};
+
+/// Create a catalog item with an already validated unit and currency
+pub async fn create_item(
+    pool: &PgPool,
+    user_id: Uuid,
+    req: &CreateCatalogItemRequest,
+    unit: &str,
+    currency: &str,
+) -> Result<CatalogItem> {
+    let item = sqlx::query_as::<_, CatalogItem>(
+        r#"
+        INSERT INTO catalog_items (user_id, name, description, unit, unit_price_minor, vat_rate, currency, work_type)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(req.name.trim())
+    .bind(&req.description)
+    .bind(unit)
+    .bind(req.unit_price_minor)
+    .bind(req.vat_rate)
+    .bind(currency)
+    .bind(req.work_type)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(item)
+}
+
+/// Update a catalog item; quotes keep the copy they were made with
+pub async fn update_item(
+    pool: &PgPool,
+    user_id: Uuid,
+    req: &UpdateCatalogItemRequest,
+    unit: &str,
+    currency: &str,
+) -> Result<Option<CatalogItem>> {
+    let item = sqlx::query_as::<_, CatalogItem>(
+        r#"
+        UPDATE catalog_items
+        SET name = $3, description = $4, unit = $5, unit_price_minor = $6, vat_rate = $7,
+            currency = $8, work_type = $9, is_active = $10, updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(req.id)
+    .bind(user_id)
+    .bind(req.name.trim())
+    .bind(&req.description)
+    .bind(unit)
+    .bind(req.unit_price_minor)
+    .bind(req.vat_rate)
+    .bind(currency)
+    .bind(req.work_type)
+    .bind(req.is_active)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(item)
+}
+
+/// Catalog of an account, by name
+pub async fn list_items(pool: &PgPool, user_id: Uuid, include_inactive: bool) -> Result<Vec<CatalogItem>> {
+    let items = sqlx::query_as::<_, CatalogItem>(
+        "SELECT * FROM catalog_items WHERE user_id = $1 AND (is_active OR $2) ORDER BY name, created_at",
+    )
+    .bind(user_id)
+    .bind(include_inactive)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
+
+/// Active catalog items of an account among `ids`
+pub async fn get_active_items(pool: &PgPool, user_id: Uuid, ids: &[Uuid]) -> Result<Vec<CatalogItem>> {
+    let items = sqlx::query_as::<_, CatalogItem>(
+        "SELECT * FROM catalog_items WHERE user_id = $1 AND id = ANY($2) AND is_active",
+    )
+    .bind(user_id)
+    .bind(ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
@@ -6,7 +6,7 @@ use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::types::Communication;
+use crate::types::{AutomaticCommunication, Communication};
 
 /// Create a new communication
 pub async fn create_communication(
@@ -59,7 +59,7 @@ pub async fn create_communication_with_date(
             id, user_id, customer_id, revision_id,
             comm_type::text, direction::text, subject, content,
             contact_name, contact_phone, email_status,
-            duration_minutes,
+            duration_minutes, entry_type, details,
             created_at, updated_at
         "#,
     )
@@ -81,6 +81,44 @@ pub async fn create_communication_with_date(
     Ok(communication)
 }
 
+/// Record an entry written by the system (reminders, route confirmations,
+/// visit completions)
+pub async fn create_automatic_communication(
+    pool: &PgPool,
+    user_id: Uuid,
+    entry: &AutomaticCommunication,
+) -> Result<Communication> {
+    let communication = sqlx::query_as::<_, Communication>(
+        r#"
+        INSERT INTO communications (
+            id, user_id, customer_id, revision_id,
+            comm_type, direction, subject, content,
+            entry_type, details
+        )
+        VALUES ($1, $2, $3, $4, $5::comm_type, 'outbound', $6, $7, $8, $9)
+        RETURNING
+            id, user_id, customer_id, revision_id,
+            comm_type::text, direction::text, subject, content,
+            contact_name, contact_phone, email_status,
+            duration_minutes, entry_type, details,
+            created_at, updated_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(entry.customer_id)
+    .bind(entry.revision_id)
+    .bind(entry.comm_type)
+    .bind(entry.subject.as_deref())
+    .bind(&entry.content)
+    .bind(entry.entry_type.as_str())
+    .bind(&entry.details)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(communication)
+}
+
 /// Get a communication by ID
 pub async fn get_communication(
     pool: &PgPool,
@@ -93,7 +131,7 @@ pub async fn get_communication(
             id, user_id, customer_id, revision_id,
             comm_type::text, direction::text, subject, content,
             contact_name, contact_phone, email_status,
-            duration_minutes,
+            duration_minutes, entry_type, details,
             created_at, updated_at
         FROM communications
         WHERE id = $1 AND user_id = $2
@@ -108,12 +146,14 @@ pub async fn get_communication(
 }
 
 /// List communications with filters
+#[allow(clippy::too_many_arguments)]
 pub async fn list_communications(
     pool: &PgPool,
     user_id: Uuid,
     customer_id: Option<Uuid>,
     revision_id: Option<Uuid>,
     comm_type: Option<&str>,
+    entry_types: &[String],
     limit: i64,
     offset: i64,
 ) -> Result<(Vec<Communication>, i64)> {
@@ -132,6 +172,10 @@ pub async fn list_communications(
         param_count += 1;
         conditions.push(format!("comm_type = ${}", param_count));
     }
+    if !entry_types.is_empty() {
+        param_count += 1;
+        conditions.push(format!("entry_type = ANY(${})", param_count));
+    }
 
     let where_clause = conditions.join(" AND ");
 
@@ -141,7 +185,7 @@ pub async fn list_communications(
             id, user_id, customer_id, revision_id,
             comm_type::text, direction::text, subject, content,
             contact_name, contact_phone, email_status,
-            duration_minutes,
+            duration_minutes, entry_type, details,
             created_at, updated_at
         FROM communications
         WHERE {}
@@ -174,6 +218,10 @@ pub async fn list_communications(
         query_builder = query_builder.bind(ct);
         count_builder = count_builder.bind(ct);
     }
+    if !entry_types.is_empty() {
+        query_builder = query_builder.bind(entry_types);
+        count_builder = count_builder.bind(entry_types);
+    }
 
     query_builder = query_builder.bind(limit).bind(offset);
 
@@ -203,7 +251,7 @@ pub async fn update_communication(
             id, user_id, customer_id, revision_id,
             comm_type::text, direction::text, subject, content,
             contact_name, contact_phone, email_status,
-            duration_minutes,
+            duration_minutes, entry_type, details,
             created_at, updated_at
         "#,
     )
@@ -241,7 +289,7 @@ pub async fn get_customer_communications(
             id, user_id, customer_id, revision_id,
             comm_type::text, direction::text, subject, content,
             contact_name, contact_phone, email_status,
-            duration_minutes,
+            duration_minutes, entry_type, details,
             created_at, updated_at
         FROM communications
         WHERE customer_id = $1 AND user_id = $2
@@ -2,8 +2,9 @@
 
 use anyhow::Result;
 use sqlx::PgPool;
+use uuid::Uuid;
 
-use crate::types::{Country, CountryJsonEntry, CountrySyncResponse, UpdateCountryRequest};
+use crate::types::{Country, CountryCoverageGap, CountryJsonEntry, CountrySyncResponse, UpdateCountryRequest};
 
 /// List countries. When `include_all` is false, only `is_supported = true` rows are returned.
 pub async fn list_countries(pool: &PgPool, include_all: bool) -> Result<Vec<Country>> {
@@ -23,6 +24,60 @@ pub async fn list_countries(pool: &PgPool, include_all: bool) -> Result<Vec<Coun
     Ok(rows)
 }
 
+/// Get a single country by its ISO alpha-2 code (case-insensitive).
+pub async fn get_country(pool: &PgPool, code: &str) -> Result<Option<Country>> {
+    let row = sqlx::query_as::<_, Country>(r#"SELECT * FROM countries WHERE code = UPPER($1)"#)
+        .bind(code.trim())
+        .fetch_optional(pool)
+        .await?;
+    Ok(row)
+}
+
+/// Map coverage of a customer's country; None when the customer has no
+/// country or it is not in the `countries` table.
+pub async fn customer_map_coverage(pool: &PgPool, customer_id: Uuid) -> Result<Option<bool>> {
+    let row: Option<(bool,)> = sqlx::query_as(
+        r#"
+        SELECT co.has_map_coverage
+        FROM customers cu
+        JOIN countries co ON co.code = UPPER(cu.country)
+        WHERE cu.id = $1
+        "#,
+    )
+    .bind(customer_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|(covered,)| covered))
+}
+
+/// Customers in countries that are unknown, unsupported or without map
+/// coverage, grouped by country, largest groups first.
+pub async fn list_coverage_gaps(pool: &PgPool) -> Result<Vec<CountryCoverageGap>> {
+    let rows = sqlx::query_as::<_, CountryCoverageGap>(
+        r#"
+        SELECT
+            UPPER(cu.country)                                  AS country,
+            co.name_en,
+            co.is_supported,
+            co.has_map_coverage,
+            COUNT(*)                                           AS customer_count,
+            COUNT(DISTINCT cu.user_id)                         AS account_count,
+            COUNT(*) FILTER (WHERE cu.lat IS NULL OR cu.lng IS NULL) AS ungeocoded_count
+        FROM customers cu
+        LEFT JOIN countries co ON co.code = UPPER(cu.country)
+        WHERE cu.deleted_at IS NULL
+          AND cu.is_anonymized = false
+          AND COALESCE(cu.country, '') <> ''
+          AND (co.code IS NULL OR NOT co.is_supported OR NOT co.has_map_coverage)
+        GROUP BY UPPER(cu.country), co.name_en, co.is_supported, co.has_map_coverage
+        ORDER BY customer_count DESC, country
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
 /// UPSERT countries from the embedded JSON.
 /// Only `name_en`, `name_cs`, `name_sk`, `alpha3` are updated — operational columns are never touched.
 pub async fn sync_countries(
@@ -83,7 +138,7 @@ pub async fn update_country(
             nominatim_priority = COALESCE($5, nominatim_priority),
             sort_order         = COALESCE($6, sort_order),
             updated_at         = now()
-        WHERE code = $1
+        WHERE code = UPPER($1)
         RETURNING *
         "#,
     )
@@ -0,0 +1,125 @@
+#![allow(dead_code)]
+//! Service coverage queries
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::coverage::{CoverageArea, CoverageAreaInput, CoverageDemandRow, CoverageSettings};
+
+/// Load the coverage mode and list of an account
+pub async fn get_coverage(pool: &PgPool, user_id: Uuid) -> Result<CoverageSettings> {
+    let mode: Option<(String,)> = sqlx::query_as("SELECT coverage_mode FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    let areas = sqlx::query_as::<_, CoverageArea>(
+        r#"
+        SELECT id, pattern, label, created_at
+        FROM service_coverage_areas
+        WHERE user_id = $1
+        ORDER BY pattern
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(CoverageSettings {
+        mode: mode.map(|(m,)| m).unwrap_or_else(|| crate::types::coverage::COVERAGE_MODE_OFF.to_string()),
+        areas,
+    })
+}
+
+/// Replace the coverage mode and list of an account
+pub async fn set_coverage(
+    pool: &PgPool,
+    user_id: Uuid,
+    mode: &str,
+    areas: &[CoverageAreaInput],
+) -> Result<CoverageSettings> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE users SET coverage_mode = $2, updated_at = NOW() WHERE id = $1")
+        .bind(user_id)
+        .bind(mode)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM service_coverage_areas WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for area in areas {
+        sqlx::query("INSERT INTO service_coverage_areas (user_id, pattern, label) VALUES ($1, $2, $3)")
+            .bind(user_id)
+            .bind(&area.pattern)
+            .bind(&area.label)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    get_coverage(pool, user_id).await
+}
+
+/// Log an address outside the served area
+pub async fn record_miss(
+    pool: &PgPool,
+    user_id: Uuid,
+    postal_code: &str,
+    city: Option<&str>,
+    source: &str,
+    rejected: bool,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO coverage_misses (user_id, postal_code, city, source, rejected)
+        VALUES ($1, $2, NULLIF(TRIM($3), ''), $4, $5)
+        "#,
+    )
+    .bind(user_id)
+    .bind(postal_code)
+    .bind(city)
+    .bind(source)
+    .bind(rejected)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Demand from areas outside the coverage, most requested first
+pub async fn demand_report(
+    pool: &PgPool,
+    user_id: Uuid,
+    date_from: Option<NaiveDate>,
+    date_to: Option<NaiveDate>,
+) -> Result<Vec<CoverageDemandRow>> {
+    let rows = sqlx::query_as::<_, CoverageDemandRow>(
+        r#"
+        SELECT postal_code,
+               MAX(city) AS city,
+               COUNT(*) AS total,
+               COUNT(*) FILTER (WHERE rejected) AS rejected,
+               MAX(created_at) AS last_seen_at
+        FROM coverage_misses
+        WHERE user_id = $1
+          AND ($2::date IS NULL OR created_at >= $2::date)
+          AND ($3::date IS NULL OR created_at < $3::date + 1)
+        GROUP BY postal_code
+        ORDER BY total DESC, postal_code
+        "#,
+    )
+    .bind(user_id)
+    .bind(date_from)
+    .bind(date_to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
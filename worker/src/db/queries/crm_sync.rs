@@ -0,0 +1,368 @@
+#![allow(dead_code)]
+//! CRM sync connector database queries
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::crm_sync::{
+    CrmConnector, CrmSyncLogEntry, CrmSyncOutcome, CreateCrmConnectorRequest,
+    UpdateCrmConnectorRequest, DEFAULT_CRM_BATCH_SIZE, DEFAULT_CRM_PULL_INTERVAL_MINUTES,
+};
+use crate::types::{Customer, Visit};
+
+const CONNECTOR_COLUMNS: &str = r#"
+    id, user_id, name, base_url, push_path, pull_path,
+    auth_header_name, auth_header_value, field_mapping,
+    batch_size, pull_interval_minutes, enabled,
+    last_push_at, last_pull_at, last_run_at, created_at, updated_at
+"#;
+
+/// Create a connector
+pub async fn create_connector(
+    pool: &PgPool,
+    user_id: Uuid,
+    req: &CreateCrmConnectorRequest,
+) -> Result<CrmConnector> {
+    let query = format!(
+        r#"
+        INSERT INTO crm_connectors (
+            user_id, name, base_url, push_path, pull_path,
+            auth_header_name, auth_header_value, field_mapping,
+            batch_size, pull_interval_minutes, enabled
+        )
+        VALUES ($1, $2, $3, COALESCE($4, '/sync'), $5, $6, $7, $8, $9, $10, $11)
+        RETURNING {}
+        "#,
+        CONNECTOR_COLUMNS
+    );
+
+    let connector = sqlx::query_as::<_, CrmConnector>(&query)
+        .bind(user_id)
+        .bind(&req.name)
+        .bind(&req.base_url)
+        .bind(&req.push_path)
+        .bind(&req.pull_path)
+        .bind(&req.auth_header_name)
+        .bind(&req.auth_header_value)
+        .bind(req.field_mapping.clone().unwrap_or_else(|| serde_json::json!({})))
+        .bind(req.batch_size.unwrap_or(DEFAULT_CRM_BATCH_SIZE))
+        .bind(req.pull_interval_minutes.unwrap_or(DEFAULT_CRM_PULL_INTERVAL_MINUTES))
+        .bind(req.enabled.unwrap_or(true))
+        .fetch_one(pool)
+        .await?;
+
+    Ok(connector)
+}
+
+/// List connectors for a user
+pub async fn list_connectors(pool: &PgPool, user_id: Uuid) -> Result<Vec<CrmConnector>> {
+    let query = format!(
+        "SELECT {} FROM crm_connectors WHERE user_id = $1 ORDER BY name",
+        CONNECTOR_COLUMNS
+    );
+
+    let connectors = sqlx::query_as::<_, CrmConnector>(&query)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(connectors)
+}
+
+/// Get a connector by ID
+pub async fn get_connector(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<Option<CrmConnector>> {
+    let query = format!(
+        "SELECT {} FROM crm_connectors WHERE id = $1 AND user_id = $2",
+        CONNECTOR_COLUMNS
+    );
+
+    let connector = sqlx::query_as::<_, CrmConnector>(&query)
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(connector)
+}
+
+/// Update a connector (only provided fields change)
+pub async fn update_connector(
+    pool: &PgPool,
+    user_id: Uuid,
+    req: &UpdateCrmConnectorRequest,
+) -> Result<Option<CrmConnector>> {
+    let query = format!(
+        r#"
+        UPDATE crm_connectors
+        SET
+            name = COALESCE($3, name),
+            base_url = COALESCE($4, base_url),
+            push_path = COALESCE($5, push_path),
+            pull_path = COALESCE($6, pull_path),
+            auth_header_name = COALESCE($7, auth_header_name),
+            auth_header_value = COALESCE($8, auth_header_value),
+            field_mapping = COALESCE($9, field_mapping),
+            batch_size = COALESCE($10, batch_size),
+            pull_interval_minutes = COALESCE($11, pull_interval_minutes),
+            enabled = COALESCE($12, enabled),
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING {}
+        "#,
+        CONNECTOR_COLUMNS
+    );
+
+    let connector = sqlx::query_as::<_, CrmConnector>(&query)
+        .bind(req.id)
+        .bind(user_id)
+        .bind(&req.name)
+        .bind(&req.base_url)
+        .bind(&req.push_path)
+        .bind(&req.pull_path)
+        .bind(&req.auth_header_name)
+        .bind(&req.auth_header_value)
+        .bind(&req.field_mapping)
+        .bind(req.batch_size)
+        .bind(req.pull_interval_minutes)
+        .bind(req.enabled)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(connector)
+}
+
+/// Delete a connector (its sync log cascades)
+pub async fn delete_connector(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM crm_connectors WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Enabled connectors whose pull interval has elapsed (or that never ran)
+pub async fn list_due_connectors(pool: &PgPool) -> Result<Vec<CrmConnector>> {
+    let query = format!(
+        r#"
+        SELECT {} FROM crm_connectors
+        WHERE enabled = TRUE
+          AND (
+            last_run_at IS NULL
+            OR last_run_at + make_interval(mins => pull_interval_minutes) <= NOW()
+          )
+        ORDER BY last_run_at NULLS FIRST
+        "#,
+        CONNECTOR_COLUMNS
+    );
+
+    let connectors = sqlx::query_as::<_, CrmConnector>(&query)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(connectors)
+}
+
+/// Record the high-water mark of a successful push
+pub async fn set_last_push_at(pool: &PgPool, id: Uuid, at: DateTime<Utc>) -> Result<()> {
+    sqlx::query("UPDATE crm_connectors SET last_push_at = $2 WHERE id = $1")
+        .bind(id)
+        .bind(at)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Record the high-water mark of a successful pull
+pub async fn set_last_pull_at(pool: &PgPool, id: Uuid, at: DateTime<Utc>) -> Result<()> {
+    sqlx::query("UPDATE crm_connectors SET last_pull_at = $2 WHERE id = $1")
+        .bind(id)
+        .bind(at)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Record the time of the last run (successful or not)
+pub async fn set_last_run_at(pool: &PgPool, id: Uuid, at: DateTime<Utc>) -> Result<()> {
+    sqlx::query("UPDATE crm_connectors SET last_run_at = $2 WHERE id = $1")
+        .bind(id)
+        .bind(at)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Remember the customer version a pull wrote, so it is not pushed back
+pub async fn mark_customer_pulled(
+    pool: &PgPool,
+    connector_id: Uuid,
+    customer_id: Uuid,
+    updated_at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO crm_pulled_customers (connector_id, customer_id, updated_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (connector_id, customer_id) DO UPDATE SET updated_at = EXCLUDED.updated_at
+        "#,
+    )
+    .bind(connector_id)
+    .bind(customer_id)
+    .bind(updated_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Customer version last written by a pull of this connector
+pub async fn get_pulled_version(pool: &PgPool, connector_id: Uuid, customer_id: Uuid) -> Result<Option<DateTime<Utc>>> {
+    let version = sqlx::query_scalar::<_, DateTime<Utc>>(
+        "SELECT updated_at FROM crm_pulled_customers WHERE connector_id = $1 AND customer_id = $2",
+    )
+    .bind(connector_id)
+    .bind(customer_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(version)
+}
+
+/// Persist the outcome of a push/pull pass
+pub async fn insert_sync_log(
+    pool: &PgPool,
+    connector: &CrmConnector,
+    direction: &str,
+    started_at: DateTime<Utc>,
+    outcome: &CrmSyncOutcome,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO crm_sync_log (
+            connector_id, user_id, direction, status,
+            items_total, items_succeeded, items_failed, conflicts, error, started_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        "#,
+    )
+    .bind(connector.id)
+    .bind(connector.user_id)
+    .bind(direction)
+    .bind(outcome.status())
+    .bind(outcome.items_total)
+    .bind(outcome.items_succeeded)
+    .bind(outcome.items_failed)
+    .bind(serde_json::to_value(&outcome.conflicts)?)
+    .bind(&outcome.error)
+    .bind(started_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Most recent sync runs for a connector
+pub async fn list_sync_log(
+    pool: &PgPool,
+    user_id: Uuid,
+    connector_id: Uuid,
+    limit: i64,
+) -> Result<Vec<CrmSyncLogEntry>> {
+    let entries = sqlx::query_as::<_, CrmSyncLogEntry>(
+        r#"
+        SELECT
+            id, connector_id, direction, status,
+            items_total, items_succeeded, items_failed, conflicts, error,
+            started_at, finished_at
+        FROM crm_sync_log
+        WHERE connector_id = $1 AND user_id = $2
+        ORDER BY started_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(connector_id)
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries)
+}
+
+/// Customers changed since the given time (all when `since` is None).
+///
+/// Versions written by this connector's own pull are left out.
+pub async fn list_customers_changed_since(
+    pool: &PgPool,
+    connector_id: Uuid,
+    user_id: Uuid,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<Customer>> {
+    let customers = sqlx::query_as::<_, Customer>(
+        r#"
+        SELECT
+            id, user_id, customer_type, name, contact_person, ico, dic,
+            email, phone, phone_raw,
+            street, city, postal_code, country,
+            lat, lng, geocode_status::text, notes, created_at, updated_at,
+            is_abandoned, deleted_at, parent_customer_id, language, customer_code,
+            acquisition_source, referrer_customer_id, lead_status, lead_lost_reason, lead_status_changed_at
+        FROM customers
+        WHERE user_id = $1
+          AND is_anonymized = FALSE
+          AND deleted_at IS NULL
+          AND ($2::timestamptz IS NULL OR updated_at > $2)
+          AND NOT EXISTS (
+            SELECT 1 FROM crm_pulled_customers p
+            WHERE p.connector_id = $3
+              AND p.customer_id = customers.id
+              AND p.updated_at = customers.updated_at
+          )
+        ORDER BY updated_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(since)
+    .bind(connector_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(customers)
+}
+
+/// Visits changed since the given time (all when `since` is None)
+pub async fn list_visits_changed_since(
+    pool: &PgPool,
+    user_id: Uuid,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<Visit>> {
+    let visits = sqlx::query_as::<_, Visit>(
+        r#"
+        SELECT
+            id, user_id, customer_id, crew_id, device_id,
+            scheduled_date, scheduled_time_start, scheduled_time_end,
+            status::text, visit_type,
+            actual_arrival, actual_departure,
+            result, field_notes,
+            requires_follow_up, follow_up_reason,
+            created_at, updated_at
+        FROM visits
+        WHERE user_id = $1
+          AND ($2::timestamptz IS NULL OR updated_at > $2)
+        ORDER BY updated_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(visits)
+}
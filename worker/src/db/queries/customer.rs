@@ -4,13 +4,19 @@
 use sqlx::PgPool;
 use uuid::Uuid;
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 
+use crate::types::analysis::LocatedCustomer;
 use crate::types::customer::{
-    Customer, CreateCustomerRequest, UpdateCustomerRequest, CustomerType,
+    Customer, CreateCustomerRequest, UpdateCustomerRequest, CustomerBulkPatch, CustomerType,
     CustomerListItem, ListCustomersRequest, CustomerSummaryResponse, SortEntry,
     ColumnFilter,
 };
+use crate::types::job::{GeocodeRerunCandidate, GeocodeRerunRequest};
+use crate::types::report::{AcquisitionSourceCounts, AcquisitionSourceRevenue};
+use crate::types::customer_hierarchy::{
+    HIERARCHY_LEVEL_BRANCH, HIERARCHY_LEVEL_PARENT, HIERARCHY_LEVEL_STANDALONE, HIERARCHY_LEVEL_TOP,
+};
 
 // ── Column filter builder ────────────────────────────────────────────────────
 
@@ -156,6 +162,7 @@ fn column_to_sql(id: &str) -> Option<&'static str> {
         "nextRevision" => Some("next_revision_date"),
         "geocodeStatus"=> Some("c.geocode_status"),
         "createdAt"    => Some("c.created_at"),
+        "customerCode" => Some("c.customer_code"),
         _              => None,
     }
 }
@@ -236,6 +243,22 @@ pub fn resolve_sort(req: &ListCustomersRequest) -> String {
     "c.name ASC NULLS LAST, c.id ASC".to_string()
 }
 
+/// WHERE condition for a hierarchy level filter. Unknown levels are ignored.
+pub fn hierarchy_level_condition(level: &str) -> Option<&'static str> {
+    match level {
+        HIERARCHY_LEVEL_PARENT => Some(
+            "EXISTS (SELECT 1 FROM customers b WHERE b.parent_customer_id = c.id AND b.is_anonymized = FALSE AND b.deleted_at IS NULL)",
+        ),
+        HIERARCHY_LEVEL_BRANCH => Some("c.parent_customer_id IS NOT NULL"),
+        HIERARCHY_LEVEL_STANDALONE => Some(
+            "c.parent_customer_id IS NULL AND NOT EXISTS \
+             (SELECT 1 FROM customers b WHERE b.parent_customer_id = c.id AND b.is_anonymized = FALSE AND b.deleted_at IS NULL)",
+        ),
+        HIERARCHY_LEVEL_TOP => Some("c.parent_customer_id IS NULL"),
+        _ => None,
+    }
+}
+
 /// Create a new customer
 pub async fn create_customer(
     pool: &PgPool,
@@ -251,26 +274,38 @@ pub async fn create_customer(
         "pending"
     };
     
+    // Explicit codes (imports) are kept, everything else gets the next one
+    let mut tx = pool.begin().await?;
+    let customer_code = match req.customer_code.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+        Some(code) => Some(code.to_string()),
+        None => super::customer_code::next_customer_code(&mut tx, user_id).await?,
+    };
+
     let customer = sqlx::query_as::<_, Customer>(
         r#"
         INSERT INTO customers (
             id, user_id, customer_type, name, contact_person, ico, dic,
             email, phone, phone_raw,
             street, city, postal_code, country,
-            lat, lng, geocode_status, notes, created_at, updated_at
+            lat, lng, geocode_status, notes, parent_customer_id, language, customer_code,
+            acquisition_source, referrer_customer_id,
+            created_at, updated_at
         )
         VALUES (
             $1, $2, $3, $4, $5, $6, $7,
             $8, $9, $10,
             $11, $12, $13, $14,
-            $15, $16, $17::geocode_status_enum, $18, NOW(), NOW()
+            $15, $16, $17::geocode_status_enum, $18, $19, $20, $21,
+            $22, $23,
+            NOW(), NOW()
         )
         RETURNING
             id, user_id, customer_type, name, contact_person, ico, dic,
             email, phone, phone_raw,
             street, city, postal_code, country,
             lat, lng, geocode_status::text, notes, created_at, updated_at,
-            is_abandoned, deleted_at
+            is_abandoned, deleted_at, parent_customer_id, language, customer_code,
+            acquisition_source, referrer_customer_id, lead_status, lead_lost_reason, lead_status_changed_at
         "#
     )
     .bind(Uuid::new_v4())
@@ -291,8 +326,14 @@ pub async fn create_customer(
     .bind(req.lng)
     .bind(geocode_status)
     .bind(&req.notes)
-    .fetch_one(pool)
+    .bind(req.parent_customer_id)
+    .bind(&req.language)
+    .bind(&customer_code)
+    .bind(&req.acquisition_source)
+    .bind(req.referrer_customer_id)
+    .fetch_one(&mut *tx)
     .await?;
+    tx.commit().await?;
 
     Ok(customer)
 }
@@ -310,7 +351,9 @@ pub async fn get_customer(
             email, phone, phone_raw,
             street, city, postal_code, country,
             lat, lng, geocode_status::text, notes, created_at, updated_at,
-            is_abandoned, deleted_at
+            is_abandoned, deleted_at, parent_customer_id, language, customer_code,
+            acquisition_source, referrer_customer_id, lead_status, lead_lost_reason, lead_status_changed_at,
+            risk_score, risk_flagged AS risk_flag
         FROM customers
         WHERE id = $1 AND user_id = $2 AND is_anonymized = FALSE
         "#
@@ -337,9 +380,10 @@ pub async fn list_customers(
             email, phone, phone_raw,
             street, city, postal_code, country,
             lat, lng, geocode_status::text, notes, created_at, updated_at,
-            is_abandoned, deleted_at
+            is_abandoned, deleted_at, parent_customer_id, language, customer_code,
+            acquisition_source, referrer_customer_id, lead_status, lead_lost_reason, lead_status_changed_at
         FROM customers
-        WHERE user_id = $1 AND is_anonymized = FALSE
+        WHERE user_id = $1 AND is_anonymized = FALSE AND deleted_at IS NULL
         ORDER BY name ASC
         LIMIT $2 OFFSET $3
         "#
@@ -353,6 +397,81 @@ pub async fn list_customers(
     Ok(customers)
 }
 
+/// List active customers with coordinates
+pub async fn list_located_customers(pool: &PgPool, user_id: Uuid) -> Result<Vec<LocatedCustomer>> {
+    let customers = sqlx::query_as::<_, LocatedCustomer>(
+        r#"
+        SELECT id, name, city, lat, lng
+        FROM customers
+        WHERE user_id = $1
+          AND lat IS NOT NULL AND lng IS NOT NULL
+          AND deleted_at IS NULL AND is_abandoned = FALSE AND is_anonymized = FALSE
+        ORDER BY name ASC
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(customers)
+}
+
+/// Customers matching a geocode re-run filter. Manually placed customers
+/// are never re-geocoded.
+const GEOCODE_RERUN_WHERE: &str = r#"
+        FROM customers c
+        WHERE c.user_id = $1
+          AND c.deleted_at IS NULL AND c.is_abandoned = FALSE AND c.is_anonymized = FALSE
+          AND c.geocode_status IS DISTINCT FROM 'manual'
+          AND NULLIF(TRIM(c.street), '') IS NOT NULL
+          AND ($2::text IS NULL OR LOWER(TRIM(c.city)) = LOWER($2))
+          AND ($3::text IS NULL OR REPLACE(COALESCE(c.postal_code, ''), ' ', '') >= $3)
+          AND ($4::text IS NULL OR REPLACE(COALESCE(c.postal_code, ''), ' ', '') <= $4)
+          AND (NOT $5 OR c.geocode_status::text = 'failed')
+          AND ($6::date IS NULL OR c.created_at >= $6::date)
+"#;
+
+/// Count the customers a geocode re-run would queue
+pub async fn count_geocode_rerun_customers(pool: &PgPool, user_id: Uuid, filter: &GeocodeRerunRequest) -> Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) {}", GEOCODE_RERUN_WHERE))
+        .bind(user_id)
+        .bind(filter.city())
+        .bind(filter.postal_code_from())
+        .bind(filter.postal_code_to())
+        .bind(filter.failed_only)
+        .bind(filter.imported_after)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(count)
+}
+
+/// List up to `limit` customers a geocode re-run would queue
+pub async fn list_geocode_rerun_customers(
+    pool: &PgPool,
+    user_id: Uuid,
+    filter: &GeocodeRerunRequest,
+    limit: i64,
+) -> Result<Vec<GeocodeRerunCandidate>> {
+    let sql = format!(
+        "SELECT c.id, c.name, c.street, c.city, c.postal_code, COALESCE(c.geocode_status::text, 'pending') AS geocode_status {} \
+         ORDER BY c.created_at DESC, c.id LIMIT $7",
+        GEOCODE_RERUN_WHERE
+    );
+    let customers = sqlx::query_as::<_, GeocodeRerunCandidate>(&sql)
+        .bind(user_id)
+        .bind(filter.city())
+        .bind(filter.postal_code_from())
+        .bind(filter.postal_code_to())
+        .bind(filter.failed_only)
+        .bind(filter.imported_after)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(customers)
+}
+
 /// Update customer coordinates (after geocoding)
 pub async fn update_customer_coordinates(
     pool: &PgPool,
@@ -376,6 +495,27 @@ pub async fn update_customer_coordinates(
     Ok(())
 }
 
+/// Mark customer coordinates as manually placed (skipped by batch geocoding)
+pub async fn mark_coordinates_manual(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE customers
+        SET geocode_status = 'manual', updated_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND lat IS NOT NULL AND lng IS NOT NULL
+        "#
+    )
+    .bind(customer_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 /// Reset customer coordinates and mark geocode as pending
 pub async fn reset_customer_coordinates(
     pool: &PgPool,
@@ -397,6 +537,47 @@ pub async fn reset_customer_coordinates(
     Ok(())
 }
 
+/// Apply one patch to many customers in a single statement, returning the
+/// customers that changed (missing, anonymized and deleted ones are left out)
+pub async fn bulk_update_customers(
+    pool: &PgPool,
+    user_id: Uuid,
+    ids: &[Uuid],
+    patch: &CustomerBulkPatch,
+) -> Result<Vec<Customer>> {
+    let customers = sqlx::query_as::<_, Customer>(
+        r#"
+        UPDATE customers
+        SET
+            customer_type = COALESCE($3, customer_type),
+            country = COALESCE($4, country),
+            language = CASE WHEN $5::text IS NULL THEN language ELSE NULLIF($5, '') END,
+            acquisition_source = CASE WHEN $6::text IS NULL THEN acquisition_source ELSE NULLIF($6, '') END,
+            referrer_customer_id = CASE WHEN $6::text IS NULL THEN referrer_customer_id ELSE NULL END,
+            updated_at = NOW()
+        WHERE id = ANY($1) AND user_id = $2
+          AND is_anonymized = FALSE AND deleted_at IS NULL
+        RETURNING
+            id, user_id, customer_type, name, contact_person, ico, dic,
+            email, phone, phone_raw,
+            street, city, postal_code, country,
+            lat, lng, geocode_status::text, notes, created_at, updated_at,
+            is_abandoned, deleted_at, parent_customer_id, language, customer_code,
+            acquisition_source, referrer_customer_id, lead_status, lead_lost_reason, lead_status_changed_at
+        "#
+    )
+    .bind(ids)
+    .bind(user_id)
+    .bind(patch.customer_type)
+    .bind(&patch.country)
+    .bind(&patch.language)
+    .bind(&patch.acquisition_source)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(customers)
+}
+
 /// Update a customer
 pub async fn update_customer(
     pool: &PgPool,
@@ -434,15 +615,19 @@ pub async fn update_customer(
             lng = COALESCE($16, lng),
             geocode_status = COALESCE($17::geocode_status_enum, geocode_status),
             notes = COALESCE($18, notes),
+            language = CASE WHEN $19::text IS NULL THEN language ELSE NULLIF($19, '') END,
+            acquisition_source = CASE WHEN $20::text IS NULL THEN acquisition_source ELSE NULLIF($20, '') END,
+            referrer_customer_id = CASE WHEN $20::text IS NULL THEN referrer_customer_id ELSE $21 END,
             updated_at = NOW()
         WHERE id = $1 AND user_id = $2
-          AND is_anonymized = FALSE
+          AND is_anonymized = FALSE AND deleted_at IS NULL
         RETURNING
             id, user_id, customer_type, name, contact_person, ico, dic,
             email, phone, phone_raw,
             street, city, postal_code, country,
             lat, lng, geocode_status::text, notes, created_at, updated_at,
-            is_abandoned, deleted_at
+            is_abandoned, deleted_at, parent_customer_id, language, customer_code,
+            acquisition_source, referrer_customer_id, lead_status, lead_lost_reason, lead_status_changed_at
         "#
     )
     .bind(req.id)
@@ -463,17 +648,84 @@ pub async fn update_customer(
     .bind(req.lng)
     .bind(geocode_status_update)
     .bind(&req.notes)
+    .bind(&req.language)
+    .bind(&req.acquisition_source)
+    .bind(req.referrer_customer_id)
     .fetch_optional(pool)
     .await?;
 
     Ok(customer)
 }
 
-/// Delete a customer
+/// Soft-delete a customer; restorable until purged
 pub async fn delete_customer(
     pool: &PgPool,
     user_id: Uuid,
     customer_id: Uuid,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE customers SET deleted_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND is_anonymized = FALSE AND deleted_at IS NULL
+        "#
+    )
+    .bind(customer_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Bring a deleted customer back; None when it is not a deleted, still
+/// intact customer of the user
+pub async fn restore_customer(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
+) -> Result<Option<Customer>> {
+    let customer = sqlx::query_as::<_, Customer>(
+        r#"
+        UPDATE customers SET deleted_at = NULL, updated_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND is_anonymized = FALSE AND deleted_at IS NOT NULL
+        RETURNING
+            id, user_id, customer_type, name, contact_person, ico, dic,
+            email, phone, phone_raw,
+            street, city, postal_code, country,
+            lat, lng, geocode_status::text, notes, created_at, updated_at,
+            is_abandoned, deleted_at, parent_customer_id, language, customer_code,
+            acquisition_source, referrer_customer_id, lead_status, lead_lost_reason, lead_status_changed_at
+        "#
+    )
+    .bind(customer_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(customer)
+}
+
+/// Customers deleted longer than `days` ago and not purged yet: (user_id, id)
+pub async fn list_customers_to_purge(pool: &PgPool, days: i32) -> Result<Vec<(Uuid, Uuid)>> {
+    let rows: Vec<(Uuid, Uuid)> = sqlx::query_as(
+        r#"
+        SELECT user_id, id FROM customers
+        WHERE deleted_at < NOW() - make_interval(days => $1::int) AND is_anonymized = FALSE
+        "#
+    )
+    .bind(days)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Purge a deleted customer: anonymize the record and the PII tied to it
+/// (the row stays, so historical references keep working)
+pub async fn purge_customer(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
 ) -> Result<bool> {
     let anonymized_name = format!("Anonymní zákazník {}", &customer_id.to_string()[..8]);
     let mut tx = pool.begin().await?;
@@ -580,9 +832,10 @@ pub async fn get_random_customers_with_coords(
             email, phone, phone_raw,
             street, city, postal_code, country,
             lat, lng, geocode_status::text, notes, created_at, updated_at,
-            is_abandoned, deleted_at
+            is_abandoned, deleted_at, parent_customer_id, language, customer_code,
+            acquisition_source, referrer_customer_id, lead_status, lead_lost_reason, lead_status_changed_at
         FROM customers
-        WHERE user_id = $1 AND is_anonymized = FALSE AND lat IS NOT NULL AND lng IS NOT NULL
+        WHERE user_id = $1 AND is_anonymized = FALSE AND deleted_at IS NULL AND lat IS NOT NULL AND lng IS NOT NULL
         ORDER BY RANDOM()
         LIMIT $2
         "#
@@ -615,6 +868,7 @@ pub async fn list_customers_extended(
     let mut conditions = vec![
         "c.user_id = $1".to_string(),
         "c.is_anonymized = FALSE".to_string(),
+        "c.deleted_at IS NULL".to_string(),
     ];
     let mut param_idx: usize = 1;
 
@@ -623,7 +877,7 @@ pub async fn list_customers_extended(
     if search_pattern.is_some() {
         param_idx += 1;
         conditions.push(format!(
-            "(LOWER(c.name) LIKE ${0} OR LOWER(c.city) LIKE ${0} OR LOWER(c.street) LIKE ${0} OR LOWER(c.email) LIKE ${0} OR c.phone LIKE ${0})",
+            "(LOWER(c.name) LIKE ${0} OR LOWER(c.city) LIKE ${0} OR LOWER(c.street) LIKE ${0} OR LOWER(c.email) LIKE ${0} OR c.phone LIKE ${0} OR LOWER(c.customer_code) LIKE ${0})",
             param_idx
         ));
     }
@@ -642,6 +896,15 @@ pub async fn list_customers_extended(
         let _ = ctype;
     }
 
+    // Hierarchy filters
+    if let Some(cond) = req.hierarchy_level.as_deref().and_then(hierarchy_level_condition) {
+        conditions.push(cond.to_string());
+    }
+    if req.parent_customer_id.is_some() {
+        param_idx += 1;
+        conditions.push(format!("c.parent_customer_id = ${}", param_idx));
+    }
+
     // Apply column filters (WHERE conditions first, before joining)
     let col_filter_clauses = if let Some(ref filters) = req.column_filters {
         build_column_filter_clauses(filters, &mut param_idx)
@@ -726,6 +989,7 @@ pub async fn list_customers_extended(
             FROM devices d
             LEFT JOIN revisions r ON d.id = r.device_id
             LEFT JOIN visits v ON (v.device_id = d.id OR (v.device_id IS NULL AND v.customer_id = d.customer_id))
+            WHERE d.deleted_at IS NULL
             GROUP BY d.id, d.customer_id, d.revision_interval_months, d.installation_date
         )
         SELECT
@@ -745,13 +1009,19 @@ pub async fn list_customers_extended(
             COALESCE(COUNT(DISTINCT ds.device_id), 0) as device_count,
             MIN(r.due_date) FILTER (WHERE r.status NOT IN ('completed', 'cancelled') AND r.due_date >= CURRENT_DATE) as next_revision_date,
             COALESCE(COUNT(DISTINCT ds.device_id) FILTER (WHERE ds.is_overdue), 0) as overdue_count,
-            COALESCE(COUNT(DISTINCT ds.device_id) FILTER (WHERE ds.is_never_serviced), 0) as never_serviced_count
+            COALESCE(COUNT(DISTINCT ds.device_id) FILTER (WHERE ds.is_never_serviced), 0) as never_serviced_count,
+            c.parent_customer_id,
+            (SELECT p.name FROM customers p WHERE p.id = c.parent_customer_id) as parent_name,
+            (SELECT COUNT(*) FROM customers b
+              WHERE b.parent_customer_id = c.id AND b.is_anonymized = FALSE AND b.deleted_at IS NULL) as branch_count,
+            c.customer_code
         FROM customers c
         LEFT JOIN device_status ds ON c.id = ds.customer_id
         LEFT JOIN revisions r ON c.id = r.customer_id
         WHERE {}
         GROUP BY c.id, c.user_id, c.customer_type, c.name, c.email, c.phone,
-                 c.street, c.city, c.postal_code, c.lat, c.lng, c.geocode_status, c.created_at
+                 c.street, c.city, c.postal_code, c.lat, c.lng, c.geocode_status, c.created_at,
+                 c.parent_customer_id, c.customer_code
         {}
         ORDER BY {}
         LIMIT ${} OFFSET ${}
@@ -779,6 +1049,10 @@ pub async fn list_customers_extended(
         query_builder = query_builder.bind(ctype);
     }
 
+    if let Some(parent_id) = req.parent_customer_id {
+        query_builder = query_builder.bind(parent_id);
+    }
+
     // Column filter values must be bound before next_revision_within_days
     // because build_column_filter_clauses allocates $N placeholders before
     // the HAVING clause for next_revision_within_days.
@@ -836,6 +1110,7 @@ pub async fn list_customers_extended(
                     FROM devices d
                     LEFT JOIN revisions r ON d.id = r.device_id
                     LEFT JOIN visits v ON (v.device_id = d.id OR (v.device_id IS NULL AND v.customer_id = d.customer_id))
+                    WHERE d.deleted_at IS NULL
                     GROUP BY d.id, d.customer_id, d.revision_interval_months, d.installation_date
                 )
                 SELECT c.id
@@ -856,7 +1131,7 @@ pub async fn list_customers_extended(
             SELECT COUNT(*) FROM (
                 SELECT c.id
                 FROM customers c
-                LEFT JOIN devices d ON c.id = d.customer_id
+                LEFT JOIN devices d ON c.id = d.customer_id AND d.deleted_at IS NULL
                 LEFT JOIN revisions r ON c.id = r.customer_id
                 WHERE {}
                 GROUP BY c.id
@@ -883,6 +1158,10 @@ pub async fn list_customers_extended(
         count_builder = count_builder.bind(ctype);
     }
 
+    if let Some(parent_id) = req.parent_customer_id {
+        count_builder = count_builder.bind(parent_id);
+    }
+
     for bind_val in col_filter_clauses.bind_values.iter().cloned() {
         count_builder = match bind_val {
             FilterBindValue::Text(s) => count_builder.bind(s),
@@ -943,6 +1222,7 @@ pub async fn get_column_distinct_values(
     let mut conditions: Vec<String> = vec![
         "c.user_id = $1".to_string(),
         "c.is_anonymized = FALSE".to_string(),
+        "c.deleted_at IS NULL".to_string(),
     ];
     let mut param_idx = 1usize;
 
@@ -950,7 +1230,7 @@ pub async fn get_column_distinct_values(
     if search_pattern.is_some() {
         param_idx += 1;
         conditions.push(format!(
-            "(LOWER(c.name) LIKE ${0} OR LOWER(c.city) LIKE ${0} OR LOWER(c.street) LIKE ${0} OR LOWER(c.email) LIKE ${0} OR c.phone LIKE ${0})",
+            "(LOWER(c.name) LIKE ${0} OR LOWER(c.city) LIKE ${0} OR LOWER(c.street) LIKE ${0} OR LOWER(c.email) LIKE ${0} OR c.phone LIKE ${0} OR LOWER(c.customer_code) LIKE ${0})",
             param_idx
         ));
     }
@@ -1052,6 +1332,7 @@ pub async fn get_column_distinct_values(
                     FROM devices d
                     LEFT JOIN revisions r ON d.id = r.device_id
                     LEFT JOIN visits v ON (v.device_id = d.id OR (v.device_id IS NULL AND v.customer_id = d.customer_id))
+                    WHERE d.deleted_at IS NULL
                     GROUP BY d.id, d.customer_id, d.revision_interval_months, d.installation_date
                 )
                 SELECT
@@ -1178,7 +1459,7 @@ pub async fn get_customer_summary(
             COUNT(*) FILTER (WHERE phone IS NULL OR phone = '') as customers_without_phone,
             COUNT(*) FILTER (WHERE email IS NULL OR email = '') as customers_without_email
         FROM customers
-        WHERE user_id = $1 AND is_anonymized = FALSE
+        WHERE user_id = $1 AND is_anonymized = FALSE AND deleted_at IS NULL
         "#
     )
     .bind(user_id)
@@ -1191,7 +1472,7 @@ pub async fn get_customer_summary(
         SELECT COUNT(*)
         FROM devices d
         INNER JOIN customers c ON d.customer_id = c.id
-        WHERE c.user_id = $1 AND c.is_anonymized = FALSE
+        WHERE c.user_id = $1 AND c.is_anonymized = FALSE AND c.deleted_at IS NULL AND d.deleted_at IS NULL
         "#
     )
     .bind(user_id)
@@ -1207,7 +1488,7 @@ pub async fn get_customer_summary(
             COUNT(*) FILTER (WHERE r.status = 'scheduled') as scheduled
         FROM revisions r
         INNER JOIN customers c ON r.customer_id = c.id
-        WHERE c.user_id = $1 AND c.is_anonymized = FALSE
+        WHERE c.user_id = $1 AND c.is_anonymized = FALSE AND c.deleted_at IS NULL
         "#
     )
     .bind(user_id)
@@ -1245,7 +1526,7 @@ pub async fn get_customer_summary(
             INNER JOIN customers c ON d.customer_id = c.id
             LEFT JOIN revisions r ON d.id = r.device_id
             LEFT JOIN visits v ON (v.device_id = d.id OR (v.device_id IS NULL AND v.customer_id = d.customer_id))
-            WHERE c.user_id = $1 AND c.is_anonymized = FALSE
+            WHERE c.user_id = $1 AND c.is_anonymized = FALSE AND c.deleted_at IS NULL AND d.deleted_at IS NULL
             GROUP BY d.id, d.customer_id, d.revision_interval_months, d.installation_date
         )
         SELECT
@@ -1293,7 +1574,7 @@ pub async fn list_pending_geocode(
         r#"
         SELECT id
         FROM customers
-        WHERE user_id = $1 AND is_anonymized = FALSE AND geocode_status = 'pending'
+        WHERE user_id = $1 AND is_anonymized = FALSE AND deleted_at IS NULL AND geocode_status = 'pending'
         ORDER BY created_at DESC
         "#
     )
@@ -1318,13 +1599,14 @@ pub async fn abandon_customer(
         r#"
         UPDATE customers
         SET is_abandoned = TRUE, updated_at = NOW()
-        WHERE id = $1 AND user_id = $2 AND is_anonymized = FALSE
+        WHERE id = $1 AND user_id = $2 AND is_anonymized = FALSE AND deleted_at IS NULL
         RETURNING
             id, user_id, customer_type, name, contact_person, ico, dic,
             email, phone, phone_raw,
             street, city, postal_code, country,
             lat, lng, geocode_status::text, notes, created_at, updated_at,
-            is_abandoned, deleted_at
+            is_abandoned, deleted_at, parent_customer_id, language, customer_code,
+            acquisition_source, referrer_customer_id, lead_status, lead_lost_reason, lead_status_changed_at
         "#,
     )
     .bind(customer_id)
@@ -1345,13 +1627,14 @@ pub async fn unabandon_customer(
         r#"
         UPDATE customers
         SET is_abandoned = FALSE, updated_at = NOW()
-        WHERE id = $1 AND user_id = $2 AND is_anonymized = FALSE
+        WHERE id = $1 AND user_id = $2 AND is_anonymized = FALSE AND deleted_at IS NULL
         RETURNING
             id, user_id, customer_type, name, contact_person, ico, dic,
             email, phone, phone_raw,
             street, city, postal_code, country,
             lat, lng, geocode_status::text, notes, created_at, updated_at,
-            is_abandoned, deleted_at
+            is_abandoned, deleted_at, parent_customer_id, language, customer_code,
+            acquisition_source, referrer_customer_id, lead_status, lead_lost_reason, lead_status_changed_at
         "#,
     )
     .bind(customer_id)
@@ -1417,6 +1700,82 @@ pub async fn anonymize_customer(
     Ok(result.rows_affected() > 0)
 }
 
+/// New customers and completed visits per acquisition source in a period
+pub async fn acquisition_source_counts(
+    pool: &PgPool,
+    user_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<AcquisitionSourceCounts>> {
+    let counts = sqlx::query_as::<_, AcquisitionSourceCounts>(
+        r#"
+        WITH new_customers AS (
+            SELECT COALESCE(acquisition_source, '') AS source, COUNT(*) AS new_customers
+            FROM customers
+            WHERE user_id = $1 AND is_anonymized = FALSE AND deleted_at IS NULL
+              AND created_at::date BETWEEN $2 AND $3
+            GROUP BY 1
+        ),
+        jobs AS (
+            SELECT
+                COALESCE(c.acquisition_source, '') AS source,
+                COUNT(DISTINCT v.customer_id) AS customers_with_jobs,
+                COUNT(*) AS completed_visits
+            FROM visits v
+            JOIN customers c ON c.id = v.customer_id
+            WHERE v.user_id = $1 AND v.status = 'completed'
+              AND v.scheduled_date BETWEEN $2 AND $3
+            GROUP BY 1
+        )
+        SELECT
+            COALESCE(n.source, j.source) AS source,
+            COALESCE(n.new_customers, 0) AS new_customers,
+            COALESCE(j.customers_with_jobs, 0) AS customers_with_jobs,
+            COALESCE(j.completed_visits, 0) AS completed_visits
+        FROM new_customers n
+        FULL OUTER JOIN jobs j ON j.source = n.source
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(counts)
+}
+
+/// Net amount billed on completed visits per acquisition source and currency
+pub async fn acquisition_source_revenue(
+    pool: &PgPool,
+    user_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<AcquisitionSourceRevenue>> {
+    let revenue = sqlx::query_as::<_, AcquisitionSourceRevenue>(
+        r#"
+        SELECT
+            COALESCE(c.acquisition_source, '') AS source,
+            wi.currency,
+            SUM(wi.amount_minor)::bigint AS amount_minor
+        FROM visit_work_items wi
+        JOIN visits v ON v.id = wi.visit_id
+        JOIN customers c ON c.id = v.customer_id
+        WHERE v.user_id = $1 AND v.status = 'completed'
+          AND v.scheduled_date BETWEEN $2 AND $3
+          AND wi.amount_minor IS NOT NULL AND wi.currency IS NOT NULL
+        GROUP BY 1, 2
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(revenue)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1920,7 +2279,7 @@ mod tests {
         for col in &[
             "name", "type", "city", "street", "postalCode",
             "phone", "email", "deviceCount", "nextRevision",
-            "geocodeStatus", "createdAt",
+            "geocodeStatus", "createdAt", "customerCode",
         ] {
             let result = build_order_by(&[se(col, "asc")]);
             assert!(
@@ -2257,5 +2616,13 @@ mod tests {
         let expr = column_to_distinct_sql("geocodeStatus").unwrap();
         assert!(expr.contains("::text"), "geocodeStatus must cast to text, got: {expr}");
     }
-}
 
+    #[test]
+    fn hierarchy_level_condition_whitelists_levels() {
+        assert_eq!(hierarchy_level_condition("branch"), Some("c.parent_customer_id IS NOT NULL"));
+        assert_eq!(hierarchy_level_condition("top"), Some("c.parent_customer_id IS NULL"));
+        assert!(hierarchy_level_condition("parent").unwrap().contains("EXISTS"));
+        assert!(hierarchy_level_condition("standalone").unwrap().contains("NOT EXISTS"));
+        assert!(hierarchy_level_condition("'; DROP TABLE customers; --").is_none());
+    }
+}
@@ -0,0 +1,68 @@
+#![allow(dead_code)]
+//! Customer code queries
+
+use anyhow::Result;
+use chrono::{Datelike, Utc};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::types::customer_code::CustomerCodePattern;
+
+/// Allocate the next code of the user's pattern.
+///
+/// Runs inside the customer insert transaction: the counter row stays locked
+/// until commit, so concurrent creations queue up and a rollback returns the
+/// number. Returns None when the pattern is empty (codes turned off).
+pub async fn next_customer_code(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+) -> Result<Option<String>> {
+    let pattern: Option<(String,)> = sqlx::query_as("SELECT customer_code_pattern FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    let Some((pattern,)) = pattern else {
+        return Ok(None);
+    };
+    if pattern.trim().is_empty() {
+        return Ok(None);
+    }
+    let pattern = match CustomerCodePattern::parse(pattern.trim()) {
+        Ok(pattern) => pattern,
+        Err(e) => {
+            warn!("Invalid customer code pattern {:?} of user {}: {}", pattern, user_id, e);
+            return Ok(None);
+        }
+    };
+
+    let year = Utc::now().year();
+    let period_year = pattern.period_year(year);
+    loop {
+        let (sequence,): (i32,) = sqlx::query_as(
+            r#"
+            INSERT INTO customer_code_counters (user_id, period_year, last_number)
+            VALUES ($1, $2, 1)
+            ON CONFLICT (user_id, period_year)
+            DO UPDATE SET last_number = customer_code_counters.last_number + 1
+            RETURNING last_number
+            "#,
+        )
+        .bind(user_id)
+        .bind(period_year)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        // Skip numbers taken by hand-entered codes or an earlier pattern
+        let code = pattern.format(year, sequence);
+        let (taken,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS(SELECT 1 FROM customers WHERE user_id = $1 AND customer_code = $2)",
+        )
+        .bind(user_id)
+        .bind(&code)
+        .fetch_one(&mut **tx)
+        .await?;
+        if !taken {
+            return Ok(Some(code));
+        }
+    }
+}
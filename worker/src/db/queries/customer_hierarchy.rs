@@ -0,0 +1,166 @@
+#![allow(dead_code)]
+//! Customer hierarchy database queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::customer_hierarchy::{CustomerBranch, HierarchyMemberSummary};
+use crate::types::RevisionStatus;
+
+/// Why a customer cannot become a branch of the given parent
+#[derive(Debug, PartialEq)]
+pub enum ParentError {
+    ParentNotFound,
+    SelfParent,
+    ParentIsBranch,
+    CustomerHasBranches,
+}
+
+impl std::fmt::Display for ParentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            ParentError::ParentNotFound => "Parent customer not found",
+            ParentError::SelfParent => "A customer cannot be its own parent",
+            ParentError::ParentIsBranch => "The parent customer is itself a branch",
+            ParentError::CustomerHasBranches => "A customer with branches cannot become a branch",
+        };
+        f.write_str(message)
+    }
+}
+
+/// Check that `parent_id` may become the parent of `customer_id`
+/// (None for a customer that does not exist yet). Hierarchies are one level deep.
+pub async fn check_parent(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Option<Uuid>,
+    parent_id: Uuid,
+) -> Result<std::result::Result<(), ParentError>> {
+    if customer_id == Some(parent_id) {
+        return Ok(Err(ParentError::SelfParent));
+    }
+
+    let parent: Option<(Option<Uuid>,)> = sqlx::query_as(
+        r#"
+        SELECT parent_customer_id FROM customers
+        WHERE id = $1 AND user_id = $2 AND is_anonymized = FALSE AND deleted_at IS NULL
+        "#,
+    )
+    .bind(parent_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    match parent {
+        None => return Ok(Err(ParentError::ParentNotFound)),
+        Some((Some(_),)) => return Ok(Err(ParentError::ParentIsBranch)),
+        Some((None,)) => {}
+    }
+
+    if let Some(customer_id) = customer_id {
+        let (has_branches,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS (SELECT 1 FROM customers WHERE parent_customer_id = $1 AND is_anonymized = FALSE AND deleted_at IS NULL)",
+        )
+        .bind(customer_id)
+        .fetch_one(pool)
+        .await?;
+        if has_branches {
+            return Ok(Err(ParentError::CustomerHasBranches));
+        }
+    }
+
+    Ok(Ok(()))
+}
+
+/// Attach a customer to a parent, or detach it. Returns false if the customer does not exist.
+pub async fn set_parent(pool: &PgPool, user_id: Uuid, customer_id: Uuid, parent_id: Option<Uuid>) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE customers SET parent_customer_id = $3, updated_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND is_anonymized = FALSE AND deleted_at IS NULL
+        "#,
+    )
+    .bind(customer_id)
+    .bind(user_id)
+    .bind(parent_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Parent company of a customer's group: its parent, or the customer itself
+pub async fn resolve_parent(pool: &PgPool, user_id: Uuid, customer_id: Uuid) -> Result<Option<Uuid>> {
+    let row: Option<(Uuid,)> = sqlx::query_as(
+        r#"
+        SELECT COALESCE(parent_customer_id, id) FROM customers
+        WHERE id = $1 AND user_id = $2 AND is_anonymized = FALSE AND deleted_at IS NULL
+        "#,
+    )
+    .bind(customer_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(id,)| id))
+}
+
+/// Branches of a parent company, by name
+pub async fn list_branches(pool: &PgPool, user_id: Uuid, parent_id: Uuid) -> Result<Vec<CustomerBranch>> {
+    let branches = sqlx::query_as::<_, CustomerBranch>(
+        r#"
+        SELECT
+            c.id, c.name, c.street, c.city,
+            (SELECT COUNT(*) FROM devices d WHERE d.customer_id = c.id AND d.deleted_at IS NULL) AS device_count
+        FROM customers c
+        WHERE c.parent_customer_id = $1 AND c.user_id = $2 AND c.is_anonymized = FALSE AND c.deleted_at IS NULL
+        ORDER BY c.name ASC NULLS LAST, c.id
+        "#,
+    )
+    .bind(parent_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(branches)
+}
+
+/// Per-member figures of a parent company and its branches, parent first
+pub async fn group_summary(pool: &PgPool, user_id: Uuid, parent_id: Uuid) -> Result<Vec<HierarchyMemberSummary>> {
+    let query = format!(
+        r#"
+        SELECT
+            c.id AS customer_id, c.name, c.city,
+            c.id = $1 AS is_parent,
+            (SELECT COUNT(*) FROM devices d WHERE d.customer_id = c.id AND d.deleted_at IS NULL) AS device_count,
+            (SELECT COUNT(*) FROM revisions r
+              WHERE r.customer_id = c.id AND r.due_date < CURRENT_DATE
+                AND r.status NOT IN ('{completed}', '{cancelled}')) AS revisions_overdue,
+            (SELECT COUNT(*) FROM revisions r
+              WHERE r.customer_id = c.id
+                AND r.due_date BETWEEN CURRENT_DATE AND CURRENT_DATE + 30
+                AND r.status NOT IN ('{completed}', '{cancelled}')) AS revisions_due_30d,
+            (SELECT COUNT(*) FROM revisions r
+              WHERE r.customer_id = c.id AND r.scheduled_date >= CURRENT_DATE
+                AND r.status NOT IN ('{completed}', '{cancelled}')) AS revisions_scheduled,
+            (SELECT COUNT(*) FROM visits v
+              WHERE v.customer_id = c.id AND v.status = 'completed'
+                AND v.scheduled_date >= CURRENT_DATE - INTERVAL '12 months') AS visits_completed_12m
+        FROM customers c
+        WHERE (c.id = $1 OR c.parent_customer_id = $1)
+          AND c.user_id = $2 AND c.is_anonymized = FALSE AND c.deleted_at IS NULL
+        ORDER BY c.id = $1 DESC, c.name ASC NULLS LAST, c.id
+        "#,
+        completed = RevisionStatus::Completed.as_str(),
+        cancelled = RevisionStatus::Cancelled.as_str(),
+    );
+
+    let members = sqlx::query_as::<_, HierarchyMemberSummary>(&query)
+        .bind(parent_id)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(members)
+}
@@ -0,0 +1,30 @@
+#![allow(dead_code)]
+//! Customer reference audit queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::customer_reference::{CustomerReferenceKind, CustomerReferenceReport};
+
+/// Count every reference to a customer. None if the customer does not exist.
+pub async fn customer_reference_report(pool: &PgPool, customer_id: Uuid) -> Result<Option<CustomerReferenceReport>> {
+    let customer: Option<(Uuid, Option<String>)> = sqlx::query_as("SELECT user_id, name FROM customers WHERE id = $1")
+        .bind(customer_id)
+        .fetch_optional(pool)
+        .await?;
+    let Some((user_id, name)) = customer else {
+        return Ok(None);
+    };
+
+    let mut counts = Vec::with_capacity(CustomerReferenceKind::ALL.len());
+    for kind in CustomerReferenceKind::ALL {
+        let count = sqlx::query_scalar::<_, i64>(kind.count_sql())
+            .bind(customer_id)
+            .fetch_one(pool)
+            .await?;
+        counts.push((kind, count));
+    }
+
+    Ok(Some(CustomerReferenceReport::new(customer_id, user_id, name, counts)))
+}
@@ -0,0 +1,243 @@
+#![allow(dead_code)]
+//! Customer risk scoring database queries
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::customer_risk::{
+    CreateRiskIncidentRequest, CustomerRiskIncident, CustomerRiskScoreChange, CustomerRiskSettings, RiskCounts,
+};
+use crate::types::WorkResult;
+
+/// Scoring settings of a user (defaults when never saved)
+pub async fn get_settings(pool: &PgPool, user_id: Uuid) -> Result<CustomerRiskSettings> {
+    let settings = sqlx::query_as::<_, CustomerRiskSettings>(
+        r#"
+        SELECT
+            no_show_weight, late_payment_weight, unpaid_weight, dispute_weight,
+            access_denied_weight, lookback_months, flag_threshold
+        FROM customer_risk_settings
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(settings.unwrap_or_default())
+}
+
+/// Save the scoring settings of a user
+pub async fn save_settings(pool: &PgPool, user_id: Uuid, settings: &CustomerRiskSettings) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO customer_risk_settings (
+            user_id, no_show_weight, late_payment_weight, unpaid_weight, dispute_weight,
+            access_denied_weight, lookback_months, flag_threshold
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (user_id) DO UPDATE SET
+            no_show_weight = EXCLUDED.no_show_weight,
+            late_payment_weight = EXCLUDED.late_payment_weight,
+            unpaid_weight = EXCLUDED.unpaid_weight,
+            dispute_weight = EXCLUDED.dispute_weight,
+            access_denied_weight = EXCLUDED.access_denied_weight,
+            lookback_months = EXCLUDED.lookback_months,
+            flag_threshold = EXCLUDED.flag_threshold,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(user_id)
+    .bind(settings.no_show_weight)
+    .bind(settings.late_payment_weight)
+    .bind(settings.unpaid_weight)
+    .bind(settings.dispute_weight)
+    .bind(settings.access_denied_weight)
+    .bind(settings.lookback_months)
+    .bind(settings.flag_threshold)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record an incident; None when the customer is not the user's
+pub async fn create_incident(
+    pool: &PgPool,
+    user_id: Uuid,
+    req: &CreateRiskIncidentRequest,
+) -> Result<Option<CustomerRiskIncident>> {
+    let incident = sqlx::query_as::<_, CustomerRiskIncident>(
+        r#"
+        INSERT INTO customer_risk_incidents (user_id, customer_id, kind, note, occurred_on)
+        SELECT $1, c.id, $3, $4, COALESCE($5, CURRENT_DATE)
+        FROM customers c
+        WHERE c.id = $2 AND c.user_id = $1 AND c.deleted_at IS NULL
+        RETURNING id, customer_id, kind, note, occurred_on, created_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(req.customer_id)
+    .bind(&req.kind)
+    .bind(req.note.as_deref().map(str::trim).filter(|note| !note.is_empty()))
+    .bind(req.occurred_on)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(incident)
+}
+
+/// Incidents of a customer, newest first
+pub async fn list_incidents(pool: &PgPool, user_id: Uuid, customer_id: Uuid) -> Result<Vec<CustomerRiskIncident>> {
+    let incidents = sqlx::query_as::<_, CustomerRiskIncident>(
+        r#"
+        SELECT id, customer_id, kind, note, occurred_on, created_at
+        FROM customer_risk_incidents
+        WHERE user_id = $1 AND customer_id = $2
+        ORDER BY occurred_on DESC, created_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .bind(customer_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(incidents)
+}
+
+/// Delete an incident, returning its customer
+pub async fn delete_incident(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<Option<Uuid>> {
+    let customer_id: Option<Uuid> = sqlx::query_scalar(
+        "DELETE FROM customer_risk_incidents WHERE id = $1 AND user_id = $2 RETURNING customer_id",
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(customer_id)
+}
+
+/// No-shows and incidents of a customer since a date
+pub async fn count_occurrences(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
+    since: NaiveDate,
+) -> Result<RiskCounts> {
+    let counts = sqlx::query_as::<_, RiskCounts>(
+        r#"
+        SELECT
+            (
+                SELECT COUNT(*) FROM visits v
+                WHERE v.user_id = $1 AND v.customer_id = $2 AND v.result = $4 AND v.scheduled_date >= $3
+            ) AS no_shows,
+            COUNT(*) FILTER (WHERE i.kind = 'late_payment') AS late_payments,
+            COUNT(*) FILTER (WHERE i.kind = 'unpaid') AS unpaid,
+            COUNT(*) FILTER (WHERE i.kind = 'dispute') AS disputes,
+            COUNT(*) FILTER (WHERE i.kind = 'access_denied') AS access_denied
+        FROM customer_risk_incidents i
+        WHERE i.user_id = $1 AND i.customer_id = $2 AND i.occurred_on >= $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(customer_id)
+    .bind(since)
+    .bind(WorkResult::CustomerAbsent.as_str())
+    .fetch_one(pool)
+    .await?;
+
+    Ok(counts)
+}
+
+/// Store a recomputed score, logging it when score or flag changed.
+/// Returns whether anything changed.
+pub async fn set_score(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
+    score: i32,
+    flagged: bool,
+    reason: &str,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        WITH old AS (
+            SELECT risk_score FROM customers
+            WHERE id = $1 AND user_id = $2 AND (risk_score <> $3 OR risk_flagged <> $4)
+            FOR UPDATE
+        ), changed AS (
+            UPDATE customers c
+            SET risk_score = $3, risk_flagged = $4
+            FROM old
+            WHERE c.id = $1
+            RETURNING old.risk_score AS old_score
+        )
+        INSERT INTO customer_risk_score_changes (user_id, customer_id, old_score, new_score, flagged, reason)
+        SELECT $2, $1, old_score, $3, $4, $5 FROM changed
+        "#,
+    )
+    .bind(customer_id)
+    .bind(user_id)
+    .bind(score)
+    .bind(flagged)
+    .bind(reason)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Customers whose score may be non-zero, of one user or of everyone:
+/// scored or flagged ones and those with any incident or no-show
+pub async fn list_scorable_customers(pool: &PgPool, user_id: Option<Uuid>) -> Result<Vec<(Uuid, Uuid)>> {
+    let customers = sqlx::query_as::<_, (Uuid, Uuid)>(
+        r#"
+        SELECT c.user_id, c.id
+        FROM customers c
+        WHERE ($1::uuid IS NULL OR c.user_id = $1)
+          AND (
+            c.risk_score > 0 OR c.risk_flagged
+            OR EXISTS (SELECT 1 FROM customer_risk_incidents i WHERE i.customer_id = c.id)
+            OR EXISTS (SELECT 1 FROM visits v WHERE v.customer_id = c.id AND v.result = $2)
+          )
+        ORDER BY c.user_id, c.id
+        "#,
+    )
+    .bind(user_id)
+    .bind(WorkResult::CustomerAbsent.as_str())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(customers)
+}
+
+/// Score audit of a user, newest first
+pub async fn list_score_changes(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<CustomerRiskScoreChange>> {
+    let changes = sqlx::query_as::<_, CustomerRiskScoreChange>(
+        r#"
+        SELECT
+            sc.id, sc.customer_id, c.name AS customer_name, sc.old_score, sc.new_score,
+            sc.flagged, sc.reason, sc.created_at
+        FROM customer_risk_score_changes sc
+        JOIN customers c ON c.id = sc.customer_id
+        WHERE sc.user_id = $1 AND ($2::uuid IS NULL OR sc.customer_id = $2)
+        ORDER BY sc.created_at DESC, sc.id
+        LIMIT $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(customer_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(changes)
+}
@@ -0,0 +1,331 @@
+#![allow(dead_code)]
+//! Customer address (site) database queries
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::customer_site::{
+    resolve_service_location, service_address_location, CreateCustomerSiteRequest, CustomerSite, ServiceLocation,
+    ServiceLocationRow, UpdateCustomerSiteRequest, ADDRESS_TYPE_BILLING, ADDRESS_TYPE_SERVICE,
+};
+use crate::types::{RevisionStatus, VisitStatus};
+
+const SITE_COLUMNS: &str = r#"
+    id, user_id, customer_id, name, address_type, is_primary, geocode_status,
+    street, city, postal_code, country, lat, lng, notes, created_at, updated_at
+"#;
+
+/// Give up the primary flag of other addresses of the same type
+async fn clear_primary(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    customer_id: Uuid,
+    address_type: &str,
+    keep_id: Option<Uuid>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE customer_sites SET is_primary = FALSE, updated_at = NOW()
+        WHERE customer_id = $1 AND address_type = $2 AND is_primary
+          AND ($3::uuid IS NULL OR id <> $3)
+        "#,
+    )
+    .bind(customer_id)
+    .bind(address_type)
+    .bind(keep_id)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Create an address for a customer owned by the user. Addresses given
+/// without coordinates wait for geocoding.
+pub async fn create_site(pool: &PgPool, user_id: Uuid, req: &CreateCustomerSiteRequest) -> Result<Option<CustomerSite>> {
+    let address_type = req.address_type.as_deref().unwrap_or(ADDRESS_TYPE_SERVICE);
+    let is_primary = req.is_primary.unwrap_or(false);
+
+    let mut tx = pool.begin().await?;
+    if is_primary {
+        clear_primary(&mut tx, req.customer_id, address_type, None).await?;
+    }
+
+    let query = format!(
+        r#"
+        INSERT INTO customer_sites (
+            user_id, customer_id, name, street, city, postal_code, country, lat, lng, notes,
+            address_type, is_primary, geocode_status
+        )
+        SELECT $1, c.id, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12,
+            CASE WHEN $8::float8 IS NOT NULL THEN 'success' ELSE 'pending' END
+        FROM customers c
+        WHERE c.id = $2 AND c.user_id = $1
+        RETURNING {}
+        "#,
+        SITE_COLUMNS
+    );
+
+    let site = sqlx::query_as::<_, CustomerSite>(&query)
+        .bind(user_id)
+        .bind(req.customer_id)
+        .bind(&req.name)
+        .bind(&req.street)
+        .bind(&req.city)
+        .bind(&req.postal_code)
+        .bind(&req.country)
+        .bind(req.lat)
+        .bind(req.lng)
+        .bind(&req.notes)
+        .bind(address_type)
+        .bind(is_primary)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(site)
+}
+
+/// Addresses of a customer, primary first within each type
+pub async fn list_sites(pool: &PgPool, user_id: Uuid, customer_id: Uuid) -> Result<Vec<CustomerSite>> {
+    let query = format!(
+        r#"
+        SELECT {} FROM customer_sites
+        WHERE customer_id = $1 AND user_id = $2
+        ORDER BY address_type, is_primary DESC, name
+        "#,
+        SITE_COLUMNS
+    );
+
+    let sites = sqlx::query_as::<_, CustomerSite>(&query)
+        .bind(customer_id)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(sites)
+}
+
+/// Get a site owned by the user
+pub async fn get_site(pool: &PgPool, user_id: Uuid, site_id: Uuid) -> Result<Option<CustomerSite>> {
+    let query = format!("SELECT {} FROM customer_sites WHERE id = $1 AND user_id = $2", SITE_COLUMNS);
+
+    let site = sqlx::query_as::<_, CustomerSite>(&query)
+        .bind(site_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(site)
+}
+
+/// Update an address. Changing the address without coordinates clears
+/// the old ones and queues it for geocoding again.
+pub async fn update_site(
+    pool: &PgPool,
+    user_id: Uuid,
+    current: &CustomerSite,
+    req: &UpdateCustomerSiteRequest,
+) -> Result<Option<CustomerSite>> {
+    let address_type = req.address_type.as_deref().unwrap_or(&current.address_type);
+    let is_primary = req.is_primary.unwrap_or(current.is_primary);
+
+    let mut tx = pool.begin().await?;
+    if is_primary {
+        clear_primary(&mut tx, current.customer_id, address_type, Some(current.id)).await?;
+    }
+
+    let query = format!(
+        r#"
+        UPDATE customer_sites SET
+            name = COALESCE($3, name),
+            street = COALESCE($4, street),
+            city = COALESCE($5, city),
+            postal_code = COALESCE($6, postal_code),
+            country = COALESCE($7, country),
+            lat = CASE
+                WHEN $8::float8 IS NOT NULL THEN $8
+                WHEN COALESCE($4, $5, $6) IS NOT NULL THEN NULL
+                ELSE lat END,
+            lng = CASE
+                WHEN $9::float8 IS NOT NULL THEN $9
+                WHEN COALESCE($4, $5, $6) IS NOT NULL THEN NULL
+                ELSE lng END,
+            geocode_status = CASE
+                WHEN $8::float8 IS NOT NULL THEN 'success'
+                WHEN COALESCE($4, $5, $6) IS NOT NULL THEN 'pending'
+                ELSE geocode_status END,
+            notes = COALESCE($10, notes),
+            address_type = $11,
+            is_primary = $12,
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING {}
+        "#,
+        SITE_COLUMNS
+    );
+
+    let site = sqlx::query_as::<_, CustomerSite>(&query)
+        .bind(current.id)
+        .bind(user_id)
+        .bind(&req.name)
+        .bind(&req.street)
+        .bind(&req.city)
+        .bind(&req.postal_code)
+        .bind(&req.country)
+        .bind(req.lat)
+        .bind(req.lng)
+        .bind(&req.notes)
+        .bind(address_type)
+        .bind(is_primary)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(site)
+}
+
+/// Delete an address; devices at it fall back to the customer's address
+pub async fn delete_site(pool: &PgPool, user_id: Uuid, site_id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM customer_sites WHERE id = $1 AND user_id = $2")
+        .bind(site_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Where the customer is served on a date, from the devices of the
+/// revisions (then visits) scheduled that day. The planner has one stop per
+/// customer, so the earliest located device decides; without one, the
+/// primary service address is used.
+/// None means the customer's own address applies.
+pub async fn service_location_for_date(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
+    date: NaiveDate,
+) -> Result<Option<ServiceLocation>> {
+    let query = format!(
+        r#"
+        SELECT
+            d.location_lat AS device_lat, d.location_lng AS device_lng,
+            s.lat AS site_lat, s.lng AS site_lng,
+            s.street AS site_street, s.city AS site_city, s.postal_code AS site_postal_code
+        FROM (
+            SELECT r.device_id, r.scheduled_time_start, 0 AS priority
+            FROM revisions r
+            WHERE r.user_id = $1 AND r.customer_id = $2 AND r.scheduled_date = $3
+              AND r.status NOT IN ('{rev_cancelled}', '{rev_completed}')
+            UNION ALL
+            SELECT v.device_id, v.scheduled_time_start, 1 AS priority
+            FROM visits v
+            WHERE v.user_id = $1 AND v.customer_id = $2 AND v.scheduled_date = $3
+              AND v.device_id IS NOT NULL
+              AND v.status NOT IN ('{visit_cancelled}', '{visit_completed}')
+        ) scheduled
+        JOIN devices d ON d.id = scheduled.device_id
+        LEFT JOIN customer_sites s ON s.id = d.site_id
+        WHERE (d.location_lat IS NOT NULL AND d.location_lng IS NOT NULL)
+           OR (s.lat IS NOT NULL AND s.lng IS NOT NULL)
+        ORDER BY scheduled.priority, scheduled.scheduled_time_start NULLS LAST
+        LIMIT 1
+        "#,
+        rev_cancelled = RevisionStatus::Cancelled.as_str(),
+        rev_completed = RevisionStatus::Completed.as_str(),
+        visit_cancelled = VisitStatus::Cancelled.as_str(),
+        visit_completed = VisitStatus::Completed.as_str(),
+    );
+
+    let row = sqlx::query_as::<_, ServiceLocationRow>(&query)
+        .bind(user_id)
+        .bind(customer_id)
+        .bind(date)
+        .fetch_optional(pool)
+        .await?;
+
+    if let Some(location) = row.as_ref().and_then(resolve_service_location) {
+        return Ok(Some(location));
+    }
+
+    let primary = primary_address(pool, user_id, customer_id, ADDRESS_TYPE_SERVICE).await?;
+    Ok(primary.as_ref().and_then(service_address_location))
+}
+
+/// Primary address of a type
+pub async fn primary_address(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
+    address_type: &str,
+) -> Result<Option<CustomerSite>> {
+    let query = format!(
+        r#"
+        SELECT {} FROM customer_sites
+        WHERE customer_id = $1 AND user_id = $2 AND address_type = $3 AND is_primary
+        "#,
+        SITE_COLUMNS
+    );
+
+    let site = sqlx::query_as::<_, CustomerSite>(&query)
+        .bind(customer_id)
+        .bind(user_id)
+        .bind(address_type)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(site)
+}
+
+/// Addresses of a customer waiting for geocoding. The primary billing
+/// address is geocoded with the customer itself.
+pub async fn list_pending_geocode(pool: &PgPool, customer_id: Uuid) -> Result<Vec<CustomerSite>> {
+    let query = format!(
+        r#"
+        SELECT {} FROM customer_sites
+        WHERE customer_id = $1 AND geocode_status = 'pending'
+          AND NOT (is_primary AND address_type = '{}')
+        "#,
+        SITE_COLUMNS, ADDRESS_TYPE_BILLING
+    );
+
+    let sites = sqlx::query_as::<_, CustomerSite>(&query)
+        .bind(customer_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(sites)
+}
+
+/// Store the geocoding outcome of an address (None = not found,
+/// needs_review = coordinates outside the account's country)
+pub async fn set_geocode_result(
+    pool: &PgPool,
+    site_id: Uuid,
+    coordinates: Option<(f64, f64)>,
+    needs_review: bool,
+) -> Result<()> {
+    let (lat, lng) = coordinates.unzip();
+    sqlx::query(
+        r#"
+        UPDATE customer_sites SET
+            lat = $2, lng = $3,
+            geocode_status = CASE
+                WHEN $2::float8 IS NULL THEN 'failed'
+                WHEN $4 THEN 'review'
+                ELSE 'success'
+            END,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(site_id)
+    .bind(lat)
+    .bind(lng)
+    .bind(needs_review)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
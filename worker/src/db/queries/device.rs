@@ -6,7 +6,9 @@ use uuid::Uuid;
 use chrono::NaiveDate;
 use anyhow::Result;
 
-use crate::types::device::{Device, CreateDeviceRequest, UpdateDeviceRequest};
+use crate::types::device::{
+    CreateDeviceRequest, Device, DeviceTransfer, TransferDeviceRequest, UpdateDeviceRequest,
+};
 
 /// Create a new device
 pub async fn create_device(
@@ -35,15 +37,22 @@ pub async fn create_device(
         INSERT INTO devices (
             id, customer_id, user_id, device_type, device_type_config_id, device_name,
             manufacturer, model, serial_number, installation_date,
-            revision_interval_months, notes, created_at, updated_at
+            revision_interval_months, notes,
+            site_id, building, floor, room, location_lat, location_lng,
+            created_at, updated_at
+        )
+        VALUES (
+            $1, $2, $3, $4::device_type_enum, $5, $6, $7, $8, $9, $10, $11, $12,
+            $13, $14, $15, $16, $17, $18, NOW(), NOW()
         )
-        VALUES ($1, $2, $3, $4::device_type_enum, $5, $6, $7, $8, $9, $10, $11, $12, NOW(), NOW())
         RETURNING
             id, customer_id, user_id,
             device_type::text, device_name,
             manufacturer, model, serial_number,
             installation_date, revision_interval_months,
-            next_due_date, notes, created_at, updated_at
+            next_due_date, notes,
+            site_id, building, floor, room, location_lat, location_lng,
+            created_at, updated_at
         "#
     )
     .bind(Uuid::new_v4())
@@ -58,6 +67,12 @@ pub async fn create_device(
     .bind(req.installation_date)
     .bind(req.revision_interval_months)
     .bind(&req.notes)
+    .bind(req.site_id)
+    .bind(&req.building)
+    .bind(&req.floor)
+    .bind(&req.room)
+    .bind(req.location_lat)
+    .bind(req.location_lng)
     .fetch_one(pool)
     .await?;
 
@@ -73,10 +88,13 @@ pub async fn list_devices(pool: &PgPool, user_id: Uuid, customer_id: Uuid) -> Re
             d.device_type::text, d.device_name,
             d.manufacturer, d.model, d.serial_number,
             d.installation_date, d.revision_interval_months,
-            d.next_due_date, d.notes, d.created_at, d.updated_at
+            d.next_due_date, d.notes,
+            d.site_id, d.building, d.floor, d.room, d.location_lat, d.location_lng,
+            d.created_at, d.updated_at
         FROM devices d
         WHERE d.customer_id = $1
           AND d.user_id = $2
+          AND d.deleted_at IS NULL
         ORDER BY d.created_at DESC
         "#
     )
@@ -97,9 +115,11 @@ pub async fn get_device(pool: &PgPool, user_id: Uuid, device_id: Uuid, customer_
             device_type::text, device_name,
             manufacturer, model, serial_number,
             installation_date, revision_interval_months,
-            next_due_date, notes, created_at, updated_at
+            next_due_date, notes,
+            site_id, building, floor, room, location_lat, location_lng,
+            created_at, updated_at
         FROM devices
-        WHERE id = $1 AND customer_id = $2 AND user_id = $3
+        WHERE id = $1 AND customer_id = $2 AND user_id = $3 AND deleted_at IS NULL
         "#
     )
     .bind(device_id)
@@ -111,6 +131,30 @@ pub async fn get_device(pool: &PgPool, user_id: Uuid, device_id: Uuid, customer_
     Ok(device)
 }
 
+/// Get a device by id alone (scanned stickers don't name the customer)
+pub async fn get_device_by_id(pool: &PgPool, user_id: Uuid, device_id: Uuid) -> Result<Option<Device>> {
+    let device = sqlx::query_as::<_, Device>(
+        r#"
+        SELECT
+            id, customer_id, user_id,
+            device_type::text, device_name,
+            manufacturer, model, serial_number,
+            installation_date, revision_interval_months,
+            next_due_date, notes,
+            site_id, building, floor, room, location_lat, location_lng,
+            created_at, updated_at
+        FROM devices
+        WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL
+        "#
+    )
+    .bind(device_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(device)
+}
+
 /// Update a device (with user ownership verification)
 pub async fn update_device(
     pool: &PgPool,
@@ -129,14 +173,22 @@ pub async fn update_device(
             serial_number = COALESCE($8, serial_number),
             installation_date = COALESCE($9, installation_date),
             revision_interval_months = COALESCE($10, revision_interval_months),
-            notes = COALESCE($11, notes)
-        WHERE id = $1 AND customer_id = $2 AND user_id = $3
+            notes = COALESCE($11, notes),
+            site_id = COALESCE($12, site_id),
+            building = COALESCE($13, building),
+            floor = COALESCE($14, floor),
+            room = COALESCE($15, room),
+            location_lat = COALESCE($16, location_lat),
+            location_lng = COALESCE($17, location_lng)
+        WHERE id = $1 AND customer_id = $2 AND user_id = $3 AND deleted_at IS NULL
         RETURNING
             id, customer_id, user_id,
             device_type::text, device_name,
             manufacturer, model, serial_number,
             installation_date, revision_interval_months,
-            next_due_date, notes, created_at, updated_at
+            next_due_date, notes,
+            site_id, building, floor, room, location_lat, location_lng,
+            created_at, updated_at
         "#
     )
     .bind(device_id)
@@ -150,6 +202,12 @@ pub async fn update_device(
     .bind(req.installation_date)
     .bind(req.revision_interval_months)
     .bind(&req.notes)
+    .bind(req.site_id)
+    .bind(&req.building)
+    .bind(&req.floor)
+    .bind(&req.room)
+    .bind(req.location_lat)
+    .bind(req.location_lng)
     .fetch_optional(pool)
     .await?;
 
@@ -173,12 +231,12 @@ pub async fn update_next_due_date(
     Ok(())
 }
 
-/// Delete a device (with user ownership verification)
+/// Soft-delete a device (with user ownership verification); restorable until purged
 pub async fn delete_device(pool: &PgPool, user_id: Uuid, device_id: Uuid, customer_id: Uuid) -> Result<bool> {
     let result = sqlx::query(
         r#"
-        DELETE FROM devices
-        WHERE id = $1 AND customer_id = $2 AND user_id = $3
+        UPDATE devices SET deleted_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND customer_id = $2 AND user_id = $3 AND deleted_at IS NULL
         "#
     )
     .bind(device_id)
@@ -189,3 +247,196 @@ pub async fn delete_device(pool: &PgPool, user_id: Uuid, device_id: Uuid, custom
 
     Ok(result.rows_affected() > 0)
 }
+
+/// Bring a deleted device back; None when it is not a deleted device of the user
+pub async fn restore_device(pool: &PgPool, user_id: Uuid, device_id: Uuid) -> Result<Option<Device>> {
+    let device = sqlx::query_as::<_, Device>(
+        r#"
+        UPDATE devices SET deleted_at = NULL, updated_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND deleted_at IS NOT NULL
+        RETURNING
+            id, customer_id, user_id,
+            device_type::text, device_name,
+            manufacturer, model, serial_number,
+            installation_date, revision_interval_months,
+            next_due_date, notes,
+            site_id, building, floor, room, location_lat, location_lng,
+            created_at, updated_at
+        "#
+    )
+    .bind(device_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(device)
+}
+
+/// Remove devices deleted longer than `days` ago for good
+pub async fn purge_deleted_devices(pool: &PgPool, days: i32) -> Result<u64> {
+    let result = sqlx::query(
+        "DELETE FROM devices WHERE deleted_at < NOW() - make_interval(days => $1::int)"
+    )
+    .bind(days)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Outcome of a device transfer
+pub struct DeviceTransferOutcome {
+    pub transfer: DeviceTransfer,
+    pub device: Device,
+    /// Moved revisions with their status before the move
+    pub moved_revisions: Vec<(Uuid, String)>,
+}
+
+/// Move a device to another customer of the user in one transaction and
+/// record the transfer. Open revisions and planned actions follow the device
+/// when `move_open_revisions`; completed and cancelled revisions stay with
+/// the original owner. None when the device is not the user's.
+pub async fn transfer_device(
+    pool: &PgPool,
+    user_id: Uuid,
+    actor_id: Uuid,
+    req: &TransferDeviceRequest,
+) -> Result<Option<DeviceTransferOutcome>> {
+    let mut tx = pool.begin().await?;
+
+    let from_customer_id: Option<Uuid> = sqlx::query_scalar(
+        "SELECT customer_id FROM devices WHERE id = $1 AND user_id = $2 FOR UPDATE"
+    )
+    .bind(req.device_id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(from_customer_id) = from_customer_id else {
+        return Ok(None);
+    };
+
+    let device = sqlx::query_as::<_, Device>(
+        r#"
+        UPDATE devices
+        SET customer_id = $3, site_id = $4, updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING
+            id, customer_id, user_id,
+            device_type::text, device_name,
+            manufacturer, model, serial_number,
+            installation_date, revision_interval_months,
+            next_due_date, notes,
+            site_id, building, floor, room, location_lat, location_lng,
+            created_at, updated_at
+        "#
+    )
+    .bind(req.device_id)
+    .bind(user_id)
+    .bind(req.to_customer_id)
+    .bind(req.site_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let moved_revisions: Vec<(Uuid, String)> = if req.move_open_revisions {
+        sqlx::query_as(
+            r#"
+            WITH moved AS (
+                SELECT id, status::text AS status FROM revisions
+                WHERE device_id = $1 AND user_id = $2 AND customer_id = $3
+                  AND status IN ('upcoming', 'scheduled', 'confirmed')
+                FOR UPDATE
+            )
+            UPDATE revisions r
+            SET customer_id = $4, updated_at = NOW()
+            FROM moved
+            WHERE r.id = moved.id
+            RETURNING r.id, moved.status
+            "#
+        )
+        .bind(req.device_id)
+        .bind(user_id)
+        .bind(from_customer_id)
+        .bind(req.to_customer_id)
+        .fetch_all(&mut *tx)
+        .await?
+    } else {
+        Vec::new()
+    };
+    let moved_revision_ids: Vec<Uuid> = moved_revisions.iter().map(|(id, _)| *id).collect();
+
+    if req.move_open_revisions {
+        sqlx::query(
+            r#"
+            UPDATE planned_actions
+            SET customer_id = $4, updated_at = NOW()
+            WHERE user_id = $1 AND customer_id = $2 AND status = 'open'
+              AND (device_id = $3 OR revision_id = ANY($5))
+            "#
+        )
+        .bind(user_id)
+        .bind(from_customer_id)
+        .bind(req.device_id)
+        .bind(req.to_customer_id)
+        .bind(&moved_revision_ids)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let kept_revision_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM revisions WHERE device_id = $1 AND user_id = $2 AND customer_id = $3"
+    )
+    .bind(req.device_id)
+    .bind(user_id)
+    .bind(from_customer_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let transfer = sqlx::query_as::<_, DeviceTransfer>(
+        r#"
+        INSERT INTO device_transfers (
+            user_id, device_id, from_customer_id, to_customer_id,
+            moved_revision_ids, kept_revision_count, note, transferred_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING
+            id, device_id, from_customer_id, to_customer_id,
+            moved_revision_ids, kept_revision_count, note,
+            transferred_by, transferred_at
+        "#
+    )
+    .bind(user_id)
+    .bind(req.device_id)
+    .bind(from_customer_id)
+    .bind(req.to_customer_id)
+    .bind(&moved_revision_ids)
+    .bind(kept_revision_count as i32)
+    .bind(req.note.as_deref())
+    .bind(actor_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Some(DeviceTransferOutcome { transfer, device, moved_revisions }))
+}
+
+/// Transfer history of a device, newest first
+pub async fn list_device_transfers(pool: &PgPool, user_id: Uuid, device_id: Uuid) -> Result<Vec<DeviceTransfer>> {
+    let transfers = sqlx::query_as::<_, DeviceTransfer>(
+        r#"
+        SELECT
+            id, device_id, from_customer_id, to_customer_id,
+            moved_revision_ids, kept_revision_count, note,
+            transferred_by, transferred_at
+        FROM device_transfers
+        WHERE device_id = $1 AND user_id = $2
+        ORDER BY transferred_at DESC
+        "#
+    )
+    .bind(device_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(transfers)
+}
@@ -31,6 +31,24 @@ pub async fn get_tenant_id_for_user(pool: &PgPool, user_id: Uuid) -> Result<Opti
 // Device type configs
 // =============================================================================
 
+/// Label the user's tenant gives a device type key, if configured
+pub async fn get_label(pool: &PgPool, user_id: Uuid, device_type_key: &str) -> Result<Option<String>> {
+    let label = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT c.label
+        FROM device_type_configs c
+        JOIN user_tenants ut ON ut.tenant_id = c.tenant_id
+        WHERE ut.user_id = $1 AND c.device_type_key = $2
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(device_type_key)
+    .fetch_optional(pool)
+    .await?;
+    Ok(label)
+}
+
 /// List all device type configs for a tenant, with their fields.
 pub async fn list_device_type_configs(
     pool: &PgPool,
@@ -0,0 +1,84 @@
+//! Dispatch board database queries
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::dispatch_board::{BoardRouteRow, BoardStop};
+
+/// Routes of a user for a day, or one route by ID
+pub async fn list_board_routes(
+    pool: &PgPool,
+    user_id: Uuid,
+    date: Option<NaiveDate>,
+    route_id: Option<Uuid>,
+) -> Result<Vec<BoardRouteRow>> {
+    let routes = sqlx::query_as::<_, BoardRouteRow>(
+        r#"
+        SELECT r.id, r.date, r.crew_id, c.name AS crew_name, r.status::text AS status
+        FROM routes r
+        LEFT JOIN crews c ON c.id = r.crew_id
+        WHERE r.user_id = $1
+          AND ($2::date IS NULL OR r.date = $2)
+          AND ($3::uuid IS NULL OR r.id = $3)
+        ORDER BY c.name NULLS LAST, r.id
+        "#,
+    )
+    .bind(user_id)
+    .bind(date)
+    .bind(route_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(routes)
+}
+
+/// Stops of the given routes with the progress of their visits, in stop order
+pub async fn list_board_stops(pool: &PgPool, route_ids: &[Uuid]) -> Result<Vec<BoardStop>> {
+    let stops = sqlx::query_as::<_, BoardStop>(
+        r#"
+        SELECT
+            rs.route_id, rs.id AS stop_id, rs.stop_order, rs.stop_type,
+            rs.customer_id, c.name AS customer_name,
+            CONCAT(COALESCE(c.street, ''), ', ', COALESCE(c.city, '')) AS address,
+            rs.estimated_arrival, rs.estimated_departure,
+            v.id AS visit_id, v.status::text AS visit_status,
+            v.actual_arrival, v.actual_departure
+        FROM route_stops rs
+        LEFT JOIN customers c ON c.id = rs.customer_id
+        LEFT JOIN visits v ON v.id = rs.visit_id
+        WHERE rs.route_id = ANY($1)
+        ORDER BY rs.route_id, rs.stop_order
+        "#,
+    )
+    .bind(route_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(stops)
+}
+
+/// Routes of a day with a stop at the customer
+pub async fn list_route_ids_for_customer(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
+    date: NaiveDate,
+) -> Result<Vec<Uuid>> {
+    let ids: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT r.id
+        FROM routes r
+        JOIN route_stops rs ON rs.route_id = r.id
+        WHERE r.user_id = $1 AND r.date = $3 AND rs.customer_id = $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(customer_id)
+    .bind(date)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ids)
+}
@@ -0,0 +1,250 @@
+#![allow(dead_code)]
+//! Deadline escalation database queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::escalation::{
+    CreateEscalationRuleRequest, EscalatedItem, EscalationLogEntry, EscalationRule,
+    UpdateEscalationRuleRequest, ESCALATION_RULE_VISIT_MISSED,
+};
+use crate::types::{RevisionStatus, VisitStatus};
+
+const RULE_COLUMNS: &str = r#"
+    id, user_id, rule_type, threshold_days, notify_email, enabled, created_at, updated_at
+"#;
+
+/// Most items a rule escalates per run; the rest follow on the next run
+const ESCALATION_BATCH_LIMIT: i64 = 200;
+
+/// Create a rule
+pub async fn create_rule(
+    pool: &PgPool,
+    user_id: Uuid,
+    req: &CreateEscalationRuleRequest,
+) -> Result<EscalationRule> {
+    let query = format!(
+        r#"
+        INSERT INTO escalation_rules (user_id, rule_type, threshold_days, notify_email, enabled)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING {}
+        "#,
+        RULE_COLUMNS
+    );
+
+    let rule = sqlx::query_as::<_, EscalationRule>(&query)
+        .bind(user_id)
+        .bind(&req.rule_type)
+        .bind(req.threshold_days)
+        .bind(req.notify_email.unwrap_or(false))
+        .bind(req.enabled.unwrap_or(true))
+        .fetch_one(pool)
+        .await?;
+
+    Ok(rule)
+}
+
+/// List rules of a user
+pub async fn list_rules(pool: &PgPool, user_id: Uuid) -> Result<Vec<EscalationRule>> {
+    let query = format!(
+        "SELECT {} FROM escalation_rules WHERE user_id = $1 ORDER BY rule_type, threshold_days",
+        RULE_COLUMNS
+    );
+
+    let rules = sqlx::query_as::<_, EscalationRule>(&query)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rules)
+}
+
+/// Enabled rules of all users, for the scheduler
+pub async fn list_enabled_rules(pool: &PgPool) -> Result<Vec<EscalationRule>> {
+    let query = format!(
+        "SELECT {} FROM escalation_rules WHERE enabled ORDER BY user_id, rule_type, threshold_days",
+        RULE_COLUMNS
+    );
+
+    let rules = sqlx::query_as::<_, EscalationRule>(&query).fetch_all(pool).await?;
+
+    Ok(rules)
+}
+
+/// Update a rule (only provided fields change)
+pub async fn update_rule(
+    pool: &PgPool,
+    user_id: Uuid,
+    req: &UpdateEscalationRuleRequest,
+) -> Result<Option<EscalationRule>> {
+    let query = format!(
+        r#"
+        UPDATE escalation_rules
+        SET
+            threshold_days = COALESCE($3, threshold_days),
+            notify_email = COALESCE($4, notify_email),
+            enabled = COALESCE($5, enabled),
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING {}
+        "#,
+        RULE_COLUMNS
+    );
+
+    let rule = sqlx::query_as::<_, EscalationRule>(&query)
+        .bind(req.id)
+        .bind(user_id)
+        .bind(req.threshold_days)
+        .bind(req.notify_email)
+        .bind(req.enabled)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(rule)
+}
+
+/// Delete a rule (its log cascades)
+pub async fn delete_rule(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM escalation_rules WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Log the items that newly match a rule and return them.
+///
+/// An item is logged once per rule, so later runs skip it; the unique
+/// (rule_id, entity_id) key keeps concurrent runs from escalating it twice.
+pub async fn escalate_rule(pool: &PgPool, rule: &EscalationRule) -> Result<Vec<EscalatedItem>> {
+    let candidates = if rule.rule_type == ESCALATION_RULE_VISIT_MISSED {
+        format!(
+            r#"
+            SELECT v.user_id, 'visit' AS entity_type, v.id AS entity_id, v.customer_id
+            FROM visits v
+            JOIN customers c ON c.id = v.customer_id
+            WHERE v.user_id = $2
+              AND v.status = '{planned}'
+              AND v.scheduled_date < CURRENT_DATE - $3::int
+              AND c.is_anonymized = FALSE AND c.deleted_at IS NULL AND c.is_abandoned = FALSE
+              AND NOT EXISTS (SELECT 1 FROM escalation_log l WHERE l.rule_id = $1 AND l.entity_id = v.id)
+            ORDER BY v.scheduled_date
+            LIMIT $4
+            "#,
+            planned = VisitStatus::Planned.as_str(),
+        )
+    } else {
+        format!(
+            r#"
+            SELECT r.user_id, 'revision' AS entity_type, r.id AS entity_id, r.customer_id
+            FROM revisions r
+            JOIN customers c ON c.id = r.customer_id
+            WHERE r.user_id = $2
+              AND r.status NOT IN ('{completed}', '{cancelled}')
+              AND r.due_date < CURRENT_DATE - $3::int
+              AND c.is_anonymized = FALSE AND c.deleted_at IS NULL AND c.is_abandoned = FALSE
+              AND NOT EXISTS (SELECT 1 FROM escalation_log l WHERE l.rule_id = $1 AND l.entity_id = r.id)
+            ORDER BY r.due_date
+            LIMIT $4
+            "#,
+            completed = RevisionStatus::Completed.as_str(),
+            cancelled = RevisionStatus::Cancelled.as_str(),
+        )
+    };
+    let reference_date = if rule.rule_type == ESCALATION_RULE_VISIT_MISSED {
+        "(SELECT scheduled_date FROM visits WHERE id = i.entity_id)"
+    } else {
+        "(SELECT due_date FROM revisions WHERE id = i.entity_id)"
+    };
+
+    let query = format!(
+        r#"
+        WITH inserted AS (
+            INSERT INTO escalation_log (user_id, rule_id, entity_type, entity_id, customer_id)
+            SELECT m.user_id, $1, m.entity_type, m.entity_id, m.customer_id
+            FROM ({candidates}) m
+            ON CONFLICT (rule_id, entity_id) DO NOTHING
+            RETURNING id, entity_id, customer_id
+        )
+        SELECT
+            i.id AS log_id, i.entity_id, i.customer_id,
+            c.name AS customer_name,
+            {reference_date} AS reference_date
+        FROM inserted i
+        LEFT JOIN customers c ON c.id = i.customer_id
+        ORDER BY reference_date, i.entity_id
+        "#,
+    );
+
+    let items = sqlx::query_as::<_, EscalatedItem>(&query)
+        .bind(rule.id)
+        .bind(rule.user_id)
+        .bind(rule.threshold_days)
+        .bind(ESCALATION_BATCH_LIMIT)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(items)
+}
+
+/// Link a log entry to the notification it raised
+pub async fn set_log_notification(pool: &PgPool, log_id: Uuid, notification_id: Uuid) -> Result<()> {
+    sqlx::query("UPDATE escalation_log SET notification_id = $2 WHERE id = $1")
+        .bind(log_id)
+        .bind(notification_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Record the outcome of the escalation e-mail for log entries
+pub async fn set_log_email_result(pool: &PgPool, log_ids: &[Uuid], error: Option<&str>) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE escalation_log SET
+            emailed_at = CASE WHEN $2::text IS NULL THEN NOW() ELSE NULL END,
+            email_error = $2
+        WHERE id = ANY($1)
+        "#,
+    )
+    .bind(log_ids)
+    .bind(error)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Escalation log of a user, newest first
+pub async fn list_log(
+    pool: &PgPool,
+    user_id: Uuid,
+    rule_id: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<EscalationLogEntry>> {
+    let entries = sqlx::query_as::<_, EscalationLogEntry>(
+        r#"
+        SELECT
+            l.id, l.rule_id, er.rule_type, l.entity_type, l.entity_id,
+            l.customer_id, c.name AS customer_name,
+            l.notification_id, l.emailed_at, l.email_error, l.created_at
+        FROM escalation_log l
+        JOIN escalation_rules er ON er.id = l.rule_id
+        LEFT JOIN customers c ON c.id = l.customer_id
+        WHERE l.user_id = $1 AND ($2::uuid IS NULL OR l.rule_id = $2)
+        ORDER BY l.created_at DESC, l.id
+        LIMIT $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(rule_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries)
+}
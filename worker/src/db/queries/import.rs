@@ -78,6 +78,42 @@ pub async fn find_customer_by_ico(pool: &PgPool, user_id: Uuid, ico: &str) -> Re
     Ok(result)
 }
 
+/// Find customer by id, scoped to the user
+pub async fn find_customer_by_id(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<Option<Uuid>> {
+    let result = sqlx::query_scalar::<_, Uuid>(
+        r#"SELECT id FROM customers WHERE user_id = $1 AND id = $2 AND is_anonymized = FALSE AND deleted_at IS NULL"#,
+    )
+    .bind(user_id)
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(result)
+}
+
+/// Names of customers by id, for import previews
+pub async fn customer_names(pool: &PgPool, user_id: Uuid, ids: &[Uuid]) -> Result<Vec<(Uuid, Option<String>)>> {
+    let rows = sqlx::query_as::<_, (Uuid, Option<String>)>(
+        r#"SELECT id, name FROM customers WHERE user_id = $1 AND id = ANY($2)"#,
+    )
+    .bind(user_id)
+    .bind(ids)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Find customer by customer code (case-insensitive)
+pub async fn find_customer_by_code(pool: &PgPool, user_id: Uuid, code: &str) -> Result<Option<Uuid>> {
+    let result = sqlx::query_scalar::<_, Uuid>(
+        r#"SELECT id FROM customers WHERE user_id = $1 AND UPPER(customer_code) = UPPER($2)"#,
+    )
+    .bind(user_id)
+    .bind(code)
+    .fetch_optional(pool)
+    .await?;
+    Ok(result)
+}
+
 /// Find customer by email
 pub async fn find_customer_by_email(pool: &PgPool, user_id: Uuid, email: &str) -> Result<Option<Uuid>> {
     let result = sqlx::query_scalar!(
@@ -102,26 +138,30 @@ pub async fn find_customer_by_phone(pool: &PgPool, user_id: Uuid, phone: &str) -
 
 /// Find device by serial number for a customer
 pub async fn find_device_by_serial(pool: &PgPool, customer_id: Uuid, serial_number: &str) -> Result<Option<Uuid>> {
-    let result = sqlx::query_scalar!(
-        r#"SELECT id FROM devices WHERE customer_id = $1 AND serial_number = $2"#,
-        customer_id, serial_number
-    ).fetch_optional(pool).await?;
+    let result: Option<Uuid> = sqlx::query_scalar(
+        r#"SELECT id FROM devices WHERE customer_id = $1 AND deleted_at IS NULL AND serial_number = $2"#,
+    )
+    .bind(customer_id)
+    .bind(serial_number)
+    .fetch_optional(pool).await?;
     Ok(result)
 }
 
 /// Find device by device_name (case-insensitive) for a customer
 pub async fn find_device_by_name(pool: &PgPool, customer_id: Uuid, device_name: &str) -> Result<Option<Uuid>> {
-    let result = sqlx::query_scalar!(
-        r#"SELECT id FROM devices WHERE customer_id = $1 AND device_name ILIKE $2"#,
-        customer_id, device_name
-    ).fetch_optional(pool).await?;
+    let result: Option<Uuid> = sqlx::query_scalar(
+        r#"SELECT id FROM devices WHERE customer_id = $1 AND deleted_at IS NULL AND device_name ILIKE $2"#,
+    )
+    .bind(customer_id)
+    .bind(device_name)
+    .fetch_optional(pool).await?;
     Ok(result)
 }
 
 /// Find device by device_type for a customer (only if exactly one device of that type exists)
 pub async fn find_device_by_type_single(pool: &PgPool, customer_id: Uuid, device_type: &str) -> Result<Option<Uuid>> {
     let result: Vec<Uuid> = sqlx::query_scalar(
-        r#"SELECT id FROM devices WHERE customer_id = $1 AND device_type::text = $2"#,
+        r#"SELECT id FROM devices WHERE customer_id = $1 AND deleted_at IS NULL AND device_type::text = $2"#,
     )
     .bind(customer_id)
     .bind(device_type)
@@ -257,7 +297,7 @@ pub async fn update_device_import(
 /// Count devices of a specific type for a customer
 pub async fn count_devices_for_customer(pool: &PgPool, customer_id: Uuid) -> Result<i64> {
     let count: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM devices WHERE customer_id = $1"
+        "SELECT COUNT(*) FROM devices WHERE customer_id = $1 AND deleted_at IS NULL"
     ).bind(customer_id).fetch_one(pool).await?;
     Ok(count.0)
 }
@@ -504,7 +544,7 @@ pub async fn create_work_item_from_import(
 /// Returns the first match if found; useful when customer context is unavailable.
 pub async fn find_device_by_serial_for_user(pool: &PgPool, user_id: Uuid, serial_number: &str) -> Result<Option<Uuid>> {
     let result = sqlx::query_scalar(
-        r#"SELECT id FROM devices WHERE user_id = $1 AND serial_number = $2 LIMIT 1"#,
+        r#"SELECT id FROM devices WHERE user_id = $1 AND deleted_at IS NULL AND serial_number = $2 LIMIT 1"#,
     )
     .bind(user_id)
     .bind(serial_number)
@@ -516,7 +556,7 @@ pub async fn find_device_by_serial_for_user(pool: &PgPool, user_id: Uuid, serial
 /// Find device by name (case-insensitive) across all devices for a user.
 pub async fn find_device_by_name_for_user(pool: &PgPool, user_id: Uuid, device_name: &str) -> Result<Option<Uuid>> {
     let result = sqlx::query_scalar(
-        r#"SELECT id FROM devices WHERE user_id = $1 AND device_name ILIKE $2 LIMIT 1"#,
+        r#"SELECT id FROM devices WHERE user_id = $1 AND deleted_at IS NULL AND device_name ILIKE $2 LIMIT 1"#,
     )
     .bind(user_id)
     .bind(device_name)
@@ -0,0 +1,241 @@
+//! Bulk revision interval adjustment queries
+
+use anyhow::Result;
+use sqlx::types::Json;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::types::interval_adjustment::{
+    IntervalAdjustment, IntervalAdjustmentAction, IntervalAdjustmentItem, IntervalAdjustmentSource,
+    IntervalAdjustmentSummary, PreviewIntervalAdjustmentRequest,
+};
+
+/// Devices of a category with their last completion and earliest open revision
+pub async fn list_sources(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    device_type_config_id: Uuid,
+    only_interval_months: Option<i32>,
+) -> Result<Vec<IntervalAdjustmentSource>> {
+    let sources = sqlx::query_as::<_, IntervalAdjustmentSource>(
+        r#"
+        SELECT
+            d.id AS device_id,
+            d.customer_id,
+            c.name AS customer_name,
+            d.device_name,
+            d.revision_interval_months,
+            d.installation_date,
+            d.next_due_date,
+            (SELECT MAX(r.completed_at)::date FROM revisions r
+             WHERE r.device_id = d.id AND r.status = 'completed') AS last_completed,
+            o.id AS open_revision_id,
+            o.status::text AS open_status,
+            o.due_date AS open_due_date
+        FROM devices d
+        JOIN customers c ON c.id = d.customer_id
+        LEFT JOIN LATERAL (
+            SELECT r.id, r.status, r.due_date FROM revisions r
+            WHERE r.device_id = d.id AND r.status IN ('upcoming', 'scheduled', 'confirmed')
+            ORDER BY r.due_date
+            LIMIT 1
+        ) o ON TRUE
+        WHERE d.user_id = $1
+          AND d.device_type_config_id = $2
+          AND ($3::int IS NULL OR d.revision_interval_months = $3)
+          AND c.deleted_at IS NULL
+          AND d.deleted_at IS NULL
+        ORDER BY c.name, d.id
+        "#
+    )
+    .bind(user_id)
+    .bind(device_type_config_id)
+    .bind(only_interval_months)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(sources)
+}
+
+/// Write the planned change of one device
+pub async fn apply_item(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    new_interval_months: i32,
+    item: &IntervalAdjustmentItem,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE devices
+        SET revision_interval_months = $3,
+            next_due_date = COALESCE($4, next_due_date),
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        "#
+    )
+    .bind(item.device_id)
+    .bind(user_id)
+    .bind(new_interval_months)
+    .bind(item.new_due_date)
+    .execute(&mut **tx)
+    .await?;
+
+    match (item.action, item.revision_id, item.new_due_date) {
+        (IntervalAdjustmentAction::Move, Some(revision_id), Some(due_date)) => {
+            sqlx::query(
+                r#"
+                UPDATE revisions SET due_date = $3, updated_at = NOW()
+                WHERE id = $1 AND user_id = $2 AND status = 'upcoming'
+                "#
+            )
+            .bind(revision_id)
+            .bind(user_id)
+            .bind(due_date)
+            .execute(&mut **tx)
+            .await?;
+        }
+        (IntervalAdjustmentAction::Create, _, Some(due_date)) => {
+            sqlx::query(
+                r#"
+                INSERT INTO revisions (id, device_id, customer_id, user_id, status, due_date)
+                VALUES (uuid_generate_v4(), $1, $2, $3, 'upcoming'::revision_status, $4)
+                "#
+            )
+            .bind(item.device_id)
+            .bind(item.customer_id)
+            .bind(user_id)
+            .bind(due_date)
+            .execute(&mut **tx)
+            .await?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Make the new interval the category default for devices added later
+pub async fn set_type_default(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: Uuid,
+    device_type_config_id: Uuid,
+    interval_months: i32,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE device_type_configs SET default_revision_interval_months = $3
+        WHERE id = $1 AND tenant_id = $2
+        "#
+    )
+    .bind(device_type_config_id)
+    .bind(tenant_id)
+    .bind(interval_months)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Store a previewed adjustment as a draft
+pub async fn create_draft(
+    pool: &PgPool,
+    user_id: Uuid,
+    actor_id: Uuid,
+    req: &PreviewIntervalAdjustmentRequest,
+    summary: &IntervalAdjustmentSummary,
+) -> Result<IntervalAdjustment> {
+    let adjustment = sqlx::query_as::<_, IntervalAdjustment>(
+        r#"
+        INSERT INTO interval_adjustments (
+            user_id, device_type_config_id, new_interval_months, only_interval_months,
+            update_type_default, regulation_note, preview_summary, created_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING *
+        "#
+    )
+    .bind(user_id)
+    .bind(req.device_type_config_id)
+    .bind(req.new_interval_months)
+    .bind(req.only_interval_months)
+    .bind(req.update_type_default)
+    .bind(req.regulation_note.as_deref().map(str::trim).filter(|n| !n.is_empty()))
+    .bind(Json(summary))
+    .bind(actor_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(adjustment)
+}
+
+/// Mark a draft (or a failed run) as running; None if it is missing or
+/// already running or applied
+pub async fn claim_for_run(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<Option<IntervalAdjustment>> {
+    let adjustment = sqlx::query_as::<_, IntervalAdjustment>(
+        r#"
+        UPDATE interval_adjustments SET status = 'running', error = NULL
+        WHERE id = $1 AND user_id = $2 AND status IN ('draft', 'failed')
+        RETURNING *
+        "#
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(adjustment)
+}
+
+pub async fn mark_applied(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+    summary: &IntervalAdjustmentSummary,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE interval_adjustments
+        SET status = 'applied', applied_summary = $2, applied_at = NOW()
+        WHERE id = $1
+        "#
+    )
+    .bind(id)
+    .bind(Json(summary))
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn mark_failed(pool: &PgPool, id: Uuid, error: &str) -> Result<()> {
+    sqlx::query("UPDATE interval_adjustments SET status = 'failed', error = $2 WHERE id = $1")
+        .bind(id)
+        .bind(error)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_adjustment(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<Option<IntervalAdjustment>> {
+    let adjustment = sqlx::query_as::<_, IntervalAdjustment>(
+        "SELECT * FROM interval_adjustments WHERE id = $1 AND user_id = $2"
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(adjustment)
+}
+
+/// Adjustments of the account, newest first
+pub async fn list_adjustments(pool: &PgPool, user_id: Uuid) -> Result<Vec<IntervalAdjustment>> {
+    let adjustments = sqlx::query_as::<_, IntervalAdjustment>(
+        "SELECT * FROM interval_adjustments WHERE user_id = $1 ORDER BY created_at DESC LIMIT 100"
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(adjustments)
+}
@@ -0,0 +1,381 @@
+#![allow(dead_code)]
+//! Spare parts inventory queries
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::inventory::{
+    ListStockRequest, LowStockAlert, StockLevel, StockLocation, StockMovement, WorkItemMaterial,
+    WorkItemMaterialInput, STOCK_REASON_ADJUSTMENT, STOCK_REASON_CONSUMPTION, STOCK_REASON_RESTOCK,
+};
+use crate::types::report::RestockCandidate;
+
+/// Same rule as `services::inventory::is_low_stock`
+const LOW_STOCK_SQL: &str = "(s.quantity < 0 OR (s.min_quantity > 0 AND s.quantity <= s.min_quantity))";
+
+const STOCK_LEVEL_SELECT: &str = r#"
+    SELECT
+        s.id, s.catalog_item_id, ci.name AS item_name, ci.unit,
+        s.crew_id, s.depot_id, COALESCE(cr.name, d.name) AS location_name,
+        s.quantity, s.min_quantity, s.updated_at
+    FROM stock_levels s
+    JOIN catalog_items ci ON ci.id = s.catalog_item_id
+    LEFT JOIN crews cr ON cr.id = s.crew_id
+    LEFT JOIN depots d ON d.id = s.depot_id
+"#;
+
+/// Whether the crew or depot belongs to the account
+pub async fn location_belongs(pool: &PgPool, user_id: Uuid, location: &StockLocation) -> Result<bool> {
+    let (exists,): (bool,) = sqlx::query_as(
+        r#"
+        SELECT EXISTS(SELECT 1 FROM crews WHERE id = $2 AND user_id = $1)
+            OR EXISTS(SELECT 1 FROM depots WHERE id = $3 AND user_id = $1)
+        "#,
+    )
+    .bind(user_id)
+    .bind(location.crew_id)
+    .bind(location.depot_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(exists)
+}
+
+/// Stock levels of an account by location and part
+pub async fn list_levels(pool: &PgPool, user_id: Uuid, req: &ListStockRequest) -> Result<Vec<StockLevel>> {
+    let levels = sqlx::query_as::<_, StockLevel>(&format!(
+        r#"
+        {}
+        WHERE s.user_id = $1
+          AND ($2::uuid IS NULL OR s.crew_id = $2)
+          AND ($3::uuid IS NULL OR s.depot_id = $3)
+          AND ($4::uuid IS NULL OR s.catalog_item_id = $4)
+          AND (NOT $5 OR {})
+        ORDER BY location_name, item_name
+        "#,
+        STOCK_LEVEL_SELECT, LOW_STOCK_SQL
+    ))
+    .bind(user_id)
+    .bind(req.crew_id)
+    .bind(req.depot_id)
+    .bind(req.catalog_item_id)
+    .bind(req.low_only)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(levels)
+}
+
+async fn get_level(pool: &PgPool, level_id: Uuid) -> Result<StockLevel> {
+    let level = sqlx::query_as::<_, StockLevel>(&format!("{} WHERE s.id = $1", STOCK_LEVEL_SELECT))
+        .bind(level_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(level)
+}
+
+/// Stock level row of a part at a location, created empty when missing
+async fn ensure_level(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+    catalog_item_id: Uuid,
+    location: &StockLocation,
+) -> Result<Uuid> {
+    let conflict = if location.crew_id.is_some() {
+        "(catalog_item_id, crew_id) WHERE crew_id IS NOT NULL"
+    } else {
+        "(catalog_item_id, depot_id) WHERE depot_id IS NOT NULL"
+    };
+    let (id,): (Uuid,) = sqlx::query_as(&format!(
+        r#"
+        INSERT INTO stock_levels (user_id, catalog_item_id, crew_id, depot_id)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT {} DO UPDATE SET updated_at = stock_levels.updated_at
+        RETURNING id
+        "#,
+        conflict
+    ))
+    .bind(user_id)
+    .bind(catalog_item_id)
+    .bind(location.crew_id)
+    .bind(location.depot_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(id)
+}
+
+/// Change a stock level and log the movement
+#[allow(clippy::too_many_arguments)]
+async fn apply_change(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+    level_id: Uuid,
+    change: f64,
+    reason: &str,
+    work_item_id: Option<Uuid>,
+    note: Option<&str>,
+    created_by: Option<Uuid>,
+) -> Result<()> {
+    sqlx::query("UPDATE stock_levels SET quantity = quantity + $2, updated_at = NOW() WHERE id = $1")
+        .bind(level_id)
+        .bind(change)
+        .execute(&mut **tx)
+        .await?;
+    // A stock back above its reorder point may alert again later
+    sqlx::query(&format!("UPDATE stock_levels s SET low_alerted = FALSE WHERE s.id = $1 AND NOT {}", LOW_STOCK_SQL))
+        .bind(level_id)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO stock_movements (user_id, stock_level_id, change, reason, work_item_id, note, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(user_id)
+    .bind(level_id)
+    .bind(change)
+    .bind(reason)
+    .bind(work_item_id)
+    .bind(note)
+    .bind(created_by)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Add delivered parts to a location
+pub async fn restock(
+    pool: &PgPool,
+    user_id: Uuid,
+    catalog_item_id: Uuid,
+    location: &StockLocation,
+    quantity: f64,
+    note: Option<&str>,
+    created_by: Uuid,
+) -> Result<StockLevel> {
+    let mut tx = pool.begin().await?;
+    let level_id = ensure_level(&mut tx, user_id, catalog_item_id, location).await?;
+    apply_change(&mut tx, user_id, level_id, quantity, STOCK_REASON_RESTOCK, None, note, Some(created_by)).await?;
+    tx.commit().await?;
+
+    get_level(pool, level_id).await
+}
+
+/// Record a stocktake count and/or the reorder point of a location
+#[allow(clippy::too_many_arguments)]
+pub async fn set_stock(
+    pool: &PgPool,
+    user_id: Uuid,
+    catalog_item_id: Uuid,
+    location: &StockLocation,
+    quantity: Option<f64>,
+    min_quantity: Option<f64>,
+    note: Option<&str>,
+    created_by: Uuid,
+) -> Result<StockLevel> {
+    let mut tx = pool.begin().await?;
+    let level_id = ensure_level(&mut tx, user_id, catalog_item_id, location).await?;
+
+    if let Some(min_quantity) = min_quantity {
+        sqlx::query("UPDATE stock_levels SET min_quantity = $2, updated_at = NOW() WHERE id = $1")
+            .bind(level_id)
+            .bind(min_quantity)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let (current,): (f64,) = sqlx::query_as("SELECT quantity FROM stock_levels WHERE id = $1 FOR UPDATE")
+        .bind(level_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    let change = quantity.map(|counted| counted - current).unwrap_or(0.0);
+    if change != 0.0 {
+        apply_change(&mut tx, user_id, level_id, change, STOCK_REASON_ADJUSTMENT, None, note, Some(created_by)).await?;
+    } else {
+        sqlx::query(&format!("UPDATE stock_levels s SET low_alerted = FALSE WHERE s.id = $1 AND NOT {}", LOW_STOCK_SQL))
+            .bind(level_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    get_level(pool, level_id).await
+}
+
+/// Movements of a stock level, newest first
+pub async fn list_movements(pool: &PgPool, user_id: Uuid, level_id: Uuid, limit: i64) -> Result<Vec<StockMovement>> {
+    let movements = sqlx::query_as::<_, StockMovement>(
+        r#"
+        SELECT id, stock_level_id, change, reason, work_item_id, note, created_by, created_at
+        FROM stock_movements
+        WHERE user_id = $1 AND stock_level_id = $2
+        ORDER BY created_at DESC, id
+        LIMIT $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(level_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(movements)
+}
+
+/// Record materials used on a work item
+pub async fn add_materials(pool: &PgPool, work_item_id: Uuid, materials: &[WorkItemMaterialInput]) -> Result<()> {
+    for material in materials {
+        sqlx::query("INSERT INTO work_item_materials (work_item_id, catalog_item_id, quantity) VALUES ($1, $2, $3)")
+            .bind(work_item_id)
+            .bind(material.catalog_item_id)
+            .bind(material.quantity)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Materials of a work item
+pub async fn list_materials(pool: &PgPool, work_item_id: Uuid) -> Result<Vec<WorkItemMaterial>> {
+    let materials = sqlx::query_as::<_, WorkItemMaterial>(
+        "SELECT * FROM work_item_materials WHERE work_item_id = $1 ORDER BY created_at, id",
+    )
+    .bind(work_item_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(materials)
+}
+
+/// Crew that did a work item: its own crew, else the visit's
+pub async fn work_item_crew(pool: &PgPool, work_item_id: Uuid) -> Result<Option<Uuid>> {
+    let crew: Option<(Option<Uuid>,)> = sqlx::query_as(
+        r#"
+        SELECT COALESCE(wi.crew_id, v.crew_id)
+        FROM visit_work_items wi
+        LEFT JOIN visits v ON v.id = wi.visit_id
+        WHERE wi.id = $1
+        "#,
+    )
+    .bind(work_item_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(crew.and_then(|(crew,)| crew))
+}
+
+/// Take a work item's not yet consumed materials from the crew's stock.
+///
+/// Returns the stock levels that dropped to their reorder point and have
+/// not been alerted yet; they are marked alerted.
+pub async fn consume_materials(
+    pool: &PgPool,
+    user_id: Uuid,
+    work_item_id: Uuid,
+    crew_id: Uuid,
+    created_by: Uuid,
+) -> Result<Vec<LowStockAlert>> {
+    let mut tx = pool.begin().await?;
+
+    let pending: Vec<(Uuid, Uuid, f64)> = sqlx::query_as(
+        r#"
+        SELECT id, catalog_item_id, quantity FROM work_item_materials
+        WHERE work_item_id = $1 AND consumed_at IS NULL
+        FOR UPDATE
+        "#,
+    )
+    .bind(work_item_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let location = StockLocation { crew_id: Some(crew_id), depot_id: None };
+    let mut level_ids = Vec::with_capacity(pending.len());
+    for (material_id, catalog_item_id, quantity) in pending {
+        let level_id = ensure_level(&mut tx, user_id, catalog_item_id, &location).await?;
+        apply_change(
+            &mut tx,
+            user_id,
+            level_id,
+            -quantity,
+            STOCK_REASON_CONSUMPTION,
+            Some(work_item_id),
+            None,
+            Some(created_by),
+        )
+        .await?;
+        sqlx::query("UPDATE work_item_materials SET consumed_at = NOW(), stock_level_id = $2 WHERE id = $1")
+            .bind(material_id)
+            .bind(level_id)
+            .execute(&mut *tx)
+            .await?;
+        level_ids.push(level_id);
+    }
+
+    let alerts = sqlx::query_as::<_, LowStockAlert>(&format!(
+        r#"
+        WITH flagged AS (
+            UPDATE stock_levels s SET low_alerted = TRUE
+            WHERE s.id = ANY($1) AND NOT s.low_alerted AND {}
+            RETURNING s.*
+        )
+        SELECT
+            f.id AS stock_level_id, f.catalog_item_id, ci.name AS item_name, ci.unit,
+            COALESCE(cr.name, d.name) AS location_name, f.quantity, f.min_quantity
+        FROM flagged f
+        JOIN catalog_items ci ON ci.id = f.catalog_item_id
+        LEFT JOIN crews cr ON cr.id = f.crew_id
+        LEFT JOIN depots d ON d.id = f.depot_id
+        "#,
+        LOW_STOCK_SQL
+    ))
+    .bind(&level_ids)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(alerts)
+}
+
+/// Stock levels with their consumption since `since`
+pub async fn restock_candidates(
+    pool: &PgPool,
+    user_id: Uuid,
+    crew_id: Option<Uuid>,
+    depot_id: Option<Uuid>,
+    since: DateTime<Utc>,
+) -> Result<Vec<RestockCandidate>> {
+    let candidates = sqlx::query_as::<_, RestockCandidate>(
+        r#"
+        SELECT
+            s.catalog_item_id, ci.name AS item_name, ci.unit,
+            s.crew_id, s.depot_id, COALESCE(cr.name, d.name) AS location_name,
+            s.quantity, s.min_quantity,
+            COALESCE((
+                SELECT -SUM(m.change) FROM stock_movements m
+                WHERE m.stock_level_id = s.id AND m.reason = 'consumption' AND m.created_at >= $4
+            ), 0) AS consumed
+        FROM stock_levels s
+        JOIN catalog_items ci ON ci.id = s.catalog_item_id
+        LEFT JOIN crews cr ON cr.id = s.crew_id
+        LEFT JOIN depots d ON d.id = s.depot_id
+        WHERE s.user_id = $1
+          AND ($2::uuid IS NULL OR s.crew_id = $2)
+          AND ($3::uuid IS NULL OR s.depot_id = $3)
+        "#,
+    )
+    .bind(user_id)
+    .bind(crew_id)
+    .bind(depot_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(candidates)
+}
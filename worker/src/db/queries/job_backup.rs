@@ -0,0 +1,174 @@
+#![allow(dead_code)]
+//! Queued job backup database queries
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::job_backup::{JobBackup, JOB_BACKUP_LOST, JOB_BACKUP_QUEUED, JOB_BACKUP_STARTED};
+
+const BACKUP_COLUMNS: &str = r#"
+    id, user_id, job_type, stream, subject, stream_sequence,
+    filename, payload_bytes, payload, status, submitted_at, updated_at
+"#;
+
+/// Record a freshly published job
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_backup(
+    pool: &PgPool,
+    job_id: Uuid,
+    user_id: Uuid,
+    job_type: &str,
+    stream: &str,
+    subject: &str,
+    stream_sequence: i64,
+    filename: Option<&str>,
+    payload_bytes: i32,
+    payload: Option<&[u8]>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO job_backups (
+            id, user_id, job_type, stream, subject, stream_sequence,
+            filename, payload_bytes, payload
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+    )
+    .bind(job_id)
+    .bind(user_id)
+    .bind(job_type)
+    .bind(stream)
+    .bind(subject)
+    .bind(stream_sequence)
+    .bind(filename)
+    .bind(payload_bytes)
+    .bind(payload)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Mark a job as picked up by a worker
+pub async fn mark_started(pool: &PgPool, job_id: Uuid) -> Result<()> {
+    sqlx::query("UPDATE job_backups SET status = $2, updated_at = NOW() WHERE id = $1")
+        .bind(job_id)
+        .bind(JOB_BACKUP_STARTED)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Mark a still-queued job as lost. Returns false if a worker got to it first.
+pub async fn mark_lost(pool: &PgPool, job_id: Uuid) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE job_backups SET status = $2, updated_at = NOW() WHERE id = $1 AND status = $3",
+    )
+    .bind(job_id)
+    .bind(JOB_BACKUP_LOST)
+    .bind(JOB_BACKUP_QUEUED)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Put a lost job back into the queue under a new stream sequence
+pub async fn mark_resubmitted(pool: &PgPool, job_id: Uuid, stream_sequence: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE job_backups
+        SET status = $2, stream_sequence = $3, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(job_id)
+    .bind(JOB_BACKUP_QUEUED)
+    .bind(stream_sequence)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// All jobs still waiting in a queue (across users, for reconciliation)
+pub async fn list_queued(pool: &PgPool) -> Result<Vec<JobBackup>> {
+    let query = format!(
+        "SELECT {} FROM job_backups WHERE status = $1 ORDER BY stream, stream_sequence",
+        BACKUP_COLUMNS
+    );
+
+    let rows = sqlx::query_as::<_, JobBackup>(&query)
+        .bind(JOB_BACKUP_QUEUED)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows)
+}
+
+/// Lost jobs of a user, newest first
+pub async fn list_lost_for_user(pool: &PgPool, user_id: Uuid, limit: i64) -> Result<Vec<JobBackup>> {
+    let query = format!(
+        r#"
+        SELECT {} FROM job_backups
+        WHERE user_id = $1 AND status = $2
+        ORDER BY submitted_at DESC
+        LIMIT $3
+        "#,
+        BACKUP_COLUMNS
+    );
+
+    let rows = sqlx::query_as::<_, JobBackup>(&query)
+        .bind(user_id)
+        .bind(JOB_BACKUP_LOST)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows)
+}
+
+/// Get a backup owned by the user
+pub async fn get_backup(pool: &PgPool, user_id: Uuid, job_id: Uuid) -> Result<Option<JobBackup>> {
+    let query = format!(
+        "SELECT {} FROM job_backups WHERE id = $1 AND user_id = $2",
+        BACKUP_COLUMNS
+    );
+
+    let row = sqlx::query_as::<_, JobBackup>(&query)
+        .bind(job_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row)
+}
+
+/// Drop backups of jobs that were picked up before the cutoff
+pub async fn delete_started_before(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM job_backups WHERE status = $1 AND updated_at < $2")
+        .bind(JOB_BACKUP_STARTED)
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Flag a job interrupted mid-processing so it can be resubmitted.
+/// Returns false if there is no started backup for it.
+pub async fn mark_interrupted(pool: &PgPool, job_id: Uuid) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE job_backups SET status = $2, updated_at = NOW() WHERE id = $1 AND status = $3",
+    )
+    .bind(job_id)
+    .bind(JOB_BACKUP_LOST)
+    .bind(JOB_BACKUP_STARTED)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,174 @@
+//! Job history database queries
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::services::job_history::JobHistoryEntry;
+
+const HISTORY_COLUMNS: &str = r#"
+    id, user_id, job_type, status, started_at, completed_at,
+    duration_ms, error, details, report
+"#;
+
+#[derive(sqlx::FromRow)]
+struct JobHistoryRow {
+    id: Uuid,
+    user_id: Uuid,
+    job_type: String,
+    status: String,
+    started_at: DateTime<Utc>,
+    completed_at: DateTime<Utc>,
+    duration_ms: i64,
+    error: Option<String>,
+    details: Option<String>,
+    report: Option<serde_json::Value>,
+}
+
+impl From<JobHistoryRow> for JobHistoryEntry {
+    fn from(row: JobHistoryRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            job_type: row.job_type,
+            status: row.status,
+            started_at: row.started_at,
+            completed_at: row.completed_at,
+            duration_ms: row.duration_ms.max(0) as u64,
+            error: row.error,
+            details: row.details,
+            report: row.report,
+        }
+    }
+}
+
+/// Filters for listing a user's job history
+#[derive(Debug, Clone, Default)]
+pub struct JobHistoryFilter {
+    /// Exact type, or a family such as `import` matching `import.customer`
+    pub job_type: Option<String>,
+    pub status: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Insert a job, or replace its previous lifecycle record. The row is
+/// owned by the worker instance writing it.
+pub async fn upsert_entry(pool: &PgPool, entry: &JobHistoryEntry, worker_instance: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO job_history (
+            id, user_id, job_type, status, started_at, completed_at,
+            duration_ms, error, details, report, worker_instance
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT (id) DO UPDATE SET
+            job_type = EXCLUDED.job_type,
+            status = EXCLUDED.status,
+            started_at = EXCLUDED.started_at,
+            completed_at = EXCLUDED.completed_at,
+            duration_ms = EXCLUDED.duration_ms,
+            error = EXCLUDED.error,
+            details = EXCLUDED.details,
+            report = EXCLUDED.report,
+            worker_instance = EXCLUDED.worker_instance,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(entry.id)
+    .bind(entry.user_id)
+    .bind(&entry.job_type)
+    .bind(&entry.status)
+    .bind(entry.started_at)
+    .bind(entry.completed_at)
+    .bind(entry.duration_ms.min(i64::MAX as u64) as i64)
+    .bind(&entry.error)
+    .bind(&entry.details)
+    .bind(&entry.report)
+    .bind(worker_instance)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Page of a user's history, newest first, with the total matching count
+pub async fn list_entries(
+    pool: &PgPool,
+    user_id: Uuid,
+    filter: &JobHistoryFilter,
+) -> Result<(Vec<JobHistoryEntry>, i64)> {
+    const WHERE: &str = r#"
+        WHERE user_id = $1
+          AND ($2::text IS NULL OR job_type = $2 OR job_type LIKE $2 || '.%')
+          AND ($3::text IS NULL OR status = $3)
+          AND ($4::timestamptz IS NULL OR completed_at >= $4)
+          AND ($5::timestamptz IS NULL OR completed_at < $5)
+    "#;
+
+    let rows = sqlx::query_as::<_, JobHistoryRow>(&format!(
+        "SELECT {} FROM job_history {} ORDER BY completed_at DESC, id LIMIT $6 OFFSET $7",
+        HISTORY_COLUMNS, WHERE
+    ))
+    .bind(user_id)
+    .bind(&filter.job_type)
+    .bind(&filter.status)
+    .bind(filter.from)
+    .bind(filter.to)
+    .bind(filter.limit)
+    .bind(filter.offset)
+    .fetch_all(pool)
+    .await?;
+
+    let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM job_history {}", WHERE))
+        .bind(user_id)
+        .bind(&filter.job_type)
+        .bind(&filter.status)
+        .bind(filter.from)
+        .bind(filter.to)
+        .fetch_one(pool)
+        .await?;
+
+    Ok((rows.into_iter().map(Into::into).collect(), total))
+}
+
+/// Turn the `running` jobs of a worker instance (and those recorded before
+/// instances were tracked) into failed ones with the given error key and
+/// return the converted entries. Running jobs of other instances are left
+/// alone.
+pub async fn fail_running(pool: &PgPool, worker_instance: &str, error: &str) -> Result<Vec<JobHistoryEntry>> {
+    let rows = sqlx::query_as::<_, JobHistoryRow>(&format!(
+        r#"
+        UPDATE job_history
+        SET status = 'failed',
+            completed_at = NOW(),
+            duration_ms = GREATEST(0, (EXTRACT(EPOCH FROM (NOW() - started_at)) * 1000)::BIGINT),
+            error = $1,
+            updated_at = NOW()
+        WHERE status = 'running' AND (worker_instance = $2 OR worker_instance IS NULL)
+        RETURNING {}
+        "#,
+        HISTORY_COLUMNS
+    ))
+    .bind(error)
+    .bind(worker_instance)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+}
+
+/// Remove finished jobs older than the given number of days
+pub async fn delete_older_than(pool: &PgPool, days: i64) -> Result<u64> {
+    let result = sqlx::query(
+        "DELETE FROM job_history WHERE status <> 'running' AND completed_at < NOW() - make_interval(days => $1::int)",
+    )
+    .bind(days as i32)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,220 @@
+#![allow(dead_code)]
+//! Web lead intake and pipeline database queries
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::lead::{LeadIntakeSettings, LeadStatusChange, UpdateLeadIntakeRequest, LEAD_STATUS_LOST};
+use crate::types::report::LeadFunnelCounts;
+
+/// Intake settings of an account, created with a fresh key on first use
+pub async fn get_or_create_settings(pool: &PgPool, user_id: Uuid, new_key: &str) -> Result<LeadIntakeSettings> {
+    sqlx::query("INSERT INTO lead_intake_settings (user_id, intake_key) VALUES ($1, $2) ON CONFLICT (user_id) DO NOTHING")
+        .bind(user_id)
+        .bind(new_key)
+        .execute(pool)
+        .await?;
+
+    let settings = sqlx::query_as::<_, LeadIntakeSettings>("SELECT * FROM lead_intake_settings WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(settings)
+}
+
+/// Settings behind an intake key
+pub async fn find_by_key(pool: &PgPool, intake_key: &str) -> Result<Option<LeadIntakeSettings>> {
+    let settings = sqlx::query_as::<_, LeadIntakeSettings>("SELECT * FROM lead_intake_settings WHERE intake_key = $1")
+        .bind(intake_key)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(settings)
+}
+
+/// Update intake settings; a missing secret keeps the stored one
+pub async fn update_settings(pool: &PgPool, user_id: Uuid, req: &UpdateLeadIntakeRequest) -> Result<Option<LeadIntakeSettings>> {
+    let settings = sqlx::query_as::<_, LeadIntakeSettings>(
+        r#"
+        UPDATE lead_intake_settings
+        SET
+            enabled = $2,
+            captcha_provider = $3,
+            captcha_secret = CASE
+                WHEN $3::text IS NULL THEN NULL
+                WHEN $4::text IS NULL THEN captcha_secret
+                ELSE NULLIF($4, '')
+            END,
+            updated_at = NOW()
+        WHERE user_id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(req.enabled)
+    .bind(&req.captcha_provider)
+    .bind(&req.captcha_secret)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(settings)
+}
+
+/// Replace the intake key, cutting off forms using the old one
+pub async fn rotate_key(pool: &PgPool, user_id: Uuid, new_key: &str) -> Result<Option<LeadIntakeSettings>> {
+    let settings = sqlx::query_as::<_, LeadIntakeSettings>(
+        "UPDATE lead_intake_settings SET intake_key = $2, updated_at = NOW() WHERE user_id = $1 RETURNING *",
+    )
+    .bind(user_id)
+    .bind(new_key)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(settings)
+}
+
+/// Move a customer's lead status from `from` to `to` and log the change.
+/// Returns false when the status is no longer `from` (changed meanwhile).
+pub async fn transition_status(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
+    from: Option<&str>,
+    to: &str,
+    reason: Option<&str>,
+    changed_by: Option<Uuid>,
+) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE customers
+        SET lead_status = $4,
+            lead_lost_reason = CASE WHEN $4 = $6 THEN $5 ELSE NULL END,
+            lead_status_changed_at = NOW(),
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND lead_status IS NOT DISTINCT FROM $3
+        "#,
+    )
+    .bind(customer_id)
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .bind(reason)
+    .bind(LEAD_STATUS_LOST)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if updated == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO lead_status_changes (user_id, customer_id, from_status, to_status, reason, changed_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(user_id)
+    .bind(customer_id)
+    .bind(from)
+    .bind(to)
+    .bind(reason)
+    .bind(changed_by)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Logged lead transitions of a customer, oldest first
+pub async fn list_status_changes(pool: &PgPool, user_id: Uuid, customer_id: Uuid) -> Result<Vec<LeadStatusChange>> {
+    let changes = sqlx::query_as::<_, LeadStatusChange>(
+        r#"
+        SELECT id, customer_id, from_status, to_status, reason, changed_by, changed_at
+        FROM lead_status_changes
+        WHERE user_id = $1 AND customer_id = $2
+        ORDER BY changed_at, id
+        "#,
+    )
+    .bind(user_id)
+    .bind(customer_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(changes)
+}
+
+/// Funnel of the leads that entered the pipeline in the period, per
+/// acquisition source ("" = unknown). Won leads count as contacted and
+/// quoted even when stages were skipped; won/lost is the current state.
+pub async fn funnel_counts(pool: &PgPool, user_id: Uuid, from: NaiveDate, to: NaiveDate) -> Result<Vec<LeadFunnelCounts>> {
+    let counts = sqlx::query_as::<_, LeadFunnelCounts>(
+        r#"
+        WITH entered AS (
+            SELECT DISTINCT customer_id
+            FROM lead_status_changes
+            WHERE user_id = $1 AND from_status IS NULL
+              AND changed_at::date BETWEEN $2 AND $3
+        ),
+        reached AS (
+            SELECT
+                l.customer_id,
+                BOOL_OR(l.to_status IN ('contacted', 'quoted', 'won')) AS contacted,
+                BOOL_OR(l.to_status IN ('quoted', 'won')) AS quoted
+            FROM lead_status_changes l
+            JOIN entered e ON e.customer_id = l.customer_id
+            GROUP BY l.customer_id
+        )
+        SELECT
+            COALESCE(c.acquisition_source, '') AS source,
+            COUNT(*) AS leads,
+            COUNT(*) FILTER (WHERE r.contacted) AS contacted,
+            COUNT(*) FILTER (WHERE r.quoted) AS quoted,
+            COUNT(*) FILTER (WHERE c.lead_status = 'won') AS won,
+            COUNT(*) FILTER (WHERE c.lead_status = 'lost') AS lost
+        FROM entered e
+        JOIN customers c ON c.id = e.customer_id
+        JOIN reached r ON r.customer_id = e.customer_id
+        WHERE c.user_id = $1
+        GROUP BY 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(counts)
+}
+
+/// Existing customer with the e-mail or phone a lead was submitted with
+pub async fn find_matching_customer(
+    pool: &PgPool,
+    user_id: Uuid,
+    email: Option<&str>,
+    phone: Option<&str>,
+) -> Result<Option<(Uuid, Option<String>)>> {
+    let customer = sqlx::query_as(
+        r#"
+        SELECT id, name FROM customers
+        WHERE user_id = $1 AND is_anonymized = FALSE AND deleted_at IS NULL
+          AND (($2::text IS NOT NULL AND LOWER(email) = LOWER($2))
+            OR ($3::text IS NOT NULL AND phone = $3))
+        ORDER BY created_at
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(email)
+    .bind(phone)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(customer)
+}
@@ -0,0 +1,73 @@
+#![allow(dead_code)]
+//! Login history database queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::login_event::LoginEvent;
+
+/// Record a login attempt
+pub async fn insert_event(
+    pool: &PgPool,
+    user_id: Uuid,
+    failure_reason: Option<&str>,
+    ip_address: Option<&str>,
+    user_agent: Option<&str>,
+    new_device: bool,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO login_events (user_id, success, failure_reason, ip_address, user_agent, new_device)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(user_id)
+    .bind(failure_reason.is_none())
+    .bind(failure_reason)
+    .bind(ip_address)
+    .bind(user_agent)
+    .bind(new_device)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Whether the user logged in successfully before, and whether from this user agent.
+/// Returns (has_previous_login, known_device).
+pub async fn device_history(pool: &PgPool, user_id: Uuid, user_agent: Option<&str>) -> Result<(bool, bool)> {
+    let row: (bool, bool) = sqlx::query_as(
+        r#"
+        SELECT
+            EXISTS (SELECT 1 FROM login_events WHERE user_id = $1 AND success),
+            EXISTS (SELECT 1 FROM login_events
+                    WHERE user_id = $1 AND success AND user_agent IS NOT DISTINCT FROM $2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(user_agent)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row)
+}
+
+/// Recent login attempts of a user, newest first
+pub async fn list_for_user(pool: &PgPool, user_id: Uuid, limit: i64) -> Result<Vec<LoginEvent>> {
+    let items = sqlx::query_as::<_, LoginEvent>(
+        r#"
+        SELECT id, user_id, success, failure_reason, ip_address, user_agent, new_device, created_at
+        FROM login_events
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
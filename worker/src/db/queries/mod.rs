@@ -1,21 +1,60 @@
 //! Database queries
 
+pub mod account_transfer;
+pub mod admin_user;
+pub mod automation;
+pub mod backup;
+pub mod calendar_feed;
+pub mod campaign;
+pub mod catalog;
 pub mod communication;
 pub mod note;
+pub mod notification;
 pub mod inbox_state;
+pub mod organization;
 pub mod planned_action;
+pub mod quality;
+pub mod quota;
+pub mod quote;
+pub mod reschedule;
+pub mod retention;
 pub mod scoring;
 pub mod country;
+pub mod coverage;
 pub mod customer;
+pub mod customer_code;
+pub mod customer_hierarchy;
+pub mod customer_risk;
+pub mod customer_reference;
+pub mod customer_site;
+pub mod dispatch_board;
 pub mod device;
 pub mod device_type_config;
+pub mod escalation;
 pub mod import;
+pub mod interval_adjustment;
+pub mod inventory;
+pub mod job_backup;
+pub mod job_history;
+pub mod lead;
+pub mod login_event;
 pub mod revision;
+pub mod revision_number;
 pub mod role;
 pub mod route;
+pub mod route_analysis;
+pub mod route_lock;
 pub mod settings;
+pub mod subcontractor;
+pub mod subscription;
+pub mod template_translation;
 pub mod user;
 pub mod crew;
+pub mod crm_sync;
 pub mod visit;
+pub mod visit_conditions;
+pub mod visit_duration;
+pub mod webhook;
 pub mod task;
+pub mod telemetry;
 pub mod work_item;
\ No newline at end of file
@@ -0,0 +1,214 @@
+#![allow(dead_code)]
+//! Notification center database queries
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::notification::{Notification, NotificationDigestItem, NotificationPreference};
+
+/// Create a notification, returning its ID
+pub async fn create_notification(
+    pool: &PgPool,
+    user_id: Uuid,
+    kind: &str,
+    title: &str,
+    body: Option<&str>,
+    entity: Option<(&str, Uuid)>,
+) -> Result<Uuid> {
+    let (id,): (Uuid,) = sqlx::query_as(
+        r#"
+        INSERT INTO notifications (user_id, kind, title, body, entity_type, entity_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(kind)
+    .bind(title)
+    .bind(body)
+    .bind(entity.map(|(entity_type, _)| entity_type))
+    .bind(entity.map(|(_, entity_id)| entity_id))
+    .fetch_one(pool)
+    .await?;
+
+    Ok(id)
+}
+
+/// List notifications, newest first
+pub async fn list_notifications(
+    pool: &PgPool,
+    user_id: Uuid,
+    unread_only: bool,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<Notification>> {
+    let items = sqlx::query_as::<_, Notification>(
+        r#"
+        SELECT id, kind, title, body, entity_type, entity_id, created_at, read_at
+        FROM notifications
+        WHERE user_id = $1 AND (NOT $2 OR read_at IS NULL)
+        ORDER BY created_at DESC, id
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(user_id)
+    .bind(unread_only)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
+
+/// Number of unread notifications
+pub async fn count_unread(pool: &PgPool, user_id: Uuid) -> Result<i64> {
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND read_at IS NULL")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+
+    Ok(count)
+}
+
+/// Mark notifications as read (all unread ones when `ids` is None)
+pub async fn mark_read(pool: &PgPool, user_id: Uuid, ids: Option<&[Uuid]>) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        UPDATE notifications SET read_at = NOW()
+        WHERE user_id = $1 AND read_at IS NULL
+          AND ($2::uuid[] IS NULL OR id = ANY($2))
+        "#,
+    )
+    .bind(user_id)
+    .bind(ids)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// ============================================================
+// Subscription preferences and digests
+// ============================================================
+
+/// Stored preferences of an account (categories without a row use the default)
+pub async fn list_preferences(pool: &PgPool, user_id: Uuid) -> Result<Vec<NotificationPreference>> {
+    let items = sqlx::query_as::<_, NotificationPreference>(
+        r#"
+        SELECT category, enabled, delivery
+        FROM notification_preferences
+        WHERE user_id = $1
+        ORDER BY category
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
+
+/// Stored preference for one category
+pub async fn get_preference(pool: &PgPool, user_id: Uuid, category: &str) -> Result<Option<NotificationPreference>> {
+    let item = sqlx::query_as::<_, NotificationPreference>(
+        "SELECT category, enabled, delivery FROM notification_preferences WHERE user_id = $1 AND category = $2",
+    )
+    .bind(user_id)
+    .bind(category)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(item)
+}
+
+/// Create or replace the preference for a category
+pub async fn upsert_preference(
+    pool: &PgPool,
+    user_id: Uuid,
+    category: &str,
+    enabled: bool,
+    delivery: &str,
+) -> Result<NotificationPreference> {
+    let item = sqlx::query_as::<_, NotificationPreference>(
+        r#"
+        INSERT INTO notification_preferences (user_id, category, enabled, delivery)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id, category) DO UPDATE SET
+            enabled = EXCLUDED.enabled,
+            delivery = EXCLUDED.delivery,
+            updated_at = NOW()
+        RETURNING category, enabled, delivery
+        "#,
+    )
+    .bind(user_id)
+    .bind(category)
+    .bind(enabled)
+    .bind(delivery)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(item)
+}
+
+/// Hold a notification back for a digest delivered after `deliver_after`
+#[allow(clippy::too_many_arguments)]
+pub async fn enqueue_digest_item(
+    pool: &PgPool,
+    user_id: Uuid,
+    category: &str,
+    kind: &str,
+    title: &str,
+    body: Option<&str>,
+    entity: Option<(&str, Uuid)>,
+    deliver_after: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO notification_digest_items
+            (user_id, category, kind, title, body, entity_type, entity_id, deliver_after)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+    )
+    .bind(user_id)
+    .bind(category)
+    .bind(kind)
+    .bind(title)
+    .bind(body)
+    .bind(entity.map(|(entity_type, _)| entity_type))
+    .bind(entity.map(|(_, entity_id)| entity_id))
+    .bind(deliver_after)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Digest items whose delivery time has come, oldest first
+pub async fn list_due_digest_items(pool: &PgPool) -> Result<Vec<NotificationDigestItem>> {
+    let items = sqlx::query_as::<_, NotificationDigestItem>(
+        r#"
+        SELECT id, user_id, category, title, created_at
+        FROM notification_digest_items
+        WHERE deliver_after <= NOW()
+        ORDER BY user_id, category, created_at
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
+
+/// Remove delivered digest items
+pub async fn delete_digest_items(pool: &PgPool, ids: &[Uuid]) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM notification_digest_items WHERE id = ANY($1)")
+        .bind(ids)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
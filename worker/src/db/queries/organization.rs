@@ -0,0 +1,289 @@
+//! Organization database queries
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::organization::{
+    InvitationForAcceptance, Organization, OrganizationInvitation, OrganizationMember, OrganizationMembership,
+    ORG_ROLE_MEMBER, ORG_ROLE_OWNER,
+};
+
+/// Organization of an owner account, created on first use
+pub async fn ensure_for_owner(pool: &PgPool, owner_id: Uuid) -> Result<Organization> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO organizations (owner_user_id, name)
+        SELECT id, COALESCE(NULLIF(business_name, ''), name) FROM users WHERE id = $1
+        ON CONFLICT (owner_user_id) DO NOTHING
+        "#,
+    )
+    .bind(owner_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let organization = sqlx::query_as::<_, Organization>(
+        "SELECT id, owner_user_id, name, created_at FROM organizations WHERE owner_user_id = $1",
+    )
+    .bind(owner_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO organization_members (organization_id, user_id, role)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id) DO NOTHING
+        "#,
+    )
+    .bind(organization.id)
+    .bind(owner_id)
+    .bind(ORG_ROLE_OWNER)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(organization)
+}
+
+/// Organization and role of a login
+pub async fn get_membership(pool: &PgPool, user_id: Uuid) -> Result<Option<OrganizationMembership>> {
+    let membership = sqlx::query_as::<_, OrganizationMembership>(
+        "SELECT organization_id, role FROM organization_members WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(membership)
+}
+
+/// Add a worker login of an owner to the owner's organization as a member
+pub async fn add_worker(pool: &PgPool, owner_id: Uuid, user_id: Uuid) -> Result<OrganizationMembership> {
+    let organization = ensure_for_owner(pool, owner_id).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO organization_members (organization_id, user_id, role)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id) DO NOTHING
+        "#,
+    )
+    .bind(organization.id)
+    .bind(user_id)
+    .bind(ORG_ROLE_MEMBER)
+    .execute(pool)
+    .await?;
+
+    Ok(OrganizationMembership { organization_id: organization.id, role: ORG_ROLE_MEMBER.to_string() })
+}
+
+/// Rename an organization
+pub async fn update_name(pool: &PgPool, organization_id: Uuid, name: &str) -> Result<Organization> {
+    let organization = sqlx::query_as::<_, Organization>(
+        r#"
+        UPDATE organizations SET name = $2
+        WHERE id = $1
+        RETURNING id, owner_user_id, name, created_at
+        "#,
+    )
+    .bind(organization_id)
+    .bind(name)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(organization)
+}
+
+/// Members of an organization, owner first
+pub async fn list_members(pool: &PgPool, organization_id: Uuid) -> Result<Vec<OrganizationMember>> {
+    let members = sqlx::query_as::<_, OrganizationMember>(
+        r#"
+        SELECT u.id AS user_id, u.email, u.name, m.role, m.joined_at, u.last_login_at
+        FROM organization_members m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.organization_id = $1
+        ORDER BY (m.role = 'owner') DESC, u.name
+        "#,
+    )
+    .bind(organization_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(members)
+}
+
+/// Change the role of a member other than the owner
+pub async fn set_member_role(pool: &PgPool, organization_id: Uuid, user_id: Uuid, role: &str) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE organization_members SET role = $3
+        WHERE organization_id = $1 AND user_id = $2 AND role <> 'owner'
+        "#,
+    )
+    .bind(organization_id)
+    .bind(user_id)
+    .bind(role)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove a member other than the owner together with their login
+pub async fn remove_member(pool: &PgPool, organization_id: Uuid, user_id: Uuid) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM users u
+        USING organization_members m, organizations o
+        WHERE u.id = $2 AND m.user_id = u.id AND m.organization_id = $1 AND m.role <> 'owner'
+          AND o.id = m.organization_id AND u.owner_id = o.owner_user_id AND u.role = 'worker'
+        "#,
+    )
+    .bind(organization_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Create an invitation, replacing a pending one for the same address
+pub async fn create_invitation(
+    pool: &PgPool,
+    organization_id: Uuid,
+    email: &str,
+    role: &str,
+    token_hash: &str,
+    invited_by: Uuid,
+    expires_at: DateTime<Utc>,
+) -> Result<OrganizationInvitation> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM organization_invitations
+        WHERE organization_id = $1 AND LOWER(email) = LOWER($2) AND accepted_at IS NULL
+        "#,
+    )
+    .bind(organization_id)
+    .bind(email)
+    .execute(&mut *tx)
+    .await?;
+
+    let invitation = sqlx::query_as::<_, OrganizationInvitation>(
+        r#"
+        INSERT INTO organization_invitations (organization_id, email, role, token_hash, invited_by, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, email, role, invited_by, expires_at, created_at
+        "#,
+    )
+    .bind(organization_id)
+    .bind(email)
+    .bind(role)
+    .bind(token_hash)
+    .bind(invited_by)
+    .bind(expires_at)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(invitation)
+}
+
+/// Pending, unexpired invitations of an organization
+pub async fn list_invitations(pool: &PgPool, organization_id: Uuid) -> Result<Vec<OrganizationInvitation>> {
+    let invitations = sqlx::query_as::<_, OrganizationInvitation>(
+        r#"
+        SELECT id, email, role, invited_by, expires_at, created_at
+        FROM organization_invitations
+        WHERE organization_id = $1 AND accepted_at IS NULL AND expires_at > NOW()
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(organization_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(invitations)
+}
+
+/// Revoke a pending invitation
+pub async fn revoke_invitation(pool: &PgPool, organization_id: Uuid, id: Uuid) -> Result<bool> {
+    let result = sqlx::query(
+        "DELETE FROM organization_invitations WHERE id = $1 AND organization_id = $2 AND accepted_at IS NULL",
+    )
+    .bind(id)
+    .bind(organization_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Pending, unexpired invitation of a token
+pub async fn find_invitation(pool: &PgPool, token_hash: &str) -> Result<Option<InvitationForAcceptance>> {
+    let invitation = sqlx::query_as::<_, InvitationForAcceptance>(
+        r#"
+        SELECT i.id, i.organization_id, o.owner_user_id, i.email, i.role, u.locale AS owner_locale
+        FROM organization_invitations i
+        JOIN organizations o ON o.id = i.organization_id
+        JOIN users u ON u.id = o.owner_user_id
+        WHERE i.token_hash = $1 AND i.accepted_at IS NULL AND i.expires_at > NOW()
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(invitation)
+}
+
+/// Create the invitee's login as a worker of the owner and join them to the
+/// organization. None when the invitation was accepted concurrently.
+pub async fn accept_invitation(
+    pool: &PgPool,
+    invitation: &InvitationForAcceptance,
+    name: &str,
+    password_hash: &str,
+) -> Result<Option<Uuid>> {
+    let mut tx = pool.begin().await?;
+
+    let claimed = sqlx::query(
+        "UPDATE organization_invitations SET accepted_at = NOW() WHERE id = $1 AND accepted_at IS NULL",
+    )
+    .bind(invitation.id)
+    .execute(&mut *tx)
+    .await?;
+    if claimed.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    // The invitation link went to this address, so it counts as verified
+    let user_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO users (email, password_hash, name, role, owner_id, locale, email_verified)
+        VALUES ($1, $2, $3, 'worker', $4, $5, TRUE)
+        RETURNING id
+        "#,
+    )
+    .bind(&invitation.email)
+    .bind(password_hash)
+    .bind(name)
+    .bind(invitation.owner_user_id)
+    .bind(&invitation.owner_locale)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query("INSERT INTO organization_members (organization_id, user_id, role) VALUES ($1, $2, $3)")
+        .bind(invitation.organization_id)
+        .bind(user_id)
+        .bind(&invitation.role)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(Some(user_id))
+}
@@ -326,6 +326,7 @@ fn build_inbox_sql_parts(req: &InboxRequest) -> InboxSqlParts {
     let mut conditions = vec![
         "c.user_id = $1".to_string(),
         "c.is_anonymized = FALSE".to_string(),
+        "c.deleted_at IS NULL".to_string(),
         "c.is_abandoned = FALSE".to_string(),
     ];
     let mut param_idx: usize = 3; // $1=user_id, $2=limit, $3=offset start after base params
@@ -538,7 +539,7 @@ pub async fn get_customer_inbox(
         FROM customers c
         INNER JOIN planned_actions pa ON pa.customer_id = c.id
         WHERE c.user_id = $1
-          AND c.is_anonymized = FALSE
+          AND c.is_anonymized = FALSE AND c.deleted_at IS NULL
           AND c.is_abandoned = FALSE
           AND pa.status IN ('open', 'snoozed')
           AND (pa.snooze_until IS NULL OR pa.snooze_until <= CURRENT_DATE)
@@ -556,7 +557,7 @@ pub async fn get_customer_inbox(
         FROM customers c
         INNER JOIN planned_actions pa ON pa.customer_id = c.id
         WHERE c.user_id = $1
-          AND c.is_anonymized = FALSE
+          AND c.is_anonymized = FALSE AND c.deleted_at IS NULL
           AND c.is_abandoned = FALSE
           AND pa.status IN ('open', 'snoozed')
           AND (pa.snooze_until IS NULL OR pa.snooze_until <= CURRENT_DATE)
@@ -0,0 +1,132 @@
+#![allow(dead_code)]
+//! Data quality rule queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::quality::QualityItem;
+
+/// Active customers of the user, aliased `c`
+const ACTIVE_CUSTOMERS: &str =
+    "c.user_id = $1 AND c.is_anonymized = FALSE AND c.deleted_at IS NULL AND c.is_abandoned = FALSE";
+
+/// Count and first `limit` items of a rule query. `from_where` is a static
+/// FROM ... WHERE clause; `extra` is bound as $2 when the clause uses it.
+async fn run_rule(
+    pool: &PgPool,
+    user_id: Uuid,
+    select: &str,
+    from_where: &str,
+    order: &str,
+    extra: Option<i32>,
+    limit: i64,
+) -> Result<(i64, Vec<QualityItem>)> {
+    let count_sql = format!("SELECT COUNT(*) {}", from_where);
+    let mut count_query = sqlx::query_scalar::<_, i64>(&count_sql).bind(user_id);
+    if let Some(extra) = extra {
+        count_query = count_query.bind(extra);
+    }
+    let count = count_query.fetch_one(pool).await?;
+    if count == 0 {
+        return Ok((0, Vec::new()));
+    }
+
+    let limit_param = if extra.is_some() { 3 } else { 2 };
+    let items_sql = format!("SELECT {} {} ORDER BY {} LIMIT ${}", select, from_where, order, limit_param);
+    let mut items_query = sqlx::query_as::<_, QualityItem>(&items_sql).bind(user_id);
+    if let Some(extra) = extra {
+        items_query = items_query.bind(extra);
+    }
+    let items = items_query.bind(limit).fetch_all(pool).await?;
+
+    Ok((count, items))
+}
+
+/// Customers without coordinates, or whose geocoding failed
+pub async fn customers_missing_coordinates(pool: &PgPool, user_id: Uuid, limit: i64) -> Result<(i64, Vec<QualityItem>)> {
+    run_rule(
+        pool,
+        user_id,
+        r#"c.id AS entity_id, c.id AS customer_id, c.name AS customer_name,
+           NULLIF(CONCAT_WS(', ', c.street, c.city, c.postal_code), '') AS detail"#,
+        &format!(
+            "FROM customers c WHERE {} AND (c.lat IS NULL OR c.lng IS NULL OR c.geocode_status = 'failed')",
+            ACTIVE_CUSTOMERS
+        ),
+        "c.name, c.id",
+        None,
+        limit,
+    )
+    .await
+}
+
+/// Devices without a revision interval; suggests the device type's default
+pub async fn devices_missing_interval(pool: &PgPool, user_id: Uuid, limit: i64) -> Result<(i64, Vec<QualityItem>)> {
+    run_rule(
+        pool,
+        user_id,
+        r#"d.id AS entity_id, c.id AS customer_id, c.name AS customer_name,
+           COALESCE(d.device_name, dtc.label) AS detail,
+           dtc.default_revision_interval_months::text AS suggested_value"#,
+        &format!(
+            r#"FROM devices d
+               JOIN customers c ON c.id = d.customer_id
+               LEFT JOIN device_type_configs dtc ON dtc.id = d.device_type_config_id
+               WHERE {} AND d.deleted_at IS NULL AND (d.revision_interval_months IS NULL OR d.revision_interval_months <= 0)"#,
+            ACTIVE_CUSTOMERS
+        ),
+        "c.name, d.id",
+        None,
+        limit,
+    )
+    .await
+}
+
+/// Overdue revisions of customers that have neither phone nor email
+pub async fn overdue_without_contact(
+    pool: &PgPool,
+    user_id: Uuid,
+    grace_days: i32,
+    limit: i64,
+) -> Result<(i64, Vec<QualityItem>)> {
+    run_rule(
+        pool,
+        user_id,
+        r#"r.id AS entity_id, c.id AS customer_id, c.name AS customer_name,
+           r.due_date::text AS detail"#,
+        &format!(
+            r#"FROM revisions r
+               JOIN customers c ON c.id = r.customer_id
+               WHERE {} AND r.user_id = $1
+                 AND r.status NOT IN ('completed', 'cancelled')
+                 AND r.due_date < CURRENT_DATE - $2::int
+                 AND NULLIF(TRIM(c.phone), '') IS NULL
+                 AND NULLIF(TRIM(c.email), '') IS NULL"#,
+            ACTIVE_CUSTOMERS
+        ),
+        "r.due_date, r.id",
+        Some(grace_days),
+        limit,
+    )
+    .await
+}
+
+/// Customers whose phone is not in international format
+pub async fn invalid_phone_numbers(pool: &PgPool, user_id: Uuid, limit: i64) -> Result<(i64, Vec<QualityItem>)> {
+    run_rule(
+        pool,
+        user_id,
+        r#"c.id AS entity_id, c.id AS customer_id, c.name AS customer_name, c.phone AS detail"#,
+        &format!(
+            r#"FROM customers c
+               WHERE {} AND NULLIF(TRIM(c.phone), '') IS NOT NULL
+                 AND c.phone !~ '^\+[1-9][0-9]{{7,14}}$'"#,
+            ACTIVE_CUSTOMERS
+        ),
+        "c.name, c.id",
+        None,
+        limit,
+    )
+    .await
+}
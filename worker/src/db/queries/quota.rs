@@ -0,0 +1,93 @@
+#![allow(dead_code)]
+//! Account plan and usage counter database queries
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Plan of an account
+pub async fn get_plan(pool: &PgPool, user_id: Uuid) -> Result<Option<String>> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT plan FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|(plan,)| plan))
+}
+
+/// Change the plan of an account, returning the previous one
+pub async fn set_plan(pool: &PgPool, user_id: Uuid, plan: &str) -> Result<Option<String>> {
+    let previous: Option<(String,)> = sqlx::query_as(
+        r#"
+        UPDATE users u
+        SET plan = $2, updated_at = NOW()
+        FROM (SELECT id, plan FROM users WHERE id = $1 FOR UPDATE) old
+        WHERE u.id = old.id
+        RETURNING old.plan
+        "#,
+    )
+    .bind(user_id)
+    .bind(plan)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(previous.map(|(p,)| p))
+}
+
+/// Customers currently on record (soft-deleted ones do not count)
+pub async fn count_customers(pool: &PgPool, user_id: Uuid) -> Result<i64> {
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM customers WHERE user_id = $1 AND deleted_at IS NULL")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+
+    Ok(count)
+}
+
+/// Counter value for a month
+pub async fn get_counter(pool: &PgPool, user_id: Uuid, metric: &str, period_start: NaiveDate) -> Result<i64> {
+    let row: Option<(i64,)> = sqlx::query_as(
+        "SELECT used FROM usage_counters WHERE user_id = $1 AND metric = $2 AND period_start = $3",
+    )
+    .bind(user_id)
+    .bind(metric)
+    .bind(period_start)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(used,)| used).unwrap_or(0))
+}
+
+/// Add `amount` to a monthly counter unless that would exceed `limit`.
+/// Returns the new value, or None if the limit would be exceeded.
+pub async fn try_increment(
+    pool: &PgPool,
+    user_id: Uuid,
+    metric: &str,
+    period_start: NaiveDate,
+    amount: i64,
+    limit: Option<i64>,
+) -> Result<Option<i64>> {
+    let row: Option<(i64,)> = sqlx::query_as(
+        r#"
+        INSERT INTO usage_counters (user_id, metric, period_start, used)
+        SELECT $1, $2, $3, $4
+        WHERE $5::bigint IS NULL OR $4 <= $5
+        ON CONFLICT (user_id, metric, period_start) DO UPDATE
+        SET used = usage_counters.used + EXCLUDED.used, updated_at = NOW()
+        WHERE $5::bigint IS NULL OR usage_counters.used + EXCLUDED.used <= $5
+        RETURNING used
+        "#,
+    )
+    .bind(user_id)
+    .bind(metric)
+    .bind(period_start)
+    .bind(amount)
+    .bind(limit)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(used,)| used))
+}
@@ -0,0 +1,317 @@
+#![allow(dead_code)]
+//! Quote queries
+
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::services::quote::{format_quote_number, work_order_note};
+use crate::types::quote::{
+    ListQuotesRequest, Quote, QuoteItem, QuoteListItem, ResolvedQuoteItem, QUOTE_STATUS_ACCEPTED,
+    QUOTE_STATUS_DECLINED, QUOTE_STATUS_DRAFT, QUOTE_STATUS_SENT,
+};
+
+const QUOTE_COLS: &str = r#"
+    id, user_id, customer_id, quote_number, title, note, currency, valid_until, status,
+    sent_at, decided_at, decline_reason, planned_action_id, created_at, updated_at
+"#;
+
+/// Status with sent quotes past their validity shown as expired
+const EFFECTIVE_STATUS_SQL: &str =
+    "CASE WHEN q.status = 'sent' AND q.valid_until < CURRENT_DATE THEN 'expired' ELSE q.status END";
+
+/// Create a draft quote with its lines. The number is taken from the
+/// account's yearly counter inside the transaction, so it is never reused.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_quote(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
+    title: &str,
+    note: Option<&str>,
+    currency: &str,
+    valid_until: NaiveDate,
+    items: &[ResolvedQuoteItem],
+) -> Result<Quote> {
+    let mut tx = pool.begin().await?;
+
+    let year = Utc::now().year();
+    let (sequence,): (i32,) = sqlx::query_as(
+        r#"
+        INSERT INTO quote_number_counters (user_id, period_year, last_number)
+        VALUES ($1, $2, 1)
+        ON CONFLICT (user_id, period_year)
+        DO UPDATE SET last_number = quote_number_counters.last_number + 1
+        RETURNING last_number
+        "#,
+    )
+    .bind(user_id)
+    .bind(year)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let quote = sqlx::query_as::<_, Quote>(&format!(
+        r#"
+        INSERT INTO quotes (user_id, customer_id, quote_number, title, note, currency, valid_until)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING {}
+        "#,
+        QUOTE_COLS
+    ))
+    .bind(user_id)
+    .bind(customer_id)
+    .bind(format_quote_number(year, sequence))
+    .bind(title.trim())
+    .bind(note)
+    .bind(currency)
+    .bind(valid_until)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    insert_items(&mut tx, quote.id, items).await?;
+
+    tx.commit().await?;
+    Ok(quote)
+}
+
+async fn insert_items(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    quote_id: Uuid,
+    items: &[ResolvedQuoteItem],
+) -> Result<()> {
+    for (position, item) in items.iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO quote_items (quote_id, position, catalog_item_id, name, unit, quantity, unit_price_minor, vat_rate, work_type)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(quote_id)
+        .bind(position as i32)
+        .bind(item.catalog_item_id)
+        .bind(&item.name)
+        .bind(&item.unit)
+        .bind(item.quantity)
+        .bind(item.unit_price_minor)
+        .bind(item.vat_rate)
+        .bind(item.work_type)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Quote of an account
+pub async fn get_quote(pool: &PgPool, user_id: Uuid, quote_id: Uuid) -> Result<Option<Quote>> {
+    let quote = sqlx::query_as::<_, Quote>(&format!("SELECT {} FROM quotes WHERE id = $1 AND user_id = $2", QUOTE_COLS))
+        .bind(quote_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(quote)
+}
+
+/// Quote behind an acceptance link
+pub async fn find_by_token_hash(pool: &PgPool, token_hash: &str) -> Result<Option<Quote>> {
+    let quote = sqlx::query_as::<_, Quote>(&format!("SELECT {} FROM quotes WHERE accept_token_hash = $1", QUOTE_COLS))
+        .bind(token_hash)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(quote)
+}
+
+/// Lines of a quote in order
+pub async fn list_items(pool: &PgPool, quote_id: Uuid) -> Result<Vec<QuoteItem>> {
+    let items = sqlx::query_as::<_, QuoteItem>("SELECT * FROM quote_items WHERE quote_id = $1 ORDER BY position")
+        .bind(quote_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(items)
+}
+
+/// Quotes of an account, newest first; `status` filters by effective status
+pub async fn list_quotes(pool: &PgPool, user_id: Uuid, req: &ListQuotesRequest) -> Result<(Vec<QuoteListItem>, i64)> {
+    let filter = format!(
+        "q.user_id = $1 AND ($2::uuid IS NULL OR q.customer_id = $2) AND ($3::text IS NULL OR {} = $3)",
+        EFFECTIVE_STATUS_SQL
+    );
+
+    let items = sqlx::query_as::<_, QuoteListItem>(&format!(
+        r#"
+        SELECT
+            q.id, q.customer_id, c.name AS customer_name, q.quote_number, q.title, q.currency,
+            q.valid_until, {} AS status,
+            COALESCE((SELECT SUM(ROUND(i.quantity * i.unit_price_minor))::bigint
+                      FROM quote_items i WHERE i.quote_id = q.id), 0) AS net_minor,
+            q.created_at
+        FROM quotes q
+        JOIN customers c ON c.id = q.customer_id
+        WHERE {}
+        ORDER BY q.created_at DESC
+        LIMIT $4 OFFSET $5
+        "#,
+        EFFECTIVE_STATUS_SQL, filter
+    ))
+    .bind(user_id)
+    .bind(req.customer_id)
+    .bind(&req.status)
+    .bind(req.limit.unwrap_or(50).clamp(1, 200))
+    .bind(req.offset.unwrap_or(0).max(0))
+    .fetch_all(pool)
+    .await?;
+
+    let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM quotes q WHERE {}", filter))
+        .bind(user_id)
+        .bind(req.customer_id)
+        .bind(&req.status)
+        .fetch_one(pool)
+        .await?;
+
+    Ok((items, total))
+}
+
+/// Replace header and lines of a draft. Returns false when the quote is
+/// missing or no longer a draft.
+pub async fn update_draft(
+    pool: &PgPool,
+    user_id: Uuid,
+    quote_id: Uuid,
+    title: &str,
+    note: Option<&str>,
+    valid_until: NaiveDate,
+    items: &[ResolvedQuoteItem],
+) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE quotes SET title = $3, note = $4, valid_until = $5, updated_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND status = $6
+        "#,
+    )
+    .bind(quote_id)
+    .bind(user_id)
+    .bind(title.trim())
+    .bind(note)
+    .bind(valid_until)
+    .bind(QUOTE_STATUS_DRAFT)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if updated == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query("DELETE FROM quote_items WHERE quote_id = $1")
+        .bind(quote_id)
+        .execute(&mut *tx)
+        .await?;
+    insert_items(&mut tx, quote_id, items).await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Delete a draft; sent quotes stay for the record
+pub async fn delete_draft(pool: &PgPool, user_id: Uuid, quote_id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM quotes WHERE id = $1 AND user_id = $2 AND status = $3")
+        .bind(quote_id)
+        .bind(user_id)
+        .bind(QUOTE_STATUS_DRAFT)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Mark a draft (or resend a sent quote) with a fresh acceptance token;
+/// an earlier link stops working
+pub async fn mark_sent(pool: &PgPool, user_id: Uuid, quote_id: Uuid, token_hash: &str) -> Result<Option<Quote>> {
+    let quote = sqlx::query_as::<_, Quote>(&format!(
+        r#"
+        UPDATE quotes
+        SET status = $4, accept_token_hash = $3, sent_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND status IN ($5, $4)
+        RETURNING {}
+        "#,
+        QUOTE_COLS
+    ))
+    .bind(quote_id)
+    .bind(user_id)
+    .bind(token_hash)
+    .bind(QUOTE_STATUS_SENT)
+    .bind(QUOTE_STATUS_DRAFT)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(quote)
+}
+
+/// Record the customer's decision on a sent, still valid quote. Returns
+/// None when the quote was decided meanwhile or has expired.
+///
+/// Acceptance creates the work order (an open planned action due today) and
+/// links it in the same transaction, so an accepted quote always has one.
+pub async fn decide(pool: &PgPool, quote_id: Uuid, accept: bool, reason: Option<&str>) -> Result<Option<Quote>> {
+    let status = if accept { QUOTE_STATUS_ACCEPTED } else { QUOTE_STATUS_DECLINED };
+    let mut tx = pool.begin().await?;
+
+    let quote = sqlx::query_as::<_, Quote>(&format!(
+        r#"
+        UPDATE quotes
+        SET status = $2, decline_reason = $3, decided_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND status = $4 AND valid_until >= CURRENT_DATE
+        RETURNING {}
+        "#,
+        QUOTE_COLS
+    ))
+    .bind(quote_id)
+    .bind(status)
+    .bind(if accept { None } else { reason })
+    .bind(QUOTE_STATUS_SENT)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(quote) = quote else {
+        return Ok(None);
+    };
+    if !accept {
+        tx.commit().await?;
+        return Ok(Some(quote));
+    }
+
+    let items = sqlx::query_as::<_, QuoteItem>("SELECT * FROM quote_items WHERE quote_id = $1 ORDER BY position")
+        .bind(quote.id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    let (planned_action_id,): (Uuid,) = sqlx::query_as(
+        r#"
+        INSERT INTO planned_actions (id, user_id, customer_id, status, due_date, note, created_at, updated_at)
+        VALUES ($1, $2, $3, 'open'::action_status, CURRENT_DATE, $4, NOW(), NOW())
+        RETURNING id
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(quote.user_id)
+    .bind(quote.customer_id)
+    .bind(work_order_note(&quote, &items))
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let quote = sqlx::query_as::<_, Quote>(&format!(
+        "UPDATE quotes SET planned_action_id = $2 WHERE id = $1 RETURNING {}",
+        QUOTE_COLS
+    ))
+    .bind(quote.id)
+    .bind(planned_action_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Some(quote))
+}
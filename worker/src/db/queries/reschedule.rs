@@ -0,0 +1,291 @@
+#![allow(dead_code)]
+//! Reschedule link and request database queries
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::reschedule::{
+    RescheduleRequest, RescheduleStatsResponse, RESCHEDULE_ACCEPTED, RESCHEDULE_DECLINED, RESCHEDULE_PENDING,
+};
+
+const REQUEST_COLUMNS: &str = r#"
+    r.id, r.user_id, r.visit_id, r.customer_id, c.name AS customer_name,
+    r.original_date, r.original_time_start, r.original_time_end,
+    r.requested_date, r.preferred_time_start, r.preferred_time_end,
+    r.customer_note, r.suggested_slots, r.status,
+    r.decided_by, r.decided_at, r.decision_note,
+    r.new_date, r.new_time_start, r.new_time_end, r.created_at
+"#;
+
+/// Store a link token hash for a visit
+pub async fn insert_link(
+    pool: &PgPool,
+    user_id: Uuid,
+    visit_id: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO visit_reschedule_links (token_hash, user_id, visit_id, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(token_hash)
+    .bind(user_id)
+    .bind(visit_id)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Resolve an unexpired link to (user_id, visit_id)
+pub async fn resolve_link(pool: &PgPool, token_hash: &str) -> Result<Option<(Uuid, Uuid)>> {
+    let row: Option<(Uuid, Uuid)> = sqlx::query_as(
+        "SELECT user_id, visit_id FROM visit_reschedule_links WHERE token_hash = $1 AND expires_at > NOW()",
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row)
+}
+
+/// Record a customer's request. Returns None if the visit already has a pending one.
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_request(
+    pool: &PgPool,
+    user_id: Uuid,
+    visit_id: Uuid,
+    customer_id: Uuid,
+    original: (NaiveDate, Option<NaiveTime>, Option<NaiveTime>),
+    requested_date: NaiveDate,
+    preferred: (Option<NaiveTime>, Option<NaiveTime>),
+    customer_note: Option<&str>,
+    suggested_slots: &serde_json::Value,
+) -> Result<Option<Uuid>> {
+    let row: Option<(Uuid,)> = sqlx::query_as(
+        r#"
+        INSERT INTO reschedule_requests (
+            user_id, visit_id, customer_id,
+            original_date, original_time_start, original_time_end,
+            requested_date, preferred_time_start, preferred_time_end,
+            customer_note, suggested_slots
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT (visit_id) WHERE status = 'pending' DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(visit_id)
+    .bind(customer_id)
+    .bind(original.0)
+    .bind(original.1)
+    .bind(original.2)
+    .bind(requested_date)
+    .bind(preferred.0)
+    .bind(preferred.1)
+    .bind(customer_note)
+    .bind(suggested_slots)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(id,)| id))
+}
+
+/// Get a request owned by the user
+pub async fn get_request(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<Option<RescheduleRequest>> {
+    let query = format!(
+        r#"
+        SELECT {} FROM reschedule_requests r
+        LEFT JOIN customers c ON c.id = r.customer_id
+        WHERE r.id = $1 AND r.user_id = $2
+        "#,
+        REQUEST_COLUMNS
+    );
+
+    let row = sqlx::query_as::<_, RescheduleRequest>(&query)
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row)
+}
+
+/// Latest request for a visit
+pub async fn latest_for_visit(pool: &PgPool, visit_id: Uuid) -> Result<Option<RescheduleRequest>> {
+    let query = format!(
+        r#"
+        SELECT {} FROM reschedule_requests r
+        LEFT JOIN customers c ON c.id = r.customer_id
+        WHERE r.visit_id = $1
+        ORDER BY r.created_at DESC
+        LIMIT 1
+        "#,
+        REQUEST_COLUMNS
+    );
+
+    let row = sqlx::query_as::<_, RescheduleRequest>(&query)
+        .bind(visit_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row)
+}
+
+/// List requests of a user, newest first
+pub async fn list_requests(
+    pool: &PgPool,
+    user_id: Uuid,
+    status: Option<&str>,
+    visit_id: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<RescheduleRequest>> {
+    let query = format!(
+        r#"
+        SELECT {} FROM reschedule_requests r
+        LEFT JOIN customers c ON c.id = r.customer_id
+        WHERE r.user_id = $1
+          AND ($2::text IS NULL OR r.status = $2)
+          AND ($3::uuid IS NULL OR r.visit_id = $3)
+        ORDER BY r.created_at DESC
+        LIMIT $4
+        "#,
+        REQUEST_COLUMNS
+    );
+
+    let rows = sqlx::query_as::<_, RescheduleRequest>(&query)
+        .bind(user_id)
+        .bind(status)
+        .bind(visit_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows)
+}
+
+/// Accept a pending request and move the visit in one transaction.
+/// Returns false if the request is no longer pending.
+#[allow(clippy::too_many_arguments)]
+pub async fn accept_request(
+    pool: &PgPool,
+    user_id: Uuid,
+    id: Uuid,
+    decided_by: Uuid,
+    new_date: NaiveDate,
+    new_time_start: Option<NaiveTime>,
+    new_time_end: Option<NaiveTime>,
+    crew_id: Option<Uuid>,
+    note: Option<&str>,
+) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let visit: Option<(Uuid,)> = sqlx::query_as(
+        r#"
+        UPDATE reschedule_requests
+        SET status = $3, decided_by = $4, decided_at = NOW(), decision_note = $5,
+            new_date = $6, new_time_start = $7, new_time_end = $8
+        WHERE id = $1 AND user_id = $2 AND status = $9
+        RETURNING visit_id
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(RESCHEDULE_ACCEPTED)
+    .bind(decided_by)
+    .bind(note)
+    .bind(new_date)
+    .bind(new_time_start)
+    .bind(new_time_end)
+    .bind(RESCHEDULE_PENDING)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some((visit_id,)) = visit else {
+        return Ok(false);
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE visits SET
+            scheduled_date = $3,
+            scheduled_time_start = $4,
+            scheduled_time_end = $5,
+            crew_id = COALESCE($6, crew_id),
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(visit_id)
+    .bind(user_id)
+    .bind(new_date)
+    .bind(new_time_start)
+    .bind(new_time_end)
+    .bind(crew_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Decline a pending request. Returns false if it is no longer pending.
+pub async fn decline_request(
+    pool: &PgPool,
+    user_id: Uuid,
+    id: Uuid,
+    decided_by: Uuid,
+    note: Option<&str>,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE reschedule_requests
+        SET status = $3, decided_by = $4, decided_at = NOW(), decision_note = $5
+        WHERE id = $1 AND user_id = $2 AND status = $6
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(RESCHEDULE_DECLINED)
+    .bind(decided_by)
+    .bind(note)
+    .bind(RESCHEDULE_PENDING)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Outcome counts of requests created since `since`
+pub async fn stats(pool: &PgPool, user_id: Uuid, since: DateTime<Utc>) -> Result<RescheduleStatsResponse> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        r#"
+        SELECT status, COUNT(*) FROM reschedule_requests
+        WHERE user_id = $1 AND created_at >= $2
+        GROUP BY status
+        "#,
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    let mut stats = RescheduleStatsResponse::default();
+    for (status, count) in rows {
+        match status.as_str() {
+            RESCHEDULE_PENDING => stats.pending = count,
+            RESCHEDULE_ACCEPTED => stats.accepted = count,
+            RESCHEDULE_DECLINED => stats.declined = count,
+            _ => {}
+        }
+    }
+
+    Ok(stats)
+}
@@ -0,0 +1,222 @@
+#![allow(dead_code)]
+//! Document retention database queries
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::retention::{
+    ExpiringPolicy, RetentionCounts, RetentionDeletion, RetentionPolicy, SetRetentionPolicyRequest,
+    RETENTION_ENTITY_COMMUNICATION, RETENTION_ENTITY_REVISION,
+};
+
+/// Most records the sweep deletes per policy and run; the rest follow on
+/// the next run
+const SWEEP_BATCH_LIMIT: i64 = 500;
+
+/// Table, reference date and scope of the records an entity type covers
+fn entity_sql(entity_type: &str) -> (&'static str, &'static str, &'static str) {
+    match entity_type {
+        RETENTION_ENTITY_REVISION => ("revisions", "completed_at::date", "completed_at IS NOT NULL"),
+        RETENTION_ENTITY_COMMUNICATION => ("communications", "created_at::date", "TRUE"),
+        _ => ("visits", "scheduled_date", "status = 'completed'"),
+    }
+}
+
+/// Policies the account has set
+pub async fn list_policies(pool: &PgPool, user_id: Uuid) -> Result<Vec<RetentionPolicy>> {
+    let policies = sqlx::query_as::<_, RetentionPolicy>(
+        r#"
+        SELECT entity_type, retention_months, delete_after_expiry, FALSE AS is_default, updated_at
+        FROM retention_policies
+        WHERE user_id = $1
+        ORDER BY entity_type
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(policies)
+}
+
+/// Create or replace the policy of an entity type
+pub async fn upsert_policy(
+    pool: &PgPool,
+    user_id: Uuid,
+    req: &SetRetentionPolicyRequest,
+) -> Result<RetentionPolicy> {
+    let policy = sqlx::query_as::<_, RetentionPolicy>(
+        r#"
+        INSERT INTO retention_policies (user_id, entity_type, retention_months, delete_after_expiry)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id, entity_type) DO UPDATE SET
+            retention_months = EXCLUDED.retention_months,
+            delete_after_expiry = EXCLUDED.delete_after_expiry,
+            updated_at = NOW()
+        RETURNING entity_type, retention_months, delete_after_expiry, FALSE AS is_default, updated_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(&req.entity_type)
+    .bind(req.retention_months)
+    .bind(req.delete_after_expiry)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(policy)
+}
+
+/// Policies with deletion after expiry, for the sweep
+pub async fn list_expiring_policies(pool: &PgPool) -> Result<Vec<ExpiringPolicy>> {
+    let policies = sqlx::query_as::<_, ExpiringPolicy>(
+        r#"
+        SELECT user_id, entity_type, retention_months
+        FROM retention_policies
+        WHERE delete_after_expiry
+        ORDER BY user_id, entity_type
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(policies)
+}
+
+/// Date the retention period of a record counts from; None when the record
+/// does not exist or is not covered (e.g. a revision not yet completed)
+pub async fn reference_date(
+    pool: &PgPool,
+    user_id: Uuid,
+    entity_type: &str,
+    entity_id: Uuid,
+) -> Result<Option<NaiveDate>> {
+    let (table, reference, scope) = entity_sql(entity_type);
+    let query = format!(
+        "SELECT {} FROM {} WHERE id = $1 AND user_id = $2 AND {}",
+        reference, table, scope
+    );
+
+    let date: Option<NaiveDate> = sqlx::query_scalar(&query)
+        .bind(entity_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(date)
+}
+
+/// Completion date of the latest completed revision of a device; deleting
+/// a device deletes its revisions with it
+pub async fn device_reference_date(pool: &PgPool, user_id: Uuid, device_id: Uuid) -> Result<Option<NaiveDate>> {
+    let date: Option<NaiveDate> = sqlx::query_scalar(
+        r#"
+        SELECT MAX(completed_at)::date
+        FROM revisions
+        WHERE device_id = $1 AND user_id = $2 AND completed_at IS NOT NULL
+        "#,
+    )
+    .bind(device_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(date)
+}
+
+/// Records of an entity type inside and past a period of `months`
+pub async fn count_records(
+    pool: &PgPool,
+    user_id: Uuid,
+    entity_type: &str,
+    months: i32,
+) -> Result<RetentionCounts> {
+    let (table, reference, scope) = entity_sql(entity_type);
+    let query = format!(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE {reference} > CURRENT_DATE - make_interval(months => $2)) AS retained_count,
+            COUNT(*) FILTER (WHERE {reference} <= CURRENT_DATE - make_interval(months => $2)) AS expired_count,
+            MIN({reference}) FILTER (WHERE {reference} > CURRENT_DATE - make_interval(months => $2)) AS oldest_retained
+        FROM {table}
+        WHERE user_id = $1 AND {scope}
+        "#
+    );
+
+    let counts = sqlx::query_as::<_, RetentionCounts>(&query)
+        .bind(user_id)
+        .bind(months)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(counts)
+}
+
+/// Delete records past the period of a policy and log them. Returns the
+/// number of deleted records.
+pub async fn delete_expired(pool: &PgPool, policy: &ExpiringPolicy) -> Result<u64> {
+    let (table, reference, scope) = entity_sql(&policy.entity_type);
+    let query = format!(
+        r#"
+        WITH deleted AS (
+            DELETE FROM {table}
+            WHERE id IN (
+                SELECT id FROM {table}
+                WHERE user_id = $1 AND {scope}
+                  AND {reference} <= CURRENT_DATE - make_interval(months => $3)
+                LIMIT $4
+            )
+            RETURNING id, {reference} AS reference_date
+        )
+        INSERT INTO retention_deletions (user_id, entity_type, entity_id, reference_date, retention_months)
+        SELECT $1, $2, id, reference_date, $3 FROM deleted
+        "#
+    );
+
+    let result = sqlx::query(&query)
+        .bind(policy.user_id)
+        .bind(&policy.entity_type)
+        .bind(policy.retention_months)
+        .bind(SWEEP_BATCH_LIMIT)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Sweep deletions per entity type in the last 12 months
+pub async fn count_deletions_last_year(pool: &PgPool, user_id: Uuid) -> Result<Vec<(String, i64)>> {
+    let counts = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT entity_type, COUNT(*)
+        FROM retention_deletions
+        WHERE user_id = $1 AND deleted_at > NOW() - INTERVAL '12 months'
+        GROUP BY entity_type
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(counts)
+}
+
+/// Latest sweep deletions
+pub async fn list_deletions(pool: &PgPool, user_id: Uuid, limit: i64) -> Result<Vec<RetentionDeletion>> {
+    let deletions = sqlx::query_as::<_, RetentionDeletion>(
+        r#"
+        SELECT id, entity_type, entity_id, reference_date, retention_months, deleted_at
+        FROM retention_deletions
+        WHERE user_id = $1
+        ORDER BY deleted_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(deletions)
+}
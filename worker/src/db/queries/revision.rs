@@ -7,6 +7,8 @@ use chrono::{Datelike, NaiveDate, NaiveTime, Utc};
 use anyhow::Result;
 
 use crate::types::revision::{CreateRevisionRequest, Revision, RevisionStats, RevisionStatus, UpdateRevisionRequest};
+use crate::types::report::DueProjectionSource;
+use crate::types::route::MultidayCandidate;
 
 // Common column list for Revision queries
 const REVISION_COLS: &str = r#"
@@ -16,7 +18,7 @@ const REVISION_COLS: &str = r#"
     r.completed_at, r.duration_minutes, r.result::text,
     r.findings, r.fulfilled_by_work_item_id,
     r.created_at, r.updated_at,
-    r.assigned_crew_id, r.route_order
+    r.assigned_crew_id, r.route_order, r.document_number
 "#;
 
 // Simpler column list without table alias (for single-table queries)
@@ -27,7 +29,7 @@ const REVISION_COLS_SIMPLE: &str = r#"
     completed_at, duration_minutes, result::text,
     findings, fulfilled_by_work_item_id,
     created_at, updated_at,
-    assigned_crew_id, route_order
+    assigned_crew_id, route_order, document_number
 "#;
 
 /// Create a new revision
@@ -126,7 +128,7 @@ pub async fn update_revision(
     Ok(revision)
 }
 
-/// Complete a revision with result and findings
+/// Complete a revision with result and findings, assigning its document number
 pub async fn complete_revision(
     pool: &PgPool,
     revision_id: Uuid,
@@ -150,6 +152,8 @@ pub async fn complete_revision(
         REVISION_COLS_SIMPLE
     );
     
+    let mut tx = pool.begin().await?;
+
     let revision = sqlx::query_as::<_, Revision>(&query)
     .bind(revision_id)
     .bind(user_id)
@@ -157,10 +161,18 @@ pub async fn complete_revision(
     .bind(findings)
     .bind(duration_minutes)
     .bind(RevisionStatus::Completed.as_str())
-    .fetch_optional(pool)
+    .fetch_optional(&mut *tx)
     .await?;
 
-    Ok(revision)
+    let Some(mut revision) = revision else {
+        return Ok(None);
+    };
+    if let Some(number) = super::revision_number::assign_revision_number(&mut tx, revision.id).await? {
+        revision.document_number = Some(number);
+    }
+
+    tx.commit().await?;
+    Ok(Some(revision))
 }
 
 /// Delete a revision
@@ -233,11 +245,13 @@ pub async fn list_revisions_for_date(
     Ok(revisions)
 }
 
-/// List revisions with optional filters
+/// List revisions with optional filters. With `include_branches`, a
+/// customer filter also matches the customer's branches.
 pub async fn list_revisions(
     pool: &PgPool,
     user_id: Uuid,
     customer_id: Option<Uuid>,
+    include_branches: bool,
     device_id: Option<Uuid>,
     status: Option<&str>,
     from_date: Option<NaiveDate>,
@@ -268,7 +282,7 @@ pub async fn list_revisions(
         LEFT JOIN devices d ON r.device_id = d.id
         LEFT JOIN customers c ON r.customer_id = c.id
         WHERE r.user_id = $1
-          AND ($2::uuid IS NULL OR r.customer_id = $2)
+          AND ($2::uuid IS NULL OR r.customer_id = $2 OR ($9 AND c.parent_customer_id = $2))
           AND ($3::uuid IS NULL OR r.device_id = $3)
           AND ($4::text IS NULL OR r.status::text = $4)
           AND ($5::date IS NULL OR {date_field} >= $5)
@@ -290,6 +304,44 @@ pub async fn list_revisions(
         .bind(to_date)
         .bind(limit)
         .bind(offset)
+        .bind(include_branches)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(revisions)
+}
+
+/// Revisions completed between two dates (inclusive), with device and
+/// customer info, in completion order
+pub async fn list_completed_between(
+    pool: &PgPool,
+    user_id: Uuid,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+) -> Result<Vec<Revision>> {
+    let query = format!(
+        r#"
+        SELECT
+            {},
+            d.device_name, d.device_type::text as device_type,
+            c.name as customer_name, c.phone as customer_phone,
+            c.street as customer_street, c.city as customer_city,
+            c.postal_code as customer_postal_code
+        FROM revisions r
+        LEFT JOIN devices d ON r.device_id = d.id
+        LEFT JOIN customers c ON r.customer_id = c.id
+        WHERE r.user_id = $1
+          AND r.status = 'completed'
+          AND r.completed_at::date BETWEEN $2 AND $3
+        ORDER BY r.completed_at, r.document_number
+        "#,
+        REVISION_COLS
+    );
+
+    let revisions = sqlx::query_as::<_, Revision>(&query)
+        .bind(user_id)
+        .bind(from_date)
+        .bind(to_date)
         .fetch_all(pool)
         .await?;
 
@@ -353,6 +405,70 @@ pub async fn list_due_soon_revisions(pool: &PgPool, user_id: Uuid, days: i32) ->
     Ok(revisions)
 }
 
+/// Customers among `customer_ids` with an unplanned revision due by `until`
+/// (overdue ones included)
+pub async fn list_customers_due_by(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_ids: &[Uuid],
+    until: NaiveDate,
+) -> Result<Vec<Uuid>> {
+    let query = format!(
+        r#"
+        SELECT DISTINCT r.customer_id
+        FROM revisions r
+        WHERE r.user_id = $1
+          AND r.customer_id = ANY($2)
+          AND r.due_date <= $3
+          AND r.status = '{}'
+        "#,
+        RevisionStatus::Upcoming.as_str()
+    );
+
+    let ids: Vec<Uuid> = sqlx::query_scalar(&query)
+        .bind(user_id)
+        .bind(customer_ids)
+        .bind(until)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(ids)
+}
+
+/// Unscheduled revisions for multi-day planning: the given ones, or all
+/// due by `until` when `revision_ids` is empty
+pub async fn list_multiday_candidates(
+    pool: &PgPool,
+    user_id: Uuid,
+    revision_ids: &[Uuid],
+    until: NaiveDate,
+) -> Result<Vec<MultidayCandidate>> {
+    let query = format!(
+        r#"
+        SELECT r.id AS revision_id, r.customer_id, r.due_date, c.lat, c.lng
+        FROM revisions r
+        JOIN customers c ON c.id = r.customer_id
+        WHERE r.user_id = $1
+          AND r.status = '{}'
+          AND r.scheduled_date IS NULL
+          AND c.deleted_at IS NULL AND c.is_abandoned = FALSE
+          AND (CARDINALITY($2::uuid[]) > 0 AND r.id = ANY($2)
+               OR CARDINALITY($2::uuid[]) = 0 AND r.due_date <= $3)
+        ORDER BY r.due_date, r.customer_id
+        "#,
+        RevisionStatus::Upcoming.as_str()
+    );
+
+    let candidates = sqlx::query_as::<_, MultidayCandidate>(&query)
+        .bind(user_id)
+        .bind(revision_ids)
+        .bind(until)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(candidates)
+}
+
 /// Get revision statistics for dashboard
 pub async fn get_revision_stats(pool: &PgPool, user_id: Uuid) -> Result<RevisionStats> {
     let today = Utc::now().date_naive();
@@ -410,6 +526,37 @@ pub async fn get_revision_stats(pool: &PgPool, user_id: Uuid) -> Result<Revision
     })
 }
 
+/// Load per-device projection sources for the capacity forecast.
+///
+/// The anchor due date is the earliest open revision; devices without one
+/// fall back to `next_due_date`, then to the last completion + interval.
+pub async fn list_due_projection_sources(pool: &PgPool, user_id: Uuid) -> Result<Vec<DueProjectionSource>> {
+    let sources = sqlx::query_as::<_, DueProjectionSource>(
+        r#"
+        SELECT
+            d.id AS device_id,
+            COALESCE(
+                (SELECT MIN(r.due_date) FROM revisions r
+                 WHERE r.device_id = d.id AND r.status NOT IN ('completed', 'cancelled')),
+                d.next_due_date,
+                (SELECT (MAX(r.completed_at)::date
+                         + make_interval(months => COALESCE(d.revision_interval_months, 12)))::date
+                 FROM revisions r
+                 WHERE r.device_id = d.id AND r.status = 'completed')
+            ) AS next_due_date,
+            d.revision_interval_months AS interval_months
+        FROM devices d
+        JOIN customers c ON c.id = d.customer_id
+        WHERE d.user_id = $1 AND d.deleted_at IS NULL AND c.is_abandoned = FALSE AND c.deleted_at IS NULL
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sources)
+}
+
 /// List revisions by device
 pub async fn list_revisions_by_device(pool: &PgPool, device_id: Uuid, user_id: Uuid) -> Result<Vec<Revision>> {
     let query = format!(
@@ -1037,6 +1184,7 @@ pub async fn create_initial_revisions_for_user(
         LEFT JOIN device_type_configs dtc
             ON d.device_type_config_id = dtc.id AND dtc.is_active = true
         WHERE d.user_id = $1
+          AND d.deleted_at IS NULL
           AND NOT EXISTS (
             SELECT 1 FROM revisions r
             WHERE r.device_id = d.id
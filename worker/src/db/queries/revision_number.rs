@@ -0,0 +1,164 @@
+#![allow(dead_code)]
+//! Revision document numbering queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::revision_number::{
+    format_document_number, generate_verification_code, CertificateRecord, IssuedDocumentNumber, NUMBERING_PERIOD_NONE,
+};
+
+/// Revision being numbered, with the series settings of its owner
+#[derive(sqlx::FromRow)]
+struct NumberingTarget {
+    user_id: Uuid,
+    document_number: Option<String>,
+    revision_numbering_enabled: bool,
+    revision_number_prefix: String,
+    revision_number_padding: i16,
+    revision_number_yearly_reset: bool,
+    completed_year: i32,
+}
+
+/// Assign the next number of the user's series to a completed revision.
+///
+/// Runs inside the completion transaction: the counter row stays locked until
+/// commit, so concurrent completions queue up and a rollback returns the number.
+/// Returns None when numbering is disabled or the revision already has a number.
+pub async fn assign_revision_number(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    revision_id: Uuid,
+) -> Result<Option<String>> {
+    let target = sqlx::query_as::<_, NumberingTarget>(
+        r#"
+        SELECT
+            r.user_id, r.document_number,
+            u.revision_numbering_enabled, u.revision_number_prefix,
+            u.revision_number_padding, u.revision_number_yearly_reset,
+            EXTRACT(YEAR FROM COALESCE(r.completed_at, NOW()))::int AS completed_year
+        FROM revisions r
+        JOIN users u ON u.id = r.user_id
+        WHERE r.id = $1
+        FOR UPDATE OF r
+        "#,
+    )
+    .bind(revision_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    let Some(target) = target else {
+        return Ok(None);
+    };
+    if target.document_number.is_some() || !target.revision_numbering_enabled {
+        return Ok(None);
+    }
+
+    let user_id = target.user_id;
+    let yearly_reset = target.revision_number_yearly_reset;
+    let period_year = if yearly_reset { target.completed_year } else { NUMBERING_PERIOD_NONE };
+    let (sequence,): (i32,) = sqlx::query_as(
+        r#"
+        INSERT INTO revision_number_counters (user_id, period_year, last_number)
+        VALUES ($1, $2, 1)
+        ON CONFLICT (user_id, period_year)
+        DO UPDATE SET last_number = revision_number_counters.last_number + 1
+        RETURNING last_number
+        "#,
+    )
+    .bind(user_id)
+    .bind(period_year)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    let document_number = format_document_number(
+        &target.revision_number_prefix,
+        target.revision_number_padding,
+        yearly_reset.then_some(target.completed_year),
+        sequence,
+    );
+
+    sqlx::query(
+        r#"
+        INSERT INTO revision_document_numbers
+            (user_id, period_year, sequence, document_number, revision_id, verification_code)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(user_id)
+    .bind(period_year)
+    .bind(sequence)
+    .bind(&document_number)
+    .bind(revision_id)
+    .bind(generate_verification_code())
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query("UPDATE revisions SET document_number = $2 WHERE id = $1")
+        .bind(revision_id)
+        .bind(&document_number)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(Some(document_number))
+}
+
+/// Register of issued numbers of one series period, in sequence order
+pub async fn list_issued(pool: &PgPool, user_id: Uuid, period_year: i32) -> Result<Vec<IssuedDocumentNumber>> {
+    let items = sqlx::query_as::<_, IssuedDocumentNumber>(
+        r#"
+        SELECT id, period_year, sequence, document_number, revision_id,
+               issued_at, voided_at, void_reason
+        FROM revision_document_numbers
+        WHERE user_id = $1 AND period_year = $2
+        ORDER BY sequence
+        "#,
+    )
+    .bind(user_id)
+    .bind(period_year)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
+
+/// Verification code of a revision's number, unless the number was voided
+pub async fn get_verification_code(pool: &PgPool, user_id: Uuid, revision_id: Uuid) -> Result<Option<String>> {
+    let code = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT verification_code
+        FROM revision_document_numbers
+        WHERE user_id = $1 AND revision_id = $2 AND voided_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(revision_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(code)
+}
+
+/// Issued numbers equal to `document_number` in any account (series of
+/// different accounts may produce the same number; the code tells them apart)
+pub async fn find_certificates(pool: &PgPool, document_number: &str) -> Result<Vec<CertificateRecord>> {
+    let items = sqlx::query_as::<_, CertificateRecord>(
+        r#"
+        SELECT
+            n.user_id, n.document_number, n.verification_code, n.issued_at, n.voided_at,
+            d.device_type::text AS device_type,
+            r.result::text AS result,
+            (COALESCE(r.completed_at, n.issued_at)::date
+                + make_interval(months => d.revision_interval_months))::date AS valid_until
+        FROM revision_document_numbers n
+        LEFT JOIN revisions r ON r.id = n.revision_id
+        LEFT JOIN devices d ON d.id = r.device_id
+        WHERE n.document_number = $1
+        "#,
+    )
+    .bind(document_number)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
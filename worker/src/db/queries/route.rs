@@ -2,11 +2,12 @@
 //! Route database queries
 
 use sqlx::PgPool;
+use sqlx::types::Json;
 use uuid::Uuid;
 use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use anyhow::Result;
 
-use crate::types::route::Route;
+use crate::types::route::{BreakLocation, Route, RouteStopNotes, RouteStopTask, UpdateRouteStopNotesRequest};
 
 /// A stop in a saved route
 #[derive(Debug, Clone, sqlx::FromRow, serde::Serialize)]
@@ -29,6 +30,9 @@ pub struct SavedRouteStop {
     pub service_duration_minutes: Option<i32>,
     pub override_service_duration_minutes: Option<i32>,
     pub override_travel_duration_minutes: Option<i32>,
+    pub notes: Option<String>,
+    pub tasks: Json<Vec<RouteStopTask>>,
+    pub break_location: Option<Json<BreakLocation>>,
 }
 
 /// Get route for a specific date and optional crew
@@ -283,6 +287,9 @@ pub async fn insert_route_stop(
     service_duration_minutes: Option<i32>,
     override_service_duration_minutes: Option<i32>,
     override_travel_duration_minutes: Option<i32>,
+    notes: Option<&str>,
+    tasks: &[RouteStopTask],
+    break_location: Option<&BreakLocation>,
 ) -> Result<SavedRouteStop> {
     let stop = sqlx::query_as::<_, SavedRouteStop>(
         r#"
@@ -292,16 +299,19 @@ pub async fn insert_route_stop(
             distance_from_previous_km, duration_from_previous_minutes,
             status, stop_type, break_duration_minutes, break_time_start,
             service_duration_minutes,
-            override_service_duration_minutes, override_travel_duration_minutes
+            override_service_duration_minutes, override_travel_duration_minutes,
+            notes, tasks, break_location
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, COALESCE($14, 'pending'), $11, $12, $13, $15, $16, $17)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, COALESCE($14, 'pending'), $11, $12, $13, $15, $16, $17,
+                NULLIF(TRIM($18), ''), $19, $20)
         RETURNING
             id, route_id, customer_id, visit_id, revision_id,
             stop_order, estimated_arrival, estimated_departure,
             distance_from_previous_km, duration_from_previous_minutes,
             status, stop_type, break_duration_minutes, break_time_start,
             service_duration_minutes,
-            override_service_duration_minutes, override_travel_duration_minutes
+            override_service_duration_minutes, override_travel_duration_minutes,
+            notes, tasks, break_location
         "#
     )
     .bind(Uuid::new_v4())
@@ -321,12 +331,66 @@ pub async fn insert_route_stop(
     .bind(service_duration_minutes)
     .bind(override_service_duration_minutes)
     .bind(override_travel_duration_minutes)
+    .bind(notes)
+    .bind(Json(tasks))
+    .bind(break_location.map(Json))
     .fetch_one(pool)
     .await?;
     
     Ok(stop)
 }
 
+/// Notes and checklists of a route's customer stops, keyed by customer and
+/// revision, so a re-save can keep them
+pub async fn list_route_stop_notes(
+    pool: &PgPool,
+    route_id: Uuid,
+) -> Result<Vec<(Option<Uuid>, Option<Uuid>, Option<String>, Json<Vec<RouteStopTask>>)>> {
+    let rows = sqlx::query_as(
+        r#"
+        SELECT customer_id, revision_id, notes, tasks
+        FROM route_stops
+        WHERE route_id = $1
+          AND (notes IS NOT NULL OR jsonb_array_length(tasks) > 0)
+        "#
+    )
+    .bind(route_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Update the notes and/or checklist of a stop on one of the user's routes,
+/// optionally only if the stop belongs to `route_id`
+pub async fn update_route_stop_notes(
+    pool: &PgPool,
+    user_id: Uuid,
+    route_id: Option<Uuid>,
+    request: &UpdateRouteStopNotesRequest,
+) -> Result<Option<RouteStopNotes>> {
+    let updated = sqlx::query_as::<_, RouteStopNotes>(
+        r#"
+        UPDATE route_stops rs
+        SET notes = CASE WHEN $3::text IS NULL THEN rs.notes ELSE NULLIF(TRIM($3), '') END,
+            tasks = COALESCE($4::jsonb, rs.tasks)
+        FROM routes r
+        WHERE rs.id = $1 AND r.id = rs.route_id AND r.user_id = $2
+          AND ($5::uuid IS NULL OR rs.route_id = $5)
+        RETURNING rs.id AS stop_id, rs.notes, rs.tasks
+        "#
+    )
+    .bind(request.stop_id)
+    .bind(user_id)
+    .bind(request.notes.as_deref())
+    .bind(request.tasks.as_ref().map(Json))
+    .bind(route_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(updated)
+}
+
 /// Route stop with customer info for loading saved routes
 #[derive(Debug, Clone, sqlx::FromRow, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -358,6 +422,12 @@ pub struct RouteStopWithInfo {
     pub service_duration_minutes: Option<i32>,
     pub override_service_duration_minutes: Option<i32>,
     pub override_travel_duration_minutes: Option<i32>,
+    pub notes: Option<String>,
+    pub tasks: Json<Vec<RouteStopTask>>,
+    pub break_location: Option<Json<BreakLocation>>,
+    /// Customer is flagged by risk scoring (the score itself stays internal)
+    #[sqlx(default)]
+    pub customer_risk_flag: bool,
 }
 
 /// Get all stops for a route with customer info
@@ -387,7 +457,11 @@ pub async fn get_route_stops_with_info(
             rs.break_time_start,
             rs.service_duration_minutes,
             rs.override_service_duration_minutes,
-            rs.override_travel_duration_minutes
+            rs.override_travel_duration_minutes,
+            rs.notes,
+            rs.tasks,
+            rs.break_location,
+            COALESCE(c.risk_flagged, FALSE) AS customer_risk_flag
         FROM route_stops rs
         LEFT JOIN customers c ON rs.customer_id = c.id
         LEFT JOIN revisions rev ON rs.revision_id = rev.id
@@ -0,0 +1,145 @@
+#![allow(dead_code)]
+//! Actual-vs-planned route analysis queries
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::route_analysis::{StopActualRow, TravelTimeFactor};
+
+/// Planned stop times are wall-clock times of the service area (CZ/SK)
+const LOCAL_TIMEZONE: &str = "Europe/Prague";
+
+/// Stops of the user's routes in a period with their recorded arrival and
+/// departure. Times recorded on the stop win over those of the visit.
+pub async fn list_stop_actuals(
+    pool: &PgPool,
+    user_id: Uuid,
+    date_from: NaiveDate,
+    date_to: NaiveDate,
+    crew_id: Option<Uuid>,
+) -> Result<Vec<StopActualRow>> {
+    let rows = sqlx::query_as::<_, StopActualRow>(
+        r#"
+        SELECT
+            r.id AS route_id,
+            r.date AS route_date,
+            rs.stop_order,
+            rs.stop_type,
+            rs.customer_id,
+            c.name AS customer_name,
+            rs.estimated_arrival,
+            rs.duration_from_previous_minutes,
+            rs.distance_from_previous_km,
+            c.lat,
+            c.lng,
+            COALESCE(rs.actual_arrival, v.actual_arrival) AT TIME ZONE $5 AS actual_arrival,
+            COALESCE(rs.actual_departure, v.actual_departure) AT TIME ZONE $5 AS actual_departure
+        FROM routes r
+        JOIN route_stops rs ON rs.route_id = r.id
+        LEFT JOIN customers c ON c.id = rs.customer_id
+        LEFT JOIN LATERAL (
+            SELECT v.actual_arrival, v.actual_departure
+            FROM visits v
+            WHERE v.user_id = r.user_id
+              AND (v.id = rs.visit_id
+                   OR (rs.visit_id IS NULL AND v.customer_id = rs.customer_id AND v.scheduled_date = r.date))
+            ORDER BY v.actual_arrival NULLS LAST
+            LIMIT 1
+        ) v ON TRUE
+        WHERE r.user_id = $1
+          AND r.date BETWEEN $2 AND $3
+          AND ($4::uuid IS NULL OR r.crew_id = $4)
+        ORDER BY r.date, r.id, rs.stop_order
+        "#,
+    )
+    .bind(user_id)
+    .bind(date_from)
+    .bind(date_to)
+    .bind(crew_id)
+    .bind(LOCAL_TIMEZONE)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Travel time correction applied to route planning, if enabled
+pub async fn get_travel_time_factor(pool: &PgPool, user_id: Uuid) -> Result<Option<f64>> {
+    let factor = sqlx::query_scalar::<_, Option<f64>>("SELECT travel_time_factor FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(factor.flatten())
+}
+
+/// Store or (with None) remove the travel time correction
+pub async fn set_travel_time_factor(pool: &PgPool, user_id: Uuid, factor: Option<f64>) -> Result<()> {
+    sqlx::query("UPDATE users SET travel_time_factor = $2, updated_at = NOW() WHERE id = $1")
+        .bind(user_id)
+        .bind(factor)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Learned zone and time-of-day factors of the user
+pub async fn list_travel_time_factors(pool: &PgPool, user_id: Uuid) -> Result<Vec<TravelTimeFactor>> {
+    let factors = sqlx::query_as::<_, TravelTimeFactor>(
+        r#"
+        SELECT zone_lat, zone_lng, time_band, factor, raw_ratio, legs, updated_at
+        FROM travel_time_factors
+        WHERE user_id = $1
+        ORDER BY zone_lat, zone_lng, time_band
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(factors)
+}
+
+/// Replace the user's learned factors
+pub async fn replace_travel_time_factors(pool: &PgPool, user_id: Uuid, factors: &[TravelTimeFactor]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM travel_time_factors WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for f in factors {
+        sqlx::query(
+            r#"
+            INSERT INTO travel_time_factors (user_id, zone_lat, zone_lng, time_band, factor, raw_ratio, legs, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(user_id)
+        .bind(f.zone_lat)
+        .bind(f.zone_lng)
+        .bind(&f.time_band)
+        .bind(f.factor)
+        .bind(f.raw_ratio)
+        .bind(f.legs)
+        .bind(f.updated_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Users with travel time correction enabled
+pub async fn list_users_with_travel_correction(pool: &PgPool) -> Result<Vec<Uuid>> {
+    let users = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE travel_time_factor IS NOT NULL")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(users)
+}
@@ -0,0 +1,101 @@
+#![allow(dead_code)]
+//! Route edit lock queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::route_lock::RouteLock;
+
+/// Take or renew the edit lock of a route. Succeeds when the route is free,
+/// the previous lock has expired or `holder_id` already holds it.
+pub async fn acquire_route_lock(
+    pool: &PgPool,
+    user_id: Uuid,
+    route_id: Uuid,
+    holder_id: Uuid,
+    ttl_seconds: u32,
+) -> Result<bool> {
+    let acquired = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO route_locks (route_id, user_id, holder_id, acquired_at, expires_at)
+        SELECT r.id, r.user_id, $3, NOW(), NOW() + make_interval(secs => $4)
+        FROM routes r
+        WHERE r.id = $1 AND r.user_id = $2
+        ON CONFLICT (route_id) DO UPDATE SET
+            holder_id = EXCLUDED.holder_id,
+            acquired_at = CASE
+                WHEN route_locks.holder_id = EXCLUDED.holder_id AND route_locks.expires_at > NOW()
+                THEN route_locks.acquired_at
+                ELSE EXCLUDED.acquired_at
+            END,
+            expires_at = EXCLUDED.expires_at
+        WHERE route_locks.holder_id = EXCLUDED.holder_id OR route_locks.expires_at <= NOW()
+        RETURNING route_id
+        "#,
+    )
+    .bind(route_id)
+    .bind(user_id)
+    .bind(holder_id)
+    .bind(ttl_seconds as f64)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(acquired.is_some())
+}
+
+/// The unexpired edit lock of a route
+pub async fn get_active_route_lock(pool: &PgPool, user_id: Uuid, route_id: Uuid) -> Result<Option<RouteLock>> {
+    let lock = sqlx::query_as::<_, RouteLock>(
+        r#"
+        SELECT l.route_id, l.holder_id, u.name AS holder_name, l.acquired_at, l.expires_at
+        FROM route_locks l
+        LEFT JOIN users u ON u.id = l.holder_id
+        WHERE l.route_id = $1 AND l.user_id = $2 AND l.expires_at > NOW()
+        "#,
+    )
+    .bind(route_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(lock)
+}
+
+/// Release a lock held by `holder_id`
+pub async fn release_route_lock(pool: &PgPool, user_id: Uuid, route_id: Uuid, holder_id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM route_locks WHERE route_id = $1 AND user_id = $2 AND holder_id = $3")
+        .bind(route_id)
+        .bind(user_id)
+        .bind(holder_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Route of a saved stop, for lock checks on stop-level edits
+pub async fn route_id_of_stop(pool: &PgPool, user_id: Uuid, stop_id: Uuid) -> Result<Option<Uuid>> {
+    let route_id = sqlx::query_scalar::<_, Uuid>(
+        "SELECT rs.route_id FROM route_stops rs JOIN routes r ON r.id = rs.route_id WHERE rs.id = $1 AND r.user_id = $2",
+    )
+    .bind(stop_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(route_id)
+}
+
+/// Route that a save for `date` would overwrite
+pub async fn route_id_for_date(pool: &PgPool, user_id: Uuid, date: chrono::NaiveDate) -> Result<Option<Uuid>> {
+    let route_id = sqlx::query_scalar::<_, Uuid>(
+        "SELECT id FROM routes WHERE user_id = $1 AND date = $2 ORDER BY updated_at DESC LIMIT 1",
+    )
+    .bind(user_id)
+    .bind(date)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(route_id)
+}
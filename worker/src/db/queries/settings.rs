@@ -10,7 +10,7 @@ use crate::types::settings::{
     Depot, CreateDepotRequest, UpdateDepotRequest,
     UserWithSettings, UpdateWorkConstraintsRequest,
     UpdateBusinessInfoRequest, UpdateEmailTemplatesRequest,
-    UpdatePreferencesRequest, UpdateBreakSettingsRequest,
+    UpdatePreferencesRequest, UpdateBreakSettingsRequest, UpdateRevisionNumberingRequest,
 };
 
 // ============================================================================
@@ -39,10 +39,13 @@ pub async fn get_user_settings(pool: &PgPool, user_id: Uuid) -> Result<Option<Us
             default_crew_id, default_depot_id,
             break_enabled, break_duration_minutes,
             break_earliest_time, break_latest_time,
-            break_min_km, break_max_km,
+            break_min_km, break_max_km, break_location_suggestions,
+            revision_numbering_enabled, revision_number_prefix,
+            revision_number_padding, revision_number_yearly_reset,
+            customer_code_pattern,
             locale,
             last_arrival_buffer_percent, last_arrival_buffer_fixed_minutes,
-            company_locale,
+            company_locale, currency,
             email_confirmation_edited_at, email_reminder_edited_at, email_third_edited_at,
             created_at, updated_at
         FROM users
@@ -115,7 +118,8 @@ pub async fn update_business_info(
             city = COALESCE($9, city),
             postal_code = COALESCE($10, postal_code),
             country = COALESCE($11, country),
-            company_locale = COALESCE($12, company_locale)
+            company_locale = COALESCE($12, company_locale),
+            currency = COALESCE($13, currency)
         WHERE id = $1
         "#
     )
@@ -131,6 +135,7 @@ pub async fn update_business_info(
     .bind(&req.postal_code)
     .bind(&req.country)
     .bind(&req.company_locale)
+    .bind(&req.currency)
     .execute(pool)
     .await?;
 
@@ -268,7 +273,8 @@ pub async fn update_break_settings(
             break_earliest_time = COALESCE($4, break_earliest_time),
             break_latest_time = COALESCE($5, break_latest_time),
             break_min_km = COALESCE($6, break_min_km),
-            break_max_km = COALESCE($7, break_max_km)
+            break_max_km = COALESCE($7, break_max_km),
+            break_location_suggestions = COALESCE($8, break_location_suggestions)
         WHERE id = $1
         "#
     )
@@ -279,12 +285,51 @@ pub async fn update_break_settings(
     .bind(latest_time)
     .bind(req.break_min_km)
     .bind(req.break_max_km)
+    .bind(req.break_location_suggestions)
     .execute(pool)
     .await?;
 
     Ok(())
 }
 
+/// Update revision numbering settings
+pub async fn update_revision_numbering(
+    pool: &PgPool,
+    user_id: Uuid,
+    req: &UpdateRevisionNumberingRequest,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE users SET
+            revision_numbering_enabled = COALESCE($2, revision_numbering_enabled),
+            revision_number_prefix = COALESCE($3, revision_number_prefix),
+            revision_number_padding = COALESCE($4, revision_number_padding),
+            revision_number_yearly_reset = COALESCE($5, revision_number_yearly_reset)
+        WHERE id = $1
+        "#
+    )
+    .bind(user_id)
+    .bind(req.enabled)
+    .bind(req.prefix.as_deref().map(str::trim))
+    .bind(req.padding)
+    .bind(req.yearly_reset)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Update the customer code pattern (empty turns generation off)
+pub async fn update_customer_code_pattern(pool: &PgPool, user_id: Uuid, pattern: &str) -> Result<()> {
+    sqlx::query("UPDATE users SET customer_code_pattern = $2 WHERE id = $1")
+        .bind(user_id)
+        .bind(pattern)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
 // ============================================================================
 // Depot Queries
 // ============================================================================
@@ -461,6 +506,12 @@ pub async fn delete_company_data(pool: &PgPool, user_id: Uuid) -> Result<()> {
         .execute(pool)
         .await?;
 
+    // Delete travel time corrections learned from the routes
+    sqlx::query("DELETE FROM travel_time_factors WHERE user_id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
     // Delete customers (CASCADE removes devices, revisions, visits,
     // communications, route_stops, visit_work_items)
     sqlx::query("DELETE FROM customers WHERE user_id = $1")
@@ -496,7 +547,8 @@ pub async fn delete_company_data(pool: &PgPool, user_id: Uuid) -> Result<()> {
             email_third_body_template = NULL,
             email_confirmation_edited_at = NULL,
             email_reminder_edited_at = NULL,
-            email_third_edited_at = NULL
+            email_third_edited_at = NULL,
+            travel_time_factor = NULL
         WHERE id = $1
         "#
     )
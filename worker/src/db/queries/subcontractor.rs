@@ -0,0 +1,367 @@
+//! Subcontractor database queries
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::types::subcontractor::{
+    AssignedRoute, AssignedStop, AssignedVisit, CreateSubcontractorRequest, SettlementVisit, Subcontractor,
+    SubcontractorRate, UpdateSubcontractorRequest, SUBCONTRACTOR_ROLE,
+};
+
+const SUBCONTRACTOR_COLUMNS: &str = r#"
+    s.id, s.name, s.company_id, s.email, s.phone, s.is_active, s.account_user_id,
+    ARRAY(SELECT c.id FROM crews c WHERE c.subcontractor_id = s.id ORDER BY c.name) AS crew_ids,
+    s.created_at, s.updated_at
+"#;
+
+/// Hand exactly `crew_ids` (crews of the account) over to a subcontractor
+async fn set_crews(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    subcontractor_id: Uuid,
+    crew_ids: &[Uuid],
+) -> Result<()> {
+    sqlx::query("UPDATE crews SET subcontractor_id = NULL WHERE user_id = $1 AND subcontractor_id = $2")
+        .bind(user_id)
+        .bind(subcontractor_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query("UPDATE crews SET subcontractor_id = $2 WHERE user_id = $1 AND id = ANY($3)")
+        .bind(user_id)
+        .bind(subcontractor_id)
+        .bind(crew_ids)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Create a subcontractor
+pub async fn create_subcontractor(
+    pool: &PgPool,
+    user_id: Uuid,
+    request: &CreateSubcontractorRequest,
+) -> Result<Subcontractor> {
+    let mut tx = pool.begin().await?;
+
+    let id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO subcontractors (user_id, name, company_id, email, phone)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(request.name.trim())
+    .bind(&request.company_id)
+    .bind(&request.email)
+    .bind(&request.phone)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    set_crews(&mut tx, user_id, id, &request.crew_ids).await?;
+    tx.commit().await?;
+
+    get_subcontractor(pool, user_id, id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("subcontractor {} vanished after insert", id))
+}
+
+/// List subcontractors of an account
+pub async fn list_subcontractors(pool: &PgPool, user_id: Uuid) -> Result<Vec<Subcontractor>> {
+    let query = format!(
+        "SELECT {} FROM subcontractors s WHERE s.user_id = $1 ORDER BY s.name",
+        SUBCONTRACTOR_COLUMNS
+    );
+    let subcontractors = sqlx::query_as::<_, Subcontractor>(&query)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(subcontractors)
+}
+
+/// Get a subcontractor of an account
+pub async fn get_subcontractor(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<Option<Subcontractor>> {
+    let query = format!(
+        "SELECT {} FROM subcontractors s WHERE s.id = $1 AND s.user_id = $2",
+        SUBCONTRACTOR_COLUMNS
+    );
+    let subcontractor = sqlx::query_as::<_, Subcontractor>(&query)
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(subcontractor)
+}
+
+/// Active subcontractor a login belongs to
+pub async fn get_by_account(pool: &PgPool, user_id: Uuid, account_user_id: Uuid) -> Result<Option<Subcontractor>> {
+    let query = format!(
+        "SELECT {} FROM subcontractors s WHERE s.user_id = $1 AND s.account_user_id = $2 AND s.is_active",
+        SUBCONTRACTOR_COLUMNS
+    );
+    let subcontractor = sqlx::query_as::<_, Subcontractor>(&query)
+        .bind(user_id)
+        .bind(account_user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(subcontractor)
+}
+
+/// Update a subcontractor; None when it is not found
+pub async fn update_subcontractor(
+    pool: &PgPool,
+    user_id: Uuid,
+    request: &UpdateSubcontractorRequest,
+) -> Result<Option<Subcontractor>> {
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE subcontractors SET
+            name = COALESCE($3, name),
+            company_id = COALESCE($4, company_id),
+            email = COALESCE($5, email),
+            phone = COALESCE($6, phone),
+            is_active = COALESCE($7, is_active)
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(request.id)
+    .bind(user_id)
+    .bind(request.name.as_deref().map(str::trim))
+    .bind(&request.company_id)
+    .bind(&request.email)
+    .bind(&request.phone)
+    .bind(request.is_active)
+    .execute(&mut *tx)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Ok(None);
+    }
+    if let Some(crew_ids) = &request.crew_ids {
+        set_crews(&mut tx, user_id, request.id, crew_ids).await?;
+    }
+    tx.commit().await?;
+
+    get_subcontractor(pool, user_id, request.id).await
+}
+
+/// Delete a subcontractor together with its login; its crews stay with the account
+pub async fn delete_subcontractor(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM users
+        WHERE owner_id = $2 AND role = $3
+          AND id = (SELECT account_user_id FROM subcontractors WHERE id = $1 AND user_id = $2)
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(SUBCONTRACTOR_ROLE)
+    .execute(&mut *tx)
+    .await?;
+
+    let result = sqlx::query("DELETE FROM subcontractors WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Link a login to a subcontractor that has none yet
+pub async fn set_account(pool: &PgPool, user_id: Uuid, id: Uuid, account_user_id: Uuid) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE subcontractors SET account_user_id = $3 WHERE id = $1 AND user_id = $2 AND account_user_id IS NULL",
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(account_user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete a subcontractor login of an account
+pub async fn delete_account(pool: &PgPool, user_id: Uuid, account_user_id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM users WHERE id = $1 AND owner_id = $2 AND role = $3")
+        .bind(account_user_id)
+        .bind(user_id)
+        .bind(SUBCONTRACTOR_ROLE)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Rate card of a subcontractor
+pub async fn get_rates(pool: &PgPool, subcontractor_id: Uuid) -> Result<Vec<SubcontractorRate>> {
+    let rates = sqlx::query_as::<_, SubcontractorRate>(
+        r#"
+        SELECT visit_type, amount_minor, currency::text AS currency
+        FROM subcontractor_rates
+        WHERE subcontractor_id = $1
+        ORDER BY visit_type
+        "#,
+    )
+    .bind(subcontractor_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rates)
+}
+
+/// Replace the rate card of a subcontractor
+pub async fn set_rates(pool: &PgPool, subcontractor_id: Uuid, rates: &[SubcontractorRate]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM subcontractor_rates WHERE subcontractor_id = $1")
+        .bind(subcontractor_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for rate in rates {
+        sqlx::query(
+            r#"
+            INSERT INTO subcontractor_rates (subcontractor_id, visit_type, amount_minor, currency)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(subcontractor_id)
+        .bind(&rate.visit_type)
+        .bind(rate.amount_minor)
+        .bind(&rate.currency)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Routes of the subcontractor's crews in a period, with their stops
+pub async fn assigned_routes(
+    pool: &PgPool,
+    user_id: Uuid,
+    subcontractor_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<AssignedRoute>> {
+    let mut routes = sqlx::query_as::<_, AssignedRoute>(
+        r#"
+        SELECT r.id, r.date, c.id AS crew_id, c.name AS crew_name, r.status::text AS status,
+               r.total_distance_km, r.total_duration_minutes
+        FROM routes r
+        JOIN crews c ON c.id = r.crew_id
+        WHERE r.user_id = $1 AND c.subcontractor_id = $2
+          AND r.date BETWEEN $3 AND $4
+        ORDER BY r.date, c.name
+        "#,
+    )
+    .bind(user_id)
+    .bind(subcontractor_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    let route_ids: Vec<Uuid> = routes.iter().map(|r| r.id).collect();
+    let stops = sqlx::query_as::<_, AssignedStop>(
+        r#"
+        SELECT rs.route_id, rs.stop_order, rs.visit_id,
+               cu.name AS customer_name, cu.contact_person, cu.phone,
+               cu.street, cu.city, cu.postal_code, cu.lat, cu.lng,
+               rs.estimated_arrival, rs.estimated_departure, rs.status
+        FROM route_stops rs
+        JOIN customers cu ON cu.id = rs.customer_id
+        WHERE rs.route_id = ANY($1)
+        ORDER BY rs.route_id, rs.stop_order
+        "#,
+    )
+    .bind(&route_ids)
+    .fetch_all(pool)
+    .await?;
+
+    for stop in stops {
+        if let Some(route) = routes.iter_mut().find(|r| r.id == stop.route_id) {
+            route.stops.push(stop);
+        }
+    }
+
+    Ok(routes)
+}
+
+/// Visits of the subcontractor's crews in a period
+pub async fn assigned_visits(
+    pool: &PgPool,
+    user_id: Uuid,
+    subcontractor_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<AssignedVisit>> {
+    let visits = sqlx::query_as::<_, AssignedVisit>(
+        r#"
+        SELECT v.id, c.id AS crew_id, c.name AS crew_name,
+               v.scheduled_date, v.scheduled_time_start, v.scheduled_time_end,
+               v.status::text AS status, v.visit_type, v.result,
+               cu.name AS customer_name, cu.contact_person, cu.phone,
+               cu.street, cu.city, cu.postal_code, cu.lat, cu.lng
+        FROM visits v
+        JOIN crews c ON c.id = v.crew_id
+        JOIN customers cu ON cu.id = v.customer_id
+        WHERE v.user_id = $1 AND c.subcontractor_id = $2
+          AND v.scheduled_date BETWEEN $3 AND $4
+        ORDER BY v.scheduled_date, v.scheduled_time_start NULLS LAST, c.name
+        "#,
+    )
+    .bind(user_id)
+    .bind(subcontractor_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(visits)
+}
+
+/// Completed visits of the subcontractor's crews in a period
+pub async fn settlement_visits(
+    pool: &PgPool,
+    user_id: Uuid,
+    subcontractor_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<SettlementVisit>> {
+    let visits = sqlx::query_as::<_, SettlementVisit>(
+        r#"
+        SELECT v.id AS visit_id, v.scheduled_date, v.visit_type, c.name AS crew_name,
+               cu.name AS customer_name, cu.city, v.result
+        FROM visits v
+        JOIN crews c ON c.id = v.crew_id
+        JOIN customers cu ON cu.id = v.customer_id
+        WHERE v.user_id = $1 AND c.subcontractor_id = $2
+          AND v.scheduled_date BETWEEN $3 AND $4
+          AND v.status = 'completed'
+        ORDER BY v.scheduled_date, c.name
+        "#,
+    )
+    .bind(user_id)
+    .bind(subcontractor_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(visits)
+}
@@ -0,0 +1,76 @@
+#![allow(dead_code)]
+//! Account subscription state database queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::subscription::SubscriptionInfo;
+
+const SUBSCRIPTION_COLUMNS: &str = r#"
+    id AS user_id, subscription_status AS status,
+    subscription_changed_at AS changed_at, billing_customer_ref
+"#;
+
+/// Subscription state of an account
+pub async fn get(pool: &PgPool, user_id: Uuid) -> Result<Option<SubscriptionInfo>> {
+    let query = format!("SELECT {} FROM users WHERE id = $1", SUBSCRIPTION_COLUMNS);
+    let row = sqlx::query_as::<_, SubscriptionInfo>(&query)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row)
+}
+
+/// Account linked to a billing customer reference
+pub async fn find_by_billing_ref(pool: &PgPool, billing_customer_ref: &str) -> Result<Option<Uuid>> {
+    let row: Option<(Uuid,)> = sqlx::query_as("SELECT id FROM users WHERE billing_customer_ref = $1")
+        .bind(billing_customer_ref)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|(id,)| id))
+}
+
+/// Status only, for the per-request read-only check
+pub async fn get_status(pool: &PgPool, user_id: Uuid) -> Result<Option<String>> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT subscription_status FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|(status,)| status))
+}
+
+/// Set the status (and optionally the billing reference), returning the
+/// previous status. The change timestamp only moves on real transitions.
+pub async fn set_status(
+    pool: &PgPool,
+    user_id: Uuid,
+    status: &str,
+    billing_customer_ref: Option<&str>,
+) -> Result<Option<String>> {
+    let previous: Option<(String,)> = sqlx::query_as(
+        r#"
+        UPDATE users u
+        SET subscription_status = $2,
+            subscription_changed_at = CASE
+                WHEN old.subscription_status = $2 THEN u.subscription_changed_at
+                ELSE NOW()
+            END,
+            billing_customer_ref = COALESCE($3, u.billing_customer_ref),
+            updated_at = NOW()
+        FROM (SELECT id, subscription_status FROM users WHERE id = $1 FOR UPDATE) old
+        WHERE u.id = old.id
+        RETURNING old.subscription_status
+        "#,
+    )
+    .bind(user_id)
+    .bind(status)
+    .bind(billing_customer_ref)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(previous.map(|(s,)| s))
+}
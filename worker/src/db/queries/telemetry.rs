@@ -0,0 +1,36 @@
+#![allow(dead_code)]
+//! Telemetry opt-in queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Whether an account opted in to telemetry
+pub async fn get_opt_in(pool: &PgPool, user_id: Uuid) -> Result<bool> {
+    let row: Option<(bool,)> = sqlx::query_as("SELECT telemetry_opt_in FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.is_some_and(|(opt_in,)| opt_in))
+}
+
+/// Opt an account in or out of telemetry
+pub async fn set_opt_in(pool: &PgPool, user_id: Uuid, opt_in: bool) -> Result<()> {
+    sqlx::query("UPDATE users SET telemetry_opt_in = $2, updated_at = NOW() WHERE id = $1")
+        .bind(user_id)
+        .bind(opt_in)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Accounts that opted in to telemetry
+pub async fn list_opted_in_users(pool: &PgPool) -> Result<Vec<Uuid>> {
+    let rows: Vec<(Uuid,)> = sqlx::query_as("SELECT id FROM users WHERE telemetry_opt_in")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter().map(|(id,)| id).collect())
+}
@@ -0,0 +1,97 @@
+#![allow(dead_code)]
+//! Customer template language version queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::template_translation::TemplateTranslation;
+
+/// List all language versions of an account's templates
+pub async fn list_translations(pool: &PgPool, user_id: Uuid) -> Result<Vec<TemplateTranslation>> {
+    let translations = sqlx::query_as::<_, TemplateTranslation>(
+        r#"
+        SELECT id, template_kind, language, subject, body, created_at, updated_at
+        FROM customer_template_translations
+        WHERE user_id = $1
+        ORDER BY template_kind, language
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(translations)
+}
+
+/// Create or replace the version of a template in one language
+pub async fn upsert_translation(
+    pool: &PgPool,
+    user_id: Uuid,
+    template_kind: &str,
+    language: &str,
+    subject: Option<&str>,
+    body: &str,
+) -> Result<TemplateTranslation> {
+    let translation = sqlx::query_as::<_, TemplateTranslation>(
+        r#"
+        INSERT INTO customer_template_translations (user_id, template_kind, language, subject, body)
+        VALUES ($1, $2, $3, NULLIF(TRIM($4), ''), $5)
+        ON CONFLICT (user_id, template_kind, language) DO UPDATE
+        SET subject = EXCLUDED.subject, body = EXCLUDED.body, updated_at = NOW()
+        RETURNING id, template_kind, language, subject, body, created_at, updated_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(template_kind)
+    .bind(language)
+    .bind(subject)
+    .bind(body)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(translation)
+}
+
+/// Delete the version of a template in one language
+pub async fn delete_translation(pool: &PgPool, user_id: Uuid, template_kind: &str, language: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "DELETE FROM customer_template_translations WHERE user_id = $1 AND template_kind = $2 AND language = $3",
+    )
+    .bind(user_id)
+    .bind(template_kind)
+    .bind(language)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Body of a template for a customer: the version in the customer's language,
+/// falling back to the version in the account locale.
+pub async fn resolve_body_for_customer(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
+    template_kind: &str,
+) -> Result<Option<String>> {
+    let body: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT t.body
+        FROM customer_template_translations t
+        JOIN users u ON u.id = t.user_id
+        LEFT JOIN customers c ON c.id = $2 AND c.user_id = t.user_id
+        WHERE t.user_id = $1 AND t.template_kind = $3
+          AND (t.language = c.language OR t.language = u.company_locale)
+        ORDER BY (t.language = c.language) IS TRUE DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(customer_id)
+    .bind(template_kind)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(body.map(|(body,)| body))
+}
@@ -18,6 +18,7 @@ const USER_COLUMNS: &str = r#"
     email_verified, verification_token_hash, verification_expires, tos_accepted_at,
     onboarding_completed_at, onboarding_step,
     locale,
+    disabled_at, last_login_at,
     created_at, updated_at
 "#;
 
@@ -35,6 +36,16 @@ pub async fn get_user(pool: &PgPool, user_id: Uuid) -> Result<Option<User>> {
     Ok(user)
 }
 
+/// Country of the account (ISO code), used to scope geocoding
+pub async fn get_country(pool: &PgPool, user_id: Uuid) -> Result<Option<String>> {
+    let country: Option<Option<String>> = sqlx::query_scalar("SELECT TRIM(country) FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(country.flatten())
+}
+
 /// Get user by email (for login)
 pub async fn get_user_by_email(pool: &PgPool, email: &str) -> Result<Option<User>> {
     let query = format!(
@@ -109,3 +120,26 @@ pub async fn delete_worker(pool: &PgPool, worker_id: Uuid, owner_id: Uuid) -> Re
 
     Ok(result.rows_affected() > 0)
 }
+
+/// Record a successful login
+pub async fn touch_last_login(pool: &PgPool, user_id: Uuid) -> Result<()> {
+    sqlx::query("UPDATE users SET last_login_at = NOW() WHERE id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Whether the account still has to confirm its email before anything is sent
+/// on its behalf (see `AuthInfo::requires_email_verification`)
+pub async fn requires_email_verification(pool: &PgPool, user_id: Uuid) -> Result<bool> {
+    let unverified: Option<bool> = sqlx::query_scalar(
+        "SELECT role = 'customer' AND NOT email_verified FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(unverified.unwrap_or(false))
+}
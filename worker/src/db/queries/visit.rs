@@ -198,6 +198,8 @@ pub async fn update_visit(
 /// Complete a visit with result, optionally updating field notes atomically.
 /// When `field_notes` is Some and differs from current value, the note and
 /// audit row are written in the same transaction before commit.
+/// Arrival and departure already stamped by progress events are kept when
+/// none are given.
 pub async fn complete_visit(
     pool: &PgPool,
     id: Uuid,
@@ -228,8 +230,8 @@ pub async fn complete_visit(
             status = 'completed',
             result = $3,
             field_notes = COALESCE($4, field_notes),
-            actual_arrival = $5,
-            actual_departure = $6,
+            actual_arrival = COALESCE($5, actual_arrival),
+            actual_departure = COALESCE($6, actual_departure),
             requires_follow_up = $7,
             follow_up_reason = $8,
             updated_at = NOW()
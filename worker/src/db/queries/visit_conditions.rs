@@ -0,0 +1,134 @@
+//! Visit conditions database queries
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::report::WeatherOutcomeCounts;
+use crate::types::weather::DailyWeather;
+
+/// Planned stop times are wall-clock times of the service area (CZ/SK)
+const LOCAL_TIMEZONE: &str = "Europe/Prague";
+
+/// Day and customer location of a visit
+pub async fn get_visit_location(
+    pool: &PgPool,
+    visit_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<(NaiveDate, Option<f64>, Option<f64>)>> {
+    let location = sqlx::query_as::<_, (NaiveDate, Option<f64>, Option<f64>)>(
+        r#"
+        SELECT v.scheduled_date, c.lat, c.lng
+        FROM visits v
+        JOIN customers c ON c.id = v.customer_id
+        WHERE v.id = $1 AND v.user_id = $2
+        "#,
+    )
+    .bind(visit_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(location)
+}
+
+/// Record the conditions of a visit. The arrival delay is read from the
+/// visit's route stop; weather already recorded is kept when none is given.
+pub async fn upsert_conditions(
+    pool: &PgPool,
+    visit_id: Uuid,
+    user_id: Uuid,
+    weather: Option<&DailyWeather>,
+) -> Result<()> {
+    let weather = weather.cloned().unwrap_or_default();
+    sqlx::query(
+        r#"
+        INSERT INTO visit_conditions (
+            visit_id, user_id, condition, weather_code, temperature_min_c, temperature_max_c,
+            precipitation_mm, snowfall_cm, wind_max_kmh, arrival_delay_minutes
+        )
+        SELECT
+            v.id, v.user_id, $3, $4, $5, $6, $7, $8, $9,
+            (
+                SELECT ROUND(EXTRACT(EPOCH FROM (
+                    (COALESCE(rs.actual_arrival, v.actual_arrival) AT TIME ZONE $10)::time - rs.estimated_arrival
+                )) / 60)::int
+                FROM route_stops rs
+                WHERE rs.visit_id = v.id
+                  AND rs.estimated_arrival IS NOT NULL
+                  AND COALESCE(rs.actual_arrival, v.actual_arrival) IS NOT NULL
+                LIMIT 1
+            )
+        FROM visits v
+        WHERE v.id = $1 AND v.user_id = $2
+        ON CONFLICT (visit_id) DO UPDATE SET
+            condition = COALESCE(EXCLUDED.condition, visit_conditions.condition),
+            weather_code = COALESCE(EXCLUDED.weather_code, visit_conditions.weather_code),
+            temperature_min_c = COALESCE(EXCLUDED.temperature_min_c, visit_conditions.temperature_min_c),
+            temperature_max_c = COALESCE(EXCLUDED.temperature_max_c, visit_conditions.temperature_max_c),
+            precipitation_mm = COALESCE(EXCLUDED.precipitation_mm, visit_conditions.precipitation_mm),
+            snowfall_cm = COALESCE(EXCLUDED.snowfall_cm, visit_conditions.snowfall_cm),
+            wind_max_kmh = COALESCE(EXCLUDED.wind_max_kmh, visit_conditions.wind_max_kmh),
+            arrival_delay_minutes = COALESCE(EXCLUDED.arrival_delay_minutes, visit_conditions.arrival_delay_minutes),
+            recorded_at = NOW()
+        "#,
+    )
+    .bind(visit_id)
+    .bind(user_id)
+    .bind(weather.condition().map(|c| c.as_str()))
+    .bind(weather.weather_code)
+    .bind(weather.temperature_min_c)
+    .bind(weather.temperature_max_c)
+    .bind(weather.precipitation_mm)
+    .bind(weather.snowfall_cm)
+    .bind(weather.wind_max_kmh)
+    .bind(LOCAL_TIMEZONE)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Outcomes of the completed and cancelled visits of a period, per weather
+/// condition or per band of the day's lowest temperature
+pub async fn outcome_counts(
+    pool: &PgPool,
+    user_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+    by_temperature: bool,
+) -> Result<Vec<WeatherOutcomeCounts>> {
+    let counts = sqlx::query_as::<_, WeatherOutcomeCounts>(
+        r#"
+        SELECT
+            CASE
+                WHEN NOT $4 THEN COALESCE(vc.condition, '')
+                WHEN vc.temperature_min_c IS NULL THEN ''
+                WHEN vc.temperature_min_c < -5 THEN 'below_minus_5'
+                WHEN vc.temperature_min_c < 0 THEN 'minus_5_to_0'
+                WHEN vc.temperature_min_c < 5 THEN '0_to_5'
+                ELSE 'above_5'
+            END AS bucket,
+            COUNT(*) AS visits,
+            COUNT(*) FILTER (WHERE v.status = 'completed') AS completed,
+            COUNT(*) FILTER (WHERE v.status = 'completed' AND v.result IN ('failed', 'customer_absent')) AS failed,
+            COUNT(*) FILTER (WHERE v.status = 'cancelled') AS cancelled,
+            AVG(vc.arrival_delay_minutes)::float8 AS avg_arrival_delay_minutes
+        FROM visits v
+        LEFT JOIN visit_conditions vc ON vc.visit_id = v.id
+        WHERE v.user_id = $1
+          AND v.scheduled_date BETWEEN $2 AND $3
+          AND v.status IN ('completed', 'cancelled')
+        GROUP BY 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .bind(by_temperature)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(counts)
+}
@@ -0,0 +1,235 @@
+//! Visit duration database queries
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::report::WorkloadRow;
+use crate::types::visit::Visit;
+use crate::types::visit_duration::{VisitDuration, VisitTimestamps, PROGRESS_ARRIVED};
+
+/// Stamp a progress event on a visit. Arriving starts a planned visit;
+/// cancelled visits are not changed.
+pub async fn record_progress(
+    pool: &PgPool,
+    visit_id: Uuid,
+    user_id: Uuid,
+    event: &str,
+    at: DateTime<Utc>,
+) -> Result<Option<Visit>> {
+    let visit = sqlx::query_as::<_, Visit>(
+        r#"
+        UPDATE visits SET
+            actual_arrival = CASE WHEN $3 THEN $4 ELSE actual_arrival END,
+            actual_departure = CASE WHEN $3 THEN actual_departure ELSE $4 END,
+            status = CASE WHEN $3 AND status = 'planned' THEN 'in_progress' ELSE status END,
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2 AND status <> 'cancelled'
+        RETURNING
+            id, user_id, customer_id, crew_id, device_id,
+            scheduled_date, scheduled_time_start, scheduled_time_end,
+            status::text, visit_type,
+            actual_arrival, actual_departure,
+            result, field_notes,
+            requires_follow_up, follow_up_reason,
+            created_at, updated_at
+        "#,
+    )
+    .bind(visit_id)
+    .bind(user_id)
+    .bind(event == PROGRESS_ARRIVED)
+    .bind(at)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(visit)
+}
+
+/// Progress timestamps of a visit and the previous departure of its crew
+/// (or of the account's crew-less visits) the same day
+pub async fn get_timestamps(pool: &PgPool, visit_id: Uuid, user_id: Uuid) -> Result<Option<VisitTimestamps>> {
+    let timestamps = sqlx::query_as::<_, VisitTimestamps>(
+        r#"
+        SELECT
+            v.actual_arrival,
+            v.actual_departure,
+            (
+                SELECT MAX(p.actual_departure)
+                FROM visits p
+                WHERE p.user_id = v.user_id
+                  AND p.scheduled_date = v.scheduled_date
+                  AND p.crew_id IS NOT DISTINCT FROM v.crew_id
+                  AND p.id <> v.id
+                  AND p.actual_departure <= v.actual_arrival
+            ) AS previous_departure
+        FROM visits v
+        WHERE v.id = $1 AND v.user_id = $2
+        "#,
+    )
+    .bind(visit_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(timestamps)
+}
+
+/// Store durations derived from progress events; overridden values stay
+pub async fn upsert_recorded(
+    pool: &PgPool,
+    visit_id: Uuid,
+    user_id: Uuid,
+    travel_minutes: Option<i32>,
+    on_site_minutes: Option<i32>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO visit_durations (visit_id, user_id, travel_minutes, on_site_minutes)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (visit_id) DO UPDATE SET
+            travel_minutes = CASE WHEN visit_durations.travel_overridden
+                THEN visit_durations.travel_minutes ELSE EXCLUDED.travel_minutes END,
+            on_site_minutes = CASE WHEN visit_durations.on_site_overridden
+                THEN visit_durations.on_site_minutes ELSE EXCLUDED.on_site_minutes END,
+            recorded_at = NOW()
+        "#,
+    )
+    .bind(visit_id)
+    .bind(user_id)
+    .bind(travel_minutes)
+    .bind(on_site_minutes)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Durations of a visit
+pub async fn get_duration(pool: &PgPool, visit_id: Uuid, user_id: Uuid) -> Result<Option<VisitDuration>> {
+    let duration = sqlx::query_as::<_, VisitDuration>(
+        r#"
+        SELECT visit_id, travel_minutes, on_site_minutes, travel_overridden, on_site_overridden, recorded_at
+        FROM visit_durations
+        WHERE visit_id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(visit_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(duration)
+}
+
+/// Override the given durations of a visit by hand. None when the visit
+/// does not exist.
+pub async fn set_override(
+    pool: &PgPool,
+    visit_id: Uuid,
+    user_id: Uuid,
+    travel_minutes: Option<i32>,
+    on_site_minutes: Option<i32>,
+) -> Result<Option<VisitDuration>> {
+    let duration = sqlx::query_as::<_, VisitDuration>(
+        r#"
+        INSERT INTO visit_durations (
+            visit_id, user_id, travel_minutes, on_site_minutes, travel_overridden, on_site_overridden
+        )
+        SELECT id, user_id, $3, $4, $3 IS NOT NULL, $4 IS NOT NULL
+        FROM visits
+        WHERE id = $1 AND user_id = $2
+        ON CONFLICT (visit_id) DO UPDATE SET
+            travel_minutes = COALESCE($3, visit_durations.travel_minutes),
+            on_site_minutes = COALESCE($4, visit_durations.on_site_minutes),
+            travel_overridden = visit_durations.travel_overridden OR $3 IS NOT NULL,
+            on_site_overridden = visit_durations.on_site_overridden OR $4 IS NOT NULL,
+            recorded_at = NOW()
+        RETURNING visit_id, travel_minutes, on_site_minutes, travel_overridden, on_site_overridden, recorded_at
+        "#,
+    )
+    .bind(visit_id)
+    .bind(user_id)
+    .bind(travel_minutes)
+    .bind(on_site_minutes)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(duration)
+}
+
+/// Drop the overrides of a visit so the next recording replaces them
+pub async fn clear_overrides(pool: &PgPool, visit_id: Uuid, user_id: Uuid) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE visit_durations SET travel_overridden = FALSE, on_site_overridden = FALSE
+        WHERE visit_id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(visit_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// On-site minutes of completed visits since a day, with their visit type
+pub async fn on_site_samples(
+    pool: &PgPool,
+    user_id: Uuid,
+    since: NaiveDate,
+    visit_type: Option<&str>,
+) -> Result<Vec<(String, i32)>> {
+    let samples = sqlx::query_as::<_, (String, i32)>(
+        r#"
+        SELECT COALESCE(v.visit_type, ''), vd.on_site_minutes
+        FROM visit_durations vd
+        JOIN visits v ON v.id = vd.visit_id
+        WHERE vd.user_id = $1
+          AND v.status = 'completed'
+          AND v.scheduled_date >= $2
+          AND vd.on_site_minutes IS NOT NULL
+          AND ($3::text IS NULL OR v.visit_type = $3)
+        "#,
+    )
+    .bind(user_id)
+    .bind(since)
+    .bind(visit_type)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(samples)
+}
+
+/// Completed visits and recorded minutes per crew of a period
+pub async fn workload_rows(pool: &PgPool, user_id: Uuid, from: NaiveDate, to: NaiveDate) -> Result<Vec<WorkloadRow>> {
+    let rows = sqlx::query_as::<_, WorkloadRow>(
+        r#"
+        SELECT
+            v.crew_id,
+            cr.name AS crew_name,
+            COUNT(*) AS visits,
+            COUNT(vd.on_site_minutes) AS timed_visits,
+            COALESCE(SUM(vd.travel_minutes), 0)::bigint AS travel_minutes,
+            COALESCE(SUM(vd.on_site_minutes), 0)::bigint AS on_site_minutes,
+            (COALESCE(SUM(vd.travel_minutes), 0) + COALESCE(SUM(vd.on_site_minutes), 0))::bigint AS worked_minutes,
+            COUNT(*) FILTER (WHERE vd.travel_overridden OR vd.on_site_overridden) AS overridden_visits
+        FROM visits v
+        LEFT JOIN visit_durations vd ON vd.visit_id = v.id
+        LEFT JOIN crews cr ON cr.id = v.crew_id
+        WHERE v.user_id = $1
+          AND v.scheduled_date BETWEEN $2 AND $3
+          AND v.status = 'completed'
+        GROUP BY v.crew_id, cr.name
+        ORDER BY cr.name NULLS LAST
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
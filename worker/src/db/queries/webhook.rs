@@ -0,0 +1,211 @@
+#![allow(dead_code)]
+//! Webhook endpoint and delivery queries
+
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::webhook::{
+    PendingWebhookDelivery, Webhook, WEBHOOK_DELIVERY_DELIVERED, WEBHOOK_DELIVERY_FAILED,
+};
+
+const WEBHOOK_COLUMNS: &str = r#"
+    w.id, w.url, w.secret, w.event_types, w.format, w.description, w.is_active, w.created_at,
+    d.status AS last_delivery_status, d.created_at AS last_delivery_at
+"#;
+
+const LAST_DELIVERY_JOIN: &str = r#"
+    LEFT JOIN LATERAL (
+        SELECT status, created_at FROM webhook_deliveries
+        WHERE webhook_id = w.id
+        ORDER BY created_at DESC
+        LIMIT 1
+    ) d ON TRUE
+"#;
+
+/// Register an endpoint
+pub async fn create_webhook(
+    pool: &PgPool,
+    user_id: Uuid,
+    url: &str,
+    secret: &str,
+    event_types: &[String],
+    format: &str,
+    description: Option<&str>,
+) -> Result<Webhook> {
+    let webhook = sqlx::query_as::<_, Webhook>(
+        r#"
+        INSERT INTO webhooks (user_id, url, secret, event_types, format, description)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, url, secret, event_types, format, description, is_active, created_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(url)
+    .bind(secret)
+    .bind(event_types)
+    .bind(format)
+    .bind(description)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(webhook)
+}
+
+/// Number of endpoints registered by a user
+pub async fn count_webhooks(pool: &PgPool, user_id: Uuid) -> Result<i64> {
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM webhooks WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(count)
+}
+
+/// List a user's endpoints with their latest delivery, newest first
+pub async fn list_webhooks(pool: &PgPool, user_id: Uuid) -> Result<Vec<Webhook>> {
+    let query = format!(
+        "SELECT {} FROM webhooks w {} WHERE w.user_id = $1 ORDER BY w.created_at DESC",
+        WEBHOOK_COLUMNS, LAST_DELIVERY_JOIN
+    );
+    let webhooks = sqlx::query_as::<_, Webhook>(&query)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(webhooks)
+}
+
+/// Get one of a user's endpoints
+pub async fn get_webhook(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<Option<Webhook>> {
+    let query = format!(
+        "SELECT {} FROM webhooks w {} WHERE w.id = $1 AND w.user_id = $2",
+        WEBHOOK_COLUMNS, LAST_DELIVERY_JOIN
+    );
+    let webhook = sqlx::query_as::<_, Webhook>(&query)
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(webhook)
+}
+
+/// Delete an endpoint together with its delivery log
+pub async fn delete_webhook(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Active endpoints of a user subscribed to an event type
+pub async fn list_subscribed(pool: &PgPool, user_id: Uuid, event_type: &str) -> Result<Vec<Webhook>> {
+    let webhooks = sqlx::query_as::<_, Webhook>(
+        r#"
+        SELECT id, url, secret, event_types, format, description, is_active, created_at
+        FROM webhooks
+        WHERE user_id = $1 AND is_active
+          AND (cardinality(event_types) = 0 OR $2 = ANY(event_types))
+        "#,
+    )
+    .bind(user_id)
+    .bind(event_type)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(webhooks)
+}
+
+/// Log a pending delivery, returning its ID
+pub async fn insert_delivery(
+    pool: &PgPool,
+    webhook_id: Uuid,
+    user_id: Uuid,
+    event_id: Uuid,
+    event_type: &str,
+    payload: &serde_json::Value,
+) -> Result<Uuid> {
+    let (id,): (Uuid,) = sqlx::query_as(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, user_id, event_id, event_type, payload)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+    )
+    .bind(webhook_id)
+    .bind(user_id)
+    .bind(event_id)
+    .bind(event_type)
+    .bind(payload)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(id)
+}
+
+/// Get a delivery with the endpoint it goes to
+pub async fn get_pending_delivery(pool: &PgPool, id: Uuid) -> Result<Option<PendingWebhookDelivery>> {
+    let delivery = sqlx::query_as::<_, PendingWebhookDelivery>(
+        r#"
+        SELECT d.id, d.event_id, d.event_type, d.payload, d.status, w.url, w.secret
+        FROM webhook_deliveries d
+        JOIN webhooks w ON w.id = d.webhook_id
+        WHERE d.id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(delivery)
+}
+
+/// Record a delivery attempt that succeeded
+pub async fn mark_delivered(pool: &PgPool, id: Uuid, response_status: i32) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = $2, attempts = attempts + 1, response_status = $3, last_error = NULL,
+            delivered_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(WEBHOOK_DELIVERY_DELIVERED)
+    .bind(response_status)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a failed attempt; `give_up` marks the delivery as failed for good
+pub async fn record_failure(
+    pool: &PgPool,
+    id: Uuid,
+    response_status: Option<i32>,
+    error: &str,
+    give_up: bool,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_deliveries
+        SET attempts = attempts + 1, response_status = $2, last_error = $3,
+            status = CASE WHEN $4 THEN $5 ELSE status END, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(response_status)
+    .bind(error)
+    .bind(give_up)
+    .bind(WEBHOOK_DELIVERY_FAILED)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
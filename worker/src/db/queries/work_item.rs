@@ -5,6 +5,7 @@ use anyhow::Result;
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::types::import::RevisionLinkOutcome;
 use crate::types::work_item::{VisitWorkItem, CreateWorkItemRequest, WorkResult};
 
 /// Create a new work item (user_id reserved for future ownership verification of the visit)
@@ -20,6 +21,7 @@ pub async fn create_work_item(
             work_type, duration_minutes, result,
             result_notes, findings,
             requires_follow_up, follow_up_reason,
+            amount_minor, vat_rate, payment_method, currency,
             created_at
         )
         VALUES (
@@ -27,6 +29,7 @@ pub async fn create_work_item(
             $6, $7, $8,
             $9, $10,
             $11, $12,
+            $13, $14, $15, $16,
             NOW()
         )
         RETURNING *
@@ -44,6 +47,10 @@ pub async fn create_work_item(
     .bind(&req.findings)
     .bind(req.requires_follow_up.unwrap_or(false))
     .bind(&req.follow_up_reason)
+    .bind(req.amount_minor)
+    .bind(req.vat_rate)
+    .bind(&req.payment_method)
+    .bind(&req.currency)
     .fetch_one(pool)
     .await?;
 
@@ -106,6 +113,10 @@ pub async fn complete_work_item(
     findings: Option<&str>,
     requires_follow_up: bool,
     follow_up_reason: Option<&str>,
+    amount_minor: Option<i64>,
+    vat_rate: Option<i32>,
+    payment_method: Option<&str>,
+    currency: Option<&str>,
 ) -> Result<Option<VisitWorkItem>> {
     let item = sqlx::query_as::<_, VisitWorkItem>(
         r#"
@@ -115,7 +126,11 @@ pub async fn complete_work_item(
             result_notes = COALESCE($4, result_notes),
             findings = COALESCE($5, findings),
             requires_follow_up = $6,
-            follow_up_reason = COALESCE($7, follow_up_reason)
+            follow_up_reason = COALESCE($7, follow_up_reason),
+            amount_minor = COALESCE($9, amount_minor),
+            vat_rate = COALESCE($10, vat_rate),
+            payment_method = COALESCE($11, payment_method),
+            currency = COALESCE($12, currency)
         WHERE id = $1
           AND id IN (
             SELECT wi.id FROM visit_work_items wi
@@ -134,12 +149,40 @@ pub async fn complete_work_item(
     .bind(requires_follow_up)
     .bind(follow_up_reason)
     .bind(user_id)
+    .bind(amount_minor)
+    .bind(vat_rate)
+    .bind(payment_method)
+    .bind(currency)
     .fetch_optional(pool)
     .await?;
 
     Ok(item)
 }
 
+/// Currency the visit's billed work items are in, ignoring one item being rewritten
+pub async fn visit_billing_currency(
+    pool: &PgPool,
+    visit_id: Uuid,
+    exclude_item_id: Option<Uuid>,
+) -> Result<Option<String>> {
+    let currency: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT currency FROM visit_work_items
+        WHERE visit_id = $1
+          AND amount_minor IS NOT NULL AND currency IS NOT NULL
+          AND ($2::uuid IS NULL OR id <> $2)
+        ORDER BY created_at ASC
+        LIMIT 1
+        "#
+    )
+    .bind(visit_id)
+    .bind(exclude_item_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(currency.map(|(c,)| c))
+}
+
 /// Get a work item by ID (with user ownership verification)
 pub async fn get_work_item(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<Option<VisitWorkItem>> {
     let item = sqlx::query_as::<_, VisitWorkItem>(
@@ -176,7 +219,7 @@ pub async fn list_work_items_for_user(pool: &PgPool, user_id: Uuid) -> Result<Ve
     Ok(items)
 }
 
-/// Fulfill a revision from a work item (denormalize result)
+/// Fulfill a revision from a work item (denormalize result) and assign its document number
 pub async fn fulfill_revision(
     pool: &PgPool,
     revision_id: Uuid,
@@ -185,6 +228,8 @@ pub async fn fulfill_revision(
     findings: Option<&str>,
     duration_minutes: Option<i32>,
 ) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
     sqlx::query(
         r#"
         UPDATE revisions SET
@@ -202,8 +247,205 @@ pub async fn fulfill_revision(
     .bind(result)
     .bind(findings)
     .bind(duration_minutes)
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
 
+    super::revision_number::assign_revision_number(&mut tx, revision_id).await?;
+
+    tx.commit().await?;
     Ok(())
 }
+
+/// Completed revision being linked to visit history
+#[derive(sqlx::FromRow)]
+struct LinkTarget {
+    customer_id: Uuid,
+    device_id: Uuid,
+    status: String,
+    result: Option<String>,
+    findings: Option<String>,
+    duration_minutes: Option<i32>,
+    work_date: Option<chrono::NaiveDate>,
+    fulfilled_by_work_item_id: Option<Uuid>,
+    assigned_crew_id: Option<Uuid>,
+}
+
+/// Connect an imported completed revision to the visit on the same customer
+/// and date, and to that day's route stop. Without a matching visit one is
+/// created when `create_missing_visits` is set.
+pub async fn link_imported_revision(
+    pool: &PgPool,
+    user_id: Uuid,
+    revision_id: Uuid,
+    create_missing_visits: bool,
+) -> Result<RevisionLinkOutcome> {
+    let mut tx = pool.begin().await?;
+
+    let target = sqlx::query_as::<_, LinkTarget>(
+        r#"
+        SELECT
+            customer_id, device_id, status::text AS status, result::text AS result,
+            findings, duration_minutes,
+            COALESCE(completed_at::date, scheduled_date) AS work_date,
+            fulfilled_by_work_item_id, assigned_crew_id
+        FROM revisions
+        WHERE id = $1 AND user_id = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(revision_id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(target) = target else {
+        return Ok(RevisionLinkOutcome::Skipped);
+    };
+    let Some(work_date) = target.work_date else {
+        return Ok(RevisionLinkOutcome::Skipped);
+    };
+    if target.status != "completed" || target.fulfilled_by_work_item_id.is_some() {
+        return Ok(RevisionLinkOutcome::Skipped);
+    }
+    let work_result = target.result.as_deref().and_then(WorkResult::from_revision_result);
+
+    // Prefer the visit to the same device, then completed visits
+    let visit: Option<(Uuid, Option<Uuid>)> = sqlx::query_as(
+        r#"
+        SELECT id, crew_id FROM visits
+        WHERE user_id = $1 AND customer_id = $2 AND scheduled_date = $3
+          AND status NOT IN ('cancelled', 'rescheduled')
+        ORDER BY device_id = $4 DESC NULLS LAST, status = 'completed' DESC, created_at
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(target.customer_id)
+    .bind(work_date)
+    .bind(target.device_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let (visit_id, crew_id, outcome) = match visit {
+        Some((visit_id, crew_id)) => (visit_id, crew_id, RevisionLinkOutcome::Linked),
+        None if create_missing_visits => {
+            // Crew of the day's route stop, if the customer was on a route
+            let route_crew: Option<(Option<Uuid>,)> = sqlx::query_as(
+                r#"
+                SELECT r.crew_id FROM route_stops rs
+                JOIN routes r ON r.id = rs.route_id
+                WHERE r.user_id = $1 AND r.date = $2 AND rs.customer_id = $3
+                LIMIT 1
+                "#,
+            )
+            .bind(user_id)
+            .bind(work_date)
+            .bind(target.customer_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            let crew_id = route_crew.and_then(|(crew,)| crew).or(target.assigned_crew_id);
+
+            let (visit_id,): (Uuid,) = sqlx::query_as(
+                r#"
+                INSERT INTO visits (
+                    user_id, customer_id, crew_id, device_id, scheduled_date,
+                    status, visit_type, result, result_notes
+                )
+                VALUES ($1, $2, $3, $4, $5, 'completed', 'revision', $6, $7)
+                RETURNING id
+                "#,
+            )
+            .bind(user_id)
+            .bind(target.customer_id)
+            .bind(crew_id)
+            .bind(target.device_id)
+            .bind(work_date)
+            .bind(work_result.as_ref().map(WorkResult::as_str))
+            .bind(&target.findings)
+            .fetch_one(&mut *tx)
+            .await?;
+            (visit_id, crew_id, RevisionLinkOutcome::VisitCreated)
+        }
+        None => return Ok(RevisionLinkOutcome::Unmatched),
+    };
+
+    // Reuse a revision work item of the device that is not linked yet
+    let existing: Option<(Uuid,)> = sqlx::query_as(
+        r#"
+        SELECT id FROM visit_work_items
+        WHERE visit_id = $1
+          AND (revision_id = $2
+               OR (revision_id IS NULL AND device_id = $3 AND work_type = 'revision'))
+        ORDER BY revision_id = $2 DESC NULLS LAST
+        LIMIT 1
+        "#,
+    )
+    .bind(visit_id)
+    .bind(revision_id)
+    .bind(target.device_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let work_item_id = match existing {
+        Some((id,)) => {
+            sqlx::query("UPDATE visit_work_items SET revision_id = $2 WHERE id = $1")
+                .bind(id)
+                .bind(revision_id)
+                .execute(&mut *tx)
+                .await?;
+            id
+        }
+        None => {
+            let (id,): (Uuid,) = sqlx::query_as(
+                r#"
+                INSERT INTO visit_work_items (
+                    visit_id, device_id, revision_id, crew_id,
+                    work_type, duration_minutes, result, findings
+                )
+                VALUES ($1, $2, $3, $4, 'revision', $5, $6::work_result, $7)
+                RETURNING id
+                "#,
+            )
+            .bind(visit_id)
+            .bind(target.device_id)
+            .bind(revision_id)
+            .bind(crew_id)
+            .bind(target.duration_minutes)
+            .bind(work_result.as_ref().map(WorkResult::as_str))
+            .bind(&target.findings)
+            .fetch_one(&mut *tx)
+            .await?;
+            id
+        }
+    };
+
+    sqlx::query("UPDATE revisions SET fulfilled_by_work_item_id = $2 WHERE id = $1")
+        .bind(revision_id)
+        .bind(work_item_id)
+        .execute(&mut *tx)
+        .await?;
+
+    // Point the day's route stop of the customer at the visit
+    sqlx::query(
+        r#"
+        UPDATE route_stops rs SET
+            visit_id = $4,
+            revision_id = COALESCE(rs.revision_id, $5)
+        FROM routes r
+        WHERE rs.route_id = r.id
+          AND r.user_id = $1 AND r.date = $2 AND rs.customer_id = $3
+          AND rs.visit_id IS NULL
+          AND (rs.revision_id IS NULL OR rs.revision_id = $5)
+        "#,
+    )
+    .bind(user_id)
+    .bind(work_date)
+    .bind(target.customer_id)
+    .bind(visit_id)
+    .bind(revision_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(outcome)
+}
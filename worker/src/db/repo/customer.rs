@@ -0,0 +1,132 @@
+//! Customer repository
+
+use anyhow::Result;
+use async_trait::async_trait;
+#[cfg(test)]
+use parking_lot::RwLock;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::types::Customer;
+
+#[async_trait]
+pub trait CustomerRepo: Send + Sync {
+    /// A customer of the user; anonymized customers are not returned
+    async fn get_customer(&self, user_id: Uuid, customer_id: Uuid) -> Result<Option<Customer>>;
+}
+
+pub struct PgCustomerRepo {
+    pool: PgPool,
+}
+
+impl PgCustomerRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CustomerRepo for PgCustomerRepo {
+    async fn get_customer(&self, user_id: Uuid, customer_id: Uuid) -> Result<Option<Customer>> {
+        queries::customer::get_customer(&self.pool, user_id, customer_id).await
+    }
+}
+
+/// SQLite storage of trial/demo installs
+#[cfg(feature = "sqlite")]
+pub struct SqliteCustomerRepo {
+    pool: sqlx::SqlitePool,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteCustomerRepo {
+    pub fn new(pool: sqlx::SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[async_trait]
+impl CustomerRepo for SqliteCustomerRepo {
+    async fn get_customer(&self, user_id: Uuid, customer_id: Uuid) -> Result<Option<Customer>> {
+        let customer = sqlx::query_as::<_, Customer>(
+            r#"
+            SELECT
+                id, user_id, customer_type, name, contact_person, ico, dic,
+                email, phone, phone_raw,
+                street, city, postal_code, country,
+                lat, lng, geocode_status, notes, created_at, updated_at,
+                is_abandoned, deleted_at, parent_customer_id, language, customer_code,
+                acquisition_source, referrer_customer_id
+            FROM customers
+            WHERE id = ?1 AND user_id = ?2 AND is_anonymized = 0
+            "#,
+        )
+        .bind(customer_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(customer)
+    }
+}
+
+/// In-memory fake for tests
+#[cfg(test)]
+#[derive(Default)]
+pub struct InMemoryCustomerRepo {
+    customers: RwLock<Vec<Customer>>,
+}
+
+#[cfg(test)]
+impl InMemoryCustomerRepo {
+    pub fn insert(&self, customer: Customer) {
+        self.customers.write().push(customer);
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl CustomerRepo for InMemoryCustomerRepo {
+    async fn get_customer(&self, user_id: Uuid, customer_id: Uuid) -> Result<Option<Customer>> {
+        Ok(self
+            .customers
+            .read()
+            .iter()
+            .find(|c| c.id == customer_id && c.user_id == user_id)
+            .cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn customer(user_id: Uuid) -> Customer {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "userId": user_id,
+            "type": "person",
+            "name": "Jan Novák",
+            "geocodeStatus": "success",
+            "createdAt": "2026-01-05T08:00:00Z",
+            "updatedAt": "2026-01-05T08:00:00Z",
+            "isAbandoned": false,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_get_customer_is_scoped_to_user() {
+        let repo = InMemoryCustomerRepo::default();
+        let user_id = Uuid::new_v4();
+        let stored = customer(user_id);
+        repo.insert(stored.clone());
+
+        let found = repo.get_customer(user_id, stored.id).await.unwrap().unwrap();
+        assert_eq!(found.name.as_deref(), Some("Jan Novák"));
+        assert!(repo.get_customer(Uuid::new_v4(), stored.id).await.unwrap().is_none());
+        assert!(repo.get_customer(user_id, Uuid::new_v4()).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,61 @@
+//! Repository traits over the query layer
+//!
+//! Handlers that take a [`Repositories`] instead of a `PgPool` can be unit
+//! tested against the in-memory fakes (test builds only) and, later, run on
+//! another backend.
+//! The Postgres implementations delegate to `db::queries`, so both paths
+//! share the same SQL. With the `sqlite` feature, [`Repositories::sqlite`]
+//! serves trial/demo installs from a single SQLite file.
+
+pub mod customer;
+pub mod revision;
+pub mod route;
+
+use std::sync::Arc;
+
+use sqlx::PgPool;
+
+pub use customer::{CustomerRepo, PgCustomerRepo};
+pub use revision::{PgRevisionRepo, RevisionRepo};
+pub use route::{PgRouteRepo, RouteRepo};
+#[cfg(test)]
+pub use customer::InMemoryCustomerRepo;
+#[cfg(test)]
+pub use revision::InMemoryRevisionRepo;
+#[cfg(test)]
+pub use route::InMemoryRouteRepo;
+#[cfg(feature = "sqlite")]
+pub use customer::SqliteCustomerRepo;
+#[cfg(feature = "sqlite")]
+pub use revision::SqliteRevisionRepo;
+#[cfg(feature = "sqlite")]
+pub use route::SqliteRouteRepo;
+
+/// Repositories shared by handlers. Clones share the implementations.
+#[derive(Clone)]
+pub struct Repositories {
+    pub customers: Arc<dyn CustomerRepo>,
+    pub revisions: Arc<dyn RevisionRepo>,
+    pub routes: Arc<dyn RouteRepo>,
+}
+
+impl Repositories {
+    /// Repositories backed by the database
+    pub fn postgres(pool: PgPool) -> Self {
+        Self {
+            customers: Arc::new(PgCustomerRepo::new(pool.clone())),
+            revisions: Arc::new(PgRevisionRepo::new(pool.clone())),
+            routes: Arc::new(PgRouteRepo::new(pool)),
+        }
+    }
+
+    /// Repositories backed by a SQLite file of a trial/demo install
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(pool: sqlx::SqlitePool) -> Self {
+        Self {
+            customers: Arc::new(SqliteCustomerRepo::new(pool.clone())),
+            revisions: Arc::new(SqliteRevisionRepo::new(pool.clone())),
+            routes: Arc::new(SqliteRouteRepo::new(pool)),
+        }
+    }
+}
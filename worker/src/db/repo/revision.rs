@@ -0,0 +1,182 @@
+//! Revision repository
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+#[cfg(test)]
+use parking_lot::RwLock;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::types::Revision;
+
+#[async_trait]
+pub trait RevisionRepo: Send + Sync {
+    async fn get_revision(&self, revision_id: Uuid, user_id: Uuid) -> Result<Option<Revision>>;
+
+    /// Revisions scheduled on a date, earliest start first
+    async fn list_revisions_for_date(&self, user_id: Uuid, date: NaiveDate) -> Result<Vec<Revision>>;
+}
+
+pub struct PgRevisionRepo {
+    pool: PgPool,
+}
+
+impl PgRevisionRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RevisionRepo for PgRevisionRepo {
+    async fn get_revision(&self, revision_id: Uuid, user_id: Uuid) -> Result<Option<Revision>> {
+        queries::revision::get_revision(&self.pool, revision_id, user_id).await
+    }
+
+    async fn list_revisions_for_date(&self, user_id: Uuid, date: NaiveDate) -> Result<Vec<Revision>> {
+        queries::revision::list_revisions_for_date(&self.pool, user_id, date).await
+    }
+}
+
+/// Columns of a revision; mirrors `queries::revision` without enum casts
+#[cfg(feature = "sqlite")]
+const SQLITE_REVISION_COLS: &str = r#"
+    id, device_id, customer_id, user_id,
+    status, due_date, scheduled_date,
+    scheduled_time_start, scheduled_time_end,
+    completed_at, duration_minutes, result,
+    findings, fulfilled_by_work_item_id,
+    created_at, updated_at,
+    assigned_crew_id, route_order, document_number
+"#;
+
+/// SQLite storage of trial/demo installs
+#[cfg(feature = "sqlite")]
+pub struct SqliteRevisionRepo {
+    pool: sqlx::SqlitePool,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteRevisionRepo {
+    pub fn new(pool: sqlx::SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[async_trait]
+impl RevisionRepo for SqliteRevisionRepo {
+    async fn get_revision(&self, revision_id: Uuid, user_id: Uuid) -> Result<Option<Revision>> {
+        let query = format!("SELECT {} FROM revisions WHERE id = ?1 AND user_id = ?2", SQLITE_REVISION_COLS);
+        let revision = sqlx::query_as::<_, Revision>(&query)
+            .bind(revision_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(revision)
+    }
+
+    async fn list_revisions_for_date(&self, user_id: Uuid, date: NaiveDate) -> Result<Vec<Revision>> {
+        let query = format!(
+            "SELECT {} FROM revisions WHERE user_id = ?1 AND scheduled_date = ?2 \
+             ORDER BY scheduled_time_start IS NULL, scheduled_time_start",
+            SQLITE_REVISION_COLS
+        );
+        let revisions = sqlx::query_as::<_, Revision>(&query)
+            .bind(user_id)
+            .bind(date)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(revisions)
+    }
+}
+
+/// In-memory fake for tests
+#[cfg(test)]
+#[derive(Default)]
+pub struct InMemoryRevisionRepo {
+    revisions: RwLock<Vec<Revision>>,
+}
+
+#[cfg(test)]
+impl InMemoryRevisionRepo {
+    pub fn insert(&self, revision: Revision) {
+        self.revisions.write().push(revision);
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl RevisionRepo for InMemoryRevisionRepo {
+    async fn get_revision(&self, revision_id: Uuid, user_id: Uuid) -> Result<Option<Revision>> {
+        Ok(self
+            .revisions
+            .read()
+            .iter()
+            .find(|r| r.id == revision_id && r.user_id == user_id)
+            .cloned())
+    }
+
+    async fn list_revisions_for_date(&self, user_id: Uuid, date: NaiveDate) -> Result<Vec<Revision>> {
+        let mut revisions: Vec<Revision> = self
+            .revisions
+            .read()
+            .iter()
+            .filter(|r| r.user_id == user_id && r.scheduled_date == Some(date))
+            .cloned()
+            .collect();
+        // NULLS LAST like the query
+        revisions.sort_by_key(|r| (r.scheduled_time_start.is_none(), r.scheduled_time_start));
+        Ok(revisions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+
+    fn revision(user_id: Uuid, date: NaiveDate, start: Option<NaiveTime>) -> Revision {
+        let mut revision: Revision = serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "deviceId": Uuid::new_v4(),
+            "customerId": Uuid::new_v4(),
+            "userId": user_id,
+            "status": "scheduled",
+            "dueDate": date,
+            "createdAt": "2026-01-05T08:00:00Z",
+            "updatedAt": "2026-01-05T08:00:00Z",
+        }))
+        .unwrap();
+        revision.scheduled_date = Some(date);
+        revision.scheduled_time_start = start;
+        revision
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_revisions_for_date_order_like_the_query() {
+        let repo = InMemoryRevisionRepo::default();
+        let user_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2026, 5, 4).unwrap();
+        let at = |hour| NaiveTime::from_hms_opt(hour, 0, 0);
+
+        let untimed = revision(user_id, date, None);
+        let late = revision(user_id, date, at(14));
+        let early = revision(user_id, date, at(8));
+        for r in [untimed.clone(), late.clone(), early.clone()] {
+            repo.insert(r);
+        }
+        repo.insert(revision(user_id, date.succ_opt().unwrap(), at(7)));
+        repo.insert(revision(Uuid::new_v4(), date, at(7)));
+
+        let ids: Vec<Uuid> = repo.list_revisions_for_date(user_id, date).await.unwrap().iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![early.id, late.id, untimed.id]);
+
+        assert!(repo.get_revision(late.id, user_id).await.unwrap().is_some());
+        assert!(repo.get_revision(late.id, Uuid::new_v4()).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,254 @@
+//! Route repository
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+#[cfg(test)]
+use parking_lot::RwLock;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::db::queries::route::{RouteStopWithInfo, RouteWithCrewInfo};
+
+#[async_trait]
+pub trait RouteRepo: Send + Sync {
+    /// Routes of all crews on a date
+    async fn list_routes_for_date(&self, user_id: Uuid, date: NaiveDate) -> Result<Vec<RouteWithCrewInfo>>;
+
+    /// Stops of a route in stop order
+    async fn get_route_stops_with_info(&self, route_id: Uuid) -> Result<Vec<RouteStopWithInfo>>;
+}
+
+pub struct PgRouteRepo {
+    pool: PgPool,
+}
+
+impl PgRouteRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RouteRepo for PgRouteRepo {
+    async fn list_routes_for_date(&self, user_id: Uuid, date: NaiveDate) -> Result<Vec<RouteWithCrewInfo>> {
+        queries::route::list_routes_for_date(&self.pool, user_id, date).await
+    }
+
+    async fn get_route_stops_with_info(&self, route_id: Uuid) -> Result<Vec<RouteStopWithInfo>> {
+        queries::route::get_route_stops_with_info(&self.pool, route_id).await
+    }
+}
+
+/// SQLite storage of trial/demo installs
+#[cfg(feature = "sqlite")]
+pub struct SqliteRouteRepo {
+    pool: sqlx::SqlitePool,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteRouteRepo {
+    pub fn new(pool: sqlx::SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[async_trait]
+impl RouteRepo for SqliteRouteRepo {
+    async fn list_routes_for_date(&self, user_id: Uuid, date: NaiveDate) -> Result<Vec<RouteWithCrewInfo>> {
+        let routes = sqlx::query_as::<_, RouteWithCrewInfo>(
+            r#"
+            SELECT
+                r.id, r.user_id, r.crew_id,
+                c.name AS crew_name,
+                r.depot_id, r.date, r.status,
+                r.total_distance_km, r.total_duration_minutes, r.optimization_score,
+                r.arrival_buffer_percent, r.arrival_buffer_fixed_minutes,
+                r.return_to_depot_distance_km, r.return_to_depot_duration_minutes,
+                (SELECT COUNT(*) FROM route_stops rs WHERE rs.route_id = r.id AND rs.stop_type = 'customer') AS stops_count,
+                r.created_at, r.updated_at
+            FROM routes r
+            LEFT JOIN crews c ON c.id = r.crew_id
+            WHERE r.user_id = ?1 AND r.date = ?2
+            ORDER BY c.name IS NULL, c.name
+            "#,
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(routes)
+    }
+
+    async fn get_route_stops_with_info(&self, route_id: Uuid) -> Result<Vec<RouteStopWithInfo>> {
+        let stops = sqlx::query_as::<_, RouteStopWithInfo>(
+            r#"
+            SELECT
+                rs.id, rs.route_id, rs.customer_id,
+                rs.visit_id, rs.revision_id,
+                rs.stop_order, rs.estimated_arrival, rs.estimated_departure,
+                rs.distance_from_previous_km, rs.duration_from_previous_minutes,
+                rs.status, rs.stop_type,
+                c.name AS customer_name,
+                COALESCE(c.street, '') || ', ' || COALESCE(c.city, '') AS address,
+                c.lat AS customer_lat,
+                c.lng AS customer_lng,
+                c.phone AS customer_phone,
+                c.email AS customer_email,
+                rev.scheduled_date,
+                rev.scheduled_time_start,
+                rev.scheduled_time_end,
+                rev.status AS revision_status,
+                rs.break_duration_minutes,
+                rs.break_time_start,
+                rs.service_duration_minutes,
+                rs.override_service_duration_minutes,
+                rs.override_travel_duration_minutes,
+                rs.notes,
+                rs.tasks,
+                rs.break_location
+            FROM route_stops rs
+            LEFT JOIN customers c ON rs.customer_id = c.id
+            LEFT JOIN revisions rev ON rs.revision_id = rev.id
+            WHERE rs.route_id = ?1
+            ORDER BY rs.stop_order
+            "#,
+        )
+        .bind(route_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(stops)
+    }
+}
+
+/// In-memory fake for tests
+#[cfg(test)]
+#[derive(Default)]
+pub struct InMemoryRouteRepo {
+    routes: RwLock<Vec<RouteWithCrewInfo>>,
+    stops: RwLock<Vec<RouteStopWithInfo>>,
+}
+
+#[cfg(test)]
+impl InMemoryRouteRepo {
+    pub fn insert_route(&self, route: RouteWithCrewInfo) {
+        self.routes.write().push(route);
+    }
+
+    pub fn insert_stop(&self, stop: RouteStopWithInfo) {
+        self.stops.write().push(stop);
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl RouteRepo for InMemoryRouteRepo {
+    async fn list_routes_for_date(&self, user_id: Uuid, date: NaiveDate) -> Result<Vec<RouteWithCrewInfo>> {
+        Ok(self
+            .routes
+            .read()
+            .iter()
+            .filter(|r| r.user_id == user_id && r.date == date)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_route_stops_with_info(&self, route_id: Uuid) -> Result<Vec<RouteStopWithInfo>> {
+        let mut stops: Vec<RouteStopWithInfo> =
+            self.stops.read().iter().filter(|s| s.route_id == route_id).cloned().collect();
+        stops.sort_by_key(|s| s.stop_order);
+        Ok(stops)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn route(user_id: Uuid, date: NaiveDate) -> RouteWithCrewInfo {
+        RouteWithCrewInfo {
+            id: Uuid::new_v4(),
+            user_id,
+            crew_id: None,
+            crew_name: None,
+            depot_id: None,
+            date,
+            status: "draft".to_string(),
+            total_distance_km: None,
+            total_duration_minutes: None,
+            optimization_score: None,
+            arrival_buffer_percent: 10.0,
+            arrival_buffer_fixed_minutes: 0.0,
+            return_to_depot_distance_km: None,
+            return_to_depot_duration_minutes: None,
+            stops_count: Some(0),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn stop(route_id: Uuid, stop_order: i32) -> RouteStopWithInfo {
+        RouteStopWithInfo {
+            id: Uuid::new_v4(),
+            route_id,
+            customer_id: None,
+            visit_id: None,
+            revision_id: None,
+            stop_order,
+            estimated_arrival: None,
+            estimated_departure: None,
+            distance_from_previous_km: None,
+            duration_from_previous_minutes: None,
+            status: "pending".to_string(),
+            stop_type: "customer".to_string(),
+            customer_name: None,
+            address: None,
+            customer_lat: None,
+            customer_lng: None,
+            customer_phone: None,
+            customer_email: None,
+            scheduled_date: None,
+            scheduled_time_start: None,
+            scheduled_time_end: None,
+            revision_status: None,
+            break_duration_minutes: None,
+            break_time_start: None,
+            service_duration_minutes: None,
+            override_service_duration_minutes: None,
+            override_travel_duration_minutes: None,
+            notes: None,
+            tasks: sqlx::types::Json(vec![]),
+            break_location: None,
+            customer_risk_flag: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_routes_and_stops() {
+        let repo = InMemoryRouteRepo::default();
+        let user_id = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2026, 5, 4).unwrap();
+
+        let today = route(user_id, date);
+        repo.insert_route(today.clone());
+        repo.insert_route(route(user_id, date.succ_opt().unwrap()));
+        repo.insert_route(route(Uuid::new_v4(), date));
+
+        let routes = repo.list_routes_for_date(user_id, date).await.unwrap();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].id, today.id);
+
+        for order in [3, 1, 2] {
+            repo.insert_stop(stop(today.id, order));
+        }
+        repo.insert_stop(stop(Uuid::new_v4(), 1));
+        let orders: Vec<i32> =
+            repo.get_route_stops_with_info(today.id).await.unwrap().iter().map(|s| s.stop_order).collect();
+        assert_eq!(orders, vec![1, 2, 3]);
+    }
+}
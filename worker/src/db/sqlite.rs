@@ -0,0 +1,136 @@
+#![allow(dead_code)]
+//! SQLite storage for trial/demo installs (`sqlite` feature)
+//!
+//! Covers the tables behind the repository traits only, with the schema
+//! subset in `migrations_sqlite/`. Everything else still needs Postgres —
+//! see "SQLite backend" in PRJ_DEVOPS.MD for what is and is not supported.
+
+use anyhow::Result;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use std::str::FromStr;
+use tracing::info;
+
+/// Open (creating if missing) a SQLite database, e.g. `sqlite://sazinka-demo.db`
+pub async fn create_pool(database_url: &str) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::from_str(database_url)?
+        .create_if_missing(true)
+        .foreign_keys(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(4)
+        .connect_with(options)
+        .await?;
+
+    Ok(pool)
+}
+
+/// Apply the SQLite schema subset
+pub async fn run_migrations(pool: &SqlitePool) -> Result<()> {
+    info!("Running SQLite migrations...");
+    sqlx::migrate!("./migrations_sqlite").run(pool).await?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::repo::Repositories;
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    /// In-memory database; one connection, since each would get its own
+    async fn test_pool() -> SqlitePool {
+        let options = SqliteConnectOptions::from_str("sqlite::memory:").unwrap().foreign_keys(true);
+        let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_repositories_read_seeded_data() {
+        let pool = test_pool().await;
+        let date = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let (user_id, crew_id, customer_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let (revision_id, route_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        sqlx::query("INSERT INTO users (id, email) VALUES (?1, 'demo@example.com')")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO crews (id, user_id, name) VALUES (?1, ?2, 'Posádka 1')")
+            .bind(crew_id)
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO customers (id, user_id, customer_type, name, street, city, lat, lng, geocode_status) \
+             VALUES (?1, ?2, 'company', 'ACME s.r.o.', 'Hlavní 1', 'Brno', 49.19, 16.6, 'success')",
+        )
+        .bind(customer_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO revisions (id, device_id, customer_id, user_id, status, due_date, scheduled_date, scheduled_time_start) \
+             VALUES (?1, ?2, ?3, ?4, 'scheduled', ?5, ?5, '09:00:00')",
+        )
+        .bind(revision_id)
+        .bind(Uuid::new_v4())
+        .bind(customer_id)
+        .bind(user_id)
+        .bind(date)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO routes (id, user_id, crew_id, date) VALUES (?1, ?2, ?3, ?4)")
+            .bind(route_id)
+            .bind(user_id)
+            .bind(crew_id)
+            .bind(date)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO route_stops (id, route_id, customer_id, revision_id, stop_order, tasks) \
+             VALUES (?1, ?2, ?3, ?4, 1, '[{\"text\":\"Kontrola\",\"done\":false}]')",
+        )
+        .bind(Uuid::new_v4())
+        .bind(route_id)
+        .bind(customer_id)
+        .bind(revision_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO route_stops (id, route_id, stop_order, stop_type) VALUES (?1, ?2, 2, 'break')")
+            .bind(Uuid::new_v4())
+            .bind(route_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let repos = Repositories::sqlite(pool);
+
+        let customer = repos.customers.get_customer(user_id, customer_id).await.unwrap().unwrap();
+        assert_eq!(customer.name.as_deref(), Some("ACME s.r.o."));
+        assert!(repos.customers.get_customer(Uuid::new_v4(), customer_id).await.unwrap().is_none());
+
+        let revisions = repos.revisions.list_revisions_for_date(user_id, date).await.unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].status, "scheduled");
+        assert!(repos.revisions.get_revision(revision_id, user_id).await.unwrap().is_some());
+
+        let routes = repos.routes.list_routes_for_date(user_id, date).await.unwrap();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].crew_name.as_deref(), Some("Posádka 1"));
+        assert_eq!(routes[0].stops_count, Some(1));
+
+        let stops = repos.routes.get_route_stops_with_info(route_id).await.unwrap();
+        assert_eq!(stops.len(), 2);
+        assert_eq!(stops[0].address.as_deref(), Some("Hlavní 1, Brno"));
+        assert_eq!(stops[0].tasks.0.len(), 1);
+        assert_eq!(stops[1].stop_type, "break");
+    }
+}
@@ -0,0 +1,172 @@
+//! Account plan, usage and subscription handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::{quota, subscription};
+use crate::subjects;
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Start all account-related NATS handlers
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting account handlers...");
+
+    let [usage_sub, subscription_sub] = subjects::subscribe_all(
+        &client,
+        [subjects::account::USAGE, subjects::account::SUBSCRIPTION],
+    )
+    .await?;
+
+    tokio::spawn(handle_usage(client.clone(), usage_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_subscription(client.clone(), subscription_sub, pool, jwt_secret));
+
+    info!("Account handlers started");
+    Ok(())
+}
+
+/// Reply ACCOUNT_READ_ONLY when the account's subscription does not allow
+/// changes. Returns true if the request was rejected.
+pub(crate) async fn reject_if_read_only(
+    client: &Client,
+    pool: &PgPool,
+    reply: &async_nats::Subject,
+    request_id: Uuid,
+    user_id: Uuid,
+) -> Result<bool> {
+    match subscription::is_read_only(pool, user_id).await {
+        Ok(false) => Ok(false),
+        Ok(true) => {
+            let error = ErrorResponse::new(
+                request_id,
+                "ACCOUNT_READ_ONLY",
+                "Subscription is past due or cancelled; the account is read-only",
+            );
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            Ok(true)
+        }
+        Err(e) => {
+            // Billing state must not take writes down with it
+            warn!("Failed to check subscription of {}: {}", user_id, e);
+            Ok(false)
+        }
+    }
+}
+
+/// Handle account.usage messages - plan consumption vs. limits
+pub async fn handle_usage(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received account.usage message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        // Workers see the usage of the account they work for
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match quota::usage(&pool, user_id).await {
+            Ok(usage) => {
+                let response = SuccessResponse::new(request.id, usage);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to load account usage: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle account.subscription messages - subscription state of the account
+pub async fn handle_subscription(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received account.subscription message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::subscription::get(&pool, user_id).await {
+            Ok(Some(info)) => {
+                let response = SuccessResponse::new(request.id, info.with_read_only());
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "User not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to load subscription: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
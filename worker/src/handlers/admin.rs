@@ -5,23 +5,45 @@
 //! - Database status and management
 //! - Valhalla status
 //! - System logs
+//! - Customer reference audits
+//! - Account transfers between instances
 
 use std::sync::Arc;
 
 use anyhow::Result;
 use async_nats::Client;
+use base64::Engine;
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use tracing::{info, error, warn};
 
 use crate::auth;
+use crate::services::account_transfer;
+use crate::services::circuit_breaker::{self, CircuitBreakerStatus};
+use crate::services::crash_report::{self, CrashReport};
+use crate::services::geocode_freshness::{self, IndexFreshness};
+use crate::services::http::{self, HttpService};
+use crate::services::routing::{diagnostics, ValhallaClient, ValhallaConfig, VALHALLA_BREAKER};
 use crate::db::queries::country as country_queries;
+use crate::db::queries::customer as customer_queries;
+use crate::db::queries::customer_reference as customer_reference_queries;
+use crate::subjects;
+use crate::transport::queue::{ensured_streams, unmatched_stream_overrides, QueueStreamState, StreamLimits};
+use crate::transport::JobQueue;
+use crate::services::import_upload;
+use crate::types::account_transfer::{
+    AccountTransferExportRequest, AccountTransferExportResponse, AccountTransferImportRequest, TransferImportOptions,
+};
+use crate::types::routing_diagnostics::{DiagnosisTrigger, ValhallaDiagnoseRequest, ValhallaDiagnosis};
 use crate::types::{
-    Request, SuccessResponse, ErrorResponse,
-    CountryListResponse, UpdateCountryRequest, CountryJsonEntry,
+    Coordinates, Request, SuccessResponse, ErrorResponse,
+    CountryListResponse, CountryCoverageReportResponse, UpdateCountryRequest, CountryJsonEntry, CustomerReferencesRequest,
 };
 
+/// Timeout of the Valhalla / Nominatim status probes
+const STATUS_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
 // ==========================================================================
 // Request/Response Types
 // ==========================================================================
@@ -68,6 +90,10 @@ pub struct ValhallaStatusRequest {}
 pub struct ValhallaStatusResponse {
     pub available: bool,
     pub url: String,
+    /// Breaker guarding routing calls; None until a Valhalla client exists
+    pub circuit_breaker: Option<CircuitBreakerStatus>,
+    /// Latest tile diagnoses (manual and automatic), newest first
+    pub recent_diagnoses: Vec<ValhallaDiagnosis>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -83,6 +109,18 @@ pub struct LogsResponse {
     pub logs: Vec<LogEntry>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReportsRequest {
+    pub limit: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReportsResponse {
+    pub reports: Vec<CrashReport>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NominatimStatusRequest {}
@@ -93,6 +131,10 @@ pub struct NominatimStatusResponse {
     pub available: bool,
     pub url: String,
     pub version: Option<String>,
+    /// Breaker guarding geocoding calls; None until a Nominatim geocoder exists
+    pub circuit_breaker: Option<CircuitBreakerStatus>,
+    /// Age of the geocoding data at the last periodic check
+    pub index_freshness: Option<IndexFreshness>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -105,6 +147,8 @@ pub struct JetStreamStatusResponse {
     pub available: bool,
     pub streams: Vec<StreamInfo>,
     pub consumers: Vec<ConsumerInfo>,
+    /// JETSTREAM_STREAM_LIMITS entries naming no known stream
+    pub unmatched_overrides: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -113,6 +157,23 @@ pub struct StreamInfo {
     pub name: String,
     pub messages: i64,
     pub bytes: i64,
+    /// Effective limits; -1 means unlimited
+    pub max_messages: i64,
+    pub max_bytes: i64,
+    pub replicas: usize,
+    pub placement_cluster: Option<String>,
+    pub placement_tags: Vec<String>,
+    /// Fuller of messages / bytes against its limit, in percent
+    pub usage_percent: Option<f64>,
+}
+
+/// How full a stream is against the nearer of its limits
+fn stream_usage_percent(state: &QueueStreamState, limits: &StreamLimits) -> Option<f64> {
+    let ratio = |used: u64, max: i64| (max > 0).then(|| used as f64 / max as f64 * 100.0);
+    match (ratio(state.messages, limits.max_messages), ratio(state.bytes, limits.max_bytes)) {
+        (Some(m), Some(b)) => Some(m.max(b)),
+        (m, b) => m.or(b),
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -147,6 +208,28 @@ pub struct GeocodeStatusResponse {
     pub stream_messages: i64,
 }
 
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQueryRequest {
+    /// Minimum level ("error", "warn", "info", "debug", "trace")
+    pub level: Option<String>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    /// NATS subject, with or without the "sazinka." prefix
+    pub subject: Option<String>,
+    /// Case-insensitive text searched in message and target
+    pub search: Option<String>,
+    pub limit: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQueryResponse {
+    pub logs: Vec<LogEntry>,
+    /// True when the scan or response size limit cut the result short
+    pub truncated: bool,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LogEntry {
@@ -198,6 +281,16 @@ pub async fn start_admin_handlers(
         }
     });
 
+    let client_valhalla_diagnose = client.clone();
+    let pool_valhalla_diagnose = pool.clone();
+    let valhalla_url_diagnose = valhalla_url.clone();
+    let jwt_valhalla_diagnose = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = handle_valhalla_diagnose(client_valhalla_diagnose, pool_valhalla_diagnose, valhalla_url_diagnose, jwt_valhalla_diagnose).await {
+            error!("Valhalla diagnose handler error: {}", e);
+        }
+    });
+
     let client4 = client.clone();
     let jwt_secret4 = Arc::clone(&jwt_secret);
     tokio::spawn(async move {
@@ -206,6 +299,14 @@ pub async fn start_admin_handlers(
         }
     });
 
+    let client_logs_query = client.clone();
+    let jwt_logs_query = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = handle_logs_query(client_logs_query, jwt_logs_query).await {
+            error!("Logs query handler error: {}", e);
+        }
+    });
+
     let client5 = client.clone();
     let nominatim_url_clone = nominatim_url.clone();
     let jwt_secret5 = Arc::clone(&jwt_secret);
@@ -232,6 +333,14 @@ pub async fn start_admin_handlers(
         }
     });
 
+    let client_crashes = client.clone();
+    let jwt_crashes = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = handle_crash_reports(client_crashes, jwt_crashes).await {
+            error!("Crash reports handler error: {}", e);
+        }
+    });
+
     // Restart stack handler
     let client_restart = client.clone();
     let jwt_restart = Arc::clone(&jwt_secret);
@@ -269,6 +378,15 @@ pub async fn start_admin_handlers(
         }
     });
 
+    let client_countries_coverage = client.clone();
+    let pool_countries_coverage = pool.clone();
+    let jwt_countries_coverage = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = handle_admin_countries_coverage(client_countries_coverage, pool_countries_coverage, jwt_countries_coverage).await {
+            error!("Admin countries coverage handler error: {}", e);
+        }
+    });
+
     let client_countries_public = client.clone();
     let pool_countries_public = pool.clone();
     let jwt_countries_public = Arc::clone(&jwt_secret);
@@ -278,13 +396,40 @@ pub async fn start_admin_handlers(
         }
     });
 
+    let client_customer_refs = client.clone();
+    let pool_customer_refs = pool.clone();
+    let jwt_customer_refs = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = handle_customer_references(client_customer_refs, pool_customer_refs, jwt_customer_refs).await {
+            error!("Customer references handler error: {}", e);
+        }
+    });
+
+    let client_transfer_export = client.clone();
+    let pool_transfer_export = pool.clone();
+    let jwt_transfer_export = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = handle_account_transfer_export(client_transfer_export, pool_transfer_export, jwt_transfer_export).await {
+            error!("Account transfer export handler error: {}", e);
+        }
+    });
+
+    let client_transfer_import = client.clone();
+    let pool_transfer_import = pool.clone();
+    let jwt_transfer_import = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = handle_account_transfer_import(client_transfer_import, pool_transfer_import, jwt_transfer_import).await {
+            error!("Account transfer import handler error: {}", e);
+        }
+    });
+
     info!("Admin handlers started");
     Ok(())
 }
 
 /// Handle database status requests
 async fn handle_db_status(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
-    let mut sub = client.subscribe("sazinka.admin.db.status").await?;
+    let mut sub = client.subscribe(subjects::admin::DB_STATUS).await?;
     
     while let Some(msg) = sub.next().await {
         let reply = match msg.reply {
@@ -400,7 +545,7 @@ async fn get_db_status(pool: &PgPool) -> Result<DbStatusResponse> {
 
 /// Handle database reset requests
 async fn handle_db_reset(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
-    let mut sub = client.subscribe("sazinka.admin.db.reset").await?;
+    let mut sub = client.subscribe(subjects::admin::DB_RESET).await?;
     
     while let Some(msg) = sub.next().await {
         let reply = match msg.reply {
@@ -568,7 +713,7 @@ struct AdminSnapshot {
 
 /// Handle Valhalla status requests
 async fn handle_valhalla_status(client: Client, valhalla_url: Option<String>, jwt_secret: Arc<String>) -> Result<()> {
-    let mut sub = client.subscribe("sazinka.admin.valhalla.status").await?;
+    let mut sub = client.subscribe(subjects::admin::VALHALLA_STATUS).await?;
     
     while let Some(msg) = sub.next().await {
         let reply = match msg.reply {
@@ -605,12 +750,11 @@ async fn handle_valhalla_status(client: Client, valhalla_url: Option<String>, jw
         let (available, url) = match &valhalla_url {
             Some(url) => {
                 // Check if Valhalla is responding
-                let http_client = reqwest::Client::builder()
-                    .timeout(std::time::Duration::from_secs(5))
-                    .build()
-                    .unwrap();
-                
-                let check = http_client.get(format!("{}/status", url)).send().await;
+                let check = http::client(HttpService::Valhalla)
+                    .get(format!("{}/status", url))
+                    .timeout(STATUS_CHECK_TIMEOUT)
+                    .send()
+                    .await;
                 (check.is_ok(), url.clone())
             }
             None => (false, "Not configured".to_string()),
@@ -619,6 +763,8 @@ async fn handle_valhalla_status(client: Client, valhalla_url: Option<String>, jw
         let response = SuccessResponse::new(request.id, ValhallaStatusResponse {
             available,
             url,
+            circuit_breaker: circuit_breaker::status_of(VALHALLA_BREAKER),
+            recent_diagnoses: diagnostics::recent_diagnoses(),
         });
 
         let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
@@ -627,9 +773,84 @@ async fn handle_valhalla_status(client: Client, valhalla_url: Option<String>, jw
     Ok(())
 }
 
+/// Handle Valhalla diagnose requests: probe sample routes across the
+/// bounding box of an account's located customers
+async fn handle_valhalla_diagnose(
+    client: Client,
+    pool: PgPool,
+    valhalla_url: Option<String>,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    let mut sub = client.subscribe(subjects::admin::VALHALLA_DIAGNOSE).await?;
+
+    while let Some(msg) = sub.next().await {
+        let reply = match msg.reply {
+            Some(r) => r,
+            None => continue,
+        };
+
+        let request: Request<ValhallaDiagnoseRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(r) => r,
+            Err(e) => {
+                let request_id = extract_request_id(&msg.payload);
+                let error = ErrorResponse::new(request_id, "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Admin access required");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let Some(url) = valhalla_url.as_deref() else {
+            let error = ErrorResponse::new(request.id, "SERVICE_UNAVAILABLE", "Valhalla is not configured");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        };
+
+        let user_id = request.payload.user_id.unwrap_or_else(|| auth_info.data_user_id());
+        let points: Vec<Coordinates> = match customer_queries::list_located_customers(&pool, user_id).await {
+            Ok(customers) => customers.iter().map(|c| Coordinates { lat: c.lat, lng: c.lng }).collect(),
+            Err(e) => {
+                error!("Failed to load customers of {} for Valhalla diagnosis: {}", user_id, e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let valhalla = ValhallaClient::new(ValhallaConfig::new(url));
+        let diagnosis = diagnostics::diagnose(&valhalla, &points, DiagnosisTrigger::Manual, Some(user_id)).await;
+        info!(
+            "Valhalla diagnosis of {} ({} customers): {} problem regions",
+            user_id,
+            points.len(),
+            diagnosis.problem_regions().count()
+        );
+        diagnostics::remember_diagnosis(diagnosis.clone());
+
+        let response = SuccessResponse::new(request.id, diagnosis);
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
+
 /// Handle Nominatim status requests
 async fn handle_nominatim_status(client: Client, nominatim_url: Option<String>, jwt_secret: Arc<String>) -> Result<()> {
-    let mut sub = client.subscribe("sazinka.admin.nominatim.status").await?;
+    let mut sub = client.subscribe(subjects::admin::NOMINATIM_STATUS).await?;
     
     while let Some(msg) = sub.next().await {
         let reply = match msg.reply {
@@ -666,13 +887,8 @@ async fn handle_nominatim_status(client: Client, nominatim_url: Option<String>,
         let (available, url, version) = match &nominatim_url {
             Some(url) => {
                 // Check Nominatim status endpoint
-                let http_client = reqwest::Client::builder()
-                    .timeout(std::time::Duration::from_secs(5))
-                    .build()
-                    .unwrap();
-                
                 let status_url = format!("{}/status?format=json", url);
-                match http_client.get(&status_url).send().await {
+                match http::client(HttpService::Nominatim).get(&status_url).timeout(STATUS_CHECK_TIMEOUT).send().await {
                     Ok(response) if response.status().is_success() => {
                         // Try to parse version from response
                         let version = response
@@ -692,6 +908,8 @@ async fn handle_nominatim_status(client: Client, nominatim_url: Option<String>,
             available,
             url,
             version,
+            circuit_breaker: circuit_breaker::status_of("nominatim"),
+            index_freshness: geocode_freshness::latest(),
         });
 
         let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
@@ -702,9 +920,7 @@ async fn handle_nominatim_status(client: Client, nominatim_url: Option<String>,
 
 /// Handle JetStream status requests
 async fn handle_jetstream_status(client: Client, jwt_secret: Arc<String>) -> Result<()> {
-    use async_nats::jetstream;
-    
-    let mut sub = client.subscribe("sazinka.admin.jetstream.status").await?;
+    let mut sub = client.subscribe(subjects::admin::JETSTREAM_STATUS).await?;
     
     while let Some(msg) = sub.next().await {
         let reply = match msg.reply {
@@ -738,47 +954,41 @@ async fn handle_jetstream_status(client: Client, jwt_secret: Arc<String>) -> Res
             continue;
         }
 
-        let js = jetstream::new(client.clone());
-        
-        // Try to get stream info
-        let (available, streams, consumers) = match js.get_stream("SAZINKA_JOBS").await {
-            Ok(mut stream) => {
-                let stream_info = match stream.info().await {
-                    Ok(info) => vec![StreamInfo {
-                        name: info.config.name.clone(),
-                        messages: info.state.messages as i64,
-                        bytes: info.state.bytes as i64,
-                    }],
-                    Err(_) => vec![],
-                };
-                
-                // Get consumer info - we need to specify the consumer type
-                let consumer_info = match stream.get_consumer::<jetstream::consumer::pull::Config>("route_workers").await {
-                    Ok(mut consumer) => match consumer.info().await {
-                        Ok(info) => vec![ConsumerInfo {
-                            name: info.config.name.clone().unwrap_or_default(),
-                            stream: info.stream_name.clone(),
-                            pending: info.num_pending as i64,
-                        }],
-                        Err(_) => vec![],
-                    },
-                    Err(_) => vec![],
-                };
-                
-                (true, stream_info, consumer_info)
-            }
-            Err(_) => {
-                // JetStream might be available but stream not created yet
-                // Check if JetStream itself is available
-                let account_info = js.query_account().await;
-                (account_info.is_ok(), vec![], vec![])
+        let queue = JobQueue::new(client.clone());
+        let available = queue.is_available().await;
+
+        let mut streams = Vec::new();
+        for (name, limits) in ensured_streams() {
+            match queue.stream_state(&name).await {
+                Ok(state) => streams.push(StreamInfo {
+                    usage_percent: stream_usage_percent(&state, &limits),
+                    name,
+                    messages: state.messages as i64,
+                    bytes: state.bytes as i64,
+                    max_messages: limits.max_messages,
+                    max_bytes: limits.max_bytes,
+                    replicas: limits.replicas,
+                    placement_cluster: limits.placement_cluster,
+                    placement_tags: limits.placement_tags,
+                }),
+                Err(e) => warn!("Failed to read state of stream {}: {}", name, e),
             }
+        }
+
+        let consumers = match queue.consumer_pending("SAZINKA_JOBS", "route_workers").await {
+            Ok(pending) => vec![ConsumerInfo {
+                name: "route_workers".to_string(),
+                stream: "SAZINKA_JOBS".to_string(),
+                pending: pending as i64,
+            }],
+            Err(_) => vec![],
         };
 
         let response = SuccessResponse::new(request.id, JetStreamStatusResponse {
             available,
             streams,
             consumers,
+            unmatched_overrides: unmatched_stream_overrides(),
         });
 
         let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
@@ -789,9 +999,7 @@ async fn handle_jetstream_status(client: Client, jwt_secret: Arc<String>) -> Res
 
 /// Handle geocode status requests
 async fn handle_geocode_status(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
-    use async_nats::jetstream;
-    
-    let mut sub = client.subscribe("sazinka.admin.geocode.status").await?;
+    let mut sub = client.subscribe(subjects::admin::GEOCODE_STATUS).await?;
     
     while let Some(msg) = sub.next().await {
         let reply = match msg.reply {
@@ -825,8 +1033,8 @@ async fn handle_geocode_status(client: Client, pool: PgPool, jwt_secret: Arc<Str
             continue;
         }
 
-        let js = jetstream::new(client.clone());
-        
+        let queue = JobQueue::new(client.clone());
+
         // Get pending customers count (only those not yet attempted)
         let pending_customers: (i64,) = sqlx::query_as(
             "SELECT COUNT(*) FROM customers WHERE geocode_status = 'pending'"
@@ -844,13 +1052,8 @@ async fn handle_geocode_status(client: Client, pool: PgPool, jwt_secret: Arc<Str
         .unwrap_or((0,));
         
         // Get stream message count
-        let stream_messages = match js.get_stream("SAZINKA_GEOCODE_JOBS").await {
-            Ok(mut stream) => {
-                match stream.info().await {
-                    Ok(info) => info.state.messages as i64,
-                    Err(_) => 0,
-                }
-            }
+        let stream_messages = match queue.stream_state("SAZINKA_GEOCODE_JOBS").await {
+            Ok(state) => state.messages as i64,
             Err(_) => 0,
         };
 
@@ -869,7 +1072,7 @@ async fn handle_geocode_status(client: Client, pool: PgPool, jwt_secret: Arc<Str
 
 /// Handle logs requests
 async fn handle_logs(client: Client, jwt_secret: Arc<String>) -> Result<()> {
-    let mut sub = client.subscribe("sazinka.admin.logs").await?;
+    let mut sub = client.subscribe(subjects::admin::LOGS).await?;
     
     while let Some(msg) = sub.next().await {
         let reply = match msg.reply {
@@ -918,6 +1121,265 @@ async fn handle_logs(client: Client, jwt_secret: Arc<String>) -> Result<()> {
     Ok(())
 }
 
+/// Handle crash report listing requests
+async fn handle_crash_reports(client: Client, jwt_secret: Arc<String>) -> Result<()> {
+    let mut sub = client.subscribe(subjects::admin::CRASHES_LIST).await?;
+
+    while let Some(msg) = sub.next().await {
+        let reply = match msg.reply {
+            Some(r) => r,
+            None => continue,
+        };
+
+        let request: Request<CrashReportsRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(r) => r,
+            Err(e) => {
+                let request_id = extract_request_id(&msg.payload);
+                let error = ErrorResponse::new(request_id, "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Admin access required");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let limit = request.payload.limit.unwrap_or(50).clamp(1, 500) as usize;
+        let logs_dir = std::env::var("LOGS_DIR").unwrap_or_else(|_| "../logs".to_string());
+        let reports = crash_report::list_reports(&logs_dir, limit);
+
+        let response = SuccessResponse::new(request.id, CrashReportsResponse { reports });
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle log query requests (search with level, time range and subject filters)
+async fn handle_logs_query(client: Client, jwt_secret: Arc<String>) -> Result<()> {
+    let mut sub = client.subscribe(subjects::admin::LOGS_QUERY).await?;
+
+    while let Some(msg) = sub.next().await {
+        let reply = match msg.reply {
+            Some(r) => r,
+            None => continue,
+        };
+
+        let request: Request<LogQueryRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(r) => r,
+            Err(e) => {
+                let request_id = extract_request_id(&msg.payload);
+                let error = ErrorResponse::new(request_id, "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Admin access required");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let logs_dir = std::env::var("LOGS_DIR").unwrap_or_else(|_| "../logs".to_string());
+        let query = request.payload;
+        // Scanning large files is blocking I/O — keep it off the async workers
+        let result = tokio::task::spawn_blocking(move || query_logs(&logs_dir, &query)).await;
+
+        match result {
+            Ok(logs) => {
+                let response = SuccessResponse::new(request.id, logs);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Log query failed: {}", e);
+                let error = ErrorResponse::new(request.id, "INTERNAL_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Bytes read from the end of each log file per query
+const LOG_QUERY_MAX_SCAN_BYTES: u64 = 8 * 1024 * 1024;
+/// Upper bound of matched messages returned per query
+const LOG_QUERY_MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+const LOG_QUERY_MAX_LIMIT: usize = 1000;
+const LOG_QUERY_MAX_MESSAGE_CHARS: usize = 4000;
+
+/// Search recent worker logs, newest files first. Only the tail of each file
+/// is scanned, rotated files older than `from` are skipped, and the scan stops
+/// at the first older day once `limit` lines have matched.
+fn query_logs(logs_dir: &str, query: &LogQueryRequest) -> LogQueryResponse {
+    let limit = query.limit.unwrap_or(200).clamp(1, LOG_QUERY_MAX_LIMIT as i32) as usize;
+    let subject = query
+        .subject
+        .as_deref()
+        .map(|s| s.trim().trim_start_matches(subjects::PREFIX).to_lowercase())
+        .filter(|s| !s.is_empty());
+    let search = query.search.as_deref().map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty());
+    let has_range = query.from.is_some() || query.to.is_some();
+
+    let mut matched: Vec<(chrono::DateTime<chrono::Utc>, LogEntry)> = Vec::new();
+    let mut truncated = false;
+
+    let Ok(entries) = std::fs::read_dir(logs_dir) else {
+        return LogQueryResponse { logs: Vec::new(), truncated };
+    };
+
+    // Rotated files carry their day: worker.log.2026-01-29; live files have none
+    // and sort first. Files of one day may interleave, so they are read together.
+    let mut files: Vec<(Option<chrono::NaiveDate>, std::path::PathBuf, String)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let filename = path.file_name().and_then(|n| n.to_str())?.to_string();
+            if !(filename.ends_with(".log") || filename.contains(".log.")) {
+                return None;
+            }
+            let day = rotated_file_day(&filename);
+            if let (Some(from), Some(day)) = (query.from, day) {
+                if day < from.date_naive() {
+                    return None;
+                }
+            }
+            Some((day, path, filename))
+        })
+        .collect();
+    files.sort_by_key(|(day, _, filename)| {
+        (std::cmp::Reverse(day.unwrap_or(chrono::NaiveDate::MAX)), filename.clone())
+    });
+
+    let mut current_day = None;
+    for (day, path, filename) in files {
+        if current_day != Some(day) {
+            if matched.len() >= limit {
+                truncated = true;
+                break;
+            }
+            current_day = Some(day);
+        }
+
+        let source = filename.split(".log").next().unwrap_or("unknown").to_string();
+        let (lines, cut) = match read_tail(&path, LOG_QUERY_MAX_SCAN_BYTES) {
+            Ok(tail) => tail,
+            Err(e) => {
+                warn!("Failed to read log file {}: {}", filename, e);
+                continue;
+            }
+        };
+        truncated |= cut;
+
+        // Lines are in time order, so a file never contributes more than its newest `limit` matches
+        let mut file_matches = 0;
+        for line in lines.into_iter().rev() {
+            if file_matches >= limit {
+                truncated = true;
+                break;
+            }
+            let Some(mut log) = parse_log_line(&line, &source) else { continue };
+            if let Some(level) = query.level.as_deref() {
+                if !matches_level_filter(&log.level, level) {
+                    continue;
+                }
+            }
+            let timestamp = match chrono::DateTime::parse_from_rfc3339(&log.timestamp) {
+                Ok(ts) => ts.with_timezone(&chrono::Utc),
+                // Lines without a reliable timestamp can't be placed in a range
+                Err(_) if has_range => continue,
+                Err(_) => chrono::DateTime::<chrono::Utc>::MIN_UTC,
+            };
+            if query.from.is_some_and(|from| timestamp < from) || query.to.is_some_and(|to| timestamp > to) {
+                continue;
+            }
+            let haystack = format!("{} {}", log.target.as_deref().unwrap_or(""), log.message).to_lowercase();
+            if subject.as_ref().is_some_and(|s| !haystack.contains(s.as_str())) {
+                continue;
+            }
+            if search.as_ref().is_some_and(|s| !haystack.contains(s.as_str())) {
+                continue;
+            }
+            if log.message.chars().count() > LOG_QUERY_MAX_MESSAGE_CHARS {
+                log.message = log.message.chars().take(LOG_QUERY_MAX_MESSAGE_CHARS).collect();
+                log.message.push('…');
+            }
+            matched.push((timestamp, log));
+            file_matches += 1;
+        }
+    }
+
+    // Newest first, then cut to the requested count and response size
+    matched.sort_by_key(|m| std::cmp::Reverse(m.0));
+    if matched.len() > limit {
+        matched.truncate(limit);
+        truncated = true;
+    }
+    let mut size = 0;
+    let mut logs = Vec::with_capacity(matched.len());
+    for (_, log) in matched {
+        size += log.message.len() + log.timestamp.len();
+        if size > LOG_QUERY_MAX_RESPONSE_BYTES {
+            truncated = true;
+            break;
+        }
+        logs.push(log);
+    }
+
+    LogQueryResponse { logs, truncated }
+}
+
+/// Day of a rotated log file ("worker.log.2026-01-29")
+fn rotated_file_day(filename: &str) -> Option<chrono::NaiveDate> {
+    let suffix = filename.rsplit(".log.").next()?;
+    chrono::NaiveDate::parse_from_str(suffix, "%Y-%m-%d").ok()
+}
+
+/// Read at most `max_bytes` from the end of a file as lines.
+/// Returns whether the beginning of the file was skipped.
+fn read_tail(path: &std::path::Path, max_bytes: u64) -> std::io::Result<(Vec<String>, bool)> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let cut = len > max_bytes;
+    if cut {
+        file.seek(SeekFrom::Start(len - max_bytes))?;
+    }
+    let mut buf = Vec::with_capacity(len.min(max_bytes) as usize);
+    file.read_to_end(&mut buf)?;
+
+    let text = String::from_utf8_lossy(&buf);
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    // The first line of a cut read is most likely partial
+    if cut && !lines.is_empty() {
+        lines.remove(0);
+    }
+    Ok((lines, cut))
+}
+
 /// Read logs from all .log files in the specified directory
 fn read_all_logs(logs_dir: &str, limit: usize, level_filter: Option<&str>) -> Vec<LogEntry> {
     let mut all_logs: Vec<LogEntry> = Vec::new();
@@ -1113,7 +1575,7 @@ fn format_bytes(bytes: i64) -> String {
 /// Handle restart-all-services request.
 /// Responds immediately, then spawns `docker compose restart` in background.
 async fn handle_restart_stack(client: Client, jwt_secret: Arc<String>) -> Result<()> {
-    let mut sub = client.subscribe("sazinka.admin.restart.all").await?;
+    let mut sub = client.subscribe(subjects::admin::RESTART_ALL).await?;
 
     while let Some(msg) = sub.next().await {
         let reply = match msg.reply {
@@ -1226,7 +1688,7 @@ async fn handle_admin_countries_list(
     pool: PgPool,
     jwt_secret: Arc<String>,
 ) -> Result<()> {
-    let mut sub = client.subscribe("sazinka.admin.countries.list").await?;
+    let mut sub = client.subscribe(subjects::admin::COUNTRIES_LIST).await?;
 
     while let Some(msg) = sub.next().await {
         let reply = match msg.reply {
@@ -1281,7 +1743,7 @@ async fn handle_admin_countries_sync(
     pool: PgPool,
     jwt_secret: Arc<String>,
 ) -> Result<()> {
-    let mut sub = client.subscribe("sazinka.admin.countries.sync").await?;
+    let mut sub = client.subscribe(subjects::admin::COUNTRIES_SYNC).await?;
 
     while let Some(msg) = sub.next().await {
         let reply = match msg.reply {
@@ -1348,7 +1810,7 @@ async fn handle_admin_countries_update(
     pool: PgPool,
     jwt_secret: Arc<String>,
 ) -> Result<()> {
-    let mut sub = client.subscribe("sazinka.admin.countries.update").await?;
+    let mut sub = client.subscribe(subjects::admin::COUNTRIES_UPDATE).await?;
 
     while let Some(msg) = sub.next().await {
         let reply = match msg.reply {
@@ -1383,6 +1845,10 @@ async fn handle_admin_countries_update(
 
         match country_queries::update_country(&pool, &request.payload).await {
             Ok(Some(country)) => {
+                info!(
+                    "Country {} updated by {}: supported={}, map_coverage={}",
+                    country.code, auth_info.user_id, country.is_supported, country.has_map_coverage
+                );
                 let resp = SuccessResponse::new(request.id, country);
                 let _ = client.publish(reply, serde_json::to_vec(&resp)?.into()).await;
             }
@@ -1401,13 +1867,69 @@ async fn handle_admin_countries_update(
     Ok(())
 }
 
+/// `sazinka.admin.countries.coverage` — admin only, customers in countries
+/// that are unknown, unsupported or without map coverage
+async fn handle_admin_countries_coverage(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    let mut sub = client.subscribe(subjects::admin::COUNTRIES_COVERAGE).await?;
+
+    while let Some(msg) = sub.next().await {
+        let reply = match msg.reply {
+            Some(r) => r,
+            None => continue,
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(r) => r,
+            Err(e) => {
+                let id = extract_request_id(&msg.payload);
+                let err = ErrorResponse::new(id, "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let err = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "admin" {
+            let err = ErrorResponse::new(request.id, "FORBIDDEN", "Admin access required");
+            let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            continue;
+        }
+
+        match country_queries::list_coverage_gaps(&pool).await {
+            Ok(items) => {
+                let resp = SuccessResponse::new(request.id, CountryCoverageReportResponse { items });
+                let _ = client.publish(reply, serde_json::to_vec(&resp)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to build country coverage report: {}", e);
+                let err = ErrorResponse::new(request.id, "DB_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// `sazinka.countries.list` — authenticated users, returns only `is_supported = true` countries
 async fn handle_countries_list(
     client: Client,
     pool: PgPool,
     jwt_secret: Arc<String>,
 ) -> Result<()> {
-    let mut sub = client.subscribe("sazinka.countries.list").await?;
+    let mut sub = client.subscribe(subjects::countries::LIST).await?;
 
     while let Some(msg) = sub.next().await {
         let reply = match msg.reply {
@@ -1446,3 +1968,297 @@ async fn handle_countries_list(
 
     Ok(())
 }
+
+/// `sazinka.admin.customer.references` — admin only, counts every row
+/// referencing a customer (merge preview and safe-delete check)
+async fn handle_customer_references(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    let mut sub = client.subscribe(subjects::admin::CUSTOMER_REFERENCES).await?;
+
+    while let Some(msg) = sub.next().await {
+        let reply = match msg.reply {
+            Some(r) => r,
+            None => continue,
+        };
+
+        let request: Request<CustomerReferencesRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(r) => r,
+            Err(e) => {
+                let id = extract_request_id(&msg.payload);
+                let err = ErrorResponse::new(id, "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let err = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "admin" {
+            let err = ErrorResponse::new(request.id, "FORBIDDEN", "Admin access required");
+            let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            continue;
+        }
+
+        match customer_reference_queries::customer_reference_report(&pool, request.payload.customer_id).await {
+            Ok(Some(report)) => {
+                let resp = SuccessResponse::new(request.id, report);
+                let _ = client.publish(reply, serde_json::to_vec(&resp)?.into()).await;
+            }
+            Ok(None) => {
+                let err = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to audit customer references: {}", e);
+                let err = ErrorResponse::new(request.id, "DB_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `sazinka.admin.account.transfer.export` — admin only, encrypted transfer
+/// archive of one account (see `services::account_transfer`)
+async fn handle_account_transfer_export(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    let mut sub = client.subscribe(subjects::admin::ACCOUNT_TRANSFER_EXPORT).await?;
+
+    while let Some(msg) = sub.next().await {
+        let reply = match msg.reply {
+            Some(r) => r,
+            None => continue,
+        };
+
+        let request: Request<AccountTransferExportRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(r) => r,
+            Err(e) => {
+                let id = extract_request_id(&msg.payload);
+                let err = ErrorResponse::new(id, "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let err = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "admin" {
+            let err = ErrorResponse::new(request.id, "FORBIDDEN", "Admin access required");
+            let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            continue;
+        }
+
+        let key = match account_transfer::parse_transfer_key(&request.payload.key) {
+            Ok(key) => key,
+            Err(e) => {
+                let err = ErrorResponse::new(request.id, "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        match account_transfer::export_account(&pool, request.payload.user_id, &key).await {
+            Ok((info, manifest, archive)) => {
+                let resp = SuccessResponse::new(request.id, AccountTransferExportResponse {
+                    filename: format!("sazinka-transfer-{}.szbk", info.owner_user_id),
+                    content_base64: base64::engine::general_purpose::STANDARD.encode(archive),
+                    owner_email: info.owner_email,
+                    tables: manifest.tables.len(),
+                    rows: manifest.tables.iter().map(|t| t.rows).sum(),
+                });
+                let _ = client.publish(reply, serde_json::to_vec(&resp)?.into()).await;
+            }
+            Err(e) => {
+                error!("Account transfer export failed: {:#}", e);
+                let err = ErrorResponse::new(request.id, "EXPORT_FAILED", format!("{:#}", e));
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `sazinka.admin.account.transfer.import` — admin only, imports a transfer
+/// archive (uploaded in chunks or inline) under new ids
+async fn handle_account_transfer_import(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    let mut sub = client.subscribe(subjects::admin::ACCOUNT_TRANSFER_IMPORT).await?;
+
+    while let Some(msg) = sub.next().await {
+        let reply = match msg.reply {
+            Some(r) => r,
+            None => continue,
+        };
+
+        let request: Request<AccountTransferImportRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(r) => r,
+            Err(e) => {
+                let id = extract_request_id(&msg.payload);
+                let err = ErrorResponse::new(id, "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let err = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "admin" {
+            let err = ErrorResponse::new(request.id, "FORBIDDEN", "Admin access required");
+            let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            continue;
+        }
+
+        let payload = &request.payload;
+        let parsed = async {
+            let key = account_transfer::parse_transfer_key(&payload.key)?;
+            let data = import_upload::load_file(auth_info.user_id, payload.upload_id, &payload.content_base64).await?;
+            account_transfer::read_transfer(&key, &data)
+        }
+        .await;
+        let (info, manifest, tables) = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                let err = ErrorResponse::new(request.id, "INVALID_REQUEST", format!("{:#}", e));
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        let options = TransferImportOptions { into_user_id: payload.into_user_id, on_conflict: payload.on_conflict };
+        match account_transfer::import_account(&pool, &info, &manifest, tables, &options).await {
+            Ok(summary) => {
+                info!(
+                    "Account {} imported as {}: {} rows inserted, {} skipped",
+                    info.owner_email, summary.owner_user_id, summary.rows_inserted, summary.rows_skipped
+                );
+                let resp = SuccessResponse::new(request.id, summary);
+                let _ = client.publish(reply, serde_json::to_vec(&resp)?.into()).await;
+            }
+            Err(e) => {
+                warn!("Account transfer import failed: {:#}", e);
+                let err = ErrorResponse::new(request.id, "IMPORT_FAILED", format!("{:#}", e));
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_logs(dir: &std::path::Path) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(
+            dir.join("worker.log"),
+            "2026-03-02T08:00:00.000000Z  INFO sazinka_worker::handlers::customer: Received customer.create message\n\
+             2026-03-02T09:00:00.000000Z ERROR sazinka_worker::handlers::customer: Failed to create customer: timeout\n\
+             2026-03-02T10:00:00.000000Z  WARN sazinka_worker::handlers::route: Route planning slow\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("worker.log.2026-02-01"),
+            "2026-02-01T09:00:00.000000Z ERROR sazinka_worker::handlers::customer: Old failure\n",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_stream_usage_percent_uses_fuller_limit() {
+        let limits = StreamLimits {
+            max_messages: 1000,
+            max_bytes: 1000,
+            replicas: 1,
+            placement_cluster: None,
+            placement_tags: vec![],
+        };
+        let state = QueueStreamState { messages: 100, bytes: 500 };
+        assert_eq!(stream_usage_percent(&state, &limits), Some(50.0));
+        let unlimited = StreamLimits { max_messages: -1, max_bytes: -1, ..limits };
+        assert_eq!(stream_usage_percent(&state, &unlimited), None);
+    }
+
+    #[test]
+    fn test_query_logs_filters() {
+        let dir = std::env::temp_dir().join(format!("sazinka-logs-{}", uuid::Uuid::new_v4()));
+        write_logs(&dir);
+        let logs_dir = dir.to_str().unwrap();
+
+        let all = query_logs(logs_dir, &LogQueryRequest::default());
+        assert_eq!(all.logs.len(), 4);
+        assert!(all.logs[0].message.contains("Route planning"));
+        assert!(!all.truncated);
+
+        let errors = query_logs(logs_dir, &LogQueryRequest { level: Some("error".into()), ..Default::default() });
+        assert_eq!(errors.logs.len(), 2);
+
+        let subject = query_logs(
+            logs_dir,
+            &LogQueryRequest { subject: Some("sazinka.customer.create".into()), ..Default::default() },
+        );
+        assert_eq!(subject.logs.len(), 1);
+
+        let range = query_logs(
+            logs_dir,
+            &LogQueryRequest {
+                from: Some("2026-03-02T08:30:00Z".parse().unwrap()),
+                to: Some("2026-03-02T09:30:00Z".parse().unwrap()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(range.logs.len(), 1);
+        assert!(range.logs[0].message.contains("timeout"));
+
+        let limited = query_logs(logs_dir, &LogQueryRequest { limit: Some(1), ..Default::default() });
+        assert_eq!(limited.logs.len(), 1);
+        assert!(limited.truncated);
+
+        // The live file fills the limit, so the rotated day is never read
+        let newest = query_logs(logs_dir, &LogQueryRequest { limit: Some(3), ..Default::default() });
+        assert_eq!(newest.logs.len(), 3);
+        assert!(newest.truncated);
+        assert!(newest.logs.iter().all(|log| !log.message.contains("Old failure")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotated_file_day() {
+        assert_eq!(rotated_file_day("worker.log.2026-01-29"), chrono::NaiveDate::from_ymd_opt(2026, 1, 29));
+        assert_eq!(rotated_file_day("worker.log"), None);
+    }
+}
@@ -0,0 +1,658 @@
+//! Admin user management handlers for NATS messages
+//!
+//! Every mutating action is recorded in `admin_audit_log`.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use chrono::Utc;
+use futures::StreamExt;
+use serde_json::json;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::auth::{self, AuthInfo};
+use crate::db::queries;
+use crate::services::email_sender::EmailSender;
+use crate::services::email_templates::{PasswordResetEmail, VerificationEmail};
+use crate::services::subscription;
+use crate::subjects;
+use crate::types::{
+    ErrorResponse, Request, SuccessResponse,
+    AdminUserActionResponse, AdminUserIdRequest, ListAdminAuditRequest, ListAdminAuditResponse,
+    ListAdminUsersRequest, ListAdminUsersResponse, SetUserDisabledRequest, SetUserPlanRequest,
+    SetUserRoleRequest, ACCOUNT_PLANS, ASSIGNABLE_USER_ROLES, AUDIT_PASSWORD_RESET_SENT,
+    AUDIT_PLAN_CHANGED, AUDIT_ROLE_CHANGED, AUDIT_USER_DISABLED, AUDIT_USER_ENABLED,
+    AUDIT_SUBSCRIPTION_CHANGED, AUDIT_VERIFICATION_RESENT, GetSubscriptionRequest, SetSubscriptionRequest,
+    SubscriptionChangedEvent, is_read_only_state, SUBSCRIPTION_STATES,
+};
+
+/// How long a forced password reset link stays valid
+const PASSWORD_RESET_TTL_HOURS: i64 = 24;
+/// Same lifetime as the link sent at registration
+const VERIFICATION_TTL_HOURS: i64 = 24;
+
+/// Start all admin user management handlers
+pub async fn start_handlers(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    email_sender: Arc<dyn EmailSender>,
+    app_base_url: Arc<String>,
+) -> Result<()> {
+    info!("Starting admin user handlers...");
+
+    let [
+        list_sub,
+        disable_sub,
+        reset_sub,
+        role_sub,
+        verification_sub,
+        plan_sub,
+        subscription_set_sub,
+        subscription_get_sub,
+        audit_sub,
+    ] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::admin::USERS_LIST,
+            subjects::admin::USERS_SET_DISABLED,
+            subjects::admin::USERS_RESET_PASSWORD,
+            subjects::admin::USERS_SET_ROLE,
+            subjects::admin::USERS_RESEND_VERIFICATION,
+            subjects::admin::USERS_SET_PLAN,
+            subjects::admin::SUBSCRIPTION_SET,
+            subjects::admin::SUBSCRIPTION_GET,
+            subjects::admin::AUDIT_LIST,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_list(client.clone(), list_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_set_disabled(client.clone(), disable_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_reset_password(
+        client.clone(),
+        reset_sub,
+        pool.clone(),
+        jwt_secret.clone(),
+        email_sender.clone(),
+        app_base_url.clone(),
+    ));
+    tokio::spawn(handle_resend_verification(
+        client.clone(),
+        verification_sub,
+        pool.clone(),
+        jwt_secret.clone(),
+        email_sender,
+        app_base_url,
+    ));
+    tokio::spawn(handle_set_role(client.clone(), role_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_set_plan(client.clone(), plan_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_set_subscription(client.clone(), subscription_set_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_get_subscription(client.clone(), subscription_get_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_audit_list(client.clone(), audit_sub, pool, jwt_secret));
+
+    info!("Admin user handlers started");
+    Ok(())
+}
+
+/// Parse the request and require the admin role. Replies with the error itself.
+async fn admin_request<T: serde::de::DeserializeOwned>(
+    client: &Client,
+    reply: &async_nats::Subject,
+    payload: &[u8],
+    jwt_secret: &str,
+) -> Result<Option<(Request<T>, AuthInfo)>> {
+    let request: Request<T> = match serde_json::from_slice(payload) {
+        Ok(req) => req,
+        Err(e) => {
+            let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            return Ok(None);
+        }
+    };
+
+    let auth_info = match auth::extract_auth(&request, jwt_secret) {
+        Ok(info) => info,
+        Err(_) => {
+            let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            return Ok(None);
+        }
+    };
+
+    if auth_info.role != "admin" {
+        let error = ErrorResponse::new(request.id, "FORBIDDEN", "Admin access required");
+        let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+        return Ok(None);
+    }
+
+    Ok(Some((request, auth_info)))
+}
+
+/// Write an audit entry; failures are logged, never surfaced to the caller
+async fn audit(pool: &PgPool, admin_id: Uuid, action: &str, target: Uuid, details: serde_json::Value) {
+    if let Err(e) = queries::admin_user::insert_audit(pool, admin_id, action, Some(target), details).await {
+        error!("Failed to write audit entry {} for user {}: {}", action, target, e);
+    }
+}
+
+/// Handle admin.users.list messages
+pub async fn handle_list(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, _)) =
+            admin_request::<ListAdminUsersRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+
+        match queries::admin_user::list_users(&pool, &request.payload).await {
+            Ok((items, total)) => {
+                let response = SuccessResponse::new(request.id, ListAdminUsersResponse { items, total });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list users: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle admin.users.set_disabled messages
+pub async fn handle_set_disabled(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            admin_request::<SetUserDisabledRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+        let payload = &request.payload;
+
+        if payload.disabled && payload.user_id == auth_info.user_id {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "You cannot disable your own account");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::admin_user::set_disabled(&pool, payload.user_id, payload.disabled).await {
+            Ok(true) => {
+                let action = if payload.disabled { AUDIT_USER_DISABLED } else { AUDIT_USER_ENABLED };
+                audit(&pool, auth_info.user_id, action, payload.user_id, json!({ "reason": payload.reason })).await;
+                info!("Admin {} {} user {}", auth_info.user_id, action, payload.user_id);
+
+                let response = SuccessResponse::new(request.id, AdminUserActionResponse {
+                    user_id: payload.user_id,
+                    ok: true,
+                });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(false) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "User not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to update disabled state: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle admin.users.reset_password messages - emails a reset link to the user
+pub async fn handle_reset_password(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    email_sender: Arc<dyn EmailSender>,
+    app_base_url: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            admin_request::<AdminUserIdRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = request.payload.user_id;
+
+        let (token, token_hash) = super::onboarding::generate_token();
+        let expires = Utc::now() + chrono::Duration::hours(PASSWORD_RESET_TTL_HOURS);
+
+        let (email, locale) = match queries::admin_user::set_password_reset_token(&pool, user_id, &token_hash, expires).await {
+            Ok(Some(row)) => row,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "User not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to store password reset token: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let reset_url = format!("{}/reset-password?token={}", app_base_url.trim_end_matches('/'), token);
+        let email_msg = PasswordResetEmail {
+            to: &email,
+            reset_url: &reset_url,
+            locale: &locale,
+        }
+        .render();
+        if let Err(e) = email_sender.send(email_msg).await {
+            warn!("Failed to send password reset email to {}: {}", email, e);
+            let error = ErrorResponse::new(request.id, "EMAIL_FAILED", e.to_string());
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        audit(&pool, auth_info.user_id, AUDIT_PASSWORD_RESET_SENT, user_id, json!({ "expiresAt": expires })).await;
+        info!("Admin {} sent password reset to user {}", auth_info.user_id, user_id);
+
+        let response = SuccessResponse::new(request.id, AdminUserActionResponse { user_id, ok: true });
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle admin.users.resend_verification messages - emails a new verification link
+pub async fn handle_resend_verification(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    email_sender: Arc<dyn EmailSender>,
+    app_base_url: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            admin_request::<AdminUserIdRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = request.payload.user_id;
+
+        match queries::user::get_user(&pool, user_id).await {
+            Ok(Some(user)) if user.email_verified => {
+                let error = ErrorResponse::new(request.id, "ALREADY_VERIFIED", "Email is already verified");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "User not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load user {}: {}", user_id, e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        let (token, token_hash) = super::onboarding::generate_token();
+        let expires = Utc::now() + chrono::Duration::hours(VERIFICATION_TTL_HOURS);
+
+        let (email, locale) = match queries::admin_user::set_verification_token(&pool, user_id, &token_hash, expires).await {
+            Ok(Some(row)) => row,
+            Ok(None) => {
+                // Verified between the two queries
+                let error = ErrorResponse::new(request.id, "ALREADY_VERIFIED", "Email is already verified");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to store verification token: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let verify_url = super::onboarding::build_verify_url(&app_base_url, &token);
+        let email_msg = VerificationEmail {
+            to: &email,
+            verify_url: &verify_url,
+            locale: &locale,
+        }
+        .render();
+        if let Err(e) = email_sender.send(email_msg).await {
+            warn!("Failed to send verification email to {}: {}", email, e);
+            let error = ErrorResponse::new(request.id, "EMAIL_FAILED", e.to_string());
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        audit(&pool, auth_info.user_id, AUDIT_VERIFICATION_RESENT, user_id, json!({ "expiresAt": expires })).await;
+        info!("Admin {} resent email verification to user {}", auth_info.user_id, user_id);
+
+        let response = SuccessResponse::new(request.id, AdminUserActionResponse { user_id, ok: true });
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle admin.users.set_role messages
+pub async fn handle_set_role(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            admin_request::<SetUserRoleRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+        let payload = &request.payload;
+
+        if !ASSIGNABLE_USER_ROLES.contains(&payload.role.as_str()) {
+            let error = ErrorResponse::new(
+                request.id,
+                "INVALID_REQUEST",
+                format!("role must be one of: {}", ASSIGNABLE_USER_ROLES.join(", ")),
+            );
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+        if payload.user_id == auth_info.user_id && payload.role != "admin" {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "You cannot remove your own admin role");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::admin_user::set_role(&pool, payload.user_id, &payload.role).await {
+            Ok(Some(previous)) => {
+                audit(
+                    &pool,
+                    auth_info.user_id,
+                    AUDIT_ROLE_CHANGED,
+                    payload.user_id,
+                    json!({ "from": previous, "to": payload.role }),
+                )
+                .await;
+                info!("Admin {} changed role of {} from {} to {}", auth_info.user_id, payload.user_id, previous, payload.role);
+
+                let response = SuccessResponse::new(request.id, AdminUserActionResponse {
+                    user_id: payload.user_id,
+                    ok: true,
+                });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "User not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to change role: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle admin.users.set_plan messages
+pub async fn handle_set_plan(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            admin_request::<SetUserPlanRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+        let payload = &request.payload;
+
+        if !ACCOUNT_PLANS.contains(&payload.plan.as_str()) {
+            let error = ErrorResponse::new(
+                request.id,
+                "INVALID_REQUEST",
+                format!("plan must be one of: {}", ACCOUNT_PLANS.join(", ")),
+            );
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::quota::set_plan(&pool, payload.user_id, &payload.plan).await {
+            Ok(Some(previous)) => {
+                audit(
+                    &pool,
+                    auth_info.user_id,
+                    AUDIT_PLAN_CHANGED,
+                    payload.user_id,
+                    json!({ "from": previous, "to": payload.plan }),
+                )
+                .await;
+                info!("Admin {} moved {} from plan {} to {}", auth_info.user_id, payload.user_id, previous, payload.plan);
+
+                let response = SuccessResponse::new(request.id, AdminUserActionResponse {
+                    user_id: payload.user_id,
+                    ok: true,
+                });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "User not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to change plan: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Account addressed by user id or billing customer reference
+async fn resolve_account(pool: &PgPool, user_id: Option<Uuid>, billing_customer_ref: Option<&str>) -> Result<Option<Uuid>> {
+    match (user_id, billing_customer_ref) {
+        (Some(id), _) => Ok(Some(id)),
+        (None, Some(reference)) => queries::subscription::find_by_billing_ref(pool, reference).await,
+        (None, None) => Ok(None),
+    }
+}
+
+/// Handle admin.subscription.set messages - called by the billing integration
+pub async fn handle_set_subscription(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            admin_request::<SetSubscriptionRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+        let payload = &request.payload;
+        let billing_ref = payload.billing_customer_ref.as_deref().map(str::trim).filter(|r| !r.is_empty());
+
+        if !SUBSCRIPTION_STATES.contains(&payload.status.as_str()) {
+            let error = ErrorResponse::new(
+                request.id,
+                "INVALID_REQUEST",
+                format!("status must be one of: {}", SUBSCRIPTION_STATES.join(", ")),
+            );
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+        if payload.user_id.is_none() && billing_ref.is_none() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "userId or billingCustomerRef is required");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let user_id = match resolve_account(&pool, payload.user_id, billing_ref).await {
+            Ok(Some(id)) => id,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "User not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to resolve billing account: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::subscription::set_status(&pool, user_id, &payload.status, billing_ref).await {
+            Ok(Some(previous)) => {
+                // Billing systems redeliver; only real transitions are audited and emitted
+                if previous != payload.status {
+                    audit(
+                        &pool,
+                        auth_info.user_id,
+                        AUDIT_SUBSCRIPTION_CHANGED,
+                        user_id,
+                        json!({ "from": previous, "to": payload.status, "reason": payload.reason }),
+                    )
+                    .await;
+                    info!("Subscription of {} moved from {} to {}", user_id, previous, payload.status);
+
+                    let event = SubscriptionChangedEvent {
+                        user_id,
+                        from: previous,
+                        to: payload.status.clone(),
+                        billing_customer_ref: billing_ref.map(str::to_string),
+                        reason: payload.reason.clone(),
+                        read_only: is_read_only_state(&payload.status),
+                    };
+                    subscription::publish_transition(&client, &event).await;
+                }
+
+                let response = SuccessResponse::new(request.id, AdminUserActionResponse { user_id, ok: true });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "User not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                // Unique index on billing_customer_ref rejects a reference taken by another account
+                error!("Failed to set subscription state: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle admin.subscription.get messages
+pub async fn handle_get_subscription(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, _)) =
+            admin_request::<GetSubscriptionRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+        let payload = &request.payload;
+
+        let result = match resolve_account(&pool, payload.user_id, payload.billing_customer_ref.as_deref()).await {
+            Ok(Some(user_id)) => queries::subscription::get(&pool, user_id).await,
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(Some(info)) => {
+                let response = SuccessResponse::new(request.id, info.with_read_only());
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "User not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to load subscription: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle admin.audit.list messages
+pub async fn handle_audit_list(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, _)) =
+            admin_request::<ListAdminAuditRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+
+        let limit = request.payload.limit.unwrap_or(100).clamp(1, 1000);
+        match queries::admin_user::list_audit(&pool, request.payload.target_user_id, limit).await {
+            Ok(items) => {
+                let response = SuccessResponse::new(request.id, ListAdminAuditResponse { items });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list audit log: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
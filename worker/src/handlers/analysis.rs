@@ -0,0 +1,163 @@
+//! Service area analysis handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::geo::point_in_rings;
+use crate::services::routing::{RoutingService, ValhallaClient};
+use crate::subjects;
+use crate::types::analysis::{IsochroneAnalysisRequest, IsochroneAnalysisResponse, IsochroneProspectResult};
+use crate::types::{Coordinates, ErrorResponse, Request, SuccessResponse};
+
+/// Start all analysis NATS handlers
+pub async fn start_handlers(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    routing_service: Arc<dyn RoutingService>,
+) -> Result<()> {
+    info!("Starting analysis handlers...");
+
+    let isochrone_sub = client.subscribe(subjects::analysis::ISOCHRONE).await?;
+
+    tokio::spawn(handle_isochrone(client.clone(), isochrone_sub, pool, jwt_secret, routing_service));
+
+    info!("Analysis handlers started");
+    Ok(())
+}
+
+/// Handle analysis.isochrone messages
+pub async fn handle_isochrone(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    routing_service: Arc<dyn RoutingService>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received analysis.isochrone message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<IsochroneAnalysisRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = &request.payload;
+        if let Err(msg) = payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        // Isochrones need the real road network; the mock router has none
+        let Some(valhalla) = routing_service.as_any().downcast_ref::<ValhallaClient>() else {
+            let error = ErrorResponse::new(request.id, "ROUTING_UNAVAILABLE", "Isochrone analysis requires Valhalla");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        };
+
+        let depot = match payload.depot_id {
+            Some(depot_id) => queries::settings::get_depot(&pool, depot_id, user_id).await,
+            None => queries::settings::get_primary_depot(&pool, user_id).await,
+        };
+        let depot = match depot {
+            Ok(Some(depot)) => depot,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Depot not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load depot for isochrone: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let center = Coordinates { lat: depot.lat, lng: depot.lng };
+        let polygon = match valhalla.get_isochrone(&center, payload.max_minutes).await {
+            Ok(polygon) => polygon,
+            Err(e) => {
+                error!("Failed to compute isochrone: {}", e);
+                let error = ErrorResponse::new(request.id, "ROUTING_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let customers = if payload.include_customers {
+            match queries::customer::list_located_customers(&pool, user_id).await {
+                Ok(customers) => customers,
+                Err(e) => {
+                    error!("Failed to list customers for isochrone: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        let (customers_inside, outside): (Vec<_>, Vec<_>) = customers
+            .into_iter()
+            .partition(|c| point_in_rings(&Coordinates { lat: c.lat, lng: c.lng }, &polygon));
+        let prospects = payload
+            .prospects
+            .iter()
+            .map(|p| IsochroneProspectResult {
+                label: p.label.clone(),
+                lat: p.lat,
+                lng: p.lng,
+                reachable: point_in_rings(&Coordinates { lat: p.lat, lng: p.lng }, &polygon),
+            })
+            .collect();
+
+        let response = SuccessResponse::new(
+            request.id,
+            IsochroneAnalysisResponse {
+                depot_id: depot.id,
+                depot_name: depot.name,
+                max_minutes: payload.max_minutes,
+                polygon,
+                customers_inside,
+                customers_outside_count: outside.len(),
+                prospects,
+            },
+        );
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
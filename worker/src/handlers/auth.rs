@@ -1,9 +1,9 @@
 #![allow(dead_code)]
-//! Authentication handlers: register, login, verify
+//! Authentication handlers: register, login, verify, login history
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use async_nats::{Client, Subscriber};
@@ -15,9 +15,14 @@ use uuid::Uuid;
 
 use crate::auth;
 use crate::db::queries;
+use crate::services::email_sender::EmailSender;
+use crate::services::email_templates::NewDeviceLoginEmail;
 use crate::types::{
     ErrorResponse, Request, SuccessResponse,
+    organization::{OrganizationMembership, ORG_ROLE_OWNER},
     user::{AuthResponse, UserPublic},
+    ListLoginsRequest, ListLoginsResponse,
+    LOGIN_FAILURE_DISABLED, LOGIN_FAILURE_INVALID_PASSWORD, LOGIN_FAILURE_LOCKED,
 };
 
 // =============================================================================
@@ -26,7 +31,7 @@ use crate::types::{
 
 use serde::{Deserialize, Serialize};
 
-fn is_duplicate_email_error(err: &anyhow::Error) -> bool {
+pub(crate) fn is_duplicate_email_error(err: &anyhow::Error) -> bool {
     let Some(sqlx_err) = err.downcast_ref::<sqlx::Error>() else {
         return false;
     };
@@ -67,7 +72,10 @@ pub struct VerifyRequest {
 // Rate limiting
 // =============================================================================
 
-/// Simple in-memory rate limiter for login attempts
+/// In-memory rate limiter and lockout policy for login attempts.
+///
+/// State is kept per worker process: with N workers an attacker gets up to
+/// N times the attempts and failures before being limited or locked out.
 pub struct RateLimiter {
     /// Map of email -> list of attempt timestamps
     attempts: Mutex<HashMap<String, Vec<Instant>>>,
@@ -75,6 +83,14 @@ pub struct RateLimiter {
     max_attempts: usize,
     /// Window duration in seconds
     window_secs: u64,
+    /// Map of email -> timestamps of failed password checks
+    failures: Mutex<HashMap<String, Vec<Instant>>>,
+    /// Map of email -> end of the current lockout
+    locked_until: Mutex<HashMap<String, Instant>>,
+    /// Failures within `failure_window_secs` that lock the account (0 = never)
+    max_failures: usize,
+    failure_window_secs: u64,
+    lockout_secs: u64,
 }
 
 impl RateLimiter {
@@ -83,9 +99,23 @@ impl RateLimiter {
             attempts: Mutex::new(HashMap::new()),
             max_attempts,
             window_secs,
+            failures: Mutex::new(HashMap::new()),
+            locked_until: Mutex::new(HashMap::new()),
+            max_failures: 0,
+            failure_window_secs: 0,
+            lockout_secs: 0,
         }
     }
 
+    /// Lock an account for `lockout_secs` after `max_failures` failed
+    /// password checks within `failure_window_secs`.
+    pub fn with_lockout(mut self, max_failures: usize, failure_window_secs: u64, lockout_secs: u64) -> Self {
+        self.max_failures = max_failures;
+        self.failure_window_secs = failure_window_secs;
+        self.lockout_secs = lockout_secs;
+        self
+    }
+
     /// Check if the given key is rate limited. Returns true if allowed, false if rate limited.
     pub fn check_and_record(&self, key: &str) -> bool {
         let mut attempts = self.attempts.lock();
@@ -105,6 +135,50 @@ impl RateLimiter {
         true
     }
 
+    /// Remaining lockout time for the key, if it is locked
+    pub fn locked_for(&self, key: &str) -> Option<Duration> {
+        let mut locked = self.locked_until.lock();
+        let now = Instant::now();
+        match locked.get(key) {
+            Some(until) if *until > now => Some(*until - now),
+            Some(_) => {
+                locked.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Record a failed password check. Returns true if this failure locked the key.
+    pub fn record_failure(&self, key: &str) -> bool {
+        if self.max_failures == 0 {
+            return false;
+        }
+
+        let mut failures = self.failures.lock();
+        let now = Instant::now();
+        let window = Duration::from_secs(self.failure_window_secs);
+
+        let entry = failures.entry(key.to_string()).or_default();
+        entry.retain(|t| now.duration_since(*t) < window);
+        entry.push(now);
+
+        if entry.len() < self.max_failures {
+            return false;
+        }
+
+        entry.clear();
+        self.locked_until
+            .lock()
+            .insert(key.to_string(), now + Duration::from_secs(self.lockout_secs));
+        true
+    }
+
+    /// Forget failures after a successful login
+    pub fn record_success(&self, key: &str) {
+        self.failures.lock().remove(key);
+    }
+
     /// Clean up old entries (call periodically)
     pub fn cleanup(&self) {
         let mut attempts = self.attempts.lock();
@@ -115,6 +189,13 @@ impl RateLimiter {
             entries.retain(|t| now.duration_since(*t) < window);
             !entries.is_empty()
         });
+
+        let failure_window = Duration::from_secs(self.failure_window_secs);
+        self.failures.lock().retain(|_, entries| {
+            entries.retain(|t| now.duration_since(*t) < failure_window);
+            !entries.is_empty()
+        });
+        self.locked_until.lock().retain(|_, until| *until > now);
     }
 }
 
@@ -213,8 +294,19 @@ pub async fn handle_register(
                         continue;
                     }
                 };
+                // Every new owner account starts its own organization
+                let membership = match queries::organization::ensure_for_owner(&pool, user.id).await {
+                    Ok(organization) => Some(OrganizationMembership {
+                        organization_id: organization.id,
+                        role: ORG_ROLE_OWNER.to_string(),
+                    }),
+                    Err(e) => {
+                        warn!("Failed to create organization of {}: {}", user.id, e);
+                        None
+                    }
+                };
                 // Generate JWT
-                let token = match auth::generate_token(user.id, &user.email, &user.role, None, &permissions, &user.locale, user.email_verified, &jwt_secret) {
+                let token = match auth::generate_token(user.id, &user.email, &user.role, None, &permissions, &user.locale, user.email_verified, membership.as_ref(), &jwt_secret) {
                     Ok(t) => t,
                     Err(e) => {
                         error!("Failed to generate token: {}", e);
@@ -257,6 +349,7 @@ pub async fn handle_login(
     pool: PgPool,
     jwt_secret: Arc<String>,
     rate_limiter: Arc<RateLimiter>,
+    email_sender: Arc<dyn EmailSender>,
 ) -> Result<()> {
     while let Some(msg) = subscriber.next().await {
         debug!("Received auth.login message");
@@ -289,10 +382,31 @@ pub async fn handle_login(
             continue;
         }
 
+        let account_key = payload.email.trim().to_lowercase();
+        let client_ip = request.client_ip.as_deref();
+        let user_agent = request.user_agent.as_deref();
+
+        // Checked before the lookup so known and unknown emails lock out alike
+        if let Some(remaining) = rate_limiter.locked_for(&account_key) {
+            if let Ok(Some(user)) = queries::user::get_user_by_email(&pool, &payload.email).await {
+                record_login(&pool, user.id, Some(LOGIN_FAILURE_LOCKED), client_ip, user_agent, false).await;
+            }
+            let minutes = remaining.as_secs().div_ceil(60);
+            let error = ErrorResponse::new(
+                request.id,
+                "ACCOUNT_LOCKED",
+                format!("Too many failed login attempts. Try again in {} minutes.", minutes),
+            );
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
         // Look up user by email
         let user = match queries::user::get_user_by_email(&pool, &payload.email).await {
             Ok(Some(user)) => user,
             Ok(None) => {
+                // Unknown emails count towards lockout too, so a lockout reveals nothing
+                rate_limiter.record_failure(&account_key);
                 let error = ErrorResponse::new(request.id, "INVALID_CREDENTIALS", "Invalid email or password");
                 let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
                 continue;
@@ -309,6 +423,10 @@ pub async fn handle_login(
         match auth::verify_password(&payload.password, &user.password_hash) {
             Ok(true) => {} // Password correct
             Ok(false) => {
+                if rate_limiter.record_failure(&account_key) {
+                    warn!("Account {} locked after repeated failed logins", user.id);
+                }
+                record_login(&pool, user.id, Some(LOGIN_FAILURE_INVALID_PASSWORD), client_ip, user_agent, false).await;
                 let error = ErrorResponse::new(request.id, "INVALID_CREDENTIALS", "Invalid email or password");
                 let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
                 continue;
@@ -321,6 +439,41 @@ pub async fn handle_login(
             }
         }
 
+        // Checked after the password so disabled accounts are not enumerable
+        if user.disabled_at.is_some() {
+            record_login(&pool, user.id, Some(LOGIN_FAILURE_DISABLED), client_ip, user_agent, false).await;
+            let error = ErrorResponse::new(request.id, "ACCOUNT_DISABLED", "This account has been disabled");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        rate_limiter.record_success(&account_key);
+
+        // The very first login is not "new"; afterwards an unseen user agent is
+        let new_device = match queries::login_event::device_history(&pool, user.id, user_agent).await {
+            Ok((has_previous, known)) => has_previous && !known,
+            Err(e) => {
+                warn!("Failed to check login history for {}: {}", user.id, e);
+                false
+            }
+        };
+        record_login(&pool, user.id, None, client_ip, user_agent, new_device).await;
+        if new_device {
+            let email_msg = NewDeviceLoginEmail {
+                to: &user.email,
+                device: user_agent,
+                ip: client_ip,
+                locale: &user.locale,
+            }
+            .render();
+            let sender = Arc::clone(&email_sender);
+            tokio::spawn(async move {
+                if let Err(e) = sender.send(email_msg).await {
+                    warn!("Failed to send new device login email: {}", e);
+                }
+            });
+        }
+
         let permissions = match queries::role::get_user_permissions(&pool, user.id).await {
             Ok(p) => p,
             Err(e) => {
@@ -331,8 +484,13 @@ pub async fn handle_login(
             }
         };
 
+        if let Err(e) = queries::user::touch_last_login(&pool, user.id).await {
+            warn!("Failed to record last login for {}: {}", user.id, e);
+        }
+
         // Generate JWT
-        let token = match auth::generate_token(user.id, &user.email, &user.role, user.owner_id, &permissions, &user.locale, user.email_verified, &jwt_secret) {
+        let membership = load_membership(&pool, user.id).await;
+        let token = match auth::generate_token(user.id, &user.email, &user.role, user.owner_id, &permissions, &user.locale, user.email_verified, membership.as_ref(), &jwt_secret) {
             Ok(t) => t,
             Err(e) => {
                 error!("Failed to generate token: {}", e);
@@ -356,6 +514,92 @@ pub async fn handle_login(
     Ok(())
 }
 
+/// Organization of a login for its token; a failed lookup only leaves the claims out
+async fn load_membership(pool: &PgPool, user_id: Uuid) -> Option<OrganizationMembership> {
+    match queries::organization::get_membership(pool, user_id).await {
+        Ok(membership) => membership,
+        Err(e) => {
+            warn!("Failed to load organization of {}: {}", user_id, e);
+            None
+        }
+    }
+}
+
+/// Write a login history entry; failures are logged, never surfaced to the caller
+async fn record_login(
+    pool: &PgPool,
+    user_id: Uuid,
+    failure_reason: Option<&str>,
+    client_ip: Option<&str>,
+    user_agent: Option<&str>,
+    new_device: bool,
+) {
+    if let Err(e) =
+        queries::login_event::insert_event(pool, user_id, failure_reason, client_ip, user_agent, new_device).await
+    {
+        warn!("Failed to record login for {}: {}", user_id, e);
+    }
+}
+
+/// Handle auth.logins.list messages - recent login history of the caller
+pub async fn handle_list_logins(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received auth.logins.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ListLoginsRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse logins list request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        // Workers see their own logins, not their owner's
+        let user_id = match request.payload.user_id {
+            Some(id) if auth_info.role == "admin" => id,
+            _ => auth_info.user_id,
+        };
+
+        match queries::login_event::list_for_user(&pool, user_id, request.payload.effective_limit()).await {
+            Ok(items) => {
+                let response = SuccessResponse::new(request.id, ListLoginsResponse { items });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list logins: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Handle auth.verify messages
 pub async fn handle_verify(
     client: Client,
@@ -405,6 +649,10 @@ pub async fn handle_verify(
         };
 
         match queries::user::get_user(&pool, user_id).await {
+            Ok(Some(user)) if user.disabled_at.is_some() => {
+                let error = ErrorResponse::new(request.id, "ACCOUNT_DISABLED", "This account has been disabled");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
             Ok(Some(user)) => {
                 let mut user_public = UserPublic::from(user.clone());
                 user_public.permissions = queries::role::get_user_permissions(&pool, user.id).await.unwrap_or_default();
@@ -473,8 +721,12 @@ pub async fn handle_refresh(
             }
         };
 
-        // Ensure user still exists
+        // Ensure user still exists and is allowed in
         match queries::user::get_user(&pool, user_id).await {
+            Ok(Some(user)) if user.disabled_at.is_some() => {
+                let error = ErrorResponse::new(request.id, "ACCOUNT_DISABLED", "This account has been disabled");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
             Ok(Some(user)) => {
                 let permissions = match queries::role::get_user_permissions(&pool, user.id).await {
                     Ok(p) => p,
@@ -487,7 +739,8 @@ pub async fn handle_refresh(
                 };
                 // Issue a fresh token
                 let owner_id = claims.owner_id.as_deref().and_then(|id| Uuid::parse_str(id).ok());
-                match auth::generate_token(user_id, &user.email, &claims.role, owner_id, &permissions, &user.locale, user.email_verified, &jwt_secret) {
+                let membership = load_membership(&pool, user.id).await;
+                match auth::generate_token(user_id, &user.email, &claims.role, owner_id, &permissions, &user.locale, user.email_verified, membership.as_ref(), &jwt_secret) {
                     Ok(new_token) => {
                         let mut user_public = UserPublic::from(user);
                         user_public.permissions = permissions;
@@ -578,6 +831,12 @@ pub async fn handle_create_worker(
             continue;
         }
 
+        if auth_info.requires_email_verification() {
+            let error = ErrorResponse::new(request.id, "EMAIL_NOT_VERIFIED", "Verify your email address before adding workers");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
         let payload = &request.payload;
 
         // Validate
@@ -631,6 +890,9 @@ pub async fn handle_create_worker(
             None, // Workers inherit owner's locale by default
         ).await {
             Ok(user) => {
+                if let Err(e) = queries::organization::add_worker(&pool, auth_info.user_id, user.id).await {
+                    warn!("Failed to add worker {} to the organization: {}", user.id, e);
+                }
                 let response = SuccessResponse::new(request.id, UserPublic::from(user));
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
             }
@@ -820,4 +1082,42 @@ mod tests {
         std::thread::sleep(std::time::Duration::from_millis(1100));
         assert!(limiter.check_and_record("test@example.com"));
     }
+
+    #[test]
+    fn test_lockout_after_max_failures() {
+        let limiter = RateLimiter::new(100, 60).with_lockout(3, 60, 60);
+        assert!(!limiter.record_failure("test@example.com"));
+        assert!(!limiter.record_failure("test@example.com"));
+        assert!(limiter.locked_for("test@example.com").is_none());
+
+        assert!(limiter.record_failure("test@example.com"));
+        assert!(limiter.locked_for("test@example.com").is_some());
+        assert!(limiter.locked_for("other@example.com").is_none());
+    }
+
+    #[test]
+    fn test_lockout_success_resets_failures() {
+        let limiter = RateLimiter::new(100, 60).with_lockout(2, 60, 60);
+        assert!(!limiter.record_failure("test@example.com"));
+        limiter.record_success("test@example.com");
+        assert!(!limiter.record_failure("test@example.com"));
+        assert!(limiter.locked_for("test@example.com").is_none());
+    }
+
+    #[test]
+    fn test_lockout_disabled_by_default() {
+        let limiter = RateLimiter::new(100, 60);
+        for _ in 0..10 {
+            assert!(!limiter.record_failure("test@example.com"));
+        }
+        assert!(limiter.locked_for("test@example.com").is_none());
+    }
+
+    #[test]
+    fn test_lockout_expires() {
+        let limiter = RateLimiter::new(100, 60).with_lockout(1, 60, 0);
+        assert!(limiter.record_failure("test@example.com"));
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert!(limiter.locked_for("test@example.com").is_none());
+    }
 }
@@ -0,0 +1,366 @@
+//! Automation rule handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::{automation, leader};
+use crate::subjects;
+use crate::types::automation::{
+    validate_rule_fields, AutomationRuleIdRequest, CreateAutomationRuleRequest, ListAutomationRulesResponse,
+    ListAutomationRunsRequest, ListAutomationRunsResponse, UpdateAutomationRuleRequest,
+};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Start all automation NATS handlers and the overdue scan
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting automation handlers...");
+
+    let [create_sub, list_sub, update_sub, delete_sub, runs_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::automation::RULE_CREATE,
+            subjects::automation::RULE_LIST,
+            subjects::automation::RULE_UPDATE,
+            subjects::automation::RULE_DELETE,
+            subjects::automation::RUN_LIST,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_create(client.clone(), create_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_list(client.clone(), list_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_update(client.clone(), update_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_delete(client.clone(), delete_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_list_runs(client.clone(), runs_sub, pool.clone(), jwt_secret.clone()));
+
+    leader::spawn_singleton(pool, "automation_overdue_scan", move |pool| {
+        automation::run_scheduler(pool, client.clone())
+    });
+
+    info!("Automation handlers started");
+    Ok(())
+}
+
+/// Handle automation.rule.create messages
+pub async fn handle_create(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received automation.rule.create message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CreateAutomationRuleRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can manage automation rules");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let payload = &request.payload;
+        if let Err(msg) = validate_rule_fields(
+            Some(&payload.name),
+            Some(&payload.trigger),
+            Some(&payload.conditions),
+            Some(&payload.actions),
+        ) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::automation::create_rule(&pool, auth_info.data_user_id(), payload).await {
+            Ok(rule) => {
+                let response = SuccessResponse::new(request.id, rule);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to create automation rule: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle automation.rule.list messages
+pub async fn handle_list(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received automation.rule.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::automation::list_rules(&pool, user_id).await {
+            Ok(rules) => {
+                let response = SuccessResponse::new(request.id, ListAutomationRulesResponse { rules });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list automation rules: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle automation.rule.update messages
+pub async fn handle_update(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received automation.rule.update message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<UpdateAutomationRuleRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can manage automation rules");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let payload = &request.payload;
+        if let Err(msg) = validate_rule_fields(
+            payload.name.as_deref(),
+            payload.trigger.as_deref(),
+            payload.conditions.as_deref(),
+            payload.actions.as_deref(),
+        ) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::automation::update_rule(&pool, auth_info.data_user_id(), payload).await {
+            Ok(Some(rule)) => {
+                let response = SuccessResponse::new(request.id, rule);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Automation rule not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to update automation rule: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle automation.rule.delete messages
+pub async fn handle_delete(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received automation.rule.delete message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<AutomationRuleIdRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can manage automation rules");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::automation::delete_rule(&pool, auth_info.data_user_id(), request.payload.id).await {
+            Ok(true) => {
+                let response = SuccessResponse::new(request.id, serde_json::json!({ "deleted": true }));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(false) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Automation rule not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to delete automation rule: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle automation.run.list messages - execution log
+pub async fn handle_list_runs(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received automation.run.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ListAutomationRunsRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let limit = request.payload.limit.unwrap_or(50).clamp(1, 500);
+        match queries::automation::list_runs(&pool, user_id, request.payload.rule_id, limit).await {
+            Ok(runs) => {
+                let response = SuccessResponse::new(request.id, ListAutomationRunsResponse { runs });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list automation runs: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,358 @@
+//! Calendar feed handlers for NATS messages
+//!
+//! The company owner creates iCal feeds of the work calendar: routes and
+//! visits of all crews (or selected ones), categorized and colored per crew,
+//! with an optional daily capacity overlay. Calendar apps fetch a feed by
+//! the secret token in its URL (`sazinka.portal.calendar.feed`, served over
+//! HTTP by the gateway); revoking a feed invalidates the token.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use chrono::{Duration, NaiveDate, Utc};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::onboarding::generate_token;
+use super::parse_owner;
+use super::slots::{crew_calendar_days, DEFAULT_CALENDAR_BLOCK_MINUTES};
+use crate::db::queries;
+use crate::db::repo::Repositories;
+use crate::services::calendar_feed::{self, CrewDayLoad, CAPACITY_DAYS, FEED_FUTURE_DAYS, FEED_PAST_DAYS};
+use crate::services::ical;
+use crate::services::rate_limiter::RateLimiter;
+use crate::subjects;
+use crate::types::calendar_feed::{
+    CreateCalendarFeedRequest, CreateCalendarFeedResponse, ListCalendarFeedsResponse, PortalCalendarFeedResponse,
+    ResolvedCalendarFeed, RevokeCalendarFeedRequest, MAX_CALENDAR_FEEDS_PER_USER,
+};
+use crate::types::{ErrorResponse, PortalTokenRequest, Request, SuccessResponse};
+
+/// Fetches per feed token and window; calendar apps poll every few minutes at most
+const FEED_FETCHES_PER_WINDOW: usize = 30;
+const FEED_FETCH_WINDOW_SECS: u64 = 300;
+/// A feed shows the work of the whole company to anyone holding its URL
+const OWNER_ONLY: &str = "Only company owners can manage calendar feeds";
+
+/// Shared by all calendar feed handlers
+#[derive(Clone)]
+pub struct CalendarFeedContext {
+    pub pool: PgPool,
+    pub repos: Repositories,
+    pub jwt_secret: Arc<String>,
+    /// Public base URL of the feed endpoint
+    pub feed_base_url: Arc<String>,
+    pub rate_limiter: Arc<RateLimiter>,
+}
+
+/// Start all calendar feed NATS handlers
+pub async fn start_handlers(
+    client: Client,
+    pool: PgPool,
+    repos: Repositories,
+    jwt_secret: Arc<String>,
+    feed_base_url: Arc<String>,
+) -> Result<()> {
+    info!("Starting calendar feed handlers...");
+
+    let rate_limiter = Arc::new(RateLimiter::new(FEED_FETCHES_PER_WINDOW, FEED_FETCH_WINDOW_SECS));
+    let ctx = CalendarFeedContext { pool, repos, jwt_secret, feed_base_url, rate_limiter };
+
+    let [create_sub, list_sub, revoke_sub, feed_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::calendar_feed::CREATE,
+            subjects::calendar_feed::LIST,
+            subjects::calendar_feed::REVOKE,
+            subjects::portal::CALENDAR_FEED,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_create(client.clone(), create_sub, ctx.clone()));
+    tokio::spawn(handle_list(client.clone(), list_sub, ctx.clone()));
+    tokio::spawn(handle_revoke(client.clone(), revoke_sub, ctx.clone()));
+    tokio::spawn(handle_portal_feed(client.clone(), feed_sub, ctx));
+
+    info!("Calendar feed handlers started");
+    Ok(())
+}
+
+/// Subscription URL of a feed token
+fn build_feed_url(feed_base_url: &str, token: &str) -> String {
+    format!("{}/calendar/{}.ics", feed_base_url.trim_end_matches('/'), token)
+}
+
+/// Hash of a feed token, as stored
+fn hash_feed_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(token.trim().as_bytes()))
+}
+
+/// Handle calendar_feed.create messages
+pub async fn handle_create(client: Client, mut subscriber: Subscriber, ctx: CalendarFeedContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received calendar_feed.create message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_owner::<CreateCalendarFeedRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret, OWNER_ONLY).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+        let payload = &request.payload;
+
+        if let Err(msg) = payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let checked = async {
+            let crews = queries::crew::list_crews(&ctx.pool, user_id, false).await?;
+            let count = queries::calendar_feed::count_active_feeds(&ctx.pool, user_id).await?;
+            anyhow::Ok((crews, count))
+        }
+        .await;
+        match checked {
+            Ok((crews, _)) if payload.crew_ids.iter().any(|id| !crews.iter().any(|c| c.id == *id)) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "Unknown crew");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Ok((_, count)) if count >= MAX_CALENDAR_FEEDS_PER_USER => {
+                let error = ErrorResponse::new(
+                    request.id,
+                    "LIMIT_EXCEEDED",
+                    format!("At most {} calendar feeds can be active", MAX_CALENDAR_FEEDS_PER_USER),
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!("Failed to check calendar feed: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        let (token, token_hash) = generate_token();
+        match queries::calendar_feed::create_feed(
+            &ctx.pool,
+            user_id,
+            payload.name.trim(),
+            &token_hash,
+            &payload.crew_ids,
+            payload.include_capacity,
+        )
+        .await
+        {
+            Ok(feed) => {
+                info!("Calendar feed {} created", feed.id);
+                let response = SuccessResponse::new(request.id, CreateCalendarFeedResponse {
+                    feed,
+                    url: build_feed_url(&ctx.feed_base_url, &token),
+                });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to create calendar feed: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle calendar_feed.list messages
+pub async fn handle_list(client: Client, mut subscriber: Subscriber, ctx: CalendarFeedContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received calendar_feed.list message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_owner::<serde_json::Value>(&client, &reply, &msg.payload, &ctx.jwt_secret, OWNER_ONLY).await?
+        else {
+            continue;
+        };
+
+        match queries::calendar_feed::list_feeds(&ctx.pool, auth_info.data_user_id()).await {
+            Ok(feeds) => {
+                let response = SuccessResponse::new(request.id, ListCalendarFeedsResponse { feeds });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list calendar feeds: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle calendar_feed.revoke messages
+pub async fn handle_revoke(client: Client, mut subscriber: Subscriber, ctx: CalendarFeedContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received calendar_feed.revoke message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_owner::<RevokeCalendarFeedRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret, OWNER_ONLY).await?
+        else {
+            continue;
+        };
+
+        match queries::calendar_feed::revoke_feed(&ctx.pool, auth_info.data_user_id(), request.payload.id).await {
+            Ok(true) => {
+                info!("Calendar feed {} revoked", request.payload.id);
+                let response = SuccessResponse::new(request.id, serde_json::json!({ "revoked": true }));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(false) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Calendar feed not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to revoke calendar feed: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle portal.calendar.feed messages - the iCal body behind a feed token
+pub async fn handle_portal_feed(client: Client, mut subscriber: Subscriber, ctx: CalendarFeedContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received portal.calendar.feed message");
+        let Some(reply) = msg.reply.clone() else { continue };
+
+        let request: Request<PortalTokenRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        // Bucket by token prefix so the full token isn't kept in memory
+        let token = request.payload.token.trim();
+        let bucket = token.chars().take(8).collect::<String>();
+        if !ctx.rate_limiter.check_and_record(&bucket) {
+            let error = ErrorResponse::new(request.id, "RATE_LIMITED", "Too many requests.");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let feed = match queries::calendar_feed::resolve_feed(&ctx.pool, &hash_feed_token(token)).await {
+            Ok(Some(feed)) => feed,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "INVALID_TOKEN", "Feed is invalid or was revoked");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to resolve calendar feed: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match render_feed(&ctx, &feed, Utc::now().date_naive()).await {
+            Ok(ics) => {
+                let response = SuccessResponse::new(request.id, PortalCalendarFeedResponse { ics });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to render calendar feed {}: {}", feed.id, e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Routes, visits and capacity of a feed as an iCal body
+async fn render_feed(ctx: &CalendarFeedContext, feed: &ResolvedCalendarFeed, today: NaiveDate) -> Result<String> {
+    let pool = &ctx.pool;
+    let user_id = feed.user_id;
+    let date_from = today - Duration::days(FEED_PAST_DAYS);
+    let date_to = today + Duration::days(FEED_FUTURE_DAYS);
+
+    let crews = queries::crew::list_crews(pool, user_id, false).await?;
+    let feed_crews = calendar_feed::feed_crews(&crews);
+    let included = |crew_id: Option<Uuid>| calendar_feed::includes_crew(&feed.crew_ids, crew_id);
+    let crew_of = |crew_id: Option<Uuid>| crew_id.and_then(|id| feed_crews.get(&id));
+
+    let mut events = vec![];
+    for route in queries::route::list_routes(pool, user_id, date_from, date_to, None, None).await? {
+        if !included(route.crew_id) {
+            continue;
+        }
+        let stops = ctx.repos.routes.get_route_stops_with_info(route.id).await?;
+        events.push(calendar_feed::route_event(&route, &stops, crew_of(route.crew_id)));
+    }
+
+    let (visits, total) =
+        queries::visit::list_visits(pool, user_id, None, Some(date_from), Some(date_to), None, None, 10_000, 0).await?;
+    if total > visits.len() as i64 {
+        warn!("Calendar feed {} truncated to {} of {} visits", feed.id, visits.len(), total);
+    }
+    for visit in visits.iter().filter(|v| v.status != "cancelled" && included(v.crew_id)) {
+        events.push(calendar_feed::visit_event(visit, crew_of(visit.crew_id)));
+    }
+
+    if feed.include_capacity {
+        let capacity_crews: Vec<_> = crews.iter().filter(|c| c.is_active && included(Some(c.id))).cloned().collect();
+        let capacity_to = today + Duration::days(CAPACITY_DAYS - 1);
+        let days =
+            crew_calendar_days(pool, &ctx.repos, user_id, &capacity_crews, today, capacity_to, DEFAULT_CALENDAR_BLOCK_MINUTES)
+                .await?;
+        for day in days {
+            let load = CrewDayLoad {
+                crew_id: day.crew_id,
+                date: day.date,
+                visit_count: day.visit_count,
+                capacity_minutes: day.capacity_minutes,
+                used_minutes: day.used_minutes,
+                load_percent: day.load_percent,
+            };
+            events.push(calendar_feed::capacity_event(&load, crew_of(Some(day.crew_id))));
+        }
+    }
+
+    Ok(ical::render_calendar(&feed.name, &events, Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_feed_url() {
+        assert_eq!(
+            build_feed_url("https://api.example.com/", "abc123"),
+            "https://api.example.com/calendar/abc123.ics"
+        );
+    }
+
+    #[test]
+    fn test_hash_feed_token_matches_generated_hash() {
+        let (token, hash) = generate_token();
+        assert_eq!(hash_feed_token(&token), hash);
+        assert_eq!(hash_feed_token(&format!(" {} ", token)), hash);
+    }
+}
@@ -0,0 +1,384 @@
+//! Campaign trigger handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::campaign::{self, CampaignQueues};
+use crate::services::leader;
+use crate::subjects;
+use crate::types::campaign::{
+    validate_campaign_message, CampaignIdRequest, CreateCampaignRequest, ListCampaignSendsRequest,
+    ListCampaignSendsResponse, ListCampaignsResponse, UpdateCampaignRequest, CAMPAIGN_TRIGGER_LAST_REVISION_AGE,
+};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Start all campaign NATS handlers and the campaign scheduler
+pub async fn start_handlers(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    queues: CampaignQueues,
+) -> Result<()> {
+    info!("Starting campaign handlers...");
+
+    let [create_sub, list_sub, update_sub, delete_sub, sends_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::campaign::CREATE,
+            subjects::campaign::LIST,
+            subjects::campaign::UPDATE,
+            subjects::campaign::DELETE,
+            subjects::campaign::SENDS_LIST,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_create(client.clone(), create_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_list(client.clone(), list_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_update(client.clone(), update_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_delete(client.clone(), delete_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_list_sends(client.clone(), sends_sub, pool.clone(), jwt_secret.clone()));
+
+    leader::spawn_singleton(pool, "campaign_scheduler", move |pool| campaign::run_scheduler(pool, queues.clone()));
+
+    info!("Campaign handlers started");
+    Ok(())
+}
+
+/// Handle campaign.create messages
+pub async fn handle_create(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received campaign.create message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CreateCampaignRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can manage campaigns");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        if let Err(msg) = request.payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::campaign::create_campaign(&pool, auth_info.data_user_id(), &request.payload).await {
+            Ok(campaign) => {
+                let response = SuccessResponse::new(request.id, campaign);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to create campaign: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle campaign.list messages
+pub async fn handle_list(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received campaign.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::campaign::list_campaigns(&pool, user_id).await {
+            Ok(campaigns) => {
+                let response = SuccessResponse::new(request.id, ListCampaignsResponse { campaigns });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list campaigns: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle campaign.update messages
+pub async fn handle_update(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received campaign.update message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<UpdateCampaignRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can manage campaigns");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let user_id = auth_info.data_user_id();
+        let payload = &request.payload;
+
+        // Trigger type and channel are fixed, so the checks depending on
+        // them run against the stored campaign
+        let existing = match queries::campaign::get_campaign(&pool, user_id, payload.id).await {
+            Ok(Some(campaign)) => campaign,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Campaign not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to get campaign: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let validation = payload.validate().and_then(|_| {
+            if existing.trigger_type == CAMPAIGN_TRIGGER_LAST_REVISION_AGE && payload.months_since_revision.is_none() {
+                return Err("monthsSinceRevision is required for this trigger".to_string());
+            }
+            validate_campaign_message(&existing.channel, payload.subject.as_deref(), &payload.body)
+        });
+        if let Err(msg) = validation {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::campaign::update_campaign(&pool, user_id, payload).await {
+            Ok(Some(campaign)) => {
+                let response = SuccessResponse::new(request.id, campaign);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Campaign not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to update campaign: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle campaign.delete messages
+pub async fn handle_delete(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received campaign.delete message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CampaignIdRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can manage campaigns");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::campaign::delete_campaign(&pool, auth_info.data_user_id(), request.payload.id).await {
+            Ok(true) => {
+                let response = SuccessResponse::new(request.id, serde_json::json!({ "deleted": true }));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(false) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Campaign not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to delete campaign: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle campaign.sends.list messages
+pub async fn handle_list_sends(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received campaign.sends.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ListCampaignSendsRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let limit = request.payload.limit.unwrap_or(50).clamp(1, 500);
+        match queries::campaign::list_sends(&pool, user_id, request.payload.campaign_id, limit).await {
+            Ok(sends) => {
+                let response = SuccessResponse::new(request.id, ListCampaignSendsResponse { sends });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list campaign sends: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+//! Revision certificate verification handler for NATS messages
+//!
+//! `sazinka.portal.certificate.verify` is public (reached through the HTTP
+//! gateway): a customer or an authority enters the report number and the
+//! verification code printed on it and learns whether the certificate is
+//! genuine and still valid. Nothing about the customer is revealed, and a
+//! wrong code answers exactly like an unknown number.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use chrono::Utc;
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::rate_limiter::{MultiRateLimiter, RateLimiterConfig};
+use crate::subjects;
+use crate::types::revision_number::{
+    certificate_status, verification_code_matches, VerifyCertificateRequest, VerifyCertificateResponse,
+};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Longest report number accepted, as stored
+const MAX_DOCUMENT_NUMBER_LENGTH: usize = 50;
+
+/// Shared by the certificate handlers
+#[derive(Clone)]
+pub struct CertificateContext {
+    pub pool: PgPool,
+    pub rate_limiter: Arc<MultiRateLimiter>,
+}
+
+/// Start the certificate verification NATS handler
+pub async fn start_handlers(client: Client, pool: PgPool) -> Result<()> {
+    info!("Starting certificate handlers...");
+
+    let rate_limiter = Arc::new(MultiRateLimiter::new(vec![
+        (
+            "certificate.ip",
+            RateLimiterConfig {
+                max_attempts: 20,
+                window_secs: 3600,
+            },
+        ),
+        // Guessing the code of one number from many addresses
+        (
+            "certificate.number",
+            RateLimiterConfig {
+                max_attempts: 10,
+                window_secs: 3600,
+            },
+        ),
+    ]));
+    let ctx = CertificateContext { pool, rate_limiter };
+
+    let [verify_sub] = subjects::subscribe_all(&client, [subjects::portal::CERTIFICATE_VERIFY]).await?;
+    tokio::spawn(handle_verify(client, verify_sub, ctx));
+
+    info!("Certificate handlers started");
+    Ok(())
+}
+
+/// Handle portal.certificate.verify messages (public, no login)
+pub async fn handle_verify(client: Client, mut subscriber: Subscriber, ctx: CertificateContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received portal.certificate.verify message");
+        let Some(reply) = msg.reply.clone() else { continue };
+
+        let request: Request<VerifyCertificateRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        let document_number = request.payload.document_number.trim();
+        if document_number.is_empty() || document_number.len() > MAX_DOCUMENT_NUMBER_LENGTH {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "Enter the report number");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let ip = request.client_ip.as_deref().unwrap_or("unknown");
+        if !ctx.rate_limiter.check_and_record("certificate.ip", ip)
+            || !ctx.rate_limiter.check_and_record("certificate.number", &document_number.to_uppercase())
+        {
+            let error = ErrorResponse::new(request.id, "RATE_LIMITED", "Too many requests.");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let found = match queries::revision_number::find_certificates(&ctx.pool, document_number).await {
+            Ok(records) => records
+                .into_iter()
+                .find(|r| verification_code_matches(&r.verification_code, &request.payload.verification_code)),
+            Err(e) => {
+                error!("Failed to look up certificate: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        let Some(record) = found else {
+            debug!("No certificate matched a verification attempt from {}", ip);
+            let error = ErrorResponse::new(request.id, "NOT_FOUND", "No certificate matches this number and code");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        };
+
+        let device_type = match record.device_type.as_deref() {
+            Some(key) => match queries::device_type_config::get_label(&ctx.pool, record.user_id, key).await {
+                Ok(label) => Some(label.unwrap_or_else(|| key.to_string())),
+                Err(e) => {
+                    error!("Failed to get device type label: {}", e);
+                    Some(key.to_string())
+                }
+            },
+            None => None,
+        };
+        let status = certificate_status(record.voided_at.is_some(), record.valid_until, Utc::now().date_naive());
+        let response = SuccessResponse::new(request.id, VerifyCertificateResponse {
+            document_number: record.document_number,
+            status: status.to_string(),
+            issued_on: record.issued_at.date_naive(),
+            valid_until: record.valid_until,
+            device_type,
+            result: record.result,
+        });
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
@@ -10,12 +10,15 @@ use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::auth;
+use super::{account, retention};
 use crate::db::queries;
+use crate::services::demo_mode;
 use crate::types::{
     CreateCommunicationRequest, ErrorResponse, ListCommunicationsRequest,
     ListCommunicationsResponse, Request, SuccessResponse, UpdateCommunicationRequest,
 };
 use crate::db::queries::planned_action as pa_queries;
+use crate::types::retention::RETENTION_ENTITY_COMMUNICATION;
 
 /// Handle communication.create messages
 pub async fn handle_create(
@@ -59,6 +62,10 @@ pub async fn handle_create(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let payload = request.payload;
         info!("Creating communication for customer {}", payload.customer_id);
 
@@ -157,6 +164,13 @@ pub async fn handle_list(
         };
 
         let payload = request.payload;
+        if let Err(msg) = payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client
+                .publish(reply, serde_json::to_vec(&error)?.into())
+                .await;
+            continue;
+        }
         let limit = payload.limit.unwrap_or(50);
         let offset = payload.offset.unwrap_or(0);
 
@@ -166,6 +180,7 @@ pub async fn handle_list(
             payload.customer_id,
             payload.revision_id,
             payload.comm_type.as_deref(),
+            &payload.entry_types,
             limit,
             offset,
         )
@@ -175,7 +190,7 @@ pub async fn handle_list(
                 let response = SuccessResponse::new(
                     request.id,
                     ListCommunicationsResponse {
-                        communications,
+                        communications: demo_mode::apply(request.demo_mode, communications),
                         total,
                     },
                 );
@@ -239,6 +254,10 @@ pub async fn handle_update(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let payload = request.payload;
 
         match queries::communication::update_communication(
@@ -322,6 +341,16 @@ pub async fn handle_delete(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if retention::reject_if_retained(&client, &pool, &reply, request.id, user_id, RETENTION_ENTITY_COMMUNICATION, request.payload.id)
+            .await?
+        {
+            continue;
+        }
+
         match queries::communication::delete_communication(&pool, request.payload.id, user_id).await
         {
             Ok(deleted) => {
@@ -0,0 +1,465 @@
+//! CRM sync connector handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::{crm_sync, leader};
+use crate::subjects;
+use crate::types::{
+    ErrorResponse, Request, SuccessResponse,
+    CreateCrmConnectorRequest, UpdateCrmConnectorRequest, CrmConnectorIdRequest,
+    ListCrmSyncLogRequest, ListCrmConnectorsResponse, ListCrmSyncLogResponse,
+    MAX_CRM_BATCH_SIZE,
+};
+
+/// Start all CRM sync NATS handlers and the pull scheduler
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting CRM sync handlers...");
+
+    let [create_sub, list_sub, update_sub, delete_sub, run_sub, log_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::crm_sync::CONNECTOR_CREATE,
+            subjects::crm_sync::CONNECTOR_LIST,
+            subjects::crm_sync::CONNECTOR_UPDATE,
+            subjects::crm_sync::CONNECTOR_DELETE,
+            subjects::crm_sync::RUN,
+            subjects::crm_sync::LOG_LIST,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_create(client.clone(), create_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_list(client.clone(), list_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_update(client.clone(), update_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_delete(client.clone(), delete_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_run(client.clone(), run_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_list_log(client.clone(), log_sub, pool.clone(), jwt_secret.clone()));
+
+    leader::spawn_singleton(pool, "crm_sync_scheduler", crm_sync::run_scheduler);
+
+    info!("CRM sync handlers started");
+    Ok(())
+}
+
+/// Shared validation for create/update payloads
+fn validate_connector_fields(
+    base_url: Option<&str>,
+    field_mapping: Option<&serde_json::Value>,
+    batch_size: Option<i32>,
+    pull_interval_minutes: Option<i32>,
+) -> std::result::Result<(), String> {
+    if let Some(url) = base_url {
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err("baseUrl must be an http(s) URL".to_string());
+        }
+    }
+    if let Some(mapping) = field_mapping {
+        crm_sync::validate_field_mapping(mapping).map_err(|e| e.to_string())?;
+    }
+    if let Some(size) = batch_size {
+        if !(1..=MAX_CRM_BATCH_SIZE).contains(&size) {
+            return Err(format!("batchSize must be between 1 and {}", MAX_CRM_BATCH_SIZE));
+        }
+    }
+    if let Some(minutes) = pull_interval_minutes {
+        if !(5..=10080).contains(&minutes) {
+            return Err("pullIntervalMinutes must be between 5 and 10080".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Handle crm_sync.connector.create messages
+pub async fn handle_create(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received crm_sync.connector.create message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CreateCrmConnectorRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can manage CRM connectors");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let payload = &request.payload;
+        if let Err(msg) = validate_connector_fields(
+            Some(&payload.base_url),
+            payload.field_mapping.as_ref(),
+            payload.batch_size,
+            payload.pull_interval_minutes,
+        ) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::crm_sync::create_connector(&pool, auth_info.data_user_id(), payload).await {
+            Ok(connector) => {
+                let response = SuccessResponse::new(request.id, connector);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to create CRM connector: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle crm_sync.connector.list messages
+pub async fn handle_list(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received crm_sync.connector.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::crm_sync::list_connectors(&pool, user_id).await {
+            Ok(connectors) => {
+                let response = SuccessResponse::new(request.id, ListCrmConnectorsResponse { connectors });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list CRM connectors: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle crm_sync.connector.update messages
+pub async fn handle_update(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received crm_sync.connector.update message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<UpdateCrmConnectorRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can manage CRM connectors");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let payload = &request.payload;
+        if let Err(msg) = validate_connector_fields(
+            payload.base_url.as_deref(),
+            payload.field_mapping.as_ref(),
+            payload.batch_size,
+            payload.pull_interval_minutes,
+        ) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::crm_sync::update_connector(&pool, auth_info.data_user_id(), payload).await {
+            Ok(Some(connector)) => {
+                let response = SuccessResponse::new(request.id, connector);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Connector not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to update CRM connector: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle crm_sync.connector.delete messages
+pub async fn handle_delete(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received crm_sync.connector.delete message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CrmConnectorIdRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can manage CRM connectors");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::crm_sync::delete_connector(&pool, auth_info.data_user_id(), request.payload.id).await {
+            Ok(true) => {
+                let response = SuccessResponse::new(request.id, serde_json::json!({ "deleted": true }));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(false) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Connector not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to delete CRM connector: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle crm_sync.run messages - run push + pull immediately
+pub async fn handle_run(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    let http = crm_sync::http_client();
+
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received crm_sync.run message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CrmConnectorIdRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let connector = match queries::crm_sync::get_connector(&pool, user_id, request.payload.id).await {
+            Ok(Some(connector)) => connector,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Connector not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load CRM connector: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match crm_sync::run_connector(&http, &pool, &connector).await {
+            Ok(result) => {
+                let response = SuccessResponse::new(request.id, result);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("CRM sync run failed: {}", e);
+                let error = ErrorResponse::new(request.id, "SYNC_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle crm_sync.log.list messages
+pub async fn handle_list_log(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received crm_sync.log.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ListCrmSyncLogRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let limit = request.payload.limit.unwrap_or(50).clamp(1, 500);
+        match queries::crm_sync::list_sync_log(&pool, user_id, request.payload.connector_id, limit).await {
+            Ok(entries) => {
+                let response = SuccessResponse::new(request.id, ListCrmSyncLogResponse { entries });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list CRM sync log: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -10,13 +10,95 @@ use tracing::{debug, error, warn};
 use uuid::Uuid;
 
 use crate::auth;
+use super::account;
 use crate::db::queries;
+use crate::db::repo::Repositories;
+use crate::services::{demo_mode, quota, webhook_delivery};
 use crate::types::{
     CreateCustomerRequest, UpdateCustomerRequest, ErrorResponse, ListRequest, 
     ListResponse, Request, SuccessResponse,
-    ListCustomersRequest, CustomerListResponse,
+    ListCustomersRequest, CustomerListResponse, QuotaMetric,
 };
-use crate::types::customer::ColumnDistinctRequest;
+use crate::types::coverage::{normalize_postal_code, CoverageVerdict, COVERAGE_SOURCE_CREATE};
+use crate::types::customer::{
+    validate_acquisition_source, BulkUpdateCustomersRequest, BulkUpdateCustomersResponse, ColumnDistinctRequest,
+};
+use crate::types::template_translation::{normalize_language, CUSTOMER_LANGUAGES};
+
+/// Normalize a requested customer language in place; false when it is not supported.
+/// An empty string (reset to the account default) is kept as is.
+fn normalize_requested_language(language: &mut Option<String>) -> bool {
+    match language.as_deref() {
+        None | Some("") => true,
+        Some(tag) => match normalize_language(tag) {
+            Some(lang) => {
+                *language = Some(lang);
+                true
+            }
+            None => false,
+        },
+    }
+}
+
+/// Check a new customer's address against the account's service coverage,
+/// logging out-of-coverage addresses for the demand report
+async fn check_coverage(pool: &PgPool, user_id: Uuid, payload: &CreateCustomerRequest) -> Result<CoverageVerdict> {
+    let coverage = queries::coverage::get_coverage(pool, user_id).await?;
+    let verdict = coverage.verdict(payload.postal_code.as_deref());
+    if verdict != CoverageVerdict::Covered {
+        let postal_code = normalize_postal_code(payload.postal_code.as_deref().unwrap_or_default());
+        let rejected = verdict == CoverageVerdict::Reject;
+        let city = payload.city.as_deref();
+        if let Err(e) = queries::coverage::record_miss(pool, user_id, &postal_code, city, COVERAGE_SOURCE_CREATE, rejected).await {
+            warn!("Failed to record coverage miss of {}: {}", user_id, e);
+        }
+    }
+    Ok(verdict)
+}
+
+/// Normalize a requested customer country to its upper-case code and check
+/// that it is supported. `current` is the customer's stored country: keeping
+/// it is always allowed, so customers created before a country was disabled
+/// can still be edited.
+async fn check_country(pool: &PgPool, country: &mut Option<String>, current: Option<&str>) -> Result<Result<(), String>> {
+    let code = match country.as_deref().map(str::trim) {
+        None | Some("") => return Ok(Ok(())),
+        Some(code) => code.to_uppercase(),
+    };
+    *country = Some(code.clone());
+    if current.is_some_and(|c| c.eq_ignore_ascii_case(&code)) {
+        return Ok(Ok(()));
+    }
+    Ok(match queries::country::get_country(pool, &code).await? {
+        Some(c) if c.is_supported => Ok(()),
+        Some(c) => Err(format!("Country {} ({}) is not supported", c.code, c.name_en)),
+        None => Err(format!("Unknown country code: {}", code)),
+    })
+}
+
+/// Check a requested acquisition source and that the referrer is another
+/// customer of the account. `customer_id` is the customer being updated.
+async fn check_acquisition(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Option<Uuid>,
+    source: Option<&str>,
+    referrer_customer_id: Option<Uuid>,
+) -> Result<Result<(), String>> {
+    if let Err(reason) = validate_acquisition_source(source, referrer_customer_id) {
+        return Ok(Err(reason));
+    }
+    let Some(referrer_id) = referrer_customer_id else {
+        return Ok(Ok(()));
+    };
+    if customer_id == Some(referrer_id) {
+        return Ok(Err("A customer cannot refer themselves".to_string()));
+    }
+    Ok(match queries::customer::get_customer(pool, user_id, referrer_id).await? {
+        Some(_) => Ok(()),
+        None => Err("Referrer customer not found".to_string()),
+    })
+}
 
 /// Handle customer.create messages
 /// 
@@ -40,7 +122,7 @@ pub async fn handle_create(
         };
 
         // Parse request
-        let request: Request<CreateCustomerRequest> = match serde_json::from_slice(&msg.payload) {
+        let mut request: Request<CreateCustomerRequest> = match serde_json::from_slice(&msg.payload) {
             Ok(req) => req,
             Err(e) => {
                 error!("Failed to parse request: {}", e);
@@ -60,9 +142,110 @@ pub async fn handle_create(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if !normalize_requested_language(&mut request.payload.language) {
+            let message = format!("language must be one of: {}", CUSTOMER_LANGUAGES.join(", "));
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", message);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+        if request.payload.language.as_deref() == Some("") {
+            request.payload.language = None;
+        }
+
+        match check_country(&pool, &mut request.payload.country, None).await {
+            Ok(Ok(())) => {}
+            Ok(Err(reason)) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", reason);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to check customer country: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        if request.payload.acquisition_source.as_deref() == Some("") {
+            request.payload.acquisition_source = None;
+        }
+        let payload = &request.payload;
+        match check_acquisition(&pool, user_id, None, payload.acquisition_source.as_deref(), payload.referrer_customer_id).await {
+            Ok(Ok(())) => {}
+            Ok(Err(reason)) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", reason);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to check referrer customer: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        if let Some(parent_id) = request.payload.parent_customer_id {
+            match queries::customer_hierarchy::check_parent(&pool, user_id, None, parent_id).await {
+                Ok(Ok(())) => {}
+                Ok(Err(reason)) => {
+                    let error = ErrorResponse::new(request.id, "INVALID_REQUEST", reason.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to check parent customer: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            }
+        }
+
+        let coverage_warning = match check_coverage(&pool, user_id, &request.payload).await {
+            Ok(CoverageVerdict::Covered) => None,
+            Ok(CoverageVerdict::Warn) => Some("customers:out_of_coverage".to_string()),
+            Ok(CoverageVerdict::Reject) => {
+                let error = ErrorResponse::new(
+                    request.id,
+                    "OUT_OF_COVERAGE",
+                    "The address is outside the service coverage area",
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                // Coverage is advisory — never block customer creation on it
+                warn!("Failed to check service coverage of {}: {}", user_id, e);
+                None
+            }
+        };
+
+        match quota::consume(&pool, user_id, QuotaMetric::Customers, 1).await {
+            Ok(Ok(())) => {}
+            Ok(Err(exceeded)) => {
+                let error = ErrorResponse::new(request.id, "QUOTA_EXCEEDED", exceeded.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to check customer quota: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
         // Create customer
         match queries::customer::create_customer(&pool, user_id, &request.payload).await {
-            Ok(customer) => {
+            Ok(mut customer) => {
+                customer.coverage_warning = coverage_warning;
+                webhook_delivery::emit(&client, &pool, user_id, "customer.created", &customer);
                 let response = SuccessResponse::new(request.id, customer);
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                 debug!("Created customer: {}", response.payload.id);
@@ -130,7 +313,7 @@ pub async fn handle_list(
                     request.id,
                     ListResponse {
                         total: customers.len() as i64, // TODO: proper count query
-                        items: customers,
+                        items: demo_mode::apply(request.demo_mode, customers),
                         limit: request.payload.limit,
                         offset: request.payload.offset,
                     },
@@ -153,7 +336,7 @@ pub async fn handle_list(
 pub async fn handle_get(
     client: Client,
     mut subscriber: Subscriber,
-    pool: PgPool,
+    repos: Repositories,
     jwt_secret: Arc<String>,
 ) -> Result<()> {
     #[derive(serde::Deserialize)]
@@ -194,9 +377,9 @@ pub async fn handle_get(
         };
 
         // Get customer
-        match queries::customer::get_customer(&pool, user_id, request.payload.id).await {
+        match repos.customers.get_customer(user_id, request.payload.id).await {
             Ok(Some(customer)) => {
-                let response = SuccessResponse::new(request.id, customer);
+                let response = SuccessResponse::new(request.id, demo_mode::apply(request.demo_mode, customer));
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                 debug!("Got customer: {}", response.payload.id);
             }
@@ -254,8 +437,67 @@ pub async fn handle_update(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         // Prepare update request - if address changed and no coordinates provided, reset coords
-        let update_request = request.payload.clone();
+        let mut update_request = request.payload.clone();
+        if !normalize_requested_language(&mut update_request.language) {
+            let message = format!("language must be one of: {}", CUSTOMER_LANGUAGES.join(", "));
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", message);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match check_acquisition(
+            &pool,
+            user_id,
+            Some(update_request.id),
+            update_request.acquisition_source.as_deref(),
+            update_request.referrer_customer_id,
+        )
+        .await
+        {
+            Ok(Ok(())) => {}
+            Ok(Err(reason)) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", reason);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to check referrer customer: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        if update_request.country.is_some() {
+            let current = match queries::customer::get_customer(&pool, user_id, update_request.id).await {
+                Ok(customer) => customer.and_then(|c| c.country),
+                Err(e) => {
+                    error!("Failed to load customer {}: {}", update_request.id, e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            };
+            match check_country(&pool, &mut update_request.country, current.as_deref()).await {
+                Ok(Ok(())) => {}
+                Ok(Err(reason)) => {
+                    let error = ErrorResponse::new(request.id, "INVALID_REQUEST", reason);
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to check customer country: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            }
+        }
         
         let address_changed = update_request.street.is_some() 
             || update_request.city.is_some() 
@@ -271,6 +513,7 @@ pub async fn handle_update(
         // Update customer
         match queries::customer::update_customer(&pool, user_id, &update_request).await {
             Ok(Some(customer)) => {
+                webhook_delivery::emit(&client, &pool, user_id, "customer.updated", &customer);
                 let response = SuccessResponse::new(request.id, customer);
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                 debug!("Updated customer: {}", response.payload.id);
@@ -290,6 +533,104 @@ pub async fn handle_update(
     Ok(())
 }
 
+/// Handle customer.bulk_update messages - one patch for many customers,
+/// applied atomically, with a result per requested customer
+pub async fn handle_bulk_update(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.bulk_update message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<BulkUpdateCustomersRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if let Err(msg) = request.payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let ids = request.payload.unique_ids();
+        let mut patch = request.payload.patch.clone();
+        if !normalize_requested_language(&mut patch.language) {
+            let message = format!("language must be one of: {}", CUSTOMER_LANGUAGES.join(", "));
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", message);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let checked = match check_acquisition(&pool, user_id, None, patch.acquisition_source.as_deref(), None).await {
+            Ok(Ok(())) => check_country(&pool, &mut patch.country, None).await,
+            other => other,
+        };
+        match checked {
+            Ok(Ok(())) => {}
+            Ok(Err(reason)) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", reason);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to check bulk customer patch: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        match queries::customer::bulk_update_customers(&pool, user_id, &ids, &patch).await {
+            Ok(customers) => {
+                for customer in &customers {
+                    webhook_delivery::emit(&client, &pool, user_id, "customer.updated", customer);
+                }
+                let updated: Vec<Uuid> = customers.iter().map(|c| c.id).collect();
+                let report = BulkUpdateCustomersResponse::new(&ids, &updated);
+                debug!("Bulk updated {} of {} customers", report.updated, ids.len());
+                let response = SuccessResponse::new(request.id, report);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to bulk update customers: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Delete response payload
 #[derive(Debug, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -405,13 +746,18 @@ pub async fn handle_delete(
             }
         };
 
-        // Anonymize customer (keeps historical FK references intact)
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        // Soft-delete; restorable until the purge job anonymizes it
         match queries::customer::delete_customer(&pool, user_id, request.payload.id).await {
             Ok(deleted) => {
                 if deleted {
+                    webhook_delivery::emit(&client, &pool, user_id, "customer.deleted", &serde_json::json!({ "id": request.payload.id }));
                     let response = SuccessResponse::new(request.id, DeleteResponse { deleted: true });
                     let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
-                    debug!("Anonymized customer: {}", request.payload.id);
+                    debug!("Deleted customer: {}", request.payload.id);
                 } else {
                     let error = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
                     let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
@@ -476,7 +822,7 @@ pub async fn handle_list_extended(
             Ok((items, total)) => {
                 let response = SuccessResponse::new(
                     request.id,
-                    CustomerListResponse { items, total },
+                    CustomerListResponse { items: demo_mode::apply(request.demo_mode, items), total },
                 );
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                 debug!("Listed {} customers (total: {})", response.payload.items.len(), total);
@@ -587,6 +933,10 @@ pub async fn handle_abandon(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         match queries::customer::abandon_customer(&pool, user_id, request.payload).await {
             Ok(Some(customer)) => {
                 let response = SuccessResponse::new(request.id, customer);
@@ -639,6 +989,10 @@ pub async fn handle_unabandon(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         match queries::customer::unabandon_customer(&pool, user_id, request.payload).await {
             Ok(Some(customer)) => {
                 let response = SuccessResponse::new(request.id, customer);
@@ -658,6 +1012,64 @@ pub async fn handle_unabandon(
     Ok(())
 }
 
+/// Handle sazinka.customer.restore — undo a soft-delete before it is purged
+pub async fn handle_restore(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.restore");
+
+        let reply = match msg.reply {
+            Some(ref r) => r.clone(),
+            None => { warn!("No reply subject"); continue; }
+        };
+
+        let request: Request<uuid::Uuid> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                let error = ErrorResponse::new(uuid::Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        match queries::customer::restore_customer(&pool, user_id, request.payload).await {
+            Ok(Some(customer)) => {
+                webhook_delivery::emit(&client, &pool, user_id, "customer.updated", &customer);
+                let response = SuccessResponse::new(request.id, customer);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+                debug!("Restored customer: {}", request.payload);
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "No deleted customer to restore");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("restore_customer error: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Handle sazinka.customer.anonymize — GDPR soft-delete (irreversible)
 pub async fn handle_anonymize(
     client: Client,
@@ -691,6 +1103,10 @@ pub async fn handle_anonymize(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         match queries::customer::anonymize_customer(&pool, user_id, request.payload).await {
             Ok(true) => {
                 let response = SuccessResponse::new(request.id, serde_json::json!({"anonymized": true}));
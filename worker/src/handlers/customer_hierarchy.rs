@@ -0,0 +1,276 @@
+//! Customer hierarchy handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::subjects;
+use super::account;
+use crate::db::queries;
+use crate::services::demo_mode;
+use crate::types::customer_hierarchy::{
+    CustomerHierarchyRequest, CustomerHierarchyResponse, HierarchySummaryResponse, HierarchyTotals,
+    SetCustomerParentRequest,
+};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Start all customer hierarchy NATS handlers
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting customer hierarchy handlers...");
+
+    let [get_sub, set_sub, summary_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::customer::HIERARCHY_GET,
+            subjects::customer::HIERARCHY_SET,
+            subjects::customer::HIERARCHY_SUMMARY,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_get(client.clone(), get_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_set(client.clone(), set_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_summary(client.clone(), summary_sub, pool, jwt_secret));
+
+    info!("Customer hierarchy handlers started");
+    Ok(())
+}
+
+/// Handle customer.hierarchy.get messages - parent company and its branches
+pub async fn handle_get(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.hierarchy.get message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CustomerHierarchyRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let result = async {
+            let Some(parent_id) =
+                queries::customer_hierarchy::resolve_parent(&pool, user_id, request.payload.customer_id).await?
+            else {
+                return Ok(None);
+            };
+            let Some(parent) = queries::customer::get_customer(&pool, user_id, parent_id).await? else {
+                return Ok(None);
+            };
+            let branches = queries::customer_hierarchy::list_branches(&pool, user_id, parent_id).await?;
+            anyhow::Ok(Some(CustomerHierarchyResponse { parent, branches }))
+        }
+        .await;
+
+        match result {
+            Ok(Some(hierarchy)) => {
+                let response = SuccessResponse::new(request.id, demo_mode::apply(request.demo_mode, hierarchy));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to load customer hierarchy: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle customer.hierarchy.set messages - attach to or detach from a parent
+pub async fn handle_set(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.hierarchy.set message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<SetCustomerParentRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let payload = &request.payload;
+        if let Some(parent_id) = payload.parent_customer_id {
+            match queries::customer_hierarchy::check_parent(&pool, user_id, Some(payload.customer_id), parent_id).await {
+                Ok(Ok(())) => {}
+                Ok(Err(reason)) => {
+                    let error = ErrorResponse::new(request.id, "INVALID_REQUEST", reason.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to check parent customer: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            }
+        }
+
+        match queries::customer_hierarchy::set_parent(&pool, user_id, payload.customer_id, payload.parent_customer_id)
+            .await
+        {
+            Ok(true) => match queries::customer::get_customer(&pool, user_id, payload.customer_id).await {
+                Ok(Some(customer)) => {
+                    let response = SuccessResponse::new(request.id, customer);
+                    let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+                }
+                Ok(None) => {
+                    let error = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                }
+                Err(e) => {
+                    error!("Failed to load customer: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                }
+            },
+            Ok(false) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to set parent customer: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle customer.hierarchy.summary messages - consolidated group report
+pub async fn handle_summary(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.hierarchy.summary message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CustomerHierarchyRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let result = async {
+            let Some(parent_id) =
+                queries::customer_hierarchy::resolve_parent(&pool, user_id, request.payload.customer_id).await?
+            else {
+                return Ok(None);
+            };
+            let members = queries::customer_hierarchy::group_summary(&pool, user_id, parent_id).await?;
+            let totals = HierarchyTotals::from_members(&members);
+            let members = demo_mode::apply(request.demo_mode, members);
+            anyhow::Ok(Some(HierarchySummaryResponse { parent_customer_id: parent_id, members, totals }))
+        }
+        .await;
+
+        match result {
+            Ok(Some(summary)) => {
+                let response = SuccessResponse::new(request.id, summary);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to build hierarchy summary: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,477 @@
+//! Customer risk scoring handlers for NATS messages
+//!
+//! Any member of an account can record an incident (crews report access
+//! problems from the field); incidents, weights and the score audit are
+//! managed by the company owner.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::account;
+use crate::auth;
+use crate::db::queries;
+use crate::services::{customer_risk, leader};
+use crate::subjects;
+use crate::types::customer_risk::{
+    CreateRiskIncidentRequest, CustomerRiskSettings, ListRiskIncidentsRequest, ListRiskIncidentsResponse,
+    ListRiskScoreChangesRequest, ListRiskScoreChangesResponse, RiskIncidentIdRequest, RiskIncidentResponse,
+    RISK_REASON_INCIDENT_ADDED, RISK_REASON_INCIDENT_REMOVED, RISK_REASON_SETTINGS_CHANGED,
+};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Start all customer risk NATS handlers and the daily sweep
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting customer risk handlers...");
+
+    let [create_sub, list_sub, delete_sub, settings_get_sub, settings_update_sub, history_sub] =
+        subjects::subscribe_all(
+            &client,
+            [
+                subjects::customer::RISK_INCIDENT_CREATE,
+                subjects::customer::RISK_INCIDENT_LIST,
+                subjects::customer::RISK_INCIDENT_DELETE,
+                subjects::customer::RISK_SETTINGS_GET,
+                subjects::customer::RISK_SETTINGS_UPDATE,
+                subjects::customer::RISK_HISTORY,
+            ],
+        )
+        .await?;
+
+    tokio::spawn(handle_create_incident(client.clone(), create_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_list_incidents(client.clone(), list_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_delete_incident(client.clone(), delete_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_get_settings(client.clone(), settings_get_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_update_settings(client.clone(), settings_update_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_history(client, history_sub, pool.clone(), jwt_secret));
+
+    leader::spawn_singleton(pool, "customer_risk_sweep", customer_risk::run_sweeper);
+
+    info!("Customer risk handlers started");
+    Ok(())
+}
+
+/// Handle customer.risk.incident.create messages
+pub async fn handle_create_incident(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.risk.incident.create message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CreateRiskIncidentRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if let Err(msg) = request.payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let incident = match queries::customer_risk::create_incident(&pool, user_id, &request.payload).await {
+            Ok(Some(incident)) => incident,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to create risk incident: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match customer_risk::refresh(&pool, user_id, incident.customer_id, RISK_REASON_INCIDENT_ADDED).await {
+            Ok(score) => {
+                let response = SuccessResponse::new(request.id, RiskIncidentResponse { incident: Some(incident), score });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to refresh risk score: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle customer.risk.incident.list messages
+pub async fn handle_list_incidents(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.risk.incident.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ListRiskIncidentsRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can view risk incidents");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::customer_risk::list_incidents(&pool, auth_info.data_user_id(), request.payload.customer_id).await {
+            Ok(incidents) => {
+                let response = SuccessResponse::new(request.id, ListRiskIncidentsResponse { incidents });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list risk incidents: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle customer.risk.incident.delete messages
+pub async fn handle_delete_incident(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.risk.incident.delete message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<RiskIncidentIdRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can remove risk incidents");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let user_id = auth_info.data_user_id();
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let customer_id = match queries::customer_risk::delete_incident(&pool, user_id, request.payload.id).await {
+            Ok(Some(customer_id)) => customer_id,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Risk incident not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to delete risk incident: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match customer_risk::refresh(&pool, user_id, customer_id, RISK_REASON_INCIDENT_REMOVED).await {
+            Ok(score) => {
+                let response = SuccessResponse::new(request.id, RiskIncidentResponse { incident: None, score });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to refresh risk score: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle customer.risk.settings.get messages
+pub async fn handle_get_settings(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.risk.settings.get message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can view risk settings");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::customer_risk::get_settings(&pool, auth_info.data_user_id()).await {
+            Ok(settings) => {
+                let response = SuccessResponse::new(request.id, settings);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to get risk settings: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle customer.risk.settings.update messages - rescores the account
+pub async fn handle_update_settings(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.risk.settings.update message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CustomerRiskSettings> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can change risk settings");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let user_id = auth_info.data_user_id();
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if let Err(msg) = request.payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::customer_risk::save_settings(&pool, user_id, &request.payload).await {
+            Ok(()) => {
+                let response = SuccessResponse::new(request.id, &request.payload);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    match customer_risk::refresh_all(&pool, Some(user_id), RISK_REASON_SETTINGS_CHANGED).await {
+                        Ok(count) => info!("Rescored {} customers of user {} after risk settings change", count, user_id),
+                        Err(e) => warn!("Failed to rescore customers of user {}: {}", user_id, e),
+                    }
+                });
+            }
+            Err(e) => {
+                error!("Failed to save risk settings: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle customer.risk.history messages - audit of score changes
+pub async fn handle_history(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.risk.history message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ListRiskScoreChangesRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can view risk history");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let limit = request.payload.limit.unwrap_or(50).clamp(1, 500);
+        match queries::customer_risk::list_score_changes(
+            &pool,
+            auth_info.data_user_id(),
+            request.payload.customer_id,
+            limit,
+        )
+        .await
+        {
+            Ok(changes) => {
+                let response = SuccessResponse::new(request.id, ListRiskScoreChangesResponse { changes });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list risk score changes: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
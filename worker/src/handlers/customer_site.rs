@@ -0,0 +1,371 @@
+//! Customer address (site) handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::subjects;
+use super::account;
+use crate::db::queries;
+use crate::services::demo_mode;
+use crate::types::customer_site::{
+    is_customer_billing, validate_address_type, validate_optional_coordinates, CreateCustomerSiteRequest,
+    CustomerSite, CustomerSiteIdRequest, ListCustomerSitesRequest, UpdateCustomerSiteRequest,
+    ADDRESS_TYPE_SERVICE,
+};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Sites of a customer
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerSiteListResponse {
+    pub items: Vec<CustomerSite>,
+}
+
+/// Response for delete operation
+#[derive(Debug, serde::Serialize)]
+pub struct DeleteResponse {
+    pub deleted: bool,
+}
+
+/// Why the primary billing address cannot be changed here
+const CUSTOMER_BILLING_MANAGED: &str = "The primary billing address is edited on the customer";
+
+/// Check the type, primary flag and coordinates of an address
+fn check_address(
+    address_type: &str,
+    is_primary: bool,
+    lat: Option<f64>,
+    lng: Option<f64>,
+) -> std::result::Result<(), &'static str> {
+    validate_address_type(address_type)?;
+    if is_customer_billing(address_type, is_primary) {
+        return Err(CUSTOMER_BILLING_MANAGED);
+    }
+    validate_optional_coordinates(lat, lng)
+}
+
+/// Look up an address the handler may change; replies and returns None otherwise
+async fn editable_site(
+    client: &Client,
+    pool: &PgPool,
+    reply: &async_nats::Subject,
+    request_id: Uuid,
+    user_id: Uuid,
+    site_id: Uuid,
+) -> Result<Option<CustomerSite>> {
+    let error = match queries::customer_site::get_site(pool, user_id, site_id).await {
+        Ok(Some(site)) if !site.is_customer_billing() => return Ok(Some(site)),
+        Ok(Some(_)) => ErrorResponse::new(request_id, "INVALID_REQUEST", CUSTOMER_BILLING_MANAGED),
+        Ok(None) => ErrorResponse::new(request_id, "NOT_FOUND", "Site not found"),
+        Err(e) => {
+            error!("Failed to load customer site: {}", e);
+            ErrorResponse::new(request_id, "DATABASE_ERROR", e.to_string())
+        }
+    };
+    let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+    Ok(None)
+}
+
+/// Start all customer site NATS handlers
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting customer site handlers...");
+
+    let [create_sub, list_sub, update_sub, delete_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::customer::SITE_CREATE,
+            subjects::customer::SITE_LIST,
+            subjects::customer::SITE_UPDATE,
+            subjects::customer::SITE_DELETE,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_create(client.clone(), create_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_list(client.clone(), list_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_update(client.clone(), update_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_delete(client.clone(), delete_sub, pool, jwt_secret));
+
+    info!("Customer site handlers started");
+    Ok(())
+}
+
+/// Handle customer.site.create messages
+pub async fn handle_create(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.site.create message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CreateCustomerSiteRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let payload = &request.payload;
+        if payload.name.trim().is_empty() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "Site name is required");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+        let address_type = payload.address_type.as_deref().unwrap_or(ADDRESS_TYPE_SERVICE);
+        let is_primary = payload.is_primary.unwrap_or(false);
+        if let Err(reason) = check_address(address_type, is_primary, payload.lat, payload.lng) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", reason);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::customer_site::create_site(&pool, user_id, payload).await {
+            Ok(Some(site)) => {
+                let response = SuccessResponse::new(request.id, site);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to create customer site: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle customer.site.list messages
+pub async fn handle_list(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.site.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ListCustomerSitesRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::customer_site::list_sites(&pool, user_id, request.payload.customer_id).await {
+            Ok(items) => {
+                let items = demo_mode::apply(request.demo_mode, items);
+                let response = SuccessResponse::new(request.id, CustomerSiteListResponse { items });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list customer sites: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle customer.site.update messages
+pub async fn handle_update(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.site.update message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<UpdateCustomerSiteRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let payload = &request.payload;
+        if payload.name.as_deref().is_some_and(|name| name.trim().is_empty()) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "Site name is required");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+        let Some(current) = editable_site(&client, &pool, &reply, request.id, user_id, payload.id).await? else {
+            continue;
+        };
+        let address_type = payload.address_type.as_deref().unwrap_or(&current.address_type);
+        let is_primary = payload.is_primary.unwrap_or(current.is_primary);
+        if let Err(reason) = check_address(address_type, is_primary, payload.lat, payload.lng) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", reason);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::customer_site::update_site(&pool, user_id, &current, payload).await {
+            Ok(Some(site)) => {
+                let response = SuccessResponse::new(request.id, site);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Site not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to update customer site: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle customer.site.delete messages. Devices at the site keep their
+/// structured location but lose the site reference.
+pub async fn handle_delete(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received customer.site.delete message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CustomerSiteIdRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if editable_site(&client, &pool, &reply, request.id, user_id, request.payload.id).await?.is_none() {
+            continue;
+        }
+
+        match queries::customer_site::delete_site(&pool, user_id, request.payload.id).await {
+            Ok(true) => {
+                let response = SuccessResponse::new(request.id, DeleteResponse { deleted: true });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(false) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Site not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to delete customer site: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+//! Debug request/response recording handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use serde::Serialize;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::services::debug_recorder::{self, DebugRecorder, Recording, RecordingConfig};
+use crate::subjects;
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Response for sazinka.admin.recording.get / set / clear
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingStateResponse {
+    pub config: RecordingConfig,
+    pub recordings: Vec<Recording>,
+}
+
+/// What a recording handler does with a request
+#[derive(Clone, Copy)]
+enum Action {
+    Get,
+    Set,
+    Clear,
+}
+
+/// Start the recorder and its admin NATS handlers.
+///
+/// The handlers subscribe without a queue group so every worker applies a
+/// set/clear; a get is answered by the first worker to reply, with only that
+/// worker's recordings.
+pub async fn start_handlers(client: Client, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting debug recording handlers...");
+
+    let recorder = Arc::new(DebugRecorder::new(RecordingConfig::from_env()));
+
+    let [get_sub, set_sub, clear_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::admin::RECORDING_GET,
+            subjects::admin::RECORDING_SET,
+            subjects::admin::RECORDING_CLEAR,
+        ],
+    )
+    .await?;
+
+    let client_recorder = client.clone();
+    let recorder_run = Arc::clone(&recorder);
+    tokio::spawn(async move {
+        if let Err(e) = debug_recorder::run(client_recorder, recorder_run).await {
+            error!("Debug recorder stopped: {}", e);
+        }
+    });
+    tokio::spawn(handle(client.clone(), get_sub, Arc::clone(&recorder), jwt_secret.clone(), Action::Get));
+    tokio::spawn(handle(client.clone(), set_sub, Arc::clone(&recorder), jwt_secret.clone(), Action::Set));
+    tokio::spawn(handle(client, clear_sub, recorder, jwt_secret, Action::Clear));
+
+    info!("Debug recording handlers started");
+    Ok(())
+}
+
+/// Handle admin.recording.{get,set,clear} messages
+async fn handle(
+    client: Client,
+    mut subscriber: Subscriber,
+    recorder: Arc<DebugRecorder>,
+    jwt_secret: Arc<String>,
+    action: Action,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received {} message", msg.subject);
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Admin access required");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match action {
+            Action::Get => {}
+            Action::Clear => recorder.clear(),
+            Action::Set => {
+                let config = serde_json::from_value::<RecordingConfig>(request.payload.clone())
+                    .map_err(|e| e.to_string())
+                    .and_then(RecordingConfig::validated);
+                match config {
+                    Ok(config) => {
+                        info!("Debug recording set by {}: {:?}", auth_info.user_id, config.subjects);
+                        recorder.set_config(config);
+                    }
+                    Err(msg) => {
+                        let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+                        let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        let state = RecordingStateResponse { config: recorder.config(), recordings: recorder.recordings() };
+        let response = SuccessResponse::new(request.id, state);
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
@@ -6,17 +6,21 @@ use anyhow::Result;
 use async_nats::{Client, Subscriber};
 use futures::StreamExt;
 use sqlx::PgPool;
-use tracing::{debug, error, warn};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::auth;
+use super::{account, retention};
 use crate::db::queries;
+use crate::services::webhook_delivery;
 use crate::types::{
     ErrorResponse, Request, SuccessResponse,
 };
 use crate::types::device::{
     CreateDeviceRequest, UpdateDeviceRequest, ListDevicesRequest, Device,
+    ListDeviceTransfersRequest, ListDeviceTransfersResponse, TransferDeviceRequest, TransferDeviceResponse,
 };
+use crate::types::customer_site::validate_optional_coordinates;
 
 /// Response for list of devices
 #[derive(Debug, serde::Serialize)]
@@ -33,6 +37,27 @@ pub struct DeleteResponse {
     pub deleted: bool,
 }
 
+/// Check a device's site and GPS override. Returns the reason when invalid.
+async fn check_device_location(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
+    site_id: Option<Uuid>,
+    lat: Option<f64>,
+    lng: Option<f64>,
+) -> Result<Option<&'static str>> {
+    if let Err(reason) = validate_optional_coordinates(lat, lng) {
+        return Ok(Some(reason));
+    }
+    if let Some(site_id) = site_id {
+        let site = queries::customer_site::get_site(pool, user_id, site_id).await?;
+        if site.is_none_or(|s| s.customer_id != customer_id) {
+            return Ok(Some("Site does not belong to the customer"));
+        }
+    }
+    Ok(None)
+}
+
 /// Handle device.create messages
 pub async fn handle_create(
     client: Client,
@@ -72,6 +97,33 @@ pub async fn handle_create(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let payload = &request.payload;
+        match check_device_location(
+            &pool,
+            user_id,
+            payload.customer_id,
+            payload.site_id,
+            payload.location_lat,
+            payload.location_lng,
+        ).await {
+            Ok(None) => {}
+            Ok(Some(reason)) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", reason);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to check device location: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
         // Create device
         match queries::device::create_device(&pool, user_id, request.payload.customer_id, &request.payload).await {
             Ok(device) => {
@@ -281,6 +333,33 @@ pub async fn handle_update(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let update = &request.payload.update;
+        match check_device_location(
+            &pool,
+            user_id,
+            request.payload.customer_id,
+            update.site_id,
+            update.location_lat,
+            update.location_lng,
+        ).await {
+            Ok(None) => {}
+            Ok(Some(reason)) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", reason);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to check device location: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
         // Update device
         match queries::device::update_device(
             &pool,
@@ -355,6 +434,16 @@ pub async fn handle_delete(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if retention::reject_if_retained(&client, &pool, &reply, request.id, user_id, "device", request.payload.id)
+            .await?
+        {
+            continue;
+        }
+
         // Delete device
         match queries::device::delete_device(&pool, user_id, request.payload.id, request.payload.customer_id).await {
             Ok(deleted) => {
@@ -377,3 +466,261 @@ pub async fn handle_delete(
 
     Ok(())
 }
+
+/// Handle device.restore messages - undo a soft-delete before it is purged
+pub async fn handle_restore(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct RestoreRequest {
+        id: Uuid,
+    }
+
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received device.restore message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<RestoreRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        match queries::device::restore_device(&pool, user_id, request.payload.id).await {
+            Ok(Some(device)) => {
+                let response = SuccessResponse::new(request.id, device);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+                debug!("Restored device: {}", request.payload.id);
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "No deleted device to restore");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to restore device: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Why a transfer can't go ahead: (error code, message)
+async fn check_transfer(
+    pool: &PgPool,
+    user_id: Uuid,
+    req: &TransferDeviceRequest,
+) -> Result<Option<(&'static str, &'static str)>> {
+    if let Err(reason) = req.validate() {
+        return Ok(Some(("INVALID_REQUEST", reason)));
+    }
+    let Some(device) = queries::device::get_device_by_id(pool, user_id, req.device_id).await? else {
+        return Ok(Some(("NOT_FOUND", "Device not found")));
+    };
+    if device.customer_id == req.to_customer_id {
+        return Ok(Some(("INVALID_REQUEST", "Device already belongs to the customer")));
+    }
+    if queries::customer::get_customer(pool, user_id, req.to_customer_id).await?.is_none() {
+        return Ok(Some(("NOT_FOUND", "Customer not found")));
+    }
+    Ok(check_device_location(pool, user_id, req.to_customer_id, req.site_id, None, None)
+        .await?
+        .map(|reason| ("INVALID_REQUEST", reason)))
+}
+
+/// The new owner already has a device with the same serial number or name
+fn is_duplicate_device_error(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<sqlx::Error>() {
+        Some(sqlx::Error::Database(db_err)) => db_err.code().as_deref() == Some("23505"),
+        _ => false,
+    }
+}
+
+/// Handle device.transfer messages
+pub async fn handle_transfer(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received device.transfer message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<TransferDeviceRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let (user_id, actor_id) = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => (info.data_user_id(), info.user_id),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let payload = &request.payload;
+        match check_transfer(&pool, user_id, payload).await {
+            Ok(None) => {}
+            Ok(Some((code, reason))) => {
+                let error = ErrorResponse::new(request.id, code, reason);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to check device transfer: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        match queries::device::transfer_device(&pool, user_id, actor_id, payload).await {
+            Ok(Some(outcome)) => {
+                info!(
+                    "Device {} transferred to customer {} ({} open revisions moved)",
+                    payload.device_id,
+                    payload.to_customer_id,
+                    outcome.moved_revisions.len()
+                );
+                webhook_delivery::emit(&client, &pool, user_id, "device.transferred", &outcome.transfer);
+                // An appointment agreed with the previous owner has to be arranged again
+                let reschedule_needed = outcome
+                    .moved_revisions
+                    .iter()
+                    .filter(|(_, status)| status != "upcoming")
+                    .map(|(id, _)| *id)
+                    .collect();
+                let response = SuccessResponse::new(
+                    request.id,
+                    TransferDeviceResponse {
+                        transfer: outcome.transfer,
+                        device: outcome.device,
+                        reschedule_needed,
+                    },
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Device not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) if is_duplicate_device_error(&e) => {
+                let error = ErrorResponse::new(
+                    request.id,
+                    "CONFLICT",
+                    "The customer already has a device with the same serial number or name",
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to transfer device: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle device.transfers messages - transfer history of a device
+pub async fn handle_list_transfers(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received device.transfers message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ListDeviceTransfersRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::device::list_device_transfers(&pool, user_id, request.payload.device_id).await {
+            Ok(transfers) => {
+                let response = SuccessResponse::new(request.id, ListDeviceTransfersResponse { transfers });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list device transfers: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
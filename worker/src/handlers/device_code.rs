@@ -0,0 +1,198 @@
+//! Device sticker handlers for NATS messages
+//!
+//! Every device has a signed code printed as a QR sticker on the device
+//! and on its revision reports. The mobile app scans it and asks
+//! `sazinka.device.lookup_by_code` for the device record.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use base64::Engine;
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+
+use super::parse_authenticated;
+use crate::db::queries;
+use crate::services::device_code::{device_code, device_url, parse_device_code, qr_matrix, qr_svg};
+use crate::services::revision_report::{report_filename, ReportRenderer};
+use crate::subjects;
+use crate::types::device::{DeviceCodeResponse, DeviceIdRequest, DeviceLookupResponse, LookupDeviceByCodeRequest};
+use crate::types::revision::{RevisionIdRequest, RevisionReportPdfResponse};
+use crate::types::{ErrorResponse, SuccessResponse};
+
+/// Shared by the device sticker handlers
+#[derive(Clone)]
+pub struct DeviceCodeContext {
+    pub pool: PgPool,
+    pub jwt_secret: Arc<String>,
+    pub app_base_url: Arc<String>,
+}
+
+/// Start the device sticker and revision report NATS handlers
+pub async fn start_handlers(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    app_base_url: Arc<String>,
+) -> Result<()> {
+    info!("Starting device code handlers...");
+    let ctx = DeviceCodeContext { pool, jwt_secret, app_base_url };
+
+    let [qr_sub, lookup_sub, report_sub] = subjects::subscribe_all(
+        &client,
+        [subjects::device::QR, subjects::device::LOOKUP_BY_CODE, subjects::revision::REPORT_PDF],
+    )
+    .await?;
+
+    tokio::spawn(handle_qr(client.clone(), qr_sub, ctx.clone()));
+    tokio::spawn(handle_lookup_by_code(client.clone(), lookup_sub, ctx.clone()));
+    tokio::spawn(handle_report_pdf(client.clone(), report_sub, ctx));
+
+    info!("Device code handlers started");
+    Ok(())
+}
+
+/// Handle device.qr messages - the sticker of a device
+pub async fn handle_qr(client: Client, mut subscriber: Subscriber, ctx: DeviceCodeContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received device.qr message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<DeviceIdRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        match queries::device::get_device_by_id(&ctx.pool, user_id, request.payload.id).await {
+            Ok(Some(device)) => {
+                let code = device_code(&ctx.jwt_secret, device.id);
+                let url = device_url(&ctx.app_base_url, &code);
+                let svg = qr_matrix(&url).map(|m| qr_svg(&m)).unwrap_or_default();
+                let response = SuccessResponse::new(request.id, DeviceCodeResponse { device_id: device.id, code, url, svg });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Device not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to get device: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle device.lookup_by_code messages - a scanned sticker
+pub async fn handle_lookup_by_code(client: Client, mut subscriber: Subscriber, ctx: DeviceCodeContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received device.lookup_by_code message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<LookupDeviceByCodeRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        let Some(device_id) = parse_device_code(&ctx.jwt_secret, &request.payload.code) else {
+            warn!("Rejected an invalid device code");
+            let error = ErrorResponse::new(request.id, "INVALID_CODE", "Not a device code");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        };
+
+        let loaded = async {
+            // Devices of other accounts are not found, like unknown ids
+            let Some(device) = queries::device::get_device_by_id(&ctx.pool, user_id, device_id).await? else {
+                return anyhow::Ok(None);
+            };
+            let customer = queries::customer::get_customer(&ctx.pool, user_id, device.customer_id).await?;
+            let revisions = queries::revision::list_revisions_by_device(&ctx.pool, device.id, user_id).await?;
+            Ok(Some((device, customer, revisions)))
+        }
+        .await;
+
+        match loaded {
+            Ok(Some((device, customer, revisions))) => {
+                let (customer_name, customer_street, customer_city, customer_postal_code) = match customer {
+                    Some(c) => (c.name, c.street, c.city, c.postal_code),
+                    None => (None, None, None, None),
+                };
+                let response = SuccessResponse::new(request.id, DeviceLookupResponse {
+                    device,
+                    customer_name,
+                    customer_street,
+                    customer_city,
+                    customer_postal_code,
+                    revisions,
+                });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Device not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to look up device: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle revision.report_pdf messages - the report with the device QR code
+pub async fn handle_report_pdf(client: Client, mut subscriber: Subscriber, ctx: DeviceCodeContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received revision.report_pdf message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<RevisionIdRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        let rendered = async {
+            let Some(revision) = queries::revision::get_revision(&ctx.pool, request.payload.id, user_id).await? else {
+                return anyhow::Ok(None);
+            };
+            let Some(mut renderer) = ReportRenderer::new(&ctx.pool, &ctx.jwt_secret, &ctx.app_base_url, user_id).await?
+            else {
+                return Ok(None);
+            };
+            Ok(renderer.render(&revision).await?.map(|pdf| (report_filename(&revision), pdf)))
+        }
+        .await;
+
+        match rendered {
+            Ok(Some((filename, pdf))) => {
+                let response = SuccessResponse::new(request.id, RevisionReportPdfResponse {
+                    filename,
+                    content_base64: base64::engine::general_purpose::STANDARD.encode(pdf),
+                });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Revision not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to render revision report: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
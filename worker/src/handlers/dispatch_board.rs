@@ -0,0 +1,84 @@
+//! Dispatch board handlers for NATS messages
+//!
+//! The board subscribes to the feed subject of a day, then requests the
+//! snapshot; deltas on the feed keep it current (see `types::dispatch_board`).
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::services::dispatch_board;
+use crate::subjects;
+use crate::types::dispatch_board::BoardSnapshotRequest;
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Start all dispatch board NATS handlers
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting dispatch board handlers...");
+
+    let snapshot_sub = client.subscribe(subjects::board::SNAPSHOT).await?;
+
+    tokio::spawn(handle_snapshot(client.clone(), snapshot_sub, pool, jwt_secret));
+
+    info!("Dispatch board handlers started");
+    Ok(())
+}
+
+/// Handle board.snapshot messages
+pub async fn handle_snapshot(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received board.snapshot message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<BoardSnapshotRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match dispatch_board::snapshot(&pool, user_id, request.payload.date, request.demo_mode).await {
+            Ok(snapshot) => {
+                let response = SuccessResponse::new(request.id, snapshot);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to load dispatch board: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -10,40 +10,59 @@
 use std::sync::Arc;
 use anyhow::Result;
 use async_nats::Client;
-use async_nats::jetstream::{self, Context as JsContext};
+use async_nats::jetstream;
 use futures::StreamExt;
 use sqlx::PgPool;
 use tracing::{info, warn, error};
 use uuid::Uuid;
 
-use crate::services::geocoding::Geocoder;
+use crate::db::queries;
+use crate::services::geocode_freshness;
+use crate::services::geocode_priority::{self, GEOCODE_PRIORITY};
+use crate::services::geocoding::{GeocodeScope, Geocoder};
+use crate::subjects;
+use crate::transport::{JobMessage, JobQueue};
 use crate::types::{
     GeocodeJobRequest, GeocodeJobStatus, GeocodeJobStatusUpdate,
     GeocodeAddressJobRequest, GeocodeAddressJobStatus, GeocodeAddressJobStatusUpdate,
     ReverseGeocodeJobRequest, ReverseGeocodeJobStatus, ReverseGeocodeJobStatusUpdate,
+    GeocodeRerunRequest, GeocodeRerunResponse, GEOCODE_RERUN_SAMPLE_SIZE, MAX_GEOCODE_RERUN_CUSTOMERS,
     QueuedGeocodeJob, ErrorResponse, Request, SuccessResponse,
 };
 
 // Stream and consumer names
 const STREAM_NAME: &str = "SAZINKA_GEOCODE_JOBS";
 const CONSUMER_NAME: &str = "geocode_workers";
-const SUBJECT_JOBS: &str = "sazinka.jobs.geocode";
-const SUBJECT_STATUS_PREFIX: &str = "sazinka.job.geocode.status";
+const SUBJECT_JOBS: &str = subjects::jobs::GEOCODE;
+const SUBJECT_STATUS_PREFIX: &str = subjects::job::GEOCODE_STATUS;
+
+/// Large jobs (imports, reruns); see `services::geocode_priority`
+const BULK_STREAM_NAME: &str = "SAZINKA_GEOCODE_BULK_JOBS";
+const BULK_CONSUMER_NAME: &str = "geocode_bulk_workers";
+const SUBJECT_BULK_JOBS: &str = subjects::jobs::GEOCODE_BULK;
 
 const ADDRESS_STREAM_NAME: &str = "SAZINKA_GEOCODE_ADDRESS_JOBS";
 const ADDRESS_CONSUMER_NAME: &str = "geocode_address_workers";
-const SUBJECT_ADDRESS_JOBS: &str = "sazinka.jobs.geocode.address";
-const SUBJECT_ADDRESS_STATUS_PREFIX: &str = "sazinka.job.geocode.address.status";
+const SUBJECT_ADDRESS_JOBS: &str = subjects::jobs::GEOCODE_ADDRESS;
+const SUBJECT_ADDRESS_STATUS_PREFIX: &str = subjects::job::GEOCODE_ADDRESS_STATUS;
 
 const REVERSE_STREAM_NAME: &str = "SAZINKA_REVERSE_GEOCODE_JOBS";
 const REVERSE_CONSUMER_NAME: &str = "reverse_geocode_workers";
-const SUBJECT_REVERSE_JOBS: &str = "sazinka.jobs.geocode.reverse";
-const SUBJECT_REVERSE_STATUS_PREFIX: &str = "sazinka.job.geocode.reverse.status";
+const SUBJECT_REVERSE_JOBS: &str = subjects::jobs::GEOCODE_REVERSE;
+const SUBJECT_REVERSE_STATUS_PREFIX: &str = subjects::job::GEOCODE_REVERSE_STATUS;
+
+/// Outcome of geocoding one customer
+enum GeocodeOutcome {
+    Located,
+    /// Located outside the account's country; stored with status 'review'
+    NeedsReview,
+    NotFound,
+}
 
 /// Geocoding job processor
 pub struct GeocodeProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
     pool: PgPool,
     geocoder: Arc<dyn Geocoder>,
 }
@@ -55,7 +74,7 @@ impl GeocodeProcessor {
         pool: PgPool,
         geocoder: Arc<dyn Geocoder>,
     ) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+        let queue = JobQueue::new(client.clone());
         
         // Create or get stream for geocoding jobs
         let stream_config = jetstream::stream::Config {
@@ -67,9 +86,20 @@ impl GeocodeProcessor {
             ..Default::default()
         };
         
-        js.get_or_create_stream(stream_config).await?;
+        queue.ensure_stream(stream_config).await?;
         info!("JetStream geocode stream '{}' ready", STREAM_NAME);
 
+        let bulk_stream_config = jetstream::stream::Config {
+            name: BULK_STREAM_NAME.to_string(),
+            subjects: vec![SUBJECT_BULK_JOBS.to_string()],
+            max_messages: 1_000,
+            max_bytes: 10 * 1024 * 1024, // 10 MB
+            retention: jetstream::stream::RetentionPolicy::WorkQueue,
+            ..Default::default()
+        };
+        queue.ensure_stream(bulk_stream_config).await?;
+        info!("JetStream geocode bulk stream '{}' ready", BULK_STREAM_NAME);
+
         let address_stream_config = jetstream::stream::Config {
             name: ADDRESS_STREAM_NAME.to_string(),
             subjects: vec![SUBJECT_ADDRESS_JOBS.to_string()],
@@ -78,7 +108,7 @@ impl GeocodeProcessor {
             retention: jetstream::stream::RetentionPolicy::WorkQueue,
             ..Default::default()
         };
-        js.get_or_create_stream(address_stream_config).await?;
+        queue.ensure_stream(address_stream_config).await?;
         info!("JetStream geocode address stream '{}' ready", ADDRESS_STREAM_NAME);
 
         let reverse_stream_config = jetstream::stream::Config {
@@ -89,25 +119,26 @@ impl GeocodeProcessor {
             retention: jetstream::stream::RetentionPolicy::WorkQueue,
             ..Default::default()
         };
-        js.get_or_create_stream(reverse_stream_config).await?;
+        queue.ensure_stream(reverse_stream_config).await?;
         info!("JetStream reverse geocode stream '{}' ready", REVERSE_STREAM_NAME);
         
         Ok(Self {
             client,
-            js,
+            queue,
             pool,
             geocoder,
         })
     }
     
-    /// Submit a geocoding job to the queue
+    /// Submit a geocoding job to the interactive or bulk queue, by size
     pub async fn submit_job(&self, request: GeocodeJobRequest) -> Result<Uuid> {
         let job = QueuedGeocodeJob::new(request);
         let job_id = job.id;
         
         // Publish to JetStream
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish(SUBJECT_JOBS, payload.into()).await?.await?;
+        let subject = geocode_priority::jobs_subject(job.request.customer_ids.len());
+        self.queue.publish(subject, payload.into()).await?;
         
         info!("Geocode job {} submitted with {} customers", job_id, job.request.customer_ids.len());
         
@@ -120,7 +151,7 @@ impl GeocodeProcessor {
     /// Publish a status update for a job
     pub async fn publish_status(&self, job_id: Uuid, status: GeocodeJobStatus) -> Result<()> {
         let update = GeocodeJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", SUBJECT_STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(SUBJECT_STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         
         self.client.publish(subject, payload.into()).await?;
@@ -129,7 +160,7 @@ impl GeocodeProcessor {
 
     pub async fn publish_address_status(&self, job_id: Uuid, status: GeocodeAddressJobStatus) -> Result<()> {
         let update = GeocodeAddressJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", SUBJECT_ADDRESS_STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(SUBJECT_ADDRESS_STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         self.client.publish(subject, payload.into()).await?;
         Ok(())
@@ -137,27 +168,32 @@ impl GeocodeProcessor {
 
     pub async fn publish_reverse_status(&self, job_id: Uuid, status: ReverseGeocodeJobStatus) -> Result<()> {
         let update = ReverseGeocodeJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", SUBJECT_REVERSE_STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(SUBJECT_REVERSE_STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         self.client.publish(subject, payload.into()).await?;
         Ok(())
     }
     
-    /// Start processing geocoding jobs from the queue
+    /// Start processing interactive geocoding jobs from the queue
     pub async fn start_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(STREAM_NAME).await?;
-        
+        self.consume_jobs(STREAM_NAME, CONSUMER_NAME, false).await
+    }
+
+    /// Start processing bulk geocoding jobs, which give way to interactive ones
+    pub async fn start_bulk_processing(self: Arc<Self>) -> Result<()> {
+        self.consume_jobs(BULK_STREAM_NAME, BULK_CONSUMER_NAME, true).await
+    }
+
+    async fn consume_jobs(self: Arc<Self>, stream: &str, consumer: &str, bulk: bool) -> Result<()> {
         let consumer_config = jetstream::consumer::pull::Config {
-            durable_name: Some(CONSUMER_NAME.to_string()),
+            durable_name: Some(consumer.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
             max_deliver: 3, // Retry up to 3 times
             ..Default::default()
         };
         
-        let consumer = stream.get_or_create_consumer(CONSUMER_NAME, consumer_config).await?;
-        info!("JetStream geocode consumer '{}' ready", CONSUMER_NAME);
-        
-        let mut messages = consumer.messages().await?;
+        let mut messages = self.queue.consume(stream, consumer_config).await?;
+        info!("JetStream geocode consumer '{}' ready", consumer);
         
         while let Some(msg) = messages.next().await {
             match msg {
@@ -165,7 +201,7 @@ impl GeocodeProcessor {
                     let processor = Arc::clone(&self);
                     
                     // Process job (not spawning separate task to maintain order)
-                    if let Err(e) = processor.process_job(msg).await {
+                    if let Err(e) = processor.process_job(msg, bulk).await {
                         error!("Failed to process geocode job: {}", e);
                     }
                 }
@@ -179,17 +215,15 @@ impl GeocodeProcessor {
     }
 
     pub async fn start_address_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(ADDRESS_STREAM_NAME).await?;
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(ADDRESS_CONSUMER_NAME.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
             max_deliver: 3,
             ..Default::default()
         };
-        let consumer = stream.get_or_create_consumer(ADDRESS_CONSUMER_NAME, consumer_config).await?;
+        let mut messages = self.queue.consume(ADDRESS_STREAM_NAME, consumer_config).await?;
         info!("JetStream geocode address consumer '{}' ready", ADDRESS_CONSUMER_NAME);
 
-        let mut messages = consumer.messages().await?;
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -206,17 +240,15 @@ impl GeocodeProcessor {
     }
 
     pub async fn start_reverse_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(REVERSE_STREAM_NAME).await?;
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(REVERSE_CONSUMER_NAME.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
             max_deliver: 3,
             ..Default::default()
         };
-        let consumer = stream.get_or_create_consumer(REVERSE_CONSUMER_NAME, consumer_config).await?;
+        let mut messages = self.queue.consume(REVERSE_STREAM_NAME, consumer_config).await?;
         info!("JetStream reverse geocode consumer '{}' ready", REVERSE_CONSUMER_NAME);
 
-        let mut messages = consumer.messages().await?;
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -233,7 +265,7 @@ impl GeocodeProcessor {
     }
     
     /// Process a single geocoding job
-    async fn process_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_job(&self, msg: JobMessage, bulk: bool) -> Result<()> {
         use crate::services::job_history::JOB_HISTORY;
         use crate::services::cancellation::CANCELLATION;
         
@@ -254,8 +286,11 @@ impl GeocodeProcessor {
         
         info!("Processing geocode job {} with {} customers", job_id, total);
         
+        let _interactive = (!bulk).then(|| GEOCODE_PRIORITY.interactive());
+        let scope = self.account_scope(user_id).await;
         let mut succeeded = 0u32;
         let mut failed = 0u32;
+        let mut needs_review = 0u32;
         let mut failed_addresses: Vec<String> = Vec::new();
         
         for (i, customer_id) in customer_ids.iter().enumerate() {
@@ -281,12 +316,24 @@ impl GeocodeProcessor {
                 }).await?;
             }
             
+            if bulk {
+                GEOCODE_PRIORITY.bulk_turn().await;
+            }
+
+            if let Err(e) = self.geocode_customer_addresses(*customer_id, &scope).await {
+                warn!("Failed to geocode addresses of customer {}: {}", customer_id, e);
+            }
+
             // Get customer from database
-            match self.geocode_customer(*customer_id).await {
-                Ok(true) => {
+            match self.geocode_customer(*customer_id, &scope, job.request.force).await {
+                Ok(GeocodeOutcome::Located) => {
                     succeeded += 1;
                 }
-                Ok(false) => {
+                Ok(GeocodeOutcome::NeedsReview) => {
+                    succeeded += 1;
+                    needs_review += 1;
+                }
+                Ok(GeocodeOutcome::NotFound) => {
                     // No result from geocoder, not an error but no coordinates
                     failed += 1;
                     if let Some(addr) = self.get_customer_address(*customer_id).await {
@@ -309,6 +356,7 @@ impl GeocodeProcessor {
             succeeded,
             failed,
             failed_addresses: failed_addresses.into_iter().take(50).collect(), // Limit to 50
+            needs_review,
         }).await?;
         
         // Acknowledge the message
@@ -331,7 +379,7 @@ impl GeocodeProcessor {
         Ok(())
     }
 
-    async fn process_address_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_address_job(&self, msg: JobMessage) -> Result<()> {
         use crate::services::job_history::JOB_HISTORY;
         
         #[derive(serde::Deserialize)]
@@ -358,10 +406,13 @@ impl GeocodeProcessor {
 
         self.publish_address_status(job_id, GeocodeAddressJobStatus::Processing).await?;
 
-        let result = self.geocoder.geocode(
+        let _interactive = GEOCODE_PRIORITY.interactive();
+        let scope = self.account_scope(user_id).await;
+        let result = self.geocoder.geocode_in(
             &job.request.street,
             &job.request.city,
             &job.request.postal_code,
+            &scope,
         ).await?;
 
         match result {
@@ -369,6 +420,7 @@ impl GeocodeProcessor {
                 self.publish_address_status(job_id, GeocodeAddressJobStatus::Completed {
                     coordinates: geo.coordinates,
                     display_name: Some(geo.display_name.clone()),
+                    needs_review: geo.needs_review(),
                 }).await?;
                 let _ = msg.ack().await;
                 JOB_HISTORY.record_completed(job_id, "geocode.address", user_id, started_at, Some(geo.display_name));
@@ -385,7 +437,7 @@ impl GeocodeProcessor {
         Ok(())
     }
 
-    async fn process_reverse_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_reverse_job(&self, msg: JobMessage) -> Result<()> {
         use crate::services::job_history::JOB_HISTORY;
         
         #[derive(serde::Deserialize)]
@@ -412,6 +464,7 @@ impl GeocodeProcessor {
 
         self.publish_reverse_status(job_id, ReverseGeocodeJobStatus::Processing).await?;
 
+        let _interactive = GEOCODE_PRIORITY.interactive();
         let result = self.geocoder.reverse_geocode(job.request.lat, job.request.lng).await?;
         match result {
             Some(addr) => {
@@ -453,8 +506,24 @@ impl GeocodeProcessor {
         Ok(())
     }
     
-    /// Geocode a single customer and update database
-    async fn geocode_customer(&self, customer_id: Uuid) -> Result<bool> {
+    /// Geocoding scope of the account (its country); CZ if unknown
+    async fn account_scope(&self, user_id: Uuid) -> GeocodeScope {
+        if user_id.is_nil() {
+            return GeocodeScope::default();
+        }
+        match queries::user::get_country(&self.pool, user_id).await {
+            Ok(country) => GeocodeScope::for_country(country.as_deref()),
+            Err(e) => {
+                warn!("Failed to load country of {}: {}", user_id, e);
+                GeocodeScope::default()
+            }
+        }
+    }
+
+    /// Geocode a single customer and update database. With `force`, customers
+    /// that already have coordinates are geocoded again; they keep their
+    /// coordinates when the address is no longer found.
+    async fn geocode_customer(&self, customer_id: Uuid, scope: &GeocodeScope, force: bool) -> Result<GeocodeOutcome> {
         // Get customer address from database
         // Note: street, city, postal_code are nullable in the schema
         let customer: Option<(Option<String>, Option<String>, Option<String>, Option<f64>, Option<f64>)> = sqlx::query_as(
@@ -472,13 +541,32 @@ impl GeocodeProcessor {
             Some(c) => c,
             None => {
                 warn!("Customer {} not found", customer_id);
-                return Ok(false);
+                return Ok(GeocodeOutcome::NotFound);
             }
         };
         
+        let located = lat.is_some() && lng.is_some();
         // Skip if already has coordinates
-        if lat.is_some() && lng.is_some() {
-            return Ok(true);
+        if located && !force {
+            return Ok(GeocodeOutcome::Located);
+        }
+
+        // Countries without map data cannot be geocoded (nor routed)
+        if queries::country::customer_map_coverage(&self.pool, customer_id).await? == Some(false) {
+            if !located {
+                sqlx::query(
+                    r#"
+                    UPDATE customers
+                    SET geocode_status = 'failed', updated_at = NOW()
+                    WHERE id = $1
+                    "#
+                )
+                .bind(customer_id)
+                .execute(&self.pool)
+                .await?;
+            }
+            warn!("Customer {} is in a country without map coverage, skipping geocoding", customer_id);
+            return Ok(GeocodeOutcome::NotFound);
         }
 
         // Need at least street and city for geocoding
@@ -486,35 +574,44 @@ impl GeocodeProcessor {
             Some(s) if !s.is_empty() => s,
             _ => {
                 warn!("Customer {} has no street address, skipping geocoding", customer_id);
-                return Ok(false);
+                return Ok(GeocodeOutcome::NotFound);
             }
         };
         let city = city_opt.unwrap_or_default();
         let postal_code = postal_code_opt.unwrap_or_default();
         
         // Call geocoder
-        let result = self.geocoder.geocode(&street, &city, &postal_code).await?;
+        let result = self.geocoder.geocode_in(&street, &city, &postal_code, scope).await?;
         
         match result {
             Some(geo_result) => {
-                // Update database with coordinates and status
+                // Results outside the account's country keep their
+                // coordinates but wait for a user to confirm them
+                let needs_review = geo_result.needs_review();
+                let status = if needs_review { "review" } else { "success" };
                 sqlx::query(
                     r#"
                     UPDATE customers
-                    SET lat = $1, lng = $2, geocode_status = 'success', updated_at = NOW()
-                    WHERE id = $3
+                    SET lat = $1, lng = $2, geocode_status = $3::geocode_status_enum, updated_at = NOW()
+                    WHERE id = $4
                     "#
                 )
                 .bind(geo_result.coordinates.lat)
                 .bind(geo_result.coordinates.lng)
+                .bind(status)
                 .bind(customer_id)
                 .execute(&self.pool)
                 .await?;
                 
-                info!("Geocoded customer {}: ({}, {})", 
-                      customer_id, geo_result.coordinates.lat, geo_result.coordinates.lng);
+                info!("Geocoded customer {}: ({}, {}){}", 
+                      customer_id, geo_result.coordinates.lat, geo_result.coordinates.lng,
+                      if needs_review { ", needs review" } else { "" });
                 
-                Ok(true)
+                Ok(if needs_review { GeocodeOutcome::NeedsReview } else { GeocodeOutcome::Located })
+            }
+            None if located => {
+                warn!("No geocoding result for customer {}, keeping previous coordinates", customer_id);
+                Ok(GeocodeOutcome::NotFound)
             }
             None => {
                 // Mark as failed - address cannot be located
@@ -531,11 +628,34 @@ impl GeocodeProcessor {
                 
                 warn!("No geocoding result for customer {} ({}, {}, {})", 
                       customer_id, street, city, postal_code);
-                Ok(false)
+                Ok(GeocodeOutcome::NotFound)
             }
         }
     }
     
+    /// Geocode the customer's other addresses that are still pending
+    async fn geocode_customer_addresses(&self, customer_id: Uuid, scope: &GeocodeScope) -> Result<()> {
+        for site in queries::customer_site::list_pending_geocode(&self.pool, customer_id).await? {
+            let street = site.street.clone().unwrap_or_default();
+            let city = site.city.clone().unwrap_or_default();
+            if street.is_empty() && city.is_empty() {
+                queries::customer_site::set_geocode_result(&self.pool, site.id, None, false).await?;
+                continue;
+            }
+            let postal_code = site.postal_code.clone().unwrap_or_default();
+
+            let result = self.geocoder.geocode_in(&street, &city, &postal_code, scope).await?;
+            let needs_review = result.as_ref().is_some_and(|r| r.needs_review());
+            let coordinates = result.map(|r| (r.coordinates.lat, r.coordinates.lng));
+            if coordinates.is_none() {
+                warn!("No geocoding result for address {} of customer {}", site.id, customer_id);
+            }
+            queries::customer_site::set_geocode_result(&self.pool, site.id, coordinates, needs_review).await?;
+        }
+
+        Ok(())
+    }
+
     /// Get customer address for error reporting
     async fn get_customer_address(&self, customer_id: Uuid) -> Option<String> {
         let result: Option<(Option<String>, Option<String>, Option<String>)> = sqlx::query_as(
@@ -673,7 +793,7 @@ pub async fn handle_geocode_address_submit(
         };
 
         let payload = serde_json::to_vec(&queued)?;
-        processor.js.publish(SUBJECT_ADDRESS_JOBS, payload.into()).await?.await?;
+        processor.queue.publish(SUBJECT_ADDRESS_JOBS, payload.into()).await?;
 
         processor.publish_address_status(job_id, GeocodeAddressJobStatus::Queued { position: 1 }).await?;
 
@@ -741,7 +861,7 @@ pub async fn handle_reverse_geocode_submit(
         };
 
         let payload = serde_json::to_vec(&queued)?;
-        processor.js.publish(SUBJECT_REVERSE_JOBS, payload.into()).await?.await?;
+        processor.queue.publish(SUBJECT_REVERSE_JOBS, payload.into()).await?;
 
         processor.publish_reverse_status(job_id, ReverseGeocodeJobStatus::Queued { position: 1 }).await?;
 
@@ -755,6 +875,146 @@ pub async fn handle_reverse_geocode_submit(
     Ok(())
 }
 
+/// Handle geocode.rerun - estimate or queue re-geocoding of the customers
+/// matching a filter
+pub async fn handle_geocode_rerun(
+    client: Client,
+    mut subscriber: async_nats::Subscriber,
+    processor: Arc<GeocodeProcessor>,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let reply = match msg.reply {
+            Some(ref r) => r.clone(),
+            None => continue,
+        };
+
+        let request: Request<GeocodeRerunRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse geocode rerun request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        // Require authentication
+        let user_id = match crate::auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let filter = &request.payload;
+        if let Err(msg) = filter.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let matched = match queries::customer::count_geocode_rerun_customers(&processor.pool, user_id, filter).await {
+            Ok(count) => count,
+            Err(e) => {
+                error!("Failed to count geocode rerun customers: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if filter.confirm && matched > MAX_GEOCODE_RERUN_CUSTOMERS {
+            let error = ErrorResponse::new(
+                request.id,
+                "TOO_MANY_CUSTOMERS",
+                format!("{} customers match; narrow the filter to at most {}", matched, MAX_GEOCODE_RERUN_CUSTOMERS),
+            );
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let limit = if filter.confirm { MAX_GEOCODE_RERUN_CUSTOMERS } else { GEOCODE_RERUN_SAMPLE_SIZE };
+        let mut customers =
+            match queries::customer::list_geocode_rerun_customers(&processor.pool, user_id, filter, limit).await {
+                Ok(customers) => customers,
+                Err(e) => {
+                    error!("Failed to list geocode rerun customers: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            };
+
+        let mut job_id = None;
+        if filter.confirm && !customers.is_empty() {
+            let job = GeocodeJobRequest {
+                user_id,
+                customer_ids: customers.iter().map(|c| c.id).collect(),
+                force: true,
+            };
+            match processor.submit_job(job).await {
+                Ok(id) => job_id = Some(id),
+                Err(e) => {
+                    error!("Failed to submit geocode rerun job: {}", e);
+                    let error = ErrorResponse::new(request.id, "SUBMIT_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            }
+        }
+        customers.truncate(GEOCODE_RERUN_SAMPLE_SIZE as usize);
+
+        let response = GeocodeRerunResponse {
+            matched,
+            sample: customers,
+            max_customers: MAX_GEOCODE_RERUN_CUSTOMERS,
+            job_id,
+        };
+        let success = SuccessResponse::new(request.id, response);
+        let _ = client.publish(reply, serde_json::to_vec(&success)?.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle geocode.index.status - age of the geocoding data, so the
+/// dashboard can warn that new addresses may fail to geocode
+pub async fn handle_index_status(
+    client: Client,
+    mut subscriber: async_nats::Subscriber,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let reply = match msg.reply {
+            Some(ref r) => r.clone(),
+            None => continue,
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if crate::auth::extract_auth(&request, &jwt_secret).is_err() {
+            let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let success = SuccessResponse::new(request.id, geocode_freshness::latest());
+        let _ = client.publish(reply, serde_json::to_vec(&success)?.into()).await;
+    }
+
+    Ok(())
+}
+
 /// Handle geocode.pending - get customers without coordinates
 pub async fn handle_geocode_pending(
     client: Client,
@@ -793,8 +1053,13 @@ pub async fn handle_geocode_pending(
         let customers: Vec<(Uuid, Option<String>, Option<String>, Option<String>)> = sqlx::query_as(
             r#"
             SELECT id, name, street, city
-            FROM customers
-            WHERE geocode_status = 'pending' AND user_id = $1
+            FROM customers c
+            WHERE user_id = $1
+              AND (geocode_status = 'pending' OR EXISTS (
+                  SELECT 1 FROM customer_sites s
+                  WHERE s.customer_id = c.id AND s.geocode_status = 'pending'
+                    AND NOT (s.is_primary AND s.address_type = 'billing')
+              ))
             ORDER BY created_at DESC
             LIMIT 1000
             "#
@@ -862,6 +1127,8 @@ mod tests {
     fn test_stream_config_values() {
         assert_eq!(STREAM_NAME, "SAZINKA_GEOCODE_JOBS");
         assert_eq!(SUBJECT_JOBS, "sazinka.jobs.geocode");
+        assert_eq!(BULK_STREAM_NAME, "SAZINKA_GEOCODE_BULK_JOBS");
+        assert_eq!(SUBJECT_BULK_JOBS, "sazinka.jobs.geocode.bulk");
         assert!(SUBJECT_STATUS_PREFIX.starts_with("sazinka.job.geocode.status"));
     }
 }
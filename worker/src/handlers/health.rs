@@ -0,0 +1,43 @@
+//! Health report handler
+//!
+//! `sazinka.health` answers with the dependency report (`types::health`)
+//! for monitoring. Like ping it needs no authentication and ignores the
+//! payload; every worker answers, so a request gets one replica's report.
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use tracing::{debug, warn};
+
+use crate::services::health;
+
+/// Handle sazinka.health messages
+pub async fn handle_health(client: Client, mut subscriber: Subscriber) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received health message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Health message without reply subject");
+                continue;
+            }
+        };
+
+        let Some(report) = health::report().await else {
+            continue;
+        };
+        if !report.ready {
+            let failing: Vec<String> = report
+                .checks
+                .iter()
+                .filter(|c| c.critical && c.detail.is_some())
+                .map(|c| format!("{}: {}", c.name, c.detail.as_deref().unwrap_or_default()))
+                .collect();
+            warn!("Worker not ready ({})", failing.join("; "));
+        }
+        let _ = client.publish(reply, serde_json::to_vec(&report)?.into()).await;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,181 @@
+//! Extension hook handlers for NATS messages
+//!
+//! Plugins (external processes run by the operator) register here for
+//! hooks on selected operations. Registration needs an admin token; every
+//! worker instance receives it and keeps its own copy.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::services::hooks::{HOOKS, REGISTRATION_TTL};
+use crate::subjects;
+use crate::types::hooks::{
+    validate_plugin_name, HookListResponse, HookRegisterRequest, HookRegisterResponse, HookUnregisterRequest,
+    HookUnregisterResponse,
+};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Start all hook NATS handlers
+pub async fn start_handlers(client: Client, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting hook handlers...");
+
+    let [register_sub, unregister_sub, list_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::hooks::REGISTER,
+            subjects::hooks::UNREGISTER,
+            subjects::hooks::LIST,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_register(client.clone(), register_sub, jwt_secret.clone()));
+    tokio::spawn(handle_unregister(client.clone(), unregister_sub, jwt_secret.clone()));
+    tokio::spawn(handle_list(client, list_sub, jwt_secret));
+
+    info!("Hook handlers started");
+    Ok(())
+}
+
+/// Authenticate an admin, replying with the error otherwise
+async fn require_admin<T>(client: &Client, reply: &str, request: &Request<T>, jwt_secret: &str) -> Result<bool> {
+    let error = match auth::extract_auth(request, jwt_secret) {
+        Ok(info) if info.role == "admin" => return Ok(true),
+        Ok(_) => ErrorResponse::new(request.id, "FORBIDDEN", "Admin access required"),
+        Err(_) => ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required"),
+    };
+    let _ = client.publish(reply.to_string(), serde_json::to_vec(&error)?.into()).await;
+    Ok(false)
+}
+
+/// Handle hooks.register messages - register or renew a plugin's hooks
+pub async fn handle_register(client: Client, mut subscriber: Subscriber, jwt_secret: Arc<String>) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received hooks.register message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<HookRegisterRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if !require_admin(&client, &reply, &request, &jwt_secret).await? {
+            continue;
+        }
+
+        let payload = request.payload;
+        if let Err(msg) = payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let registered = HOOKS.register(&payload.plugin, payload.hooks);
+        debug!("Hook plugin {} registered {} hooks", payload.plugin, registered);
+        let response = SuccessResponse::new(
+            request.id,
+            HookRegisterResponse {
+                plugin: payload.plugin,
+                registered,
+                ttl_seconds: REGISTRATION_TTL.as_secs(),
+            },
+        );
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle hooks.unregister messages - a plugin shutting down
+pub async fn handle_unregister(client: Client, mut subscriber: Subscriber, jwt_secret: Arc<String>) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received hooks.unregister message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<HookUnregisterRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if !require_admin(&client, &reply, &request, &jwt_secret).await? {
+            continue;
+        }
+
+        if let Err(msg) = validate_plugin_name(&request.payload.plugin) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let removed = HOOKS.unregister(&request.payload.plugin);
+        info!("Hook plugin {} unregistered ({} hooks)", request.payload.plugin, removed);
+        let response = SuccessResponse::new(request.id, HookUnregisterResponse { removed });
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle hooks.list messages - live registrations of this worker
+pub async fn handle_list(client: Client, mut subscriber: Subscriber, jwt_secret: Arc<String>) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received hooks.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if !require_admin(&client, &reply, &request, &jwt_secret).await? {
+            continue;
+        }
+
+        let response = SuccessResponse::new(request.id, HookListResponse { hooks: HOOKS.list() });
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
@@ -19,7 +19,7 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicU32, Ordering};
 use anyhow::Result;
 use async_nats::{Client, Subscriber};
-use async_nats::jetstream::{self, Context as JsContext};
+use async_nats::jetstream;
 use chrono::{NaiveDate, NaiveTime};
 use futures::StreamExt;
 use sqlx::PgPool;
@@ -28,7 +28,15 @@ use serde_json::json;
 use uuid::Uuid;
 
 use crate::auth;
+use crate::subjects;
+use crate::transport::{JobMessage, JobQueue};
+use super::account;
 use crate::db::queries;
+use crate::services::job_backup::{self, BackupJob};
+use crate::services::import_formats::{to_canonical_csv, ImportKind};
+use crate::services::csv_encoding::replacement_issues;
+use crate::services::import_upload::{self, UploadClaim};
+use crate::services::geocode_priority;
 use crate::types::{
     ErrorResponse, Request, SuccessResponse,
     ImportBatchResponse, ImportIssue, ImportIssueLevel, ImportIssueCode,
@@ -42,20 +50,66 @@ use crate::types::{
     WorkType, WorkResult,
     CustomerImportJobRequest, CustomerImportJobStatus, CustomerImportJobStatusUpdate,
     CustomerImportJobSubmitResponse, QueuedCustomerImportJob,
-    CreateCustomerRequest, CustomerType,
+    CreateCustomerRequest, CustomerType, normalize_acquisition_source,
+    CustomerRefMatch, CustomerRefPreviewRequest, CustomerRefPreviewResponse, CustomerRefPreviewRow,
 };
 
 // =============================================================================
 // CUSTOMER REFERENCE RESOLUTION
 // =============================================================================
 
-/// Find customer by reference (ICO, email, or phone)
+/// Prefixes of an explicit customer id reference ("#id:" typed by hand,
+/// "customer_uuid:" written by the export)
+const CUSTOMER_ID_PREFIXES: &[&str] = &["#id:", "customer_uuid:"];
+
+/// Explicit customer id of a reference, if it carries an id prefix.
+/// `Some(None)` means the prefix is there but the id is not a valid UUID.
+pub(crate) fn parse_customer_id_ref(customer_ref: &str) -> Option<Option<Uuid>> {
+    let customer_ref = customer_ref.trim();
+    CUSTOMER_ID_PREFIXES.iter().find_map(|prefix| {
+        customer_ref
+            .get(..prefix.len())
+            .filter(|head| head.eq_ignore_ascii_case(prefix))
+            .map(|_| Uuid::parse_str(customer_ref[prefix.len()..].trim()).ok())
+    })
+}
+
+/// Find customer by reference (customer code, ICO, email, or phone)
 pub async fn resolve_customer_ref(pool: &PgPool, user_id: Uuid, customer_ref: &str) -> Result<Option<Uuid>> {
-    // Try ICO first (8 digits)
+    Ok(resolve_customer_ref_match(pool, user_id, customer_ref).await?.map(|(id, _)| id))
+}
+
+/// Find customer by reference and report which key matched.
+/// Resolution order: explicit id → customer code → ICO → email → phone.
+/// An explicit id never falls through to the other keys.
+pub async fn resolve_customer_ref_match(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_ref: &str,
+) -> Result<Option<(Uuid, CustomerRefMatch)>> {
+    let customer_ref = customer_ref.trim();
+    if customer_ref.is_empty() {
+        return Ok(None);
+    }
+
+    if let Some(id) = parse_customer_id_ref(customer_ref) {
+        let Some(id) = id else {
+            return Ok(None);
+        };
+        let found = queries::import::find_customer_by_id(pool, user_id, id).await?;
+        return Ok(found.map(|id| (id, CustomerRefMatch::Id)));
+    }
+
+    // Customer codes are unique per account, so they win over everything else
+    if let Some(id) = queries::import::find_customer_by_code(pool, user_id, customer_ref).await? {
+        return Ok(Some((id, CustomerRefMatch::CustomerCode)));
+    }
+
+    // Try ICO (8 digits)
     if customer_ref.chars().all(|c| c.is_ascii_digit()) && customer_ref.len() <= 8 {
         let ico = format!("{:0>8}", customer_ref);
         if let Some(id) = queries::import::find_customer_by_ico(pool, user_id, &ico).await? {
-            return Ok(Some(id));
+            return Ok(Some((id, CustomerRefMatch::Ico)));
         }
     }
     
@@ -63,15 +117,15 @@ pub async fn resolve_customer_ref(pool: &PgPool, user_id: Uuid, customer_ref: &s
     if customer_ref.contains('@') {
         let email = customer_ref.to_lowercase();
         if let Some(id) = queries::import::find_customer_by_email(pool, user_id, &email).await? {
-            return Ok(Some(id));
+            return Ok(Some((id, CustomerRefMatch::Email)));
         }
     }
     
     // Try phone (starts with + or is digits only)
-    if customer_ref.starts_with('+') || customer_ref.chars().all(|c| c.is_ascii_digit()) {
-        let phone = customer_ref.replace([' ', '-', '(', ')'], "");
+    let phone = customer_ref.replace([' ', '-', '(', ')'], "");
+    if phone.starts_with('+') || (!phone.is_empty() && phone.chars().all(|c| c.is_ascii_digit())) {
         if let Some(id) = queries::import::find_customer_by_phone(pool, user_id, &phone).await? {
-            return Ok(Some(id));
+            return Ok(Some((id, CustomerRefMatch::Phone)));
         }
     }
     
@@ -96,6 +150,154 @@ pub async fn resolve_device_ref(pool: &PgPool, _user_id: Uuid, customer_id: Uuid
     Ok(None)
 }
 
+// =============================================================================
+// CUSTOMER REFERENCE PREVIEW
+// =============================================================================
+
+/// Headers accepted for the customer reference column (same as the CSV row types)
+const CUSTOMER_REF_HEADERS: &[&str] = &["customer_ref", "zakaznik", "customer"];
+/// Rows resolved by one preview request
+const MAX_PREVIEW_ROWS: usize = 10_000;
+
+/// Customer references of a semicolon-separated CSV, with their line numbers.
+/// Fails when the file has no customer reference column.
+pub(crate) fn customer_refs_from_csv(content: &str) -> Result<Vec<(i32, Option<String>)>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let column = reader
+        .headers()?
+        .iter()
+        .position(|h| CUSTOMER_REF_HEADERS.iter().any(|name| h.trim().eq_ignore_ascii_case(name)))
+        .ok_or_else(|| anyhow::anyhow!("import:missing_customer_ref"))?;
+
+    let mut refs = Vec::new();
+    for (idx, record) in reader.records().take(MAX_PREVIEW_ROWS).enumerate() {
+        let value = record?
+            .get(column)
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(String::from);
+        refs.push(((idx + 2) as i32, value)); // +2 for header and 1-based indexing
+    }
+    Ok(refs)
+}
+
+/// Resolve customer references without importing anything
+pub async fn preview_customer_refs(
+    pool: &PgPool,
+    user_id: Uuid,
+    refs: Vec<(i32, Option<String>)>,
+) -> Result<CustomerRefPreviewResponse> {
+    // Files reference the same customer many times; resolve each value once
+    let mut resolved: std::collections::HashMap<String, Option<(Uuid, CustomerRefMatch)>> = std::collections::HashMap::new();
+    for value in refs.iter().filter_map(|(_, v)| v.as_ref()) {
+        if !resolved.contains_key(value) {
+            let found = resolve_customer_ref_match(pool, user_id, value).await?;
+            resolved.insert(value.clone(), found);
+        }
+    }
+
+    let mut ids: Vec<Uuid> = resolved.values().flatten().map(|(id, _)| *id).collect();
+    ids.sort();
+    ids.dedup();
+    let names: std::collections::HashMap<Uuid, Option<String>> =
+        queries::import::customer_names(pool, user_id, &ids).await?.into_iter().collect();
+
+    let mut response = CustomerRefPreviewResponse {
+        rows: Vec::with_capacity(refs.len()),
+        total: refs.len() as u32,
+        matched: 0,
+        unmatched: 0,
+        missing: 0,
+    };
+    for (row_number, customer_ref) in refs {
+        let found = customer_ref.as_ref().and_then(|v| resolved.get(v).copied().flatten());
+        match (&customer_ref, found) {
+            (None, _) => response.missing += 1,
+            (Some(_), Some(_)) => response.matched += 1,
+            (Some(_), None) => response.unmatched += 1,
+        }
+        response.rows.push(CustomerRefPreviewRow {
+            row_number,
+            customer_ref,
+            customer_id: found.map(|(id, _)| id),
+            customer_name: found.and_then(|(id, _)| names.get(&id).cloned().flatten()),
+            matched_by: found.map(|(_, key)| key),
+        });
+    }
+    Ok(response)
+}
+
+/// Handle import.customer_ref.preview messages
+pub async fn handle_customer_ref_preview(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received import.customer_ref.preview message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CustomerRefPreviewRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = &request.payload;
+        let refs = match import_upload::load_csv(user_id, payload.filename.as_deref().unwrap_or_default(), payload.upload_id, &payload.csv_content, payload.csv_base64.as_deref())
+            .await
+            .and_then(|decoded| customer_refs_from_csv(&decoded.text))
+        {
+            Ok(refs) => refs,
+            Err(e) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match preview_customer_refs(&pool, user_id, refs).await {
+            Ok(preview) => {
+                let response = SuccessResponse::new(request.id, preview);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Customer reference preview failed: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 // =============================================================================
 // TYPE PARSING
 // =============================================================================
@@ -272,6 +474,10 @@ pub async fn handle_device_import(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let mut imported_count = 0;
         let mut updated_count = 0;
         let mut errors = Vec::new();
@@ -442,6 +648,10 @@ pub async fn handle_revision_import(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let mut imported_count = 0;
         let mut updated_count = 0;
         let mut errors = Vec::new();
@@ -653,6 +863,10 @@ pub async fn handle_communication_import(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let mut imported_count = 0;
         let errors_list: Vec<ImportIssue> = Vec::new();
         let mut errors = errors_list;
@@ -824,6 +1038,10 @@ pub async fn handle_work_log_import(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let mut imported_count = 0;
         let mut errors = Vec::new();
 
@@ -1007,13 +1225,13 @@ pub async fn handle_work_log_import(
 // Stream and consumer names for customer import
 pub(crate) const CUSTOMER_IMPORT_STREAM: &str = "SAZINKA_CUSTOMER_IMPORT_JOBS";
 pub(crate) const CUSTOMER_IMPORT_CONSUMER: &str = "customer_import_workers";
-pub(crate) const CUSTOMER_IMPORT_SUBJECT: &str = "sazinka.import.customer.submit";
-pub(crate) const CUSTOMER_IMPORT_STATUS_PREFIX: &str = "sazinka.job.import.customer.status";
+pub(crate) const CUSTOMER_IMPORT_SUBJECT: &str = subjects::import::CUSTOMER_SUBMIT;
+pub(crate) const CUSTOMER_IMPORT_STATUS_PREFIX: &str = subjects::job::IMPORT_CUSTOMER_STATUS;
 
 /// Processor for customer import jobs
 pub struct CustomerImportProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
     pool: PgPool,
     pending_count: AtomicU32,
 }
@@ -1021,7 +1239,7 @@ pub struct CustomerImportProcessor {
 impl CustomerImportProcessor {
     /// Create a new customer import processor
     pub async fn new(client: Client, pool: PgPool) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+        let queue = JobQueue::new(client.clone());
         
         // Create or get stream for customer import jobs
         let stream_config = jetstream::stream::Config {
@@ -1033,12 +1251,12 @@ impl CustomerImportProcessor {
             ..Default::default()
         };
         
-        js.get_or_create_stream(stream_config).await?;
+        queue.ensure_stream(stream_config).await?;
         info!("JetStream customer import stream '{}' ready", CUSTOMER_IMPORT_STREAM);
         
         Ok(Self {
             client,
-            js,
+            queue,
             pool,
             pending_count: AtomicU32::new(0),
         })
@@ -1051,7 +1269,14 @@ impl CustomerImportProcessor {
         
         // Publish to JetStream
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish(CUSTOMER_IMPORT_SUBJECT, payload.into()).await?.await?;
+        job_backup::publish_with_backup(&self.queue, &self.pool, BackupJob {
+            job_id,
+            user_id,
+            job_type: "import.customer",
+            stream: CUSTOMER_IMPORT_STREAM,
+            subject: CUSTOMER_IMPORT_SUBJECT,
+            filename: Some(&job.request.filename),
+        }, payload).await?;
         
         let pending = self.pending_count.fetch_add(1, Ordering::Relaxed) + 1;
         
@@ -1071,7 +1296,7 @@ impl CustomerImportProcessor {
     /// Publish a status update for a job
     pub async fn publish_status(&self, job_id: Uuid, status: CustomerImportJobStatus) -> Result<()> {
         let update = CustomerImportJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", CUSTOMER_IMPORT_STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(CUSTOMER_IMPORT_STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         
         self.client.publish(subject, payload.into()).await?;
@@ -1080,8 +1305,6 @@ impl CustomerImportProcessor {
     
     /// Start processing jobs from the queue
     pub async fn start_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(CUSTOMER_IMPORT_STREAM).await?;
-        
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(CUSTOMER_IMPORT_CONSUMER.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
@@ -1089,11 +1312,9 @@ impl CustomerImportProcessor {
             ..Default::default()
         };
         
-        let consumer = stream.get_or_create_consumer(CUSTOMER_IMPORT_CONSUMER, consumer_config).await?;
+        let mut messages = self.queue.consume(CUSTOMER_IMPORT_STREAM, consumer_config).await?;
         info!("JetStream customer import consumer '{}' ready", CUSTOMER_IMPORT_CONSUMER);
         
-        let mut messages = consumer.messages().await?;
-        
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -1116,11 +1337,13 @@ impl CustomerImportProcessor {
     }
     
     /// Process a single customer import job
-    async fn process_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_job(&self, msg: JobMessage) -> Result<()> {
         use crate::services::job_history::JOB_HISTORY;
         use crate::services::cancellation::CANCELLATION;
         
         let job: QueuedCustomerImportJob = serde_json::from_slice(&msg.payload)?;
+        job_backup::mark_started(&self.pool, job.id).await;
+        JOB_HISTORY.record_running(job.id, "import.customer", job.user_id, job.submitted_at);
         let job_id = job.id;
         let user_id = job.user_id;
         let started_at = job.submitted_at;
@@ -1143,14 +1366,24 @@ impl CustomerImportProcessor {
         if let Err(e) = msg.ack().await {
             error!("Failed to ack customer import job {}: {:?}", job_id, e);
         }
+        // The uploaded file is removed once the job is done
+        let _upload = job.request.upload_id.map(|id| UploadClaim::new(user_id, id));
         
         // Publish parsing status
         self.publish_status(job_id, CustomerImportJobStatus::Parsing { progress: 0 }).await?;
         
-        // Parse CSV
-        let csv_content = &job.request.csv_content;
-        let rows = match self.parse_csv(csv_content).await {
-            Ok(rows) => rows,
+        // Decode and parse CSV
+        let decoded = import_upload::load_csv(user_id, &job.request.filename, job.request.upload_id, &job.request.csv_content, job.request.csv_base64.as_deref()).await;
+        let parsed = match decoded {
+            // Exports of other applications are mapped to our columns first
+            Ok(decoded) => match to_canonical_csv(job.request.source_system, ImportKind::Customers, &decoded.text) {
+                Ok(text) => self.parse_csv(&text).await.map(|rows| (decoded, rows)),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        let (decoded, rows) = match parsed {
+            Ok(parsed) => parsed,
             Err(e) => {
                 let error_msg = json!({"key": "import:csv_parse_error", "params": {"error": e.to_string()}}).to_string();
                 self.publish_status(job_id, CustomerImportJobStatus::Failed { error: error_msg.clone() }).await?;
@@ -1169,10 +1402,14 @@ impl CustomerImportProcessor {
         
         self.publish_status(job_id, CustomerImportJobStatus::Parsing { progress: 100 }).await?;
         
+        // Rows beyond the plan's customer limit are rejected
+        let quota_remaining = super::import_processors::customer_quota_remaining(&self.pool, user_id).await;
+        let coverage = super::import_processors::customer_coverage(&self.pool, user_id).await;
+
         // Import customers
         let mut succeeded = 0u32;
         let mut failed = 0u32;
-        let mut issues: Vec<ImportIssue> = Vec::new();
+        let mut issues: Vec<ImportIssue> = replacement_issues(&decoded.text);
         
         for (idx, row) in rows.iter().enumerate() {
             let processed = (idx + 1) as u32;
@@ -1197,6 +1434,29 @@ impl CustomerImportProcessor {
                 }).await?;
             }
             
+            if quota_remaining.is_some_and(|remaining| succeeded as i64 >= remaining) {
+                failed += 1;
+                issues.push(super::import_processors::quota_exceeded_issue((idx + 2) as i32, None));
+                continue;
+            }
+
+            let coverage_issue = super::import_processors::coverage_issue(
+                &self.pool,
+                user_id,
+                coverage.as_ref(),
+                (idx + 2) as i32,
+                row.postal_code.as_deref(),
+                row.city.as_deref(),
+            ).await;
+            if let Some(issue) = coverage_issue {
+                let skip = matches!(issue.level, ImportIssueLevel::Error);
+                issues.push(issue);
+                if skip {
+                    failed += 1;
+                    continue;
+                }
+            }
+
             // Create customer
             match self.create_customer(user_id, row).await {
                 Ok(_) => succeeded += 1,
@@ -1218,10 +1478,11 @@ impl CustomerImportProcessor {
         }
         
         // Generate structured report
-        let report = super::import_processors::build_import_report(
+        let mut report = super::import_processors::build_import_report(
             job_id, "import.customer", &job.request.filename,
             started_at, total, succeeded, failed, issues,
         );
+        report.encoding = Some(decoded.encoding.to_string());
         super::import_processors::persist_report(&report);
         
         // Publish completion
@@ -1272,21 +1533,22 @@ impl CustomerImportProcessor {
         let request = GeocodeJobRequest {
             user_id,
             customer_ids,
+            force: false,
         };
         
         let job = QueuedGeocodeJob::new(request);
         let job_id = job.id;
         
         // Get JetStream context and publish
-        let js = jetstream::new(self.client.clone());
+        let queue = JobQueue::new(self.client.clone());
         let payload = serde_json::to_vec(&job)?;
-        js.publish("sazinka.jobs.geocode", payload.into()).await?.await?;
+        queue.publish(geocode_priority::jobs_subject(count), payload.into()).await?;
         
         info!("Triggered geocoding job {} for {} customers after import", job_id, count);
         
         // Publish initial status
         let status_update = GeocodeJobStatusUpdate::new(job_id, GeocodeJobStatus::Queued { position: 1 });
-        let status_subject = format!("sazinka.job.geocode.status.{}", job_id);
+        let status_subject = subjects::job_status(subjects::job::GEOCODE_STATUS, job_id);
         let status_payload = serde_json::to_vec(&status_update)?;
         self.client.publish(status_subject, status_payload.into()).await?;
         
@@ -1383,6 +1645,11 @@ impl CustomerImportProcessor {
                 lat: None,
                 lng: None,
                 notes: row.notes.clone(),
+                parent_customer_id: None,
+                language: None,
+                customer_code: row.customer_code.clone(),
+                acquisition_source: row.acquisition_source.as_deref().and_then(normalize_acquisition_source).map(str::to_string),
+                referrer_customer_id: None,
             },
         ).await?;
         
@@ -1426,6 +1693,12 @@ pub struct CsvCustomerRow {
     pub country: Option<String>,
     #[serde(alias = "notes", alias = "poznamka", alias = "poznamky")]
     pub notes: Option<String>,
+    /// Kept as the customer's code; generated from the account pattern when empty
+    #[serde(alias = "customer_code", alias = "kod_zakaznika", alias = "kod")]
+    pub customer_code: Option<String>,
+    /// Acquisition source; Czech labels are accepted, unknown values are ignored
+    #[serde(alias = "acquisition_source", alias = "source", alias = "zdroj")]
+    pub acquisition_source: Option<String>,
 }
 
 /// CSV row for notes.csv import.
@@ -1529,6 +1802,10 @@ pub async fn handle_customer_import_submit(
                 continue;
             }
         };
+
+        if account::reject_if_read_only(&client, &processor.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
         
         match processor.submit_job(user_id, request.payload).await {
             Ok(response) => {
@@ -7,7 +7,7 @@ use std::sync::atomic::{AtomicU32, Ordering};
 use std::io::{Read as IoRead, Cursor};
 use anyhow::Result;
 use async_nats::{Client, Subscriber};
-use async_nats::jetstream::{self, Context as JsContext};
+use async_nats::jetstream;
 use chrono::{NaiveDate, NaiveTime, Utc};
 use futures::StreamExt;
 use sqlx::PgPool;
@@ -17,16 +17,19 @@ use uuid::Uuid;
 use serde_json::json;
 
 use crate::auth;
+use crate::subjects;
+use crate::transport::{JobMessage, JobQueue};
+use super::account;
 use crate::db::queries;
 use crate::types::{
     ErrorResponse, Request, SuccessResponse,
-    CreateDeviceRequest, CreateRevisionRequest, CreateCustomerRequest, CustomerType,
+    CreateDeviceRequest, CreateRevisionRequest, CreateCustomerRequest, CustomerType, normalize_acquisition_source,
     ImportIssue, ImportIssueLevel, ImportIssueCode, ImportReport,
     // Device import types
     DeviceImportJobRequest, DeviceImportJobStatus, DeviceImportJobStatusUpdate,
     DeviceImportJobSubmitResponse, QueuedDeviceImportJob,
     // Revision import types
-    RevisionImportJobRequest, RevisionImportJobStatus, RevisionImportJobStatusUpdate,
+    RevisionImportJobRequest, RevisionImportJobStatus, RevisionImportJobStatusUpdate, RevisionLinkSummary,
     RevisionImportJobSubmitResponse, QueuedRevisionImportJob,
     // Communication import types
     CommunicationImportJobRequest, CommunicationImportJobStatus, CommunicationImportJobStatusUpdate,
@@ -38,8 +41,20 @@ use crate::types::{
     ZipImportJobRequest, ZipImportJobStatus, ZipImportJobStatusUpdate,
     ZipImportJobSubmitResponse, QueuedZipImportJob, ZipImportFileInfo, ZipImportFileType,
     ZipImportFileResult,
+    // KML import types (reuse customer import status)
+    KmlImportJobRequest, QueuedKmlImportJob,
+    CustomerImportJobStatus, CustomerImportJobStatusUpdate, CustomerImportJobSubmitResponse,
+    QuotaMetric,
 };
+use crate::types::coverage::{normalize_postal_code, CoverageSettings, CoverageVerdict, COVERAGE_SOURCE_IMPORT};
+use crate::services::job_backup::{self, BackupJob};
 use crate::services::job_history::JOB_HISTORY;
+use crate::services::import_formats::{to_canonical_csv, ImportKind};
+use crate::services::csv_encoding::{decode_csv, replacement_issues, DecodedCsv};
+use crate::services::import_upload::{self, UploadClaim};
+use crate::services::kml::{self, KmlPlacemark};
+use crate::services::quota;
+use crate::services::geocode_priority;
 
 use super::import::{resolve_customer_ref, resolve_device_ref, parse_work_type, parse_work_result};
 
@@ -47,6 +62,72 @@ use super::import::{resolve_customer_ref, resolve_device_ref, parse_work_type, p
 // IMPORT REPORT HELPERS
 // =============================================================================
 
+/// Customers the account may still create in an import (None = no limit).
+/// A failed check does not block the import.
+pub async fn customer_quota_remaining(pool: &PgPool, user_id: Uuid) -> Option<i64> {
+    match quota::remaining(pool, user_id, QuotaMetric::Customers).await {
+        Ok(remaining) => remaining,
+        Err(e) => {
+            warn!("Failed to check customer quota of {}: {}", user_id, e);
+            None
+        }
+    }
+}
+
+/// Issue for a row rejected because the plan's customer limit is reached
+pub fn quota_exceeded_issue(row_number: i32, original_value: Option<String>) -> ImportIssue {
+    ImportIssue {
+        row_number,
+        level: ImportIssueLevel::Error,
+        code: ImportIssueCode::QuotaExceeded,
+        field: String::new(),
+        message: "import:quota_exceeded".to_string(),
+        original_value,
+    }
+}
+
+/// Service coverage of an account for an import; None (no check) when it cannot be loaded
+pub async fn customer_coverage(pool: &PgPool, user_id: Uuid) -> Option<CoverageSettings> {
+    match queries::coverage::get_coverage(pool, user_id).await {
+        Ok(coverage) => Some(coverage),
+        Err(e) => {
+            warn!("Failed to load service coverage of {}: {}", user_id, e);
+            None
+        }
+    }
+}
+
+/// Check an imported customer address against the service coverage and log
+/// misses. Returns an Error issue when the row must be skipped, a Warning
+/// issue when it is imported outside the coverage, None when covered.
+pub async fn coverage_issue(
+    pool: &PgPool,
+    user_id: Uuid,
+    coverage: Option<&CoverageSettings>,
+    row_number: i32,
+    postal_code: Option<&str>,
+    city: Option<&str>,
+) -> Option<ImportIssue> {
+    let level = match coverage?.verdict(postal_code) {
+        CoverageVerdict::Covered => return None,
+        CoverageVerdict::Warn => ImportIssueLevel::Warning,
+        CoverageVerdict::Reject => ImportIssueLevel::Error,
+    };
+    let rejected = matches!(level, ImportIssueLevel::Error);
+    let normalized = normalize_postal_code(postal_code.unwrap_or_default());
+    if let Err(e) = queries::coverage::record_miss(pool, user_id, &normalized, city, COVERAGE_SOURCE_IMPORT, rejected).await {
+        warn!("Failed to record coverage miss of {}: {}", user_id, e);
+    }
+    Some(ImportIssue {
+        row_number,
+        level,
+        code: ImportIssueCode::OutOfCoverage,
+        field: "postal_code".to_string(),
+        message: "import:out_of_coverage".to_string(),
+        original_value: postal_code.map(String::from),
+    })
+}
+
 /// Classify an error message into a machine-readable error code
 pub fn classify_error(error_msg: &str) -> (ImportIssueCode, &'static str) {
     let lower = error_msg.to_lowercase();
@@ -104,6 +185,7 @@ pub fn build_import_report(
         imported_count: succeeded,
         updated_count: 0,
         skipped_count: total - succeeded - failed,
+        encoding: None,
         issues,
     }
 }
@@ -157,6 +239,12 @@ pub(crate) struct CsvDeviceRow {
     pub(crate) revision_interval_months: Option<i32>,
     #[serde(alias = "poznamky", alias = "notes")]
     pub(crate) notes: Option<String>,
+    #[serde(alias = "budova", alias = "building")]
+    pub(crate) building: Option<String>,
+    #[serde(alias = "podlazi", alias = "patro", alias = "floor")]
+    pub(crate) floor: Option<String>,
+    #[serde(alias = "mistnost", alias = "room")]
+    pub(crate) room: Option<String>,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -243,19 +331,19 @@ struct CsvVisitRow {
 
 pub(crate) const DEVICE_IMPORT_STREAM: &str = "SAZINKA_DEVICE_IMPORT_JOBS";
 pub(crate) const DEVICE_IMPORT_CONSUMER: &str = "device_import_workers";
-pub(crate) const DEVICE_IMPORT_SUBJECT: &str = "sazinka.import.device.submit";
-pub(crate) const DEVICE_IMPORT_STATUS_PREFIX: &str = "sazinka.job.import.device.status";
+pub(crate) const DEVICE_IMPORT_SUBJECT: &str = subjects::import::DEVICE_SUBMIT;
+pub(crate) const DEVICE_IMPORT_STATUS_PREFIX: &str = subjects::job::IMPORT_DEVICE_STATUS;
 
 pub struct DeviceImportProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
     pool: PgPool,
     pending_count: AtomicU32,
 }
 
 impl DeviceImportProcessor {
     pub async fn new(client: Client, pool: PgPool) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+        let queue = JobQueue::new(client.clone());
         
         let stream_config = jetstream::stream::Config {
             name: DEVICE_IMPORT_STREAM.to_string(),
@@ -266,12 +354,12 @@ impl DeviceImportProcessor {
             ..Default::default()
         };
         
-        js.get_or_create_stream(stream_config).await?;
+        queue.ensure_stream(stream_config).await?;
         info!("JetStream device import stream '{}' ready", DEVICE_IMPORT_STREAM);
         
         Ok(Self {
             client,
-            js,
+            queue,
             pool,
             pending_count: AtomicU32::new(0),
         })
@@ -282,7 +370,14 @@ impl DeviceImportProcessor {
         let job_id = job.id;
         
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish(DEVICE_IMPORT_SUBJECT, payload.into()).await?.await?;
+        job_backup::publish_with_backup(&self.queue, &self.pool, BackupJob {
+            job_id,
+            user_id,
+            job_type: "import.device",
+            stream: DEVICE_IMPORT_STREAM,
+            subject: DEVICE_IMPORT_SUBJECT,
+            filename: Some(&job.request.filename),
+        }, payload).await?;
         
         let pending = self.pending_count.fetch_add(1, Ordering::Relaxed) + 1;
         
@@ -298,15 +393,13 @@ impl DeviceImportProcessor {
     
     pub async fn publish_status(&self, job_id: Uuid, status: DeviceImportJobStatus) -> Result<()> {
         let update = DeviceImportJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", DEVICE_IMPORT_STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(DEVICE_IMPORT_STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         self.client.publish(subject, payload.into()).await?;
         Ok(())
     }
     
     pub async fn start_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(DEVICE_IMPORT_STREAM).await?;
-        
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(DEVICE_IMPORT_CONSUMER.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
@@ -314,11 +407,9 @@ impl DeviceImportProcessor {
             ..Default::default()
         };
         
-        let consumer = stream.get_or_create_consumer(DEVICE_IMPORT_CONSUMER, consumer_config).await?;
+        let mut messages = self.queue.consume(DEVICE_IMPORT_STREAM, consumer_config).await?;
         info!("JetStream device import consumer '{}' ready", DEVICE_IMPORT_CONSUMER);
         
-        let mut messages = consumer.messages().await?;
-        
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -338,10 +429,12 @@ impl DeviceImportProcessor {
         Ok(())
     }
     
-    async fn process_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_job(&self, msg: JobMessage) -> Result<()> {
         use crate::services::cancellation::CANCELLATION;
         
         let job: QueuedDeviceImportJob = serde_json::from_slice(&msg.payload)?;
+        job_backup::mark_started(&self.pool, job.id).await;
+        JOB_HISTORY.record_running(job.id, "import.device", job.user_id, job.submitted_at);
         let job_id = job.id;
         let user_id = job.user_id;
         let started_at = job.submitted_at;
@@ -361,11 +454,22 @@ impl DeviceImportProcessor {
         if let Err(e) = msg.ack().await {
             error!("Failed to ack device import job {}: {:?}", job_id, e);
         }
+        // The uploaded file is removed once the job is done
+        let _upload = job.request.upload_id.map(|id| UploadClaim::new(user_id, id));
         
         self.publish_status(job_id, DeviceImportJobStatus::Parsing { progress: 0 }).await?;
         
-        let rows = match self.parse_csv(&job.request.csv_content).await {
-            Ok(rows) => rows,
+        let decoded = import_upload::load_csv(user_id, &job.request.filename, job.request.upload_id, &job.request.csv_content, job.request.csv_base64.as_deref()).await;
+        let parsed = match decoded {
+            // Exports of other applications are mapped to our columns first
+            Ok(decoded) => match to_canonical_csv(job.request.source_system, ImportKind::Devices, &decoded.text) {
+                Ok(text) => self.parse_csv(&text).await.map(|rows| (decoded, rows)),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        let (decoded, rows) = match parsed {
+            Ok(parsed) => parsed,
             Err(e) => {
                 let error_msg = json!({"key": "import:csv_parse_error", "params": {"error": e.to_string()}}).to_string();
                 self.publish_status(job_id, DeviceImportJobStatus::Failed { error: error_msg.clone() }).await?;
@@ -386,7 +490,7 @@ impl DeviceImportProcessor {
         
         let mut succeeded = 0u32;
         let mut failed = 0u32;
-        let mut issues: Vec<ImportIssue> = Vec::new();
+        let mut issues: Vec<ImportIssue> = replacement_issues(&decoded.text);
         
         for (idx, row) in rows.iter().enumerate() {
             let processed = (idx + 1) as u32;
@@ -425,10 +529,11 @@ impl DeviceImportProcessor {
             }
         }
         
-        let report = build_import_report(
+        let mut report = build_import_report(
             job_id, "import.device", &job.request.filename,
             started_at, total, succeeded, failed, issues,
         );
+        report.encoding = Some(decoded.encoding.to_string());
         persist_report(&report);
         
         self.publish_status(job_id, DeviceImportJobStatus::Completed {
@@ -507,6 +612,12 @@ impl DeviceImportProcessor {
             installation_date,
             revision_interval_months: revision_interval,
             notes: row.notes.clone(),
+            site_id: None,
+            building: row.building.clone(),
+            floor: row.floor.clone(),
+            room: row.room.clone(),
+            location_lat: None,
+            location_lng: None,
         };
         
         let device = queries::device::create_device(
@@ -550,6 +661,10 @@ pub async fn handle_device_import_submit(
                 continue;
             }
         };
+
+        if account::reject_if_read_only(&client, &processor.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
         
         match processor.submit_job(user_id, request.payload).await {
             Ok(response) => {
@@ -573,19 +688,19 @@ pub async fn handle_device_import_submit(
 
 pub(crate) const REVISION_IMPORT_STREAM: &str = "SAZINKA_REVISION_IMPORT_JOBS";
 pub(crate) const REVISION_IMPORT_CONSUMER: &str = "revision_import_workers";
-pub(crate) const REVISION_IMPORT_SUBJECT: &str = "sazinka.import.revision.submit";
-pub(crate) const REVISION_IMPORT_STATUS_PREFIX: &str = "sazinka.job.import.revision.status";
+pub(crate) const REVISION_IMPORT_SUBJECT: &str = subjects::import::REVISION_SUBMIT;
+pub(crate) const REVISION_IMPORT_STATUS_PREFIX: &str = subjects::job::IMPORT_REVISION_STATUS;
 
 pub struct RevisionImportProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
     pool: PgPool,
     pending_count: AtomicU32,
 }
 
 impl RevisionImportProcessor {
     pub async fn new(client: Client, pool: PgPool) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+        let queue = JobQueue::new(client.clone());
         
         let stream_config = jetstream::stream::Config {
             name: REVISION_IMPORT_STREAM.to_string(),
@@ -596,12 +711,12 @@ impl RevisionImportProcessor {
             ..Default::default()
         };
         
-        js.get_or_create_stream(stream_config).await?;
+        queue.ensure_stream(stream_config).await?;
         info!("JetStream revision import stream '{}' ready", REVISION_IMPORT_STREAM);
         
         Ok(Self {
             client,
-            js,
+            queue,
             pool,
             pending_count: AtomicU32::new(0),
         })
@@ -612,7 +727,14 @@ impl RevisionImportProcessor {
         let job_id = job.id;
         
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish(REVISION_IMPORT_SUBJECT, payload.into()).await?.await?;
+        job_backup::publish_with_backup(&self.queue, &self.pool, BackupJob {
+            job_id,
+            user_id,
+            job_type: "import.revision",
+            stream: REVISION_IMPORT_STREAM,
+            subject: REVISION_IMPORT_SUBJECT,
+            filename: Some(&job.request.filename),
+        }, payload).await?;
         
         let pending = self.pending_count.fetch_add(1, Ordering::Relaxed) + 1;
         
@@ -628,15 +750,13 @@ impl RevisionImportProcessor {
     
     pub async fn publish_status(&self, job_id: Uuid, status: RevisionImportJobStatus) -> Result<()> {
         let update = RevisionImportJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", REVISION_IMPORT_STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(REVISION_IMPORT_STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         self.client.publish(subject, payload.into()).await?;
         Ok(())
     }
     
     pub async fn start_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(REVISION_IMPORT_STREAM).await?;
-        
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(REVISION_IMPORT_CONSUMER.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
@@ -644,11 +764,9 @@ impl RevisionImportProcessor {
             ..Default::default()
         };
         
-        let consumer = stream.get_or_create_consumer(REVISION_IMPORT_CONSUMER, consumer_config).await?;
+        let mut messages = self.queue.consume(REVISION_IMPORT_STREAM, consumer_config).await?;
         info!("JetStream revision import consumer '{}' ready", REVISION_IMPORT_CONSUMER);
         
-        let mut messages = consumer.messages().await?;
-        
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -668,10 +786,12 @@ impl RevisionImportProcessor {
         Ok(())
     }
     
-    async fn process_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_job(&self, msg: JobMessage) -> Result<()> {
         use crate::services::cancellation::CANCELLATION;
         
         let job: QueuedRevisionImportJob = serde_json::from_slice(&msg.payload)?;
+        job_backup::mark_started(&self.pool, job.id).await;
+        JOB_HISTORY.record_running(job.id, "import.revision", job.user_id, job.submitted_at);
         let job_id = job.id;
         let user_id = job.user_id;
         let started_at = job.submitted_at;
@@ -691,11 +811,18 @@ impl RevisionImportProcessor {
         if let Err(e) = msg.ack().await {
             error!("Failed to ack revision import job {}: {:?}", job_id, e);
         }
+        // The uploaded file is removed once the job is done
+        let _upload = job.request.upload_id.map(|id| UploadClaim::new(user_id, id));
         
         self.publish_status(job_id, RevisionImportJobStatus::Parsing { progress: 0 }).await?;
         
-        let rows = match self.parse_csv(&job.request.csv_content).await {
-            Ok(rows) => rows,
+        let decoded = import_upload::load_csv(user_id, &job.request.filename, job.request.upload_id, &job.request.csv_content, job.request.csv_base64.as_deref()).await;
+        let parsed = match decoded {
+            Ok(decoded) => self.parse_csv(&decoded.text).await.map(|rows| (decoded, rows)),
+            Err(e) => Err(e),
+        };
+        let (decoded, rows) = match parsed {
+            Ok(parsed) => parsed,
             Err(e) => {
                 let error_msg = json!({"key": "import:csv_parse_error", "params": {"error": e.to_string()}}).to_string();
                 self.publish_status(job_id, RevisionImportJobStatus::Failed { error: error_msg.clone() }).await?;
@@ -716,7 +843,8 @@ impl RevisionImportProcessor {
         
         let mut succeeded = 0u32;
         let mut failed = 0u32;
-        let mut issues: Vec<ImportIssue> = Vec::new();
+        let mut issues: Vec<ImportIssue> = replacement_issues(&decoded.text);
+        let mut revision_ids: Vec<Uuid> = Vec::new();
         
         for (idx, row) in rows.iter().enumerate() {
             let processed = (idx + 1) as u32;
@@ -737,7 +865,10 @@ impl RevisionImportProcessor {
             }
             
             match self.create_revision(user_id, row, (idx + 2) as i32, &mut issues).await {
-                Ok(_) => succeeded += 1,
+                Ok(revision_id) => {
+                    succeeded += 1;
+                    revision_ids.push(revision_id);
+                }
                 Err(e) => {
                     failed += 1;
                     let row_num = (idx + 2) as i32;
@@ -755,10 +886,17 @@ impl RevisionImportProcessor {
             }
         }
         
-        let report = build_import_report(
+        let linking = if job.request.link_visits {
+            Some(self.link_visits(user_id, &revision_ids, job.request.create_missing_visits).await)
+        } else {
+            None
+        };
+
+        let mut report = build_import_report(
             job_id, "import.revision", &job.request.filename,
             started_at, total, succeeded, failed, issues,
         );
+        report.encoding = Some(decoded.encoding.to_string());
         persist_report(&report);
         
         self.publish_status(job_id, RevisionImportJobStatus::Completed {
@@ -766,6 +904,7 @@ impl RevisionImportProcessor {
             succeeded,
             failed,
             report: report.clone(),
+            linking,
         }).await?;
         
         JOB_HISTORY.record_completed_with_report(
@@ -797,6 +936,22 @@ impl RevisionImportProcessor {
         Ok(rows)
     }
     
+    /// Link imported revisions to visit history; failures are logged, not fatal
+    async fn link_visits(&self, user_id: Uuid, revision_ids: &[Uuid], create_missing: bool) -> RevisionLinkSummary {
+        let mut summary = RevisionLinkSummary::default();
+        for &revision_id in revision_ids {
+            match queries::work_item::link_imported_revision(&self.pool, user_id, revision_id, create_missing).await {
+                Ok(outcome) => summary.record(outcome),
+                Err(e) => warn!("Failed to link imported revision {} to a visit: {}", revision_id, e),
+            }
+        }
+        info!(
+            "Linked imported revisions for user {}: {} linked, {} visits created, {} unmatched",
+            user_id, summary.linked, summary.visits_created, summary.unmatched
+        );
+        summary
+    }
+
     /// Create a revision from a CSV row. Uses upsert logic to handle duplicates gracefully.
     async fn create_revision(&self, user_id: Uuid, row: &CsvRevisionRow, row_num: i32, issues: &mut Vec<ImportIssue>) -> Result<Uuid> {
         let customer_ref = row.customer_ref.as_ref()
@@ -910,6 +1065,10 @@ pub async fn handle_revision_import_submit(
                 continue;
             }
         };
+
+        if account::reject_if_read_only(&client, &processor.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
         
         match processor.submit_job(user_id, request.payload).await {
             Ok(response) => {
@@ -933,19 +1092,19 @@ pub async fn handle_revision_import_submit(
 
 pub(crate) const COMMUNICATION_IMPORT_STREAM: &str = "SAZINKA_COMMUNICATION_IMPORT_JOBS";
 pub(crate) const COMMUNICATION_IMPORT_CONSUMER: &str = "communication_import_workers";
-pub(crate) const COMMUNICATION_IMPORT_SUBJECT: &str = "sazinka.import.communication.submit";
-pub(crate) const COMMUNICATION_IMPORT_STATUS_PREFIX: &str = "sazinka.job.import.communication.status";
+pub(crate) const COMMUNICATION_IMPORT_SUBJECT: &str = subjects::import::COMMUNICATION_SUBMIT;
+pub(crate) const COMMUNICATION_IMPORT_STATUS_PREFIX: &str = subjects::job::IMPORT_COMMUNICATION_STATUS;
 
 pub struct CommunicationImportProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
     pool: PgPool,
     pending_count: AtomicU32,
 }
 
 impl CommunicationImportProcessor {
     pub async fn new(client: Client, pool: PgPool) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+        let queue = JobQueue::new(client.clone());
         
         let stream_config = jetstream::stream::Config {
             name: COMMUNICATION_IMPORT_STREAM.to_string(),
@@ -956,12 +1115,12 @@ impl CommunicationImportProcessor {
             ..Default::default()
         };
         
-        js.get_or_create_stream(stream_config).await?;
+        queue.ensure_stream(stream_config).await?;
         info!("JetStream communication import stream '{}' ready", COMMUNICATION_IMPORT_STREAM);
         
         Ok(Self {
             client,
-            js,
+            queue,
             pool,
             pending_count: AtomicU32::new(0),
         })
@@ -972,7 +1131,14 @@ impl CommunicationImportProcessor {
         let job_id = job.id;
         
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish(COMMUNICATION_IMPORT_SUBJECT, payload.into()).await?.await?;
+        job_backup::publish_with_backup(&self.queue, &self.pool, BackupJob {
+            job_id,
+            user_id,
+            job_type: "import.communication",
+            stream: COMMUNICATION_IMPORT_STREAM,
+            subject: COMMUNICATION_IMPORT_SUBJECT,
+            filename: Some(&job.request.filename),
+        }, payload).await?;
         
         let pending = self.pending_count.fetch_add(1, Ordering::Relaxed) + 1;
         
@@ -988,15 +1154,13 @@ impl CommunicationImportProcessor {
     
     pub async fn publish_status(&self, job_id: Uuid, status: CommunicationImportJobStatus) -> Result<()> {
         let update = CommunicationImportJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", COMMUNICATION_IMPORT_STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(COMMUNICATION_IMPORT_STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         self.client.publish(subject, payload.into()).await?;
         Ok(())
     }
     
     pub async fn start_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(COMMUNICATION_IMPORT_STREAM).await?;
-        
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(COMMUNICATION_IMPORT_CONSUMER.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
@@ -1004,11 +1168,9 @@ impl CommunicationImportProcessor {
             ..Default::default()
         };
         
-        let consumer = stream.get_or_create_consumer(COMMUNICATION_IMPORT_CONSUMER, consumer_config).await?;
+        let mut messages = self.queue.consume(COMMUNICATION_IMPORT_STREAM, consumer_config).await?;
         info!("JetStream communication import consumer '{}' ready", COMMUNICATION_IMPORT_CONSUMER);
         
-        let mut messages = consumer.messages().await?;
-        
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -1028,10 +1190,12 @@ impl CommunicationImportProcessor {
         Ok(())
     }
     
-    async fn process_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_job(&self, msg: JobMessage) -> Result<()> {
         use crate::services::cancellation::CANCELLATION;
         
         let job: QueuedCommunicationImportJob = serde_json::from_slice(&msg.payload)?;
+        job_backup::mark_started(&self.pool, job.id).await;
+        JOB_HISTORY.record_running(job.id, "import.communication", job.user_id, job.submitted_at);
         let job_id = job.id;
         let user_id = job.user_id;
         let started_at = job.submitted_at;
@@ -1051,11 +1215,18 @@ impl CommunicationImportProcessor {
         if let Err(e) = msg.ack().await {
             error!("Failed to ack communication import job {}: {:?}", job_id, e);
         }
+        // The uploaded file is removed once the job is done
+        let _upload = job.request.upload_id.map(|id| UploadClaim::new(user_id, id));
         
         self.publish_status(job_id, CommunicationImportJobStatus::Parsing { progress: 0 }).await?;
         
-        let rows = match self.parse_csv(&job.request.csv_content).await {
-            Ok(rows) => rows,
+        let decoded = import_upload::load_csv(user_id, &job.request.filename, job.request.upload_id, &job.request.csv_content, job.request.csv_base64.as_deref()).await;
+        let parsed = match decoded {
+            Ok(decoded) => self.parse_csv(&decoded.text).await.map(|rows| (decoded, rows)),
+            Err(e) => Err(e),
+        };
+        let (decoded, rows) = match parsed {
+            Ok(parsed) => parsed,
             Err(e) => {
                 let error_msg = json!({"key": "import:csv_parse_error", "params": {"error": e.to_string()}}).to_string();
                 self.publish_status(job_id, CommunicationImportJobStatus::Failed { error: error_msg.clone() }).await?;
@@ -1076,7 +1247,7 @@ impl CommunicationImportProcessor {
         
         let mut succeeded = 0u32;
         let mut failed = 0u32;
-        let mut issues: Vec<ImportIssue> = Vec::new();
+        let mut issues: Vec<ImportIssue> = replacement_issues(&decoded.text);
         
         for (idx, row) in rows.iter().enumerate() {
             let processed = (idx + 1) as u32;
@@ -1115,10 +1286,11 @@ impl CommunicationImportProcessor {
             }
         }
         
-        let report = build_import_report(
+        let mut report = build_import_report(
             job_id, "import.communication", &job.request.filename,
             started_at, total, succeeded, failed, issues,
         );
+        report.encoding = Some(decoded.encoding.to_string());
         persist_report(&report);
         
         self.publish_status(job_id, CommunicationImportJobStatus::Completed {
@@ -1229,6 +1401,10 @@ pub async fn handle_communication_import_submit(
                 continue;
             }
         };
+
+        if account::reject_if_read_only(&client, &processor.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
         
         match processor.submit_job(user_id, request.payload).await {
             Ok(response) => {
@@ -1252,19 +1428,19 @@ pub async fn handle_communication_import_submit(
 
 pub(crate) const WORK_LOG_IMPORT_STREAM: &str = "SAZINKA_WORKLOG_IMPORT_JOBS";
 pub(crate) const WORK_LOG_IMPORT_CONSUMER: &str = "worklog_import_workers";
-pub(crate) const WORK_LOG_IMPORT_SUBJECT: &str = "sazinka.import.worklog.submit";
-pub(crate) const WORK_LOG_IMPORT_STATUS_PREFIX: &str = "sazinka.job.import.worklog.status";
+pub(crate) const WORK_LOG_IMPORT_SUBJECT: &str = subjects::import::WORKLOG_SUBMIT;
+pub(crate) const WORK_LOG_IMPORT_STATUS_PREFIX: &str = subjects::job::IMPORT_WORKLOG_STATUS;
 
 pub struct WorkLogImportProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
     pool: PgPool,
     pending_count: AtomicU32,
 }
 
 impl WorkLogImportProcessor {
     pub async fn new(client: Client, pool: PgPool) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+        let queue = JobQueue::new(client.clone());
         
         let stream_config = jetstream::stream::Config {
             name: WORK_LOG_IMPORT_STREAM.to_string(),
@@ -1275,12 +1451,12 @@ impl WorkLogImportProcessor {
             ..Default::default()
         };
         
-        js.get_or_create_stream(stream_config).await?;
+        queue.ensure_stream(stream_config).await?;
         info!("JetStream work_log import stream '{}' ready", WORK_LOG_IMPORT_STREAM);
         
         Ok(Self {
             client,
-            js,
+            queue,
             pool,
             pending_count: AtomicU32::new(0),
         })
@@ -1291,7 +1467,14 @@ impl WorkLogImportProcessor {
         let job_id = job.id;
         
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish(WORK_LOG_IMPORT_SUBJECT, payload.into()).await?.await?;
+        job_backup::publish_with_backup(&self.queue, &self.pool, BackupJob {
+            job_id,
+            user_id,
+            job_type: "import.visit",
+            stream: WORK_LOG_IMPORT_STREAM,
+            subject: WORK_LOG_IMPORT_SUBJECT,
+            filename: Some(&job.request.filename),
+        }, payload).await?;
         
         let pending = self.pending_count.fetch_add(1, Ordering::Relaxed) + 1;
         
@@ -1307,15 +1490,13 @@ impl WorkLogImportProcessor {
     
     pub async fn publish_status(&self, job_id: Uuid, status: WorkLogImportJobStatus) -> Result<()> {
         let update = WorkLogImportJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", WORK_LOG_IMPORT_STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(WORK_LOG_IMPORT_STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         self.client.publish(subject, payload.into()).await?;
         Ok(())
     }
     
     pub async fn start_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(WORK_LOG_IMPORT_STREAM).await?;
-        
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(WORK_LOG_IMPORT_CONSUMER.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
@@ -1323,11 +1504,9 @@ impl WorkLogImportProcessor {
             ..Default::default()
         };
         
-        let consumer = stream.get_or_create_consumer(WORK_LOG_IMPORT_CONSUMER, consumer_config).await?;
+        let mut messages = self.queue.consume(WORK_LOG_IMPORT_STREAM, consumer_config).await?;
         info!("JetStream work_log import consumer '{}' ready", WORK_LOG_IMPORT_CONSUMER);
         
-        let mut messages = consumer.messages().await?;
-        
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -1347,10 +1526,12 @@ impl WorkLogImportProcessor {
         Ok(())
     }
     
-    async fn process_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_job(&self, msg: JobMessage) -> Result<()> {
         use crate::services::cancellation::CANCELLATION;
         
         let job: QueuedWorkLogImportJob = serde_json::from_slice(&msg.payload)?;
+        job_backup::mark_started(&self.pool, job.id).await;
+        JOB_HISTORY.record_running(job.id, "import.visit", job.user_id, job.submitted_at);
         let job_id = job.id;
         let user_id = job.user_id;
         let started_at = job.submitted_at;
@@ -1370,11 +1551,18 @@ impl WorkLogImportProcessor {
         if let Err(e) = msg.ack().await {
             error!("Failed to ack visit import job {}: {:?}", job_id, e);
         }
+        // The uploaded file is removed once the job is done
+        let _upload = job.request.upload_id.map(|id| UploadClaim::new(user_id, id));
         
         self.publish_status(job_id, WorkLogImportJobStatus::Parsing { progress: 0 }).await?;
         
-        let rows = match self.parse_csv(&job.request.csv_content).await {
-            Ok(rows) => rows,
+        let decoded = import_upload::load_csv(user_id, &job.request.filename, job.request.upload_id, &job.request.csv_content, job.request.csv_base64.as_deref()).await;
+        let parsed = match decoded {
+            Ok(decoded) => self.parse_csv(&decoded.text).await.map(|rows| (decoded, rows)),
+            Err(e) => Err(e),
+        };
+        let (decoded, rows) = match parsed {
+            Ok(parsed) => parsed,
             Err(e) => {
                 let error_msg = json!({"key": "import:csv_parse_error", "params": {"error": e.to_string()}}).to_string();
                 self.publish_status(job_id, WorkLogImportJobStatus::Failed { error: error_msg.clone() }).await?;
@@ -1395,7 +1583,7 @@ impl WorkLogImportProcessor {
         
         let mut succeeded = 0u32;
         let mut failed = 0u32;
-        let mut issues: Vec<ImportIssue> = Vec::new();
+        let mut issues: Vec<ImportIssue> = replacement_issues(&decoded.text);
 
         // Group rows by (customer_ref, scheduled_date) — preserving original order
         let mut group_order: Vec<(String, String)> = Vec::new();
@@ -1449,10 +1637,11 @@ impl WorkLogImportProcessor {
             }
         }
         
-        let report = build_import_report(
+        let mut report = build_import_report(
             job_id, "import.worklog", &job.request.filename,
             started_at, total, succeeded, failed, issues,
         );
+        report.encoding = Some(decoded.encoding.to_string());
         persist_report(&report);
         
         self.publish_status(job_id, WorkLogImportJobStatus::Completed {
@@ -1664,6 +1853,10 @@ pub async fn handle_work_log_import_submit(
                 continue;
             }
         };
+
+        if account::reject_if_read_only(&client, &processor.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
         
         match processor.submit_job(user_id, request.payload).await {
             Ok(response) => {
@@ -1687,19 +1880,19 @@ pub async fn handle_work_log_import_submit(
 
 pub(crate) const ZIP_IMPORT_STREAM: &str = "SAZINKA_ZIP_IMPORT_JOBS";
 pub(crate) const ZIP_IMPORT_CONSUMER: &str = "zip_import_workers";
-pub(crate) const ZIP_IMPORT_SUBJECT: &str = "sazinka.import.zip.submit";
-pub(crate) const ZIP_IMPORT_STATUS_PREFIX: &str = "sazinka.job.import.zip.status";
+pub(crate) const ZIP_IMPORT_SUBJECT: &str = subjects::import::ZIP_SUBMIT;
+pub(crate) const ZIP_IMPORT_STATUS_PREFIX: &str = subjects::job::IMPORT_ZIP_STATUS;
 
 pub struct ZipImportProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
     pool: PgPool,
     pending_count: AtomicU32,
 }
 
 impl ZipImportProcessor {
     pub async fn new(client: Client, pool: PgPool) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+        let queue = JobQueue::new(client.clone());
         
         let stream_config = jetstream::stream::Config {
             name: ZIP_IMPORT_STREAM.to_string(),
@@ -1710,12 +1903,12 @@ impl ZipImportProcessor {
             ..Default::default()
         };
         
-        js.get_or_create_stream(stream_config).await?;
+        queue.ensure_stream(stream_config).await?;
         info!("JetStream ZIP import stream '{}' ready", ZIP_IMPORT_STREAM);
         
         Ok(Self {
             client,
-            js,
+            queue,
             pool,
             pending_count: AtomicU32::new(0),
         })
@@ -1723,10 +1916,7 @@ impl ZipImportProcessor {
     
     pub async fn submit_job(&self, user_id: Uuid, request: ZipImportJobRequest) -> Result<ZipImportJobSubmitResponse> {
         // First, analyze the ZIP to detect files
-        let zip_data = base64::Engine::decode(
-            &base64::engine::general_purpose::STANDARD,
-            &request.zip_content_base64
-        )?;
+        let zip_data = import_upload::load_file(user_id, request.upload_id, &request.zip_content_base64).await?;
         
         let detected_files = self.analyze_zip(&zip_data)?;
         
@@ -1738,7 +1928,14 @@ impl ZipImportProcessor {
         let job_id = job.id;
         
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish(ZIP_IMPORT_SUBJECT, payload.into()).await?.await?;
+        job_backup::publish_with_backup(&self.queue, &self.pool, BackupJob {
+            job_id,
+            user_id,
+            job_type: "import.zip",
+            stream: ZIP_IMPORT_STREAM,
+            subject: ZIP_IMPORT_SUBJECT,
+            filename: Some(&job.request.filename),
+        }, payload).await?;
         
         let pending = self.pending_count.fetch_add(1, Ordering::Relaxed) + 1;
         
@@ -1789,15 +1986,13 @@ impl ZipImportProcessor {
     
     pub async fn publish_status(&self, job_id: Uuid, status: ZipImportJobStatus) -> Result<()> {
         let update = ZipImportJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", ZIP_IMPORT_STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(ZIP_IMPORT_STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         self.client.publish(subject, payload.into()).await?;
         Ok(())
     }
     
     pub async fn start_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(ZIP_IMPORT_STREAM).await?;
-        
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(ZIP_IMPORT_CONSUMER.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
@@ -1805,11 +2000,9 @@ impl ZipImportProcessor {
             ..Default::default()
         };
         
-        let consumer = stream.get_or_create_consumer(ZIP_IMPORT_CONSUMER, consumer_config).await?;
+        let mut messages = self.queue.consume(ZIP_IMPORT_STREAM, consumer_config).await?;
         info!("JetStream ZIP import consumer '{}' ready", ZIP_IMPORT_CONSUMER);
         
-        let mut messages = consumer.messages().await?;
-        
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -1829,10 +2022,12 @@ impl ZipImportProcessor {
         Ok(())
     }
     
-    async fn process_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_job(&self, msg: JobMessage) -> Result<()> {
         use crate::services::cancellation::CANCELLATION;
         
         let job: QueuedZipImportJob = serde_json::from_slice(&msg.payload)?;
+        job_backup::mark_started(&self.pool, job.id).await;
+        JOB_HISTORY.record_running(job.id, "import.zip", job.user_id, job.submitted_at);
         let job_id = job.id;
         let user_id = job.user_id;
         let started_at = job.submitted_at;
@@ -1855,14 +2050,13 @@ impl ZipImportProcessor {
         if let Err(e) = msg.ack().await {
             error!("Failed to ack ZIP import job {}: {:?}", job_id, e);
         }
+        // The uploaded file is removed once the job is done
+        let _upload = job.request.upload_id.map(|id| UploadClaim::new(user_id, id));
         
         // Extract ZIP
         self.publish_status(job_id, ZipImportJobStatus::Extracting { progress: 0 }).await?;
         
-        let zip_data = match base64::Engine::decode(
-            &base64::engine::general_purpose::STANDARD,
-            &job.request.zip_content_base64
-        ) {
+        let zip_data = match import_upload::load_file(user_id, job.request.upload_id, &job.request.zip_content_base64).await {
             Ok(data) => data,
             Err(e) => {
                 let error_msg = json!({"key": "import:zip_decode_error", "params": {"error": e.to_string()}}).to_string();
@@ -1907,8 +2101,8 @@ impl ZipImportProcessor {
             }).await?;
             
             // Read file content from ZIP
-            let csv_content = match self.read_file_from_zip(&mut archive, &file_info.filename) {
-                Ok(content) => content,
+            let decoded = match self.read_file_from_zip(&mut archive, &file_info.filename) {
+                Ok(decoded) => decoded,
                 Err(e) => {
                     warn!("Failed to read '{}' from ZIP: {}", file_info.filename, e);
                     let error_report = build_import_report(
@@ -1938,10 +2132,13 @@ impl ZipImportProcessor {
             // Import the file based on type
             let (succeeded, failed, file_issues) = match self.import_csv_by_type(
                 user_id, 
-                &csv_content, 
+                &decoded.text, 
                 file_info.file_type
             ).await {
-                Ok((s, f, issues)) => (s, f, issues),
+                Ok((s, f, mut issues)) => {
+                    issues.splice(0..0, replacement_issues(&decoded.text));
+                    (s, f, issues)
+                }
                 Err(e) => {
                     warn!("Failed to import '{}': {}", file_info.filename, e);
                     (0, 1, vec![ImportIssue {
@@ -1955,10 +2152,11 @@ impl ZipImportProcessor {
                 }
             };
             
-            let file_report = build_import_report(
+            let mut file_report = build_import_report(
                 job_id, &format!("import.zip.{}", file_info.file_type.type_name()),
                 &file_info.filename, started_at, succeeded + failed, succeeded, failed, file_issues,
             );
+            file_report.encoding = Some(decoded.encoding.to_string());
             persist_report(&file_report);
             
             results.push(ZipImportFileResult {
@@ -2042,11 +2240,11 @@ impl ZipImportProcessor {
         Ok(())
     }
     
-    fn read_file_from_zip(&self, archive: &mut zip::ZipArchive<Cursor<&Vec<u8>>>, filename: &str) -> Result<String> {
+    fn read_file_from_zip(&self, archive: &mut zip::ZipArchive<Cursor<&Vec<u8>>>, filename: &str) -> Result<DecodedCsv> {
         let mut file = archive.by_name(filename)?;
-        let mut content = String::new();
-        file.read_to_string(&mut content)?;
-        Ok(content)
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+        Ok(decode_csv(&content))
     }
     
     async fn import_csv_by_type(&self, user_id: Uuid, csv_content: &str, file_type: ZipImportFileType) -> Result<(u32, u32, Vec<ImportIssue>)> {
@@ -2081,6 +2279,8 @@ impl ZipImportProcessor {
             .flexible(true)
             .from_reader(csv_content.as_bytes());
         
+        let quota_remaining = customer_quota_remaining(&self.pool, user_id).await;
+        let coverage = customer_coverage(&self.pool, user_id).await;
         let mut succeeded = 0u32;
         let mut failed = 0u32;
         let mut issues = Vec::new();
@@ -2088,7 +2288,20 @@ impl ZipImportProcessor {
         for (idx, result) in reader.deserialize::<CsvCustomerRow>().enumerate() {
             let row_num = (idx + 2) as i32;
             match result {
+                Ok(_) if quota_remaining.is_some_and(|remaining| succeeded as i64 >= remaining) => {
+                    failed += 1;
+                    issues.push(quota_exceeded_issue(row_num, None));
+                }
                 Ok(row) => {
+                    let postal_code = row.postal_code.as_deref();
+                    if let Some(issue) = coverage_issue(&self.pool, user_id, coverage.as_ref(), row_num, postal_code, row.city.as_deref()).await {
+                        let skip = matches!(issue.level, ImportIssueLevel::Error);
+                        issues.push(issue);
+                        if skip {
+                            failed += 1;
+                            continue;
+                        }
+                    }
                     match self.create_customer_from_row(user_id, &row).await {
                         Ok(_) => succeeded += 1,
                         Err(e) => {
@@ -2159,6 +2372,11 @@ impl ZipImportProcessor {
             lat: None,
             lng: None,
             notes: row.notes.clone(),
+            parent_customer_id: None,
+            language: None,
+            customer_code: row.customer_code.clone(),
+            acquisition_source: row.acquisition_source.as_deref().and_then(normalize_acquisition_source).map(str::to_string),
+            referrer_customer_id: None,
         };
         
         let customer = queries::customer::create_customer(&self.pool, user_id, &request).await?;
@@ -2232,6 +2450,12 @@ impl ZipImportProcessor {
             installation_date,
             revision_interval_months: revision_interval,
             notes: row.notes.clone(),
+            site_id: None,
+            building: row.building.clone(),
+            floor: row.floor.clone(),
+            room: row.room.clone(),
+            location_lat: None,
+            location_lng: None,
         };
         
         let device = queries::device::create_device(
@@ -2607,6 +2831,7 @@ impl ZipImportProcessor {
         let request = GeocodeJobRequest {
             user_id,
             customer_ids,
+            force: false,
         };
         
         let job = QueuedGeocodeJob::new(request);
@@ -2614,13 +2839,13 @@ impl ZipImportProcessor {
         
         // Publish to geocode queue
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish("sazinka.jobs.geocode", payload.into()).await?.await?;
+        self.queue.publish(geocode_priority::jobs_subject(count), payload.into()).await?;
         
         info!("Triggered geocoding job {} for {} customers after ZIP import", job_id, count);
         
         // Publish initial status
         let status_update = GeocodeJobStatusUpdate::new(job_id, GeocodeJobStatus::Queued { position: 1 });
-        let status_subject = format!("sazinka.job.geocode.status.{}", job_id);
+        let status_subject = subjects::job_status(subjects::job::GEOCODE_STATUS, job_id);
         let status_payload = serde_json::to_vec(&status_update)?;
         self.client.publish(status_subject, status_payload.into()).await?;
         
@@ -2823,6 +3048,10 @@ pub async fn handle_zip_import_submit(
                 continue;
             }
         };
+
+        if account::reject_if_read_only(&client, &processor.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
         
         match processor.submit_job(user_id, request.payload).await {
             Ok(response) => {
@@ -2840,6 +3069,349 @@ pub async fn handle_zip_import_submit(
     Ok(())
 }
 
+// =============================================================================
+// KML/KMZ CUSTOMER IMPORT PROCESSOR
+// =============================================================================
+
+pub(crate) const KML_IMPORT_STREAM: &str = "SAZINKA_KML_IMPORT_JOBS";
+pub(crate) const KML_IMPORT_CONSUMER: &str = "kml_import_workers";
+pub(crate) const KML_IMPORT_SUBJECT: &str = subjects::import::KML_SUBMIT;
+pub(crate) const KML_IMPORT_STATUS_PREFIX: &str = subjects::job::IMPORT_KML_STATUS;
+
+pub struct KmlImportProcessor {
+    client: Client,
+    queue: JobQueue,
+    pool: PgPool,
+    pending_count: AtomicU32,
+}
+
+impl KmlImportProcessor {
+    pub async fn new(client: Client, pool: PgPool) -> Result<Self> {
+        let queue = JobQueue::new(client.clone());
+
+        let stream_config = jetstream::stream::Config {
+            name: KML_IMPORT_STREAM.to_string(),
+            subjects: vec![KML_IMPORT_SUBJECT.to_string()],
+            max_messages: 1_000,
+            max_bytes: 500 * 1024 * 1024,
+            retention: jetstream::stream::RetentionPolicy::WorkQueue,
+            ..Default::default()
+        };
+
+        queue.ensure_stream(stream_config).await?;
+        info!("JetStream KML import stream '{}' ready", KML_IMPORT_STREAM);
+
+        Ok(Self {
+            client,
+            queue,
+            pool,
+            pending_count: AtomicU32::new(0),
+        })
+    }
+
+    pub async fn submit_job(&self, user_id: Uuid, request: KmlImportJobRequest) -> Result<CustomerImportJobSubmitResponse> {
+        let job = QueuedKmlImportJob::new(user_id, request);
+        let job_id = job.id;
+
+        let payload = serde_json::to_vec(&job)?;
+        job_backup::publish_with_backup(&self.queue, &self.pool, BackupJob {
+            job_id,
+            user_id,
+            job_type: "import.kml",
+            stream: KML_IMPORT_STREAM,
+            subject: KML_IMPORT_SUBJECT,
+            filename: Some(&job.request.filename),
+        }, payload).await?;
+
+        let pending = self.pending_count.fetch_add(1, Ordering::Relaxed) + 1;
+
+        info!("KML import job {} submitted, position {} in queue", job_id, pending);
+
+        self.publish_status(job_id, CustomerImportJobStatus::Queued { position: pending }).await?;
+
+        Ok(CustomerImportJobSubmitResponse {
+            job_id,
+            message: "import:job_queued".to_string(),
+        })
+    }
+
+    pub async fn publish_status(&self, job_id: Uuid, status: CustomerImportJobStatus) -> Result<()> {
+        let update = CustomerImportJobStatusUpdate::new(job_id, status);
+        let subject = subjects::job_status(KML_IMPORT_STATUS_PREFIX, job_id);
+        let payload = serde_json::to_vec(&update)?;
+        self.client.publish(subject, payload.into()).await?;
+        Ok(())
+    }
+
+    pub async fn start_processing(self: Arc<Self>) -> Result<()> {
+        let consumer_config = jetstream::consumer::pull::Config {
+            durable_name: Some(KML_IMPORT_CONSUMER.to_string()),
+            ack_policy: jetstream::consumer::AckPolicy::Explicit,
+            max_deliver: 3,
+            ..Default::default()
+        };
+
+        let mut messages = self.queue.consume(KML_IMPORT_STREAM, consumer_config).await?;
+        info!("JetStream KML import consumer '{}' ready", KML_IMPORT_CONSUMER);
+
+        while let Some(msg) = messages.next().await {
+            match msg {
+                Ok(msg) => {
+                    let processor = Arc::clone(&self);
+                    tokio::spawn(async move {
+                        if let Err(e) = processor.process_job(msg).await {
+                            error!("Failed to process KML import job: {}", e);
+                        }
+                    });
+                }
+                Err(e) => {
+                    error!("Error receiving KML import message: {}", e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn process_job(&self, msg: JobMessage) -> Result<()> {
+        use crate::services::cancellation::CANCELLATION;
+
+        let job: QueuedKmlImportJob = serde_json::from_slice(&msg.payload)?;
+        job_backup::mark_started(&self.pool, job.id).await;
+        JOB_HISTORY.record_running(job.id, "import.kml", job.user_id, job.submitted_at);
+        let job_id = job.id;
+        let user_id = job.user_id;
+        let started_at = job.submitted_at;
+
+        let _guard = CANCELLATION.register(job_id, user_id);
+        if CANCELLATION.is_cancelled(&job_id) {
+            msg.ack().await.ok();
+            self.publish_status(job_id, CustomerImportJobStatus::Cancelled { processed: 0, total: 0 }).await?;
+            JOB_HISTORY.record_cancelled(job_id, "import.kml", user_id, started_at);
+            return Ok(());
+        }
+
+        info!("Processing KML import job {} from file '{}'", job_id, job.request.filename);
+        self.pending_count.fetch_sub(1, Ordering::Relaxed);
+
+        // ACK immediately to prevent redelivery during long processing
+        if let Err(e) = msg.ack().await {
+            error!("Failed to ack KML import job {}: {:?}", job_id, e);
+        }
+        // The uploaded file is removed once the job is done
+        let _upload = job.request.upload_id.map(|id| UploadClaim::new(user_id, id));
+
+        self.publish_status(job_id, CustomerImportJobStatus::Parsing { progress: 0 }).await?;
+
+        let placemarks = match import_upload::load_file(user_id, job.request.upload_id, &job.request.content_base64)
+            .await
+            .and_then(|bytes| Self::parse_placemarks(&bytes))
+        {
+            Ok(placemarks) => placemarks,
+            Err(e) => {
+                let error_msg = json!({"key": "import:kml_parse_error", "params": {"error": e.to_string()}}).to_string();
+                self.publish_status(job_id, CustomerImportJobStatus::Failed { error: error_msg.clone() }).await?;
+                JOB_HISTORY.record_failed(job_id, "import.kml", user_id, started_at, error_msg);
+                return Ok(());
+            }
+        };
+
+        let total = placemarks.len() as u32;
+        if total == 0 {
+            let error_msg = "import:kml_empty".to_string();
+            self.publish_status(job_id, CustomerImportJobStatus::Failed { error: error_msg.clone() }).await?;
+            JOB_HISTORY.record_failed(job_id, "import.kml", user_id, started_at, error_msg);
+            return Ok(());
+        }
+
+        self.publish_status(job_id, CustomerImportJobStatus::Parsing { progress: 100 }).await?;
+
+        let quota_remaining = customer_quota_remaining(&self.pool, user_id).await;
+        let mut succeeded = 0u32;
+        let mut failed = 0u32;
+        let mut issues: Vec<ImportIssue> = Vec::new();
+
+        for (idx, placemark) in placemarks.iter().enumerate() {
+            let processed = (idx + 1) as u32;
+            // Placemarks have no header row — number them from 1
+            let row_num = processed as i32;
+
+            if idx % 50 == 0 && CANCELLATION.is_cancelled(&job_id) {
+                self.publish_status(job_id, CustomerImportJobStatus::Cancelled { processed: idx as u32, total }).await?;
+                JOB_HISTORY.record_cancelled(job_id, "import.kml", user_id, started_at);
+                return Ok(());
+            }
+
+            if processed.is_multiple_of(10) || processed == total {
+                self.publish_status(job_id, CustomerImportJobStatus::Importing {
+                    processed,
+                    total,
+                    succeeded,
+                    failed,
+                }).await?;
+            }
+
+            // Lines and polygons are not customers — skip with a warning
+            if placemark.lat.is_none() || placemark.lng.is_none() {
+                issues.push(ImportIssue {
+                    row_number: row_num,
+                    level: ImportIssueLevel::Warning,
+                    code: ImportIssueCode::InvalidValue,
+                    field: "coordinates".to_string(),
+                    message: "import:kml_placemark_not_point".to_string(),
+                    original_value: placemark.name.clone(),
+                });
+                continue;
+            }
+
+            if quota_remaining.is_some_and(|remaining| succeeded as i64 >= remaining) {
+                failed += 1;
+                issues.push(quota_exceeded_issue(row_num, placemark.name.clone()));
+                continue;
+            }
+
+            match self.create_customer(user_id, placemark).await {
+                Ok(_) => succeeded += 1,
+                Err(e) => {
+                    failed += 1;
+                    let err_msg = e.to_string();
+                    let (code, field) = classify_error(&err_msg);
+                    issues.push(ImportIssue {
+                        row_number: row_num,
+                        level: ImportIssueLevel::Error,
+                        code,
+                        field: field.to_string(),
+                        message: err_msg,
+                        original_value: placemark.name.clone(),
+                    });
+                }
+            }
+        }
+
+        let report = build_import_report(
+            job_id, "import.kml", &job.request.filename,
+            started_at, total, succeeded, failed, issues,
+        );
+        persist_report(&report);
+
+        self.publish_status(job_id, CustomerImportJobStatus::Completed {
+            total,
+            succeeded,
+            failed,
+            report: report.clone(),
+        }).await?;
+
+        JOB_HISTORY.record_completed_with_report(
+            job_id,
+            "import.kml",
+            user_id,
+            started_at,
+            Some(json!({"key": "import:completed_summary", "params": {"succeeded": succeeded, "total": total}}).to_string()),
+            serde_json::to_value(&report).ok(),
+        );
+
+        info!("KML import job {} completed: {}/{} succeeded", job_id, succeeded, total);
+
+        Ok(())
+    }
+
+    fn parse_placemarks(bytes: &[u8]) -> Result<Vec<KmlPlacemark>> {
+        let content = kml::decode_kml_payload(bytes)?;
+        kml::parse_kml(&content)
+    }
+
+    async fn create_customer(&self, user_id: Uuid, placemark: &KmlPlacemark) -> Result<Uuid> {
+        let name = placemark.name.clone()
+            .ok_or_else(|| anyhow::anyhow!("import:missing_name"))?;
+
+        let (street, postal_code, city) = placemark.address.as_deref()
+            .map(kml::split_address)
+            .unwrap_or((None, None, None));
+
+        let customer = queries::customer::create_customer(
+            &self.pool,
+            user_id,
+            &CreateCustomerRequest {
+                name: Some(name),
+                customer_type: Some(CustomerType::Person),
+                contact_person: None,
+                ico: None,
+                dic: None,
+                email: None,
+                phone: None,
+                phone_raw: None,
+                street,
+                city,
+                postal_code,
+                country: None,
+                lat: placemark.lat,
+                lng: placemark.lng,
+                notes: placemark.description.clone(),
+                parent_customer_id: None,
+                language: None,
+                customer_code: None,
+                acquisition_source: None,
+                referrer_customer_id: None,
+            },
+        ).await?;
+
+        queries::customer::mark_coordinates_manual(&self.pool, user_id, customer.id).await?;
+
+        Ok(customer.id)
+    }
+}
+
+pub async fn handle_kml_import_submit(
+    client: Client,
+    mut subscriber: Subscriber,
+    jwt_secret: Arc<String>,
+    processor: Arc<KmlImportProcessor>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let reply = match msg.reply {
+            Some(ref r) => r.clone(),
+            None => continue,
+        };
+
+        let request: Request<KmlImportJobRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse KML import submit request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &processor.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        match processor.submit_job(user_id, request.payload).await {
+            Ok(response) => {
+                let success = SuccessResponse::new(request.id, response);
+                let _ = client.publish(reply, serde_json::to_vec(&success)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to submit KML import job: {}", e);
+                let error = ErrorResponse::new(request.id, "SUBMIT_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 // =============================================================================
 // HELPER FUNCTIONS
 // =============================================================================
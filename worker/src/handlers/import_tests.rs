@@ -518,6 +518,26 @@ mod csv_customer_row_tests {
         assert_eq!(rows[0].phone.as_deref(), Some("602111222"));
     }
 
+    /// The acquisition source column is optional and accepts the Czech header.
+    #[test]
+    fn csv_customer_row_acquisition_source() {
+        let csv = "nazev;zdroj\nJan Novak;doporučení";
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(b';')
+            .has_headers(true)
+            .from_reader(csv.as_bytes());
+        let rows: Vec<CsvCustomerRow> = reader.deserialize().collect::<Result<_, _>>().unwrap();
+        assert_eq!(rows[0].acquisition_source.as_deref(), Some("doporučení"));
+
+        let csv = "name\nJan Novak";
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(b';')
+            .has_headers(true)
+            .from_reader(csv.as_bytes());
+        let rows: Vec<CsvCustomerRow> = reader.deserialize().collect::<Result<_, _>>().unwrap();
+        assert!(rows[0].acquisition_source.is_none());
+    }
+
     /// UTF-8 BOM should be handled gracefully.
     #[test]
     fn csv_customer_row_utf8_bom() {
@@ -740,6 +760,34 @@ mod export_ref_resolution_tests {
         assert!(parsed.is_ok(), "UUID after prefix must be valid");
     }
 
+    /// Explicit id references accept "#id:" and the exported "customer_uuid:" prefix.
+    #[test]
+    fn parse_customer_id_ref_prefixes() {
+        use crate::handlers::import::parse_customer_id_ref;
+        let id = uuid::Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap();
+        assert_eq!(parse_customer_id_ref("#id:00000000-0000-0000-0000-000000000001"), Some(Some(id)));
+        assert_eq!(parse_customer_id_ref(" #ID: 00000000-0000-0000-0000-000000000001 "), Some(Some(id)));
+        assert_eq!(parse_customer_id_ref("customer_uuid:00000000-0000-0000-0000-000000000001"), Some(Some(id)));
+        assert_eq!(parse_customer_id_ref("#id:not-a-uuid"), Some(None));
+        assert_eq!(parse_customer_id_ref("CUS-2025-0001"), None);
+        assert_eq!(parse_customer_id_ref("12345678"), None);
+    }
+
+    /// The preview reads the customer_ref column (or its aliases) with line numbers.
+    #[test]
+    fn customer_refs_from_csv_reads_column() {
+        use crate::handlers::import::customer_refs_from_csv;
+        let csv = "device_type;Zakaznik\ngas_boiler;CUS-2025-0001\nchimney;\nfireplace; 12345678 ";
+        let refs = customer_refs_from_csv(csv).unwrap();
+        assert_eq!(refs, vec![
+            (2, Some("CUS-2025-0001".to_string())),
+            (3, None),
+            (4, Some("12345678".to_string())),
+        ]);
+
+        assert!(customer_refs_from_csv("device_type;name\ngas_boiler;x").is_err());
+    }
+
     /// Import resolver must handle device_uuid: prefix before normal heuristics.
     #[test]
     fn resolve_device_ref_uuid_prefix_recognized() {
@@ -0,0 +1,226 @@
+//! Chunked import upload handlers (begin / chunk / commit)
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use base64::Engine;
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::account;
+use crate::auth;
+use crate::services::import_upload::{UploadError, IMPORT_UPLOADS, MAX_CHUNK_BYTES};
+use crate::subjects;
+use crate::types::{
+    ErrorResponse, ImportUploadBeginRequest, ImportUploadBeginResponse, ImportUploadChunkRequest,
+    ImportUploadChunkResponse, ImportUploadCommitRequest, ImportUploadCommitResponse, Request,
+    SuccessResponse,
+};
+
+/// Subscribe the upload subjects and remove expired uploads in the background
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting import upload handlers...");
+
+    let begin_sub = client.subscribe(subjects::import::UPLOAD_BEGIN).await?;
+    let chunk_sub = client.subscribe(subjects::import::UPLOAD_CHUNK).await?;
+    let commit_sub = client.subscribe(subjects::import::UPLOAD_COMMIT).await?;
+
+    tokio::spawn(handle_begin(client.clone(), begin_sub, pool, jwt_secret.clone()));
+    tokio::spawn(handle_chunk(client.clone(), chunk_sub, jwt_secret.clone()));
+    tokio::spawn(handle_commit(client.clone(), commit_sub, jwt_secret.clone()));
+    tokio::spawn(crate::services::import_upload::run_sweeper());
+
+    info!("Import upload handlers started");
+    Ok(())
+}
+
+async fn reply_upload_error(client: &Client, reply: async_nats::Subject, request_id: Uuid, e: &UploadError) -> Result<()> {
+    if let UploadError::Io(io) = e {
+        error!("Import upload I/O error: {}", io);
+    }
+    let error = ErrorResponse::new(request_id, e.code(), e.to_string());
+    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+    Ok(())
+}
+
+/// Handle import.upload.begin messages
+pub async fn handle_begin(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received import.upload.begin message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ImportUploadBeginRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        match IMPORT_UPLOADS.begin(user_id, request.payload.total_size) {
+            Ok(upload_id) => {
+                info!(
+                    "Import upload {} of '{}' ({} bytes) started",
+                    upload_id, request.payload.filename, request.payload.total_size
+                );
+                let response = SuccessResponse::new(
+                    request.id,
+                    ImportUploadBeginResponse { upload_id, max_chunk_size: MAX_CHUNK_BYTES },
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => reply_upload_error(&client, reply, request.id, &e).await?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle import.upload.chunk messages
+pub async fn handle_chunk(
+    client: Client,
+    mut subscriber: Subscriber,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ImportUploadChunkRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = request.payload;
+        let data = match base64::engine::general_purpose::STANDARD.decode(payload.data_base64.trim()) {
+            Ok(data) => data,
+            Err(e) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", format!("Invalid base64 chunk: {}", e));
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match IMPORT_UPLOADS.append(user_id, payload.upload_id, payload.index, &data) {
+            Ok(received) => {
+                let response = SuccessResponse::new(
+                    request.id,
+                    ImportUploadChunkResponse { upload_id: payload.upload_id, received },
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => reply_upload_error(&client, reply, request.id, &e).await?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle import.upload.commit messages
+pub async fn handle_commit(
+    client: Client,
+    mut subscriber: Subscriber,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received import.upload.commit message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ImportUploadCommitRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = request.payload;
+        let upload_id = payload.upload_id;
+        // Hashing a large file must not stall the other handlers
+        let committed = tokio::task::spawn_blocking(move || {
+            IMPORT_UPLOADS.commit(user_id, upload_id, payload.sha256.as_deref())
+        })
+        .await?;
+
+        match committed {
+            Ok(committed) => {
+                info!("Import upload {} committed ({} bytes)", upload_id, committed.size);
+                let response = SuccessResponse::new(
+                    request.id,
+                    ImportUploadCommitResponse { upload_id, size: committed.size, sha256: committed.sha256 },
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => reply_upload_error(&client, reply, request.id, &e).await?,
+        }
+    }
+
+    Ok(())
+}
@@ -11,6 +11,7 @@ use uuid::Uuid;
 
 use crate::auth;
 use crate::db::queries;
+use crate::services::demo_mode;
 use crate::types::{ErrorResponse, InboxRequest, Request, SuccessResponse};
 
 /// Handle sazinka.inbox.query — returns the customer-centric planning inbox
@@ -52,7 +53,7 @@ pub async fn handle_query(
 
         match queries::planned_action::get_customer_inbox(&pool, user_id, request.payload).await {
             Ok(inbox) => {
-                let response = SuccessResponse::new(request.id, inbox);
+                let response = SuccessResponse::new(request.id, demo_mode::apply(request.demo_mode, inbox));
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
             }
             Err(e) => {
@@ -0,0 +1,231 @@
+//! Bulk revision interval adjustment handlers (preview / apply / list)
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::account;
+use crate::auth;
+use crate::db::queries;
+use crate::services::interval_adjustment;
+use crate::subjects;
+use crate::types::{
+    ApplyIntervalAdjustmentRequest, ApplyIntervalAdjustmentResponse, ErrorResponse,
+    ListIntervalAdjustmentsResponse, PreviewIntervalAdjustmentRequest, Request, SuccessResponse,
+};
+
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting interval adjustment handlers...");
+
+    let preview_sub = client.subscribe(subjects::interval_adjustment::PREVIEW).await?;
+    let apply_sub = client.subscribe(subjects::interval_adjustment::APPLY).await?;
+    let list_sub = client.subscribe(subjects::interval_adjustment::LIST).await?;
+
+    tokio::spawn(handle_preview(client.clone(), preview_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_apply(client.clone(), apply_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_list(client.clone(), list_sub, pool, jwt_secret));
+
+    info!("Interval adjustment handlers started");
+    Ok(())
+}
+
+/// Handle interval_adjustment.preview messages - dry run stored as a draft
+pub async fn handle_preview(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received interval_adjustment.preview message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<PreviewIntervalAdjustmentRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let (user_id, actor_id) = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => (info.data_user_id(), info.user_id),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if let Err(reason) = request.payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", reason);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match interval_adjustment::preview(&pool, user_id, actor_id, &request.payload).await {
+            Ok(Some(preview)) => {
+                let response = SuccessResponse::new(request.id, preview);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Device type not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to preview interval adjustment: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle interval_adjustment.apply messages - start the job applying a draft
+pub async fn handle_apply(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received interval_adjustment.apply message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ApplyIntervalAdjustmentRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let id = request.payload.id;
+        let claimed = match queries::interval_adjustment::claim_for_run(&pool, user_id, id).await {
+            Ok(claimed) => claimed,
+            Err(e) => {
+                error!("Failed to claim interval adjustment {}: {}", id, e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let Some(adjustment) = claimed else {
+            // Tell a missing draft apart from one that already ran
+            let error = match queries::interval_adjustment::get_adjustment(&pool, user_id, id).await {
+                Ok(Some(existing)) => ErrorResponse::new(
+                    request.id,
+                    "CONFLICT",
+                    format!("Adjustment is already {}", existing.status),
+                ),
+                _ => ErrorResponse::new(request.id, "NOT_FOUND", "Adjustment not found"),
+            };
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        };
+
+        info!("Applying interval adjustment {} for user {}", id, user_id);
+        tokio::spawn(interval_adjustment::run_job(client.clone(), pool.clone(), user_id, adjustment));
+
+        let response = SuccessResponse::new(request.id, ApplyIntervalAdjustmentResponse { job_id: id });
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle interval_adjustment.list messages
+pub async fn handle_list(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received interval_adjustment.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::interval_adjustment::list_adjustments(&pool, user_id).await {
+            Ok(adjustments) => {
+                let response = SuccessResponse::new(request.id, ListIntervalAdjustmentsResponse { adjustments });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list interval adjustments: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,389 @@
+//! Spare parts inventory handlers for NATS messages
+//!
+//! Parts are catalog items stocked on crew vehicles and in depots.
+//! Deliveries are booked with `sazinka.inventory.stock.restock`, stocktakes
+//! and reorder points with `sazinka.inventory.stock.set`. Materials entered
+//! on a work item are taken from the crew's stock when the work item is
+//! completed; a part reaching its reorder point raises a notification.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::account;
+use super::parse_authenticated;
+use crate::db::queries;
+use crate::services::notification_dispatch;
+use crate::services::quote::format_quantity;
+use crate::subjects;
+use crate::types::inventory::{
+    validate_materials, ListMaterialsRequest, ListMovementsRequest, ListStockRequest, RestockRequest,
+    SetStockRequest, WorkItemMaterialInput,
+};
+use crate::types::notification::{NOTIFICATION_CATEGORY_SYSTEM, NOTIFICATION_KIND_LOW_STOCK};
+use crate::types::work_item::VisitWorkItem;
+use crate::types::{ErrorResponse, SuccessResponse};
+
+const DEFAULT_MOVEMENTS_LIMIT: i64 = 100;
+const MAX_MOVEMENTS_LIMIT: i64 = 500;
+
+/// Shared by all inventory handlers
+#[derive(Clone)]
+pub struct InventoryContext {
+    pub pool: PgPool,
+    pub jwt_secret: Arc<String>,
+}
+
+/// Start all inventory NATS handlers
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting inventory handlers...");
+    let ctx = InventoryContext { pool, jwt_secret };
+
+    let [list_sub, restock_sub, set_sub, movements_sub, materials_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::inventory::STOCK_LIST,
+            subjects::inventory::STOCK_RESTOCK,
+            subjects::inventory::STOCK_SET,
+            subjects::inventory::MOVEMENTS_LIST,
+            subjects::inventory::MATERIALS_LIST,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_stock_list(client.clone(), list_sub, ctx.clone()));
+    tokio::spawn(handle_restock(client.clone(), restock_sub, ctx.clone()));
+    tokio::spawn(handle_stock_set(client.clone(), set_sub, ctx.clone()));
+    tokio::spawn(handle_movements_list(client.clone(), movements_sub, ctx.clone()));
+    tokio::spawn(handle_materials_list(client.clone(), materials_sub, ctx));
+
+    info!("Inventory handlers started");
+    Ok(())
+}
+
+/// Check that materials are valid and name active catalog items of the
+/// account (inner Err with the message otherwise)
+pub(crate) async fn check_materials(
+    pool: &PgPool,
+    user_id: Uuid,
+    materials: &[WorkItemMaterialInput],
+) -> Result<std::result::Result<(), String>> {
+    if let Err(msg) = validate_materials(materials) {
+        return Ok(Err(msg));
+    }
+    let ids: Vec<Uuid> = materials.iter().map(|m| m.catalog_item_id).collect::<HashSet<_>>().into_iter().collect();
+    if ids.is_empty() {
+        return Ok(Ok(()));
+    }
+    let found = queries::catalog::get_active_items(pool, user_id, &ids).await?;
+    if found.len() != ids.len() {
+        return Ok(Err("Material not found in the catalog".to_string()));
+    }
+    Ok(Ok(()))
+}
+
+/// Take the materials of a completed work item from the crew's stock and
+/// notify about parts that ran low. Failures are logged; the work item
+/// stays completed and its materials wait for the next completion.
+pub(crate) async fn consume_for_work_item(pool: &PgPool, user_id: Uuid, actor_id: Uuid, item: &VisitWorkItem) {
+    if item.result.is_none() {
+        return;
+    }
+    let consumed = async {
+        let Some(crew_id) = queries::inventory::work_item_crew(pool, item.id).await? else {
+            return anyhow::Ok(None);
+        };
+        let alerts = queries::inventory::consume_materials(pool, user_id, item.id, crew_id, actor_id).await?;
+        Ok(Some(alerts))
+    }
+    .await;
+
+    let alerts = match consumed {
+        Ok(Some(alerts)) => alerts,
+        Ok(None) => {
+            debug!("Work item {} has no crew; materials not taken from stock", item.id);
+            return;
+        }
+        Err(e) => {
+            warn!("Failed to take materials of work item {} from stock: {}", item.id, e);
+            return;
+        }
+    };
+
+    for alert in alerts {
+        let title = format!("Low stock – {} ({})", alert.item_name, alert.location_name);
+        let body = format!(
+            "{} {} left, reorder point {} {}",
+            format_quantity(alert.quantity),
+            alert.unit,
+            format_quantity(alert.min_quantity),
+            alert.unit
+        );
+        notification_dispatch::notify_or_warn(
+            pool,
+            user_id,
+            NOTIFICATION_CATEGORY_SYSTEM,
+            NOTIFICATION_KIND_LOW_STOCK,
+            &title,
+            Some(&body),
+            Some(("catalog_item", alert.catalog_item_id)),
+        )
+        .await;
+    }
+}
+
+/// Handle inventory.stock.list messages
+pub async fn handle_stock_list(client: Client, mut subscriber: Subscriber, ctx: InventoryContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received inventory.stock.list message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<ListStockRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        match queries::inventory::list_levels(&ctx.pool, user_id, &request.payload).await {
+            Ok(levels) => {
+                let response = SuccessResponse::new(request.id, levels);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list stock levels: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that the location and part belong to the account. Replies with
+/// the error itself.
+async fn check_stock_target(
+    client: &Client,
+    reply: &async_nats::Subject,
+    ctx: &InventoryContext,
+    request_id: Uuid,
+    user_id: Uuid,
+    catalog_item_id: Uuid,
+    location: &crate::types::inventory::StockLocation,
+) -> Result<bool> {
+    if let Err(msg) = location.validate() {
+        let error = ErrorResponse::new(request_id, "INVALID_REQUEST", msg);
+        let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+        return Ok(false);
+    }
+
+    let checked = async {
+        let location_ok = queries::inventory::location_belongs(&ctx.pool, user_id, location).await?;
+        let item_ok = !queries::catalog::get_active_items(&ctx.pool, user_id, &[catalog_item_id]).await?.is_empty();
+        anyhow::Ok((location_ok, item_ok))
+    }
+    .await;
+    let message = match checked {
+        Ok((true, true)) => return Ok(true),
+        Ok((false, _)) => "Crew or depot not found",
+        Ok((_, false)) => "Catalog item not found",
+        Err(e) => {
+            error!("Failed to check stock location: {}", e);
+            let error = ErrorResponse::new(request_id, "DATABASE_ERROR", e.to_string());
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            return Ok(false);
+        }
+    };
+    let error = ErrorResponse::new(request_id, "NOT_FOUND", message);
+    let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+    Ok(false)
+}
+
+/// Handle inventory.stock.restock messages - book delivered parts
+pub async fn handle_restock(client: Client, mut subscriber: Subscriber, ctx: InventoryContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received inventory.stock.restock message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<RestockRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+        let payload = &request.payload;
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if !payload.quantity.is_finite() || payload.quantity <= 0.0 {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "quantity must be positive");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+        if !check_stock_target(&client, &reply, &ctx, request.id, user_id, payload.catalog_item_id, &payload.location).await? {
+            continue;
+        }
+
+        match queries::inventory::restock(
+            &ctx.pool,
+            user_id,
+            payload.catalog_item_id,
+            &payload.location,
+            payload.quantity,
+            payload.note.as_deref(),
+            auth_info.user_id,
+        )
+        .await
+        {
+            Ok(level) => {
+                let response = SuccessResponse::new(request.id, level);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to restock: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle inventory.stock.set messages - stocktake count and reorder point
+pub async fn handle_stock_set(client: Client, mut subscriber: Subscriber, ctx: InventoryContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received inventory.stock.set message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<SetStockRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+        let payload = &request.payload;
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let invalid = if payload.quantity.is_none() && payload.min_quantity.is_none() {
+            Some("quantity or minQuantity is required")
+        } else if payload.quantity.is_some_and(|q| !q.is_finite()) {
+            Some("quantity must be a number")
+        } else if payload.min_quantity.is_some_and(|q| !q.is_finite() || q < 0.0) {
+            Some("minQuantity must not be negative")
+        } else {
+            None
+        };
+        if let Some(msg) = invalid {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+        if !check_stock_target(&client, &reply, &ctx, request.id, user_id, payload.catalog_item_id, &payload.location).await? {
+            continue;
+        }
+
+        match queries::inventory::set_stock(
+            &ctx.pool,
+            user_id,
+            payload.catalog_item_id,
+            &payload.location,
+            payload.quantity,
+            payload.min_quantity,
+            payload.note.as_deref(),
+            auth_info.user_id,
+        )
+        .await
+        {
+            Ok(level) => {
+                let response = SuccessResponse::new(request.id, level);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to set stock: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle inventory.movements.list messages
+pub async fn handle_movements_list(client: Client, mut subscriber: Subscriber, ctx: InventoryContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received inventory.movements.list message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<ListMovementsRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+        let limit = request.payload.limit.unwrap_or(DEFAULT_MOVEMENTS_LIMIT).clamp(1, MAX_MOVEMENTS_LIMIT);
+
+        match queries::inventory::list_movements(&ctx.pool, user_id, request.payload.stock_level_id, limit).await {
+            Ok(movements) => {
+                let response = SuccessResponse::new(request.id, movements);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list stock movements: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle inventory.materials.list messages - materials of a work item
+pub async fn handle_materials_list(client: Client, mut subscriber: Subscriber, ctx: InventoryContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received inventory.materials.list message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<ListMaterialsRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+        let work_item_id = request.payload.work_item_id;
+
+        let loaded = async {
+            if queries::work_item::get_work_item(&ctx.pool, user_id, work_item_id).await?.is_none() {
+                return anyhow::Ok(None);
+            }
+            Ok(Some(queries::inventory::list_materials(&ctx.pool, work_item_id).await?))
+        }
+        .await;
+        match loaded {
+            Ok(Some(materials)) => {
+                let response = SuccessResponse::new(request.id, materials);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Work item not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list work item materials: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
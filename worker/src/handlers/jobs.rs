@@ -11,30 +11,36 @@ use std::sync::atomic::{AtomicU32, Ordering};
 use std::collections::HashMap;
 use anyhow::Result;
 use async_nats::Client;
-use async_nats::jetstream::{self, Context as JsContext};
-use chrono::Utc;
+use async_nats::jetstream;
+use chrono::{DateTime, Utc};
 use futures::StreamExt;
 use sqlx::PgPool;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use serde_json::json;
 
 use crate::db::queries;
 use crate::defaults::{default_work_end, default_work_start, DEFAULT_SERVICE_DURATION_MINUTES};
-use crate::services::routing::{RoutingService, MockRoutingService};
-use crate::services::vrp::{VrpSolver, VrpProblem, VrpStop, Depot, SolverConfig, StopTimeWindow, BreakConfig};
+use crate::services::quota;
+use crate::services::travel_correction;
+use crate::services::colocation::{LocationGroups, COLOCATION_RADIUS_M};
+use crate::services::routing::{anomaly_warnings, diagnostics, verify_matrices, MatrixVerdict, MockRoutingService, RoutingService};
+use crate::services::vrp::{VrpSolver, VrpProblem, VrpStop, Depot, SolverConfig, StopTimeWindow, StopDemand, BreakConfig};
+use crate::subjects;
+use crate::transport::{JobMessage, JobQueue};
 use crate::types::{
     Coordinates, ErrorResponse, Request, SuccessResponse,
     JobSubmitResponse, JobStatus, JobStatusUpdate, QueuedJob, RoutePlanJobRequest,
     PlannedRouteStop, RoutePlanResponse, RouteWarning, StopType,
+    ListLostJobsRequest, ListLostJobsResponse, LostJobInfo, QuotaMetric,
 };
 
 // Stream and consumer names
 const STREAM_NAME: &str = "SAZINKA_JOBS";
 const CONSUMER_NAME: &str = "route_workers";
-const SUBJECT_JOBS: &str = "sazinka.jobs.route";
-const SUBJECT_STATUS_PREFIX: &str = "sazinka.job.status";
+const SUBJECT_JOBS: &str = subjects::jobs::ROUTE;
+const SUBJECT_STATUS_PREFIX: &str = subjects::job::STATUS;
 
 /// Statistics about the job queue
 #[derive(Debug, Clone)]
@@ -50,7 +56,7 @@ pub struct QueueStats {
 /// Shared state for job processing
 pub struct JobProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
     pool: PgPool,
     routing_service: Arc<dyn RoutingService>,
     pending_count: AtomicU32,
@@ -63,7 +69,7 @@ impl JobProcessor {
         pool: PgPool,
         routing_service: Arc<dyn RoutingService>,
     ) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+        let queue = JobQueue::new(client.clone());
         
         // Create or get stream
         let stream_config = jetstream::stream::Config {
@@ -75,12 +81,12 @@ impl JobProcessor {
             ..Default::default()
         };
         
-        js.get_or_create_stream(stream_config).await?;
+        queue.ensure_stream(stream_config).await?;
         info!("JetStream stream '{}' ready", STREAM_NAME);
         
         Ok(Self {
             client,
-            js,
+            queue,
             pool,
             routing_service,
             pending_count: AtomicU32::new(0),
@@ -94,7 +100,7 @@ impl JobProcessor {
         
         // Publish to JetStream
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish(SUBJECT_JOBS, payload.into()).await?.await?;
+        self.queue.publish(SUBJECT_JOBS, payload.into()).await?;
         
         let pending = self.pending_count.fetch_add(1, Ordering::Relaxed) + 1;
         let estimated_wait = self.estimate_wait_time(pending);
@@ -126,7 +132,7 @@ impl JobProcessor {
     /// Publish a status update for a job
     pub async fn publish_status(&self, job_id: Uuid, status: JobStatus) -> Result<()> {
         let update = JobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", SUBJECT_STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(SUBJECT_STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         
         self.client.publish(subject, payload.into()).await?;
@@ -141,8 +147,6 @@ impl JobProcessor {
     
     /// Start processing jobs from the queue
     pub async fn start_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(STREAM_NAME).await?;
-        
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(CONSUMER_NAME.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
@@ -150,11 +154,9 @@ impl JobProcessor {
             ..Default::default()
         };
         
-        let consumer = stream.get_or_create_consumer(CONSUMER_NAME, consumer_config).await?;
+        let mut messages = self.queue.consume(STREAM_NAME, consumer_config).await?;
         info!("JetStream consumer '{}' ready", CONSUMER_NAME);
         
-        let mut messages = consumer.messages().await?;
-        
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -177,7 +179,7 @@ impl JobProcessor {
     }
     
     /// Process a single job
-    async fn process_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_job(&self, msg: JobMessage) -> Result<()> {
         use crate::services::job_history::JOB_HISTORY;
         
         let job: QueuedJob = serde_json::from_slice(&msg.payload)?;
@@ -196,6 +198,7 @@ impl JobProcessor {
         
         info!("Processing route job {} with {} customers", job_id, job.request.customer_ids.len());
         self.pending_count.fetch_sub(1, Ordering::Relaxed);
+        JOB_HISTORY.record_running(job_id, "route", user_id, started_at);
         
         // Publish processing status
         self.publish_status(job_id, JobStatus::Processing {
@@ -378,18 +381,25 @@ impl JobProcessor {
             }
         }
         
+        // Units of one building are one physical place: route between
+        // places only
+        let places = LocationGroups::new(&locations, COLOCATION_RADIUS_M);
+        if places.colocated_count() > 0 {
+            debug!("{} stops share a building with another stop", places.colocated_count());
+        }
+
         // Get distance/time matrices
         self.publish_status(job_id, JobStatus::Processing {
             progress: 40,
             message: "jobs:calculating_distances".to_string(),
         }).await?;
         
-        let (matrices, routing_fallback_used) = match self.routing_service.get_matrices(&locations).await {
+        let (mut matrices, mut routing_fallback_used) = match self.routing_service.get_matrices(&places.anchors).await {
             Ok(m) => (m, false),
             Err(e) => {
                 warn!("Primary routing failed: {}. Using fallback.", e);
                 let mock_service = MockRoutingService::new();
-                match mock_service.get_matrices(&locations).await {
+                match mock_service.get_matrices(&places.anchors).await {
                     Ok(m) => (m, true),
                     Err(e2) => {
                         return Err(anyhow::anyhow!("Routing failed: {}", e2));
@@ -398,6 +408,37 @@ impl JobProcessor {
             }
         };
         
+        // Sanity-check the routing data: a few bad legs are replaced by
+        // estimates and flagged, a mostly bad matrix is discarded entirely
+        let mut matrix_affected: Vec<usize> = Vec::new();
+        if !routing_fallback_used {
+            let verdict = verify_matrices(&places.anchors, &mut matrices);
+            diagnostics::observe_matrix(&self.routing_service, &places.anchors, &verdict);
+            match verdict {
+                MatrixVerdict::Clean => {}
+                MatrixVerdict::Repaired { affected } => matrix_affected = places.members(&affected),
+                MatrixVerdict::Untrustworthy => {
+                    matrices = MockRoutingService::new().estimate(&places.anchors);
+                    routing_fallback_used = true;
+                }
+            }
+        }
+
+        // Correct systematic routing bias measured on past routes
+        if !routing_fallback_used {
+            match travel_correction::load_model(&self.pool, user_id).await {
+                Ok(model) if !model.is_empty() => {
+                    model.apply_to_matrix(&mut matrices, &places.anchors);
+                    debug!("Travel time correction applied ({} zone factors)", model.zone_factor_count());
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to load travel time correction: {}", e),
+            }
+        }
+
+        // Legs between units of the same building are zero
+        let matrices = places.expand(&matrices);
+
         // Solve VRP
         self.publish_status(job_id, JobStatus::Processing {
             progress: 60,
@@ -493,6 +534,15 @@ impl JobProcessor {
             });
         }
         
+        // Flag stops whose travel legs had to be estimated
+        if !matrix_affected.is_empty() {
+            let matrix_customers: Vec<(Uuid, String)> = valid_customers
+                .iter()
+                .map(|c| (c.id, c.name.clone().unwrap_or_default()))
+                .collect();
+            warnings.extend(anomaly_warnings(&matrix_affected, &matrix_customers, &planned_stops));
+        }
+
         if routing_fallback_used {
             warnings.push(RouteWarning {
                 stop_index: None,
@@ -653,14 +703,34 @@ impl JobProcessor {
                     }
                 };
                 
+                // Devices at a service site or with a GPS override are visited there
+                let location = match queries::customer_site::service_location_for_date(
+                    &self.pool,
+                    user_id,
+                    *customer_id,
+                    date,
+                )
+                .await
+                {
+                    Ok(location) => location,
+                    Err(e) => {
+                        warn!("Failed to load service location for customer {} on {}: {}", customer_id, date, e);
+                        None
+                    }
+                };
+                let (street, city, postal_code) = match &location {
+                    Some(l) if l.has_address() => (l.street.clone(), l.city.clone(), l.postal_code.clone()),
+                    _ => (customer.street.clone(), customer.city.clone(), customer.postal_code.clone()),
+                };
+
                 customers.push(CustomerForRoute {
                     id: customer.id,
                     name: customer.name.clone(),
-                    street: customer.street.clone(),
-                    city: customer.city.clone(),
-                    postal_code: customer.postal_code.clone(),
-                    lat: customer.lat,
-                    lng: customer.lng,
+                    street,
+                    city,
+                    postal_code,
+                    lat: location.as_ref().map_or(customer.lat, |l| Some(l.coordinates.lat)),
+                    lng: location.as_ref().map_or(customer.lng, |l| Some(l.coordinates.lng)),
                     scheduled_time_start: tw_start,
                     scheduled_time_end: tw_end,
                 });
@@ -717,6 +787,7 @@ impl JobProcessor {
                     service_duration_minutes: stop_service_duration,
                     time_window,
                     priority: 1,
+                    demand: StopDemand::default(),
                 })
             })
             .collect();
@@ -726,6 +797,7 @@ impl JobProcessor {
             stops,
             shift_start,
             shift_end,
+            end_location: None,
             break_config,
         }
     }
@@ -790,8 +862,24 @@ pub async fn handle_job_submit(
             }
         };
         
+        let user_id = auth.data_user_id();
+        match quota::consume(&processor.pool, user_id, QuotaMetric::RoutePlans, 1).await {
+            Ok(Ok(())) => {}
+            Ok(Err(exceeded)) => {
+                let error = ErrorResponse::new(request_id, "QUOTA_EXCEEDED", exceeded.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to check route plan quota: {}", e);
+                let error = ErrorResponse::new(request_id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
         let mut job_request = request.payload;
-        job_request.user_id = Some(auth.data_user_id());
+        job_request.user_id = Some(user_id);
         
         match processor.submit_job(job_request).await {
             Ok(response) => {
@@ -859,22 +947,49 @@ fn extract_request_id(payload: &[u8]) -> Uuid {
 // Job History Handler
 // ==========================================================================
 
+/// Default and largest page of job history
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+const MAX_HISTORY_LIMIT: i64 = 200;
+
 /// Request to get job history
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ListJobHistoryRequest {
-    pub limit: Option<usize>,
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub offset: Option<i64>,
+    /// Exact type (`import.customer`) or family (`import`)
     pub job_type: Option<String>,
     pub status: Option<String>,
+    /// Jobs finished at or after this time
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// Jobs finished before this time
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl ListJobHistoryRequest {
+    fn filter(&self) -> queries::job_history::JobHistoryFilter {
+        queries::job_history::JobHistoryFilter {
+            job_type: self.job_type.clone().filter(|t| !t.is_empty()),
+            status: self.status.clone().filter(|s| !s.is_empty()),
+            from: self.from,
+            to: self.to,
+            limit: self.limit.unwrap_or(DEFAULT_HISTORY_LIMIT).clamp(1, MAX_HISTORY_LIMIT),
+            offset: self.offset.unwrap_or(0).max(0),
+        }
+    }
 }
 
 /// Handle jobs.history requests
 pub async fn handle_job_history(
     client: Client,
     mut subscriber: async_nats::Subscriber,
+    pool: PgPool,
     jwt_secret: Arc<String>,
 ) -> Result<()> {
-    use crate::services::job_history::{JOB_HISTORY, JobHistoryResponse};
+    use crate::services::job_history::JobHistoryResponse;
     
     while let Some(msg) = subscriber.next().await {
         let reply = match msg.reply {
@@ -902,13 +1017,19 @@ pub async fn handle_job_history(
             }
         };
         
-        let limit = request.payload.limit.unwrap_or(50);
-        
         // Filter by caller_id for multi-tenant isolation
-        let history: JobHistoryResponse = JOB_HISTORY.get_recent_for_user(caller_id, limit);
-        
-        let success = SuccessResponse::new(request.id, history);
-        let _ = client.publish(reply, serde_json::to_vec(&success)?.into()).await;
+        let filter = request.payload.filter();
+        match queries::job_history::list_entries(&pool, caller_id, &filter).await {
+            Ok((jobs, total)) => {
+                let success = SuccessResponse::new(request.id, JobHistoryResponse { jobs, total });
+                let _ = client.publish(reply, serde_json::to_vec(&success)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list job history: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
     }
     
     Ok(())
@@ -1017,11 +1138,19 @@ pub async fn handle_job_cancel(
 }
 
 /// Handle jobs.retry requests
+///
+/// Only jobs whose queue message was lost can be retried: they are
+/// republished from the Postgres backup under the same job id.
 pub async fn handle_job_retry(
     client: Client,
     mut subscriber: async_nats::Subscriber,
+    pool: PgPool,
     jwt_secret: Arc<String>,
 ) -> Result<()> {
+    use crate::services::job_backup::{self, ResubmitError};
+
+    let queue = JobQueue::new(client.clone());
+
     while let Some(msg) = subscriber.next().await {
         let reply = match msg.reply {
             Some(ref r) => r.clone(),
@@ -1039,7 +1168,7 @@ pub async fn handle_job_retry(
         };
 
         // Require authentication
-        let _user_id = match crate::auth::extract_auth(&request, &jwt_secret) {
+        let user_id = match crate::auth::extract_auth(&request, &jwt_secret) {
             Ok(info) => info.data_user_id(),
             Err(_) => {
                 let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
@@ -1053,14 +1182,21 @@ pub async fn handle_job_retry(
         
         info!("Attempting to retry job {} of type {}", job_id, job_type);
         
-        // In a full implementation, we would:
-        // 1. Look up the original job request
-        // 2. Re-submit it to the appropriate queue
-        // For now, return a placeholder response
-        
+        let (success, message) = match job_backup::resubmit(&queue, &pool, user_id, job_id).await {
+            Ok(Ok(())) => (true, "jobs:retry_resubmitted"),
+            Ok(Err(ResubmitError::NotFound)) | Ok(Err(ResubmitError::NotLost)) => (false, "jobs:retry_not_available"),
+            Ok(Err(ResubmitError::PayloadNotKept)) => (false, "jobs:retry_payload_not_kept"),
+            Err(e) => {
+                error!("Failed to resubmit job {}: {}", job_id, e);
+                let error = ErrorResponse::new(request.id, "RETRY_FAILED", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
         let response = JobActionResponse {
-            success: false,
-            message: "jobs:retry_not_implemented".to_string(),
+            success,
+            message: message.to_string(),
             job_id,
         };
         let success = SuccessResponse::new(request.id, response);
@@ -1070,6 +1206,58 @@ pub async fn handle_job_retry(
     Ok(())
 }
 
+/// Handle jobs.lost requests — queued jobs whose message vanished from JetStream
+pub async fn handle_lost_jobs(
+    client: Client,
+    mut subscriber: async_nats::Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let reply = match msg.reply {
+            Some(ref r) => r.clone(),
+            None => continue,
+        };
+
+        let request: Request<ListLostJobsRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse lost jobs request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match crate::auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let limit = request.payload.limit.unwrap_or(50).clamp(1, 500);
+        match queries::job_backup::list_lost_for_user(&pool, user_id, limit).await {
+            Ok(backups) => {
+                let response = ListLostJobsResponse {
+                    jobs: backups.into_iter().map(LostJobInfo::from).collect(),
+                };
+                let success = SuccessResponse::new(request.id, response);
+                let _ = client.publish(reply, serde_json::to_vec(&success)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list lost jobs: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 // ==========================================================================
 // Tests
 // ==========================================================================
@@ -1078,6 +1266,20 @@ pub async fn handle_job_retry(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_job_history_filter_bounds_paging() {
+        let request: ListJobHistoryRequest =
+            serde_json::from_str(r#"{"limit": 5000, "offset": -3, "jobType": "import", "status": ""}"#).unwrap();
+        let filter = request.filter();
+        assert_eq!(filter.limit, MAX_HISTORY_LIMIT);
+        assert_eq!(filter.offset, 0);
+        assert_eq!(filter.job_type.as_deref(), Some("import"));
+        assert!(filter.status.is_none());
+
+        let request: ListJobHistoryRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(request.filter().limit, DEFAULT_HISTORY_LIMIT);
+    }
+
     #[test]
     fn test_queue_stats_default_values() {
         let stats = QueueStats {
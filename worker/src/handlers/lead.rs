@@ -0,0 +1,554 @@
+//! Web lead intake handlers for NATS messages
+//!
+//! Website contact and booking forms post to `sazinka.portal.lead.submit`
+//! (no login) through the HTTP gateway with the account's intake key.
+//! Submissions go through a honeypot, per-IP and per-key rate limits and an
+//! optional captcha. A new contact becomes a customer in the "new" lead
+//! state; a known one gets the message logged. The dispatcher is notified
+//! in-app and by e-mail.
+//!
+//! Leads then move through the pipeline (contacted, quoted, won or lost)
+//! with `sazinka.lead.transition`; every change is logged for the
+//! conversion funnel report.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::account;
+use super::onboarding::generate_token;
+use super::{parse_authenticated, parse_owner};
+use crate::db::queries;
+use crate::services::email_sender::EmailSender;
+use crate::services::email_templates::NewLeadEmail;
+use crate::services::lead_intake;
+use crate::services::notification_dispatch;
+use crate::services::quota;
+use crate::services::rate_limiter::{MultiRateLimiter, RateLimiterConfig};
+use crate::subjects;
+use crate::types::customer::ACQUISITION_SOURCE_WEB_FORM;
+use crate::types::lead::{
+    validate_lead_transition, LeadHistoryRequest, LeadIntakeSettingsResponse, SubmitWebLeadRequest,
+    SubmitWebLeadResponse, TransitionLeadRequest, UpdateLeadIntakeRequest, LEAD_STATUS_NEW,
+};
+use crate::types::notification::{NOTIFICATION_CATEGORY_SYSTEM, NOTIFICATION_KIND_LEAD};
+use crate::types::{CreateCustomerRequest, ErrorResponse, QuotaMetric, Request, SuccessResponse};
+
+const OWNER_ONLY: &str = "Only company owners can manage web lead intake";
+
+/// Shared by all lead handlers
+#[derive(Clone)]
+pub struct LeadContext {
+    pub pool: PgPool,
+    pub jwt_secret: Arc<String>,
+    pub email_sender: Arc<dyn EmailSender>,
+    pub rate_limiter: Arc<MultiRateLimiter>,
+}
+
+/// Start all lead intake NATS handlers
+pub async fn start_handlers(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    email_sender: Arc<dyn EmailSender>,
+) -> Result<()> {
+    info!("Starting lead handlers...");
+
+    let rate_limiter = Arc::new(MultiRateLimiter::new(vec![
+        (
+            "lead.ip",
+            RateLimiterConfig {
+                max_attempts: 5,
+                window_secs: 3600,
+            },
+        ),
+        (
+            "lead.key",
+            RateLimiterConfig {
+                max_attempts: 50,
+                window_secs: 3600,
+            },
+        ),
+    ]));
+    let ctx = LeadContext { pool, jwt_secret, email_sender, rate_limiter };
+
+    let [get_sub, update_sub, rotate_sub, submit_sub, transition_sub, history_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::lead::INTAKE_GET,
+            subjects::lead::INTAKE_UPDATE,
+            subjects::lead::INTAKE_ROTATE,
+            subjects::portal::LEAD_SUBMIT,
+            subjects::lead::TRANSITION,
+            subjects::lead::HISTORY,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_intake_get(client.clone(), get_sub, ctx.clone()));
+    tokio::spawn(handle_intake_update(client.clone(), update_sub, ctx.clone()));
+    tokio::spawn(handle_intake_rotate(client.clone(), rotate_sub, ctx.clone()));
+    tokio::spawn(handle_submit(client.clone(), submit_sub, ctx.clone()));
+    tokio::spawn(handle_transition(client.clone(), transition_sub, ctx.clone()));
+    tokio::spawn(handle_history(client.clone(), history_sub, ctx));
+
+    info!("Lead handlers started");
+    Ok(())
+}
+
+/// Handle lead.intake.get messages - settings incl. the form key, created on first use
+pub async fn handle_intake_get(client: Client, mut subscriber: Subscriber, ctx: LeadContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received lead.intake.get message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_owner::<serde_json::Value>(&client, &reply, &msg.payload, &ctx.jwt_secret, OWNER_ONLY).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        let (key, _) = generate_token();
+        match queries::lead::get_or_create_settings(&ctx.pool, user_id, &key).await {
+            Ok(settings) => {
+                let response = SuccessResponse::new(request.id, LeadIntakeSettingsResponse::from(settings));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to load lead intake settings: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle lead.intake.update messages
+pub async fn handle_intake_update(client: Client, mut subscriber: Subscriber, ctx: LeadContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received lead.intake.update message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_owner::<UpdateLeadIntakeRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret, OWNER_ONLY).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if let Err(msg) = request.payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let (key, _) = generate_token();
+        let updated = async {
+            queries::lead::get_or_create_settings(&ctx.pool, user_id, &key).await?;
+            queries::lead::update_settings(&ctx.pool, user_id, &request.payload).await
+        }
+        .await;
+        match updated {
+            Ok(Some(settings)) => {
+                let response = SuccessResponse::new(request.id, LeadIntakeSettingsResponse::from(settings));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Lead intake is not set up");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to update lead intake settings: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle lead.intake.rotate messages - new form key, the old one stops working
+pub async fn handle_intake_rotate(client: Client, mut subscriber: Subscriber, ctx: LeadContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received lead.intake.rotate message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_owner::<serde_json::Value>(&client, &reply, &msg.payload, &ctx.jwt_secret, OWNER_ONLY).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let (key, _) = generate_token();
+        match queries::lead::rotate_key(&ctx.pool, user_id, &key).await {
+            Ok(Some(settings)) => {
+                info!("Rotated lead intake key of {}", user_id);
+                let response = SuccessResponse::new(request.id, LeadIntakeSettingsResponse::from(settings));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Lead intake is not set up");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to rotate lead intake key: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle portal.lead.submit messages (public, no login)
+pub async fn handle_submit(client: Client, mut subscriber: Subscriber, ctx: LeadContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received portal.lead.submit message");
+        let Some(reply) = msg.reply.clone() else { continue };
+
+        let request: Request<SubmitWebLeadRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        let payload = &request.payload;
+        let received = SuccessResponse::new(request.id, SubmitWebLeadResponse { received: true });
+
+        // Bucket keys by prefix so full keys aren't kept in memory
+        let ip = request.client_ip.as_deref().unwrap_or("unknown");
+        let key_bucket = payload.key.trim().chars().take(8).collect::<String>();
+        if !ctx.rate_limiter.check_and_record("lead.ip", ip) || !ctx.rate_limiter.check_and_record("lead.key", &key_bucket) {
+            let error = ErrorResponse::new(request.id, "RATE_LIMITED", "Too many requests.");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        // Bots get the normal reply so they don't learn to skip the field
+        if payload.is_honeypot_filled() {
+            debug!("Dropped web lead with filled honeypot from {}", ip);
+            let _ = client.publish(reply, serde_json::to_vec(&received)?.into()).await;
+            continue;
+        }
+
+        if let Err(msg) = payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let settings = match queries::lead::find_by_key(&ctx.pool, payload.key.trim()).await {
+            Ok(Some(settings)) if settings.enabled => settings,
+            Ok(_) => {
+                let error = ErrorResponse::new(request.id, "INVALID_KEY", "This form is not accepting submissions");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to resolve lead intake key: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        let user_id = settings.user_id;
+
+        if let (Some(provider), Some(secret)) = (settings.captcha_provider.as_deref(), settings.captcha_secret.as_deref()) {
+            let token = payload.captcha_token.as_deref().unwrap_or("");
+            let passed = !token.is_empty()
+                && match lead_intake::verify_captcha(provider, secret, token, request.client_ip.as_deref()).await {
+                    Ok(passed) => passed,
+                    Err(e) => {
+                        warn!("Captcha verification for {} failed: {}", user_id, e);
+                        false
+                    }
+                };
+            if !passed {
+                let error = ErrorResponse::new(request.id, "CAPTCHA_FAILED", "Captcha verification failed");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let name = payload.name.trim();
+        let email = payload.email.as_deref().map(str::trim).filter(|e| !e.is_empty());
+        let phone = payload.phone.as_deref().map(str::trim).filter(|p| !p.is_empty());
+
+        let existing = match queries::lead::find_matching_customer(&ctx.pool, user_id, email, phone).await {
+            Ok(existing) => existing,
+            Err(e) => {
+                error!("Failed to match web lead to customers: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let customer_id = match existing {
+            Some((id, _)) => id,
+            None => {
+                match quota::consume(&ctx.pool, user_id, QuotaMetric::Customers, 1).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(exceeded)) => {
+                        warn!("Web lead for {} rejected: {}", user_id, exceeded);
+                        let error = ErrorResponse::new(request.id, "QUOTA_EXCEEDED", "This form is not accepting submissions");
+                        let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                        continue;
+                    }
+                    Err(e) => {
+                        error!("Failed to check customer quota: {}", e);
+                        let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                        let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                        continue;
+                    }
+                }
+
+                let create = lead_customer(payload);
+                let created = async {
+                    let customer = queries::customer::create_customer(&ctx.pool, user_id, &create).await?;
+                    queries::lead::transition_status(&ctx.pool, user_id, customer.id, None, LEAD_STATUS_NEW, None, None).await?;
+                    anyhow::Ok(customer.id)
+                }
+                .await;
+                match created {
+                    Ok(id) => id,
+                    Err(e) => {
+                        error!("Failed to create customer from web lead: {}", e);
+                        let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                        let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                        continue;
+                    }
+                }
+            }
+        };
+        let existing_customer = existing.is_some();
+
+        let owner = match queries::settings::get_user_settings(&ctx.pool, user_id).await {
+            Ok(owner) => owner,
+            Err(e) => {
+                warn!("Failed to load settings for web lead {}: {}", user_id, e);
+                None
+            }
+        };
+        let locale = owner.as_ref().map(|o| o.locale.as_str()).unwrap_or("en");
+
+        let note = lead_intake::lead_note(payload, locale);
+        if let Err(e) = queries::communication::create_communication(
+            &ctx.pool,
+            user_id,
+            customer_id,
+            None,
+            "note",
+            "inbound",
+            Some(lead_intake::lead_subject(locale)),
+            &note,
+            Some(name),
+            phone,
+            None,
+        )
+        .await
+        {
+            warn!("Failed to log web lead on customer {}: {}", customer_id, e);
+        }
+
+        info!("Web lead for {} stored on customer {} (existing: {})", user_id, customer_id, existing_customer);
+        let _ = client.publish(reply, serde_json::to_vec(&received)?.into()).await;
+
+        let contact = lead_intake::contact_line(email, phone);
+        notification_dispatch::notify_or_warn(
+            &ctx.pool,
+            user_id,
+            NOTIFICATION_CATEGORY_SYSTEM,
+            NOTIFICATION_KIND_LEAD,
+            &lead_intake::notification_title(name, locale),
+            Some(&contact),
+            Some(("customer", customer_id)),
+        )
+        .await;
+
+        if let Some(owner) = owner {
+            let email_msg = NewLeadEmail {
+                to: &owner.email,
+                name,
+                contact: &contact,
+                message: payload.message.as_deref(),
+                preferred_date: payload.preferred_date,
+                existing_customer,
+                locale: &owner.locale,
+            }
+            .render();
+            let sender = Arc::clone(&ctx.email_sender);
+            tokio::spawn(async move {
+                if let Err(e) = sender.send(email_msg).await {
+                    warn!("Failed to send new lead email: {}", e);
+                }
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle lead.transition messages - move a customer through the lead pipeline
+pub async fn handle_transition(client: Client, mut subscriber: Subscriber, ctx: LeadContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received lead.transition message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<TransitionLeadRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+        let payload = &request.payload;
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let reason = match payload.validated_reason() {
+            Ok(reason) => reason,
+            Err(msg) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let customer = match queries::customer::get_customer(&ctx.pool, user_id, payload.customer_id).await {
+            Ok(Some(customer)) => customer,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load customer: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let from = customer.lead_status.as_deref();
+        if let Err(msg) = validate_lead_transition(from, &payload.status) {
+            let error = ErrorResponse::new(request.id, "INVALID_STATE", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let moved = queries::lead::transition_status(
+            &ctx.pool,
+            user_id,
+            customer.id,
+            from,
+            &payload.status,
+            reason.as_deref(),
+            Some(auth_info.user_id),
+        )
+        .await;
+        let moved = match moved {
+            Ok(true) => queries::customer::get_customer(&ctx.pool, user_id, customer.id).await,
+            Ok(false) => {
+                let error = ErrorResponse::new(request.id, "CONFLICT", "The lead was changed meanwhile; reload and retry");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => Err(e),
+        };
+        match moved {
+            Ok(Some(updated)) => {
+                info!("Lead {} moved from {:?} to {}", customer.id, from, payload.status);
+                let response = SuccessResponse::new(request.id, updated);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to move lead: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle lead.history messages - logged pipeline changes of a customer
+pub async fn handle_history(client: Client, mut subscriber: Subscriber, ctx: LeadContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received lead.history message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<LeadHistoryRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+
+        match queries::lead::list_status_changes(&ctx.pool, auth_info.data_user_id(), request.payload.customer_id).await {
+            Ok(changes) => {
+                let response = SuccessResponse::new(request.id, changes);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list lead changes: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Customer created for a new web lead
+fn lead_customer(payload: &SubmitWebLeadRequest) -> CreateCustomerRequest {
+    let text = |value: &Option<String>| value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(String::from);
+    CreateCustomerRequest {
+        customer_type: None,
+        name: Some(payload.name.trim().to_string()),
+        contact_person: None,
+        ico: None,
+        dic: None,
+        email: text(&payload.email),
+        phone: text(&payload.phone),
+        phone_raw: text(&payload.phone),
+        street: text(&payload.street),
+        city: text(&payload.city),
+        postal_code: text(&payload.postal_code),
+        country: None,
+        lat: None,
+        lng: None,
+        notes: None,
+        parent_customer_id: None,
+        language: None,
+        customer_code: None,
+        acquisition_source: Some(ACQUISITION_SOURCE_WEB_FORM.to_string()),
+        referrer_customer_id: None,
+    }
+}
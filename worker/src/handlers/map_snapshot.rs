@@ -0,0 +1,263 @@
+//! Static map snapshot handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use base64::Engine;
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::routing::{RoutingService, ValhallaClient};
+use crate::services::static_map::{MapMarker, MapOverlay, MarkerKind, StaticMapRenderer};
+use crate::subjects;
+use crate::types::map_snapshot::{
+    map_snapshot_size, CustomerMapSnapshotRequest, MapSnapshotResponse, RouteMapSnapshotRequest,
+};
+use crate::types::{Coordinates, ErrorResponse, Request, SuccessResponse};
+
+/// Start all map snapshot NATS handlers
+pub async fn start_handlers(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    renderer: Arc<StaticMapRenderer>,
+    routing_service: Arc<dyn RoutingService>,
+) -> Result<()> {
+    info!("Starting map snapshot handlers...");
+
+    let [route_sub, customer_sub] = subjects::subscribe_all(
+        &client,
+        [subjects::map::SNAPSHOT_ROUTE, subjects::map::SNAPSHOT_CUSTOMER],
+    )
+    .await?;
+
+    tokio::spawn(handle_route_snapshot(
+        client.clone(),
+        route_sub,
+        pool.clone(),
+        jwt_secret.clone(),
+        renderer.clone(),
+        routing_service,
+    ));
+    tokio::spawn(handle_customer_snapshot(client.clone(), customer_sub, pool, jwt_secret, renderer));
+
+    info!("Map snapshot handlers started");
+    Ok(())
+}
+
+fn snapshot_response(renderer: &StaticMapRenderer, png: &[u8], width: u32, height: u32) -> MapSnapshotResponse {
+    MapSnapshotResponse {
+        image_data_url: format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png)),
+        width,
+        height,
+        attribution: renderer.attribution().to_string(),
+    }
+}
+
+/// Depot, stops in order and the road geometry between them (straight lines without Valhalla)
+async fn route_overlay(
+    pool: &PgPool,
+    routing_service: &dyn RoutingService,
+    user_id: Uuid,
+    route_id: Uuid,
+) -> Result<Option<MapOverlay>> {
+    let Some(route) = queries::route::get_route_by_id(pool, user_id, route_id).await? else {
+        return Ok(None);
+    };
+    let depot = match route.depot_id {
+        Some(depot_id) => queries::settings::get_depot(pool, depot_id, user_id).await?,
+        None => queries::settings::get_primary_depot(pool, user_id).await?,
+    };
+    let stops = queries::route::get_route_stops_with_info(pool, route_id).await?;
+
+    let mut markers = Vec::new();
+    let mut waypoints = Vec::new();
+    if let Some(ref depot) = depot {
+        markers.push(MapMarker { lat: depot.lat, lng: depot.lng, kind: MarkerKind::Depot });
+        waypoints.push(Coordinates { lat: depot.lat, lng: depot.lng });
+    }
+    for stop in &stops {
+        if let (Some(lat), Some(lng)) = (stop.customer_lat, stop.customer_lng) {
+            markers.push(MapMarker { lat, lng, kind: MarkerKind::Stop });
+            waypoints.push(Coordinates { lat, lng });
+        }
+    }
+    if let Some(ref depot) = depot {
+        waypoints.push(Coordinates { lat: depot.lat, lng: depot.lng });
+    }
+
+    let straight: Vec<[f64; 2]> = waypoints.iter().map(|c| [c.lng, c.lat]).collect();
+    let path = match routing_service.as_any().downcast_ref::<ValhallaClient>() {
+        Some(valhalla) if waypoints.len() >= 2 => match valhalla.get_route_geometry(&waypoints).await {
+            Ok(geometry) if !geometry.coordinates.is_empty() => geometry.coordinates,
+            _ => straight,
+        },
+        _ => straight,
+    };
+
+    Ok(Some(MapOverlay { path, markers }))
+}
+
+/// Handle map.snapshot.route messages
+pub async fn handle_route_snapshot(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    renderer: Arc<StaticMapRenderer>,
+    routing_service: Arc<dyn RoutingService>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received map.snapshot.route message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<RouteMapSnapshotRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = &request.payload;
+        let overlay = match route_overlay(&pool, routing_service.as_ref(), user_id, payload.route_id).await {
+            Ok(Some(overlay)) if !overlay.markers.is_empty() => overlay,
+            Ok(Some(_)) => {
+                let error = ErrorResponse::new(request.id, "NO_LOCATION", "Route has no located stops");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Route not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load route for map snapshot: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let (width, height) = map_snapshot_size(payload.width, payload.height);
+        match renderer.render(&overlay, width, height).await {
+            Ok(png) => {
+                let response = SuccessResponse::new(request.id, snapshot_response(&renderer, &png, width, height));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to render route map snapshot: {}", e);
+                let error = ErrorResponse::new(request.id, "RENDER_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle map.snapshot.customer messages
+pub async fn handle_customer_snapshot(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    renderer: Arc<StaticMapRenderer>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received map.snapshot.customer message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CustomerMapSnapshotRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = &request.payload;
+        let (lat, lng) = match queries::customer::get_customer(&pool, user_id, payload.customer_id).await {
+            Ok(Some(customer)) => match (customer.lat, customer.lng) {
+                (Some(lat), Some(lng)) => (lat, lng),
+                _ => {
+                    let error = ErrorResponse::new(request.id, "NO_LOCATION", "Customer has no coordinates");
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            },
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load customer for map snapshot: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let overlay = MapOverlay {
+            path: Vec::new(),
+            markers: vec![MapMarker { lat, lng, kind: MarkerKind::Customer }],
+        };
+        let (width, height) = map_snapshot_size(payload.width, payload.height);
+        match renderer.render(&overlay, width, height).await {
+            Ok(png) => {
+                let response = SuccessResponse::new(request.id, snapshot_response(&renderer, &png, width, height));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to render customer map snapshot: {}", e);
+                let error = ErrorResponse::new(request.id, "RENDER_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
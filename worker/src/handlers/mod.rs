@@ -1,32 +1,70 @@
 //! NATS message handlers
 
+pub mod account;
 pub mod admin;
+pub mod admin_users;
+pub mod analysis;
 pub mod auth;
+pub mod automation;
+pub mod calendar_feed;
+pub mod campaign;
+pub mod certificate;
 pub mod communication;
+pub mod coverage;
 pub mod crew;
+pub mod crm_sync;
 pub mod customer;
+pub mod customer_hierarchy;
+pub mod customer_risk;
+pub mod customer_site;
+pub mod dispatch_board;
+pub mod debug_recording;
 pub mod device;
+pub mod device_code;
 pub mod device_type_config;
+pub mod escalation;
 pub mod export;
 pub mod geocode;
+pub mod health;
+pub mod hooks;
 pub mod import;
 pub mod import_processors;
+pub mod import_upload;
+pub mod interval_adjustment;
 #[cfg(test)]
 pub mod import_tests;
 pub mod inbox;
+pub mod inventory;
 pub mod jobs;
+pub mod lead;
+pub mod map_snapshot;
 pub mod note;
+pub mod notification;
 pub mod onboarding;
+pub mod organization;
 pub mod ping;
 pub mod planned_action;
+pub mod presence;
+pub mod quality;
+pub mod quote;
+pub mod report;
+pub mod reschedule;
+pub mod retention;
 pub mod revision;
 pub mod role;
 pub mod route;
+pub mod route_history;
+pub mod route_multiday;
 pub mod scoring;
 pub mod settings;
 pub mod slots;
+pub mod subcontractor;
 pub mod task;
+pub mod telemetry;
+pub mod template_translation;
 pub mod visit;
+pub mod visit_duration;
+pub mod webhook;
 pub mod work_item;
 
 use anyhow::Result;
@@ -38,13 +76,133 @@ use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::config::Config;
+use crate::db::repo::Repositories;
+use crate::services::backup;
+use crate::services::geocode_freshness;
+use crate::services::travel_correction;
+use crate::services::soft_delete;
+use crate::services::leader;
+use crate::services::crash_report;
+use crate::services::health::{self as health_service, HealthTargets};
 use crate::services::email_sender::{EmailSender, LogEmailSender, ResendEmailSender};
 use crate::services::geocoding::{create_geocoder, Geocoder};
 use crate::services::rate_limiter::{MultiRateLimiter, RateLimiterConfig};
 use crate::services::routing::{create_routing_service_with_fallback, RoutingService};
+use crate::services::static_map::StaticMapRenderer;
 use crate::services::valhalla_processor::ValhallaProcessor;
+use crate::subjects;
 use crate::types::{ErrorResponse, GeometryJobRequest, MatrixJobRequest, Request, SuccessResponse};
 
+// ==========================================================================
+// Request Parsing Shared by Handlers
+// ==========================================================================
+
+/// Parse the request and authenticate it. Replies with the error itself.
+pub(crate) async fn parse_authenticated<T: serde::de::DeserializeOwned>(
+    client: &Client,
+    reply: &async_nats::Subject,
+    payload: &[u8],
+    jwt_secret: &str,
+) -> Result<Option<(Request<T>, crate::auth::AuthInfo)>> {
+    let request: Request<T> = match serde_json::from_slice(payload) {
+        Ok(req) => req,
+        Err(e) => {
+            error!("Failed to parse request: {}", e);
+            let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            return Ok(None);
+        }
+    };
+
+    match crate::auth::extract_auth(&request, jwt_secret) {
+        Ok(info) => Ok(Some((request, info))),
+        Err(_) => {
+            let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            Ok(None)
+        }
+    }
+}
+
+/// Publish an error response
+pub(crate) async fn reply_error(
+    client: &Client,
+    reply: &async_nats::Subject,
+    request_id: Uuid,
+    code: &str,
+    message: impl Into<String>,
+) -> Result<()> {
+    let error = ErrorResponse::new(request_id, code, message);
+    let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+    Ok(())
+}
+
+/// Publish a success response
+pub(crate) async fn reply_success<T: serde::Serialize>(
+    client: &Client,
+    reply: &async_nats::Subject,
+    request_id: Uuid,
+    payload: T,
+) -> Result<()> {
+    let response = SuccessResponse::new(request_id, payload);
+    let _ = client.publish(reply.clone(), serde_json::to_vec(&response)?.into()).await;
+    Ok(())
+}
+
+/// Parse the request and check it comes from the company owner; others get
+/// `forbidden` back. Replies with the error itself.
+pub(crate) async fn parse_owner<T: serde::de::DeserializeOwned>(
+    client: &Client,
+    reply: &async_nats::Subject,
+    payload: &[u8],
+    jwt_secret: &str,
+    forbidden: &str,
+) -> Result<Option<(Request<T>, crate::auth::AuthInfo)>> {
+    let Some((request, auth_info)) = parse_authenticated::<T>(client, reply, payload, jwt_secret).await? else {
+        return Ok(None);
+    };
+
+    if auth_info.role != "customer" && auth_info.role != "admin" {
+        let error = ErrorResponse::new(request.id, "FORBIDDEN", forbidden);
+        let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+        return Ok(None);
+    }
+
+    Ok(Some((request, auth_info)))
+}
+
+/// Parse an unauthenticated portal request and rate limit it by its link
+/// token. Returns the request with the trimmed token. Replies with the error
+/// itself.
+pub(crate) async fn parse_portal<T: serde::de::DeserializeOwned>(
+    client: &Client,
+    reply: &async_nats::Subject,
+    payload: &[u8],
+    rate_limiter: &MultiRateLimiter,
+    limiter: &str,
+    token_of: impl Fn(&T) -> &str,
+) -> Result<Option<(Request<T>, String)>> {
+    let request: Request<T> = match serde_json::from_slice(payload) {
+        Ok(req) => req,
+        Err(e) => {
+            let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            return Ok(None);
+        }
+    };
+
+    // Bucket by token prefix so the full token isn't kept in memory
+    let token = token_of(&request.payload).trim().to_string();
+    let bucket = token.chars().take(8).collect::<String>();
+    if !rate_limiter.check_and_record(limiter, &bucket) {
+        let error = ErrorResponse::new(request.id, "RATE_LIMITED", "Too many requests.");
+        let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+        return Ok(None);
+    }
+
+    Ok(Some((request, token)))
+}
+
 // ==========================================================================
 // Valhalla JetStream Handlers
 // ==========================================================================
@@ -172,20 +330,38 @@ async fn handle_valhalla_geometry_submit(
 pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Result<()> {
     info!("Starting message handlers...");
 
+    // Panics anywhere in the worker are announced to admins over NATS
+    crash_report::set_alert_client(client.clone());
+
     // Create shared geocoder
     let geocoder: Arc<dyn Geocoder> = Arc::from(create_geocoder());
     info!("Geocoder initialized: {}", geocoder.name());
 
+    // Dependency checks behind sazinka.health and the gateway probes
+    health_service::install(
+        client.clone(),
+        pool.clone(),
+        HealthTargets {
+            valhalla_url: config.valhalla_url.clone(),
+            nominatim_url: (geocoder.name() == "nominatim").then(|| config.nominatim_url.clone()),
+        },
+    );
+
     // Create routing service with automatic Valhalla detection
     let routing_service: Arc<dyn RoutingService> =
         Arc::from(create_routing_service_with_fallback(config.valhalla_url.clone()).await);
     info!("Routing service initialized: {}", routing_service.name());
 
+    // Jobs still marked running belong to the previous process; collect them
+    // before any processor starts picking up new work, then start recording
+    let interrupted_jobs = crate::services::job_history::JOB_HISTORY.start(pool.clone()).await;
+
     // JWT secret for authentication
     let jwt_secret = Arc::new(config.jwt_secret.clone());
 
-    // Rate limiter for login attempts (5 attempts per 5 minutes)
-    let rate_limiter = Arc::new(auth::RateLimiter::new(5, 300));
+    // Rate limiter for login attempts (5 attempts per 5 minutes);
+    // 10 failed passwords within an hour lock the account for 30 minutes
+    let rate_limiter = Arc::new(auth::RateLimiter::new(5, 300).with_lockout(10, 3600, 1800));
 
     // Multi-rate-limiter for onboarding endpoints
     let onboarding_rate_limiter = Arc::new(MultiRateLimiter::new(vec![
@@ -217,6 +393,20 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
                 window_secs: 300,
             },
         ),
+        (
+            "password.reset",
+            RateLimiterConfig {
+                max_attempts: 10,
+                window_secs: 300,
+            },
+        ),
+        (
+            "org.invite.accept",
+            RateLimiterConfig {
+                max_attempts: 10,
+                window_secs: 300,
+            },
+        ),
     ]));
 
     // Email sender: use Resend in production, LogEmailSender otherwise
@@ -255,188 +445,237 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         });
     }
 
+    // SMS processor (JetStream-based async SMS delivery)
+    let sms_processor = Arc::new(
+        crate::services::sms_processor::SmsProcessor::new(
+            client.clone(),
+            pool.clone(),
+            crate::services::sms_processor::SmsConfig::from_env(),
+        )
+        .await?,
+    );
+    {
+        let processor = Arc::clone(&sms_processor);
+        tokio::spawn(async move {
+            if let Err(e) = processor.start_processing().await {
+                error!("SMS processor error: {}", e);
+            }
+        });
+    }
+
+    // Webhook processor (JetStream-based delivery of entity change callbacks)
+    let webhook_processor = Arc::new(
+        crate::services::webhook_delivery::WebhookProcessor::new(client.clone(), pool.clone()).await?,
+    );
+    tokio::spawn(async move {
+        if let Err(e) = webhook_processor.start_processing().await {
+            error!("Webhook processor error: {}", e);
+        }
+    });
+
     let app_base_url = Arc::new(config.app_base_url.clone());
 
     // Onboarding subscriptions
-    let register_start_sub = client.subscribe("sazinka.auth.register.start").await?;
-    let verify_email_sub = client.subscribe("sazinka.auth.email.verify").await?;
-    let resend_verify_sub = client.subscribe("sazinka.auth.email.resend").await?;
-    let waitlist_join_sub = client.subscribe("sazinka.waitlist.join").await?;
-    let onb_profile_sub = client.subscribe("sazinka.onboarding.profile").await?;
-    let onb_devices_sub = client.subscribe("sazinka.onboarding.devices").await?;
-    let onb_complete_sub = client.subscribe("sazinka.onboarding.complete").await?;
-    let dev_verify_sub = client.subscribe("sazinka.auth.dev.verify").await?;
+    let register_start_sub = client.subscribe(subjects::auth::REGISTER_START).await?;
+    let verify_email_sub = client.subscribe(subjects::auth::EMAIL_VERIFY).await?;
+    let verify_email_alias_sub = client.subscribe(subjects::auth::VERIFY_EMAIL).await?;
+    let resend_verify_sub = client.subscribe(subjects::auth::EMAIL_RESEND).await?;
+    let waitlist_join_sub = client.subscribe(subjects::waitlist::JOIN).await?;
+    let onb_profile_sub = client.subscribe(subjects::onboarding::PROFILE).await?;
+    let onb_devices_sub = client.subscribe(subjects::onboarding::DEVICES).await?;
+    let onb_complete_sub = client.subscribe(subjects::onboarding::COMPLETE).await?;
+    let dev_verify_sub = client.subscribe(subjects::auth::DEV_VERIFY).await?;
+    let password_reset_sub = client.subscribe(subjects::auth::PASSWORD_RESET).await?;
 
     // Auth subscriptions
-    let auth_register_sub = client.subscribe("sazinka.auth.register").await?;
-    let auth_login_sub = client.subscribe("sazinka.auth.login").await?;
-    let auth_verify_sub = client.subscribe("sazinka.auth.verify").await?;
-    let auth_refresh_sub = client.subscribe("sazinka.auth.refresh").await?;
-    let auth_worker_create_sub = client.subscribe("sazinka.auth.worker.create").await?;
-    let auth_worker_list_sub = client.subscribe("sazinka.auth.worker.list").await?;
-    let auth_worker_delete_sub = client.subscribe("sazinka.auth.worker.delete").await?;
+    let auth_register_sub = client.subscribe(subjects::auth::REGISTER).await?;
+    let auth_login_sub = client.subscribe(subjects::auth::LOGIN).await?;
+    let auth_verify_sub = client.subscribe(subjects::auth::VERIFY).await?;
+    let auth_refresh_sub = client.subscribe(subjects::auth::REFRESH).await?;
+    let auth_logins_sub = client.subscribe(subjects::auth::LOGINS_LIST).await?;
+    let auth_worker_create_sub = client.subscribe(subjects::auth::WORKER_CREATE).await?;
+    let auth_worker_list_sub = client.subscribe(subjects::auth::WORKER_LIST).await?;
+    let auth_worker_delete_sub = client.subscribe(subjects::auth::WORKER_DELETE).await?;
 
     // Subscribe to all subjects
-    let ping_sub = client.subscribe("sazinka.ping").await?;
-    let customer_create_sub = client.subscribe("sazinka.customer.create").await?;
-    let customer_list_sub = client.subscribe("sazinka.customer.list").await?;
-    let customer_get_sub = client.subscribe("sazinka.customer.get").await?;
-    let customer_update_sub = client.subscribe("sazinka.customer.update").await?;
-    let customer_delete_sub = client.subscribe("sazinka.customer.delete").await?;
-    let customer_random_sub = client.subscribe("sazinka.customer.random").await?;
-    let customer_list_extended_sub = client.subscribe("sazinka.customer.list.extended").await?;
-    let customer_summary_sub = client.subscribe("sazinka.customer.summary").await?;
-    let customer_abandon_sub = client.subscribe("sazinka.customer.abandon").await?;
-    let customer_unabandon_sub = client.subscribe("sazinka.customer.unabandon").await?;
-    let customer_anonymize_sub = client.subscribe("sazinka.customer.anonymize").await?;
-    let customer_column_distinct_sub = client.subscribe("sazinka.customer.column.distinct").await?;
+    let ping_sub = client.subscribe(subjects::PING).await?;
+    let health_sub = client.subscribe(subjects::HEALTH).await?;
+    let customer_create_sub = client.subscribe(subjects::customer::CREATE).await?;
+    let customer_list_sub = client.subscribe(subjects::customer::LIST).await?;
+    let customer_get_sub = client.subscribe(subjects::customer::GET).await?;
+    let customer_update_sub = client.subscribe(subjects::customer::UPDATE).await?;
+    let customer_bulk_update_sub = client.subscribe(subjects::customer::BULK_UPDATE).await?;
+    let customer_delete_sub = client.subscribe(subjects::customer::DELETE).await?;
+    let customer_random_sub = client.subscribe(subjects::customer::RANDOM).await?;
+    let customer_list_extended_sub = client.subscribe(subjects::customer::LIST_EXTENDED).await?;
+    let customer_summary_sub = client.subscribe(subjects::customer::SUMMARY).await?;
+    let customer_abandon_sub = client.subscribe(subjects::customer::ABANDON).await?;
+    let customer_unabandon_sub = client.subscribe(subjects::customer::UNABANDON).await?;
+    let customer_restore_sub = client.subscribe(subjects::customer::RESTORE).await?;
+    let customer_anonymize_sub = client.subscribe(subjects::customer::ANONYMIZE).await?;
+    let customer_column_distinct_sub = client.subscribe(subjects::customer::COLUMN_DISTINCT).await?;
 
     // Planned action subscriptions
-    let pa_create_sub = client.subscribe("sazinka.planned_action.create").await?;
-    let pa_list_sub = client.subscribe("sazinka.planned_action.list").await?;
-    let pa_get_sub = client.subscribe("sazinka.planned_action.get").await?;
-    let pa_update_sub = client.subscribe("sazinka.planned_action.update").await?;
-    let pa_cancel_sub = client.subscribe("sazinka.planned_action.cancel").await?;
-    let pa_complete_sub = client.subscribe("sazinka.planned_action.complete").await?;
+    let pa_create_sub = client.subscribe(subjects::planned_action::CREATE).await?;
+    let pa_list_sub = client.subscribe(subjects::planned_action::LIST).await?;
+    let pa_get_sub = client.subscribe(subjects::planned_action::GET).await?;
+    let pa_update_sub = client.subscribe(subjects::planned_action::UPDATE).await?;
+    let pa_cancel_sub = client.subscribe(subjects::planned_action::CANCEL).await?;
+    let pa_complete_sub = client.subscribe(subjects::planned_action::COMPLETE).await?;
 
     // Inbox subscription
-    let inbox_query_sub = client.subscribe("sazinka.inbox.query").await?;
+    let inbox_query_sub = client.subscribe(subjects::inbox::QUERY).await?;
 
     // Scoring subscriptions
-    let scoring_create_sub = client.subscribe("sazinka.scoring.rule_set.create").await?;
-    let scoring_list_sub = client.subscribe("sazinka.scoring.rule_set.list").await?;
-    let scoring_update_sub = client.subscribe("sazinka.scoring.rule_set.update").await?;
-    let scoring_archive_sub = client.subscribe("sazinka.scoring.rule_set.archive").await?;
+    let scoring_create_sub = client.subscribe(subjects::scoring::RULE_SET_CREATE).await?;
+    let scoring_list_sub = client.subscribe(subjects::scoring::RULE_SET_LIST).await?;
+    let scoring_update_sub = client.subscribe(subjects::scoring::RULE_SET_UPDATE).await?;
+    let scoring_archive_sub = client.subscribe(subjects::scoring::RULE_SET_ARCHIVE).await?;
     let scoring_set_default_sub = client
-        .subscribe("sazinka.scoring.rule_set.set_default")
+        .subscribe(subjects::scoring::RULE_SET_SET_DEFAULT)
         .await?;
-    let scoring_delete_sub = client.subscribe("sazinka.scoring.rule_set.delete").await?;
+    let scoring_delete_sub = client.subscribe(subjects::scoring::RULE_SET_DELETE).await?;
     let scoring_restore_defaults_sub = client
-        .subscribe("sazinka.scoring.rule_set.restore_defaults")
+        .subscribe(subjects::scoring::RULE_SET_RESTORE_DEFAULTS)
         .await?;
-    let inbox_state_get_sub = client.subscribe("sazinka.inbox_state.get").await?;
-    let inbox_state_save_sub = client.subscribe("sazinka.inbox_state.save").await?;
-
-    let route_plan_sub = client.subscribe("sazinka.route.plan").await?;
-    let route_save_sub = client.subscribe("sazinka.route.save").await?;
-    let route_delete_sub = client.subscribe("sazinka.route.delete").await?;
-    let route_update_sub = client.subscribe("sazinka.route.update").await?;
-    let route_get_sub = client.subscribe("sazinka.route.get").await?;
-    let route_list_for_date_sub = client.subscribe("sazinka.route.list_for_date").await?;
-    let route_list_sub = client.subscribe("sazinka.route.list").await?;
+    let inbox_state_get_sub = client.subscribe(subjects::inbox_state::GET).await?;
+    let inbox_state_save_sub = client.subscribe(subjects::inbox_state::SAVE).await?;
+
+    let route_plan_sub = client.subscribe(subjects::route::PLAN).await?;
+    let route_save_sub = client.subscribe(subjects::route::SAVE).await?;
+    let route_delete_sub = client.subscribe(subjects::route::DELETE).await?;
+    let route_update_sub = client.subscribe(subjects::route::UPDATE).await?;
+    let route_stop_note_sub = client.subscribe(subjects::route::STOP_NOTE_UPDATE).await?;
+    let route_lock_sub = client.subscribe(subjects::route::LOCK).await?;
+    let route_unlock_sub = client.subscribe(subjects::route::UNLOCK).await?;
+    let route_analysis_sub = client.subscribe(subjects::route::ANALYSIS).await?;
+    let route_break_suggest_sub = client.subscribe(subjects::route::BREAK_SUGGEST).await?;
+    let route_get_sub = client.subscribe(subjects::route::GET).await?;
+    let route_list_for_date_sub = client.subscribe(subjects::route::LIST_FOR_DATE).await?;
+    let route_list_sub = client.subscribe(subjects::route::LIST).await?;
     let route_insertion_sub = client
-        .subscribe("sazinka.route.insertion.calculate")
+        .subscribe(subjects::route::INSERTION_CALCULATE)
         .await?;
-    let route_insertion_batch_sub = client.subscribe("sazinka.route.insertion.batch").await?;
-    let route_recalculate_sub = client.subscribe("sazinka.route.recalculate").await?;
+    let route_insertion_batch_sub = client.subscribe(subjects::route::INSERTION_BATCH).await?;
+    let route_insertion_multi_day_sub = client.subscribe(subjects::route::INSERTION_MULTI_DAY).await?;
+    let route_recalculate_sub = client.subscribe(subjects::route::RECALCULATE).await?;
+    let route_reoptimize_live_sub = client.subscribe(subjects::route::REOPTIMIZE_LIVE).await?;
 
     // Device subjects
-    let device_create_sub = client.subscribe("sazinka.device.create").await?;
-    let device_list_sub = client.subscribe("sazinka.device.list").await?;
-    let device_get_sub = client.subscribe("sazinka.device.get").await?;
-    let device_update_sub = client.subscribe("sazinka.device.update").await?;
-    let device_delete_sub = client.subscribe("sazinka.device.delete").await?;
+    let device_create_sub = client.subscribe(subjects::device::CREATE).await?;
+    let device_list_sub = client.subscribe(subjects::device::LIST).await?;
+    let device_get_sub = client.subscribe(subjects::device::GET).await?;
+    let device_update_sub = client.subscribe(subjects::device::UPDATE).await?;
+    let device_delete_sub = client.subscribe(subjects::device::DELETE).await?;
+    let device_restore_sub = client.subscribe(subjects::device::RESTORE).await?;
+    let device_transfer_sub = client.subscribe(subjects::device::TRANSFER).await?;
+    let device_transfers_sub = client.subscribe(subjects::device::TRANSFERS).await?;
 
     // Device type config subjects
-    let dtc_list_sub = client.subscribe("sazinka.device_type_config.list").await?;
-    let dtc_get_sub = client.subscribe("sazinka.device_type_config.get").await?;
+    let dtc_list_sub = client.subscribe(subjects::device_type_config::LIST).await?;
+    let dtc_get_sub = client.subscribe(subjects::device_type_config::GET).await?;
     let dtc_create_sub = client
-        .subscribe("sazinka.device_type_config.create")
+        .subscribe(subjects::device_type_config::CREATE)
         .await?;
     let dtc_update_sub = client
-        .subscribe("sazinka.device_type_config.update")
+        .subscribe(subjects::device_type_config::UPDATE)
         .await?;
-    let dtf_create_sub = client.subscribe("sazinka.device_type_field.create").await?;
-    let dtf_update_sub = client.subscribe("sazinka.device_type_field.update").await?;
+    let dtf_create_sub = client.subscribe(subjects::device_type_field::CREATE).await?;
+    let dtf_update_sub = client.subscribe(subjects::device_type_field::UPDATE).await?;
     let dtf_set_active_sub = client
-        .subscribe("sazinka.device_type_field.set_active")
+        .subscribe(subjects::device_type_field::SET_ACTIVE)
         .await?;
     let dtf_reorder_sub = client
-        .subscribe("sazinka.device_type_field.reorder")
+        .subscribe(subjects::device_type_field::REORDER)
         .await?;
 
     // Revision subjects
-    let revision_create_sub = client.subscribe("sazinka.revision.create").await?;
-    let revision_list_sub = client.subscribe("sazinka.revision.list").await?;
-    let revision_get_sub = client.subscribe("sazinka.revision.get").await?;
-    let revision_update_sub = client.subscribe("sazinka.revision.update").await?;
-    let revision_delete_sub = client.subscribe("sazinka.revision.delete").await?;
-    let revision_complete_sub = client.subscribe("sazinka.revision.complete").await?;
-    let revision_upcoming_sub = client.subscribe("sazinka.revision.upcoming").await?;
-    let revision_stats_sub = client.subscribe("sazinka.revision.stats").await?;
-    let revision_suggest_sub = client.subscribe("sazinka.revision.suggest").await?;
-    let revision_queue_sub = client.subscribe("sazinka.revision.queue").await?;
-    let revision_snooze_sub = client.subscribe("sazinka.revision.snooze").await?;
-    let revision_schedule_sub = client.subscribe("sazinka.revision.schedule").await?;
-    let revision_unschedule_sub = client.subscribe("sazinka.revision.unschedule").await?;
+    let revision_create_sub = client.subscribe(subjects::revision::CREATE).await?;
+    let revision_list_sub = client.subscribe(subjects::revision::LIST).await?;
+    let revision_get_sub = client.subscribe(subjects::revision::GET).await?;
+    let revision_update_sub = client.subscribe(subjects::revision::UPDATE).await?;
+    let revision_delete_sub = client.subscribe(subjects::revision::DELETE).await?;
+    let revision_complete_sub = client.subscribe(subjects::revision::COMPLETE).await?;
+    let revision_upcoming_sub = client.subscribe(subjects::revision::UPCOMING).await?;
+    let revision_stats_sub = client.subscribe(subjects::revision::STATS).await?;
+    let revision_suggest_sub = client.subscribe(subjects::revision::SUGGEST).await?;
+    let revision_queue_sub = client.subscribe(subjects::revision::QUEUE).await?;
+    let revision_snooze_sub = client.subscribe(subjects::revision::SNOOZE).await?;
+    let revision_schedule_sub = client.subscribe(subjects::revision::SCHEDULE).await?;
+    let revision_unschedule_sub = client.subscribe(subjects::revision::UNSCHEDULE).await?;
+    let revision_numbers_list_sub = client.subscribe(subjects::revision::NUMBERS_LIST).await?;
 
     // Slots subjects
-    let slots_suggest_sub = client.subscribe("sazinka.slots.suggest").await?;
-    let slots_suggest_v2_sub = client.subscribe("sazinka.slots.suggest.v2").await?;
-    let slots_validate_sub = client.subscribe("sazinka.slots.validate").await?;
+    let slots_suggest_sub = client.subscribe(subjects::slots::SUGGEST).await?;
+    let slots_suggest_v2_sub = client.subscribe(subjects::slots::SUGGEST_V2).await?;
+    let slots_validate_sub = client.subscribe(subjects::slots::VALIDATE).await?;
+    let slots_calendar_sub = client.subscribe(subjects::slots::CALENDAR).await?;
 
     // Settings subjects
-    let settings_get_sub = client.subscribe("sazinka.settings.get").await?;
-    let settings_work_update_sub = client.subscribe("sazinka.settings.work.update").await?;
-    let settings_business_update_sub = client.subscribe("sazinka.settings.business.update").await?;
-    let settings_email_update_sub = client.subscribe("sazinka.settings.email.update").await?;
+    let settings_get_sub = client.subscribe(subjects::settings::GET).await?;
+    let settings_work_update_sub = client.subscribe(subjects::settings::WORK_UPDATE).await?;
+    let settings_business_update_sub = client.subscribe(subjects::settings::BUSINESS_UPDATE).await?;
+    let settings_email_update_sub = client.subscribe(subjects::settings::EMAIL_UPDATE).await?;
     let settings_preferences_update_sub = client
-        .subscribe("sazinka.settings.preferences.update")
+        .subscribe(subjects::settings::PREFERENCES_UPDATE)
         .await?;
-    let settings_break_update_sub = client.subscribe("sazinka.settings.break.update").await?;
-    let account_delete_sub = client.subscribe("sazinka.account.delete").await?;
+    let settings_break_update_sub = client.subscribe(subjects::settings::BREAK_UPDATE).await?;
+    let settings_numbering_update_sub = client.subscribe(subjects::settings::NUMBERING_UPDATE).await?;
+    let settings_customer_codes_update_sub = client.subscribe(subjects::settings::CUSTOMER_CODES_UPDATE).await?;
+    let import_customer_ref_preview_sub = client.subscribe(subjects::import::CUSTOMER_REF_PREVIEW).await?;
+    let account_delete_sub = client.subscribe(subjects::account::DELETE).await?;
 
     // Depot subjects
-    let depot_list_sub = client.subscribe("sazinka.depot.list").await?;
-    let depot_create_sub = client.subscribe("sazinka.depot.create").await?;
-    let depot_update_sub = client.subscribe("sazinka.depot.update").await?;
-    let depot_delete_sub = client.subscribe("sazinka.depot.delete").await?;
-    let depot_geocode_sub = client.subscribe("sazinka.depot.geocode").await?;
+    let depot_list_sub = client.subscribe(subjects::depot::LIST).await?;
+    let depot_create_sub = client.subscribe(subjects::depot::CREATE).await?;
+    let depot_update_sub = client.subscribe(subjects::depot::UPDATE).await?;
+    let depot_delete_sub = client.subscribe(subjects::depot::DELETE).await?;
+    let depot_geocode_sub = client.subscribe(subjects::depot::GEOCODE).await?;
 
     // Communication subjects
-    let comm_create_sub = client.subscribe("sazinka.communication.create").await?;
-    let comm_list_sub = client.subscribe("sazinka.communication.list").await?;
-    let comm_update_sub = client.subscribe("sazinka.communication.update").await?;
-    let comm_delete_sub = client.subscribe("sazinka.communication.delete").await?;
+    let comm_create_sub = client.subscribe(subjects::communication::CREATE).await?;
+    let comm_list_sub = client.subscribe(subjects::communication::LIST).await?;
+    let comm_update_sub = client.subscribe(subjects::communication::UPDATE).await?;
+    let comm_delete_sub = client.subscribe(subjects::communication::DELETE).await?;
 
     // Visit subjects
-    let visit_create_sub = client.subscribe("sazinka.visit.create").await?;
-    let visit_list_sub = client.subscribe("sazinka.visit.list").await?;
-    let visit_update_sub = client.subscribe("sazinka.visit.update").await?;
-    let visit_complete_sub = client.subscribe("sazinka.visit.complete").await?;
-    let visit_delete_sub = client.subscribe("sazinka.visit.delete").await?;
-    let visit_get_sub = client.subscribe("sazinka.visit.get").await?;
-    let visit_update_field_notes_sub = client.subscribe("sazinka.visit.update_field_notes").await?;
-    let visit_notes_history_sub = client.subscribe("sazinka.visit.notes.history").await?;
+    let visit_create_sub = client.subscribe(subjects::visit::CREATE).await?;
+    let visit_list_sub = client.subscribe(subjects::visit::LIST).await?;
+    let visit_update_sub = client.subscribe(subjects::visit::UPDATE).await?;
+    let visit_complete_sub = client.subscribe(subjects::visit::COMPLETE).await?;
+    let visit_delete_sub = client.subscribe(subjects::visit::DELETE).await?;
+    let visit_get_sub = client.subscribe(subjects::visit::GET).await?;
+    let visit_update_field_notes_sub = client.subscribe(subjects::visit::UPDATE_FIELD_NOTES).await?;
+    let visit_notes_history_sub = client.subscribe(subjects::visit::NOTES_HISTORY).await?;
 
     // Unified note subjects
-    let note_create_sub  = client.subscribe("sazinka.note.create").await?;
-    let note_update_sub  = client.subscribe("sazinka.note.update").await?;
-    let note_list_sub    = client.subscribe("sazinka.note.list").await?;
-    let note_audit_sub   = client.subscribe("sazinka.note.audit").await?;
-    let note_delete_sub  = client.subscribe("sazinka.note.delete").await?;
+    let note_create_sub  = client.subscribe(subjects::note::CREATE).await?;
+    let note_update_sub  = client.subscribe(subjects::note::UPDATE).await?;
+    let note_list_sub    = client.subscribe(subjects::note::LIST).await?;
+    let note_audit_sub   = client.subscribe(subjects::note::AUDIT).await?;
+    let note_delete_sub  = client.subscribe(subjects::note::DELETE).await?;
 
     // Crew subjects
-    let crew_create_sub = client.subscribe("sazinka.crew.create").await?;
-    let crew_list_sub = client.subscribe("sazinka.crew.list").await?;
-    let crew_update_sub = client.subscribe("sazinka.crew.update").await?;
-    let crew_delete_sub = client.subscribe("sazinka.crew.delete").await?;
+    let crew_create_sub = client.subscribe(subjects::crew::CREATE).await?;
+    let crew_list_sub = client.subscribe(subjects::crew::LIST).await?;
+    let crew_update_sub = client.subscribe(subjects::crew::UPDATE).await?;
+    let crew_delete_sub = client.subscribe(subjects::crew::DELETE).await?;
 
     // Work item subjects
-    let work_item_create_sub = client.subscribe("sazinka.work_item.create").await?;
-    let work_item_list_sub = client.subscribe("sazinka.work_item.list").await?;
-    let work_item_get_sub = client.subscribe("sazinka.work_item.get").await?;
-    let work_item_complete_sub = client.subscribe("sazinka.work_item.complete").await?;
+    let work_item_create_sub = client.subscribe(subjects::work_item::CREATE).await?;
+    let work_item_list_sub = client.subscribe(subjects::work_item::LIST).await?;
+    let work_item_get_sub = client.subscribe(subjects::work_item::GET).await?;
+    let work_item_complete_sub = client.subscribe(subjects::work_item::COMPLETE).await?;
 
     // Task subjects
-    let task_type_create_sub = client.subscribe("sazinka.task_type.create").await?;
-    let task_type_list_sub = client.subscribe("sazinka.task_type.list").await?;
-    let task_type_update_sub = client.subscribe("sazinka.task_type.update").await?;
-    let task_create_sub = client.subscribe("sazinka.task.create").await?;
-    let task_list_sub = client.subscribe("sazinka.task.list").await?;
-    let task_get_sub = client.subscribe("sazinka.task.get").await?;
-    let task_update_sub = client.subscribe("sazinka.task.update").await?;
-    let task_complete_sub = client.subscribe("sazinka.task.complete").await?;
+    let task_type_create_sub = client.subscribe(subjects::task_type::CREATE).await?;
+    let task_type_list_sub = client.subscribe(subjects::task_type::LIST).await?;
+    let task_type_update_sub = client.subscribe(subjects::task_type::UPDATE).await?;
+    let task_create_sub = client.subscribe(subjects::task::CREATE).await?;
+    let task_list_sub = client.subscribe(subjects::task::LIST).await?;
+    let task_get_sub = client.subscribe(subjects::task::GET).await?;
+    let task_update_sub = client.subscribe(subjects::task::UPDATE).await?;
+    let task_complete_sub = client.subscribe(subjects::task::COMPLETE).await?;
 
     // Old sync import subjects removed - now using async processors
 
@@ -448,12 +687,14 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let client_customer_list = client.clone();
     let client_customer_get = client.clone();
     let client_customer_update = client.clone();
+    let client_customer_bulk_update = client.clone();
     let client_customer_delete = client.clone();
     let client_customer_random = client.clone();
     let client_customer_list_extended = client.clone();
     let client_customer_summary = client.clone();
     let client_customer_abandon = client.clone();
     let client_customer_unabandon = client.clone();
+    let client_customer_restore = client.clone();
     let client_customer_anonymize = client.clone();
     let client_customer_column_distinct = client.clone();
     let client_pa_create = client.clone();
@@ -476,10 +717,17 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let client_route_save = client.clone();
     let client_route_delete = client.clone();
     let client_route_update = client.clone();
+    let client_route_stop_note = client.clone();
+    let client_route_lock = client.clone();
+    let client_route_unlock = client.clone();
+    let client_route_analysis = client.clone();
+    let client_route_break_suggest = client.clone();
     let client_route_get = client.clone();
     let client_route_insertion = client.clone();
     let client_route_insertion_batch = client.clone();
+    let client_route_insertion_multi_day = client.clone();
     let client_route_recalculate = client.clone();
+    let client_route_reoptimize_live = client.clone();
 
     // Device handler clones
     let client_device_create = client.clone();
@@ -487,6 +735,12 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let client_device_get = client.clone();
     let client_device_update = client.clone();
     let client_device_delete = client.clone();
+    let client_device_restore = client.clone();
+    let client_device_transfer = client.clone();
+    let client_device_transfers = client.clone();
+
+    // Repositories shared by handlers that are migrated off the raw pool
+    let repos = Repositories::postgres(pool.clone());
 
     // Revision handler clones
     let client_revision_create = client.clone();
@@ -502,20 +756,24 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let client_revision_snooze = client.clone();
     let client_revision_schedule = client.clone();
     let client_revision_unschedule = client.clone();
+    let client_revision_numbers = client.clone();
     let client_slots_suggest = client.clone();
     let client_slots_suggest_v2 = client.clone();
     let client_slots_validate = client.clone();
+    let client_slots_calendar = client.clone();
 
     let pool_customer_create = pool.clone();
     let pool_customer_list = pool.clone();
-    let pool_customer_get = pool.clone();
+    let repos_customer_get = repos.clone();
     let pool_customer_update = pool.clone();
+    let pool_customer_bulk_update = pool.clone();
     let pool_customer_delete = pool.clone();
     let pool_customer_random = pool.clone();
     let pool_customer_list_extended = pool.clone();
     let pool_customer_summary = pool.clone();
     let pool_customer_abandon = pool.clone();
     let pool_customer_unabandon = pool.clone();
+    let pool_customer_restore = pool.clone();
     let pool_customer_anonymize = pool.clone();
     let pool_customer_column_distinct = pool.clone();
     let pool_pa_create = pool.clone();
@@ -538,10 +796,17 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let pool_route_save = pool.clone();
     let pool_route_delete = pool.clone();
     let pool_route_update = pool.clone();
+    let pool_route_stop_note = pool.clone();
+    let pool_route_lock = pool.clone();
+    let pool_route_unlock = pool.clone();
+    let pool_route_analysis = pool.clone();
+    let pool_route_break_suggest = pool.clone();
     let pool_route_get = pool.clone();
     let pool_route_insertion = pool.clone();
     let pool_route_insertion_batch = pool.clone();
+    let pool_route_insertion_multi_day = pool.clone();
     let pool_route_recalculate = pool.clone();
+    let pool_route_reoptimize_live = pool.clone();
 
     // Device pool clones
     let pool_device_create = pool.clone();
@@ -549,11 +814,14 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let pool_device_get = pool.clone();
     let pool_device_update = pool.clone();
     let pool_device_delete = pool.clone();
+    let pool_device_restore = pool.clone();
+    let pool_device_transfer = pool.clone();
+    let pool_device_transfers = pool.clone();
 
     // Revision pool clones
     let pool_revision_create = pool.clone();
     let pool_revision_list = pool.clone();
-    let pool_revision_get = pool.clone();
+    let repos_revision_get = repos.clone();
     let pool_revision_update = pool.clone();
     let pool_revision_delete = pool.clone();
     let pool_revision_complete = pool.clone();
@@ -564,9 +832,14 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let pool_revision_snooze = pool.clone();
     let pool_revision_schedule = pool.clone();
     let pool_revision_unschedule = pool.clone();
+    let pool_revision_numbers = pool.clone();
     let pool_slots_suggest = pool.clone();
     let pool_slots_suggest_v2 = pool.clone();
     let pool_slots_validate = pool.clone();
+    let pool_slots_calendar = pool.clone();
+    let repos_slots_suggest_v2 = repos.clone();
+    let repos_slots_validate = repos.clone();
+    let repos_slots_calendar = repos.clone();
 
     // Settings handler clones
     let client_settings_get = client.clone();
@@ -575,6 +848,9 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let client_settings_email = client.clone();
     let client_settings_preferences = client.clone();
     let client_settings_break = client.clone();
+    let client_settings_numbering = client.clone();
+    let client_settings_customer_codes = client.clone();
+    let client_import_customer_ref_preview = client.clone();
     let client_account_delete = client.clone();
 
     // Depot handler clones
@@ -591,6 +867,9 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let pool_settings_email = pool.clone();
     let pool_settings_preferences = pool.clone();
     let pool_settings_break = pool.clone();
+    let pool_settings_numbering = pool.clone();
+    let pool_settings_customer_codes = pool.clone();
+    let pool_import_customer_ref_preview = pool.clone();
     let pool_account_delete = pool.clone();
 
     // Depot pool clones
@@ -677,7 +956,9 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let routing_plan = Arc::clone(&routing_service);
     let routing_insertion = Arc::clone(&routing_service);
     let routing_insertion_batch = Arc::clone(&routing_service);
+    let routing_insertion_multi_day = Arc::clone(&routing_service);
     let routing_recalculate = Arc::clone(&routing_service);
+    let routing_reoptimize_live = Arc::clone(&routing_service);
     let routing_slots_suggest_v2 = Arc::clone(&routing_service);
     let routing_slots_validate = Arc::clone(&routing_service);
 
@@ -686,12 +967,14 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let jwt_secret_customer_list = Arc::clone(&jwt_secret);
     let jwt_secret_customer_get = Arc::clone(&jwt_secret);
     let jwt_secret_customer_update = Arc::clone(&jwt_secret);
+    let jwt_secret_customer_bulk_update = Arc::clone(&jwt_secret);
     let jwt_secret_customer_delete = Arc::clone(&jwt_secret);
     let jwt_secret_customer_random = Arc::clone(&jwt_secret);
     let jwt_secret_customer_list_extended = Arc::clone(&jwt_secret);
     let jwt_secret_customer_summary = Arc::clone(&jwt_secret);
     let jwt_secret_customer_abandon = Arc::clone(&jwt_secret);
     let jwt_secret_customer_unabandon = Arc::clone(&jwt_secret);
+    let jwt_secret_customer_restore = Arc::clone(&jwt_secret);
     let jwt_secret_customer_anonymize = Arc::clone(&jwt_secret);
     let jwt_secret_customer_column_distinct = Arc::clone(&jwt_secret);
     let jwt_secret_pa_create = Arc::clone(&jwt_secret);
@@ -716,10 +999,18 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let jwt_secret_route_save = Arc::clone(&jwt_secret);
     let jwt_secret_route_delete = Arc::clone(&jwt_secret);
     let jwt_secret_route_update = Arc::clone(&jwt_secret);
+    let jwt_secret_route_stop_note = Arc::clone(&jwt_secret);
+    let jwt_secret_route_lock = Arc::clone(&jwt_secret);
+    let jwt_secret_route_unlock = Arc::clone(&jwt_secret);
+    let jwt_secret_route_analysis = Arc::clone(&jwt_secret);
+    let jwt_secret_route_break_suggest = Arc::clone(&jwt_secret);
+    let nominatim_url_route_break_suggest = config.nominatim_url.clone();
     let jwt_secret_route_get = Arc::clone(&jwt_secret);
     let jwt_secret_route_insertion = Arc::clone(&jwt_secret);
     let jwt_secret_route_insertion_batch = Arc::clone(&jwt_secret);
+    let jwt_secret_route_insertion_multi_day = Arc::clone(&jwt_secret);
     let jwt_secret_route_recalculate = Arc::clone(&jwt_secret);
+    let jwt_secret_route_reoptimize_live = Arc::clone(&jwt_secret);
 
     // JWT secret clones for device handlers
     let jwt_secret_device_create = Arc::clone(&jwt_secret);
@@ -727,6 +1018,9 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let jwt_secret_device_get = Arc::clone(&jwt_secret);
     let jwt_secret_device_update = Arc::clone(&jwt_secret);
     let jwt_secret_device_delete = Arc::clone(&jwt_secret);
+    let jwt_secret_device_restore = Arc::clone(&jwt_secret);
+    let jwt_secret_device_transfer = Arc::clone(&jwt_secret);
+    let jwt_secret_device_transfers = Arc::clone(&jwt_secret);
 
     // Pool + JWT clones for device_type_config handlers
     let pool_dtc_list = pool.clone();
@@ -768,11 +1062,13 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let jwt_secret_revision_snooze = Arc::clone(&jwt_secret);
     let jwt_secret_revision_schedule = Arc::clone(&jwt_secret);
     let jwt_secret_revision_unschedule = Arc::clone(&jwt_secret);
+    let jwt_secret_revision_numbers = Arc::clone(&jwt_secret);
 
     // JWT secret clones for slots handler
     let jwt_secret_slots_suggest = Arc::clone(&jwt_secret);
     let jwt_secret_slots_suggest_v2 = Arc::clone(&jwt_secret);
     let jwt_secret_slots_validate = Arc::clone(&jwt_secret);
+    let jwt_secret_slots_calendar = Arc::clone(&jwt_secret);
 
     // JWT secret clones for settings handlers
     let jwt_secret_settings_get = Arc::clone(&jwt_secret);
@@ -781,6 +1077,9 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let jwt_secret_settings_email = Arc::clone(&jwt_secret);
     let jwt_secret_settings_preferences = Arc::clone(&jwt_secret);
     let jwt_secret_settings_break = Arc::clone(&jwt_secret);
+    let jwt_secret_settings_numbering = Arc::clone(&jwt_secret);
+    let jwt_secret_settings_customer_codes = Arc::clone(&jwt_secret);
+    let jwt_secret_import_customer_ref_preview = Arc::clone(&jwt_secret);
     let jwt_secret_account_delete = Arc::clone(&jwt_secret);
 
     // JWT secret clones for depot handlers
@@ -846,6 +1145,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let pool_auth_login = pool.clone();
     let jwt_secret_login = Arc::clone(&jwt_secret);
     let rate_limiter_login = Arc::clone(&rate_limiter);
+    let sender_login = Arc::clone(&email_sender);
     tokio::spawn(async move {
         if let Err(e) = auth::handle_login(
             client_auth_login,
@@ -853,6 +1153,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
             pool_auth_login,
             jwt_secret_login,
             rate_limiter_login,
+            sender_login,
         )
         .await
         {
@@ -892,6 +1193,22 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         }
     });
 
+    let client_auth_logins = client.clone();
+    let pool_auth_logins = pool.clone();
+    let jwt_secret_logins = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = auth::handle_list_logins(
+            client_auth_logins,
+            auth_logins_sub,
+            pool_auth_logins,
+            jwt_secret_logins,
+        )
+        .await
+        {
+            error!("Auth logins list handler error: {}", e);
+        }
+    });
+
     let client_worker_create = client.clone();
     let pool_worker_create = pool.clone();
     let jwt_secret_worker_create = Arc::clone(&jwt_secret);
@@ -976,6 +1293,18 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
             }
         });
     }
+    {
+        let client_ve = client.clone();
+        let pool_ve = pool.clone();
+        let rl_ve = Arc::clone(&onboarding_rate_limiter);
+        tokio::spawn(async move {
+            if let Err(e) =
+                onboarding::handle_verify_email(client_ve, verify_email_alias_sub, pool_ve, rl_ve).await
+            {
+                error!("onboarding.verify_email (alias) error: {}", e);
+            }
+        });
+    }
     {
         let client_rv = client.clone();
         let pool_rv = pool.clone();
@@ -997,6 +1326,18 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
             }
         });
     }
+    {
+        let client_pr = client.clone();
+        let pool_pr = pool.clone();
+        let rl_pr = Arc::clone(&onboarding_rate_limiter);
+        tokio::spawn(async move {
+            if let Err(e) =
+                onboarding::handle_password_reset(client_pr, password_reset_sub, pool_pr, rl_pr).await
+            {
+                error!("auth.password.reset error: {}", e);
+            }
+        });
+    }
     {
         let client_wl = client.clone();
         let pool_wl = pool.clone();
@@ -1063,10 +1404,507 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         }
     });
 
+    // Start admin user management handlers
+    let client_admin_users = client.clone();
+    let pool_admin_users = pool.clone();
+    let jwt_secret_admin_users = Arc::clone(&jwt_secret);
+    let sender_admin_users = Arc::clone(&email_sender);
+    let url_admin_users = Arc::clone(&app_base_url);
+    tokio::spawn(async move {
+        if let Err(e) = admin_users::start_handlers(
+            client_admin_users,
+            pool_admin_users,
+            jwt_secret_admin_users,
+            sender_admin_users,
+            url_admin_users,
+        )
+        .await
+        {
+            error!("Admin user handlers error: {}", e);
+        }
+    });
+
+    // Start account usage handlers
+    let client_account = client.clone();
+    let pool_account = pool.clone();
+    let jwt_secret_account = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = account::start_handlers(client_account, pool_account, jwt_secret_account).await {
+            error!("Account handlers error: {}", e);
+        }
+    });
+
+    // Start customer hierarchy handlers
+    let client_hierarchy = client.clone();
+    let pool_hierarchy = pool.clone();
+    let jwt_secret_hierarchy = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = customer_hierarchy::start_handlers(client_hierarchy, pool_hierarchy, jwt_secret_hierarchy).await {
+            error!("Customer hierarchy handlers error: {}", e);
+        }
+    });
+
+    // Start customer service site handlers
+    let client_site = client.clone();
+    let pool_site = pool.clone();
+    let jwt_secret_site = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = customer_site::start_handlers(client_site, pool_site, jwt_secret_site).await {
+            error!("Customer site handlers error: {}", e);
+        }
+    });
+
+    // Start customer reschedule request handlers
+    let client_reschedule = client.clone();
+    let pool_reschedule = pool.clone();
+    let repos_reschedule = repos.clone();
+    let jwt_secret_reschedule = Arc::clone(&jwt_secret);
+    let sender_reschedule = Arc::clone(&email_sender);
+    let url_reschedule = Arc::clone(&app_base_url);
+    let routing_reschedule = Arc::clone(&routing_service);
+    tokio::spawn(async move {
+        if let Err(e) = reschedule::start_handlers(
+            client_reschedule,
+            pool_reschedule,
+            repos_reschedule,
+            jwt_secret_reschedule,
+            sender_reschedule,
+            url_reschedule,
+            routing_reschedule,
+        )
+        .await
+        {
+            error!("Reschedule handlers error: {}", e);
+        }
+    });
+
+    // Start calendar feed handlers
+    let client_calendar_feed = client.clone();
+    let pool_calendar_feed = pool.clone();
+    let repos_calendar_feed = repos.clone();
+    let jwt_secret_calendar_feed = Arc::clone(&jwt_secret);
+    let url_calendar_feed = Arc::new(config.calendar_feed_url.clone());
+    tokio::spawn(async move {
+        if let Err(e) = calendar_feed::start_handlers(
+            client_calendar_feed,
+            pool_calendar_feed,
+            repos_calendar_feed,
+            jwt_secret_calendar_feed,
+            url_calendar_feed,
+        )
+        .await
+        {
+            error!("Calendar feed handlers error: {}", e);
+        }
+    });
+
+    // Start web lead intake handlers
+    let client_lead = client.clone();
+    let pool_lead = pool.clone();
+    let jwt_secret_lead = Arc::clone(&jwt_secret);
+    let sender_lead = Arc::clone(&email_sender);
+    tokio::spawn(async move {
+        if let Err(e) = lead::start_handlers(client_lead, pool_lead, jwt_secret_lead, sender_lead).await {
+            error!("Lead handlers error: {}", e);
+        }
+    });
+
+    // Start inventory handlers
+    let client_inventory = client.clone();
+    let pool_inventory = pool.clone();
+    let jwt_secret_inventory = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = inventory::start_handlers(client_inventory, pool_inventory, jwt_secret_inventory).await {
+            error!("Inventory handlers error: {}", e);
+        }
+    });
+
+    // Start quote and catalog handlers
+    let client_quote = client.clone();
+    let pool_quote = pool.clone();
+    let jwt_secret_quote = Arc::clone(&jwt_secret);
+    let url_quote = Arc::clone(&app_base_url);
+    tokio::spawn(async move {
+        if let Err(e) = quote::start_handlers(client_quote, pool_quote, jwt_secret_quote, url_quote).await {
+            error!("Quote handlers error: {}", e);
+        }
+    });
+
+    // Start certificate verification handler
+    let client_certificate = client.clone();
+    let pool_certificate = pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = certificate::start_handlers(client_certificate, pool_certificate).await {
+            error!("Certificate handlers error: {}", e);
+        }
+    });
+
+    // Start device sticker and revision report handlers
+    let client_device_code = client.clone();
+    let pool_device_code = pool.clone();
+    let jwt_secret_device_code = Arc::clone(&jwt_secret);
+    let url_device_code = Arc::clone(&app_base_url);
+    tokio::spawn(async move {
+        if let Err(e) = device_code::start_handlers(client_device_code, pool_device_code, jwt_secret_device_code, url_device_code).await {
+            error!("Device code handlers error: {}", e);
+        }
+    });
+
+    // Start "plan like last year" handler
+    let client_route_history = client.clone();
+    let pool_route_history = pool.clone();
+    let jwt_secret_route_history = Arc::clone(&jwt_secret);
+    let routing_route_history = Arc::clone(&routing_service);
+    tokio::spawn(async move {
+        if let Err(e) = route_history::start_handlers(
+            client_route_history,
+            pool_route_history,
+            jwt_secret_route_history,
+            routing_route_history,
+        )
+        .await
+        {
+            error!("Route history handlers error: {}", e);
+        }
+    });
+
+    // Start multi-day route planning handler
+    let client_route_multiday = client.clone();
+    let pool_route_multiday = pool.clone();
+    let jwt_secret_route_multiday = Arc::clone(&jwt_secret);
+    let routing_route_multiday = Arc::clone(&routing_service);
+    tokio::spawn(async move {
+        if let Err(e) = route_multiday::start_handlers(
+            client_route_multiday,
+            pool_route_multiday,
+            jwt_secret_route_multiday,
+            routing_route_multiday,
+        )
+        .await
+        {
+            error!("Multi-day route planning handlers error: {}", e);
+        }
+    });
+
+    // Start extension hook handlers
+    let client_hooks = client.clone();
+    let jwt_secret_hooks = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = hooks::start_handlers(client_hooks, jwt_secret_hooks).await {
+            error!("Hook handlers error: {}", e);
+        }
+    });
+
+    // Start presence handlers
+    let client_presence = client.clone();
+    let pool_presence = pool.clone();
+    let jwt_secret_presence = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = presence::start_handlers(client_presence, pool_presence, jwt_secret_presence).await {
+            error!("Presence handlers error: {}", e);
+        }
+    });
+
+    // Start retention handlers
+    let client_retention = client.clone();
+    let pool_retention = pool.clone();
+    let jwt_secret_retention = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = retention::start_handlers(client_retention, pool_retention, jwt_secret_retention).await {
+            error!("Retention handlers error: {}", e);
+        }
+    });
+
+    // Start report handlers
+    let client_report = client.clone();
+    let pool_report = pool.clone();
+    let jwt_secret_report = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = report::start_handlers(client_report, pool_report, jwt_secret_report).await {
+            error!("Report handlers error: {}", e);
+        }
+    });
+
+    // Start CRM sync handlers
+    let client_crm_sync = client.clone();
+    let pool_crm_sync = pool.clone();
+    let jwt_secret_crm_sync = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = crm_sync::start_handlers(client_crm_sync, pool_crm_sync, jwt_secret_crm_sync).await {
+            error!("CRM sync handlers error: {}", e);
+        }
+    });
+
+    // Start notification center handlers
+    let client_notification = client.clone();
+    let pool_notification = pool.clone();
+    let jwt_secret_notification = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = notification::start_handlers(client_notification, pool_notification, jwt_secret_notification).await {
+            error!("Notification handlers error: {}", e);
+        }
+    });
+
+    // Start automation rule handlers
+    let client_automation = client.clone();
+    let pool_automation = pool.clone();
+    let jwt_secret_automation = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = automation::start_handlers(client_automation, pool_automation, jwt_secret_automation).await {
+            error!("Automation handlers error: {}", e);
+        }
+    });
+
+    // Start customer risk scoring handlers
+    let client_customer_risk = client.clone();
+    let pool_customer_risk = pool.clone();
+    let jwt_secret_customer_risk = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = customer_risk::start_handlers(client_customer_risk, pool_customer_risk, jwt_secret_customer_risk).await {
+            error!("Customer risk handlers error: {}", e);
+        }
+    });
+
+    // Start subcontractor handlers
+    let client_subcontractor = client.clone();
+    let pool_subcontractor = pool.clone();
+    let jwt_secret_subcontractor = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) =
+            subcontractor::start_handlers(client_subcontractor, pool_subcontractor, jwt_secret_subcontractor).await
+        {
+            error!("Subcontractor handlers error: {}", e);
+        }
+    });
+
+    // Start organization handlers
+    let client_org = client.clone();
+    let org_ctx = organization::OrgContext {
+        pool: pool.clone(),
+        jwt_secret: Arc::clone(&jwt_secret),
+        email_sender: Arc::clone(&email_sender),
+        app_base_url: Arc::clone(&app_base_url),
+        rate_limiter: Arc::clone(&onboarding_rate_limiter),
+    };
+    tokio::spawn(async move {
+        if let Err(e) = organization::start_handlers(client_org, org_ctx).await {
+            error!("Organization handlers error: {}", e);
+        }
+    });
+
+    // Start visit progress and duration handlers
+    let client_visit_duration = client.clone();
+    let pool_visit_duration = pool.clone();
+    let jwt_secret_visit_duration = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) =
+            visit_duration::start_handlers(client_visit_duration, pool_visit_duration, jwt_secret_visit_duration).await
+        {
+            error!("Visit duration handlers error: {}", e);
+        }
+    });
+
+    // Start escalation handlers
+    let client_escalation = client.clone();
+    let pool_escalation = pool.clone();
+    let jwt_secret_escalation = Arc::clone(&jwt_secret);
+    let sender_escalation = Arc::clone(&email_sender);
+    tokio::spawn(async move {
+        if let Err(e) = escalation::start_handlers(
+            client_escalation,
+            pool_escalation,
+            jwt_secret_escalation,
+            sender_escalation,
+        )
+        .await
+        {
+            error!("Escalation handlers error: {}", e);
+        }
+    });
+
+    // Start campaign handlers
+    let client_campaign = client.clone();
+    let pool_campaign = pool.clone();
+    let jwt_secret_campaign = Arc::clone(&jwt_secret);
+    let campaign_queues = crate::services::campaign::CampaignQueues {
+        email: Arc::clone(&email_processor),
+        sms: Arc::clone(&sms_processor),
+    };
+    tokio::spawn(async move {
+        if let Err(e) =
+            campaign::start_handlers(client_campaign, pool_campaign, jwt_secret_campaign, campaign_queues).await
+        {
+            error!("Campaign handlers error: {}", e);
+        }
+    });
+
+    // Start analysis handlers
+    let client_analysis = client.clone();
+    let pool_analysis = pool.clone();
+    let jwt_secret_analysis = Arc::clone(&jwt_secret);
+    let routing_analysis = Arc::clone(&routing_service);
+    tokio::spawn(async move {
+        if let Err(e) =
+            analysis::start_handlers(client_analysis, pool_analysis, jwt_secret_analysis, routing_analysis).await
+        {
+            error!("Analysis handlers error: {}", e);
+        }
+    });
+
+    // Start dispatch board handlers
+    let client_board = client.clone();
+    let pool_board = pool.clone();
+    let jwt_secret_board = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = dispatch_board::start_handlers(client_board, pool_board, jwt_secret_board).await {
+            error!("Dispatch board handlers error: {}", e);
+        }
+    });
+
+    // Start data quality handlers
+    let client_quality = client.clone();
+    let pool_quality = pool.clone();
+    let jwt_secret_quality = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = quality::start_handlers(client_quality, pool_quality, jwt_secret_quality).await {
+            error!("Quality handlers error: {}", e);
+        }
+    });
+
+    // Start map snapshot handlers
+    let client_map_snapshot = client.clone();
+    let pool_map_snapshot = pool.clone();
+    let jwt_secret_map_snapshot = Arc::clone(&jwt_secret);
+    let routing_map_snapshot = Arc::clone(&routing_service);
+    let map_renderer = Arc::new(StaticMapRenderer::new(
+        config.map_tile_url.clone(),
+        config.map_tile_attribution.clone(),
+    ));
+    tokio::spawn(async move {
+        if let Err(e) = map_snapshot::start_handlers(
+            client_map_snapshot,
+            pool_map_snapshot,
+            jwt_secret_map_snapshot,
+            map_renderer,
+            routing_map_snapshot,
+        )
+        .await
+        {
+            error!("Map snapshot handlers error: {}", e);
+        }
+    });
+
+    // Start customer template language handlers
+    let client_template_translation = client.clone();
+    let pool_template_translation = pool.clone();
+    let jwt_secret_template_translation = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = template_translation::start_handlers(
+            client_template_translation,
+            pool_template_translation,
+            jwt_secret_template_translation,
+        )
+        .await
+        {
+            error!("Template translation handlers error: {}", e);
+        }
+    });
+
+    // Start service coverage handlers
+    let client_coverage = client.clone();
+    let pool_coverage = pool.clone();
+    let jwt_secret_coverage = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = coverage::start_handlers(client_coverage, pool_coverage, jwt_secret_coverage).await {
+            error!("Coverage handlers error: {}", e);
+        }
+    });
+
+    // Start telemetry handlers
+    let client_telemetry = client.clone();
+    let pool_telemetry = pool.clone();
+    let jwt_secret_telemetry = Arc::clone(&jwt_secret);
+    let telemetry_endpoint = config.telemetry_endpoint.clone();
+    tokio::spawn(async move {
+        if let Err(e) = telemetry::start_handlers(
+            client_telemetry,
+            pool_telemetry,
+            jwt_secret_telemetry,
+            telemetry_endpoint,
+        )
+        .await
+        {
+            error!("Telemetry handlers error: {}", e);
+        }
+    });
+
+    // Start webhook handlers
+    let client_webhook = client.clone();
+    let pool_webhook = pool.clone();
+    let jwt_secret_webhook = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = webhook::start_handlers(client_webhook, pool_webhook, jwt_secret_webhook).await {
+            error!("Webhook handlers error: {}", e);
+        }
+    });
+
+    // Start chunked import upload handlers
+    let client_import_upload = client.clone();
+    let pool_import_upload = pool.clone();
+    let jwt_secret_import_upload = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = import_upload::start_handlers(client_import_upload, pool_import_upload, jwt_secret_import_upload).await {
+            error!("Import upload handlers error: {}", e);
+        }
+    });
+
+    // Start bulk revision interval adjustment handlers
+    let client_interval_adjustment = client.clone();
+    let pool_interval_adjustment = pool.clone();
+    let jwt_secret_interval_adjustment = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = interval_adjustment::start_handlers(
+            client_interval_adjustment,
+            pool_interval_adjustment,
+            jwt_secret_interval_adjustment,
+        )
+        .await
+        {
+            error!("Interval adjustment handlers error: {}", e);
+        }
+    });
+
+    // Start scheduled off-site backups (on one worker only)
+    let backup_config = config.backup.clone();
+    leader::spawn_singleton(pool.clone(), "backup_scheduler", move |pool| {
+        backup::run_scheduler(pool, backup_config.clone())
+    });
+
+    // Watch the age of the geocoding index
+    tokio::spawn(geocode_freshness::run_monitor(config.nominatim_url.clone(), config.nominatim_max_data_age_days));
+    let geocode_index_sub = client.subscribe(subjects::geocode::INDEX_STATUS).await?;
+    let client_geocode_index = client.clone();
+    let jwt_secret_geocode_index = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = geocode::handle_index_status(client_geocode_index, geocode_index_sub, jwt_secret_geocode_index).await {
+            error!("Geocode index status handler error: {}", e);
+        }
+    });
+
+    // Re-learn travel time corrections from recent routes (on one worker only)
+    leader::spawn_singleton(pool.clone(), "travel_correction", travel_correction::run_scheduler);
+
+    // Purge customers and devices deleted past the restore window (on one worker only)
+    leader::spawn_singleton(pool.clone(), "soft_delete_purger", soft_delete::run_purger);
+
     // Spawn handlers
-    let ping_handle = tokio::spawn(async move { ping::handle_ping(client_ping, ping_sub).await });
+    let ping_handle = crash_report::spawn_named("ping", async move { ping::handle_ping(client_ping, ping_sub).await });
+    let health_handle = crash_report::spawn_named("health", {
+        let c = client.clone();
+        async move { health::handle_health(c, health_sub).await }
+    });
 
-    let customer_create_handle = tokio::spawn(async move {
+    let customer_create_handle = crash_report::spawn_named("customer_create", async move {
         customer::handle_create(
             client_customer_create,
             customer_create_sub,
@@ -1076,7 +1914,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let customer_list_handle = tokio::spawn(async move {
+    let customer_list_handle = crash_report::spawn_named("customer_list", async move {
         customer::handle_list(
             client_customer_list,
             customer_list_sub,
@@ -1086,17 +1924,17 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let customer_get_handle = tokio::spawn(async move {
+    let customer_get_handle = crash_report::spawn_named("customer_get", async move {
         customer::handle_get(
             client_customer_get,
             customer_get_sub,
-            pool_customer_get,
+            repos_customer_get,
             jwt_secret_customer_get,
         )
         .await
     });
 
-    let customer_update_handle = tokio::spawn(async move {
+    let customer_update_handle = crash_report::spawn_named("customer_update", async move {
         customer::handle_update(
             client_customer_update,
             customer_update_sub,
@@ -1106,7 +1944,17 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let customer_delete_handle = tokio::spawn(async move {
+    let customer_bulk_update_handle = crash_report::spawn_named("customer_bulk_update", async move {
+        customer::handle_bulk_update(
+            client_customer_bulk_update,
+            customer_bulk_update_sub,
+            pool_customer_bulk_update,
+            jwt_secret_customer_bulk_update,
+        )
+        .await
+    });
+
+    let customer_delete_handle = crash_report::spawn_named("customer_delete", async move {
         customer::handle_delete(
             client_customer_delete,
             customer_delete_sub,
@@ -1116,7 +1964,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let customer_random_handle = tokio::spawn(async move {
+    let customer_random_handle = crash_report::spawn_named("customer_random", async move {
         customer::handle_random(
             client_customer_random,
             customer_random_sub,
@@ -1126,7 +1974,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let customer_list_extended_handle = tokio::spawn(async move {
+    let customer_list_extended_handle = crash_report::spawn_named("customer_list_extended", async move {
         customer::handle_list_extended(
             client_customer_list_extended,
             customer_list_extended_sub,
@@ -1136,7 +1984,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let customer_summary_handle = tokio::spawn(async move {
+    let customer_summary_handle = crash_report::spawn_named("customer_summary", async move {
         customer::handle_summary(
             client_customer_summary,
             customer_summary_sub,
@@ -1146,7 +1994,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let customer_abandon_handle = tokio::spawn(async move {
+    let customer_abandon_handle = crash_report::spawn_named("customer_abandon", async move {
         customer::handle_abandon(
             client_customer_abandon,
             customer_abandon_sub,
@@ -1156,7 +2004,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let customer_unabandon_handle = tokio::spawn(async move {
+    let customer_unabandon_handle = crash_report::spawn_named("customer_unabandon", async move {
         customer::handle_unabandon(
             client_customer_unabandon,
             customer_unabandon_sub,
@@ -1166,7 +2014,17 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let customer_anonymize_handle = tokio::spawn(async move {
+    let customer_restore_handle = crash_report::spawn_named("customer_restore", async move {
+        customer::handle_restore(
+            client_customer_restore,
+            customer_restore_sub,
+            pool_customer_restore,
+            jwt_secret_customer_restore,
+        )
+        .await
+    });
+
+    let customer_anonymize_handle = crash_report::spawn_named("customer_anonymize", async move {
         customer::handle_anonymize(
             client_customer_anonymize,
             customer_anonymize_sub,
@@ -1176,7 +2034,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let customer_column_distinct_handle = tokio::spawn(async move {
+    let customer_column_distinct_handle = crash_report::spawn_named("customer_column_distinct", async move {
         customer::handle_column_distinct(
             client_customer_column_distinct,
             customer_column_distinct_sub,
@@ -1186,7 +2044,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let pa_create_handle = tokio::spawn(async move {
+    let pa_create_handle = crash_report::spawn_named("pa_create", async move {
         planned_action::handle_create(
             client_pa_create,
             pa_create_sub,
@@ -1196,7 +2054,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let pa_list_handle = tokio::spawn(async move {
+    let pa_list_handle = crash_report::spawn_named("pa_list", async move {
         planned_action::handle_list(
             client_pa_list,
             pa_list_sub,
@@ -1206,11 +2064,11 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let pa_get_handle = tokio::spawn(async move {
+    let pa_get_handle = crash_report::spawn_named("pa_get", async move {
         planned_action::handle_get(client_pa_get, pa_get_sub, pool_pa_get, jwt_secret_pa_get).await
     });
 
-    let pa_update_handle = tokio::spawn(async move {
+    let pa_update_handle = crash_report::spawn_named("pa_update", async move {
         planned_action::handle_update(
             client_pa_update,
             pa_update_sub,
@@ -1220,7 +2078,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let pa_cancel_handle = tokio::spawn(async move {
+    let pa_cancel_handle = crash_report::spawn_named("pa_cancel", async move {
         planned_action::handle_cancel(
             client_pa_cancel,
             pa_cancel_sub,
@@ -1230,7 +2088,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let pa_complete_handle = tokio::spawn(async move {
+    let pa_complete_handle = crash_report::spawn_named("pa_complete", async move {
         planned_action::handle_complete(
             client_pa_complete,
             pa_complete_sub,
@@ -1240,7 +2098,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let inbox_query_handle = tokio::spawn(async move {
+    let inbox_query_handle = crash_report::spawn_named("inbox_query", async move {
         inbox::handle_query(
             client_inbox_query,
             inbox_query_sub,
@@ -1250,7 +2108,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let scoring_create_handle = tokio::spawn(async move {
+    let scoring_create_handle = crash_report::spawn_named("scoring_create", async move {
         scoring::handle_create_rule_set(
             client_scoring_create,
             scoring_create_sub,
@@ -1260,7 +2118,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let scoring_list_handle = tokio::spawn(async move {
+    let scoring_list_handle = crash_report::spawn_named("scoring_list", async move {
         scoring::handle_list_rule_sets(
             client_scoring_list,
             scoring_list_sub,
@@ -1270,7 +2128,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let scoring_update_handle = tokio::spawn(async move {
+    let scoring_update_handle = crash_report::spawn_named("scoring_update", async move {
         scoring::handle_update_rule_set(
             client_scoring_update,
             scoring_update_sub,
@@ -1280,7 +2138,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let scoring_archive_handle = tokio::spawn(async move {
+    let scoring_archive_handle = crash_report::spawn_named("scoring_archive", async move {
         scoring::handle_archive_rule_set(
             client_scoring_archive,
             scoring_archive_sub,
@@ -1290,7 +2148,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let scoring_set_default_handle = tokio::spawn(async move {
+    let scoring_set_default_handle = crash_report::spawn_named("scoring_set_default", async move {
         scoring::handle_set_default_rule_set(
             client_scoring_set_default,
             scoring_set_default_sub,
@@ -1300,7 +2158,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let scoring_delete_handle = tokio::spawn(async move {
+    let scoring_delete_handle = crash_report::spawn_named("scoring_delete", async move {
         scoring::handle_delete_rule_set(
             client_scoring_delete,
             scoring_delete_sub,
@@ -1310,7 +2168,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let scoring_restore_defaults_handle = tokio::spawn(async move {
+    let scoring_restore_defaults_handle = crash_report::spawn_named("scoring_restore_defaults", async move {
         scoring::handle_restore_rule_set_defaults(
             client_scoring_restore_defaults,
             scoring_restore_defaults_sub,
@@ -1320,7 +2178,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let inbox_state_get_handle = tokio::spawn(async move {
+    let inbox_state_get_handle = crash_report::spawn_named("inbox_state_get", async move {
         scoring::handle_get_inbox_state(
             client_inbox_state_get,
             inbox_state_get_sub,
@@ -1330,7 +2188,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let inbox_state_save_handle = tokio::spawn(async move {
+    let inbox_state_save_handle = crash_report::spawn_named("inbox_state_save", async move {
         scoring::handle_save_inbox_state(
             client_inbox_state_save,
             inbox_state_save_sub,
@@ -1340,7 +2198,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let route_plan_handle = tokio::spawn(async move {
+    let route_plan_handle = crash_report::spawn_named("route_plan", async move {
         route::handle_plan(
             client_route_plan,
             route_plan_sub,
@@ -1351,7 +2209,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let route_save_handle = tokio::spawn(async move {
+    let route_save_handle = crash_report::spawn_named("route_save", async move {
         route::handle_save(
             client_route_save,
             route_save_sub,
@@ -1361,7 +2219,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let route_delete_handle = tokio::spawn(async move {
+    let route_delete_handle = crash_report::spawn_named("route_delete", async move {
         route::handle_delete(
             client_route_delete,
             route_delete_sub,
@@ -1371,7 +2229,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let route_update_handle = tokio::spawn(async move {
+    let route_update_handle = crash_report::spawn_named("route_update", async move {
         route::handle_update(
             client_route_update,
             route_update_sub,
@@ -1381,7 +2239,40 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let route_get_handle = tokio::spawn(async move {
+    let route_stop_note_handle = crash_report::spawn_named("route_stop_note", async move {
+        route::handle_stop_note_update(
+            client_route_stop_note,
+            route_stop_note_sub,
+            pool_route_stop_note,
+            jwt_secret_route_stop_note,
+        )
+        .await
+    });
+
+    let route_lock_handle = crash_report::spawn_named("route_lock", async move {
+        route::handle_lock(client_route_lock, route_lock_sub, pool_route_lock, jwt_secret_route_lock).await
+    });
+
+    let route_unlock_handle = crash_report::spawn_named("route_unlock", async move {
+        route::handle_unlock(client_route_unlock, route_unlock_sub, pool_route_unlock, jwt_secret_route_unlock).await
+    });
+
+    let route_analysis_handle = crash_report::spawn_named("route_analysis", async move {
+        route::handle_analysis(client_route_analysis, route_analysis_sub, pool_route_analysis, jwt_secret_route_analysis).await
+    });
+
+    let route_break_suggest_handle = crash_report::spawn_named("route_break_suggest", async move {
+        route::handle_break_suggest(
+            client_route_break_suggest,
+            route_break_suggest_sub,
+            pool_route_break_suggest,
+            jwt_secret_route_break_suggest,
+            nominatim_url_route_break_suggest,
+        )
+        .await
+    });
+
+    let route_get_handle = crash_report::spawn_named("route_get", async move {
         route::handle_get(
             client_route_get,
             route_get_sub,
@@ -1392,13 +2283,13 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     });
 
     let client_route_list = client.clone();
-    let pool_route_list = pool.clone();
+    let repos_route_list = repos.clone();
     let jwt_secret_route_list = jwt_secret.clone();
-    let route_list_for_date_handle = tokio::spawn(async move {
+    let route_list_for_date_handle = crash_report::spawn_named("route_list_for_date", async move {
         route::handle_list_for_date(
             client_route_list,
             route_list_for_date_sub,
-            pool_route_list,
+            repos_route_list,
             jwt_secret_route_list,
         )
         .await
@@ -1407,7 +2298,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     let client_route_list2 = client.clone();
     let pool_route_list2 = pool.clone();
     let jwt_secret_route_list2 = jwt_secret.clone();
-    let route_list_handle = tokio::spawn(async move {
+    let route_list_handle = crash_report::spawn_named("route_list", async move {
         route::handle_list(
             client_route_list2,
             route_list_sub,
@@ -1417,7 +2308,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let route_insertion_handle = tokio::spawn(async move {
+    let route_insertion_handle = crash_report::spawn_named("route_insertion", async move {
         route::handle_insertion_calculate(
             client_route_insertion,
             route_insertion_sub,
@@ -1428,7 +2319,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let route_insertion_batch_handle = tokio::spawn(async move {
+    let route_insertion_batch_handle = crash_report::spawn_named("route_insertion_batch", async move {
         route::handle_insertion_batch(
             client_route_insertion_batch,
             route_insertion_batch_sub,
@@ -1439,7 +2330,18 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let route_recalculate_handle = tokio::spawn(async move {
+    let route_insertion_multi_day_handle = crash_report::spawn_named("route_insertion_multi_day", async move {
+        route::handle_insertion_multi_day(
+            client_route_insertion_multi_day,
+            route_insertion_multi_day_sub,
+            pool_route_insertion_multi_day,
+            jwt_secret_route_insertion_multi_day,
+            routing_insertion_multi_day,
+        )
+        .await
+    });
+
+    let route_recalculate_handle = crash_report::spawn_named("route_recalculate", async move {
         route::handle_recalculate(
             client_route_recalculate,
             route_recalculate_sub,
@@ -1450,8 +2352,19 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
+    let route_reoptimize_live_handle = crash_report::spawn_named("route_reoptimize_live", async move {
+        route::handle_reoptimize_live(
+            client_route_reoptimize_live,
+            route_reoptimize_live_sub,
+            pool_route_reoptimize_live,
+            jwt_secret_route_reoptimize_live,
+            routing_reoptimize_live,
+        )
+        .await
+    });
+
     // Device handlers
-    let device_create_handle = tokio::spawn(async move {
+    let device_create_handle = crash_report::spawn_named("device_create", async move {
         device::handle_create(
             client_device_create,
             device_create_sub,
@@ -1461,7 +2374,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let device_list_handle = tokio::spawn(async move {
+    let device_list_handle = crash_report::spawn_named("device_list", async move {
         device::handle_list(
             client_device_list,
             device_list_sub,
@@ -1471,7 +2384,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let device_get_handle = tokio::spawn(async move {
+    let device_get_handle = crash_report::spawn_named("device_get", async move {
         device::handle_get(
             client_device_get,
             device_get_sub,
@@ -1481,7 +2394,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let device_update_handle = tokio::spawn(async move {
+    let device_update_handle = crash_report::spawn_named("device_update", async move {
         device::handle_update(
             client_device_update,
             device_update_sub,
@@ -1491,7 +2404,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let device_delete_handle = tokio::spawn(async move {
+    let device_delete_handle = crash_report::spawn_named("device_delete", async move {
         device::handle_delete(
             client_device_delete,
             device_delete_sub,
@@ -1501,15 +2414,45 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
+    let device_restore_handle = crash_report::spawn_named("device_restore", async move {
+        device::handle_restore(
+            client_device_restore,
+            device_restore_sub,
+            pool_device_restore,
+            jwt_secret_device_restore,
+        )
+        .await
+    });
+
+    let device_transfer_handle = crash_report::spawn_named("device_transfer", async move {
+        device::handle_transfer(
+            client_device_transfer,
+            device_transfer_sub,
+            pool_device_transfer,
+            jwt_secret_device_transfer,
+        )
+        .await
+    });
+
+    let device_transfers_handle = crash_report::spawn_named("device_transfers", async move {
+        device::handle_list_transfers(
+            client_device_transfers,
+            device_transfers_sub,
+            pool_device_transfers,
+            jwt_secret_device_transfers,
+        )
+        .await
+    });
+
     // Device type config handlers
-    let dtc_list_handle = tokio::spawn(async move {
+    let dtc_list_handle = crash_report::spawn_named("dtc_list", async move {
         device_type_config::handle_list(client_dtc_list, dtc_list_sub, pool_dtc_list, jwt_dtc_list)
             .await
     });
-    let dtc_get_handle = tokio::spawn(async move {
+    let dtc_get_handle = crash_report::spawn_named("dtc_get", async move {
         device_type_config::handle_get(client_dtc_get, dtc_get_sub, pool_dtc_get, jwt_dtc_get).await
     });
-    let dtc_create_handle = tokio::spawn(async move {
+    let dtc_create_handle = crash_report::spawn_named("dtc_create", async move {
         device_type_config::handle_create(
             client_dtc_create,
             dtc_create_sub,
@@ -1518,7 +2461,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         )
         .await
     });
-    let dtc_update_handle = tokio::spawn(async move {
+    let dtc_update_handle = crash_report::spawn_named("dtc_update", async move {
         device_type_config::handle_update(
             client_dtc_update,
             dtc_update_sub,
@@ -1527,7 +2470,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         )
         .await
     });
-    let dtf_create_handle = tokio::spawn(async move {
+    let dtf_create_handle = crash_report::spawn_named("dtf_create", async move {
         device_type_config::handle_field_create(
             client_dtf_create,
             dtf_create_sub,
@@ -1536,7 +2479,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         )
         .await
     });
-    let dtf_update_handle = tokio::spawn(async move {
+    let dtf_update_handle = crash_report::spawn_named("dtf_update", async move {
         device_type_config::handle_field_update(
             client_dtf_update,
             dtf_update_sub,
@@ -1545,7 +2488,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         )
         .await
     });
-    let dtf_set_active_handle = tokio::spawn(async move {
+    let dtf_set_active_handle = crash_report::spawn_named("dtf_set_active", async move {
         device_type_config::handle_field_set_active(
             client_dtf_set_active,
             dtf_set_active_sub,
@@ -1554,7 +2497,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         )
         .await
     });
-    let dtf_reorder_handle = tokio::spawn(async move {
+    let dtf_reorder_handle = crash_report::spawn_named("dtf_reorder", async move {
         device_type_config::handle_field_reorder(
             client_dtf_reorder,
             dtf_reorder_sub,
@@ -1565,7 +2508,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     });
 
     // Revision handlers
-    let revision_create_handle = tokio::spawn(async move {
+    let revision_create_handle = crash_report::spawn_named("revision_create", async move {
         revision::handle_create(
             client_revision_create,
             revision_create_sub,
@@ -1575,7 +2518,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let revision_list_handle = tokio::spawn(async move {
+    let revision_list_handle = crash_report::spawn_named("revision_list", async move {
         revision::handle_list(
             client_revision_list,
             revision_list_sub,
@@ -1585,17 +2528,17 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let revision_get_handle = tokio::spawn(async move {
+    let revision_get_handle = crash_report::spawn_named("revision_get", async move {
         revision::handle_get(
             client_revision_get,
             revision_get_sub,
-            pool_revision_get,
+            repos_revision_get,
             jwt_secret_revision_get,
         )
         .await
     });
 
-    let revision_update_handle = tokio::spawn(async move {
+    let revision_update_handle = crash_report::spawn_named("revision_update", async move {
         revision::handle_update(
             client_revision_update,
             revision_update_sub,
@@ -1605,7 +2548,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let revision_delete_handle = tokio::spawn(async move {
+    let revision_delete_handle = crash_report::spawn_named("revision_delete", async move {
         revision::handle_delete(
             client_revision_delete,
             revision_delete_sub,
@@ -1615,7 +2558,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let revision_complete_handle = tokio::spawn(async move {
+    let revision_complete_handle = crash_report::spawn_named("revision_complete", async move {
         revision::handle_complete(
             client_revision_complete,
             revision_complete_sub,
@@ -1625,7 +2568,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let revision_upcoming_handle = tokio::spawn(async move {
+    let revision_upcoming_handle = crash_report::spawn_named("revision_upcoming", async move {
         revision::handle_upcoming(
             client_revision_upcoming,
             revision_upcoming_sub,
@@ -1635,7 +2578,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let revision_stats_handle = tokio::spawn(async move {
+    let revision_stats_handle = crash_report::spawn_named("revision_stats", async move {
         revision::handle_stats(
             client_revision_stats,
             revision_stats_sub,
@@ -1645,7 +2588,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let revision_suggest_handle = tokio::spawn(async move {
+    let revision_suggest_handle = crash_report::spawn_named("revision_suggest", async move {
         revision::handle_suggest(
             client_revision_suggest,
             revision_suggest_sub,
@@ -1655,7 +2598,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let revision_queue_handle = tokio::spawn(async move {
+    let revision_queue_handle = crash_report::spawn_named("revision_queue", async move {
         revision::handle_queue(
             client_revision_queue,
             revision_queue_sub,
@@ -1665,7 +2608,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let revision_snooze_handle = tokio::spawn(async move {
+    let revision_snooze_handle = crash_report::spawn_named("revision_snooze", async move {
         revision::handle_snooze(
             client_revision_snooze,
             revision_snooze_sub,
@@ -1675,7 +2618,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let revision_schedule_handle = tokio::spawn(async move {
+    let revision_schedule_handle = crash_report::spawn_named("revision_schedule", async move {
         revision::handle_schedule(
             client_revision_schedule,
             revision_schedule_sub,
@@ -1685,7 +2628,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let revision_unschedule_handle = tokio::spawn(async move {
+    let revision_unschedule_handle = crash_report::spawn_named("revision_unschedule", async move {
         revision::handle_unschedule(
             client_revision_unschedule,
             revision_unschedule_sub,
@@ -1695,8 +2638,18 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
+    let revision_numbers_handle = crash_report::spawn_named("revision_numbers", async move {
+        revision::handle_list_document_numbers(
+            client_revision_numbers,
+            revision_numbers_list_sub,
+            pool_revision_numbers,
+            jwt_secret_revision_numbers,
+        )
+        .await
+    });
+
     // Slots handlers
-    let slots_suggest_handle = tokio::spawn(async move {
+    let slots_suggest_handle = crash_report::spawn_named("slots_suggest", async move {
         slots::handle_suggest(
             client_slots_suggest,
             slots_suggest_sub,
@@ -1705,29 +2658,41 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         )
         .await
     });
-    let slots_suggest_v2_handle = tokio::spawn(async move {
+    let slots_suggest_v2_handle = crash_report::spawn_named("slots_suggest_v2", async move {
         slots::handle_suggest_v2(
             client_slots_suggest_v2,
             slots_suggest_v2_sub,
             pool_slots_suggest_v2,
+            repos_slots_suggest_v2,
             jwt_secret_slots_suggest_v2,
             routing_slots_suggest_v2,
         )
         .await
     });
-    let slots_validate_handle = tokio::spawn(async move {
+    let slots_validate_handle = crash_report::spawn_named("slots_validate", async move {
         slots::handle_validate(
             client_slots_validate,
             slots_validate_sub,
             pool_slots_validate,
+            repos_slots_validate,
             jwt_secret_slots_validate,
             routing_slots_validate,
         )
         .await
     });
+    let slots_calendar_handle = crash_report::spawn_named("slots_calendar", async move {
+        slots::handle_calendar(
+            client_slots_calendar,
+            slots_calendar_sub,
+            pool_slots_calendar,
+            repos_slots_calendar,
+            jwt_secret_slots_calendar,
+        )
+        .await
+    });
 
     // Settings handlers
-    let settings_get_handle = tokio::spawn(async move {
+    let settings_get_handle = crash_report::spawn_named("settings_get", async move {
         settings::handle_get_settings(
             client_settings_get,
             settings_get_sub,
@@ -1737,7 +2702,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let settings_work_handle = tokio::spawn(async move {
+    let settings_work_handle = crash_report::spawn_named("settings_work", async move {
         settings::handle_update_work_constraints(
             client_settings_work,
             settings_work_update_sub,
@@ -1747,7 +2712,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let settings_business_handle = tokio::spawn(async move {
+    let settings_business_handle = crash_report::spawn_named("settings_business", async move {
         settings::handle_update_business_info(
             client_settings_business,
             settings_business_update_sub,
@@ -1757,7 +2722,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let settings_email_handle = tokio::spawn(async move {
+    let settings_email_handle = crash_report::spawn_named("settings_email", async move {
         settings::handle_update_email_templates(
             client_settings_email,
             settings_email_update_sub,
@@ -1767,7 +2732,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let settings_preferences_handle = tokio::spawn(async move {
+    let settings_preferences_handle = crash_report::spawn_named("settings_preferences", async move {
         settings::handle_update_preferences(
             client_settings_preferences,
             settings_preferences_update_sub,
@@ -1777,7 +2742,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let settings_break_handle = tokio::spawn(async move {
+    let settings_break_handle = crash_report::spawn_named("settings_break", async move {
         settings::handle_update_break_settings(
             client_settings_break,
             settings_break_update_sub,
@@ -1787,7 +2752,37 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let account_delete_handle = tokio::spawn(async move {
+    let settings_numbering_handle = crash_report::spawn_named("settings_numbering", async move {
+        settings::handle_update_revision_numbering(
+            client_settings_numbering,
+            settings_numbering_update_sub,
+            pool_settings_numbering,
+            jwt_secret_settings_numbering,
+        )
+        .await
+    });
+
+    let settings_customer_codes_handle = crash_report::spawn_named("settings_customer_codes", async move {
+        settings::handle_update_customer_codes(
+            client_settings_customer_codes,
+            settings_customer_codes_update_sub,
+            pool_settings_customer_codes,
+            jwt_secret_settings_customer_codes,
+        )
+        .await
+    });
+
+    let import_customer_ref_preview_handle = crash_report::spawn_named("import_customer_ref_preview", async move {
+        import::handle_customer_ref_preview(
+            client_import_customer_ref_preview,
+            import_customer_ref_preview_sub,
+            pool_import_customer_ref_preview,
+            jwt_secret_import_customer_ref_preview,
+        )
+        .await
+    });
+
+    let account_delete_handle = crash_report::spawn_named("account_delete", async move {
         settings::handle_delete_account(
             client_account_delete,
             account_delete_sub,
@@ -1798,7 +2793,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     });
 
     // Depot handlers
-    let depot_list_handle = tokio::spawn(async move {
+    let depot_list_handle = crash_report::spawn_named("depot_list", async move {
         settings::handle_list_depots(
             client_depot_list,
             depot_list_sub,
@@ -1808,7 +2803,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let depot_create_handle = tokio::spawn(async move {
+    let depot_create_handle = crash_report::spawn_named("depot_create", async move {
         settings::handle_create_depot(
             client_depot_create,
             depot_create_sub,
@@ -1819,7 +2814,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let depot_update_handle = tokio::spawn(async move {
+    let depot_update_handle = crash_report::spawn_named("depot_update", async move {
         settings::handle_update_depot(
             client_depot_update,
             depot_update_sub,
@@ -1829,7 +2824,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let depot_delete_handle = tokio::spawn(async move {
+    let depot_delete_handle = crash_report::spawn_named("depot_delete", async move {
         settings::handle_delete_depot(
             client_depot_delete,
             depot_delete_sub,
@@ -1839,7 +2834,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let depot_geocode_handle = tokio::spawn(async move {
+    let depot_geocode_handle = crash_report::spawn_named("depot_geocode", async move {
         settings::handle_geocode_depot(
             client_depot_geocode,
             depot_geocode_sub,
@@ -1850,7 +2845,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     });
 
     // Communication handlers
-    let comm_create_handle = tokio::spawn(async move {
+    let comm_create_handle = crash_report::spawn_named("comm_create", async move {
         communication::handle_create(
             client_comm_create,
             comm_create_sub,
@@ -1860,7 +2855,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let comm_list_handle = tokio::spawn(async move {
+    let comm_list_handle = crash_report::spawn_named("comm_list", async move {
         communication::handle_list(
             client_comm_list,
             comm_list_sub,
@@ -1870,7 +2865,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let comm_update_handle = tokio::spawn(async move {
+    let comm_update_handle = crash_report::spawn_named("comm_update", async move {
         communication::handle_update(
             client_comm_update,
             comm_update_sub,
@@ -1880,7 +2875,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let comm_delete_handle = tokio::spawn(async move {
+    let comm_delete_handle = crash_report::spawn_named("comm_delete", async move {
         communication::handle_delete(
             client_comm_delete,
             comm_delete_sub,
@@ -1891,7 +2886,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     });
 
     // Visit handlers
-    let visit_create_handle = tokio::spawn(async move {
+    let visit_create_handle = crash_report::spawn_named("visit_create", async move {
         visit::handle_create(
             client_visit_create,
             visit_create_sub,
@@ -1901,7 +2896,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let visit_list_handle = tokio::spawn(async move {
+    let visit_list_handle = crash_report::spawn_named("visit_list", async move {
         visit::handle_list(
             client_visit_list,
             visit_list_sub,
@@ -1911,7 +2906,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let visit_update_handle = tokio::spawn(async move {
+    let visit_update_handle = crash_report::spawn_named("visit_update", async move {
         visit::handle_update(
             client_visit_update,
             visit_update_sub,
@@ -1921,7 +2916,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let visit_complete_handle = tokio::spawn(async move {
+    let visit_complete_handle = crash_report::spawn_named("visit_complete", async move {
         visit::handle_complete(
             client_visit_complete,
             visit_complete_sub,
@@ -1931,7 +2926,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let visit_delete_handle = tokio::spawn(async move {
+    let visit_delete_handle = crash_report::spawn_named("visit_delete", async move {
         visit::handle_delete(
             client_visit_delete,
             visit_delete_sub,
@@ -1941,7 +2936,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let visit_get_handle = tokio::spawn(async move {
+    let visit_get_handle = crash_report::spawn_named("visit_get", async move {
         visit::handle_get(
             client_visit_get,
             visit_get_sub,
@@ -1951,7 +2946,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let visit_update_field_notes_handle = tokio::spawn(async move {
+    let visit_update_field_notes_handle = crash_report::spawn_named("visit_update_field_notes", async move {
         visit::handle_update_field_notes(
             client_visit_update_field_notes,
             visit_update_field_notes_sub,
@@ -1961,7 +2956,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let visit_notes_history_handle = tokio::spawn(async move {
+    let visit_notes_history_handle = crash_report::spawn_named("visit_notes_history", async move {
         visit::handle_notes_history(
             client_visit_notes_history,
             visit_notes_history_sub,
@@ -1972,24 +2967,24 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     });
 
     // Unified note handlers
-    let note_create_handle = tokio::spawn(async move {
+    let note_create_handle = crash_report::spawn_named("note_create", async move {
         note::handle_create(client_note_create, note_create_sub, pool_note_create, jwt_secret_note_create).await
     });
-    let note_update_handle = tokio::spawn(async move {
+    let note_update_handle = crash_report::spawn_named("note_update", async move {
         note::handle_update(client_note_update, note_update_sub, pool_note_update, jwt_secret_note_update).await
     });
-    let note_list_handle = tokio::spawn(async move {
+    let note_list_handle = crash_report::spawn_named("note_list", async move {
         note::handle_list(client_note_list, note_list_sub, pool_note_list, jwt_secret_note_list).await
     });
-    let note_audit_handle = tokio::spawn(async move {
+    let note_audit_handle = crash_report::spawn_named("note_audit", async move {
         note::handle_audit(client_note_audit, note_audit_sub, pool_note_audit, jwt_secret_note_audit).await
     });
-    let note_delete_handle = tokio::spawn(async move {
+    let note_delete_handle = crash_report::spawn_named("note_delete", async move {
         note::handle_delete(client_note_delete, note_delete_sub, pool_note_delete, jwt_secret_note_delete).await
     });
 
     // Crew handlers
-    let crew_create_handle = tokio::spawn(async move {
+    let crew_create_handle = crash_report::spawn_named("crew_create", async move {
         crew::handle_create(
             client_crew_create,
             crew_create_sub,
@@ -1999,7 +2994,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let crew_list_handle = tokio::spawn(async move {
+    let crew_list_handle = crash_report::spawn_named("crew_list", async move {
         crew::handle_list(
             client_crew_list,
             crew_list_sub,
@@ -2009,7 +3004,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let crew_update_handle = tokio::spawn(async move {
+    let crew_update_handle = crash_report::spawn_named("crew_update", async move {
         crew::handle_update(
             client_crew_update,
             crew_update_sub,
@@ -2019,7 +3014,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let crew_delete_handle = tokio::spawn(async move {
+    let crew_delete_handle = crash_report::spawn_named("crew_delete", async move {
         crew::handle_delete(
             client_crew_delete,
             crew_delete_sub,
@@ -2030,7 +3025,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
     });
 
     // Work item handlers
-    let work_item_create_handle = tokio::spawn(async move {
+    let work_item_create_handle = crash_report::spawn_named("work_item_create", async move {
         work_item::handle_create(
             client_work_item_create,
             work_item_create_sub,
@@ -2040,7 +3035,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let work_item_list_handle = tokio::spawn(async move {
+    let work_item_list_handle = crash_report::spawn_named("work_item_list", async move {
         work_item::handle_list(
             client_work_item_list,
             work_item_list_sub,
@@ -2050,7 +3045,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let work_item_get_handle = tokio::spawn(async move {
+    let work_item_get_handle = crash_report::spawn_named("work_item_get", async move {
         work_item::handle_get(
             client_work_item_get,
             work_item_get_sub,
@@ -2060,7 +3055,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
-    let work_item_complete_handle = tokio::spawn(async move {
+    let work_item_complete_handle = crash_report::spawn_named("work_item_complete", async move {
         work_item::handle_complete(
             client_work_item_complete,
             work_item_complete_sub,
@@ -2070,6 +3065,15 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         .await
     });
 
+    // Start debug recording handlers
+    let client_recording = client.clone();
+    let jwt_secret_recording = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        if let Err(e) = debug_recording::start_handlers(client_recording, jwt_secret_recording).await {
+            error!("Debug recording handlers error: {}", e);
+        }
+    });
+
     // Old sync import handlers removed - replaced by async processors below
 
     // Start admin handlers
@@ -2108,7 +3112,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
 
                 // Subscribe to customer import submit
                 let customer_import_submit_sub = match client_customer_import
-                    .subscribe("sazinka.import.customer.submit")
+                    .subscribe(subjects::import::CUSTOMER_SUBMIT)
                     .await
                 {
                     Ok(sub) => sub,
@@ -2166,7 +3170,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
                 let processor = Arc::new(processor);
 
                 let device_import_submit_sub = match client_device_import
-                    .subscribe("sazinka.import.device.submit")
+                    .subscribe(subjects::import::DEVICE_SUBMIT)
                     .await
                 {
                     Ok(sub) => sub,
@@ -2222,7 +3226,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
                 let processor = Arc::new(processor);
 
                 let revision_import_submit_sub = match client_revision_import
-                    .subscribe("sazinka.import.revision.submit")
+                    .subscribe(subjects::import::REVISION_SUBMIT)
                     .await
                 {
                     Ok(sub) => sub,
@@ -2278,7 +3282,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
                 let processor = Arc::new(processor);
 
                 let communication_import_submit_sub = match client_communication_import
-                    .subscribe("sazinka.import.communication.submit")
+                    .subscribe(subjects::import::COMMUNICATION_SUBMIT)
                     .await
                 {
                     Ok(sub) => sub,
@@ -2334,7 +3338,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
                 let processor = Arc::new(processor);
 
                 let visit_import_submit_sub = match client_visit_import
-                    .subscribe("sazinka.import.visit.submit")
+                    .subscribe(subjects::import::VISIT_SUBMIT)
                     .await
                 {
                     Ok(sub) => sub,
@@ -2387,7 +3391,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
                 let processor = Arc::new(processor);
 
                 let zip_import_submit_sub = match client_zip_import
-                    .subscribe("sazinka.import.zip.submit")
+                    .subscribe(subjects::import::ZIP_SUBMIT)
                     .await
                 {
                     Ok(sub) => sub,
@@ -2428,21 +3432,79 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         }
     });
 
+    // Start KML/KMZ customer import processor
+    let client_kml_import = client.clone();
+    let pool_kml_import = pool.clone();
+    let jwt_secret_kml_import = Arc::clone(&jwt_secret);
+    tokio::spawn(async move {
+        match import_processors::KmlImportProcessor::new(client_kml_import.clone(), pool_kml_import)
+            .await
+        {
+            Ok(processor) => {
+                let processor = Arc::new(processor);
+
+                let kml_import_submit_sub = match client_kml_import
+                    .subscribe(subjects::import::KML_SUBMIT)
+                    .await
+                {
+                    Ok(sub) => sub,
+                    Err(e) => {
+                        error!("Failed to subscribe to import.kml.submit: {}", e);
+                        return;
+                    }
+                };
+
+                let client_submit = client_kml_import.clone();
+                let processor_submit = Arc::clone(&processor);
+                let jwt_secret_submit = Arc::clone(&jwt_secret_kml_import);
+                tokio::spawn(async move {
+                    if let Err(e) = import_processors::handle_kml_import_submit(
+                        client_submit,
+                        kml_import_submit_sub,
+                        jwt_secret_submit,
+                        processor_submit,
+                    )
+                    .await
+                    {
+                        error!("KML import submit handler error: {}", e);
+                    }
+                });
+
+                let processor_main = Arc::clone(&processor);
+                tokio::spawn(async move {
+                    if let Err(e) = processor_main.start_processing().await {
+                        error!("KML import processor error: {}", e);
+                    }
+                });
+
+                info!("KML import processor started");
+            }
+            Err(e) => {
+                error!("Failed to create KML import processor: {}", e);
+            }
+        }
+    });
+
     // Start Export+ processor
     let client_export = client.clone();
     let pool_export = pool.clone();
     let jwt_secret_export = Arc::clone(&jwt_secret);
+    let url_export = Arc::clone(&app_base_url);
+    let storage_export = config.export_storage.clone();
     tokio::spawn(async move {
         match crate::services::export_processor::ExportProcessor::new(
             client_export.clone(),
             pool_export,
+            Arc::clone(&jwt_secret_export),
+            url_export,
+            storage_export,
         )
         .await
         {
             Ok(processor) => {
                 let processor = Arc::new(processor);
 
-                let export_submit_sub = match client_export.subscribe("sazinka.export.submit").await
+                let export_submit_sub = match client_export.subscribe(subjects::export::SUBMIT).await
                 {
                     Ok(sub) => sub,
                     Err(e) => {
@@ -2451,7 +3513,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
                     }
                 };
                 let export_download_sub =
-                    match client_export.subscribe("sazinka.export.download").await {
+                    match client_export.subscribe(subjects::export::DOWNLOAD).await {
                         Ok(sub) => sub,
                         Err(e) => {
                             error!("Failed to subscribe to export.download: {}", e);
@@ -2524,7 +3586,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
 
                 // Subscribe to geocode subjects
                 let geocode_submit_sub =
-                    match client_geocode.subscribe("sazinka.geocode.submit").await {
+                    match client_geocode.subscribe(subjects::geocode::SUBMIT).await {
                         Ok(sub) => sub,
                         Err(e) => {
                             error!("Failed to subscribe to geocode.submit: {}", e);
@@ -2532,15 +3594,23 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
                         }
                     };
                 let geocode_pending_sub =
-                    match client_geocode.subscribe("sazinka.geocode.pending").await {
+                    match client_geocode.subscribe(subjects::geocode::PENDING).await {
                         Ok(sub) => sub,
                         Err(e) => {
                             error!("Failed to subscribe to geocode.pending: {}", e);
                             return;
                         }
                     };
+                let geocode_rerun_sub =
+                    match client_geocode.subscribe(subjects::geocode::RERUN).await {
+                        Ok(sub) => sub,
+                        Err(e) => {
+                            error!("Failed to subscribe to geocode.rerun: {}", e);
+                            return;
+                        }
+                    };
                 let geocode_address_sub = match client_geocode
-                    .subscribe("sazinka.geocode.address.submit")
+                    .subscribe(subjects::geocode::ADDRESS_SUBMIT)
                     .await
                 {
                     Ok(sub) => sub,
@@ -2550,7 +3620,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
                     }
                 };
                 let reverse_geocode_sub = match client_geocode
-                    .subscribe("sazinka.geocode.reverse.submit")
+                    .subscribe(subjects::geocode::REVERSE_SUBMIT)
                     .await
                 {
                     Ok(sub) => sub,
@@ -2577,6 +3647,23 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
                     }
                 });
 
+                // Start rerun handler
+                let client_rerun = client_geocode.clone();
+                let processor_rerun = Arc::clone(&processor);
+                let jwt_secret_geocode_rerun = Arc::clone(&jwt_secret_geocode);
+                tokio::spawn(async move {
+                    if let Err(e) = geocode::handle_geocode_rerun(
+                        client_rerun,
+                        geocode_rerun_sub,
+                        processor_rerun,
+                        jwt_secret_geocode_rerun,
+                    )
+                    .await
+                    {
+                        error!("Geocode rerun handler error: {}", e);
+                    }
+                });
+
                 // Start pending handler
                 let client_pending = client_geocode.clone();
                 let jwt_secret_geocode_pending = Arc::clone(&jwt_secret_geocode);
@@ -2632,6 +3719,12 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
                         error!("Geocode processor error: {}", e);
                     }
                 });
+                let processor_bulk = Arc::clone(&processor);
+                tokio::spawn(async move {
+                    if let Err(e) = processor_bulk.start_bulk_processing().await {
+                        error!("Geocode bulk processor error: {}", e);
+                    }
+                });
                 let processor_address = Arc::clone(&processor);
                 tokio::spawn(async move {
                     if let Err(e) = processor_address.start_address_processing().await {
@@ -2668,7 +3761,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
 
                     // Subscribe to Valhalla job subjects
                     let matrix_submit_sub = match client_valhalla
-                        .subscribe("sazinka.valhalla.matrix.submit")
+                        .subscribe(subjects::valhalla::MATRIX_SUBMIT)
                         .await
                     {
                         Ok(sub) => sub,
@@ -2678,7 +3771,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
                         }
                     };
                     let geometry_submit_sub = match client_valhalla
-                        .subscribe("sazinka.valhalla.geometry.submit")
+                        .subscribe(subjects::valhalla::GEOMETRY_SUBMIT)
                         .await
                     {
                         Ok(sub) => sub,
@@ -2766,7 +3859,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
 
                 // Subscribe to route job submit
                 let route_submit_sub =
-                    match client_route_jobs.subscribe("sazinka.route.submit").await {
+                    match client_route_jobs.subscribe(subjects::route::SUBMIT).await {
                         Ok(sub) => sub,
                         Err(e) => {
                             error!("Failed to subscribe to route.submit: {}", e);
@@ -2809,70 +3902,88 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
 
     // Start job management handlers (history, cancel, retry)
     let client_job_history = client.clone();
+    let pool_job_history = pool.clone();
     let jwt_secret_job_history = Arc::clone(&jwt_secret);
-    let job_history_sub = client.subscribe("sazinka.jobs.history").await?;
-    let job_history_handle = tokio::spawn(async move {
-        jobs::handle_job_history(client_job_history, job_history_sub, jwt_secret_job_history).await
+    let job_history_sub = client.subscribe(subjects::jobs::HISTORY).await?;
+    let job_history_handle = crash_report::spawn_named("job_history", async move {
+        jobs::handle_job_history(client_job_history, job_history_sub, pool_job_history, jwt_secret_job_history).await
     });
 
     let client_job_cancel = client.clone();
     let jwt_secret_job_cancel = Arc::clone(&jwt_secret);
-    let job_cancel_sub = client.subscribe("sazinka.jobs.cancel").await?;
-    let job_cancel_handle = tokio::spawn(async move {
+    let job_cancel_sub = client.subscribe(subjects::jobs::CANCEL).await?;
+    let job_cancel_handle = crash_report::spawn_named("job_cancel", async move {
         jobs::handle_job_cancel(client_job_cancel, job_cancel_sub, jwt_secret_job_cancel).await
     });
 
     let client_job_retry = client.clone();
+    let pool_job_retry = pool.clone();
     let jwt_secret_job_retry = Arc::clone(&jwt_secret);
-    let job_retry_sub = client.subscribe("sazinka.jobs.retry").await?;
-    let job_retry_handle = tokio::spawn(async move {
-        jobs::handle_job_retry(client_job_retry, job_retry_sub, jwt_secret_job_retry).await
+    let job_retry_sub = client.subscribe(subjects::jobs::RETRY).await?;
+    let job_retry_handle = crash_report::spawn_named("job_retry", async move {
+        jobs::handle_job_retry(client_job_retry, job_retry_sub, pool_job_retry, jwt_secret_job_retry).await
     });
 
+    let client_job_lost = client.clone();
+    let pool_job_lost = pool.clone();
+    let jwt_secret_job_lost = Arc::clone(&jwt_secret);
+    let job_lost_sub = client.subscribe(subjects::jobs::LOST).await?;
+    let job_lost_handle = crash_report::spawn_named("job_lost", async move {
+        jobs::handle_lost_jobs(client_job_lost, job_lost_sub, pool_job_lost, jwt_secret_job_lost).await
+    });
+
+    // Fail jobs interrupted by the restart and flag queued jobs whose
+    // JetStream messages did not survive it
+    tokio::spawn(crate::services::job_backup::run_startup_reconciliation(
+        client.clone(),
+        pool.clone(),
+        interrupted_jobs,
+    ));
+
     // Task handlers
-    let task_type_create_handle = tokio::spawn({
+    let task_type_create_handle = crash_report::spawn_named("task_type_create", {
         let c = client.clone();
         let p = pool.clone();
         let j = Arc::clone(&jwt_secret);
         async move { task::handle_task_type_create(c, task_type_create_sub, p, j).await }
     });
-    let task_type_list_handle = tokio::spawn({
+    let task_type_list_handle = crash_report::spawn_named("task_type_list", {
         let c = client.clone();
         let p = pool.clone();
         let j = Arc::clone(&jwt_secret);
         async move { task::handle_task_type_list(c, task_type_list_sub, p, j).await }
     });
-    let task_type_update_handle = tokio::spawn({
+    let task_type_update_handle = crash_report::spawn_named("task_type_update", {
         let c = client.clone();
         let p = pool.clone();
         let j = Arc::clone(&jwt_secret);
         async move { task::handle_task_type_update(c, task_type_update_sub, p, j).await }
     });
-    let task_create_handle = tokio::spawn({
+    let task_create_handle = crash_report::spawn_named("task_create", {
         let c = client.clone();
         let p = pool.clone();
         let j = Arc::clone(&jwt_secret);
         async move { task::handle_task_create(c, task_create_sub, p, j).await }
     });
-    let task_list_handle = tokio::spawn({
+    let task_list_handle = crash_report::spawn_named("task_list", {
         let c = client.clone();
         let p = pool.clone();
         let j = Arc::clone(&jwt_secret);
         async move { task::handle_task_list(c, task_list_sub, p, j).await }
     });
-    let task_get_handle = tokio::spawn({
+    let task_get_handle = crash_report::spawn_named("task_get", {
         let c = client.clone();
         let p = pool.clone();
         let j = Arc::clone(&jwt_secret);
         async move { task::handle_task_get(c, task_get_sub, p, j).await }
     });
-    let task_update_handle = tokio::spawn({
+    let task_update_handle = crash_report::spawn_named("task_update", {
         let c = client.clone();
         let p = pool.clone();
         let j = Arc::clone(&jwt_secret);
         async move { task::handle_task_update(c, task_update_sub, p, j).await }
     });
-    let task_complete_handle = tokio::spawn({
+    let task_complete_handle = crash_report::spawn_named("task_complete", {
         let c = client.clone();
         let p = pool.clone();
         let j = Arc::clone(&jwt_secret);
@@ -2887,16 +3998,19 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
 
     let handles: Vec<_> = vec![
         ping_handle.boxed(),
+        health_handle.boxed(),
         customer_create_handle.boxed(),
         customer_list_handle.boxed(),
         customer_get_handle.boxed(),
         customer_update_handle.boxed(),
+        customer_bulk_update_handle.boxed(),
         customer_delete_handle.boxed(),
         customer_random_handle.boxed(),
         customer_list_extended_handle.boxed(),
         customer_summary_handle.boxed(),
         customer_abandon_handle.boxed(),
         customer_unabandon_handle.boxed(),
+        customer_restore_handle.boxed(),
         customer_anonymize_handle.boxed(),
         customer_column_distinct_handle.boxed(),
         pa_create_handle.boxed(),
@@ -2919,17 +4033,27 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         route_save_handle.boxed(),
         route_delete_handle.boxed(),
         route_update_handle.boxed(),
+        route_stop_note_handle.boxed(),
+        route_lock_handle.boxed(),
+        route_unlock_handle.boxed(),
+        route_analysis_handle.boxed(),
+        route_break_suggest_handle.boxed(),
         route_get_handle.boxed(),
         route_list_for_date_handle.boxed(),
         route_list_handle.boxed(),
         route_insertion_handle.boxed(),
         route_insertion_batch_handle.boxed(),
+        route_insertion_multi_day_handle.boxed(),
         route_recalculate_handle.boxed(),
+        route_reoptimize_live_handle.boxed(),
         device_create_handle.boxed(),
         device_list_handle.boxed(),
         device_get_handle.boxed(),
         device_update_handle.boxed(),
         device_delete_handle.boxed(),
+        device_restore_handle.boxed(),
+        device_transfer_handle.boxed(),
+        device_transfers_handle.boxed(),
         revision_create_handle.boxed(),
         revision_list_handle.boxed(),
         revision_get_handle.boxed(),
@@ -2943,15 +4067,20 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         revision_snooze_handle.boxed(),
         revision_schedule_handle.boxed(),
         revision_unschedule_handle.boxed(),
+        revision_numbers_handle.boxed(),
         slots_suggest_handle.boxed(),
         slots_suggest_v2_handle.boxed(),
         slots_validate_handle.boxed(),
+        slots_calendar_handle.boxed(),
         settings_get_handle.boxed(),
         settings_work_handle.boxed(),
         settings_business_handle.boxed(),
         settings_email_handle.boxed(),
         settings_preferences_handle.boxed(),
         settings_break_handle.boxed(),
+        settings_numbering_handle.boxed(),
+        settings_customer_codes_handle.boxed(),
+        import_customer_ref_preview_handle.boxed(),
         account_delete_handle.boxed(),
         depot_list_handle.boxed(),
         depot_create_handle.boxed(),
@@ -2986,6 +4115,7 @@ pub async fn start_handlers(client: Client, pool: PgPool, config: &Config) -> Re
         job_history_handle.boxed(),
         job_cancel_handle.boxed(),
         job_retry_handle.boxed(),
+        job_lost_handle.boxed(),
         dtc_list_handle.boxed(),
         dtc_get_handle.boxed(),
         dtc_create_handle.boxed(),
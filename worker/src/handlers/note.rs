@@ -21,6 +21,7 @@ use tracing::{debug, error, warn};
 use uuid::Uuid;
 
 use crate::auth;
+use super::account;
 use crate::db::queries;
 use crate::types::{
     AuditNoteRequest, AuditNoteResponse, CreateNoteRequest, DeleteNoteRequest,
@@ -90,6 +91,10 @@ pub async fn handle_create(
             Uuid::nil()
         );
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let payload = request.payload;
 
         // Validate entity_type
@@ -189,6 +194,10 @@ pub async fn handle_update(
             Uuid::nil()
         );
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let payload = request.payload;
 
         if let Err(code) = crate::types::validate_content(&payload.content) {
@@ -358,6 +367,10 @@ pub async fn handle_delete(
             Uuid::nil()
         );
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let note_id = request.payload.note_id;
 
         match queries::note::delete_note(&pool, note_id, user_id).await {
@@ -0,0 +1,306 @@
+//! Notification center handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::{leader, notification_dispatch};
+use crate::subjects;
+use crate::types::notification::{
+    ListNotificationsRequest, ListNotificationsResponse, MarkNotificationsReadRequest,
+    MarkNotificationsReadResponse, NotificationPreferencesResponse, SetNotificationPreferenceRequest,
+};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Start all notification center NATS handlers
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting notification handlers...");
+
+    let [list_sub, read_sub, preferences_list_sub, preferences_set_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::notification::LIST,
+            subjects::notification::READ,
+            subjects::notification::PREFERENCES_LIST,
+            subjects::notification::PREFERENCES_SET,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_list(client.clone(), list_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_mark_read(client.clone(), read_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_list_preferences(
+        client.clone(),
+        preferences_list_sub,
+        pool.clone(),
+        jwt_secret.clone(),
+    ));
+    tokio::spawn(handle_set_preference(client.clone(), preferences_set_sub, pool.clone(), jwt_secret));
+
+    leader::spawn_singleton(pool, "notification_digests", notification_dispatch::run_digest_sender);
+
+    info!("Notification handlers started");
+    Ok(())
+}
+
+/// Handle notification.list messages
+pub async fn handle_list(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received notification.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ListNotificationsRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = &request.payload;
+        let limit = payload.limit.unwrap_or(50).clamp(1, 200);
+        let offset = payload.offset.unwrap_or(0).max(0);
+        let result = async {
+            let items = queries::notification::list_notifications(&pool, user_id, payload.unread_only, limit, offset).await?;
+            let unread_count = queries::notification::count_unread(&pool, user_id).await?;
+            anyhow::Ok(ListNotificationsResponse { items, unread_count })
+        }
+        .await;
+
+        match result {
+            Ok(list) => {
+                let response = SuccessResponse::new(request.id, list);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list notifications: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle notification.read messages
+pub async fn handle_mark_read(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received notification.read message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<MarkNotificationsReadRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let result = async {
+            let updated = queries::notification::mark_read(&pool, user_id, request.payload.ids.as_deref()).await?;
+            let unread_count = queries::notification::count_unread(&pool, user_id).await?;
+            anyhow::Ok(MarkNotificationsReadResponse { updated, unread_count })
+        }
+        .await;
+
+        match result {
+            Ok(marked) => {
+                let response = SuccessResponse::new(request.id, marked);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to mark notifications read: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle notification.preferences.list messages - one entry per category
+pub async fn handle_list_preferences(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received notification.preferences.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match notification_dispatch::preferences(&pool, user_id).await {
+            Ok(preferences) => {
+                let response = SuccessResponse::new(request.id, NotificationPreferencesResponse { preferences });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list notification preferences: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle notification.preferences.set messages
+pub async fn handle_set_preference(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received notification.preferences.set message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<SetNotificationPreferenceRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        // Notifications belong to the account, so its owner decides how they arrive
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can change notification preferences");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let payload = &request.payload;
+        if let Err(msg) = payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::notification::upsert_preference(
+            &pool,
+            auth_info.data_user_id(),
+            &payload.category,
+            payload.enabled,
+            &payload.delivery,
+        )
+        .await
+        {
+            Ok(preference) => {
+                info!(
+                    "Notifications of category {} set to {} (enabled: {})",
+                    preference.category, preference.delivery, preference.enabled
+                );
+                let response = SuccessResponse::new(request.id, preference);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to set notification preference: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -3,8 +3,9 @@
 //!
 //! Handlers exposed:
 //!   - `handle_register_start`       — `sazinka.auth.register.start`
-//!   - `handle_verify_email`         — `sazinka.auth.email.verify`
+//!   - `handle_verify_email`         — `sazinka.auth.email.verify` (also `sazinka.auth.verify_email`)
 //!   - `handle_resend_verification`  — `sazinka.auth.email.resend`
+//!   - `handle_password_reset`       — `sazinka.auth.password.reset`
 //!   - `handle_waitlist_join`        — `sazinka.waitlist.join`
 //!   - `handle_onboarding_profile`   — `sazinka.onboarding.profile`
 //!   - `handle_onboarding_devices`   — `sazinka.onboarding.devices`
@@ -25,6 +26,7 @@ use crate::db::queries;
 use crate::services::email_sender::EmailSender;
 use crate::services::email_templates::{AlreadyRegisteredEmail, VerificationEmail};
 use crate::services::rate_limiter::MultiRateLimiter;
+use crate::subjects;
 use crate::types::{ErrorResponse, Request, SuccessResponse};
 
 // =============================================================================
@@ -157,7 +159,7 @@ pub struct OnboardingOkResponse {
 // =============================================================================
 
 /// Validate password: min 8 chars, at least 1 uppercase, 1 lowercase, 1 digit.
-fn validate_password(password: &str) -> bool {
+pub(crate) fn validate_password(password: &str) -> bool {
     if password.len() < 8 {
         return false;
     }
@@ -169,7 +171,7 @@ fn validate_password(password: &str) -> bool {
 
 /// Generate a cryptographically random URL-safe token and its SHA-256 hash.
 /// Returns `(plain_token, hex_hash)`.
-fn generate_token() -> (String, String) {
+pub(crate) fn generate_token() -> (String, String) {
     let random_bytes: [u8; 32] = rand::random();
     let token = hex::encode(random_bytes);
     let hash = hex::encode(Sha256::digest(token.as_bytes()));
@@ -177,12 +179,12 @@ fn generate_token() -> (String, String) {
 }
 
 /// Hash a plain token with SHA-256 → hex string.
-fn hash_token(token: &str) -> String {
+pub(crate) fn hash_token(token: &str) -> String {
     hex::encode(Sha256::digest(token.as_bytes()))
 }
 
 /// Build the verification URL for a given base URL and token.
-fn build_verify_url(app_base_url: &str, token: &str) -> String {
+pub(crate) fn build_verify_url(app_base_url: &str, token: &str) -> String {
     format!("{}/verify?token={}", app_base_url.trim_end_matches('/'), token)
 }
 
@@ -604,6 +606,105 @@ pub async fn handle_resend_verification(
     Ok(())
 }
 
+// =============================================================================
+// handle_password_reset  — `sazinka.auth.password.reset`
+// Completes an admin-initiated reset with the emailed token.
+// =============================================================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordResetRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+pub async fn handle_password_reset(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    rate_limiter: Arc<MultiRateLimiter>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        let reply = match msg.reply {
+            Some(ref r) => r.clone(),
+            None => continue,
+        };
+
+        let request: Request<PasswordResetRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(r) => r,
+            Err(e) => {
+                let err = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        let token = request.payload.token.trim().to_string();
+        let bucket = token.chars().take(8).collect::<String>();
+        if !rate_limiter.check_and_record("password.reset", &bucket) {
+            let err = ErrorResponse::new(request.id, "RATE_LIMITED", "Too many requests.");
+            let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            continue;
+        }
+
+        if !validate_password(&request.payload.new_password) {
+            let err = ErrorResponse::new(
+                request.id,
+                "WEAK_PASSWORD",
+                "Password must be at least 8 characters with uppercase, lowercase, and a digit.",
+            );
+            let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            continue;
+        }
+
+        let password_hash = match crate::auth::hash_password(&request.payload.new_password) {
+            Ok(h) => h,
+            Err(e) => {
+                error!("argon2 hash error: {}", e);
+                let err = ErrorResponse::new(request.id, "INTERNAL_ERROR", "Internal error.");
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        let result = sqlx::query(
+            r#"UPDATE users
+               SET password_hash = $2,
+                   password_reset_token_hash = NULL,
+                   password_reset_expires = NULL,
+                   updated_at = now()
+               WHERE password_reset_token_hash = $1
+                 AND password_reset_expires > now()"#,
+        )
+        .bind(hash_token(&token))
+        .bind(&password_hash)
+        .execute(&pool)
+        .await;
+
+        match result {
+            Err(e) => {
+                error!("password.reset DB error: {}", e);
+                let err = ErrorResponse::new(request.id, "DB_ERROR", "Internal error.");
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            }
+            Ok(r) if r.rows_affected() == 0 => {
+                let err = ErrorResponse::new(
+                    request.id,
+                    "INVALID_OR_EXPIRED_TOKEN",
+                    "The reset link is invalid or has expired. Please ask for a new one.",
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+            }
+            Ok(_) => {
+                info!("Password reset completed");
+                let resp = SuccessResponse::new(request.id, OnboardingOkResponse { ok: true });
+                let _ = client.publish(reply, serde_json::to_vec(&resp)?.into()).await;
+            }
+        }
+    }
+    Ok(())
+}
+
 // =============================================================================
 // B7: handle_waitlist_join
 // =============================================================================
@@ -1076,7 +1177,7 @@ pub async fn handle_onboarding_complete(
             "city": d.city,
             "postal_code": d.postal_code,
         });
-        let subject: async_nats::Subject = "sazinka.geocode.address.submit".into();
+        let subject: async_nats::Subject = subjects::geocode::ADDRESS_SUBMIT.into();
         if let Err(e) = client
             .publish(subject, serde_json::to_vec(&geocode_payload).unwrap_or_default().into())
             .await
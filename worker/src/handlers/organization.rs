@@ -0,0 +1,551 @@
+//! Organization (team) handlers for NATS messages
+//!
+//! Every owner account has one organization. Owners and admins rename it,
+//! invite colleagues by email and manage member roles; members only read it.
+//! Accepting an invitation creates a worker login of the owner, so the new
+//! member shares the owner's customers, routes and devices.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use chrono::{Duration, Utc};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::account;
+use super::auth::is_duplicate_email_error;
+use super::onboarding::{generate_token, hash_token, validate_password};
+use super::{parse_authenticated, reply_error, reply_success};
+use crate::auth::{self, AuthInfo};
+use crate::db::queries;
+use crate::services::email_sender::EmailSender;
+use crate::services::email_templates::OrganizationInviteEmail;
+use crate::services::rate_limiter::MultiRateLimiter;
+use crate::subjects;
+use crate::types::organization::{
+    can_manage, AcceptInvitationRequest, CreateInvitationRequest, InvitationIdRequest, ListInvitationsResponse,
+    MemberIdRequest, Organization, OrganizationResponse, UpdateMemberRequest, UpdateOrganizationRequest,
+    INVITATION_TTL_DAYS, ORG_ROLE_OWNER,
+};
+use crate::types::user::UserPublic;
+use crate::types::Request;
+
+/// Shared by all organization handlers
+#[derive(Clone)]
+pub struct OrgContext {
+    pub pool: PgPool,
+    pub jwt_secret: Arc<String>,
+    pub email_sender: Arc<dyn EmailSender>,
+    pub app_base_url: Arc<String>,
+    pub rate_limiter: Arc<MultiRateLimiter>,
+}
+
+/// Start all organization NATS handlers
+pub async fn start_handlers(client: Client, ctx: OrgContext) -> Result<()> {
+    info!("Starting organization handlers...");
+
+    let [get_sub, update_sub, invite_create_sub, invite_list_sub, invite_revoke_sub, invite_accept_sub, member_update_sub, member_remove_sub] =
+        subjects::subscribe_all(
+            &client,
+            [
+                subjects::org::GET,
+                subjects::org::UPDATE,
+                subjects::org::INVITE_CREATE,
+                subjects::org::INVITE_LIST,
+                subjects::org::INVITE_REVOKE,
+                subjects::org::INVITE_ACCEPT,
+                subjects::org::MEMBER_UPDATE,
+                subjects::org::MEMBER_REMOVE,
+            ],
+        )
+        .await?;
+
+    tokio::spawn(handle_get(client.clone(), get_sub, ctx.clone()));
+    tokio::spawn(handle_update(client.clone(), update_sub, ctx.clone()));
+    tokio::spawn(handle_invite_create(client.clone(), invite_create_sub, ctx.clone()));
+    tokio::spawn(handle_invite_list(client.clone(), invite_list_sub, ctx.clone()));
+    tokio::spawn(handle_invite_revoke(client.clone(), invite_revoke_sub, ctx.clone()));
+    tokio::spawn(handle_invite_accept(client.clone(), invite_accept_sub, ctx.clone()));
+    tokio::spawn(handle_member_update(client.clone(), member_update_sub, ctx.clone()));
+    tokio::spawn(handle_member_remove(client, member_remove_sub, ctx));
+
+    info!("Organization handlers started");
+    Ok(())
+}
+
+/// Caller's organization and role, read from the database rather than the
+/// token claims. Replies FORBIDDEN itself for logins outside any team.
+async fn resolve_caller(
+    client: &Client,
+    pool: &PgPool,
+    reply: &async_nats::Subject,
+    request_id: Uuid,
+    auth_info: &AuthInfo,
+) -> Result<Option<(Organization, String)>> {
+    let owner_id = match (auth_info.role.as_str(), auth_info.owner_id) {
+        ("customer" | "admin", _) => auth_info.user_id,
+        ("worker", Some(owner_id)) => owner_id,
+        _ => {
+            reply_error(client, reply, request_id, "FORBIDDEN", "Login is not part of a team").await?;
+            return Ok(None);
+        }
+    };
+
+    let resolved = async {
+        let organization = queries::organization::ensure_for_owner(pool, owner_id).await?;
+        if owner_id == auth_info.user_id {
+            return Ok((organization, ORG_ROLE_OWNER.to_string()));
+        }
+        // Workers created before organizations existed join on first use
+        let membership = match queries::organization::get_membership(pool, auth_info.user_id).await? {
+            Some(membership) => membership,
+            None => queries::organization::add_worker(pool, owner_id, auth_info.user_id).await?,
+        };
+        anyhow::Ok((organization, membership.role))
+    }
+    .await;
+
+    match resolved {
+        Ok(resolved) => Ok(Some(resolved)),
+        Err(e) => {
+            error!("Failed to resolve organization of {}: {}", auth_info.user_id, e);
+            reply_error(client, reply, request_id, "DATABASE_ERROR", e.to_string()).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Like `resolve_caller`, but only for owners and admins
+async fn resolve_manager(
+    client: &Client,
+    pool: &PgPool,
+    reply: &async_nats::Subject,
+    request_id: Uuid,
+    auth_info: &AuthInfo,
+) -> Result<Option<Organization>> {
+    let Some((organization, role)) = resolve_caller(client, pool, reply, request_id, auth_info).await? else {
+        return Ok(None);
+    };
+    if !can_manage(&role) {
+        reply_error(client, reply, request_id, "FORBIDDEN", "Only team owners and admins can manage the team").await?;
+        return Ok(None);
+    }
+    Ok(Some(organization))
+}
+
+async fn build_response(pool: &PgPool, organization: Organization, role: String) -> Result<OrganizationResponse> {
+    let members = queries::organization::list_members(pool, organization.id).await?;
+    let invitations = if can_manage(&role) {
+        queries::organization::list_invitations(pool, organization.id).await?
+    } else {
+        Vec::new()
+    };
+    Ok(OrganizationResponse { organization, role, members, invitations })
+}
+
+/// Handle org.get messages
+pub async fn handle_get(client: Client, mut subscriber: Subscriber, ctx: OrgContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received org.get message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<serde_json::Value>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let Some((organization, role)) = resolve_caller(&client, &ctx.pool, &reply, request.id, &auth_info).await?
+        else {
+            continue;
+        };
+
+        match build_response(&ctx.pool, organization, role).await {
+            Ok(response) => reply_success(&client, &reply, request.id, response).await?,
+            Err(e) => {
+                error!("Failed to load organization: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle org.update messages
+pub async fn handle_update(client: Client, mut subscriber: Subscriber, ctx: OrgContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received org.update message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<UpdateOrganizationRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        if let Err(msg) = request.payload.validate() {
+            reply_error(&client, &reply, request.id, "INVALID_REQUEST", msg).await?;
+            continue;
+        }
+        let Some((organization, role)) = resolve_caller(&client, &ctx.pool, &reply, request.id, &auth_info).await?
+        else {
+            continue;
+        };
+        if !can_manage(&role) {
+            reply_error(&client, &reply, request.id, "FORBIDDEN", "Only team owners and admins can manage the team").await?;
+            continue;
+        }
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, organization.owner_user_id).await? {
+            continue;
+        }
+
+        let result = async {
+            let organization =
+                queries::organization::update_name(&ctx.pool, organization.id, request.payload.name.trim()).await?;
+            build_response(&ctx.pool, organization, role).await
+        }
+        .await;
+        match result {
+            Ok(response) => reply_success(&client, &reply, request.id, response).await?,
+            Err(e) => {
+                error!("Failed to update organization: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle org.invite.create messages - stores the invitation and emails the link
+pub async fn handle_invite_create(client: Client, mut subscriber: Subscriber, ctx: OrgContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received org.invite.create message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<CreateInvitationRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        if let Err(msg) = request.payload.validate() {
+            reply_error(&client, &reply, request.id, "INVALID_REQUEST", msg).await?;
+            continue;
+        }
+        let Some(organization) = resolve_manager(&client, &ctx.pool, &reply, request.id, &auth_info).await? else {
+            continue;
+        };
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, organization.owner_user_id).await? {
+            continue;
+        }
+
+        let email = request.payload.email.trim().to_lowercase();
+        let inviter = match queries::user::get_user_by_email(&ctx.pool, &email).await {
+            Ok(Some(_)) => {
+                reply_error(&client, &reply, request.id, "DUPLICATE_EMAIL", "Email is already registered").await?;
+                continue;
+            }
+            Ok(None) => queries::user::get_user(&ctx.pool, auth_info.user_id).await,
+            Err(e) => Err(e),
+        };
+        let inviter = match inviter {
+            Ok(Some(inviter)) => inviter,
+            Ok(None) => {
+                reply_error(&client, &reply, request.id, "NOT_FOUND", "User not found").await?;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load inviter {}: {}", auth_info.user_id, e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+                continue;
+            }
+        };
+
+        let (token, token_hash) = generate_token();
+        let expires_at = Utc::now() + Duration::days(INVITATION_TTL_DAYS);
+        let invitation = match queries::organization::create_invitation(
+            &ctx.pool,
+            organization.id,
+            &email,
+            &request.payload.role,
+            &token_hash,
+            auth_info.user_id,
+            expires_at,
+        )
+        .await
+        {
+            Ok(invitation) => invitation,
+            Err(e) => {
+                error!("Failed to create invitation: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+                continue;
+            }
+        };
+
+        let accept_url = format!("{}/join?token={}", ctx.app_base_url.trim_end_matches('/'), token);
+        let email_msg = OrganizationInviteEmail {
+            to: &email,
+            organization_name: &organization.name,
+            inviter_name: &inviter.name,
+            accept_url: &accept_url,
+            valid_days: INVITATION_TTL_DAYS,
+            locale: &inviter.locale,
+        }
+        .render();
+        if let Err(e) = ctx.email_sender.send(email_msg).await {
+            warn!("Failed to send invitation email to {}: {}", email, e);
+            let _ = queries::organization::revoke_invitation(&ctx.pool, organization.id, invitation.id).await;
+            reply_error(&client, &reply, request.id, "EMAIL_FAILED", e.to_string()).await?;
+            continue;
+        }
+
+        info!("User {} invited {} to organization {}", auth_info.user_id, email, organization.id);
+        reply_success(&client, &reply, request.id, invitation).await?;
+    }
+
+    Ok(())
+}
+
+/// Handle org.invite.list messages
+pub async fn handle_invite_list(client: Client, mut subscriber: Subscriber, ctx: OrgContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received org.invite.list message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<serde_json::Value>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let Some(organization) = resolve_manager(&client, &ctx.pool, &reply, request.id, &auth_info).await? else {
+            continue;
+        };
+
+        match queries::organization::list_invitations(&ctx.pool, organization.id).await {
+            Ok(invitations) => {
+                reply_success(&client, &reply, request.id, ListInvitationsResponse { invitations }).await?
+            }
+            Err(e) => {
+                error!("Failed to list invitations: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle org.invite.revoke messages
+pub async fn handle_invite_revoke(client: Client, mut subscriber: Subscriber, ctx: OrgContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received org.invite.revoke message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<InvitationIdRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let Some(organization) = resolve_manager(&client, &ctx.pool, &reply, request.id, &auth_info).await? else {
+            continue;
+        };
+
+        match queries::organization::revoke_invitation(&ctx.pool, organization.id, request.payload.id).await {
+            Ok(true) => reply_success(&client, &reply, request.id, serde_json::json!({ "deleted": true })).await?,
+            Ok(false) => reply_error(&client, &reply, request.id, "NOT_FOUND", "Invitation not found").await?,
+            Err(e) => {
+                error!("Failed to revoke invitation: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle org.invite.accept messages - public, authorized by the emailed token
+pub async fn handle_invite_accept(client: Client, mut subscriber: Subscriber, ctx: OrgContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received org.invite.accept message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let request: Request<AcceptInvitationRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                reply_error(&client, &reply, Uuid::nil(), "INVALID_REQUEST", e.to_string()).await?;
+                continue;
+            }
+        };
+
+        let token = request.payload.token.trim().to_string();
+        let bucket = token.chars().take(8).collect::<String>();
+        if !ctx.rate_limiter.check_and_record("org.invite.accept", &bucket) {
+            reply_error(&client, &reply, request.id, "RATE_LIMITED", "Too many requests.").await?;
+            continue;
+        }
+        if let Err(msg) = request.payload.validate() {
+            reply_error(&client, &reply, request.id, "INVALID_REQUEST", msg).await?;
+            continue;
+        }
+        if !validate_password(&request.payload.password) {
+            reply_error(
+                &client,
+                &reply,
+                request.id,
+                "WEAK_PASSWORD",
+                "Password must be at least 8 characters with uppercase, lowercase, and a digit.",
+            )
+            .await?;
+            continue;
+        }
+
+        let invitation = match queries::organization::find_invitation(&ctx.pool, &hash_token(&token)).await {
+            Ok(Some(invitation)) => invitation,
+            Ok(None) => {
+                reply_error(
+                    &client,
+                    &reply,
+                    request.id,
+                    "INVALID_OR_EXPIRED_TOKEN",
+                    "The invitation is invalid or has expired. Please ask for a new one.",
+                )
+                .await?;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load invitation: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+                continue;
+            }
+        };
+
+        let password_hash = match auth::hash_password(&request.payload.password) {
+            Ok(hash) => hash,
+            Err(e) => {
+                error!("argon2 hash error: {}", e);
+                reply_error(&client, &reply, request.id, "INTERNAL_ERROR", "Internal error.").await?;
+                continue;
+            }
+        };
+
+        let name = request.payload.name.trim();
+        let user = match queries::organization::accept_invitation(&ctx.pool, &invitation, name, &password_hash).await {
+            Ok(Some(user_id)) => queries::user::get_user(&ctx.pool, user_id).await,
+            Ok(None) => {
+                reply_error(&client, &reply, request.id, "INVALID_OR_EXPIRED_TOKEN", "The invitation was already used.")
+                    .await?;
+                continue;
+            }
+            Err(e) if is_duplicate_email_error(&e) => {
+                reply_error(&client, &reply, request.id, "DUPLICATE_EMAIL", "Email is already registered").await?;
+                continue;
+            }
+            Err(e) => Err(e),
+        };
+        match user {
+            Ok(Some(user)) => {
+                info!("User {} joined organization {}", user.id, invitation.organization_id);
+                reply_success(&client, &reply, request.id, UserPublic::from(user)).await?;
+            }
+            Ok(None) => reply_error(&client, &reply, request.id, "NOT_FOUND", "User not found").await?,
+            Err(e) => {
+                error!("Failed to accept invitation: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle org.member.update messages
+pub async fn handle_member_update(client: Client, mut subscriber: Subscriber, ctx: OrgContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received org.member.update message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<UpdateMemberRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        if let Err(msg) = request.payload.validate() {
+            reply_error(&client, &reply, request.id, "INVALID_REQUEST", msg).await?;
+            continue;
+        }
+        let Some(organization) = resolve_manager(&client, &ctx.pool, &reply, request.id, &auth_info).await? else {
+            continue;
+        };
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, organization.owner_user_id).await? {
+            continue;
+        }
+
+        let UpdateMemberRequest { user_id, role } = &request.payload;
+        match queries::organization::set_member_role(&ctx.pool, organization.id, *user_id, role).await {
+            Ok(true) => reply_success(&client, &reply, request.id, serde_json::json!({ "updated": true })).await?,
+            Ok(false) => reply_error(&client, &reply, request.id, "NOT_FOUND", "Member not found").await?,
+            Err(e) => {
+                error!("Failed to update member role: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle org.member.remove messages - deletes the member's login
+pub async fn handle_member_remove(client: Client, mut subscriber: Subscriber, ctx: OrgContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received org.member.remove message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<MemberIdRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        if request.payload.user_id == auth_info.user_id {
+            reply_error(&client, &reply, request.id, "INVALID_REQUEST", "You cannot remove yourself").await?;
+            continue;
+        }
+        let Some(organization) = resolve_manager(&client, &ctx.pool, &reply, request.id, &auth_info).await? else {
+            continue;
+        };
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, organization.owner_user_id).await? {
+            continue;
+        }
+
+        match queries::organization::remove_member(&ctx.pool, organization.id, request.payload.user_id).await {
+            Ok(true) => {
+                info!("User {} removed member {} from organization {}", auth_info.user_id, request.payload.user_id, organization.id);
+                reply_success(&client, &reply, request.id, serde_json::json!({ "deleted": true })).await?
+            }
+            Ok(false) => reply_error(&client, &reply, request.id, "NOT_FOUND", "Member not found").await?,
+            Err(e) => {
+                error!("Failed to remove member: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,253 @@
+//! Presence handlers for NATS messages
+//!
+//! Lets the UI show who else has a route, customer or visit open, so two
+//! dispatchers notice each other before optimistic locking has to reject
+//! one of the saves.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::presence::{PresenceRegistry, HEARTBEAT_INTERVAL};
+use crate::subjects;
+use crate::types::presence::{
+    validate_entity_type, PresenceHeartbeatRequest, PresenceLeaveRequest, PresenceListRequest,
+    PresenceResponse, PresenceViewer,
+};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Start all presence NATS handlers
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting presence handlers...");
+
+    let [heartbeat_sub, leave_sub, list_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::presence::HEARTBEAT,
+            subjects::presence::LEAVE,
+            subjects::presence::LIST,
+        ],
+    )
+    .await?;
+
+    let registry = Arc::new(PresenceRegistry::new());
+
+    tokio::spawn(handle_heartbeat(
+        client.clone(),
+        heartbeat_sub,
+        pool,
+        jwt_secret.clone(),
+        registry.clone(),
+    ));
+    tokio::spawn(handle_leave(client.clone(), leave_sub, jwt_secret.clone(), registry.clone()));
+    tokio::spawn(handle_list(client.clone(), list_sub, jwt_secret, registry));
+
+    info!("Presence handlers started");
+    Ok(())
+}
+
+fn response(viewers: Vec<PresenceViewer>) -> PresenceResponse {
+    PresenceResponse { viewers, heartbeat_seconds: HEARTBEAT_INTERVAL.as_secs() }
+}
+
+/// Handle presence.heartbeat messages - record the session, return the others
+pub async fn handle_heartbeat(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    registry: Arc<PresenceRegistry>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received presence.heartbeat message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<PresenceHeartbeatRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = &request.payload;
+        if let Err(msg) = payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let account_id = auth_info.data_user_id();
+
+        // The name is only needed the first time a session shows up on an entity
+        let name = if registry.is_known(account_id, &payload.entity_type, payload.entity_id, &payload.session_id) {
+            String::new()
+        } else {
+            match queries::user::get_user(&pool, auth_info.user_id).await {
+                Ok(Some(user)) => user.name,
+                Ok(None) => {
+                    let error = ErrorResponse::new(request.id, "NOT_FOUND", "User not found");
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to load user for presence: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            }
+        };
+
+        let viewers = registry.heartbeat(
+            account_id,
+            &payload.entity_type,
+            payload.entity_id,
+            &payload.session_id,
+            auth_info.user_id,
+            &name,
+            payload.editing,
+        );
+        let response = SuccessResponse::new(request.id, response(viewers));
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle presence.leave messages
+pub async fn handle_leave(
+    client: Client,
+    mut subscriber: Subscriber,
+    jwt_secret: Arc<String>,
+    registry: Arc<PresenceRegistry>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received presence.leave message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<PresenceLeaveRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let account_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = &request.payload;
+        let entity = match (&payload.entity_type, payload.entity_id) {
+            (Some(entity_type), Some(entity_id)) => Some((entity_type.as_str(), entity_id)),
+            (None, None) => None,
+            _ => {
+                let error = ErrorResponse::new(
+                    request.id,
+                    "INVALID_REQUEST",
+                    "entityType and entityId must be given together",
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        registry.leave(account_id, &payload.session_id, entity);
+        let response = SuccessResponse::new(request.id, serde_json::json!({ "left": true }));
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle presence.list messages - everyone on an entity, without joining it
+pub async fn handle_list(
+    client: Client,
+    mut subscriber: Subscriber,
+    jwt_secret: Arc<String>,
+    registry: Arc<PresenceRegistry>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received presence.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<PresenceListRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let account_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = &request.payload;
+        if let Err(msg) = validate_entity_type(&payload.entity_type) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let viewers = registry.list(account_id, &payload.entity_type, payload.entity_id);
+        let response = SuccessResponse::new(request.id, response(viewers));
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+//! Data quality report handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use chrono::Utc;
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::subjects;
+use crate::types::quality::{calling_code, normalize_phone, QualityFinding, QualityReport, QualityReportRequest, QualityRule};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Start all data quality NATS handlers
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting quality handlers...");
+
+    let report_sub = client.subscribe(subjects::quality::REPORT).await?;
+
+    tokio::spawn(handle_report(client.clone(), report_sub, pool, jwt_secret));
+
+    info!("Quality handlers started");
+    Ok(())
+}
+
+/// Handle quality.report messages
+pub async fn handle_report(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received quality.report message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<QualityReportRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if let Err(msg) = request.payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match build_report(&pool, user_id, &request.payload).await {
+            Ok(report) => {
+                let response = SuccessResponse::new(request.id, report);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to build quality report: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the requested rules and collect their findings
+pub async fn build_report(pool: &PgPool, user_id: Uuid, request: &QualityReportRequest) -> Result<QualityReport> {
+    let limit = request.item_limit();
+    let mut findings = Vec::new();
+
+    for rule in request.effective_rules() {
+        let (count, mut items) = match rule {
+            QualityRule::CustomerMissingCoordinates => {
+                queries::quality::customers_missing_coordinates(pool, user_id, limit).await?
+            }
+            QualityRule::DeviceMissingInterval => queries::quality::devices_missing_interval(pool, user_id, limit).await?,
+            QualityRule::OverdueWithoutContact => {
+                queries::quality::overdue_without_contact(pool, user_id, request.grace_days(), limit).await?
+            }
+            QualityRule::InvalidPhoneFormat => queries::quality::invalid_phone_numbers(pool, user_id, limit).await?,
+        };
+
+        if rule == QualityRule::InvalidPhoneFormat && !items.is_empty() {
+            let country = queries::user::get_country(pool, user_id).await?;
+            let code = calling_code(country.as_deref());
+            for item in &mut items {
+                item.suggested_value = item.detail.as_deref().and_then(|phone| normalize_phone(phone, code));
+            }
+        }
+
+        findings.push(QualityFinding {
+            rule,
+            severity: rule.severity(),
+            count,
+            items,
+            fix: rule.fix(),
+        });
+    }
+
+    Ok(QualityReport {
+        generated_at: Utc::now(),
+        findings,
+    })
+}
@@ -0,0 +1,945 @@
+//! Quote and service catalog handlers for NATS messages
+//!
+//! Dispatchers keep a catalog of priced services and build quotes from it.
+//! `sazinka.quote.send` returns a tokenized link the customer opens without
+//! logging in (`sazinka.portal.quote.*`) to accept or decline the quote. An
+//! accepted quote becomes a work order: an open planned action listing the
+//! quoted items. Sending and the customer's decision are logged as
+//! communications, so they show up on the customer timeline, and move a
+//! lead through the pipeline (quoted, won).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use base64::Engine;
+use chrono::{Duration, Utc};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::account;
+use super::onboarding::generate_token;
+use super::{parse_authenticated, parse_portal};
+use crate::db::queries;
+use crate::services::demo_mode;
+use crate::services::notification_dispatch;
+use crate::services::quote::{
+    quote_totals, render_quote_pdf, resolve_items, DEFAULT_QUOTE_VALIDITY_DAYS,
+    QUOTE_COMMUNICATION_SUBJECT,
+};
+use crate::services::rate_limiter::{MultiRateLimiter, RateLimiterConfig};
+use crate::subjects;
+use crate::types::catalog::{
+    validate_catalog_item, CreateCatalogItemRequest, ListCatalogItemsRequest, UpdateCatalogItemRequest,
+};
+use crate::types::lead::{validate_lead_transition, LEAD_STATUS_QUOTED, LEAD_STATUS_WON};
+use crate::types::notification::{NOTIFICATION_CATEGORY_SYSTEM, NOTIFICATION_KIND_QUOTE};
+use crate::types::quote::{
+    validate_quote_header, CreateQuoteRequest, ListQuotesRequest, ListQuotesResponse, PortalQuoteDecisionRequest,
+    PortalQuoteItem, PortalQuoteRequest, PortalQuoteResponse, Quote, QuoteDetail, QuoteIdRequest, QuoteItemInput,
+    QuoteLinkResponse, QuotePdfResponse, UpdateQuoteRequest, MAX_DECLINE_REASON_LENGTH, QUOTE_STATUS_ACCEPTED,
+    QUOTE_STATUS_DRAFT, QUOTE_STATUS_SENT,
+};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Shared by all quote handlers
+#[derive(Clone)]
+pub struct QuoteContext {
+    pub pool: PgPool,
+    pub jwt_secret: Arc<String>,
+    pub app_base_url: Arc<String>,
+    pub rate_limiter: Arc<MultiRateLimiter>,
+}
+
+/// Start all quote and catalog NATS handlers
+pub async fn start_handlers(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    app_base_url: Arc<String>,
+) -> Result<()> {
+    info!("Starting quote handlers...");
+
+    let rate_limiter = Arc::new(MultiRateLimiter::new(vec![
+        (
+            "quote.view",
+            RateLimiterConfig {
+                max_attempts: 30,
+                window_secs: 300,
+            },
+        ),
+        (
+            "quote.decide",
+            RateLimiterConfig {
+                max_attempts: 5,
+                window_secs: 3600,
+            },
+        ),
+    ]));
+    let ctx = QuoteContext { pool, jwt_secret, app_base_url, rate_limiter };
+
+    let [
+        catalog_create_sub,
+        catalog_list_sub,
+        catalog_update_sub,
+        create_sub,
+        get_sub,
+        list_sub,
+        update_sub,
+        delete_sub,
+        send_sub,
+        pdf_sub,
+        portal_get_sub,
+        portal_decide_sub,
+    ] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::catalog::CREATE,
+            subjects::catalog::LIST,
+            subjects::catalog::UPDATE,
+            subjects::quote::CREATE,
+            subjects::quote::GET,
+            subjects::quote::LIST,
+            subjects::quote::UPDATE,
+            subjects::quote::DELETE,
+            subjects::quote::SEND,
+            subjects::quote::PDF,
+            subjects::portal::QUOTE_GET,
+            subjects::portal::QUOTE_DECIDE,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_catalog_create(client.clone(), catalog_create_sub, ctx.clone()));
+    tokio::spawn(handle_catalog_list(client.clone(), catalog_list_sub, ctx.clone()));
+    tokio::spawn(handle_catalog_update(client.clone(), catalog_update_sub, ctx.clone()));
+    tokio::spawn(handle_create(client.clone(), create_sub, ctx.clone()));
+    tokio::spawn(handle_get(client.clone(), get_sub, ctx.clone()));
+    tokio::spawn(handle_list(client.clone(), list_sub, ctx.clone()));
+    tokio::spawn(handle_update(client.clone(), update_sub, ctx.clone()));
+    tokio::spawn(handle_delete(client.clone(), delete_sub, ctx.clone()));
+    tokio::spawn(handle_send(client.clone(), send_sub, ctx.clone()));
+    tokio::spawn(handle_pdf(client.clone(), pdf_sub, ctx.clone()));
+    tokio::spawn(handle_portal_get(client.clone(), portal_get_sub, ctx.clone()));
+    tokio::spawn(handle_portal_decide(client.clone(), portal_decide_sub, ctx));
+
+    info!("Quote handlers started");
+    Ok(())
+}
+
+/// Build the portal URL for an acceptance token
+fn build_quote_url(app_base_url: &str, token: &str) -> String {
+    format!("{}/portal/quote?token={}", app_base_url.trim_end_matches('/'), token)
+}
+
+/// Hash of an acceptance token, as stored
+fn hash_quote_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(token.trim().as_bytes()))
+}
+
+/// Parse a portal request, rate limit it and resolve its token to the
+/// quote. Replies with the error itself.
+async fn parse_portal_quote<T: serde::de::DeserializeOwned>(
+    client: &Client,
+    reply: &async_nats::Subject,
+    payload: &[u8],
+    ctx: &QuoteContext,
+    limiter: &str,
+    token_of: impl Fn(&T) -> &str,
+) -> Result<Option<(Request<T>, Quote)>> {
+    let Some((request, token)) = parse_portal(client, reply, payload, &ctx.rate_limiter, limiter, token_of).await?
+    else {
+        return Ok(None);
+    };
+
+    match queries::quote::find_by_token_hash(&ctx.pool, &hash_quote_token(&token)).await {
+        Ok(Some(quote)) => Ok(Some((request, quote))),
+        Ok(None) => {
+            let error = ErrorResponse::new(request.id, "INVALID_TOKEN", "Link is invalid");
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            Ok(None)
+        }
+        Err(e) => {
+            error!("Failed to resolve quote link: {}", e);
+            let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            Ok(None)
+        }
+    }
+}
+
+/// Account currency, falling back to the default
+async fn account_currency(pool: &PgPool, user_id: Uuid) -> Result<String> {
+    let settings = queries::settings::get_user_settings(pool, user_id).await?;
+    Ok(settings
+        .map(|s| s.currency)
+        .unwrap_or_else(|| crate::types::currency::DEFAULT_CURRENCY.to_string()))
+}
+
+/// Catalog items referenced by quote lines
+async fn load_catalog(pool: &PgPool, user_id: Uuid, items: &[QuoteItemInput]) -> Result<HashMap<Uuid, crate::types::catalog::CatalogItem>> {
+    let ids: Vec<Uuid> = items.iter().filter_map(|i| i.catalog_item_id).collect();
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let items = queries::catalog::get_active_items(pool, user_id, &ids).await?;
+    Ok(items.into_iter().map(|i| (i.id, i)).collect())
+}
+
+/// Quote with lines and totals
+async fn load_detail(pool: &PgPool, quote: Quote) -> Result<QuoteDetail> {
+    let items = queries::quote::list_items(pool, quote.id).await?;
+    let totals = quote_totals(&items);
+    let mut quote = quote;
+    quote.status = quote.effective_status(Utc::now().date_naive()).to_string();
+    Ok(QuoteDetail { quote, items, totals })
+}
+
+/// Log a quote event on the customer timeline; failures only cost the entry
+async fn log_on_timeline(pool: &PgPool, quote: &Quote, direction: &str, text: &str) {
+    if let Err(e) = queries::communication::create_communication(
+        pool,
+        quote.user_id,
+        quote.customer_id,
+        None,
+        "note",
+        direction,
+        Some(QUOTE_COMMUNICATION_SUBJECT),
+        text,
+        None,
+        None,
+        None,
+    )
+    .await
+    {
+        warn!("Failed to log quote {} on customer {}: {}", quote.id, quote.customer_id, e);
+    }
+}
+
+/// Move the customer's lead to `to` when the pipeline allows it
+async fn advance_lead(pool: &PgPool, user_id: Uuid, customer_id: Uuid, to: &str, changed_by: Option<Uuid>) {
+    let advanced = async {
+        let Some(customer) = queries::customer::get_customer(pool, user_id, customer_id).await? else {
+            return anyhow::Ok(());
+        };
+        let from = customer.lead_status.as_deref();
+        if from.is_some() && from != Some(to) && validate_lead_transition(from, to).is_ok() {
+            queries::lead::transition_status(pool, user_id, customer_id, from, to, None, changed_by).await?;
+        }
+        Ok(())
+    }
+    .await;
+    if let Err(e) = advanced {
+        warn!("Failed to move lead {} to {}: {}", customer_id, to, e);
+    }
+}
+
+/// Handle catalog.create messages
+pub async fn handle_catalog_create(client: Client, mut subscriber: Subscriber, ctx: QuoteContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received catalog.create message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<CreateCatalogItemRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+        let payload = &request.payload;
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let currency = match account_currency(&ctx.pool, user_id).await {
+            Ok(currency) => currency,
+            Err(e) => {
+                error!("Failed to load account currency: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        let (unit, currency) = match validate_catalog_item(
+            &payload.name,
+            payload.unit.as_deref(),
+            payload.unit_price_minor,
+            payload.vat_rate,
+            payload.currency.as_deref(),
+            &currency,
+        ) {
+            Ok(fields) => fields,
+            Err(msg) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::catalog::create_item(&ctx.pool, user_id, payload, &unit, &currency).await {
+            Ok(item) => {
+                let response = SuccessResponse::new(request.id, item);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to create catalog item: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle catalog.list messages
+pub async fn handle_catalog_list(client: Client, mut subscriber: Subscriber, ctx: QuoteContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received catalog.list message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<ListCatalogItemsRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        match queries::catalog::list_items(&ctx.pool, user_id, request.payload.include_inactive).await {
+            Ok(items) => {
+                let response = SuccessResponse::new(request.id, items);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list catalog items: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle catalog.update messages
+pub async fn handle_catalog_update(client: Client, mut subscriber: Subscriber, ctx: QuoteContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received catalog.update message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<UpdateCatalogItemRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+        let payload = &request.payload;
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let currency = match account_currency(&ctx.pool, user_id).await {
+            Ok(currency) => currency,
+            Err(e) => {
+                error!("Failed to load account currency: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        let (unit, currency) = match validate_catalog_item(
+            &payload.name,
+            payload.unit.as_deref(),
+            payload.unit_price_minor,
+            payload.vat_rate,
+            payload.currency.as_deref(),
+            &currency,
+        ) {
+            Ok(fields) => fields,
+            Err(msg) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::catalog::update_item(&ctx.pool, user_id, payload, &unit, &currency).await {
+            Ok(Some(item)) => {
+                let response = SuccessResponse::new(request.id, item);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Catalog item not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to update catalog item: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle quote.create messages - new draft in the account currency
+pub async fn handle_create(client: Client, mut subscriber: Subscriber, ctx: QuoteContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received quote.create message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<CreateQuoteRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+        let payload = &request.payload;
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let today = Utc::now().date_naive();
+        let valid_until = payload.valid_until.unwrap_or(today + Duration::days(DEFAULT_QUOTE_VALIDITY_DAYS));
+        if let Err(msg) = validate_quote_header(&payload.title, &payload.items, valid_until, today) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let loaded = async {
+            let customer = queries::customer::get_customer(&ctx.pool, user_id, payload.customer_id).await?;
+            let currency = account_currency(&ctx.pool, user_id).await?;
+            let catalog = load_catalog(&ctx.pool, user_id, &payload.items).await?;
+            anyhow::Ok((customer, currency, catalog))
+        }
+        .await;
+        let (currency, catalog) = match loaded {
+            Ok((Some(_), currency, catalog)) => (currency, catalog),
+            Ok((None, _, _)) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to prepare quote: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let items = match resolve_items(&payload.items, &catalog, &currency) {
+            Ok(items) => items,
+            Err(msg) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let created = async {
+            let quote = queries::quote::create_quote(
+                &ctx.pool,
+                user_id,
+                payload.customer_id,
+                &payload.title,
+                payload.note.as_deref(),
+                &currency,
+                valid_until,
+                &items,
+            )
+            .await?;
+            load_detail(&ctx.pool, quote).await
+        }
+        .await;
+        match created {
+            Ok(detail) => {
+                info!("Created quote {} for customer {}", detail.quote.quote_number, detail.quote.customer_id);
+                let response = SuccessResponse::new(request.id, detail);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to create quote: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle quote.get messages
+pub async fn handle_get(client: Client, mut subscriber: Subscriber, ctx: QuoteContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received quote.get message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<QuoteIdRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        let loaded = async {
+            match queries::quote::get_quote(&ctx.pool, user_id, request.payload.id).await? {
+                Some(quote) => Ok(Some(load_detail(&ctx.pool, quote).await?)),
+                None => anyhow::Ok(None),
+            }
+        }
+        .await;
+        match loaded {
+            Ok(Some(detail)) => {
+                let response = SuccessResponse::new(request.id, demo_mode::apply(request.demo_mode, detail));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Quote not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to load quote: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle quote.list messages
+pub async fn handle_list(client: Client, mut subscriber: Subscriber, ctx: QuoteContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received quote.list message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<ListQuotesRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        match queries::quote::list_quotes(&ctx.pool, user_id, &request.payload).await {
+            Ok((items, total)) => {
+                let items = demo_mode::apply(request.demo_mode, items);
+                let response = SuccessResponse::new(request.id, ListQuotesResponse { items, total });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list quotes: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle quote.update messages - drafts only
+pub async fn handle_update(client: Client, mut subscriber: Subscriber, ctx: QuoteContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received quote.update message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<UpdateQuoteRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+        let payload = &request.payload;
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let today = Utc::now().date_naive();
+        if let Err(msg) = validate_quote_header(&payload.title, &payload.items, payload.valid_until, today) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let loaded = async {
+            let quote = queries::quote::get_quote(&ctx.pool, user_id, payload.id).await?;
+            let catalog = load_catalog(&ctx.pool, user_id, &payload.items).await?;
+            anyhow::Ok((quote, catalog))
+        }
+        .await;
+        let (quote, catalog) = match loaded {
+            Ok((Some(quote), catalog)) if quote.status == QUOTE_STATUS_DRAFT => (quote, catalog),
+            Ok((Some(_), _)) => {
+                let error = ErrorResponse::new(request.id, "INVALID_STATE", "Only draft quotes can be edited");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Ok((None, _)) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Quote not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load quote: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let items = match resolve_items(&payload.items, &catalog, &quote.currency) {
+            Ok(items) => items,
+            Err(msg) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let updated = async {
+            let updated = queries::quote::update_draft(
+                &ctx.pool,
+                user_id,
+                quote.id,
+                &payload.title,
+                payload.note.as_deref(),
+                payload.valid_until,
+                &items,
+            )
+            .await?;
+            if !updated {
+                return anyhow::Ok(None);
+            }
+            match queries::quote::get_quote(&ctx.pool, user_id, quote.id).await? {
+                Some(quote) => Ok(Some(load_detail(&ctx.pool, quote).await?)),
+                None => Ok(None),
+            }
+        }
+        .await;
+        match updated {
+            Ok(Some(detail)) => {
+                let response = SuccessResponse::new(request.id, detail);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "INVALID_STATE", "Only draft quotes can be edited");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to update quote: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle quote.delete messages - drafts only
+pub async fn handle_delete(client: Client, mut subscriber: Subscriber, ctx: QuoteContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received quote.delete message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<QuoteIdRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        match queries::quote::delete_draft(&ctx.pool, user_id, request.payload.id).await {
+            Ok(true) => {
+                let response = SuccessResponse::new(request.id, serde_json::json!({ "deleted": true }));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(false) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Draft quote not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to delete quote: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle quote.send messages - issue the acceptance link. Sending again
+/// replaces the link; the earlier one stops working.
+pub async fn handle_send(client: Client, mut subscriber: Subscriber, ctx: QuoteContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received quote.send message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<QuoteIdRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let today = Utc::now().date_naive();
+        match queries::quote::get_quote(&ctx.pool, user_id, request.payload.id).await {
+            Ok(Some(quote)) if quote.valid_until < today => {
+                let error = ErrorResponse::new(request.id, "INVALID_STATE", "The quote has expired");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Ok(Some(quote)) if quote.status == QUOTE_STATUS_DRAFT || quote.status == QUOTE_STATUS_SENT => {}
+            Ok(Some(_)) => {
+                let error = ErrorResponse::new(request.id, "INVALID_STATE", "The quote has already been decided");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Quote not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load quote: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        let (token, token_hash) = generate_token();
+        let quote = match queries::quote::mark_sent(&ctx.pool, user_id, request.payload.id, &token_hash).await {
+            Ok(Some(quote)) => quote,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "INVALID_STATE", "The quote has already been decided");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to send quote: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let response = SuccessResponse::new(request.id, QuoteLinkResponse {
+            url: build_quote_url(&ctx.app_base_url, &token),
+            valid_until: quote.valid_until,
+        });
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+
+        info!("Sent quote {} to customer {}", quote.quote_number, quote.customer_id);
+        log_on_timeline(&ctx.pool, &quote, "outbound", &format!("Quote {} sent: {}", quote.quote_number, quote.title)).await;
+        advance_lead(&ctx.pool, user_id, quote.customer_id, LEAD_STATUS_QUOTED, Some(auth_info.user_id)).await;
+    }
+
+    Ok(())
+}
+
+/// Handle quote.pdf messages - the quote as a PDF in the company language
+pub async fn handle_pdf(client: Client, mut subscriber: Subscriber, ctx: QuoteContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received quote.pdf message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<QuoteIdRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        let loaded = async {
+            let Some(quote) = queries::quote::get_quote(&ctx.pool, user_id, request.payload.id).await? else {
+                return anyhow::Ok(None);
+            };
+            let items = queries::quote::list_items(&ctx.pool, quote.id).await?;
+            let customer = queries::customer::get_customer(&ctx.pool, user_id, quote.customer_id).await?;
+            let settings = queries::settings::get_user_settings(&ctx.pool, user_id).await?;
+            Ok(Some((quote, items, customer, settings)))
+        }
+        .await;
+
+        match loaded {
+            Ok(Some((quote, items, customer, Some(settings)))) => {
+                let customer_name = customer.and_then(|c| c.name).unwrap_or_default();
+                let pdf = render_quote_pdf(&quote, &items, &settings, &customer_name, &settings.company_locale);
+                let response = SuccessResponse::new(request.id, QuotePdfResponse {
+                    filename: format!("{}.pdf", quote.quote_number),
+                    content_base64: base64::engine::general_purpose::STANDARD.encode(pdf),
+                });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(_) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Quote not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to render quote PDF: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle portal.quote.get messages - the quote behind a link
+pub async fn handle_portal_get(client: Client, mut subscriber: Subscriber, ctx: QuoteContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received portal.quote.get message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, quote)) =
+            parse_portal_quote::<PortalQuoteRequest>(&client, &reply, &msg.payload, &ctx, "quote.view", |p| p.token.as_str())
+                .await?
+        else {
+            continue;
+        };
+
+        let loaded = async {
+            let items = queries::quote::list_items(&ctx.pool, quote.id).await?;
+            let settings = queries::settings::get_user_settings(&ctx.pool, quote.user_id).await?;
+            anyhow::Ok((items, settings))
+        }
+        .await;
+
+        match loaded {
+            Ok((items, settings)) => {
+                let company_name = settings.map(|s| s.business_name.unwrap_or(s.name)).unwrap_or_default();
+                let response = SuccessResponse::new(request.id, PortalQuoteResponse {
+                    quote_number: quote.quote_number.clone(),
+                    title: quote.title.clone(),
+                    note: quote.note.clone(),
+                    company_name,
+                    currency: quote.currency.clone(),
+                    valid_until: quote.valid_until,
+                    status: quote.effective_status(Utc::now().date_naive()).to_string(),
+                    totals: quote_totals(&items),
+                    items: items.iter().map(PortalQuoteItem::from).collect(),
+                });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to load portal quote: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle portal.quote.decide messages - the customer accepts or declines
+pub async fn handle_portal_decide(client: Client, mut subscriber: Subscriber, ctx: QuoteContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received portal.quote.decide message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, quote)) = parse_portal_quote::<PortalQuoteDecisionRequest>(
+            &client,
+            &reply,
+            &msg.payload,
+            &ctx,
+            "quote.decide",
+            |p| p.token.as_str(),
+        )
+        .await?
+        else {
+            continue;
+        };
+        let payload = &request.payload;
+
+        let reason = payload.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+        if reason.is_some_and(|r| r.chars().count() > MAX_DECLINE_REASON_LENGTH) {
+            let error = ErrorResponse::new(
+                request.id,
+                "INVALID_REQUEST",
+                format!("reason is limited to {} characters", MAX_DECLINE_REASON_LENGTH),
+            );
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let decided = match queries::quote::decide(&ctx.pool, quote.id, payload.accept, reason).await {
+            Ok(Some(decided)) => decided,
+            Ok(None) => {
+                let status = quote.effective_status(Utc::now().date_naive()).to_string();
+                let error = ErrorResponse::new(request.id, "INVALID_STATE", format!("The quote is {}", status));
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to record quote decision: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        let user_id = decided.user_id;
+        let accepted = decided.status == QUOTE_STATUS_ACCEPTED;
+        info!("Quote {} of {} {}", decided.quote_number, user_id, decided.status);
+
+        let response = SuccessResponse::new(request.id, serde_json::json!({ "status": decided.status }));
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+
+        let mut text = format!("Quote {} {} by the customer", decided.quote_number, decided.status);
+        if let Some(reason) = reason.filter(|_| !accepted) {
+            text.push_str(&format!(": {}", reason));
+        }
+        log_on_timeline(&ctx.pool, &decided, "inbound", &text).await;
+
+        if accepted {
+            if let Some(action_id) = decided.planned_action_id {
+                info!("Quote {} became work order {}", decided.quote_number, action_id);
+            }
+            advance_lead(&ctx.pool, user_id, decided.customer_id, LEAD_STATUS_WON, None).await;
+        }
+
+        let title = format!("Quote {} {}", decided.quote_number, decided.status);
+        notification_dispatch::notify_or_warn(
+            &ctx.pool,
+            user_id,
+            NOTIFICATION_CATEGORY_SYSTEM,
+            NOTIFICATION_KIND_QUOTE,
+            &title,
+            Some(&decided.title),
+            Some(("customer", decided.customer_id)),
+        )
+        .await;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_quote_url_trims_slash() {
+        assert_eq!(
+            build_quote_url("https://app.example.com/", "abc"),
+            "https://app.example.com/portal/quote?token=abc"
+        );
+    }
+
+    #[test]
+    fn test_hash_quote_token_ignores_whitespace() {
+        assert_eq!(hash_quote_token(" abc\n"), hash_quote_token("abc"));
+        assert_eq!(hash_quote_token("abc").len(), 64);
+    }
+}
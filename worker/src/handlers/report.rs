@@ -0,0 +1,474 @@
+//! Report handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use chrono::Utc;
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::acquisition_report;
+use crate::services::capacity_forecast::{self, ForecastParams};
+use crate::services::inventory::{self, DEFAULT_RESTOCK_DAYS, MAX_RESTOCK_DAYS};
+use crate::services::lead_funnel;
+use crate::services::visit_duration;
+use crate::services::weather_report;
+use crate::subjects;
+use crate::types::{
+    AcquisitionSourceReportRequest, CapacityForecastRequest, ErrorResponse, LeadFunnelRequest, Request,
+    RestockReportRequest, RestockReportResponse, SuccessResponse, WeatherOutcomeReportRequest,
+    WorkloadReportRequest,
+};
+
+/// Start all report-related NATS handlers
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting report handlers...");
+
+    let capacity_forecast_sub = client.subscribe(subjects::report::CAPACITY_FORECAST).await?;
+    let acquisition_sources_sub = client.subscribe(subjects::report::ACQUISITION_SOURCES).await?;
+    let lead_funnel_sub = client.subscribe(subjects::report::LEAD_FUNNEL).await?;
+    let restock_sub = client.subscribe(subjects::report::RESTOCK).await?;
+    let weather_outcomes_sub = client.subscribe(subjects::report::WEATHER_OUTCOMES).await?;
+    let workload_sub = client.subscribe(subjects::report::WORKLOAD).await?;
+
+    tokio::spawn(handle_capacity_forecast(client.clone(), capacity_forecast_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_acquisition_sources(client.clone(), acquisition_sources_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_lead_funnel(client.clone(), lead_funnel_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_restock(client.clone(), restock_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_weather_outcomes(client.clone(), weather_outcomes_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_workload(client.clone(), workload_sub, pool.clone(), jwt_secret.clone()));
+
+    info!("Report handlers started");
+    Ok(())
+}
+
+/// Handle report.capacity_forecast messages - weekly due volume vs. crew capacity
+pub async fn handle_capacity_forecast(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received report.capacity_forecast message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<CapacityForecastRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let stops_per_day = match request.payload.stops_per_day {
+            Some(n) => n,
+            None => match queries::settings::get_user_settings(&pool, user_id).await {
+                Ok(Some(settings)) => settings.max_revisions_per_day,
+                Ok(None) => {
+                    let error = ErrorResponse::new(request.id, "NOT_FOUND", "User not found");
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to load user settings: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            },
+        };
+
+        let crew_count = match queries::crew::list_crews(&pool, user_id, true).await {
+            Ok(crews) => crews.len() as i64,
+            Err(e) => {
+                error!("Failed to list crews: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let sources = match queries::revision::list_due_projection_sources(&pool, user_id).await {
+            Ok(sources) => sources,
+            Err(e) => {
+                error!("Failed to load due projection sources: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let params = ForecastParams {
+            start: request.payload.from_date.unwrap_or_else(|| Utc::now().date_naive()),
+            weeks: request.payload.weeks.unwrap_or(capacity_forecast::DEFAULT_WEEKS),
+            crew_count,
+            stops_per_day,
+            working_days_per_week: request
+                .payload
+                .working_days_per_week
+                .unwrap_or(capacity_forecast::DEFAULT_WORKING_DAYS_PER_WEEK),
+        };
+
+        let forecast = capacity_forecast::build_forecast(&sources, &params);
+        let response = SuccessResponse::new(request.id, forecast);
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle report.acquisition_sources messages - new customers, jobs and revenue per source
+pub async fn handle_acquisition_sources(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received report.acquisition_sources message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<AcquisitionSourceReportRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let to_date = request.payload.to_date.unwrap_or_else(|| Utc::now().date_naive());
+        let from_date = request.payload.from_date.unwrap_or_else(|| acquisition_report::default_from(to_date));
+        if from_date > to_date {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "fromDate must not be after toDate");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let counts = match queries::customer::acquisition_source_counts(&pool, user_id, from_date, to_date).await {
+            Ok(counts) => counts,
+            Err(e) => {
+                error!("Failed to count customers per acquisition source: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let revenue = match queries::customer::acquisition_source_revenue(&pool, user_id, from_date, to_date).await {
+            Ok(revenue) => revenue,
+            Err(e) => {
+                error!("Failed to sum revenue per acquisition source: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let report = acquisition_report::build_report(from_date, to_date, &counts, &revenue);
+        let response = SuccessResponse::new(request.id, report);
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle report.lead_funnel messages - lead conversion per acquisition source
+pub async fn handle_lead_funnel(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received report.lead_funnel message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<LeadFunnelRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let to_date = request.payload.to_date.unwrap_or_else(|| Utc::now().date_naive());
+        let from_date = request.payload.from_date.unwrap_or_else(|| acquisition_report::default_from(to_date));
+        if from_date > to_date {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "fromDate must not be after toDate");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::lead::funnel_counts(&pool, user_id, from_date, to_date).await {
+            Ok(counts) => {
+                let funnel = lead_funnel::build_funnel(from_date, to_date, &counts);
+                let response = SuccessResponse::new(request.id, funnel);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to count lead funnel: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle report.restock messages - parts to bring to each vehicle and depot
+pub async fn handle_restock(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received report.restock message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<RestockReportRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = &request.payload;
+        let days = payload.days.unwrap_or(DEFAULT_RESTOCK_DAYS);
+        if !(1..=MAX_RESTOCK_DAYS).contains(&days) {
+            let error = ErrorResponse::new(
+                request.id,
+                "INVALID_REQUEST",
+                format!("days must be between 1 and {}", MAX_RESTOCK_DAYS),
+            );
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let since = Utc::now() - chrono::Duration::days(days);
+        match queries::inventory::restock_candidates(&pool, user_id, payload.crew_id, payload.depot_id, since).await {
+            Ok(candidates) => {
+                let rows = inventory::build_restock_report(&candidates);
+                let response = SuccessResponse::new(request.id, RestockReportResponse { days, rows });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to build restock report: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle report.weather_outcomes messages - failed and cancelled visits and
+/// arrival delays per weather condition and temperature band
+pub async fn handle_weather_outcomes(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received report.weather_outcomes message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<WeatherOutcomeReportRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let to_date = request.payload.to_date.unwrap_or_else(|| Utc::now().date_naive());
+        let from_date = request.payload.from_date.unwrap_or_else(|| acquisition_report::default_from(to_date));
+        if from_date > to_date {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "fromDate must not be after toDate");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let counts = tokio::try_join!(
+            queries::visit_conditions::outcome_counts(&pool, user_id, from_date, to_date, false),
+            queries::visit_conditions::outcome_counts(&pool, user_id, from_date, to_date, true),
+        );
+        match counts {
+            Ok((conditions, temperature_bands)) => {
+                let report = weather_report::build_report(from_date, to_date, &conditions, &temperature_bands);
+                let response = SuccessResponse::new(request.id, report);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to count visit outcomes per weather: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle report.workload messages - travel and on-site minutes of the
+/// completed visits per crew, the base for payroll
+pub async fn handle_workload(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received report.workload message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<WorkloadReportRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let to_date = request.payload.to_date.unwrap_or_else(|| Utc::now().date_naive());
+        let from_date = request.payload.from_date.unwrap_or_else(|| visit_duration::default_from(to_date));
+        if from_date > to_date {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "fromDate must not be after toDate");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::visit_duration::workload_rows(&pool, user_id, from_date, to_date).await {
+            Ok(rows) => {
+                let report = visit_duration::build_workload_report(from_date, to_date, rows);
+                let response = SuccessResponse::new(request.id, report);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to load workload: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,701 @@
+//! Customer reschedule request handlers for NATS messages
+//!
+//! Dispatchers create a tokenized link for an upcoming visit. The end customer
+//! opens it (`sazinka.portal.reschedule.*`, no login) and asks for another
+//! date; the request stores the slot engine's suggestions for that day and the
+//! dispatcher is emailed. Accepting moves the visit, declining leaves it.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use chrono::{Duration, Utc};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::account;
+use super::onboarding::generate_token;
+use super::slots::{suggest_crew_slots, SuggestSlotsV2Request};
+use super::{parse_authenticated, parse_portal};
+use crate::db::queries;
+use crate::db::repo::Repositories;
+use crate::defaults::DEFAULT_SERVICE_DURATION_MINUTES;
+use crate::services::email_sender::EmailSender;
+use crate::services::email_templates::RescheduleRequestedEmail;
+use crate::services::rate_limiter::{MultiRateLimiter, RateLimiterConfig};
+use crate::services::routing::RoutingService;
+use crate::subjects;
+use crate::types::{
+    AcceptRescheduleRequest, Coordinates, CreateRescheduleLinkRequest, DeclineRescheduleRequest, ErrorResponse,
+    ListRescheduleRequestsRequest, ListRescheduleRequestsResponse, PortalRescheduleStatus, PortalTokenRequest,
+    PortalVisitResponse, RescheduleLinkResponse, Request, SubmitRescheduleRequest, SuccessResponse,
+    MAX_RESCHEDULE_NOTE_LEN, RESCHEDULE_STATES,
+};
+use crate::types::reschedule::validate_requested_slot;
+
+/// How long a shared link works
+const LINK_TTL_DAYS: i64 = 30;
+/// Suggestions kept on a request
+const MAX_STORED_SUGGESTIONS: usize = 5;
+/// Window of the outcome statistics
+const STATS_WINDOW_DAYS: i64 = 90;
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_LIST_LIMIT: i64 = 200;
+
+/// Only visits that have not happened yet can be moved
+const RESCHEDULABLE_STATUS: &str = "planned";
+
+/// Shared by all reschedule handlers
+#[derive(Clone)]
+pub struct RescheduleContext {
+    pub pool: PgPool,
+    pub repos: Repositories,
+    pub jwt_secret: Arc<String>,
+    pub email_sender: Arc<dyn EmailSender>,
+    pub app_base_url: Arc<String>,
+    pub routing_service: Arc<dyn RoutingService>,
+    pub rate_limiter: Arc<MultiRateLimiter>,
+}
+
+/// Start all reschedule-related NATS handlers
+pub async fn start_handlers(
+    client: Client,
+    pool: PgPool,
+    repos: Repositories,
+    jwt_secret: Arc<String>,
+    email_sender: Arc<dyn EmailSender>,
+    app_base_url: Arc<String>,
+    routing_service: Arc<dyn RoutingService>,
+) -> Result<()> {
+    info!("Starting reschedule handlers...");
+
+    let rate_limiter = Arc::new(MultiRateLimiter::new(vec![
+        (
+            "portal.view",
+            RateLimiterConfig {
+                max_attempts: 30,
+                window_secs: 300,
+            },
+        ),
+        (
+            "portal.request",
+            RateLimiterConfig {
+                max_attempts: 5,
+                window_secs: 3600,
+            },
+        ),
+    ]));
+    let ctx = RescheduleContext { pool, repos, jwt_secret, email_sender, app_base_url, routing_service, rate_limiter };
+
+    let [
+        link_sub,
+        list_sub,
+        accept_sub,
+        decline_sub,
+        stats_sub,
+        portal_get_sub,
+        portal_request_sub,
+    ] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::reschedule::LINK_CREATE,
+            subjects::reschedule::LIST,
+            subjects::reschedule::ACCEPT,
+            subjects::reschedule::DECLINE,
+            subjects::reschedule::STATS,
+            subjects::portal::RESCHEDULE_GET,
+            subjects::portal::RESCHEDULE_REQUEST,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_create_link(client.clone(), link_sub, ctx.clone()));
+    tokio::spawn(handle_list(client.clone(), list_sub, ctx.clone()));
+    tokio::spawn(handle_accept(client.clone(), accept_sub, ctx.clone()));
+    tokio::spawn(handle_decline(client.clone(), decline_sub, ctx.clone()));
+    tokio::spawn(handle_stats(client.clone(), stats_sub, ctx.clone()));
+    tokio::spawn(handle_portal_get(client.clone(), portal_get_sub, ctx.clone()));
+    tokio::spawn(handle_portal_request(client.clone(), portal_request_sub, ctx));
+
+    info!("Reschedule handlers started");
+    Ok(())
+}
+
+/// Build the portal URL for a link token
+fn build_reschedule_url(app_base_url: &str, token: &str) -> String {
+    format!("{}/portal/reschedule?token={}", app_base_url.trim_end_matches('/'), token)
+}
+
+/// Hash of a portal token, as stored
+fn hash_link_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(token.trim().as_bytes()))
+}
+
+/// Parse a portal request, rate limit it and resolve its token to
+/// (request, user_id, visit_id). Replies with the error itself.
+async fn parse_portal_link<T: serde::de::DeserializeOwned>(
+    client: &Client,
+    reply: &async_nats::Subject,
+    payload: &[u8],
+    ctx: &RescheduleContext,
+    limiter: &str,
+    token_of: impl Fn(&T) -> &str,
+) -> Result<Option<(Request<T>, Uuid, Uuid)>> {
+    let Some((request, token)) = parse_portal(client, reply, payload, &ctx.rate_limiter, limiter, token_of).await?
+    else {
+        return Ok(None);
+    };
+
+    match queries::reschedule::resolve_link(&ctx.pool, &hash_link_token(&token)).await {
+        Ok(Some((user_id, visit_id))) => Ok(Some((request, user_id, visit_id))),
+        Ok(None) => {
+            let error = ErrorResponse::new(request.id, "INVALID_TOKEN", "Link is invalid or has expired");
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            Ok(None)
+        }
+        Err(e) => {
+            error!("Failed to resolve reschedule link: {}", e);
+            let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            Ok(None)
+        }
+    }
+}
+
+/// Handle reschedule.link.create messages
+pub async fn handle_create_link(client: Client, mut subscriber: Subscriber, ctx: RescheduleContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received reschedule.link.create message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<CreateRescheduleLinkRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let visit_id = request.payload.visit_id;
+        match queries::visit::get_visit(&ctx.pool, visit_id, user_id).await {
+            Ok(Some(visit)) if visit.status == RESCHEDULABLE_STATUS => {}
+            Ok(Some(_)) => {
+                let error = ErrorResponse::new(request.id, "INVALID_STATE", "Only planned visits can be rescheduled");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Visit not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load visit: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        let (token, token_hash) = generate_token();
+        let expires_at = Utc::now() + Duration::days(LINK_TTL_DAYS);
+        match queries::reschedule::insert_link(&ctx.pool, user_id, visit_id, &token_hash, expires_at).await {
+            Ok(()) => {
+                let response = SuccessResponse::new(request.id, RescheduleLinkResponse {
+                    url: build_reschedule_url(&ctx.app_base_url, &token),
+                    expires_at,
+                });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to create reschedule link: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle portal.reschedule.get messages - visit details behind a link
+pub async fn handle_portal_get(client: Client, mut subscriber: Subscriber, ctx: RescheduleContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received portal.reschedule.get message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, user_id, visit_id)) = parse_portal_link::<PortalTokenRequest>(
+            &client,
+            &reply,
+            &msg.payload,
+            &ctx,
+            "portal.view",
+            |p| p.token.as_str(),
+        )
+        .await?
+        else {
+            continue;
+        };
+
+        let loaded = async {
+            let visit = queries::visit::get_visit(&ctx.pool, visit_id, user_id).await?;
+            let settings = queries::settings::get_user_settings(&ctx.pool, user_id).await?;
+            let latest = queries::reschedule::latest_for_visit(&ctx.pool, visit_id).await?;
+            anyhow::Ok((visit, settings, latest))
+        }
+        .await;
+
+        match loaded {
+            Ok((Some(visit), settings, latest)) => {
+                let response = SuccessResponse::new(request.id, PortalVisitResponse {
+                    business_name: settings.and_then(|s| s.business_name),
+                    scheduled_date: visit.scheduled_date,
+                    scheduled_time_start: visit.scheduled_time_start,
+                    scheduled_time_end: visit.scheduled_time_end,
+                    status: visit.status,
+                    latest_request: latest.as_ref().map(PortalRescheduleStatus::from),
+                });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok((None, _, _)) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Visit not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to load portal visit: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Slot engine suggestions for the requested date, best first.
+/// Failures only cost the dispatcher the hints, never the request.
+async fn suggest_for_request(
+    ctx: &RescheduleContext,
+    user_id: Uuid,
+    customer: &crate::types::Customer,
+    settings: &crate::types::settings::UserWithSettings,
+    visit: &crate::types::Visit,
+    payload: &SubmitRescheduleRequest,
+) -> serde_json::Value {
+    let (Some(lat), Some(lng)) = (customer.lat, customer.lng) else {
+        return serde_json::json!([]);
+    };
+    let service_duration_minutes = match (visit.scheduled_time_start, visit.scheduled_time_end) {
+        (Some(start), Some(end)) if end > start => (end - start).num_minutes() as i32,
+        _ => DEFAULT_SERVICE_DURATION_MINUTES as i32,
+    };
+    let req = SuggestSlotsV2Request {
+        date: payload.requested_date,
+        customer_id: customer.id,
+        service_duration_minutes,
+        preferred_time_start: payload.preferred_time_start,
+        preferred_time_end: payload.preferred_time_end,
+        crew_ids: None,
+        max_per_crew: None,
+    };
+
+    match suggest_crew_slots(&ctx.pool, &ctx.repos, ctx.routing_service.as_ref(), user_id, settings, Coordinates { lat, lng }, &req)
+        .await
+    {
+        Ok(mut result) => {
+            result.suggestions.truncate(MAX_STORED_SUGGESTIONS);
+            serde_json::to_value(&result.suggestions).unwrap_or_else(|_| serde_json::json!([]))
+        }
+        Err(e) => {
+            warn!("Slot suggestions for reschedule of visit {} failed: {}", visit.id, e);
+            serde_json::json!([])
+        }
+    }
+}
+
+/// Handle portal.reschedule.request messages - customer asks for another date
+pub async fn handle_portal_request(client: Client, mut subscriber: Subscriber, ctx: RescheduleContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received portal.reschedule.request message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, user_id, visit_id)) = parse_portal_link::<SubmitRescheduleRequest>(
+            &client,
+            &reply,
+            &msg.payload,
+            &ctx,
+            "portal.request",
+            |p| p.token.as_str(),
+        )
+        .await?
+        else {
+            continue;
+        };
+        let payload = &request.payload;
+
+        if let Err(msg) = validate_requested_slot(
+            payload.requested_date,
+            payload.preferred_time_start,
+            payload.preferred_time_end,
+            Utc::now().date_naive(),
+        ) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+        let note = payload
+            .note
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(|n| n.chars().take(MAX_RESCHEDULE_NOTE_LEN).collect::<String>());
+
+        let loaded = async {
+            let visit = queries::visit::get_visit(&ctx.pool, visit_id, user_id).await?;
+            let settings = queries::settings::get_user_settings(&ctx.pool, user_id).await?;
+            anyhow::Ok((visit, settings))
+        }
+        .await;
+        let (visit, settings) = match loaded {
+            Ok((Some(visit), Some(settings))) => (visit, settings),
+            Ok(_) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Visit not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load visit for reschedule: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        if visit.status != RESCHEDULABLE_STATUS {
+            let error = ErrorResponse::new(request.id, "INVALID_STATE", "This visit can no longer be rescheduled");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let customer = match queries::customer::get_customer(&ctx.pool, user_id, visit.customer_id).await {
+            Ok(Some(c)) => c,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Visit not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load customer for reschedule: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let suggestions = suggest_for_request(&ctx, user_id, &customer, &settings, &visit, payload).await;
+        let suggestion_count = suggestions.as_array().map(Vec::len).unwrap_or(0);
+
+        let inserted = queries::reschedule::insert_request(
+            &ctx.pool,
+            user_id,
+            visit.id,
+            customer.id,
+            (visit.scheduled_date, visit.scheduled_time_start, visit.scheduled_time_end),
+            payload.requested_date,
+            (payload.preferred_time_start, payload.preferred_time_end),
+            note.as_deref(),
+            &suggestions,
+        )
+        .await;
+
+        match inserted {
+            Ok(Some(request_id)) => {
+                info!("Reschedule request {} for visit {} ({} suggestions)", request_id, visit.id, suggestion_count);
+
+                let customer_name = customer.name.clone().unwrap_or_default();
+                let email_msg = RescheduleRequestedEmail {
+                    to: &settings.email,
+                    customer_name: &customer_name,
+                    original_date: visit.scheduled_date,
+                    requested_date: payload.requested_date,
+                    note: note.as_deref(),
+                    suggestion_count,
+                    locale: &settings.locale,
+                }
+                .render();
+                let sender = Arc::clone(&ctx.email_sender);
+                tokio::spawn(async move {
+                    if let Err(e) = sender.send(email_msg).await {
+                        warn!("Failed to send reschedule request email: {}", e);
+                    }
+                });
+
+                let status = match queries::reschedule::latest_for_visit(&ctx.pool, visit.id).await {
+                    Ok(Some(latest)) => Some(PortalRescheduleStatus::from(&latest)),
+                    _ => None,
+                };
+                let response = SuccessResponse::new(request.id, status);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(
+                    request.id,
+                    "RESCHEDULE_PENDING",
+                    "A reschedule request for this visit is already waiting for an answer",
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to store reschedule request: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", "Internal error");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle reschedule.list messages
+pub async fn handle_list(client: Client, mut subscriber: Subscriber, ctx: RescheduleContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received reschedule.list message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<ListRescheduleRequestsRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret)
+                .await?
+        else {
+            continue;
+        };
+        let payload = &request.payload;
+
+        if let Some(status) = payload.status.as_deref() {
+            if !RESCHEDULE_STATES.contains(&status) {
+                let error = ErrorResponse::new(
+                    request.id,
+                    "INVALID_REQUEST",
+                    format!("status must be one of: {}", RESCHEDULE_STATES.join(", ")),
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+        let limit = payload.limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, MAX_LIST_LIMIT);
+
+        match queries::reschedule::list_requests(
+            &ctx.pool,
+            auth_info.data_user_id(),
+            payload.status.as_deref(),
+            payload.visit_id,
+            limit,
+        )
+        .await
+        {
+            Ok(items) => {
+                let response = SuccessResponse::new(request.id, ListRescheduleRequestsResponse { items });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list reschedule requests: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle reschedule.accept messages - moves the visit
+pub async fn handle_accept(client: Client, mut subscriber: Subscriber, ctx: RescheduleContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received reschedule.accept message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<AcceptRescheduleRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+        let payload = &request.payload;
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let existing = match queries::reschedule::get_request(&ctx.pool, user_id, payload.id).await {
+            Ok(Some(r)) => r,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Reschedule request not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load reschedule request: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let new_date = payload.scheduled_date.unwrap_or(existing.requested_date);
+        let new_start = payload.scheduled_time_start.or(existing.preferred_time_start);
+        let new_end = payload.scheduled_time_end.or(existing.preferred_time_end);
+        if let (Some(start), Some(end)) = (new_start, new_end) {
+            if end <= start {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "Time window must end after it starts");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        match queries::reschedule::accept_request(
+            &ctx.pool,
+            user_id,
+            payload.id,
+            auth_info.user_id,
+            new_date,
+            new_start,
+            new_end,
+            payload.crew_id,
+            payload.note.as_deref(),
+        )
+        .await
+        {
+            Ok(true) => {
+                info!("Reschedule request {} accepted: visit {} moved to {}", payload.id, existing.visit_id, new_date);
+                match queries::reschedule::get_request(&ctx.pool, user_id, payload.id).await {
+                    Ok(updated) => {
+                        let response = SuccessResponse::new(request.id, updated);
+                        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+                    }
+                    Err(e) => {
+                        error!("Failed to reload reschedule request: {}", e);
+                        let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                        let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    }
+                }
+            }
+            Ok(false) => {
+                let error = ErrorResponse::new(request.id, "INVALID_STATE", "Request was already decided");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to accept reschedule request: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle reschedule.decline messages - the visit keeps its date
+pub async fn handle_decline(client: Client, mut subscriber: Subscriber, ctx: RescheduleContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received reschedule.decline message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<DeclineRescheduleRequest>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+        let user_id = auth_info.data_user_id();
+        let payload = &request.payload;
+
+        if account::reject_if_read_only(&client, &ctx.pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        match queries::reschedule::decline_request(
+            &ctx.pool,
+            user_id,
+            payload.id,
+            auth_info.user_id,
+            payload.note.as_deref(),
+        )
+        .await
+        {
+            Ok(true) => {
+                info!("Reschedule request {} declined", payload.id);
+                match queries::reschedule::get_request(&ctx.pool, user_id, payload.id).await {
+                    Ok(updated) => {
+                        let response = SuccessResponse::new(request.id, updated);
+                        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+                    }
+                    Err(e) => {
+                        error!("Failed to reload reschedule request: {}", e);
+                        let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                        let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    }
+                }
+            }
+            Ok(false) => {
+                let error = ErrorResponse::new(
+                    request.id,
+                    "NOT_FOUND",
+                    "Reschedule request not found or already decided",
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to decline reschedule request: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle reschedule.stats messages - accept/decline outcomes
+pub async fn handle_stats(client: Client, mut subscriber: Subscriber, ctx: RescheduleContext) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received reschedule.stats message");
+        let Some(reply) = msg.reply.clone() else { continue };
+        let Some((request, auth_info)) =
+            parse_authenticated::<serde_json::Value>(&client, &reply, &msg.payload, &ctx.jwt_secret).await?
+        else {
+            continue;
+        };
+
+        let since = Utc::now() - Duration::days(STATS_WINDOW_DAYS);
+        match queries::reschedule::stats(&ctx.pool, auth_info.data_user_id(), since).await {
+            Ok(stats) => {
+                let response = SuccessResponse::new(request.id, stats);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to load reschedule stats: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_reschedule_url_trims_slash() {
+        assert_eq!(
+            build_reschedule_url("https://app.example.com/", "abc"),
+            "https://app.example.com/portal/reschedule?token=abc"
+        );
+    }
+
+    #[test]
+    fn test_hash_link_token_matches_generated_hash() {
+        let (token, hash) = generate_token();
+        assert_eq!(hash_link_token(&token), hash);
+        assert_eq!(hash_link_token(&format!(" {} ", token)), hash);
+    }
+}
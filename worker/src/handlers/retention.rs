@@ -0,0 +1,259 @@
+//! Document retention handlers for NATS messages
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::{leader, retention};
+use crate::subjects;
+use crate::types::retention::{
+    validate_policy, ListRetentionPoliciesResponse, SetRetentionPolicyRequest,
+};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Start all retention NATS handlers and the retention sweeper
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting retention handlers...");
+
+    let [list_sub, set_sub, report_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::retention::POLICY_LIST,
+            subjects::retention::POLICY_SET,
+            subjects::retention::REPORT,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_list_policies(client.clone(), list_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_set_policy(client.clone(), set_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_report(client.clone(), report_sub, pool.clone(), jwt_secret.clone()));
+
+    leader::spawn_singleton(pool, "retention_sweeper", retention::run_sweeper);
+
+    info!("Retention handlers started");
+    Ok(())
+}
+
+/// Reply RETENTION_ACTIVE when a record (or, for a device, its revision
+/// reports) is still inside its retention period. Returns true if the
+/// request was rejected.
+pub(crate) async fn reject_if_retained(
+    client: &Client,
+    pool: &PgPool,
+    reply: &async_nats::Subject,
+    request_id: Uuid,
+    user_id: Uuid,
+    entity_type: &str,
+    entity_id: Uuid,
+) -> Result<bool> {
+    let until = if entity_type == "device" {
+        retention::device_retained_until(pool, user_id, entity_id).await
+    } else {
+        retention::retained_until_for(pool, user_id, entity_type, entity_id).await
+    };
+    match until {
+        Ok(None) => Ok(false),
+        Ok(Some(until)) => {
+            let message = if entity_type == "device" {
+                format!("The device has revision reports that must be kept until {}", until)
+            } else {
+                format!("The {} must be kept until {}", entity_type, until)
+            };
+            let error = ErrorResponse::new(request_id, "RETENTION_ACTIVE", message);
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            Ok(true)
+        }
+        Err(e) => {
+            // Deletion is irreversible; refuse it when the period is unknown
+            error!("Failed to check retention of {} {}: {}", entity_type, entity_id, e);
+            let error = ErrorResponse::new(request_id, "DATABASE_ERROR", e.to_string());
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&error)?.into()).await;
+            Ok(true)
+        }
+    }
+}
+
+/// Handle retention.policy.list messages - effective policy per entity type
+pub async fn handle_list_policies(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received retention.policy.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::retention::list_policies(&pool, user_id).await {
+            Ok(policies) => {
+                let policies = retention::effective_policies(policies);
+                let response = SuccessResponse::new(request.id, ListRetentionPoliciesResponse { policies });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list retention policies: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle retention.policy.set messages
+pub async fn handle_set_policy(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received retention.policy.set message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<SetRetentionPolicyRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can manage retention policies");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let payload = &request.payload;
+        if let Err(msg) = validate_policy(&payload.entity_type, payload.retention_months) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::retention::upsert_policy(&pool, auth_info.data_user_id(), payload).await {
+            Ok(policy) => {
+                info!(
+                    "Retention of {} set to {} months (delete after expiry: {})",
+                    policy.entity_type, policy.retention_months, policy.delete_after_expiry
+                );
+                let response = SuccessResponse::new(request.id, policy);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to set retention policy: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle retention.report messages - retained, expired and deleted records
+pub async fn handle_report(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received retention.report message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match retention::build_report(&pool, user_id).await {
+            Ok(report) => {
+                let response = SuccessResponse::new(request.id, report);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to build retention report: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
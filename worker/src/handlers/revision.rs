@@ -10,7 +10,10 @@ use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::auth;
+use super::{account, retention};
 use crate::db::queries;
+use crate::services::{demo_mode, webhook_delivery};
+use crate::db::repo::Repositories;
 use crate::types::{
     ErrorResponse, Request, SuccessResponse,
 };
@@ -21,6 +24,8 @@ use crate::types::revision::{
     CallQueueRequest, SnoozeRevisionRequest, ScheduleRevisionRequest,
 };
 use crate::types::planned_action::CreatePlannedActionRequest;
+use crate::types::revision_number::{find_sequence_gaps, ListDocumentNumbersRequest, ListDocumentNumbersResponse};
+use crate::types::retention::RETENTION_ENTITY_REVISION;
 
 /// Response for list of revisions
 #[derive(Debug, serde::Serialize)]
@@ -84,9 +89,14 @@ pub async fn handle_create(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         // Create revision
         match queries::revision::create_revision(&pool, user_id, &request.payload).await {
             Ok(revision) => {
+                webhook_delivery::emit(&client, &pool, user_id, "revision.created", &revision);
                 let response = SuccessResponse::new(request.id, revision);
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                 debug!("Created revision: {}", response.payload.id);
@@ -146,6 +156,7 @@ pub async fn handle_list(
             &pool,
             user_id,
             request.payload.customer_id,
+            request.payload.include_branches,
             request.payload.device_id,
             request.payload.status.as_deref(),
             request.payload.from_date,
@@ -159,7 +170,7 @@ pub async fn handle_list(
                     request.id,
                     RevisionListResponse {
                         total: revisions.len() as i64,
-                        items: revisions,
+                        items: demo_mode::apply(request.demo_mode, revisions),
                     },
                 );
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
@@ -180,7 +191,7 @@ pub async fn handle_list(
 pub async fn handle_get(
     client: Client,
     mut subscriber: Subscriber,
-    pool: PgPool,
+    repos: Repositories,
     jwt_secret: Arc<String>,
 ) -> Result<()> {
     #[derive(serde::Deserialize)]
@@ -222,9 +233,9 @@ pub async fn handle_get(
         };
 
         // Get revision
-        match queries::revision::get_revision(&pool, request.payload.id, user_id).await {
+        match repos.revisions.get_revision(request.payload.id, user_id).await {
             Ok(Some(revision)) => {
-                let response = SuccessResponse::new(request.id, revision);
+                let response = SuccessResponse::new(request.id, demo_mode::apply(request.demo_mode, revision));
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                 debug!("Got revision: {}", response.payload.id);
             }
@@ -282,6 +293,10 @@ pub async fn handle_update(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         // Update revision
         match queries::revision::update_revision(
             &pool,
@@ -290,6 +305,7 @@ pub async fn handle_update(
             &request.payload,
         ).await {
             Ok(Some(revision)) => {
+                webhook_delivery::emit(&client, &pool, user_id, "revision.updated", &revision);
                 let response = SuccessResponse::new(request.id, revision);
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                 debug!("Updated revision: {}", response.payload.id);
@@ -348,6 +364,10 @@ pub async fn handle_complete(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         // Complete revision
         match queries::revision::complete_revision(
             &pool,
@@ -358,6 +378,7 @@ pub async fn handle_complete(
             request.payload.duration_minutes,
         ).await {
             Ok(Some(revision)) => {
+                webhook_delivery::emit(&client, &pool, user_id, "revision.updated", &revision);
                 let response = SuccessResponse::new(request.id, revision);
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                 debug!("Completed revision: {}", response.payload.id);
@@ -422,10 +443,21 @@ pub async fn handle_delete(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if retention::reject_if_retained(&client, &pool, &reply, request.id, user_id, RETENTION_ENTITY_REVISION, request.payload.id)
+            .await?
+        {
+            continue;
+        }
+
         // Delete revision
         match queries::revision::delete_revision(&pool, request.payload.id, user_id).await {
             Ok(deleted) => {
                 if deleted {
+                    webhook_delivery::emit(&client, &pool, user_id, "revision.deleted", &serde_json::json!({ "id": request.payload.id }));
                     let response = SuccessResponse::new(request.id, DeleteResponse { deleted: true });
                     let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                     debug!("Deleted revision: {}", request.payload.id);
@@ -689,7 +721,8 @@ pub async fn handle_queue(
         };
 
         match queries::revision::get_call_queue(&pool, user_id, request.payload).await {
-            Ok(queue_response) => {
+            Ok(mut queue_response) => {
+                queue_response.items = demo_mode::apply(request.demo_mode, queue_response.items);
                 let response = SuccessResponse::new(request.id, queue_response);
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
             }
@@ -741,6 +774,10 @@ pub async fn handle_snooze(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         match queries::revision::snooze_revision(
             &pool,
             user_id,
@@ -824,6 +861,10 @@ pub async fn handle_unschedule(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         info!("Unscheduling revision {} for user {}", request.payload.id, user_id);
 
         match queries::revision::unschedule_revision(&pool, user_id, request.payload.id).await {
@@ -885,6 +926,10 @@ pub async fn handle_schedule(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         info!("Scheduling revision {} for user {} on {}", 
             request.payload.id, user_id, request.payload.scheduled_date);
 
@@ -918,3 +963,61 @@ pub async fn handle_schedule(
 
     Ok(())
 }
+
+/// Handle revision.numbers.list messages - register of issued document numbers
+pub async fn handle_list_document_numbers(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received revision.numbers.list message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<ListDocumentNumbersRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::revision_number::list_issued(&pool, user_id, request.payload.period_year).await {
+            Ok(items) => {
+                let sequences: Vec<i32> = items.iter().map(|item| item.sequence).collect();
+                let voided_count = items.iter().filter(|item| item.voided_at.is_some()).count() as i64;
+                let response = SuccessResponse::new(
+                    request.id,
+                    ListDocumentNumbersResponse { gaps: find_sequence_gaps(&sequences), voided_count, items },
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to list document numbers: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
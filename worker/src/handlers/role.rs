@@ -11,6 +11,7 @@ use uuid::Uuid;
 
 use crate::auth;
 use crate::db::queries;
+use crate::subjects;
 use crate::types::{
     ErrorResponse, Request, SuccessResponse,
     CreateRoleRequest, UpdateRoleRequest, AssignRoleRequest, UnassignRoleRequest,
@@ -22,15 +23,31 @@ pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String
     info!("Starting role handlers...");
 
     // Subscribe to all role subjects
-    let role_create_sub = client.subscribe("sazinka.role.create").await?;
-    let role_list_sub = client.subscribe("sazinka.role.list").await?;
-    let role_get_sub = client.subscribe("sazinka.role.get").await?;
-    let role_update_sub = client.subscribe("sazinka.role.update").await?;
-    let role_delete_sub = client.subscribe("sazinka.role.delete").await?;
-    let role_assign_sub = client.subscribe("sazinka.role.assign").await?;
-    let role_unassign_sub = client.subscribe("sazinka.role.unassign").await?;
-    let user_roles_get_sub = client.subscribe("sazinka.user.roles.get").await?;
-    let user_roles_set_sub = client.subscribe("sazinka.user.roles.set").await?;
+    let [
+        role_create_sub,
+        role_list_sub,
+        role_get_sub,
+        role_update_sub,
+        role_delete_sub,
+        role_assign_sub,
+        role_unassign_sub,
+        user_roles_get_sub,
+        user_roles_set_sub,
+    ] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::role::CREATE,
+            subjects::role::LIST,
+            subjects::role::GET,
+            subjects::role::UPDATE,
+            subjects::role::DELETE,
+            subjects::role::ASSIGN,
+            subjects::role::UNASSIGN,
+            subjects::user::ROLES_GET,
+            subjects::user::ROLES_SET,
+        ],
+    )
+    .await?;
 
     // Spawn handlers
     tokio::spawn(handle_create(client.clone(), role_create_sub, pool.clone(), jwt_secret.clone()));
@@ -12,18 +12,34 @@ use tracing::{debug, error, warn, info};
 use uuid::Uuid;
 
 use crate::auth;
+use super::account;
 use crate::db::queries;
+use crate::db::repo::Repositories;
 use crate::defaults::{default_work_end, default_work_start, DEFAULT_SERVICE_DURATION_MINUTES};
+use crate::services::break_location;
+use crate::services::hooks::{self, HookOutcome};
 use crate::services::insertion::{calculate_insertion_positions, StopMeta};
-use crate::services::routing::{RoutingService, MockRoutingService};
+use crate::services::quota;
+use crate::services::route_analysis;
+use crate::services::travel_correction::{self, TravelTimeModel};
+use crate::services::{communication_log, demo_mode, dispatch_board, webhook_delivery};
+use crate::services::colocation::{LocationGroups, COLOCATION_RADIUS_M};
+use crate::services::routing::{anomaly_warnings, diagnostics, verify_matrices, MatrixVerdict, MockRoutingService, RoutingService};
 use crate::services::sequential_schedule::{
     self, ScheduleInput, ScheduleStop as SeqScheduleStop,
     StopType as SeqStopType,
 };
-use crate::services::vrp::{VrpSolver, VrpProblem, VrpStop, Depot, SolverConfig, StopTimeWindow, BreakConfig};
+use crate::services::vrp::{
+    VrpSolver, VrpProblem, VrpStop, Depot, SolverConfig, StopTimeWindow, StopDemand, BreakConfig,
+    FIXED_STOP_PRIORITY,
+};
+use crate::types::hooks::{AFTER_ROUTE_SAVE, BEFORE_ROUTE_SAVE};
 use crate::types::{
-    Coordinates, ErrorResponse, Request, SuccessResponse,
-    PlannedRouteStop, RoutePlanRequest, RoutePlanResponse, RouteStatus, RouteWarning, StopType,
+    BreakLocation, BreakSuggestRequest, BreakSuggestResponse, Coordinates, ErrorResponse, FixedRouteStop, Request, SuccessResponse,
+    PlannedRouteStop, QuotaMetric, RouteAnalysisRequest, RouteLock, RouteLockRequest, RoutePlanRequest, RoutePlanResponse,
+    RouteReoptimizeLiveRequest, RouteReoptimizeLiveResponse, RouteStatus,
+    RouteStopTask, RouteUnlockRequest, RouteUnlockResponse, RouteWarning, StopType, UpdateRouteStopNotesRequest,
+    validate_stop_notes,
 };
 
 /// Handle route.plan messages
@@ -71,7 +87,7 @@ pub async fn handle_plan(
         let plan_request = &request.payload;
 
         // Validate request
-        if plan_request.customer_ids.is_empty() {
+        if plan_request.customer_ids.is_empty() && plan_request.fixed_stops.is_empty() {
             let response = SuccessResponse::new(request.id, RoutePlanResponse {
                 stops: vec![],
                 total_distance_km: 0.0,
@@ -90,339 +106,449 @@ pub async fn handle_plan(
             continue;
         }
 
-        // Load customers from database
-        let customers = match load_customers(&pool, user_id, &plan_request.customer_ids, plan_request.date).await {
-            Ok(c) => c,
+        // Each optimization counts towards the monthly route plan quota
+        match quota::consume(&pool, user_id, QuotaMetric::RoutePlans, 1).await {
+            Ok(Ok(())) => {}
+            Ok(Err(exceeded)) => {
+                let error = ErrorResponse::new(request.id, "QUOTA_EXCEEDED", exceeded.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
             Err(e) => {
-                error!("Failed to load customers: {}", e);
+                error!("Failed to check route plan quota: {}", e);
                 let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
                 let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
                 continue;
             }
-        };
-
-        // Filter customers with valid coordinates
-        let (valid_customers, invalid_ids): (Vec<_>, Vec<_>) = customers
-            .into_iter()
-            .partition(|c| c.lat.is_some() && c.lng.is_some());
+        }
 
-        let mut warnings = Vec::new();
-        for customer in &invalid_ids {
-            warnings.push(RouteWarning {
-                stop_index: None,
-                warning_type: "MISSING_COORDINATES".to_string(),
-                message: json!({"key": "jobs:customer_no_coordinates_excluded", "params": {"name": customer.name.as_deref().unwrap_or("(unnamed)")}}).to_string(),
-            });
+        match optimize_route(&pool, &routing_service, user_id, plan_request).await {
+            Ok(response) => {
+                let response = SuccessResponse::new(request.id, response);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err((code, message)) => {
+                let error = ErrorResponse::new(request.id, code, message);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
         }
+    }
 
-        if valid_customers.is_empty() {
-            let response = SuccessResponse::new(request.id, RoutePlanResponse {
-                stops: vec![],
-                total_distance_km: 0.0,
-                total_duration_minutes: 0,
-                algorithm: "none".to_string(),
-                solve_time_ms: 0,
-                solver_log: vec![],
-                optimization_score: 0,
-                warnings,
-                unassigned: plan_request.customer_ids.clone(),
-                geometry: vec![],
-                return_to_depot_distance_km: None,
-                return_to_depot_duration_minutes: None,
-            });
-            let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
-            continue;
+    Ok(())
+}
+
+/// Optimize one day's route with the VRP solver. Errors carry the reply
+/// error code and message.
+pub(crate) async fn optimize_route(
+    pool: &PgPool,
+    routing_service: &Arc<dyn RoutingService>,
+    user_id: Uuid,
+    plan_request: &RoutePlanRequest,
+) -> std::result::Result<RoutePlanResponse, (&'static str, String)> {
+    // Load customers from database
+    let all_customer_ids = plan_request.all_customer_ids();
+    let mut customers = match load_customers(pool, user_id, &all_customer_ids, plan_request.date).await {
+        Ok(c) => c,
+        Err(e) => {
+            error!("Failed to load customers: {}", e);
+            return Err(("DATABASE_ERROR", e.to_string()));
         }
+    };
 
-        // Load crew (if specified) for working hours and arrival buffer
-        let crew = if let Some(crew_id) = plan_request.crew_id {
-            match queries::crew::get_crew(&pool, crew_id, user_id).await {
-                Ok(Some(c)) => {
-                    info!("Using crew '{}': working hours {:?}-{:?}",
-                        c.name, c.working_hours_start, c.working_hours_end);
-                    Some(c)
-                }
-                _ => {
-                    warn!("Crew {} not found, using user settings", crew_id);
-                    None
-                }
-            }
-        } else {
-            None
-        };
+    // Fixed appointments are pinned to their agreed time
+    for customer in &mut customers {
+        customer.fixed = plan_request.fixed_stop(customer.id).cloned();
+    }
 
-        let arrival_buffer_percent = plan_request.arrival_buffer_percent;
-        let arrival_buffer_fixed_minutes = plan_request.arrival_buffer_fixed_minutes;
+    // Filter customers with valid coordinates
+    let (valid_customers, invalid_ids): (Vec<_>, Vec<_>) = customers
+        .into_iter()
+        .partition(|c| c.lat.is_some() && c.lng.is_some());
+
+    let mut warnings = Vec::new();
+    for customer in &invalid_ids {
+        warnings.push(RouteWarning {
+            stop_index: None,
+            warning_type: "MISSING_COORDINATES".to_string(),
+            message: json!({"key": "jobs:customer_no_coordinates_excluded", "params": {"name": customer.name.as_deref().unwrap_or("(unnamed)")}}).to_string(),
+        });
+    }
 
-        // Load user settings for service duration, break config, and fallback working hours
-        let (user_shift_start, user_shift_end, service_duration, break_config) = match queries::settings::get_user_settings(&pool, user_id).await {
-            Ok(Some(settings)) => {
-                let break_cfg = if settings.break_enabled {
-                    Some(BreakConfig {
-                        earliest_time: settings.break_earliest_time,
-                        latest_time: settings.break_latest_time,
-                        duration_minutes: settings.break_duration_minutes as u32,
-                    })
-                } else {
-                    None
-                };
-                (settings.working_hours_start, settings.working_hours_end, settings.default_service_duration_minutes as u32, break_cfg)
+    if valid_customers.is_empty() {
+        return Ok(RoutePlanResponse {
+            stops: vec![],
+            total_distance_km: 0.0,
+            total_duration_minutes: 0,
+            algorithm: "none".to_string(),
+            solve_time_ms: 0,
+            solver_log: vec![],
+            optimization_score: 0,
+            warnings,
+            unassigned: all_customer_ids,
+            geometry: vec![],
+            return_to_depot_distance_km: None,
+            return_to_depot_duration_minutes: None,
+        });
+    }
+
+    // Load crew (if specified) for working hours and arrival buffer
+    let crew = if let Some(crew_id) = plan_request.crew_id {
+        match queries::crew::get_crew(pool, crew_id, user_id).await {
+            Ok(Some(c)) => {
+                info!("Using crew '{}': working hours {:?}-{:?}",
+                    c.name, c.working_hours_start, c.working_hours_end);
+                Some(c)
             }
-            Ok(None) => {
-                warn!("User {} not found in database, using default settings", user_id);
-                (
-                    default_work_start(),
-                    default_work_end(),
-                    DEFAULT_SERVICE_DURATION_MINUTES,
-                    None,
-                )
+            _ => {
+                warn!("Crew {} not found, using user settings", crew_id);
+                None
             }
-            Err(e) => {
-                warn!("Failed to load user settings: {}, using defaults", e);
-                (
-                    default_work_start(),
-                    default_work_end(),
-                    DEFAULT_SERVICE_DURATION_MINUTES,
-                    None,
-                )
-            }
-        };
-
-        // Crew working hours take priority over user settings
-        let shift_start = crew.as_ref().map(|c| c.working_hours_start).unwrap_or(user_shift_start);
-        let shift_end = crew.as_ref().map(|c| c.working_hours_end).unwrap_or(user_shift_end);
-        info!("Route planning shift: {:?}-{:?} (crew override: {})", shift_start, shift_end, crew.is_some());
-
-        // Build VRP problem
-        let vrp_problem = build_vrp_problem(
-            &plan_request.start_location,
-            &valid_customers,
-            shift_start,
-            shift_end,
-            service_duration,
-            break_config,
-        );
+        }
+    } else {
+        None
+    };
+
+    let arrival_buffer_percent = plan_request.arrival_buffer_percent;
+    let arrival_buffer_fixed_minutes = plan_request.arrival_buffer_fixed_minutes;
+
+    // Load user settings for service duration, break config, and fallback working hours
+    let (user_shift_start, user_shift_end, service_duration, break_config) = match queries::settings::get_user_settings(pool, user_id).await {
+        Ok(Some(settings)) => {
+            let break_cfg = if settings.break_enabled {
+                Some(BreakConfig {
+                    earliest_time: settings.break_earliest_time,
+                    latest_time: settings.break_latest_time,
+                    duration_minutes: settings.break_duration_minutes as u32,
+                })
+            } else {
+                None
+            };
+            (settings.working_hours_start, settings.working_hours_end, settings.default_service_duration_minutes as u32, break_cfg)
+        }
+        Ok(None) => {
+            warn!("User {} not found in database, using default settings", user_id);
+            (
+                default_work_start(),
+                default_work_end(),
+                DEFAULT_SERVICE_DURATION_MINUTES,
+                None,
+            )
+        }
+        Err(e) => {
+            warn!("Failed to load user settings: {}, using defaults", e);
+            (
+                default_work_start(),
+                default_work_end(),
+                DEFAULT_SERVICE_DURATION_MINUTES,
+                None,
+            )
+        }
+    };
+
+    // Crew working hours take priority over user settings
+    let shift_start = crew.as_ref().map(|c| c.working_hours_start).unwrap_or(user_shift_start);
+    let shift_end = crew.as_ref().map(|c| c.working_hours_end).unwrap_or(user_shift_end);
+    // A day already under way continues from now; a break already taken
+    // or past its window is not planned
+    let shift_start = plan_request.not_before.map_or(shift_start, |t| t.max(shift_start));
+    let break_config = break_config
+        .filter(|b| !plan_request.without_break && b.latest_time >= shift_start)
+        .map(|b| BreakConfig { earliest_time: b.earliest_time.max(shift_start), ..b });
+    info!("Route planning shift: {:?}-{:?} (crew override: {})", shift_start, shift_end, crew.is_some());
+
+    // Build VRP problem
+    let mut vrp_problem = build_vrp_problem(
+        &plan_request.start_location,
+        &valid_customers,
+        shift_start,
+        shift_end,
+        service_duration,
+        break_config,
+    );
+    vrp_problem.end_location = plan_request.end_location;
+
+    // Build location list for matrix (depot + customers [+ end])
+    let mut locations = vec![plan_request.start_location];
+    for customer in &valid_customers {
+        if let Some(coords) = customer_coordinates(customer) {
+            locations.push(coords);
+        }
+    }
+    locations.extend(plan_request.end_location);
 
-        // Build location list for matrix (depot + customers)
-        let mut locations = vec![plan_request.start_location];
-        for customer in &valid_customers {
-            if let Some(coords) = customer_coordinates(customer) {
-                locations.push(coords);
+    // Units of one building are one physical place: route between
+    // places only
+    let places = LocationGroups::new(&locations, COLOCATION_RADIUS_M);
+    if places.colocated_count() > 0 {
+        debug!("{} stops share a building with another stop", places.colocated_count());
+    }
+
+    // Get distance/time matrices (with fallback to mock if Valhalla fails)
+    let (mut matrices, mut routing_fallback_used) = match routing_service.get_matrices(&places.anchors).await {
+        Ok(m) => (m, false),
+        Err(e) => {
+            warn!("Primary routing service failed: {}. Falling back to mock routing.", e);
+            let mock_service = crate::services::routing::MockRoutingService::new();
+            match mock_service.get_matrices(&places.anchors).await {
+                Ok(m) => (m, true),
+                Err(e2) => {
+                    error!("Mock routing also failed: {}", e2);
+                    return Err(("ROUTING_ERROR", e.to_string()));
+                }
+            }
+        }
+    };
+
+    // Sanity-check the routing data: a few bad legs are replaced by
+    // estimates and flagged, a mostly bad matrix is discarded entirely
+    let mut matrix_affected: Vec<usize> = Vec::new();
+    if !routing_fallback_used {
+        let verdict = verify_matrices(&places.anchors, &mut matrices);
+        diagnostics::observe_matrix(routing_service, &places.anchors, &verdict);
+        match verdict {
+            MatrixVerdict::Clean => {}
+            MatrixVerdict::Repaired { affected } => matrix_affected = places.members(&affected),
+            MatrixVerdict::Untrustworthy => {
+                matrices = MockRoutingService::new().estimate(&places.anchors);
+                routing_fallback_used = true;
             }
         }
+    }
 
-        // Get distance/time matrices (with fallback to mock if Valhalla fails)
-        let (matrices, routing_fallback_used) = match routing_service.get_matrices(&locations).await {
-            Ok(m) => (m, false),
-            Err(e) => {
-                warn!("Primary routing service failed: {}. Falling back to mock routing.", e);
-                let mock_service = crate::services::routing::MockRoutingService::new();
-                match mock_service.get_matrices(&locations).await {
-                    Ok(m) => (m, true),
-                    Err(e2) => {
-                        error!("Mock routing also failed: {}", e2);
-                        let error = ErrorResponse::new(request.id, "ROUTING_ERROR", e.to_string());
-                        let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
-                        continue;
-                    }
-                }
+    // Correct systematic routing bias measured on past routes
+    if !routing_fallback_used {
+        match travel_correction::load_model(pool, user_id).await {
+            Ok(model) if !model.is_empty() => {
+                model.apply_to_matrix(&mut matrices, &places.anchors);
+                debug!("Travel time correction applied ({} zone factors)", model.zone_factor_count());
             }
-        };
+            Ok(_) => {}
+            Err(e) => warn!("Failed to load travel time correction: {}", e),
+        }
+    }
 
-        // Solve VRP - solver handles timeout and spawn_blocking internally
-        let solver_config = SolverConfig::with_buffer(5, 500, arrival_buffer_percent, arrival_buffer_fixed_minutes);
-        let solver = VrpSolver::new(solver_config);
-        let solution = match solver.solve(&vrp_problem, &matrices, plan_request.date).await {
-            Ok(s) => {
-                // If solver used heuristic fallback and we had time windows,
-                // check if the solution respects them (heuristic ignores time windows)
-                if s.algorithm.contains("heuristic") && vrp_problem.stops.iter().any(|stop| stop.time_window.is_some()) {
-                    info!("Heuristic fallback used with time windows present - windows may not be respected");
-                }
-                s
+    // Legs between units of the same building are zero
+    let matrices = places.expand(&matrices);
+
+    // Solve VRP - solver handles timeout and spawn_blocking internally
+    let solver_config = SolverConfig::with_buffer(5, 500, arrival_buffer_percent, arrival_buffer_fixed_minutes);
+    let solver = VrpSolver::new(solver_config);
+    let solution = match solver.solve(&vrp_problem, &matrices, plan_request.date).await {
+        Ok(s) => {
+            // If solver used heuristic fallback and we had time windows,
+            // check if the solution respects them (heuristic ignores time windows)
+            if s.algorithm.contains("heuristic") && vrp_problem.stops.iter().any(|stop| stop.time_window.is_some()) {
+                info!("Heuristic fallback used with time windows present - windows may not be respected");
             }
-            Err(e) => {
-                error!("VRP solver failed completely: {}", e);
-                let error = ErrorResponse::new(request.id, "SOLVER_ERROR", e.to_string());
-                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
-                continue;
+            s
+        }
+        Err(e) => {
+            error!("VRP solver failed completely: {}", e);
+            return Err(("SOLVER_ERROR", e.to_string()));
+        }
+    };
+
+    // Build response
+    let customer_matrix_index: HashMap<Uuid, usize> = valid_customers
+        .iter()
+        .enumerate()
+        .map(|(idx, c)| (c.id, idx + 1)) // 0 is depot
+        .collect();
+    let mut planned_stops: Vec<PlannedRouteStop> = Vec::new();
+    let mut previous_matrix_index: usize = 0;
+    for stop in &solution.stops {
+        // Find original customer
+        if let Some(customer) = valid_customers.iter().find(|c| c.id.to_string() == stop.stop_id) {
+            let matrix_index = customer_matrix_index.get(&customer.id).copied().unwrap_or(0);
+            let leg_distance_km = if matrix_index > 0 {
+                Some(matrices.distance(previous_matrix_index, matrix_index) as f64 / 1000.0)
+            } else {
+                None
+            };
+            let leg_duration_min = if matrix_index > 0 {
+                Some((matrices.duration(previous_matrix_index, matrix_index) as i32 + 30) / 60)
+            } else {
+                None
+            };
+            planned_stops.push(PlannedRouteStop {
+                customer_id: customer.id,
+                customer_name: customer.name.clone().unwrap_or_default(),
+                address: format!(
+                    "{}, {} {}",
+                    customer.street.as_deref().unwrap_or(""),
+                    customer.city.as_deref().unwrap_or(""),
+                    customer.postal_code.as_deref().unwrap_or("")
+                ),
+                coordinates: customer_coordinates(customer).unwrap_or(plan_request.start_location),
+                order: stop.order as i32,
+                eta: stop.arrival_time,
+                etd: stop.departure_time,
+                service_duration_minutes: customer
+                    .fixed
+                    .as_ref()
+                    .map_or(service_duration, |f| fixed_service_minutes(f, service_duration)) as i32,
+                time_window: match (&customer.fixed, customer.scheduled_time_start, customer.scheduled_time_end) {
+                    (Some(fixed), _, _) => Some(crate::types::TimeWindow {
+                        start: fixed.arrival_time,
+                        end: fixed.arrival_time
+                            + chrono::Duration::minutes(fixed_service_minutes(fixed, service_duration) as i64),
+                        is_hard: true,
+                    }),
+                    (None, Some(start), Some(end)) => Some(crate::types::TimeWindow {
+                        start,
+                        end,
+                        is_hard: true,
+                    }),
+                    _ => None,
+                },
+                stop_type: Some(StopType::Customer),
+                break_duration_minutes: None,
+                break_time_start: None,
+                distance_from_previous_km: leg_distance_km,
+                duration_from_previous_minutes: leg_duration_min,
+                override_service_duration_minutes: None,
+                override_travel_duration_minutes: None,
+            });
+            if matrix_index > 0 {
+                previous_matrix_index = matrix_index;
             }
-        };
+        } else if stop.customer_id.is_nil() {
+            // Break stop — crew stays at the previous location,
+            // so the travel leg is 0 km / 0 min.
+            planned_stops.push(PlannedRouteStop {
+                customer_id: Uuid::nil(),
+                customer_name: "Pauza".to_string(),
+                address: "Pauza".to_string(),
+                coordinates: plan_request.start_location,
+                order: stop.order as i32,
+                eta: stop.arrival_time,
+                etd: stop.departure_time,
+                service_duration_minutes: ((stop.departure_time - stop.arrival_time).num_minutes().max(0)) as i32,
+                time_window: None,
+                stop_type: Some(StopType::Break),
+                break_duration_minutes: Some(((stop.departure_time - stop.arrival_time).num_minutes().max(0)) as i32),
+                break_time_start: Some(stop.arrival_time),
+                distance_from_previous_km: Some(0.0),
+                duration_from_previous_minutes: Some(0),
+                override_service_duration_minutes: None,
+                override_travel_duration_minutes: None,
+            });
+        }
+    }
 
-        // Build response
-        let customer_matrix_index: HashMap<Uuid, usize> = valid_customers
+    // Add solver warnings
+    for w in &solution.warnings {
+        warnings.push(RouteWarning {
+            stop_index: None,
+            warning_type: w.warning_type.clone(),
+            message: w.message.clone(),
+        });
+    }
+
+    // Flag stops whose travel legs had to be estimated
+    if !matrix_affected.is_empty() {
+        let matrix_customers: Vec<(Uuid, String)> = valid_customers
             .iter()
-            .enumerate()
-            .map(|(idx, c)| (c.id, idx + 1)) // 0 is depot
+            .map(|c| (c.id, c.name.clone().unwrap_or_default()))
             .collect();
-        let mut planned_stops: Vec<PlannedRouteStop> = Vec::new();
-        let mut previous_matrix_index: usize = 0;
-        for stop in &solution.stops {
-            // Find original customer
-            if let Some(customer) = valid_customers.iter().find(|c| c.id.to_string() == stop.stop_id) {
-                let matrix_index = customer_matrix_index.get(&customer.id).copied().unwrap_or(0);
-                let leg_distance_km = if matrix_index > 0 {
-                    Some(matrices.distance(previous_matrix_index, matrix_index) as f64 / 1000.0)
-                } else {
-                    None
-                };
-                let leg_duration_min = if matrix_index > 0 {
-                    Some((matrices.duration(previous_matrix_index, matrix_index) as i32 + 30) / 60)
-                } else {
-                    None
-                };
-                planned_stops.push(PlannedRouteStop {
-                    customer_id: customer.id,
-                    customer_name: customer.name.clone().unwrap_or_default(),
-                    address: format!(
-                        "{}, {} {}",
-                        customer.street.as_deref().unwrap_or(""),
-                        customer.city.as_deref().unwrap_or(""),
-                        customer.postal_code.as_deref().unwrap_or("")
-                    ),
-                    coordinates: customer_coordinates(customer).unwrap_or(plan_request.start_location),
-                    order: stop.order as i32,
-                    eta: stop.arrival_time,
-                    etd: stop.departure_time,
-                    service_duration_minutes: service_duration as i32,
-                    time_window: match (customer.scheduled_time_start, customer.scheduled_time_end) {
-                        (Some(start), Some(end)) => Some(crate::types::TimeWindow {
-                            start,
-                            end,
-                            is_hard: true,
-                        }),
-                        _ => None,
-                    },
-                    stop_type: Some(StopType::Customer),
-                    break_duration_minutes: None,
-                    break_time_start: None,
-                    distance_from_previous_km: leg_distance_km,
-                    duration_from_previous_minutes: leg_duration_min,
-                    override_service_duration_minutes: None,
-                    override_travel_duration_minutes: None,
-                });
-                if matrix_index > 0 {
-                    previous_matrix_index = matrix_index;
-                }
-            } else if stop.customer_id.is_nil() {
-                // Break stop — crew stays at the previous location,
-                // so the travel leg is 0 km / 0 min.
-                planned_stops.push(PlannedRouteStop {
-                    customer_id: Uuid::nil(),
-                    customer_name: "Pauza".to_string(),
-                    address: "Pauza".to_string(),
-                    coordinates: plan_request.start_location,
-                    order: stop.order as i32,
-                    eta: stop.arrival_time,
-                    etd: stop.departure_time,
-                    service_duration_minutes: ((stop.departure_time - stop.arrival_time).num_minutes().max(0)) as i32,
-                    time_window: None,
-                    stop_type: Some(StopType::Break),
-                    break_duration_minutes: Some(((stop.departure_time - stop.arrival_time).num_minutes().max(0)) as i32),
-                    break_time_start: Some(stop.arrival_time),
-                    distance_from_previous_km: Some(0.0),
-                    duration_from_previous_minutes: Some(0),
-                    override_service_duration_minutes: None,
-                    override_travel_duration_minutes: None,
-                });
-            }
-        }
+        warnings.extend(anomaly_warnings(&matrix_affected, &matrix_customers, &planned_stops));
+    }
 
-        // Add solver warnings
-        for w in &solution.warnings {
-            warnings.push(RouteWarning {
-                stop_index: None,
-                warning_type: w.warning_type.clone(),
-                message: w.message.clone(),
-            });
+    // Add routing fallback warning if applicable
+    if routing_fallback_used {
+        warnings.push(RouteWarning {
+            stop_index: None,
+            warning_type: "ROUTING_FALLBACK".to_string(),
+            message: json!({"key": "jobs:routing_fallback"}).to_string(),
+        });
+    }
+
+    // Collect unassigned customer IDs
+    let mut unassigned: Vec<Uuid> = invalid_ids.iter().map(|c| c.id).collect();
+    for stop_id in &solution.unassigned {
+        if let Ok(id) = Uuid::parse_str(stop_id) {
+            unassigned.push(id);
         }
+    }
 
-        // Add routing fallback warning if applicable
-        if routing_fallback_used {
+    // A fixed appointment that could not be kept needs the dispatcher
+    for customer in invalid_ids.iter().chain(valid_customers.iter()) {
+        if customer.fixed.is_some() && unassigned.contains(&customer.id) {
             warnings.push(RouteWarning {
                 stop_index: None,
-                warning_type: "ROUTING_FALLBACK".to_string(),
-                message: json!({"key": "jobs:routing_fallback"}).to_string(),
+                warning_type: "FIXED_STOP_UNASSIGNED".to_string(),
+                message: json!({"key": "jobs:fixed_stop_unassigned", "params": {"name": customer.name.as_deref().unwrap_or("(unnamed)")}}).to_string(),
             });
         }
+    }
 
-        // Collect unassigned customer IDs
-        let mut unassigned: Vec<Uuid> = invalid_ids.iter().map(|c| c.id).collect();
-        for stop_id in &solution.unassigned {
-            if let Ok(id) = Uuid::parse_str(stop_id) {
-                unassigned.push(id);
-            }
+    // Build route geometry
+    // Order: depot -> stops in order -> depot (or the end location)
+    let geometry = if !planned_stops.is_empty() {
+        let mut route_coords: Vec<Coordinates> = vec![plan_request.start_location];
+        for stop in &planned_stops {
+            route_coords.push(stop.coordinates);
         }
-
-        // Build route geometry
-        // Order: depot -> stops in order -> depot
-        let geometry = if !planned_stops.is_empty() {
-            let mut route_coords: Vec<Coordinates> = vec![plan_request.start_location];
-            for stop in &planned_stops {
-                route_coords.push(stop.coordinates);
-            }
-            route_coords.push(plan_request.start_location); // Return to depot
-            
-            // Try to get real route geometry from Valhalla
-            if !routing_fallback_used {
-                if let Some(valhalla) = routing_service.as_any().downcast_ref::<crate::services::routing::ValhallaClient>() {
-                    match valhalla.get_route_geometry(&route_coords).await {
-                        Ok(geom) => geom.coordinates,
-                        Err(e) => {
-                            warn!("Failed to get route geometry: {}. Using straight lines.", e);
-                            route_coords.iter().map(|c| [c.lng, c.lat]).collect()
-                        }
+        route_coords.push(plan_request.end_location.unwrap_or(plan_request.start_location)); // Return to depot
+        
+        // Try to get real route geometry from Valhalla
+        if !routing_fallback_used {
+            if let Some(valhalla) = routing_service.as_any().downcast_ref::<crate::services::routing::ValhallaClient>() {
+                match valhalla.get_route_geometry(&route_coords).await {
+                    Ok(geom) => geom.coordinates,
+                    Err(e) => {
+                        warn!("Failed to get route geometry: {}. Using straight lines.", e);
+                        route_coords.iter().map(|c| [c.lng, c.lat]).collect()
                     }
-                } else {
-                    // Not a Valhalla client, use straight lines
-                    route_coords.iter().map(|c| [c.lng, c.lat]).collect()
                 }
             } else {
-                // Routing fallback was used, use straight lines
+                // Not a Valhalla client, use straight lines
                 route_coords.iter().map(|c| [c.lng, c.lat]).collect()
             }
         } else {
-            vec![]
-        };
-
-        let return_to_depot_distance_km = if previous_matrix_index > 0 {
-            Some(matrices.distance(previous_matrix_index, 0) as f64 / 1000.0)
-        } else {
-            None
-        };
-        let return_to_depot_duration_minutes = if previous_matrix_index > 0 {
-            Some((matrices.duration(previous_matrix_index, 0) as i32 + 30) / 60)
-        } else {
-            None
-        };
-
-        let response = SuccessResponse::new(request.id, RoutePlanResponse {
-            stops: planned_stops,
-            total_distance_km: solution.total_distance_meters as f64 / 1000.0,
-            total_duration_minutes: (solution.total_duration_seconds / 60) as i32,
-            algorithm: solution.algorithm.clone(),
-            solve_time_ms: solution.solve_time_ms,
-            solver_log: solution.solver_log.clone(),
-            optimization_score: solution.optimization_score as i32,
-            warnings,
-            unassigned,
-            geometry,
-            return_to_depot_distance_km,
-            return_to_depot_duration_minutes,
-        });
-
-        info!(
-            "Route planned for {} customers: {:.1} km, {} min, score={}",
-            valid_customers.len(),
-            solution.total_distance_meters as f64 / 1000.0,
-            solution.total_duration_seconds / 60,
-            solution.optimization_score
-        );
-
-        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
-    }
-
-    Ok(())
+            // Routing fallback was used, use straight lines
+            route_coords.iter().map(|c| [c.lng, c.lat]).collect()
+        }
+    } else {
+        vec![]
+    };
+
+    let end_index = vrp_problem.end_index();
+    let return_to_depot_distance_km = if previous_matrix_index > 0 {
+        Some(matrices.distance(previous_matrix_index, end_index) as f64 / 1000.0)
+    } else {
+        None
+    };
+    let return_to_depot_duration_minutes = if previous_matrix_index > 0 {
+        Some((matrices.duration(previous_matrix_index, end_index) as i32 + 30) / 60)
+    } else {
+        None
+    };
+
+    let response = RoutePlanResponse {
+        stops: planned_stops,
+        total_distance_km: solution.total_distance_meters as f64 / 1000.0,
+        total_duration_minutes: (solution.total_duration_seconds / 60) as i32,
+        algorithm: solution.algorithm.clone(),
+        solve_time_ms: solution.solve_time_ms,
+        solver_log: solution.solver_log.clone(),
+        optimization_score: solution.optimization_score as i32,
+        warnings,
+        unassigned,
+        geometry,
+        return_to_depot_distance_km,
+        return_to_depot_duration_minutes,
+    };
+
+    info!(
+        "Route planned for {} customers: {:.1} km, {} min, score={}",
+        valid_customers.len(),
+        solution.total_distance_meters as f64 / 1000.0,
+        solution.total_duration_seconds / 60,
+        solution.optimization_score
+    );
+
+    Ok(response)
 }
 
 /// Simple customer data for route planning
@@ -437,6 +563,8 @@ struct CustomerForRoute {
     /// Scheduled time window from revision (if any)
     scheduled_time_start: Option<chrono::NaiveTime>,
     scheduled_time_end: Option<chrono::NaiveTime>,
+    /// Appointment pinned by the dispatcher (partial day planning)
+    fixed: Option<FixedRouteStop>,
 }
 
 /// Load customers from database, including scheduled time windows from revisions
@@ -468,16 +596,32 @@ async fn load_customers(
                 }
             };
 
+            // Devices at a service site or with a GPS override are visited there
+            let location = match queries::customer_site::service_location_for_date(
+                pool, user_id, *customer_id, date,
+            ).await {
+                Ok(location) => location,
+                Err(e) => {
+                    warn!("Failed to load service location for customer {}: {}", customer_id, e);
+                    None
+                }
+            };
+            let (street, city, postal_code) = match &location {
+                Some(l) if l.has_address() => (l.street.clone(), l.city.clone(), l.postal_code.clone()),
+                _ => (customer.street.clone(), customer.city.clone(), customer.postal_code.clone()),
+            };
+
             customers.push(CustomerForRoute {
                 id: customer.id,
                 name: customer.name.clone(),
-                street: customer.street.clone(),
-                city: customer.city.clone(),
-                postal_code: customer.postal_code.clone(),
-                lat: customer.lat,
-                lng: customer.lng,
+                street,
+                city,
+                postal_code,
+                lat: location.as_ref().map_or(customer.lat, |l| Some(l.coordinates.lat)),
+                lng: location.as_ref().map_or(customer.lng, |l| Some(l.coordinates.lng)),
                 scheduled_time_start: tw_start,
                 scheduled_time_end: tw_end,
+                fixed: None,
             });
         }
     }
@@ -501,8 +645,17 @@ fn build_vrp_problem(
             // Scheduled customers support two modes:
             // - Flexible: service < full window, window [start, end-service].
             // - Pinned: service >= full window, point arrival [start, start].
-            let (time_window, stop_service_duration) = match (c.scheduled_time_start, c.scheduled_time_end) {
-                (Some(start), Some(end)) => {
+            let (time_window, stop_service_duration) = match (&c.fixed, c.scheduled_time_start, c.scheduled_time_end) {
+                // Fixed appointment: point arrival at the agreed time
+                (Some(fixed), _, _) => (
+                    Some(StopTimeWindow {
+                        start: fixed.arrival_time,
+                        end: fixed.arrival_time,
+                        is_hard: true,
+                    }),
+                    fixed_service_minutes(fixed, service_duration_minutes),
+                ),
+                (None, Some(start), Some(end)) => {
                     let slot_minutes = (end - start).num_minutes();
                     if slot_minutes > 0 && service_duration_minutes < slot_minutes as u32 {
                         let latest_start = end - chrono::Duration::minutes(service_duration_minutes as i64);
@@ -548,7 +701,8 @@ fn build_vrp_problem(
                 coordinates,
                 service_duration_minutes: stop_service_duration,
                 time_window,
-                priority: 1,
+                priority: if c.fixed.is_some() { FIXED_STOP_PRIORITY } else { 1 },
+                demand: StopDemand::default(),
             })
         })
         .collect();
@@ -560,10 +714,16 @@ fn build_vrp_problem(
         stops,
         shift_start,
         shift_end,
+        end_location: None,
         break_config,
     }
 }
 
+/// Service minutes of a fixed appointment
+fn fixed_service_minutes(fixed: &FixedRouteStop, default_minutes: u32) -> u32 {
+    fixed.service_duration_minutes.filter(|m| *m > 0).unwrap_or(default_minutes)
+}
+
 fn customer_coordinates(customer: &CustomerForRoute) -> Option<Coordinates> {
     Some(Coordinates {
         lat: customer.lat?,
@@ -616,7 +776,7 @@ where
 fn default_buffer_percent() -> f64 { 10.0 }
 
 /// Request to save a route
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SaveRouteRequest {
     pub date: NaiveDate,
@@ -639,7 +799,7 @@ pub struct SaveRouteRequest {
 }
 
 /// A stop to save
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SaveRouteStop {
     #[serde(default, deserialize_with = "deserialize_optional_uuid")]
@@ -664,6 +824,15 @@ pub struct SaveRouteStop {
     pub override_service_duration_minutes: Option<i32>,
     #[serde(default)]
     pub override_travel_duration_minutes: Option<i32>,
+    /// Dispatcher instructions; kept from the previous save when both notes
+    /// and tasks are absent
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub tasks: Option<Vec<RouteStopTask>>,
+    /// Chosen place of a break stop; ignored on customer stops
+    #[serde(default)]
+    pub break_location: Option<BreakLocation>,
 }
 
 /// Response after saving a route
@@ -746,8 +915,8 @@ pub async fn handle_save(
         };
 
         // Check auth
-        let user_id = match auth::extract_auth(&request, &jwt_secret) {
-            Ok(info) => info.data_user_id(),
+        let (user_id, editor_id) = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => (info.data_user_id(), info.user_id),
             Err(e) => {
                 warn!("Route save auth failed: {}", e);
                 let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
@@ -756,7 +925,40 @@ pub async fn handle_save(
             }
         };
 
-        let payload = request.payload;
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let payload = match hooks::run_before(&client, BEFORE_ROUTE_SAVE, user_id, request.payload).await {
+            HookOutcome::Proceed(payload) => payload,
+            HookOutcome::Vetoed { plugin, reason } => {
+                info!("route.save vetoed by hook plugin {}: {}", plugin, reason);
+                let error = ErrorResponse::new(request.id, "HOOK_VETOED", reason);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        match queries::route_lock::route_id_for_date(&pool, user_id, payload.date).await {
+            Ok(Some(route_id)) => {
+                if reject_if_locked(&client, &pool, &reply, request.id, user_id, route_id, editor_id).await? {
+                    continue;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to look up route of {} for lock check: {}", payload.date, e),
+        }
+        if let Err(msg) = payload
+            .stops
+            .iter()
+            .try_for_each(|stop| {
+                validate_stop_notes(stop.notes.as_deref(), stop.tasks.as_deref().unwrap_or_default())?;
+                stop.break_location.as_ref().map_or(Ok(()), BreakLocation::validate)
+            })
+        {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
         info!("Saving route for date {} with {} stops", payload.date, payload.stops.len());
         for (i, stop) in payload.stops.iter().enumerate() {
             debug!(
@@ -785,6 +987,15 @@ pub async fn handle_save(
             payload.arrival_buffer_fixed_minutes,
         ).await {
             Ok(route) => {
+                // Remember stop notes so a save that does not send them keeps them
+                let previous_notes = match queries::route::list_route_stop_notes(&pool, route.id).await {
+                    Ok(rows) => rows,
+                    Err(e) => {
+                        warn!("Failed to load stop notes of route {}: {}", route.id, e);
+                        Vec::new()
+                    }
+                };
+
                 // Delete existing stops
                 if let Err(e) = queries::route::delete_route_stops(&pool, route.id).await {
                     error!("Failed to delete existing stops: {}", e);
@@ -796,7 +1007,21 @@ pub async fn handle_save(
                 // Insert new stops
                 let mut saved_count = 0;
                 for stop in &payload.stops {
-                    let stop_type = stop.stop_type.unwrap_or(StopType::Customer).as_str().to_string();
+                    let stop_type = stop.stop_type.unwrap_or(StopType::Customer);
+                    let break_location = stop.break_location.as_ref().filter(|_| stop_type == StopType::Break);
+                    let (notes, tasks) = if stop.notes.is_none() && stop.tasks.is_none() {
+                        previous_notes
+                            .iter()
+                            .find(|(customer_id, revision_id, _, _)| {
+                                stop.customer_id.is_some()
+                                    && *customer_id == stop.customer_id
+                                    && *revision_id == stop.revision_id
+                            })
+                            .map(|(_, _, notes, tasks)| (notes.clone(), tasks.0.clone()))
+                            .unwrap_or_default()
+                    } else {
+                        (stop.notes.clone(), stop.tasks.clone().unwrap_or_default())
+                    };
                     if let Err(e) = queries::route::insert_route_stop(
                         &pool,
                         route.id,
@@ -808,13 +1033,16 @@ pub async fn handle_save(
                         stop.etd,
                         stop.distance_from_previous_km,
                         stop.duration_from_previous_minutes,
-                        stop_type,
+                        stop_type.as_str().to_string(),
                         stop.break_duration_minutes,
                         stop.break_time_start,
                         stop.status.as_deref(),
                         stop.service_duration_minutes,
                         stop.override_service_duration_minutes,
                         stop.override_travel_duration_minutes,
+                        notes.as_deref(),
+                        &tasks,
+                        break_location,
                     ).await {
                         warn!("Failed to insert stop: {}", e);
                     } else {
@@ -832,6 +1060,9 @@ pub async fn handle_save(
                 );
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                 info!("Saved route {} with {} stops", route.id, saved_count);
+                webhook_delivery::emit(&client, &pool, user_id, "route.created", &route);
+                dispatch_board::publish_route(&client, &pool, user_id, route.id);
+                hooks::notify_after(&client, AFTER_ROUTE_SAVE, user_id, &route);
 
                 // Update user's last-used buffer preferences
                 if let Err(e) = queries::settings::update_last_arrival_buffer(
@@ -861,6 +1092,9 @@ pub struct UpdateRouteRequest {
     pub crew_id: Option<Option<Uuid>>,
     pub depot_id: Option<Option<Uuid>>,
     pub status: Option<String>,
+    /// Notes and checklists of stops on this route
+    #[serde(default)]
+    pub stop_notes: Vec<UpdateRouteStopNotesRequest>,
 }
 
 /// Response after updating a route
@@ -898,8 +1132,8 @@ pub async fn handle_update(
             }
         };
 
-        let user_id = match auth::extract_auth(&request, &jwt_secret) {
-            Ok(info) => info.data_user_id(),
+        let (user_id, editor_id) = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => (info.data_user_id(), info.user_id),
             Err(_) => {
                 let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
                 let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
@@ -907,18 +1141,47 @@ pub async fn handle_update(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let payload = request.payload;
-        info!("Updating route {} (crew={:?}, depot={:?}, status={:?})",
-            payload.route_id, payload.crew_id, payload.depot_id, payload.status);
+        if reject_if_locked(&client, &pool, &reply, request.id, user_id, payload.route_id, editor_id).await? {
+            continue;
+        }
+        if let Err(msg) = payload.stop_notes.iter().try_for_each(|n| n.validate()) {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+        info!("Updating route {} (crew={:?}, depot={:?}, status={:?}, stop notes={})",
+            payload.route_id, payload.crew_id, payload.depot_id, payload.status, payload.stop_notes.len());
+
+        // Only a transition into "confirmed" is logged to the customers' history
+        let confirming = payload.status.as_deref() == Some(RouteStatus::Confirmed.as_str())
+            && matches!(
+                queries::route::get_route_by_id(&pool, user_id, payload.route_id).await,
+                Ok(Some(route)) if route.status != RouteStatus::Confirmed
+            );
 
-        match queries::route::update_route(
-            &pool,
-            payload.route_id,
-            user_id,
-            payload.crew_id,
-            payload.depot_id,
-            payload.status.as_deref(),
-        ).await {
+        let result = async {
+            let mut updated = queries::route::update_route(
+                &pool,
+                payload.route_id,
+                user_id,
+                payload.crew_id,
+                payload.depot_id,
+                payload.status.as_deref(),
+            ).await?;
+            for stop_notes in &payload.stop_notes {
+                updated |= queries::route::update_route_stop_notes(&pool, user_id, Some(payload.route_id), stop_notes)
+                    .await?
+                    .is_some();
+            }
+            anyhow::Ok(updated)
+        }.await;
+
+        match result {
             Ok(updated) => {
                 let response = SuccessResponse::new(
                     request.id,
@@ -927,6 +1190,16 @@ pub async fn handle_update(
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                 if updated {
                     info!("Route {} updated", payload.route_id);
+                    webhook_delivery::emit(&client, &pool, user_id, "route.updated", &serde_json::json!({
+                        "id": payload.route_id,
+                        "crewId": payload.crew_id,
+                        "depotId": payload.depot_id,
+                        "status": payload.status,
+                    }));
+                    dispatch_board::publish_route(&client, &pool, user_id, payload.route_id);
+                    if confirming {
+                        communication_log::record_route_confirmed(&pool, user_id, payload.route_id).await;
+                    }
                 } else {
                     warn!("Route {} not found or not owned by user", payload.route_id);
                 }
@@ -942,29 +1215,15 @@ pub async fn handle_update(
     Ok(())
 }
 
-/// Request to delete a route
-#[derive(Debug, serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct DeleteRouteRequest {
-    pub route_id: Uuid,
-}
-
-/// Response after deleting a route
-#[derive(Debug, serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct DeleteRouteResponse {
-    pub deleted: bool,
-}
-
-/// Handle route.delete messages
-pub async fn handle_delete(
+/// Handle route.stop.note.update messages
+pub async fn handle_stop_note_update(
     client: Client,
     mut subscriber: Subscriber,
     pool: PgPool,
     jwt_secret: Arc<String>,
 ) -> Result<()> {
     while let Some(msg) = subscriber.next().await {
-        debug!("Received route.delete message");
+        debug!("Received route.stop.note.update message");
 
         let reply = match msg.reply {
             Some(ref reply) => reply.clone(),
@@ -974,7 +1233,7 @@ pub async fn handle_delete(
             }
         };
 
-        let request: Request<DeleteRouteRequest> = match serde_json::from_slice(&msg.payload) {
+        let request: Request<UpdateRouteStopNotesRequest> = match serde_json::from_slice(&msg.payload) {
             Ok(req) => req,
             Err(e) => {
                 error!("Failed to parse request: {}", e);
@@ -984,8 +1243,8 @@ pub async fn handle_delete(
             }
         };
 
-        let user_id = match auth::extract_auth(&request, &jwt_secret) {
-            Ok(info) => info.data_user_id(),
+        let (user_id, editor_id) = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => (info.data_user_id(), info.user_id),
             Err(_) => {
                 let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
                 let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
@@ -993,25 +1252,135 @@ pub async fn handle_delete(
             }
         };
 
-        info!("Deleting route {}", request.payload.route_id);
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
 
-        match queries::route::delete_route_by_id(&pool, request.payload.route_id, user_id).await {
-            Ok(deleted) => {
-                let response = SuccessResponse::new(
-                    request.id,
-                    DeleteRouteResponse { deleted },
-                );
-                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
-                if deleted {
-                    info!("Route {} deleted", request.payload.route_id);
-                } else {
-                    warn!("Route {} not found or not owned by user", request.payload.route_id);
+        if let Err(msg) = request.payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::route_lock::route_id_of_stop(&pool, user_id, request.payload.stop_id).await {
+            Ok(Some(route_id)) => {
+                if reject_if_locked(&client, &pool, &reply, request.id, user_id, route_id, editor_id).await? {
+                    continue;
                 }
             }
-            Err(e) => {
-                error!("Failed to delete route: {}", e);
-                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
-                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            Ok(None) => {}
+            Err(e) => warn!("Failed to look up route of stop {} for lock check: {}", request.payload.stop_id, e),
+        }
+
+        match queries::route::update_route_stop_notes(&pool, user_id, None, &request.payload).await {
+            Ok(Some(notes)) => {
+                let response = SuccessResponse::new(request.id, notes);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Route stop not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to update route stop notes: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Request to delete a route
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteRouteRequest {
+    pub route_id: Uuid,
+}
+
+/// Response after deleting a route
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteRouteResponse {
+    pub deleted: bool,
+}
+
+/// Handle route.delete messages
+pub async fn handle_delete(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received route.delete message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<DeleteRouteRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let (user_id, editor_id) = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => (info.data_user_id(), info.user_id),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if reject_if_locked(&client, &pool, &reply, request.id, user_id, request.payload.route_id, editor_id).await? {
+            continue;
+        }
+
+        info!("Deleting route {}", request.payload.route_id);
+
+        // The board feed of the route's day learns about the removal
+        let route_date = queries::route::get_route_by_id(&pool, user_id, request.payload.route_id)
+            .await
+            .ok()
+            .flatten()
+            .map(|route| route.date);
+
+        match queries::route::delete_route_by_id(&pool, request.payload.route_id, user_id).await {
+            Ok(deleted) => {
+                let response = SuccessResponse::new(
+                    request.id,
+                    DeleteRouteResponse { deleted },
+                );
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+                if deleted {
+                    info!("Route {} deleted", request.payload.route_id);
+                    webhook_delivery::emit(&client, &pool, user_id, "route.deleted", &serde_json::json!({ "id": request.payload.route_id }));
+                    if let Some(date) = route_date {
+                        dispatch_board::publish_route_removed(&client, user_id, date, request.payload.route_id);
+                    }
+                } else {
+                    warn!("Route {} not found or not owned by user", request.payload.route_id);
+                }
+            }
+            Err(e) => {
+                error!("Failed to delete route: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
             }
         }
     }
@@ -1091,7 +1460,7 @@ pub async fn handle_get(
                             request.id,
                             GetRouteResponse {
                                 route: Some(route),
-                                stops,
+                                stops: demo_mode::apply(request.demo_mode, stops),
                             },
                         );
                         let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
@@ -1130,7 +1499,7 @@ pub async fn handle_get(
 pub async fn handle_list_for_date(
     client: Client,
     mut subscriber: Subscriber,
-    pool: PgPool,
+    repos: Repositories,
     jwt_secret: Arc<String>,
 ) -> Result<()> {
     while let Some(msg) = subscriber.next().await {
@@ -1175,7 +1544,7 @@ pub async fn handle_list_for_date(
             }
         };
 
-        match queries::route::list_routes_for_date(&pool, user_id, date).await {
+        match repos.routes.list_routes_for_date(user_id, date).await {
             Ok(routes) => {
                 let response = SuccessResponse::new(
                     request.id,
@@ -1260,6 +1629,346 @@ pub async fn handle_list(
     Ok(())
 }
 
+// ============================================================================
+// Route Edit Locks
+// ============================================================================
+
+/// Reply ROUTE_LOCKED when another dispatcher holds the route's edit lock.
+/// Returns true when the request was rejected.
+async fn reject_if_locked(
+    client: &Client,
+    pool: &PgPool,
+    reply: &async_nats::Subject,
+    request_id: Uuid,
+    user_id: Uuid,
+    route_id: Uuid,
+    editor_id: Uuid,
+) -> Result<bool> {
+    match queries::route_lock::get_active_route_lock(pool, user_id, route_id).await {
+        Ok(Some(lock)) if lock.blocks(editor_id, chrono::Utc::now()) => {
+            let _ = client.publish(reply.clone(), serde_json::to_vec(&route_locked_error(request_id, &lock))?.into()).await;
+            Ok(true)
+        }
+        Ok(_) => Ok(false),
+        Err(e) => {
+            // A broken lock table must not block every save
+            warn!("Failed to check edit lock of route {}: {}", route_id, e);
+            Ok(false)
+        }
+    }
+}
+
+fn route_locked_error(request_id: Uuid, lock: &RouteLock) -> ErrorResponse {
+    let mut error = ErrorResponse::new(
+        request_id,
+        "ROUTE_LOCKED",
+        format!(
+            "Route is being edited by {} until {}",
+            lock.holder_name.as_deref().unwrap_or("another dispatcher"),
+            lock.expires_at.format("%H:%M:%S UTC")
+        ),
+    );
+    error.error.details = serde_json::to_value(lock).ok();
+    error
+}
+
+/// Handle route.lock messages - acquire or renew a route's edit lock
+pub async fn handle_lock(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received route.lock message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<RouteLockRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let (user_id, editor_id) = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => (info.data_user_id(), info.user_id),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = request.payload;
+        if let Err(msg) = payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let result = async {
+            let acquired =
+                queries::route_lock::acquire_route_lock(&pool, user_id, payload.route_id, editor_id, payload.ttl()).await?;
+            let lock = queries::route_lock::get_active_route_lock(&pool, user_id, payload.route_id).await?;
+            anyhow::Ok((acquired, lock))
+        }
+        .await;
+
+        let response = match result {
+            Ok((true, Some(lock))) => {
+                debug!("Route {} locked by {} until {}", payload.route_id, editor_id, lock.expires_at);
+                serde_json::to_vec(&SuccessResponse::new(request.id, lock))?
+            }
+            Ok((false, Some(lock))) => serde_json::to_vec(&route_locked_error(request.id, &lock))?,
+            Ok((_, None)) => serde_json::to_vec(&ErrorResponse::new(request.id, "NOT_FOUND", "Route not found"))?,
+            Err(e) => {
+                error!("Failed to lock route {}: {}", payload.route_id, e);
+                serde_json::to_vec(&ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string()))?
+            }
+        };
+        let _ = client.publish(reply, response.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle route.unlock messages - release the caller's edit lock
+pub async fn handle_unlock(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received route.unlock message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<RouteUnlockRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let (user_id, editor_id) = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => (info.data_user_id(), info.user_id),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::route_lock::release_route_lock(&pool, user_id, request.payload.route_id, editor_id).await {
+            Ok(released) => {
+                let response = SuccessResponse::new(request.id, RouteUnlockResponse { released });
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to unlock route {}: {}", request.payload.route_id, e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle route.analysis messages - compare planned and actual times of past
+/// routes and optionally store the resulting travel time correction
+pub async fn handle_analysis(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received route.analysis message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<RouteAnalysisRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        let user_id = auth_info.data_user_id();
+
+        let payload = request.payload;
+        if let Err(msg) = payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        // The correction changes planning for the whole account
+        if payload.apply_correction.is_some() && auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Changing the travel time correction requires customer or admin role");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let result = async {
+            let rows = queries::route_analysis::list_stop_actuals(
+                &pool, user_id, payload.date_from, payload.date_to, payload.crew_id,
+            )
+            .await?;
+            let mut analysis = route_analysis::analyze(&rows);
+
+            match payload.apply_correction {
+                Some(true) => {
+                    let stored = travel_correction::store(&pool, user_id, &analysis).await?;
+                    if !stored {
+                        return Ok(Err(format!(
+                            "At least {} measured legs are needed for a correction factor",
+                            route_analysis::MIN_LEGS_FOR_FACTOR
+                        )));
+                    }
+                }
+                Some(false) => travel_correction::clear(&pool, user_id).await?,
+                None => {}
+            }
+            analysis.applied_factor = queries::route_analysis::get_travel_time_factor(&pool, user_id).await?;
+            analysis.zone_factors = queries::route_analysis::list_travel_time_factors(&pool, user_id).await?;
+
+            anyhow::Ok(Ok(analysis))
+        }
+        .await;
+
+        let response = match result {
+            Ok(Ok(analysis)) => serde_json::to_vec(&SuccessResponse::new(request.id, analysis))?,
+            Ok(Err(msg)) => serde_json::to_vec(&ErrorResponse::new(request.id, "INVALID_REQUEST", msg))?,
+            Err(e) => {
+                error!("Failed to analyse routes for user {}: {}", user_id, e);
+                serde_json::to_vec(&ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string()))?
+            }
+        };
+        let _ = client.publish(reply, response.into()).await;
+    }
+
+    Ok(())
+}
+
+/// Handle route.break.suggest messages
+///
+/// Suggests restaurants and fuel stations near the leg a break falls on.
+/// Nothing is looked up for users who turned break location suggestions off.
+pub async fn handle_break_suggest(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    nominatim_url: String,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received route.break.suggest message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<BreakSuggestRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = request.payload;
+        if let Err(msg) = payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let enabled = match queries::settings::get_user_settings(&pool, user_id).await {
+            Ok(settings) => settings.is_some_and(|s| s.break_location_suggestions),
+            Err(e) => {
+                error!("Failed to load settings of user {}: {}", user_id, e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        if !enabled {
+            let response = SuccessResponse::new(request.id, BreakSuggestResponse { enabled, suggestions: Vec::new() });
+            let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            continue;
+        }
+
+        let limit = payload.limit.unwrap_or(break_location::DEFAULT_SUGGESTIONS);
+        let response = match break_location::suggest(&nominatim_url, payload.from, payload.to, limit).await {
+            Ok(suggestions) => serde_json::to_vec(&SuccessResponse::new(
+                request.id,
+                BreakSuggestResponse { enabled, suggestions },
+            ))?,
+            Err(e) => {
+                warn!("Break location lookup failed for user {}: {}", user_id, e);
+                serde_json::to_vec(&ErrorResponse::new(request.id, "GEOCODING_ERROR", e.to_string()))?
+            }
+        };
+        let _ = client.publish(reply, response.into()).await;
+    }
+
+    Ok(())
+}
+
 // ============================================================================
 // Insertion Calculation Handlers (1×K + K×1 Matrix Strategy)
 // ============================================================================
@@ -1612,90 +2321,425 @@ pub async fn handle_insertion_batch(
             }
         };
 
-        let num_stops = calc_req.route_stops.len();
-
-        // Calculate insertion for each candidate
-        for (candidate_idx, candidate) in calc_req.candidates.iter().enumerate() {
-            // If no route stops, insertion is trivially at position 0
-            if calc_req.route_stops.is_empty() {
-                results.push(BatchInsertionResult {
-                    candidate_id: candidate.id.clone(),
-                    best_delta_km: 0.0,
-                    best_delta_min: candidate.service_duration_minutes as f64,
-                    best_insert_after_index: -1,
-                    status: "ok".to_string(),
-                    is_feasible: true,
-                });
+        let num_stops = calc_req.route_stops.len();
+
+        // Calculate insertion for each candidate
+        for (candidate_idx, candidate) in calc_req.candidates.iter().enumerate() {
+            // If no route stops, insertion is trivially at position 0
+            if calc_req.route_stops.is_empty() {
+                results.push(BatchInsertionResult {
+                    candidate_id: candidate.id.clone(),
+                    best_delta_km: 0.0,
+                    best_delta_min: candidate.service_duration_minutes as f64,
+                    best_insert_after_index: -1,
+                    status: "ok".to_string(),
+                    is_feasible: true,
+                });
+                continue;
+            }
+
+            // Find best insertion position for this candidate
+            let mut best_delta_min = f64::MAX;
+            let mut best_delta_km = 0.0;
+            let mut best_insert_idx: i32 = -1;
+
+            for insert_idx in 0..=num_stops {
+                // Matrix indices for "from" and "to" nodes
+                // Route stops start at index num_candidates+1
+                let from_matrix_idx = if insert_idx == 0 { 
+                    depot_idx 
+                } else { 
+                    num_candidates + insert_idx  // route stop at insert_idx-1
+                };
+                let to_matrix_idx = if insert_idx >= num_stops { 
+                    depot_idx 
+                } else { 
+                    num_candidates + 1 + insert_idx  // route stop at insert_idx
+                };
+
+                // Current edge cost (from -> to without candidate)
+                let current_distance_m = matrices.distances[from_matrix_idx][to_matrix_idx] as f64;
+                let current_time_s = matrices.durations[from_matrix_idx][to_matrix_idx] as f64;
+
+                // New costs with candidate insertion
+                let dist_from_to_candidate = matrices.distances[from_matrix_idx][candidate_idx] as f64;
+                let dist_candidate_to_next = matrices.distances[candidate_idx][to_matrix_idx] as f64;
+                let time_from_to_candidate = matrices.durations[from_matrix_idx][candidate_idx] as f64;
+                let time_candidate_to_next = matrices.durations[candidate_idx][to_matrix_idx] as f64;
+
+                let delta_km = (dist_from_to_candidate + dist_candidate_to_next - current_distance_m) / 1000.0;
+                let delta_min = (time_from_to_candidate + time_candidate_to_next - current_time_s) / 60.0 
+                    + candidate.service_duration_minutes as f64;
+
+                if delta_min < best_delta_min {
+                    best_delta_min = delta_min;
+                    best_delta_km = delta_km;
+                    best_insert_idx = (insert_idx as i32) - 1;
+                }
+            }
+
+            // Determine status based on best delta
+            let status = if best_delta_min < 15.0 {
+                "ok"
+            } else if best_delta_min < 30.0 {
+                "tight"
+            } else {
+                "conflict"
+            };
+
+            results.push(BatchInsertionResult {
+                candidate_id: candidate.id.clone(),
+                best_delta_km,
+                best_delta_min,
+                best_insert_after_index: best_insert_idx,
+                status: status.to_string(),
+                is_feasible: status != "conflict",
+            });
+        }
+
+        let processing_time_ms = start_time.elapsed().as_millis() as u64;
+        info!("Batch insertion calculated for {} candidates in {}ms", results.len(), processing_time_ms);
+
+        let response = SuccessResponse::new(request.id, CalculateBatchInsertionResponse {
+            results,
+            processing_time_ms,
+        });
+
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Multi-day Insertion Handler
+// ============================================================================
+
+const DEFAULT_MULTI_DAY_OPTIONS: usize = 5;
+const MAX_MULTI_DAY_OPTIONS: usize = 50;
+const MAX_MULTI_DAY_RANGE_DAYS: i64 = 62;
+
+/// Request to rank insertion of one customer into all routes of a date range
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiDayInsertionRequest {
+    pub customer_id: Uuid,
+    pub date_from: chrono::NaiveDate,
+    pub date_to: chrono::NaiveDate,
+    /// Defaults to the user's default service duration
+    pub service_duration_minutes: Option<u32>,
+    /// Only routes of these crews (all crews when absent)
+    pub crew_ids: Option<Vec<Uuid>>,
+    /// Number of options returned
+    pub limit: Option<usize>,
+}
+
+impl MultiDayInsertionRequest {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.date_to < self.date_from {
+            return Err("dateTo must not be before dateFrom");
+        }
+        if (self.date_to - self.date_from).num_days() > MAX_MULTI_DAY_RANGE_DAYS {
+            return Err("Date range must be at most 62 days");
+        }
+        if self.limit.is_some_and(|limit| !(1..=MAX_MULTI_DAY_OPTIONS).contains(&limit)) {
+            return Err("limit must be between 1 and 50");
+        }
+        if self.service_duration_minutes == Some(0) {
+            return Err("serviceDurationMinutes must be positive");
+        }
+        Ok(())
+    }
+}
+
+/// Best insertion into one existing route
+#[derive(Debug, serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiDayInsertionOption {
+    pub route_id: Uuid,
+    pub date: chrono::NaiveDate,
+    pub crew_id: Option<Uuid>,
+    pub crew_name: Option<String>,
+    pub position: InsertionPosition,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiDayInsertionResponse {
+    pub customer_id: Uuid,
+    /// Cheapest feasible options first
+    pub options: Vec<MultiDayInsertionOption>,
+    pub routes_evaluated: usize,
+}
+
+/// Keep feasible options, cheapest added time first (earlier day on ties)
+fn rank_insertion_options(mut options: Vec<MultiDayInsertionOption>, limit: usize) -> Vec<MultiDayInsertionOption> {
+    options.retain(|o| o.position.status != "conflict");
+    options.sort_by(|a, b| {
+        a.position
+            .delta_min
+            .partial_cmp(&b.position.delta_min)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.date.cmp(&b.date))
+    });
+    options.truncate(limit);
+    options
+}
+
+/// Service minutes of a saved stop
+fn saved_stop_service_minutes(stop: &queries::route::RouteStopWithInfo, default_minutes: i32) -> i32 {
+    stop.override_service_duration_minutes
+        .or(stop.service_duration_minutes)
+        .or_else(|| match (stop.estimated_arrival, stop.estimated_departure) {
+            (Some(a), Some(d)) if d > a => Some((d - a).num_minutes() as i32),
+            _ => None,
+        })
+        .unwrap_or(default_minutes)
+}
+
+/// Best insertion of a candidate into the saved stops of one route
+async fn best_route_insertion(
+    routing_service: &dyn RoutingService,
+    route_id: Uuid,
+    stops: &[queries::route::RouteStopWithInfo],
+    depot: Coordinates,
+    candidate: Coordinates,
+    service_minutes: i32,
+    working_hours: (chrono::NaiveTime, chrono::NaiveTime),
+) -> Result<Option<InsertionPosition>> {
+    let mut locations: Vec<Coordinates> = vec![candidate, depot];
+    let mut stops_meta: Vec<StopMeta> = Vec::new();
+    for stop in stops.iter().filter(|s| s.stop_type != "break") {
+        let (Some(lat), Some(lng)) = (stop.customer_lat, stop.customer_lng) else {
+            continue;
+        };
+        locations.push(Coordinates { lat, lng });
+        stops_meta.push(StopMeta {
+            name: stop.customer_name.clone().unwrap_or_else(|| "common:customer".to_string()),
+            arrival_time: stop.estimated_arrival,
+            departure_time: stop.estimated_departure,
+            time_window_start: stop.scheduled_time_start,
+            time_window_end: stop.scheduled_time_end,
+            service_duration_minutes: saved_stop_service_minutes(stop, service_minutes),
+        });
+    }
+
+    let matrices = match routing_service.get_matrices(&locations).await {
+        Ok(m) => m,
+        Err(e) => {
+            warn!("Routing service failed for route {}: {}. Using estimates.", route_id, e);
+            MockRoutingService::new().get_matrices(&locations).await?
+        }
+    };
+
+    let stop_indices: Vec<usize> = (0..stops_meta.len()).map(|i| i + 2).collect();
+    let computed = calculate_insertion_positions(
+        &matrices,
+        0,
+        1,
+        &stop_indices,
+        &stops_meta,
+        service_minutes,
+        working_hours.0,
+        working_hours.1,
+    );
+    // Positions come sorted by added time; prefer the cheapest one that fits
+    let best = computed.iter().find(|p| p.status != "conflict").or(computed.first());
+    Ok(best.map(|p| InsertionPosition {
+        insert_after_index: p.insert_after_index,
+        insert_after_name: p.insert_after_name.clone(),
+        insert_before_name: p.insert_before_name.clone(),
+        delta_km: p.delta_km,
+        delta_min: p.delta_min,
+        estimated_arrival: p.estimated_arrival.format("%H:%M").to_string(),
+        estimated_departure: p.estimated_departure.format("%H:%M").to_string(),
+        status: p.status.clone(),
+        conflict_reason: p.conflict_reason.clone(),
+    }))
+}
+
+/// Handle route.insertion.multi_day messages
+/// Ranks inserting one customer into every existing route within a date range
+pub async fn handle_insertion_multi_day(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    routing_service: Arc<dyn RoutingService>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received route.insertion.multi_day message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<MultiDayInsertionRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
                 continue;
             }
+        };
 
-            // Find best insertion position for this candidate
-            let mut best_delta_min = f64::MAX;
-            let mut best_delta_km = 0.0;
-            let mut best_insert_idx: i32 = -1;
-
-            for insert_idx in 0..=num_stops {
-                // Matrix indices for "from" and "to" nodes
-                // Route stops start at index num_candidates+1
-                let from_matrix_idx = if insert_idx == 0 { 
-                    depot_idx 
-                } else { 
-                    num_candidates + insert_idx  // route stop at insert_idx-1
-                };
-                let to_matrix_idx = if insert_idx >= num_stops { 
-                    depot_idx 
-                } else { 
-                    num_candidates + 1 + insert_idx  // route stop at insert_idx
-                };
-
-                // Current edge cost (from -> to without candidate)
-                let current_distance_m = matrices.distances[from_matrix_idx][to_matrix_idx] as f64;
-                let current_time_s = matrices.durations[from_matrix_idx][to_matrix_idx] as f64;
+        let req = request.payload;
+        if let Err(e) = req.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", e);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
 
-                // New costs with candidate insertion
-                let dist_from_to_candidate = matrices.distances[from_matrix_idx][candidate_idx] as f64;
-                let dist_candidate_to_next = matrices.distances[candidate_idx][to_matrix_idx] as f64;
-                let time_from_to_candidate = matrices.durations[from_matrix_idx][candidate_idx] as f64;
-                let time_candidate_to_next = matrices.durations[candidate_idx][to_matrix_idx] as f64;
+        let customer = match queries::customer::get_customer(&pool, user_id, req.customer_id).await {
+            Ok(Some(c)) => c,
+            Ok(None) => {
+                let error = ErrorResponse::new(request.id, "NOT_FOUND", "Customer not found");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to load customer: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        let (Some(lat), Some(lng)) = (customer.lat, customer.lng) else {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "Customer has no coordinates");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        };
+        let candidate = Coordinates { lat, lng };
 
-                let delta_km = (dist_from_to_candidate + dist_candidate_to_next - current_distance_m) / 1000.0;
-                let delta_min = (time_from_to_candidate + time_candidate_to_next - current_time_s) / 60.0 
-                    + candidate.service_duration_minutes as f64;
+        let loaded = async {
+            let settings = queries::settings::get_user_settings(&pool, user_id).await?;
+            let routes = queries::route::list_routes(&pool, user_id, req.date_from, req.date_to, None, None).await?;
+            let crews = queries::crew::list_crews(&pool, user_id, false).await?;
+            anyhow::Ok((settings, routes, crews))
+        }
+        .await;
+        let (settings, routes, crews) = match loaded {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Failed to load routes for multi-day insertion: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
 
-                if delta_min < best_delta_min {
-                    best_delta_min = delta_min;
-                    best_delta_km = delta_km;
-                    best_insert_idx = (insert_idx as i32) - 1;
+        let service_minutes = req
+            .service_duration_minutes
+            .map(|m| m as i32)
+            .or(settings.as_ref().map(|s| s.default_service_duration_minutes))
+            .unwrap_or(DEFAULT_SERVICE_DURATION_MINUTES as i32);
+        let fallback_depot = Coordinates {
+            lat: settings.as_ref().and_then(|s| s.lat).unwrap_or(50.0755),
+            lng: settings.as_ref().and_then(|s| s.lng).unwrap_or(14.4378),
+        };
+        let default_depot_id = settings.as_ref().and_then(|s| s.default_depot_id);
+        let crews: HashMap<Uuid, crate::types::Crew> = crews.into_iter().map(|c| (c.id, c)).collect();
+
+        let mut depots: HashMap<Uuid, Option<Coordinates>> = HashMap::new();
+        let mut options: Vec<MultiDayInsertionOption> = Vec::new();
+        let mut routes_evaluated = 0;
+        for route in &routes {
+            // Finished days cannot take another visit
+            if route.status == "completed" {
+                continue;
+            }
+            if let Some(ids) = &req.crew_ids {
+                if !route.crew_id.is_some_and(|id| ids.contains(&id)) {
+                    continue;
+                }
+            }
+            let crew = route.crew_id.and_then(|id| crews.get(&id));
+
+            let mut depot = None;
+            for depot_id in [route.depot_id, crew.and_then(|c| c.home_depot_id), default_depot_id].into_iter().flatten() {
+                depot = match depots.get(&depot_id) {
+                    Some(found) => *found,
+                    None => {
+                        let found = queries::settings::get_depot(&pool, depot_id, user_id)
+                            .await
+                            .ok()
+                            .flatten()
+                            .map(|d| Coordinates { lat: d.lat, lng: d.lng });
+                        depots.insert(depot_id, found);
+                        found
+                    }
+                };
+                if depot.is_some() {
+                    break;
                 }
             }
 
-            // Determine status based on best delta
-            let status = if best_delta_min < 15.0 {
-                "ok"
-            } else if best_delta_min < 30.0 {
-                "tight"
-            } else {
-                "conflict"
+            let working_hours = crew
+                .map(|c| (c.working_hours_start, c.working_hours_end))
+                .unwrap_or_else(|| (default_work_start(), default_work_end()));
+            let stops = match queries::route::get_route_stops_with_info(&pool, route.id).await {
+                Ok(stops) => stops,
+                Err(e) => {
+                    warn!("Skipping route {} in multi-day insertion: {}", route.id, e);
+                    continue;
+                }
             };
-
-            results.push(BatchInsertionResult {
-                candidate_id: candidate.id.clone(),
-                best_delta_km,
-                best_delta_min,
-                best_insert_after_index: best_insert_idx,
-                status: status.to_string(),
-                is_feasible: status != "conflict",
-            });
+            routes_evaluated += 1;
+            // Already visited that day
+            if stops.iter().any(|s| s.customer_id == Some(req.customer_id)) {
+                continue;
+            }
+            match best_route_insertion(
+                routing_service.as_ref(),
+                route.id,
+                &stops,
+                depot.unwrap_or(fallback_depot),
+                candidate,
+                service_minutes,
+                working_hours,
+            )
+            .await
+            {
+                Ok(Some(position)) => {
+                    options.push(MultiDayInsertionOption {
+                        route_id: route.id,
+                        date: route.date,
+                        crew_id: route.crew_id,
+                        crew_name: route.crew_name.clone(),
+                        position,
+                    });
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Skipping route {} in multi-day insertion: {}", route.id, e),
+            }
         }
 
-        let processing_time_ms = start_time.elapsed().as_millis() as u64;
-        info!("Batch insertion calculated for {} candidates in {}ms", results.len(), processing_time_ms);
-
-        let response = SuccessResponse::new(request.id, CalculateBatchInsertionResponse {
-            results,
-            processing_time_ms,
+        let options = rank_insertion_options(options, req.limit.unwrap_or(DEFAULT_MULTI_DAY_OPTIONS));
+        info!(
+            "Multi-day insertion: {} options from {} routes for customer {}",
+            options.len(),
+            routes_evaluated,
+            req.customer_id
+        );
+        let response = SuccessResponse::new(request.id, MultiDayInsertionResponse {
+            customer_id: req.customer_id,
+            options,
+            routes_evaluated,
         });
-
         let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
     }
 
@@ -1749,6 +2793,24 @@ pub struct RecalcStopInput {
     pub customer_name: Option<String>,
 }
 
+/// Matrix legs of a computed schedule with the time each one departs,
+/// including the return to the depot
+fn schedule_legs(input: &ScheduleInput, result: &sequential_schedule::ScheduleResult) -> Vec<(usize, usize, chrono::NaiveTime)> {
+    let mut legs = Vec::with_capacity(input.stops.len() + 1);
+    let mut from = input.depot_matrix_idx;
+    let mut departure = result.depot_departure;
+    for (i, computed) in result.stops.iter().enumerate() {
+        if input.stops[i].stop_type == SeqStopType::Customer {
+            let to = input.stop_matrix_indices[i];
+            legs.push((from, to, departure));
+            from = to;
+        }
+        departure = computed.estimated_departure;
+    }
+    legs.push((from, input.depot_matrix_idx, departure));
+    legs
+}
+
 /// Request payload for route.recalculate
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -1818,8 +2880,8 @@ fn parse_time_flexible(s: &str) -> Option<chrono::NaiveTime> {
 pub async fn handle_recalculate(
     client: Client,
     mut subscriber: Subscriber,
-    _pool: PgPool,
-    _jwt_secret: Arc<String>,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
     routing_service: Arc<dyn RoutingService>,
 ) -> Result<()> {
     while let Some(msg) = subscriber.next().await {
@@ -1893,13 +2955,22 @@ pub async fn handle_recalculate(
             }
         }
 
+        // Units of one building share a matrix entry, so the legs between
+        // them are zero
+        let places = LocationGroups::new(&locations, COLOCATION_RADIUS_M);
+        let locations = places.anchors;
+        let stop_matrix_indices: Vec<usize> = stop_matrix_indices
+            .into_iter()
+            .map(|idx| places.group_of[idx])
+            .collect();
+
         // Fetch routing matrix
-        let matrices = match routing_service.get_matrices(&locations).await {
-            Ok(m) => m,
+        let (mut matrices, routing_fallback_used) = match routing_service.get_matrices(&locations).await {
+            Ok(m) => (m, false),
             Err(e) => {
                 warn!("route.recalculate: routing failed: {}. Using mock.", e);
                 match MockRoutingService::new().get_matrices(&locations).await {
-                    Ok(m) => m,
+                    Ok(m) => (m, true),
                     Err(e2) => {
                         error!("route.recalculate: mock also failed: {}", e2);
                         let err = ErrorResponse::new(request.id, "ROUTING_ERROR", e.to_string());
@@ -1910,6 +2981,20 @@ pub async fn handle_recalculate(
             }
         };
 
+        // Travel time correction of the caller's account; anonymous
+        // recalculations and mock estimates stay uncorrected
+        let correction = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) if !routing_fallback_used => travel_correction::load_model(&pool, info.data_user_id())
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("route.recalculate: failed to load travel time correction: {}", e);
+                    TravelTimeModel::default()
+                }),
+            _ => TravelTimeModel::default(),
+        };
+        let raw_matrices = matrices.clone();
+        correction.apply_to_matrix(&mut matrices, &locations);
+
         // Build ScheduleInput
         let schedule_stops: Vec<SeqScheduleStop> = payload
             .stops
@@ -1952,12 +3037,22 @@ pub async fn handle_recalculate(
             arrival_buffer_fixed_minutes: payload.arrival_buffer_fixed_minutes,
         };
 
-        let result = sequential_schedule::compute_sequential_schedule(
+        let mut result = sequential_schedule::compute_sequential_schedule(
             &input,
             &matrices.distances,
             &matrices.durations,
         );
 
+        // Second pass: every leg with the factor of the time band it departs in
+        if correction.zone_factor_count() > 0 {
+            correction.apply_to_legs(&mut matrices, &raw_matrices, &locations, &schedule_legs(&input, &result));
+            result = sequential_schedule::compute_sequential_schedule(
+                &input,
+                &matrices.distances,
+                &matrices.durations,
+            );
+        }
+
         // Build response
         let stops: Vec<RecalcStopResult> = result
             .stops
@@ -1996,6 +3091,180 @@ pub async fn handle_recalculate(
     Ok(())
 }
 
+/// Handle route.reoptimize.live: re-plan the stops of a saved route that
+/// are still ahead, from where the technician is now
+pub async fn handle_reoptimize_live(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    routing_service: Arc<dyn RoutingService>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received route.reoptimize.live message");
+
+        let reply = match msg.reply {
+            Some(ref r) => r.clone(),
+            None => {
+                warn!("route.reoptimize.live: message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<RouteReoptimizeLiveRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("route.reoptimize.live: failed to parse: {}", e);
+                let err = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&err)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match reoptimize_live(&pool, &routing_service, user_id, &request.payload).await {
+            Ok(response) => {
+                let response = SuccessResponse::new(request.id, response);
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err((code, message)) => {
+                let error = ErrorResponse::new(request.id, code, message);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Stops of a route split for a live re-plan
+#[derive(Debug, Default)]
+struct LiveStops {
+    /// Completed and skipped route stops in route order
+    locked_stop_ids: Vec<Uuid>,
+    /// Customers still to visit
+    remaining_customer_ids: Vec<Uuid>,
+    /// Location of the last locked customer stop
+    last_position: Option<Coordinates>,
+    /// A break stop is among the locked ones
+    break_taken: bool,
+}
+
+/// Lock the `finished` route stops and collect the customers of the rest.
+/// Break stops still ahead are dropped, the solver places the break again.
+fn split_live_stops(stops: &[queries::route::RouteStopWithInfo], finished: &[Uuid]) -> LiveStops {
+    let mut ordered: Vec<&queries::route::RouteStopWithInfo> = stops.iter().collect();
+    ordered.sort_by_key(|s| s.stop_order);
+
+    let mut live = LiveStops::default();
+    for stop in ordered {
+        let is_break = stop.stop_type == "break";
+        if finished.contains(&stop.id) {
+            live.locked_stop_ids.push(stop.id);
+            if is_break {
+                live.break_taken = true;
+            } else if let (Some(lat), Some(lng)) = (stop.customer_lat, stop.customer_lng) {
+                live.last_position = Some(Coordinates { lat, lng });
+            }
+        } else if let (false, Some(customer_id)) = (is_break, stop.customer_id) {
+            if !live.remaining_customer_ids.contains(&customer_id) {
+                live.remaining_customer_ids.push(customer_id);
+            }
+        }
+    }
+    live
+}
+
+/// Re-plan the rest of a route day. Errors carry the reply error code and
+/// message.
+async fn reoptimize_live(
+    pool: &PgPool,
+    routing_service: &Arc<dyn RoutingService>,
+    user_id: Uuid,
+    live: &RouteReoptimizeLiveRequest,
+) -> std::result::Result<RouteReoptimizeLiveResponse, (&'static str, String)> {
+    let db_error = |e: anyhow::Error| {
+        error!("route.reoptimize.live: database error: {}", e);
+        ("DATABASE_ERROR", e.to_string())
+    };
+
+    let route = queries::route::get_route_by_id(pool, user_id, live.route_id)
+        .await
+        .map_err(db_error)?
+        .ok_or(("NOT_FOUND", "Route not found".to_string()))?;
+    let stops = queries::route::get_route_stops_with_info(pool, route.id).await.map_err(db_error)?;
+    let depot = super::route_history::route_start(pool, user_id, route.depot_id, route.crew_id)
+        .await
+        .map_err(db_error)?;
+
+    let finished: Vec<Uuid> = live.completed_stop_ids.iter().chain(&live.skipped_stop_ids).copied().collect();
+    let split = split_live_stops(&stops, &finished);
+    let start_location = live
+        .current_position
+        .or(split.last_position)
+        .or(depot)
+        .ok_or(("INVALID_REQUEST", "No current position, finished stop or depot to start from".to_string()))?;
+
+    info!(
+        "route.reoptimize.live: route {} at {}, {} locked, {} remaining",
+        route.id, live.current_time, split.locked_stop_ids.len(), split.remaining_customer_ids.len()
+    );
+
+    let plan = if split.remaining_customer_ids.is_empty() {
+        RoutePlanResponse {
+            stops: vec![],
+            total_distance_km: 0.0,
+            total_duration_minutes: 0,
+            algorithm: "none".to_string(),
+            solve_time_ms: 0,
+            solver_log: vec![],
+            optimization_score: 100,
+            warnings: vec![],
+            unassigned: vec![],
+            geometry: vec![],
+            return_to_depot_distance_km: None,
+            return_to_depot_duration_minutes: None,
+        }
+    } else {
+        // A re-plan counts towards the monthly route plan quota
+        match quota::consume(pool, user_id, QuotaMetric::RoutePlans, 1).await {
+            Ok(Ok(())) => {}
+            Ok(Err(exceeded)) => return Err(("QUOTA_EXCEEDED", exceeded.to_string())),
+            Err(e) => return Err(db_error(e)),
+        }
+
+        let plan_request = RoutePlanRequest {
+            start_location,
+            customer_ids: split.remaining_customer_ids.clone(),
+            date: route.date,
+            working_hours: None,
+            crew_id: route.crew_id,
+            arrival_buffer_percent: route.arrival_buffer_percent,
+            arrival_buffer_fixed_minutes: route.arrival_buffer_fixed_minutes,
+            fixed_stops: vec![],
+            not_before: Some(live.current_time),
+            end_location: depot,
+            without_break: split.break_taken,
+        };
+        optimize_route(pool, routing_service, user_id, &plan_request).await?
+    };
+
+    Ok(RouteReoptimizeLiveResponse {
+        route_id: route.id,
+        locked_stop_ids: split.locked_stop_ids,
+        start_location,
+        plan,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2034,6 +3303,7 @@ mod tests {
                 lng: Some(14.5),
                 scheduled_time_start: None,
                 scheduled_time_end: None,
+                fixed: None,
             },
             CustomerForRoute {
                 id: Uuid::new_v4(),
@@ -2045,6 +3315,7 @@ mod tests {
                 lng: Some(16.6),
                 scheduled_time_start: None,
                 scheduled_time_end: None,
+                fixed: None,
             },
         ];
 
@@ -2075,6 +3346,7 @@ mod tests {
                 lng: Some(14.5),
                 scheduled_time_start: None,
                 scheduled_time_end: None,
+                fixed: None,
             },
         ];
 
@@ -2104,6 +3376,7 @@ mod tests {
             lng: Some(14.5),
             scheduled_time_start: Some(chrono::NaiveTime::from_hms_opt(8, 0, 0).unwrap()),
             scheduled_time_end: Some(chrono::NaiveTime::from_hms_opt(12, 0, 0).unwrap()),
+            fixed: None,
         }];
 
         // Service 60 min is shorter than 4h window => flexible.
@@ -2135,6 +3408,7 @@ mod tests {
             lng: Some(14.5),
             scheduled_time_start: Some(chrono::NaiveTime::from_hms_opt(8, 0, 0).unwrap()),
             scheduled_time_end: Some(chrono::NaiveTime::from_hms_opt(9, 0, 0).unwrap()),
+            fixed: None,
         }];
 
         // Service 90 min is >= window length 60 => pinned behavior.
@@ -2154,6 +3428,45 @@ mod tests {
         assert_eq!(tw.end, chrono::NaiveTime::from_hms_opt(8, 0, 0).unwrap());
     }
 
+    #[test]
+    fn test_build_vrp_problem_pins_fixed_stop() {
+        let id = Uuid::new_v4();
+        let arrival = chrono::NaiveTime::from_hms_opt(10, 30, 0).unwrap();
+        let customers = vec![CustomerForRoute {
+            id,
+            name: Some("Customer A".to_string()),
+            street: Some("Street 1".to_string()),
+            city: Some("Prague".to_string()),
+            postal_code: Some("11000".to_string()),
+            lat: Some(50.1),
+            lng: Some(14.5),
+            // The fixed appointment wins over the revision's window
+            scheduled_time_start: Some(chrono::NaiveTime::from_hms_opt(8, 0, 0).unwrap()),
+            scheduled_time_end: Some(chrono::NaiveTime::from_hms_opt(12, 0, 0).unwrap()),
+            fixed: Some(FixedRouteStop {
+                customer_id: id,
+                arrival_time: arrival,
+                service_duration_minutes: Some(90),
+            }),
+        }];
+
+        let problem = build_vrp_problem(
+            &prague(),
+            &customers,
+            default_work_start(),
+            default_work_end(),
+            30,
+            None,
+        );
+
+        let stop = &problem.stops[0];
+        let tw = stop.time_window.as_ref().expect("fixed window expected");
+        assert_eq!(stop.priority, FIXED_STOP_PRIORITY);
+        assert_eq!(stop.service_duration_minutes, 90);
+        assert_eq!(tw.start, arrival);
+        assert_eq!(tw.end, arrival);
+    }
+
     #[test]
     fn test_build_vrp_problem_uses_working_hours() {
         let customers = vec![
@@ -2167,6 +3480,7 @@ mod tests {
                 lng: Some(14.5),
                 scheduled_time_start: None,
                 scheduled_time_end: None,
+                fixed: None,
             },
         ];
 
@@ -2184,6 +3498,57 @@ mod tests {
         assert_eq!(problem.shift_end.hour(), 16);
     }
 
+    fn multi_day_option(day: u32, delta_min: f64, status: &str) -> MultiDayInsertionOption {
+        MultiDayInsertionOption {
+            route_id: Uuid::new_v4(),
+            date: chrono::NaiveDate::from_ymd_opt(2026, 3, day).unwrap(),
+            crew_id: None,
+            crew_name: None,
+            position: InsertionPosition {
+                insert_after_index: 0,
+                insert_after_name: "A".to_string(),
+                insert_before_name: "B".to_string(),
+                delta_km: 1.0,
+                delta_min,
+                estimated_arrival: "09:00".to_string(),
+                estimated_departure: "10:00".to_string(),
+                status: status.to_string(),
+                conflict_reason: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_rank_insertion_options_drops_conflicts_and_orders_by_cost() {
+        let options = vec![
+            multi_day_option(5, 20.0, "ok"),
+            multi_day_option(2, 5.0, "conflict"),
+            multi_day_option(4, 10.0, "tight"),
+            multi_day_option(3, 20.0, "ok"),
+        ];
+        let ranked = rank_insertion_options(options, 2);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].date, chrono::NaiveDate::from_ymd_opt(2026, 3, 4).unwrap());
+        // Equal cost: earlier day first
+        assert_eq!(ranked[1].date, chrono::NaiveDate::from_ymd_opt(2026, 3, 3).unwrap());
+    }
+
+    #[test]
+    fn test_multi_day_insertion_request_validation() {
+        let request = |from: u32, to: u32, limit: Option<usize>| MultiDayInsertionRequest {
+            customer_id: Uuid::new_v4(),
+            date_from: chrono::NaiveDate::from_ymd_opt(2026, 3, from).unwrap(),
+            date_to: chrono::NaiveDate::from_ymd_opt(2026, 3, to).unwrap(),
+            service_duration_minutes: None,
+            crew_ids: None,
+            limit,
+        };
+        assert!(request(1, 31, None).validate().is_ok());
+        assert!(request(10, 9, None).validate().is_err());
+        assert!(request(1, 2, Some(0)).validate().is_err());
+        assert!(request(1, 2, Some(51)).validate().is_err());
+    }
+
     #[test]
     fn test_working_hours_default() {
         // Default WorkingHours uses full day (0:00-23:59) to not constrain planning
@@ -2222,4 +3587,84 @@ mod tests {
         assert!((req.arrival_buffer_percent - 10.0).abs() < f64::EPSILON);
         assert!(req.arrival_buffer_fixed_minutes.abs() < f64::EPSILON);
     }
+
+    fn route_stop(stop_type: &str, order: i32, customer_id: Option<Uuid>, lat: f64) -> queries::route::RouteStopWithInfo {
+        queries::route::RouteStopWithInfo {
+            id: Uuid::new_v4(),
+            route_id: Uuid::nil(),
+            customer_id,
+            visit_id: None,
+            revision_id: None,
+            stop_order: order,
+            estimated_arrival: None,
+            estimated_departure: None,
+            distance_from_previous_km: None,
+            duration_from_previous_minutes: None,
+            status: "pending".to_string(),
+            stop_type: stop_type.to_string(),
+            customer_name: None,
+            address: None,
+            customer_lat: customer_id.map(|_| lat),
+            customer_lng: customer_id.map(|_| 14.4),
+            customer_phone: None,
+            customer_email: None,
+            scheduled_date: None,
+            scheduled_time_start: None,
+            scheduled_time_end: None,
+            revision_status: None,
+            break_duration_minutes: None,
+            break_time_start: None,
+            service_duration_minutes: None,
+            override_service_duration_minutes: None,
+            override_travel_duration_minutes: None,
+            notes: None,
+            tasks: sqlx::types::Json(vec![]),
+            break_location: None,
+            customer_risk_flag: false,
+        }
+    }
+
+    #[test]
+    fn test_split_live_stops_locks_finished_in_route_order() {
+        let (a, b, c, d) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let stops = vec![
+            route_stop("customer", 3, Some(c), 50.3),
+            route_stop("customer", 1, Some(a), 50.1),
+            route_stop("break", 4, None, 0.0),
+            route_stop("customer", 2, Some(b), 50.2),
+            route_stop("customer", 5, Some(d), 50.5),
+        ];
+        // a completed, c skipped, b and d still ahead
+        let finished = vec![stops[0].id, stops[1].id];
+
+        let live = split_live_stops(&stops, &finished);
+
+        assert_eq!(live.locked_stop_ids, vec![stops[1].id, stops[0].id]);
+        assert_eq!(live.remaining_customer_ids, vec![b, d]);
+        assert!((live.last_position.unwrap().lat - 50.3).abs() < 1e-9);
+        assert!(!live.break_taken);
+    }
+
+    #[test]
+    fn test_split_live_stops_taken_break() {
+        let a = Uuid::new_v4();
+        let stops = vec![route_stop("break", 1, None, 0.0), route_stop("customer", 2, Some(a), 50.1)];
+
+        let live = split_live_stops(&stops, &[stops[0].id]);
+
+        assert!(live.break_taken);
+        assert!(live.last_position.is_none());
+        assert_eq!(live.remaining_customer_ids, vec![a]);
+    }
+
+    #[test]
+    fn test_split_live_stops_nothing_finished() {
+        let stops = vec![route_stop("customer", 1, Some(Uuid::new_v4()), 50.1), route_stop("break", 2, None, 0.0)];
+
+        let live = split_live_stops(&stops, &[]);
+
+        assert!(live.locked_stop_ids.is_empty());
+        assert_eq!(live.remaining_customer_ids.len(), 1);
+        assert!(live.last_position.is_none());
+    }
 }
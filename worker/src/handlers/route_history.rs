@@ -0,0 +1,238 @@
+//! "Plan like last year" handler for NATS messages
+//!
+//! `sazinka.route.plan.from_history` proposes the routes of a week from the
+//! routes driven in the same week last year, see services::route_template.
+//! Proposals are not saved; the dispatcher saves the ones they accept
+//! through route.save.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use chrono::Duration;
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::quota;
+use crate::services::route_template::{mirrored_date, source_week, split_due, DUE_HORIZON_DAYS};
+use crate::services::routing::RoutingService;
+use crate::subjects;
+use crate::types::route::{HistoryRouteProposal, RoutePlanFromHistoryRequest, RoutePlanFromHistoryResponse};
+use crate::types::{Coordinates, ErrorResponse, QuotaMetric, Request, RoutePlanRequest, SuccessResponse};
+
+use super::route::optimize_route;
+
+/// Shared by the route history handlers
+#[derive(Clone)]
+pub struct RouteHistoryContext {
+    pub pool: PgPool,
+    pub jwt_secret: Arc<String>,
+    pub routing_service: Arc<dyn RoutingService>,
+}
+
+/// Start the route history NATS handler
+pub async fn start_handlers(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    routing_service: Arc<dyn RoutingService>,
+) -> Result<()> {
+    info!("Starting route history handlers...");
+    let ctx = RouteHistoryContext { pool, jwt_secret, routing_service };
+
+    let [from_history_sub] = subjects::subscribe_all(&client, [subjects::route::PLAN_FROM_HISTORY]).await?;
+    tokio::spawn(handle_plan_from_history(client, from_history_sub, ctx));
+
+    info!("Route history handlers started");
+    Ok(())
+}
+
+/// Start of a route: its depot, the crew's home depot, else the primary depot
+pub(crate) async fn route_start(
+    pool: &PgPool,
+    user_id: Uuid,
+    depot_id: Option<Uuid>,
+    crew_id: Option<Uuid>,
+) -> Result<Option<Coordinates>> {
+    let crew_depot_id = match crew_id {
+        Some(crew_id) => queries::crew::get_crew(pool, crew_id, user_id).await?.and_then(|c| c.home_depot_id),
+        None => None,
+    };
+    for id in [depot_id, crew_depot_id].into_iter().flatten() {
+        if let Some(depot) = queries::settings::get_depot(pool, id, user_id).await? {
+            return Ok(Some(Coordinates { lat: depot.lat, lng: depot.lng }));
+        }
+    }
+    let primary = queries::settings::get_primary_depot(pool, user_id).await?;
+    Ok(primary.map(|depot| Coordinates { lat: depot.lat, lng: depot.lng }))
+}
+
+/// Settings of last year's route carried over to its proposal
+struct SourceRoute {
+    depot_id: Option<Uuid>,
+    crew_id: Option<Uuid>,
+    arrival_buffer_percent: f64,
+    arrival_buffer_fixed_minutes: f64,
+}
+
+/// Last year's routes with their customers split into due again and not
+async fn load_proposals(
+    pool: &PgPool,
+    user_id: Uuid,
+    request: &RoutePlanFromHistoryRequest,
+) -> Result<(RoutePlanFromHistoryResponse, Vec<SourceRoute>)> {
+    let (week_start, week_end) = source_week(request.date);
+    let routes = queries::route::list_routes(pool, user_id, week_start, week_end, request.crew_id, None).await?;
+
+    let mut proposals = Vec::new();
+    let mut route_settings = Vec::new();
+    for route in routes {
+        let stops = queries::route::get_route_stops_with_info(pool, route.id).await?;
+        let customers: Vec<Uuid> = stops
+            .iter()
+            .filter(|s| s.stop_type == "customer")
+            .filter_map(|s| s.customer_id)
+            .collect();
+        let date = mirrored_date(route.date);
+        let due: HashSet<Uuid> =
+            queries::revision::list_customers_due_by(pool, user_id, &customers, date + Duration::days(DUE_HORIZON_DAYS))
+                .await?
+                .into_iter()
+                .collect();
+        let (customer_ids, not_due_customer_ids) = split_due(&customers, &due);
+
+        proposals.push(HistoryRouteProposal {
+            source_route_id: route.id,
+            source_date: route.date,
+            date,
+            crew_id: route.crew_id,
+            crew_name: route.crew_name,
+            customer_ids,
+            not_due_customer_ids,
+            plan: None,
+            skipped_reason: None,
+        });
+        route_settings.push(SourceRoute {
+            depot_id: route.depot_id,
+            crew_id: route.crew_id,
+            arrival_buffer_percent: route.arrival_buffer_percent,
+            arrival_buffer_fixed_minutes: route.arrival_buffer_fixed_minutes,
+        });
+    }
+
+    let response = RoutePlanFromHistoryResponse { source_week_start: week_start, source_week_end: week_end, proposals };
+    Ok((response, route_settings))
+}
+
+/// Handle route.plan.from_history messages
+pub async fn handle_plan_from_history(
+    client: Client,
+    mut subscriber: Subscriber,
+    ctx: RouteHistoryContext,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received route.plan.from_history message");
+        let Some(reply) = msg.reply.clone() else { continue };
+
+        let request: Request<RoutePlanFromHistoryRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        let user_id = match auth::extract_auth(&request, &ctx.jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let (mut response, route_settings) = match load_proposals(&ctx.pool, user_id, &request.payload).await {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                error!("Failed to load last year's routes: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        // Every re-optimized route counts towards the route plan quota
+        let to_plan = response.proposals.iter().filter(|p| !p.customer_ids.is_empty()).count() as i64;
+        if to_plan > 0 {
+            match quota::consume(&ctx.pool, user_id, QuotaMetric::RoutePlans, to_plan).await {
+                Ok(Ok(())) => {}
+                Ok(Err(exceeded)) => {
+                    let error = ErrorResponse::new(request.id, "QUOTA_EXCEEDED", exceeded.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to check route plan quota: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            }
+        }
+
+        for (proposal, source) in response.proposals.iter_mut().zip(route_settings) {
+            if proposal.customer_ids.is_empty() {
+                proposal.skipped_reason = Some("NO_DUE_CUSTOMERS".to_string());
+                continue;
+            }
+            let start_location = match route_start(&ctx.pool, user_id, source.depot_id, source.crew_id).await {
+                Ok(Some(start)) => start,
+                Ok(None) => {
+                    proposal.skipped_reason = Some("NO_DEPOT".to_string());
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Failed to load depot of route {}: {}", proposal.source_route_id, e);
+                    proposal.skipped_reason = Some("DATABASE_ERROR".to_string());
+                    continue;
+                }
+            };
+
+            let plan_request = RoutePlanRequest {
+                start_location,
+                customer_ids: proposal.customer_ids.clone(),
+                date: proposal.date,
+                working_hours: None,
+                crew_id: source.crew_id,
+                arrival_buffer_percent: source.arrival_buffer_percent,
+                arrival_buffer_fixed_minutes: source.arrival_buffer_fixed_minutes,
+                fixed_stops: vec![],
+                not_before: None,
+                end_location: None,
+                without_break: false,
+            };
+            match optimize_route(&ctx.pool, &ctx.routing_service, user_id, &plan_request).await {
+                Ok(plan) => proposal.plan = Some(plan),
+                Err((code, message)) => {
+                    warn!("Failed to re-plan route {}: {}", proposal.source_route_id, message);
+                    proposal.skipped_reason = Some(code.to_string());
+                }
+            }
+        }
+
+        info!(
+            "Proposed {} routes for the week of {} from last year",
+            response.proposals.len(),
+            request.payload.date
+        );
+        let response = SuccessResponse::new(request.id, response);
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,296 @@
+//! Multi-day route planning handler for NATS messages
+//!
+//! `sazinka.route.plan.multiday` spreads a pool of due revisions over the
+//! working days of a range (services::vrp::assign_days) and optimizes every
+//! day with the regular solver. Stops a day cannot fit move to the next
+//! day. Proposals are not saved; the dispatcher saves the days they accept
+//! through route.save.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use chrono::NaiveDate;
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::quota;
+use crate::services::routing::RoutingService;
+use crate::services::vrp::{assign_days, planning_days, shift_capacity_minutes, DayStop, MAX_MULTIDAY_DAYS};
+use crate::subjects;
+use crate::types::route::{MultidayRouteDay, RoutePlanMultidayRequest, RoutePlanMultidayResponse};
+use crate::types::{Coordinates, ErrorResponse, QuotaMetric, Request, RoutePlanRequest, SuccessResponse};
+
+use super::route::optimize_route;
+use super::route_history::route_start;
+
+/// Service duration when the account has no settings
+const FALLBACK_SERVICE_MINUTES: u32 = 30;
+
+/// Shared by the multi-day planning handlers
+#[derive(Clone)]
+pub struct RouteMultidayContext {
+    pub pool: PgPool,
+    pub jwt_secret: Arc<String>,
+    pub routing_service: Arc<dyn RoutingService>,
+}
+
+/// Start the multi-day planning NATS handler
+pub async fn start_handlers(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    routing_service: Arc<dyn RoutingService>,
+) -> Result<()> {
+    info!("Starting multi-day route planning handlers...");
+    let ctx = RouteMultidayContext { pool, jwt_secret, routing_service };
+
+    let [multiday_sub] = subjects::subscribe_all(&client, [subjects::route::PLAN_MULTIDAY]).await?;
+    tokio::spawn(handle_plan_multiday(client, multiday_sub, ctx));
+
+    info!("Multi-day route planning handlers started");
+    Ok(())
+}
+
+/// Customers to plan: earliest due date and revisions per customer
+struct Pool {
+    stops: Vec<DayStop>,
+    revisions: HashMap<Uuid, Vec<Uuid>>,
+    missing_coordinates: Vec<Uuid>,
+}
+
+async fn load_pool(
+    pool: &PgPool,
+    user_id: Uuid,
+    request: &RoutePlanMultidayRequest,
+    service_duration_minutes: u32,
+) -> Result<Pool> {
+    let candidates =
+        queries::revision::list_multiday_candidates(pool, user_id, &request.revision_ids, request.end_date).await?;
+
+    let mut by_customer: BTreeMap<Uuid, DayStop> = BTreeMap::new();
+    let mut revisions: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    let mut missing_coordinates = Vec::new();
+    for candidate in candidates {
+        let (Some(lat), Some(lng)) = (candidate.lat, candidate.lng) else {
+            if !missing_coordinates.contains(&candidate.customer_id) {
+                missing_coordinates.push(candidate.customer_id);
+            }
+            continue;
+        };
+        revisions.entry(candidate.customer_id).or_default().push(candidate.revision_id);
+        by_customer
+            .entry(candidate.customer_id)
+            .and_modify(|stop| stop.due_date = stop.due_date.min(candidate.due_date))
+            .or_insert(DayStop {
+                customer_id: candidate.customer_id,
+                coordinates: Coordinates { lat, lng },
+                due_date: candidate.due_date,
+                service_duration_minutes,
+            });
+    }
+
+    Ok(Pool { stops: by_customer.into_values().collect(), revisions, missing_coordinates })
+}
+
+/// Shift length (minus the break) and default service duration of the
+/// crew or, without one, of the account
+async fn shift_settings(pool: &PgPool, user_id: Uuid, crew_id: Option<Uuid>) -> Result<(u32, u32)> {
+    let settings = queries::settings::get_user_settings(pool, user_id).await?;
+    let crew = match crew_id {
+        Some(crew_id) => queries::crew::get_crew(pool, crew_id, user_id).await?,
+        None => None,
+    };
+
+    let Some(settings) = settings else {
+        return Ok((8 * 60, FALLBACK_SERVICE_MINUTES));
+    };
+    let shift_start = crew.as_ref().map_or(settings.working_hours_start, |c| c.working_hours_start);
+    let shift_end = crew.as_ref().map_or(settings.working_hours_end, |c| c.working_hours_end);
+    let break_minutes = if settings.break_enabled { settings.break_duration_minutes.max(0) as u32 } else { 0 };
+    Ok((
+        shift_capacity_minutes(shift_start, shift_end, break_minutes),
+        settings.default_service_duration_minutes.max(1) as u32,
+    ))
+}
+
+/// Handle route.plan.multiday messages
+pub async fn handle_plan_multiday(
+    client: Client,
+    mut subscriber: Subscriber,
+    ctx: RouteMultidayContext,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received route.plan.multiday message");
+        let Some(reply) = msg.reply.clone() else { continue };
+
+        let request: Request<RoutePlanMultidayRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        let user_id = match auth::extract_auth(&request, &ctx.jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let payload = &request.payload;
+        let span_days = (payload.end_date - payload.start_date).num_days() + 1;
+        if !(1..=MAX_MULTIDAY_DAYS).contains(&span_days) {
+            let message = format!("The range must span 1 to {} days", MAX_MULTIDAY_DAYS);
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", message);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+        let days: Vec<NaiveDate> = planning_days(payload.start_date, payload.end_date, payload.include_weekends);
+        if days.is_empty() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", "The range has no working days");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let start_location = match payload.start_location {
+            Some(start) => Some(start),
+            None => match route_start(&ctx.pool, user_id, None, payload.crew_id).await {
+                Ok(start) => start,
+                Err(e) => {
+                    error!("Failed to load depot: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            },
+        };
+        let Some(start_location) = start_location else {
+            let error = ErrorResponse::new(request.id, "NO_DEPOT", "Set a depot or pass a start location");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        };
+
+        let loaded = match shift_settings(&ctx.pool, user_id, payload.crew_id).await {
+            Ok((capacity, service)) => load_pool(&ctx.pool, user_id, payload, service)
+                .await
+                .map(|stops| (capacity, stops)),
+            Err(e) => Err(e),
+        };
+        let (capacity, candidates) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                error!("Failed to load revisions for multi-day planning: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let assignment = assign_days(&start_location, &candidates.stops, &days, capacity);
+
+        // Every optimized day counts towards the route plan quota
+        let to_plan = assignment.days.iter().filter(|day| !day.is_empty()).count() as i64;
+        if to_plan > 0 {
+            match quota::consume(&ctx.pool, user_id, QuotaMetric::RoutePlans, to_plan).await {
+                Ok(Ok(())) => {}
+                Ok(Err(exceeded)) => {
+                    let error = ErrorResponse::new(request.id, "QUOTA_EXCEEDED", exceeded.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to check route plan quota: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            }
+        }
+
+        let due_dates: HashMap<Uuid, NaiveDate> =
+            candidates.stops.iter().map(|stop| (stop.customer_id, stop.due_date)).collect();
+        let mut carry: Vec<Uuid> = Vec::new();
+        let mut planned_days = Vec::with_capacity(days.len());
+        for (date, day) in days.iter().zip(&assignment.days) {
+            let mut customer_ids = std::mem::take(&mut carry);
+            customer_ids.extend(day.iter().map(|&idx| candidates.stops[idx].customer_id));
+
+            let mut planned = MultidayRouteDay {
+                date: *date,
+                customer_ids: vec![],
+                revision_ids: vec![],
+                late_customer_ids: vec![],
+                plan: None,
+                skipped_reason: None,
+            };
+            if customer_ids.is_empty() {
+                planned_days.push(planned);
+                continue;
+            }
+
+            let plan_request = RoutePlanRequest {
+                start_location,
+                customer_ids: customer_ids.clone(),
+                date: *date,
+                working_hours: None,
+                crew_id: payload.crew_id,
+                // Defaults of route.plan
+                arrival_buffer_percent: 10.0,
+                arrival_buffer_fixed_minutes: 0.0,
+                fixed_stops: vec![],
+                not_before: None,
+                end_location: None,
+                without_break: false,
+            };
+            match optimize_route(&ctx.pool, &ctx.routing_service, user_id, &plan_request).await {
+                Ok(plan) => {
+                    carry = plan.unassigned.clone();
+                    planned.customer_ids = customer_ids.into_iter().filter(|id| !carry.contains(id)).collect();
+                    planned.plan = Some(plan);
+                }
+                Err((code, message)) => {
+                    warn!("Failed to plan {} of a multi-day plan: {}", date, message);
+                    planned.skipped_reason = Some(code.to_string());
+                    carry = customer_ids;
+                }
+            }
+            for customer_id in &planned.customer_ids {
+                if due_dates.get(customer_id).is_some_and(|due| due < date) {
+                    planned.late_customer_ids.push(*customer_id);
+                }
+                if let Some(revisions) = candidates.revisions.get(customer_id) {
+                    planned.revision_ids.extend(revisions);
+                }
+            }
+            planned_days.push(planned);
+        }
+
+        let mut unassigned_customer_ids = carry;
+        unassigned_customer_ids.extend(assignment.unassigned.iter().map(|&idx| candidates.stops[idx].customer_id));
+
+        info!(
+            "Spread {} customers over {} days ({} unassigned)",
+            candidates.stops.len(),
+            days.len(),
+            unassigned_customer_ids.len()
+        );
+        let response = RoutePlanMultidayResponse {
+            days: planned_days,
+            unassigned_customer_ids,
+            missing_coordinates_customer_ids: candidates.missing_coordinates,
+        };
+        let response = SuccessResponse::new(request.id, response);
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+
+    Ok(())
+}
@@ -10,13 +10,16 @@ use uuid::Uuid;
 
 use crate::auth;
 use crate::db::queries;
+use crate::services::geocode_priority::GEOCODE_PRIORITY;
 use crate::services::geocoding::Geocoder;
+use crate::types::currency::{normalize_currency, SUPPORTED_CURRENCIES};
 use crate::types::{
     EmptyPayload, ErrorResponse, Request, SuccessResponse,
     CreateDepotRequest, UpdateDepotRequest, DeleteDepotRequest,
     ListDepotsResponse, UserSettings,
     UpdateWorkConstraintsRequest, UpdateBusinessInfoRequest, UpdateEmailTemplatesRequest,
-    UpdatePreferencesRequest, UpdateBreakSettingsRequest,
+    UpdatePreferencesRequest, UpdateBreakSettingsRequest, UpdateRevisionNumberingRequest,
+    UpdateCustomerCodeRequest,
     DeleteAccountRequest, DeleteAccountResponse,
 };
 
@@ -82,6 +85,8 @@ pub async fn handle_get_settings(
                     email_templates: user.to_email_templates(),
                     preferences: user.to_preferences(),
                     break_settings: user.to_break_settings(),
+                    revision_numbering: user.to_revision_numbering(),
+                    customer_codes: user.to_customer_codes(),
                     depots,
                 };
 
@@ -196,7 +201,7 @@ pub async fn handle_update_business_info(
             }
         };
 
-        let request: Request<UpdateBusinessInfoRequest> = match serde_json::from_slice(&msg.payload) {
+        let mut request: Request<UpdateBusinessInfoRequest> = match serde_json::from_slice(&msg.payload) {
             Ok(req) => req,
             Err(e) => {
                 error!("Failed to parse request: {}", e);
@@ -222,6 +227,21 @@ pub async fn handle_update_business_info(
         }
         let user_id = auth_info.data_user_id();
 
+        if let Some(ref code) = request.payload.currency {
+            match normalize_currency(code) {
+                Some(currency) => request.payload.currency = Some(currency),
+                None => {
+                    let error = ErrorResponse::new(
+                        request.id,
+                        "INVALID_REQUEST",
+                        format!("currency must be one of: {}", SUPPORTED_CURRENCIES.join(", ")),
+                    );
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            }
+        }
+
         // Update business info
         match queries::settings::update_business_info(&pool, user_id, &request.payload).await {
             Ok(()) => {
@@ -291,6 +311,12 @@ pub async fn handle_update_email_templates(
             let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
             continue;
         }
+        // Customer notifications go out from this address, so it must be confirmed first
+        if auth_info.requires_email_verification() {
+            let error = ErrorResponse::new(request.id, "EMAIL_NOT_VERIFIED", "Verify your email address before setting up customer emails");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
         let user_id = auth_info.data_user_id();
 
         // Update email templates
@@ -617,11 +643,13 @@ pub async fn handle_geocode_depot(
         };
 
         // Geocode the address
+        let interactive = GEOCODE_PRIORITY.interactive();
         let result = geocoder.geocode(
             &request.payload.street,
             &request.payload.city,
             &request.payload.postal_code,
         ).await;
+        drop(interactive);
 
         match result {
             Ok(Some(geo_result)) => {
@@ -786,6 +814,156 @@ pub async fn handle_update_break_settings(
     Ok(())
 }
 
+// ============================================================================
+// Update Revision Numbering Handler
+// ============================================================================
+
+/// Handle settings.numbering.update messages
+pub async fn handle_update_revision_numbering(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received settings.numbering.update message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<UpdateRevisionNumberingRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        // Settings require customer or admin role
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Settings access requires customer or admin role");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+        let user_id = auth_info.data_user_id();
+
+        if let Err(message) = request.payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", message);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::settings::update_revision_numbering(&pool, user_id, &request.payload).await {
+            Ok(_) => {
+                if let Ok(Some(user)) = queries::settings::get_user_settings(&pool, user_id).await {
+                    let response = SuccessResponse::new(request.id, user.to_revision_numbering());
+                    let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+                } else {
+                    let error = ErrorResponse::new(request.id, "USER_NOT_FOUND", "User not found");
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                }
+            }
+            Err(e) => {
+                error!("Failed to update revision numbering: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Update Customer Code Pattern Handler
+// ============================================================================
+
+/// Handle settings.customer_codes.update messages
+pub async fn handle_update_customer_codes(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received settings.customer_codes.update message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<UpdateCustomerCodeRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+        // Settings require customer or admin role
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Settings access requires customer or admin role");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+        let user_id = auth_info.data_user_id();
+
+        if let Err(message) = request.payload.validate() {
+            let error = ErrorResponse::new(request.id, "INVALID_REQUEST", message);
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        match queries::settings::update_customer_code_pattern(&pool, user_id, request.payload.pattern.trim()).await {
+            Ok(_) => {
+                if let Ok(Some(user)) = queries::settings::get_user_settings(&pool, user_id).await {
+                    let response = SuccessResponse::new(request.id, user.to_customer_codes());
+                    let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+                } else {
+                    let error = ErrorResponse::new(request.id, "USER_NOT_FOUND", "User not found");
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                }
+            }
+            Err(e) => {
+                error!("Failed to update customer code pattern: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 // ============================================================================
 // Delete Account Handler (GDPR Data Excise)
 // ============================================================================
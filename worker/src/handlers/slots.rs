@@ -15,6 +15,7 @@ use serde_json::json;
 
 use crate::auth;
 use crate::db::queries;
+use crate::db::repo::Repositories;
 use crate::defaults::DEFAULT_SERVICE_DURATION_MINUTES;
 use crate::services::insertion::{calculate_insertion_positions, time_overlap_minutes, StopMeta};
 use crate::services::routing::{MockRoutingService, RoutingService};
@@ -109,6 +110,7 @@ pub async fn handle_suggest(
             &pool,
             user_id,
             None,
+            false,
             None,
             Some("scheduled"),
             Some(req.date),
@@ -274,8 +276,70 @@ pub struct ValidateSlotResponse {
     pub slack_after_minutes: Option<i32>,
 }
 
+/// Default length of a slots.calendar time block
+pub const DEFAULT_CALENDAR_BLOCK_MINUTES: i32 = 60;
+/// Longest date range of one slots.calendar request
+pub const MAX_CALENDAR_DAYS: i64 = 62;
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotCalendarRequest {
+    pub date_from: chrono::NaiveDate,
+    pub date_to: chrono::NaiveDate,
+    /// All active crews when not given
+    pub crew_id: Option<Uuid>,
+    pub block_minutes: Option<i32>,
+}
+
+impl SlotCalendarRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.date_to < self.date_from {
+            return Err("dateTo must not be before dateFrom".to_string());
+        }
+        if (self.date_to - self.date_from).num_days() >= MAX_CALENDAR_DAYS {
+            return Err(format!("Date range is limited to {} days", MAX_CALENDAR_DAYS));
+        }
+        if let Some(minutes) = self.block_minutes {
+            if !(15..=240).contains(&minutes) {
+                return Err("blockMinutes must be between 15 and 240".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarBlock {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub used_minutes: i32,
+    pub free_minutes: i32,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrewCalendarDay {
+    pub date: chrono::NaiveDate,
+    pub crew_id: Uuid,
+    pub crew_name: String,
+    pub visit_count: i32,
+    pub capacity_minutes: i32,
+    pub used_minutes: i32,
+    pub free_minutes: i32,
+    pub load_percent: i32,
+    pub blocks: Vec<CalendarBlock>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotCalendarResponse {
+    pub days: Vec<CrewCalendarDay>,
+}
+
 #[derive(Debug, Clone)]
 struct CrewDayStop {
+    customer_id: Uuid,
     customer_name: String,
     coordinates: Coordinates,
     arrival_time: Option<NaiveTime>,
@@ -283,6 +347,8 @@ struct CrewDayStop {
     time_window_start: Option<NaiveTime>,
     time_window_end: Option<NaiveTime>,
     service_duration_minutes: i32,
+    /// Driving to the stop, when known from a planned route
+    travel_minutes: i32,
 }
 
 fn stop_start(stop: &CrewDayStop) -> Option<NaiveTime> {
@@ -343,6 +409,61 @@ fn slot_score(
     (0.40 * travel + 0.25 * fit + 0.20 * pref + 0.15 * balance).round() as i32
 }
 
+/// Times a crew is busy: each visit with the drive to it, overlaps merged
+fn busy_intervals(stops: &[CrewDayStop]) -> Vec<(NaiveTime, NaiveTime)> {
+    let mut intervals: Vec<(NaiveTime, NaiveTime)> = stops
+        .iter()
+        .filter_map(|stop| {
+            let start = stop_start(stop)?;
+            let end = stop_end(stop)?;
+            let travel_start = start - chrono::Duration::minutes(stop.travel_minutes as i64);
+            // Driving that would begin before midnight is cut off
+            let travel_start = if travel_start > start { NaiveTime::MIN } else { travel_start };
+            (end > travel_start).then_some((travel_start, end))
+        })
+        .collect();
+    intervals.sort();
+
+    let mut merged: Vec<(NaiveTime, NaiveTime)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Split working hours into blocks with the busy minutes of each
+fn calendar_blocks(
+    work_start: NaiveTime,
+    work_end: NaiveTime,
+    block_minutes: i32,
+    busy: &[(NaiveTime, NaiveTime)],
+) -> Vec<CalendarBlock> {
+    let mut blocks = vec![];
+    let mut start = work_start;
+    while start < work_end {
+        let end = add_minutes(start, block_minutes).min(work_end);
+        // add_minutes wraps at midnight
+        let end = if end <= start { work_end } else { end };
+        let used: i32 = busy
+            .iter()
+            .map(|(s, e)| time_overlap_minutes(start, end, *s, *e).max(0))
+            .sum();
+        let length = minutes_between(start, end);
+        let used = used.min(length);
+        blocks.push(CalendarBlock {
+            start,
+            end,
+            used_minutes: used,
+            free_minutes: length - used,
+        });
+        start = end;
+    }
+    blocks
+}
+
 async fn resolve_depot_for_crew(
     pool: &PgPool,
     user_id: Uuid,
@@ -366,17 +487,17 @@ async fn resolve_depot_for_crew(
 }
 
 async fn build_crew_day_stops(
-    pool: &PgPool,
+    repos: &Repositories,
     user_id: Uuid,
     date: chrono::NaiveDate,
     crew_id: Uuid,
 ) -> Result<Vec<CrewDayStop>> {
-    let routes = queries::route::list_routes_for_date(pool, user_id, date).await?;
+    let routes = repos.routes.list_routes_for_date(user_id, date).await?;
     let route_for_crew = routes.into_iter().find(|r| r.crew_id == Some(crew_id));
     let mut out: Vec<CrewDayStop> = vec![];
     let mut existing_customer_ids: HashSet<Uuid> = HashSet::new();
     if let Some(route) = route_for_crew {
-        let route_stops = queries::route::get_route_stops_with_info(pool, route.id).await?;
+        let route_stops = repos.routes.get_route_stops_with_info(route.id).await?;
         for s in route_stops {
             if s.stop_type == "break" {
                 continue;
@@ -390,6 +511,7 @@ async fn build_crew_day_stops(
                     _ => DEFAULT_SERVICE_DURATION_MINUTES as i32,
                 };
                 out.push(CrewDayStop {
+                    customer_id,
                     customer_name: s.customer_name.unwrap_or_else(|| "common:customer".to_string()),
                     coordinates: Coordinates { lat, lng },
                     arrival_time: s.estimated_arrival,
@@ -397,12 +519,17 @@ async fn build_crew_day_stops(
                     time_window_start: s.scheduled_time_start,
                     time_window_end: s.scheduled_time_end,
                     service_duration_minutes: duration,
+                    travel_minutes: s
+                        .override_travel_duration_minutes
+                        .or(s.duration_from_previous_minutes)
+                        .unwrap_or(0)
+                        .max(0),
                 });
             }
         }
     }
 
-    let revisions = queries::revision::list_revisions_for_date(pool, user_id, date).await?;
+    let revisions = repos.revisions.list_revisions_for_date(user_id, date).await?;
     let mut customer_cache: HashMap<Uuid, Option<crate::types::Customer>> = HashMap::new();
     for rev in revisions {
         if rev.assigned_crew_id != Some(crew_id) {
@@ -417,7 +544,7 @@ async fn build_crew_day_stops(
         let customer = if let Some(cached) = customer_cache.get(&rev.customer_id) {
             cached.clone()
         } else {
-            let fetched = queries::customer::get_customer(pool, user_id, rev.customer_id).await?;
+            let fetched = repos.customers.get_customer(user_id, rev.customer_id).await?;
             customer_cache.insert(rev.customer_id, fetched.clone());
             fetched
         };
@@ -434,6 +561,7 @@ async fn build_crew_day_stops(
             .unwrap_or(DEFAULT_SERVICE_DURATION_MINUTES as i32);
         let departure = start.map(|s| add_minutes(s, duration));
         out.push(CrewDayStop {
+            customer_id: rev.customer_id,
             customer_name: c.name.unwrap_or_else(|| "common:customer".to_string()),
             coordinates: Coordinates { lat, lng },
             arrival_time: start,
@@ -441,6 +569,7 @@ async fn build_crew_day_stops(
             time_window_start: rev.scheduled_time_start,
             time_window_end: rev.scheduled_time_end,
             service_duration_minutes: duration,
+            travel_minutes: 0,
         });
     }
 
@@ -448,10 +577,188 @@ async fn build_crew_day_stops(
     Ok(out)
 }
 
+/// Crew-aware slot suggestions for a customer location on one day.
+/// Shared by slots.suggest.v2 and the reschedule request flow.
+pub(crate) async fn suggest_crew_slots(
+    pool: &PgPool,
+    repos: &Repositories,
+    routing_service: &dyn RoutingService,
+    user_id: Uuid,
+    settings: &crate::types::settings::UserWithSettings,
+    candidate: Coordinates,
+    req: &SuggestSlotsV2Request,
+) -> Result<SuggestSlotsV2Response> {
+    let all_crews = queries::crew::list_crews(pool, user_id, true).await?;
+    let target_crews: Vec<crate::types::Crew> = if let Some(ids) = req.crew_ids.clone() {
+        all_crews.into_iter().filter(|c| ids.contains(&c.id)).collect()
+    } else {
+        all_crews
+    };
+
+    let mut warnings: Vec<SlotWarning> = vec![];
+    if target_crews.is_empty() {
+        warnings.push(SlotWarning {
+            severity: "error".to_string(),
+            warning_type: "no_crews".to_string(),
+            message: json!({"key": "planner:slot.no_active_crew"}).to_string(),
+            conflicting_customer: None,
+        });
+        return Ok(SuggestSlotsV2Response {
+            suggestions: vec![],
+            warnings,
+        });
+    }
+
+    struct CrewComputed {
+        crew_id: Uuid,
+        crew_name: String,
+        load: i32,
+        suggestions: Vec<CrewSlotSuggestion>,
+    }
+
+    let mut crew_results: Vec<CrewComputed> = vec![];
+    let max_per_crew = req.max_per_crew.unwrap_or(3).max(1) as usize;
+
+    for crew in target_crews {
+        let depot = resolve_depot_for_crew(pool, user_id, &crew, settings).await;
+        let day_stops = build_crew_day_stops(repos, user_id, req.date, crew.id).await?;
+
+        let mut locations: Vec<Coordinates> = vec![candidate, depot];
+        for s in &day_stops {
+            locations.push(s.coordinates);
+        }
+
+        let matrices = match routing_service.get_matrices(&locations).await {
+            Ok(m) => m,
+            Err(e) => {
+                warn!("Routing service failed for crew {}: {}. Using fallback.", crew.name, e);
+                let mock = MockRoutingService::new();
+                match mock.get_matrices(&locations).await {
+                    Ok(m) => m,
+                    Err(_) => {
+                        warnings.push(SlotWarning {
+                            severity: "warning".to_string(),
+                            warning_type: "routing_fallback".to_string(),
+                            message: json!({"key": "planner:slot.crew_route_failed", "params": {"name": crew.name}}).to_string(),
+                            conflicting_customer: None,
+                        });
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let stop_indices: Vec<usize> = (0..day_stops.len()).map(|i| i + 2).collect();
+        let stops_meta: Vec<StopMeta> = day_stops
+            .iter()
+            .map(|s| StopMeta {
+                name: s.customer_name.clone(),
+                arrival_time: s.arrival_time,
+                departure_time: s.departure_time,
+                time_window_start: s.time_window_start,
+                time_window_end: s.time_window_end,
+                service_duration_minutes: s.service_duration_minutes,
+            })
+            .collect();
+        let insertion_positions = calculate_insertion_positions(
+            &matrices,
+            0,
+            1,
+            &stop_indices,
+            &stops_meta,
+            req.service_duration_minutes,
+            crew.working_hours_start,
+            crew.working_hours_end,
+        );
+
+        let total_service: i32 = day_stops.iter().map(|s| s.service_duration_minutes).sum();
+        let mut travel_total = 0i32;
+        if !day_stops.is_empty() {
+            travel_total += (matrices.durations[1][2] / 60) as i32;
+            for i in 0..day_stops.len().saturating_sub(1) {
+                let from_idx = i + 2;
+                let to_idx = i + 3;
+                travel_total += (matrices.durations[from_idx][to_idx] / 60) as i32;
+            }
+            let last_idx = day_stops.len() + 1;
+            travel_total += (matrices.durations[last_idx][1] / 60) as i32;
+        }
+        let load = day_load_percent(crew.working_hours_start, crew.working_hours_end, total_service, travel_total);
+
+        let mut suggestions: Vec<CrewSlotSuggestion> = insertion_positions
+            .iter()
+            .map(|p| CrewSlotSuggestion {
+                crew_id: crew.id,
+                crew_name: crew.name.clone(),
+                start_time: p.estimated_arrival,
+                end_time: p.estimated_departure,
+                insert_position: p.insert_after_index + 1,
+                score: 0,
+                delta_travel_minutes: p.delta_min.round() as i32,
+                delta_distance_km: p.delta_km,
+                estimated_arrival: p.estimated_arrival,
+                slack_before_minutes: p.slack_before_minutes.unwrap_or(0),
+                slack_after_minutes: p.slack_after_minutes.unwrap_or(0),
+                day_load_percent: load,
+                status: p.status.clone(),
+                reason: p
+                    .conflict_reason
+                    .clone()
+                    .unwrap_or_else(|| "planner:slot.suitable".to_string()),
+            })
+            .collect();
+        suggestions.sort_by_key(|s| s.delta_travel_minutes);
+        suggestions.truncate(max_per_crew);
+        crew_results.push(CrewComputed {
+            crew_id: crew.id,
+            crew_name: crew.name.clone(),
+            load,
+            suggestions,
+        });
+    }
+
+    let avg_load = if crew_results.is_empty() {
+        0
+    } else {
+        crew_results.iter().map(|c| c.load).sum::<i32>() / crew_results.len() as i32
+    };
+
+    let mut final_suggestions: Vec<CrewSlotSuggestion> = vec![];
+    for crew in &mut crew_results {
+        for suggestion in &mut crew.suggestions {
+            suggestion.score = slot_score(
+                suggestion.delta_travel_minutes,
+                suggestion.slack_before_minutes,
+                suggestion.slack_after_minutes,
+                suggestion.start_time,
+                suggestion.end_time,
+                req.preferred_time_start,
+                req.preferred_time_end,
+                crew.load,
+                avg_load,
+            );
+            suggestion.reason = json!({"key": "planner:slot.suggestion_detail", "params": {"crewName": crew.crew_name, "deltaMinutes": suggestion.delta_travel_minutes, "slackMinutes": suggestion.slack_after_minutes}}).to_string();
+        }
+        crew.suggestions.sort_by(|a, b| b.score.cmp(&a.score));
+        final_suggestions.extend(crew.suggestions.clone());
+    }
+    final_suggestions.sort_by(|a, b| b.score.cmp(&a.score));
+    info!(
+        "slots.suggest.v2: generated {} suggestions for {} crews",
+        final_suggestions.len(),
+        crew_results.len()
+    );
+    Ok(SuggestSlotsV2Response {
+        suggestions: final_suggestions,
+        warnings,
+    })
+}
+
 pub async fn handle_suggest_v2(
     client: Client,
     mut subscriber: Subscriber,
     pool: PgPool,
+    repos: Repositories,
     jwt_secret: Arc<String>,
     routing_service: Arc<dyn RoutingService>,
 ) -> Result<()> {
@@ -489,7 +796,7 @@ pub async fn handle_suggest_v2(
                 continue;
             }
         };
-        let customer = match queries::customer::get_customer(&pool, user_id, req.customer_id).await? {
+        let customer = match repos.customers.get_customer(user_id, req.customer_id).await? {
             Some(c) => c,
             None => {
                 let response = error_response!(request.id, "NOT_FOUND", "Customer not found");
@@ -507,189 +814,250 @@ pub async fn handle_suggest_v2(
             lng: customer_lng,
         };
 
-        let all_crews = queries::crew::list_crews(&pool, user_id, true).await?;
-        let target_crews: Vec<crate::types::Crew> = if let Some(ids) = req.crew_ids.clone() {
-            all_crews.into_iter().filter(|c| ids.contains(&c.id)).collect()
-        } else {
-            all_crews
+        let response = match suggest_crew_slots(&pool, &repos, routing_service.as_ref(), user_id, &settings, candidate, &req).await {
+            Ok(result) => SuccessResponse::new(request.id, result),
+            Err(e) => {
+                error!("Failed to suggest slots: {}", e);
+                let response = error_response!(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+                continue;
+            }
         };
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+    Ok(())
+}
 
-        let mut warnings: Vec<SlotWarning> = vec![];
-        if target_crews.is_empty() {
-            warnings.push(SlotWarning {
-                severity: "error".to_string(),
-                warning_type: "no_crews".to_string(),
-                message: json!({"key": "planner:slot.no_active_crew"}).to_string(),
-                conflicting_customer: None,
-            });
-            let response = SuccessResponse::new(
-                request.id,
-                SuggestSlotsV2Response {
-                    suggestions: vec![],
-                    warnings,
-                },
-            );
-            let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
-            continue;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        struct CrewComputed {
-            crew_id: Uuid,
-            crew_name: String,
-            load: i32,
-            suggestions: Vec<CrewSlotSuggestion>,
-        }
+    fn make_time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).expect("valid time")
+    }
 
-        let mut crew_results: Vec<CrewComputed> = vec![];
-        let max_per_crew = req.max_per_crew.unwrap_or(3).max(1) as usize;
+    // ── build_crew_day_stops (in-memory repositories) ──
 
-        for crew in target_crews {
-            let depot = resolve_depot_for_crew(&pool, user_id, &crew, &settings).await;
-            let day_stops = build_crew_day_stops(&pool, user_id, req.date, crew.id).await?;
+    fn fake_customer(user_id: Uuid, name: &str, lat: f64) -> crate::types::Customer {
+        crate::types::Customer {
+            id: Uuid::new_v4(),
+            user_id,
+            customer_type: Default::default(),
+            name: Some(name.to_string()),
+            contact_person: None,
+            ico: None,
+            dic: None,
+            email: None,
+            phone: None,
+            phone_raw: None,
+            street: None,
+            city: None,
+            postal_code: None,
+            country: None,
+            lat: Some(lat),
+            lng: Some(14.4),
+            geocode_status: "success".to_string(),
+            notes: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            is_abandoned: false,
+            deleted_at: None,
+            parent_customer_id: None,
+            language: None,
+            customer_code: None,
+            acquisition_source: None,
+            referrer_customer_id: None,
+            lead_status: None,
+            lead_lost_reason: None,
+            lead_status_changed_at: None,
+            risk_score: None,
+            risk_flag: None,
+            coverage_warning: None,
+        }
+    }
 
-            let mut locations: Vec<Coordinates> = vec![candidate, depot];
-            for s in &day_stops {
-                locations.push(s.coordinates);
-            }
+    fn fake_revision(
+        user_id: Uuid,
+        customer_id: Uuid,
+        crew_id: Uuid,
+        date: chrono::NaiveDate,
+        start: Option<NaiveTime>,
+    ) -> crate::types::Revision {
+        crate::types::Revision {
+            id: Uuid::new_v4(),
+            device_id: Uuid::new_v4(),
+            customer_id,
+            user_id,
+            status: "scheduled".to_string(),
+            due_date: date,
+            scheduled_date: Some(date),
+            scheduled_time_start: start,
+            scheduled_time_end: start.map(|s| add_minutes(s, 60)),
+            completed_at: None,
+            duration_minutes: Some(45),
+            result: None,
+            findings: None,
+            fulfilled_by_work_item_id: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            assigned_crew_id: Some(crew_id),
+            route_order: None,
+            document_number: None,
+            device_name: None,
+            device_type: None,
+            customer_name: None,
+            customer_phone: None,
+            customer_street: None,
+            customer_city: None,
+            customer_postal_code: None,
+        }
+    }
 
-            let matrices = match routing_service.get_matrices(&locations).await {
-                Ok(m) => m,
-                Err(e) => {
-                    warn!("Routing service failed for crew {}: {}. Using fallback.", crew.name, e);
-                    let mock = MockRoutingService::new();
-                    match mock.get_matrices(&locations).await {
-                        Ok(m) => m,
-                        Err(_) => {
-                            warnings.push(SlotWarning {
-                                severity: "warning".to_string(),
-                                warning_type: "routing_fallback".to_string(),
-                                message: json!({"key": "planner:slot.crew_route_failed", "params": {"name": crew.name}}).to_string(),
-                                conflicting_customer: None,
-                            });
-                            continue;
-                        }
-                    }
-                }
-            };
+    #[tokio::test]
+    async fn test_build_crew_day_stops_merges_route_and_scheduled_revisions() {
+        use crate::db::repo::{InMemoryCustomerRepo, InMemoryRevisionRepo, InMemoryRouteRepo};
+
+        let user_id = Uuid::new_v4();
+        let crew_id = Uuid::new_v4();
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 5, 4).unwrap();
+        let customers = Arc::new(InMemoryCustomerRepo::default());
+        let revisions = Arc::new(InMemoryRevisionRepo::default());
+        let routes = Arc::new(InMemoryRouteRepo::default());
+
+        let on_route = fake_customer(user_id, "On route", 50.1);
+        let scheduled = fake_customer(user_id, "Scheduled", 50.2);
+        let unscheduled = fake_customer(user_id, "No time", 50.3);
+        let route_id = Uuid::new_v4();
+        routes.insert_route(queries::route::RouteWithCrewInfo {
+            id: route_id,
+            user_id,
+            crew_id: Some(crew_id),
+            crew_name: Some("Crew".to_string()),
+            depot_id: None,
+            date,
+            status: "draft".to_string(),
+            total_distance_km: None,
+            total_duration_minutes: None,
+            optimization_score: None,
+            arrival_buffer_percent: 0.0,
+            arrival_buffer_fixed_minutes: 0.0,
+            return_to_depot_distance_km: None,
+            return_to_depot_duration_minutes: None,
+            stops_count: Some(1),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        });
+        routes.insert_stop(queries::route::RouteStopWithInfo {
+            id: Uuid::new_v4(),
+            route_id,
+            customer_id: Some(on_route.id),
+            visit_id: None,
+            revision_id: None,
+            stop_order: 1,
+            estimated_arrival: Some(make_time(13, 0)),
+            estimated_departure: Some(make_time(13, 30)),
+            distance_from_previous_km: None,
+            duration_from_previous_minutes: None,
+            status: "pending".to_string(),
+            stop_type: "customer".to_string(),
+            customer_name: on_route.name.clone(),
+            address: None,
+            customer_lat: on_route.lat,
+            customer_lng: on_route.lng,
+            customer_phone: None,
+            customer_email: None,
+            scheduled_date: None,
+            scheduled_time_start: None,
+            scheduled_time_end: None,
+            revision_status: None,
+            break_duration_minutes: None,
+            break_time_start: None,
+            service_duration_minutes: None,
+            override_service_duration_minutes: None,
+            override_travel_duration_minutes: None,
+            notes: None,
+            tasks: sqlx::types::Json(vec![]),
+            break_location: None,
+            customer_risk_flag: false,
+        });
+        // Already on the route: not listed twice
+        revisions.insert(fake_revision(user_id, on_route.id, crew_id, date, Some(make_time(13, 0))));
+        revisions.insert(fake_revision(user_id, scheduled.id, crew_id, date, Some(make_time(9, 0))));
+        revisions.insert(fake_revision(user_id, unscheduled.id, crew_id, date, None));
+        // Another crew's revision
+        revisions.insert(fake_revision(user_id, scheduled.id, Uuid::new_v4(), date, Some(make_time(8, 0))));
+        for customer in [on_route, scheduled, unscheduled] {
+            customers.insert(customer);
+        }
 
-            let stop_indices: Vec<usize> = (0..day_stops.len()).map(|i| i + 2).collect();
-            let stops_meta: Vec<StopMeta> = day_stops
-                .iter()
-                .map(|s| StopMeta {
-                    name: s.customer_name.clone(),
-                    arrival_time: s.arrival_time,
-                    departure_time: s.departure_time,
-                    time_window_start: s.time_window_start,
-                    time_window_end: s.time_window_end,
-                    service_duration_minutes: s.service_duration_minutes,
-                })
-                .collect();
-            let insertion_positions = calculate_insertion_positions(
-                &matrices,
-                0,
-                1,
-                &stop_indices,
-                &stops_meta,
-                req.service_duration_minutes,
-                crew.working_hours_start,
-                crew.working_hours_end,
-            );
+        let repos = Repositories { customers, revisions, routes };
+        let stops = build_crew_day_stops(&repos, user_id, date, crew_id).await.unwrap();
 
-            let total_service: i32 = day_stops.iter().map(|s| s.service_duration_minutes).sum();
-            let mut travel_total = 0i32;
-            if !day_stops.is_empty() {
-                travel_total += (matrices.durations[1][2] / 60) as i32;
-                for i in 0..day_stops.len().saturating_sub(1) {
-                    let from_idx = i + 2;
-                    let to_idx = i + 3;
-                    travel_total += (matrices.durations[from_idx][to_idx] / 60) as i32;
-                }
-                let last_idx = day_stops.len() + 1;
-                travel_total += (matrices.durations[last_idx][1] / 60) as i32;
-            }
-            let load = day_load_percent(crew.working_hours_start, crew.working_hours_end, total_service, travel_total);
-
-            let mut suggestions: Vec<CrewSlotSuggestion> = insertion_positions
-                .iter()
-                .map(|p| CrewSlotSuggestion {
-                    crew_id: crew.id,
-                    crew_name: crew.name.clone(),
-                    start_time: p.estimated_arrival,
-                    end_time: p.estimated_departure,
-                    insert_position: p.insert_after_index + 1,
-                    score: 0,
-                    delta_travel_minutes: p.delta_min.round() as i32,
-                    delta_distance_km: p.delta_km,
-                    estimated_arrival: p.estimated_arrival,
-                    slack_before_minutes: p.slack_before_minutes.unwrap_or(0),
-                    slack_after_minutes: p.slack_after_minutes.unwrap_or(0),
-                    day_load_percent: load,
-                    status: p.status.clone(),
-                    reason: p
-                        .conflict_reason
-                        .clone()
-                        .unwrap_or_else(|| "planner:slot.suitable".to_string()),
-                })
-                .collect();
-            suggestions.sort_by_key(|s| s.delta_travel_minutes);
-            suggestions.truncate(max_per_crew);
-            crew_results.push(CrewComputed {
-                crew_id: crew.id,
-                crew_name: crew.name.clone(),
-                load,
-                suggestions,
-            });
+        let names: Vec<&str> = stops.iter().map(|s| s.customer_name.as_str()).collect();
+        assert_eq!(names, vec!["Scheduled", "On route"]);
+        assert_eq!(stops[0].service_duration_minutes, 45);
+        assert_eq!(stops[0].departure_time, Some(make_time(9, 45)));
+        assert_eq!(stops[1].service_duration_minutes, 30);
+    }
+
+    // ── slots.calendar ──
+
+    fn visit(start: (u32, u32), end: (u32, u32), travel_minutes: i32) -> CrewDayStop {
+        CrewDayStop {
+            customer_id: Uuid::new_v4(),
+            customer_name: "Customer".to_string(),
+            coordinates: Coordinates { lat: 50.0, lng: 14.4 },
+            arrival_time: Some(make_time(start.0, start.1)),
+            departure_time: Some(make_time(end.0, end.1)),
+            time_window_start: None,
+            time_window_end: None,
+            service_duration_minutes: minutes_between(make_time(start.0, start.1), make_time(end.0, end.1)),
+            travel_minutes,
         }
+    }
 
-        let avg_load = if crew_results.is_empty() {
-            0
-        } else {
-            crew_results.iter().map(|c| c.load).sum::<i32>() / crew_results.len() as i32
-        };
+    #[test]
+    fn test_busy_intervals_include_travel_and_merge() {
+        let busy = busy_intervals(&[visit((10, 0), (10, 30), 15), visit((8, 0), (9, 0), 20), visit((10, 40), (11, 0), 10)]);
 
-        let mut final_suggestions: Vec<CrewSlotSuggestion> = vec![];
-        for crew in &mut crew_results {
-            for suggestion in &mut crew.suggestions {
-                suggestion.score = slot_score(
-                    suggestion.delta_travel_minutes,
-                    suggestion.slack_before_minutes,
-                    suggestion.slack_after_minutes,
-                    suggestion.start_time,
-                    suggestion.end_time,
-                    req.preferred_time_start,
-                    req.preferred_time_end,
-                    crew.load,
-                    avg_load,
-                );
-                suggestion.reason = json!({"key": "planner:slot.suggestion_detail", "params": {"crewName": crew.crew_name, "deltaMinutes": suggestion.delta_travel_minutes, "slackMinutes": suggestion.slack_after_minutes}}).to_string();
-            }
-            crew.suggestions.sort_by(|a, b| b.score.cmp(&a.score));
-            final_suggestions.extend(crew.suggestions.clone());
-        }
-        final_suggestions.sort_by(|a, b| b.score.cmp(&a.score));
-        info!(
-            "slots.suggest.v2: generated {} suggestions for {} crews",
-            final_suggestions.len(),
-            crew_results.len()
+        assert_eq!(
+            busy,
+            vec![(make_time(7, 40), make_time(9, 0)), (make_time(9, 45), make_time(11, 0))]
         );
-        let response = SuccessResponse::new(
-            request.id,
-            SuggestSlotsV2Response {
-                suggestions: final_suggestions,
-                warnings,
-            },
+    }
+
+    #[test]
+    fn test_calendar_blocks_split_working_hours() {
+        let busy = vec![(make_time(8, 30), make_time(9, 15))];
+        let blocks = calendar_blocks(make_time(8, 0), make_time(10, 30), 60, &busy);
+
+        let summary: Vec<(NaiveTime, i32, i32)> = blocks.iter().map(|b| (b.start, b.used_minutes, b.free_minutes)).collect();
+        assert_eq!(
+            summary,
+            vec![(make_time(8, 0), 30, 30), (make_time(9, 0), 15, 45), (make_time(10, 0), 0, 30)]
         );
-        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+        assert_eq!(blocks[2].end, make_time(10, 30));
     }
-    Ok(())
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_slot_calendar_request_validation() {
+        let date = |d| chrono::NaiveDate::from_ymd_opt(2026, 5, d).unwrap();
+        let request = |from, to, block_minutes| SlotCalendarRequest {
+            date_from: date(from),
+            date_to: date(to),
+            crew_id: None,
+            block_minutes,
+        };
 
-    fn make_time(h: u32, m: u32) -> NaiveTime {
-        NaiveTime::from_hms_opt(h, m, 0).expect("valid time")
+        assert!(request(4, 8, None).validate().is_ok());
+        assert!(request(8, 4, None).validate().is_err());
+        assert!(request(4, 8, Some(5)).validate().is_err());
+        assert!(SlotCalendarRequest {
+            date_to: date(4) + chrono::Duration::days(MAX_CALENDAR_DAYS),
+            ..request(4, 4, None)
+        }
+        .validate()
+        .is_err());
     }
 
     // ── preference_score ──
@@ -801,6 +1169,7 @@ pub async fn handle_validate(
     client: Client,
     mut subscriber: Subscriber,
     pool: PgPool,
+    repos: Repositories,
     jwt_secret: Arc<String>,
     routing_service: Arc<dyn RoutingService>,
 ) -> Result<()> {
@@ -846,7 +1215,7 @@ pub async fn handle_validate(
                 continue;
             }
         };
-        let customer = match queries::customer::get_customer(&pool, user_id, req.customer_id).await? {
+        let customer = match repos.customers.get_customer(user_id, req.customer_id).await? {
             Some(c) => c,
             None => {
                 let response = error_response!(request.id, "NOT_FOUND", "Customer not found");
@@ -861,7 +1230,7 @@ pub async fn handle_validate(
         };
         let candidate = Coordinates { lat: c_lat, lng: c_lng };
         let depot = resolve_depot_for_crew(&pool, user_id, &crew, &settings).await;
-        let day_stops = build_crew_day_stops(&pool, user_id, req.date, crew.id).await?;
+        let day_stops = build_crew_day_stops(&repos, user_id, req.date, crew.id).await?;
 
         let mut warnings: Vec<SlotWarning> = vec![];
         let mut hard_error = false;
@@ -1025,3 +1394,135 @@ pub async fn handle_validate(
     }
     Ok(())
 }
+
+/// Handle slots.calendar - free and used crew capacity per day and time block
+pub async fn handle_calendar(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    repos: Repositories,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received slots.calendar message");
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => continue,
+        };
+
+        let request: Request<SlotCalendarRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                let response = error_response!(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let response = error_response!(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+                continue;
+            }
+        };
+
+        let req = request.payload;
+        if let Err(e) = req.validate() {
+            let response = error_response!(request.id, "INVALID_REQUEST", e);
+            let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            continue;
+        }
+
+        let crews: Vec<crate::types::Crew> = queries::crew::list_crews(&pool, user_id, true)
+            .await?
+            .into_iter()
+            .filter(|c| req.crew_id.is_none_or(|id| c.id == id))
+            .collect();
+        if crews.is_empty() && req.crew_id.is_some() {
+            let response = error_response!(request.id, "NOT_FOUND", "Crew not found");
+            let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            continue;
+        }
+
+        let block_minutes = req.block_minutes.unwrap_or(DEFAULT_CALENDAR_BLOCK_MINUTES);
+        let days = crew_calendar_days(&pool, &repos, user_id, &crews, req.date_from, req.date_to, block_minutes).await?;
+
+        debug!("slots.calendar: {} crew days", days.len());
+        let response = SuccessResponse::new(request.id, SlotCalendarResponse { days });
+        let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+    }
+    Ok(())
+}
+
+/// Free and used capacity of each crew on each day of the range
+pub(crate) async fn crew_calendar_days(
+    pool: &PgPool,
+    repos: &Repositories,
+    user_id: Uuid,
+    crews: &[crate::types::Crew],
+    date_from: chrono::NaiveDate,
+    date_to: chrono::NaiveDate,
+    block_minutes: i32,
+) -> Result<Vec<CrewCalendarDay>> {
+    // Visits booked outside of routes and revisions
+    let (visits, _) =
+        queries::visit::list_visits(pool, user_id, None, Some(date_from), Some(date_to), None, None, 10_000, 0)
+            .await?;
+
+    let mut days: Vec<CrewCalendarDay> = vec![];
+    for date in date_from.iter_days().take_while(|d| *d <= date_to) {
+        for crew in crews {
+            let mut stops = build_crew_day_stops(repos, user_id, date, crew.id).await?;
+            for visit in &visits {
+                if visit.scheduled_date != date
+                    || visit.crew_id != Some(crew.id)
+                    || visit.status == "cancelled"
+                    || stops.iter().any(|s| s.customer_id == visit.customer_id)
+                {
+                    continue;
+                }
+                let Some(start) = visit.scheduled_time_start else {
+                    continue;
+                };
+                let end = visit
+                    .scheduled_time_end
+                    .filter(|end| *end > start)
+                    .unwrap_or_else(|| add_minutes(start, DEFAULT_SERVICE_DURATION_MINUTES as i32));
+                stops.push(CrewDayStop {
+                    customer_id: visit.customer_id,
+                    customer_name: visit.customer_name.clone().unwrap_or_default(),
+                    coordinates: Coordinates { lat: 0.0, lng: 0.0 },
+                    arrival_time: Some(start),
+                    departure_time: Some(end),
+                    time_window_start: visit.scheduled_time_start,
+                    time_window_end: visit.scheduled_time_end,
+                    service_duration_minutes: minutes_between(start, end),
+                    travel_minutes: 0,
+                });
+            }
+
+            let blocks = calendar_blocks(
+                crew.working_hours_start,
+                crew.working_hours_end,
+                block_minutes,
+                &busy_intervals(&stops),
+            );
+            let capacity = minutes_between(crew.working_hours_start, crew.working_hours_end);
+            let used: i32 = blocks.iter().map(|b| b.used_minutes).sum();
+            days.push(CrewCalendarDay {
+                date,
+                crew_id: crew.id,
+                crew_name: crew.name.clone(),
+                visit_count: stops.len() as i32,
+                capacity_minutes: capacity,
+                used_minutes: used,
+                free_minutes: capacity - used,
+                load_percent: day_load_percent(crew.working_hours_start, crew.working_hours_end, used, 0),
+                blocks,
+            });
+        }
+    }
+    Ok(days)
+}
@@ -0,0 +1,657 @@
+//! Subcontractor handlers for NATS messages
+//!
+//! The account owner manages subcontractors, their crews, logins and rate
+//! cards. A subcontractor login (role `subcontractor`) sees only the routes
+//! and visits of its crews (`my.*`) and its own settlement; it has no data of
+//! its own, so the account's other subjects show it nothing.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::account;
+use super::auth::is_duplicate_email_error;
+use super::{parse_authenticated, reply_error, reply_success};
+use crate::auth::{self, AuthInfo};
+use crate::db::queries;
+use crate::services::subcontractor as settlement;
+use crate::subjects;
+use crate::types::currency::DEFAULT_CURRENCY;
+use crate::types::subcontractor::{
+    AssignedRoutesResponse, AssignedVisitsResponse, AssignedWorkRequest, CreateSubcontractorAccountRequest,
+    CreateSubcontractorRequest, ListSubcontractorsResponse, SetSubcontractorRatesRequest, SettlementRequest,
+    Subcontractor, SubcontractorIdRequest, SubcontractorRatesResponse, UpdateSubcontractorRequest,
+    SUBCONTRACTOR_ROLE,
+};
+use crate::types::user::UserPublic;
+
+/// Start all subcontractor NATS handlers
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting subcontractor handlers...");
+
+    let [create_sub, list_sub, update_sub, delete_sub, account_sub, rates_get_sub, rates_set_sub, settlement_sub, my_routes_sub, my_visits_sub] =
+        subjects::subscribe_all(
+            &client,
+            [
+                subjects::subcontractor::CREATE,
+                subjects::subcontractor::LIST,
+                subjects::subcontractor::UPDATE,
+                subjects::subcontractor::DELETE,
+                subjects::subcontractor::ACCOUNT_CREATE,
+                subjects::subcontractor::RATES_GET,
+                subjects::subcontractor::RATES_SET,
+                subjects::subcontractor::SETTLEMENT,
+                subjects::subcontractor::MY_ROUTES,
+                subjects::subcontractor::MY_VISITS,
+            ],
+        )
+        .await?;
+
+    tokio::spawn(handle_create(client.clone(), create_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_list(client.clone(), list_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_update(client.clone(), update_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_delete(client.clone(), delete_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_create_account(client.clone(), account_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_get_rates(client.clone(), rates_get_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_set_rates(client.clone(), rates_set_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_settlement(client.clone(), settlement_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_my_routes(client.clone(), my_routes_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_my_visits(client, my_visits_sub, pool, jwt_secret));
+
+    info!("Subcontractor handlers started");
+    Ok(())
+}
+
+fn is_owner(auth_info: &AuthInfo) -> bool {
+    auth_info.role == "customer" || auth_info.role == "admin"
+}
+
+/// Owner's subcontractor by id. Replies NOT_FOUND itself.
+async fn find_owned(
+    client: &Client,
+    pool: &PgPool,
+    reply: &async_nats::Subject,
+    request_id: Uuid,
+    user_id: Uuid,
+    id: Uuid,
+) -> Result<Option<Subcontractor>> {
+    match queries::subcontractor::get_subcontractor(pool, user_id, id).await {
+        Ok(Some(subcontractor)) => Ok(Some(subcontractor)),
+        Ok(None) => {
+            reply_error(client, reply, request_id, "NOT_FOUND", "Subcontractor not found").await?;
+            Ok(None)
+        }
+        Err(e) => {
+            error!("Failed to load subcontractor: {}", e);
+            reply_error(client, reply, request_id, "DATABASE_ERROR", e.to_string()).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Subcontractor of a subcontractor login and the account it works for.
+/// Replies FORBIDDEN itself for other roles and deactivated subcontractors.
+async fn find_own(
+    client: &Client,
+    pool: &PgPool,
+    reply: &async_nats::Subject,
+    request_id: Uuid,
+    auth_info: &AuthInfo,
+) -> Result<Option<(Uuid, Subcontractor)>> {
+    let owner_id = match auth_info.owner_id {
+        Some(owner_id) if auth_info.role == SUBCONTRACTOR_ROLE => owner_id,
+        _ => {
+            reply_error(client, reply, request_id, "FORBIDDEN", "Only subcontractors have assigned work").await?;
+            return Ok(None);
+        }
+    };
+    match queries::subcontractor::get_by_account(pool, owner_id, auth_info.user_id).await {
+        Ok(Some(subcontractor)) => Ok(Some((owner_id, subcontractor))),
+        Ok(None) => {
+            reply_error(client, reply, request_id, "FORBIDDEN", "Subcontractor is deactivated").await?;
+            Ok(None)
+        }
+        Err(e) => {
+            error!("Failed to load subcontractor of {}: {}", auth_info.user_id, e);
+            reply_error(client, reply, request_id, "DATABASE_ERROR", e.to_string()).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Handle subcontractor.create messages
+pub async fn handle_create(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received subcontractor.create message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<CreateSubcontractorRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+
+        if !is_owner(&auth_info) {
+            reply_error(&client, &reply, request.id, "FORBIDDEN", "Only the account owner can manage subcontractors").await?;
+            continue;
+        }
+        let user_id = auth_info.data_user_id();
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+        if let Err(msg) = request.payload.validate() {
+            reply_error(&client, &reply, request.id, "INVALID_REQUEST", msg).await?;
+            continue;
+        }
+
+        match queries::subcontractor::create_subcontractor(&pool, user_id, &request.payload).await {
+            Ok(subcontractor) => reply_success(&client, &reply, request.id, subcontractor).await?,
+            Err(e) => {
+                error!("Failed to create subcontractor: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle subcontractor.list messages
+pub async fn handle_list(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received subcontractor.list message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<serde_json::Value>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+
+        if !is_owner(&auth_info) {
+            reply_error(&client, &reply, request.id, "FORBIDDEN", "Only the account owner can manage subcontractors").await?;
+            continue;
+        }
+
+        match queries::subcontractor::list_subcontractors(&pool, auth_info.data_user_id()).await {
+            Ok(items) => reply_success(&client, &reply, request.id, ListSubcontractorsResponse { items }).await?,
+            Err(e) => {
+                error!("Failed to list subcontractors: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle subcontractor.update messages
+pub async fn handle_update(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received subcontractor.update message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<UpdateSubcontractorRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+
+        if !is_owner(&auth_info) {
+            reply_error(&client, &reply, request.id, "FORBIDDEN", "Only the account owner can manage subcontractors").await?;
+            continue;
+        }
+        let user_id = auth_info.data_user_id();
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+        if let Err(msg) = request.payload.validate() {
+            reply_error(&client, &reply, request.id, "INVALID_REQUEST", msg).await?;
+            continue;
+        }
+
+        match queries::subcontractor::update_subcontractor(&pool, user_id, &request.payload).await {
+            Ok(Some(subcontractor)) => reply_success(&client, &reply, request.id, subcontractor).await?,
+            Ok(None) => reply_error(&client, &reply, request.id, "NOT_FOUND", "Subcontractor not found").await?,
+            Err(e) => {
+                error!("Failed to update subcontractor: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle subcontractor.delete messages
+pub async fn handle_delete(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received subcontractor.delete message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<SubcontractorIdRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+
+        if !is_owner(&auth_info) {
+            reply_error(&client, &reply, request.id, "FORBIDDEN", "Only the account owner can manage subcontractors").await?;
+            continue;
+        }
+        let user_id = auth_info.data_user_id();
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        match queries::subcontractor::delete_subcontractor(&pool, user_id, request.payload.id).await {
+            Ok(true) => {
+                #[derive(Serialize)]
+                struct DeleteResult {
+                    deleted: bool,
+                }
+                reply_success(&client, &reply, request.id, DeleteResult { deleted: true }).await?;
+            }
+            Ok(false) => reply_error(&client, &reply, request.id, "NOT_FOUND", "Subcontractor not found").await?,
+            Err(e) => {
+                error!("Failed to delete subcontractor: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle subcontractor.account.create messages (login for a subcontractor)
+pub async fn handle_create_account(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received subcontractor.account.create message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<CreateSubcontractorAccountRequest>(&client, &reply, &msg.payload, &jwt_secret)
+                .await?
+        else {
+            continue;
+        };
+
+        if !is_owner(&auth_info) {
+            reply_error(&client, &reply, request.id, "FORBIDDEN", "Only the account owner can manage subcontractors").await?;
+            continue;
+        }
+        if auth_info.requires_email_verification() {
+            reply_error(&client, &reply, request.id, "EMAIL_NOT_VERIFIED", "Verify your email address before adding subcontractor logins").await?;
+            continue;
+        }
+        let user_id = auth_info.data_user_id();
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let payload = &request.payload;
+        if payload.email.is_empty() || payload.password.is_empty() || payload.name.is_empty() {
+            reply_error(&client, &reply, request.id, "VALIDATION_ERROR", "Email, password, and name are required").await?;
+            continue;
+        }
+        if payload.password.len() < 8 {
+            reply_error(&client, &reply, request.id, "VALIDATION_ERROR", "Password must be at least 8 characters").await?;
+            continue;
+        }
+
+        let Some(subcontractor) =
+            find_owned(&client, &pool, &reply, request.id, user_id, payload.subcontractor_id).await?
+        else {
+            continue;
+        };
+        if subcontractor.account_user_id.is_some() {
+            reply_error(&client, &reply, request.id, "ACCOUNT_EXISTS", "Subcontractor already has a login").await?;
+            continue;
+        }
+
+        let password_hash = match auth::hash_password(&payload.password) {
+            Ok(hash) => hash,
+            Err(e) => {
+                error!("Failed to hash password: {}", e);
+                reply_error(&client, &reply, request.id, "INTERNAL_ERROR", "Failed to process password").await?;
+                continue;
+            }
+        };
+
+        // Owned like a worker login, but data queries stay on its own id
+        let user = match queries::user::create_user(
+            &pool,
+            &payload.email,
+            &password_hash,
+            &payload.name,
+            Some(&subcontractor.name),
+            SUBCONTRACTOR_ROLE,
+            Some(user_id),
+            None,
+        )
+        .await
+        {
+            Ok(user) => user,
+            Err(e) if is_duplicate_email_error(&e) => {
+                reply_error(&client, &reply, request.id, "DUPLICATE_EMAIL", "Email is already registered").await?;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to create subcontractor login: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+                continue;
+            }
+        };
+
+        match queries::subcontractor::set_account(&pool, user_id, subcontractor.id, user.id).await {
+            Ok(true) => reply_success(&client, &reply, request.id, UserPublic::from(user)).await?,
+            result => {
+                // Lost a race with another login for the same subcontractor
+                if let Err(e) = &result {
+                    error!("Failed to link subcontractor login: {}", e);
+                }
+                if let Err(e) = queries::subcontractor::delete_account(&pool, user_id, user.id).await {
+                    error!("Failed to remove unlinked subcontractor login {}: {}", user.id, e);
+                }
+                reply_error(&client, &reply, request.id, "ACCOUNT_EXISTS", "Subcontractor already has a login").await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle subcontractor.rates.get messages
+pub async fn handle_get_rates(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received subcontractor.rates.get message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<SubcontractorIdRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+
+        if !is_owner(&auth_info) {
+            reply_error(&client, &reply, request.id, "FORBIDDEN", "Only the account owner can manage subcontractors").await?;
+            continue;
+        }
+        let Some(subcontractor) =
+            find_owned(&client, &pool, &reply, request.id, auth_info.data_user_id(), request.payload.id).await?
+        else {
+            continue;
+        };
+
+        match queries::subcontractor::get_rates(&pool, subcontractor.id).await {
+            Ok(rates) => {
+                let response = SubcontractorRatesResponse { subcontractor_id: subcontractor.id, rates };
+                reply_success(&client, &reply, request.id, response).await?;
+            }
+            Err(e) => {
+                error!("Failed to load subcontractor rates: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle subcontractor.rates.set messages
+pub async fn handle_set_rates(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received subcontractor.rates.set message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<SetSubcontractorRatesRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+
+        if !is_owner(&auth_info) {
+            reply_error(&client, &reply, request.id, "FORBIDDEN", "Only the account owner can manage subcontractors").await?;
+            continue;
+        }
+        let user_id = auth_info.data_user_id();
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+        let Some(subcontractor) =
+            find_owned(&client, &pool, &reply, request.id, user_id, request.payload.subcontractor_id).await?
+        else {
+            continue;
+        };
+
+        let account_currency = match queries::settings::get_user_settings(&pool, user_id).await {
+            Ok(settings) => settings.map(|s| s.currency).unwrap_or_else(|| DEFAULT_CURRENCY.to_string()),
+            Err(e) => {
+                error!("Failed to load account currency: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+                continue;
+            }
+        };
+        let rates = match request.payload.validate(&account_currency) {
+            Ok(rates) => rates,
+            Err(msg) => {
+                reply_error(&client, &reply, request.id, "INVALID_REQUEST", msg).await?;
+                continue;
+            }
+        };
+
+        match queries::subcontractor::set_rates(&pool, subcontractor.id, &rates).await {
+            Ok(()) => {
+                let response = SubcontractorRatesResponse { subcontractor_id: subcontractor.id, rates };
+                reply_success(&client, &reply, request.id, response).await?;
+            }
+            Err(e) => {
+                error!("Failed to save subcontractor rates: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle subcontractor.settlement messages (owner: any subcontractor,
+/// subcontractor: its own)
+pub async fn handle_settlement(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received subcontractor.settlement message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<SettlementRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+        let payload = &request.payload;
+
+        if let Err(msg) = payload.validate() {
+            reply_error(&client, &reply, request.id, "INVALID_REQUEST", msg).await?;
+            continue;
+        }
+
+        let found = if is_owner(&auth_info) {
+            let Some(id) = payload.subcontractor_id else {
+                reply_error(&client, &reply, request.id, "INVALID_REQUEST", "subcontractorId is required").await?;
+                continue;
+            };
+            let user_id = auth_info.data_user_id();
+            find_owned(&client, &pool, &reply, request.id, user_id, id)
+                .await?
+                .map(|subcontractor| (user_id, subcontractor))
+        } else {
+            find_own(&client, &pool, &reply, request.id, &auth_info).await?
+        };
+        let Some((user_id, subcontractor)) = found else {
+            continue;
+        };
+
+        let loaded = tokio::try_join!(
+            queries::subcontractor::settlement_visits(&pool, user_id, subcontractor.id, payload.from_date, payload.to_date),
+            queries::subcontractor::get_rates(&pool, subcontractor.id),
+        );
+        match loaded {
+            Ok((visits, rates)) => {
+                let response =
+                    settlement::build_settlement(&subcontractor, payload.from_date, payload.to_date, visits, &rates);
+                reply_success(&client, &reply, request.id, response).await?;
+            }
+            Err(e) => {
+                error!("Failed to build subcontractor settlement: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle subcontractor.my.routes messages
+pub async fn handle_my_routes(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received subcontractor.my.routes message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<AssignedWorkRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+        let payload = &request.payload;
+
+        if let Err(msg) = payload.validate() {
+            reply_error(&client, &reply, request.id, "INVALID_REQUEST", msg).await?;
+            continue;
+        }
+        let Some((user_id, subcontractor)) = find_own(&client, &pool, &reply, request.id, &auth_info).await? else {
+            continue;
+        };
+
+        match queries::subcontractor::assigned_routes(&pool, user_id, subcontractor.id, payload.from_date, payload.to_date)
+            .await
+        {
+            Ok(routes) => reply_success(&client, &reply, request.id, AssignedRoutesResponse { routes }).await?,
+            Err(e) => {
+                error!("Failed to list assigned routes: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle subcontractor.my.visits messages
+pub async fn handle_my_visits(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received subcontractor.my.visits message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<AssignedWorkRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+        let payload = &request.payload;
+
+        if let Err(msg) = payload.validate() {
+            reply_error(&client, &reply, request.id, "INVALID_REQUEST", msg).await?;
+            continue;
+        }
+        let Some((user_id, subcontractor)) = find_own(&client, &pool, &reply, request.id, &auth_info).await? else {
+            continue;
+        };
+
+        match queries::subcontractor::assigned_visits(&pool, user_id, subcontractor.id, payload.from_date, payload.to_date)
+            .await
+        {
+            Ok(visits) => reply_success(&client, &reply, request.id, AssignedVisitsResponse { visits }).await?,
+            Err(e) => {
+                error!("Failed to list assigned visits: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
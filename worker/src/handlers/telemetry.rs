@@ -0,0 +1,172 @@
+//! Telemetry opt-in handlers for NATS messages
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::queries;
+use crate::services::leader;
+use crate::services::telemetry::{self, TELEMETRY};
+use crate::subjects;
+use crate::types::telemetry::{SetTelemetryRequest, TelemetrySettingsResponse};
+use crate::types::{ErrorResponse, Request, SuccessResponse};
+
+/// Start the telemetry flush loop and settings NATS handlers
+pub async fn start_handlers(
+    client: Client,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    endpoint: Option<String>,
+) -> Result<()> {
+    info!("Starting telemetry handlers...");
+
+    let [get_sub, set_sub] = subjects::subscribe_all(
+        &client,
+        [subjects::settings::TELEMETRY_GET, subjects::settings::TELEMETRY_SET],
+    )
+    .await?;
+
+    let endpoint = Arc::new(endpoint);
+    tokio::spawn(handle_get(client.clone(), get_sub, pool.clone(), jwt_secret.clone(), endpoint.clone()));
+    tokio::spawn(handle_set(client.clone(), set_sub, pool.clone(), jwt_secret, endpoint.clone()));
+    tokio::spawn(telemetry::serve_collect(client.clone(), pool.clone()));
+    leader::spawn_singleton(pool, "telemetry_scheduler", move |_| {
+        telemetry::run_scheduler(client.clone(), (*endpoint).clone())
+    });
+
+    info!("Telemetry handlers started");
+    Ok(())
+}
+
+fn settings_response(user_id: Uuid, enabled: bool, endpoint: &Option<String>) -> TelemetrySettingsResponse {
+    let user_ids: HashSet<Uuid> = [user_id].into_iter().collect();
+    TelemetrySettingsResponse {
+        enabled,
+        endpoint: endpoint.clone(),
+        preview: TELEMETRY.preview(&user_ids),
+    }
+}
+
+/// Handle settings.telemetry.get messages
+pub async fn handle_get(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    endpoint: Arc<Option<String>>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received settings.telemetry.get message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<serde_json::Value> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let user_id = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info.data_user_id(),
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        match queries::telemetry::get_opt_in(&pool, user_id).await {
+            Ok(enabled) => {
+                let response = SuccessResponse::new(request.id, settings_response(user_id, enabled, &endpoint));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to load telemetry opt-in: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle settings.telemetry.set messages
+pub async fn handle_set(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+    endpoint: Arc<Option<String>>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received settings.telemetry.set message");
+
+        let reply = match msg.reply {
+            Some(ref reply) => reply.clone(),
+            None => {
+                warn!("Message without reply subject");
+                continue;
+            }
+        };
+
+        let request: Request<SetTelemetryRequest> = match serde_json::from_slice(&msg.payload) {
+            Ok(req) => req,
+            Err(e) => {
+                error!("Failed to parse request: {}", e);
+                let error = ErrorResponse::new(Uuid::nil(), "INVALID_REQUEST", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        let auth_info = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => info,
+            Err(_) => {
+                let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        };
+
+        if auth_info.role != "customer" && auth_info.role != "admin" {
+            let error = ErrorResponse::new(request.id, "FORBIDDEN", "Only company owners can change telemetry settings");
+            let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            continue;
+        }
+
+        let user_id = auth_info.data_user_id();
+        let enabled = request.payload.enabled;
+        match queries::telemetry::set_opt_in(&pool, user_id, enabled).await {
+            Ok(()) => {
+                info!("Telemetry {} for {}", if enabled { "enabled" } else { "disabled" }, user_id);
+                let response = SuccessResponse::new(request.id, settings_response(user_id, enabled, &endpoint));
+                let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
+            }
+            Err(e) => {
+                error!("Failed to save telemetry opt-in: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -10,12 +10,20 @@ use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::auth;
+use super::{account, retention};
 use crate::db::queries;
+use crate::services::hooks::{self, HookOutcome};
+use crate::services::{
+    communication_log, customer_risk, demo_mode, dispatch_board, visit_duration, weather, webhook_delivery,
+};
+use crate::types::customer_risk::RISK_REASON_VISIT_COMPLETED;
+use crate::types::hooks::{AFTER_VISIT_COMPLETE, BEFORE_VISIT_COMPLETE};
 use crate::types::{
     CompleteVisitRequest, CreateVisitRequest, ErrorResponse, ListNotesHistoryRequest,
     ListNotesHistoryResponse, ListVisitsRequest, ListVisitsResponse, Request, SuccessResponse,
-    UpdateFieldNotesRequest, UpdateVisitRequest,
+    UpdateFieldNotesRequest, UpdateVisitRequest, VisitStatus,
 };
+use crate::types::retention::RETENTION_ENTITY_VISIT;
 
 /// Handle visit.create messages
 pub async fn handle_create(
@@ -58,6 +66,10 @@ pub async fn handle_create(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let payload = request.payload;
         info!(
             "Creating visit for customer {} on {}",
@@ -80,6 +92,8 @@ pub async fn handle_create(
         {
             Ok(visit) => {
                 let visit_id = visit.id;
+                webhook_delivery::emit(&client, &pool, user_id, "visit.created", &visit);
+                dispatch_board::publish_customer_day(&client, &pool, user_id, visit.customer_id, visit.scheduled_date);
                 let response = SuccessResponse::new(request.id, visit);
                 let _ = client
                     .publish(reply, serde_json::to_vec(&response)?.into())
@@ -158,6 +172,7 @@ pub async fn handle_list(
         .await
         {
             Ok((visits, total)) => {
+                let visits = demo_mode::apply(request.demo_mode, visits);
                 let response =
                     SuccessResponse::new(request.id, ListVisitsResponse { visits, total });
                 let _ = client
@@ -219,8 +234,22 @@ pub async fn handle_update(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let payload = request.payload;
 
+        // A visit moved to another day leaves the board of its old day
+        let previous_date = match payload.scheduled_date {
+            Some(_) => queries::visit::get_visit(&pool, payload.id, user_id)
+                .await
+                .ok()
+                .flatten()
+                .map(|visit| visit.scheduled_date),
+            None => None,
+        };
+
         match queries::visit::update_visit(
             &pool,
             payload.id,
@@ -234,6 +263,14 @@ pub async fn handle_update(
         .await
         {
             Ok(Some(visit)) => {
+                webhook_delivery::emit(&client, &pool, user_id, "visit.updated", &visit);
+                if payload.status.as_deref() == Some(VisitStatus::Cancelled.as_str()) {
+                    weather::record_in_background(&pool, user_id, visit.id);
+                }
+                dispatch_board::publish_customer_day(&client, &pool, user_id, visit.customer_id, visit.scheduled_date);
+                if let Some(date) = previous_date.filter(|date| *date != visit.scheduled_date) {
+                    dispatch_board::publish_customer_day(&client, &pool, user_id, visit.customer_id, date);
+                }
                 let response = SuccessResponse::new(request.id, visit);
                 let _ = client
                     .publish(reply, serde_json::to_vec(&response)?.into())
@@ -300,7 +337,21 @@ pub async fn handle_complete(
             }
         };
 
-        let payload = request.payload;
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let payload = match hooks::run_before(&client, BEFORE_VISIT_COMPLETE, user_id, request.payload).await {
+            HookOutcome::Proceed(payload) => payload,
+            HookOutcome::Vetoed { plugin, reason } => {
+                info!("visit.complete vetoed by hook plugin {}: {}", plugin, reason);
+                let error = ErrorResponse::new(request.id, "HOOK_VETOED", reason);
+                let _ = client
+                    .publish(reply, serde_json::to_vec(&error)?.into())
+                    .await;
+                continue;
+            }
+        };
 
         match queries::visit::complete_visit(
             &pool,
@@ -317,11 +368,19 @@ pub async fn handle_complete(
         .await
         {
             Ok(Some(visit)) => {
+                webhook_delivery::emit(&client, &pool, user_id, "visit.updated", &visit);
+                hooks::notify_after(&client, AFTER_VISIT_COMPLETE, user_id, &visit);
+                customer_risk::refresh_in_background(&pool, user_id, visit.customer_id, RISK_REASON_VISIT_COMPLETED);
+                weather::record_in_background(&pool, user_id, visit.id);
+                visit_duration::record_in_background(&pool, user_id, visit.id);
+                dispatch_board::publish_customer_day(&client, &pool, user_id, visit.customer_id, visit.scheduled_date);
+                let entry = communication_log::visit_completed_entry(&visit);
                 let response = SuccessResponse::new(request.id, visit);
                 let _ = client
                     .publish(reply, serde_json::to_vec(&response)?.into())
                     .await;
                 info!("Completed visit {} with result: {}", payload.id, payload.result);
+                communication_log::record(&pool, user_id, &entry).await;
             }
             Ok(None) => {
                 let error = ErrorResponse::new(request.id, "NOT_FOUND", "Visit not found");
@@ -388,12 +447,30 @@ pub async fn handle_delete(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        if retention::reject_if_retained(&client, &pool, &reply, request.id, user_id, RETENTION_ENTITY_VISIT, request.payload.id)
+            .await?
+        {
+            continue;
+        }
+
+        let deleted_visit = queries::visit::get_visit(&pool, request.payload.id, user_id).await.ok().flatten();
+
         match queries::visit::delete_visit(&pool, request.payload.id, user_id).await {
             Ok(deleted) => {
                 #[derive(serde::Serialize)]
                 struct DeleteResponse {
                     deleted: bool,
                 }
+                if deleted {
+                    webhook_delivery::emit(&client, &pool, user_id, "visit.deleted", &serde_json::json!({ "id": request.payload.id }));
+                    if let Some(visit) = &deleted_visit {
+                        dispatch_board::publish_customer_day(&client, &pool, user_id, visit.customer_id, visit.scheduled_date);
+                    }
+                }
                 let response = SuccessResponse::new(request.id, DeleteResponse { deleted });
                 let _ = client
                     .publish(reply, serde_json::to_vec(&response)?.into())
@@ -480,6 +557,8 @@ pub async fn handle_get(
                     }
                 };
 
+                let customer = demo_mode::apply(request.demo_mode, customer);
+
                 let work_items_count = work_items.len();
                 let response = SuccessResponse::new(
                     request.id,
@@ -548,6 +627,10 @@ pub async fn handle_update_field_notes(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let payload = request.payload;
 
         if payload.field_notes.len() > 10_000 {
@@ -0,0 +1,234 @@
+//! Visit progress and duration handlers for NATS messages
+//!
+//! The crew app reports "arrived" and "completed" per visit; each event
+//! stamps the visit and recomputes its travel and on-site time. Office staff
+//! can override either value, and the on-site times of completed visits are
+//! served back as learned service durations per visit type.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_nats::{Client, Subscriber};
+use chrono::{Duration, Utc};
+use futures::StreamExt;
+use sqlx::PgPool;
+use tracing::{debug, error, info, warn};
+
+use super::account;
+use super::{parse_authenticated, reply_error, reply_success};
+use crate::db::queries;
+use crate::services::visit_duration::{self, LEARNING_WINDOW_DAYS};
+use crate::services::{dispatch_board, webhook_delivery};
+use crate::subjects;
+use crate::types::visit_duration::{
+    LearnedDurationsRequest, LearnedDurationsResponse, SetVisitDurationRequest, VisitDurationRequest,
+    VisitProgressRequest,
+};
+
+/// Start all visit progress and duration NATS handlers
+pub async fn start_handlers(client: Client, pool: PgPool, jwt_secret: Arc<String>) -> Result<()> {
+    info!("Starting visit duration handlers...");
+
+    let [progress_sub, get_sub, set_sub, learned_sub] = subjects::subscribe_all(
+        &client,
+        [
+            subjects::visit::PROGRESS,
+            subjects::visit::DURATION_GET,
+            subjects::visit::DURATION_SET,
+            subjects::visit::DURATION_LEARNED,
+        ],
+    )
+    .await?;
+
+    tokio::spawn(handle_progress(client.clone(), progress_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_get(client.clone(), get_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_set(client.clone(), set_sub, pool.clone(), jwt_secret.clone()));
+    tokio::spawn(handle_learned(client, learned_sub, pool, jwt_secret));
+
+    info!("Visit duration handlers started");
+    Ok(())
+}
+
+/// Handle visit.progress messages - stamps arrival or departure
+pub async fn handle_progress(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received visit.progress message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<VisitProgressRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+
+        let user_id = auth_info.data_user_id();
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+        if let Err(msg) = request.payload.validate() {
+            reply_error(&client, &reply, request.id, "INVALID_REQUEST", msg).await?;
+            continue;
+        }
+
+        let payload = request.payload;
+        let at = payload.at.unwrap_or_else(Utc::now);
+        match queries::visit_duration::record_progress(&pool, payload.visit_id, user_id, &payload.event, at).await {
+            Ok(Some(visit)) => {
+                visit_duration::record_in_background(&pool, user_id, visit.id);
+                webhook_delivery::emit(&client, &pool, user_id, "visit.updated", &visit);
+                dispatch_board::publish_customer_day(&client, &pool, user_id, visit.customer_id, visit.scheduled_date);
+                debug!("Visit {} {} at {}", visit.id, payload.event, at);
+                reply_success(&client, &reply, request.id, visit).await?;
+            }
+            Ok(None) => reply_error(&client, &reply, request.id, "NOT_FOUND", "Visit not found or cancelled").await?,
+            Err(e) => {
+                error!("Failed to record visit progress: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle visit.duration.get messages
+pub async fn handle_get(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received visit.duration.get message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<VisitDurationRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+
+        let user_id = auth_info.data_user_id();
+        match queries::visit_duration::get_duration(&pool, request.payload.visit_id, user_id).await {
+            Ok(Some(duration)) => reply_success(&client, &reply, request.id, duration).await?,
+            Ok(None) => reply_error(&client, &reply, request.id, "NOT_FOUND", "No durations recorded").await?,
+            Err(e) => {
+                error!("Failed to load visit durations: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle visit.duration.set messages - manual override or reset
+pub async fn handle_set(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received visit.duration.set message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<SetVisitDurationRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+
+        let user_id = auth_info.data_user_id();
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+        if let Err(msg) = request.payload.validate() {
+            reply_error(&client, &reply, request.id, "INVALID_REQUEST", msg).await?;
+            continue;
+        }
+
+        let payload = request.payload;
+        let result = if payload.reset {
+            async {
+                queries::visit_duration::clear_overrides(&pool, payload.visit_id, user_id).await?;
+                visit_duration::record(&pool, user_id, payload.visit_id).await?;
+                queries::visit_duration::get_duration(&pool, payload.visit_id, user_id).await
+            }
+            .await
+        } else {
+            queries::visit_duration::set_override(
+                &pool,
+                payload.visit_id,
+                user_id,
+                payload.travel_minutes,
+                payload.on_site_minutes,
+            )
+            .await
+        };
+
+        match result {
+            Ok(Some(duration)) => {
+                info!("Durations of visit {} set by user {}", payload.visit_id, auth_info.user_id);
+                reply_success(&client, &reply, request.id, duration).await?
+            }
+            Ok(None) => reply_error(&client, &reply, request.id, "NOT_FOUND", "Visit not found").await?,
+            Err(e) => {
+                error!("Failed to set visit durations: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle visit.duration.learned messages - service durations per visit type
+pub async fn handle_learned(
+    client: Client,
+    mut subscriber: Subscriber,
+    pool: PgPool,
+    jwt_secret: Arc<String>,
+) -> Result<()> {
+    while let Some(msg) = subscriber.next().await {
+        debug!("Received visit.duration.learned message");
+        let Some(reply) = msg.reply.clone() else {
+            warn!("Message without reply subject");
+            continue;
+        };
+        let Some((request, auth_info)) =
+            parse_authenticated::<LearnedDurationsRequest>(&client, &reply, &msg.payload, &jwt_secret).await?
+        else {
+            continue;
+        };
+
+        let since = Utc::now().date_naive() - Duration::days(LEARNING_WINDOW_DAYS);
+        let visit_type = request.payload.visit_type.as_deref();
+        match queries::visit_duration::on_site_samples(&pool, auth_info.data_user_id(), since, visit_type).await {
+            Ok(samples) => {
+                let response = LearnedDurationsResponse {
+                    window_days: LEARNING_WINDOW_DAYS,
+                    items: visit_duration::learn_all(&samples),
+                };
+                reply_success(&client, &reply, request.id, response).await?;
+            }
+            Err(e) => {
+                error!("Failed to load on-site samples: {}", e);
+                reply_error(&client, &reply, request.id, "DATABASE_ERROR", e.to_string()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
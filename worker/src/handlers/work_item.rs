@@ -9,10 +9,13 @@ use tracing::{debug, error, warn};
 use uuid::Uuid;
 
 use crate::auth;
+use super::account;
+use super::inventory;
 use crate::db::queries;
 use crate::types::{
     ErrorResponse, Request, SuccessResponse,
 };
+use crate::types::currency::{resolve_billing_currency, DEFAULT_CURRENCY};
 use crate::types::work_item::{
     CreateWorkItemRequest, CompleteWorkItemRequest,
     ListWorkItemsRequest, ListWorkItemsResponse,
@@ -37,7 +40,7 @@ pub async fn handle_create(
             }
         };
 
-        let request: Request<CreateWorkItemRequest> = match serde_json::from_slice(&msg.payload) {
+        let mut request: Request<CreateWorkItemRequest> = match serde_json::from_slice(&msg.payload) {
             Ok(req) => req,
             Err(e) => {
                 error!("Failed to parse request: {}", e);
@@ -48,8 +51,8 @@ pub async fn handle_create(
         };
 
         // Check auth
-        let user_id = match auth::extract_auth(&request, &jwt_secret) {
-            Ok(info) => info.data_user_id(),
+        let (user_id, actor_id) = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => (info.data_user_id(), info.user_id),
             Err(_) => {
                 let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
                 let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
@@ -57,8 +60,52 @@ pub async fn handle_create(
             }
         };
 
-        match queries::work_item::create_work_item(&pool, user_id, &request.payload).await {
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
+        let payload = &request.payload;
+        match inventory::check_materials(&pool, user_id, &payload.materials).await {
+            Ok(Ok(())) => {}
+            Ok(Err(msg)) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to check work item materials: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        if payload.amount_minor.is_some() || payload.currency.is_some() {
+            match billing_currency(&pool, user_id, payload.visit_id, None, payload.currency.as_deref()).await {
+                Ok(Ok(currency)) => request.payload.currency = Some(currency),
+                Ok(Err(msg)) => {
+                    let error = ErrorResponse::new(request.id, "CURRENCY_MISMATCH", msg);
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to resolve work item currency: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            }
+        }
+
+        let created = async {
+            let item = queries::work_item::create_work_item(&pool, user_id, &request.payload).await?;
+            queries::inventory::add_materials(&pool, item.id, &request.payload.materials).await?;
+            anyhow::Ok(item)
+        }
+        .await;
+        match created {
             Ok(item) => {
+                inventory::consume_for_work_item(&pool, user_id, actor_id, &item).await;
                 let response = SuccessResponse::new(request.id, item);
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                 debug!("Created work item: {}", response.payload.id);
@@ -223,8 +270,8 @@ pub async fn handle_complete(
             }
         };
 
-        let user_id = match auth::extract_auth(&request, &jwt_secret) {
-            Ok(info) => info.data_user_id(),
+        let (user_id, actor_id) = match auth::extract_auth(&request, &jwt_secret) {
+            Ok(info) => (info.data_user_id(), info.user_id),
             Err(_) => {
                 let error = ErrorResponse::new(request.id, "UNAUTHORIZED", "Authentication required");
                 let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
@@ -232,7 +279,53 @@ pub async fn handle_complete(
             }
         };
 
+        if account::reject_if_read_only(&client, &pool, &reply, request.id, user_id).await? {
+            continue;
+        }
+
         let payload = &request.payload;
+        match inventory::check_materials(&pool, user_id, &payload.materials).await {
+            Ok(Ok(())) => {}
+            Ok(Err(msg)) => {
+                let error = ErrorResponse::new(request.id, "INVALID_REQUEST", msg);
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to check work item materials: {}", e);
+                let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                continue;
+            }
+        }
+
+        let mut currency = None;
+        if payload.amount_minor.is_some() || payload.currency.is_some() {
+            let resolved = match queries::work_item::get_work_item(&pool, user_id, payload.id).await {
+                Ok(Some(item)) => {
+                    let requested = payload.currency.as_deref().or(item.currency.as_deref());
+                    billing_currency(&pool, user_id, item.visit_id, Some(item.id), requested).await
+                }
+                // Ownership is checked again by the update, which reports NOT_FOUND
+                Ok(None) => Ok(Ok(DEFAULT_CURRENCY.to_string())),
+                Err(e) => Err(e),
+            };
+            match resolved {
+                Ok(Ok(resolved)) => currency = Some(resolved),
+                Ok(Err(msg)) => {
+                    let error = ErrorResponse::new(request.id, "CURRENCY_MISMATCH", msg);
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to resolve work item currency: {}", e);
+                    let error = ErrorResponse::new(request.id, "DATABASE_ERROR", e.to_string());
+                    let _ = client.publish(reply, serde_json::to_vec(&error)?.into()).await;
+                    continue;
+                }
+            }
+        }
+
         match queries::work_item::complete_work_item(
             &pool,
             user_id,
@@ -243,8 +336,16 @@ pub async fn handle_complete(
             payload.findings.as_deref(),
             payload.requires_follow_up.unwrap_or(false),
             payload.follow_up_reason.as_deref(),
+            payload.amount_minor,
+            payload.vat_rate,
+            payload.payment_method.as_deref(),
+            currency.as_deref(),
         ).await {
             Ok(Some(item)) => {
+                if let Err(e) = queries::inventory::add_materials(&pool, item.id, &payload.materials).await {
+                    warn!("Failed to record materials of work item {}: {}", item.id, e);
+                }
+                inventory::consume_for_work_item(&pool, user_id, actor_id, &item).await;
                 let response = SuccessResponse::new(request.id, item);
                 let _ = client.publish(reply, serde_json::to_vec(&response)?.into()).await;
                 debug!("Completed work item: {}", payload.id);
@@ -264,6 +365,23 @@ pub async fn handle_complete(
     Ok(())
 }
 
+/// Currency for a billed work item of a visit (inner Err when it would mix currencies)
+async fn billing_currency(
+    pool: &PgPool,
+    user_id: Uuid,
+    visit_id: Uuid,
+    item_id: Option<Uuid>,
+    requested: Option<&str>,
+) -> Result<std::result::Result<String, String>> {
+    let visit_currency = queries::work_item::visit_billing_currency(pool, visit_id, item_id).await?;
+    let account_currency = queries::settings::get_user_settings(pool, user_id)
+        .await?
+        .map(|s| s.currency)
+        .unwrap_or_else(|| DEFAULT_CURRENCY.to_string());
+
+    Ok(resolve_billing_currency(requested, visit_currency.as_deref(), &account_currency))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,6 +1,7 @@
 //! Sazinka Worker — Backend service for CRM and route planning
 //!
-//! Connects to NATS and handles messages from the frontend.
+//! Connects to NATS (or runs standalone with an embedded broker and HTTP
+//! gateway) and handles messages from the frontend.
 
 mod admin;
 mod auth;
@@ -10,6 +11,8 @@ mod defaults;
 mod db;
 mod handlers;
 mod services;
+mod subjects;
+mod transport;
 mod types;
 
 use anyhow::Result;
@@ -25,7 +28,7 @@ async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
 
     // Migrate only needs DATABASE_URL — skip full config validation
-    if matches!(cli.command, Some(cli::Command::Migrate)) {
+    if let Some(cli::Command::Migrate { dry_run, reconcile, rollback, force }) = cli.command {
         tracing_subscriber::fmt()
             .with_env_filter(
                 std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
@@ -34,8 +37,15 @@ async fn main() -> Result<()> {
         let database_url = std::env::var("DATABASE_URL")
             .map_err(|_| anyhow::anyhow!("DATABASE_URL must be set"))?;
         let pool = db::create_pool(&database_url).await?;
-        db::run_migrations(&pool).await?;
-        info!("Migrations complete, exiting.");
+        match (rollback, dry_run) {
+            (Some(target), true) => print!("{}", db::dry_run_rollback(&pool, target).await?),
+            (Some(target), false) => db::rollback_migrations(&pool, target, force).await?,
+            (None, true) => print!("{}", db::dry_run_migrations(&pool).await?),
+            (None, false) => {
+                db::run_migrations(&pool, reconcile || db::reconcile_from_env()).await?;
+                info!("Migrations complete, exiting.");
+            }
+        }
         return Ok(());
     }
 
@@ -43,11 +53,29 @@ async fn main() -> Result<()> {
     let pool = db::create_pool(&config.database_url).await?;
 
     match cli.command {
-        Some(cli::Command::Migrate) => unreachable!("handled above"),
+        Some(cli::Command::Migrate { .. }) => unreachable!("handled above"),
         Some(cli::Command::CreateAdmin { email }) => {
-            db::run_migrations(&pool).await?;
+            db::run_migrations(&pool, db::reconcile_from_env()).await?;
             admin::create_admin_interactive(&pool, &email).await
         }
+        Some(cli::Command::Backup { user, output }) => {
+            services::backup::backup_command(&pool, config.backup.as_ref(), user, output).await
+        }
+        Some(cli::Command::ListBackups) => services::backup::list_command(config.backup.as_ref()).await,
+        Some(cli::Command::Restore { object, file }) => {
+            // The backup's rows need the current schema
+            db::run_migrations(&pool, db::reconcile_from_env()).await?;
+            services::backup::restore_command(&pool, config.backup.as_ref(), object, file).await
+        }
+        Some(cli::Command::ExportAccount { user, output, key }) => {
+            services::account_transfer::export_command(&pool, user, output, key).await
+        }
+        Some(cli::Command::ImportAccount { file, key, into, on_conflict }) => {
+            // The transferred rows need the current schema
+            db::run_migrations(&pool, db::reconcile_from_env()).await?;
+            let options = types::account_transfer::TransferImportOptions { into_user_id: into, on_conflict };
+            services::account_transfer::import_command(&pool, file, key, options).await
+        }
         Some(cli::Command::Serve) | None => run_server(config, pool).await,
     }
 }
@@ -72,28 +100,21 @@ async fn run_server(config: config::Config, pool: sqlx::PgPool) -> Result<()> {
         .with(tracing_subscriber::fmt::layer().with_writer(non_blocking).with_ansi(false))
         .init();
 
+    services::crash_report::install_panic_hook(&logs_dir);
+
     info!("Starting Sazinka Worker...");
     info!("Configuration loaded");
 
     info!("Connected to PostgreSQL");
 
-    db::run_migrations(&pool).await?;
+    db::run_migrations(&pool, db::reconcile_from_env()).await?;
     info!("Database migrations complete");
 
     db::ensure_countries_synced(&pool).await?;
 
     admin::ensure_admin_from_env(&pool).await;
 
-    let nats_client = match (std::env::var("NATS_USER"), std::env::var("NATS_PASSWORD")) {
-        (Ok(user), Ok(password)) if !user.is_empty() => {
-            async_nats::ConnectOptions::new()
-                .user_and_password(user, password)
-                .connect(&config.nats_url)
-                .await?
-        }
-        _ => async_nats::connect(&config.nats_url).await?,
-    };
-    info!("Connected to NATS at {}", config.nats_url);
+    let nats_client = transport::connect(&config).await?;
 
     let handler_result = handlers::start_handlers(nats_client, pool, &config).await;
 
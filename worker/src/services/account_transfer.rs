@@ -0,0 +1,499 @@
+//! Account transfer between Sazinka instances
+//!
+//! When a territory moves to another franchisee, its account is exported on
+//! one instance and imported on another. The transfer archive is an account
+//! backup (see [`backup`]) with an extra `transfer.json`, encrypted with a
+//! key both instances share (`TRANSFER_KEY`).
+//!
+//! On import every row gets a new id and every reference to a transferred
+//! row is rewritten, so the data can never collide with rows of the target
+//! instance. Users whose email already exists are resolved by
+//! [`ConflictPolicy`]; any other conflicting row keeps the target's data.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Write};
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use chrono::Utc;
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+
+use crate::db::queries;
+use crate::services::backup;
+use crate::types::account_transfer::{
+    ConflictPolicy, TransferImportOptions, TransferInfo, TransferSummary, TRANSFER_FORMAT_VERSION,
+};
+use crate::types::backup::{BackupManifest, BackupScope};
+
+const TRANSFER_FILE: &str = "transfer.json";
+const KEY_ENV: &str = "TRANSFER_KEY";
+
+/// Decrypted transfer: metadata, backup manifest and table rows
+pub type TransferArchive = (TransferInfo, BackupManifest, Vec<(String, Value)>);
+
+/// Transfer key from `--key` or `TRANSFER_KEY` (base64, 32 bytes)
+pub fn transfer_key(arg: Option<&str>) -> Result<[u8; 32]> {
+    let encoded = match arg {
+        Some(key) => key.to_string(),
+        None => std::env::var(KEY_ENV)
+            .map_err(|_| anyhow!("--key or {} is required — generate one with: openssl rand -base64 32", KEY_ENV))?,
+    };
+    parse_transfer_key(&encoded)
+}
+
+pub fn parse_transfer_key(encoded: &str) -> Result<[u8; 32]> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .context("Transfer key must be base64 — generate one with: openssl rand -base64 32")?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| anyhow!("Transfer key must be 32 bytes (current: {} bytes)", bytes.len()))
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+/// Encrypted transfer archive of the account owned by `user_id`
+pub async fn export_account(pool: &PgPool, user_id: Uuid, key: &[u8; 32]) -> Result<(TransferInfo, BackupManifest, Vec<u8>)> {
+    let (email, owner_id) = queries::account_transfer::get_user_email_and_owner(pool, user_id)
+        .await?
+        .ok_or_else(|| anyhow!("User {} not found", user_id))?;
+    if let Some(owner_id) = owner_id {
+        bail!("User {} is a worker of account {} — transfer the owner's account", user_id, owner_id);
+    }
+
+    let (manifest, archive) = backup::create_archive(pool, BackupScope::Account { user_id }).await?;
+    let info = TransferInfo {
+        format: TRANSFER_FORMAT_VERSION,
+        exported_at: Utc::now(),
+        owner_user_id: user_id,
+        owner_email: email,
+    };
+    let archive = add_transfer_info(archive, &info)?;
+
+    Ok((info, manifest, backup::encrypt(key, &archive)?))
+}
+
+fn add_transfer_info(archive: Vec<u8>, info: &TransferInfo) -> Result<Vec<u8>> {
+    let mut zip_writer = zip::ZipWriter::new_append(Cursor::new(archive))?;
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip_writer.start_file(TRANSFER_FILE, options)?;
+    zip_writer.write_all(&serde_json::to_vec_pretty(info)?)?;
+
+    Ok(zip_writer.finish()?.into_inner())
+}
+
+/// Decrypt a transfer archive and read its metadata and table rows
+pub fn read_transfer(key: &[u8; 32], data: &[u8]) -> Result<TransferArchive> {
+    let archive = backup::decrypt(key, data)?;
+
+    let info: TransferInfo = {
+        let mut zip = zip::ZipArchive::new(Cursor::new(archive.as_slice())).context("Transfer is not a valid archive")?;
+        let file = zip
+            .by_name(TRANSFER_FILE)
+            .context("Archive is a backup, not an account transfer — use restore")?;
+        serde_json::from_reader(file)?
+    };
+    if info.format != TRANSFER_FORMAT_VERSION {
+        bail!("Unsupported transfer format {} (expected {})", info.format, TRANSFER_FORMAT_VERSION);
+    }
+
+    let (manifest, tables) = backup::read_archive(&archive)?;
+    if manifest.scope != (BackupScope::Account { user_id: info.owner_user_id }) {
+        bail!("Transfer metadata does not match the archived account");
+    }
+
+    Ok((info, manifest, tables))
+}
+
+// ============================================================================
+// Import
+// ============================================================================
+
+/// How the users of an archive map onto this instance
+#[derive(Debug, Default, PartialEq)]
+pub struct UserResolution {
+    pub owner_user_id: Uuid,
+    /// Archived user id → existing user id
+    pub fixed: HashMap<Uuid, Uuid>,
+    /// Users rows that are not inserted
+    pub dropped: Vec<Uuid>,
+    /// Emails of archived users that resolved to existing users
+    pub merged: Vec<String>,
+}
+
+/// Decide which archived users are created and which map onto existing
+/// ones. `users` are the archived (id, email) pairs, `existing` the users of
+/// this instance by lowercase email: (id, owner_id).
+pub fn resolve_users(
+    info: &TransferInfo,
+    users: &[(Uuid, String)],
+    existing: &HashMap<String, (Uuid, Option<Uuid>)>,
+    options: &TransferImportOptions,
+) -> Result<UserResolution> {
+    let mut resolution = UserResolution::default();
+    let mut conflicts = Vec::new();
+
+    resolution.owner_user_id = match options.into_user_id {
+        Some(into) => {
+            resolution.dropped.push(info.owner_user_id);
+            into
+        }
+        None => match existing.get(&info.owner_email.to_lowercase()) {
+            None => Uuid::new_v4(),
+            Some(_) if options.on_conflict == ConflictPolicy::Fail => {
+                conflicts.push(info.owner_email.clone());
+                Uuid::nil()
+            }
+            Some((_, Some(_))) => bail!(
+                "{} is a worker of another account here — import with --into the account to merge with",
+                info.owner_email
+            ),
+            Some((existing_id, None)) => {
+                resolution.dropped.push(info.owner_user_id);
+                resolution.merged.push(info.owner_email.clone());
+                *existing_id
+            }
+        },
+    };
+    resolution.fixed.insert(info.owner_user_id, resolution.owner_user_id);
+
+    for (id, email) in users.iter().filter(|(id, _)| *id != info.owner_user_id) {
+        if !existing.contains_key(&email.to_lowercase()) {
+            continue;
+        }
+        match options.on_conflict {
+            ConflictPolicy::Fail => conflicts.push(email.clone()),
+            ConflictPolicy::Skip => {
+                // Never attach data to someone else's user: the worker's
+                // rows go to the owner of the imported account
+                resolution.fixed.insert(*id, resolution.owner_user_id);
+                resolution.dropped.push(*id);
+                resolution.merged.push(email.clone());
+            }
+        }
+    }
+
+    if !conflicts.is_empty() {
+        bail!(
+            "Users already exist on this instance: {} — import with --on-conflict skip or --into",
+            conflicts.join(", ")
+        );
+    }
+    Ok(resolution)
+}
+
+/// Give every row with an `id` a new one (or the one in `fixed`) and rewrite
+/// every value referencing a transferred row. References are matched by
+/// value, so columns without a declared foreign key and ids inside JSON
+/// columns are rewritten too.
+pub fn remap_ids(tables: &mut [(String, Value)], fixed: &HashMap<Uuid, Uuid>) -> HashMap<Uuid, Uuid> {
+    let mut map = fixed.clone();
+    for (_, rows) in tables.iter() {
+        for row in rows.as_array().into_iter().flatten() {
+            if let Some(id) = row.get("id").and_then(Value::as_str).and_then(|s| Uuid::parse_str(s).ok()) {
+                map.entry(id).or_insert_with(Uuid::new_v4);
+            }
+        }
+    }
+    for (_, rows) in tables.iter_mut() {
+        rewrite_ids(rows, &map);
+    }
+    map
+}
+
+fn rewrite_ids(value: &mut Value, map: &HashMap<Uuid, Uuid>) {
+    match value {
+        Value::String(s) => {
+            if let Some(new_id) = Uuid::parse_str(s).ok().and_then(|id| map.get(&id)) {
+                *s = new_id.to_string();
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| rewrite_ids(item, map)),
+        Value::Object(fields) => fields.values_mut().for_each(|field| rewrite_ids(field, map)),
+        _ => {}
+    }
+}
+
+fn archived_users(tables: &[(String, Value)]) -> Vec<(Uuid, String)> {
+    tables
+        .iter()
+        .find(|(name, _)| name == "users")
+        .and_then(|(_, rows)| rows.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|row| {
+            let id = Uuid::parse_str(row.get("id")?.as_str()?).ok()?;
+            Some((id, row.get("email")?.as_str()?.to_string()))
+        })
+        .collect()
+}
+
+/// Import a transfer archive under new ids
+pub async fn import_account(
+    pool: &PgPool,
+    info: &TransferInfo,
+    manifest: &BackupManifest,
+    mut tables: Vec<(String, Value)>,
+    options: &TransferImportOptions,
+) -> Result<TransferSummary> {
+    if let Some(into) = options.into_user_id {
+        match queries::account_transfer::get_user_email_and_owner(pool, into).await? {
+            None => bail!("User {} not found", into),
+            Some((_, Some(_))) => bail!("User {} is not an account owner", into),
+            Some(_) => {}
+        }
+    }
+
+    let users = archived_users(&tables);
+    let emails: Vec<String> = users.iter().map(|(_, email)| email.clone()).collect();
+    let existing: HashMap<String, (Uuid, Option<Uuid>)> = queries::account_transfer::find_users_by_email(pool, &emails)
+        .await?
+        .into_iter()
+        .map(|(email, id, owner_id)| (email.to_lowercase(), (id, owner_id)))
+        .collect();
+    let resolution = resolve_users(info, &users, &existing, options)?;
+
+    for (name, rows) in tables.iter_mut() {
+        if name == "users" {
+            if let Some(rows) = rows.as_array_mut() {
+                rows.retain(|row| {
+                    let id = row.get("id").and_then(Value::as_str).and_then(|s| Uuid::parse_str(s).ok());
+                    !id.is_some_and(|id| resolution.dropped.contains(&id))
+                });
+            }
+        }
+    }
+    remap_ids(&mut tables, &resolution.fixed);
+
+    let restored = backup::restore_archive(pool, manifest, tables).await?;
+    Ok(TransferSummary {
+        owner_user_id: resolution.owner_user_id,
+        merged_users: resolution.merged,
+        tables: restored.tables,
+        rows_inserted: restored.rows_inserted,
+        rows_skipped: restored.rows_skipped,
+    })
+}
+
+// ============================================================================
+// CLI
+// ============================================================================
+
+/// `sazinka-worker export-account --user ID --output FILE [--key KEY]`
+pub async fn export_command(pool: &PgPool, user_id: Uuid, output: std::path::PathBuf, key: Option<String>) -> Result<()> {
+    let key = transfer_key(key.as_deref())?;
+    let (info, manifest, archive) = export_account(pool, user_id, &key).await?;
+    std::fs::write(&output, archive).with_context(|| format!("Failed to write {}", output.display()))?;
+
+    let rows: usize = manifest.tables.iter().map(|t| t.rows).sum();
+    println!(
+        "Account {} exported to {} ({} tables, {} rows)",
+        info.owner_email,
+        output.display(),
+        manifest.tables.len(),
+        rows
+    );
+    Ok(())
+}
+
+/// `sazinka-worker import-account --file FILE [--into ID] [--on-conflict fail|skip] [--key KEY]`
+pub async fn import_command(
+    pool: &PgPool,
+    file: std::path::PathBuf,
+    key: Option<String>,
+    options: TransferImportOptions,
+) -> Result<()> {
+    let key = transfer_key(key.as_deref())?;
+    let data = std::fs::read(&file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let (info, manifest, tables) = read_transfer(&key, &data)?;
+    println!(
+        "Importing account {} exported {} ({} tables)...",
+        info.owner_email,
+        info.exported_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        manifest.tables.len()
+    );
+
+    let summary = import_account(pool, &info, &manifest, tables, &options).await?;
+    for email in &summary.merged_users {
+        println!("  {} already exists here, its data now belongs to the account", email);
+    }
+    println!(
+        "Import complete: account owner {}, {} rows inserted, {} kept from existing data, {} tables",
+        summary.owner_user_id, summary.rows_inserted, summary.rows_skipped, summary.tables
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn info(owner: Uuid) -> TransferInfo {
+        TransferInfo {
+            format: TRANSFER_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            owner_user_id: owner,
+            owner_email: "Owner@Example.com".to_string(),
+        }
+    }
+
+    fn options(into_user_id: Option<Uuid>, on_conflict: ConflictPolicy) -> TransferImportOptions {
+        TransferImportOptions { into_user_id, on_conflict }
+    }
+
+    #[test]
+    fn test_parse_transfer_key() {
+        let encoded = base64::engine::general_purpose::STANDARD.encode([7u8; 32]);
+        assert_eq!(parse_transfer_key(&encoded).unwrap(), [7u8; 32]);
+        assert!(parse_transfer_key("c2hvcnQ=").is_err());
+        assert!(parse_transfer_key("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_remap_ids_rewrites_references_and_json() {
+        let user = Uuid::new_v4();
+        let customer = Uuid::new_v4();
+        let shared = Uuid::new_v4();
+        let mut tables = vec![
+            ("users".to_string(), json!([{ "id": user.to_string(), "owner_id": null }])),
+            (
+                "customers".to_string(),
+                json!([{
+                    "id": customer.to_string(),
+                    "user_id": user.to_string(),
+                    "country_id": shared.to_string(),
+                    "settings": { "contacts": [customer.to_string()] }
+                }]),
+            ),
+        ];
+
+        let map = remap_ids(&mut tables, &HashMap::new());
+
+        let new_user = map[&user].to_string();
+        let new_customer = map[&customer].to_string();
+        assert_ne!(new_user, user.to_string());
+        assert_eq!(tables[0].1[0]["id"], new_user);
+        assert_eq!(tables[1].1[0]["id"], new_customer);
+        assert_eq!(tables[1].1[0]["user_id"], new_user);
+        assert_eq!(tables[1].1[0]["settings"]["contacts"][0], new_customer);
+        // Rows outside the archive keep their ids
+        assert_eq!(tables[1].1[0]["country_id"], shared.to_string());
+    }
+
+    #[test]
+    fn test_remap_ids_uses_fixed_mapping() {
+        let user = Uuid::new_v4();
+        let target = Uuid::new_v4();
+        let mut tables = vec![("routes".to_string(), json!([{ "id": Uuid::new_v4().to_string(), "user_id": user.to_string() }]))];
+
+        remap_ids(&mut tables, &HashMap::from([(user, target)]));
+        assert_eq!(tables[0].1[0]["user_id"], target.to_string());
+    }
+
+    #[test]
+    fn test_resolve_users_new_account() {
+        let owner = Uuid::new_v4();
+        let worker = Uuid::new_v4();
+        let users = vec![(owner, "owner@example.com".to_string()), (worker, "worker@example.com".to_string())];
+
+        let resolution = resolve_users(&info(owner), &users, &HashMap::new(), &options(None, ConflictPolicy::Fail)).unwrap();
+        assert_ne!(resolution.owner_user_id, owner);
+        assert_eq!(resolution.fixed[&owner], resolution.owner_user_id);
+        assert!(resolution.dropped.is_empty());
+        assert!(resolution.merged.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_users_fail_lists_conflicts() {
+        let owner = Uuid::new_v4();
+        let worker = Uuid::new_v4();
+        let users = vec![(owner, "owner@example.com".to_string()), (worker, "worker@example.com".to_string())];
+        let existing = HashMap::from([
+            ("owner@example.com".to_string(), (Uuid::new_v4(), None)),
+            ("worker@example.com".to_string(), (Uuid::new_v4(), Some(Uuid::new_v4()))),
+        ]);
+
+        let err = resolve_users(&info(owner), &users, &existing, &options(None, ConflictPolicy::Fail)).unwrap_err();
+        assert!(err.to_string().contains("Owner@Example.com"));
+        assert!(err.to_string().contains("worker@example.com"));
+    }
+
+    #[test]
+    fn test_resolve_users_skip_merges_into_existing_owner() {
+        let owner = Uuid::new_v4();
+        let worker = Uuid::new_v4();
+        let existing_owner = Uuid::new_v4();
+        let users = vec![(owner, "owner@example.com".to_string()), (worker, "worker@example.com".to_string())];
+        let existing = HashMap::from([
+            ("owner@example.com".to_string(), (existing_owner, None)),
+            ("worker@example.com".to_string(), (Uuid::new_v4(), Some(Uuid::new_v4()))),
+        ]);
+
+        let resolution = resolve_users(&info(owner), &users, &existing, &options(None, ConflictPolicy::Skip)).unwrap();
+        assert_eq!(resolution.owner_user_id, existing_owner);
+        assert_eq!(resolution.fixed[&worker], existing_owner, "worker data stays in the account");
+        assert_eq!(resolution.dropped, vec![owner, worker]);
+        assert_eq!(resolution.merged.len(), 2);
+    }
+
+    #[test]
+    fn test_resolve_users_skip_refuses_owner_that_is_a_worker_here() {
+        let owner = Uuid::new_v4();
+        let users = vec![(owner, "owner@example.com".to_string())];
+        let existing = HashMap::from([("owner@example.com".to_string(), (Uuid::new_v4(), Some(Uuid::new_v4())))]);
+
+        assert!(resolve_users(&info(owner), &users, &existing, &options(None, ConflictPolicy::Skip)).is_err());
+    }
+
+    #[test]
+    fn test_resolve_users_into_existing_account() {
+        let owner = Uuid::new_v4();
+        let target = Uuid::new_v4();
+        let users = vec![(owner, "owner@example.com".to_string())];
+        // The owner's own email does not conflict when merging into another account
+        let existing = HashMap::from([("owner@example.com".to_string(), (Uuid::new_v4(), None))]);
+
+        let resolution = resolve_users(&info(owner), &users, &existing, &options(Some(target), ConflictPolicy::Fail)).unwrap();
+        assert_eq!(resolution.owner_user_id, target);
+        assert_eq!(resolution.dropped, vec![owner]);
+    }
+
+    #[test]
+    fn test_transfer_info_roundtrip() {
+        let owner = Uuid::new_v4();
+        let archive = {
+            let mut zip_writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+            zip_writer.start_file("manifest.json", SimpleFileOptions::default()).unwrap();
+            zip_writer
+                .write_all(
+                    &serde_json::to_vec(&BackupManifest {
+                        format: crate::types::backup::BACKUP_FORMAT_VERSION,
+                        created_at: Utc::now(),
+                        scope: BackupScope::Account { user_id: owner },
+                        schema_version: 84,
+                        tables: vec![],
+                    })
+                    .unwrap(),
+                )
+                .unwrap();
+            zip_writer.finish().unwrap().into_inner()
+        };
+        let key = [3u8; 32];
+        let encrypted = backup::encrypt(&key, &add_transfer_info(archive.clone(), &info(owner)).unwrap()).unwrap();
+
+        let (read_info, manifest, tables) = read_transfer(&key, &encrypted).unwrap();
+        assert_eq!(read_info.owner_user_id, owner);
+        assert_eq!(manifest.schema_version, 84);
+        assert!(tables.is_empty());
+
+        // A plain account backup is not a transfer
+        let plain = backup::encrypt(&key, &archive).unwrap();
+        assert!(read_transfer(&key, &plain).is_err());
+    }
+}
@@ -0,0 +1,675 @@
+//! Accounting exports for Czech bookkeeping software (Pohoda, Money S3)
+//!
+//! Each completed visit becomes one issued-invoice draft whose lines are the
+//! visit's work items, priced with the item's billed net amount and VAT rate
+//! (unbilled items go out at zero for the accountant to fill in). A draft
+//! never mixes currencies: items billed in another currency get a draft of
+//! their own, and drafts outside the account currency are marked as
+//! foreign-currency documents.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::services::vat_summary::format_minor;
+use crate::types::currency::DEFAULT_CURRENCY;
+use crate::types::work_item::{VisitWorkItem, WorkType};
+use crate::types::{Customer, Device, VisitWithCustomer};
+
+/// Company identification and number series for accounting exports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountingExportOptions {
+    /// Overrides the IČO from business settings
+    pub company_ico: Option<String>,
+    /// Overrides the DIČ from business settings
+    pub company_dic: Option<String>,
+    /// Overrides the business name from settings
+    pub company_name: Option<String>,
+    /// Document number prefix, e.g. "FV2026"
+    #[serde(default = "default_number_prefix")]
+    pub number_prefix: String,
+    /// First sequence number of this export
+    #[serde(default = "default_first_number")]
+    pub first_number: u32,
+    /// Zero-padded width of the sequence part
+    #[serde(default = "default_number_width")]
+    pub number_width: usize,
+    /// Invoice work at branches to their parent company
+    #[serde(default)]
+    pub invoice_branches_to_parent: bool,
+}
+
+fn default_number_prefix() -> String { "SZ".to_string() }
+fn default_first_number() -> u32 { 1 }
+fn default_number_width() -> usize { 5 }
+
+impl Default for AccountingExportOptions {
+    fn default() -> Self {
+        Self {
+            company_ico: None,
+            company_dic: None,
+            company_name: None,
+            number_prefix: default_number_prefix(),
+            first_number: default_first_number(),
+            number_width: default_number_width(),
+            invoice_branches_to_parent: false,
+        }
+    }
+}
+
+impl AccountingExportOptions {
+    /// Format the n-th document number of the series (0-based offset)
+    pub fn document_number(&self, offset: usize) -> String {
+        format!(
+            "{}{:0width$}",
+            self.number_prefix,
+            self.first_number as usize + offset,
+            width = self.number_width
+        )
+    }
+}
+
+/// Resolved identity of the issuing company
+#[derive(Debug, Clone, Default)]
+pub struct AccountingCompany {
+    pub name: Option<String>,
+    pub ico: Option<String>,
+    pub dic: Option<String>,
+    /// Account currency: the books' home currency, also used for visits
+    /// without billed work items
+    pub currency: String,
+}
+
+/// Invoice recipient
+#[derive(Debug, Clone, Default)]
+pub struct InvoicePartner {
+    pub name: String,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+    pub ico: Option<String>,
+    pub dic: Option<String>,
+}
+
+/// One invoice line (derived from a work item)
+#[derive(Debug, Clone)]
+pub struct InvoiceLine {
+    pub text: String,
+    pub quantity: f64,
+    pub unit: &'static str,
+    /// Net amount of the whole line in minor units (None = not billed)
+    pub net_minor: Option<i64>,
+    /// VAT rate in percent
+    pub vat_rate: Option<i32>,
+}
+
+impl InvoiceLine {
+    /// Net price per unit as a decimal string ("0.00" for unbilled lines)
+    pub fn unit_price(&self) -> String {
+        let net = self.net_minor.unwrap_or(0);
+        if self.quantity > 0.0 {
+            format_minor((net as f64 / self.quantity).round() as i64)
+        } else {
+            format_minor(net)
+        }
+    }
+}
+
+/// Pohoda VAT rate name for a rate in percent
+fn pohoda_vat_rate(vat_rate: Option<i32>) -> &'static str {
+    match vat_rate.unwrap_or(0) {
+        0 => "none",
+        rate if rate >= 21 => "high",
+        _ => "low",
+    }
+}
+
+/// Issued invoice draft for one completed visit
+#[derive(Debug, Clone)]
+pub struct InvoiceDraft {
+    pub number: String,
+    pub visit_id: Uuid,
+    pub date: NaiveDate,
+    pub text: String,
+    /// ISO 4217 code shared by all lines
+    pub currency: String,
+    pub partner: InvoicePartner,
+    pub lines: Vec<InvoiceLine>,
+}
+
+fn work_type_label(work_type: &WorkType) -> &'static str {
+    match work_type {
+        WorkType::Revision => "Revize",
+        WorkType::Repair => "Oprava",
+        WorkType::Installation => "Instalace",
+        WorkType::Consultation => "Konzultace",
+        WorkType::FollowUp => "Následná návštěva",
+    }
+}
+
+/// Build invoice drafts for completed visits that have at least one work item.
+///
+/// Drafts are ordered by visit date and numbered from the configured series.
+/// Unbilled items follow the visit's billing currency, or `default_currency`
+/// when nothing on the visit is billed.
+pub fn build_invoice_drafts(
+    visits: &[VisitWithCustomer],
+    work_items: &[VisitWorkItem],
+    customers: &[Customer],
+    devices: &[Device],
+    options: &AccountingExportOptions,
+    default_currency: &str,
+) -> Vec<InvoiceDraft> {
+    let customer_lookup: HashMap<Uuid, &Customer> = customers.iter().map(|c| (c.id, c)).collect();
+    let device_lookup: HashMap<Uuid, &Device> = devices.iter().map(|d| (d.id, d)).collect();
+    let mut items_by_visit: HashMap<Uuid, Vec<&VisitWorkItem>> = HashMap::new();
+    for wi in work_items {
+        items_by_visit.entry(wi.visit_id).or_default().push(wi);
+    }
+    let default_currency = if default_currency.is_empty() { DEFAULT_CURRENCY } else { default_currency };
+
+    let mut completed: Vec<&VisitWithCustomer> = visits
+        .iter()
+        .filter(|v| v.status == "completed" && items_by_visit.contains_key(&v.id))
+        .collect();
+    completed.sort_by_key(|v| (v.scheduled_date, v.id));
+
+    // One document per visit and currency
+    let documents: Vec<(&VisitWithCustomer, &str, Vec<&VisitWorkItem>)> = completed
+        .into_iter()
+        .flat_map(|visit| {
+            let items = &items_by_visit[&visit.id];
+            let visit_currency = items
+                .iter()
+                .find_map(|wi| wi.amount_minor.and(wi.currency.as_deref()))
+                .unwrap_or(default_currency);
+            let mut by_currency: BTreeMap<&str, Vec<&VisitWorkItem>> = BTreeMap::new();
+            for wi in items {
+                let currency = match wi.amount_minor {
+                    Some(_) => wi.currency.as_deref().unwrap_or(visit_currency),
+                    None => visit_currency,
+                };
+                by_currency.entry(currency).or_default().push(wi);
+            }
+            by_currency.into_iter().map(move |(currency, items)| (visit, currency, items))
+        })
+        .collect();
+
+    documents
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (visit, currency, items))| {
+            let customer = customer_lookup.get(&visit.customer_id).copied();
+            let parent = customer
+                .filter(|_| options.invoice_branches_to_parent)
+                .and_then(|c| c.parent_customer_id)
+                .and_then(|id| customer_lookup.get(&id).copied());
+            let billed = parent.or(customer);
+            let partner = InvoicePartner {
+                name: billed
+                    .and_then(|c| c.name.clone())
+                    .or_else(|| visit.customer_name.clone())
+                    .unwrap_or_default(),
+                street: billed.and_then(|c| c.street.clone()),
+                city: billed.and_then(|c| c.city.clone()),
+                postal_code: billed.and_then(|c| c.postal_code.clone()),
+                ico: billed.and_then(|c| c.ico.clone()).filter(|s| !s.is_empty()),
+                dic: billed.and_then(|c| c.dic.clone()).filter(|s| !s.is_empty()),
+            };
+
+            let lines = items
+                .iter()
+                .map(|wi| {
+                    let device_name = wi
+                        .device_id
+                        .and_then(|id| device_lookup.get(&id))
+                        .and_then(|d| d.device_name.clone().or_else(|| Some(d.device_type.clone())));
+                    let label = work_type_label(&wi.work_type);
+                    let text = match device_name {
+                        Some(name) => format!("{} – {}", label, name),
+                        None => label.to_string(),
+                    };
+                    // Bill by the hour when a duration was recorded, otherwise per piece
+                    let (quantity, unit) = match wi.duration_minutes {
+                        Some(minutes) if minutes > 0 => ((minutes as f64 / 60.0 * 100.0).round() / 100.0, "hod"),
+                        _ => (1.0, "ks"),
+                    };
+                    InvoiceLine { text, quantity, unit, net_minor: wi.amount_minor, vat_rate: wi.vat_rate }
+                })
+                .collect();
+
+            InvoiceDraft {
+                number: options.document_number(idx),
+                visit_id: visit.id,
+                date: visit.scheduled_date,
+                text: match parent.and(customer).and_then(|c| c.name.as_deref()) {
+                    Some(branch) => format!("Servisní návštěva {} – {}", visit.scheduled_date.format("%-d. %-m. %Y"), branch),
+                    None => format!("Servisní návštěva {}", visit.scheduled_date.format("%-d. %-m. %Y")),
+                },
+                currency: currency.to_string(),
+                partner,
+                lines,
+            }
+        })
+        .collect()
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn push_element(out: &mut String, indent: usize, tag: &str, value: Option<&str>) {
+    if let Some(v) = value.filter(|v| !v.is_empty()) {
+        out.push_str(&format!("{:indent$}<{tag}>{}</{tag}>\n", "", xml_escape(v), indent = indent, tag = tag));
+    }
+}
+
+/// Render drafts as a Pohoda XML data pack (`dat:dataPack`, invoice schema 2.0)
+pub fn render_pohoda_xml(drafts: &[InvoiceDraft], company: &AccountingCompany, pack_id: &str) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        concat!(
+            "<dat:dataPack version=\"2.0\" id=\"{}\" ico=\"{}\" application=\"Sazinka\" note=\"Sazinka export\"\n",
+            "  xmlns:dat=\"http://www.stormware.cz/schema/version_2/data.xsd\"\n",
+            "  xmlns:inv=\"http://www.stormware.cz/schema/version_2/invoice.xsd\"\n",
+            "  xmlns:typ=\"http://www.stormware.cz/schema/version_2/type.xsd\">\n"
+        ),
+        xml_escape(pack_id),
+        xml_escape(company.ico.as_deref().unwrap_or_default())
+    ));
+
+    for draft in drafts {
+        let foreign = draft.currency != company.currency;
+        out.push_str(&format!("  <dat:dataPackItem version=\"2.0\" id=\"{}\">\n", xml_escape(&draft.number)));
+        out.push_str("    <inv:invoice version=\"2.0\">\n");
+        out.push_str("      <inv:invoiceHeader>\n");
+        out.push_str("        <inv:invoiceType>issuedInvoice</inv:invoiceType>\n");
+        out.push_str("        <inv:number>\n");
+        push_element(&mut out, 10, "typ:numberRequested", Some(&draft.number));
+        out.push_str("        </inv:number>\n");
+        push_element(&mut out, 8, "inv:date", Some(&draft.date.to_string()));
+        push_element(&mut out, 8, "inv:dateTax", Some(&draft.date.to_string()));
+        push_element(&mut out, 8, "inv:text", Some(&draft.text));
+        out.push_str("        <inv:partnerIdentity>\n");
+        out.push_str("          <typ:address>\n");
+        push_element(&mut out, 12, "typ:company", Some(&draft.partner.name));
+        push_element(&mut out, 12, "typ:street", draft.partner.street.as_deref());
+        push_element(&mut out, 12, "typ:city", draft.partner.city.as_deref());
+        push_element(&mut out, 12, "typ:zip", draft.partner.postal_code.as_deref());
+        push_element(&mut out, 12, "typ:ico", draft.partner.ico.as_deref());
+        push_element(&mut out, 12, "typ:dic", draft.partner.dic.as_deref());
+        out.push_str("          </typ:address>\n");
+        out.push_str("        </inv:partnerIdentity>\n");
+        out.push_str("        <inv:myIdentity>\n");
+        out.push_str("          <typ:address>\n");
+        push_element(&mut out, 12, "typ:company", company.name.as_deref());
+        push_element(&mut out, 12, "typ:ico", company.ico.as_deref());
+        push_element(&mut out, 12, "typ:dic", company.dic.as_deref());
+        out.push_str("          </typ:address>\n");
+        out.push_str("        </inv:myIdentity>\n");
+        push_element(&mut out, 8, "inv:symVar", Some(&draft.number.chars().filter(|c| c.is_ascii_digit()).collect::<String>()));
+        push_element(&mut out, 8, "inv:intNote", Some(&format!("Sazinka visit {}", draft.visit_id)));
+        out.push_str("      </inv:invoiceHeader>\n");
+        out.push_str("      <inv:invoiceDetail>\n");
+        for line in &draft.lines {
+            out.push_str("        <inv:invoiceItem>\n");
+            push_element(&mut out, 10, "inv:text", Some(&line.text));
+            push_element(&mut out, 10, "inv:quantity", Some(&line.quantity.to_string()));
+            push_element(&mut out, 10, "inv:unit", Some(line.unit));
+            push_element(&mut out, 10, "inv:rateVAT", Some(pohoda_vat_rate(line.vat_rate)));
+            let price_block = if foreign { "inv:foreignCurrency" } else { "inv:homeCurrency" };
+            out.push_str(&format!("          <{}>\n", price_block));
+            push_element(&mut out, 12, "typ:unitPrice", Some(&line.unit_price()));
+            out.push_str(&format!("          </{}>\n", price_block));
+            out.push_str("        </inv:invoiceItem>\n");
+        }
+        out.push_str("      </inv:invoiceDetail>\n");
+        if foreign {
+            out.push_str("      <inv:invoiceSummary>\n");
+            out.push_str("        <inv:foreignCurrency>\n");
+            out.push_str("          <typ:currency>\n");
+            push_element(&mut out, 12, "typ:ids", Some(&draft.currency));
+            out.push_str("          </typ:currency>\n");
+            out.push_str("        </inv:foreignCurrency>\n");
+            out.push_str("      </inv:invoiceSummary>\n");
+        }
+        out.push_str("    </inv:invoice>\n");
+        out.push_str("  </dat:dataPackItem>\n");
+    }
+
+    out.push_str("</dat:dataPack>\n");
+    out
+}
+
+/// Render drafts as a Money S3 XML transfer file (`MoneyData` / `SeznamFaktVyd`)
+pub fn render_money_s3_xml(drafts: &[InvoiceDraft], company: &AccountingCompany) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<MoneyData ICAgendy=\"{}\" KodAgendy=\"\" HospRokOd=\"\" HospRokDo=\"\" description=\"Sazinka export\" ExpZkratka=\"_FV\" ExpDate=\"{}\" VyberZaznamu=\"0\" GUID=\"{{{}}}\">\n",
+        xml_escape(company.ico.as_deref().unwrap_or_default()),
+        chrono::Utc::now().date_naive(),
+        Uuid::new_v4().to_string().to_uppercase()
+    ));
+    out.push_str("  <SeznamFaktVyd>\n");
+
+    for draft in drafts {
+        out.push_str("    <FaktVyd>\n");
+        push_element(&mut out, 6, "Doklad", Some(&draft.number));
+        push_element(&mut out, 6, "Popis", Some(&draft.text));
+        push_element(&mut out, 6, "DatVyst", Some(&draft.date.to_string()));
+        push_element(&mut out, 6, "DatUcPr", Some(&draft.date.to_string()));
+        push_element(&mut out, 6, "DatPln", Some(&draft.date.to_string()));
+        push_element(&mut out, 6, "VarSymbol", Some(&draft.number.chars().filter(|c| c.is_ascii_digit()).collect::<String>()));
+        out.push_str("      <DodOdb>\n");
+        push_element(&mut out, 8, "ObchNazev", Some(&draft.partner.name));
+        out.push_str("        <ObchAdresa>\n");
+        push_element(&mut out, 10, "Ulice", draft.partner.street.as_deref());
+        push_element(&mut out, 10, "Misto", draft.partner.city.as_deref());
+        push_element(&mut out, 10, "PSC", draft.partner.postal_code.as_deref());
+        out.push_str("        </ObchAdresa>\n");
+        push_element(&mut out, 8, "ICO", draft.partner.ico.as_deref());
+        push_element(&mut out, 8, "DIC", draft.partner.dic.as_deref());
+        out.push_str("      </DodOdb>\n");
+        out.push_str("      <MojeFirma>\n");
+        push_element(&mut out, 8, "Nazev", company.name.as_deref());
+        push_element(&mut out, 8, "ICO", company.ico.as_deref());
+        push_element(&mut out, 8, "DIC", company.dic.as_deref());
+        out.push_str("      </MojeFirma>\n");
+        if draft.currency != company.currency {
+            out.push_str("      <Valuty>\n");
+            out.push_str("        <Mena>\n");
+            push_element(&mut out, 10, "Kod", Some(&draft.currency));
+            out.push_str("        </Mena>\n");
+            out.push_str("      </Valuty>\n");
+        }
+        push_element(&mut out, 6, "Pozn", Some(&format!("Sazinka visit {}", draft.visit_id)));
+        out.push_str("      <SeznamPolozek>\n");
+        for line in &draft.lines {
+            out.push_str("        <Polozka>\n");
+            push_element(&mut out, 10, "Popis", Some(&line.text));
+            push_element(&mut out, 10, "PocetMJ", Some(&line.quantity.to_string()));
+            push_element(&mut out, 10, "MJ", Some(line.unit));
+            push_element(&mut out, 10, "Cena", Some(&line.unit_price()));
+            // Prices are net of VAT
+            out.push_str("          <CenaTyp>0</CenaTyp>\n");
+            push_element(&mut out, 10, "SazbaDPH", Some(&line.vat_rate.unwrap_or(0).to_string()));
+            out.push_str("        </Polozka>\n");
+        }
+        out.push_str("      </SeznamPolozek>\n");
+        out.push_str("    </FaktVyd>\n");
+    }
+
+    out.push_str("  </SeznamFaktVyd>\n");
+    out.push_str("</MoneyData>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn draft() -> InvoiceDraft {
+        InvoiceDraft {
+            number: "FV202600001".to_string(),
+            visit_id: Uuid::nil(),
+            date: NaiveDate::from_ymd_opt(2026, 3, 5).unwrap(),
+            text: "Servisní návštěva 5. 3. 2026".to_string(),
+            currency: "CZK".to_string(),
+            partner: InvoicePartner {
+                name: "Novák & syn".to_string(),
+                street: Some("Masarykova 12".to_string()),
+                city: Some("Brno".to_string()),
+                postal_code: Some("60200".to_string()),
+                ico: Some("12345678".to_string()),
+                dic: None,
+            },
+            lines: vec![InvoiceLine {
+                text: "Revize – Kotel".to_string(),
+                quantity: 1.5,
+                unit: "hod",
+                net_minor: Some(150_000),
+                vat_rate: Some(21),
+            }],
+        }
+    }
+
+    fn work_item(visit_id: Uuid, duration: Option<i32>) -> VisitWorkItem {
+        VisitWorkItem {
+            id: Uuid::new_v4(),
+            visit_id,
+            device_id: None,
+            revision_id: None,
+            crew_id: None,
+            work_type: WorkType::Revision,
+            duration_minutes: duration,
+            result: None,
+            result_notes: None,
+            findings: None,
+            requires_follow_up: false,
+            follow_up_reason: None,
+            created_at: Utc::now(),
+            amount_minor: None,
+            vat_rate: None,
+            payment_method: None,
+            currency: None,
+        }
+    }
+
+    fn visit(status: &str, day: u32) -> VisitWithCustomer {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "userId": Uuid::nil(),
+            "customerId": Uuid::nil(),
+            "crewId": null,
+            "deviceId": null,
+            "scheduledDate": format!("2026-03-{:02}", day),
+            "scheduledTimeStart": null,
+            "scheduledTimeEnd": null,
+            "status": status,
+            "visitType": "revision",
+            "actualArrival": null,
+            "actualDeparture": null,
+            "result": null,
+            "fieldNotes": null,
+            "requiresFollowUp": null,
+            "followUpReason": null,
+            "createdAt": Utc::now(),
+            "updatedAt": Utc::now(),
+            "customerName": "Jan Novák",
+            "customerStreet": null,
+            "customerCity": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_document_number_is_padded() {
+        let options = AccountingExportOptions { number_prefix: "FV2026".into(), first_number: 7, ..Default::default() };
+        assert_eq!(options.document_number(0), "FV202600007");
+        assert_eq!(options.document_number(3), "FV202600010");
+    }
+
+    #[test]
+    fn test_build_invoice_drafts_only_completed_with_items() {
+        let done_late = visit("completed", 20);
+        let done_early = visit("completed", 2);
+        let planned = visit("planned", 10);
+        let done_empty = visit("completed", 15);
+        let items = vec![
+            work_item(done_late.id, Some(90)),
+            work_item(done_early.id, None),
+            work_item(planned.id, None),
+        ];
+
+        let drafts = build_invoice_drafts(
+            &[done_late.clone(), planned, done_early.clone(), done_empty],
+            &items,
+            &[],
+            &[],
+            &AccountingExportOptions::default(),
+            "CZK",
+        );
+
+        assert_eq!(drafts.len(), 2);
+        assert_eq!(drafts[0].visit_id, done_early.id);
+        assert_eq!(drafts[0].number, "SZ00001");
+        assert_eq!(drafts[0].lines[0].unit, "ks");
+        assert_eq!(drafts[1].number, "SZ00002");
+        assert_eq!(drafts[1].lines[0].quantity, 1.5);
+        assert_eq!(drafts[1].lines[0].unit, "hod");
+        assert_eq!(drafts[1].partner.name, "Jan Novák");
+    }
+
+    fn customer(id: Uuid, name: &str, ico: &str, parent: Option<Uuid>) -> Customer {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "userId": Uuid::nil(),
+            "type": "company",
+            "name": name,
+            "ico": ico,
+            "geocodeStatus": "success",
+            "createdAt": Utc::now(),
+            "updatedAt": Utc::now(),
+            "isAbandoned": false,
+            "parentCustomerId": parent,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_branch_invoiced_to_parent_when_enabled() {
+        let hq = customer(Uuid::new_v4(), "Facility a.s.", "11111111", None);
+        let branch = customer(Uuid::nil(), "Pobočka Brno", "", Some(hq.id));
+        let done = visit("completed", 4);
+        let items = vec![work_item(done.id, None)];
+        let customers = [hq, branch];
+
+        let separate = build_invoice_drafts(
+            std::slice::from_ref(&done),
+            &items,
+            &customers,
+            &[],
+            &AccountingExportOptions::default(),
+            "CZK",
+        );
+        assert_eq!(separate[0].partner.name, "Pobočka Brno");
+
+        let options = AccountingExportOptions { invoice_branches_to_parent: true, ..Default::default() };
+        let grouped = build_invoice_drafts(std::slice::from_ref(&done), &items, &customers, &[], &options, "CZK");
+        assert_eq!(grouped[0].partner.name, "Facility a.s.");
+        assert_eq!(grouped[0].partner.ico.as_deref(), Some("11111111"));
+        assert!(grouped[0].text.ends_with("– Pobočka Brno"));
+    }
+
+    #[test]
+    fn test_build_invoice_drafts_split_by_currency() {
+        let done = visit("completed", 6);
+        let mut czk = work_item(done.id, None);
+        czk.amount_minor = Some(100_000);
+        czk.currency = Some("CZK".to_string());
+        let mut eur = work_item(done.id, None);
+        eur.amount_minor = Some(5_000);
+        eur.currency = Some("EUR".to_string());
+        let unbilled = work_item(done.id, None);
+
+        let drafts = build_invoice_drafts(
+            std::slice::from_ref(&done),
+            &[czk, unbilled, eur],
+            &[],
+            &[],
+            &AccountingExportOptions::default(),
+            "EUR",
+        );
+        assert_eq!(drafts.len(), 2);
+        assert_eq!(drafts[0].currency, "CZK");
+        assert_eq!(drafts[0].lines.len(), 2);
+        assert_eq!(drafts[0].lines[0].net_minor, Some(100_000));
+        assert_eq!(drafts[1].currency, "EUR");
+        assert_eq!(drafts[1].number, "SZ00002");
+
+        let options = AccountingExportOptions::default();
+        let unbilled_only =
+            build_invoice_drafts(std::slice::from_ref(&done), &[work_item(done.id, None)], &[], &[], &options, "EUR");
+        assert_eq!(unbilled_only[0].currency, "EUR");
+    }
+
+    #[test]
+    fn test_render_foreign_currency_documents() {
+        let mut euro = draft();
+        euro.currency = "EUR".to_string();
+        let company = AccountingCompany { currency: "CZK".into(), ..Default::default() };
+
+        let pohoda = render_pohoda_xml(std::slice::from_ref(&euro), &company, "export-1");
+        assert!(pohoda.contains("<typ:ids>EUR</typ:ids>"));
+        assert!(!pohoda.contains("<inv:homeCurrency>"));
+        assert!(roxmltree::Document::parse(&pohoda).is_ok());
+
+        let money = render_money_s3_xml(std::slice::from_ref(&euro), &company);
+        assert!(money.contains("<Kod>EUR</Kod>"));
+        assert!(roxmltree::Document::parse(&money).is_ok());
+
+        assert!(!render_pohoda_xml(&[draft()], &company, "export-1").contains("<typ:ids>"));
+
+        // Books kept in euros: the EUR draft is a home-currency document
+        let euro_books = AccountingCompany { currency: "EUR".into(), ..Default::default() };
+        assert!(render_pohoda_xml(&[euro], &euro_books, "export-1").contains("<inv:homeCurrency>"));
+    }
+
+    #[test]
+    fn test_render_line_prices_and_vat_rates() {
+        let mut reduced = draft();
+        reduced.lines[0].vat_rate = Some(12);
+        let mut unbilled = draft();
+        unbilled.lines[0].net_minor = None;
+        unbilled.lines[0].vat_rate = None;
+        let company = AccountingCompany { currency: "CZK".into(), ..Default::default() };
+
+        let pohoda = render_pohoda_xml(&[draft()], &company, "export-1");
+        assert!(pohoda.contains("<typ:unitPrice>1000.00</typ:unitPrice>"));
+        assert!(pohoda.contains("<inv:rateVAT>high</inv:rateVAT>"));
+        assert!(render_pohoda_xml(&[reduced.clone()], &company, "export-1").contains("<inv:rateVAT>low</inv:rateVAT>"));
+        let zero = render_pohoda_xml(&[unbilled.clone()], &company, "export-1");
+        assert!(zero.contains("<typ:unitPrice>0.00</typ:unitPrice>"));
+        assert!(zero.contains("<inv:rateVAT>none</inv:rateVAT>"));
+
+        let money = render_money_s3_xml(&[draft()], &company);
+        assert!(money.contains("<Cena>1000.00</Cena>"));
+        assert!(money.contains("<SazbaDPH>21</SazbaDPH>"));
+        assert!(render_money_s3_xml(&[reduced], &company).contains("<SazbaDPH>12</SazbaDPH>"));
+        assert!(render_money_s3_xml(&[unbilled], &company).contains("<SazbaDPH>0</SazbaDPH>"));
+    }
+
+    #[test]
+    fn test_render_pohoda_xml() {
+        let company = AccountingCompany { ico: Some("87654321".into()), ..Default::default() };
+        let xml = render_pohoda_xml(&[draft()], &company, "export-1");
+        assert!(xml.contains("ico=\"87654321\""));
+        assert!(xml.contains("<inv:invoiceType>issuedInvoice</inv:invoiceType>"));
+        assert!(xml.contains("<typ:numberRequested>FV202600001</typ:numberRequested>"));
+        assert!(xml.contains("<typ:company>Novák &amp; syn</typ:company>"));
+        assert!(!xml.contains("<typ:dic>"));
+        assert!(roxmltree::Document::parse(&xml).is_ok());
+    }
+
+    #[test]
+    fn test_render_money_s3_xml() {
+        let company = AccountingCompany { name: Some("Kominictví Brno".into()), ..Default::default() };
+        let xml = render_money_s3_xml(&[draft()], &company);
+        assert!(xml.contains("<Nazev>Kominictví Brno</Nazev>"));
+        assert!(xml.contains("<Doklad>FV202600001</Doklad>"));
+        assert!(xml.contains("<PocetMJ>1.5</PocetMJ>"));
+        assert!(xml.contains("<ICO>12345678</ICO>"));
+        assert!(roxmltree::Document::parse(&xml).is_ok());
+    }
+}
@@ -0,0 +1,123 @@
+//! Acquisition source report
+//!
+//! Puts new customers, completed visits and billed revenue side by side per
+//! acquisition source, so owners see which marketing channel brings work.
+//! Revenue stays split by currency.
+
+use std::collections::BTreeMap;
+
+use chrono::{Months, NaiveDate};
+
+use crate::types::report::{
+    AcquisitionSourceCounts, AcquisitionSourceReportResponse, AcquisitionSourceReportRow, AcquisitionSourceRevenue,
+    CurrencyAmount,
+};
+
+/// Default report period ending on `to`: the preceding year
+pub fn default_from(to: NaiveDate) -> NaiveDate {
+    to.checked_sub_months(Months::new(12)).unwrap_or(to)
+}
+
+/// Merge per-source counts and revenue into report rows, busiest sources
+/// first and the unknown source last
+pub fn build_report(
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    counts: &[AcquisitionSourceCounts],
+    revenue: &[AcquisitionSourceRevenue],
+) -> AcquisitionSourceReportResponse {
+    let mut rows: BTreeMap<&str, AcquisitionSourceReportRow> = BTreeMap::new();
+    let row_for = |source: &str| AcquisitionSourceReportRow {
+        source: Some(source.to_string()).filter(|s| !s.is_empty()),
+        new_customers: 0,
+        customers_with_jobs: 0,
+        completed_visits: 0,
+        revenue: Vec::new(),
+    };
+
+    for count in counts {
+        let row = rows.entry(&count.source).or_insert_with(|| row_for(&count.source));
+        row.new_customers += count.new_customers;
+        row.customers_with_jobs += count.customers_with_jobs;
+        row.completed_visits += count.completed_visits;
+    }
+    for amount in revenue {
+        let row = rows.entry(&amount.source).or_insert_with(|| row_for(&amount.source));
+        row.revenue.push(CurrencyAmount {
+            currency: amount.currency.clone(),
+            amount_minor: amount.amount_minor,
+        });
+    }
+
+    let mut sources: Vec<AcquisitionSourceReportRow> = rows.into_values().collect();
+    for row in &mut sources {
+        row.revenue.sort_by(|a, b| a.currency.cmp(&b.currency));
+    }
+    sources.sort_by(|a, b| {
+        a.source
+            .is_none()
+            .cmp(&b.source.is_none())
+            .then(b.completed_visits.cmp(&a.completed_visits))
+            .then(b.new_customers.cmp(&a.new_customers))
+            .then(a.source.cmp(&b.source))
+    });
+
+    AcquisitionSourceReportResponse { from_date, to_date, sources }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(source: &str, new_customers: i64, completed_visits: i64) -> AcquisitionSourceCounts {
+        AcquisitionSourceCounts {
+            source: source.to_string(),
+            new_customers,
+            customers_with_jobs: completed_visits,
+            completed_visits,
+        }
+    }
+
+    fn revenue(source: &str, currency: &str, amount_minor: i64) -> AcquisitionSourceRevenue {
+        AcquisitionSourceRevenue {
+            source: source.to_string(),
+            currency: currency.to_string(),
+            amount_minor,
+        }
+    }
+
+    #[test]
+    fn test_default_from_is_one_year_back() {
+        let to = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        assert_eq!(default_from(to), NaiveDate::from_ymd_opt(2025, 10, 16).unwrap());
+    }
+
+    #[test]
+    fn test_build_report_merges_counts_and_revenue() {
+        let from = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 12, 31).unwrap();
+        let report = build_report(
+            from,
+            to,
+            &[counts("", 10, 40), counts("phone", 3, 5), counts("referral", 4, 12)],
+            &[
+                revenue("referral", "EUR", 5_000),
+                revenue("referral", "CZK", 120_000),
+                revenue("web_form", "CZK", 30_000),
+            ],
+        );
+
+        let sources: Vec<Option<&str>> = report.sources.iter().map(|r| r.source.as_deref()).collect();
+        assert_eq!(sources, vec![Some("referral"), Some("phone"), Some("web_form"), None]);
+
+        let referral = &report.sources[0];
+        assert_eq!(referral.new_customers, 4);
+        assert_eq!(referral.completed_visits, 12);
+        let currencies: Vec<&str> = referral.revenue.iter().map(|r| r.currency.as_str()).collect();
+        assert_eq!(currencies, vec!["CZK", "EUR"]);
+
+        // Revenue without counts still gets a row
+        assert_eq!(report.sources[2].completed_visits, 0);
+        assert_eq!(report.sources[2].revenue[0].amount_minor, 30_000);
+    }
+}
@@ -0,0 +1,28 @@
+//! Bounded reads from uploaded ZIP containers
+//!
+//! XLSX workbooks and KMZ map exports are ZIP archives, and a small upload
+//! can inflate to far more than its own size. Entries are read through a
+//! byte cap instead of an unbounded `read_to_end`.
+
+use std::io::Read;
+
+/// Read at most `max_bytes` from `reader`; `None` when there is more
+pub fn read_limited(reader: impl Read, max_bytes: u64) -> std::io::Result<Option<Vec<u8>>> {
+    let mut content = Vec::new();
+    reader.take(max_bytes + 1).read_to_end(&mut content)?;
+    if content.len() as u64 > max_bytes {
+        return Ok(None);
+    }
+    Ok(Some(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_limited_stops_at_the_cap() {
+        assert_eq!(read_limited(&b"<x/>"[..], 4).unwrap(), Some(b"<x/>".to_vec()));
+        assert_eq!(read_limited(&b"<x></x>"[..], 4).unwrap(), None);
+    }
+}
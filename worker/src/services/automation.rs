@@ -0,0 +1,298 @@
+//! Automation rules
+//!
+//! Every entity change event (the ones webhooks are sent for) runs the
+//! account's enabled rules for that trigger; an hourly scan raises
+//! `revision.overdue` once per overdue revision and rule. A rule whose
+//! conditions hold runs its actions in order and stops at the first
+//! failure; each execution is logged.
+//!
+//! The event context holds the changed entity under its name and, when the
+//! entity belongs to a customer, the customer:
+//! `{"trigger": "revision.updated", "revision": {..}, "customer": {..}}`.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_nats::Client;
+use chrono::{Days, Utc};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::email_processor;
+use crate::services::notification_dispatch;
+use crate::services::template_renderer::{render_template, render_template_html, TemplateVars};
+use crate::transport::JobQueue;
+use crate::types::automation::{
+    conditions_match, lookup, trigger_entity, AutomationAction, AutomationRule, AUTOMATION_RUN_FAILED,
+    AUTOMATION_RUN_SKIPPED, AUTOMATION_RUN_SUCCEEDED, AUTOMATION_TRIGGER_REVISION_OVERDUE,
+};
+use crate::types::notification::{NOTIFICATION_CATEGORY_REMINDER, NOTIFICATION_KIND_AUTOMATION};
+use crate::types::{CreateTaskRequest, CustomEmailRequest, EmailJobRequest};
+
+const SCHEDULER_TICK: Duration = Duration::from_secs(3600);
+
+fn uuid_field(value: &Value, key: &str) -> Option<Uuid> {
+    value.get(key)?.as_str()?.parse().ok()
+}
+
+/// Context the conditions and placeholders of a rule see
+pub async fn build_context(pool: &PgPool, user_id: Uuid, trigger: &str, data: &Value) -> Result<Value> {
+    let entity = trigger_entity(trigger);
+    let mut context = json!({ "trigger": trigger });
+    context[entity] = data.clone();
+
+    if entity != "customer" {
+        if let Some(customer_id) = uuid_field(data, "customerId") {
+            if let Some(customer) = queries::customer::get_customer(pool, user_id, customer_id).await? {
+                context["customer"] = serde_json::to_value(customer)?;
+            }
+        }
+    }
+    Ok(context)
+}
+
+/// Entity and customer of an event context
+fn context_ids(trigger: &str, context: &Value) -> (Option<Uuid>, Option<Uuid>) {
+    let entity_id = context.get(trigger_entity(trigger)).and_then(|data| uuid_field(data, "id"));
+    let customer_id = context.get("customer").and_then(|customer| uuid_field(customer, "id"));
+    (entity_id, customer_id)
+}
+
+/// Placeholder values: every scalar of the context by its dotted path
+pub fn context_vars(context: &Value) -> BTreeMap<String, String> {
+    fn collect(out: &mut BTreeMap<String, String>, prefix: &str, value: &Value) {
+        match value {
+            Value::Object(obj) => {
+                for (key, v) in obj {
+                    let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                    collect(out, &path, v);
+                }
+            }
+            Value::String(s) => {
+                out.insert(prefix.to_string(), s.clone());
+            }
+            Value::Number(_) | Value::Bool(_) => {
+                out.insert(prefix.to_string(), value.to_string());
+            }
+            Value::Null | Value::Array(_) => {}
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    collect(&mut out, "", context);
+    out
+}
+
+fn template_vars(vars: &BTreeMap<String, String>) -> TemplateVars<'_> {
+    vars.iter().map(|(key, value)| (key.as_str(), value.clone())).collect()
+}
+
+/// Run one action of a rule
+async fn run_action(
+    queue: &JobQueue,
+    pool: &PgPool,
+    rule: &AutomationRule,
+    action: &AutomationAction,
+    context: &Value,
+    vars: &TemplateVars<'_>,
+) -> Result<()> {
+    let entity = trigger_entity(&rule.trigger);
+    let (entity_id, customer_id) = context_ids(&rule.trigger, context);
+
+    match action {
+        AutomationAction::SendEmail { subject, body } => {
+            let to = lookup(context, "customer.email")
+                .and_then(Value::as_str)
+                .filter(|email| !email.trim().is_empty())
+                .ok_or_else(|| anyhow!("customer has no e-mail"))?;
+            let request = EmailJobRequest::Custom(CustomEmailRequest {
+                to: to.to_string(),
+                subject: render_template(subject, vars),
+                body_html: render_template_html(body, vars),
+                body_text: Some(render_template(body, vars)),
+            });
+            email_processor::enqueue(queue, rule.user_id, request).await?;
+        }
+        AutomationAction::CreateTask { task_type_id, due_in_days, note } => {
+            let customer_id = customer_id.ok_or_else(|| anyhow!("event has no customer"))?;
+            if queries::task::get_task_type(pool, rule.user_id, *task_type_id).await?.is_none() {
+                return Err(anyhow!("task type {} not found", task_type_id));
+            }
+            let data = context.get(entity).unwrap_or(&Value::Null);
+            let request = CreateTaskRequest {
+                task_type_id: *task_type_id,
+                customer_id,
+                visit_id: if entity == "visit" { entity_id } else { None },
+                device_id: uuid_field(data, "deviceId"),
+                payload: Some(json!({
+                    "note": note.as_deref().map(|note| render_template(note, vars)),
+                    "automationRuleId": rule.id,
+                })),
+                due_date: due_in_days
+                    .and_then(|days| Utc::now().date_naive().checked_add_days(Days::new(days.max(0) as u64))),
+            };
+            queries::task::create_task(pool, rule.user_id, &request).await?;
+        }
+        AutomationAction::Notify { title, body } => {
+            let body = body.as_deref().map(|body| render_template(body, vars));
+            notification_dispatch::notify(
+                pool,
+                rule.user_id,
+                NOTIFICATION_CATEGORY_REMINDER,
+                NOTIFICATION_KIND_AUTOMATION,
+                &render_template(title, vars),
+                body.as_deref(),
+                entity_id.map(|id| (entity, id)),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Run the actions of a rule whose conditions hold and log the execution
+async fn execute(queue: &JobQueue, pool: &PgPool, rule: &AutomationRule, context: &Value) -> Result<()> {
+    let vars = context_vars(context);
+    let vars = template_vars(&vars);
+
+    let mut actions_run = 0;
+    let mut failure = None;
+    for action in rule.actions.iter() {
+        match run_action(queue, pool, rule, action, context, &vars).await {
+            Ok(()) => actions_run += 1,
+            Err(e) => {
+                failure = Some(format!("{}: {}", action.kind(), e));
+                break;
+            }
+        }
+    }
+
+    let status = if failure.is_some() { AUTOMATION_RUN_FAILED } else { AUTOMATION_RUN_SUCCEEDED };
+    if let Some(ref e) = failure {
+        warn!("Automation rule {} failed: {}", rule.id, e);
+    }
+    let (entity_id, customer_id) = context_ids(&rule.trigger, context);
+    queries::automation::insert_run(
+        pool,
+        rule,
+        &rule.trigger,
+        entity_id,
+        customer_id,
+        status,
+        actions_run,
+        failure.as_deref(),
+    )
+    .await
+}
+
+/// Run the rules of an account for an event, returning how many matched
+pub async fn run_event(queue: &JobQueue, pool: &PgPool, user_id: Uuid, trigger: &str, data: &Value) -> Result<usize> {
+    let rules = queries::automation::list_enabled_for_trigger(pool, user_id, trigger).await?;
+    if rules.is_empty() {
+        return Ok(0);
+    }
+
+    let context = build_context(pool, user_id, trigger, data).await?;
+    let mut matched = 0;
+    for rule in rules.iter().filter(|rule| conditions_match(&rule.conditions, &context)) {
+        execute(queue, pool, rule, &context).await?;
+        matched += 1;
+    }
+    Ok(matched)
+}
+
+/// Run the rules of an account for an entity change, in the background
+pub fn on_event(client: &Client, pool: &PgPool, user_id: Uuid, trigger: &'static str, data: &Value) {
+    let queue = JobQueue::new(client.clone());
+    let pool = pool.clone();
+    let data = data.clone();
+    tokio::spawn(async move {
+        if let Err(e) = run_event(&queue, &pool, user_id, trigger, &data).await {
+            warn!("Failed to run automation rules for {} of user {}: {}", trigger, user_id, e);
+        }
+    });
+}
+
+/// Evaluate overdue revisions not seen yet by each `revision.overdue` rule.
+/// Returns the number of executed rules.
+pub async fn run_overdue_scan(queue: &JobQueue, pool: &PgPool) -> Result<usize> {
+    let trigger = AUTOMATION_TRIGGER_REVISION_OVERDUE;
+    let mut executed = 0;
+
+    for rule in queries::automation::list_all_enabled_for_trigger(pool, trigger).await? {
+        for revision_id in queries::automation::list_unprocessed_overdue_revisions(pool, &rule).await? {
+            let Some(revision) = queries::revision::get_revision(pool, revision_id, rule.user_id).await? else {
+                continue;
+            };
+            let context = build_context(pool, rule.user_id, trigger, &serde_json::to_value(&revision)?).await?;
+            if conditions_match(&rule.conditions, &context) {
+                execute(queue, pool, &rule, &context).await?;
+                executed += 1;
+            } else {
+                let (_, customer_id) = context_ids(trigger, &context);
+                queries::automation::insert_run(
+                    pool,
+                    &rule,
+                    trigger,
+                    Some(revision_id),
+                    customer_id,
+                    AUTOMATION_RUN_SKIPPED,
+                    0,
+                    None,
+                )
+                .await?;
+            }
+        }
+    }
+
+    Ok(executed)
+}
+
+/// Background loop raising `revision.overdue`
+pub async fn run_scheduler(pool: PgPool, client: Client) {
+    info!("Automation overdue scan started");
+    let queue = JobQueue::new(client);
+    let mut ticker = tokio::time::interval(SCHEDULER_TICK);
+
+    loop {
+        ticker.tick().await;
+
+        match run_overdue_scan(&queue, &pool).await {
+            Ok(0) => {}
+            Ok(count) => info!("Ran {} automation rules on overdue revisions", count),
+            Err(e) => error!("Failed to run automation overdue scan: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_vars_and_ids() {
+        let customer_id = Uuid::new_v4();
+        let revision_id = Uuid::new_v4();
+        let context = json!({
+            "trigger": "revision.overdue",
+            "revision": { "id": revision_id, "dueDate": "2026-03-01", "durationMinutes": 45, "note": null },
+            "customer": { "id": customer_id, "name": "Jan Novák", "tags": ["vip"] },
+        });
+
+        let vars = context_vars(&context);
+        assert_eq!(vars["customer.name"], "Jan Novák");
+        assert_eq!(vars["revision.durationMinutes"], "45");
+        assert!(!vars.contains_key("revision.note"));
+        assert!(!vars.contains_key("customer.tags"));
+
+        let rendered = render_template("{{customer.name}}: due {{revision.dueDate}}", &template_vars(&vars));
+        assert_eq!(rendered, "Jan Novák: due 2026-03-01");
+
+        assert_eq!(context_ids("revision.overdue", &context), (Some(revision_id), Some(customer_id)));
+        assert_eq!(context_ids("route.updated", &context), (None, Some(customer_id)));
+    }
+}
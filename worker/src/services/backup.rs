@@ -0,0 +1,692 @@
+#![allow(dead_code)]
+//! Encrypted data backups
+//!
+//! A backup is a ZIP archive (`manifest.json` plus one `tables/<name>.json`
+//! per table) encrypted with AES-256-GCM:
+//!
+//! ```text
+//! "SZBK" 0x01 | 12-byte nonce | ciphertext + tag
+//! ```
+//!
+//! The scheduler uploads a full backup to S3-compatible storage every
+//! `BACKUP_INTERVAL_HOURS` and keeps the newest `BACKUP_RETENTION` of them.
+//! `sazinka-worker backup` / `restore` do the same by hand, also for a single
+//! account. Restores run in one transaction and only insert rows that are
+//! missing, so existing data is never overwritten.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read, Write};
+use std::time::Duration;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+use aws_sdk_s3::error::DisplayErrorContext;
+use aws_sdk_s3::primitives::ByteStream;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rand::RngCore;
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+
+use crate::config::{BackupConfig, BackupStorageConfig};
+use crate::db::queries;
+use crate::types::backup::{
+    BackupManifest, BackupScope, BackupTableEntry, StoredBackup, TableSchema, BACKUP_FORMAT_VERSION,
+};
+
+/// Header of encrypted archives (also authenticated as associated data)
+const MAGIC: &[u8; 5] = b"SZBK\x01";
+const NONCE_LEN: usize = 12;
+const OBJECT_PREFIX: &str = "sazinka-";
+const OBJECT_SUFFIX: &str = ".szbk";
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// How often the scheduler checks whether a backup is due
+const SCHEDULER_TICK: Duration = Duration::from_secs(600);
+
+// ============================================================================
+// Archive
+// ============================================================================
+
+/// Dump the tables of `scope` into an (unencrypted) archive
+pub async fn create_archive(pool: &PgPool, scope: BackupScope) -> Result<(BackupManifest, Vec<u8>)> {
+    let schemas = queries::backup::load_schema(pool).await?;
+    let schema_version = queries::backup::schema_version(pool).await?;
+
+    let mut zip_writer = zip::ZipWriter::new(Cursor::new(Vec::<u8>::new()));
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut tables = Vec::new();
+
+    for schema in &schemas {
+        let rows = match scope {
+            BackupScope::Full => queries::backup::dump_table(pool, &schema.name, None, None).await?,
+            BackupScope::Account { user_id } => match queries::backup::account_filter(&schemas, &schema.name) {
+                Some(filter) => queries::backup::dump_table(pool, &schema.name, Some(&filter), Some(user_id)).await?,
+                None => continue,
+            },
+        };
+        let count = rows.as_array().map_or(0, Vec::len);
+
+        zip_writer.start_file(format!("tables/{}.json", schema.name), options)?;
+        zip_writer.write_all(&serde_json::to_vec(&rows)?)?;
+        tables.push(BackupTableEntry { name: schema.name.clone(), rows: count });
+    }
+
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT_VERSION,
+        created_at: Utc::now(),
+        scope,
+        schema_version,
+        tables,
+    };
+    zip_writer.start_file("manifest.json", options)?;
+    zip_writer.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+
+    Ok((manifest, zip_writer.finish()?.into_inner()))
+}
+
+/// Read the manifest and table rows of an archive
+pub fn read_archive(bytes: &[u8]) -> Result<(BackupManifest, Vec<(String, Value)>)> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).context("Backup is not a valid archive")?;
+
+    let manifest: BackupManifest = serde_json::from_reader(
+        archive.by_name("manifest.json").context("Backup has no manifest")?,
+    )?;
+    if manifest.format != BACKUP_FORMAT_VERSION {
+        bail!("Unsupported backup format {} (expected {})", manifest.format, BACKUP_FORMAT_VERSION);
+    }
+
+    let mut tables = Vec::with_capacity(manifest.tables.len());
+    for entry in &manifest.tables {
+        let mut file = archive
+            .by_name(&format!("tables/{}.json", entry.name))
+            .with_context(|| format!("Backup is missing table {}", entry.name))?;
+        let mut json = Vec::new();
+        file.read_to_end(&mut json)?;
+        tables.push((entry.name.clone(), serde_json::from_slice(&json)?));
+    }
+
+    Ok((manifest, tables))
+}
+
+// ============================================================================
+// Encryption
+// ============================================================================
+
+pub fn encrypt(key: &[u8; 32], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = Aes256Gcm::new(key.into());
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: MAGIC })
+        .map_err(|_| anyhow!("Backup encryption failed"))?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+pub fn decrypt(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>> {
+    let body = data.strip_prefix(MAGIC.as_slice()).ok_or_else(|| anyhow!("Not a Sazinka backup file"))?;
+    if body.len() < NONCE_LEN {
+        bail!("Backup file is truncated");
+    }
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+
+    Aes256Gcm::new(key.into())
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: MAGIC })
+        .map_err(|_| anyhow!("Backup cannot be decrypted — wrong BACKUP_ENCRYPTION_KEY or damaged file"))
+}
+
+// ============================================================================
+// Restore
+// ============================================================================
+
+/// How the tables of a backup are inserted
+#[derive(Debug)]
+pub struct RestorePlan {
+    /// Parents before children
+    pub order: Vec<String>,
+    /// (table, column) inserted as NULL and filled once all rows exist
+    pub deferred: Vec<(String, String)>,
+}
+
+/// Plan the insertion of `tables`. Foreign key cycles (users ↔ depots,
+/// revisions ↔ work items) are broken by deferring nullable columns of
+/// tables with a primary key.
+pub fn restore_order(schemas: &[TableSchema], tables: &[String]) -> Result<RestorePlan> {
+    let included: HashSet<&str> = tables.iter().map(String::as_str).collect();
+    let mut pending: Vec<&TableSchema> = schemas.iter().filter(|t| included.contains(t.name.as_str())).collect();
+    let mut placed: HashSet<&str> = HashSet::new();
+    let mut deferred: Vec<(String, String)> = Vec::new();
+    let mut order = Vec::new();
+
+    // Foreign keys of a table whose parent still has to be inserted: (column, parent)
+    let blocking = |table: &TableSchema, placed: &HashSet<&str>, deferred: &[(String, String)]| -> Vec<(String, String)> {
+        table
+            .foreign_keys
+            .iter()
+            .filter(|fk| {
+                fk.parent_table != table.name
+                    && included.contains(fk.parent_table.as_str())
+                    && !placed.contains(fk.parent_table.as_str())
+                    && !deferred.iter().any(|(t, c)| *t == table.name && *c == fk.column)
+            })
+            .map(|fk| (fk.column.clone(), fk.parent_table.clone()))
+            .collect()
+    };
+
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|t| blocking(t, &placed, &deferred).is_empty());
+        if let Some(i) = ready {
+            let table = pending.remove(i);
+            placed.insert(table.name.as_str());
+            order.push(table.name.clone());
+            continue;
+        }
+
+        // Every pending table waits for another: defer a nullable column on a
+        // cycle (deferring any other column could break check constraints)
+        let waits_for = |from: &str, target: &str| -> bool {
+            let mut stack = vec![from.to_string()];
+            let mut seen = HashSet::new();
+            while let Some(name) = stack.pop() {
+                if name == target {
+                    return true;
+                }
+                if !seen.insert(name.clone()) {
+                    continue;
+                }
+                if let Some(table) = pending.iter().find(|t| t.name == name) {
+                    stack.extend(blocking(table, &placed, &deferred).into_iter().map(|(_, parent)| parent));
+                }
+            }
+            false
+        };
+        let candidate = pending.iter().filter(|t| !t.primary_key.is_empty()).find_map(|t| {
+            blocking(t, &placed, &deferred)
+                .into_iter()
+                .find(|(column, parent)| t.is_nullable(column) && waits_for(parent, &t.name))
+                .map(|(column, _)| (t.name.clone(), column))
+        });
+        match candidate {
+            Some(edge) => deferred.push(edge),
+            None => bail!(
+                "Cannot order tables for restore: {}",
+                pending.iter().map(|t| t.name.as_str()).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+
+    Ok(RestorePlan { order, deferred })
+}
+
+/// Counts of a finished restore
+#[derive(Debug, Default)]
+pub struct RestoreSummary {
+    pub tables: usize,
+    pub rows_inserted: u64,
+    pub rows_skipped: u64,
+}
+
+/// Insert the rows of an archive that are missing from the database
+pub async fn restore_archive(
+    pool: &PgPool,
+    manifest: &BackupManifest,
+    tables: Vec<(String, Value)>,
+) -> Result<RestoreSummary> {
+    let target_version = queries::backup::schema_version(pool).await?;
+    if manifest.schema_version > target_version {
+        bail!(
+            "Backup comes from a newer schema (migration {}, database has {}) — upgrade the worker first",
+            manifest.schema_version,
+            target_version
+        );
+    }
+
+    let schemas = queries::backup::load_schema(pool).await?;
+    let known: HashSet<&str> = schemas.iter().map(|t| t.name.as_str()).collect();
+    for (name, _) in &tables {
+        if !known.contains(name.as_str()) {
+            warn!("Table {} of the backup no longer exists, skipping it", name);
+        }
+    }
+
+    let rows: HashMap<String, Value> = tables.into_iter().collect();
+    let names: Vec<String> = rows.keys().cloned().collect();
+    let RestorePlan { order, deferred } = restore_order(&schemas, &names)?;
+    let schema_of = |name: &str| schemas.iter().find(|t| t.name == name).expect("ordered tables exist");
+
+    let mut summary = RestoreSummary::default();
+    let mut tx = pool.begin().await?;
+    for name in &order {
+        let table_rows = &rows[name];
+        let columns: Vec<String> = deferred.iter().filter(|(t, _)| t == name).map(|(_, c)| c.clone()).collect();
+        let inserted = queries::backup::insert_rows(&mut tx, schema_of(name), table_rows, &columns)
+            .await
+            .with_context(|| format!("Failed to restore table {}", name))?;
+
+        summary.tables += 1;
+        summary.rows_inserted += inserted;
+        summary.rows_skipped += table_rows.as_array().map_or(0, Vec::len) as u64 - inserted;
+    }
+    for (name, column) in &deferred {
+        queries::backup::fill_deferred_column(&mut tx, schema_of(name), &rows[name], column)
+            .await
+            .with_context(|| format!("Failed to restore {}.{}", name, column))?;
+    }
+    tx.commit().await?;
+
+    Ok(summary)
+}
+
+// ============================================================================
+// Off-site storage
+// ============================================================================
+
+/// Object name of a backup, e.g. `backups/sazinka-full-20260301T020000Z.szbk`
+pub fn object_key(prefix: &str, scope: &BackupScope, created_at: DateTime<Utc>) -> String {
+    format!(
+        "{}{}{}-{}{}",
+        prefix,
+        OBJECT_PREFIX,
+        scope.label(),
+        created_at.format(TIMESTAMP_FORMAT),
+        OBJECT_SUFFIX
+    )
+}
+
+/// Scope label and creation time of a backup object name
+pub fn parse_object_key(prefix: &str, key: &str) -> Option<(String, DateTime<Utc>)> {
+    let name = key.strip_prefix(prefix)?.strip_prefix(OBJECT_PREFIX)?.strip_suffix(OBJECT_SUFFIX)?;
+    let (label, timestamp) = name.rsplit_once('-')?;
+    let created_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?.and_utc();
+    Some((label.to_string(), created_at))
+}
+
+/// Keys of the backups beyond the newest `retention` ones
+pub fn expired_backups(backups: &[StoredBackup], retention: usize) -> Vec<String> {
+    let mut sorted: Vec<&StoredBackup> = backups.iter().collect();
+    sorted.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    sorted.into_iter().skip(retention).map(|b| b.key.clone()).collect()
+}
+
+/// S3-compatible bucket holding the archives
+pub struct BackupStore {
+    client: aws_sdk_s3::Client,
+    config: BackupStorageConfig,
+}
+
+impl BackupStore {
+    /// Credentials come from the standard AWS variables (AWS_ACCESS_KEY_ID, ...)
+    pub async fn new(config: BackupStorageConfig) -> Self {
+        let sdk_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_config::Region::new(config.region.clone()))
+            .load()
+            .await;
+        let mut builder = aws_sdk_s3::config::Builder::from(&sdk_config);
+        if let Some(endpoint) = &config.endpoint {
+            builder = builder.endpoint_url(endpoint).force_path_style(true);
+        }
+
+        Self { client: aws_sdk_s3::Client::from_conf(builder.build()), config }
+    }
+
+    pub async fn upload(&self, key: &str, bytes: Vec<u8>) -> Result<()> {
+        self.client
+            .put_object()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .body(ByteStream::from(bytes))
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to upload backup {}: {}", key, DisplayErrorContext(e)))?;
+        Ok(())
+    }
+
+    pub async fn download(&self, key: &str) -> Result<Vec<u8>> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to download backup {}: {}", key, DisplayErrorContext(e)))?;
+        let bytes = object.body.collect().await.context("Failed to read backup download")?;
+        Ok(bytes.into_bytes().to_vec())
+    }
+
+    /// Backups in the bucket, oldest first; `scope` narrows them to one scope
+    pub async fn list(&self, scope: Option<&BackupScope>) -> Result<Vec<StoredBackup>> {
+        let label = scope.map(BackupScope::label);
+        let mut backups = Vec::new();
+        let mut continuation: Option<String> = None;
+
+        loop {
+            let page = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.config.bucket)
+                .prefix(format!("{}{}", self.config.prefix, OBJECT_PREFIX))
+                .set_continuation_token(continuation.take())
+                .send()
+                .await
+                .map_err(|e| anyhow!("Failed to list backups: {}", DisplayErrorContext(e)))?;
+
+            for object in page.contents() {
+                let Some(key) = object.key() else { continue };
+                let Some((object_label, created_at)) = parse_object_key(&self.config.prefix, key) else {
+                    continue;
+                };
+                if label.as_ref().is_some_and(|l| *l != object_label) {
+                    continue;
+                }
+                backups.push(StoredBackup {
+                    key: key.to_string(),
+                    created_at,
+                    size: object.size().unwrap_or_default(),
+                });
+            }
+
+            match page.next_continuation_token() {
+                Some(token) if page.is_truncated().unwrap_or(false) => continuation = Some(token.to_string()),
+                _ => break,
+            }
+        }
+
+        backups.sort_by_key(|b| b.created_at);
+        Ok(backups)
+    }
+
+    pub async fn delete(&self, key: &str) -> Result<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to delete backup {}: {}", key, DisplayErrorContext(e)))?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Jobs
+// ============================================================================
+
+/// Create an encrypted backup of `scope`, upload it and drop expired ones
+pub async fn run_backup(
+    pool: &PgPool,
+    store: &BackupStore,
+    config: &BackupConfig,
+    scope: BackupScope,
+) -> Result<StoredBackup> {
+    let (manifest, archive) = create_archive(pool, scope).await?;
+    let encrypted = encrypt(&config.key, &archive)?;
+    let key = object_key(&store.config.prefix, &scope, manifest.created_at);
+    let size = encrypted.len() as i64;
+    store.upload(&key, encrypted).await?;
+
+    let backups = store.list(Some(&scope)).await?;
+    for expired in expired_backups(&backups, config.retention) {
+        match store.delete(&expired).await {
+            Ok(()) => info!("Deleted expired backup {}", expired),
+            Err(e) => warn!("{}", e),
+        }
+    }
+
+    Ok(StoredBackup { key, created_at: manifest.created_at, size })
+}
+
+/// Background loop creating full backups. Without storage nothing is scheduled.
+pub async fn run_scheduler(pool: PgPool, config: Option<BackupConfig>) {
+    let Some((config, storage)) = config.and_then(|c| c.storage.clone().map(|s| (c, s))) else {
+        info!("Backup storage not configured, scheduled backups disabled");
+        return;
+    };
+    info!(
+        "Backup scheduler started (s3://{}/{}, every {} h, keeping {})",
+        storage.bucket,
+        storage.prefix,
+        config.interval.as_secs() / 3600,
+        config.retention
+    );
+
+    let store = BackupStore::new(storage).await;
+    let interval = chrono::Duration::from_std(config.interval).unwrap_or(chrono::Duration::days(1));
+    let mut ticker = tokio::time::interval(SCHEDULER_TICK);
+
+    loop {
+        ticker.tick().await;
+
+        // The newest upload decides, so restarts do not cause extra backups
+        let latest = match store.list(Some(&BackupScope::Full)).await {
+            Ok(backups) => backups.last().map(|b| b.created_at),
+            Err(e) => {
+                error!("{}", e);
+                continue;
+            }
+        };
+        if latest.is_some_and(|at| Utc::now() - at < interval) {
+            continue;
+        }
+
+        match run_backup(&pool, &store, &config, BackupScope::Full).await {
+            Ok(backup) => info!("Backup {} uploaded ({} bytes)", backup.key, backup.size),
+            Err(e) => error!("Scheduled backup failed: {:#}", e),
+        }
+    }
+}
+
+// ============================================================================
+// CLI
+// ============================================================================
+
+fn require_config(config: Option<&BackupConfig>) -> Result<&BackupConfig> {
+    config.ok_or_else(|| anyhow!("BACKUP_ENCRYPTION_KEY must be set — generate one with: openssl rand -base64 32"))
+}
+
+async fn require_store(config: &BackupConfig) -> Result<BackupStore> {
+    let storage = config.storage.clone().ok_or_else(|| anyhow!("BACKUP_S3_BUCKET must be set"))?;
+    Ok(BackupStore::new(storage).await)
+}
+
+/// `sazinka-worker backup [--user ID] [--output FILE]`
+pub async fn backup_command(
+    pool: &PgPool,
+    config: Option<&BackupConfig>,
+    user_id: Option<Uuid>,
+    output: Option<std::path::PathBuf>,
+) -> Result<()> {
+    let config = require_config(config)?;
+    let scope = match user_id {
+        Some(user_id) => BackupScope::Account { user_id },
+        None => BackupScope::Full,
+    };
+
+    match output {
+        Some(path) => {
+            let (manifest, archive) = create_archive(pool, scope).await?;
+            std::fs::write(&path, encrypt(&config.key, &archive)?)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            let rows: usize = manifest.tables.iter().map(|t| t.rows).sum();
+            println!("Backup written to {} ({} tables, {} rows)", path.display(), manifest.tables.len(), rows);
+        }
+        None => {
+            let store = require_store(config).await?;
+            let backup = run_backup(pool, &store, config, scope).await?;
+            println!("Backup uploaded: {} ({} bytes)", backup.key, backup.size);
+        }
+    }
+    Ok(())
+}
+
+/// `sazinka-worker list-backups`
+pub async fn list_command(config: Option<&BackupConfig>) -> Result<()> {
+    let store = require_store(require_config(config)?).await?;
+    let backups = store.list(None).await?;
+    if backups.is_empty() {
+        println!("No backups found");
+    }
+    for backup in backups {
+        println!("{}  {:>12} B  {}", backup.created_at.format("%Y-%m-%d %H:%M:%S UTC"), backup.size, backup.key);
+    }
+    Ok(())
+}
+
+/// `sazinka-worker restore --object KEY | --file FILE`
+pub async fn restore_command(
+    pool: &PgPool,
+    config: Option<&BackupConfig>,
+    object: Option<String>,
+    file: Option<std::path::PathBuf>,
+) -> Result<()> {
+    let config = require_config(config)?;
+    let encrypted = match (object, file) {
+        (Some(key), _) => require_store(config).await?.download(&key).await?,
+        (None, Some(path)) => std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?,
+        (None, None) => bail!("Either --object or --file is required"),
+    };
+
+    let (manifest, tables) = read_archive(&decrypt(&config.key, &encrypted)?)?;
+    println!(
+        "Restoring {} backup from {} ({} tables)...",
+        manifest.scope.label(),
+        manifest.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        manifest.tables.len()
+    );
+    let summary = restore_archive(pool, &manifest, tables).await?;
+    println!(
+        "Restore complete: {} rows inserted, {} already present, {} tables",
+        summary.rows_inserted, summary.rows_skipped, summary.tables
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::backup::{ColumnSchema, ForeignKeySchema};
+    use chrono::TimeZone;
+
+    fn table(name: &str, foreign_keys: &[(&str, &str, bool)]) -> TableSchema {
+        let mut columns = vec![ColumnSchema { name: "id".into(), nullable: false }];
+        columns.extend(foreign_keys.iter().map(|(c, _, nullable)| ColumnSchema { name: c.to_string(), nullable: *nullable }));
+        TableSchema {
+            name: name.into(),
+            columns,
+            primary_key: vec!["id".into()],
+            foreign_keys: foreign_keys
+                .iter()
+                .map(|(c, p, _)| ForeignKeySchema { column: c.to_string(), parent_table: p.to_string(), parent_column: "id".into() })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_encrypt_roundtrip() {
+        let key = [42u8; 32];
+        let encrypted = encrypt(&key, b"archive").unwrap();
+        assert!(encrypted.starts_with(MAGIC));
+        assert_eq!(decrypt(&key, &encrypted).unwrap(), b"archive");
+        assert_ne!(encrypt(&key, b"archive").unwrap(), encrypted, "nonce must be fresh");
+    }
+
+    #[test]
+    fn test_decrypt_rejects_wrong_key_and_tampering() {
+        let encrypted = encrypt(&[1u8; 32], b"archive").unwrap();
+        assert!(decrypt(&[2u8; 32], &encrypted).is_err());
+
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&[1u8; 32], &tampered).is_err());
+        assert!(decrypt(&[1u8; 32], b"PK\x03\x04").is_err());
+    }
+
+    #[test]
+    fn test_object_key_roundtrip() {
+        let at = Utc.with_ymd_and_hms(2026, 3, 1, 2, 0, 0).unwrap();
+        let user_id = Uuid::new_v4();
+        let scope = BackupScope::Account { user_id };
+
+        let key = object_key("backups/", &scope, at);
+        assert_eq!(key, format!("backups/sazinka-account-{}-20260301T020000Z.szbk", user_id));
+        assert_eq!(parse_object_key("backups/", &key), Some((scope.label(), at)));
+        assert_eq!(
+            parse_object_key("backups/", &object_key("backups/", &BackupScope::Full, at)),
+            Some(("full".to_string(), at))
+        );
+        assert!(parse_object_key("backups/", "backups/notes.txt").is_none());
+    }
+
+    #[test]
+    fn test_expired_backups_keeps_newest() {
+        let backups: Vec<StoredBackup> = (1..=5)
+            .map(|day| StoredBackup {
+                key: format!("b{}", day),
+                created_at: Utc.with_ymd_and_hms(2026, 3, day, 2, 0, 0).unwrap(),
+                size: 1,
+            })
+            .collect();
+
+        let mut expired = expired_backups(&backups, 3);
+        expired.sort();
+        assert_eq!(expired, vec!["b1", "b2"]);
+        assert!(expired_backups(&backups, 10).is_empty());
+    }
+
+    #[test]
+    fn test_restore_order_parents_first() {
+        let schemas = vec![
+            table("route_stops", &[("route_id", "routes", false)]),
+            table("routes", &[("user_id", "users", false)]),
+            table("users", &[]),
+        ];
+        let names: Vec<String> = schemas.iter().map(|t| t.name.clone()).collect();
+
+        let plan = restore_order(&schemas, &names).unwrap();
+        assert_eq!(plan.order, vec!["users", "routes", "route_stops"]);
+        assert!(plan.deferred.is_empty());
+    }
+
+    #[test]
+    fn test_restore_order_defers_nullable_cycle_edge() {
+        let schemas = vec![
+            table("depots", &[("user_id", "users", false)]),
+            table("users", &[("default_depot_id", "depots", true), ("owner_id", "users", true)]),
+        ];
+        let names: Vec<String> = schemas.iter().map(|t| t.name.clone()).collect();
+
+        let plan = restore_order(&schemas, &names).unwrap();
+        assert_eq!(plan.order, vec!["users", "depots"]);
+        assert_eq!(plan.deferred, vec![("users".to_string(), "default_depot_id".to_string())]);
+    }
+
+    #[test]
+    fn test_restore_order_defers_only_cycle_edges() {
+        let schemas = vec![
+            table("depots", &[("user_id", "users", false)]),
+            table("route_stops", &[("customer_id", "customers", true)]),
+            table("customers", &[("user_id", "users", false)]),
+            table("users", &[("default_depot_id", "depots", true)]),
+        ];
+        let names: Vec<String> = schemas.iter().map(|t| t.name.clone()).collect();
+
+        let plan = restore_order(&schemas, &names).unwrap();
+        assert_eq!(plan.deferred, vec![("users".to_string(), "default_depot_id".to_string())]);
+        assert_eq!(plan.order, vec!["users", "depots", "customers", "route_stops"]);
+    }
+
+    #[test]
+    fn test_restore_order_fails_on_required_cycle() {
+        let schemas = vec![table("a", &[("b_id", "b", false)]), table("b", &[("a_id", "a", false)])];
+        assert!(restore_order(&schemas, &["a".to_string(), "b".to_string()]).is_err());
+    }
+}
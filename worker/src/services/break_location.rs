@@ -0,0 +1,212 @@
+#![allow(dead_code)]
+//! Break location suggestions
+//!
+//! A break is held where the previous stop was unless a place is chosen for
+//! it. Restaurants and fuel stations around the middle of the leg the break
+//! falls on are looked up in Nominatim and ranked by the detour they add to
+//! the leg. Users can turn the lookup off, in which case no coordinates of
+//! their route leave the worker.
+
+use anyhow::{Context, Result};
+use tracing::warn;
+
+use crate::services::geo::haversine_distance;
+use crate::services::geocoding::BoundingBox;
+use crate::services::http::{self, HttpService};
+use crate::types::route::{BreakLocation, BreakPlaceKind, BreakSuggestion, MAX_BREAK_LOCATION_NAME_LENGTH};
+use crate::types::Coordinates;
+
+/// Suggestions returned when the request sets no limit
+pub const DEFAULT_SUGGESTIONS: usize = 5;
+/// Search radius around the leg midpoint, half the leg length within these bounds
+const MIN_SEARCH_RADIUS_KM: f64 = 2.0;
+const MAX_SEARCH_RADIUS_KM: f64 = 8.0;
+/// Places asked from Nominatim per kind
+const RESULTS_PER_KIND: usize = 20;
+const KM_PER_DEGREE_LAT: f64 = 111.32;
+
+/// Area searched for a break after `from` (and before `to`)
+pub fn search_area(from: Coordinates, to: Option<Coordinates>) -> BoundingBox {
+    let (center, radius_km) = match to {
+        Some(to) => (
+            Coordinates { lat: (from.lat + to.lat) / 2.0, lng: (from.lng + to.lng) / 2.0 },
+            (haversine_distance(&from, &to) / 2.0).clamp(MIN_SEARCH_RADIUS_KM, MAX_SEARCH_RADIUS_KM),
+        ),
+        None => (from, MIN_SEARCH_RADIUS_KM),
+    };
+    let d_lat = radius_km / KM_PER_DEGREE_LAT;
+    let d_lng = radius_km / (KM_PER_DEGREE_LAT * center.lat.to_radians().cos().max(0.1));
+
+    BoundingBox::new(center.lat - d_lat, center.lng - d_lng, center.lat + d_lat, center.lng + d_lng)
+}
+
+/// Place of one Nominatim `format=jsonv2` search result; unnamed places are skipped
+pub fn parse_place(result: &serde_json::Value, kind: BreakPlaceKind) -> Option<BreakLocation> {
+    let name = result.get("name")?.as_str()?.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let lat = result.get("lat")?.as_str()?.parse().ok()?;
+    let lng = result.get("lon")?.as_str()?.parse().ok()?;
+    // display_name starts with the place's own name
+    let address = result
+        .get("display_name")
+        .and_then(|d| d.as_str())
+        .map(|d| d.strip_prefix(name).unwrap_or(d).trim_start_matches([',', ' ']).to_string())
+        .filter(|a| !a.is_empty());
+
+    Some(BreakLocation {
+        name: name.chars().take(MAX_BREAK_LOCATION_NAME_LENGTH).collect(),
+        kind,
+        lat,
+        lng,
+        address,
+    })
+}
+
+/// Extra distance of a break at `place` compared to driving the leg directly
+pub fn detour_km(from: Coordinates, to: Option<Coordinates>, place: &BreakLocation) -> f64 {
+    let at = Coordinates { lat: place.lat, lng: place.lng };
+    match to {
+        Some(to) => haversine_distance(&from, &at) + haversine_distance(&at, &to) - haversine_distance(&from, &to),
+        None => haversine_distance(&from, &at),
+    }
+}
+
+/// Places ordered by detour, one per name and spot
+pub fn rank(
+    from: Coordinates,
+    to: Option<Coordinates>,
+    places: Vec<BreakLocation>,
+    limit: usize,
+) -> Vec<BreakSuggestion> {
+    let mut suggestions: Vec<BreakSuggestion> = places
+        .into_iter()
+        .map(|location| BreakSuggestion {
+            detour_km: (detour_km(from, to, &location).max(0.0) * 10.0).round() / 10.0,
+            location,
+        })
+        .collect();
+    suggestions.sort_by(|a, b| a.detour_km.total_cmp(&b.detour_km));
+    // The same place is often mapped as both a node and a building
+    let mut seen = Vec::new();
+    suggestions.retain(|s| {
+        let at = Coordinates { lat: s.location.lat, lng: s.location.lng };
+        let duplicate = seen
+            .iter()
+            .any(|(name, c)| *name == s.location.name && haversine_distance(c, &at) < 0.1);
+        if !duplicate {
+            seen.push((s.location.name.clone(), at));
+        }
+        !duplicate
+    });
+    suggestions.truncate(limit);
+    suggestions
+}
+
+async fn search_kind(nominatim_url: &str, area: &BoundingBox, kind: BreakPlaceKind) -> Result<Vec<BreakLocation>> {
+    let url = format!(
+        "{}/search?amenity={}&format=jsonv2&viewbox={},{},{},{}&bounded=1&limit={}",
+        nominatim_url.trim_end_matches('/'),
+        kind.amenity(),
+        area.min_lng,
+        area.max_lat,
+        area.max_lng,
+        area.min_lat,
+        RESULTS_PER_KIND
+    );
+    let client = http::client(HttpService::Nominatim);
+    let response = http::send_idempotent(HttpService::Nominatim, || client.get(&url))
+        .await
+        .context("Failed to send place search request")?;
+    if !response.status().is_success() {
+        anyhow::bail!("Nominatim place search returned {}", response.status());
+    }
+    let results: Vec<serde_json::Value> = response.json().await.context("Failed to parse place search response")?;
+
+    Ok(results.iter().filter_map(|r| parse_place(r, kind)).collect())
+}
+
+/// Restaurants and fuel stations for a break after `from`, best first.
+/// Fails only when no kind could be searched.
+pub async fn suggest(
+    nominatim_url: &str,
+    from: Coordinates,
+    to: Option<Coordinates>,
+    limit: usize,
+) -> Result<Vec<BreakSuggestion>> {
+    let area = search_area(from, to);
+    let mut places = Vec::new();
+    let mut last_error = None;
+    for kind in [BreakPlaceKind::Restaurant, BreakPlaceKind::Fuel] {
+        match search_kind(nominatim_url, &area, kind).await {
+            Ok(found) => places.extend(found),
+            Err(e) => {
+                warn!("Break place search for {} failed: {}", kind.amenity(), e);
+                last_error = Some(e);
+            }
+        }
+    }
+    if places.is_empty() {
+        if let Some(e) = last_error {
+            return Err(e);
+        }
+    }
+
+    Ok(rank(from, to, places, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRNO: Coordinates = Coordinates { lat: 49.195, lng: 16.608 };
+    const VYSKOV: Coordinates = Coordinates { lat: 49.277, lng: 16.999 };
+
+    fn place(name: &str, lat: f64, lng: f64) -> BreakLocation {
+        BreakLocation { name: name.to_string(), kind: BreakPlaceKind::Restaurant, lat, lng, address: None }
+    }
+
+    #[test]
+    fn test_search_area_around_leg_midpoint() {
+        let area = search_area(BRNO, Some(VYSKOV));
+        assert!(area.contains(&Coordinates { lat: 49.236, lng: 16.8035 }));
+        assert!(!area.contains(&BRNO));
+
+        // Without a next stop: a small area around the previous one
+        let area = search_area(BRNO, None);
+        assert!(area.contains(&BRNO));
+        assert!((area.max_lat - area.min_lat) * KM_PER_DEGREE_LAT < 2.0 * MIN_SEARCH_RADIUS_KM + 0.01);
+    }
+
+    #[test]
+    fn test_parse_place() {
+        let result = serde_json::json!({
+            "name": "Pizzerie Roma",
+            "lat": "49.2361",
+            "lon": "16.8040",
+            "display_name": "Pizzerie Roma, Hlavní 12, Rousínov, Česko"
+        });
+        let parsed = parse_place(&result, BreakPlaceKind::Restaurant).unwrap();
+        assert_eq!(parsed.name, "Pizzerie Roma");
+        assert_eq!(parsed.lat, 49.2361);
+        assert_eq!(parsed.address.as_deref(), Some("Hlavní 12, Rousínov, Česko"));
+
+        let unnamed = serde_json::json!({ "name": "", "lat": "49.2", "lon": "16.8", "display_name": "x" });
+        assert!(parse_place(&unnamed, BreakPlaceKind::Fuel).is_none());
+    }
+
+    #[test]
+    fn test_rank_by_detour_and_dedupe() {
+        let on_the_way = place("Na cestě", 49.236, 16.8035);
+        let aside = place("Stranou", 49.30, 16.75);
+        let duplicate = place("Na cestě", 49.2362, 16.8036);
+
+        let ranked = rank(BRNO, Some(VYSKOV), vec![aside, on_the_way, duplicate], 5);
+
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].location.name, "Na cestě");
+        assert!(ranked[0].detour_km < ranked[1].detour_km);
+        assert_eq!(rank(BRNO, Some(VYSKOV), ranked.into_iter().map(|s| s.location).collect(), 1).len(), 1);
+    }
+}
@@ -0,0 +1,332 @@
+//! Work calendar feed contents
+//!
+//! Turns routes, visits and crew capacity into iCal events. Every event of
+//! a crew carries the crew's name as a category and the crew's color, so
+//! calendar apps can filter crews and tell them apart; work without a crew
+//! is categorized as unassigned.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate, NaiveTime};
+use uuid::Uuid;
+
+use crate::db::queries::route::{RouteStopWithInfo, RouteWithCrewInfo};
+use crate::defaults::DEFAULT_SERVICE_DURATION_MINUTES;
+use crate::services::ical::{crew_color, EventTime, IcalEvent};
+use crate::types::{Crew, VisitWithCustomer};
+
+/// Days before today covered by a feed
+pub const FEED_PAST_DAYS: i64 = 14;
+/// Days after today covered by a feed
+pub const FEED_FUTURE_DAYS: i64 = 90;
+/// Days from today with a capacity overlay; it is the costliest part of a feed
+pub const CAPACITY_DAYS: i64 = 14;
+
+const UNASSIGNED: &str = "Unassigned";
+
+/// Name and color of a crew in the feed
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedCrew {
+    pub name: String,
+    pub color: &'static str,
+}
+
+/// Load of one crew on one day
+#[derive(Debug, Clone)]
+pub struct CrewDayLoad {
+    pub crew_id: Uuid,
+    pub date: NaiveDate,
+    pub visit_count: i32,
+    pub capacity_minutes: i32,
+    pub used_minutes: i32,
+    pub load_percent: i32,
+}
+
+/// Feed crews by ID; colors follow the order of the company's crew list
+pub fn feed_crews(crews: &[Crew]) -> HashMap<Uuid, FeedCrew> {
+    crews
+        .iter()
+        .enumerate()
+        .map(|(index, crew)| (crew.id, FeedCrew { name: crew.name.clone(), color: crew_color(index) }))
+        .collect()
+}
+
+/// Whether a feed limited to `crew_ids` (empty: all) shows work of `crew_id`
+pub fn includes_crew(crew_ids: &[Uuid], crew_id: Option<Uuid>) -> bool {
+    crew_ids.is_empty() || crew_id.is_some_and(|id| crew_ids.contains(&id))
+}
+
+/// One event spanning a route's stops, listing them in the description
+pub fn route_event(route: &RouteWithCrewInfo, stops: &[RouteStopWithInfo], crew: Option<&FeedCrew>) -> IcalEvent {
+    let customer_stops: Vec<&RouteStopWithInfo> = stops.iter().filter(|s| s.stop_type == "customer").collect();
+    let start = customer_stops.first().and_then(|s| s.estimated_arrival.or(s.scheduled_time_start));
+    let end = customer_stops.last().and_then(|s| s.estimated_departure.or(s.scheduled_time_end));
+    let (start, end) = match (start, end) {
+        (Some(start), Some(end)) if end > start => (local(route.date, start), local(route.date, end)),
+        _ => all_day(route.date),
+    };
+
+    let mut lines = vec![format!("Status: {}", route.status)];
+    for stop in &customer_stops {
+        let time = stop
+            .estimated_arrival
+            .or(stop.scheduled_time_start)
+            .map(|t| t.format("%H:%M").to_string())
+            .unwrap_or_else(|| "--:--".to_string());
+        let name = stop.customer_name.as_deref().unwrap_or_default();
+        let address = stop.address.as_deref().map(|a| a.trim_matches(|c| c == ',' || c == ' ')).unwrap_or_default();
+        lines.push(if address.is_empty() { format!("{} {}", time, name) } else { format!("{} {}, {}", time, name, address) });
+    }
+
+    IcalEvent {
+        uid: format!("route-{}@sazinka", route.id),
+        start,
+        end,
+        summary: format!("Route {} · {} stops", crew_name(crew), customer_stops.len()),
+        description: Some(lines.join("\n")),
+        location: None,
+        categories: categories(crew, "Route"),
+        color: crew.map(|c| c.color),
+        transparent: false,
+    }
+}
+
+/// One event per visit at its scheduled time, all-day when it has none
+pub fn visit_event(visit: &VisitWithCustomer, crew: Option<&FeedCrew>) -> IcalEvent {
+    let (start, end) = match visit.scheduled_time_start {
+        Some(start) => {
+            let end = visit
+                .scheduled_time_end
+                .filter(|end| *end > start)
+                .unwrap_or_else(|| start + Duration::minutes(DEFAULT_SERVICE_DURATION_MINUTES as i64));
+            // A default duration running past midnight ends the same day
+            let end = if end > start { end } else { NaiveTime::from_hms_opt(23, 59, 0).unwrap_or(start) };
+            (local(visit.scheduled_date, start), local(visit.scheduled_date, end))
+        }
+        None => all_day(visit.scheduled_date),
+    };
+    let location = [visit.customer_street.as_deref(), visit.customer_city.as_deref()]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    IcalEvent {
+        uid: format!("visit-{}@sazinka", visit.id),
+        start,
+        end,
+        summary: format!("{} ({})", visit.customer_name.as_deref().unwrap_or("Visit"), visit.visit_type),
+        description: Some(format!("Status: {}\nCrew: {}", visit.status, crew_name(crew))),
+        location: (!location.is_empty()).then_some(location),
+        categories: categories(crew, "Visit"),
+        color: crew.map(|c| c.color),
+        transparent: false,
+    }
+}
+
+/// All-day overlay with a crew's booked and free time, not blocking the calendar
+pub fn capacity_event(load: &CrewDayLoad, crew: Option<&FeedCrew>) -> IcalEvent {
+    let (start, end) = all_day(load.date);
+    let free = (load.capacity_minutes - load.used_minutes).max(0);
+    IcalEvent {
+        uid: format!("capacity-{}-{}@sazinka", load.crew_id, load.date.format("%Y%m%d")),
+        start,
+        end,
+        summary: format!("{}: {} % booked, {} free", crew_name(crew), load.load_percent, format_minutes(free)),
+        description: Some(format!(
+            "{} stops, {} of {} booked",
+            load.visit_count,
+            format_minutes(load.used_minutes),
+            format_minutes(load.capacity_minutes)
+        )),
+        location: None,
+        categories: categories(crew, "Capacity"),
+        color: crew.map(|c| c.color),
+        transparent: true,
+    }
+}
+
+fn crew_name(crew: Option<&FeedCrew>) -> &str {
+    crew.map(|c| c.name.as_str()).unwrap_or(UNASSIGNED)
+}
+
+fn categories(crew: Option<&FeedCrew>, kind: &str) -> Vec<String> {
+    vec![crew_name(crew).to_string(), kind.to_string()]
+}
+
+fn local(date: NaiveDate, time: NaiveTime) -> EventTime {
+    EventTime::Local(date.and_time(time))
+}
+
+fn all_day(date: NaiveDate) -> (EventTime, EventTime) {
+    (EventTime::Date(date), EventTime::Date(date.succ_opt().unwrap_or(date)))
+}
+
+/// "2 h 30 min", "45 min", "3 h"
+fn format_minutes(minutes: i32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{} min", m),
+        (h, 0) => format!("{} h", h),
+        (h, m) => format!("{} h {} min", h, m),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn crew() -> FeedCrew {
+        FeedCrew { name: "Crew A".to_string(), color: "royalblue" }
+    }
+
+    fn visit(start: Option<NaiveTime>, end: Option<NaiveTime>) -> VisitWithCustomer {
+        VisitWithCustomer {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            customer_id: Uuid::nil(),
+            crew_id: None,
+            device_id: None,
+            scheduled_date: date(),
+            scheduled_time_start: start,
+            scheduled_time_end: end,
+            status: "planned".to_string(),
+            visit_type: "revision".to_string(),
+            actual_arrival: None,
+            actual_departure: None,
+            result: None,
+            field_notes: None,
+            requires_follow_up: None,
+            follow_up_reason: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            customer_name: Some("Novák".to_string()),
+            customer_street: Some("Hlavní 1".to_string()),
+            customer_city: Some("Brno".to_string()),
+        }
+    }
+
+    fn route() -> RouteWithCrewInfo {
+        RouteWithCrewInfo {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            crew_id: None,
+            crew_name: None,
+            depot_id: None,
+            date: date(),
+            status: "confirmed".to_string(),
+            total_distance_km: None,
+            total_duration_minutes: None,
+            optimization_score: None,
+            arrival_buffer_percent: 0.0,
+            arrival_buffer_fixed_minutes: 0.0,
+            return_to_depot_distance_km: None,
+            return_to_depot_duration_minutes: None,
+            stops_count: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn stop(stop_type: &str, arrival: Option<NaiveTime>, departure: Option<NaiveTime>) -> RouteStopWithInfo {
+        RouteStopWithInfo {
+            id: Uuid::nil(),
+            route_id: Uuid::nil(),
+            customer_id: None,
+            visit_id: None,
+            revision_id: None,
+            stop_order: 1,
+            estimated_arrival: arrival,
+            estimated_departure: departure,
+            distance_from_previous_km: None,
+            duration_from_previous_minutes: None,
+            status: "pending".to_string(),
+            stop_type: stop_type.to_string(),
+            customer_name: Some("Novák".to_string()),
+            address: Some("Hlavní 1, Brno".to_string()),
+            customer_lat: None,
+            customer_lng: None,
+            customer_phone: None,
+            customer_email: None,
+            scheduled_date: None,
+            scheduled_time_start: None,
+            scheduled_time_end: None,
+            revision_status: None,
+            break_duration_minutes: None,
+            break_time_start: None,
+            service_duration_minutes: None,
+            override_service_duration_minutes: None,
+            override_travel_duration_minutes: None,
+            notes: None,
+            tasks: sqlx::types::Json(vec![]),
+            break_location: None,
+            customer_risk_flag: false,
+        }
+    }
+
+    #[test]
+    fn test_includes_crew() {
+        let id = Uuid::new_v4();
+        assert!(includes_crew(&[], None));
+        assert!(includes_crew(&[id], Some(id)));
+        assert!(!includes_crew(&[id], None));
+        assert!(!includes_crew(&[id], Some(Uuid::new_v4())));
+    }
+
+    #[test]
+    fn test_visit_event_times() {
+        let event = visit_event(&visit(Some(time(8, 30)), None), Some(&crew()));
+        assert_eq!(event.start, EventTime::Local(date().and_time(time(8, 30))));
+        assert_eq!(event.end, EventTime::Local(date().and_time(time(9, 30))));
+        assert_eq!(event.location.as_deref(), Some("Hlavní 1, Brno"));
+        assert_eq!(event.categories, vec!["Crew A", "Visit"]);
+        assert_eq!(event.color, Some("royalblue"));
+
+        let untimed = visit_event(&visit(None, None), None);
+        assert_eq!(untimed.start, EventTime::Date(date()));
+        assert_eq!(untimed.categories[0], UNASSIGNED);
+        assert_eq!(untimed.color, None);
+    }
+
+    #[test]
+    fn test_route_event_spans_customer_stops() {
+        let stops = vec![
+            stop("customer", Some(time(8, 0)), Some(time(9, 0))),
+            stop("break", Some(time(11, 0)), Some(time(11, 30))),
+            stop("customer", Some(time(13, 0)), Some(time(14, 15))),
+        ];
+        let event = route_event(&route(), &stops, Some(&crew()));
+        assert_eq!(event.start, EventTime::Local(date().and_time(time(8, 0))));
+        assert_eq!(event.end, EventTime::Local(date().and_time(time(14, 15))));
+        assert_eq!(event.summary, "Route Crew A · 2 stops");
+        assert!(event.description.unwrap().contains("13:00 Novák, Hlavní 1, Brno"));
+
+        let unscheduled = route_event(&route(), &[stop("customer", None, None)], None);
+        assert_eq!(unscheduled.start, EventTime::Date(date()));
+    }
+
+    #[test]
+    fn test_capacity_event() {
+        let load = CrewDayLoad {
+            crew_id: Uuid::nil(),
+            date: date(),
+            visit_count: 4,
+            capacity_minutes: 540,
+            used_minutes: 390,
+            load_percent: 72,
+        };
+        let event = capacity_event(&load, Some(&crew()));
+        assert_eq!(event.summary, "Crew A: 72 % booked, 2 h 30 min free");
+        assert_eq!(event.description.as_deref(), Some("4 stops, 6 h 30 min of 9 h booked"));
+        assert!(event.transparent);
+    }
+}
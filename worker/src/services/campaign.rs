@@ -0,0 +1,172 @@
+//! Campaign triggers
+//!
+//! A background loop evaluates every enabled campaign once a day, in the
+//! campaign's active months. Newly matching customers get the campaign
+//! message rendered for them and queued as an e-mail or SMS job; every
+//! queued or failed message is logged for the campaign statistics.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+use crate::db::queries;
+use crate::services::email_processor::EmailProcessor;
+use crate::services::sms_processor::SmsProcessor;
+use crate::services::template_renderer::{render_template, render_template_html, TemplateVars};
+use crate::types::campaign::{Campaign, CampaignRecipient, CAMPAIGN_CHANNEL_SMS};
+use crate::types::settings::UserWithSettings;
+use crate::types::{CampaignEmailRequest, EmailJobRequest, SmsCampaignRequest, SmsJobRequest};
+
+const SCHEDULER_TICK: Duration = Duration::from_secs(3600);
+
+/// Most messages a campaign queues per day; the rest follow the next day
+const CAMPAIGN_BATCH_LIMIT: i64 = 200;
+
+/// Job pipelines campaign messages are queued to
+#[derive(Clone)]
+pub struct CampaignQueues {
+    pub email: Arc<EmailProcessor>,
+    pub sms: Arc<SmsProcessor>,
+}
+
+/// Placeholders available in campaign messages
+pub fn campaign_vars<'a>(recipient: &CampaignRecipient, settings: &UserWithSettings) -> TemplateVars<'a> {
+    let last_revision = recipient
+        .last_revision_date
+        .map(|date| format_date(date, &settings.company_locale))
+        .unwrap_or_default();
+    let company_name = settings.business_name.clone().unwrap_or_default();
+
+    let mut vars: TemplateVars<'a> = HashMap::new();
+    vars.insert("customerName", recipient.customer_name.clone().unwrap_or_default());
+    vars.insert("lastRevisionDate", last_revision);
+    vars.insert("companyName", company_name.clone());
+    vars.insert("business_name", company_name);
+    vars.insert("phone", settings.phone.clone().unwrap_or_default());
+    vars.insert("email", settings.email.clone());
+    vars
+}
+
+fn format_date(date: NaiveDate, locale: &str) -> String {
+    match locale {
+        "cs" | "sk" => date.format("%d.%m.%Y").to_string(),
+        _ => date.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// Queue the campaign message for one customer. Returns the job ID.
+async fn queue_message(
+    queues: &CampaignQueues,
+    campaign: &Campaign,
+    recipient: &CampaignRecipient,
+    settings: &UserWithSettings,
+) -> Result<std::result::Result<uuid::Uuid, String>> {
+    let vars = campaign_vars(recipient, settings);
+
+    if campaign.channel == CAMPAIGN_CHANNEL_SMS {
+        let request = SmsJobRequest::Campaign(SmsCampaignRequest {
+            campaign_id: campaign.id,
+            customer_id: recipient.customer_id,
+            phone_number: recipient.contact.clone(),
+            message: render_template(&campaign.body, &vars),
+        });
+        return Ok(queues
+            .sms
+            .submit_job(campaign.user_id, request)
+            .await?
+            .map(|submitted| submitted.job_id)
+            .map_err(|exceeded| exceeded.to_string()));
+    }
+
+    let subject = campaign.subject.as_deref().unwrap_or_default();
+    let request = EmailJobRequest::Campaign(CampaignEmailRequest {
+        campaign_id: campaign.id,
+        customer_id: recipient.customer_id,
+        to: recipient.contact.clone(),
+        subject: render_template(subject, &vars),
+        body_html: render_template_html(&campaign.body, &vars),
+        body_text: render_template(&campaign.body, &vars),
+    });
+    Ok(Ok(queues.email.submit_job(campaign.user_id, request).await?.job_id))
+}
+
+/// Evaluate one campaign. Returns the number of queued messages.
+async fn run_campaign(pool: &PgPool, queues: &CampaignQueues, campaign: &Campaign) -> Result<usize> {
+    let Some(settings) = queries::settings::get_user_settings(pool, campaign.user_id).await? else {
+        return Ok(0);
+    };
+
+    let recipients = queries::campaign::match_recipients(pool, campaign, CAMPAIGN_BATCH_LIMIT).await?;
+    let mut queued = 0;
+    for recipient in &recipients {
+        match queue_message(queues, campaign, recipient, &settings).await? {
+            Ok(job_id) => {
+                queries::campaign::record_send(pool, campaign, recipient.customer_id, &recipient.contact, Some(job_id), None)
+                    .await?;
+                queued += 1;
+            }
+            Err(reason) => {
+                // Out of SMS credits: the remaining customers would fail the same way
+                warn!("Campaign {} stopped: {}", campaign.id, reason);
+                queries::campaign::record_send(pool, campaign, recipient.customer_id, &recipient.contact, None, Some(&reason))
+                    .await?;
+                break;
+            }
+        }
+    }
+
+    Ok(queued)
+}
+
+/// Evaluate all campaigns due on `today`
+pub async fn run_campaigns(pool: &PgPool, queues: &CampaignQueues, today: NaiveDate) -> Result<usize> {
+    let mut queued = 0;
+    for campaign in queries::campaign::list_due_campaigns(pool).await? {
+        if campaign.is_active_in(today.month()) {
+            match run_campaign(pool, queues, &campaign).await {
+                Ok(count) => queued += count,
+                Err(e) => {
+                    error!("Campaign {} failed: {}", campaign.id, e);
+                    continue;
+                }
+            }
+        }
+        queries::campaign::set_last_run(pool, campaign.id, today).await?;
+    }
+
+    Ok(queued)
+}
+
+/// Background loop evaluating campaigns
+pub async fn run_scheduler(pool: PgPool, queues: CampaignQueues) {
+    info!("Campaign scheduler started");
+    let mut ticker = tokio::time::interval(SCHEDULER_TICK);
+
+    loop {
+        ticker.tick().await;
+
+        let today = chrono::Utc::now().date_naive();
+        match run_campaigns(&pool, &queues, today).await {
+            Ok(0) => {}
+            Ok(count) => info!("Queued {} campaign messages", count),
+            Err(e) => error!("Failed to run campaigns: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_date_per_locale() {
+        let date = NaiveDate::from_ymd_opt(2025, 10, 3).unwrap();
+        assert_eq!(format_date(date, "cs"), "03.10.2025");
+        assert_eq!(format_date(date, "en"), "2025-10-03");
+    }
+}
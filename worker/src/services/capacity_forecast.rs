@@ -0,0 +1,249 @@
+//! Seasonal capacity forecast
+//!
+//! Projects revision due volume per week from device intervals and compares
+//! it with crew capacity (crews × working days × stops per day), so that
+//! peaks such as the autumn heating season are visible months in advance.
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+
+use crate::types::report::{CapacityForecastResponse, CapacityForecastWeek, DueProjectionSource};
+
+/// Default forecast horizon (one year)
+pub const DEFAULT_WEEKS: u32 = 52;
+/// Upper bound for the forecast horizon
+pub const MAX_WEEKS: u32 = 104;
+/// Default working days per week
+pub const DEFAULT_WORKING_DAYS_PER_WEEK: u32 = 5;
+
+/// Parameters for building a forecast
+#[derive(Debug, Clone)]
+pub struct ForecastParams {
+    pub start: NaiveDate,
+    pub weeks: u32,
+    pub crew_count: i64,
+    pub stops_per_day: i32,
+    pub working_days_per_week: u32,
+}
+
+/// Monday of the week containing `date`
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Expand one source into its due dates up to and including `horizon_end`.
+///
+/// An anchor before `start` is a single overdue date; the occurrences missed
+/// since then are not repeated, and the following dates are projected from
+/// `start`, when the overdue revision is caught up. A missing or
+/// non-positive interval yields only the anchor date.
+pub fn project_due_dates(source: &DueProjectionSource, start: NaiveDate, horizon_end: NaiveDate) -> Vec<NaiveDate> {
+    let Some(anchor) = source.next_due_date else {
+        return Vec::new();
+    };
+    if anchor > horizon_end {
+        return Vec::new();
+    }
+
+    let mut dates = vec![anchor];
+    let Some(months) = source.interval_months.filter(|m| *m > 0) else {
+        return dates;
+    };
+    let base = anchor.max(start);
+    let mut step: u32 = 1;
+    while let Some(due) = base.checked_add_months(Months::new(months as u32 * step)) {
+        if due > horizon_end {
+            break;
+        }
+        dates.push(due);
+        step += 1;
+    }
+    dates
+}
+
+/// Build the weekly forecast.
+///
+/// Overdue devices (due before `params.start`) are counted once as backlog
+/// in the first week.
+pub fn build_forecast(sources: &[DueProjectionSource], params: &ForecastParams) -> CapacityForecastResponse {
+    let start = week_start(params.start);
+    let weeks = params.weeks.clamp(1, MAX_WEEKS);
+    let horizon_end = start + Duration::weeks(weeks as i64) - Duration::days(1);
+
+    let mut due_counts = vec![0i64; weeks as usize];
+    let mut backlog: i64 = 0;
+    for source in sources {
+        for due in project_due_dates(source, start, horizon_end) {
+            if due < start {
+                backlog += 1;
+            } else {
+                let index = ((due - start).num_days() / 7) as usize;
+                due_counts[index] += 1;
+            }
+        }
+    }
+
+    let working_days = params.working_days_per_week.clamp(1, 7) as i64;
+    let capacity = params.crew_count.max(1) * working_days * params.stops_per_day.max(0) as i64;
+
+    let weeks: Vec<CapacityForecastWeek> = due_counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, due_count)| {
+            let backlog_count = if i == 0 { backlog } else { 0 };
+            let demand = due_count + backlog_count;
+            let utilization_percent = if capacity > 0 {
+                (demand as f64 / capacity as f64 * 1000.0).round() / 10.0
+            } else {
+                0.0
+            };
+            CapacityForecastWeek {
+                week_start: start + Duration::weeks(i as i64),
+                due_count,
+                backlog_count,
+                capacity,
+                utilization_percent,
+                is_over_capacity: demand > capacity,
+            }
+        })
+        .collect();
+
+    let total_due = weeks.iter().map(|w| w.due_count + w.backlog_count).sum();
+    let total_capacity = capacity * weeks.len() as i64;
+    let over_capacity_weeks = weeks.iter().filter(|w| w.is_over_capacity).count() as u32;
+
+    CapacityForecastResponse {
+        weeks,
+        crew_count: params.crew_count.max(1),
+        stops_per_day: params.stops_per_day,
+        working_days_per_week: working_days as u32,
+        total_due,
+        total_capacity,
+        over_capacity_weeks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn source(due: Option<NaiveDate>, interval: Option<i32>) -> DueProjectionSource {
+        DueProjectionSource {
+            device_id: Uuid::new_v4(),
+            next_due_date: due,
+            interval_months: interval,
+        }
+    }
+
+    fn params(start: NaiveDate, weeks: u32) -> ForecastParams {
+        ForecastParams {
+            start,
+            weeks,
+            crew_count: 1,
+            stops_per_day: 2,
+            working_days_per_week: 5,
+        }
+    }
+
+    #[test]
+    fn test_week_start_is_monday() {
+        // 2026-10-16 is a Friday
+        assert_eq!(week_start(date(2026, 10, 16)), date(2026, 10, 12));
+        assert_eq!(week_start(date(2026, 10, 12)), date(2026, 10, 12));
+    }
+
+    #[test]
+    fn test_project_due_dates_repeats_by_interval() {
+        let s = source(Some(date(2026, 1, 15)), Some(6));
+        let dates = project_due_dates(&s, date(2026, 1, 1), date(2027, 3, 1));
+        assert_eq!(dates, vec![date(2026, 1, 15), date(2026, 7, 15), date(2027, 1, 15)]);
+    }
+
+    #[test]
+    fn test_project_due_dates_without_interval_yields_anchor_only() {
+        let s = source(Some(date(2026, 1, 15)), None);
+        assert_eq!(project_due_dates(&s, date(2026, 1, 1), date(2030, 1, 1)), vec![date(2026, 1, 15)]);
+        let s = source(Some(date(2026, 1, 15)), Some(0));
+        assert_eq!(project_due_dates(&s, date(2026, 1, 1), date(2030, 1, 1)), vec![date(2026, 1, 15)]);
+    }
+
+    #[test]
+    fn test_project_due_dates_without_anchor_is_empty() {
+        let s = source(None, Some(12));
+        assert!(project_due_dates(&s, date(2026, 1, 1), date(2030, 1, 1)).is_empty());
+    }
+
+    #[test]
+    fn test_project_due_dates_overdue_projects_from_start() {
+        let s = source(Some(date(2026, 1, 15)), Some(6));
+        let dates = project_due_dates(&s, date(2026, 3, 2), date(2027, 3, 5));
+        assert_eq!(dates, vec![date(2026, 1, 15), date(2026, 9, 2), date(2027, 3, 2)]);
+    }
+
+    #[test]
+    fn test_build_forecast_buckets_by_week() {
+        let start = date(2026, 10, 12);
+        let sources = vec![
+            source(Some(date(2026, 10, 13)), Some(12)),
+            source(Some(date(2026, 10, 18)), Some(12)),
+            source(Some(date(2026, 10, 19)), Some(12)),
+        ];
+        let forecast = build_forecast(&sources, &params(start, 2));
+        assert_eq!(forecast.weeks.len(), 2);
+        assert_eq!(forecast.weeks[0].due_count, 2);
+        assert_eq!(forecast.weeks[1].due_count, 1);
+        assert_eq!(forecast.weeks[1].week_start, date(2026, 10, 19));
+        assert_eq!(forecast.weeks[0].capacity, 10);
+        assert_eq!(forecast.total_due, 3);
+        assert_eq!(forecast.total_capacity, 20);
+    }
+
+    #[test]
+    fn test_build_forecast_counts_overdue_as_backlog() {
+        let start = date(2026, 10, 12);
+        let sources = vec![source(Some(date(2026, 9, 1)), Some(12))];
+        let forecast = build_forecast(&sources, &params(start, 4));
+        assert_eq!(forecast.weeks[0].backlog_count, 1);
+        assert_eq!(forecast.weeks[0].due_count, 0);
+        assert_eq!(forecast.weeks[1].backlog_count, 0);
+    }
+
+    #[test]
+    fn test_build_forecast_counts_long_overdue_device_once() {
+        let start = date(2026, 10, 12);
+        let sources = vec![source(Some(date(2020, 3, 1)), Some(6))];
+        let forecast = build_forecast(&sources, &params(start, 52));
+        assert_eq!(forecast.weeks[0].backlog_count, 1);
+        // Missed occurrences since 2020 are not repeated; the next revision
+        // falls six months after catching up
+        let due: Vec<_> = forecast.weeks.iter().filter(|w| w.due_count > 0).map(|w| w.week_start).collect();
+        assert_eq!(due, vec![date(2027, 4, 12)]);
+        assert_eq!(forecast.total_due, 2);
+    }
+
+    #[test]
+    fn test_build_forecast_flags_over_capacity_weeks() {
+        let start = date(2026, 10, 12);
+        let sources: Vec<_> = (0..11)
+            .map(|_| source(Some(date(2026, 10, 14)), Some(12)))
+            .collect();
+        let forecast = build_forecast(&sources, &params(start, 2));
+        assert!(forecast.weeks[0].is_over_capacity);
+        assert!(!forecast.weeks[1].is_over_capacity);
+        assert_eq!(forecast.weeks[0].utilization_percent, 110.0);
+        assert_eq!(forecast.over_capacity_weeks, 1);
+    }
+
+    #[test]
+    fn test_build_forecast_without_crews_assumes_one() {
+        let mut p = params(date(2026, 10, 12), 1);
+        p.crew_count = 0;
+        let forecast = build_forecast(&[], &p);
+        assert_eq!(forecast.crew_count, 1);
+        assert_eq!(forecast.weeks[0].capacity, 10);
+    }
+}
@@ -0,0 +1,259 @@
+#![allow(dead_code)]
+//! Circuit breakers for external services
+//!
+//! After `threshold` consecutive failures the breaker opens and calls fail
+//! immediately, so callers fall back (mock routing, no geocode) instead of
+//! waiting for HTTP timeouts. Once `recovery_time` has passed, a single
+//! probe request is let through (half-open): success closes the breaker,
+//! failure re-opens it for another `recovery_time`.
+//!
+//! Breakers shared by all clients of one service are kept in a registry
+//! so their state can be reported by the admin health checks.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tracing::{info, warn};
+
+/// Breaker state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Requests pass through
+    Closed,
+    /// Requests are rejected until the recovery time passes
+    Open,
+    /// One probe request decides whether to close again
+    HalfOpen,
+}
+
+/// Breaker state as reported by health checks
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitBreakerStatus {
+    pub name: String,
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    pub threshold: u32,
+    pub recovery_secs: u64,
+    pub opened_at: Option<DateTime<Utc>>,
+    /// Requests rejected without calling the service since startup
+    pub rejected_requests: u64,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened: Option<(Instant, DateTime<Utc>)>,
+    probe_in_flight: bool,
+    rejected_requests: u64,
+}
+
+/// Circuit breaker to prevent hammering a failing service
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: String,
+    threshold: u32,
+    recovery_time: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, recovery_time: Duration) -> Self {
+        Self::named("unnamed", threshold, recovery_time)
+    }
+
+    pub fn named(name: &str, threshold: u32, recovery_time: Duration) -> Self {
+        Self {
+            name: name.to_string(),
+            threshold: threshold.max(1),
+            recovery_time,
+            inner: Mutex::new(Inner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                opened: None,
+                probe_in_flight: false,
+                rejected_requests: 0,
+            }),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether calls are currently blocked (no state change)
+    pub fn is_open(&self) -> bool {
+        let inner = self.inner.lock();
+        match inner.state {
+            BreakerState::Closed => false,
+            BreakerState::Open => !self.recovery_elapsed(&inner),
+            BreakerState::HalfOpen => inner.probe_in_flight,
+        }
+    }
+
+    /// Ask to make a call. Moves an open breaker to half-open once the
+    /// recovery time has passed and admits exactly one probe.
+    pub fn allow_request(&self) -> bool {
+        let mut inner = self.inner.lock();
+        let allowed = match inner.state {
+            BreakerState::Closed => true,
+            BreakerState::Open if self.recovery_elapsed(&inner) => {
+                info!("Circuit breaker {} half-open, probing", self.name);
+                inner.state = BreakerState::HalfOpen;
+                inner.probe_in_flight = true;
+                true
+            }
+            BreakerState::HalfOpen if !inner.probe_in_flight => {
+                inner.probe_in_flight = true;
+                true
+            }
+            BreakerState::Open | BreakerState::HalfOpen => false,
+        };
+        if !allowed {
+            inner.rejected_requests += 1;
+        }
+        allowed
+    }
+
+    /// Record a failure
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        inner.probe_in_flight = false;
+        let reopen = inner.state == BreakerState::HalfOpen;
+        if reopen || inner.consecutive_failures >= self.threshold {
+            if inner.state == BreakerState::Closed {
+                warn!(
+                    "Circuit breaker {} opened after {} consecutive failures",
+                    self.name, inner.consecutive_failures
+                );
+            }
+            inner.state = BreakerState::Open;
+            inner.opened = Some((Instant::now(), Utc::now()));
+        }
+    }
+
+    /// Record a success (closes the breaker)
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock();
+        if inner.state != BreakerState::Closed {
+            info!("Circuit breaker {} closed, service recovered", self.name);
+        }
+        inner.state = BreakerState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened = None;
+        inner.probe_in_flight = false;
+    }
+
+    pub fn status(&self) -> CircuitBreakerStatus {
+        let inner = self.inner.lock();
+        CircuitBreakerStatus {
+            name: self.name.clone(),
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            threshold: self.threshold,
+            recovery_secs: self.recovery_time.as_secs(),
+            opened_at: inner.opened.map(|(_, at)| at),
+            rejected_requests: inner.rejected_requests,
+        }
+    }
+
+    fn recovery_elapsed(&self, inner: &Inner) -> bool {
+        inner.opened.is_none_or(|(at, _)| at.elapsed() >= self.recovery_time)
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref REGISTRY: Mutex<Vec<Arc<CircuitBreaker>>> = Mutex::new(Vec::new());
+}
+
+/// Breaker shared by every client of a service. The first registration
+/// of a name decides its threshold and recovery time.
+pub fn shared(name: &str, threshold: u32, recovery_time: Duration) -> Arc<CircuitBreaker> {
+    let mut registry = REGISTRY.lock();
+    if let Some(existing) = registry.iter().find(|b| b.name == name) {
+        return Arc::clone(existing);
+    }
+    let breaker = Arc::new(CircuitBreaker::named(name, threshold, recovery_time));
+    registry.push(Arc::clone(&breaker));
+    breaker
+}
+
+/// Status of a shared breaker, if one is registered under the name
+pub fn status_of(name: &str) -> Option<CircuitBreakerStatus> {
+    REGISTRY.lock().iter().find(|b| b.name == name).map(|b| b.status())
+}
+
+/// Status of all shared breakers
+pub fn statuses() -> Vec<CircuitBreakerStatus> {
+    REGISTRY.lock().iter().map(|b| b.status()).collect()
+}
+
+/// Threshold and recovery time from `{PREFIX}_CB_THRESHOLD` / `{PREFIX}_CB_RECOVERY_SECS`
+pub fn settings_from_env(prefix: &str, default_threshold: u32, default_recovery_secs: u64) -> (u32, Duration) {
+    let threshold = std::env::var(format!("{}_CB_THRESHOLD", prefix))
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(default_threshold);
+    let recovery_secs = std::env::var(format!("{}_CB_RECOVERY_SECS", prefix))
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(default_recovery_secs);
+    (threshold, Duration::from_secs(recovery_secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_half_open_admits_single_probe() {
+        let breaker = CircuitBreaker::new(2, Duration::ZERO);
+        breaker.record_failure();
+        assert!(breaker.allow_request());
+        breaker.record_failure();
+        assert_eq!(breaker.status().state, BreakerState::Open);
+
+        // Recovery time (zero) has passed: one probe, the rest rejected
+        assert!(breaker.allow_request());
+        assert_eq!(breaker.status().state, BreakerState::HalfOpen);
+        assert!(!breaker.allow_request());
+        assert!(breaker.is_open());
+
+        breaker.record_success();
+        assert_eq!(breaker.status().state, BreakerState::Closed);
+        assert!(breaker.allow_request());
+        assert_eq!(breaker.status().rejected_requests, 1);
+    }
+
+    #[test]
+    fn test_failed_probe_reopens() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+        assert!(!breaker.allow_request());
+
+        let probing = CircuitBreaker::new(1, Duration::ZERO);
+        probing.record_failure();
+        assert!(probing.allow_request());
+        probing.record_failure();
+        let status = probing.status();
+        assert_eq!(status.state, BreakerState::Open);
+        assert!(status.opened_at.is_some());
+    }
+
+    #[test]
+    fn test_shared_breakers_are_reused() {
+        let a = shared("test-shared", 3, Duration::from_secs(10));
+        let b = shared("test-shared", 7, Duration::from_secs(99));
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(status_of("test-shared").unwrap().threshold, 3);
+        assert!(status_of("test-missing").is_none());
+    }
+}
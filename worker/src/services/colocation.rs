@@ -0,0 +1,126 @@
+#![allow(dead_code)]
+//! Co-located stops
+//!
+//! Apartment buildings put many customers at (almost) the same coordinates.
+//! Locations within a few metres of each other are one physical place: the
+//! routing matrix is computed for one representative of each place only,
+//! and the legs between units of the same building are zero.
+
+use crate::services::geo::haversine_distance;
+use crate::services::routing::DistanceTimeMatrices;
+use crate::types::Coordinates;
+
+/// Locations closer than this are treated as the same building
+pub const COLOCATION_RADIUS_M: f64 = 25.0;
+
+/// Locations grouped into physical places
+#[derive(Debug, Clone)]
+pub struct LocationGroups {
+    /// Representative of each place: its first location
+    pub anchors: Vec<Coordinates>,
+    /// Place of each location, in input order
+    pub group_of: Vec<usize>,
+}
+
+impl LocationGroups {
+    /// Group locations; each joins the first place whose representative is
+    /// within `radius_m`
+    pub fn new(locations: &[Coordinates], radius_m: f64) -> Self {
+        let mut anchors: Vec<Coordinates> = Vec::new();
+        let group_of = locations
+            .iter()
+            .map(|location| {
+                anchors
+                    .iter()
+                    .position(|anchor| haversine_distance(anchor, location) * 1000.0 <= radius_m)
+                    .unwrap_or_else(|| {
+                        anchors.push(*location);
+                        anchors.len() - 1
+                    })
+            })
+            .collect();
+
+        Self { anchors, group_of }
+    }
+
+    /// Number of locations sharing a place with another one
+    pub fn colocated_count(&self) -> usize {
+        self.group_of.len() - self.anchors.len()
+    }
+
+    pub fn same_place(&self, a: usize, b: usize) -> bool {
+        self.group_of[a] == self.group_of[b]
+    }
+
+    /// Locations of the places in `groups`, e.g. to map matrix anomalies
+    /// found on the anchors back to the locations
+    pub fn members(&self, groups: &[usize]) -> Vec<usize> {
+        (0..self.group_of.len())
+            .filter(|i| groups.contains(&self.group_of[*i]))
+            .collect()
+    }
+
+    /// Full matrices over all locations from matrices over the anchors
+    pub fn expand(&self, compact: &DistanceTimeMatrices) -> DistanceTimeMatrices {
+        let expand = |rows: &Vec<Vec<u64>>| -> Vec<Vec<u64>> {
+            self.group_of
+                .iter()
+                .map(|&from| {
+                    self.group_of
+                        .iter()
+                        .map(|&to| if from == to { 0 } else { rows[from][to] })
+                        .collect()
+                })
+                .collect()
+        };
+
+        DistanceTimeMatrices {
+            distances: expand(&compact.distances),
+            durations: expand(&compact.durations),
+            size: self.group_of.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEPOT: Coordinates = Coordinates { lat: 49.195, lng: 16.608 };
+    const BLOCK: Coordinates = Coordinates { lat: 49.2101, lng: 16.6302 };
+    // About 10 m from BLOCK: another entrance of the same building
+    const BLOCK_ENTRANCE: Coordinates = Coordinates { lat: 49.21019, lng: 16.63023 };
+    const VILLA: Coordinates = Coordinates { lat: 49.22, lng: 16.65 };
+
+    #[test]
+    fn test_groups_nearby_locations() {
+        let groups = LocationGroups::new(&[DEPOT, BLOCK, VILLA, BLOCK_ENTRANCE, BLOCK], COLOCATION_RADIUS_M);
+
+        assert_eq!(groups.anchors.len(), 3);
+        assert_eq!(groups.anchors[2].lat, VILLA.lat);
+        assert_eq!(groups.group_of, vec![0, 1, 2, 1, 1]);
+        assert_eq!(groups.colocated_count(), 2);
+        assert!(groups.same_place(1, 3));
+        assert!(!groups.same_place(2, 3));
+        assert_eq!(groups.members(&[1]), vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn test_expand_zeroes_legs_within_a_place() {
+        let groups = LocationGroups::new(&[DEPOT, BLOCK, BLOCK_ENTRANCE, VILLA], COLOCATION_RADIUS_M);
+        let compact = DistanceTimeMatrices {
+            distances: vec![vec![0, 3000, 5000], vec![3000, 0, 2000], vec![5000, 2000, 0]],
+            durations: vec![vec![0, 300, 500], vec![300, 0, 200], vec![500, 200, 0]],
+            size: 3,
+        };
+
+        let full = groups.expand(&compact);
+
+        assert_eq!(full.size, 4);
+        assert_eq!(full.distance(0, 2), 3000);
+        assert_eq!(full.distance(1, 2), 0);
+        assert_eq!(full.duration(2, 1), 0);
+        assert_eq!(full.duration(2, 3), 200);
+        assert_eq!(full.duration(3, 0), 500);
+    }
+}
@@ -0,0 +1,304 @@
+//! Automatic communication history
+//!
+//! Besides the entries users type in, the customer's communication history
+//! records what the system did on its own: reminder, confirmation and
+//! campaign messages that went out, the confirmation of the route a visit is
+//! planned in, and the completed visit itself. Recording is best effort — a
+//! failure is logged and never fails the send or the update that caused it.
+
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
+use serde_json::json;
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::db::queries::route::RouteStopWithInfo;
+use crate::types::{
+    AutomaticCommunication, CommunicationEntryType, SmsJobRequest, StopType, Visit,
+};
+
+/// Write an automatic entry; errors are logged only
+pub async fn record(pool: &PgPool, user_id: Uuid, entry: &AutomaticCommunication) {
+    if let Err(e) = queries::communication::create_automatic_communication(pool, user_id, entry).await {
+        error!(
+            "Failed to record {} communication for customer {}: {}",
+            entry.entry_type.as_str(),
+            entry.customer_id,
+            e
+        );
+    }
+}
+
+/// Log the confirmation of a route for every customer planned in it
+pub async fn record_route_confirmed(pool: &PgPool, user_id: Uuid, route_id: Uuid) {
+    let route = match queries::route::get_route_by_id(pool, user_id, route_id).await {
+        Ok(Some(route)) => route,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Failed to load route {} for communication log: {}", route_id, e);
+            return;
+        }
+    };
+    let stops = match queries::route::get_route_stops_with_info(pool, route_id).await {
+        Ok(stops) => stops,
+        Err(e) => {
+            warn!("Failed to load stops of route {} for communication log: {}", route_id, e);
+            return;
+        }
+    };
+    for entry in route_confirmed_entries(route_id, route.date, &stops) {
+        record(pool, user_id, &entry).await;
+    }
+}
+
+/// One entry per customer on the route, with its planned arrival
+pub fn route_confirmed_entries(
+    route_id: Uuid,
+    date: NaiveDate,
+    stops: &[RouteStopWithInfo],
+) -> Vec<AutomaticCommunication> {
+    let mut seen = HashSet::new();
+    stops
+        .iter()
+        .filter(|s| s.stop_type == StopType::Customer.as_str())
+        .filter_map(|s| s.customer_id.map(|customer_id| (customer_id, s)))
+        .filter(|(customer_id, _)| seen.insert(*customer_id))
+        .map(|(customer_id, stop)| {
+            let arrival = stop.estimated_arrival.map(|t| t.format("%H:%M").to_string());
+            let content = match &arrival {
+                Some(time) => format!(
+                    "Route for {} confirmed, visit planned at {} (stop {})",
+                    date, time, stop.stop_order
+                ),
+                None => format!("Route for {} confirmed (stop {})", date, stop.stop_order),
+            };
+            AutomaticCommunication {
+                customer_id,
+                revision_id: stop.revision_id,
+                entry_type: CommunicationEntryType::RouteConfirmed,
+                comm_type: "note",
+                subject: Some("Route confirmed".to_string()),
+                content,
+                details: json!({
+                    "routeId": route_id,
+                    "date": date,
+                    "stopOrder": stop.stop_order,
+                    "estimatedArrival": arrival,
+                    "visitId": stop.visit_id,
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Entry for a completed visit: result, times, field notes and follow-up
+pub fn visit_completed_entry(visit: &Visit) -> AutomaticCommunication {
+    let result = visit.result.as_deref().unwrap_or("completed");
+    let mut content = format!("Visit on {} completed: {}", visit.scheduled_date, result);
+    if let Some(notes) = visit.field_notes.as_deref().filter(|n| !n.trim().is_empty()) {
+        content.push_str("\n\n");
+        content.push_str(notes);
+    }
+    if let Some(reason) = visit.follow_up_reason.as_deref().filter(|_| visit.requires_follow_up == Some(true)) {
+        content.push_str(&format!("\n\nFollow-up required: {}", reason));
+    }
+    AutomaticCommunication {
+        customer_id: visit.customer_id,
+        revision_id: None,
+        entry_type: CommunicationEntryType::VisitCompleted,
+        comm_type: "note",
+        subject: Some("Visit completed".to_string()),
+        content,
+        details: json!({
+            "visitId": visit.id,
+            "crewId": visit.crew_id,
+            "result": visit.result,
+            "actualArrival": visit.actual_arrival,
+            "actualDeparture": visit.actual_departure,
+            "requiresFollowUp": visit.requires_follow_up.unwrap_or(false),
+            "followUpReason": visit.follow_up_reason,
+        }),
+    }
+}
+
+/// Entry for an SMS that went out
+pub fn sms_entry(request: &SmsJobRequest) -> AutomaticCommunication {
+    let (customer_id, revision_id, entry_type, content, extra) = match request {
+        SmsJobRequest::Reminder(r) => (
+            r.customer_id,
+            Some(r.revision_id),
+            CommunicationEntryType::ReminderSms,
+            r.message.clone(),
+            json!({}),
+        ),
+        SmsJobRequest::Confirmation(r) => (
+            r.customer_id,
+            Some(r.revision_id),
+            CommunicationEntryType::ConfirmationSms,
+            match &r.time_window {
+                Some(window) => format!("Appointment on {} {} confirmed", r.scheduled_date, window),
+                None => format!("Appointment on {} confirmed", r.scheduled_date),
+            },
+            json!({ "scheduledDate": r.scheduled_date, "timeWindow": r.time_window }),
+        ),
+        SmsJobRequest::Campaign(r) => (
+            r.customer_id,
+            None,
+            CommunicationEntryType::CampaignSms,
+            r.message.clone(),
+            json!({ "campaignId": r.campaign_id }),
+        ),
+    };
+    let mut details = json!({ "recipient": request.phone_number() });
+    if let (Some(details), Some(extra)) = (details.as_object_mut(), extra.as_object()) {
+        details.extend(extra.clone());
+    }
+    AutomaticCommunication {
+        customer_id,
+        revision_id,
+        entry_type,
+        comm_type: "sms",
+        subject: None,
+        content,
+        details,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{SmsCampaignRequest, SmsReminderRequest};
+    use chrono::{NaiveTime, Utc};
+    use sqlx::types::Json;
+
+    fn stop(customer_id: Option<Uuid>, stop_type: &str, order: i32, arrival: Option<NaiveTime>) -> RouteStopWithInfo {
+        RouteStopWithInfo {
+            id: Uuid::new_v4(),
+            route_id: Uuid::nil(),
+            customer_id,
+            visit_id: None,
+            revision_id: None,
+            stop_order: order,
+            estimated_arrival: arrival,
+            estimated_departure: None,
+            distance_from_previous_km: None,
+            duration_from_previous_minutes: None,
+            status: "pending".to_string(),
+            stop_type: stop_type.to_string(),
+            customer_name: None,
+            address: None,
+            customer_lat: None,
+            customer_lng: None,
+            customer_phone: None,
+            customer_email: None,
+            scheduled_date: None,
+            scheduled_time_start: None,
+            scheduled_time_end: None,
+            revision_status: None,
+            break_duration_minutes: None,
+            break_time_start: None,
+            service_duration_minutes: None,
+            override_service_duration_minutes: None,
+            override_travel_duration_minutes: None,
+            notes: None,
+            tasks: Json(vec![]),
+            break_location: None,
+            customer_risk_flag: false,
+        }
+    }
+
+    fn visit() -> Visit {
+        Visit {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            customer_id: Uuid::new_v4(),
+            crew_id: None,
+            device_id: None,
+            scheduled_date: NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(),
+            scheduled_time_start: None,
+            scheduled_time_end: None,
+            status: "completed".to_string(),
+            visit_type: "revision".to_string(),
+            actual_arrival: None,
+            actual_departure: None,
+            result: Some("successful".to_string()),
+            field_notes: None,
+            requires_follow_up: None,
+            follow_up_reason: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn route_confirmation_logs_each_customer_once_and_skips_breaks() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        let date = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let stops = vec![
+            stop(Some(a), "customer", 1, NaiveTime::from_hms_opt(8, 30, 0)),
+            stop(None, "break", 2, None),
+            stop(Some(b), "customer", 3, None),
+            stop(Some(a), "customer", 4, None),
+        ];
+        let entries = route_confirmed_entries(Uuid::nil(), date, &stops);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].customer_id, a);
+        assert_eq!(entries[0].content, "Route for 2026-03-02 confirmed, visit planned at 08:30 (stop 1)");
+        assert_eq!(entries[0].details["estimatedArrival"], "08:30");
+        assert_eq!(entries[1].content, "Route for 2026-03-02 confirmed (stop 3)");
+        assert!(entries.iter().all(|e| e.entry_type == CommunicationEntryType::RouteConfirmed));
+    }
+
+    #[test]
+    fn visit_completion_includes_notes_and_follow_up() {
+        let mut v = visit();
+        v.field_notes = Some("Replaced the filter".to_string());
+        v.requires_follow_up = Some(true);
+        v.follow_up_reason = Some("Order spare part".to_string());
+        let entry = visit_completed_entry(&v);
+        assert_eq!(entry.customer_id, v.customer_id);
+        assert_eq!(entry.entry_type, CommunicationEntryType::VisitCompleted);
+        assert_eq!(
+            entry.content,
+            "Visit on 2026-03-02 completed: successful\n\nReplaced the filter\n\nFollow-up required: Order spare part"
+        );
+        assert_eq!(entry.details["requiresFollowUp"], true);
+    }
+
+    #[test]
+    fn visit_completion_ignores_reason_without_follow_up() {
+        let mut v = visit();
+        v.follow_up_reason = Some("stale".to_string());
+        assert_eq!(visit_completed_entry(&v).content, "Visit on 2026-03-02 completed: successful");
+    }
+
+    #[test]
+    fn sms_entries_carry_type_and_recipient() {
+        let customer_id = Uuid::new_v4();
+        let reminder = sms_entry(&SmsJobRequest::Reminder(SmsReminderRequest {
+            revision_id: Uuid::nil(),
+            customer_id,
+            phone_number: "+420123456789".to_string(),
+            message: "Revize se blíží".to_string(),
+        }));
+        assert_eq!(reminder.entry_type, CommunicationEntryType::ReminderSms);
+        assert_eq!(reminder.comm_type, "sms");
+        assert_eq!(reminder.revision_id, Some(Uuid::nil()));
+        assert_eq!(reminder.details["recipient"], "+420123456789");
+
+        let campaign_id = Uuid::new_v4();
+        let campaign = sms_entry(&SmsJobRequest::Campaign(SmsCampaignRequest {
+            campaign_id,
+            customer_id,
+            phone_number: "+420987654321".to_string(),
+            message: "Sleva".to_string(),
+        }));
+        assert_eq!(campaign.entry_type, CommunicationEntryType::CampaignSms);
+        assert_eq!(campaign.details["campaignId"], campaign_id.to_string());
+        assert_eq!(campaign.details["recipient"], "+420987654321");
+    }
+}
@@ -0,0 +1,242 @@
+#![allow(dead_code)]
+//! Panic capture and crash reports
+//!
+//! A process-wide panic hook turns every panic into a structured crash
+//! report (message, location, backtrace, task name) written as JSON to
+//! `{LOGS_DIR}/crash-reports/` and announced on `sazinka.admin.alerts.crash`.
+//! Tasks started through [`spawn_named`] carry their name into the report;
+//! panics in other tasks are still captured, just without a task name.
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+use uuid::Uuid;
+use crate::subjects;
+
+/// NATS subject receiving crash alerts for the admin UI
+pub const CRASH_ALERT_SUBJECT: &str = subjects::admin::ALERTS_CRASH;
+const CRASH_DIR_NAME: &str = "crash-reports";
+/// Reports kept on disk; older ones are deleted when a new one is written
+pub const MAX_CRASH_REPORTS: usize = 200;
+
+static CRASH_DIR: OnceCell<PathBuf> = OnceCell::new();
+static ALERT_CLIENT: OnceCell<async_nats::Client> = OnceCell::new();
+
+tokio::task_local! {
+    static TASK_NAME: &'static str;
+}
+
+/// Structured report of one panic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    pub version: String,
+    /// Name given to the task in spawn_named, if any
+    pub task: Option<String>,
+    pub thread: Option<String>,
+    pub message: String,
+    /// "file:line:column" of the panic
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+/// Spawn a task whose name is recorded in crash reports if it panics
+pub fn spawn_named<F>(task: &'static str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(TASK_NAME.scope(task, future))
+}
+
+/// Install the panic hook. Reports go to `{logs_dir}/crash-reports`;
+/// the previous hook still runs so panics keep appearing on stderr.
+pub fn install_panic_hook(logs_dir: &str) {
+    let _ = CRASH_DIR.set(Path::new(logs_dir).join(CRASH_DIR_NAME));
+
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let report = CrashReport {
+            id: Uuid::new_v4(),
+            occurred_at: Utc::now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            task: TASK_NAME.try_with(|name| name.to_string()).ok(),
+            thread: std::thread::current().name().map(String::from),
+            message: panic_message(info.payload()),
+            location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            backtrace: Backtrace::force_capture().to_string(),
+        };
+        handle_report(report);
+        previous(info);
+    }));
+}
+
+/// Publish crash alerts through this client from now on
+pub fn set_alert_client(client: async_nats::Client) {
+    let _ = ALERT_CLIENT.set(client);
+}
+
+/// Text of a panic payload (`panic!` produces &str or String)
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+fn handle_report(report: CrashReport) {
+    error!(
+        "Panic in task {}: {} at {}",
+        report.task.as_deref().unwrap_or("<unnamed>"),
+        report.message,
+        report.location.as_deref().unwrap_or("<unknown>"),
+    );
+
+    if let Some(dir) = CRASH_DIR.get() {
+        if let Err(e) = write_report(dir, &report) {
+            warn!("Failed to write crash report: {}", e);
+        }
+    }
+
+    if let (Some(client), Ok(runtime)) = (ALERT_CLIENT.get(), tokio::runtime::Handle::try_current()) {
+        match serde_json::to_vec(&report) {
+            Ok(payload) => {
+                let client = client.clone();
+                runtime.spawn(async move {
+                    if let Err(e) = client.publish(CRASH_ALERT_SUBJECT, payload.into()).await {
+                        warn!("Failed to publish crash alert: {}", e);
+                    }
+                });
+            }
+            Err(e) => warn!("Failed to serialize crash report: {}", e),
+        }
+    }
+}
+
+/// Write a report as `{timestamp}-{id}.json` so file names sort by time,
+/// keeping only the newest [`MAX_CRASH_REPORTS`] so a panic loop cannot
+/// fill the disk
+pub fn write_report(dir: &Path, report: &CrashReport) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let file_name = format!("{}-{}.json", report.occurred_at.format("%Y%m%dT%H%M%S%.3fZ"), report.id);
+    let path = dir.join(file_name);
+    std::fs::write(&path, serde_json::to_vec_pretty(report)?)?;
+    prune_reports(dir, MAX_CRASH_REPORTS);
+    Ok(path)
+}
+
+/// Report files in `dir`, newest first
+fn report_paths(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort_unstable_by(|a, b| b.cmp(a));
+    paths
+}
+
+/// Delete all but the newest `keep` reports
+fn prune_reports(dir: &Path, keep: usize) {
+    for path in report_paths(dir).into_iter().skip(keep) {
+        if let Err(e) = std::fs::remove_file(&path) {
+            warn!("Failed to remove old crash report {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Most recent crash reports, newest first
+pub fn list_reports(logs_dir: &str, limit: usize) -> Vec<CrashReport> {
+    read_reports(&Path::new(logs_dir).join(CRASH_DIR_NAME), limit)
+}
+
+fn read_reports(dir: &Path, limit: usize) -> Vec<CrashReport> {
+    report_paths(dir)
+        .into_iter()
+        .filter_map(|p| std::fs::read(&p).ok())
+        .filter_map(|bytes| serde_json::from_slice::<CrashReport>(&bytes).ok())
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(occurred_at: DateTime<Utc>, message: &str) -> CrashReport {
+        CrashReport {
+            id: Uuid::new_v4(),
+            occurred_at,
+            version: "0.0.0".to_string(),
+            task: Some("customer_create".to_string()),
+            thread: None,
+            message: message.to_string(),
+            location: Some("src/handlers/customer.rs:1:1".to_string()),
+            backtrace: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let static_payload: Box<dyn Any + Send> = Box::new("boom");
+        assert_eq!(panic_message(static_payload.as_ref()), "boom");
+        let owned_payload: Box<dyn Any + Send> = Box::new(format!("index {} out of range", 3));
+        assert_eq!(panic_message(owned_payload.as_ref()), "index 3 out of range");
+        let other_payload: Box<dyn Any + Send> = Box::new(42u8);
+        assert_eq!(panic_message(other_payload.as_ref()), "non-string panic payload");
+    }
+
+    #[test]
+    fn test_reports_listed_newest_first() {
+        let dir = std::env::temp_dir().join(format!("sazinka-crash-{}", Uuid::new_v4()));
+        let older = report(Utc::now() - chrono::Duration::minutes(5), "older");
+        let newer = report(Utc::now(), "newer");
+        write_report(&dir, &older).unwrap();
+        write_report(&dir, &newer).unwrap();
+
+        let listed = read_reports(&dir, 10);
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].message, "newer");
+        assert_eq!(read_reports(&dir, 1).len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_reports() {
+        let dir = std::env::temp_dir().join(format!("sazinka-crash-{}", Uuid::new_v4()));
+        for minutes in (0..5).rev() {
+            let r = report(Utc::now() - chrono::Duration::minutes(minutes), &format!("{} min ago", minutes));
+            write_report(&dir, &r).unwrap();
+        }
+
+        prune_reports(&dir, 2);
+        let listed = read_reports(&dir, 10);
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].message, "0 min ago");
+        assert_eq!(listed[1].message, "1 min ago");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_spawn_named_sets_task_name() {
+        let name = spawn_named("probe", async { TASK_NAME.with(|n| n.to_string()) }).await.unwrap();
+        assert_eq!(name, "probe");
+    }
+}
@@ -0,0 +1,458 @@
+//! Generic REST connector for third-party CRM sync
+//!
+//! Pushes changed customers and visits to an external API in batches and
+//! pulls customer updates back on a schedule. Field names are translated
+//! through the connector's `field_mapping` (local → remote); an empty mapping
+//! sends our own camelCase field names unchanged.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+use crate::db::queries;
+use crate::services::http::{self, HttpService};
+use crate::types::crm_sync::{CrmConnector, CrmSyncConflict, CrmSyncOutcome, RunCrmSyncResponse};
+use crate::types::{Customer, UpdateCustomerRequest};
+
+/// How often the scheduler looks for due connectors
+const SCHEDULER_TICK: Duration = Duration::from_secs(60);
+
+/// Customer fields a pull is allowed to overwrite
+pub const PULLABLE_CUSTOMER_FIELDS: &[&str] = &[
+    "type", "name", "contactPerson", "ico", "dic", "email", "phone",
+    "street", "city", "postalCode", "country", "notes",
+];
+
+/// HTTP client shared by manual and scheduled runs
+pub fn http_client() -> reqwest::Client {
+    http::client(HttpService::Crm)
+}
+
+/// Validate a mapping object: every value must be a non-empty string
+pub fn validate_field_mapping(mapping: &Value) -> Result<()> {
+    let obj = mapping.as_object().ok_or_else(|| anyhow!("fieldMapping must be an object"))?;
+    for (local, remote) in obj {
+        match remote.as_str() {
+            Some(r) if !r.trim().is_empty() => {}
+            _ => return Err(anyhow!("fieldMapping.{} must be a non-empty string", local)),
+        }
+    }
+    Ok(())
+}
+
+fn mapping_pairs(mapping: &Value) -> Vec<(&str, &str)> {
+    mapping
+        .as_object()
+        .map(|obj| {
+            obj.iter()
+                .filter_map(|(local, remote)| remote.as_str().map(|r| (local.as_str(), r)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Translate a local record into the remote shape
+pub fn map_outbound(record: &Value, mapping: &Value) -> Value {
+    let pairs = mapping_pairs(mapping);
+    if pairs.is_empty() {
+        return record.clone();
+    }
+
+    let mut out = Map::new();
+    for (local, remote) in pairs {
+        if let Some(value) = record.get(local) {
+            out.insert(remote.to_string(), value.clone());
+        }
+    }
+    Value::Object(out)
+}
+
+/// Translate a remote record back to local field names
+pub fn map_inbound(record: &Value, mapping: &Value) -> Map<String, Value> {
+    let pairs = mapping_pairs(mapping);
+    let Some(obj) = record.as_object() else {
+        return Map::new();
+    };
+    if pairs.is_empty() {
+        return obj.clone();
+    }
+
+    let mut out = Map::new();
+    for (local, remote) in pairs {
+        if let Some(value) = obj.get(remote) {
+            out.insert(local.to_string(), value.clone());
+        }
+    }
+    out
+}
+
+/// Diff an inbound record against the local customer.
+///
+/// Changed fields become an update unless the customer was also edited
+/// locally since the last pull — then they are reported as conflicts and
+/// the local value wins.
+pub fn build_customer_update(
+    local: &Customer,
+    inbound: &Map<String, Value>,
+    locally_changed: bool,
+) -> Result<(Option<UpdateCustomerRequest>, Vec<CrmSyncConflict>)> {
+    let local_json = serde_json::to_value(local)?;
+    let mut changes = Map::new();
+    let mut conflicts = Vec::new();
+
+    for field in PULLABLE_CUSTOMER_FIELDS {
+        let Some(remote_value) = inbound.get(*field) else {
+            continue;
+        };
+        let local_value = local_json.get(*field).cloned().unwrap_or(Value::Null);
+        if *remote_value == local_value {
+            continue;
+        }
+        if locally_changed {
+            conflicts.push(CrmSyncConflict {
+                entity_id: local.id,
+                field: field.to_string(),
+                local_value,
+                remote_value: remote_value.clone(),
+            });
+        } else {
+            changes.insert(field.to_string(), remote_value.clone());
+        }
+    }
+
+    if changes.is_empty() {
+        return Ok((None, conflicts));
+    }
+
+    changes.insert("id".to_string(), json!(local.id));
+    let update: UpdateCustomerRequest = serde_json::from_value(Value::Object(changes))?;
+    Ok((Some(update), conflicts))
+}
+
+fn endpoint(connector: &CrmConnector, path: &str) -> String {
+    format!("{}{}", connector.base_url.trim_end_matches('/'), path)
+}
+
+fn with_auth(builder: reqwest::RequestBuilder, connector: &CrmConnector) -> reqwest::RequestBuilder {
+    match (&connector.auth_header_name, &connector.auth_header_value) {
+        (Some(name), Some(value)) => builder.header(name.as_str(), value.as_str()),
+        _ => builder,
+    }
+}
+
+async fn push_batch(
+    http: &reqwest::Client,
+    connector: &CrmConnector,
+    entity: &str,
+    records: &[Value],
+) -> Result<()> {
+    let body = json!({ "entity": entity, "records": records });
+    let response = with_auth(http.post(endpoint(connector, &connector.push_path)), connector)
+        .json(&body)
+        .send()
+        .await
+        .context("Failed to send CRM push request")?;
+
+    if !response.status().is_success() {
+        return Err(anyhow!("CRM push returned HTTP {}", response.status()));
+    }
+    Ok(())
+}
+
+async fn push_entity(
+    http: &reqwest::Client,
+    connector: &CrmConnector,
+    entity: &str,
+    records: Vec<Value>,
+    outcome: &mut CrmSyncOutcome,
+) {
+    let batch_size = connector.batch_size.max(1) as usize;
+    for batch in records.chunks(batch_size) {
+        outcome.items_total += batch.len() as i32;
+        match push_batch(http, connector, entity, batch).await {
+            Ok(()) => outcome.items_succeeded += batch.len() as i32,
+            Err(e) => {
+                warn!("CRM connector {} push of {} batch failed: {}", connector.id, entity, e);
+                outcome.items_failed += batch.len() as i32;
+                outcome.error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+/// Push customers and visits changed since the last successful push
+pub async fn push(http: &reqwest::Client, pool: &PgPool, connector: &CrmConnector) -> Result<CrmSyncOutcome> {
+    let since = connector.last_push_at;
+    let customers = queries::crm_sync::list_customers_changed_since(pool, connector.id, connector.user_id, since).await?;
+    let visits = queries::crm_sync::list_visits_changed_since(pool, connector.user_id, since).await?;
+
+    let customer_records = customers
+        .iter()
+        .map(|c| serde_json::to_value(c).map(|v| map_outbound(&v, &connector.field_mapping)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let visit_records = visits
+        .iter()
+        .map(|v| serde_json::to_value(v).map(|v| map_outbound(&v, &connector.field_mapping)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut outcome = CrmSyncOutcome::default();
+    push_entity(http, connector, "customer", customer_records, &mut outcome).await;
+    push_entity(http, connector, "visit", visit_records, &mut outcome).await;
+    Ok(outcome)
+}
+
+async fn fetch_remote_records(http: &reqwest::Client, connector: &CrmConnector, path: &str) -> Result<Vec<Value>> {
+    let build = || {
+        let request = with_auth(http.get(endpoint(connector, path)), connector);
+        match connector.last_pull_at {
+            Some(since) => request.query(&[("updatedSince", since.to_rfc3339())]),
+            None => request,
+        }
+    };
+
+    let response = http::send_idempotent(HttpService::Crm, build)
+        .await
+        .context("Failed to send CRM pull request")?;
+    if !response.status().is_success() {
+        return Err(anyhow!("CRM pull returned HTTP {}", response.status()));
+    }
+
+    // Accept either a bare array or { "records": [...] }
+    let body: Value = response.json().await.context("Failed to parse CRM pull response")?;
+    match body {
+        Value::Array(records) => Ok(records),
+        Value::Object(mut obj) => match obj.remove("records") {
+            Some(Value::Array(records)) => Ok(records),
+            _ => Err(anyhow!("CRM pull response has no records array")),
+        },
+        _ => Err(anyhow!("CRM pull response is not a JSON array")),
+    }
+}
+
+/// Pull remote customer updates and apply non-conflicting changes.
+///
+/// Remote records are matched on our customer id, which the remote side
+/// stores under the field mapped from `id`.
+pub async fn pull(http: &reqwest::Client, pool: &PgPool, connector: &CrmConnector, path: &str) -> Result<CrmSyncOutcome> {
+    let records = fetch_remote_records(http, connector, path).await?;
+    let mut outcome = CrmSyncOutcome { items_total: records.len() as i32, ..Default::default() };
+
+    for record in &records {
+        let inbound = map_inbound(record, &connector.field_mapping);
+        let customer_id = inbound
+            .get("id")
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse::<uuid::Uuid>().ok());
+
+        let Some(customer_id) = customer_id else {
+            outcome.items_failed += 1;
+            continue;
+        };
+
+        let customer = match queries::customer::get_customer(pool, connector.user_id, customer_id).await? {
+            Some(c) => c,
+            None => {
+                outcome.items_failed += 1;
+                continue;
+            }
+        };
+
+        // A version this connector wrote itself is not a local change
+        let pulled_version = queries::crm_sync::get_pulled_version(pool, connector.id, customer_id).await?;
+        let locally_changed = connector.last_pull_at.is_some_and(|t| customer.updated_at > t)
+            && pulled_version != Some(customer.updated_at);
+        let (update, conflicts) = build_customer_update(&customer, &inbound, locally_changed)?;
+        outcome.conflicts.extend(conflicts);
+
+        if let Some(update) = update {
+            if let Some(updated) = queries::customer::update_customer(pool, connector.user_id, &update).await? {
+                queries::crm_sync::mark_customer_pulled(pool, connector.id, updated.id, updated.updated_at).await?;
+            }
+        }
+        outcome.items_succeeded += 1;
+    }
+
+    Ok(outcome)
+}
+
+/// Run a full push + pull cycle for one connector, logging both passes
+pub async fn run_connector(http: &reqwest::Client, pool: &PgPool, connector: &CrmConnector) -> Result<RunCrmSyncResponse> {
+    let push_started = Utc::now();
+    let push_outcome = push(http, pool, connector).await.unwrap_or_else(|e| CrmSyncOutcome {
+        error: Some(e.to_string()),
+        ..Default::default()
+    });
+    queries::crm_sync::insert_sync_log(pool, connector, "push", push_started, &push_outcome).await?;
+    // Only advance the watermark when everything went through, so failed
+    // records are retried on the next run
+    if push_outcome.error.is_none() && push_outcome.items_failed == 0 {
+        queries::crm_sync::set_last_push_at(pool, connector.id, push_started).await?;
+    }
+
+    let pull_outcome = match connector.pull_path.as_deref() {
+        Some(path) => {
+            let pull_started = Utc::now();
+            let outcome = pull(http, pool, connector, path).await.unwrap_or_else(|e| CrmSyncOutcome {
+                error: Some(e.to_string()),
+                ..Default::default()
+            });
+            queries::crm_sync::insert_sync_log(pool, connector, "pull", pull_started, &outcome).await?;
+            // Same rule as the push watermark; changes made on the remote
+            // while we were pulling are picked up next time
+            if outcome.error.is_none() && outcome.items_failed == 0 {
+                queries::crm_sync::set_last_pull_at(pool, connector.id, pull_started).await?;
+            }
+            Some(outcome)
+        }
+        None => None,
+    };
+    queries::crm_sync::set_last_run_at(pool, connector.id, Utc::now()).await?;
+
+    Ok(RunCrmSyncResponse { push: push_outcome, pull: pull_outcome })
+}
+
+/// Background loop running every due connector
+pub async fn run_scheduler(pool: PgPool) {
+    info!("CRM sync scheduler started");
+    let http = http_client();
+    let mut ticker = tokio::time::interval(SCHEDULER_TICK);
+
+    loop {
+        ticker.tick().await;
+
+        let connectors = match queries::crm_sync::list_due_connectors(&pool).await {
+            Ok(c) => c,
+            Err(e) => {
+                error!("Failed to list due CRM connectors: {}", e);
+                continue;
+            }
+        };
+
+        for connector in connectors {
+            match run_connector(&http, &pool, &connector).await {
+                Ok(result) => info!(
+                    "CRM connector {} synced: pushed {}/{}",
+                    connector.id, result.push.items_succeeded, result.push.items_total
+                ),
+                Err(e) => error!("CRM connector {} sync failed: {}", connector.id, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CustomerType;
+    use uuid::Uuid;
+
+    fn customer() -> Customer {
+        Customer {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            customer_type: CustomerType::Person,
+            name: Some("Jan Novák".into()),
+            contact_person: None,
+            ico: None,
+            dic: None,
+            email: Some("jan@example.cz".into()),
+            phone: None,
+            phone_raw: None,
+            street: None,
+            city: Some("Brno".into()),
+            postal_code: None,
+            country: None,
+            lat: None,
+            lng: None,
+            geocode_status: "pending".into(),
+            notes: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            is_abandoned: false,
+            deleted_at: None,
+            parent_customer_id: None,
+            language: None,
+            customer_code: None,
+            acquisition_source: None,
+            referrer_customer_id: None,
+            lead_status: None,
+            lead_lost_reason: None,
+            lead_status_changed_at: None,
+            risk_score: None,
+            risk_flag: None,
+            coverage_warning: None,
+        }
+    }
+
+    #[test]
+    fn test_map_outbound_renames_and_drops_unmapped() {
+        let record = json!({"id": "1", "name": "Jan", "email": "a@b.cz"});
+        let mapping = json!({"id": "externalRef", "name": "fullName"});
+        assert_eq!(map_outbound(&record, &mapping), json!({"externalRef": "1", "fullName": "Jan"}));
+    }
+
+    #[test]
+    fn test_map_outbound_empty_mapping_passes_through() {
+        let record = json!({"id": "1", "name": "Jan"});
+        assert_eq!(map_outbound(&record, &json!({})), record);
+    }
+
+    #[test]
+    fn test_map_inbound_is_inverse() {
+        let mapping = json!({"id": "externalRef", "name": "fullName"});
+        let inbound = map_inbound(&json!({"externalRef": "1", "fullName": "Jan", "other": 5}), &mapping);
+        assert_eq!(Value::Object(inbound), json!({"id": "1", "name": "Jan"}));
+    }
+
+    #[test]
+    fn test_validate_field_mapping() {
+        assert!(validate_field_mapping(&json!({"name": "fullName"})).is_ok());
+        assert!(validate_field_mapping(&json!({"name": ""})).is_err());
+        assert!(validate_field_mapping(&json!({"name": 1})).is_err());
+        assert!(validate_field_mapping(&json!(["name"])).is_err());
+    }
+
+    #[test]
+    fn test_build_customer_update_applies_changes() {
+        let local = customer();
+        let mut inbound = Map::new();
+        inbound.insert("email".into(), json!("novy@example.cz"));
+        inbound.insert("city".into(), json!("Brno"));
+
+        let (update, conflicts) = build_customer_update(&local, &inbound, false).unwrap();
+        let update = update.unwrap();
+        assert_eq!(update.id, local.id);
+        assert_eq!(update.email.as_deref(), Some("novy@example.cz"));
+        assert!(update.city.is_none());
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_build_customer_update_reports_conflicts_when_locally_changed() {
+        let local = customer();
+        let mut inbound = Map::new();
+        inbound.insert("email".into(), json!("novy@example.cz"));
+
+        let (update, conflicts) = build_customer_update(&local, &inbound, true).unwrap();
+        assert!(update.is_none());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field, "email");
+        assert_eq!(conflicts[0].local_value, json!("jan@example.cz"));
+    }
+
+    #[test]
+    fn test_build_customer_update_ignores_non_pullable_fields() {
+        let local = customer();
+        let mut inbound = Map::new();
+        inbound.insert("lat".into(), json!(49.0));
+        inbound.insert("userId".into(), json!(Uuid::new_v4()));
+
+        let (update, conflicts) = build_customer_update(&local, &inbound, false).unwrap();
+        assert!(update.is_none());
+        assert!(conflicts.is_empty());
+    }
+}
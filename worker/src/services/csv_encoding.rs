@@ -0,0 +1,126 @@
+//! Character encoding of imported CSV files
+//!
+//! Excel and most Czech accounting software export CSV in Windows-1250, so
+//! import files arrive as raw bytes and are transcoded to UTF-8 before
+//! parsing. A BOM decides outright; valid UTF-8 is taken as is; anything
+//! else goes to chardetng with the `.cz` hint, which tells Windows-1250 from
+//! ISO-8859-2 and the Western code pages.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+
+use crate::types::import::{ImportIssue, ImportIssueCode, ImportIssueLevel};
+
+/// Name reported for text that arrived already decoded
+pub const UTF_8_NAME: &str = "UTF-8";
+
+/// CSV text after transcoding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedCsv {
+    pub text: String,
+    /// WHATWG name of the detected encoding, e.g. "windows-1250"
+    pub encoding: &'static str,
+}
+
+/// Detect the encoding of a file and transcode it to UTF-8.
+/// Undecodable bytes become U+FFFD, see [`replacement_issues`].
+pub fn decode_csv(bytes: &[u8]) -> DecodedCsv {
+    let (encoding, body) = match Encoding::for_bom(bytes) {
+        Some((encoding, bom_len)) => (encoding, &bytes[bom_len..]),
+        None if std::str::from_utf8(bytes).is_ok() => (UTF_8, bytes),
+        None => {
+            let mut detector = EncodingDetector::new();
+            detector.feed(bytes, true);
+            (detector.guess(Some(b"cz"), true), bytes)
+        }
+    };
+    let (text, _) = encoding.decode_without_bom_handling(body);
+    DecodedCsv { text: text.into_owned(), encoding: encoding.name() }
+}
+
+/// CSV of an import request: the raw file when the client sent it
+/// (`csvBase64`), otherwise the text it decoded itself
+pub fn decode_payload(csv_content: &str, csv_base64: Option<&str>) -> Result<DecodedCsv> {
+    match csv_base64 {
+        Some(encoded) => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(encoded.trim())
+                .context("Invalid base64 file content")?;
+            Ok(decode_csv(&bytes))
+        }
+        None => Ok(DecodedCsv {
+            text: csv_content.trim_start_matches('\u{feff}').to_string(),
+            encoding: UTF_8_NAME,
+        }),
+    }
+}
+
+/// A warning for every line containing replacement characters, numbered
+/// like the other issues (header is line 1)
+pub fn replacement_issues(text: &str) -> Vec<ImportIssue> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| line.contains(char::REPLACEMENT_CHARACTER))
+        .map(|(idx, line)| ImportIssue {
+            row_number: (idx + 1) as i32,
+            level: ImportIssueLevel::Warning,
+            code: ImportIssueCode::EncodingError,
+            field: String::new(),
+            message: "Row contains characters that could not be decoded".to_string(),
+            original_value: Some(line.to_string()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::WINDOWS_1250;
+
+    const CZECH: &str = "jméno;město\nŽluťoučký kůň;Říčany\nPavel Čermák;Ústí nad Labem\n";
+
+    #[test]
+    fn test_utf8_passes_through() {
+        let decoded = decode_csv(CZECH.as_bytes());
+        assert_eq!(decoded.encoding, "UTF-8");
+        assert_eq!(decoded.text, CZECH);
+
+        let mut with_bom = vec![0xEF, 0xBB, 0xBF];
+        with_bom.extend_from_slice(CZECH.as_bytes());
+        assert_eq!(decode_csv(&with_bom).text, CZECH);
+    }
+
+    #[test]
+    fn test_detects_windows_1250() {
+        let (bytes, _, _) = WINDOWS_1250.encode(CZECH);
+        assert!(std::str::from_utf8(&bytes).is_err());
+
+        let decoded = decode_csv(&bytes);
+        assert_eq!(decoded.encoding, "windows-1250");
+        assert_eq!(decoded.text, CZECH);
+        assert!(replacement_issues(&decoded.text).is_empty());
+    }
+
+    #[test]
+    fn test_decode_payload() {
+        let (bytes, _, _) = WINDOWS_1250.encode(CZECH);
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        assert_eq!(decode_payload("", Some(&encoded)).unwrap().text, CZECH);
+        assert!(decode_payload("", Some("not base64!")).is_err());
+
+        let plain = decode_payload("\u{feff}a;b\n", None).unwrap();
+        assert_eq!(plain.text, "a;b\n");
+        assert_eq!(plain.encoding, UTF_8_NAME);
+    }
+
+    #[test]
+    fn test_replacement_issues() {
+        let issues = replacement_issues("name;city\nNovák;Brno\nNov\u{fffd}k;Praha\n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].row_number, 3);
+        assert!(matches!(issues[0].level, ImportIssueLevel::Warning));
+        assert!(matches!(issues[0].code, ImportIssueCode::EncodingError));
+    }
+}
@@ -0,0 +1,110 @@
+//! Customer risk scoring
+//!
+//! Scores are stored on the customer and recomputed when an incident is
+//! recorded or removed, when a visit is completed and when the weights
+//! change. A daily sweep lets occurrences age out of the lookback window.
+//! Every change of score or flag is logged.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{Months, NaiveDate, Utc};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::types::customer_risk::{CustomerRiskScore, CustomerRiskSettings, RISK_REASON_AGED_OUT};
+
+const SWEEP_TICK: Duration = Duration::from_secs(24 * 3600);
+
+/// First day of the lookback window
+fn window_start(settings: &CustomerRiskSettings, today: NaiveDate) -> NaiveDate {
+    today
+        .checked_sub_months(Months::new(settings.lookback_months.max(0) as u32))
+        .unwrap_or(NaiveDate::MIN)
+}
+
+async fn refresh_with(
+    pool: &PgPool,
+    settings: &CustomerRiskSettings,
+    user_id: Uuid,
+    customer_id: Uuid,
+    reason: &str,
+) -> Result<(CustomerRiskScore, bool)> {
+    let since = window_start(settings, Utc::now().date_naive());
+    let counts = queries::customer_risk::count_occurrences(pool, user_id, customer_id, since).await?;
+    let risk_score = settings.score(&counts);
+    let risk_flag = settings.is_flagged(risk_score);
+    let changed = queries::customer_risk::set_score(pool, user_id, customer_id, risk_score, risk_flag, reason).await?;
+    Ok((CustomerRiskScore { risk_score, risk_flag }, changed))
+}
+
+/// Recompute the score of a customer
+pub async fn refresh(pool: &PgPool, user_id: Uuid, customer_id: Uuid, reason: &str) -> Result<CustomerRiskScore> {
+    let settings = queries::customer_risk::get_settings(pool, user_id).await?;
+    let (score, _) = refresh_with(pool, &settings, user_id, customer_id, reason).await?;
+    Ok(score)
+}
+
+/// Recompute the score of a customer in the background
+pub fn refresh_in_background(pool: &PgPool, user_id: Uuid, customer_id: Uuid, reason: &'static str) {
+    let pool = pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = refresh(&pool, user_id, customer_id, reason).await {
+            warn!("Failed to refresh risk score of customer {}: {}", customer_id, e);
+        }
+    });
+}
+
+/// Recompute every scorable customer of one user, or of everyone.
+/// Returns how many scores changed.
+pub async fn refresh_all(pool: &PgPool, user_id: Option<Uuid>, reason: &str) -> Result<usize> {
+    let mut settings: Option<(Uuid, CustomerRiskSettings)> = None;
+    let mut changed = 0;
+
+    for (owner_id, customer_id) in queries::customer_risk::list_scorable_customers(pool, user_id).await? {
+        if settings.as_ref().map(|(id, _)| *id) != Some(owner_id) {
+            settings = Some((owner_id, queries::customer_risk::get_settings(pool, owner_id).await?));
+        }
+        let Some((_, ref owner_settings)) = settings else { continue };
+
+        match refresh_with(pool, owner_settings, owner_id, customer_id, reason).await {
+            Ok((_, true)) => changed += 1,
+            Ok(_) => {}
+            Err(e) => warn!("Failed to refresh risk score of customer {}: {}", customer_id, e),
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Run the aging sweep once a day
+pub async fn run_sweeper(pool: PgPool) {
+    info!("Customer risk sweep started");
+    let mut ticker = tokio::time::interval(SWEEP_TICK);
+
+    loop {
+        ticker.tick().await;
+
+        match refresh_all(&pool, None, RISK_REASON_AGED_OUT).await {
+            Ok(0) => {}
+            Ok(count) => info!("Updated {} customer risk scores", count),
+            Err(e) => error!("Failed to sweep customer risk scores: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_start() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 31).unwrap();
+        let settings = CustomerRiskSettings { lookback_months: 1, ..Default::default() };
+        assert_eq!(window_start(&settings, today), NaiveDate::from_ymd_opt(2026, 2, 28).unwrap());
+        let settings = CustomerRiskSettings { lookback_months: 24, ..Default::default() };
+        assert_eq!(window_start(&settings, today), NaiveDate::from_ymd_opt(2024, 3, 31).unwrap());
+    }
+}
@@ -0,0 +1,375 @@
+#![allow(dead_code)]
+//! Request/response recording for debugging
+//!
+//! When enabled for a set of subjects (at runtime, from the admin UI or
+//! `DEBUG_RECORD_SUBJECTS`), the recorder listens on those subjects next to
+//! the regular handlers, samples requests and pairs them with the replies
+//! seen on `_INBOX.>`. Payloads are redacted before they are stored in a
+//! bounded in-memory ring buffer — nothing is written to disk.
+//!
+//! Recordings are per worker instance: each worker records only the requests
+//! it handled, and the admin get/set/clear requests reach whichever worker
+//! answers first. Run a single worker while recording.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_nats::{Client, Message};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use parking_lot::Mutex;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::watch;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::subjects;
+
+/// Keys whose values never enter a recording (compared case-insensitively)
+pub const DEFAULT_REDACT_KEYS: &[&str] = &[
+    "token", "accessToken", "refreshToken", "password", "newPassword", "currentPassword",
+    "secret", "apiKey", "authorization", "name", "email", "phone", "phoneRaw", "contactPerson",
+    "street", "ico", "dic", "notes", "iban", "accountNumber",
+];
+const REDACTED: &str = "[redacted]";
+pub const DEFAULT_CAPACITY: usize = 200;
+const MAX_CAPACITY: usize = 2000;
+/// Requests whose reply has not arrived within this time are no longer paired
+const REPLY_TIMEOUT: Duration = Duration::from_secs(120);
+const INBOX_WILDCARD: &str = "_INBOX.>";
+
+/// What the recorder captures
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingConfig {
+    /// NATS subjects to record; wildcards allowed. Empty disables recording.
+    pub subjects: Vec<String>,
+    /// Fraction of requests recorded (0.0–1.0)
+    pub sample_rate: f64,
+    /// Ring buffer size
+    pub capacity: usize,
+    /// Keys redacted on top of DEFAULT_REDACT_KEYS
+    #[serde(default)]
+    pub redact_keys: Vec<String>,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            subjects: Vec::new(),
+            sample_rate: 1.0,
+            capacity: DEFAULT_CAPACITY,
+            redact_keys: Vec::new(),
+        }
+    }
+}
+
+impl RecordingConfig {
+    /// Initial config from DEBUG_RECORD_SUBJECTS (comma-separated)
+    pub fn from_env() -> Self {
+        Self::from_subject_list(&std::env::var("DEBUG_RECORD_SUBJECTS").unwrap_or_default())
+    }
+
+    /// Config for a comma-separated subject list; subjects the admin UI
+    /// would refuse are logged and dropped
+    fn from_subject_list(list: &str) -> Self {
+        let subjects = list
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .filter(|s| match validate_subject(s) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("Ignoring DEBUG_RECORD_SUBJECTS entry: {}", e);
+                    false
+                }
+            })
+            .map(String::from)
+            .collect();
+        Self { subjects, ..Self::default() }.validated().unwrap_or_default()
+    }
+
+    /// Validate and normalize a config set by an admin
+    pub fn validated(mut self) -> Result<Self, String> {
+        if !(0.0..=1.0).contains(&self.sample_rate) {
+            return Err("sampleRate must be between 0 and 1".to_string());
+        }
+        if self.capacity == 0 || self.capacity > MAX_CAPACITY {
+            return Err(format!("capacity must be between 1 and {}", MAX_CAPACITY));
+        }
+        self.subjects = self.subjects.iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+        for subject in &self.subjects {
+            validate_subject(subject)?;
+        }
+        self.subjects.dedup();
+        Ok(self)
+    }
+}
+
+fn validate_subject(subject: &str) -> Result<(), String> {
+    if !subject.starts_with(subjects::PREFIX) {
+        return Err(format!("only sazinka.* subjects can be recorded: {}", subject));
+    }
+    Ok(())
+}
+
+/// One recorded request and its reply
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recording {
+    pub id: Uuid,
+    pub subject: String,
+    pub recorded_at: DateTime<Utc>,
+    pub request: Value,
+    pub response: Option<Value>,
+    pub duration_ms: Option<u64>,
+}
+
+/// Replace the values of sensitive keys anywhere in a JSON document
+pub fn redact(value: &mut Value, extra_keys: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, val) in map.iter_mut() {
+                let sensitive = DEFAULT_REDACT_KEYS.iter().any(|k| k.eq_ignore_ascii_case(key))
+                    || extra_keys.iter().any(|k| k.eq_ignore_ascii_case(key));
+                if sensitive && !val.is_null() {
+                    *val = Value::String(REDACTED.to_string());
+                } else {
+                    redact(val, extra_keys);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact(item, extra_keys)),
+        _ => {}
+    }
+}
+
+/// Payload as redacted JSON; non-JSON payloads are summarized by size
+fn redacted_payload(payload: &[u8], extra_keys: &[String]) -> Value {
+    match serde_json::from_slice::<Value>(payload) {
+        Ok(mut value) => {
+            redact(&mut value, extra_keys);
+            value
+        }
+        Err(_) => Value::String(format!("<{} bytes, not JSON>", payload.len())),
+    }
+}
+
+struct Pending {
+    recording_id: Uuid,
+    started: Instant,
+}
+
+/// Runtime-configurable recorder with a bounded buffer
+pub struct DebugRecorder {
+    config: watch::Sender<RecordingConfig>,
+    recordings: Mutex<VecDeque<Recording>>,
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+impl DebugRecorder {
+    pub fn new(config: RecordingConfig) -> Self {
+        let (config, _) = watch::channel(config);
+        Self {
+            config,
+            recordings: Mutex::new(VecDeque::new()),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> RecordingConfig {
+        self.config.borrow().clone()
+    }
+
+    /// Apply a new config; the listener re-subscribes
+    pub fn set_config(&self, config: RecordingConfig) {
+        let capacity = config.capacity;
+        self.config.send_replace(config);
+        let mut recordings = self.recordings.lock();
+        while recordings.len() > capacity {
+            recordings.pop_front();
+        }
+    }
+
+    /// Recordings, newest first
+    pub fn recordings(&self) -> Vec<Recording> {
+        self.recordings.lock().iter().rev().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.recordings.lock().clear();
+        self.pending.lock().clear();
+    }
+
+    /// Record a request seen on a recorded subject (subject to sampling)
+    pub fn record_request(&self, subject: &str, reply: Option<&str>, payload: &[u8]) {
+        let config = self.config();
+        if config.sample_rate < 1.0 && rand::thread_rng().gen::<f64>() >= config.sample_rate {
+            return;
+        }
+
+        let recording = Recording {
+            id: Uuid::new_v4(),
+            subject: subject.to_string(),
+            recorded_at: Utc::now(),
+            request: redacted_payload(payload, &config.redact_keys),
+            response: None,
+            duration_ms: None,
+        };
+        if let Some(reply) = reply {
+            let mut pending = self.pending.lock();
+            pending.retain(|_, p| p.started.elapsed() < REPLY_TIMEOUT);
+            pending.insert(reply.to_string(), Pending { recording_id: recording.id, started: Instant::now() });
+        }
+
+        let mut recordings = self.recordings.lock();
+        while recordings.len() >= config.capacity {
+            recordings.pop_front();
+        }
+        recordings.push_back(recording);
+    }
+
+    /// Attach a reply to its request, if that request was recorded
+    pub fn record_reply(&self, reply_subject: &str, payload: &[u8]) {
+        let Some(pending) = self.pending.lock().remove(reply_subject) else {
+            return;
+        };
+        let extra_keys = self.config.borrow().redact_keys.clone();
+        let mut recordings = self.recordings.lock();
+        if let Some(recording) = recordings.iter_mut().find(|r| r.id == pending.recording_id) {
+            recording.response = Some(redacted_payload(payload, &extra_keys));
+            recording.duration_ms = Some(pending.started.elapsed().as_millis() as u64);
+        }
+    }
+
+    fn handle_message(&self, msg: Message) {
+        let subject = msg.subject.as_str();
+        if subject.starts_with("_INBOX.") {
+            self.record_reply(subject, &msg.payload);
+        } else {
+            self.record_request(subject, msg.reply.as_deref(), &msg.payload);
+        }
+    }
+}
+
+/// Listen on the configured subjects, re-subscribing whenever the config changes
+pub async fn run(client: Client, recorder: Arc<DebugRecorder>) -> Result<()> {
+    let mut changes = recorder.config.subscribe();
+
+    loop {
+        let subjects = changes.borrow_and_update().subjects.clone();
+        if subjects.is_empty() {
+            changes.changed().await?;
+            continue;
+        }
+
+        info!("Debug recording enabled for: {}", subjects.join(", "));
+        let mut subscribers = Vec::with_capacity(subjects.len() + 1);
+        for subject in subjects.iter().map(String::as_str).chain([INBOX_WILDCARD]) {
+            match client.subscribe(subject.to_string()).await {
+                Ok(subscriber) => subscribers.push(subscriber),
+                Err(e) => warn!("Debug recorder failed to subscribe to {}: {}", subject, e),
+            }
+        }
+        let mut messages = stream::select_all(subscribers);
+
+        loop {
+            tokio::select! {
+                changed = changes.changed() => {
+                    changed?;
+                    break;
+                }
+                Some(msg) = messages.next() => recorder.handle_message(msg),
+            }
+        }
+        // Dropping the stream unsubscribes before the next round
+        drop(messages);
+        info!("Debug recording configuration changed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_nested() {
+        let mut value = json!({
+            "token": "eyJ...",
+            "payload": {
+                "name": "Jan",
+                "city": "Brno",
+                "email": "jan@example.cz",
+                "contacts": [{"phone": "+420602000000"}],
+                "notes": null,
+                "secretCode": "x"
+            }
+        });
+        redact(&mut value, &["secretCode".to_string()]);
+        assert_eq!(value["token"], REDACTED);
+        assert_eq!(value["payload"]["name"], REDACTED);
+        assert_eq!(value["payload"]["city"], "Brno");
+        assert_eq!(value["payload"]["email"], REDACTED);
+        assert_eq!(value["payload"]["contacts"][0]["phone"], REDACTED);
+        assert!(value["payload"]["notes"].is_null());
+        assert_eq!(value["payload"]["secretCode"], REDACTED);
+    }
+
+    #[test]
+    fn test_request_paired_with_reply() {
+        let recorder = DebugRecorder::new(RecordingConfig {
+            subjects: vec!["sazinka.customer.create".to_string()],
+            ..RecordingConfig::default()
+        });
+        recorder.record_request("sazinka.customer.create", Some("_INBOX.abc"), br#"{"token":"t","payload":{}}"#);
+        recorder.record_reply("_INBOX.abc", br#"{"payload":{"id":"1"}}"#);
+        recorder.record_reply("_INBOX.other", br#"{}"#);
+
+        let recordings = recorder.recordings();
+        assert_eq!(recordings.len(), 1);
+        assert_eq!(recordings[0].request["token"], REDACTED);
+        assert_eq!(recordings[0].response.as_ref().unwrap()["payload"]["id"], "1");
+        assert!(recordings[0].duration_ms.is_some());
+    }
+
+    #[test]
+    fn test_ring_buffer_capacity() {
+        let recorder = DebugRecorder::new(RecordingConfig { capacity: 2, ..RecordingConfig::default() });
+        for i in 0..3 {
+            recorder.record_request("sazinka.ping", None, format!(r#"{{"n":{}}}"#, i).as_bytes());
+        }
+        let recordings = recorder.recordings();
+        assert_eq!(recordings.len(), 2);
+        assert_eq!(recordings[0].request["n"], 2);
+
+        recorder.set_config(RecordingConfig { capacity: 1, ..RecordingConfig::default() });
+        assert_eq!(recorder.recordings().len(), 1);
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let config = RecordingConfig {
+            subjects: vec![" sazinka.customer.* ".to_string(), String::new()],
+            ..RecordingConfig::default()
+        };
+        assert_eq!(config.validated().unwrap().subjects, vec!["sazinka.customer.*"]);
+
+        let foreign = RecordingConfig { subjects: vec![">".to_string()], ..RecordingConfig::default() };
+        assert!(foreign.validated().is_err());
+
+        let rate = RecordingConfig { sample_rate: 1.5, ..RecordingConfig::default() };
+        assert!(rate.validated().is_err());
+    }
+
+    #[test]
+    fn test_env_subjects_drop_invalid_entries() {
+        let config = RecordingConfig::from_subject_list(" sazinka.customer.create, >, ,sazinka.route.*");
+        assert_eq!(config.subjects, vec!["sazinka.customer.create", "sazinka.route.*"]);
+        assert!(RecordingConfig::from_subject_list("").subjects.is_empty());
+    }
+}
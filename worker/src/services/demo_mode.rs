@@ -0,0 +1,361 @@
+//! Demo mode
+//!
+//! Sessions recording screenshots or training videos send `demoMode` in the
+//! request envelope. List and get handlers then replace customer names,
+//! contacts and addresses with pseudonyms derived from the customer id, so
+//! the same customer keeps the same pseudonym on every screen. Free-text
+//! notes and message bodies are dropped, they may name the customer.
+//!
+//! Coordinates are deliberately kept: route geometry, distances and ETAs on
+//! the same screens are computed from them, and jittered points would
+//! contradict them. A map zoomed in far enough still shows the real house,
+//! so recordings over production data should keep maps zoomed out.
+//!
+//! The dispatch board feed is shared by all sessions of an account, so demo
+//! sessions get a feed of their own carrying pseudonymized deltas
+//! (`subjects::board::demo_feed`).
+
+use uuid::Uuid;
+
+use crate::db::queries::route::RouteStopWithInfo;
+use crate::types::customer_hierarchy::{CustomerBranch, CustomerHierarchyResponse, HierarchyMemberSummary};
+use crate::types::customer_site::CustomerSite;
+use crate::types::dispatch_board::{BoardDelta, BoardRoute, BoardSnapshot, BoardStop};
+use crate::types::quote::{QuoteDetail, QuoteListItem};
+use crate::types::{
+    CallQueueItem, Communication, Customer, CustomerListItem, InboxItem, InboxResponse, Revision, VisitWithCustomer,
+};
+
+const FIRST_NAMES: &[&str] = &[
+    "Jan", "Petr", "Jana", "Eva", "Martin", "Lucie", "Tomáš", "Hana", "Pavel", "Věra", "Jiří", "Alena", "Karel",
+    "Marie", "David", "Tereza",
+];
+
+const SURNAMES: &[&str] = &[
+    "Ukázka", "Vzorek", "Příkladný", "Zkušební", "Modelový", "Pokusný", "Náhradní", "Cvičný",
+];
+
+const STREETS: &[&str] = &["Ukázková", "Vzorová", "Modelová", "Zkušební", "Cvičná", "Náhradní"];
+
+/// City with its postal code
+const CITIES: &[(&str, &str)] = &[
+    ("Demoves", "10000"),
+    ("Ukázkov", "25001"),
+    ("Vzorná Lhota", "37001"),
+    ("Modelín", "50002"),
+    ("Příkladice", "60200"),
+];
+
+/// Stand-in contact data of a customer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pseudonym {
+    pub name: String,
+    pub contact_person: String,
+    pub email: String,
+    pub phone: String,
+    pub street: String,
+    pub city: String,
+    pub postal_code: String,
+}
+
+impl Pseudonym {
+    /// One-line address as route and board stops show it
+    pub fn address(&self) -> String {
+        format!("{}, {}", self.street, self.city)
+    }
+}
+
+fn pick<T: Copy>(items: &[T], seed: u128, shift: u32) -> T {
+    items[((seed >> shift) % items.len() as u128) as usize]
+}
+
+/// Pseudonym of a customer; the same id always gets the same pseudonym.
+/// Joined rows (revisions, visits) do not carry the customer type, so
+/// companies get a person's name too and look the same on every screen.
+pub fn pseudonym(customer_id: Uuid) -> Pseudonym {
+    let seed = customer_id.as_u128();
+    let (city, postal_code) = pick(CITIES, seed, 16);
+    let short = &customer_id.simple().to_string()[..6];
+    let number = (seed >> 24) % 1_000_000;
+
+    Pseudonym {
+        name: format!("{} {}", pick(FIRST_NAMES, seed, 0), pick(SURNAMES, seed, 8)),
+        contact_person: format!("{} {}", pick(FIRST_NAMES, seed, 40), pick(SURNAMES, seed, 44)),
+        email: format!("zakaznik-{}@example.com", short),
+        phone: format!("+420 600 {:03} {:03}", number / 1000, number % 1000),
+        street: format!("{} {}", pick(STREETS, seed, 32), (seed >> 48) % 150 + 1),
+        city: city.to_string(),
+        postal_code: postal_code.to_string(),
+    }
+}
+
+/// Replace what a value shows of its customer(s)
+pub trait Pseudonymize {
+    fn pseudonymize(&mut self);
+}
+
+impl<T: Pseudonymize> Pseudonymize for Vec<T> {
+    fn pseudonymize(&mut self) {
+        self.iter_mut().for_each(Pseudonymize::pseudonymize);
+    }
+}
+
+/// Set `field` to the pseudonym value unless it is empty
+fn replace(field: &mut Option<String>, value: &str) {
+    if field.is_some() {
+        *field = Some(value.to_string());
+    }
+}
+
+impl Pseudonymize for Customer {
+    fn pseudonymize(&mut self) {
+        let p = pseudonym(self.id);
+        replace(&mut self.name, &p.name);
+        replace(&mut self.contact_person, &p.contact_person);
+        replace(&mut self.email, &p.email);
+        replace(&mut self.phone, &p.phone);
+        replace(&mut self.phone_raw, &p.phone.replace(' ', ""));
+        replace(&mut self.street, &p.street);
+        replace(&mut self.city, &p.city);
+        replace(&mut self.postal_code, &p.postal_code);
+        replace(&mut self.ico, "00000000");
+        self.dic = self.dic.as_ref().map(|_| "CZ00000000".to_string());
+        self.notes = None;
+    }
+}
+
+impl Pseudonymize for CustomerListItem {
+    fn pseudonymize(&mut self) {
+        let p = pseudonym(self.id);
+        replace(&mut self.name, &p.name);
+        replace(&mut self.email, &p.email);
+        replace(&mut self.phone, &p.phone);
+        replace(&mut self.street, &p.street);
+        replace(&mut self.city, &p.city);
+        replace(&mut self.postal_code, &p.postal_code);
+        if let Some(parent_id) = self.parent_customer_id {
+            replace(&mut self.parent_name, &pseudonym(parent_id).name);
+        }
+    }
+}
+
+impl Pseudonymize for Revision {
+    fn pseudonymize(&mut self) {
+        let p = pseudonym(self.customer_id);
+        replace(&mut self.customer_name, &p.name);
+        replace(&mut self.customer_phone, &p.phone);
+        replace(&mut self.customer_street, &p.street);
+        replace(&mut self.customer_city, &p.city);
+        replace(&mut self.customer_postal_code, &p.postal_code);
+    }
+}
+
+impl Pseudonymize for CallQueueItem {
+    fn pseudonymize(&mut self) {
+        let p = pseudonym(self.customer_id);
+        replace(&mut self.customer_name, &p.name);
+        replace(&mut self.customer_phone, &p.phone);
+        replace(&mut self.customer_email, &p.email);
+        replace(&mut self.customer_street, &p.street);
+        replace(&mut self.customer_city, &p.city);
+        replace(&mut self.customer_postal_code, &p.postal_code);
+    }
+}
+
+impl Pseudonymize for VisitWithCustomer {
+    fn pseudonymize(&mut self) {
+        let p = pseudonym(self.customer_id);
+        replace(&mut self.customer_name, &p.name);
+        replace(&mut self.customer_street, &p.street);
+        replace(&mut self.customer_city, &p.city);
+    }
+}
+
+impl Pseudonymize for RouteStopWithInfo {
+    fn pseudonymize(&mut self) {
+        let Some(customer_id) = self.customer_id else {
+            return;
+        };
+        let p = pseudonym(customer_id);
+        replace(&mut self.customer_name, &p.name);
+        replace(&mut self.address, &p.address());
+        replace(&mut self.customer_phone, &p.phone);
+        replace(&mut self.customer_email, &p.email);
+        self.notes = None;
+    }
+}
+
+impl Pseudonymize for InboxItem {
+    fn pseudonymize(&mut self) {
+        let p = pseudonym(self.id);
+        replace(&mut self.name, &p.name);
+        replace(&mut self.phone, &p.phone);
+        replace(&mut self.email, &p.email);
+        replace(&mut self.street, &p.street);
+        replace(&mut self.city, &p.city);
+        replace(&mut self.postal_code, &p.postal_code);
+        self.next_action_note = None;
+    }
+}
+
+impl Pseudonymize for InboxResponse {
+    fn pseudonymize(&mut self) {
+        self.items.pseudonymize();
+    }
+}
+
+impl Pseudonymize for CustomerSite {
+    fn pseudonymize(&mut self) {
+        // The primary billing address mirrors the customer row; other
+        // addresses get pseudonyms of their own
+        let p = pseudonym(if self.is_customer_billing() { self.customer_id } else { self.id });
+        replace(&mut self.street, &p.street);
+        replace(&mut self.city, &p.city);
+        replace(&mut self.postal_code, &p.postal_code);
+        self.notes = None;
+    }
+}
+
+impl Pseudonymize for CustomerBranch {
+    fn pseudonymize(&mut self) {
+        let p = pseudonym(self.id);
+        replace(&mut self.name, &p.name);
+        replace(&mut self.street, &p.street);
+        replace(&mut self.city, &p.city);
+    }
+}
+
+impl Pseudonymize for CustomerHierarchyResponse {
+    fn pseudonymize(&mut self) {
+        self.parent.pseudonymize();
+        self.branches.pseudonymize();
+    }
+}
+
+impl Pseudonymize for HierarchyMemberSummary {
+    fn pseudonymize(&mut self) {
+        let p = pseudonym(self.customer_id);
+        replace(&mut self.name, &p.name);
+        replace(&mut self.city, &p.city);
+    }
+}
+
+impl Pseudonymize for BoardStop {
+    fn pseudonymize(&mut self) {
+        let Some(customer_id) = self.customer_id else {
+            return;
+        };
+        let p = pseudonym(customer_id);
+        replace(&mut self.customer_name, &p.name);
+        replace(&mut self.address, &p.address());
+    }
+}
+
+impl Pseudonymize for BoardRoute {
+    fn pseudonymize(&mut self) {
+        self.stops.pseudonymize();
+    }
+}
+
+impl Pseudonymize for BoardSnapshot {
+    fn pseudonymize(&mut self) {
+        self.routes.pseudonymize();
+    }
+}
+
+impl Pseudonymize for BoardDelta {
+    fn pseudonymize(&mut self) {
+        if let Some(route) = &mut self.route {
+            route.pseudonymize();
+        }
+    }
+}
+
+impl Pseudonymize for Communication {
+    fn pseudonymize(&mut self) {
+        let p = pseudonym(self.customer_id);
+        replace(&mut self.contact_name, &p.contact_person);
+        replace(&mut self.contact_phone, &p.phone);
+        self.subject = None;
+        self.content.clear();
+        self.details = None;
+    }
+}
+
+impl Pseudonymize for QuoteListItem {
+    fn pseudonymize(&mut self) {
+        replace(&mut self.customer_name, &pseudonym(self.customer_id).name);
+    }
+}
+
+impl Pseudonymize for QuoteDetail {
+    fn pseudonymize(&mut self) {
+        self.quote.note = None;
+        self.quote.decline_reason = None;
+    }
+}
+
+/// Pseudonymize `value` when the session is in demo mode
+pub fn apply<T: Pseudonymize>(demo_mode: bool, mut value: T) -> T {
+    if demo_mode {
+        value.pseudonymize();
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pseudonym_is_stable_per_customer() {
+        let id = Uuid::from_u128(0x1234_5678_9abc_def0_1122_3344_5566_7788);
+        assert_eq!(pseudonym(id), pseudonym(id));
+        assert_ne!(pseudonym(id).phone, pseudonym(Uuid::from_u128(42)).phone);
+
+        let p = pseudonym(id);
+        assert!(p.email.ends_with("@example.com"));
+        assert!(p.phone.starts_with("+420 600 "));
+        assert!(CITIES.iter().any(|(city, postal_code)| *city == p.city && *postal_code == p.postal_code));
+    }
+
+    #[test]
+    fn test_board_stop_matches_customer_pseudonym() {
+        let customer_id = Uuid::from_u128(7);
+        let mut stop = BoardStop {
+            route_id: Uuid::nil(),
+            stop_id: Uuid::nil(),
+            stop_order: 1,
+            stop_type: "customer".to_string(),
+            customer_id: Some(customer_id),
+            customer_name: Some("Jan Novák".to_string()),
+            address: Some("Dlouhá 5, Praha".to_string()),
+            estimated_arrival: None,
+            estimated_departure: None,
+            visit_id: None,
+            visit_status: None,
+            actual_arrival: None,
+            actual_departure: None,
+        };
+        let mut break_stop = BoardStop { customer_id: None, customer_name: None, address: None, ..stop.clone() };
+        stop.pseudonymize();
+        break_stop.pseudonymize();
+
+        let p = pseudonym(customer_id);
+        assert_eq!(stop.customer_name, Some(p.name.clone()));
+        assert_eq!(stop.address, Some(p.address()));
+        assert_eq!(break_stop.customer_name, None);
+        assert_eq!(break_stop.address, None);
+    }
+
+    #[test]
+    fn test_replace_keeps_empty_fields_empty() {
+        let mut empty = None;
+        replace(&mut empty, "x");
+        assert_eq!(empty, None);
+
+        let mut set = Some("Jan Novák".to_string());
+        replace(&mut set, "Jan Ukázka");
+        assert_eq!(set.as_deref(), Some("Jan Ukázka"));
+    }
+}
@@ -0,0 +1,144 @@
+//! Device sticker codes
+//!
+//! A sticker on the device carries a QR code of `{app_base_url}/d/{code}`,
+//! where the code is the device id followed by a short HMAC signature, so a
+//! scanned code can't be turned into another device's id by guessing. The
+//! signature only proves the code was issued by this deployment; lookups
+//! are still scoped to the account of the technician scanning it.
+
+use hmac::{Hmac, Mac};
+use qrcodegen::{QrCode, QrCodeEcc};
+use sha2::Sha256;
+use uuid::Uuid;
+
+/// Hex characters of the signature kept in the code
+const SIGNATURE_LEN: usize = 16;
+/// Quiet zone around the QR code in modules, as the standard requires
+pub const QR_BORDER: usize = 4;
+
+/// Modules of a QR code, row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrMatrix {
+    pub size: usize,
+    modules: Vec<bool>,
+}
+
+impl QrMatrix {
+    /// Whether the module at column `x`, row `y` is dark
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        x < self.size && y < self.size && self.modules[y * self.size + x]
+    }
+}
+
+fn signature(secret: &str, device_id: Uuid) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(b"device:");
+    mac.update(device_id.as_bytes());
+    let mut sig = hex::encode(mac.finalize().into_bytes());
+    sig.truncate(SIGNATURE_LEN);
+    sig
+}
+
+/// Signed code of a device
+pub fn device_code(secret: &str, device_id: Uuid) -> String {
+    format!("{}.{}", device_id.simple(), signature(secret, device_id))
+}
+
+/// URL encoded in the sticker QR code
+pub fn device_url(app_base_url: &str, code: &str) -> String {
+    format!("{}/d/{}", app_base_url.trim_end_matches('/'), code)
+}
+
+/// Device id of a scanned code; accepts the full URL or the bare code.
+/// None when the code is malformed or its signature doesn't match.
+pub fn parse_device_code(secret: &str, scanned: &str) -> Option<Uuid> {
+    let scanned = scanned.trim();
+    let code = scanned.rsplit('/').next()?.split(['?', '#']).next()?;
+    let (id, sig) = code.split_once('.')?;
+    let device_id = Uuid::try_parse(id).ok()?;
+    let expected = signature(secret, device_id);
+    // Constant time comparison; the length is public
+    let matches = sig.len() == expected.len()
+        && sig.bytes().zip(expected.bytes()).fold(0u8, |acc, (a, b)| acc | (a.to_ascii_lowercase() ^ b)) == 0;
+    matches.then_some(device_id)
+}
+
+/// QR code of `text` with medium error correction (survives a scratched
+/// sticker). None only if the text is too long for a QR code.
+pub fn qr_matrix(text: &str) -> Option<QrMatrix> {
+    let qr = QrCode::encode_text(text, QrCodeEcc::Medium).ok()?;
+    let size = qr.size() as usize;
+    let modules = (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .map(|(x, y)| qr.get_module(x as i32, y as i32))
+        .collect();
+    Some(QrMatrix { size, modules })
+}
+
+/// SVG of a QR code, one path of unit squares with the quiet zone included
+pub fn qr_svg(matrix: &QrMatrix) -> String {
+    let dim = matrix.size + 2 * QR_BORDER;
+    let mut path = String::new();
+    for y in 0..matrix.size {
+        for x in 0..matrix.size {
+            if matrix.is_dark(x, y) {
+                path.push_str(&format!("M{},{}h1v1h-1z", x + QR_BORDER, y + QR_BORDER));
+            }
+        }
+    }
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {dim} {dim}\" shape-rendering=\"crispEdges\">\
+         <rect width=\"100%\" height=\"100%\" fill=\"#fff\"/><path d=\"{path}\" fill=\"#000\"/></svg>"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "test-secret";
+
+    #[test]
+    fn test_code_round_trip() {
+        let id = Uuid::new_v4();
+        let code = device_code(SECRET, id);
+        assert_eq!(code.len(), 32 + 1 + SIGNATURE_LEN);
+        assert_eq!(parse_device_code(SECRET, &code), Some(id));
+
+        let url = device_url("https://app.sazinka.cz/", &code);
+        assert_eq!(url, format!("https://app.sazinka.cz/d/{}", code));
+        assert_eq!(parse_device_code(SECRET, &url), Some(id));
+        assert_eq!(parse_device_code(SECRET, &format!(" {}?src=sticker ", url)), Some(id));
+        assert_eq!(parse_device_code(SECRET, &code.to_uppercase()), Some(id));
+    }
+
+    #[test]
+    fn test_rejects_forged_codes() {
+        let id = Uuid::new_v4();
+        let code = device_code(SECRET, id);
+        assert_eq!(parse_device_code("other-secret", &code), None);
+
+        let (_, sig) = code.split_once('.').unwrap();
+        let other = format!("{}.{}", Uuid::new_v4().simple(), sig);
+        assert_eq!(parse_device_code(SECRET, &other), None);
+
+        assert_eq!(parse_device_code(SECRET, &id.to_string()), None);
+        assert_eq!(parse_device_code(SECRET, &code[..code.len() - 1]), None);
+        assert_eq!(parse_device_code(SECRET, ""), None);
+    }
+
+    #[test]
+    fn test_qr_svg() {
+        let matrix = qr_matrix("https://app.sazinka.cz/d/abc").unwrap();
+        assert!(matrix.size >= 21);
+        // Finder pattern corner is dark, outside the matrix is light
+        assert!(matrix.is_dark(0, 0));
+        assert!(!matrix.is_dark(matrix.size, 0));
+
+        let svg = qr_svg(&matrix);
+        let dim = matrix.size + 2 * QR_BORDER;
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(&format!("viewBox=\"0 0 {dim} {dim}\"")));
+        assert!(svg.contains("M4,4h1v1h-1z"));
+    }
+}
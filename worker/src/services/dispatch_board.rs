@@ -0,0 +1,110 @@
+//! Dispatch board feed
+//!
+//! Handlers that change a route or a visit call in here; the new state of
+//! each affected route is read back and published on the feed subject of
+//! its day (`subjects::board::feed`). Publishing runs in the background and
+//! nobody listening costs one query per change. Each delta also goes out
+//! pseudonymized on the day's demo feed for demo-mode sessions.
+
+use anyhow::Result;
+use async_nats::Client;
+use chrono::{NaiveDate, Utc};
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::demo_mode;
+use crate::subjects;
+use crate::types::dispatch_board::{
+    assemble_routes, BoardDelta, BoardRoute, BoardSnapshot, BOARD_DELTA_ROUTE, BOARD_DELTA_ROUTE_REMOVED,
+};
+
+fn version() -> i64 {
+    Utc::now().timestamp_micros()
+}
+
+/// Current state of the routes of a day, or of one route
+async fn load_routes(
+    pool: &PgPool,
+    user_id: Uuid,
+    date: Option<NaiveDate>,
+    route_id: Option<Uuid>,
+) -> Result<(Vec<NaiveDate>, Vec<BoardRoute>)> {
+    let version = version();
+    let rows = queries::dispatch_board::list_board_routes(pool, user_id, date, route_id).await?;
+    let dates = rows.iter().map(|row| row.date).collect();
+    let ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+    let stops = queries::dispatch_board::list_board_stops(pool, &ids).await?;
+    Ok((dates, assemble_routes(rows, stops, version)))
+}
+
+/// Snapshot of a day's board. Demo sessions get pseudonyms and the demo feed.
+pub async fn snapshot(pool: &PgPool, user_id: Uuid, date: NaiveDate, demo: bool) -> Result<BoardSnapshot> {
+    let (_, routes) = load_routes(pool, user_id, Some(date), None).await?;
+    let subject = if demo { subjects::board::demo_feed(user_id, date) } else { subjects::board::feed(user_id, date) };
+    Ok(demo_mode::apply(demo, BoardSnapshot { date, subject, routes }))
+}
+
+/// Publish on the day's feed, and pseudonymized on its demo feed
+async fn publish(client: &Client, user_id: Uuid, delta: &BoardDelta) -> Result<()> {
+    client
+        .publish(subjects::board::feed(user_id, delta.date), serde_json::to_vec(delta)?.into())
+        .await?;
+    let demo = demo_mode::apply(true, delta.clone());
+    client
+        .publish(subjects::board::demo_feed(user_id, delta.date), serde_json::to_vec(&demo)?.into())
+        .await?;
+    Ok(())
+}
+
+/// Publish the current state of one route
+pub async fn publish_route_now(client: &Client, pool: &PgPool, user_id: Uuid, route_id: Uuid) -> Result<()> {
+    let (dates, routes) = load_routes(pool, user_id, None, Some(route_id)).await?;
+    for (date, route) in dates.into_iter().zip(routes) {
+        let delta = BoardDelta { date, kind: BOARD_DELTA_ROUTE, route_id, version: route.version, route: Some(route) };
+        publish(client, user_id, &delta).await?;
+    }
+    Ok(())
+}
+
+/// Publish the current state of one route, in the background
+pub fn publish_route(client: &Client, pool: &PgPool, user_id: Uuid, route_id: Uuid) {
+    let client = client.clone();
+    let pool = pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = publish_route_now(&client, &pool, user_id, route_id).await {
+            warn!("Failed to publish board update of route {}: {}", route_id, e);
+        }
+    });
+}
+
+/// Publish that a route of a day was deleted
+pub fn publish_route_removed(client: &Client, user_id: Uuid, date: NaiveDate, route_id: Uuid) {
+    let client = client.clone();
+    let delta = BoardDelta { date, kind: BOARD_DELTA_ROUTE_REMOVED, route_id, version: version(), route: None };
+    tokio::spawn(async move {
+        if let Err(e) = publish(&client, user_id, &delta).await {
+            warn!("Failed to publish board removal of route {}: {}", route_id, e);
+        }
+    });
+}
+
+/// Publish the routes of a day that stop at a customer (after a change of
+/// one of the customer's visits), in the background
+pub fn publish_customer_day(client: &Client, pool: &PgPool, user_id: Uuid, customer_id: Uuid, date: NaiveDate) {
+    let client = client.clone();
+    let pool = pool.clone();
+    tokio::spawn(async move {
+        let result = async {
+            for route_id in queries::dispatch_board::list_route_ids_for_customer(&pool, user_id, customer_id, date).await? {
+                publish_route_now(&client, &pool, user_id, route_id).await?;
+            }
+            anyhow::Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            warn!("Failed to publish board update for customer {} on {}: {}", customer_id, date, e);
+        }
+    });
+}
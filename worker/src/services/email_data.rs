@@ -12,6 +12,9 @@ use crate::types::settings::{
     default_confirmation_subject, default_confirmation_template, default_reminder_subject,
     default_reminder_template,
 };
+use crate::types::template_translation::{
+    normalize_language, TEMPLATE_KIND_EMAIL_CONFIRMATION, TEMPLATE_KIND_EMAIL_REMINDER,
+};
 
 // ============================================================================
 // Output structs
@@ -126,10 +129,83 @@ pub fn select_reminder_templates(
     }
 }
 
+/// Signature shared by `select_confirmation_templates` and `select_reminder_templates`.
+pub type TemplateSelector =
+    fn(Option<&str>, Option<&str>, Option<DateTime<Utc>>, &str) -> (String, String);
+
+/// Customer language and the account's version of a template in it, if any.
+#[derive(Debug, Default, sqlx::FromRow)]
+pub struct CustomerTemplate {
+    pub language: Option<String>,
+    pub subject: Option<String>,
+    pub body: Option<String>,
+}
+
+/// Select subject, body and locale for a customer.
+///
+/// Order: the account's version in the customer's language; the built-in
+/// default in that language while the account templates are unedited;
+/// otherwise the account's own templates in the account locale.
+pub fn select_customer_templates(
+    customer: &CustomerTemplate,
+    account_subject: Option<&str>,
+    account_body: Option<&str>,
+    account_edited_at: Option<DateTime<Utc>>,
+    account_locale: &str,
+    select: TemplateSelector,
+) -> (String, String, String) {
+    let language = customer.language.as_deref().and_then(normalize_language);
+    let body = customer.body.as_deref().filter(|b| !b.trim().is_empty());
+
+    match (language, body) {
+        (Some(lang), Some(body)) => {
+            let subject = match customer.subject.as_deref().filter(|s| !s.trim().is_empty()) {
+                Some(subject) => subject.to_string(),
+                None => select(None, None, None, &lang).0,
+            };
+            (subject, body.to_string(), lang)
+        }
+        (Some(lang), None) if account_edited_at.is_none() => {
+            let (subject, body) = select(None, None, None, &lang);
+            (subject, body, lang)
+        }
+        _ => {
+            let (subject, body) =
+                select(account_subject, account_body, account_edited_at, account_locale);
+            (subject, body, account_locale.to_string())
+        }
+    }
+}
+
 // ============================================================================
 // DB queries
 // ============================================================================
 
+/// Load the customer's language and the matching version of a template.
+async fn load_customer_template(
+    pool: &PgPool,
+    user_id: Uuid,
+    customer_id: Uuid,
+    template_kind: &str,
+) -> Result<CustomerTemplate> {
+    let template = sqlx::query_as::<_, CustomerTemplate>(
+        r#"
+        SELECT c.language, t.subject, t.body
+        FROM customers c
+        LEFT JOIN customer_template_translations t
+               ON t.user_id = c.user_id AND t.template_kind = $3 AND t.language = c.language
+        WHERE c.id = $1 AND c.user_id = $2
+        "#,
+    )
+    .bind(customer_id)
+    .bind(user_id)
+    .bind(template_kind)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(template.unwrap_or_default())
+}
+
 /// Raw row returned by the confirmation data query.
 #[derive(Debug, sqlx::FromRow)]
 struct ConfirmationRow {
@@ -201,12 +277,15 @@ pub async fn resolve_confirmation_data(
         None => return Ok(None),
     };
 
-    let locale = row.company_locale.as_deref().unwrap_or("en");
-    let (subject_template, body_template) = select_confirmation_templates(
+    let customer_template =
+        load_customer_template(pool, user_id, customer_id, TEMPLATE_KIND_EMAIL_CONFIRMATION).await?;
+    let (subject_template, body_template, locale) = select_customer_templates(
+        &customer_template,
         row.subject_template.as_deref(),
         row.body_template.as_deref(),
         row.edited_at,
-        locale,
+        row.company_locale.as_deref().unwrap_or("en"),
+        select_confirmation_templates,
     );
 
     Ok(Some(ConfirmationData {
@@ -221,7 +300,7 @@ pub async fn resolve_confirmation_data(
         company_name: row.business_name.unwrap_or_default(),
         company_phone: row.phone.unwrap_or_default(),
         company_email: row.user_email.unwrap_or_default(),
-        company_locale: locale.to_string(),
+        company_locale: locale,
         subject_template,
         body_template,
     }))
@@ -293,12 +372,15 @@ pub async fn resolve_reminder_data(
         None => return Ok(None),
     };
 
-    let locale = row.company_locale.as_deref().unwrap_or("en");
-    let (subject_template, body_template) = select_reminder_templates(
+    let customer_template =
+        load_customer_template(pool, user_id, customer_id, TEMPLATE_KIND_EMAIL_REMINDER).await?;
+    let (subject_template, body_template, locale) = select_customer_templates(
+        &customer_template,
         row.subject_template.as_deref(),
         row.body_template.as_deref(),
         row.edited_at,
-        locale,
+        row.company_locale.as_deref().unwrap_or("en"),
+        select_reminder_templates,
     );
 
     Ok(Some(ReminderData {
@@ -309,7 +391,7 @@ pub async fn resolve_reminder_data(
         company_name: row.business_name.unwrap_or_default(),
         company_phone: row.phone.unwrap_or_default(),
         company_email: row.user_email.unwrap_or_default(),
-        company_locale: locale.to_string(),
+        company_locale: locale,
         subject_template,
         body_template,
     }))
@@ -454,7 +536,7 @@ mod tests {
 
     #[test]
     fn confirmation_templates_unknown_locale_falls_back_to_en() {
-        let (subj, _) = select_confirmation_templates(None, None, None, "de");
+        let (subj, _) = select_confirmation_templates(None, None, None, "fr");
         assert!(subj.contains("confirmation") || subj.contains("Confirmation"));
     }
 
@@ -503,4 +585,60 @@ mod tests {
         let (subj, _) = select_reminder_templates(Some(""), Some(""), Some(Utc::now()), "cs");
         assert!(subj.contains("Připomínka"));
     }
+
+    #[test]
+    fn confirmation_templates_de_locale_default() {
+        let (subj, body) = select_confirmation_templates(None, None, None, "de");
+        assert!(subj.contains("Terminbestätigung"));
+        assert!(body.starts_with("Sehr geehrte"));
+    }
+
+    // ---- select_customer_templates ----
+
+    #[test]
+    fn customer_templates_use_translation_in_customer_language() {
+        let customer = CustomerTemplate {
+            language: Some("de".to_string()),
+            subject: None,
+            body: Some("Guten Tag {{customerName}}".to_string()),
+        };
+        let (subj, body, locale) = select_customer_templates(
+            &customer,
+            Some("Vlastní"),
+            Some("Vlastní tělo"),
+            Some(Utc::now()),
+            "cs",
+            select_reminder_templates,
+        );
+        assert_eq!(body, "Guten Tag {{customerName}}");
+        assert!(subj.contains("Terminerinnerung"));
+        assert_eq!(locale, "de");
+    }
+
+    #[test]
+    fn customer_templates_use_language_default_when_account_unedited() {
+        let customer = CustomerTemplate { language: Some("de".to_string()), ..Default::default() };
+        let (_, body, locale) = select_customer_templates(&customer, None, None, None, "cs", select_reminder_templates);
+        assert!(body.starts_with("Sehr geehrte"));
+        assert_eq!(locale, "de");
+    }
+
+    #[test]
+    fn customer_templates_fall_back_to_account_templates() {
+        let customer = CustomerTemplate { language: Some("de".to_string()), ..Default::default() };
+        let (subj, body, locale) = select_customer_templates(
+            &customer,
+            Some("Vlastní"),
+            Some("Vlastní tělo"),
+            Some(Utc::now()),
+            "cs",
+            select_reminder_templates,
+        );
+        assert_eq!((subj.as_str(), body.as_str(), locale.as_str()), ("Vlastní", "Vlastní tělo", "cs"));
+
+        let no_language = CustomerTemplate::default();
+        let (subj, _, locale) = select_customer_templates(&no_language, None, None, None, "sk", select_reminder_templates);
+        assert!(subj.contains("Pripomienka"));
+        assert_eq!(locale, "sk");
+    }
 }
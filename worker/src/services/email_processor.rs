@@ -9,24 +9,28 @@
 use std::sync::Arc;
 use anyhow::Result;
 use async_nats::Client;
-use async_nats::jetstream::{self, Context as JsContext};
+use async_nats::jetstream;
 use aws_sdk_sesv2::types::{Body, Content, Destination, EmailContent, Message};
 use futures::StreamExt;
 use sqlx::PgPool;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
-use crate::services::{domain_verification, email_data, template_renderer};
+use crate::db::queries;
+use crate::services::{communication_log, domain_verification, email_data, template_renderer};
+use crate::subjects;
+use crate::transport::{JobMessage, JobQueue};
 use crate::types::{
-    AppointmentConfirmationRequest, CustomEmailRequest, EmailJobRequest, EmailJobStatus,
+    AppointmentConfirmationRequest, AutomaticCommunication, CampaignEmailRequest, CommunicationEntryType,
+    CustomEmailRequest, EmailJobRequest, EmailJobStatus,
     EmailJobStatusUpdate, EmailJobSubmitResponse, QueuedEmailJob, RevisionReminderRequest,
 };
 
 // Stream and consumer names
 const STREAM_NAME: &str = "SAZINKA_EMAIL_JOBS";
 const CONSUMER_NAME: &str = "email_workers";
-const SUBJECT: &str = "sazinka.jobs.email";
-const STATUS_PREFIX: &str = "sazinka.job.email.status";
+const SUBJECT: &str = subjects::jobs::EMAIL;
+const STATUS_PREFIX: &str = subjects::job::EMAIL_STATUS;
 
 // ============================================================================
 // Configuration
@@ -61,6 +65,9 @@ pub enum EmailSendError {
     #[error("Recipient has no email address")]
     NoRecipient,
 
+    #[error("Sender account email is not verified")]
+    SenderNotVerified,
+
     #[error("Permanent SES error: {0}")]
     Permanent(String),
 
@@ -86,7 +93,7 @@ pub fn classify_ses_error(err: &aws_sdk_sesv2::Error) -> EmailSendError {
 /// Email job processor with JetStream integration and SES delivery.
 pub struct EmailProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
     ses_client: Option<aws_sdk_sesv2::Client>,
     fallback_from_email: String,
     fallback_from_name: String,
@@ -94,6 +101,15 @@ pub struct EmailProcessor {
     pool: PgPool,
 }
 
+/// Publish an email job without a processor at hand (e.g. from an event
+/// listener), returning the job ID
+pub async fn enqueue(queue: &JobQueue, user_id: Uuid, request: EmailJobRequest) -> Result<Uuid> {
+    let job = QueuedEmailJob::new(user_id, request);
+    let subject = format!("{}.{}", SUBJECT, job.request.type_name());
+    queue.publish(subject, serde_json::to_vec(&job)?.into()).await?;
+    Ok(job.id)
+}
+
 impl EmailProcessor {
     /// Create a new email processor, initializing JetStream stream.
     pub async fn new(
@@ -101,17 +117,17 @@ impl EmailProcessor {
         pool: PgPool,
         ses_config: Option<SesEmailConfig>,
     ) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+        let queue = JobQueue::new(client.clone());
 
         let stream_config = jetstream::stream::Config {
             name: STREAM_NAME.to_string(),
-            subjects: vec![format!("{}.*", SUBJECT)],
+            subjects: vec![subjects::wildcard(SUBJECT)],
             max_messages: 10_000,
             max_bytes: 50 * 1024 * 1024,
             retention: jetstream::stream::RetentionPolicy::WorkQueue,
             ..Default::default()
         };
-        js.get_or_create_stream(stream_config).await?;
+        queue.ensure_stream(stream_config).await?;
         info!("JetStream email stream '{}' ready", STREAM_NAME);
 
         let (ses_client, fallback_from_email, fallback_from_name, configuration_set) =
@@ -136,7 +152,7 @@ impl EmailProcessor {
 
         Ok(Self {
             client,
-            js,
+            queue,
             ses_client,
             fallback_from_email,
             fallback_from_name,
@@ -151,13 +167,8 @@ impl EmailProcessor {
         user_id: Uuid,
         request: EmailJobRequest,
     ) -> Result<EmailJobSubmitResponse> {
-        let job = QueuedEmailJob::new(user_id, request);
-        let job_id = job.id;
-        let email_type = job.request.type_name().to_string();
-
-        let subject = format!("{}.{}", SUBJECT, email_type);
-        let payload = serde_json::to_vec(&job)?;
-        self.js.publish(subject, payload.into()).await?.await?;
+        let email_type = request.type_name().to_string();
+        let job_id = enqueue(&self.queue, user_id, request).await?;
 
         info!("Email job {} submitted: {}", job_id, email_type);
         self.publish_status(job_id, EmailJobStatus::Queued { position: 1 })
@@ -173,7 +184,7 @@ impl EmailProcessor {
     /// Publish an email job status update.
     pub async fn publish_status(&self, job_id: Uuid, status: EmailJobStatus) -> Result<()> {
         let update = EmailJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         self.client.publish(subject, payload.into()).await?;
         Ok(())
@@ -181,23 +192,17 @@ impl EmailProcessor {
 
     /// Start processing email jobs from the queue.
     pub async fn start_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(STREAM_NAME).await?;
-
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(CONSUMER_NAME.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
             max_deliver: 5,
-            filter_subject: format!("{}.>", SUBJECT),
+            filter_subject: subjects::tail_wildcard(SUBJECT),
             ..Default::default()
         };
 
-        let consumer = stream
-            .get_or_create_consumer(CONSUMER_NAME, consumer_config)
-            .await?;
+        let mut messages = self.queue.consume(STREAM_NAME, consumer_config).await?;
         info!("JetStream email consumer '{}' ready", CONSUMER_NAME);
 
-        let mut messages = consumer.messages().await?;
-
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -220,7 +225,7 @@ impl EmailProcessor {
     // -------------------------------------------------------------------------
 
     /// Process a single email job from JetStream.
-    async fn process_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_job(&self, msg: JobMessage) -> Result<()> {
         let job: QueuedEmailJob = serde_json::from_slice(&msg.payload)?;
         let job_id = job.id;
 
@@ -256,6 +261,7 @@ impl EmailProcessor {
                 self.process_reminder(job.user_id, req).await
             }
             EmailJobRequest::Custom(req) => self.process_custom(job.user_id, req).await,
+            EmailJobRequest::Campaign(req) => self.process_campaign(job.user_id, req).await,
         };
 
         match result {
@@ -335,6 +341,7 @@ impl EmailProcessor {
 
         self.log_communication(
             user_id,
+            CommunicationEntryType::ConfirmationEmail,
             req.customer_id,
             Some(req.revision_id),
             &subject,
@@ -390,6 +397,7 @@ impl EmailProcessor {
 
         self.log_communication(
             user_id,
+            CommunicationEntryType::ReminderEmail,
             req.customer_id,
             Some(req.revision_id),
             &subject,
@@ -415,12 +423,53 @@ impl EmailProcessor {
         Ok((message_id, req.to.clone()))
     }
 
+    /// Process a campaign email job (rendered by the campaign scheduler).
+    async fn process_campaign(
+        &self,
+        user_id: Uuid,
+        req: &CampaignEmailRequest,
+    ) -> Result<(String, String), EmailSendError> {
+        let settings = crate::db::queries::settings::get_user_settings(&self.pool, user_id)
+            .await
+            .map_err(|e| EmailSendError::Permanent(e.to_string()))?;
+        let (business_name, business_email) = settings
+            .map(|s| (s.business_name.unwrap_or_default(), s.email))
+            .unwrap_or_default();
+
+        let message_id = self
+            .send_email(
+                user_id,
+                &req.to,
+                &req.subject,
+                &req.body_html,
+                &req.body_text,
+                &business_name,
+                &business_email,
+            )
+            .await?;
+
+        self.log_communication(
+            user_id,
+            CommunicationEntryType::CampaignEmail,
+            req.customer_id,
+            None,
+            &req.subject,
+            &req.body_html,
+            &req.to,
+            &message_id,
+        )
+        .await;
+
+        Ok((message_id, req.to.clone()))
+    }
+
     // -------------------------------------------------------------------------
     // Phase 5 — SES send function
     // -------------------------------------------------------------------------
 
     /// Send an email via SES. Resolves the correct `From` address dynamically
     /// using the user's domain verification status and business identity.
+    /// Accounts that have not confirmed their own email address send nothing.
     async fn send_email(
         &self,
         user_id: Uuid,
@@ -436,6 +485,13 @@ impl EmailProcessor {
             .as_ref()
             .ok_or(EmailSendError::NotConfigured)?;
 
+        let unverified = queries::user::requires_email_verification(&self.pool, user_id)
+            .await
+            .map_err(|e| EmailSendError::Permanent(e.to_string()))?;
+        if unverified {
+            return Err(EmailSendError::SenderNotVerified);
+        }
+
         let active_domain = domain_verification::get_active_domain(&self.pool, user_id)
             .await
             .map_err(|e| EmailSendError::Permanent(e.to_string()))?;
@@ -505,35 +561,30 @@ impl EmailProcessor {
     // Phase 7 — CRM communication logging
     // -------------------------------------------------------------------------
 
-    /// Log a sent email to the communications table. Non-fatal: errors are logged
+    /// Log a sent email to the communication history. Non-fatal: errors are logged
     /// but do not affect the job outcome (email was already sent).
+    #[allow(clippy::too_many_arguments)]
     async fn log_communication(
         &self,
         user_id: Uuid,
+        entry_type: CommunicationEntryType,
         customer_id: Uuid,
         revision_id: Option<Uuid>,
         subject: &str,
         content: &str,
-        _recipient: &str,
-        _message_id: &str,
+        recipient: &str,
+        message_id: &str,
     ) {
-        if let Err(e) = crate::db::queries::communication::create_communication(
-            &self.pool,
-            user_id,
+        let entry = AutomaticCommunication {
             customer_id,
             revision_id,
-            "email_sent",
-            "outbound",
-            Some(subject),
-            content,
-            None,
-            None,
-            None,
-        )
-        .await
-        {
-            error!("Failed to log email communication: {}", e);
-        }
+            entry_type,
+            comm_type: "email_sent",
+            subject: Some(subject.to_string()),
+            content: content.to_string(),
+            details: serde_json::json!({ "recipient": recipient, "messageId": message_id }),
+        };
+        communication_log::record(&self.pool, user_id, &entry).await;
     }
 }
 
@@ -599,6 +650,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn email_send_error_display_sender_not_verified() {
+        assert_eq!(
+            EmailSendError::SenderNotVerified.to_string(),
+            "Sender account email is not verified"
+        );
+    }
+
     #[test]
     fn email_send_error_display_permanent() {
         let e = EmailSendError::Permanent("bad address".to_string());
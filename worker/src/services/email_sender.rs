@@ -13,6 +13,8 @@ use anyhow::Result;
 use async_trait::async_trait;
 use tracing::info;
 
+use crate::services::http::{self, HttpService};
+
 // =============================================================================
 // Core trait
 // =============================================================================
@@ -116,7 +118,7 @@ impl ResendEmailSender {
 #[async_trait]
 impl EmailSender for ResendEmailSender {
     async fn send(&self, msg: EmailMessage) -> Result<()> {
-        let client = reqwest::Client::new();
+        let client = http::client(HttpService::Email);
 
         let mut body = HashMap::new();
         body.insert("from", self.from.as_str());
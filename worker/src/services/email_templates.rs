@@ -4,11 +4,20 @@
 //! Supported templates:
 //!   - `VerificationEmail`   — sent on initial registration and resend
 //!   - `AlreadyRegistered`   — anti-enumeration: sent when a verified email re-registers
+//!   - `PasswordResetEmail`  — sent when an admin forces a password reset
+//!   - `NewDeviceLoginEmail` — sent after a login from a device not seen before
+//!   - `RescheduleRequestedEmail` — tells the dispatcher a customer wants another date
+//!   - `EscalationDigestEmail` — lists overdue revisions and missed visits raised by escalation rules
+//!   - `NewLeadEmail`        — tells the dispatcher a website form came in
+//!   - `OrganizationInviteEmail` — invites a colleague to join the owner's team
 //!
 //! Each template is rendered per-locale (en, cs, sk).
 //! The `render()` method returns an `EmailMessage` ready to pass to `EmailSender::send`.
 
+use chrono::NaiveDate;
+
 use crate::services::email_sender::EmailMessage;
+use crate::services::template_renderer::html_escape;
 
 // =============================================================================
 // Verification email
@@ -148,6 +157,448 @@ impl<'a> AlreadyRegisteredEmail<'a> {
     }
 }
 
+// =============================================================================
+// Password reset email (admin-initiated)
+// =============================================================================
+
+pub struct PasswordResetEmail<'a> {
+    pub to: &'a str,
+    pub reset_url: &'a str,
+    pub locale: &'a str,
+}
+
+impl<'a> PasswordResetEmail<'a> {
+    pub fn render(&self) -> EmailMessage {
+        let (subject, body_html, body_text) = match self.locale {
+            "cs" => (
+                "Nastavte si nové heslo – Sazinka",
+                format!(
+                    r#"<p>Dobrý den,</p>
+<p>Správce vašeho účtu Sazinka požádal o změnu hesla. Nové heslo si nastavíte kliknutím na odkaz níže:</p>
+<p><a href="{url}">{url}</a></p>
+<p>Odkaz platí 24 hodin.</p>"#,
+                    url = self.reset_url
+                ),
+                format!(
+                    "Dobrý den,\n\nNastavte si nové heslo kliknutím na: {}\n\nOdkaz platí 24 hodin.",
+                    self.reset_url
+                ),
+            ),
+            "sk" => (
+                "Nastavte si nové heslo – Sazinka",
+                format!(
+                    r#"<p>Dobrý deň,</p>
+<p>Správca vášho účtu Sazinka požiadal o zmenu hesla. Nové heslo si nastavíte kliknutím na odkaz nižšie:</p>
+<p><a href="{url}">{url}</a></p>
+<p>Odkaz platí 24 hodín.</p>"#,
+                    url = self.reset_url
+                ),
+                format!(
+                    "Dobrý deň,\n\nNastavte si nové heslo kliknutím na: {}\n\nOdkaz platí 24 hodín.",
+                    self.reset_url
+                ),
+            ),
+            _ => (
+                "Set a new password – Sazinka",
+                format!(
+                    r#"<p>Hello,</p>
+<p>An administrator has requested a password change for your Sazinka account. Click the link below to set a new password:</p>
+<p><a href="{url}">{url}</a></p>
+<p>This link is valid for 24 hours.</p>"#,
+                    url = self.reset_url
+                ),
+                format!(
+                    "Hello,\n\nSet a new password by clicking: {}\n\nThis link is valid for 24 hours.",
+                    self.reset_url
+                ),
+            ),
+        };
+
+        EmailMessage {
+            to: self.to.to_string(),
+            subject: subject.to_string(),
+            html: body_html,
+            text: body_text,
+        }
+    }
+}
+
+// =============================================================================
+// New device login email
+// =============================================================================
+
+pub struct NewDeviceLoginEmail<'a> {
+    pub to: &'a str,
+    /// Client user agent, if known
+    pub device: Option<&'a str>,
+    /// Client IP address, if known
+    pub ip: Option<&'a str>,
+    pub locale: &'a str,
+}
+
+impl<'a> NewDeviceLoginEmail<'a> {
+    pub fn render(&self) -> EmailMessage {
+        let device = self.device.unwrap_or("?");
+        let ip = self.ip.unwrap_or("?");
+        let (device_html, ip_html) = (html_escape(device), html_escape(ip));
+        let (subject, body_html, body_text) = match self.locale {
+            "cs" => (
+                "Nové přihlášení k vašemu účtu – Sazinka",
+                format!(
+                    r#"<p>Dobrý den,</p>
+<p>K vašemu účtu Sazinka se právě někdo přihlásil z nového zařízení:</p>
+<p>Zařízení: {device}<br>IP adresa: {ip}</p>
+<p>Pokud jste to nebyli vy, ihned si změňte heslo a kontaktujte podporu.</p>"#,
+                    device = device_html,
+                    ip = ip_html
+                ),
+                format!(
+                    "Dobrý den,\n\nK vašemu účtu se někdo přihlásil z nového zařízení.\nZařízení: {}\nIP adresa: {}\n\nPokud jste to nebyli vy, ihned si změňte heslo.",
+                    device, ip
+                ),
+            ),
+            "sk" => (
+                "Nové prihlásenie do vášho účtu – Sazinka",
+                format!(
+                    r#"<p>Dobrý deň,</p>
+<p>Do vášho účtu Sazinka sa práve niekto prihlásil z nového zariadenia:</p>
+<p>Zariadenie: {device}<br>IP adresa: {ip}</p>
+<p>Ak ste to neboli vy, ihneď si zmeňte heslo a kontaktujte podporu.</p>"#,
+                    device = device_html,
+                    ip = ip_html
+                ),
+                format!(
+                    "Dobrý deň,\n\nDo vášho účtu sa niekto prihlásil z nového zariadenia.\nZariadenie: {}\nIP adresa: {}\n\nAk ste to neboli vy, ihneď si zmeňte heslo.",
+                    device, ip
+                ),
+            ),
+            _ => (
+                "New sign-in to your account – Sazinka",
+                format!(
+                    r#"<p>Hello,</p>
+<p>Your Sazinka account was just signed in to from a new device:</p>
+<p>Device: {device}<br>IP address: {ip}</p>
+<p>If this wasn't you, change your password right away and contact support.</p>"#,
+                    device = device_html,
+                    ip = ip_html
+                ),
+                format!(
+                    "Hello,\n\nYour account was signed in to from a new device.\nDevice: {}\nIP address: {}\n\nIf this wasn't you, change your password right away.",
+                    device, ip
+                ),
+            ),
+        };
+
+        EmailMessage {
+            to: self.to.to_string(),
+            subject: subject.to_string(),
+            html: body_html,
+            text: body_text,
+        }
+    }
+}
+
+// =============================================================================
+// Reschedule requested email
+// =============================================================================
+
+pub struct RescheduleRequestedEmail<'a> {
+    pub to: &'a str,
+    pub customer_name: &'a str,
+    pub original_date: NaiveDate,
+    pub requested_date: NaiveDate,
+    /// Free text from the customer
+    pub note: Option<&'a str>,
+    /// Number of slots the slot engine found on the requested date
+    pub suggestion_count: usize,
+    pub locale: &'a str,
+}
+
+impl<'a> RescheduleRequestedEmail<'a> {
+    pub fn render(&self) -> EmailMessage {
+        let name_html = html_escape(self.customer_name);
+        let note = self.note.unwrap_or("");
+        let note_html = html_escape(note);
+        let (subject, body_html, body_text) = match self.locale {
+            "cs" => {
+                let (from, to) = (self.original_date.format("%d.%m.%Y"), self.requested_date.format("%d.%m.%Y"));
+                (
+                    format!("Žádost o změnu termínu – {}", self.customer_name),
+                    format!(
+                        r#"<p>Dobrý den,</p>
+<p>Zákazník {name} žádá o přesun návštěvy z {from} na {to}.</p>
+<p>Poznámka: {note}</p>
+<p>Volné termíny v požadovaný den: {count}. Žádost schválíte nebo zamítnete v aplikaci Sazinka.</p>"#,
+                        name = name_html,
+                        from = from,
+                        to = to,
+                        note = note_html,
+                        count = self.suggestion_count
+                    ),
+                    format!(
+                        "Dobrý den,\n\nZákazník {} žádá o přesun návštěvy z {} na {}.\nPoznámka: {}\n\nVolné termíny v požadovaný den: {}.",
+                        self.customer_name, from, to, note, self.suggestion_count
+                    ),
+                )
+            }
+            "sk" => {
+                let (from, to) = (self.original_date.format("%d.%m.%Y"), self.requested_date.format("%d.%m.%Y"));
+                (
+                    format!("Žiadosť o zmenu termínu – {}", self.customer_name),
+                    format!(
+                        r#"<p>Dobrý deň,</p>
+<p>Zákazník {name} žiada o presun návštevy z {from} na {to}.</p>
+<p>Poznámka: {note}</p>
+<p>Voľné termíny v požadovaný deň: {count}. Žiadosť schválite alebo zamietnete v aplikácii Sazinka.</p>"#,
+                        name = name_html,
+                        from = from,
+                        to = to,
+                        note = note_html,
+                        count = self.suggestion_count
+                    ),
+                    format!(
+                        "Dobrý deň,\n\nZákazník {} žiada o presun návštevy z {} na {}.\nPoznámka: {}\n\nVoľné termíny v požadovaný deň: {}.",
+                        self.customer_name, from, to, note, self.suggestion_count
+                    ),
+                )
+            }
+            _ => {
+                let (from, to) = (self.original_date.format("%Y-%m-%d"), self.requested_date.format("%Y-%m-%d"));
+                (
+                    format!("Reschedule request – {}", self.customer_name),
+                    format!(
+                        r#"<p>Hello,</p>
+<p>Customer {name} asks to move their visit from {from} to {to}.</p>
+<p>Note: {note}</p>
+<p>Free slots on the requested day: {count}. Accept or decline the request in Sazinka.</p>"#,
+                        name = name_html,
+                        from = from,
+                        to = to,
+                        note = note_html,
+                        count = self.suggestion_count
+                    ),
+                    format!(
+                        "Hello,\n\nCustomer {} asks to move their visit from {} to {}.\nNote: {}\n\nFree slots on the requested day: {}.",
+                        self.customer_name, from, to, note, self.suggestion_count
+                    ),
+                )
+            }
+        };
+
+        EmailMessage {
+            to: self.to.to_string(),
+            subject,
+            html: body_html,
+            text: body_text,
+        }
+    }
+}
+
+// =============================================================================
+// Escalation digest email
+// =============================================================================
+
+pub struct EscalationDigestEmail<'a> {
+    pub to: &'a str,
+    /// One line per escalated item, already localized
+    pub lines: &'a [String],
+    pub locale: &'a str,
+}
+
+impl<'a> EscalationDigestEmail<'a> {
+    pub fn render(&self) -> EmailMessage {
+        let count = self.lines.len();
+        let items_html: String = self
+            .lines
+            .iter()
+            .map(|line| format!("<li>{}</li>", html_escape(line)))
+            .collect();
+        let items_text: String = self.lines.iter().map(|line| format!("- {}\n", line)).collect();
+        let (subject, intro, outro) = match self.locale {
+            "cs" => (
+                format!("Eskalace termínů – {} položek vyžaduje pozornost", count),
+                "Dobrý den,\n\nnásledující položky překročily nastavený termín:",
+                "Podrobnosti najdete v centru upozornění aplikace Sazinka.",
+            ),
+            "sk" => (
+                format!("Eskalácia termínov – {} položiek vyžaduje pozornosť", count),
+                "Dobrý deň,\n\nnasledujúce položky prekročili nastavený termín:",
+                "Podrobnosti nájdete v centre upozornení aplikácie Sazinka.",
+            ),
+            _ => (
+                format!("Deadline escalation – {} items need attention", count),
+                "Hello,\n\nthe following items are past their deadline:",
+                "See the notification center in Sazinka for details.",
+            ),
+        };
+        let intro_html = intro.replace("\n\n", "</p>\n<p>");
+
+        EmailMessage {
+            to: self.to.to_string(),
+            subject,
+            html: format!("<p>{}</p>\n<ul>{}</ul>\n<p>{}</p>", intro_html, items_html, outro),
+            text: format!("{}\n\n{}\n{}", intro, items_text, outro),
+        }
+    }
+}
+
+// =============================================================================
+// New lead email
+// =============================================================================
+
+pub struct NewLeadEmail<'a> {
+    pub to: &'a str,
+    pub name: &'a str,
+    /// E-mail and/or phone the visitor left, as one line
+    pub contact: &'a str,
+    pub message: Option<&'a str>,
+    pub preferred_date: Option<NaiveDate>,
+    /// The submission matched a customer already in the address book
+    pub existing_customer: bool,
+    pub locale: &'a str,
+}
+
+impl<'a> NewLeadEmail<'a> {
+    pub fn render(&self) -> EmailMessage {
+        let message = self.message.unwrap_or("");
+        let (subject, intro, date_label, outro) = match self.locale {
+            "cs" => (
+                format!("Nová poptávka z webu – {}", self.name),
+                if self.existing_customer {
+                    "Dobrý den,
+
+stávající zákazník odeslal formulář na webu:"
+                } else {
+                    "Dobrý den,
+
+z formuláře na webu přišla nová poptávka:"
+                },
+                "Požadovaný termín",
+                "Poptávku najdete mezi zákazníky v aplikaci Sazinka.",
+            ),
+            "sk" => (
+                format!("Nový dopyt z webu – {}", self.name),
+                if self.existing_customer {
+                    "Dobrý deň,
+
+existujúci zákazník odoslal formulár na webe:"
+                } else {
+                    "Dobrý deň,
+
+z formulára na webe prišiel nový dopyt:"
+                },
+                "Požadovaný termín",
+                "Dopyt nájdete medzi zákazníkmi v aplikácii Sazinka.",
+            ),
+            _ => (
+                format!("New web enquiry – {}", self.name),
+                if self.existing_customer {
+                    "Hello,
+
+an existing customer sent the form on your website:"
+                } else {
+                    "Hello,
+
+a new enquiry came in through your website form:"
+                },
+                "Preferred date",
+                "You will find the lead among your customers in Sazinka.",
+            ),
+        };
+        let date = self.preferred_date.map(|d| match self.locale {
+            "cs" | "sk" => d.format("%d.%m.%Y").to_string(),
+            _ => d.format("%Y-%m-%d").to_string(),
+        });
+        let date_text = date.as_ref().map(|d| format!("{}: {}\n", date_label, d)).unwrap_or_default();
+        let date_html = date.as_ref().map(|d| format!("<p>{}: {}</p>\n", date_label, d)).unwrap_or_default();
+        let intro_html = intro.replace("\n\n", "</p>\n<p>");
+
+        EmailMessage {
+            to: self.to.to_string(),
+            subject,
+            html: format!(
+                "<p>{}</p>\n<p><strong>{}</strong><br>{}</p>\n{}<p>{}</p>\n<p>{}</p>",
+                intro_html,
+                html_escape(self.name),
+                html_escape(self.contact),
+                date_html,
+                html_escape(message),
+                outro
+            ),
+            text: format!("{}\n\n{}\n{}\n{}\n{}\n\n{}", intro, self.name, self.contact, date_text, message, outro),
+        }
+    }
+}
+
+// =============================================================================
+// Organization invitation email
+// =============================================================================
+
+pub struct OrganizationInviteEmail<'a> {
+    pub to: &'a str,
+    pub organization_name: &'a str,
+    pub inviter_name: &'a str,
+    pub accept_url: &'a str,
+    pub valid_days: i64,
+    pub locale: &'a str,
+}
+
+impl<'a> OrganizationInviteEmail<'a> {
+    /// Invitation sentence with the given (plain or escaped) names
+    fn invite(&self, inviter: &str, organization: &str) -> String {
+        match self.locale {
+            "cs" => format!("{} vás zve do týmu {} v aplikaci Sazinka.", inviter, organization),
+            "sk" => format!("{} vás pozýva do tímu {} v aplikácii Sazinka.", inviter, organization),
+            _ => format!("{} has invited you to the {} team on Sazinka.", inviter, organization),
+        }
+    }
+
+    pub fn render(&self) -> EmailMessage {
+        let (subject, greeting, cta, validity) = match self.locale {
+            "cs" => (
+                format!("Pozvánka do týmu {} – Sazinka", self.organization_name),
+                "Dobrý den,",
+                "Účet si založíte na odkazu:",
+                format!("Pozvánka platí {} dní.", self.valid_days),
+            ),
+            "sk" => (
+                format!("Pozvánka do tímu {} – Sazinka", self.organization_name),
+                "Dobrý deň,",
+                "Účet si založíte na odkaze:",
+                format!("Pozvánka platí {} dní.", self.valid_days),
+            ),
+            _ => (
+                format!("Join {} on Sazinka", self.organization_name),
+                "Hello,",
+                "Create your account here:",
+                format!("This invitation is valid for {} days.", self.valid_days),
+            ),
+        };
+        let invite_html = self.invite(&html_escape(self.inviter_name), &html_escape(self.organization_name));
+
+        EmailMessage {
+            to: self.to.to_string(),
+            subject,
+            html: format!(
+                "<p>{}</p>\n<p>{}</p>\n<p>{}</p>\n<p><a href=\"{url}\">{url}</a></p>\n<p>{}</p>",
+                greeting,
+                invite_html,
+                cta,
+                validity,
+                url = self.accept_url
+            ),
+            text: format!(
+                "{}\n\n{}\n\n{} {}\n\n{}",
+                greeting,
+                self.invite(self.inviter_name, self.organization_name),
+                cta,
+                self.accept_url,
+                validity
+            ),
+        }
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -262,4 +713,171 @@ mod tests {
         assert!(email.subject.contains("already registered"));
         assert!(email.text.contains("https://app.sazinka.cz/login"));
     }
+
+    // --- PasswordResetEmail ---
+
+    #[test]
+    fn password_reset_email_en() {
+        let email = PasswordResetEmail {
+            to: "user@example.com",
+            reset_url: "https://app.sazinka.cz/reset-password?token=abc123",
+            locale: "en",
+        }
+        .render();
+        assert!(email.subject.contains("new password"));
+        assert!(email.html.contains("https://app.sazinka.cz/reset-password?token=abc123"));
+        assert!(email.text.contains("24 hours"));
+    }
+
+    #[test]
+    fn password_reset_email_cs() {
+        let email = PasswordResetEmail {
+            to: "user@example.com",
+            reset_url: "https://app.sazinka.cz/reset-password?token=abc123",
+            locale: "cs",
+        }
+        .render();
+        assert!(email.subject.contains("nové heslo"));
+        assert!(email.text.contains("24 hodin"));
+    }
+
+    // --- NewDeviceLoginEmail ---
+
+    #[test]
+    fn new_device_login_email_en() {
+        let email = NewDeviceLoginEmail {
+            to: "user@example.com",
+            device: Some("Mozilla/5.0 (X11; Linux x86_64)"),
+            ip: Some("203.0.113.7"),
+            locale: "en",
+        }
+        .render();
+        assert!(email.subject.contains("New sign-in"));
+        assert!(email.html.contains("203.0.113.7"));
+        assert!(email.text.contains("Mozilla/5.0 (X11; Linux x86_64)"));
+    }
+
+    #[test]
+    fn new_device_login_email_escapes_user_agent() {
+        let email = NewDeviceLoginEmail {
+            to: "user@example.com",
+            device: Some("<script>alert(1)</script>"),
+            ip: None,
+            locale: "cs",
+        }
+        .render();
+        assert!(email.subject.contains("Nové přihlášení"));
+        assert!(!email.html.contains("<script>"));
+        assert!(email.html.contains("&lt;script&gt;"));
+    }
+
+    // --- RescheduleRequestedEmail ---
+
+    #[test]
+    fn reschedule_requested_email_cs_formats_dates() {
+        let email = RescheduleRequestedEmail {
+            to: "dispatch@example.com",
+            customer_name: "Jan Novák",
+            original_date: NaiveDate::from_ymd_opt(2026, 3, 10).unwrap(),
+            requested_date: NaiveDate::from_ymd_opt(2026, 3, 17).unwrap(),
+            note: Some("Dopoledne"),
+            suggestion_count: 2,
+            locale: "cs",
+        }
+        .render();
+        assert!(email.subject.contains("Jan Novák"));
+        assert!(email.text.contains("10.03.2026"));
+        assert!(email.text.contains("17.03.2026"));
+    }
+
+    #[test]
+    fn reschedule_requested_email_escapes_note() {
+        let email = RescheduleRequestedEmail {
+            to: "dispatch@example.com",
+            customer_name: "<b>Eve</b>",
+            original_date: NaiveDate::from_ymd_opt(2026, 3, 10).unwrap(),
+            requested_date: NaiveDate::from_ymd_opt(2026, 3, 17).unwrap(),
+            note: Some("<script>x</script>"),
+            suggestion_count: 0,
+            locale: "en",
+        }
+        .render();
+        assert!(!email.html.contains("<script>"));
+        assert!(email.html.contains("&lt;b&gt;Eve&lt;/b&gt;"));
+        assert!(email.text.contains("2026-03-17"));
+    }
+
+    // --- EscalationDigestEmail ---
+
+    #[test]
+    fn escalation_digest_email_lists_items() {
+        let lines = vec!["Revision overdue – Jan Novák".to_string(), "Missed visit – <b>Eve</b>".to_string()];
+        let email = EscalationDigestEmail { to: "owner@example.com", lines: &lines, locale: "en" }.render();
+        assert!(email.subject.contains("2 items"));
+        assert!(email.text.contains("- Revision overdue – Jan Novák"));
+        assert!(email.html.contains("<li>Missed visit – &lt;b&gt;Eve&lt;/b&gt;</li>"));
+    }
+
+    #[test]
+    fn escalation_digest_email_cs() {
+        let lines = vec!["Revize po termínu – Jan Novák".to_string()];
+        let email = EscalationDigestEmail { to: "owner@example.com", lines: &lines, locale: "cs" }.render();
+        assert!(email.subject.contains("Eskalace"));
+        assert!(email.text.starts_with("Dobrý den"));
+    }
+
+    // --- OrganizationInviteEmail ---
+
+    #[test]
+    fn organization_invite_email_en() {
+        let email = OrganizationInviteEmail {
+            to: "jana@example.com",
+            organization_name: "Revize <Novák>",
+            inviter_name: "Petr Novák",
+            accept_url: "https://app.sazinka.cz/join?token=abc123",
+            valid_days: 7,
+            locale: "en",
+        }
+        .render();
+        assert_eq!(email.to, "jana@example.com");
+        assert!(email.subject.contains("Join Revize <Novák>"));
+        assert!(email.html.contains("Revize &lt;Novák&gt;"));
+        assert!(email.html.contains("https://app.sazinka.cz/join?token=abc123"));
+        assert!(email.text.contains("valid for 7 days"));
+    }
+
+    #[test]
+    fn organization_invite_email_cs() {
+        let email = OrganizationInviteEmail {
+            to: "jana@example.com",
+            organization_name: "Revize Novák",
+            inviter_name: "Petr Novák",
+            accept_url: "https://app.sazinka.cz/join?token=abc123",
+            valid_days: 7,
+            locale: "cs",
+        }
+        .render();
+        assert!(email.subject.contains("Pozvánka do týmu"));
+        assert!(email.text.starts_with("Dobrý den"));
+    }
+
+    // --- NewLeadEmail ---
+
+    #[test]
+    fn new_lead_email_escapes_message() {
+        let email = NewLeadEmail {
+            to: "dispatch@example.com",
+            name: "<b>Eve</b>",
+            contact: "eve@example.com",
+            message: Some("<script>x</script>"),
+            preferred_date: NaiveDate::from_ymd_opt(2026, 5, 4),
+            existing_customer: false,
+            locale: "cs",
+        }
+        .render();
+        assert!(email.subject.contains("Nová poptávka"));
+        assert!(!email.html.contains("<script>"));
+        assert!(email.html.contains("&lt;b&gt;Eve&lt;/b&gt;"));
+        assert!(email.text.contains("04.05.2026"));
+    }
 }
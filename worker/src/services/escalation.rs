@@ -0,0 +1,190 @@
+//! Deadline escalations
+//!
+//! A background loop applies every enabled escalation rule: items that newly
+//! match are logged (once per rule), raised in the notification center and,
+//! for rules with e-mail enabled, sent to the account owner in one digest.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::email_sender::EmailSender;
+use crate::services::email_templates::EscalationDigestEmail;
+use crate::services::notification_dispatch;
+use crate::types::escalation::{escalation_entity_type, EscalationRule, ESCALATION_RULE_VISIT_MISSED};
+use crate::types::notification::{NOTIFICATION_CATEGORY_REMINDER, NOTIFICATION_KIND_ESCALATION};
+
+const SCHEDULER_TICK: Duration = Duration::from_secs(3600);
+
+/// Localized notification title and body for an escalated item
+pub fn notification_text(
+    rule_type: &str,
+    customer_name: &str,
+    reference_date: NaiveDate,
+    locale: &str,
+) -> (String, String) {
+    let missed = rule_type == ESCALATION_RULE_VISIT_MISSED;
+    match locale {
+        "cs" => {
+            let date = reference_date.format("%d.%m.%Y");
+            if missed {
+                (
+                    format!("Zmeškaná návštěva – {}", customer_name),
+                    format!("Návštěva naplánovaná na {} nebyla uzavřena.", date),
+                )
+            } else {
+                (
+                    format!("Revize po termínu – {}", customer_name),
+                    format!("Revize měla být provedena do {} a stále není hotová.", date),
+                )
+            }
+        }
+        "sk" => {
+            let date = reference_date.format("%d.%m.%Y");
+            if missed {
+                (
+                    format!("Zmeškaná návšteva – {}", customer_name),
+                    format!("Návšteva naplánovaná na {} nebola uzavretá.", date),
+                )
+            } else {
+                (
+                    format!("Revízia po termíne – {}", customer_name),
+                    format!("Revízia mala byť vykonaná do {} a stále nie je hotová.", date),
+                )
+            }
+        }
+        _ => {
+            let date = reference_date.format("%Y-%m-%d");
+            if missed {
+                (
+                    format!("Missed visit – {}", customer_name),
+                    format!("The visit planned for {} was not closed.", date),
+                )
+            } else {
+                (
+                    format!("Revision overdue – {}", customer_name),
+                    format!("The revision was due on {} and is still open.", date),
+                )
+            }
+        }
+    }
+}
+
+/// Apply the rules of one user. Returns the number of escalated items.
+async fn escalate_user(
+    pool: &PgPool,
+    email_sender: &dyn EmailSender,
+    user_id: Uuid,
+    rules: &[EscalationRule],
+) -> Result<usize> {
+    let Some(settings) = queries::settings::get_user_settings(pool, user_id).await? else {
+        return Ok(0);
+    };
+
+    let mut escalated = 0;
+    let mut email_logs = Vec::new();
+    let mut email_lines = Vec::new();
+
+    for rule in rules {
+        let items = queries::escalation::escalate_rule(pool, rule).await?;
+        for item in items {
+            let customer_name = item.customer_name.as_deref().unwrap_or("-");
+            let (title, body) = notification_text(&rule.rule_type, customer_name, item.reference_date, &settings.locale);
+            let notification_id = notification_dispatch::notify(
+                pool,
+                user_id,
+                NOTIFICATION_CATEGORY_REMINDER,
+                NOTIFICATION_KIND_ESCALATION,
+                &title,
+                Some(&body),
+                Some((escalation_entity_type(&rule.rule_type), item.entity_id)),
+            )
+            .await?;
+            // Held back for a digest (or switched off): nothing to link yet
+            if let Some(notification_id) = notification_id {
+                queries::escalation::set_log_notification(pool, item.log_id, notification_id).await?;
+            }
+
+            if rule.notify_email {
+                email_logs.push(item.log_id);
+                email_lines.push(format!("{}: {}", title, body));
+            }
+            escalated += 1;
+        }
+    }
+
+    if !email_lines.is_empty() {
+        let message = EscalationDigestEmail { to: &settings.email, lines: &email_lines, locale: &settings.locale }.render();
+        let outcome = email_sender.send(message).await;
+        if let Err(ref e) = outcome {
+            warn!("Failed to send escalation e-mail to user {}: {}", user_id, e);
+        }
+        let email_error = outcome.err().map(|e| e.to_string());
+        queries::escalation::set_log_email_result(pool, &email_logs, email_error.as_deref()).await?;
+    }
+
+    Ok(escalated)
+}
+
+/// Apply all enabled rules once
+pub async fn run_escalations(pool: &PgPool, email_sender: &dyn EmailSender) -> Result<usize> {
+    let mut rules_by_user: BTreeMap<Uuid, Vec<EscalationRule>> = BTreeMap::new();
+    for rule in queries::escalation::list_enabled_rules(pool).await? {
+        rules_by_user.entry(rule.user_id).or_default().push(rule);
+    }
+
+    let mut escalated = 0;
+    for (user_id, rules) in rules_by_user {
+        match escalate_user(pool, email_sender, user_id, &rules).await {
+            Ok(count) => escalated += count,
+            Err(e) => error!("Escalation failed for user {}: {}", user_id, e),
+        }
+    }
+
+    Ok(escalated)
+}
+
+/// Background loop applying escalation rules
+pub async fn run_scheduler(pool: PgPool, email_sender: Arc<dyn EmailSender>) {
+    info!("Escalation scheduler started");
+    let mut ticker = tokio::time::interval(SCHEDULER_TICK);
+
+    loop {
+        ticker.tick().await;
+
+        match run_escalations(&pool, email_sender.as_ref()).await {
+            Ok(0) => {}
+            Ok(count) => info!("Escalated {} overdue items", count),
+            Err(e) => error!("Failed to run escalations: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::escalation::ESCALATION_RULE_REVISION_OVERDUE;
+
+    #[test]
+    fn test_notification_text_revision_en() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let (title, body) = notification_text(ESCALATION_RULE_REVISION_OVERDUE, "Jan Novák", date, "en");
+        assert_eq!(title, "Revision overdue – Jan Novák");
+        assert!(body.contains("2026-03-10"));
+    }
+
+    #[test]
+    fn test_notification_text_missed_visit_cs() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let (title, body) = notification_text(ESCALATION_RULE_VISIT_MISSED, "Jan Novák", date, "cs");
+        assert!(title.starts_with("Zmeškaná návštěva"));
+        assert!(body.contains("10.03.2026"));
+    }
+}
@@ -1,6 +1,6 @@
 //! Export JetStream processor for asynchronous Export+ jobs.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{Cursor, Write};
 use std::path::PathBuf;
@@ -8,7 +8,7 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use anyhow::{anyhow, Result};
-use async_nats::jetstream::{self, Context as JsContext};
+use async_nats::jetstream;
 use async_nats::Client;
 use chrono::{DateTime, Duration, NaiveDate, Utc};
 use futures::StreamExt;
@@ -20,7 +20,15 @@ use uuid::Uuid;
 use zip::write::SimpleFileOptions;
 
 use crate::db::queries;
+use crate::config::ExportStorageConfig;
+use crate::services::accounting_export::{self, AccountingCompany, AccountingExportOptions};
+use crate::services::export_storage::ExportStore;
 use crate::services::job_history::JOB_HISTORY;
+use crate::services::revision_report::{report_filename, ReportRenderer};
+use crate::services::vat_summary;
+use crate::subjects;
+use crate::transport::{JobMessage, JobQueue};
+use crate::types::currency::DEFAULT_CURRENCY;
 
 /// Typed error for export operations — distinguishes cancellation from real errors.
 #[derive(thiserror::Error, Debug)]
@@ -33,8 +41,12 @@ pub enum ExportError {
 
 const STREAM_NAME: &str = "SAZINKA_EXPORT_JOBS";
 const CONSUMER_NAME: &str = "export_workers";
-const SUBJECT: &str = "sazinka.export";
-const STATUS_PREFIX: &str = "sazinka.job.export.status";
+const SUBJECT: &str = subjects::EXPORT;
+const STATUS_PREFIX: &str = subjects::job::EXPORT_STATUS;
+/// Folder of the revision report PDFs in the archive
+const REVISION_REPORTS_DIR: &str = "revision_reports";
+/// Reports rendered between two progress updates
+const REPORT_PROGRESS_EVERY: usize = 10;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -55,6 +67,14 @@ pub enum ExportFile {
     WorkLog,
     Routes,
     Notes,
+    /// Issued-invoice drafts for completed visits (Pohoda XML data pack)
+    PohodaXml,
+    /// Issued-invoice drafts for completed visits (Money S3 XML transfer)
+    MoneyS3Xml,
+    /// Monthly VAT summary of billed work items (CSV + XLSX)
+    VatSummary,
+    /// Report PDF of every revision completed in the period
+    RevisionReports,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -79,6 +99,9 @@ pub struct ExportPlusRequest {
     pub selected_worker_id: Option<String>,
     pub user_time_zone: Option<String>,
     pub user_time_zone_offset_minutes: Option<i32>,
+    /// Company identification and number series for Pohoda / Money S3 files
+    #[serde(default)]
+    pub accounting: Option<AccountingExportOptions>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -154,25 +177,40 @@ struct ExportDataSet {
 
 pub struct ExportProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
     pool: PgPool,
+    /// Signs the device QR codes printed on revision reports
+    jwt_secret: Arc<String>,
+    app_base_url: Arc<String>,
+    /// Bucket receiving finished archives, when configured
+    store: Option<ExportStore>,
 }
 
 impl ExportProcessor {
-    pub async fn new(client: Client, pool: PgPool) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+    pub async fn new(
+        client: Client,
+        pool: PgPool,
+        jwt_secret: Arc<String>,
+        app_base_url: Arc<String>,
+        storage: Option<ExportStorageConfig>,
+    ) -> Result<Self> {
+        let queue = JobQueue::new(client.clone());
         let stream_config = jetstream::stream::Config {
             name: STREAM_NAME.to_string(),
-            subjects: vec![format!("{}.>", SUBJECT)],
+            subjects: vec![subjects::tail_wildcard(SUBJECT)],
             max_messages: 1000,
             max_bytes: 200 * 1024 * 1024,
             retention: jetstream::stream::RetentionPolicy::WorkQueue,
             ..Default::default()
         };
-        js.get_or_create_stream(stream_config).await?;
+        queue.ensure_stream(stream_config).await?;
         info!("JetStream export stream '{}' ready", STREAM_NAME);
 
-        Ok(Self { client, js, pool })
+        let store = match storage {
+            Some(config) => Some(ExportStore::new(config).await),
+            None => None,
+        };
+        Ok(Self { client, queue, pool, jwt_secret, app_base_url, store })
     }
 
     pub async fn submit_job(&self, user_id: Uuid, request: ExportPlusRequest) -> Result<ExportSubmitResponse> {
@@ -184,7 +222,7 @@ impl ExportProcessor {
         let job_id = job.id;
         let payload = serde_json::to_vec(&job)?;
         let subject = format!("{}.submit", SUBJECT);
-        self.js.publish(subject, payload.into()).await?.await?;
+        self.queue.publish(subject, payload.into()).await?;
 
         self.publish_status(job_id, json!({ "type": "queued", "position": 1 })).await?;
 
@@ -222,24 +260,22 @@ impl ExportProcessor {
             timestamp: Utc::now(),
             status,
         };
-        let subject = format!("{}.{}", STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         self.client.publish(subject, payload.into()).await?;
         Ok(())
     }
 
     pub async fn start_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(STREAM_NAME).await?;
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(CONSUMER_NAME.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
             max_deliver: 3,
-            filter_subject: format!("{}.>", SUBJECT),
+            filter_subject: subjects::tail_wildcard(SUBJECT),
             ..Default::default()
         };
 
-        let consumer = stream.get_or_create_consumer(CONSUMER_NAME, consumer_config).await?;
-        let mut messages = consumer.messages().await?;
+        let mut messages = self.queue.consume(STREAM_NAME, consumer_config).await?;
 
         while let Some(msg) = messages.next().await {
             match msg {
@@ -256,7 +292,7 @@ impl ExportProcessor {
         Ok(())
     }
 
-    async fn process_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_job(&self, msg: JobMessage) -> Result<()> {
         use crate::services::cancellation::CANCELLATION;
         
         let started_wall = Utc::now();
@@ -273,6 +309,7 @@ impl ExportProcessor {
             JOB_HISTORY.record_cancelled(job_id, "export", user_id, started_wall);
             return Ok(());
         }
+        JOB_HISTORY.record_running(job_id, "export", user_id, started_wall);
 
         self.publish_status(
             job_id,
@@ -283,7 +320,7 @@ impl ExportProcessor {
         let outcome = self.build_export_zip(user_id, &job.request, job_id).await;
 
         match outcome {
-            Ok((row_count, file_size, file_name)) => {
+            Ok((row_count, file_size, file_name, download_url)) => {
                 self.publish_status(
                     job_id,
                     json!({
@@ -293,7 +330,8 @@ impl ExportProcessor {
                             "fileName": file_name,
                             "fileSizeBytes": file_size,
                             "rowCount": row_count,
-                            "downloadReady": true
+                            "downloadReady": true,
+                            "downloadUrl": download_url
                         }
                     }),
                 )
@@ -338,7 +376,7 @@ impl ExportProcessor {
         user_id: Uuid,
         request: &ExportPlusRequest,
         job_id: Uuid,
-    ) -> Result<(u32, u64, String), ExportError> {
+    ) -> Result<(u32, u64, String, Option<String>), ExportError> {
         use crate::services::cancellation::CANCELLATION;
 
         let filters = &request.filters;
@@ -425,6 +463,53 @@ impl ExportProcessor {
             files.push(("notes.csv".to_string(), build_notes_csv(&dataset)));
         }
 
+        // Accounting files are user-level as well — one document per completed visit
+        let mut accounting_documents: u32 = 0;
+        if request.selected_files.contains(&ExportFile::PohodaXml)
+            || request.selected_files.contains(&ExportFile::MoneyS3Xml)
+        {
+            let options = request.accounting.clone().unwrap_or_default();
+            let company = self.resolve_accounting_company(user_id, &options).await?;
+            let drafts = accounting_export::build_invoice_drafts(
+                &dataset.visits,
+                &dataset.work_items,
+                &dataset.customers,
+                &dataset.devices,
+                &options,
+                &company.currency,
+            );
+            accounting_documents = drafts.len() as u32;
+
+            if request.selected_files.contains(&ExportFile::PohodaXml) {
+                files.push((
+                    "pohoda_invoices.xml".to_string(),
+                    accounting_export::render_pohoda_xml(&drafts, &company, &job_id.to_string()),
+                ));
+            }
+            if request.selected_files.contains(&ExportFile::MoneyS3Xml) {
+                files.push((
+                    "money_s3_invoices.xml".to_string(),
+                    accounting_export::render_money_s3_xml(&drafts, &company),
+                ));
+            }
+        }
+
+        // VAT summary is user-level too; XLSX goes alongside the CSV
+        let mut binary_files: Vec<(String, Vec<u8>)> = Vec::new();
+        if request.selected_files.contains(&ExportFile::VatSummary) {
+            let rows = vat_summary::build_vat_summary(&dataset.visits, &dataset.work_items);
+            files.push(("vat_summary.csv".to_string(), build_vat_summary_csv(&rows)));
+            binary_files.push(("vat_summary.xlsx".to_string(), vat_summary::render_vat_summary_xlsx(&rows)?));
+        }
+
+        // Revision reports: one PDF per completed revision in a folder
+        let mut report_count: u32 = 0;
+        if request.selected_files.contains(&ExportFile::RevisionReports) {
+            report_count = self
+                .render_revision_reports(user_id, date_from, date_to, job_id, &mut binary_files)
+                .await?;
+        }
+
         // ── Cancellation check: after CSV generation ──
         if CANCELLATION.is_cancelled(&job_id) {
             return Err(ExportError::Cancelled);
@@ -438,24 +523,86 @@ impl ExportProcessor {
 
         let mut zip_writer = zip::ZipWriter::new(Cursor::new(Vec::<u8>::new()));
         let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
-        let mut total_rows: u32 = 0;
+        let mut total_rows: u32 = accounting_documents + report_count;
 
         for (name, content) in files {
-            total_rows += content.lines().skip(1).count() as u32;
+            if name.ends_with(".csv") {
+                total_rows += content.lines().skip(1).count() as u32;
+            }
             zip_writer.start_file(name, options).map_err(|e| ExportError::Other(anyhow::Error::from(e)))?;
             zip_writer.write_all(content.as_bytes()).map_err(|e| ExportError::Other(anyhow::Error::from(e)))?;
         }
 
+        for (name, bytes) in binary_files {
+            zip_writer.start_file(name, options).map_err(|e| ExportError::Other(anyhow::Error::from(e)))?;
+            zip_writer.write_all(&bytes).map_err(|e| ExportError::Other(anyhow::Error::from(e)))?;
+        }
+
         let zip_cursor = zip_writer.finish().map_err(|e| ExportError::Other(anyhow::Error::from(e)))?;
         let zip_bytes = zip_cursor.into_inner();
         let file_size = zip_bytes.len() as u64;
         Self::save_export_file(user_id, job_id, &zip_bytes)?;
+        let file_name = export_download_filename(Utc::now(), request.user_time_zone_offset_minutes, job_id);
+
+        // The local copy stays downloadable if the upload fails
+        let download_url = match &self.store {
+            Some(store) => match store.deliver(user_id, job_id, &file_name, zip_bytes).await {
+                Ok(url) => Some(url),
+                Err(e) => {
+                    warn!("Export job {}: {}", job_id, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        Ok((total_rows, file_size, file_name, download_url))
+    }
+
+    /// Render the report of every revision completed between the dates into
+    /// `out`, reporting progress between 45 and 80 %. Returns the count.
+    async fn render_revision_reports(
+        &self,
+        user_id: Uuid,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        job_id: Uuid,
+        out: &mut Vec<(String, Vec<u8>)>,
+    ) -> Result<u32, ExportError> {
+        use crate::services::cancellation::CANCELLATION;
+
+        let revisions = queries::revision::list_completed_between(&self.pool, user_id, date_from, date_to).await?;
+        let Some(mut renderer) = ReportRenderer::new(&self.pool, &self.jwt_secret, &self.app_base_url, user_id).await?
+        else {
+            return Ok(0);
+        };
 
-        Ok((
-            total_rows,
-            file_size,
-            export_download_filename(Utc::now(), request.user_time_zone_offset_minutes, job_id),
-        ))
+        let total = revisions.len();
+        let mut taken = HashSet::new();
+        let mut count: u32 = 0;
+        for (done, revision) in revisions.iter().enumerate() {
+            if CANCELLATION.is_cancelled(&job_id) {
+                return Err(ExportError::Cancelled);
+            }
+            if let Some(pdf) = renderer.render(revision).await? {
+                out.push((report_archive_path(revision, &mut taken), pdf));
+                count += 1;
+            }
+            if (done + 1) % REPORT_PROGRESS_EVERY == 0 && done + 1 < total {
+                self.publish_status(
+                    job_id,
+                    json!({
+                        "type": "processing",
+                        "progress": 45 + 35 * (done + 1) / total,
+                        "message": "jobs:rendering_reports",
+                        "processed": done + 1,
+                        "total": total
+                    }),
+                )
+                .await?;
+            }
+        }
+        Ok(count)
     }
 
     async fn load_dataset(
@@ -476,6 +623,7 @@ impl ExportProcessor {
             &self.pool,
             user_id,
             None,
+            false,
             None,
             None,
             Some(date_from),
@@ -495,6 +643,7 @@ impl ExportProcessor {
             None,
             None,
             None,
+            &[],
             10_000,
             0,
         )
@@ -609,6 +758,24 @@ impl ExportProcessor {
         }
     }
 
+    /// Issuing company: explicit options win over business settings
+    async fn resolve_accounting_company(
+        &self,
+        user_id: Uuid,
+        options: &AccountingExportOptions,
+    ) -> Result<AccountingCompany> {
+        let settings = queries::settings::get_user_settings(&self.pool, user_id).await?;
+        Ok(AccountingCompany {
+            name: options
+                .company_name
+                .clone()
+                .or_else(|| settings.as_ref().and_then(|s| s.business_name.clone())),
+            ico: options.company_ico.clone().or_else(|| settings.as_ref().and_then(|s| s.ico.clone())),
+            dic: options.company_dic.clone().or_else(|| settings.as_ref().and_then(|s| s.dic.clone())),
+            currency: settings.map(|s| s.currency).unwrap_or_else(|| DEFAULT_CURRENCY.to_string()),
+        })
+    }
+
     fn collect_files_for_context(
         &self,
         selected: &[ExportFile],
@@ -650,8 +817,12 @@ impl ExportProcessor {
                         build_route_stops_csv(dataset, worker, include_worker_uuid_col),
                     ));
                 }
-                // Notes are user-level, not worker-scoped — handled outside collect_files_for_context
-                ExportFile::Notes => {},
+                // Notes and accounting files are user-level, not worker-scoped — handled outside collect_files_for_context
+                ExportFile::Notes
+                | ExportFile::PohodaXml
+                | ExportFile::MoneyS3Xml
+                | ExportFile::VatSummary
+                | ExportFile::RevisionReports => {},
             }
         }
     }
@@ -675,6 +846,19 @@ impl ExportProcessor {
     }
 }
 
+/// Path of a report in the archive; reports without a document number
+/// completed the same day get the revision id appended
+fn report_archive_path(revision: &crate::types::Revision, taken: &mut HashSet<String>) -> String {
+    let filename = report_filename(revision);
+    let mut path = format!("{}/{}", REVISION_REPORTS_DIR, filename);
+    if !taken.insert(path.clone()) {
+        let stem = filename.trim_end_matches(".pdf");
+        path = format!("{}/{}-{}.pdf", REVISION_REPORTS_DIR, stem, revision.id.simple());
+        taken.insert(path.clone());
+    }
+    path
+}
+
 fn parse_date_opt(value: Option<&str>) -> Result<Option<NaiveDate>> {
     match value {
         Some(v) if !v.trim().is_empty() => Ok(Some(NaiveDate::parse_from_str(v, "%Y-%m-%d")?)),
@@ -726,8 +910,13 @@ fn pseudo_id(worker_uuid: Option<&str>, entity: &str, source_id: Uuid) -> String
 }
 
 /// Resolve a customer reference for export (round-trip safe).
-/// Priority: ICO -> email -> phone -> customer_uuid:<uuid>
+/// Priority: customer code -> ICO -> email -> phone -> customer_uuid:<uuid>
 fn customer_ref(customer: &crate::types::Customer) -> String {
+    if let Some(ref code) = customer.customer_code {
+        if !code.is_empty() {
+            return code.clone();
+        }
+    }
     if let Some(ref ico) = customer.ico {
         if !ico.is_empty() {
             return ico.clone();
@@ -796,6 +985,7 @@ fn write_csv(headers: &[&str], rows: &[Vec<String>]) -> String {
 fn build_customers_csv(dataset: &ExportDataSet, worker: Option<&WorkerCtx>, include_worker: bool) -> String {
     let mut headers = vec![
         "type", "name", "contact_person", "ico", "dic", "street", "city", "postal_code", "country", "phone", "email",
+        "customer_code", "acquisition_source",
     ];
     if include_worker {
         headers.insert(0, "worker_uuid");
@@ -820,6 +1010,8 @@ fn build_customers_csv(dataset: &ExportDataSet, worker: Option<&WorkerCtx>, incl
                 c.country.clone().unwrap_or_default(),
                 c.phone.clone().unwrap_or_default(),
                 c.email.clone().unwrap_or_default(),
+                c.customer_code.clone().unwrap_or_default(),
+                c.acquisition_source.clone().unwrap_or_default(),
             ];
             if include_worker {
                 row.insert(0, worker.map(|w| w.worker_uuid.clone()).unwrap_or_default());
@@ -1071,7 +1263,7 @@ fn build_routes_csv(dataset: &ExportDataSet, worker: Option<&WorkerCtx>, include
 
 /// Build notes.csv — one row per non-deleted note entry.
 /// entity_ref encodes a round-trip-safe reference:
-///   customer → ICO / email / phone / customer_uuid:<uuid>
+///   customer → code / ICO / email / phone / customer_uuid:<uuid>
 ///   device   → serial_number / device_name / device_uuid:<uuid>
 ///   visit    → visit_uuid:<uuid>
 fn build_notes_csv(dataset: &ExportDataSet) -> String {
@@ -1118,6 +1310,12 @@ fn build_notes_csv(dataset: &ExportDataSet) -> String {
     write_csv(&headers, &rows)
 }
 
+/// Build vat_summary.csv — one row per (month, currency, VAT rate, payment method).
+fn build_vat_summary_csv(rows: &[vat_summary::VatSummaryRow]) -> String {
+    let rows = rows.iter().map(|r| r.cells()).collect::<Vec<_>>();
+    write_csv(vat_summary::VAT_SUMMARY_HEADERS, &rows)
+}
+
 fn build_route_stops_csv(dataset: &ExportDataSet, worker: Option<&WorkerCtx>, include_worker: bool) -> String {
     let mut headers = vec![
         "route_id",
@@ -1135,6 +1333,8 @@ fn build_route_stops_csv(dataset: &ExportDataSet, worker: Option<&WorkerCtx>, in
         "scheduled_time_start",
         "scheduled_time_end",
         "revision_status",
+        "notes",
+        "tasks",
     ];
     if include_worker {
         headers.insert(0, "worker_uuid");
@@ -1167,6 +1367,12 @@ fn build_route_stops_csv(dataset: &ExportDataSet, worker: Option<&WorkerCtx>, in
                 s.scheduled_time_start.map(|t| t.format("%H:%M").to_string()).unwrap_or_default(),
                 s.scheduled_time_end.map(|t| t.format("%H:%M").to_string()).unwrap_or_default(),
                 s.revision_status.clone().unwrap_or_default(),
+                s.notes.clone().unwrap_or_default(),
+                s.tasks
+                    .iter()
+                    .map(|t| format!("[{}] {}", if t.done { "x" } else { " " }, t.text))
+                    .collect::<Vec<_>>()
+                    .join(" | "),
             ];
             if include_worker {
                 row.insert(0, worker.map(|w| w.worker_uuid.clone()).unwrap_or_default());
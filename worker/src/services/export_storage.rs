@@ -0,0 +1,83 @@
+//! Object storage delivery of finished exports
+//!
+//! When EXPORT_S3_BUCKET is set, finished export archives are uploaded to
+//! the bucket and the job result carries a presigned download link, so
+//! large month-end archives don't travel through NATS. The worker keeps
+//! its local copy for the regular download subject either way.
+
+use anyhow::{anyhow, Context, Result};
+use aws_sdk_s3::error::DisplayErrorContext;
+use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::primitives::ByteStream;
+use uuid::Uuid;
+
+use crate::config::ExportStorageConfig;
+
+/// Key of an export archive in the bucket
+pub fn object_key(prefix: &str, user_id: Uuid, job_id: Uuid) -> String {
+    format!("{}{}/{}.zip", prefix, user_id, job_id)
+}
+
+/// S3-compatible bucket receiving export archives
+pub struct ExportStore {
+    client: aws_sdk_s3::Client,
+    config: ExportStorageConfig,
+}
+
+impl ExportStore {
+    /// Credentials come from the standard AWS variables (AWS_ACCESS_KEY_ID, ...)
+    pub async fn new(config: ExportStorageConfig) -> Self {
+        let sdk_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_config::Region::new(config.region.clone()))
+            .load()
+            .await;
+        let mut builder = aws_sdk_s3::config::Builder::from(&sdk_config);
+        if let Some(endpoint) = &config.endpoint {
+            builder = builder.endpoint_url(endpoint).force_path_style(true);
+        }
+
+        Self { client: aws_sdk_s3::Client::from_conf(builder.build()), config }
+    }
+
+    /// Upload an archive and return a download link valid for the configured time
+    pub async fn deliver(&self, user_id: Uuid, job_id: Uuid, filename: &str, bytes: Vec<u8>) -> Result<String> {
+        let key = object_key(&self.config.prefix, user_id, job_id);
+        self.client
+            .put_object()
+            .bucket(&self.config.bucket)
+            .key(&key)
+            .content_type("application/zip")
+            .content_disposition(format!("attachment; filename=\"{}\"", filename))
+            .body(ByteStream::from(bytes))
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to upload export {}: {}", key, DisplayErrorContext(e)))?;
+
+        let presigning = PresigningConfig::expires_in(self.config.link_ttl).context("Invalid export link lifetime")?;
+        let request = self
+            .client
+            .get_object()
+            .bucket(&self.config.bucket)
+            .key(&key)
+            .presigned(presigning)
+            .await
+            .map_err(|e| anyhow!("Failed to sign export link {}: {}", key, DisplayErrorContext(e)))?;
+        Ok(request.uri().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_key() {
+        let user = Uuid::nil();
+        let job = Uuid::from_u128(1);
+        assert_eq!(
+            object_key("exports/", user, job),
+            format!("exports/{}/{}.zip", user, job)
+        );
+        assert!(object_key("", user, job).starts_with("00000000-"));
+    }
+}
@@ -72,6 +72,28 @@ pub fn time_matrix(points: &[Coordinates]) -> Vec<Vec<f64>> {
     matrix
 }
 
+/// Whether a point lies inside polygon rings ([lng, lat] pairs).
+///
+/// Uses the even-odd rule, so holes and disjoint polygons need no special
+/// handling: a point inside a hole is inside two rings and counts as outside.
+pub fn point_in_rings(point: &Coordinates, rings: &[Vec<[f64; 2]>]) -> bool {
+    let mut inside = false;
+    for ring in rings {
+        let mut j = ring.len().wrapping_sub(1);
+        for i in 0..ring.len() {
+            let [xi, yi] = ring[i];
+            let [xj, yj] = ring[j];
+            if (yi > point.lat) != (yj > point.lat)
+                && point.lng < (xj - xi) * (point.lat - yi) / (yj - yi) + xi
+            {
+                inside = !inside;
+            }
+            j = i;
+        }
+    }
+    inside
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -139,4 +161,16 @@ mod tests {
         // Should be symmetric
         assert!((matrix[0][1] - matrix[1][0]).abs() < 0.001);
     }
+
+    #[test]
+    fn test_point_in_rings_with_hole() {
+        let outer = vec![[16.0, 49.0], [17.0, 49.0], [17.0, 50.0], [16.0, 50.0], [16.0, 49.0]];
+        let hole = vec![[16.4, 49.4], [16.6, 49.4], [16.6, 49.6], [16.4, 49.6], [16.4, 49.4]];
+        let rings = vec![outer, hole];
+
+        assert!(point_in_rings(&Coordinates { lat: 49.2, lng: 16.2 }, &rings));
+        assert!(!point_in_rings(&Coordinates { lat: 49.5, lng: 16.5 }, &rings));
+        assert!(!point_in_rings(&Coordinates { lat: 50.5, lng: 16.5 }, &rings));
+        assert!(!point_in_rings(&Coordinates { lat: 49.5, lng: 16.5 }, &[]));
+    }
 }
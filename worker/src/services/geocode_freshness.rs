@@ -0,0 +1,138 @@
+#![allow(dead_code)]
+//! Freshness of the self-hosted Nominatim index
+//!
+//! Nominatim reports when its OSM data was last updated (`/status`). An
+//! index that is never re-imported silently misses new streets and
+//! developments — their addresses just fail to geocode. The monitor
+//! checks the timestamp periodically and keeps the latest verdict for the
+//! admin diagnostics and the dashboard.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::services::http::{self, HttpService};
+
+/// Index older than this is reported as stale (NOMINATIM_MAX_DATA_AGE_DAYS)
+pub const DEFAULT_MAX_AGE_DAYS: i64 = 180;
+/// How often the monitor asks Nominatim for its data timestamp
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Age of the geocoding data at the last check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexFreshness {
+    pub checked_at: DateTime<Utc>,
+    /// OSM data timestamp reported by Nominatim; None when it reports none
+    pub data_updated: Option<DateTime<Utc>>,
+    pub age_days: Option<i64>,
+    pub max_age_days: i64,
+    /// Older than `max_age_days`; an unknown age is never stale
+    pub stale: bool,
+}
+
+impl IndexFreshness {
+    pub fn evaluate(data_updated: Option<DateTime<Utc>>, max_age_days: i64, now: DateTime<Utc>) -> Self {
+        let age_days = data_updated.map(|updated| (now - updated).num_days());
+        Self {
+            checked_at: now,
+            data_updated,
+            age_days,
+            max_age_days,
+            stale: age_days.is_some_and(|age| age > max_age_days),
+        }
+    }
+}
+
+/// `data_updated` of a Nominatim `/status?format=json` response
+pub fn parse_data_updated(status: &serde_json::Value) -> Option<DateTime<Utc>> {
+    let raw = status.get("data_updated")?.as_str()?;
+    DateTime::parse_from_rfc3339(raw).ok().map(|t| t.with_timezone(&Utc))
+}
+
+lazy_static::lazy_static! {
+    static ref LATEST: Mutex<Option<IndexFreshness>> = Mutex::new(None);
+}
+
+/// Result of the last check, if one has run
+pub fn latest() -> Option<IndexFreshness> {
+    LATEST.lock().clone()
+}
+
+/// Ask Nominatim for its data timestamp
+pub async fn check(nominatim_url: &str, max_age_days: i64) -> Result<IndexFreshness> {
+    let url = format!("{}/status?format=json", nominatim_url.trim_end_matches('/'));
+    let response = http::client(HttpService::Nominatim)
+        .get(&url)
+        .timeout(STATUS_TIMEOUT)
+        .send()
+        .await
+        .context("Failed to reach Nominatim status")?;
+    if !response.status().is_success() {
+        anyhow::bail!("Nominatim status returned {}", response.status());
+    }
+    let status: serde_json::Value = response.json().await.context("Failed to parse Nominatim status")?;
+
+    Ok(IndexFreshness::evaluate(parse_data_updated(&status), max_age_days, Utc::now()))
+}
+
+/// Check the index periodically, warning when it is stale
+pub async fn run_monitor(nominatim_url: String, max_age_days: i64) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        match check(&nominatim_url, max_age_days).await {
+            Ok(freshness) => {
+                match (freshness.stale, freshness.age_days) {
+                    (true, Some(age)) => warn!(
+                        "Nominatim data is {} days old (limit {}), new addresses may fail to geocode",
+                        age, max_age_days
+                    ),
+                    (_, None) => info!("Nominatim does not report its data timestamp"),
+                    _ => {}
+                }
+                *LATEST.lock() = Some(freshness);
+            }
+            Err(e) => warn!("Failed to check Nominatim index freshness: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_data_updated() {
+        let status = serde_json::json!({"status": 0, "message": "OK", "data_updated": "2024-03-04T14:47:00+00:00"});
+        assert_eq!(parse_data_updated(&status), Some(Utc.with_ymd_and_hms(2024, 3, 4, 14, 47, 0).unwrap()));
+        assert_eq!(parse_data_updated(&serde_json::json!({"status": 0})), None);
+        assert_eq!(parse_data_updated(&serde_json::json!({"data_updated": "yesterday"})), None);
+    }
+
+    #[test]
+    fn test_evaluate_marks_old_index_stale() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+
+        let fresh = IndexFreshness::evaluate(Some(now - chrono::Duration::days(30)), 180, now);
+        assert_eq!(fresh.age_days, Some(30));
+        assert!(!fresh.stale);
+
+        let stale = IndexFreshness::evaluate(Some(now - chrono::Duration::days(200)), 180, now);
+        assert_eq!(stale.age_days, Some(200));
+        assert!(stale.stale);
+    }
+
+    #[test]
+    fn test_evaluate_unknown_age_is_not_stale() {
+        let freshness = IndexFreshness::evaluate(None, 180, Utc::now());
+        assert!(freshness.age_days.is_none());
+        assert!(!freshness.stale);
+    }
+}
@@ -0,0 +1,163 @@
+//! Interactive vs. bulk geocoding
+//!
+//! Jobs of up to `INTERACTIVE_MAX_CUSTOMERS` customers (a customer just
+//! created or edited) go to the interactive geocode queue, larger ones
+//! (imports, reruns, the pending sweep) to the bulk queue. Address and
+//! reverse lookups and depot geocoding are interactive as well.
+//!
+//! Both kinds share one Nominatim, so bulk jobs yield: before each customer
+//! they wait while an interactive request is in flight, then pause for
+//! `GEOCODE_BULK_INTERVAL_MS`, or longer while interactive requests were
+//! seen within the last `BUSY_WINDOW`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::subjects;
+
+/// Largest geocode job that is still interactive
+pub const INTERACTIVE_MAX_CUSTOMERS: usize = 20;
+
+const DEFAULT_BULK_INTERVAL_MS: u64 = 50;
+/// Pause between bulk customers shortly after interactive traffic
+const BUSY_INTERVAL: Duration = Duration::from_millis(500);
+const BUSY_WINDOW: Duration = Duration::from_secs(10);
+const WAIT_POLL: Duration = Duration::from_millis(100);
+/// Longest a bulk job waits for interactive requests before it goes on anyway
+const MAX_BULK_WAIT: Duration = Duration::from_secs(30);
+
+/// Global geocoding priority gate
+pub static GEOCODE_PRIORITY: Lazy<GeocodePriority> = Lazy::new(GeocodePriority::from_env);
+
+/// Queue subject for a geocode job of `customer_count` customers
+pub fn jobs_subject(customer_count: usize) -> &'static str {
+    if customer_count > INTERACTIVE_MAX_CUSTOMERS {
+        subjects::jobs::GEOCODE_BULK
+    } else {
+        subjects::jobs::GEOCODE
+    }
+}
+
+/// Tracks interactive geocoding so bulk jobs can give way to it
+pub struct GeocodePriority {
+    in_flight: AtomicUsize,
+    last_interactive: Mutex<Option<Instant>>,
+    bulk_interval: Duration,
+}
+
+/// Marks an interactive request as in flight until dropped
+pub struct InteractiveGuard<'a> {
+    priority: &'a GeocodePriority,
+}
+
+impl Drop for InteractiveGuard<'_> {
+    fn drop(&mut self) {
+        *self.priority.last_interactive.lock() = Some(Instant::now());
+        self.priority.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl GeocodePriority {
+    pub fn new(bulk_interval: Duration) -> Self {
+        Self {
+            in_flight: AtomicUsize::new(0),
+            last_interactive: Mutex::new(None),
+            bulk_interval,
+        }
+    }
+
+    /// Reads GEOCODE_BULK_INTERVAL_MS (pause between bulk customers, default 50)
+    pub fn from_env() -> Self {
+        let ms = std::env::var("GEOCODE_BULK_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_BULK_INTERVAL_MS);
+        Self::new(Duration::from_millis(ms))
+    }
+
+    /// Mark an interactive request; keep the guard while it runs
+    pub fn interactive(&self) -> InteractiveGuard<'_> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        *self.last_interactive.lock() = Some(Instant::now());
+        InteractiveGuard { priority: self }
+    }
+
+    /// Interactive requests running right now
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Pause before the next bulk customer
+    fn bulk_pause(&self, now: Instant) -> Duration {
+        let busy = self
+            .last_interactive
+            .lock()
+            .is_some_and(|last| now.saturating_duration_since(last) < BUSY_WINDOW);
+        if busy {
+            self.bulk_interval.max(BUSY_INTERVAL)
+        } else {
+            self.bulk_interval
+        }
+    }
+
+    /// Wait for the turn of the next bulk customer
+    pub async fn bulk_turn(&self) {
+        let started = Instant::now();
+        while self.in_flight() > 0 && started.elapsed() < MAX_BULK_WAIT {
+            tokio::time::sleep(WAIT_POLL).await;
+        }
+        let pause = self.bulk_pause(Instant::now());
+        if !pause.is_zero() {
+            tokio::time::sleep(pause).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jobs_subject_by_size() {
+        assert_eq!(jobs_subject(1), subjects::jobs::GEOCODE);
+        assert_eq!(jobs_subject(INTERACTIVE_MAX_CUSTOMERS), subjects::jobs::GEOCODE);
+        assert_eq!(jobs_subject(INTERACTIVE_MAX_CUSTOMERS + 1), subjects::jobs::GEOCODE_BULK);
+    }
+
+    #[test]
+    fn test_guard_tracks_in_flight() {
+        let priority = GeocodePriority::new(Duration::ZERO);
+        let first = priority.interactive();
+        let second = priority.interactive();
+        assert_eq!(priority.in_flight(), 2);
+        drop(first);
+        drop(second);
+        assert_eq!(priority.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_bulk_pause_grows_after_interactive_traffic() {
+        let priority = GeocodePriority::new(Duration::from_millis(50));
+        let now = Instant::now();
+        assert_eq!(priority.bulk_pause(now), Duration::from_millis(50));
+
+        drop(priority.interactive());
+        assert_eq!(priority.bulk_pause(Instant::now()), BUSY_INTERVAL);
+        assert_eq!(priority.bulk_pause(Instant::now() + BUSY_WINDOW), Duration::from_millis(50));
+
+        let slow = GeocodePriority::new(Duration::from_secs(1));
+        drop(slow.interactive());
+        assert_eq!(slow.bulk_pause(Instant::now()), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_turn_without_traffic_does_not_wait() {
+        let priority = GeocodePriority::new(Duration::ZERO);
+        let started = Instant::now();
+        priority.bulk_turn().await;
+        assert!(started.elapsed() < WAIT_POLL);
+    }
+}
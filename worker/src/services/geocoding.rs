@@ -11,6 +11,7 @@
 
 use anyhow::Result;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use crate::types::Coordinates;
 
 /// Geocoder trait - abstraction for all geocoding implementations
@@ -25,6 +26,18 @@ pub trait Geocoder: Send + Sync {
 
     /// Reverse geocode coordinates to address
     async fn reverse_geocode(&self, lat: f64, lng: f64) -> Result<Option<ReverseGeocodingResult>>;
+
+    /// Geocode within a scope (the account's country). Results outside
+    /// the scope's bounds come back with low confidence for review.
+    async fn geocode_in(
+        &self,
+        street: &str,
+        city: &str,
+        postal_code: &str,
+        scope: &GeocodeScope,
+    ) -> Result<Option<GeocodingResult>> {
+        Ok(self.geocode(street, city, postal_code).await?.map(|r| scope.assess(r)))
+    }
 }
 
 /// Result of geocoding operation
@@ -38,6 +51,97 @@ pub struct GeocodingResult {
     pub display_name: String,
 }
 
+/// Confidence below which a geocoded address needs manual review
+pub const REVIEW_CONFIDENCE_THRESHOLD: f64 = 0.5;
+
+/// Confidence of results outside the expected country bounds
+const OUT_OF_BOUNDS_CONFIDENCE: f64 = 0.2;
+
+impl GeocodingResult {
+    /// Whether the coordinates should be confirmed by a user before use
+    pub fn needs_review(&self) -> bool {
+        self.confidence < REVIEW_CONFIDENCE_THRESHOLD
+    }
+}
+
+/// Latitude/longitude rectangle
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub min_lng: f64,
+    pub max_lat: f64,
+    pub max_lng: f64,
+}
+
+impl BoundingBox {
+    pub const fn new(min_lat: f64, min_lng: f64, max_lat: f64, max_lng: f64) -> Self {
+        Self { min_lat, min_lng, max_lat, max_lng }
+    }
+
+    pub fn contains(&self, c: &Coordinates) -> bool {
+        (self.min_lat..=self.max_lat).contains(&c.lat) && (self.min_lng..=self.max_lng).contains(&c.lng)
+    }
+}
+
+/// Countries with known bounds: (ISO code, English name, bounds)
+const COUNTRY_SCOPES: &[(&str, &str, BoundingBox)] = &[
+    ("CZ", "Czech Republic", BoundingBox::new(48.5, 12.0, 51.1, 18.9)),
+    ("SK", "Slovakia", BoundingBox::new(47.7, 16.8, 49.7, 22.6)),
+    ("AT", "Austria", BoundingBox::new(46.3, 9.5, 49.1, 17.2)),
+    ("DE", "Germany", BoundingBox::new(47.2, 5.8, 55.1, 15.1)),
+    ("PL", "Poland", BoundingBox::new(49.0, 14.1, 54.9, 24.2)),
+    ("HU", "Hungary", BoundingBox::new(45.7, 16.1, 48.6, 22.9)),
+];
+
+/// Where geocoding results are expected, derived from the account's country
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeocodeScope {
+    /// ISO 3166-1 alpha-2 code, lowercase (Nominatim `countrycodes`)
+    pub country_code: String,
+    /// English name appended to free-form queries, if known
+    pub country_name: Option<&'static str>,
+    /// Expected area; None when the country has no known bounds
+    pub bounds: Option<BoundingBox>,
+}
+
+impl GeocodeScope {
+    /// Scope of a country code (e.g. users.country); falls back to CZ
+    pub fn for_country(code: Option<&str>) -> Self {
+        let code = code.map(str::trim).filter(|c| c.len() == 2).unwrap_or("CZ").to_uppercase();
+        match COUNTRY_SCOPES.iter().find(|(c, _, _)| *c == code) {
+            Some((_, name, bounds)) => Self {
+                country_code: code.to_lowercase(),
+                country_name: Some(name),
+                bounds: Some(*bounds),
+            },
+            None => Self { country_code: code.to_lowercase(), country_name: None, bounds: None },
+        }
+    }
+
+    pub fn contains(&self, c: &Coordinates) -> bool {
+        self.bounds.is_none_or(|b| b.contains(c))
+    }
+
+    /// Lower the confidence of a result outside the expected bounds
+    pub fn assess(&self, mut result: GeocodingResult) -> GeocodingResult {
+        if !self.contains(&result.coordinates) {
+            tracing::warn!(
+                "Geocoding result {} ({}, {}) is outside {}",
+                result.display_name, result.coordinates.lat, result.coordinates.lng, self.country_code
+            );
+            result.confidence = result.confidence.min(OUT_OF_BOUNDS_CONFIDENCE);
+        }
+        result
+    }
+}
+
+impl Default for GeocodeScope {
+    fn default() -> Self {
+        Self::for_country(None)
+    }
+}
+
 /// Result of reverse geocoding operation
 #[derive(Debug, Clone)]
 pub struct ReverseGeocodingResult {
@@ -55,6 +159,47 @@ mod tests {
     // MockGeocoder Tests (TDD - these tests define the expected behavior)
     // ==========================================================================
 
+    #[test]
+    fn geocode_scope_for_account_country() {
+        let sk = GeocodeScope::for_country(Some("sk"));
+        assert_eq!(sk.country_code, "sk");
+        assert_eq!(sk.country_name, Some("Slovakia"));
+
+        assert_eq!(GeocodeScope::for_country(None).country_code, "cz");
+        assert_eq!(GeocodeScope::for_country(Some("  ")).country_code, "cz");
+
+        let unknown = GeocodeScope::for_country(Some("FR"));
+        assert_eq!(unknown.country_code, "fr");
+        assert!(unknown.bounds.is_none());
+        assert!(unknown.contains(&Coordinates { lat: 0.0, lng: 0.0 }));
+    }
+
+    #[test]
+    fn out_of_bounds_result_needs_review() {
+        let scope = GeocodeScope::for_country(Some("CZ"));
+        let result = |lat, lng| GeocodingResult {
+            coordinates: Coordinates { lat, lng },
+            confidence: 0.8,
+            display_name: "Hlavná 1".to_string(),
+        };
+
+        // Brno stays as is
+        assert!(!scope.assess(result(49.1951, 16.6068)).needs_review());
+        // Košice (same street name in Slovakia) is flagged
+        let kosice = scope.assess(result(48.7164, 21.2611));
+        assert!(kosice.needs_review());
+        assert_eq!(kosice.confidence, OUT_OF_BOUNDS_CONFIDENCE);
+    }
+
+    #[tokio::test]
+    async fn default_geocode_in_assesses_mock_results() {
+        let geocoder = MockGeocoder::new();
+        let cz = geocoder.geocode_in("Hlavní 1", "Praha", "11000", &GeocodeScope::default()).await.unwrap().unwrap();
+        assert!(!cz.needs_review());
+        let hu = geocoder.geocode_in("Hlavní 1", "Praha", "11000", &GeocodeScope::for_country(Some("HU"))).await.unwrap().unwrap();
+        assert!(hu.needs_review());
+    }
+
     #[tokio::test]
     async fn mock_geocoder_returns_coordinates_for_any_address() {
         let geocoder = MockGeocoder::new();
@@ -356,62 +501,14 @@ impl Geocoder for MockGeocoder {
 }
 
 // ==========================================================================
-// CircuitBreaker Implementation
+// CircuitBreaker
 // ==========================================================================
 
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use std::time::{Duration, Instant};
-use std::sync::atomic::{AtomicU32, Ordering};
-
-/// Circuit breaker to prevent hammering a failing service
-pub struct CircuitBreaker {
-    failure_count: AtomicU32,
-    threshold: u32,
-    last_failure: Arc<Mutex<Option<Instant>>>,
-    recovery_time: Duration,
-}
+use std::time::Duration;
 
-impl CircuitBreaker {
-    pub fn new(threshold: u32, recovery_time: Duration) -> Self {
-        Self {
-            failure_count: AtomicU32::new(0),
-            threshold,
-            last_failure: Arc::new(Mutex::new(None)),
-            recovery_time,
-        }
-    }
-    
-    /// Check if circuit is open (blocking calls)
-    pub fn is_open(&self) -> bool {
-        let count = self.failure_count.load(Ordering::Relaxed);
-        if count >= self.threshold {
-            // Check if recovery time has passed
-            if let Ok(last) = self.last_failure.try_lock() {
-                if let Some(last_time) = *last {
-                    if last_time.elapsed() >= self.recovery_time {
-                        return false; // Allow retry (half-open)
-                    }
-                }
-            }
-            return true;
-        }
-        false
-    }
-    
-    /// Record a failure
-    pub fn record_failure(&self) {
-        self.failure_count.fetch_add(1, Ordering::Relaxed);
-        if let Ok(mut last) = self.last_failure.try_lock() {
-            *last = Some(Instant::now());
-        }
-    }
-    
-    /// Record a success (resets failure count)
-    pub fn record_success(&self) {
-        self.failure_count.store(0, Ordering::Relaxed);
-    }
-}
+pub use crate::services::circuit_breaker::CircuitBreaker;
+use crate::services::circuit_breaker;
 
 // ==========================================================================
 // NominatimGeocoder Implementation
@@ -433,7 +530,7 @@ const DEFAULT_CIRCUIT_BREAKER_RECOVERY_SECS: u64 = 300;
 pub struct NominatimGeocoder {
     client: NominatimClient,
     /// Circuit breaker - pub(crate) for testing
-    pub(crate) circuit_breaker: CircuitBreaker,
+    pub(crate) circuit_breaker: Arc<CircuitBreaker>,
 }
 
 impl NominatimGeocoder {
@@ -452,9 +549,17 @@ impl NominatimGeocoder {
         circuit_breaker_threshold: u32,
         circuit_breaker_recovery: Duration,
     ) -> Self {
+        Self::with_breaker(
+            base_url,
+            Arc::new(CircuitBreaker::named("nominatim", circuit_breaker_threshold, circuit_breaker_recovery)),
+        )
+    }
+
+    /// Create with a given (possibly shared) circuit breaker
+    pub fn with_breaker(base_url: &str, circuit_breaker: Arc<CircuitBreaker>) -> Self {
         Self {
             client: NominatimClient::new(base_url),
-            circuit_breaker: CircuitBreaker::new(circuit_breaker_threshold, circuit_breaker_recovery),
+            circuit_breaker,
         }
     }
     
@@ -467,21 +572,14 @@ impl NominatimGeocoder {
             .or_else(|_| std::env::var("NOMINATIM_BASE_URL"))
             .unwrap_or_else(|_| "http://localhost:8080".to_string());
         
-        let cb_threshold = std::env::var("NOMINATIM_CB_THRESHOLD")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(DEFAULT_CIRCUIT_BREAKER_THRESHOLD);
-        
-        let cb_recovery_secs = std::env::var("NOMINATIM_CB_RECOVERY_SECS")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(DEFAULT_CIRCUIT_BREAKER_RECOVERY_SECS);
-        
-        Self::with_config(
-            &base_url,
-            cb_threshold,
-            Duration::from_secs(cb_recovery_secs),
-        )
+        let (cb_threshold, cb_recovery) = circuit_breaker::settings_from_env(
+            "NOMINATIM",
+            DEFAULT_CIRCUIT_BREAKER_THRESHOLD,
+            DEFAULT_CIRCUIT_BREAKER_RECOVERY_SECS,
+        );
+
+        // Shared, so the breaker state shows up in the admin health check
+        Self::with_breaker(&base_url, circuit_breaker::shared("nominatim", cb_threshold, cb_recovery))
     }
 }
 
@@ -494,21 +592,35 @@ impl Default for NominatimGeocoder {
 #[async_trait]
 impl Geocoder for NominatimGeocoder {
     async fn geocode(&self, street: &str, city: &str, postal_code: &str) -> Result<Option<GeocodingResult>> {
+        self.geocode_in(street, city, postal_code, &GeocodeScope::default()).await
+    }
+
+    async fn geocode_in(
+        &self,
+        street: &str,
+        city: &str,
+        postal_code: &str,
+        scope: &GeocodeScope,
+    ) -> Result<Option<GeocodingResult>> {
         // Check circuit breaker first
-        if self.circuit_breaker.is_open() {
+        if !self.circuit_breaker.allow_request() {
             tracing::warn!("Circuit breaker is open, rejecting geocoding request");
             return Err(anyhow::anyhow!("Geocoding service temporarily unavailable (circuit breaker open)"));
         }
         
         // Make the request (no rate limiting for local Nominatim)
-        match self.client.geocode(street, city, postal_code).await {
+        match self.client.geocode_in(street, city, postal_code, scope).await {
             Ok(Some(coords)) => {
                 self.circuit_breaker.record_success();
-                Ok(Some(GeocodingResult {
+                let display_name = match scope.country_name {
+                    Some(country) => format!("{}, {}, {}, {}", street, postal_code, city, country),
+                    None => format!("{}, {}, {}", street, postal_code, city),
+                };
+                Ok(Some(scope.assess(GeocodingResult {
                     coordinates: coords,
                     confidence: 0.8, // Nominatim doesn't provide confidence, use default
-                    display_name: format!("{}, {}, {}, Czech Republic", street, postal_code, city),
-                }))
+                    display_name,
+                })))
             }
             Ok(None) => {
                 // No result found is not a failure
@@ -528,7 +640,7 @@ impl Geocoder for NominatimGeocoder {
     }
 
     async fn reverse_geocode(&self, lat: f64, lng: f64) -> Result<Option<ReverseGeocodingResult>> {
-        if self.circuit_breaker.is_open() {
+        if !self.circuit_breaker.allow_request() {
             tracing::warn!("Circuit breaker is open, rejecting reverse geocoding request");
             return Err(anyhow::anyhow!("Geocoding service temporarily unavailable (circuit breaker open)"));
         }
@@ -0,0 +1,173 @@
+//! Dependency health checks
+//!
+//! Builds the report behind `sazinka.health` and the gateway's `/healthz`
+//! and `/readyz` probes (see `types::health`). The probes check their own
+//! process: every replica answers `sazinka.health`, so asking over NATS
+//! could report another pod. Checks run concurrently and each gets
+//! `CHECK_TIMEOUT`, so a hanging dependency cannot stall a probe.
+//! The report is public, so details name the failure, not hosts or errors.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use async_nats::connection::State;
+use async_nats::Client;
+use once_cell::sync::OnceCell;
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::services::circuit_breaker::{self, BreakerState};
+use crate::services::http::{self, HttpService};
+use crate::services::routing::VALHALLA_BREAKER;
+use crate::transport::queue::ensured_streams;
+use crate::transport::JobQueue;
+use crate::types::health::{CheckStatus, HealthCheck, HealthReport};
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+const NOMINATIM_BREAKER: &str = "nominatim";
+
+/// Set once the handlers start; until then the worker is not ready
+static CONTEXT: OnceCell<HealthContext> = OnceCell::new();
+
+struct HealthContext {
+    client: Client,
+    pool: PgPool,
+    targets: HealthTargets,
+}
+
+/// External services to probe; None when not used by this install
+#[derive(Debug, Clone, Default)]
+pub struct HealthTargets {
+    pub valhalla_url: Option<String>,
+    /// Only set when geocoding goes through Nominatim
+    pub nominatim_url: Option<String>,
+}
+
+/// Make this process's dependencies available to [`report`]
+pub fn install(client: Client, pool: PgPool, targets: HealthTargets) {
+    if CONTEXT.set(HealthContext { client, pool, targets }).is_err() {
+        warn!("Health checks already installed");
+    }
+}
+
+/// Report of this process, or None while the worker is starting
+pub async fn report() -> Option<HealthReport> {
+    let context = CONTEXT.get()?;
+    Some(check_all(&context.client, &context.pool, &context.targets).await)
+}
+
+/// Check all dependencies
+pub async fn check_all(client: &Client, pool: &PgPool, targets: &HealthTargets) -> HealthReport {
+    let (database, nats, jetstream, valhalla, geocoder) = tokio::join!(
+        timed("database", true, check_database(pool)),
+        timed("nats", true, async { check_nats(client) }),
+        timed("jetstream", true, check_jetstream(client)),
+        timed("valhalla", false, check_service(targets.valhalla_url.as_deref(), "/status", HttpService::Valhalla, VALHALLA_BREAKER)),
+        timed(
+            "geocoder",
+            false,
+            check_service(targets.nominatim_url.as_deref(), "/status?format=json", HttpService::Nominatim, NOMINATIM_BREAKER),
+        ),
+    );
+    HealthReport::new(vec![database, nats, jetstream, valhalla, geocoder])
+}
+
+/// Run one check with the timeout and measure it
+async fn timed(
+    name: &str,
+    critical: bool,
+    check: impl Future<Output = (CheckStatus, Option<String>)>,
+) -> HealthCheck {
+    let started = Instant::now();
+    let (status, detail) = tokio::time::timeout(CHECK_TIMEOUT, check)
+        .await
+        .unwrap_or((CheckStatus::Down, Some("timed out".to_string())));
+    HealthCheck {
+        latency_ms: started.elapsed().as_millis() as u64,
+        ..HealthCheck::new(name, critical, status, detail)
+    }
+}
+
+async fn check_database(pool: &PgPool) -> (CheckStatus, Option<String>) {
+    match sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(pool).await {
+        Ok(_) => (CheckStatus::Ok, None),
+        Err(e) => {
+            warn!("Health check query failed: {}", e);
+            (CheckStatus::Down, Some("query failed".to_string()))
+        }
+    }
+}
+
+fn check_nats(client: &Client) -> (CheckStatus, Option<String>) {
+    match client.connection_state() {
+        State::Connected => (CheckStatus::Ok, None),
+        _ => (CheckStatus::Down, Some("not connected".to_string())),
+    }
+}
+
+/// JetStream answers and every stream a processor ensured still exists
+async fn check_jetstream(client: &Client) -> (CheckStatus, Option<String>) {
+    let queue = JobQueue::new(client.clone());
+    if !queue.is_available().await {
+        return (CheckStatus::Down, Some("unavailable".to_string()));
+    }
+    let mut missing = Vec::new();
+    for name in ensured_streams().into_keys() {
+        if queue.stream_state(&name).await.is_err() {
+            missing.push(name);
+        }
+    }
+    if missing.is_empty() {
+        (CheckStatus::Ok, None)
+    } else {
+        (CheckStatus::Degraded, Some(format!("missing streams: {}", missing.join(", "))))
+    }
+}
+
+/// Probe an HTTP service and read the state of its circuit breaker
+async fn check_service(
+    base_url: Option<&str>,
+    path: &str,
+    service: HttpService,
+    breaker: &str,
+) -> (CheckStatus, Option<String>) {
+    let Some(base_url) = base_url else {
+        return (CheckStatus::Skipped, None);
+    };
+    let reachable = http::client(service)
+        .get(format!("{}{}", base_url, path))
+        .timeout(CHECK_TIMEOUT)
+        .send()
+        .await
+        .is_ok_and(|response| response.status().is_success());
+    let breaker_open = circuit_breaker::status_of(breaker).is_some_and(|status| status.state == BreakerState::Open);
+    service_status(reachable, breaker_open)
+}
+
+fn service_status(reachable: bool, breaker_open: bool) -> (CheckStatus, Option<String>) {
+    match (reachable, breaker_open) {
+        (false, _) => (CheckStatus::Down, Some("unreachable".to_string())),
+        (true, true) => (CheckStatus::Degraded, Some("circuit breaker open".to_string())),
+        (true, false) => (CheckStatus::Ok, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_status() {
+        assert_eq!(service_status(true, false), (CheckStatus::Ok, None));
+        assert_eq!(service_status(true, true).0, CheckStatus::Degraded);
+        assert_eq!(service_status(false, false).0, CheckStatus::Down);
+        assert_eq!(service_status(false, true).0, CheckStatus::Down);
+    }
+
+    #[tokio::test]
+    async fn test_check_service_skipped_without_url() {
+        let (status, detail) = check_service(None, "/status", HttpService::Valhalla, VALHALLA_BREAKER).await;
+        assert_eq!(status, CheckStatus::Skipped);
+        assert!(detail.is_none());
+    }
+}
@@ -0,0 +1,290 @@
+//! Extension hooks
+//!
+//! External processes register over NATS for hook points of selected
+//! operations. A before hook receives the request before it is applied and
+//! answers within its timeout: allow it, veto it with a reason, or enrich it
+//! by returning the changed request. An after hook is only notified of the
+//! result. Registrations live in the worker's memory and expire unless the
+//! plugin renews them, so a plugin that stopped is no longer waited for.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use async_nats::Client;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::types::hooks::{HookAction, HookCall, HookRegistration, HookReply, HookSpec};
+
+/// Global hook registry singleton
+pub static HOOKS: Lazy<HookRegistry> = Lazy::new(HookRegistry::default);
+
+/// A registration without renewal for this long is dropped
+pub const REGISTRATION_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+struct Registered {
+    spec: HookSpec,
+    expires: Instant,
+}
+
+/// Hooks per plugin
+#[derive(Default)]
+pub struct HookRegistry {
+    plugins: Mutex<BTreeMap<String, Vec<Registered>>>,
+}
+
+impl HookRegistry {
+    /// Replace the hooks of `plugin`
+    pub fn register(&self, plugin: &str, hooks: Vec<HookSpec>) -> usize {
+        self.register_at(plugin, hooks, Instant::now())
+    }
+
+    fn register_at(&self, plugin: &str, hooks: Vec<HookSpec>, now: Instant) -> usize {
+        let count = hooks.len();
+        let registered = hooks.into_iter().map(|spec| Registered { spec, expires: now + REGISTRATION_TTL }).collect();
+        self.plugins.lock().insert(plugin.to_string(), registered);
+        count
+    }
+
+    /// Remove the hooks of `plugin`, returning how many there were
+    pub fn unregister(&self, plugin: &str) -> usize {
+        self.plugins.lock().remove(plugin).map_or(0, |hooks| hooks.len())
+    }
+
+    /// Live hooks on `point`, by plugin name
+    pub fn hooks_for(&self, point: &str) -> Vec<(String, HookSpec)> {
+        self.hooks_for_at(point, Instant::now())
+    }
+
+    fn hooks_for_at(&self, point: &str, now: Instant) -> Vec<(String, HookSpec)> {
+        let mut plugins = self.plugins.lock();
+        plugins.retain(|_, hooks| hooks.iter().any(|h| h.expires > now));
+        plugins
+            .iter()
+            .flat_map(|(plugin, hooks)| {
+                hooks
+                    .iter()
+                    .filter(|h| h.spec.point == point && h.expires > now)
+                    .map(move |h| (plugin.clone(), h.spec.clone()))
+            })
+            .collect()
+    }
+
+    /// Every live registration
+    pub fn list(&self) -> Vec<HookRegistration> {
+        let now = Instant::now();
+        let mut plugins = self.plugins.lock();
+        plugins.retain(|_, hooks| hooks.iter().any(|h| h.expires > now));
+        plugins
+            .iter()
+            .flat_map(|(plugin, hooks)| {
+                hooks.iter().map(move |h| HookRegistration {
+                    plugin: plugin.clone(),
+                    point: h.spec.point.clone(),
+                    subject: h.spec.subject.clone(),
+                    timeout_ms: h.spec.timeout_ms(),
+                    fail_closed: h.spec.fail_closed,
+                    expires_in_seconds: h.expires.saturating_duration_since(now).as_secs(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Result of the before hooks of an operation
+#[derive(Debug)]
+pub enum HookOutcome<T> {
+    /// Go on with the request, possibly enriched
+    Proceed(T),
+    Vetoed { plugin: String, reason: String },
+}
+
+/// Apply one plugin's answer to the request so far
+fn apply_reply(plugin: &str, payload: serde_json::Value, reply: HookReply) -> Result<serde_json::Value, String> {
+    match reply.action {
+        HookAction::Allow => Ok(payload),
+        HookAction::Veto => Err(reply.reason.unwrap_or_else(|| format!("Rejected by {}", plugin))),
+        HookAction::Enrich => match reply.payload {
+            Some(enriched) => Ok(enriched),
+            None => {
+                warn!("Hook plugin {} enriched without a payload, ignored", plugin);
+                Ok(payload)
+            }
+        },
+    }
+}
+
+/// Run the before hooks of `point` on a request, plugin by plugin. A plugin
+/// that does not answer in time is skipped, or vetoes when it registered as
+/// fail-closed. An enriched request that no longer parses is ignored.
+pub async fn run_before<T: Serialize + DeserializeOwned>(
+    client: &Client,
+    point: &str,
+    user_id: Uuid,
+    request: T,
+) -> HookOutcome<T> {
+    let hooks = HOOKS.hooks_for(point);
+    if hooks.is_empty() {
+        return HookOutcome::Proceed(request);
+    }
+
+    let mut payload = match serde_json::to_value(&request) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!("Failed to serialize {} for hooks: {}", point, e);
+            return HookOutcome::Proceed(request);
+        }
+    };
+    let mut request = request;
+
+    for (plugin, spec) in hooks {
+        let call = HookCall { point, user_id, payload: &payload };
+        let body = match serde_json::to_vec(&call) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to serialize hook call {}: {}", point, e);
+                continue;
+            }
+        };
+        let answer = tokio::time::timeout(
+            Duration::from_millis(spec.timeout_ms()),
+            client.request(spec.subject.clone(), body.into()),
+        )
+        .await;
+        let reply = match answer {
+            Ok(Ok(msg)) => serde_json::from_slice::<HookReply>(&msg.payload).map_err(|e| e.to_string()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(format!("no answer within {} ms", spec.timeout_ms())),
+        };
+        let reply = match reply {
+            Ok(reply) => reply,
+            Err(e) if spec.fail_closed => {
+                warn!("Hook plugin {} on {} failed, vetoing: {}", plugin, point, e);
+                return HookOutcome::Vetoed { reason: format!("{} is not available", plugin), plugin };
+            }
+            Err(e) => {
+                warn!("Hook plugin {} on {} failed, skipped: {}", plugin, point, e);
+                continue;
+            }
+        };
+
+        payload = match apply_reply(&plugin, payload, reply) {
+            Ok(next) => next,
+            Err(reason) => {
+                debug!("Hook plugin {} vetoed {}: {}", plugin, point, reason);
+                return HookOutcome::Vetoed { plugin, reason };
+            }
+        };
+        match serde_json::from_value::<T>(payload.clone()) {
+            Ok(enriched) => request = enriched,
+            Err(e) => {
+                warn!("Hook plugin {} returned an invalid {} request, ignored: {}", plugin, point, e);
+                payload = serde_json::to_value(&request).unwrap_or(payload);
+            }
+        }
+    }
+
+    HookOutcome::Proceed(request)
+}
+
+/// Notify the after hooks of `point` of a result. Runs in the background and
+/// never waits for the plugins.
+pub fn notify_after(client: &Client, point: &'static str, user_id: Uuid, result: &impl Serialize) {
+    let hooks = HOOKS.hooks_for(point);
+    if hooks.is_empty() {
+        return;
+    }
+    let payload = match serde_json::to_value(result) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!("Failed to serialize {} for hooks: {}", point, e);
+            return;
+        }
+    };
+    let body = match serde_json::to_vec(&HookCall { point, user_id, payload: &payload }) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("Failed to serialize hook call {}: {}", point, e);
+            return;
+        }
+    };
+
+    let client = client.clone();
+    tokio::spawn(async move {
+        for (plugin, spec) in hooks {
+            if let Err(e) = client.publish(spec.subject, body.clone().into()).await {
+                warn!("Failed to notify hook plugin {} of {}: {}", plugin, point, e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::hooks::{AFTER_ROUTE_SAVE, BEFORE_ROUTE_SAVE};
+    use serde_json::json;
+
+    fn spec(point: &str, subject: &str) -> HookSpec {
+        HookSpec { point: point.to_string(), subject: subject.to_string(), timeout_ms: None, fail_closed: false }
+    }
+
+    #[test]
+    fn test_registration_replaces_and_orders_by_plugin() {
+        let registry = HookRegistry::default();
+        registry.register("zeta", vec![spec(BEFORE_ROUTE_SAVE, "plugins.zeta")]);
+        registry.register("alpha", vec![spec(BEFORE_ROUTE_SAVE, "plugins.alpha"), spec(AFTER_ROUTE_SAVE, "plugins.alpha.after")]);
+
+        let before: Vec<String> = registry.hooks_for(BEFORE_ROUTE_SAVE).into_iter().map(|(p, _)| p).collect();
+        assert_eq!(before, vec!["alpha", "zeta"]);
+
+        registry.register("alpha", vec![spec(AFTER_ROUTE_SAVE, "plugins.alpha.after")]);
+        assert_eq!(registry.hooks_for(BEFORE_ROUTE_SAVE).len(), 1);
+        assert_eq!(registry.list().len(), 2);
+
+        assert_eq!(registry.unregister("alpha"), 1);
+        assert_eq!(registry.unregister("alpha"), 0);
+    }
+
+    #[test]
+    fn test_registration_expires() {
+        let registry = HookRegistry::default();
+        let start = Instant::now();
+        registry.register_at("rules", vec![spec(BEFORE_ROUTE_SAVE, "plugins.rules")], start);
+
+        assert_eq!(registry.hooks_for_at(BEFORE_ROUTE_SAVE, start + REGISTRATION_TTL / 2).len(), 1);
+        assert!(registry.hooks_for_at(BEFORE_ROUTE_SAVE, start + REGISTRATION_TTL).is_empty());
+        assert!(registry.list().is_empty());
+    }
+
+    #[test]
+    fn test_apply_reply() {
+        let payload = json!({ "date": "2026-03-02" });
+        let reply = |action, reason: Option<&str>, payload: Option<serde_json::Value>| HookReply {
+            action,
+            reason: reason.map(str::to_string),
+            payload,
+        };
+
+        assert_eq!(apply_reply("rules", payload.clone(), reply(HookAction::Allow, None, None)), Ok(payload.clone()));
+        assert_eq!(
+            apply_reply("rules", payload.clone(), reply(HookAction::Veto, Some("no Sundays"), None)),
+            Err("no Sundays".to_string())
+        );
+        assert_eq!(
+            apply_reply("rules", payload.clone(), reply(HookAction::Veto, None, None)),
+            Err("Rejected by rules".to_string())
+        );
+        let enriched = json!({ "date": "2026-03-02", "crewId": null });
+        assert_eq!(
+            apply_reply("rules", payload.clone(), reply(HookAction::Enrich, None, Some(enriched.clone()))),
+            Ok(enriched)
+        );
+        assert_eq!(apply_reply("rules", payload.clone(), reply(HookAction::Enrich, None, None)), Ok(payload));
+    }
+}
@@ -0,0 +1,226 @@
+#![allow(dead_code)]
+//! Shared HTTP clients for external services
+//!
+//! Every external service gets one reqwest client (and so one connection
+//! pool) for the whole process, built from per-service settings that can
+//! be overridden with `{PREFIX}_HTTP_TIMEOUT_SECS` / `{PREFIX}_HTTP_RETRIES`.
+//! Idempotent calls go through [`send_idempotent`], which retries
+//! connection errors, timeouts and 408/429/5xx responses with exponential
+//! backoff and jitter.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use rand::Rng;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use tracing::debug;
+
+/// User agent identifying us to public services (Nominatim usage policy)
+const PUBLIC_USER_AGENT: &str = "Ariadline/1.0 (https://ariadline.cz)";
+const DEFAULT_USER_AGENT: &str = concat!("Sazinka/", env!("CARGO_PKG_VERSION"));
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const POOL_MAX_IDLE_PER_HOST: usize = 8;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// External services we talk to over HTTP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HttpService {
+    Valhalla,
+    Nominatim,
+    MapTiles,
+    Crm,
+    Telemetry,
+    Email,
+    Webhook,
+    Sms,
+    Captcha,
+    Weather,
+}
+
+impl HttpService {
+    /// Prefix of the service's environment overrides
+    pub fn env_prefix(self) -> &'static str {
+        match self {
+            HttpService::Valhalla => "VALHALLA",
+            HttpService::Nominatim => "NOMINATIM",
+            HttpService::MapTiles => "MAP_TILE",
+            HttpService::Crm => "CRM",
+            HttpService::Telemetry => "TELEMETRY",
+            HttpService::Email => "EMAIL",
+            HttpService::Webhook => "WEBHOOK",
+            HttpService::Sms => "SMS",
+            HttpService::Captcha => "CAPTCHA",
+            HttpService::Weather => "WEATHER",
+        }
+    }
+
+    fn default_settings(self) -> HttpSettings {
+        let (timeout_secs, max_retries, user_agent) = match self {
+            HttpService::Valhalla => (30, 1, DEFAULT_USER_AGENT),
+            HttpService::Nominatim => (10, 2, PUBLIC_USER_AGENT),
+            HttpService::MapTiles => (10, 2, DEFAULT_USER_AGENT),
+            HttpService::Crm => (30, 2, PUBLIC_USER_AGENT),
+            HttpService::Telemetry => (15, 0, DEFAULT_USER_AGENT),
+            HttpService::Email => (15, 1, DEFAULT_USER_AGENT),
+            HttpService::Webhook => (10, 3, DEFAULT_USER_AGENT),
+            HttpService::Sms => (15, 1, DEFAULT_USER_AGENT),
+            HttpService::Captcha => (10, 1, DEFAULT_USER_AGENT),
+            HttpService::Weather => (10, 2, PUBLIC_USER_AGENT),
+        };
+        HttpSettings {
+            timeout: Duration::from_secs(timeout_secs),
+            max_retries,
+            user_agent,
+        }
+    }
+}
+
+/// Client settings of one service
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpSettings {
+    /// Whole-request timeout
+    pub timeout: Duration,
+    /// Retries of idempotent calls after the first attempt
+    pub max_retries: u32,
+    pub user_agent: &'static str,
+}
+
+impl HttpSettings {
+    /// Defaults of a service with environment overrides applied
+    pub fn for_service(service: HttpService) -> Self {
+        let mut settings = service.default_settings();
+        let prefix = service.env_prefix();
+        if let Some(secs) = env_number::<u64>(&format!("{}_HTTP_TIMEOUT_SECS", prefix)) {
+            settings.timeout = Duration::from_secs(secs.max(1));
+        }
+        if let Some(retries) = env_number::<u32>(&format!("{}_HTTP_RETRIES", prefix)) {
+            settings.max_retries = retries;
+        }
+        settings
+    }
+}
+
+fn env_number<T: std::str::FromStr>(key: &str) -> Option<T> {
+    std::env::var(key).ok().and_then(|v| v.trim().parse().ok())
+}
+
+lazy_static::lazy_static! {
+    static ref CLIENTS: Mutex<HashMap<HttpService, Client>> = Mutex::new(HashMap::new());
+}
+
+/// The shared client of a service. Clones share the connection pool.
+pub fn client(service: HttpService) -> Client {
+    CLIENTS
+        .lock()
+        .entry(service)
+        .or_insert_with(|| build_client(&HttpSettings::for_service(service)))
+        .clone()
+}
+
+fn build_client(settings: &HttpSettings) -> Client {
+    Client::builder()
+        .user_agent(settings.user_agent)
+        .timeout(settings.timeout)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+        .build()
+        .expect("Failed to create HTTP client")
+}
+
+/// Whether a response status is worth retrying
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::REQUEST_TIMEOUT
+}
+
+fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout()
+}
+
+/// Delay before retry number `attempt` (0-based): exponential backoff
+/// capped at RETRY_MAX_DELAY, with jitter in the upper half of the window
+pub fn retry_delay(attempt: u32) -> Duration {
+    let window = RETRY_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(RETRY_MAX_DELAY);
+    let half = window / 2;
+    half + half.mul_f64(rand::thread_rng().gen::<f64>())
+}
+
+/// Send an idempotent request, retrying transient failures per the
+/// service's settings. `build` is called again for every attempt. After
+/// the last attempt the final response is returned as is, even if it is
+/// a retryable error status, so callers keep their own status handling.
+pub async fn send_idempotent<F>(service: HttpService, build: F) -> reqwest::Result<Response>
+where
+    F: Fn() -> RequestBuilder,
+{
+    let max_retries = HttpSettings::for_service(service).max_retries;
+    let mut attempt = 0;
+    loop {
+        let result = build().send().await;
+        let retryable = match &result {
+            Ok(response) => is_retryable_status(response.status()),
+            Err(e) => is_retryable_error(e),
+        };
+        if !retryable || attempt >= max_retries {
+            return result;
+        }
+
+        let delay = retry_delay(attempt);
+        match &result {
+            Ok(response) => debug!("{:?} returned {}, retrying in {:?}", service, response.status(), delay),
+            Err(e) => debug!("{:?} request failed ({}), retrying in {:?}", service, e, delay),
+        }
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::REQUEST_TIMEOUT));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(StatusCode::OK));
+    }
+
+    #[test]
+    fn test_retry_delay_grows_with_jitter_and_cap() {
+        for attempt in 0..3 {
+            let window = RETRY_BASE_DELAY * 2u32.pow(attempt);
+            let delay = retry_delay(attempt);
+            assert!(delay >= window / 2 && delay <= window, "attempt {}: {:?}", attempt, delay);
+        }
+        assert!(retry_delay(30) <= RETRY_MAX_DELAY);
+        assert!(retry_delay(30) >= RETRY_MAX_DELAY / 2);
+    }
+
+    #[test]
+    fn test_settings_env_override() {
+        std::env::set_var("SMS_HTTP_TIMEOUT_SECS", "42");
+        std::env::set_var("SMS_HTTP_RETRIES", "not-a-number");
+        let settings = HttpSettings::for_service(HttpService::Sms);
+        assert_eq!(settings.timeout, Duration::from_secs(42));
+        assert_eq!(settings.max_retries, HttpService::Sms.default_settings().max_retries);
+        std::env::remove_var("SMS_HTTP_TIMEOUT_SECS");
+        std::env::remove_var("SMS_HTTP_RETRIES");
+    }
+
+    #[tokio::test]
+    async fn test_clients_are_shared_per_service() {
+        // Building twice must not panic and returns the cached client
+        let _ = client(HttpService::Webhook);
+        let _ = client(HttpService::Webhook);
+        assert!(CLIENTS.lock().contains_key(&HttpService::Webhook));
+    }
+}
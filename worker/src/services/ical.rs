@@ -0,0 +1,231 @@
+//! iCalendar (RFC 5545) rendering
+//!
+//! Minimal writer for subscription feeds: timed events in the company's
+//! time zone or all-day events, with text escaping, line folding and the
+//! RFC 7986 calendar name and event colors that calendar apps pick up.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+/// Time zone of scheduled dates and times
+pub const TIME_ZONE: &str = "Europe/Prague";
+
+/// Longest content line in octets, line break excluded
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Rules of `TIME_ZONE`, so clients do not have to know the zone by name
+const VTIMEZONE: &[&str] = &[
+    "BEGIN:VTIMEZONE",
+    "TZID:Europe/Prague",
+    "BEGIN:DAYLIGHT",
+    "TZOFFSETFROM:+0100",
+    "TZOFFSETTO:+0200",
+    "TZNAME:CEST",
+    "DTSTART:19700329T020000",
+    "RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU",
+    "END:DAYLIGHT",
+    "BEGIN:STANDARD",
+    "TZOFFSETFROM:+0200",
+    "TZOFFSETTO:+0100",
+    "TZNAME:CET",
+    "DTSTART:19701025T030000",
+    "RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU",
+    "END:STANDARD",
+    "END:VTIMEZONE",
+];
+
+/// CSS color names (RFC 7986 COLOR) handed out to crews in order
+const CREW_COLORS: &[&str] = &[
+    "royalblue",
+    "seagreen",
+    "darkorange",
+    "mediumpurple",
+    "crimson",
+    "teal",
+    "goldenrod",
+    "slategray",
+];
+
+/// Start or end of an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventTime {
+    /// All-day; the end date is exclusive
+    Date(NaiveDate),
+    /// Wall-clock time in `TIME_ZONE`
+    Local(NaiveDateTime),
+}
+
+/// One VEVENT
+#[derive(Debug, Clone)]
+pub struct IcalEvent {
+    /// Stable across fetches, so clients update events instead of duplicating them
+    pub uid: String,
+    pub start: EventTime,
+    pub end: EventTime,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub categories: Vec<String>,
+    pub color: Option<&'static str>,
+    /// Shown as free time (overlays that should not block the calendar)
+    pub transparent: bool,
+}
+
+/// Color of the crew at `index` in the company's crew list
+pub fn crew_color(index: usize) -> &'static str {
+    CREW_COLORS[index % CREW_COLORS.len()]
+}
+
+/// Render a whole calendar; `stamp` is the DTSTAMP of every event
+pub fn render_calendar(name: &str, events: &[IcalEvent], stamp: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    for line in ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//Sazinka//Work calendar//EN", "CALSCALE:GREGORIAN", "METHOD:PUBLISH"] {
+        push_line(&mut out, line);
+    }
+    push_line(&mut out, &format!("NAME:{}", escape_text(name)));
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+    push_line(&mut out, &format!("X-WR-TIMEZONE:{}", TIME_ZONE));
+    push_line(&mut out, "REFRESH-INTERVAL;VALUE=DURATION:PT1H");
+    push_line(&mut out, "X-PUBLISHED-TTL:PT1H");
+    for line in VTIMEZONE {
+        push_line(&mut out, line);
+    }
+
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+    for event in events {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", event.uid));
+        push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+        push_line(&mut out, &format!("DTSTART{}", format_time(event.start)));
+        push_line(&mut out, &format!("DTEND{}", format_time(event.end)));
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&event.summary)));
+        if let Some(description) = &event.description {
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        if let Some(location) = &event.location {
+            push_line(&mut out, &format!("LOCATION:{}", escape_text(location)));
+        }
+        if !event.categories.is_empty() {
+            let categories: Vec<String> = event.categories.iter().map(|c| escape_text(c)).collect();
+            push_line(&mut out, &format!("CATEGORIES:{}", categories.join(",")));
+        }
+        if let Some(color) = event.color {
+            push_line(&mut out, &format!("COLOR:{}", color));
+        }
+        push_line(&mut out, if event.transparent { "TRANSP:TRANSPARENT" } else { "TRANSP:OPAQUE" });
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Property parameters and value of a DTSTART/DTEND
+fn format_time(time: EventTime) -> String {
+    match time {
+        EventTime::Date(date) => format!(";VALUE=DATE:{}", date.format("%Y%m%d")),
+        EventTime::Local(at) => format!(";TZID={}:{}", TIME_ZONE, at.format("%Y%m%dT%H%M%S")),
+    }
+}
+
+/// Escape a TEXT value
+fn escape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Append a content line, folded to `MAX_LINE_OCTETS` without splitting characters
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event() -> IcalEvent {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        IcalEvent {
+            uid: "visit-1@sazinka".to_string(),
+            start: EventTime::Local(date.and_hms_opt(8, 30, 0).unwrap()),
+            end: EventTime::Local(date.and_hms_opt(9, 15, 0).unwrap()),
+            summary: "Revize kotle, Novák".to_string(),
+            description: Some("Line one\nLine two; more".to_string()),
+            location: None,
+            categories: vec!["Crew A".to_string()],
+            color: Some(crew_color(0)),
+            transparent: false,
+        }
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape_text("a,b;c\\d\r\ne"), "a\\,b\\;c\\\\d\\ne");
+    }
+
+    #[test]
+    fn test_long_lines_are_folded_on_char_boundaries() {
+        let mut out = String::new();
+        let line = format!("SUMMARY:{}", "žluťoučký kůň ".repeat(12));
+        push_line(&mut out, &line);
+
+        let lines: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n").collect();
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| l.len() <= MAX_LINE_OCTETS));
+        assert!(lines[1..].iter().all(|l| l.starts_with(' ')));
+        assert_eq!(out.replace("\r\n ", "").trim_end(), line.trim_end());
+    }
+
+    #[test]
+    fn test_render_calendar() {
+        let stamp = Utc.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap();
+        let ics = render_calendar("Company", &[event()], stamp);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("X-WR-CALNAME:Company\r\n"));
+        assert!(ics.contains("BEGIN:VTIMEZONE\r\nTZID:Europe/Prague\r\n"));
+        assert!(ics.contains("DTSTAMP:20261017T120000Z\r\n"));
+        assert!(ics.contains("DTSTART;TZID=Europe/Prague:20261019T083000\r\n"));
+        assert!(ics.contains("SUMMARY:Revize kotle\\, Novák\r\n"));
+        assert!(ics.contains("DESCRIPTION:Line one\\nLine two\\; more\r\n"));
+        assert!(ics.contains("CATEGORIES:Crew A\r\nCOLOR:royalblue\r\nTRANSP:OPAQUE\r\n"));
+        assert!(!ics.contains("LOCATION:"));
+    }
+
+    #[test]
+    fn test_all_day_event() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let mut overlay = event();
+        overlay.start = EventTime::Date(date);
+        overlay.end = EventTime::Date(date.succ_opt().unwrap());
+        overlay.transparent = true;
+        let ics = render_calendar("Company", &[overlay], Utc::now());
+        assert!(ics.contains("DTSTART;VALUE=DATE:20261019\r\nDTEND;VALUE=DATE:20261020\r\n"));
+        assert!(ics.contains("TRANSP:TRANSPARENT\r\n"));
+    }
+
+    #[test]
+    fn test_crew_colors_cycle() {
+        assert_eq!(crew_color(0), crew_color(CREW_COLORS.len()));
+        assert_ne!(crew_color(0), crew_color(1));
+    }
+}
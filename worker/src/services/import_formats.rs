@@ -0,0 +1,262 @@
+//! Export formats of other chimney-sweep and revision applications
+//!
+//! Customers migrating from Kominík SW or REVIZEX upload their application's
+//! export unchanged and pick the source system; the file is rewritten into
+//! our own CSV layout (`;`, our column names) and then goes through the
+//! regular import. Both applications export fixed column layouts, so the
+//! columns are mapped by position and their header row is ignored.
+//!
+//! Customer numbers of the source system become our customer codes, which
+//! is what the device exports reference the customer by.
+
+use anyhow::{bail, Result};
+use csv::StringRecord;
+
+use crate::types::import::SourceSystem;
+
+/// Columns written for a customer import
+const CUSTOMER_COLUMNS: [&str; 12] = [
+    "type", "name", "contact_person", "ico", "dic", "email", "phone", "street", "city", "postal_code", "notes",
+    "customer_code",
+];
+
+/// Columns written for a device import
+const DEVICE_COLUMNS: [&str; 9] = [
+    "customer_ref", "device_type", "manufacturer", "model", "serial_number", "installation_date",
+    "revision_interval_months", "room", "notes",
+];
+
+/// What the file contains
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportKind {
+    Customers,
+    Devices,
+}
+
+/// Value of column `idx`, trimmed; empty when the row is shorter
+fn col(record: &StringRecord, idx: usize) -> &str {
+    record.get(idx).map(str::trim).unwrap_or("")
+}
+
+/// Non-empty parts joined by `sep`
+fn join(parts: &[&str], sep: &str) -> String {
+    parts.iter().filter(|p| !p.is_empty()).copied().collect::<Vec<_>>().join(sep)
+}
+
+/// Device kinds as the source applications label them
+fn device_type(label: &str) -> String {
+    let lower = label.to_lowercase();
+    let key = if lower.contains("komín") || lower.contains("komin") || lower.contains("spalinov") {
+        "chimney"
+    } else if lower.contains("krb") || lower.contains("kamna") || lower.contains("pevná paliva") {
+        "fireplace"
+    } else if lower.contains("ohřívač") || lower.contains("průtokov") || lower.contains("bojler") {
+        "gas_water_heater"
+    } else if lower.contains("sporák") || lower.contains("varn") {
+        "gas_stove"
+    } else if lower.contains("kotel") || lower.contains("plyn") {
+        "gas_boiler"
+    } else if lower.is_empty() {
+        return String::new();
+    } else {
+        "other"
+    };
+    key.to_string()
+}
+
+/// REVIZEX keeps the address in one field: "Street 12, 602 00 Brno"
+fn split_address(address: &str) -> (String, String, String) {
+    let Some((street, rest)) = address.rsplit_once(',') else {
+        return (address.to_string(), String::new(), String::new());
+    };
+    let rest = rest.trim();
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit() || *c == ' ').collect();
+    let postal_code: String = digits.chars().filter(char::is_ascii_digit).collect();
+    if postal_code.len() == 5 {
+        (street.trim().to_string(), postal_code, rest[digits.len()..].trim().to_string())
+    } else {
+        (street.trim().to_string(), String::new(), rest.to_string())
+    }
+}
+
+/// Kominík SW "Adresář zákazníků": číslo; příjmení; jméno; firma; IČ; DIČ;
+/// ulice; číslo popisné; obec; PSČ; telefon; e-mail; poznámka
+fn kominik_customer(r: &StringRecord) -> Vec<String> {
+    let person = join(&[col(r, 2), col(r, 1)], " ");
+    let company = col(r, 3);
+    let (kind, name, contact) = if company.is_empty() {
+        ("person", person, String::new())
+    } else {
+        ("company", company.to_string(), person)
+    };
+    vec![
+        kind.to_string(),
+        name,
+        contact,
+        col(r, 4).to_string(),
+        col(r, 5).to_string(),
+        col(r, 11).to_string(),
+        col(r, 10).to_string(),
+        join(&[col(r, 6), col(r, 7)], " "),
+        col(r, 8).to_string(),
+        col(r, 9).to_string(),
+        col(r, 12).to_string(),
+        col(r, 0).to_string(),
+    ]
+}
+
+/// Kominík SW "Spotřebiče": číslo zákazníka; druh; výrobce; typ; výrobní
+/// číslo; rok instalace; umístění; interval kontrol (měsíce); poznámka
+fn kominik_device(r: &StringRecord) -> Vec<String> {
+    let year = col(r, 5);
+    let installed = if year.len() == 4 && year.chars().all(|c| c.is_ascii_digit()) {
+        format!("{}-01-01", year)
+    } else {
+        year.to_string()
+    };
+    vec![
+        col(r, 0).to_string(),
+        device_type(col(r, 1)),
+        col(r, 2).to_string(),
+        col(r, 3).to_string(),
+        col(r, 4).to_string(),
+        installed,
+        col(r, 7).to_string(),
+        col(r, 6).to_string(),
+        col(r, 8).to_string(),
+    ]
+}
+
+/// REVIZEX "Export odběratelů": ID, odběratel, kontaktní osoba, IČO, DIČ,
+/// adresa, telefon, e-mail, poznámka
+fn revizex_customer(r: &StringRecord) -> Vec<String> {
+    let (street, postal_code, city) = split_address(col(r, 5));
+    let is_company = !col(r, 3).is_empty();
+    vec![
+        if is_company { "company" } else { "person" }.to_string(),
+        col(r, 1).to_string(),
+        col(r, 2).to_string(),
+        col(r, 3).to_string(),
+        col(r, 4).to_string(),
+        col(r, 7).to_string(),
+        col(r, 6).to_string(),
+        street,
+        city,
+        postal_code,
+        col(r, 8).to_string(),
+        col(r, 0).to_string(),
+    ]
+}
+
+/// REVIZEX "Export zařízení": ID odběratele, zařízení, výrobce, model,
+/// výrobní číslo, datum instalace, perioda revize (roky), umístění, poznámka
+fn revizex_device(r: &StringRecord) -> Vec<String> {
+    let interval = col(r, 6)
+        .replace(',', ".")
+        .parse::<f64>()
+        .map(|years| ((years * 12.0).round() as i32).to_string())
+        .unwrap_or_default();
+    vec![
+        col(r, 0).to_string(),
+        device_type(col(r, 1)),
+        col(r, 2).to_string(),
+        col(r, 3).to_string(),
+        col(r, 4).to_string(),
+        col(r, 5).to_string(),
+        interval,
+        col(r, 7).to_string(),
+        col(r, 8).to_string(),
+    ]
+}
+
+/// Rewrite an export of `source` into our CSV layout. Our own format is
+/// returned unchanged.
+pub fn to_canonical_csv(source: SourceSystem, kind: ImportKind, content: &str) -> Result<String> {
+    let (delimiter, map): (u8, fn(&StringRecord) -> Vec<String>) = match (source, kind) {
+        (SourceSystem::Sazinka, _) => return Ok(content.to_string()),
+        (SourceSystem::KominikSw, ImportKind::Customers) => (b';', kominik_customer),
+        (SourceSystem::KominikSw, ImportKind::Devices) => (b';', kominik_device),
+        (SourceSystem::Revizex, ImportKind::Customers) => (b',', revizex_customer),
+        (SourceSystem::Revizex, ImportKind::Devices) => (b',', revizex_device),
+    };
+    let columns: &[&str] = match kind {
+        ImportKind::Customers => &CUSTOMER_COLUMNS,
+        ImportKind::Devices => &DEVICE_COLUMNS,
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let mut writer = csv::WriterBuilder::new().delimiter(b';').from_writer(Vec::new());
+    writer.write_record(columns)?;
+    for record in reader.records() {
+        writer.write_record(map(&record?))?;
+    }
+
+    let Ok(bytes) = writer.into_inner() else { bail!("Failed to write converted CSV") };
+    Ok(String::from_utf8(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(csv: &str) -> Vec<Vec<String>> {
+        csv.lines().map(|l| l.split(';').map(str::to_string).collect()).collect()
+    }
+
+    #[test]
+    fn test_sazinka_unchanged() {
+        let csv = "name;city\nNovák;Brno\n";
+        assert_eq!(to_canonical_csv(SourceSystem::Sazinka, ImportKind::Customers, csv).unwrap(), csv);
+    }
+
+    #[test]
+    fn test_kominik_customers() {
+        let csv = "Číslo;Příjmení;Jméno;Firma;IČ;DIČ;Ulice;Č.p.;Obec;PSČ;Telefon;E-mail;Poznámka\n\
+                   1042;Novák;Jan;;;;Lipová;12;Brno;602 00;777123456;jan@example.cz;\n\
+                   1043;Dvořák;Petr;Teplo s.r.o.;12345678;CZ12345678;Hlavní;1;Praha;11000;;;VIP\n";
+        let out = rows(&to_canonical_csv(SourceSystem::KominikSw, ImportKind::Customers, csv).unwrap());
+        assert_eq!(out[0], CUSTOMER_COLUMNS);
+        assert_eq!(out[1][..3], ["person", "Jan Novák", ""]);
+        assert_eq!(out[1][7], "Lipová 12");
+        assert_eq!(out[1][11], "1042");
+        assert_eq!(out[2][..4], ["company", "Teplo s.r.o.", "Petr Dvořák", "12345678"]);
+        assert_eq!(out[2][10], "VIP");
+    }
+
+    #[test]
+    fn test_kominik_devices() {
+        let csv = "Zákazník;Druh;Výrobce;Typ;Výr. č.;Rok;Umístění;Interval;Poznámka\n\
+                   1042;Plynový kotel;Vaillant;ecoTEC;SN1;2015;kotelna;12;\n\
+                   1042;Komínové těleso;;;;;;6;\n";
+        let out = rows(&to_canonical_csv(SourceSystem::KominikSw, ImportKind::Devices, csv).unwrap());
+        assert_eq!(out[0], DEVICE_COLUMNS);
+        assert_eq!(out[1], ["1042", "gas_boiler", "Vaillant", "ecoTEC", "SN1", "2015-01-01", "12", "kotelna", ""]);
+        assert_eq!(out[2][1], "chimney");
+    }
+
+    #[test]
+    fn test_revizex() {
+        let csv = "ID,Odběratel,Kontakt,IČO,DIČ,Adresa,Telefon,Email,Poznámka\n\
+                   R-7,\"Bytové družstvo Lipová\",Eva Malá,87654321,,\"Lipová 12, 602 00 Brno\",,bd@example.cz,\n";
+        let out = rows(&to_canonical_csv(SourceSystem::Revizex, ImportKind::Customers, csv).unwrap());
+        assert_eq!(out[1][..3], ["company", "Bytové družstvo Lipová", "Eva Malá"]);
+        assert_eq!(out[1][7..10], ["Lipová 12", "Brno", "60200"]);
+        assert_eq!(out[1][11], "R-7");
+
+        let csv = "ID,Zařízení,Výrobce,Model,Výr. č.,Instalace,Perioda,Umístění,Poznámka\n\
+                   R-7,Průtokový ohřívač,Junkers,WR10,X1,1.3.2019,\"0,5\",koupelna,\n";
+        let out = rows(&to_canonical_csv(SourceSystem::Revizex, ImportKind::Devices, csv).unwrap());
+        assert_eq!(out[1], ["R-7", "gas_water_heater", "Junkers", "WR10", "X1", "1.3.2019", "6", "koupelna", ""]);
+    }
+
+    #[test]
+    fn test_split_address() {
+        assert_eq!(split_address("Lipová 12, 602 00 Brno"), ("Lipová 12".into(), "60200".into(), "Brno".into()));
+        assert_eq!(split_address("Lipová 12, Brno"), ("Lipová 12".into(), String::new(), "Brno".into()));
+        assert_eq!(split_address("Lipová 12"), ("Lipová 12".into(), String::new(), String::new()));
+    }
+}
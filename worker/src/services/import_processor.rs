@@ -13,12 +13,14 @@ use std::sync::Arc;
 use std::time::Instant;
 use anyhow::Result;
 use async_nats::Client;
-use async_nats::jetstream::{self, Context as JsContext};
+use async_nats::jetstream;
 use futures::StreamExt;
 use sqlx::PgPool;
 use tracing::{info, warn, error};
 use uuid::Uuid;
 
+use crate::subjects;
+use crate::transport::{JobMessage, JobQueue};
 use crate::types::{
     ImportJobRequest, ImportJobStatus, ImportJobStatusUpdate,
     QueuedImportJob, ImportJobSubmitResponse, ImportIssue,
@@ -27,36 +29,36 @@ use crate::types::{
 // Stream and consumer names
 const STREAM_NAME: &str = "SAZINKA_IMPORT_JOBS";
 const CONSUMER_NAME: &str = "import_workers";
-const SUBJECT: &str = "sazinka.jobs.import";
-const STATUS_PREFIX: &str = "sazinka.job.import.status";
+const SUBJECT: &str = subjects::jobs::IMPORT;
+const STATUS_PREFIX: &str = subjects::job::IMPORT_STATUS;
 
 /// Import job processor with JetStream integration
 pub struct ImportProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
     pool: PgPool,
 }
 
 impl ImportProcessor {
     /// Create a new import processor, initializing JetStream stream
     pub async fn new(client: Client, pool: PgPool) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+        let queue = JobQueue::new(client.clone());
         
         // Create import stream
         let stream_config = jetstream::stream::Config {
             name: STREAM_NAME.to_string(),
-            subjects: vec![format!("{}.*", SUBJECT)], // sazinka.jobs.import.*
+            subjects: vec![subjects::wildcard(SUBJECT)], // sazinka.jobs.import.*
             max_messages: 1_000,
             max_bytes: 100 * 1024 * 1024, // 100 MB (imports can be large)
             retention: jetstream::stream::RetentionPolicy::WorkQueue,
             ..Default::default()
         };
-        js.get_or_create_stream(stream_config).await?;
+        queue.ensure_stream(stream_config).await?;
         info!("JetStream import stream '{}' ready", STREAM_NAME);
         
         Ok(Self {
             client,
-            js,
+            queue,
             pool,
         })
     }
@@ -73,7 +75,7 @@ impl ImportProcessor {
         
         // Publish to JetStream
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish(subject, payload.into()).await?.await?;
+        self.queue.publish(subject, payload.into()).await?;
         
         info!("Import job {} submitted: {} {} items", job_id, item_count, import_type);
         
@@ -91,7 +93,7 @@ impl ImportProcessor {
     /// Publish an import job status update
     pub async fn publish_status(&self, job_id: Uuid, status: ImportJobStatus) -> Result<()> {
         let update = ImportJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         
         self.client.publish(subject, payload.into()).await?;
@@ -100,21 +102,17 @@ impl ImportProcessor {
     
     /// Start processing import jobs from the queue
     pub async fn start_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(STREAM_NAME).await?;
-        
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(CONSUMER_NAME.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
             max_deliver: 3, // Retry up to 3 times
-            filter_subject: format!("{}.>", SUBJECT), // Match all import subjects
+            filter_subject: subjects::tail_wildcard(SUBJECT), // Match all import subjects
             ..Default::default()
         };
         
-        let consumer = stream.get_or_create_consumer(CONSUMER_NAME, consumer_config).await?;
+        let mut messages = self.queue.consume(STREAM_NAME, consumer_config).await?;
         info!("JetStream import consumer '{}' ready", CONSUMER_NAME);
         
-        let mut messages = consumer.messages().await?;
-        
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -135,7 +133,7 @@ impl ImportProcessor {
     }
     
     /// Process a single import job
-    async fn process_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_job(&self, msg: JobMessage) -> Result<()> {
         let start_time = Instant::now();
         let job: QueuedImportJob = serde_json::from_slice(&msg.payload)?;
         let job_id = job.id;
@@ -0,0 +1,359 @@
+//! Chunked import uploads
+//!
+//! Large import files don't fit into a single NATS message. The client
+//! opens an upload (`sazinka.import.upload.begin`), sends the file in
+//! base64 chunks of at most `MAX_CHUNK_BYTES` (`...upload.chunk`) and
+//! commits it (`...upload.commit`); the import submit then references the
+//! upload id instead of carrying the content. Uploads are files under
+//! `uploads/<user>/`, so they work the same with JetStream and in
+//! standalone mode. A committed upload is removed when the import job that
+//! used it finishes, leftovers by the hourly sweep.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use base64::Engine;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::services::csv_encoding::{decode_csv, decode_payload, DecodedCsv, UTF_8_NAME};
+use crate::services::xlsx;
+
+/// Largest decoded chunk; base64 of it stays well below the 1 MB NATS payload limit
+pub const MAX_CHUNK_BYTES: usize = 512 * 1024;
+/// Largest file accepted through the chunked protocol
+pub const MAX_UPLOAD_BYTES: u64 = 200 * 1024 * 1024;
+/// Unfinished and unused uploads are removed after this time
+pub const UPLOAD_TTL: Duration = Duration::from_secs(24 * 3600);
+const SWEEP_TICK: Duration = Duration::from_secs(3600);
+
+/// Upload store of the worker
+pub static IMPORT_UPLOADS: Lazy<UploadStore> = Lazy::new(|| UploadStore::new(PathBuf::from("uploads")));
+
+/// Why an upload operation was refused
+#[derive(Debug, thiserror::Error)]
+pub enum UploadError {
+    #[error("Upload not found or expired")]
+    NotFound,
+    #[error("File is larger than {max} bytes")]
+    TooLarge { max: u64 },
+    #[error("Chunk is larger than {max} bytes")]
+    ChunkTooLarge { max: usize },
+    #[error("Expected chunk {expected}, got {got}")]
+    OutOfOrder { expected: u32, got: u32 },
+    #[error("Upload incomplete: {received} of {total} bytes received")]
+    Incomplete { received: u64, total: u64 },
+    #[error("Checksum mismatch")]
+    ChecksumMismatch,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl UploadError {
+    /// Error code of the NATS reply
+    pub fn code(&self) -> &'static str {
+        match self {
+            UploadError::NotFound => "NOT_FOUND",
+            UploadError::TooLarge { .. } | UploadError::ChunkTooLarge { .. } => "PAYLOAD_TOO_LARGE",
+            UploadError::OutOfOrder { .. } | UploadError::Incomplete { .. } | UploadError::ChecksumMismatch => {
+                "INVALID_REQUEST"
+            }
+            UploadError::Io(_) => "INTERNAL_ERROR",
+        }
+    }
+}
+
+/// Upload in progress
+struct UploadSession {
+    user_id: Uuid,
+    total_size: u64,
+    received: u64,
+    next_index: u32,
+    started_at: Instant,
+}
+
+/// Committed upload: size and SHA-256 of the stored file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommittedUpload {
+    pub size: u64,
+    pub sha256: String,
+}
+
+pub struct UploadStore {
+    dir: PathBuf,
+    sessions: Mutex<HashMap<Uuid, UploadSession>>,
+}
+
+impl UploadStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, sessions: Mutex::new(HashMap::new()) }
+    }
+
+    fn user_dir(&self, user_id: Uuid) -> PathBuf {
+        self.dir.join(user_id.to_string())
+    }
+
+    fn part_path(&self, user_id: Uuid, upload_id: Uuid) -> PathBuf {
+        self.user_dir(user_id).join(format!("{}.part", upload_id))
+    }
+
+    fn committed_path(&self, user_id: Uuid, upload_id: Uuid) -> PathBuf {
+        self.user_dir(user_id).join(upload_id.to_string())
+    }
+
+    /// Open an upload of `total_size` bytes
+    pub fn begin(&self, user_id: Uuid, total_size: u64) -> Result<Uuid, UploadError> {
+        if total_size > MAX_UPLOAD_BYTES {
+            return Err(UploadError::TooLarge { max: MAX_UPLOAD_BYTES });
+        }
+        let upload_id = Uuid::new_v4();
+        std::fs::create_dir_all(self.user_dir(user_id))?;
+        std::fs::File::create(self.part_path(user_id, upload_id))?;
+        self.sessions.lock().insert(
+            upload_id,
+            UploadSession { user_id, total_size, received: 0, next_index: 0, started_at: Instant::now() },
+        );
+        Ok(upload_id)
+    }
+
+    /// Append chunk `index`; chunks come in order, a repeated chunk is
+    /// acknowledged without being written again. Returns the bytes received.
+    pub fn append(&self, user_id: Uuid, upload_id: Uuid, index: u32, data: &[u8]) -> Result<u64, UploadError> {
+        if data.len() > MAX_CHUNK_BYTES {
+            return Err(UploadError::ChunkTooLarge { max: MAX_CHUNK_BYTES });
+        }
+        let mut sessions = self.sessions.lock();
+        let session = sessions
+            .get_mut(&upload_id)
+            .filter(|s| s.user_id == user_id)
+            .ok_or(UploadError::NotFound)?;
+        if index < session.next_index {
+            return Ok(session.received);
+        }
+        if index > session.next_index {
+            return Err(UploadError::OutOfOrder { expected: session.next_index, got: index });
+        }
+        if session.received + data.len() as u64 > session.total_size {
+            return Err(UploadError::TooLarge { max: session.total_size });
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(self.part_path(user_id, upload_id))?;
+        file.write_all(data)?;
+        session.received += data.len() as u64;
+        session.next_index += 1;
+        Ok(session.received)
+    }
+
+    /// Finish an upload; `sha256` (hex), when given, must match the stored bytes
+    pub fn commit(&self, user_id: Uuid, upload_id: Uuid, sha256: Option<&str>) -> Result<CommittedUpload, UploadError> {
+        let mut sessions = self.sessions.lock();
+        let session = sessions
+            .get(&upload_id)
+            .filter(|s| s.user_id == user_id)
+            .ok_or(UploadError::NotFound)?;
+        if session.received != session.total_size {
+            return Err(UploadError::Incomplete { received: session.received, total: session.total_size });
+        }
+        let part = self.part_path(user_id, upload_id);
+        let digest = hex::encode(Sha256::digest(std::fs::read(&part)?));
+        if sha256.is_some_and(|expected| !expected.trim().eq_ignore_ascii_case(&digest)) {
+            return Err(UploadError::ChecksumMismatch);
+        }
+        std::fs::rename(&part, self.committed_path(user_id, upload_id))?;
+        let size = session.received;
+        sessions.remove(&upload_id);
+        Ok(CommittedUpload { size, sha256: digest })
+    }
+
+    /// Content of a committed upload of the user
+    pub async fn read(&self, user_id: Uuid, upload_id: Uuid) -> Result<Vec<u8>, UploadError> {
+        match tokio::fs::read(self.committed_path(user_id, upload_id)).await {
+            Ok(bytes) => Ok(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(UploadError::NotFound),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Remove a committed upload once its import is done
+    pub fn discard(&self, user_id: Uuid, upload_id: Uuid) {
+        let path = self.committed_path(user_id, upload_id);
+        if let Err(e) = std::fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Failed to remove import upload {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Drop unfinished sessions and files older than `max_age`; returns the files removed
+    pub fn sweep(&self, max_age: Duration) -> usize {
+        self.sessions.lock().retain(|_, s| s.started_at.elapsed() < max_age);
+
+        let Ok(users) = std::fs::read_dir(&self.dir) else {
+            return 0;
+        };
+        let now = SystemTime::now();
+        let mut removed = 0;
+        for file in users.flatten().filter_map(|user| std::fs::read_dir(user.path()).ok()).flatten().flatten() {
+            let expired = file
+                .metadata()
+                .and_then(|m| m.modified())
+                .map(|modified| now.duration_since(modified).unwrap_or_default() >= max_age)
+                .unwrap_or(false);
+            if expired && std::fs::remove_file(file.path()).is_ok() {
+                removed += 1;
+            }
+        }
+        removed
+    }
+}
+
+/// Removes the committed upload of an import job when the job is done
+pub struct UploadClaim {
+    user_id: Uuid,
+    upload_id: Uuid,
+}
+
+impl UploadClaim {
+    pub fn new(user_id: Uuid, upload_id: Uuid) -> Self {
+        Self { user_id, upload_id }
+    }
+}
+
+impl Drop for UploadClaim {
+    fn drop(&mut self) {
+        IMPORT_UPLOADS.discard(self.user_id, self.upload_id);
+    }
+}
+
+/// CSV of an import request: the committed upload when referenced, else the
+/// inline content. Files named `*.xlsx` are read as Excel workbooks and
+/// rewritten to CSV.
+pub async fn load_csv(
+    user_id: Uuid,
+    filename: &str,
+    upload_id: Option<Uuid>,
+    csv_content: &str,
+    csv_base64: Option<&str>,
+) -> anyhow::Result<DecodedCsv> {
+    if xlsx::is_xlsx_filename(filename) {
+        let bytes = match (upload_id, csv_base64) {
+            (Some(upload_id), _) => IMPORT_UPLOADS.read(user_id, upload_id).await?,
+            (None, Some(encoded)) => base64::engine::general_purpose::STANDARD.decode(encoded.trim())?,
+            (None, None) => anyhow::bail!("import:xlsx_requires_file"),
+        };
+        return Ok(DecodedCsv { text: xlsx::xlsx_to_csv(&bytes)?, encoding: UTF_8_NAME });
+    }
+    match upload_id {
+        Some(upload_id) => Ok(decode_csv(&IMPORT_UPLOADS.read(user_id, upload_id).await?)),
+        None => decode_payload(csv_content, csv_base64),
+    }
+}
+
+/// Bytes of a binary import file (ZIP, KML/KMZ): the committed upload when
+/// referenced, else the inline base64 content
+pub async fn load_file(user_id: Uuid, upload_id: Option<Uuid>, content_base64: &str) -> anyhow::Result<Vec<u8>> {
+    match upload_id {
+        Some(upload_id) => Ok(IMPORT_UPLOADS.read(user_id, upload_id).await?),
+        None => Ok(base64::engine::general_purpose::STANDARD.decode(content_base64.trim())?),
+    }
+}
+
+/// Remove expired uploads every hour
+pub async fn run_sweeper() {
+    let mut ticker = tokio::time::interval(SWEEP_TICK);
+    loop {
+        ticker.tick().await;
+        match IMPORT_UPLOADS.sweep(UPLOAD_TTL) {
+            0 => {}
+            count => info!("Removed {} expired import uploads", count),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> UploadStore {
+        UploadStore::new(std::env::temp_dir().join(format!("sazinka-uploads-{}", Uuid::new_v4())))
+    }
+
+    #[tokio::test]
+    async fn chunks_are_assembled_in_order() {
+        let store = store();
+        let user = Uuid::new_v4();
+        let id = store.begin(user, 11).unwrap();
+        assert_eq!(store.append(user, id, 0, b"hello ").unwrap(), 6);
+        // A retried chunk is acknowledged without being written twice
+        assert_eq!(store.append(user, id, 0, b"hello ").unwrap(), 6);
+        assert_eq!(store.append(user, id, 1, b"world").unwrap(), 11);
+
+        let committed = store.commit(user, id, None).unwrap();
+        assert_eq!(committed.size, 11);
+        assert_eq!(committed.sha256, hex::encode(Sha256::digest(b"hello world")));
+        assert_eq!(store.read(user, id).await.unwrap(), b"hello world");
+
+        store.discard(user, id);
+        assert!(matches!(store.read(user, id).await, Err(UploadError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn inline_content_is_used_without_upload() {
+        let user = Uuid::new_v4();
+        assert_eq!(load_csv(user, "a.csv", None, "\u{feff}a;b", None).await.unwrap().text, "a;b");
+        assert_eq!(load_file(user, None, "aGk=").await.unwrap(), b"hi");
+        assert!(load_file(user, Some(Uuid::new_v4()), "").await.is_err());
+    }
+
+    #[test]
+    fn out_of_order_and_foreign_chunks_are_rejected() {
+        let store = store();
+        let user = Uuid::new_v4();
+        let id = store.begin(user, 10).unwrap();
+        assert!(matches!(
+            store.append(user, id, 1, b"x"),
+            Err(UploadError::OutOfOrder { expected: 0, got: 1 })
+        ));
+        assert!(matches!(store.append(Uuid::new_v4(), id, 0, b"x"), Err(UploadError::NotFound)));
+    }
+
+    #[test]
+    fn commit_checks_size_and_checksum() {
+        let store = store();
+        let user = Uuid::new_v4();
+        let id = store.begin(user, 4).unwrap();
+        store.append(user, id, 0, b"ab").unwrap();
+        assert!(matches!(store.commit(user, id, None), Err(UploadError::Incomplete { received: 2, total: 4 })));
+        assert!(matches!(store.append(user, id, 1, b"cde"), Err(UploadError::TooLarge { max: 4 })));
+        store.append(user, id, 1, b"cd").unwrap();
+        assert!(matches!(store.commit(user, id, Some("00")), Err(UploadError::ChecksumMismatch)));
+        let digest = hex::encode(Sha256::digest(b"abcd")).to_uppercase();
+        assert!(store.commit(user, id, Some(&digest)).is_ok());
+    }
+
+    #[test]
+    fn oversized_uploads_and_chunks_are_refused() {
+        let store = store();
+        let user = Uuid::new_v4();
+        assert!(matches!(store.begin(user, MAX_UPLOAD_BYTES + 1), Err(UploadError::TooLarge { .. })));
+        let id = store.begin(user, MAX_UPLOAD_BYTES).unwrap();
+        let chunk = vec![0u8; MAX_CHUNK_BYTES + 1];
+        assert!(matches!(store.append(user, id, 0, &chunk), Err(UploadError::ChunkTooLarge { .. })));
+    }
+
+    #[test]
+    fn sweep_removes_expired_sessions_and_files() {
+        let store = store();
+        let user = Uuid::new_v4();
+        let id = store.begin(user, 1).unwrap();
+        assert_eq!(store.sweep(UPLOAD_TTL), 0);
+        assert_eq!(store.sweep(Duration::ZERO), 1);
+        assert!(matches!(store.append(user, id, 0, b"x"), Err(UploadError::NotFound)));
+    }
+}
@@ -0,0 +1,257 @@
+//! Bulk revision interval adjustment
+//!
+//! A regulation change of a device category's inspection interval is
+//! previewed first: every matching device gets a plan (move its upcoming
+//! revision, create one, leave an agreed appointment alone, ...) and the
+//! adjustment is stored as a draft with the summary. Applying the draft runs
+//! as a background job that recomputes the plan against current data and
+//! writes it in a single transaction.
+//!
+//! The new interval is counted from the last completed revision; devices
+//! never revised count from the implied previous date (open due date or
+//! `next_due_date` minus the old interval), then from the installation date.
+
+use async_nats::Client;
+use chrono::{Months, NaiveDate, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::job_history::JOB_HISTORY;
+use crate::subjects;
+use crate::types::interval_adjustment::{
+    IntervalAdjustment, IntervalAdjustmentAction, IntervalAdjustmentItem, IntervalAdjustmentSource,
+    IntervalAdjustmentSummary, PreviewIntervalAdjustmentRequest, PreviewIntervalAdjustmentResponse,
+    MAX_PREVIEW_ITEMS,
+};
+
+/// Job type in the job history
+const JOB_TYPE: &str = "interval_adjustment";
+
+/// Date the new interval of a device is counted from
+fn anchor_date(source: &IntervalAdjustmentSource) -> Option<NaiveDate> {
+    let implied_previous = || {
+        source
+            .open_due_date
+            .or(source.next_due_date)
+            .and_then(|due| due.checked_sub_months(Months::new(source.revision_interval_months.max(0) as u32)))
+    };
+    source
+        .last_completed
+        .or_else(implied_previous)
+        .or(source.installation_date)
+}
+
+/// Plan the change of one device
+pub fn plan_item(source: &IntervalAdjustmentSource, new_interval_months: i32) -> IntervalAdjustmentItem {
+    let anchor = anchor_date(source);
+    let new_due_date = anchor.and_then(|a| a.checked_add_months(Months::new(new_interval_months.max(0) as u32)));
+    let action = match (new_due_date, source.open_status.as_deref()) {
+        (None, _) => IntervalAdjustmentAction::NoAnchor,
+        (Some(_), Some("scheduled" | "confirmed")) => IntervalAdjustmentAction::KeepScheduled,
+        (Some(_), Some(_)) if source.open_due_date == new_due_date => IntervalAdjustmentAction::Unchanged,
+        (Some(_), Some(_)) => IntervalAdjustmentAction::Move,
+        (Some(_), None) => IntervalAdjustmentAction::Create,
+    };
+    IntervalAdjustmentItem {
+        device_id: source.device_id,
+        customer_id: source.customer_id,
+        customer_name: source.customer_name.clone(),
+        device_name: source.device_name.clone(),
+        old_interval_months: source.revision_interval_months,
+        anchor_date: anchor,
+        revision_id: source.open_revision_id,
+        old_due_date: source.open_due_date,
+        new_due_date,
+        action,
+    }
+}
+
+/// Dry run: plan every matching device and store the adjustment as a
+/// draft. None when the device type is not the account's.
+pub async fn preview(
+    pool: &PgPool,
+    user_id: Uuid,
+    actor_id: Uuid,
+    req: &PreviewIntervalAdjustmentRequest,
+) -> anyhow::Result<Option<PreviewIntervalAdjustmentResponse>> {
+    let Some(tenant_id) = queries::device_type_config::get_tenant_id_for_user(pool, user_id).await? else {
+        return Ok(None);
+    };
+    if queries::device_type_config::get_device_type_config(pool, tenant_id, req.device_type_config_id)
+        .await?
+        .is_none()
+    {
+        return Ok(None);
+    }
+
+    let mut tx = pool.begin().await?;
+    let sources = queries::interval_adjustment::list_sources(
+        &mut tx,
+        user_id,
+        req.device_type_config_id,
+        req.only_interval_months,
+    )
+    .await?;
+    tx.commit().await?;
+
+    let mut items: Vec<IntervalAdjustmentItem> =
+        sources.iter().map(|s| plan_item(s, req.new_interval_months)).collect();
+    let summary = IntervalAdjustmentSummary::from_items(&items);
+    let adjustment = queries::interval_adjustment::create_draft(pool, user_id, actor_id, req, &summary).await?;
+
+    let items_truncated = items.len() > MAX_PREVIEW_ITEMS;
+    items.truncate(MAX_PREVIEW_ITEMS);
+    Ok(Some(PreviewIntervalAdjustmentResponse { adjustment, items, items_truncated }))
+}
+
+/// Recompute the plan and write it in one transaction
+async fn apply(pool: &PgPool, user_id: Uuid, adjustment: &IntervalAdjustment) -> anyhow::Result<IntervalAdjustmentSummary> {
+    let tenant_id = queries::device_type_config::get_tenant_id_for_user(pool, user_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Account has no tenant"))?;
+
+    let mut tx = pool.begin().await?;
+    let sources = queries::interval_adjustment::list_sources(
+        &mut tx,
+        user_id,
+        adjustment.device_type_config_id,
+        adjustment.only_interval_months,
+    )
+    .await?;
+    let items: Vec<IntervalAdjustmentItem> =
+        sources.iter().map(|s| plan_item(s, adjustment.new_interval_months)).collect();
+    for item in &items {
+        queries::interval_adjustment::apply_item(&mut tx, user_id, adjustment.new_interval_months, item).await?;
+    }
+    if adjustment.update_type_default {
+        queries::interval_adjustment::set_type_default(
+            &mut tx,
+            tenant_id,
+            adjustment.device_type_config_id,
+            adjustment.new_interval_months,
+        )
+        .await?;
+    }
+    let summary = IntervalAdjustmentSummary::from_items(&items);
+    queries::interval_adjustment::mark_applied(&mut tx, adjustment.id, &summary).await?;
+    tx.commit().await?;
+
+    Ok(summary)
+}
+
+async fn publish_status(client: &Client, job_id: Uuid, status: serde_json::Value) {
+    let update = json!({ "jobId": job_id, "timestamp": Utc::now(), "status": status });
+    let subject = subjects::job_status(subjects::job::INTERVAL_ADJUSTMENT_STATUS, job_id);
+    if let Ok(payload) = serde_json::to_vec(&update) {
+        let _ = client.publish(subject, payload.into()).await;
+    }
+}
+
+/// Background job applying a claimed adjustment; the adjustment id is the job id
+pub async fn run_job(client: Client, pool: PgPool, user_id: Uuid, adjustment: IntervalAdjustment) {
+    let job_id = adjustment.id;
+    let started_at = Utc::now();
+    JOB_HISTORY.record_running(job_id, JOB_TYPE, user_id, started_at);
+    publish_status(&client, job_id, json!({ "type": "processing", "progress": 10, "message": "jobs:applying" })).await;
+
+    match apply(&pool, user_id, &adjustment).await {
+        Ok(summary) => {
+            info!(
+                "Interval adjustment {} applied: {} devices, {} moved, {} created",
+                job_id, summary.devices, summary.moved, summary.created
+            );
+            let report = serde_json::to_value(&summary).ok();
+            publish_status(&client, job_id, json!({ "type": "completed", "result": report })).await;
+            JOB_HISTORY.record_completed_with_report(
+                job_id,
+                JOB_TYPE,
+                user_id,
+                started_at,
+                Some(format!("{} devices set to {} months", summary.devices, adjustment.new_interval_months)),
+                report,
+            );
+        }
+        Err(e) => {
+            error!("Interval adjustment {} failed: {}", job_id, e);
+            if let Err(mark_err) = queries::interval_adjustment::mark_failed(&pool, job_id, &e.to_string()).await {
+                error!("Failed to mark interval adjustment {} as failed: {}", job_id, mark_err);
+            }
+            publish_status(&client, job_id, json!({ "type": "failed", "error": e.to_string() })).await;
+            JOB_HISTORY.record_failed(job_id, JOB_TYPE, user_id, started_at, e.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn source() -> IntervalAdjustmentSource {
+        IntervalAdjustmentSource {
+            device_id: Uuid::new_v4(),
+            customer_id: Uuid::new_v4(),
+            customer_name: None,
+            device_name: None,
+            revision_interval_months: 12,
+            installation_date: None,
+            next_due_date: None,
+            last_completed: None,
+            open_revision_id: None,
+            open_status: None,
+            open_due_date: None,
+        }
+    }
+
+    #[test]
+    fn upcoming_revision_moves_from_last_completion() {
+        let mut s = source();
+        s.last_completed = Some(date(2025, 3, 10));
+        s.open_revision_id = Some(Uuid::new_v4());
+        s.open_status = Some("upcoming".to_string());
+        s.open_due_date = Some(date(2026, 3, 10));
+        let item = plan_item(&s, 24);
+        assert_eq!(item.action, IntervalAdjustmentAction::Move);
+        assert_eq!(item.new_due_date, Some(date(2027, 3, 10)));
+        assert_eq!(item.old_due_date, Some(date(2026, 3, 10)));
+    }
+
+    #[test]
+    fn never_revised_device_counts_from_implied_previous_date() {
+        let mut s = source();
+        s.installation_date = Some(date(2010, 1, 1));
+        s.next_due_date = Some(date(2026, 6, 1));
+        let item = plan_item(&s, 6);
+        assert_eq!(item.anchor_date, Some(date(2025, 6, 1)));
+        assert_eq!(item.new_due_date, Some(date(2025, 12, 1)));
+        assert_eq!(item.action, IntervalAdjustmentAction::Create);
+    }
+
+    #[test]
+    fn agreed_appointments_are_kept() {
+        let mut s = source();
+        s.last_completed = Some(date(2025, 1, 15));
+        s.open_status = Some("confirmed".to_string());
+        s.open_due_date = Some(date(2026, 1, 15));
+        assert_eq!(plan_item(&s, 6).action, IntervalAdjustmentAction::KeepScheduled);
+    }
+
+    #[test]
+    fn same_due_date_is_unchanged_and_missing_anchor_reported() {
+        let mut s = source();
+        s.last_completed = Some(date(2025, 1, 15));
+        s.open_status = Some("upcoming".to_string());
+        s.open_due_date = Some(date(2026, 1, 15));
+        assert_eq!(plan_item(&s, 12).action, IntervalAdjustmentAction::Unchanged);
+
+        let item = plan_item(&source(), 12);
+        assert_eq!(item.action, IntervalAdjustmentAction::NoAnchor);
+        assert_eq!(item.new_due_date, None);
+    }
+}
@@ -0,0 +1,102 @@
+//! Spare parts inventory rules
+//!
+//! When a stock level counts as low and what the restock report suggests
+//! bringing to each location.
+
+use crate::types::report::{RestockCandidate, RestockRow};
+
+/// Default consumption window of the restock report
+pub const DEFAULT_RESTOCK_DAYS: i64 = 30;
+pub const MAX_RESTOCK_DAYS: i64 = 365;
+
+/// Stock at or below its reorder point, or negative when there is none
+pub fn is_low_stock(quantity: f64, min_quantity: f64) -> bool {
+    quantity < 0.0 || (min_quantity > 0.0 && quantity <= min_quantity)
+}
+
+/// Locations that are low, or would run low if the last window's
+/// consumption repeats. The suggestion tops the stock up to the reorder
+/// point plus that consumption, in whole units.
+pub fn build_restock_report(candidates: &[RestockCandidate]) -> Vec<RestockRow> {
+    let mut rows: Vec<RestockRow> = candidates
+        .iter()
+        .filter_map(|c| {
+            let low = is_low_stock(c.quantity, c.min_quantity);
+            let projected_low = c.consumed > 0.0 && c.quantity - c.consumed <= c.min_quantity;
+            if !low && !projected_low {
+                return None;
+            }
+            let suggested = (c.min_quantity + c.consumed - c.quantity).max(0.0).ceil();
+            Some(RestockRow {
+                catalog_item_id: c.catalog_item_id,
+                item_name: c.item_name.clone(),
+                unit: c.unit.clone(),
+                crew_id: c.crew_id,
+                depot_id: c.depot_id,
+                location_name: c.location_name.clone(),
+                quantity: c.quantity,
+                min_quantity: c.min_quantity,
+                consumed: c.consumed,
+                low,
+                suggested_quantity: suggested,
+            })
+        })
+        .collect();
+
+    rows.sort_by(|a, b| {
+        b.low
+            .cmp(&a.low)
+            .then_with(|| a.location_name.cmp(&b.location_name))
+            .then_with(|| a.item_name.cmp(&b.item_name))
+    });
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn candidate(location: &str, quantity: f64, min_quantity: f64, consumed: f64) -> RestockCandidate {
+        RestockCandidate {
+            catalog_item_id: Uuid::nil(),
+            item_name: "Termočlánek".to_string(),
+            unit: "ks".to_string(),
+            crew_id: Some(Uuid::nil()),
+            depot_id: None,
+            location_name: location.to_string(),
+            quantity,
+            min_quantity,
+            consumed,
+        }
+    }
+
+    #[test]
+    fn test_is_low_stock() {
+        assert!(is_low_stock(2.0, 2.0));
+        assert!(!is_low_stock(3.0, 2.0));
+        assert!(!is_low_stock(0.0, 0.0));
+        assert!(is_low_stock(-1.0, 0.0));
+    }
+
+    #[test]
+    fn test_restock_report() {
+        let rows = build_restock_report(&[
+            // Fine: plenty left even if consumption repeats
+            candidate("Dodávka A", 10.0, 2.0, 3.0),
+            // Projected low: 4 - 3 = 1 <= 2
+            candidate("Dodávka B", 4.0, 2.0, 3.0),
+            // Low already
+            candidate("Dodávka C", 1.0, 2.0, 0.5),
+            // Untracked part (no reorder point, no consumption)
+            candidate("Sklad", 0.0, 0.0, 0.0),
+        ]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].location_name, "Dodávka C");
+        assert!(rows[0].low);
+        assert_eq!(rows[0].suggested_quantity, 2.0);
+        assert_eq!(rows[1].location_name, "Dodávka B");
+        assert!(!rows[1].low);
+        assert_eq!(rows[1].suggested_quantity, 1.0);
+    }
+}
@@ -0,0 +1,240 @@
+//! Postgres backup of queued jobs
+//!
+//! Submitters publish through [`publish_with_backup`], which records the
+//! stream sequence of the message next to a copy of the payload. Processors
+//! call [`mark_started`] before acking, so at startup any backup still marked
+//! `queued` whose message is missing from the stream was dropped by JetStream
+//! (purge, deleted stream, limits) — or by a restart, in standalone mode,
+//! whose queue lives in memory — and is flagged as lost.
+//!
+//! Jobs the previous process was still working on are reported by the job
+//! history as interrupted; their owners get a final `failed` status update
+//! and the backup is flagged as lost so the job can be retried.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use async_nats::Client;
+use chrono::Utc;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::job_history::{JobHistoryEntry, INTERRUPTED_ERROR};
+use crate::services::leader;
+use crate::subjects;
+use crate::transport::JobQueue;
+use crate::types::job_backup::{JOB_BACKUP_LOST, MAX_BACKUP_PAYLOAD_BYTES};
+use crate::types::{CustomerImportJobStatus, CustomerImportJobStatusUpdate};
+
+/// Give processors time to (re)create their streams before reconciling
+const RECONCILE_DELAY: Duration = Duration::from_secs(10);
+/// Backups of picked-up jobs are kept this long for diagnostics
+const STARTED_RETENTION_DAYS: i64 = 7;
+
+/// What a submitter knows about the job it is publishing
+pub struct BackupJob<'a> {
+    pub job_id: Uuid,
+    pub user_id: Uuid,
+    pub job_type: &'a str,
+    pub stream: &'a str,
+    pub subject: &'a str,
+    pub filename: Option<&'a str>,
+}
+
+/// Portion of the payload worth keeping (None for oversized uploads)
+pub fn backup_payload(payload: &[u8]) -> Option<&[u8]> {
+    (payload.len() <= MAX_BACKUP_PAYLOAD_BYTES).then_some(payload)
+}
+
+/// Publish a job to the job queue and mirror it into Postgres.
+///
+/// The publish result decides success; a failed backup is only logged so
+/// that a database hiccup never blocks job submission.
+pub async fn publish_with_backup(
+    queue: &JobQueue,
+    pool: &PgPool,
+    job: BackupJob<'_>,
+    payload: Vec<u8>,
+) -> Result<()> {
+    let payload_bytes = payload.len() as i32;
+    let kept = backup_payload(&payload).map(|p| p.to_vec());
+
+    let sequence = queue.publish(job.subject, payload.into()).await?;
+
+    if let Err(e) = queries::job_backup::insert_backup(
+        pool,
+        job.job_id,
+        job.user_id,
+        job.job_type,
+        job.stream,
+        job.subject,
+        sequence as i64,
+        job.filename,
+        payload_bytes,
+        kept.as_deref(),
+    )
+    .await
+    {
+        warn!("Failed to back up job {} ({}): {}", job.job_id, job.job_type, e);
+    }
+
+    Ok(())
+}
+
+/// Record that a worker picked the job up. Must run before the message is acked.
+pub async fn mark_started(pool: &PgPool, job_id: Uuid) {
+    if let Err(e) = queries::job_backup::mark_started(pool, job_id).await {
+        warn!("Failed to mark job backup {} as started: {}", job_id, e);
+    }
+}
+
+/// Flag queued backups whose messages are gone. Returns the number of lost jobs.
+pub async fn reconcile(queue: &JobQueue, pool: &PgPool) -> Result<u32> {
+    let cutoff = Utc::now() - chrono::Duration::days(STARTED_RETENTION_DAYS);
+    let pruned = queries::job_backup::delete_started_before(pool, cutoff).await?;
+    if pruned > 0 {
+        info!("Pruned {} old job backups", pruned);
+    }
+
+    let mut lost = 0u32;
+    for backup in queries::job_backup::list_queued(pool).await? {
+        match queue.contains(&backup.stream, backup.stream_sequence as u64).await {
+            Ok(true) => {}
+            Ok(false) => {
+                if queries::job_backup::mark_lost(pool, backup.id).await? {
+                    warn!(
+                        "Job {} ({}) of user {} is missing from stream {} — marked lost",
+                        backup.id, backup.job_type, backup.user_id, backup.stream
+                    );
+                    lost += 1;
+                }
+            }
+            Err(e) => {
+                warn!("Could not check job {} in stream {}: {}", backup.id, backup.stream, e);
+            }
+        }
+    }
+
+    Ok(lost)
+}
+
+/// Status subject of an import job, by its job history type.
+///
+/// All import status enums share the `failed` shape, so one update type
+/// serves every import kind. Work log imports keep their legacy history name.
+pub fn import_status_subject(job_type: &str, job_id: Uuid) -> Option<String> {
+    let kind = match job_type.strip_prefix("import.")? {
+        "visit" => "worklog",
+        kind => kind,
+    };
+    Some(subjects::job_status(&subjects::job::import_status(kind), job_id))
+}
+
+/// Tell owners that their interrupted jobs failed and make them retryable
+pub async fn recover_interrupted(client: &Client, pool: &PgPool, interrupted: &[JobHistoryEntry]) {
+    for entry in interrupted {
+        warn!("Job {} ({}) of user {} was interrupted by a restart", entry.id, entry.job_type, entry.user_id);
+
+        if let Some(subject) = import_status_subject(&entry.job_type, entry.id) {
+            let update = CustomerImportJobStatusUpdate::new(entry.id, CustomerImportJobStatus::Failed {
+                error: INTERRUPTED_ERROR.to_string(),
+            });
+            match serde_json::to_vec(&update) {
+                Ok(payload) => {
+                    if let Err(e) = client.publish(subject, payload.into()).await {
+                        warn!("Failed to notify about interrupted job {}: {}", entry.id, e);
+                    }
+                }
+                Err(e) => warn!("Failed to serialize interrupted status for {}: {}", entry.id, e),
+            }
+        }
+
+        if let Err(e) = queries::job_backup::mark_interrupted(pool, entry.id).await {
+            warn!("Failed to flag backup of interrupted job {}: {}", entry.id, e);
+        }
+    }
+}
+
+/// Run reconciliation once, shortly after startup. Every worker recovers
+/// its own interrupted jobs; the stream check covers all workers' backups,
+/// so only one of the workers starting together runs it.
+pub async fn run_startup_reconciliation(client: Client, pool: PgPool, interrupted: Vec<JobHistoryEntry>) {
+    recover_interrupted(&client, &pool, &interrupted).await;
+
+    leader::run_once(&pool, "job_backup_reconciliation", async {
+        tokio::time::sleep(RECONCILE_DELAY).await;
+        let queue = JobQueue::new(client.clone());
+        match reconcile(&queue, &pool).await {
+            Ok(0) => info!("Job backup reconciliation: no lost jobs"),
+            Ok(n) => warn!("Job backup reconciliation: {} lost jobs", n),
+            Err(e) => error!("Job backup reconciliation failed: {}", e),
+        }
+    })
+    .await;
+}
+
+/// Why a lost job could not be resubmitted
+#[derive(Debug, PartialEq)]
+pub enum ResubmitError {
+    NotFound,
+    NotLost,
+    PayloadNotKept,
+}
+
+/// Republish a lost job from its backup
+pub async fn resubmit(
+    queue: &JobQueue,
+    pool: &PgPool,
+    user_id: Uuid,
+    job_id: Uuid,
+) -> Result<std::result::Result<(), ResubmitError>> {
+    let Some(backup) = queries::job_backup::get_backup(pool, user_id, job_id).await? else {
+        return Ok(Err(ResubmitError::NotFound));
+    };
+    if backup.status != JOB_BACKUP_LOST {
+        return Ok(Err(ResubmitError::NotLost));
+    }
+    let Some(payload) = backup.payload else {
+        return Ok(Err(ResubmitError::PayloadNotKept));
+    };
+
+    let sequence = queue.publish(backup.subject.clone(), payload.into()).await?;
+    queries::job_backup::mark_resubmitted(pool, job_id, sequence as i64).await?;
+    info!("Resubmitted lost job {} ({})", job_id, backup.job_type);
+
+    Ok(Ok(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_payload_keeps_small_payloads() {
+        let payload = vec![b'x'; 1024];
+        assert_eq!(backup_payload(&payload).map(|p| p.len()), Some(1024));
+    }
+
+    #[test]
+    fn test_import_status_subject_maps_history_types() {
+        let id = Uuid::nil();
+        assert_eq!(
+            import_status_subject("import.customer", id).unwrap(),
+            subjects::job_status(&subjects::job::import_status("customer"), id)
+        );
+        assert_eq!(
+            import_status_subject("import.visit", id).unwrap(),
+            subjects::job_status(&subjects::job::import_status("worklog"), id)
+        );
+        assert!(import_status_subject("geocode", id).is_none());
+    }
+
+    #[test]
+    fn test_backup_payload_skips_oversized_uploads() {
+        let payload = vec![b'x'; MAX_BACKUP_PAYLOAD_BYTES + 1];
+        assert!(backup_payload(&payload).is_none());
+        assert!(backup_payload(&payload[..MAX_BACKUP_PAYLOAD_BYTES]).is_some());
+    }
+}
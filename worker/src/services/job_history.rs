@@ -1,20 +1,51 @@
 #![allow(dead_code)]
 //! Job history service
 //!
-//! Stores recent job completions in memory with file-backed persistence
-//! so history survives worker restarts.
+//! Records the lifecycle of background jobs in the `job_history` table so
+//! history survives worker restarts. Jobs are recorded as `running` when a
+//! worker picks them up; the final record replaces that row, so a `running`
+//! row of this worker instance found at startup belongs to a job the
+//! previous process never finished. Rows carry the instance
+//! ([`worker_instance`]) because several workers share the table: a
+//! starting worker must not fail the live jobs of the others.
+//!
+//! Processors record synchronously; entries go through a channel to a
+//! single writer task, which keeps each job's records in order. Entries
+//! recorded before [`JobHistoryService::start`] wait in the channel.
 
-use std::collections::VecDeque;
 use std::path::Path;
-use std::sync::Arc;
-use parking_lot::RwLock;
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use tracing::{info, warn};
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
 
-const MAX_HISTORY_SIZE: usize = 100;
-const HISTORY_FILE: &str = "logs/job-history.json";
+use crate::db::queries;
+use crate::services::notification_dispatch;
+use crate::services::telemetry::TELEMETRY;
+
+/// JSON file the history was kept in before it moved to Postgres
+const LEGACY_HISTORY_FILE: &str = "logs/job-history.json";
+/// Finished jobs are kept this long
+const HISTORY_RETENTION_DAYS: i64 = 180;
+/// Error key for jobs cut off by a worker restart
+pub const INTERRUPTED_ERROR: &str = "jobs:interrupted";
+
+/// Stable name of this worker across restarts: WORKER_INSTANCE_ID, else the
+/// host name (container / pod name), else "local"
+pub fn worker_instance() -> &'static str {
+    static INSTANCE: once_cell::sync::Lazy<String> = once_cell::sync::Lazy::new(|| {
+        ["WORKER_INSTANCE_ID", "HOSTNAME"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|v| !v.trim().is_empty()))
+            .map(|v| v.trim().to_string())
+            .unwrap_or_else(|| "local".to_string())
+    });
+    &INSTANCE
+}
 
 /// Job entry in history
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,26 +69,80 @@ pub struct JobHistoryEntry {
 #[serde(rename_all = "camelCase")]
 pub struct JobHistoryResponse {
     pub jobs: Vec<JobHistoryEntry>,
-    pub total: usize,
+    /// Jobs matching the filters (all pages)
+    pub total: i64,
 }
 
-/// Job history storage backed by an in-memory deque + JSON file on disk.
+/// Job history recorder backed by Postgres
 pub struct JobHistoryService {
-    history: Arc<RwLock<VecDeque<JobHistoryEntry>>>,
+    sender: mpsc::UnboundedSender<JobHistoryEntry>,
+    receiver: Mutex<Option<mpsc::UnboundedReceiver<JobHistoryEntry>>>,
 }
 
 impl JobHistoryService {
     pub fn new() -> Self {
-        let mut deque = VecDeque::with_capacity(MAX_HISTORY_SIZE);
-        if let Some(loaded) = Self::load_from_disk() {
-            for entry in loaded {
-                deque.push_back(entry);
-            }
-            info!("Loaded {} job history entries from disk", deque.len());
-        }
+        let (sender, receiver) = mpsc::unbounded_channel();
         Self {
-            history: Arc::new(RwLock::new(deque)),
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+        }
+    }
+
+    /// Move any legacy file history into the table, fail the jobs the
+    /// previous process of this instance left `running` and start writing
+    /// new records.
+    ///
+    /// Must be called at startup before any processor starts picking up
+    /// jobs. Returns the interrupted entries.
+    pub async fn start(&self, pool: PgPool) -> Vec<JobHistoryEntry> {
+        import_legacy_file(&pool).await;
+
+        match queries::job_history::delete_older_than(&pool, HISTORY_RETENTION_DAYS).await {
+            Ok(0) => {}
+            Ok(deleted) => info!("Removed {} job history entries older than {} days", deleted, HISTORY_RETENTION_DAYS),
+            Err(e) => warn!("Failed to prune job history: {}", e),
         }
+
+        let interrupted = match queries::job_history::fail_running(&pool, worker_instance(), INTERRUPTED_ERROR).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("Failed to mark interrupted jobs: {}", e);
+                Vec::new()
+            }
+        };
+
+        match self.receiver.lock().take() {
+            Some(receiver) => {
+                tokio::spawn(run_writer(pool, receiver));
+            }
+            None => warn!("Job history writer already started"),
+        }
+
+        interrupted
+    }
+
+    /// Record a job that a worker just picked up
+    pub fn record_running(
+        &self,
+        id: Uuid,
+        job_type: &str,
+        user_id: Uuid,
+        started_at: DateTime<Utc>,
+    ) {
+        let entry = JobHistoryEntry {
+            id,
+            user_id,
+            job_type: job_type.to_string(),
+            status: "running".to_string(),
+            started_at,
+            completed_at: started_at,
+            duration_ms: 0,
+            error: None,
+            details: None,
+            report: None,
+        };
+        
+        self.add_entry(entry);
     }
     
     /// Record a completed job (without report)
@@ -97,6 +182,8 @@ impl JobHistoryService {
             details,
             report,
         };
+
+        TELEMETRY.record_usage(user_id, &format!("jobs.{}", job_type));
         
         self.add_entry(entry);
     }
@@ -125,6 +212,8 @@ impl JobHistoryService {
             details: None,
             report: None,
         };
+
+        TELEMETRY.record_error(user_id, job_type, entry.error.as_deref().unwrap_or_default());
         
         self.add_entry(entry);
     }
@@ -157,116 +246,58 @@ impl JobHistoryService {
     }
     
     fn add_entry(&self, entry: JobHistoryEntry) {
-        let mut history = self.history.write();
-        
-        if history.len() >= MAX_HISTORY_SIZE {
-            history.pop_back();
+        if self.sender.send(entry).is_err() {
+            warn!("Job history writer has stopped; entry dropped");
         }
-        
-        history.push_front(entry);
-        
-        Self::save_to_disk(&history);
     }
-    
-    /// Get recent job history (all users — for admin use only)
-    pub fn get_recent(&self, limit: usize) -> JobHistoryResponse {
-        let history = self.history.read();
-        let jobs: Vec<JobHistoryEntry> = history
-            .iter()
-            .take(limit)
-            .cloned()
-            .collect();
-        let total = history.len();
-        
-        JobHistoryResponse { jobs, total }
-    }
-    
-    /// Get recent job history filtered by user (multi-tenant safe)
-    pub fn get_recent_for_user(&self, user_id: Uuid, limit: usize) -> JobHistoryResponse {
-        let history = self.history.read();
-        let jobs: Vec<JobHistoryEntry> = history
-            .iter()
-            .filter(|j| j.user_id == user_id)
-            .take(limit)
-            .cloned()
-            .collect();
-        let total = jobs.len();
-        
-        JobHistoryResponse { jobs, total }
-    }
-    
-    /// Get jobs by type
-    pub fn get_by_type(&self, job_type: &str, limit: usize) -> JobHistoryResponse {
-        let history = self.history.read();
-        let jobs: Vec<JobHistoryEntry> = history
-            .iter()
-            .filter(|j| j.job_type == job_type)
-            .take(limit)
-            .cloned()
-            .collect();
-        let total = jobs.len();
-        
-        JobHistoryResponse { jobs, total }
-    }
-    
-    /// Get jobs by status
-    pub fn get_by_status(&self, status: &str, limit: usize) -> JobHistoryResponse {
-        let history = self.history.read();
-        let jobs: Vec<JobHistoryEntry> = history
-            .iter()
-            .filter(|j| j.status == status)
-            .take(limit)
-            .cloned()
-            .collect();
-        let total = jobs.len();
-        
-        JobHistoryResponse { jobs, total }
+}
+
+impl Default for JobHistoryService {
+    fn default() -> Self {
+        Self::new()
     }
-    
-    fn load_from_disk() -> Option<Vec<JobHistoryEntry>> {
-        let path = Path::new(HISTORY_FILE);
-        if !path.exists() {
-            return None;
+}
+
+/// Write recorded entries in order until the service is dropped; finished
+/// jobs are also announced to their owner as notifications
+async fn run_writer(pool: PgPool, mut receiver: mpsc::UnboundedReceiver<JobHistoryEntry>) {
+    while let Some(entry) = receiver.recv().await {
+        if let Err(e) = queries::job_history::upsert_entry(&pool, &entry, worker_instance()).await {
+            warn!("Failed to record job {} ({}) in history: {}", entry.id, entry.status, e);
         }
-        match std::fs::read_to_string(path) {
-            Ok(content) => match serde_json::from_str::<Vec<JobHistoryEntry>>(&content) {
-                Ok(entries) => Some(entries),
-                Err(e) => {
-                    warn!("Failed to parse job history file: {}", e);
-                    None
-                }
-            },
-            Err(e) => {
-                warn!("Failed to read job history file: {}", e);
-                None
-            }
+        if let Err(e) = notification_dispatch::notify_job(&pool, &entry).await {
+            warn!("Failed to notify about job {}: {}", entry.id, e);
         }
     }
-    
-    fn save_to_disk(history: &VecDeque<JobHistoryEntry>) {
-        let path = Path::new(HISTORY_FILE);
-        if let Some(dir) = path.parent() {
-            if let Err(e) = std::fs::create_dir_all(dir) {
-                warn!("Failed to create job history directory: {}", e);
-                return;
-            }
+}
+
+/// One-time move of the pre-Postgres JSON history into the table
+async fn import_legacy_file(pool: &PgPool) {
+    let path = Path::new(LEGACY_HISTORY_FILE);
+    if !path.exists() {
+        return;
+    }
+    let entries = match std::fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| Ok(serde_json::from_str::<Vec<JobHistoryEntry>>(&content)?))
+    {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to read legacy job history file: {}", e);
+            return;
         }
-        let entries: Vec<&JobHistoryEntry> = history.iter().collect();
-        match serde_json::to_string_pretty(&entries) {
-            Ok(json) => {
-                if let Err(e) = std::fs::write(path, json) {
-                    warn!("Failed to write job history file: {}", e);
-                }
-            }
-            Err(e) => warn!("Failed to serialize job history: {}", e),
+    };
+
+    for entry in &entries {
+        if let Err(e) = queries::job_history::upsert_entry(pool, entry, worker_instance()).await {
+            warn!("Failed to import legacy job history, keeping the file: {}", e);
+            return;
         }
     }
-}
-
-impl Default for JobHistoryService {
-    fn default() -> Self {
-        Self::new()
+    if let Err(e) = std::fs::remove_file(path) {
+        warn!("Failed to remove legacy job history file: {}", e);
     }
+    info!("Imported {} job history entries from {}", entries.len(), LEGACY_HISTORY_FILE);
 }
 
 // Global instance for easy access
@@ -277,124 +308,122 @@ lazy_static::lazy_static! {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::atomic::{AtomicU32, Ordering};
-
-    static TEST_COUNTER: AtomicU32 = AtomicU32::new(0);
 
-    fn fresh_service() -> JobHistoryService {
-        TEST_COUNTER.fetch_add(1, Ordering::SeqCst);
-        JobHistoryService {
-            history: Arc::new(RwLock::new(VecDeque::with_capacity(MAX_HISTORY_SIZE))),
+    /// Entries recorded so far, as the writer would receive them
+    fn recorded(service: &JobHistoryService) -> Vec<JobHistoryEntry> {
+        let mut guard = service.receiver.lock();
+        let receiver = guard.as_mut().expect("writer not started in tests");
+        let mut entries = Vec::new();
+        while let Ok(entry) = receiver.try_recv() {
+            entries.push(entry);
         }
+        entries
     }
 
     #[test]
     fn test_record_completed_job() {
-        let service = fresh_service();
+        let service = JobHistoryService::new();
         let id = Uuid::new_v4();
         let user_id = Uuid::new_v4();
         let started_at = Utc::now() - chrono::Duration::seconds(5);
         
         service.record_completed(id, "geocode", user_id, started_at, Some("5 addresses".to_string()));
         
-        let history = service.get_recent(10);
-        assert_eq!(history.jobs.len(), 1);
-        assert_eq!(history.jobs[0].id, id);
-        assert_eq!(history.jobs[0].status, "completed");
-        assert_eq!(history.jobs[0].user_id, user_id);
+        let history = recorded(&service);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, id);
+        assert_eq!(history[0].status, "completed");
+        assert_eq!(history[0].user_id, user_id);
+        assert!(history[0].duration_ms >= 5000);
     }
 
     #[test]
     fn test_record_failed_job() {
-        let service = fresh_service();
+        let service = JobHistoryService::new();
         let id = Uuid::new_v4();
         let user_id = Uuid::new_v4();
         let started_at = Utc::now();
         
         service.record_failed(id, "geocode", user_id, started_at, "Connection timeout".to_string());
         
-        let history = service.get_recent(10);
-        assert_eq!(history.jobs.len(), 1);
-        assert_eq!(history.jobs[0].status, "failed");
-        assert_eq!(history.jobs[0].error, Some("Connection timeout".to_string()));
-    }
-
-    #[test]
-    fn test_history_limit() {
-        let service = fresh_service();
-        let user_id = Uuid::new_v4();
-        
-        for i in 0..150 {
-            let id = Uuid::new_v4();
-            service.record_completed(id, "test", user_id, Utc::now(), Some(format!("Job {}", i)));
-        }
-        
-        let history = service.get_recent(200);
-        assert_eq!(history.jobs.len(), MAX_HISTORY_SIZE);
-    }
-
-    #[test]
-    fn test_get_by_type() {
-        let service = fresh_service();
-        let user_id = Uuid::new_v4();
-        
-        service.record_completed(Uuid::new_v4(), "geocode", user_id, Utc::now(), None);
-        service.record_completed(Uuid::new_v4(), "route", user_id, Utc::now(), None);
-        service.record_completed(Uuid::new_v4(), "geocode", user_id, Utc::now(), None);
-        
-        let geocode_jobs = service.get_by_type("geocode", 10);
-        assert_eq!(geocode_jobs.jobs.len(), 2);
+        let history = recorded(&service);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, "failed");
+        assert_eq!(history[0].error, Some("Connection timeout".to_string()));
     }
 
     #[test]
     fn test_record_cancelled_appears_in_history() {
-        let service = fresh_service();
+        let service = JobHistoryService::new();
         let id = Uuid::new_v4();
         let user_id = Uuid::new_v4();
         let started_at = Utc::now() - chrono::Duration::seconds(3);
 
         service.record_cancelled(id, "import.customer", user_id, started_at);
 
-        let history = service.get_recent(10);
-        assert_eq!(history.jobs.len(), 1);
-        assert_eq!(history.jobs[0].id, id);
-        assert_eq!(history.jobs[0].status, "cancelled");
-        assert_eq!(history.jobs[0].user_id, user_id);
-        assert_eq!(history.jobs[0].job_type, "import.customer");
-        assert!(history.jobs[0].error.is_none());
+        let history = recorded(&service);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, id);
+        assert_eq!(history[0].status, "cancelled");
+        assert_eq!(history[0].user_id, user_id);
+        assert_eq!(history[0].job_type, "import.customer");
+        assert!(history[0].error.is_none());
     }
 
     #[test]
-    fn test_get_recent_for_user_isolates_users() {
-        let service = fresh_service();
-        let user_a = Uuid::new_v4();
-        let user_b = Uuid::new_v4();
-
-        service.record_completed(Uuid::new_v4(), "geocode", user_a, Utc::now(), None);
-        service.record_completed(Uuid::new_v4(), "route", user_b, Utc::now(), None);
-        service.record_completed(Uuid::new_v4(), "export", user_a, Utc::now(), None);
-        service.record_cancelled(Uuid::new_v4(), "import", user_b, Utc::now());
-
-        let history_a = service.get_recent_for_user(user_a, 50);
-        assert_eq!(history_a.jobs.len(), 2);
-        assert!(history_a.jobs.iter().all(|j| j.user_id == user_a));
-
-        let history_b = service.get_recent_for_user(user_b, 50);
-        assert_eq!(history_b.jobs.len(), 2);
-        assert!(history_b.jobs.iter().all(|j| j.user_id == user_b));
+    fn test_record_completed_with_report() {
+        let service = JobHistoryService::new();
+        let report = serde_json::json!({ "importedCount": 3 });
+
+        service.record_completed_with_report(
+            Uuid::new_v4(),
+            "import.device",
+            Uuid::new_v4(),
+            Utc::now(),
+            None,
+            Some(report.clone()),
+        );
+
+        let history = recorded(&service);
+        assert_eq!(history[0].report, Some(report));
     }
 
     #[test]
-    fn test_record_completed_with_user_id() {
-        let service = fresh_service();
+    fn test_lifecycle_records_keep_order() {
+        let service = JobHistoryService::new();
         let id = Uuid::new_v4();
         let user_id = Uuid::new_v4();
         let started_at = Utc::now();
 
-        service.record_completed(id, "route", user_id, started_at, Some("10 stops".to_string()));
+        service.record_running(id, "import.customer", user_id, started_at);
+        service.record_completed(id, "import.customer", user_id, started_at, None);
+
+        // The writer upserts by id, so the final record replaces `running`
+        let history = recorded(&service);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].status, "running");
+        assert_eq!(history[0].duration_ms, 0);
+        assert_eq!(history[1].id, id);
+        assert_eq!(history[1].status, "completed");
+    }
+
+    #[test]
+    fn test_legacy_file_entries_deserialize() {
+        let json = r#"[{
+            "id": "00000000-0000-0000-0000-000000000001",
+            "userId": "00000000-0000-0000-0000-000000000002",
+            "jobType": "export",
+            "status": "completed",
+            "startedAt": "2026-01-05T10:00:00Z",
+            "completedAt": "2026-01-05T10:00:02Z",
+            "durationMs": 2000,
+            "error": null,
+            "details": null
+        }]"#;
 
-        let history = service.get_recent(10);
-        assert_eq!(history.jobs[0].user_id, user_id);
-        assert_eq!(history.jobs[0].status, "completed");
+        let entries: Vec<JobHistoryEntry> = serde_json::from_str(json).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].job_type, "export");
+        assert!(entries[0].report.is_none());
     }
 }
@@ -0,0 +1,259 @@
+//! KML / KMZ parsing for map-pin customer import
+//!
+//! Mapy.cz and Google My Maps both export placemarks as KML (optionally
+//! zipped as KMZ). Only point placemarks are relevant for customers.
+
+use std::io::{Cursor, Read};
+
+use anyhow::{anyhow, Result};
+
+use crate::services::archive::read_limited;
+
+/// Largest unpacked KML document accepted from a KMZ archive
+const MAX_KML_BYTES: u64 = 64 * 1024 * 1024;
+
+/// A single placemark extracted from a KML document
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KmlPlacemark {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub address: Option<String>,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+}
+
+/// Decode raw upload bytes into KML text.
+///
+/// KMZ files are ZIP archives (detected by the `PK` magic) containing a
+/// `doc.kml` or any other `.kml` entry.
+pub fn decode_kml_payload(bytes: &[u8]) -> Result<String> {
+    if bytes.starts_with(b"PK") {
+        return extract_kml_from_kmz(bytes);
+    }
+    String::from_utf8(bytes.to_vec()).map_err(|_| anyhow!("import:kml_invalid_encoding"))
+}
+
+fn extract_kml_from_kmz(bytes: &[u8]) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+
+    let names: Vec<String> = archive.file_names().map(|n| n.to_string()).collect();
+    let entry = names
+        .iter()
+        .find(|n| n.eq_ignore_ascii_case("doc.kml"))
+        .or_else(|| names.iter().find(|n| n.to_lowercase().ends_with(".kml")))
+        .ok_or_else(|| anyhow!("import:kmz_missing_kml"))?
+        .clone();
+
+    let file = archive.by_name(&entry)?;
+    read_kml(file, MAX_KML_BYTES)
+}
+
+/// Read the unpacked KML text, refusing documents larger than `max_bytes`
+fn read_kml(reader: impl Read, max_bytes: u64) -> Result<String> {
+    let Some(content) = read_limited(reader, max_bytes)? else {
+        return Err(anyhow!("import:kmz_too_large"));
+    };
+    String::from_utf8(content).map_err(|_| anyhow!("import:kml_invalid_encoding"))
+}
+
+/// Parse all placemarks from a KML document
+pub fn parse_kml(content: &str) -> Result<Vec<KmlPlacemark>> {
+    let doc = roxmltree::Document::parse(content)
+        .map_err(|e| anyhow!("import:kml_parse_error: {}", e))?;
+
+    let placemarks = doc
+        .descendants()
+        .filter(|n| n.is_element() && n.tag_name().name() == "Placemark")
+        .map(|node| {
+            let coordinates = node
+                .descendants()
+                .find(|n| n.is_element() && n.tag_name().name() == "Point")
+                .and_then(|point| child_text(point, "coordinates"))
+                .and_then(|text| parse_coordinates(&text));
+
+            KmlPlacemark {
+                name: child_text(node, "name"),
+                description: child_text(node, "description").map(|d| strip_html(&d)).filter(|d| !d.is_empty()),
+                address: child_text(node, "address"),
+                lat: coordinates.map(|(lat, _)| lat),
+                lng: coordinates.map(|(_, lng)| lng),
+            }
+        })
+        .collect();
+
+    Ok(placemarks)
+}
+
+fn child_text(node: roxmltree::Node, name: &str) -> Option<String> {
+    node.children()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+        .map(|n| n.descendants().filter(|d| d.is_text()).filter_map(|d| d.text()).collect::<String>())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+/// Parse a KML `coordinates` value (`lng,lat[,alt]`) into `(lat, lng)`.
+///
+/// Only the first tuple is used; out-of-range values are rejected.
+pub fn parse_coordinates(text: &str) -> Option<(f64, f64)> {
+    let first = text.split_whitespace().next()?;
+    let mut parts = first.split(',');
+    let lng: f64 = parts.next()?.trim().parse().ok()?;
+    let lat: f64 = parts.next()?.trim().parse().ok()?;
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+        return None;
+    }
+    Some((lat, lng))
+}
+
+/// Remove HTML tags and collapse whitespace (My Maps descriptions are HTML)
+pub fn strip_html(input: &str) -> String {
+    let with_breaks = input
+        .replace("<br>", "\n")
+        .replace("<br/>", "\n")
+        .replace("<br />", "\n");
+
+    let mut out = String::with_capacity(with_breaks.len());
+    let mut in_tag = false;
+    for c in with_breaks.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+
+    out.replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Split a single-line address ("Street 1, 602 00 Brno") into
+/// `(street, postal_code, city)`.
+pub fn split_address(address: &str) -> (Option<String>, Option<String>, Option<String>) {
+    let segments: Vec<&str> = address.split(',').map(str::trim).filter(|s| !s.is_empty()).collect();
+    let Some((last, rest)) = segments.split_last() else {
+        return (None, None, None);
+    };
+
+    let digits: String = last.chars().take_while(|c| c.is_ascii_digit() || *c == ' ').collect();
+    let postal: String = digits.chars().filter(|c| c.is_ascii_digit()).collect();
+    let (postal_code, city) = if postal.len() == 5 {
+        let city = last[digits.len()..].trim();
+        (Some(postal), (!city.is_empty()).then(|| city.to_string()))
+    } else {
+        (None, Some(last.to_string()))
+    };
+
+    let street = (!rest.is_empty()).then(|| rest.join(", "));
+    (street, postal_code, city)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const SAMPLE_KML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2">
+  <Document>
+    <name>Zákazníci</name>
+    <Folder>
+      <Placemark>
+        <name>Jan Novák</name>
+        <description><![CDATA[Kotel Viadrus<br>tel. 777 123 456]]></description>
+        <address>Masarykova 12, 602 00 Brno</address>
+        <Point><coordinates>16.6068,49.1951,0</coordinates></Point>
+      </Placemark>
+      <Placemark>
+        <name>Trasa</name>
+        <LineString><coordinates>16.1,49.1 16.2,49.2</coordinates></LineString>
+      </Placemark>
+    </Folder>
+  </Document>
+</kml>"#;
+
+    #[test]
+    fn test_parse_kml_extracts_point_placemarks() {
+        let placemarks = parse_kml(SAMPLE_KML).unwrap();
+        assert_eq!(placemarks.len(), 2);
+        let first = &placemarks[0];
+        assert_eq!(first.name.as_deref(), Some("Jan Novák"));
+        assert_eq!(first.description.as_deref(), Some("Kotel Viadrus\ntel. 777 123 456"));
+        assert_eq!(first.address.as_deref(), Some("Masarykova 12, 602 00 Brno"));
+        assert_eq!(first.lat, Some(49.1951));
+        assert_eq!(first.lng, Some(16.6068));
+    }
+
+    #[test]
+    fn test_parse_kml_line_string_has_no_coordinates() {
+        let placemarks = parse_kml(SAMPLE_KML).unwrap();
+        assert_eq!(placemarks[1].name.as_deref(), Some("Trasa"));
+        assert!(placemarks[1].lat.is_none());
+    }
+
+    #[test]
+    fn test_parse_kml_rejects_invalid_xml() {
+        assert!(parse_kml("<kml><Placemark>").is_err());
+    }
+
+    #[test]
+    fn test_parse_coordinates() {
+        assert_eq!(parse_coordinates(" 14.42,50.08,0 "), Some((50.08, 14.42)));
+        assert_eq!(parse_coordinates("14.42,50.08"), Some((50.08, 14.42)));
+        assert_eq!(parse_coordinates("14.42"), None);
+        assert_eq!(parse_coordinates("14.42,95.0"), None);
+    }
+
+    #[test]
+    fn test_strip_html() {
+        assert_eq!(strip_html("<b>Pozor</b> pes<br/>  zvonit&nbsp;2x"), "Pozor pes\nzvonit 2x");
+    }
+
+    #[test]
+    fn test_split_address_with_postal_code() {
+        let (street, postal, city) = split_address("Masarykova 12, 602 00 Brno");
+        assert_eq!(street.as_deref(), Some("Masarykova 12"));
+        assert_eq!(postal.as_deref(), Some("60200"));
+        assert_eq!(city.as_deref(), Some("Brno"));
+    }
+
+    #[test]
+    fn test_split_address_without_postal_code() {
+        let (street, postal, city) = split_address("Hlavní 5, Kuřim");
+        assert_eq!(street.as_deref(), Some("Hlavní 5"));
+        assert!(postal.is_none());
+        assert_eq!(city.as_deref(), Some("Kuřim"));
+    }
+
+    #[test]
+    fn test_read_kml_rejects_oversized_documents() {
+        assert_eq!(read_kml(&b"<kml/>"[..], 6).unwrap(), "<kml/>");
+        let err = read_kml(&b"<kml></kml>"[..], 6).unwrap_err();
+        assert_eq!(err.to_string(), "import:kmz_too_large");
+    }
+
+    #[test]
+    fn test_decode_kml_payload_plain_text() {
+        let content = decode_kml_payload(SAMPLE_KML.as_bytes()).unwrap();
+        assert!(content.contains("Placemark"));
+    }
+
+    #[test]
+    fn test_decode_kml_payload_kmz() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("doc.kml", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(SAMPLE_KML.as_bytes()).unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let content = decode_kml_payload(&bytes).unwrap();
+        assert_eq!(parse_kml(&content).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,89 @@
+//! Lead conversion funnel
+//!
+//! Turns per-source stage counts into funnel rows with a conversion rate
+//! and a total over all sources.
+
+use chrono::NaiveDate;
+
+use crate::types::report::{LeadFunnelCounts, LeadFunnelResponse, LeadFunnelRow};
+
+fn funnel_row(counts: &LeadFunnelCounts) -> LeadFunnelRow {
+    let conversion_rate = if counts.leads > 0 {
+        counts.won as f64 / counts.leads as f64
+    } else {
+        0.0
+    };
+    LeadFunnelRow {
+        source: Some(counts.source.clone()).filter(|s| !s.is_empty()),
+        leads: counts.leads,
+        contacted: counts.contacted,
+        quoted: counts.quoted,
+        won: counts.won,
+        lost: counts.lost,
+        conversion_rate,
+    }
+}
+
+/// Funnel rows per source, most leads first and the unknown source last
+pub fn build_funnel(from_date: NaiveDate, to_date: NaiveDate, counts: &[LeadFunnelCounts]) -> LeadFunnelResponse {
+    let mut total = LeadFunnelCounts::default();
+    for count in counts {
+        total.leads += count.leads;
+        total.contacted += count.contacted;
+        total.quoted += count.quoted;
+        total.won += count.won;
+        total.lost += count.lost;
+    }
+
+    let mut sources: Vec<LeadFunnelRow> = counts.iter().map(funnel_row).collect();
+    sources.sort_by(|a, b| {
+        a.source
+            .is_none()
+            .cmp(&b.source.is_none())
+            .then(b.leads.cmp(&a.leads))
+            .then(a.source.cmp(&b.source))
+    });
+
+    LeadFunnelResponse { from_date, to_date, total: funnel_row(&total), sources }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(source: &str, leads: i64, won: i64) -> LeadFunnelCounts {
+        LeadFunnelCounts {
+            source: source.to_string(),
+            leads,
+            contacted: leads - 1,
+            quoted: won + 1,
+            won,
+            lost: 1,
+        }
+    }
+
+    #[test]
+    fn test_build_funnel() {
+        let from = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 12, 31).unwrap();
+        let funnel = build_funnel(from, to, &[counts("", 10, 1), counts("phone", 4, 2), counts("web_form", 8, 2)]);
+
+        let sources: Vec<Option<&str>> = funnel.sources.iter().map(|r| r.source.as_deref()).collect();
+        assert_eq!(sources, vec![Some("web_form"), Some("phone"), None]);
+        assert_eq!(funnel.sources[1].conversion_rate, 0.5);
+
+        assert_eq!(funnel.total.source, None);
+        assert_eq!(funnel.total.leads, 22);
+        assert_eq!(funnel.total.won, 5);
+        assert_eq!(funnel.total.lost, 3);
+        assert!((funnel.total.conversion_rate - 5.0 / 22.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_build_funnel_without_leads() {
+        let day = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let funnel = build_funnel(day, day, &[]);
+        assert!(funnel.sources.is_empty());
+        assert_eq!(funnel.total.conversion_rate, 0.0);
+    }
+}
@@ -0,0 +1,160 @@
+//! Web lead intake helpers
+//!
+//! Captcha verification against the provider configured for the account and
+//! the text logged on the customer for a website submission, in the account
+//! owner's language.
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::services::http::{self, HttpService};
+use crate::types::lead::{captcha_verify_url, SubmitWebLeadRequest};
+
+#[derive(Debug, Deserialize)]
+struct CaptchaVerifyResponse {
+    success: bool,
+}
+
+/// Ask the captcha provider whether `token` was solved. All supported
+/// providers take the same form fields and answer with `success`.
+pub async fn verify_captcha(provider: &str, secret: &str, token: &str, remote_ip: Option<&str>) -> Result<bool> {
+    let url = captcha_verify_url(provider).ok_or_else(|| anyhow!("Unknown captcha provider {}", provider))?;
+
+    let mut form = vec![("secret", secret), ("response", token)];
+    if let Some(ip) = remote_ip {
+        form.push(("remoteip", ip));
+    }
+
+    let response = http::client(HttpService::Captcha).post(url).form(&form).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Captcha provider returned {}", response.status()));
+    }
+    let verdict: CaptchaVerifyResponse = response.json().await?;
+    Ok(verdict.success)
+}
+
+/// E-mail and phone of a submission as one line
+pub fn contact_line(email: Option<&str>, phone: Option<&str>) -> String {
+    [email, phone]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+struct LeadLabels {
+    subject: &'static str,
+    notification: &'static str,
+    preferred_date: &'static str,
+    address: &'static str,
+    contact: &'static str,
+}
+
+fn lead_labels(locale: &str) -> LeadLabels {
+    match locale {
+        "cs" => LeadLabels {
+            subject: "Webový formulář",
+            notification: "Nová poptávka z webu",
+            preferred_date: "Požadovaný termín",
+            address: "Adresa",
+            contact: "Kontakt",
+        },
+        "sk" => LeadLabels {
+            subject: "Webový formulár",
+            notification: "Nový dopyt z webu",
+            preferred_date: "Požadovaný termín",
+            address: "Adresa",
+            contact: "Kontakt",
+        },
+        _ => LeadLabels {
+            subject: "Web form",
+            notification: "New web enquiry",
+            preferred_date: "Preferred date",
+            address: "Address",
+            contact: "Contact",
+        },
+    }
+}
+
+/// Subject of the communication logged for a submission
+pub fn lead_subject(locale: &str) -> &'static str {
+    lead_labels(locale).subject
+}
+
+/// Title of the dispatcher notification for a submission
+pub fn notification_title(name: &str, locale: &str) -> String {
+    format!("{} – {}", lead_labels(locale).notification, name)
+}
+
+/// Communication text of a submission: the message, the preferred date and
+/// the address the visitor typed (it may differ from the customer record)
+pub fn lead_note(req: &SubmitWebLeadRequest, locale: &str) -> String {
+    let labels = lead_labels(locale);
+    let mut lines = Vec::new();
+    if let Some(message) = req.message.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        lines.push(message.to_string());
+    }
+    if let Some(date) = req.preferred_date {
+        lines.push(format!("{}: {}", labels.preferred_date, format_date(date, locale)));
+    }
+    let address = [req.street.as_deref(), req.postal_code.as_deref(), req.city.as_deref()]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !address.is_empty() {
+        lines.push(format!("{}: {}", labels.address, address));
+    }
+    let contact = contact_line(req.email.as_deref(), req.phone.as_deref());
+    if !contact.is_empty() {
+        lines.push(format!("{}: {}", labels.contact, contact));
+    }
+    lines.join("\n")
+}
+
+fn format_date(date: NaiveDate, locale: &str) -> String {
+    match locale {
+        "cs" | "sk" => date.format("%d.%m.%Y").to_string(),
+        _ => date.format("%Y-%m-%d").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contact_line_skips_blanks() {
+        assert_eq!(contact_line(Some("a@b.cz"), Some(" 602111222 ")), "a@b.cz, 602111222");
+        assert_eq!(contact_line(Some(""), Some("602111222")), "602111222");
+        assert_eq!(contact_line(None, None), "");
+    }
+
+    #[test]
+    fn test_lead_note() {
+        let req = SubmitWebLeadRequest {
+            key: "k".to_string(),
+            name: "Jan".to_string(),
+            email: Some("jan@example.cz".to_string()),
+            city: Some("Brno".to_string()),
+            message: Some(" Revize kotle ".to_string()),
+            preferred_date: NaiveDate::from_ymd_opt(2026, 5, 4),
+            ..Default::default()
+        };
+        assert_eq!(
+            lead_note(&req, "en"),
+            "Revize kotle\nPreferred date: 2026-05-04\nAddress: Brno\nContact: jan@example.cz"
+        );
+        assert_eq!(
+            lead_note(&req, "cs"),
+            "Revize kotle\nPožadovaný termín: 04.05.2026\nAdresa: Brno\nKontakt: jan@example.cz"
+        );
+        assert_eq!(lead_subject("sk"), "Webový formulár");
+        assert_eq!(notification_title("Jan", "de"), "New web enquiry – Jan");
+    }
+}
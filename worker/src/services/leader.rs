@@ -0,0 +1,133 @@
+//! Leader election for singleton background services
+//!
+//! Schedulers that act on shared data (backups, campaigns, escalations,
+//! retention sweeps, digests, ...) must run on one worker only when several
+//! workers share the database. Each such service is started through
+//! [`spawn_singleton`]: the worker that holds a Postgres session-level
+//! advisory lock for the service runs it, the others retry every
+//! [`RETRY_INTERVAL`]. The lock lives on a dedicated connection, so it is
+//! released as soon as the holder crashes, shuts down or loses the database,
+//! and a standby takes over on its next attempt.
+//!
+//! A service that loses its lock is dropped at its next await point. The
+//! scheduled services work in idempotent ticks, so a tick cut short is
+//! simply done again by the new leader.
+//!
+//! One-shot passes (e.g. the startup reconciliation) go through
+//! [`run_once`] instead: workers starting together contend once, and only
+//! the winner runs the pass.
+
+use std::future::Future;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use sqlx::{Connection, PgConnection, PgPool};
+use tracing::{debug, info, warn};
+
+/// First key of every lock taken here, keeps them apart from other
+/// advisory lock users of the database
+const LOCK_NAMESPACE: i32 = 0x535a_4b41;
+/// How often a standby tries to take over
+pub const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+/// How often the leader checks its lock connection is still alive
+const LEASE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Second lock key of a service, stable across builds and workers
+fn lock_key(name: &str) -> i32 {
+    let digest = Sha256::digest(name.as_bytes());
+    i32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]])
+}
+
+/// Open a dedicated connection and try to lock `name` on it
+async fn try_acquire(pool: &PgPool, name: &str) -> anyhow::Result<Option<PgConnection>> {
+    let mut conn = PgConnection::connect_with(&pool.connect_options()).await?;
+    let acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1, $2)")
+        .bind(LOCK_NAMESPACE)
+        .bind(lock_key(name))
+        .fetch_one(&mut conn)
+        .await?;
+    if acquired {
+        Ok(Some(conn))
+    } else {
+        let _ = conn.close().await;
+        Ok(None)
+    }
+}
+
+/// Run the service built by `make` on exactly one worker at a time.
+/// `make` is called again whenever this worker (re)gains leadership.
+pub fn spawn_singleton<F, Fut>(pool: PgPool, name: &'static str, make: F) -> tokio::task::JoinHandle<()>
+where
+    F: Fn(PgPool) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            match try_acquire(&pool, name).await {
+                Ok(Some(mut lock_conn)) => {
+                    info!("Leading singleton service {}", name);
+                    let service = make(pool.clone());
+                    tokio::pin!(service);
+                    let mut lease_check = tokio::time::interval(LEASE_CHECK_INTERVAL);
+                    lease_check.tick().await;
+
+                    loop {
+                        tokio::select! {
+                            _ = &mut service => {
+                                // The service has nothing to do (e.g. not configured);
+                                // dropping the connection hands the lock over
+                                info!("Singleton service {} finished", name);
+                                return;
+                            }
+                            _ = lease_check.tick() => {
+                                if let Err(e) = lock_conn.ping().await {
+                                    warn!("Lost leadership of singleton service {}: {}", name, e);
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+                Ok(None) => debug!("Singleton service {} runs on another worker", name),
+                Err(e) => warn!("Failed to contend for singleton service {}: {}", name, e),
+            }
+            tokio::time::sleep(RETRY_INTERVAL).await;
+        }
+    })
+}
+
+/// Run `task` unless another worker is already running it under `name`.
+/// Returns whether this worker ran it; there is no standby retry.
+pub async fn run_once<Fut>(pool: &PgPool, name: &'static str, task: Fut) -> bool
+where
+    Fut: Future<Output = ()>,
+{
+    match try_acquire(pool, name).await {
+        Ok(Some(lock_conn)) => {
+            task.await;
+            // Closing the connection releases the lock
+            let _ = lock_conn.close().await;
+            true
+        }
+        Ok(None) => {
+            debug!("One-shot service {} runs on another worker", name);
+            false
+        }
+        Err(e) => {
+            warn!("Failed to contend for one-shot service {}: {}", name, e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_keys_are_stable_and_distinct() {
+        assert_eq!(lock_key("retention_sweeper"), lock_key("retention_sweeper"));
+        assert_ne!(lock_key("retention_sweeper"), lock_key("backup_scheduler"));
+        assert_eq!(lock_key(""), i32::from_be_bytes([0xe3, 0xb0, 0xc4, 0x42]));
+    }
+}
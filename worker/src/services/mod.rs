@@ -1,24 +1,84 @@
 //! Business logic services
 
+pub mod account_transfer;
+pub mod accounting_export;
+pub mod acquisition_report;
+pub mod archive;
+pub mod automation;
+pub mod backup;
+pub mod calendar_feed;
+pub mod break_location;
+pub mod campaign;
 pub mod cancellation;
+pub mod capacity_forecast;
+pub mod circuit_breaker;
+pub mod colocation;
+pub mod communication_log;
+pub mod crash_report;
+pub mod crm_sync;
+pub mod csv_encoding;
+pub mod customer_risk;
+pub mod debug_recorder;
+pub mod demo_mode;
+pub mod dispatch_board;
+pub mod device_code;
 pub mod domain_verification;
 pub mod email_data;
 pub mod template_renderer;
 pub mod email_processor;
 pub mod email_sender;
 pub mod email_templates;
+pub mod escalation;
 pub mod export_processor;
+pub mod export_storage;
 pub mod geo;
+pub mod geocode_freshness;
+pub mod geocode_priority;
 pub mod geocoding;
+pub mod health;
+pub mod hooks;
+pub mod http;
+pub mod ical;
+pub mod import_formats;
 pub mod import_processor;
+pub mod import_upload;
 pub mod insertion;
+pub mod interval_adjustment;
+pub mod inventory;
+pub mod job_backup;
 pub mod job_history;
+pub mod kml;
+pub mod lead_funnel;
+pub mod leader;
+pub mod lead_intake;
 pub mod nominatim;
+pub mod notification_dispatch;
+pub mod pdf;
+pub mod presence;
+pub mod quota;
+pub mod quote;
 pub mod rate_limiter;
+pub mod retention;
+pub mod revision_report;
+pub mod route_analysis;
+pub mod route_template;
 pub mod routing;
 pub mod scoring;
 pub mod sequential_schedule;
 pub mod slot_suggester;
 pub mod sms_processor;
+pub mod soft_delete;
+pub mod static_map;
+pub mod subcontractor;
+pub mod subscription;
+pub mod travel_correction;
+pub mod telemetry;
 pub mod valhalla_processor;
+pub mod vat_summary;
+pub mod visit_duration;
 pub mod vrp;
+pub mod weather;
+pub mod weather_report;
+pub mod webhook_delivery;
+pub mod webhook_events;
+pub mod xlsx;
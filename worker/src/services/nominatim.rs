@@ -3,6 +3,8 @@
 
 use anyhow::{Context, Result};
 use serde::Deserialize;
+use crate::services::geocoding::GeocodeScope;
+use crate::services::http::{self, HttpService};
 use crate::types::Coordinates;
 
 /// Nominatim API response
@@ -38,35 +40,42 @@ pub struct NominatimClient {
 impl NominatimClient {
     /// Create a new client
     pub fn new(base_url: &str) -> Self {
-        let client = reqwest::Client::builder()
-            .user_agent("Ariadline/1.0 (https://ariadline.cz)")
-            .build()
-            .expect("Failed to create HTTP client");
-
         Self {
             base_url: base_url.to_string(),
-            client,
+            client: http::client(HttpService::Nominatim),
         }
     }
 
-    /// Geocode an address to coordinates
+    /// Geocode an address in the Czech Republic to coordinates
     pub async fn geocode(&self, address: &str, city: &str, postal_code: &str) -> Result<Option<Coordinates>> {
-        let pc = postal_code.trim();
-        let full_address = if pc.is_empty() {
-            format!("{}, {}, Czech Republic", address, city)
-        } else {
-            format!("{}, {}, {}, Czech Republic", address, pc, city)
-        };
+        self.geocode_in(address, city, postal_code, &GeocodeScope::default()).await
+    }
 
-        let url = format!(
-            "{}/search?q={}&format=json&countrycodes=cz&limit=1",
+    /// Geocode an address restricted to the scope's country. The scope's
+    /// bounds are passed as a (non-binding) viewbox to prefer results there.
+    pub async fn geocode_in(
+        &self,
+        address: &str,
+        city: &str,
+        postal_code: &str,
+        scope: &GeocodeScope,
+    ) -> Result<Option<Coordinates>> {
+        let mut parts = vec![address.trim(), postal_code.trim(), city.trim()];
+        parts.extend(scope.country_name);
+        parts.retain(|p| !p.is_empty());
+        let full_address = parts.join(", ");
+
+        let mut url = format!(
+            "{}/search?q={}&format=json&countrycodes={}&limit=1",
             self.base_url,
-            urlencoding::encode(&full_address)
+            urlencoding::encode(&full_address),
+            urlencoding::encode(&scope.country_code)
         );
+        if let Some(b) = scope.bounds {
+            url.push_str(&format!("&viewbox={},{},{},{}", b.min_lng, b.max_lat, b.max_lng, b.min_lat));
+        }
 
-        let response = self.client
-            .get(&url)
-            .send()
+        let response = http::send_idempotent(HttpService::Nominatim, || self.client.get(&url))
             .await
             .context("Failed to send geocoding request")?;
 
@@ -98,9 +107,7 @@ impl NominatimClient {
             lng
         );
 
-        let response = self.client
-            .get(&url)
-            .send()
+        let response = http::send_idempotent(HttpService::Nominatim, || self.client.get(&url))
             .await
             .context("Failed to send reverse geocoding request")?;
 
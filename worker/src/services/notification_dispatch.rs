@@ -0,0 +1,253 @@
+//! Notification dispatch
+//!
+//! Every in-app notification goes through [`notify`], which applies the
+//! account's subscription preference for the notification's category:
+//! disabled categories are dropped, hourly and daily ones are held back
+//! and delivered as one summary notification per category by
+//! [`run_digest_sender`].
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveTime, Timelike, Utc};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::job_history::JobHistoryEntry;
+use crate::types::notification::{
+    NotificationDigestItem, NotificationPreference, NOTIFICATION_CATEGORIES, NOTIFICATION_CATEGORY_IMPORT,
+    NOTIFICATION_CATEGORY_ROUTE, NOTIFICATION_CATEGORY_SYSTEM, NOTIFICATION_DELIVERY_DAILY,
+    NOTIFICATION_DELIVERY_HOURLY, NOTIFICATION_KIND_DIGEST, NOTIFICATION_KIND_JOB,
+};
+
+/// How often due digests are looked for
+const DIGEST_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Daily summaries are delivered at this time (UTC), early in the Czech morning
+const DAILY_DIGEST_HOUR_UTC: u32 = 5;
+/// Titles listed in a summary before "and N more"
+const DIGEST_MAX_LINES: usize = 10;
+
+/// When a held-back notification is due: the next full hour, or the next
+/// daily delivery time. None for immediate delivery.
+pub fn next_delivery(delivery: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let hour_start = now
+        .with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(now);
+    match delivery {
+        NOTIFICATION_DELIVERY_HOURLY => Some(hour_start + ChronoDuration::hours(1)),
+        NOTIFICATION_DELIVERY_DAILY => {
+            let time = NaiveTime::from_hms_opt(DAILY_DIGEST_HOUR_UTC, 0, 0)?;
+            let today = now.date_naive().and_time(time).and_utc();
+            Some(if today > now { today } else { today + ChronoDuration::days(1) })
+        }
+        _ => None,
+    }
+}
+
+/// Title and body of the summary for one category
+pub fn digest_text(category: &str, titles: &[&str]) -> (String, String) {
+    let title = match titles.len() {
+        1 => format!("1 {} notification", category),
+        n => format!("{} {} notifications", n, category),
+    };
+    let mut lines: Vec<String> = titles.iter().take(DIGEST_MAX_LINES).map(|t| format!("• {}", t)).collect();
+    if titles.len() > DIGEST_MAX_LINES {
+        lines.push(format!("and {} more", titles.len() - DIGEST_MAX_LINES));
+    }
+    (title, lines.join("\n"))
+}
+
+/// Category of a job type; None for jobs too small to notify about
+/// (single-address geocoding started from a form)
+pub fn job_category(job_type: &str) -> Option<&'static str> {
+    match job_type {
+        "export" => Some(NOTIFICATION_CATEGORY_IMPORT),
+        t if t.starts_with("import") => Some(NOTIFICATION_CATEGORY_IMPORT),
+        t if t.starts_with("route") => Some(NOTIFICATION_CATEGORY_ROUTE),
+        "geocode" => Some(NOTIFICATION_CATEGORY_SYSTEM),
+        _ => None,
+    }
+}
+
+fn job_label(job_type: &str) -> String {
+    match job_type {
+        "export" => "Export".to_string(),
+        "route" => "Route planning".to_string(),
+        "geocode" => "Geocoding".to_string(),
+        t => match t.strip_prefix("import.") {
+            Some(what) => {
+                let mut chars = what.chars();
+                let first = chars.next().map(|c| c.to_uppercase().collect::<String>()).unwrap_or_default();
+                format!("{}{} import", first, chars.as_str())
+            }
+            None => t.to_string(),
+        },
+    }
+}
+
+/// Preference of an account for a category (default when unset)
+async fn preference_for(pool: &PgPool, user_id: Uuid, category: &str) -> Result<NotificationPreference> {
+    Ok(queries::notification::get_preference(pool, user_id, category)
+        .await?
+        .unwrap_or_else(|| NotificationPreference::default_for(category)))
+}
+
+/// Effective preferences, one per category
+pub async fn preferences(pool: &PgPool, user_id: Uuid) -> Result<Vec<NotificationPreference>> {
+    let stored = queries::notification::list_preferences(pool, user_id).await?;
+    Ok(NOTIFICATION_CATEGORIES
+        .iter()
+        .map(|category| {
+            stored
+                .iter()
+                .find(|p| p.category == *category)
+                .cloned()
+                .unwrap_or_else(|| NotificationPreference::default_for(category))
+        })
+        .collect())
+}
+
+/// Deliver a notification according to the account's preference.
+///
+/// Returns the notification ID when it was created right away; None when
+/// the category is switched off or held back for a digest.
+pub async fn notify(
+    pool: &PgPool,
+    user_id: Uuid,
+    category: &str,
+    kind: &str,
+    title: &str,
+    body: Option<&str>,
+    entity: Option<(&str, Uuid)>,
+) -> Result<Option<Uuid>> {
+    let preference = preference_for(pool, user_id, category).await?;
+    if !preference.enabled {
+        return Ok(None);
+    }
+    match next_delivery(&preference.delivery, Utc::now()) {
+        None => Ok(Some(
+            queries::notification::create_notification(pool, user_id, kind, title, body, entity).await?,
+        )),
+        Some(deliver_after) => {
+            queries::notification::enqueue_digest_item(pool, user_id, category, kind, title, body, entity, deliver_after)
+                .await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Notify the owner about a finished job (completed or failed; cancelled
+/// jobs were stopped by the user and need no notice)
+pub async fn notify_job(pool: &PgPool, entry: &JobHistoryEntry) -> Result<()> {
+    let Some(category) = job_category(&entry.job_type) else {
+        return Ok(());
+    };
+    if entry.user_id.is_nil() {
+        return Ok(());
+    }
+    let label = job_label(&entry.job_type);
+    let (title, body) = match entry.status.as_str() {
+        "completed" => (format!("{} finished", label), None),
+        "failed" => (format!("{} failed", label), entry.error.as_deref()),
+        _ => return Ok(()),
+    };
+    notify(pool, entry.user_id, category, NOTIFICATION_KIND_JOB, &title, body, Some(("job", entry.id))).await?;
+    Ok(())
+}
+
+/// Turn due digest items into one summary notification per account and category
+pub async fn send_due_digests(pool: &PgPool) -> Result<usize> {
+    let items = queries::notification::list_due_digest_items(pool).await?;
+    let mut groups: BTreeMap<(Uuid, String), Vec<&NotificationDigestItem>> = BTreeMap::new();
+    for item in &items {
+        groups.entry((item.user_id, item.category.clone())).or_default().push(item);
+    }
+
+    let mut sent = 0;
+    for ((user_id, category), group) in groups {
+        let titles: Vec<&str> = group.iter().map(|item| item.title.as_str()).collect();
+        let (title, body) = digest_text(&category, &titles);
+        queries::notification::create_notification(pool, user_id, NOTIFICATION_KIND_DIGEST, &title, Some(&body), None)
+            .await?;
+        // Deleted only after the summary exists: a failure repeats a summary
+        // rather than losing the notifications
+        let ids: Vec<Uuid> = group.iter().map(|item| item.id).collect();
+        queries::notification::delete_digest_items(pool, &ids).await?;
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+/// Periodic digest delivery
+pub async fn run_digest_sender(pool: PgPool) {
+    let mut interval = tokio::time::interval(DIGEST_INTERVAL);
+    loop {
+        interval.tick().await;
+        match send_due_digests(&pool).await {
+            Ok(0) => {}
+            Ok(sent) => info!("Delivered {} notification digests", sent),
+            Err(e) => error!("Failed to deliver notification digests: {}", e),
+        }
+    }
+}
+
+/// Log-only wrapper for callers that must not fail on a notification
+pub async fn notify_or_warn(
+    pool: &PgPool,
+    user_id: Uuid,
+    category: &str,
+    kind: &str,
+    title: &str,
+    body: Option<&str>,
+    entity: Option<(&str, Uuid)>,
+) {
+    if let Err(e) = notify(pool, user_id, category, kind, title, body, entity).await {
+        warn!("Failed to create {} notification: {}", kind, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_next_delivery() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 14, 25, 7).unwrap();
+        assert_eq!(next_delivery("immediate", now), None);
+        assert_eq!(next_delivery("hourly", now), Some(Utc.with_ymd_and_hms(2026, 3, 10, 15, 0, 0).unwrap()));
+        assert_eq!(next_delivery("daily", now), Some(Utc.with_ymd_and_hms(2026, 3, 11, 5, 0, 0).unwrap()));
+
+        let early = Utc.with_ymd_and_hms(2026, 3, 10, 3, 0, 0).unwrap();
+        assert_eq!(next_delivery("daily", early), Some(Utc.with_ymd_and_hms(2026, 3, 10, 5, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_digest_text_truncates() {
+        let titles: Vec<String> = (1..=12).map(|i| format!("Customer import {} finished", i)).collect();
+        let refs: Vec<&str> = titles.iter().map(String::as_str).collect();
+        let (title, body) = digest_text("import", &refs);
+        assert_eq!(title, "12 import notifications");
+        assert_eq!(body.lines().count(), DIGEST_MAX_LINES + 1);
+        assert!(body.ends_with("and 2 more"));
+
+        let (title, _) = digest_text("route", &["Route planning finished"]);
+        assert_eq!(title, "1 route notification");
+    }
+
+    #[test]
+    fn test_job_category_and_label() {
+        assert_eq!(job_category("import.customer"), Some(NOTIFICATION_CATEGORY_IMPORT));
+        assert_eq!(job_category("export"), Some(NOTIFICATION_CATEGORY_IMPORT));
+        assert_eq!(job_category("route"), Some(NOTIFICATION_CATEGORY_ROUTE));
+        assert_eq!(job_category("geocode"), Some(NOTIFICATION_CATEGORY_SYSTEM));
+        assert_eq!(job_category("geocode.address"), None);
+        assert_eq!(job_label("import.customer"), "Customer import");
+        assert_eq!(job_label("route"), "Route planning");
+    }
+}
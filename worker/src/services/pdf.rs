@@ -0,0 +1,394 @@
+//! Minimal PDF writer
+//!
+//! A4 documents (quotes, reports) of text, rules and QR codes set top to
+//! bottom with the standard Helvetica fonts, so no font files or PDF
+//! dependency are needed.
+//! Text is WinAnsi encoded; letters outside it (most Czech háčky) fall back
+//! to their base letter.
+
+use std::io::Write;
+
+use crate::services::device_code::{QrMatrix, QR_BORDER};
+
+pub const A4_WIDTH: f32 = 595.0;
+pub const A4_HEIGHT: f32 = 842.0;
+pub const MARGIN: f32 = 50.0;
+/// Usable width between the margins
+pub const CONTENT_WIDTH: f32 = A4_WIDTH - 2.0 * MARGIN;
+
+const LINE_SPACING: f32 = 1.35;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// One cell of a table row: left edge (or right edge for `Align::Right`)
+/// measured from the left margin
+#[derive(Debug, Clone, Copy)]
+pub struct Cell<'a> {
+    pub x: f32,
+    pub text: &'a str,
+    pub align: Align,
+}
+
+impl<'a> Cell<'a> {
+    pub fn left(x: f32, text: &'a str) -> Self {
+        Self { x, text, align: Align::Left }
+    }
+
+    pub fn right(x: f32, text: &'a str) -> Self {
+        Self { x, text, align: Align::Right }
+    }
+}
+
+/// A document being written top to bottom; pages break automatically
+pub struct PdfDocument {
+    pages: Vec<Vec<u8>>,
+    current: Vec<u8>,
+    /// Baseline of the next line, from the page bottom
+    y: f32,
+}
+
+impl Default for PdfDocument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfDocument {
+    pub fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            current: Vec::new(),
+            y: A4_HEIGHT - MARGIN,
+        }
+    }
+
+    /// Write a paragraph, wrapped to the content width
+    pub fn text(&mut self, text: &str, size: f32, font: Font) {
+        for paragraph in text.lines() {
+            for line in wrap(paragraph, size, CONTENT_WIDTH) {
+                self.advance(size);
+                self.put(MARGIN, self.y, size, font, &line);
+            }
+        }
+    }
+
+    /// Write one row of cells on the same baseline
+    pub fn row(&mut self, cells: &[Cell<'_>], size: f32, font: Font) {
+        self.advance(size);
+        for cell in cells {
+            let x = match cell.align {
+                Align::Left => MARGIN + cell.x,
+                Align::Right => MARGIN + cell.x - text_width(cell.text, size),
+            };
+            self.put(x, self.y, size, font, cell.text);
+        }
+    }
+
+    /// Horizontal rule across the content width
+    pub fn rule(&mut self) {
+        self.space(4.0);
+        let _ = writeln!(
+            self.current,
+            "0.5 w {:.2} {:.2} m {:.2} {:.2} l S",
+            MARGIN,
+            self.y,
+            A4_WIDTH - MARGIN,
+            self.y
+        );
+        self.space(2.0);
+    }
+
+    /// QR code `side` points wide (quiet zone included) with its left edge
+    /// `x` from the left margin; moves to a new page if it doesn't fit
+    pub fn qr(&mut self, matrix: &QrMatrix, x: f32, side: f32) {
+        if self.y - side < MARGIN {
+            self.page_break();
+        }
+        let module = side / (matrix.size + 2 * QR_BORDER) as f32;
+        let left = MARGIN + x + QR_BORDER as f32 * module;
+        let top = self.y - QR_BORDER as f32 * module;
+        for row in 0..matrix.size {
+            for col in 0..matrix.size {
+                if matrix.is_dark(col, row) {
+                    let _ = writeln!(
+                        self.current,
+                        "{:.2} {:.2} {:.2} {:.2} re",
+                        left + col as f32 * module,
+                        top - (row + 1) as f32 * module,
+                        module,
+                        module
+                    );
+                }
+            }
+        }
+        let _ = writeln!(self.current, "f");
+        self.y -= side;
+    }
+
+    /// Vertical gap
+    pub fn space(&mut self, points: f32) {
+        self.y -= points;
+        if self.y < MARGIN {
+            self.page_break();
+        }
+    }
+
+    pub fn page_break(&mut self) {
+        self.pages.push(std::mem::take(&mut self.current));
+        self.y = A4_HEIGHT - MARGIN;
+    }
+
+    /// Serialize the document
+    pub fn finish(mut self) -> Vec<u8> {
+        if !self.current.is_empty() || self.pages.is_empty() {
+            self.pages.push(std::mem::take(&mut self.current));
+        }
+
+        let mut out: Vec<u8> = Vec::new();
+        let mut offsets: Vec<usize> = Vec::new();
+        out.extend_from_slice(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n");
+
+        let page_count = self.pages.len();
+        // 1 catalog, 2 page tree, 3-4 fonts, then page + content per page
+        let page_ids: Vec<usize> = (0..page_count).map(|i| 5 + 2 * i).collect();
+
+        let mut object = |out: &mut Vec<u8>, body: &[u8]| {
+            offsets.push(out.len());
+            let _ = writeln!(out, "{} 0 obj", offsets.len());
+            out.extend_from_slice(body);
+            out.extend_from_slice(b"\nendobj\n");
+        };
+
+        object(&mut out, b"<< /Type /Catalog /Pages 2 0 R >>");
+        let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+        object(
+            &mut out,
+            format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), page_count).as_bytes(),
+        );
+        object(
+            &mut out,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+        );
+        object(
+            &mut out,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>",
+        );
+        for (page_id, content) in page_ids.iter().zip(&self.pages) {
+            object(
+                &mut out,
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                    A4_WIDTH,
+                    A4_HEIGHT,
+                    page_id + 1
+                )
+                .as_bytes(),
+            );
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend_from_slice(content);
+            stream.extend_from_slice(b"\nendstream");
+            object(&mut out, &stream);
+        }
+
+        let xref_offset = out.len();
+        let _ = write!(out, "xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+        for offset in &offsets {
+            let _ = writeln!(out, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            out,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            offsets.len() + 1,
+            xref_offset
+        );
+        out
+    }
+
+    /// Move the cursor down one line of `size`, breaking the page if needed
+    fn advance(&mut self, size: f32) {
+        let height = size * LINE_SPACING;
+        if self.y - height < MARGIN {
+            self.page_break();
+        }
+        self.y -= height;
+    }
+
+    fn put(&mut self, x: f32, y: f32, size: f32, font: Font, text: &str) {
+        let _ = write!(self.current, "BT /{} {} Tf {:.2} {:.2} Td (", font.resource(), size, x, y);
+        self.current.extend_from_slice(&encode_text(text));
+        self.current.extend_from_slice(b") Tj ET\n");
+    }
+}
+
+/// Approximate width of Helvetica text in points
+pub fn text_width(text: &str, size: f32) -> f32 {
+    let em: f32 = text
+        .chars()
+        .map(|c| match c {
+            ' ' | 'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '\'' | '|' | '!' => 0.278,
+            'f' | 't' | 'r' | '(' | ')' | '-' => 0.333,
+            'm' | 'w' | 'M' | 'W' | '%' => 0.833,
+            c if c.is_ascii_digit() => 0.556,
+            c if c.is_uppercase() => 0.667,
+            _ => 0.556,
+        })
+        .sum();
+    em * size
+}
+
+/// Split a paragraph into lines no wider than `width`
+fn wrap(paragraph: &str, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in paragraph.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+        if text_width(&candidate, size) <= width || line.is_empty() {
+            line = candidate;
+        } else {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// Encode text as an escaped WinAnsi string body
+pub fn encode_text(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        let byte = match c {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                c as u8
+            }
+            ' '..='~' => c as u8,
+            '\u{A0}'..='\u{FF}' => c as u32 as u8,
+            '€' => 0x80,
+            '„' => 0x84,
+            '…' => 0x85,
+            'Š' => 0x8A,
+            'Ž' => 0x8E,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            'š' => 0x9A,
+            'ž' => 0x9E,
+            _ => fold_letter(c).unwrap_or(b'?'),
+        };
+        out.push(byte);
+    }
+    out
+}
+
+/// Base letter of Czech and Slovak letters missing from WinAnsi
+fn fold_letter(c: char) -> Option<u8> {
+    let base = match c {
+        'č' => 'c',
+        'Č' => 'C',
+        'ď' => 'd',
+        'Ď' => 'D',
+        'ě' => 'e',
+        'Ě' => 'E',
+        'ĺ' | 'ľ' => 'l',
+        'Ĺ' | 'Ľ' => 'L',
+        'ň' => 'n',
+        'Ň' => 'N',
+        'ř' | 'ŕ' => 'r',
+        'Ř' | 'Ŕ' => 'R',
+        'ť' => 't',
+        'Ť' => 'T',
+        'ů' => 'u',
+        'Ů' => 'U',
+        _ => return None,
+    };
+    Some(base as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_text() {
+        assert_eq!(encode_text("a(b)\\"), b"a\\(b\\)\\\\".to_vec());
+        // á and ž are WinAnsi, ř and ů fold to their base letter
+        assert_eq!(encode_text("Žár řů"), vec![0x8E, 0xE1, b'r', b' ', b'r', b'u']);
+        assert_eq!(encode_text("☃"), b"?".to_vec());
+    }
+
+    #[test]
+    fn test_wrap() {
+        let lines = wrap("one two three four five six", 10.0, 60.0);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| text_width(l, 10.0) <= 60.0));
+        assert_eq!(lines.join(" "), "one two three four five six");
+        assert_eq!(wrap("", 10.0, 60.0), vec![String::new()]);
+    }
+
+    #[test]
+    fn test_document_structure() {
+        let mut doc = PdfDocument::new();
+        doc.text("Nabídka", 18.0, Font::Bold);
+        doc.rule();
+        for i in 0..80 {
+            doc.row(&[Cell::left(0.0, "Item"), Cell::right(CONTENT_WIDTH, &i.to_string())], 10.0, Font::Regular);
+        }
+        let bytes = doc.finish();
+        let find = |needle: &[u8]| bytes.windows(needle.len()).rposition(|w| w == needle);
+
+        assert!(bytes.starts_with(b"%PDF-1.4"));
+        assert!(bytes.ends_with(b"%%EOF\n"));
+        assert!(find(b"/Count 2").is_some());
+
+        // Every xref entry points at its object
+        let tail = std::str::from_utf8(&bytes[find(b"startxref\n").unwrap() + 10..]).unwrap();
+        let xref_at: usize = tail.lines().next().unwrap().parse().unwrap();
+        let xref = std::str::from_utf8(&bytes[xref_at..]).unwrap();
+        let entries: Vec<&str> = xref.lines().skip(3).take_while(|l| l.ends_with(" n ")).collect();
+        assert_eq!(entries.len(), 8);
+        for (i, entry) in entries.iter().enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(bytes[offset..].starts_with(format!("{} 0 obj", i + 1).as_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_qr_fills_dark_modules() {
+        let matrix = crate::services::device_code::qr_matrix("https://example.com/d/1").unwrap();
+        let dark = (0..matrix.size)
+            .flat_map(|y| (0..matrix.size).map(move |x| (x, y)))
+            .filter(|&(x, y)| matrix.is_dark(x, y))
+            .count();
+
+        let mut doc = PdfDocument::new();
+        doc.qr(&matrix, 0.0, 100.0);
+        assert_eq!(doc.y, A4_HEIGHT - MARGIN - 100.0);
+        let content = String::from_utf8(doc.current.clone()).unwrap();
+        assert_eq!(content.matches(" re\n").count(), dark);
+        assert!(content.ends_with("f\n"));
+    }
+}
@@ -0,0 +1,183 @@
+//! Presence: who is viewing or editing an entity right now
+//!
+//! Browser tabs send a heartbeat per entity on screen; a session missing
+//! two heartbeats in a row drops out. The registry lives in the worker's
+//! memory (presence is short-lived and not worth persisting) and is scoped
+//! per account, so workers see their company's colleagues only.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use uuid::Uuid;
+
+use crate::types::presence::PresenceViewer;
+
+/// Interval clients are asked to send heartbeats in
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A session without a heartbeat for this long is gone
+pub const PRESENCE_TTL: Duration = Duration::from_secs(35);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct EntityKey {
+    account_id: Uuid,
+    entity_type: String,
+    entity_id: Uuid,
+}
+
+#[derive(Debug, Clone)]
+struct Session {
+    user_id: Uuid,
+    name: String,
+    editing: bool,
+    since: DateTime<Utc>,
+    last_seen: Instant,
+}
+
+/// Sessions per entity
+#[derive(Default)]
+pub struct PresenceRegistry {
+    entities: Mutex<HashMap<EntityKey, HashMap<String, Session>>>,
+}
+
+/// Viewers of an entity other than `session_id`, editors first
+fn others(sessions: &HashMap<String, Session>, session_id: Option<&str>) -> Vec<PresenceViewer> {
+    let mut viewers: Vec<PresenceViewer> = sessions
+        .iter()
+        .filter(|(id, _)| Some(id.as_str()) != session_id)
+        .map(|(_, s)| PresenceViewer { user_id: s.user_id, name: s.name.clone(), editing: s.editing, since: s.since })
+        .collect();
+    viewers.sort_by(|a, b| b.editing.cmp(&a.editing).then(a.since.cmp(&b.since)));
+    viewers
+}
+
+impl PresenceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop sessions whose heartbeat ran out
+    fn prune(entities: &mut HashMap<EntityKey, HashMap<String, Session>>, now: Instant) {
+        entities.retain(|_, sessions| {
+            sessions.retain(|_, s| now.duration_since(s.last_seen) < PRESENCE_TTL);
+            !sessions.is_empty()
+        });
+    }
+
+    /// Whether the session is already known on the entity, so the caller
+    /// can skip looking up the user's name
+    pub fn is_known(&self, account_id: Uuid, entity_type: &str, entity_id: Uuid, session_id: &str) -> bool {
+        let key = EntityKey { account_id, entity_type: entity_type.to_string(), entity_id };
+        self.entities.lock().get(&key).is_some_and(|sessions| sessions.contains_key(session_id))
+    }
+
+    /// Record a heartbeat and return the other sessions on the entity.
+    /// `name` is only used for a session new on the entity.
+    #[allow(clippy::too_many_arguments)]
+    pub fn heartbeat(
+        &self,
+        account_id: Uuid,
+        entity_type: &str,
+        entity_id: Uuid,
+        session_id: &str,
+        user_id: Uuid,
+        name: &str,
+        editing: bool,
+    ) -> Vec<PresenceViewer> {
+        self.heartbeat_at(account_id, entity_type, entity_id, session_id, user_id, name, editing, Instant::now())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn heartbeat_at(
+        &self,
+        account_id: Uuid,
+        entity_type: &str,
+        entity_id: Uuid,
+        session_id: &str,
+        user_id: Uuid,
+        name: &str,
+        editing: bool,
+        now: Instant,
+    ) -> Vec<PresenceViewer> {
+        let mut entities = self.entities.lock();
+        Self::prune(&mut entities, now);
+
+        let key = EntityKey { account_id, entity_type: entity_type.to_string(), entity_id };
+        let sessions = entities.entry(key).or_default();
+        sessions
+            .entry(session_id.to_string())
+            .and_modify(|s| {
+                s.editing = editing;
+                s.last_seen = now;
+            })
+            .or_insert_with(|| Session { user_id, name: name.to_string(), editing, since: Utc::now(), last_seen: now });
+        others(sessions, Some(session_id))
+    }
+
+    /// Remove a session from one entity, or from all when `entity` is None
+    pub fn leave(&self, account_id: Uuid, session_id: &str, entity: Option<(&str, Uuid)>) {
+        let mut entities = self.entities.lock();
+        for (key, sessions) in entities.iter_mut() {
+            let matches = key.account_id == account_id
+                && entity.is_none_or(|(entity_type, entity_id)| key.entity_type == entity_type && key.entity_id == entity_id);
+            if matches {
+                sessions.remove(session_id);
+            }
+        }
+        entities.retain(|_, sessions| !sessions.is_empty());
+    }
+
+    /// Everyone on an entity
+    pub fn list(&self, account_id: Uuid, entity_type: &str, entity_id: Uuid) -> Vec<PresenceViewer> {
+        let mut entities = self.entities.lock();
+        Self::prune(&mut entities, Instant::now());
+        let key = EntityKey { account_id, entity_type: entity_type.to_string(), entity_id };
+        entities.get(&key).map(|sessions| others(sessions, None)).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTE: &str = "route";
+
+    #[test]
+    fn test_heartbeat_returns_others() {
+        let registry = PresenceRegistry::new();
+        let account = Uuid::from_u128(1);
+        let route = Uuid::from_u128(10);
+        let jana = Uuid::from_u128(2);
+        let petr = Uuid::from_u128(3);
+
+        assert!(registry.heartbeat(account, ROUTE, route, "tab-a", jana, "Jana", true).is_empty());
+        let seen_by_petr = registry.heartbeat(account, ROUTE, route, "tab-b", petr, "Petr", false);
+        assert_eq!(seen_by_petr.len(), 1);
+        assert_eq!(seen_by_petr[0].name, "Jana");
+        assert!(seen_by_petr[0].editing);
+
+        // Other accounts and entities are separate
+        assert!(registry.list(Uuid::from_u128(99), ROUTE, route).is_empty());
+        assert!(registry.list(account, ROUTE, Uuid::from_u128(11)).is_empty());
+        assert_eq!(registry.list(account, ROUTE, route).len(), 2);
+        assert!(registry.is_known(account, ROUTE, route, "tab-a"));
+    }
+
+    #[test]
+    fn test_sessions_expire_and_leave() {
+        let registry = PresenceRegistry::new();
+        let account = Uuid::from_u128(1);
+        let route = Uuid::from_u128(10);
+        let start = Instant::now();
+
+        registry.heartbeat_at(account, ROUTE, route, "tab-a", Uuid::from_u128(2), "Jana", false, start);
+        let later = start + PRESENCE_TTL + Duration::from_secs(1);
+        let seen = registry.heartbeat_at(account, ROUTE, route, "tab-b", Uuid::from_u128(3), "Petr", false, later);
+        assert!(seen.is_empty());
+
+        registry.leave(account, "tab-b", None);
+        assert!(!registry.is_known(account, ROUTE, route, "tab-b"));
+    }
+}
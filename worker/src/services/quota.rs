@@ -0,0 +1,152 @@
+//! Usage quotas per account plan
+//!
+//! Handlers call [`consume`] before doing metered work and reply with
+//! `QUOTA_EXCEEDED` when it refuses. Monthly metrics are counted in
+//! `usage_counters`; the customer limit is checked against the live count.
+
+use std::fmt;
+
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::types::quota::{AccountUsageResponse, PlanLimits, QuotaMetric, UsageItem, PLAN_FREE};
+
+/// A metered action that the account's plan does not allow
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuotaExceeded {
+    pub metric: QuotaMetric,
+    pub limit: i64,
+    pub used: i64,
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Plan limit reached for {}: {} of {} used",
+            self.metric.as_str(),
+            self.used,
+            self.limit
+        )
+    }
+}
+
+/// First day of the month containing `date`
+pub fn period_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// Whether `used + amount` fits into `limit`
+fn fits(used: i64, amount: i64, limit: Option<i64>) -> bool {
+    limit.is_none_or(|limit| used + amount <= limit)
+}
+
+/// Limits of the account's plan
+pub async fn limits_for(pool: &PgPool, user_id: Uuid) -> Result<(String, PlanLimits)> {
+    let plan = queries::quota::get_plan(pool, user_id)
+        .await?
+        .unwrap_or_else(|| PLAN_FREE.to_string());
+    let limits = PlanLimits::for_plan(&plan);
+    Ok((plan, limits))
+}
+
+/// Current consumption of a metric
+async fn used(pool: &PgPool, user_id: Uuid, metric: QuotaMetric) -> Result<i64> {
+    if metric.is_monthly() {
+        let period = period_start(Utc::now().date_naive());
+        queries::quota::get_counter(pool, user_id, metric.as_str(), period).await
+    } else {
+        queries::quota::count_customers(pool, user_id).await
+    }
+}
+
+/// Remaining allowance of a metric (None = unlimited)
+pub async fn remaining(pool: &PgPool, user_id: Uuid, metric: QuotaMetric) -> Result<Option<i64>> {
+    let (_, limits) = limits_for(pool, user_id).await?;
+    let Some(limit) = limits.limit(metric) else {
+        return Ok(None);
+    };
+    let used = used(pool, user_id, metric).await?;
+    Ok(Some((limit - used).max(0)))
+}
+
+/// Take `amount` units of a metric from the account's allowance.
+///
+/// Monthly counters are incremented atomically. Live totals (customers) are
+/// only checked, since the row being created is what counts.
+pub async fn consume(
+    pool: &PgPool,
+    user_id: Uuid,
+    metric: QuotaMetric,
+    amount: i64,
+) -> Result<std::result::Result<(), QuotaExceeded>> {
+    let (_, limits) = limits_for(pool, user_id).await?;
+    let limit = limits.limit(metric);
+
+    if metric.is_monthly() {
+        let period = period_start(Utc::now().date_naive());
+        if queries::quota::try_increment(pool, user_id, metric.as_str(), period, amount, limit)
+            .await?
+            .is_some()
+        {
+            return Ok(Ok(()));
+        }
+        let used = queries::quota::get_counter(pool, user_id, metric.as_str(), period).await?;
+        return Ok(Err(QuotaExceeded { metric, limit: limit.unwrap_or(0), used }));
+    }
+
+    let Some(limit) = limit else {
+        return Ok(Ok(()));
+    };
+    let used = used(pool, user_id, metric).await?;
+    if fits(used, amount, Some(limit)) {
+        Ok(Ok(()))
+    } else {
+        Ok(Err(QuotaExceeded { metric, limit, used }))
+    }
+}
+
+/// Consumption of every metric against the plan
+pub async fn usage(pool: &PgPool, user_id: Uuid) -> Result<AccountUsageResponse> {
+    let (plan, limits) = limits_for(pool, user_id).await?;
+    let period = period_start(Utc::now().date_naive());
+
+    let mut items = Vec::with_capacity(QuotaMetric::ALL.len());
+    for metric in QuotaMetric::ALL {
+        items.push(UsageItem {
+            metric,
+            used: used(pool, user_id, metric).await?,
+            limit: limits.limit(metric),
+            period_start: metric.is_monthly().then_some(period),
+        });
+    }
+
+    Ok(AccountUsageResponse { plan, items })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_period_start_is_first_of_month() {
+        let date = NaiveDate::from_ymd_opt(2026, 2, 17).unwrap();
+        assert_eq!(period_start(date), NaiveDate::from_ymd_opt(2026, 2, 1).unwrap());
+    }
+
+    #[test]
+    fn test_fits() {
+        assert!(fits(49, 1, Some(50)));
+        assert!(!fits(50, 1, Some(50)));
+        assert!(fits(1_000_000, 1, None));
+    }
+
+    #[test]
+    fn test_quota_exceeded_message() {
+        let err = QuotaExceeded { metric: QuotaMetric::RoutePlans, limit: 30, used: 30 };
+        assert_eq!(err.to_string(), "Plan limit reached for route_plans: 30 of 30 used");
+    }
+}
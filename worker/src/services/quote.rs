@@ -0,0 +1,420 @@
+//! Quote helpers
+//!
+//! Resolving quote lines against the catalog, totals, quote numbers, the
+//! PDF handed to the customer and the note of the work order an accepted
+//! quote becomes.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::services::pdf::{Cell, Font, PdfDocument, CONTENT_WIDTH};
+use crate::services::vat_summary::{format_minor, line_vat};
+use crate::types::catalog::{CatalogItem, DEFAULT_CATALOG_UNIT, MAX_CATALOG_NAME_LENGTH, MAX_CATALOG_UNIT_LENGTH};
+use crate::types::currency::currency_symbol;
+use crate::types::quote::{Quote, QuoteItem, QuoteItemInput, QuoteTotals, ResolvedQuoteItem};
+use crate::types::settings::UserWithSettings;
+
+/// Days a new quote stays valid unless the request says otherwise
+pub const DEFAULT_QUOTE_VALIDITY_DAYS: i64 = 30;
+
+/// Subject of the communications logged on the customer timeline
+pub const QUOTE_COMMUNICATION_SUBJECT: &str = "Quote";
+
+/// Quote number of the `sequence`-th quote of `year` ("N2026-0001")
+pub fn format_quote_number(year: i32, sequence: i32) -> String {
+    format!("N{}-{:04}", year, sequence)
+}
+
+/// Net amount of a line: quantity times unit price, rounded to minor units
+pub fn line_net(quantity: f64, unit_price_minor: i64) -> i64 {
+    (quantity * unit_price_minor as f64).round() as i64
+}
+
+/// Fill quote lines from the catalog and validate them.
+///
+/// Fields given on the line win over the catalog item; catalog items must
+/// be priced in the quote currency.
+pub fn resolve_items(
+    inputs: &[QuoteItemInput],
+    catalog: &HashMap<Uuid, CatalogItem>,
+    currency: &str,
+) -> Result<Vec<ResolvedQuoteItem>, String> {
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(i, input)| {
+            let line = i + 1;
+            let item = match input.catalog_item_id {
+                Some(id) => {
+                    let item = catalog.get(&id).ok_or_else(|| format!("Line {}: catalog item not found", line))?;
+                    if item.currency != currency {
+                        return Err(format!("Line {}: catalog item is priced in {}, not {}", line, item.currency, currency));
+                    }
+                    Some(item)
+                }
+                None => None,
+            };
+
+            let name = input
+                .name
+                .as_deref()
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .or(item.map(|i| i.name.as_str()))
+                .ok_or_else(|| format!("Line {}: name is required", line))?;
+            if name.chars().count() > MAX_CATALOG_NAME_LENGTH {
+                return Err(format!("Line {}: name is limited to {} characters", line, MAX_CATALOG_NAME_LENGTH));
+            }
+            let unit = input
+                .unit
+                .as_deref()
+                .map(str::trim)
+                .filter(|u| !u.is_empty())
+                .or(item.map(|i| i.unit.as_str()))
+                .unwrap_or(DEFAULT_CATALOG_UNIT);
+            if unit.chars().count() > MAX_CATALOG_UNIT_LENGTH {
+                return Err(format!("Line {}: unit is limited to {} characters", line, MAX_CATALOG_UNIT_LENGTH));
+            }
+            if !input.quantity.is_finite() || input.quantity <= 0.0 {
+                return Err(format!("Line {}: quantity must be positive", line));
+            }
+            let unit_price_minor = input
+                .unit_price_minor
+                .or(item.map(|i| i.unit_price_minor))
+                .ok_or_else(|| format!("Line {}: unitPriceMinor is required", line))?;
+            if unit_price_minor < 0 {
+                return Err(format!("Line {}: unitPriceMinor must not be negative", line));
+            }
+            let vat_rate = input
+                .vat_rate
+                .or(item.map(|i| i.vat_rate))
+                .ok_or_else(|| format!("Line {}: vatRate is required", line))?;
+            if !(0..=100).contains(&vat_rate) {
+                return Err(format!("Line {}: vatRate must be between 0 and 100", line));
+            }
+
+            Ok(ResolvedQuoteItem {
+                catalog_item_id: input.catalog_item_id,
+                name: name.to_string(),
+                unit: unit.to_string(),
+                quantity: input.quantity,
+                unit_price_minor,
+                vat_rate,
+                work_type: input.work_type.or(item.and_then(|i| i.work_type)),
+            })
+        })
+        .collect()
+}
+
+/// Net, VAT and gross of a quote; VAT is rounded per line
+pub fn quote_totals(items: &[QuoteItem]) -> QuoteTotals {
+    let mut totals = QuoteTotals::default();
+    for item in items {
+        let net = line_net(item.quantity, item.unit_price_minor);
+        let vat = line_vat(net, Some(item.vat_rate));
+        totals.net_minor += net;
+        totals.vat_minor += vat;
+        totals.gross_minor += net + vat;
+    }
+    totals
+}
+
+/// Note of the work order created from an accepted quote
+pub fn work_order_note(quote: &Quote, items: &[QuoteItem]) -> String {
+    let mut lines = vec![format!("{} {}: {}", QUOTE_COMMUNICATION_SUBJECT, quote.quote_number, quote.title)];
+    for item in items {
+        let kind = item.work_type.map(|w| format!(" [{}]", w.as_str())).unwrap_or_default();
+        lines.push(format!("- {} {} × {}{}", format_quantity(item.quantity), item.unit, item.name, kind));
+    }
+    lines.join("\n")
+}
+
+/// Quantity without a trailing ".0" for whole numbers
+pub fn format_quantity(quantity: f64) -> String {
+    if quantity.fract() == 0.0 && quantity.abs() < 1e15 {
+        format!("{}", quantity as i64)
+    } else {
+        format!("{}", quantity)
+    }
+}
+
+struct PdfLabels {
+    title: &'static str,
+    valid_until: &'static str,
+    customer: &'static str,
+    item: &'static str,
+    quantity: &'static str,
+    unit_price: &'static str,
+    vat: &'static str,
+    total: &'static str,
+    net_total: &'static str,
+    vat_total: &'static str,
+    gross_total: &'static str,
+}
+
+fn pdf_labels(locale: &str) -> PdfLabels {
+    match locale {
+        "cs" => PdfLabels {
+            title: "Cenová nabídka",
+            valid_until: "Platnost do",
+            customer: "Zákazník",
+            item: "Položka",
+            quantity: "Množství",
+            unit_price: "Cena/j.",
+            vat: "DPH",
+            total: "Celkem",
+            net_total: "Celkem bez DPH",
+            vat_total: "DPH",
+            gross_total: "Celkem k úhradě",
+        },
+        "sk" => PdfLabels {
+            title: "Cenová ponuka",
+            valid_until: "Platnosť do",
+            customer: "Zákazník",
+            item: "Položka",
+            quantity: "Množstvo",
+            unit_price: "Cena/j.",
+            vat: "DPH",
+            total: "Spolu",
+            net_total: "Spolu bez DPH",
+            vat_total: "DPH",
+            gross_total: "Spolu na úhradu",
+        },
+        _ => PdfLabels {
+            title: "Quote",
+            valid_until: "Valid until",
+            customer: "Customer",
+            item: "Item",
+            quantity: "Quantity",
+            unit_price: "Unit price",
+            vat: "VAT",
+            total: "Total",
+            net_total: "Total excl. VAT",
+            vat_total: "VAT",
+            gross_total: "Total due",
+        },
+    }
+}
+
+/// Render a quote as a PDF in the account's language
+pub fn render_quote_pdf(
+    quote: &Quote,
+    items: &[QuoteItem],
+    company: &UserWithSettings,
+    customer_name: &str,
+    locale: &str,
+) -> Vec<u8> {
+    let labels = pdf_labels(locale);
+    let symbol = currency_symbol(&quote.currency);
+    let money = |minor: i64| format!("{} {}", format_minor(minor), symbol);
+
+    let mut doc = PdfDocument::new();
+    doc.text(&format!("{} {}", labels.title, quote.quote_number), 18.0, Font::Bold);
+    doc.space(6.0);
+
+    write_company_header(&mut doc, &company_lines(company));
+    doc.space(8.0);
+
+    doc.text(&format!("{}: {}", labels.customer, customer_name), 10.0, Font::Regular);
+    doc.text(&format!("{}: {}", labels.valid_until, format_date(quote.valid_until, locale)), 10.0, Font::Regular);
+    doc.space(8.0);
+    doc.text(&quote.title, 13.0, Font::Bold);
+    if let Some(note) = quote.note.as_deref().filter(|n| !n.trim().is_empty()) {
+        doc.text(note, 10.0, Font::Regular);
+    }
+    doc.space(8.0);
+
+    let columns = |name: &str, qty: &str, price: &str, vat: &str, total: &str| -> [String; 5] {
+        [name.to_string(), qty.to_string(), price.to_string(), vat.to_string(), total.to_string()]
+    };
+    let header = columns(labels.item, labels.quantity, labels.unit_price, labels.vat, labels.total);
+    table_row(&mut doc, &header, Font::Bold);
+    doc.rule();
+    for item in items {
+        let net = line_net(item.quantity, item.unit_price_minor);
+        let row = columns(
+            &item.name,
+            &format!("{} {}", format_quantity(item.quantity), item.unit),
+            &money(item.unit_price_minor),
+            &format!("{} %", item.vat_rate),
+            &money(net),
+        );
+        table_row(&mut doc, &row, Font::Regular);
+    }
+    doc.rule();
+
+    let totals = quote_totals(items);
+    for (label, amount, font) in [
+        (labels.net_total, totals.net_minor, Font::Regular),
+        (labels.vat_total, totals.vat_minor, Font::Regular),
+        (labels.gross_total, totals.gross_minor, Font::Bold),
+    ] {
+        doc.row(&[Cell::left(250.0, label), Cell::right(CONTENT_WIDTH, &money(amount))], 10.0, font);
+    }
+
+    doc.finish()
+}
+
+/// Company name, address and registration numbers printed on documents
+pub fn company_lines(company: &UserWithSettings) -> Vec<String> {
+    let mut lines = vec![company.business_name.clone().unwrap_or_else(|| company.name.clone())];
+    let address = [company.street.as_deref(), company.postal_code.as_deref(), company.city.as_deref()]
+        .into_iter()
+        .flatten()
+        .filter(|s| !s.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !address.is_empty() {
+        lines.push(address);
+    }
+    let ids = [company.ico.as_deref().map(|v| format!("IČO {}", v)), company.dic.as_deref().map(|v| format!("DIČ {}", v))]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !ids.is_empty() {
+        lines.push(ids);
+    }
+    lines
+}
+
+/// Company lines at the top of a document, the name in bold
+pub fn write_company_header(doc: &mut PdfDocument, lines: &[String]) {
+    for (i, line) in lines.iter().enumerate() {
+        if i == 0 {
+            doc.text(line, 11.0, Font::Bold);
+        } else {
+            doc.text(line, 10.0, Font::Regular);
+        }
+    }
+}
+
+fn table_row(doc: &mut PdfDocument, cells: &[String; 5], font: Font) {
+    doc.row(
+        &[
+            Cell::left(0.0, &cells[0]),
+            Cell::right(290.0, &cells[1]),
+            Cell::right(370.0, &cells[2]),
+            Cell::right(410.0, &cells[3]),
+            Cell::right(CONTENT_WIDTH, &cells[4]),
+        ],
+        10.0,
+        font,
+    );
+}
+
+/// Date as written in documents of the locale
+pub fn format_date(date: NaiveDate, locale: &str) -> String {
+    match locale {
+        "cs" | "sk" => date.format("%-d. %-m. %Y").to_string(),
+        _ => date.format("%Y-%m-%d").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::work_item::WorkType;
+    use chrono::Utc;
+
+    fn catalog_item(id: Uuid, currency: &str) -> CatalogItem {
+        CatalogItem {
+            id,
+            user_id: Uuid::nil(),
+            name: "Revize kotle".to_string(),
+            description: None,
+            unit: "ks".to_string(),
+            unit_price_minor: 150_000,
+            vat_rate: 21,
+            currency: currency.to_string(),
+            work_type: Some(WorkType::Revision),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn quote_item(quantity: f64, unit_price_minor: i64, vat_rate: i32) -> QuoteItem {
+        QuoteItem {
+            id: Uuid::nil(),
+            quote_id: Uuid::nil(),
+            position: 0,
+            catalog_item_id: None,
+            name: "Práce".to_string(),
+            unit: "h".to_string(),
+            quantity,
+            unit_price_minor,
+            vat_rate,
+            work_type: None,
+        }
+    }
+
+    #[test]
+    fn test_format_quote_number() {
+        assert_eq!(format_quote_number(2026, 1), "N2026-0001");
+        assert_eq!(format_quote_number(2026, 12345), "N2026-12345");
+    }
+
+    #[test]
+    fn test_resolve_items_from_catalog() {
+        let id = Uuid::new_v4();
+        let catalog = HashMap::from([(id, catalog_item(id, "CZK"))]);
+        let inputs = vec![
+            QuoteItemInput { catalog_item_id: Some(id), quantity: 2.0, ..Default::default() },
+            QuoteItemInput {
+                catalog_item_id: Some(id),
+                name: Some("Revize se slevou".to_string()),
+                quantity: 1.0,
+                unit_price_minor: Some(120_000),
+                ..Default::default()
+            },
+        ];
+        let items = resolve_items(&inputs, &catalog, "CZK").unwrap();
+        assert_eq!(items[0].name, "Revize kotle");
+        assert_eq!(items[0].unit_price_minor, 150_000);
+        assert_eq!(items[0].work_type, Some(WorkType::Revision));
+        assert_eq!(items[1].name, "Revize se slevou");
+        assert_eq!(items[1].unit_price_minor, 120_000);
+        assert_eq!(items[1].vat_rate, 21);
+    }
+
+    #[test]
+    fn test_resolve_items_rejects_invalid_lines() {
+        let id = Uuid::new_v4();
+        let catalog = HashMap::from([(id, catalog_item(id, "EUR"))]);
+        let from_catalog = QuoteItemInput { catalog_item_id: Some(id), quantity: 1.0, ..Default::default() };
+        assert!(resolve_items(&[from_catalog], &catalog, "CZK").unwrap_err().contains("EUR"));
+
+        let unknown = QuoteItemInput { catalog_item_id: Some(Uuid::new_v4()), quantity: 1.0, ..Default::default() };
+        assert!(resolve_items(&[unknown], &catalog, "CZK").is_err());
+
+        let custom = QuoteItemInput {
+            name: Some("Materiál".to_string()),
+            quantity: 0.0,
+            unit_price_minor: Some(100),
+            vat_rate: Some(21),
+            ..Default::default()
+        };
+        assert!(resolve_items(std::slice::from_ref(&custom), &catalog, "CZK").unwrap_err().contains("quantity"));
+        let no_price = QuoteItemInput { quantity: 1.0, unit_price_minor: None, ..custom };
+        assert!(resolve_items(&[no_price], &catalog, "CZK").unwrap_err().contains("unitPriceMinor"));
+    }
+
+    #[test]
+    fn test_quote_totals_round_per_line() {
+        let items = vec![quote_item(1.5, 33_333, 21), quote_item(2.0, 10_000, 12)];
+        let totals = quote_totals(&items);
+        // 1.5 × 333.33 = 499.995 → 500.00 net, 105.00 VAT; 200.00 net, 24.00 VAT
+        assert_eq!(totals.net_minor, 50_000 + 20_000);
+        assert_eq!(totals.vat_minor, 10_500 + 2_400);
+        assert_eq!(totals.gross_minor, totals.net_minor + totals.vat_minor);
+    }
+
+    #[test]
+    fn test_format_quantity() {
+        assert_eq!(format_quantity(2.0), "2");
+        assert_eq!(format_quantity(1.5), "1.5");
+    }
+}
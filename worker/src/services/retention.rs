@@ -0,0 +1,183 @@
+//! Document retention
+//!
+//! Records covered by a retention policy cannot be deleted until their
+//! period has passed: the period runs from the completion date of a
+//! revision, the date of a completed visit or the date of a communication.
+//! Policies with `delete_after_expiry` are applied by a daily sweep, which
+//! logs every record it removes for the retention report.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{Months, NaiveDate, Utc};
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::types::retention::{
+    RetentionPolicy, RetentionReportEntry, RetentionReportResponse, RETENTION_ENTITY_REVISION,
+    RETENTION_ENTITY_TYPES,
+};
+
+const SWEEP_TICK: Duration = Duration::from_secs(24 * 3600);
+
+/// Sweep deletions listed in the report
+const REPORT_DELETIONS_LIMIT: i64 = 100;
+
+/// Last day a record is kept: the reference date plus the period
+pub fn retained_until(reference_date: NaiveDate, months: i32) -> NaiveDate {
+    reference_date
+        .checked_add_months(Months::new(months.max(0) as u32))
+        .unwrap_or(NaiveDate::MAX)
+}
+
+/// Some(last day) while the record must still be kept
+pub fn deletion_blocked_until(reference_date: NaiveDate, months: i32, today: NaiveDate) -> Option<NaiveDate> {
+    let until = retained_until(reference_date, months);
+    (today < until).then_some(until)
+}
+
+/// The account's policies completed with the defaults, in entity type order
+pub fn effective_policies(set: Vec<RetentionPolicy>) -> Vec<RetentionPolicy> {
+    RETENTION_ENTITY_TYPES
+        .iter()
+        .map(|entity_type| {
+            set.iter()
+                .find(|p| p.entity_type == *entity_type)
+                .cloned()
+                .unwrap_or_else(|| RetentionPolicy::default_for(entity_type))
+        })
+        .collect()
+}
+
+/// Effective policy of one entity type
+pub async fn policy_for(pool: &PgPool, user_id: Uuid, entity_type: &str) -> Result<RetentionPolicy> {
+    let set = queries::retention::list_policies(pool, user_id).await?;
+    Ok(set
+        .into_iter()
+        .find(|p| p.entity_type == entity_type)
+        .unwrap_or_else(|| RetentionPolicy::default_for(entity_type)))
+}
+
+/// Some(last day) while a record may not be deleted yet
+pub async fn retained_until_for(
+    pool: &PgPool,
+    user_id: Uuid,
+    entity_type: &str,
+    entity_id: Uuid,
+) -> Result<Option<NaiveDate>> {
+    let Some(reference) = queries::retention::reference_date(pool, user_id, entity_type, entity_id).await? else {
+        return Ok(None);
+    };
+    let policy = policy_for(pool, user_id, entity_type).await?;
+    Ok(deletion_blocked_until(reference, policy.retention_months, Utc::now().date_naive()))
+}
+
+/// Some(last day) while a device holds revision reports that must be kept
+pub async fn device_retained_until(pool: &PgPool, user_id: Uuid, device_id: Uuid) -> Result<Option<NaiveDate>> {
+    let Some(reference) = queries::retention::device_reference_date(pool, user_id, device_id).await? else {
+        return Ok(None);
+    };
+    let policy = policy_for(pool, user_id, RETENTION_ENTITY_REVISION).await?;
+    Ok(deletion_blocked_until(reference, policy.retention_months, Utc::now().date_naive()))
+}
+
+/// Retention report of an account for auditors
+pub async fn build_report(pool: &PgPool, user_id: Uuid) -> Result<RetentionReportResponse> {
+    let policies = effective_policies(queries::retention::list_policies(pool, user_id).await?);
+    let deleted = queries::retention::count_deletions_last_year(pool, user_id).await?;
+
+    let mut entries = Vec::with_capacity(policies.len());
+    for policy in policies {
+        let counts = queries::retention::count_records(pool, user_id, &policy.entity_type, policy.retention_months).await?;
+        let deleted_last_year = deleted
+            .iter()
+            .find(|(entity_type, _)| *entity_type == policy.entity_type)
+            .map_or(0, |(_, count)| *count);
+        entries.push(RetentionReportEntry {
+            next_expiry: counts.oldest_retained.map(|date| retained_until(date, policy.retention_months)),
+            retained_count: counts.retained_count,
+            expired_count: counts.expired_count,
+            deleted_last_year,
+            policy,
+        });
+    }
+
+    Ok(RetentionReportResponse {
+        generated_at: Utc::now(),
+        entries,
+        recent_deletions: queries::retention::list_deletions(pool, user_id, REPORT_DELETIONS_LIMIT).await?,
+    })
+}
+
+/// Delete expired records of every policy with deletion after expiry
+async fn sweep(pool: &PgPool) -> Result<u64> {
+    let mut deleted = 0;
+    for policy in queries::retention::list_expiring_policies(pool).await? {
+        match queries::retention::delete_expired(pool, &policy).await {
+            Ok(count) => deleted += count,
+            Err(e) => error!(
+                "Failed to delete expired {} records of user {}: {}",
+                policy.entity_type, policy.user_id, e
+            ),
+        }
+    }
+    Ok(deleted)
+}
+
+/// Run the retention sweep once a day
+pub async fn run_sweeper(pool: PgPool) {
+    info!("Retention sweeper started");
+    let mut ticker = tokio::time::interval(SWEEP_TICK);
+
+    loop {
+        ticker.tick().await;
+
+        match sweep(&pool).await {
+            Ok(0) => {}
+            Ok(count) => info!("Deleted {} records past their retention period", count),
+            Err(e) => error!("Failed to run retention sweep: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::retention::{RETENTION_ENTITY_COMMUNICATION, RETENTION_ENTITY_VISIT};
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    #[test]
+    fn test_retained_until() {
+        assert_eq!(retained_until(d(2016, 3, 15), 120), d(2026, 3, 15));
+        // Month ends clamp
+        assert_eq!(retained_until(d(2024, 1, 31), 1), d(2024, 2, 29));
+    }
+
+    #[test]
+    fn test_deletion_blocked_until() {
+        let completed = d(2016, 3, 15);
+        assert_eq!(deletion_blocked_until(completed, 120, d(2026, 3, 14)), Some(d(2026, 3, 15)));
+        assert_eq!(deletion_blocked_until(completed, 120, d(2026, 3, 15)), None);
+        assert_eq!(deletion_blocked_until(completed, 60, d(2026, 3, 14)), None);
+    }
+
+    #[test]
+    fn test_effective_policies_fill_defaults() {
+        let mut visit = RetentionPolicy::default_for(RETENTION_ENTITY_VISIT);
+        visit.retention_months = 24;
+        visit.is_default = false;
+
+        let policies = effective_policies(vec![visit]);
+        let types: Vec<&str> = policies.iter().map(|p| p.entity_type.as_str()).collect();
+        assert_eq!(types, [RETENTION_ENTITY_REVISION, RETENTION_ENTITY_VISIT, RETENTION_ENTITY_COMMUNICATION]);
+        assert_eq!(policies[1].retention_months, 24);
+        assert!(!policies[1].is_default);
+        assert!(policies[0].is_default);
+        assert_eq!(policies[0].retention_months, 120);
+    }
+}
@@ -0,0 +1,400 @@
+//! Revision report PDF
+//!
+//! The report left with the customer after a revision: company, customer,
+//! device, date, result and findings. It ends with the QR code of the
+//! device sticker so next year's technician can scan the report (or the
+//! sticker) and open the device record straight away. Numbered reports
+//! also carry a verification code for the public verification page.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::device_code::{device_code, device_url, qr_matrix, QrMatrix};
+use crate::services::pdf::{Font, PdfDocument};
+use crate::services::quote::{company_lines, format_date, write_company_header};
+use crate::types::device::Device;
+use crate::types::revision::Revision;
+use crate::types::revision_number::format_verification_code;
+
+/// Side of the printed QR code in points (about 4 cm)
+const QR_SIDE: f32 = 113.0;
+
+/// Everything printed on a revision report
+pub struct RevisionReport<'a> {
+    pub revision: &'a Revision,
+    pub device: &'a Device,
+    /// Label of the device type configured for the account
+    pub device_type_label: &'a str,
+    /// Company lines, the name first
+    pub company: &'a [String],
+    pub device_url: &'a str,
+    pub qr: Option<&'a QrMatrix>,
+    /// Printed verification code and the page to check it on
+    pub verification: Option<(&'a str, &'a str)>,
+}
+
+struct ReportLabels {
+    title: &'static str,
+    customer: &'static str,
+    address: &'static str,
+    device: &'static str,
+    serial_number: &'static str,
+    location: &'static str,
+    date: &'static str,
+    result: &'static str,
+    findings: &'static str,
+    next_due: &'static str,
+    scan: &'static str,
+    verify: &'static str,
+    code: &'static str,
+    passed: &'static str,
+    failed: &'static str,
+    conditional: &'static str,
+}
+
+fn report_labels(locale: &str) -> ReportLabels {
+    match locale {
+        "cs" => ReportLabels {
+            title: "Zpráva o revizi",
+            customer: "Zákazník",
+            address: "Adresa",
+            device: "Zařízení",
+            serial_number: "Výrobní číslo",
+            location: "Umístění",
+            date: "Datum revize",
+            result: "Výsledek",
+            findings: "Zjištění",
+            next_due: "Příští revize",
+            scan: "Naskenujte pro otevření záznamu zařízení",
+            verify: "Pravost zprávy ověříte na",
+            code: "kód",
+            passed: "Vyhovuje",
+            failed: "Nevyhovuje",
+            conditional: "Vyhovuje s výhradami",
+        },
+        "sk" => ReportLabels {
+            title: "Správa o revízii",
+            customer: "Zákazník",
+            address: "Adresa",
+            device: "Zariadenie",
+            serial_number: "Výrobné číslo",
+            location: "Umiestnenie",
+            date: "Dátum revízie",
+            result: "Výsledok",
+            findings: "Zistenia",
+            next_due: "Ďalšia revízia",
+            scan: "Naskenujte pre otvorenie záznamu zariadenia",
+            verify: "Pravosť správy overíte na",
+            code: "kód",
+            passed: "Vyhovuje",
+            failed: "Nevyhovuje",
+            conditional: "Vyhovuje s výhradami",
+        },
+        _ => ReportLabels {
+            title: "Revision report",
+            customer: "Customer",
+            address: "Address",
+            device: "Device",
+            serial_number: "Serial number",
+            location: "Location",
+            date: "Revision date",
+            result: "Result",
+            findings: "Findings",
+            next_due: "Next revision",
+            scan: "Scan to open the device record",
+            verify: "Verify this report at",
+            code: "code",
+            passed: "Passed",
+            failed: "Failed",
+            conditional: "Passed with conditions",
+        },
+    }
+}
+
+/// Page where a report number and verification code are checked
+pub fn certificate_verify_url(app_base_url: &str) -> String {
+    format!("{}/verify", app_base_url.trim_end_matches('/'))
+}
+
+/// Date the revision was carried out: completion, else the scheduled day
+pub fn revision_date(revision: &Revision) -> NaiveDate {
+    revision
+        .completed_at
+        .map(|at| at.date_naive())
+        .or(revision.scheduled_date)
+        .unwrap_or(revision.due_date)
+}
+
+/// File name of the report, by document number when one was assigned
+pub fn report_filename(revision: &Revision) -> String {
+    let name = revision
+        .document_number
+        .as_deref()
+        .map(|n| n.replace(['/', '\\'], "-"))
+        .unwrap_or_else(|| format!("revision-{}", revision_date(revision).format("%Y-%m-%d")));
+    format!("{}.pdf", name)
+}
+
+/// Render a revision report as a PDF in the account's language
+pub fn render_revision_report_pdf(report: &RevisionReport<'_>, locale: &str) -> Vec<u8> {
+    let labels = report_labels(locale);
+    let revision = report.revision;
+    let device = report.device;
+
+    let mut doc = PdfDocument::new();
+    let title = match revision.document_number.as_deref() {
+        Some(number) => format!("{} {}", labels.title, number),
+        None => labels.title.to_string(),
+    };
+    doc.text(&title, 18.0, Font::Bold);
+    doc.space(6.0);
+    write_company_header(&mut doc, report.company);
+    doc.space(8.0);
+
+    let field = |doc: &mut PdfDocument, label: &str, value: &str| {
+        if !value.trim().is_empty() {
+            doc.text(&format!("{}: {}", label, value), 10.0, Font::Regular);
+        }
+    };
+
+    field(&mut doc, labels.customer, revision.customer_name.as_deref().unwrap_or_default());
+    let address = join_present(&[
+        revision.customer_street.as_deref(),
+        revision.customer_postal_code.as_deref(),
+        revision.customer_city.as_deref(),
+    ]);
+    field(&mut doc, labels.address, &address);
+    doc.space(8.0);
+
+    let device_title = join_present(&[
+        Some(report.device_type_label),
+        device.device_name.as_deref(),
+        device.manufacturer.as_deref(),
+        device.model.as_deref(),
+    ]);
+    field(&mut doc, labels.device, &device_title);
+    field(&mut doc, labels.serial_number, device.serial_number.as_deref().unwrap_or_default());
+    let location = join_present(&[device.building.as_deref(), device.floor.as_deref(), device.room.as_deref()]);
+    field(&mut doc, labels.location, &location);
+    doc.space(8.0);
+
+    field(&mut doc, labels.date, &format_date(revision_date(revision), locale));
+    let result = match revision.result.as_deref() {
+        Some("passed") => labels.passed,
+        Some("failed") => labels.failed,
+        Some("conditional") => labels.conditional,
+        Some(other) => other,
+        None => "",
+    };
+    doc.space(4.0);
+    if !result.is_empty() {
+        doc.text(&format!("{}: {}", labels.result, result), 12.0, Font::Bold);
+    }
+    if let Some(findings) = revision.findings.as_deref().filter(|f| !f.trim().is_empty()) {
+        doc.space(4.0);
+        doc.text(labels.findings, 10.0, Font::Bold);
+        doc.text(findings, 10.0, Font::Regular);
+    }
+    if let Some(next_due) = device.next_due_date {
+        doc.space(4.0);
+        field(&mut doc, labels.next_due, &format_date(next_due, locale));
+    }
+
+    if let (Some(number), Some((code, url))) = (revision.document_number.as_deref(), report.verification) {
+        doc.space(8.0);
+        doc.text(
+            &format!("{} {} ({} {}, {} {})", labels.verify, url, labels.title, number, labels.code, code),
+            9.0,
+            Font::Regular,
+        );
+    }
+
+    if let Some(qr) = report.qr {
+        doc.space(16.0);
+        doc.rule();
+        doc.space(6.0);
+        doc.qr(qr, 0.0, QR_SIDE);
+        doc.text(labels.scan, 9.0, Font::Regular);
+        doc.text(report.device_url, 8.0, Font::Regular);
+    }
+
+    doc.finish()
+}
+
+/// Renders the reports of one account, loading what a report needs beyond
+/// the revision itself. Company details and device type labels are loaded
+/// once, so batches don't repeat those queries.
+pub struct ReportRenderer<'a> {
+    pool: &'a PgPool,
+    jwt_secret: &'a str,
+    app_base_url: &'a str,
+    user_id: Uuid,
+    company: Vec<String>,
+    locale: String,
+    type_labels: HashMap<String, String>,
+}
+
+impl<'a> ReportRenderer<'a> {
+    /// None when the account doesn't exist
+    pub async fn new(pool: &'a PgPool, jwt_secret: &'a str, app_base_url: &'a str, user_id: Uuid) -> Result<Option<Self>> {
+        let Some(settings) = queries::settings::get_user_settings(pool, user_id).await? else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            pool,
+            jwt_secret,
+            app_base_url,
+            user_id,
+            company: company_lines(&settings),
+            locale: settings.company_locale.clone(),
+            type_labels: HashMap::new(),
+        }))
+    }
+
+    /// PDF of a revision of the account; None when its device is gone
+    pub async fn render(&mut self, revision: &Revision) -> Result<Option<Vec<u8>>> {
+        let Some(device) = queries::device::get_device_by_id(self.pool, self.user_id, revision.device_id).await? else {
+            return Ok(None);
+        };
+
+        let mut revision = revision.clone();
+        if revision.customer_name.is_none() {
+            if let Some(customer) = queries::customer::get_customer(self.pool, self.user_id, revision.customer_id).await? {
+                revision.customer_name = customer.name;
+                revision.customer_street = customer.street;
+                revision.customer_city = customer.city;
+                revision.customer_postal_code = customer.postal_code;
+            }
+        }
+
+        if !self.type_labels.contains_key(&device.device_type) {
+            let label = queries::device_type_config::get_label(self.pool, self.user_id, &device.device_type)
+                .await?
+                .unwrap_or_else(|| device.device_type.clone());
+            self.type_labels.insert(device.device_type.clone(), label);
+        }
+        let verification_code = match revision.document_number {
+            Some(_) => queries::revision_number::get_verification_code(self.pool, self.user_id, revision.id)
+                .await?
+                .map(|code| format_verification_code(&code)),
+            None => None,
+        };
+
+        let url = device_url(self.app_base_url, &device_code(self.jwt_secret, device.id));
+        let qr = qr_matrix(&url);
+        let verify_url = certificate_verify_url(self.app_base_url);
+        let report = RevisionReport {
+            revision: &revision,
+            device: &device,
+            device_type_label: &self.type_labels[&device.device_type],
+            company: &self.company,
+            device_url: &url,
+            qr: qr.as_ref(),
+            verification: verification_code.as_deref().map(|code| (code, verify_url.as_str())),
+        };
+        Ok(Some(render_revision_report_pdf(&report, &self.locale)))
+    }
+}
+
+fn join_present(parts: &[Option<&str>]) -> String {
+    parts
+        .iter()
+        .flatten()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::device_code::qr_matrix;
+    use chrono::{TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn revision() -> Revision {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::nil(),
+            "deviceId": Uuid::nil(),
+            "customerId": Uuid::nil(),
+            "userId": Uuid::nil(),
+            "status": "completed",
+            "dueDate": "2026-05-01",
+            "createdAt": Utc::now(),
+            "updatedAt": Utc::now(),
+        }))
+        .unwrap()
+    }
+
+    fn device() -> Device {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::nil(),
+            "customerId": Uuid::nil(),
+            "userId": Uuid::nil(),
+            "deviceType": "gas_boiler",
+            "revisionIntervalMonths": 12,
+            "createdAt": Utc::now(),
+            "updatedAt": Utc::now(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_revision_date_and_filename() {
+        let mut rev = revision();
+        assert_eq!(revision_date(&rev), NaiveDate::from_ymd_opt(2026, 5, 1).unwrap());
+        assert_eq!(report_filename(&rev), "revision-2026-05-01.pdf");
+
+        rev.scheduled_date = NaiveDate::from_ymd_opt(2026, 4, 20);
+        rev.completed_at = Some(Utc.with_ymd_and_hms(2026, 4, 22, 9, 0, 0).unwrap());
+        assert_eq!(revision_date(&rev), NaiveDate::from_ymd_opt(2026, 4, 22).unwrap());
+
+        rev.document_number = Some("R/2026/0042".to_string());
+        assert_eq!(report_filename(&rev), "R-2026-0042.pdf");
+    }
+
+    #[test]
+    fn test_render_includes_qr() {
+        let mut rev = revision();
+        rev.result = Some("passed".to_string());
+        rev.findings = Some("Bez závad".to_string());
+        let dev = device();
+        let company = vec!["Revize s.r.o.".to_string()];
+        let url = "https://app.sazinka.cz/d/abc.def";
+        let qr = qr_matrix(url).unwrap();
+
+        let render = |rev: &Revision, qr: Option<&QrMatrix>| {
+            let report = RevisionReport {
+                revision: rev,
+                device: &dev,
+                device_type_label: "Plynový kotel",
+                company: &company,
+                device_url: url,
+                qr,
+                verification: Some(("AB7K-M2QX", "https://app.sazinka.cz/verify")),
+            };
+            render_revision_report_pdf(&report, "cs")
+        };
+        let contains = |bytes: &[u8], needle: &[u8]| bytes.windows(needle.len()).any(|w| w == needle);
+
+        let with_qr = render(&rev, Some(&qr));
+        assert!(with_qr.starts_with(b"%PDF-1.4"));
+        assert!(contains(&with_qr, b"Vyhovuje"));
+        assert!(contains(&with_qr, url.as_bytes()));
+        assert!(contains(&with_qr, b" re\n"));
+
+        // Only numbered reports can be verified
+        assert!(!contains(&with_qr, b"AB7K-M2QX"));
+        rev.document_number = Some("R2026/0042".to_string());
+        assert!(contains(&render(&rev, Some(&qr)), b"AB7K-M2QX"));
+
+        let without = render(&rev, None);
+        assert!(!contains(&without, b" re\n"));
+    }
+}
@@ -0,0 +1,235 @@
+#![allow(dead_code)]
+//! Actual vs planned route analysis
+//!
+//! Replays finished routes: each stop's planned ETA against the recorded
+//! arrival, and each leg's planned driving time against the time between
+//! leaving one customer and arriving at the next. Legs are aggregated by
+//! length to expose systematic routing bias; with enough data the overall
+//! ratio becomes a correction factor for future planning.
+
+use std::collections::HashMap;
+
+use crate::types::route_analysis::{
+    LegBand, LegDeviation, RouteAnalysisResponse, StopActualRow, StopDeviation, TimeBand, TravelBias, ZoneCell,
+    TRAVEL_FACTOR_MAX, TRAVEL_FACTOR_MIN,
+};
+use crate::types::Coordinates;
+
+/// Legs needed before a correction factor is suggested
+pub const MIN_LEGS_FOR_FACTOR: usize = 20;
+
+/// Whether the actual leg time can be driving at all; longer gaps mean a
+/// detour, a break or an unrecorded stop and would skew the bias
+fn plausible_leg(planned_minutes: i64, actual_minutes: i64) -> bool {
+    actual_minutes > 0 && actual_minutes <= planned_minutes * 3 + 30
+}
+
+fn bias(band: Option<LegBand>, legs: &[&LegDeviation]) -> Option<TravelBias> {
+    let planned: i64 = legs.iter().map(|l| l.planned_minutes).sum();
+    let actual: i64 = legs.iter().map(|l| l.actual_minutes).sum();
+    if planned <= 0 {
+        return None;
+    }
+    Some(TravelBias {
+        band,
+        legs: legs.len(),
+        planned_minutes: planned,
+        actual_minutes: actual,
+        ratio: actual as f64 / planned as f64,
+    })
+}
+
+/// Analyse stop rows ordered by route and stop order
+pub fn analyze(rows: &[StopActualRow]) -> RouteAnalysisResponse {
+    let mut stops = Vec::new();
+    let mut legs = Vec::new();
+
+    for (i, row) in rows.iter().enumerate() {
+        if row.stop_type != "customer" {
+            continue;
+        }
+
+        if let (Some(planned), Some(actual)) = (row.estimated_arrival, row.actual_arrival) {
+            if actual.date() == row.route_date {
+                stops.push(StopDeviation {
+                    route_id: row.route_id,
+                    date: row.route_date,
+                    stop_order: row.stop_order,
+                    customer_id: row.customer_id,
+                    customer_name: row.customer_name.clone(),
+                    planned_arrival: planned,
+                    actual_arrival: actual.time(),
+                    delay_minutes: (actual.time() - planned).num_minutes(),
+                });
+            }
+        }
+
+        // Legs between two customer stops only; the first stop's leg starts
+        // at the depot, whose departure is not recorded
+        let Some(prev) = i.checked_sub(1).map(|p| &rows[p]) else { continue };
+        if prev.route_id != row.route_id || prev.stop_type != "customer" {
+            continue;
+        }
+        let (Some(departed), Some(arrived), Some(planned)) =
+            (prev.actual_departure, row.actual_arrival, row.duration_from_previous_minutes)
+        else {
+            continue;
+        };
+        let planned = planned as i64;
+        let actual = (arrived - departed).num_minutes();
+        if planned <= 0 || !plausible_leg(planned, actual) {
+            continue;
+        }
+        let zone = match (prev.lat, prev.lng, row.lat, row.lng) {
+            (Some(from_lat), Some(from_lng), Some(to_lat), Some(to_lng)) => Some(ZoneCell::of_leg(
+                Coordinates { lat: from_lat, lng: from_lng },
+                Coordinates { lat: to_lat, lng: to_lng },
+            )),
+            _ => None,
+        };
+        legs.push(LegDeviation {
+            route_id: row.route_id,
+            date: row.route_date,
+            to_stop_order: row.stop_order,
+            planned_minutes: planned,
+            actual_minutes: actual,
+            distance_km: row.distance_from_previous_km,
+            band: row.distance_from_previous_km.map(LegBand::from_km),
+            zone,
+            time_band: TimeBand::from_time(departed.time()),
+        });
+    }
+
+    let mean_delay_minutes = (!stops.is_empty())
+        .then(|| stops.iter().map(|s| s.delay_minutes as f64).sum::<f64>() / stops.len() as f64);
+
+    let all: Vec<&LegDeviation> = legs.iter().collect();
+    let overall = bias(None, &all);
+
+    let mut banded: HashMap<LegBand, Vec<&LegDeviation>> = HashMap::new();
+    for leg in &legs {
+        if let Some(band) = leg.band {
+            banded.entry(band).or_default().push(leg);
+        }
+    }
+    let by_band = [LegBand::Short, LegBand::Medium, LegBand::Long]
+        .into_iter()
+        .filter_map(|band| banded.get(&band).and_then(|legs| bias(Some(band), legs)))
+        .collect();
+
+    let suggested_factor = overall
+        .as_ref()
+        .filter(|b| b.legs >= MIN_LEGS_FOR_FACTOR)
+        .map(|b| (b.ratio.clamp(TRAVEL_FACTOR_MIN, TRAVEL_FACTOR_MAX) * 100.0).round() / 100.0);
+
+    RouteAnalysisResponse {
+        stops,
+        legs,
+        mean_delay_minutes,
+        overall,
+        by_band,
+        suggested_factor,
+        applied_factor: None,
+        zone_factors: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, NaiveTime};
+    use uuid::Uuid;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, 10).unwrap()
+    }
+
+    fn stop(route_id: Uuid, order: i32, planned: &str, arrived: &str, departed: &str, leg_min: i32, km: f64) -> StopActualRow {
+        let at = |t: &str| date().and_time(NaiveTime::parse_from_str(t, "%H:%M").unwrap());
+        StopActualRow {
+            route_id,
+            route_date: date(),
+            stop_order: order,
+            stop_type: "customer".to_string(),
+            customer_id: Some(Uuid::new_v4()),
+            customer_name: None,
+            estimated_arrival: Some(NaiveTime::parse_from_str(planned, "%H:%M").unwrap()),
+            duration_from_previous_minutes: Some(leg_min),
+            distance_from_previous_km: Some(km),
+            lat: Some(49.2),
+            lng: Some(16.6),
+            actual_arrival: Some(at(arrived)),
+            actual_departure: Some(at(departed)),
+        }
+    }
+
+    #[test]
+    fn test_analyze_compares_stops_and_legs() {
+        let route = Uuid::new_v4();
+        let rows = vec![
+            stop(route, 1, "08:30", "08:40", "09:10", 30, 20.0),
+            stop(route, 2, "09:30", "09:35", "10:00", 20, 3.0),
+            stop(route, 3, "10:20", "10:25", "10:50", 20, 10.0),
+        ];
+
+        let analysis = analyze(&rows);
+
+        assert_eq!(analysis.stops.len(), 3);
+        assert_eq!(analysis.stops[0].delay_minutes, 10);
+        assert_eq!(analysis.mean_delay_minutes, Some(20.0 / 3.0));
+        // The first leg starts at the depot and is not measured
+        assert_eq!(analysis.legs.len(), 2);
+        assert_eq!(analysis.legs[0].actual_minutes, 25);
+        assert_eq!(analysis.legs[0].band, Some(LegBand::Short));
+        assert_eq!(analysis.legs[0].time_band, TimeBand::Midday);
+        assert!(analysis.legs[0].zone.is_some());
+        let overall = analysis.overall.unwrap();
+        assert_eq!((overall.planned_minutes, overall.actual_minutes), (40, 50));
+        assert_eq!(analysis.by_band.len(), 2);
+        assert!(analysis.suggested_factor.is_none());
+    }
+
+    #[test]
+    fn test_analyze_skips_legs_across_breaks_and_routes() {
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let mut lunch = stop(first, 2, "12:00", "12:00", "12:30", 0, 0.0);
+        lunch.stop_type = "break".to_string();
+        let rows = vec![
+            stop(first, 1, "11:00", "11:00", "11:40", 20, 10.0),
+            lunch,
+            stop(first, 3, "13:00", "13:00", "13:30", 20, 10.0),
+            stop(second, 1, "13:50", "13:50", "14:00", 20, 10.0),
+        ];
+
+        assert!(analyze(&rows).legs.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_ignores_implausible_legs() {
+        let route = Uuid::new_v4();
+        let rows = vec![
+            stop(route, 1, "08:00", "08:00", "08:30", 10, 5.0),
+            // Three hours for a ten minute drive: an unrecorded stop, not traffic
+            stop(route, 2, "08:40", "11:30", "12:00", 10, 5.0),
+        ];
+
+        assert!(analyze(&rows).legs.is_empty());
+    }
+
+    #[test]
+    fn test_suggested_factor_needs_enough_legs() {
+        let route = Uuid::new_v4();
+        let mut rows = vec![stop(route, 0, "07:00", "07:00", "07:00", 10, 5.0)];
+        // 24 legs planned at 10 min, driven in 12
+        for i in 1..=24 {
+            let start = 7 * 60 + (i - 1) * 12;
+            let hm = |m: i32| format!("{:02}:{:02}", m / 60, m % 60);
+            rows.push(stop(route, i, &hm(start + 10), &hm(start + 12), &hm(start + 12), 10, 8.0));
+        }
+
+        let analysis = analyze(&rows);
+        assert_eq!(analysis.legs.len(), 24);
+        assert_eq!(analysis.suggested_factor, Some(1.2));
+    }
+}
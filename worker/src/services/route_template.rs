@@ -0,0 +1,76 @@
+//! "Plan like last year"
+//!
+//! Seasonal businesses drive much the same routes every year. The routes of
+//! the same week last year (52 weeks back, so weekdays line up) are taken as
+//! templates: each is moved to the same weekday of the planned week, keeps
+//! only the customers whose revisions are due again and is re-optimized.
+
+use std::collections::HashSet;
+
+use chrono::{Datelike, Duration, NaiveDate};
+use uuid::Uuid;
+
+/// 52 weeks: the same weekday of the same week last year
+pub const HISTORY_OFFSET_DAYS: i64 = 364;
+
+/// Revisions due up to this many days after the planned day count as due
+pub const DUE_HORIZON_DAYS: i64 = 30;
+
+/// Monday of the week of `date`
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Monday and Sunday of the week last year matching the week of `date`
+pub fn source_week(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start = week_start(date) - Duration::days(HISTORY_OFFSET_DAYS);
+    (start, start + Duration::days(6))
+}
+
+/// Day of the planned week mirroring a day of last year's week
+pub fn mirrored_date(source_date: NaiveDate) -> NaiveDate {
+    source_date + Duration::days(HISTORY_OFFSET_DAYS)
+}
+
+/// Customers of a route in visiting order, split into due again and not;
+/// a customer visited twice is kept once
+pub fn split_due(route_customers: &[Uuid], due: &HashSet<Uuid>) -> (Vec<Uuid>, Vec<Uuid>) {
+    let mut seen = HashSet::new();
+    route_customers
+        .iter()
+        .filter(|id| seen.insert(**id))
+        .partition(|id| due.contains(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    #[test]
+    fn test_source_week_keeps_weekdays() {
+        // Wednesday 2026-10-14
+        let (start, end) = source_week(d(2026, 10, 14));
+        assert_eq!(start, d(2025, 10, 13));
+        assert_eq!(start.weekday(), chrono::Weekday::Mon);
+        assert_eq!(end, d(2025, 10, 19));
+
+        assert_eq!(mirrored_date(d(2025, 10, 15)), d(2026, 10, 14));
+        assert_eq!(week_start(d(2026, 10, 18)), d(2026, 10, 12));
+    }
+
+    #[test]
+    fn test_split_due() {
+        let a = Uuid::from_u128(1);
+        let b = Uuid::from_u128(2);
+        let c = Uuid::from_u128(3);
+        let due: HashSet<Uuid> = [a, c].into_iter().collect();
+
+        let (kept, left_out) = split_due(&[c, b, a, c], &due);
+        assert_eq!(kept, vec![c, a]);
+        assert_eq!(left_out, vec![b]);
+    }
+}
@@ -0,0 +1,404 @@
+//! Valhalla tile health and coverage diagnostics
+//!
+//! A diagnosis splits the bounding box of some points (an account's
+//! customers, or the locations of failing matrices) into a grid, picks a
+//! few sample points per cell and computes one matrix between all
+//! samples. Cells whose samples cannot be reached have missing tiles;
+//! cells with many implausible legs have low-quality data.
+//!
+//! Matrix outcomes of route planning are tracked per 1° region. When a
+//! region sees an unusual share of failed legs, it is diagnosed
+//! automatically; the latest diagnoses are kept for the admin status.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::sanity::{find_anomalies, AnomalyKind};
+use super::{MatrixVerdict, RoutingService};
+use crate::services::geocoding::BoundingBox;
+use crate::types::routing_diagnostics::{DiagnosisTrigger, RegionDiagnosis, RegionHealth, ValhallaDiagnosis};
+use crate::types::Coordinates;
+
+/// The diagnosed area is split into GRID_SIZE × GRID_SIZE cells
+const GRID_SIZE: usize = 4;
+/// Sample points probed per cell
+const SAMPLES_PER_CELL: usize = 2;
+/// Share of a cell's legs that must be unreachable to report missing tiles
+const TILES_MISSING_SHARE: f64 = 0.5;
+/// Share of a cell's legs that must be bad to report low-quality data
+const LOW_QUALITY_SHARE: f64 = 0.25;
+
+/// Size of the regions matrix failures are tracked in, in degrees
+const REGION_SIZE_DEG: f64 = 1.0;
+/// Matrix outcomes older than this are forgotten
+const FAILURE_WINDOW: Duration = Duration::from_secs(60 * 60);
+/// Located points a region needs in the window before its rate is judged
+const MIN_OBSERVATIONS: usize = 20;
+/// Share of failed points that triggers a diagnosis
+const FAILURE_RATE_THRESHOLD: f64 = 0.3;
+/// A region is diagnosed automatically at most this often
+const DIAGNOSIS_COOLDOWN: Duration = Duration::from_secs(6 * 60 * 60);
+/// Diagnoses kept for the admin status
+const MAX_RECENT_DIAGNOSES: usize = 20;
+
+/// Smallest rectangle containing all points
+pub fn bounding_box(points: &[Coordinates]) -> Option<BoundingBox> {
+    let first = points.first()?;
+    Some(points.iter().fold(BoundingBox::new(first.lat, first.lng, first.lat, first.lng), |b, p| {
+        BoundingBox::new(b.min_lat.min(p.lat), b.min_lng.min(p.lng), b.max_lat.max(p.lat), b.max_lng.max(p.lng))
+    }))
+}
+
+/// Grid cells of `bounds` that contain points, each with up to
+/// SAMPLES_PER_CELL points spread over the cell's points
+fn sample_cells(points: &[Coordinates], bounds: &BoundingBox) -> Vec<(BoundingBox, Vec<Coordinates>)> {
+    let lat_step = (bounds.max_lat - bounds.min_lat) / GRID_SIZE as f64;
+    let lng_step = (bounds.max_lng - bounds.min_lng) / GRID_SIZE as f64;
+    let index = |value: f64, min: f64, step: f64| {
+        if step > 0.0 { (((value - min) / step) as usize).min(GRID_SIZE - 1) } else { 0 }
+    };
+
+    let mut cells: Vec<Vec<Coordinates>> = vec![Vec::new(); GRID_SIZE * GRID_SIZE];
+    for p in points {
+        let row = index(p.lat, bounds.min_lat, lat_step);
+        let col = index(p.lng, bounds.min_lng, lng_step);
+        cells[row * GRID_SIZE + col].push(*p);
+    }
+
+    cells
+        .into_iter()
+        .enumerate()
+        .filter(|(_, cell)| !cell.is_empty())
+        .map(|(i, cell)| {
+            let (row, col) = ((i / GRID_SIZE) as f64, (i % GRID_SIZE) as f64);
+            let cell_bounds = BoundingBox::new(
+                bounds.min_lat + row * lat_step,
+                bounds.min_lng + col * lng_step,
+                bounds.min_lat + (row + 1.0) * lat_step,
+                bounds.min_lng + (col + 1.0) * lng_step,
+            );
+            let count = cell.len().min(SAMPLES_PER_CELL);
+            let samples = (0..count).map(|k| cell[k * cell.len() / count]).collect();
+            (cell_bounds, samples)
+        })
+        .collect()
+}
+
+fn region_health(legs: usize, unreachable: usize, suspicious: usize) -> RegionHealth {
+    if legs == 0 {
+        return RegionHealth::Ok;
+    }
+    let share = |count: usize| count as f64 / legs as f64;
+    if share(unreachable) >= TILES_MISSING_SHARE {
+        RegionHealth::TilesMissing
+    } else if share(unreachable + suspicious) >= LOW_QUALITY_SHARE {
+        RegionHealth::LowQuality
+    } else {
+        RegionHealth::Ok
+    }
+}
+
+/// Probe the routing service with sample routes across the points' area
+pub async fn diagnose(
+    routing: &dyn RoutingService,
+    points: &[Coordinates],
+    trigger: DiagnosisTrigger,
+    user_id: Option<Uuid>,
+) -> ValhallaDiagnosis {
+    let bounds = bounding_box(points);
+    let mut diagnosis = ValhallaDiagnosis {
+        diagnosed_at: Utc::now(),
+        trigger,
+        user_id,
+        bounds,
+        regions: Vec::new(),
+        error: None,
+    };
+    let Some(bounds) = bounds else {
+        diagnosis.error = Some("No located points to probe".to_string());
+        return diagnosis;
+    };
+
+    let cells = sample_cells(points, &bounds);
+    let samples: Vec<Coordinates> = cells.iter().flat_map(|(_, s)| s.iter().copied()).collect();
+    let cell_of: Vec<usize> = cells.iter().enumerate().flat_map(|(i, (_, s))| std::iter::repeat_n(i, s.len())).collect();
+    if samples.len() < 2 {
+        diagnosis.error = Some("At least two located points are needed to probe routes".to_string());
+        return diagnosis;
+    }
+
+    let matrices = match routing.get_matrices(&samples).await {
+        Ok(m) => m,
+        Err(e) => {
+            diagnosis.error = Some(format!("Routing probe failed: {}", e));
+            return diagnosis;
+        }
+    };
+
+    let mut unreachable = vec![0usize; cells.len()];
+    let mut suspicious = vec![0usize; cells.len()];
+    for anomaly in find_anomalies(&samples, &matrices) {
+        let counts = if anomaly.kind == AnomalyKind::Unreachable { &mut unreachable } else { &mut suspicious };
+        let (from, to) = (cell_of[anomaly.from], cell_of[anomaly.to]);
+        counts[from] += 1;
+        if to != from {
+            counts[to] += 1;
+        }
+    }
+
+    let n = samples.len();
+    diagnosis.regions = cells
+        .iter()
+        .enumerate()
+        .map(|(i, (cell_bounds, cell_samples))| {
+            let own = cell_samples.len();
+            // Outgoing and incoming legs, counting legs within the cell once
+            let legs = own * (n - 1) * 2 - own * own.saturating_sub(1);
+            RegionDiagnosis {
+                bounds: *cell_bounds,
+                samples: own,
+                legs,
+                unreachable_legs: unreachable[i],
+                suspicious_legs: suspicious[i],
+                health: region_health(legs, unreachable[i], suspicious[i]),
+            }
+        })
+        .collect();
+    diagnosis
+}
+
+/// 1° region a point falls into
+type RegionKey = (i32, i32);
+
+fn region_of(c: &Coordinates) -> RegionKey {
+    ((c.lat / REGION_SIZE_DEG).floor() as i32, (c.lng / REGION_SIZE_DEG).floor() as i32)
+}
+
+#[derive(Default)]
+struct RegionStats {
+    /// (when, failed) per located point of a checked matrix
+    observations: VecDeque<(Instant, bool)>,
+    last_diagnosis: Option<Instant>,
+}
+
+/// Sliding-window rate of failed matrix legs per region
+#[derive(Default)]
+struct MatrixFailureTracker {
+    regions: HashMap<RegionKey, RegionStats>,
+}
+
+impl MatrixFailureTracker {
+    /// Record a checked matrix; `failed` are indices of locations with bad
+    /// legs. Returns regions whose failure rate calls for a diagnosis.
+    fn record(&mut self, locations: &[Coordinates], failed: &[usize], now: Instant) -> Vec<RegionKey> {
+        let mut touched = Vec::new();
+        for (i, location) in locations.iter().enumerate() {
+            let key = region_of(location);
+            self.regions.entry(key).or_default().observations.push_back((now, failed.contains(&i)));
+            if !touched.contains(&key) {
+                touched.push(key);
+            }
+        }
+
+        let mut due = Vec::new();
+        for key in touched {
+            let stats = self.regions.entry(key).or_default();
+            while stats.observations.front().is_some_and(|(at, _)| now.duration_since(*at) > FAILURE_WINDOW) {
+                stats.observations.pop_front();
+            }
+            let total = stats.observations.len();
+            let failures = stats.observations.iter().filter(|(_, failed)| *failed).count();
+            let cooled_down = stats.last_diagnosis.is_none_or(|at| now.duration_since(at) >= DIAGNOSIS_COOLDOWN);
+            if total >= MIN_OBSERVATIONS && failures as f64 / total as f64 >= FAILURE_RATE_THRESHOLD && cooled_down {
+                stats.last_diagnosis = Some(now);
+                due.push(key);
+            }
+        }
+        due
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref MATRIX_FAILURES: Mutex<MatrixFailureTracker> = Mutex::new(MatrixFailureTracker::default());
+    static ref RECENT_DIAGNOSES: Mutex<VecDeque<ValhallaDiagnosis>> = Mutex::new(VecDeque::new());
+}
+
+/// Keep a diagnosis for the admin status, logging problem regions
+pub fn remember_diagnosis(diagnosis: ValhallaDiagnosis) {
+    for region in diagnosis.problem_regions() {
+        warn!(
+            "Routing data {:?} around {:.2},{:.2}–{:.2},{:.2}: {} unreachable, {} suspicious of {} legs",
+            region.health,
+            region.bounds.min_lat,
+            region.bounds.min_lng,
+            region.bounds.max_lat,
+            region.bounds.max_lng,
+            region.unreachable_legs,
+            region.suspicious_legs,
+            region.legs,
+        );
+    }
+    let mut recent = RECENT_DIAGNOSES.lock();
+    recent.push_front(diagnosis);
+    recent.truncate(MAX_RECENT_DIAGNOSES);
+}
+
+/// Latest diagnoses, newest first
+pub fn recent_diagnoses() -> Vec<ValhallaDiagnosis> {
+    RECENT_DIAGNOSES.lock().iter().cloned().collect()
+}
+
+/// Track the sanity verdict of a Valhalla matrix and diagnose regions
+/// with an unusual rate of failures in the background
+pub fn observe_matrix(routing: &Arc<dyn RoutingService>, locations: &[Coordinates], verdict: &MatrixVerdict) {
+    if routing.name() != "Valhalla" {
+        return;
+    }
+    let failed: Vec<usize> = match verdict {
+        MatrixVerdict::Clean => Vec::new(),
+        MatrixVerdict::Repaired { affected } => affected.clone(),
+        MatrixVerdict::Untrustworthy => (0..locations.len()).collect(),
+    };
+    let due = MATRIX_FAILURES.lock().record(locations, &failed, Instant::now());
+
+    for region in due {
+        let points: Vec<Coordinates> = locations.iter().filter(|c| region_of(c) == region).copied().collect();
+        info!("Unusual rate of routing matrix failures around {:?}, diagnosing Valhalla data", region);
+        let routing = Arc::clone(routing);
+        tokio::spawn(async move {
+            let diagnosis = diagnose(routing.as_ref(), &points, DiagnosisTrigger::MatrixFailures, None).await;
+            remember_diagnosis(diagnosis);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::routing::{DistanceTimeMatrices, MockRoutingService};
+    use async_trait::async_trait;
+
+    fn point(lat: f64, lng: f64) -> Coordinates {
+        Coordinates { lat, lng }
+    }
+
+    /// Mock routing where everything inside `dead` is unreachable
+    struct DeadZoneRouting {
+        dead: BoundingBox,
+    }
+
+    #[async_trait]
+    impl RoutingService for DeadZoneRouting {
+        async fn get_matrices(&self, locations: &[Coordinates]) -> anyhow::Result<DistanceTimeMatrices> {
+            let mut matrices = MockRoutingService::new().estimate(locations);
+            for i in 0..locations.len() {
+                for j in 0..locations.len() {
+                    if i != j && (self.dead.contains(&locations[i]) || self.dead.contains(&locations[j])) {
+                        matrices.distances[i][j] = u64::MAX / 2;
+                        matrices.durations[i][j] = u64::MAX / 2;
+                    }
+                }
+            }
+            Ok(matrices)
+        }
+
+        fn name(&self) -> &str {
+            "DeadZone"
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    fn spread_points() -> Vec<Coordinates> {
+        let mut points = Vec::new();
+        for i in 0..4 {
+            for j in 0..4 {
+                points.push(point(49.0 + i as f64 * 0.25 + 0.1, 14.0 + j as f64 * 0.5 + 0.2));
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bounds = bounding_box(&[point(50.0, 14.5), point(49.2, 16.6), point(49.8, 18.2)]).unwrap();
+        assert_eq!(bounds, BoundingBox::new(49.2, 14.5, 50.0, 18.2));
+        assert!(bounding_box(&[]).is_none());
+    }
+
+    #[test]
+    fn test_sample_cells_caps_samples_per_cell() {
+        let mut points = spread_points();
+        points.extend((0..5).map(|k| point(49.1 + k as f64 * 0.01, 14.2)));
+        let bounds = bounding_box(&points).unwrap();
+        let cells = sample_cells(&points, &bounds);
+
+        assert_eq!(cells.len(), GRID_SIZE * GRID_SIZE);
+        assert!(cells.iter().all(|(_, samples)| (1..=SAMPLES_PER_CELL).contains(&samples.len())));
+        assert!(cells.iter().all(|(b, samples)| samples.iter().all(|s| b.contains(s))));
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_healthy_area() {
+        let diagnosis = diagnose(&MockRoutingService::new(), &spread_points(), DiagnosisTrigger::Manual, None).await;
+
+        assert!(diagnosis.error.is_none());
+        assert_eq!(diagnosis.regions.len(), GRID_SIZE * GRID_SIZE);
+        assert_eq!(diagnosis.problem_regions().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_finds_missing_tiles() {
+        let routing = DeadZoneRouting { dead: BoundingBox::new(49.0, 14.0, 49.3, 14.6) };
+        let diagnosis = diagnose(&routing, &spread_points(), DiagnosisTrigger::Manual, None).await;
+
+        let problems: Vec<&RegionDiagnosis> = diagnosis.problem_regions().collect();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].health, RegionHealth::TilesMissing);
+        assert!(problems[0].bounds.contains(&point(49.1, 14.2)));
+        assert_eq!(problems[0].unreachable_legs, problems[0].legs);
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_needs_two_points() {
+        let diagnosis = diagnose(&MockRoutingService::new(), &[point(50.0, 14.0)], DiagnosisTrigger::Manual, None).await;
+        assert!(diagnosis.error.is_some());
+        assert!(diagnosis.regions.is_empty());
+    }
+
+    #[test]
+    fn test_tracker_triggers_on_failure_rate_with_cooldown() {
+        let mut tracker = MatrixFailureTracker::default();
+        let now = Instant::now();
+        let prague: Vec<Coordinates> = (0..10).map(|k| point(50.0 + k as f64 * 0.01, 14.4)).collect();
+
+        // Healthy matrices never trigger
+        assert!(tracker.record(&prague, &[], now).is_empty());
+        assert!(tracker.record(&prague, &[], now).is_empty());
+
+        // 10 of 30 observations failed (33 %) → diagnose once
+        let all: Vec<usize> = (0..10).collect();
+        assert_eq!(tracker.record(&prague, &all, now), vec![(50, 14)]);
+        assert!(tracker.record(&prague, &all, now).is_empty());
+
+        // After the cooldown (and the window) the region can trigger again
+        let later = now + DIAGNOSIS_COOLDOWN;
+        tracker.record(&prague, &all, later);
+        assert_eq!(tracker.record(&prague, &all, later), vec![(50, 14)]);
+    }
+
+    #[test]
+    fn test_tracker_ignores_sparse_regions() {
+        let mut tracker = MatrixFailureTracker::default();
+        let points = vec![point(48.1, 17.1), point(48.2, 17.2)];
+        assert!(tracker.record(&points, &[0, 1], Instant::now()).is_empty());
+    }
+}
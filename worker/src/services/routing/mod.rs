@@ -3,12 +3,16 @@
 //!
 //! Uses Valhalla for production, mock for tests.
 
+pub mod diagnostics;
+mod sanity;
 mod valhalla;
 
-pub use valhalla::{ValhallaClient, ValhallaConfig};
+pub use sanity::{anomaly_warnings, verify_matrices, MatrixVerdict};
+pub use valhalla::{ValhallaClient, ValhallaConfig, VALHALLA_BREAKER};
 
 use async_trait::async_trait;
 use anyhow::Result;
+use crate::services::http::{self, HttpService};
 use crate::types::Coordinates;
 use std::collections::HashMap;
 use std::hash::Hash;
@@ -121,16 +125,14 @@ impl MockRoutingService {
             average_speed_kmh,
         }
     }
-}
 
-#[async_trait]
-impl RoutingService for MockRoutingService {
-    async fn get_matrices(&self, locations: &[Coordinates]) -> Result<DistanceTimeMatrices> {
+    /// Estimated matrices (never fails, unlike a real routing service)
+    pub fn estimate(&self, locations: &[Coordinates]) -> DistanceTimeMatrices {
         use crate::services::geo::haversine_distance;
 
         let n = locations.len();
         if n == 0 {
-            return Ok(DistanceTimeMatrices::empty());
+            return DistanceTimeMatrices::empty();
         }
 
         let mut distances = vec![vec![0u64; n]; n];
@@ -153,11 +155,18 @@ impl RoutingService for MockRoutingService {
             }
         }
 
-        Ok(DistanceTimeMatrices {
+        DistanceTimeMatrices {
             distances,
             durations,
             size: n,
-        })
+        }
+    }
+}
+
+#[async_trait]
+impl RoutingService for MockRoutingService {
+    async fn get_matrices(&self, locations: &[Coordinates]) -> Result<DistanceTimeMatrices> {
+        Ok(self.estimate(locations))
     }
 
     fn name(&self) -> &str {
@@ -214,15 +223,17 @@ pub async fn create_routing_service_with_fallback(
     ))
 }
 
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Check if Valhalla is healthy by making a simple status request
 async fn check_valhalla_health(base_url: &str) -> Result<()> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()?;
-    
     // Try the status endpoint
     let url = format!("{}/status", base_url);
-    let response = client.get(&url).send().await?;
+    let response = http::client(HttpService::Valhalla)
+        .get(&url)
+        .timeout(HEALTH_CHECK_TIMEOUT)
+        .send()
+        .await?;
     
     if response.status().is_success() {
         Ok(())
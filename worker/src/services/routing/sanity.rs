@@ -0,0 +1,339 @@
+//! Sanity checks of distance/time matrices
+//!
+//! Bad routing data (zero durations between distant points, unreachable
+//! sentinels, wildly asymmetric legs) otherwise flows straight into the
+//! solver and produces absurd routes. Each cell is compared with the
+//! straight-line distance between its locations, opposite legs are
+//! compared with each other and a sample of triangles is spot-checked.
+//! A few bad cells are replaced by estimates; when too much of the matrix
+//! is bad, the caller should discard it and estimate everything.
+
+use std::collections::BTreeSet;
+
+use rand::Rng;
+use serde_json::json;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::DistanceTimeMatrices;
+use crate::services::geo::{haversine_distance, road_distance, travel_time_minutes};
+use crate::types::{Coordinates, PlannedRouteStop, RouteWarning};
+
+/// Locations closer than this may legitimately have zero travel between them
+const MIN_SEPARATION_KM: f64 = 0.3;
+/// Legs longer than this are treated as "unreachable" placeholders
+const MAX_LEG_DURATION_S: u64 = 12 * 60 * 60;
+const MAX_LEG_DISTANCE_M: u64 = 1_500_000;
+/// Average speed above which a leg is physically implausible
+const MAX_SPEED_KMH: f64 = 150.0;
+/// Road distance may not be much shorter than the straight line
+const MIN_ROAD_TO_STRAIGHT_RATIO: f64 = 0.8;
+/// Road distance this many times the straight line (plus slack) is a detour nobody drives
+const MAX_ROAD_TO_STRAIGHT_RATIO: f64 = 5.0;
+const DETOUR_SLACK_M: f64 = 10_000.0;
+/// Opposite legs may differ (one-way streets, motorway exits), but not this much
+const MAX_ASYMMETRY_RATIO: f64 = 3.0;
+const ASYMMETRY_SLACK_S: f64 = 600.0;
+/// A direct leg this much slower than going through a third stop is suspicious
+const TRIANGLE_RATIO: f64 = 1.5;
+const TRIANGLE_SLACK_S: f64 = 600.0;
+/// Triangles checked; smaller matrices are checked exhaustively
+const MAX_TRIANGLE_CHECKS: usize = 5000;
+/// Share of bad cells above which the whole matrix is discarded
+const MAX_ANOMALY_SHARE: f64 = 0.25;
+
+/// What is wrong with a matrix cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnomalyKind {
+    /// Zero distance or duration between distinct locations
+    ZeroTravel,
+    /// Placeholder-sized distance or duration
+    Unreachable,
+    /// Implied average speed is not drivable
+    ImpossibleSpeed,
+    /// Road distance shorter than the straight line
+    ShorterThanStraightLine,
+    /// Road distance many times the straight line
+    ExcessiveDetour,
+    /// Duration far from the opposite leg's
+    Asymmetric,
+    /// Direct leg much slower than going through another location
+    TriangleViolation,
+}
+
+/// One suspicious matrix cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixAnomaly {
+    pub from: usize,
+    pub to: usize,
+    pub kind: AnomalyKind,
+}
+
+/// Outcome of checking a matrix
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatrixVerdict {
+    /// Nothing suspicious found
+    Clean,
+    /// Bad cells were replaced by estimates; the listed location indices
+    /// (into the location list) are affected
+    Repaired { affected: Vec<usize> },
+    /// Too much of the matrix is bad (or its shape is wrong) to trust it
+    Untrustworthy,
+}
+
+/// Find suspicious cells in a matrix computed for `locations`
+pub fn find_anomalies(locations: &[Coordinates], matrices: &DistanceTimeMatrices) -> Vec<MatrixAnomaly> {
+    let n = locations.len();
+    let mut anomalies = Vec::new();
+
+    for i in 0..n {
+        for j in 0..n {
+            if i == j {
+                continue;
+            }
+            if let Some(kind) = check_cell(&locations[i], &locations[j], matrices.distance(i, j), matrices.duration(i, j)) {
+                anomalies.push(MatrixAnomaly { from: i, to: j, kind });
+            }
+        }
+    }
+
+    // Only legs that passed the cell checks are compared with each other
+    let flagged: BTreeSet<(usize, usize)> = anomalies.iter().map(|a| (a.from, a.to)).collect();
+    let usable = |i: usize, j: usize| !flagged.contains(&(i, j));
+
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if !usable(i, j) || !usable(j, i) {
+                continue;
+            }
+            let (there, back) = (matrices.duration(i, j) as f64, matrices.duration(j, i) as f64);
+            let (short, long) = if there <= back { (there, back) } else { (back, there) };
+            if long > short * MAX_ASYMMETRY_RATIO + ASYMMETRY_SLACK_S {
+                let (from, to) = if there > back { (i, j) } else { (j, i) };
+                anomalies.push(MatrixAnomaly { from, to, kind: AnomalyKind::Asymmetric });
+            }
+        }
+    }
+
+    let flagged: BTreeSet<(usize, usize)> = anomalies.iter().map(|a| (a.from, a.to)).collect();
+    let mut violations = BTreeSet::new();
+    for (i, j, k) in triangles(n) {
+        if [(i, k), (i, j), (j, k)].iter().any(|leg| flagged.contains(leg)) {
+            continue;
+        }
+        let direct = matrices.duration(i, k) as f64;
+        let via = (matrices.duration(i, j) + matrices.duration(j, k)) as f64;
+        if direct > via * TRIANGLE_RATIO + TRIANGLE_SLACK_S {
+            violations.insert((i, k));
+        }
+    }
+    anomalies.extend(violations.into_iter().map(|(from, to)| MatrixAnomaly { from, to, kind: AnomalyKind::TriangleViolation }));
+
+    anomalies
+}
+
+fn check_cell(from: &Coordinates, to: &Coordinates, distance_m: u64, duration_s: u64) -> Option<AnomalyKind> {
+    let straight_km = haversine_distance(from, to);
+    let straight_m = straight_km * 1000.0;
+
+    if distance_m >= MAX_LEG_DISTANCE_M || duration_s >= MAX_LEG_DURATION_S {
+        return Some(AnomalyKind::Unreachable);
+    }
+    if straight_km < MIN_SEPARATION_KM {
+        return None;
+    }
+    if distance_m == 0 || duration_s == 0 {
+        return Some(AnomalyKind::ZeroTravel);
+    }
+    if (distance_m as f64) < straight_m * MIN_ROAD_TO_STRAIGHT_RATIO {
+        return Some(AnomalyKind::ShorterThanStraightLine);
+    }
+    if (distance_m as f64) > straight_m * MAX_ROAD_TO_STRAIGHT_RATIO + DETOUR_SLACK_M {
+        return Some(AnomalyKind::ExcessiveDetour);
+    }
+    let speed_kmh = distance_m as f64 / duration_s as f64 * 3.6;
+    if speed_kmh > MAX_SPEED_KMH {
+        return Some(AnomalyKind::ImpossibleSpeed);
+    }
+    None
+}
+
+/// Triangles (i, via j, k) to check: all of them for small matrices,
+/// a random sample for large ones
+fn triangles(n: usize) -> Vec<(usize, usize, usize)> {
+    let distinct = |&(i, j, k): &(usize, usize, usize)| i != j && j != k && i != k;
+    if n.saturating_pow(3) <= MAX_TRIANGLE_CHECKS {
+        (0..n)
+            .flat_map(|i| (0..n).flat_map(move |j| (0..n).map(move |k| (i, j, k))))
+            .filter(distinct)
+            .collect()
+    } else {
+        let mut rng = rand::thread_rng();
+        (0..MAX_TRIANGLE_CHECKS)
+            .map(|_| (rng.gen_range(0..n), rng.gen_range(0..n), rng.gen_range(0..n)))
+            .filter(distinct)
+            .collect()
+    }
+}
+
+/// Check a matrix and repair it in place when only a few cells are bad.
+/// Anomalies are logged; the caller decides how to surface the verdict.
+pub fn verify_matrices(locations: &[Coordinates], matrices: &mut DistanceTimeMatrices) -> MatrixVerdict {
+    let n = locations.len();
+    let shape_ok = matrices.size == n
+        && matrices.distances.len() == n
+        && matrices.durations.len() == n
+        && matrices.distances.iter().chain(&matrices.durations).all(|row| row.len() == n);
+    if !shape_ok {
+        warn!("Routing matrix has size {} for {} locations, discarding it", matrices.size, n);
+        return MatrixVerdict::Untrustworthy;
+    }
+    if n < 2 {
+        return MatrixVerdict::Clean;
+    }
+
+    let anomalies = find_anomalies(locations, matrices);
+    if anomalies.is_empty() {
+        return MatrixVerdict::Clean;
+    }
+
+    let bad_cells: BTreeSet<(usize, usize)> = anomalies.iter().map(|a| (a.from, a.to)).collect();
+    let share = bad_cells.len() as f64 / (n * (n - 1)) as f64;
+    for anomaly in anomalies.iter().take(10) {
+        warn!(
+            "Routing matrix anomaly {:?} on leg {} -> {}: {} m, {} s",
+            anomaly.kind,
+            anomaly.from,
+            anomaly.to,
+            matrices.distance(anomaly.from, anomaly.to),
+            matrices.duration(anomaly.from, anomaly.to),
+        );
+    }
+    if share > MAX_ANOMALY_SHARE {
+        warn!("{} of {} routing matrix legs look wrong, discarding the matrix", bad_cells.len(), n * (n - 1));
+        return MatrixVerdict::Untrustworthy;
+    }
+
+    for &(from, to) in &bad_cells {
+        let (a, b) = (&locations[from], &locations[to]);
+        matrices.distances[from][to] = (road_distance(a, b) * 1000.0) as u64;
+        matrices.durations[from][to] = (travel_time_minutes(a, b) * 60.0) as u64;
+    }
+    warn!("Replaced {} routing matrix legs with estimates", bad_cells.len());
+
+    let affected: BTreeSet<usize> = bad_cells.iter().flat_map(|&(from, to)| [from, to]).collect();
+    MatrixVerdict::Repaired { affected: affected.into_iter().collect() }
+}
+
+/// Plan warnings for locations affected by a repaired matrix. Location 0
+/// is the depot; location i > 0 is `customers[i - 1]` (id, name).
+pub fn anomaly_warnings(
+    affected: &[usize],
+    customers: &[(Uuid, String)],
+    planned_stops: &[PlannedRouteStop],
+) -> Vec<RouteWarning> {
+    affected
+        .iter()
+        .filter_map(|&location| {
+            if location == 0 {
+                return Some(RouteWarning {
+                    stop_index: None,
+                    warning_type: "ROUTING_DATA_ANOMALY".to_string(),
+                    message: json!({"key": "jobs:routing_data_anomaly_depot"}).to_string(),
+                });
+            }
+            let (customer_id, name) = customers.get(location - 1)?;
+            let stop_index = planned_stops.iter().position(|s| s.customer_id == *customer_id);
+            Some(RouteWarning {
+                stop_index: stop_index.map(|i| i as i32),
+                warning_type: "ROUTING_DATA_ANOMALY".to_string(),
+                message: json!({"key": "jobs:routing_data_anomaly", "params": {"name": name}}).to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::routing::{MockRoutingService, RoutingService};
+
+    fn locations() -> Vec<Coordinates> {
+        vec![
+            Coordinates { lat: 50.0755, lng: 14.4378 }, // Prague
+            Coordinates { lat: 49.1951, lng: 16.6068 }, // Brno
+            Coordinates { lat: 49.8209, lng: 18.2625 }, // Ostrava
+            Coordinates { lat: 49.7384, lng: 13.3736 }, // Plzeň
+            Coordinates { lat: 50.7663, lng: 15.0543 }, // Liberec
+        ]
+    }
+
+    async fn estimated(locations: &[Coordinates]) -> DistanceTimeMatrices {
+        MockRoutingService::new().get_matrices(locations).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_estimated_matrix_is_clean() {
+        let locations = locations();
+        let mut matrices = estimated(&locations).await;
+        assert_eq!(verify_matrices(&locations, &mut matrices), MatrixVerdict::Clean);
+    }
+
+    #[tokio::test]
+    async fn test_zero_and_unreachable_legs_repaired() {
+        let locations = locations();
+        let mut matrices = estimated(&locations).await;
+        matrices.durations[0][1] = 0;
+        matrices.distances[2][3] = u64::MAX / 2;
+
+        let anomalies = find_anomalies(&locations, &matrices);
+        assert!(anomalies.contains(&MatrixAnomaly { from: 0, to: 1, kind: AnomalyKind::ZeroTravel }));
+        assert!(anomalies.contains(&MatrixAnomaly { from: 2, to: 3, kind: AnomalyKind::Unreachable }));
+
+        let verdict = verify_matrices(&locations, &mut matrices);
+        assert_eq!(verdict, MatrixVerdict::Repaired { affected: vec![0, 1, 2, 3] });
+        assert!(matrices.duration(0, 1) > 0);
+        assert!(matrices.distance(2, 3) < MAX_LEG_DISTANCE_M);
+    }
+
+    #[tokio::test]
+    async fn test_asymmetric_and_triangle_anomalies() {
+        let locations = locations();
+        let mut matrices = estimated(&locations).await;
+        // Prague -> Liberec suddenly takes 11 hours (distance unchanged)
+        matrices.durations[0][4] = 11 * 3600;
+
+        let kinds: Vec<AnomalyKind> = find_anomalies(&locations, &matrices)
+            .into_iter()
+            .filter(|a| (a.from, a.to) == (0, 4))
+            .map(|a| a.kind)
+            .collect();
+        assert!(kinds.contains(&AnomalyKind::Asymmetric));
+
+        let verdict = verify_matrices(&locations, &mut matrices);
+        assert!(matches!(verdict, MatrixVerdict::Repaired { ref affected } if affected.contains(&4)));
+    }
+
+    #[tokio::test]
+    async fn test_mostly_broken_matrix_untrustworthy() {
+        let locations = locations();
+        let mut matrices = estimated(&locations).await;
+        for row in matrices.durations.iter_mut() {
+            row.iter_mut().for_each(|d| *d = 0);
+        }
+        assert_eq!(verify_matrices(&locations, &mut matrices), MatrixVerdict::Untrustworthy);
+
+        let mut wrong_shape = estimated(&locations[..3]).await;
+        assert_eq!(verify_matrices(&locations, &mut wrong_shape), MatrixVerdict::Untrustworthy);
+    }
+
+    #[test]
+    fn test_anomaly_warnings_point_at_stops() {
+        let customers = vec![(Uuid::new_v4(), "Novák".to_string()), (Uuid::new_v4(), "Svoboda".to_string())];
+        let warnings = anomaly_warnings(&[0, 2, 7], &customers, &[]);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].message.contains("routing_data_anomaly_depot"));
+        assert!(warnings[1].message.contains("Svoboda"));
+        assert_eq!(warnings[1].stop_index, None);
+    }
+}
@@ -6,12 +6,21 @@
 use async_trait::async_trait;
 use anyhow::{Result, Context};
 use reqwest::Client;
+use std::sync::Arc;
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
+use crate::services::circuit_breaker::{self, CircuitBreaker};
+use crate::services::http::{self, HttpService};
 use crate::types::Coordinates;
 use super::{RoutingService, DistanceTimeMatrices, RouteGeometry};
 
+/// Name of the breaker shared by all Valhalla clients
+pub const VALHALLA_BREAKER: &str = "valhalla";
+const DEFAULT_CIRCUIT_BREAKER_THRESHOLD: u32 = 3;
+const DEFAULT_CIRCUIT_BREAKER_RECOVERY_SECS: u64 = 30;
+
 /// Valhalla client configuration
 #[derive(Debug, Clone)]
 pub struct ValhallaConfig {
@@ -43,16 +52,51 @@ impl ValhallaConfig {
 pub struct ValhallaClient {
     client: Client,
     config: ValhallaConfig,
+    breaker: Arc<CircuitBreaker>,
 }
 
 impl ValhallaClient {
     pub fn new(config: ValhallaConfig) -> Self {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(config.timeout_seconds))
-            .build()
-            .expect("Failed to create HTTP client");
+        let client = http::client(HttpService::Valhalla);
+
+        let (threshold, recovery) = circuit_breaker::settings_from_env(
+            "VALHALLA",
+            DEFAULT_CIRCUIT_BREAKER_THRESHOLD,
+            DEFAULT_CIRCUIT_BREAKER_RECOVERY_SECS,
+        );
+        let breaker = circuit_breaker::shared(VALHALLA_BREAKER, threshold, recovery);
 
-        Self { client, config }
+        Self { client, config, breaker }
+    }
+
+    /// POST a JSON request through the circuit breaker. Connection errors,
+    /// timeouts and 5xx responses count as failures; 4xx (e.g. no route
+    /// between points) does not.
+    async fn post<T: Serialize>(&self, path: &str, body: &T) -> Result<reqwest::Response> {
+        if !self.breaker.allow_request() {
+            anyhow::bail!("Valhalla temporarily unavailable (circuit breaker open)");
+        }
+
+        let url = format!("{}/{}", self.config.base_url, path);
+        let timeout = Duration::from_secs(self.config.timeout_seconds);
+        let result = http::send_idempotent(HttpService::Valhalla, || {
+            self.client.post(&url).timeout(timeout).json(body)
+        })
+        .await;
+        match result {
+            Ok(response) if response.status().is_server_error() => {
+                self.breaker.record_failure();
+                Ok(response)
+            }
+            Ok(response) => {
+                self.breaker.record_success();
+                Ok(response)
+            }
+            Err(e) => {
+                self.breaker.record_failure();
+                Err(e.into())
+            }
+        }
     }
 
     /// Build the sources_to_targets request
@@ -103,14 +147,11 @@ impl ValhallaClient {
         }
 
         let request = self.build_route_request(locations);
-        let url = format!("{}/route", self.config.base_url);
 
         debug!("Requesting route geometry from Valhalla for {} locations", locations.len());
 
-        let response = self.client
-            .post(&url)
-            .json(&request)
-            .send()
+        let response = self
+            .post("route", &request)
             .await
             .context("Failed to send route request to Valhalla")?;
 
@@ -146,6 +187,45 @@ impl ValhallaClient {
     }
 }
 
+impl ValhallaClient {
+    /// Build the isochrone request for one travel-time contour
+    pub fn build_isochrone_request(&self, center: &Coordinates, minutes: u32) -> IsochroneRequest {
+        IsochroneRequest {
+            locations: vec![ValhallaLocation { lat: center.lat, lon: center.lng, radius: Some(500) }],
+            costing: "auto".to_string(),
+            contours: vec![IsochroneContour { time: minutes as f64 }],
+            polygons: true,
+        }
+    }
+
+    /// Area reachable from `center` within `minutes` of driving.
+    ///
+    /// Returns polygon rings as [lng, lat] pairs (outer rings and holes alike).
+    pub async fn get_isochrone(&self, center: &Coordinates, minutes: u32) -> Result<Vec<Vec<[f64; 2]>>> {
+        let request = self.build_isochrone_request(center, minutes);
+
+        debug!("Requesting {} min isochrone from Valhalla", minutes);
+
+        let response = self
+            .post("isochrone", &request)
+            .await
+            .context("Failed to send isochrone request to Valhalla")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Valhalla isochrone returned error {}: {}", status, body);
+        }
+
+        let collection: IsochroneResponse = response
+            .json()
+            .await
+            .context("Failed to parse Valhalla isochrone response")?;
+
+        isochrone_rings(collection)
+    }
+}
+
 #[async_trait]
 impl RoutingService for ValhallaClient {
     async fn get_matrices(&self, locations: &[Coordinates]) -> Result<DistanceTimeMatrices> {
@@ -164,14 +244,11 @@ impl RoutingService for ValhallaClient {
         }
 
         let request = self.build_matrix_request(locations);
-        let url = format!("{}/sources_to_targets", self.config.base_url);
 
         debug!("Requesting distance matrix from Valhalla for {} locations", n);
 
-        let response = self.client
-            .post(&url)
-            .json(&request)
-            .send()
+        let response = self
+            .post("sources_to_targets", &request)
             .await
             .context("Failed to send request to Valhalla")?;
 
@@ -301,6 +378,67 @@ struct Leg {
     shape: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct IsochroneRequest {
+    locations: Vec<ValhallaLocation>,
+    costing: String,
+    contours: Vec<IsochroneContour>,
+    polygons: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct IsochroneContour {
+    /// Travel time in minutes
+    time: f64,
+}
+
+/// GeoJSON feature collection returned by /isochrone
+#[derive(Debug, Deserialize)]
+struct IsochroneResponse {
+    features: Vec<IsochroneFeature>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IsochroneFeature {
+    geometry: IsochroneGeometry,
+}
+
+#[derive(Debug, Deserialize)]
+struct IsochroneGeometry {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    coordinates: serde_json::Value,
+}
+
+/// Flatten the polygons of an isochrone response into rings
+fn isochrone_rings(collection: IsochroneResponse) -> Result<Vec<Vec<[f64; 2]>>> {
+    let rings: Vec<Vec<[f64; 2]>> = collection
+        .features
+        .into_iter()
+        .map(|feature| {
+            let geometry = feature.geometry;
+            Ok(match geometry.kind.as_str() {
+                "Polygon" => serde_json::from_value::<Vec<Vec<[f64; 2]>>>(geometry.coordinates)?,
+                "MultiPolygon" => serde_json::from_value::<Vec<Vec<Vec<[f64; 2]>>>>(geometry.coordinates)?
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+                _ => Vec::new(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .filter(|ring| ring.len() >= 3)
+        .collect();
+
+    if rings.is_empty() {
+        anyhow::bail!("Valhalla isochrone returned no polygon");
+    }
+    Ok(rings)
+}
+
 /// Decode Valhalla's encoded polyline format
 /// Precision is 6 decimal places for Valhalla (vs 5 for Google)
 fn decode_polyline(encoded: &str, precision: u32) -> Result<Vec<[f64; 2]>> {
@@ -542,4 +680,37 @@ mod tests {
         assert!((last[0] - 16.6068).abs() < 0.1, "Last lng should be near Brno");
         assert!((last[1] - 49.1951).abs() < 0.1, "Last lat should be near Brno");
     }
+
+    #[test]
+    fn test_build_isochrone_request() {
+        let client = ValhallaClient::new(ValhallaConfig::default());
+        let request = client.build_isochrone_request(&Coordinates { lat: 49.1951, lng: 16.6068 }, 45);
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["contours"][0]["time"], 45.0);
+        assert_eq!(json["polygons"], true);
+        assert_eq!(json["locations"][0]["lon"], 16.6068);
+    }
+
+    #[test]
+    fn test_isochrone_rings_from_polygon_and_multipolygon() {
+        let collection: IsochroneResponse = serde_json::from_value(serde_json::json!({
+            "type": "FeatureCollection",
+            "features": [
+                { "type": "Feature", "properties": { "contour": 30 }, "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[16.0, 49.0], [17.0, 49.0], [17.0, 50.0], [16.0, 49.0]]]
+                } },
+                { "type": "Feature", "properties": {}, "geometry": {
+                    "type": "MultiPolygon",
+                    "coordinates": [[[[18.0, 49.0], [18.5, 49.0], [18.5, 49.5], [18.0, 49.0]]]]
+                } },
+                { "type": "Feature", "properties": {}, "geometry": { "type": "Point", "coordinates": [16.6, 49.2] } }
+            ]
+        }))
+        .unwrap();
+
+        let rings = isochrone_rings(collection).unwrap();
+        assert_eq!(rings.len(), 2);
+        assert_eq!(rings[1][1], [18.5, 49.0]);
+    }
 }
@@ -16,21 +16,26 @@
 use std::sync::Arc;
 use anyhow::Result;
 use async_nats::Client;
-use async_nats::jetstream::{self, Context as JsContext};
+use async_nats::jetstream;
 use futures::StreamExt;
+use sqlx::PgPool;
 use tracing::{info, warn, error};
 use uuid::Uuid;
 
+use crate::services::communication_log;
+use crate::services::quota::{self, QuotaExceeded};
+use crate::subjects;
+use crate::transport::{JobMessage, JobQueue};
 use crate::types::{
     SmsJobRequest, SmsJobStatus, SmsJobStatusUpdate,
-    QueuedSmsJob, SmsJobSubmitResponse,
+    QueuedSmsJob, SmsJobSubmitResponse, QuotaMetric,
 };
 
 // Stream and consumer names
 const STREAM_NAME: &str = "SAZINKA_SMS_JOBS";
 const CONSUMER_NAME: &str = "sms_workers";
-const SUBJECT: &str = "sazinka.jobs.sms";
-const STATUS_PREFIX: &str = "sazinka.job.sms.status";
+const SUBJECT: &str = subjects::jobs::SMS;
+const STATUS_PREFIX: &str = subjects::job::SMS_STATUS;
 
 /// SMS processor configuration (Twilio)
 #[derive(Debug, Clone)]
@@ -65,25 +70,26 @@ impl SmsConfig {
 /// SMS job processor with JetStream integration
 pub struct SmsProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
+    pool: PgPool,
     config: Option<SmsConfig>,
 }
 
 impl SmsProcessor {
     /// Create a new SMS processor, initializing JetStream stream
-    pub async fn new(client: Client, config: Option<SmsConfig>) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+    pub async fn new(client: Client, pool: PgPool, config: Option<SmsConfig>) -> Result<Self> {
+        let queue = JobQueue::new(client.clone());
         
         // Create SMS stream
         let stream_config = jetstream::stream::Config {
             name: STREAM_NAME.to_string(),
-            subjects: vec![format!("{}.*", SUBJECT)], // sazinka.jobs.sms.*
+            subjects: vec![subjects::wildcard(SUBJECT)], // sazinka.jobs.sms.*
             max_messages: 10_000,
             max_bytes: 10 * 1024 * 1024, // 10 MB (SMS are small)
             retention: jetstream::stream::RetentionPolicy::WorkQueue,
             ..Default::default()
         };
-        js.get_or_create_stream(stream_config).await?;
+        queue.ensure_stream(stream_config).await?;
         info!("JetStream SMS stream '{}' ready", STREAM_NAME);
         
         if config.is_none() {
@@ -92,13 +98,22 @@ impl SmsProcessor {
         
         Ok(Self {
             client,
-            js,
+            queue,
+            pool,
             config,
         })
     }
     
-    /// Submit an SMS job to the queue
-    pub async fn submit_job(&self, user_id: Uuid, request: SmsJobRequest) -> Result<SmsJobSubmitResponse> {
+    /// Submit an SMS job to the queue. Each message takes one SMS credit of the plan.
+    pub async fn submit_job(
+        &self,
+        user_id: Uuid,
+        request: SmsJobRequest,
+    ) -> Result<std::result::Result<SmsJobSubmitResponse, QuotaExceeded>> {
+        if let Err(exceeded) = quota::consume(&self.pool, user_id, QuotaMetric::SmsCredits, 1).await? {
+            return Ok(Err(exceeded));
+        }
+
         let job = QueuedSmsJob::new(user_id, request);
         let job_id = job.id;
         let sms_type = job.request.type_name().to_string();
@@ -106,23 +121,23 @@ impl SmsProcessor {
         let subject = format!("{}.{}", SUBJECT, sms_type);
         
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish(subject, payload.into()).await?.await?;
+        self.queue.publish(subject, payload.into()).await?;
         
         info!("SMS job {} submitted: {}", job_id, sms_type);
         
         self.publish_status(job_id, SmsJobStatus::Queued { position: 1 }).await?;
         
-        Ok(SmsJobSubmitResponse {
+        Ok(Ok(SmsJobSubmitResponse {
             job_id,
             sms_type,
             message: "SMS job submitted".to_string(),
-        })
+        }))
     }
     
     /// Publish an SMS job status update
     pub async fn publish_status(&self, job_id: Uuid, status: SmsJobStatus) -> Result<()> {
         let update = SmsJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         
         self.client.publish(subject, payload.into()).await?;
@@ -131,21 +146,17 @@ impl SmsProcessor {
     
     /// Start processing SMS jobs from the queue
     pub async fn start_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(STREAM_NAME).await?;
-        
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(CONSUMER_NAME.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
             max_deliver: 5, // More retries for SMS delivery
-            filter_subject: format!("{}.>", SUBJECT),
+            filter_subject: subjects::tail_wildcard(SUBJECT),
             ..Default::default()
         };
         
-        let consumer = stream.get_or_create_consumer(CONSUMER_NAME, consumer_config).await?;
+        let mut messages = self.queue.consume(STREAM_NAME, consumer_config).await?;
         info!("JetStream SMS consumer '{}' ready", CONSUMER_NAME);
         
-        let mut messages = consumer.messages().await?;
-        
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -164,8 +175,14 @@ impl SmsProcessor {
         Ok(())
     }
     
+    /// Log a sent SMS to the customer's communication history. Non-fatal:
+    /// the message is already out when this runs.
+    async fn log_communication(&self, user_id: Uuid, request: &SmsJobRequest) {
+        communication_log::record(&self.pool, user_id, &communication_log::sms_entry(request)).await;
+    }
+
     /// Process a single SMS job
-    async fn process_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_job(&self, msg: JobMessage) -> Result<()> {
         let job: QueuedSmsJob = serde_json::from_slice(&msg.payload)?;
         let job_id = job.id;
         
@@ -196,7 +213,8 @@ impl SmsProcessor {
             return Ok(());
         }
         
-        // TODO: Implement actual SMS sending using Twilio
+        // TODO: Implement actual SMS sending using Twilio and call
+        // `log_communication` once the provider accepts the message.
         // For now, just mark as failed with "not implemented"
         
         warn!("SMS sending not yet implemented for job {}", job_id);
@@ -0,0 +1,52 @@
+//! Purge of soft-deleted customers and devices
+//!
+//! Deleting a customer or a device only marks it; it can be restored for
+//! `PURGE_AFTER_DAYS`. After that a daily job purges it for good: customers
+//! are anonymized (their rows stay for the history of visits and revisions
+//! that reference them), devices are deleted.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+use crate::db::queries;
+
+/// How long a deleted customer or device can be restored
+pub const PURGE_AFTER_DAYS: i32 = 30;
+
+const PURGE_TICK: Duration = Duration::from_secs(24 * 3600);
+
+/// Purge everything deleted more than `days` ago, returning how many
+/// customers and devices were purged
+pub async fn purge(pool: &PgPool, days: i32) -> Result<(usize, u64)> {
+    let mut customers = 0;
+    for (user_id, customer_id) in queries::customer::list_customers_to_purge(pool, days).await? {
+        match queries::customer::purge_customer(pool, user_id, customer_id).await {
+            Ok(true) => customers += 1,
+            Ok(false) => {}
+            Err(e) => warn!("Failed to purge customer {}: {}", customer_id, e),
+        }
+    }
+    let devices = queries::device::purge_deleted_devices(pool, days).await?;
+    Ok((customers, devices))
+}
+
+/// Run the purge once a day
+pub async fn run_purger(pool: PgPool) {
+    info!("Soft-delete purger started");
+    let mut ticker = tokio::time::interval(PURGE_TICK);
+
+    loop {
+        ticker.tick().await;
+
+        match purge(&pool, PURGE_AFTER_DAYS).await {
+            Ok((0, 0)) => {}
+            Ok((customers, devices)) => {
+                info!("Purged {} deleted customers and {} deleted devices", customers, devices)
+            }
+            Err(e) => error!("Failed to purge deleted records: {}", e),
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! Static map snapshots
+//!
+//! Composes raster tiles from a tile server into a PNG and draws the route
+//! line and markers on top, for printed route sheets and revision reports.
+//! Tiles that cannot be fetched are left blank so a document still gets a
+//! map with its overlay when the tile server is unreachable.
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use anyhow::{anyhow, Result};
+use futures::future::join_all;
+use parking_lot::Mutex;
+use reqwest::Client;
+use tiny_skia::{
+    Color, FillRule, LineCap, LineJoin, Paint, PathBuilder, Pixmap, PixmapPaint, Rect, Stroke, Transform,
+};
+use tracing::warn;
+
+use crate::services::http::{self, HttpService};
+
+const TILE_SIZE: f64 = 256.0;
+const MAX_ZOOM: u8 = 17;
+/// Zoom used when the map shows a single location
+const SINGLE_POINT_ZOOM: u8 = 16;
+/// Free space kept around the overlay, in pixels
+const PADDING: f64 = 40.0;
+/// Tiles kept in memory; the cache is dropped as a whole when full
+const TILE_CACHE_CAPACITY: usize = 512;
+/// Web Mercator latitude limit
+const MAX_LATITUDE: f64 = 85.051_128_78;
+
+/// Kind of point drawn on the map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerKind {
+    Depot,
+    Stop,
+    Customer,
+}
+
+/// Point drawn on the map
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapMarker {
+    pub lat: f64,
+    pub lng: f64,
+    pub kind: MarkerKind,
+}
+
+/// Everything drawn over the tiles
+#[derive(Debug, Clone, Default)]
+pub struct MapOverlay {
+    /// Route line as [lng, lat] pairs
+    pub path: Vec<[f64; 2]>,
+    pub markers: Vec<MapMarker>,
+}
+
+impl MapOverlay {
+    /// All points the map has to show, as [lng, lat]
+    fn points(&self) -> Vec<[f64; 2]> {
+        self.path
+            .iter()
+            .copied()
+            .chain(self.markers.iter().map(|m| [m.lng, m.lat]))
+            .filter(|[lng, lat]| lng.is_finite() && lat.is_finite())
+            .collect()
+    }
+}
+
+/// Visible part of the Web Mercator world at one zoom level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapViewport {
+    pub zoom: u8,
+    /// World pixel coordinates of the top-left corner
+    pub origin_x: f64,
+    pub origin_y: f64,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// World pixel X of a longitude at the zoom level
+pub fn world_x(lng: f64, zoom: u8) -> f64 {
+    (lng + 180.0) / 360.0 * TILE_SIZE * f64::from(1u32 << zoom)
+}
+
+/// World pixel Y of a latitude at the zoom level
+pub fn world_y(lat: f64, zoom: u8) -> f64 {
+    let lat = lat.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+    (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0 * TILE_SIZE * f64::from(1u32 << zoom)
+}
+
+impl MapViewport {
+    /// Largest zoom showing all points inside the padded image, centered on them
+    pub fn fit(points: &[[f64; 2]], width: u32, height: u32) -> Option<Self> {
+        let first = points.first()?;
+        let (mut min_lng, mut max_lng, mut min_lat, mut max_lat) = (first[0], first[0], first[1], first[1]);
+        for [lng, lat] in points {
+            min_lng = min_lng.min(*lng);
+            max_lng = max_lng.max(*lng);
+            min_lat = min_lat.min(*lat);
+            max_lat = max_lat.max(*lat);
+        }
+
+        let usable_w = (f64::from(width) - 2.0 * PADDING).max(1.0);
+        let usable_h = (f64::from(height) - 2.0 * PADDING).max(1.0);
+        let zoom = if min_lng == max_lng && min_lat == max_lat {
+            SINGLE_POINT_ZOOM
+        } else {
+            (0..=MAX_ZOOM)
+                .rev()
+                .find(|&z| {
+                    world_x(max_lng, z) - world_x(min_lng, z) <= usable_w
+                        && world_y(min_lat, z) - world_y(max_lat, z) <= usable_h
+                })
+                .unwrap_or(0)
+        };
+
+        let center_x = (world_x(min_lng, zoom) + world_x(max_lng, zoom)) / 2.0;
+        let center_y = (world_y(min_lat, zoom) + world_y(max_lat, zoom)) / 2.0;
+        Some(Self {
+            zoom,
+            origin_x: center_x - f64::from(width) / 2.0,
+            origin_y: center_y - f64::from(height) / 2.0,
+            width,
+            height,
+        })
+    }
+
+    /// Image pixel position of a coordinate
+    pub fn project(&self, lng: f64, lat: f64) -> (f32, f32) {
+        (
+            (world_x(lng, self.zoom) - self.origin_x) as f32,
+            (world_y(lat, self.zoom) - self.origin_y) as f32,
+        )
+    }
+
+    /// Tiles (x, y) covering the image, rows outside the world skipped
+    pub fn tiles(&self) -> Vec<(i64, i64)> {
+        let tile = |px: f64| (px / TILE_SIZE).floor() as i64;
+        let rows = (1i64 << self.zoom) - 1;
+        let (x0, x1) = (tile(self.origin_x), tile(self.origin_x + f64::from(self.width) - 1.0));
+        let (y0, y1) = (tile(self.origin_y).max(0), tile(self.origin_y + f64::from(self.height) - 1.0).min(rows));
+
+        (y0..=y1).flat_map(|y| (x0..=x1).map(move |x| (x, y))).collect()
+    }
+}
+
+/// Fill a `{z}/{x}/{y}` tile URL template; X wraps around the antimeridian
+pub fn tile_url(template: &str, zoom: u8, x: i64, y: i64) -> String {
+    let x = x.rem_euclid(1i64 << zoom);
+    template
+        .replace("{z}", &zoom.to_string())
+        .replace("{x}", &x.to_string())
+        .replace("{y}", &y.to_string())
+}
+
+fn marker_style(kind: MarkerKind) -> (Color, f32) {
+    match kind {
+        MarkerKind::Depot => (Color::from_rgba8(33, 37, 41, 255), 8.0),
+        MarkerKind::Stop => (Color::from_rgba8(13, 110, 253, 255), 7.0),
+        MarkerKind::Customer => (Color::from_rgba8(220, 53, 69, 255), 9.0),
+    }
+}
+
+/// Draw fetched tiles and the overlay into an image
+pub fn compose(viewport: &MapViewport, tiles: &[((i64, i64), Pixmap)], overlay: &MapOverlay) -> Result<Pixmap> {
+    let mut pixmap = Pixmap::new(viewport.width, viewport.height).ok_or_else(|| anyhow!("Invalid map size"))?;
+    pixmap.fill(Color::from_rgba8(229, 227, 223, 255));
+
+    for ((x, y), tile) in tiles {
+        let left = (*x as f64 * TILE_SIZE - viewport.origin_x).round() as i32;
+        let top = (*y as f64 * TILE_SIZE - viewport.origin_y).round() as i32;
+        pixmap.draw_pixmap(left, top, tile.as_ref(), &PixmapPaint::default(), Transform::identity(), None);
+    }
+
+    if overlay.path.len() >= 2 {
+        let mut builder = PathBuilder::new();
+        for (idx, [lng, lat]) in overlay.path.iter().enumerate() {
+            let (px, py) = viewport.project(*lng, *lat);
+            if idx == 0 {
+                builder.move_to(px, py);
+            } else {
+                builder.line_to(px, py);
+            }
+        }
+        if let Some(path) = builder.finish() {
+            let mut paint = Paint::default();
+            paint.set_color_rgba8(13, 110, 253, 200);
+            paint.anti_alias = true;
+            let stroke = Stroke { width: 4.0, line_cap: LineCap::Round, line_join: LineJoin::Round, ..Default::default() };
+            pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+        }
+    }
+
+    let mut outline = Paint::default();
+    outline.set_color_rgba8(255, 255, 255, 255);
+    outline.anti_alias = true;
+    for marker in &overlay.markers {
+        let (px, py) = viewport.project(marker.lng, marker.lat);
+        let (color, radius) = marker_style(marker.kind);
+        let mut fill = Paint::default();
+        fill.set_color(color);
+        fill.anti_alias = true;
+
+        if marker.kind == MarkerKind::Depot {
+            let outer = Rect::from_xywh(px - radius - 2.0, py - radius - 2.0, 2.0 * radius + 4.0, 2.0 * radius + 4.0);
+            let inner = Rect::from_xywh(px - radius, py - radius, 2.0 * radius, 2.0 * radius);
+            if let (Some(outer), Some(inner)) = (outer, inner) {
+                pixmap.fill_rect(outer, &outline, Transform::identity(), None);
+                pixmap.fill_rect(inner, &fill, Transform::identity(), None);
+            }
+        } else {
+            if let Some(circle) = PathBuilder::from_circle(px, py, radius + 2.0) {
+                pixmap.fill_path(&circle, &outline, FillRule::Winding, Transform::identity(), None);
+            }
+            if let Some(circle) = PathBuilder::from_circle(px, py, radius) {
+                pixmap.fill_path(&circle, &fill, FillRule::Winding, Transform::identity(), None);
+            }
+        }
+    }
+
+    Ok(pixmap)
+}
+
+/// Renders static map snapshots from a raster tile server
+pub struct StaticMapRenderer {
+    client: Client,
+    tile_url: String,
+    attribution: String,
+    cache: Mutex<HashMap<(u8, i64, i64), Vec<u8>>>,
+}
+
+impl StaticMapRenderer {
+    pub fn new(tile_url: impl Into<String>, attribution: impl Into<String>) -> Self {
+        let client = http::client(HttpService::MapTiles);
+
+        Self {
+            client,
+            tile_url: tile_url.into(),
+            attribution: attribution.into(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Attribution required by the tile provider
+    pub fn attribution(&self) -> &str {
+        &self.attribution
+    }
+
+    async fn fetch_tile(&self, zoom: u8, x: i64, y: i64) -> Option<Pixmap> {
+        let key = (zoom, x.rem_euclid(1i64 << zoom), y);
+        let cached = self.cache.lock().get(&key).cloned();
+        let bytes = match cached {
+            Some(bytes) => bytes,
+            None => {
+                let url = tile_url(&self.tile_url, zoom, x, y);
+                let response = http::send_idempotent(HttpService::MapTiles, || self.client.get(&url))
+                    .await
+                    .and_then(|r| r.error_for_status());
+                let bytes = match response {
+                    Ok(response) => response.bytes().await.ok()?.to_vec(),
+                    Err(e) => {
+                        warn!("Failed to fetch map tile {}: {}", url, e);
+                        return None;
+                    }
+                };
+                let mut cache = self.cache.lock();
+                if cache.len() >= TILE_CACHE_CAPACITY {
+                    cache.clear();
+                }
+                cache.insert(key, bytes.clone());
+                bytes
+            }
+        };
+
+        match Pixmap::decode_png(&bytes) {
+            Ok(tile) => Some(tile),
+            Err(e) => {
+                warn!("Map tile {}/{}/{} is not a PNG: {}", zoom, x, y, e);
+                None
+            }
+        }
+    }
+
+    /// Render the overlay on a map fitted around it, as PNG bytes
+    pub async fn render(&self, overlay: &MapOverlay, width: u32, height: u32) -> Result<Vec<u8>> {
+        let viewport = MapViewport::fit(&overlay.points(), width, height)
+            .ok_or_else(|| anyhow!("Nothing to show on the map"))?;
+
+        let fetches = viewport.tiles().into_iter().map(|(x, y)| async move {
+            self.fetch_tile(viewport.zoom, x, y).await.map(|tile| ((x, y), tile))
+        });
+        let tiles: Vec<_> = join_all(fetches).await.into_iter().flatten().collect();
+
+        let pixmap = compose(&viewport, &tiles, overlay)?;
+        Ok(pixmap.encode_png()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_world_projection() {
+        assert_eq!(world_x(-180.0, 0), 0.0);
+        assert_eq!(world_x(0.0, 1), 256.0);
+        assert!((world_y(0.0, 0) - 128.0).abs() < 1e-9);
+        assert!(world_y(50.0, 0) < 128.0);
+    }
+
+    #[test]
+    fn test_fit_single_point_uses_fixed_zoom() {
+        let viewport = MapViewport::fit(&[[16.6, 49.2]], 800, 500).unwrap();
+        assert_eq!(viewport.zoom, SINGLE_POINT_ZOOM);
+        let (x, y) = viewport.project(16.6, 49.2);
+        assert!((x - 400.0).abs() < 0.5 && (y - 250.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_fit_keeps_points_inside_padding() {
+        let points = [[14.42, 50.08], [16.61, 49.19], [17.25, 49.59]];
+        let viewport = MapViewport::fit(&points, 800, 500).unwrap();
+        for [lng, lat] in points {
+            let (x, y) = viewport.project(lng, lat);
+            assert!(x >= PADDING as f32 - 0.5 && x <= 800.0 - PADDING as f32 + 0.5);
+            assert!(y >= PADDING as f32 - 0.5 && y <= 500.0 - PADDING as f32 + 0.5);
+        }
+        // One zoom level more would not fit
+        let wider = world_x(17.25, viewport.zoom + 1) - world_x(14.42, viewport.zoom + 1);
+        let taller = world_y(49.19, viewport.zoom + 1) - world_y(50.08, viewport.zoom + 1);
+        assert!(wider > 720.0 || taller > 420.0);
+    }
+
+    #[test]
+    fn test_tiles_cover_viewport() {
+        let viewport = MapViewport { zoom: 2, origin_x: 100.0, origin_y: 300.0, width: 300, height: 100 };
+        assert_eq!(viewport.tiles(), vec![(0, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn test_tile_url_wraps_x() {
+        let template = "https://tiles.example/{z}/{x}/{y}.png";
+        assert_eq!(tile_url(template, 3, -1, 2), "https://tiles.example/3/7/2.png");
+        assert_eq!(tile_url(template, 3, 4, 2), "https://tiles.example/3/4/2.png");
+    }
+
+    #[test]
+    fn test_compose_without_tiles_draws_overlay() {
+        let overlay = MapOverlay {
+            path: vec![[16.60, 49.19], [16.62, 49.20]],
+            markers: vec![
+                MapMarker { lat: 49.19, lng: 16.60, kind: MarkerKind::Depot },
+                MapMarker { lat: 49.20, lng: 16.62, kind: MarkerKind::Stop },
+            ],
+        };
+        let viewport = MapViewport::fit(&overlay.points(), 400, 300).unwrap();
+        let pixmap = compose(&viewport, &[], &overlay).unwrap();
+
+        let (x, y) = viewport.project(16.62, 49.20);
+        let pixel = pixmap.pixel(x as u32, y as u32).unwrap();
+        assert_eq!((pixel.red(), pixel.green(), pixel.blue()), (13, 110, 253));
+
+        let png = pixmap.encode_png().unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+}
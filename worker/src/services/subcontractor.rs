@@ -0,0 +1,161 @@
+//! Subcontractor settlement
+//!
+//! Prices the completed visits of a subcontractor's crews in a period with
+//! its rate card: the rate of the visit's type, else the `default` rate.
+//! Visits no rate applies to are listed without an amount and counted, so
+//! the owner can complete the card before paying out.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use crate::types::subcontractor::{
+    SettlementLine, SettlementResponse, SettlementTotal, SettlementVisit, Subcontractor, SubcontractorRate,
+    DEFAULT_RATE_VISIT_TYPE,
+};
+
+/// Rate that applies to a visit type
+fn rate_for<'a>(rates: &'a [SubcontractorRate], visit_type: Option<&str>) -> Option<&'a SubcontractorRate> {
+    visit_type
+        .and_then(|visit_type| rates.iter().find(|r| r.visit_type == visit_type))
+        .or_else(|| rates.iter().find(|r| r.visit_type == DEFAULT_RATE_VISIT_TYPE))
+}
+
+/// Settlement of the completed visits of a period
+pub fn build_settlement(
+    subcontractor: &Subcontractor,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    visits: Vec<SettlementVisit>,
+    rates: &[SubcontractorRate],
+) -> SettlementResponse {
+    let mut totals: BTreeMap<String, SettlementTotal> = BTreeMap::new();
+    let mut unrated_visits = 0;
+
+    let lines = visits
+        .into_iter()
+        .map(|visit| {
+            let rate = rate_for(rates, visit.visit_type.as_deref());
+            match rate {
+                Some(rate) => {
+                    let total = totals.entry(rate.currency.clone()).or_insert_with(|| SettlementTotal {
+                        currency: rate.currency.clone(),
+                        visits: 0,
+                        amount_minor: 0,
+                    });
+                    total.visits += 1;
+                    total.amount_minor += rate.amount_minor;
+                }
+                None => unrated_visits += 1,
+            }
+            SettlementLine {
+                visit_id: visit.visit_id,
+                scheduled_date: visit.scheduled_date,
+                visit_type: visit.visit_type,
+                crew_name: visit.crew_name,
+                customer_name: visit.customer_name,
+                city: visit.city,
+                result: visit.result,
+                amount_minor: rate.map(|r| r.amount_minor),
+                currency: rate.map(|r| r.currency.clone()),
+            }
+        })
+        .collect();
+
+    SettlementResponse {
+        subcontractor_id: subcontractor.id,
+        subcontractor_name: subcontractor.name.clone(),
+        from_date,
+        to_date,
+        lines,
+        totals: totals.into_values().collect(),
+        unrated_visits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn subcontractor() -> Subcontractor {
+        Subcontractor {
+            id: Uuid::new_v4(),
+            name: "Revize Novák s.r.o.".to_string(),
+            company_id: None,
+            email: None,
+            phone: None,
+            is_active: true,
+            account_user_id: None,
+            crew_ids: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn visit(visit_type: Option<&str>) -> SettlementVisit {
+        SettlementVisit {
+            visit_id: Uuid::new_v4(),
+            scheduled_date: NaiveDate::from_ymd_opt(2026, 3, 10).unwrap(),
+            visit_type: visit_type.map(str::to_string),
+            crew_name: "Posádka Novák".to_string(),
+            customer_name: Some("Jan Dvořák".to_string()),
+            city: Some("Brno".to_string()),
+            result: Some("successful".to_string()),
+        }
+    }
+
+    fn rate(visit_type: &str, amount_minor: i64, currency: &str) -> SubcontractorRate {
+        SubcontractorRate {
+            visit_type: visit_type.to_string(),
+            amount_minor,
+            currency: currency.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_settlement_applies_type_then_default_rate() {
+        let from = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 3, 31).unwrap();
+        let rates = [rate("revision", 80000, "CZK"), rate("default", 50000, "CZK"), rate("installation", 10000, "EUR")];
+        let settlement = build_settlement(
+            &subcontractor(),
+            from,
+            to,
+            vec![visit(Some("revision")), visit(Some("revision")), visit(Some("repair")), visit(None), visit(Some("installation"))],
+            &rates,
+        );
+
+        let amounts: Vec<Option<i64>> = settlement.lines.iter().map(|l| l.amount_minor).collect();
+        assert_eq!(amounts, vec![Some(80000), Some(80000), Some(50000), Some(50000), Some(10000)]);
+        assert_eq!(
+            settlement.totals,
+            vec![
+                SettlementTotal { currency: "CZK".into(), visits: 4, amount_minor: 260000 },
+                SettlementTotal { currency: "EUR".into(), visits: 1, amount_minor: 10000 },
+            ]
+        );
+        assert_eq!(settlement.unrated_visits, 0);
+    }
+
+    #[test]
+    fn test_settlement_counts_unrated_visits() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let settlement = build_settlement(
+            &subcontractor(),
+            date,
+            date,
+            vec![visit(Some("revision")), visit(Some("repair")), visit(None)],
+            &[rate("revision", 80000, "CZK")],
+        );
+
+        assert_eq!(settlement.unrated_visits, 2);
+        assert_eq!(settlement.lines[1].amount_minor, None);
+        assert_eq!(settlement.lines[1].currency, None);
+        assert_eq!(settlement.totals, vec![SettlementTotal { currency: "CZK".into(), visits: 1, amount_minor: 80000 }]);
+
+        let empty = build_settlement(&subcontractor(), date, date, vec![], &[]);
+        assert!(empty.lines.is_empty() && empty.totals.is_empty());
+    }
+}
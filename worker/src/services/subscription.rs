@@ -0,0 +1,70 @@
+//! Account subscription state
+//!
+//! An external billing system drives the state through the admin
+//! subscription handlers. Every real transition is published on
+//! [`SUBSCRIPTION_CHANGED_SUBJECT`] in the regular event envelope, and write
+//! handlers consult [`is_read_only`] so past-due and cancelled accounts can
+//! still read their data but not change it.
+
+use anyhow::Result;
+use async_nats::Client;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::webhook_events::render_event;
+use crate::types::subscription::{
+    is_read_only_state, SubscriptionChangedEvent, SUBSCRIPTION_CHANGED_EVENT, SUBSCRIPTION_CHANGED_SUBJECT,
+};
+use crate::types::WebhookPayloadFormat;
+
+/// Whether the account may only read data. Unknown accounts are not blocked
+/// here; the handlers' own lookups report them.
+pub async fn is_read_only(pool: &PgPool, user_id: Uuid) -> Result<bool> {
+    let status = queries::subscription::get_status(pool, user_id).await?;
+    Ok(status.as_deref().is_some_and(is_read_only_state))
+}
+
+/// Envelope of a `subscription.changed` event
+pub fn render_transition(event_id: Uuid, occurred_at: DateTime<Utc>, event: &SubscriptionChangedEvent) -> Value {
+    let data = serde_json::to_value(event).unwrap_or(Value::Null);
+    render_event(WebhookPayloadFormat::Envelope, event_id, SUBSCRIPTION_CHANGED_EVENT, occurred_at, &data)
+}
+
+/// Publish a transition. Failures are only logged: the state is already stored.
+pub async fn publish_transition(client: &Client, event: &SubscriptionChangedEvent) {
+    let body = render_transition(Uuid::new_v4(), Utc::now(), event);
+    match serde_json::to_vec(&body) {
+        Ok(payload) => {
+            if let Err(e) = client.publish(SUBSCRIPTION_CHANGED_SUBJECT, payload.into()).await {
+                warn!("Failed to publish subscription change of {}: {}", event.user_id, e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize subscription change of {}: {}", event.user_id, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::subscription::{SUBSCRIPTION_ACTIVE, SUBSCRIPTION_PAST_DUE};
+
+    #[test]
+    fn test_render_transition_uses_envelope() {
+        let event = SubscriptionChangedEvent {
+            user_id: Uuid::nil(),
+            from: SUBSCRIPTION_ACTIVE.to_string(),
+            to: SUBSCRIPTION_PAST_DUE.to_string(),
+            billing_customer_ref: Some("cus_123".to_string()),
+            reason: Some("invoice.payment_failed".to_string()),
+            read_only: true,
+        };
+        let body = render_transition(Uuid::nil(), Utc::now(), &event);
+        assert_eq!(body["type"], SUBSCRIPTION_CHANGED_EVENT);
+        assert_eq!(body["data"]["to"], "past_due");
+        assert_eq!(body["data"]["billingCustomerRef"], "cus_123");
+    }
+}
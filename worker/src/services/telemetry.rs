@@ -0,0 +1,428 @@
+#![allow(dead_code)]
+//! Anonymous telemetry
+//!
+//! Collects error signatures and aggregate usage counters per account in
+//! memory. Periodically, the counters of accounts that opted in are merged
+//! into one payload without any account, customer or user identifiers and
+//! posted to the deployment's telemetry endpoint; everything else is
+//! discarded. The payload an account contributes can be previewed in
+//! settings, so users see exactly what leaves the server.
+//!
+//! Each worker counts its own events. The flush runs on one worker only: it
+//! asks every worker for its period's payload over NATS, combines the
+//! replies and posts them as one.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+
+use anyhow::Result;
+use async_nats::Client;
+use futures::StreamExt;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::http::{self, HttpService};
+use crate::subjects;
+
+/// How often collected data is flushed to the endpoint
+const FLUSH_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+/// How long the flushing worker waits for the other workers' payloads.
+/// A worker answering later loses its period.
+const COLLECT_WINDOW: Duration = Duration::from_secs(5);
+
+/// Error occurrences sharing the same signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorSignature {
+    /// Stable hash of the error kind and its normalized message
+    pub signature: String,
+    /// Where the error happened (e.g. job type "import.customer")
+    pub kind: String,
+    /// Message key when the error is a translatable key; free text is never sent
+    pub key: Option<String>,
+    pub count: u64,
+}
+
+/// Payload posted to the telemetry endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryPayload {
+    pub version: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub errors: Vec<ErrorSignature>,
+    pub usage: BTreeMap<String, u64>,
+}
+
+impl TelemetryPayload {
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty() && self.usage.is_empty()
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct AccountTelemetry {
+    errors: BTreeMap<String, ErrorSignature>,
+    usage: BTreeMap<String, u64>,
+}
+
+/// Translatable message key of an error, if it is one.
+/// Accepts a bare key ("import:csv_empty") or a JSON object with a "key" field.
+pub fn error_key(message: &str) -> Option<String> {
+    let trimmed = message.trim();
+    let candidate = match serde_json::from_str::<serde_json::Value>(trimmed) {
+        Ok(value) => value.get("key")?.as_str()?.to_string(),
+        Err(_) => trimmed.to_string(),
+    };
+    let is_key = candidate.split_once(':').is_some_and(|(ns, name)| {
+        !ns.is_empty()
+            && !name.is_empty()
+            && candidate
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == ':' || c == '.')
+    });
+    is_key.then_some(candidate)
+}
+
+/// Message with variable parts (numbers, ids) blanked so occurrences group together.
+/// Every word containing a digit becomes "#".
+pub fn normalize_error_message(message: &str) -> String {
+    fn flush(word: &mut String, out: &mut String) {
+        if word.chars().any(|c| c.is_ascii_digit()) {
+            out.push('#');
+        } else {
+            out.push_str(word);
+        }
+        word.clear();
+    }
+
+    let mut normalized = String::with_capacity(message.len());
+    let mut word = String::new();
+    for c in message.chars() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut normalized);
+            normalized.push(c);
+        }
+    }
+    flush(&mut word, &mut normalized);
+    normalized.chars().take(200).collect()
+}
+
+/// Stable signature of an error
+pub fn error_signature(kind: &str, message: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(kind.as_bytes());
+    hasher.update([0]);
+    hasher.update(normalize_error_message(message).as_bytes());
+    hex::encode(&hasher.finalize()[..8])
+}
+
+/// In-memory collector of telemetry counters
+pub struct TelemetryCollector {
+    period_start: Mutex<DateTime<Utc>>,
+    accounts: Mutex<HashMap<Uuid, AccountTelemetry>>,
+}
+
+impl TelemetryCollector {
+    pub fn new() -> Self {
+        Self {
+            period_start: Mutex::new(Utc::now()),
+            accounts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count an error of an account
+    pub fn record_error(&self, user_id: Uuid, kind: &str, message: &str) {
+        let signature = error_signature(kind, message);
+        let mut accounts = self.accounts.lock();
+        let entry = accounts
+            .entry(user_id)
+            .or_default()
+            .errors
+            .entry(signature.clone())
+            .or_insert_with(|| ErrorSignature {
+                signature,
+                kind: kind.to_string(),
+                key: error_key(message),
+                count: 0,
+            });
+        entry.count += 1;
+    }
+
+    /// Count a usage event of an account
+    pub fn record_usage(&self, user_id: Uuid, metric: &str) {
+        let mut accounts = self.accounts.lock();
+        *accounts.entry(user_id).or_default().usage.entry(metric.to_string()).or_insert(0) += 1;
+    }
+
+    /// Payload the given accounts would contribute now, without resetting counters
+    pub fn preview(&self, user_ids: &HashSet<Uuid>) -> TelemetryPayload {
+        let accounts = self.accounts.lock();
+        let selected = accounts.iter().filter(|(id, _)| user_ids.contains(id)).map(|(_, a)| a);
+        merge(*self.period_start.lock(), selected)
+    }
+
+    /// Build the payload of the opted-in accounts and reset all counters
+    pub fn take(&self, opted_in: &HashSet<Uuid>) -> TelemetryPayload {
+        let drained: Vec<(Uuid, AccountTelemetry)> = self.accounts.lock().drain().collect();
+        let period_start = std::mem::replace(&mut *self.period_start.lock(), Utc::now());
+        let selected = drained.iter().filter(|(id, _)| opted_in.contains(id)).map(|(_, a)| a);
+        merge(period_start, selected)
+    }
+}
+
+impl Default for TelemetryCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn merge<'a>(period_start: DateTime<Utc>, accounts: impl Iterator<Item = &'a AccountTelemetry>) -> TelemetryPayload {
+    let mut errors: BTreeMap<String, ErrorSignature> = BTreeMap::new();
+    let mut usage: BTreeMap<String, u64> = BTreeMap::new();
+    for account in accounts {
+        for (signature, error) in &account.errors {
+            errors
+                .entry(signature.clone())
+                .and_modify(|e| e.count += error.count)
+                .or_insert_with(|| error.clone());
+        }
+        for (metric, count) in &account.usage {
+            *usage.entry(metric.clone()).or_insert(0) += count;
+        }
+    }
+
+    TelemetryPayload {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        period_start,
+        period_end: Utc::now(),
+        errors: errors.into_values().collect(),
+        usage,
+    }
+}
+
+// Global instance for easy access
+lazy_static::lazy_static! {
+    pub static ref TELEMETRY: TelemetryCollector = TelemetryCollector::new();
+}
+
+/// Combine the payloads of several workers into one
+pub fn combine(payloads: Vec<TelemetryPayload>) -> TelemetryPayload {
+    let now = Utc::now();
+    let mut errors: BTreeMap<String, ErrorSignature> = BTreeMap::new();
+    let mut usage: BTreeMap<String, u64> = BTreeMap::new();
+    let mut period_start = now;
+    let mut period_end = None;
+    for payload in payloads {
+        period_start = period_start.min(payload.period_start);
+        period_end = period_end.max(Some(payload.period_end));
+        for error in payload.errors {
+            errors
+                .entry(error.signature.clone())
+                .and_modify(|e| e.count += error.count)
+                .or_insert(error);
+        }
+        for (metric, count) in payload.usage {
+            *usage.entry(metric).or_insert(0) += count;
+        }
+    }
+
+    TelemetryPayload {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        period_start,
+        period_end: period_end.unwrap_or(now),
+        errors: errors.into_values().collect(),
+        usage,
+    }
+}
+
+/// Take this worker's payload of opted-in accounts, resetting its counters
+async fn take_opted_in(pool: &PgPool) -> Result<TelemetryPayload> {
+    let opted_in: HashSet<Uuid> = queries::telemetry::list_opted_in_users(pool).await?.into_iter().collect();
+    Ok(TELEMETRY.take(&opted_in))
+}
+
+/// Answer the flushing worker's collection requests with this worker's payload
+pub async fn serve_collect(client: Client, pool: PgPool) -> Result<()> {
+    let mut subscriber = client.subscribe(subjects::internal::TELEMETRY_COLLECT).await?;
+    while let Some(msg) = subscriber.next().await {
+        let Some(reply) = msg.reply else {
+            continue;
+        };
+        let payload = match take_opted_in(&pool).await {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to collect telemetry, period dropped: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = client.publish(reply, serde_json::to_vec(&payload)?.into()).await {
+            warn!("Failed to hand over telemetry, period dropped: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Ask every worker for its payload and gather the replies
+async fn collect(client: &Client) -> Result<Vec<TelemetryPayload>> {
+    let inbox = client.new_inbox();
+    let mut replies = client.subscribe(inbox.clone()).await?;
+    client
+        .publish_with_reply(subjects::internal::TELEMETRY_COLLECT, inbox, Vec::new().into())
+        .await?;
+
+    let mut payloads = Vec::new();
+    let window = tokio::time::sleep(COLLECT_WINDOW);
+    tokio::pin!(window);
+    loop {
+        tokio::select! {
+            msg = replies.next() => match msg {
+                Some(msg) => match serde_json::from_slice(&msg.payload) {
+                    Ok(payload) => payloads.push(payload),
+                    Err(e) => warn!("Invalid telemetry payload from a worker: {}", e),
+                },
+                None => break,
+            },
+            _ = &mut window => break,
+        }
+    }
+    Ok(payloads)
+}
+
+/// Post collected data of opted-in accounts of all workers to the endpoint once
+pub async fn flush(client: &Client, http: &reqwest::Client, endpoint: &str) -> Result<usize> {
+    let payload = combine(collect(client).await?);
+    if payload.is_empty() {
+        return Ok(0);
+    }
+
+    let count = payload.errors.len() + payload.usage.len();
+    http.post(endpoint).json(&payload).send().await?.error_for_status()?;
+    Ok(count)
+}
+
+/// Background loop flushing telemetry, run on one worker. Without an
+/// endpoint nothing is ever sent.
+pub async fn run_scheduler(client: Client, endpoint: Option<String>) {
+    let Some(endpoint) = endpoint else {
+        info!("Telemetry endpoint not configured, telemetry disabled");
+        return;
+    };
+    info!("Telemetry scheduler started ({})", endpoint);
+
+    let http = http::client(HttpService::Telemetry);
+    let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+    // The first tick completes immediately — skip it so a full period is collected
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+
+        match flush(&client, &http, &endpoint).await {
+            Ok(0) => debug!("No telemetry to send"),
+            Ok(count) => info!("Sent {} telemetry entries", count),
+            Err(e) => warn!("Failed to send telemetry, period dropped: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_key() {
+        assert_eq!(error_key("import:csv_empty").as_deref(), Some("import:csv_empty"));
+        assert_eq!(
+            error_key(r#"{"key": "import:csv_parse_error", "params": {"error": "Jan Novák"}}"#).as_deref(),
+            Some("import:csv_parse_error")
+        );
+        assert_eq!(error_key("Customer Jan Novák not found"), None);
+        assert_eq!(error_key("error: connection refused"), None);
+    }
+
+    #[test]
+    fn test_normalize_error_message() {
+        assert_eq!(
+            normalize_error_message("visit 5f0c-a1b2 not found (row 12)"),
+            "visit # not found (row #)"
+        );
+    }
+
+    #[test]
+    fn test_signature_ignores_numbers() {
+        assert_eq!(
+            error_signature("import.customer", "row 12 failed"),
+            error_signature("import.customer", "row 873 failed")
+        );
+        assert_ne!(
+            error_signature("import.customer", "row 12 failed"),
+            error_signature("import.device", "row 12 failed")
+        );
+    }
+
+    #[test]
+    fn test_take_only_includes_opted_in_accounts() {
+        let collector = TelemetryCollector::new();
+        let opted_in = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        collector.record_error(opted_in, "import.customer", "import:csv_empty");
+        collector.record_error(opted_in, "import.customer", "import:csv_empty");
+        collector.record_error(other, "import.customer", "import:csv_empty");
+        collector.record_usage(opted_in, "jobs.import.customer");
+        collector.record_usage(other, "jobs.export");
+
+        let ids: HashSet<Uuid> = [opted_in].into_iter().collect();
+        let payload = collector.take(&ids);
+        assert_eq!(payload.errors.len(), 1);
+        assert_eq!(payload.errors[0].count, 2);
+        assert_eq!(payload.usage.get("jobs.import.customer"), Some(&1));
+        assert!(!payload.usage.contains_key("jobs.export"));
+
+        // Counters of all accounts are reset
+        let all: HashSet<Uuid> = [opted_in, other].into_iter().collect();
+        assert!(collector.take(&all).is_empty());
+    }
+
+    #[test]
+    fn test_combine_sums_worker_payloads() {
+        let first = TelemetryCollector::new();
+        let second = TelemetryCollector::new();
+        let user = Uuid::new_v4();
+        first.record_error(user, "import.customer", "import:csv_empty");
+        first.record_usage(user, "jobs.export");
+        second.record_error(user, "import.customer", "import:csv_empty");
+        second.record_usage(user, "jobs.export");
+        second.record_usage(user, "jobs.import.customer");
+
+        let ids: HashSet<Uuid> = [user].into_iter().collect();
+        let (a, b) = (first.take(&ids), second.take(&ids));
+        let period_start = a.period_start.min(b.period_start);
+        let combined = combine(vec![a, b]);
+        assert_eq!(combined.period_start, period_start);
+        assert_eq!(combined.errors.len(), 1);
+        assert_eq!(combined.errors[0].count, 2);
+        assert_eq!(combined.usage.get("jobs.export"), Some(&2));
+        assert_eq!(combined.usage.get("jobs.import.customer"), Some(&1));
+
+        assert!(combine(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_preview_does_not_reset() {
+        let collector = TelemetryCollector::new();
+        let user = Uuid::new_v4();
+        collector.record_usage(user, "jobs.export");
+        let ids: HashSet<Uuid> = [user].into_iter().collect();
+        assert_eq!(collector.preview(&ids).usage.get("jobs.export"), Some(&1));
+        assert_eq!(collector.preview(&ids).usage.get("jobs.export"), Some(&1));
+    }
+}
@@ -38,7 +38,7 @@ pub fn render_template_html(template: &str, vars: &TemplateVars<'_>) -> String {
 }
 
 /// Escape the five XML/HTML special characters in `s`.
-fn html_escape(s: &str) -> String {
+pub(crate) fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
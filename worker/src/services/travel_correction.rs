@@ -0,0 +1,313 @@
+#![allow(dead_code)]
+//! Learned travel time correction
+//!
+//! Routing durations come without traffic data, so town centres at rush
+//! hour are underestimated while open roads are not. Actual leg times of
+//! past routes are grouped per zone (0.1° grid cell) and time band; cells
+//! with enough legs get their own multiplicative factor. Planning applies
+//! the most specific factor available for a leg: zone and time band, then
+//! the zone's average, then the account-wide factor.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{NaiveTime, Utc};
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::route_analysis;
+use crate::services::routing::DistanceTimeMatrices;
+use crate::types::route_analysis::{
+    LegDeviation, RouteAnalysisResponse, TimeBand, TravelTimeFactor, ZoneCell, TRAVEL_FACTOR_MAX,
+    TRAVEL_FACTOR_MIN,
+};
+use crate::types::Coordinates;
+
+/// Legs a zone and time band needs before it gets its own factor
+pub const MIN_LEGS_PER_CELL: usize = 8;
+/// Past routes the periodic re-learning looks at
+const LEARNING_WINDOW_DAYS: i64 = 90;
+const RELEARN_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Zone and time band factors from measured legs
+pub fn learn(legs: &[LegDeviation]) -> Vec<TravelTimeFactor> {
+    let mut cells: HashMap<(ZoneCell, TimeBand), (usize, i64, i64)> = HashMap::new();
+    for leg in legs {
+        let Some(zone) = leg.zone else { continue };
+        let cell = cells.entry((zone, leg.time_band)).or_default();
+        cell.0 += 1;
+        cell.1 += leg.planned_minutes;
+        cell.2 += leg.actual_minutes;
+    }
+
+    let now = Utc::now();
+    let mut factors: Vec<TravelTimeFactor> = cells
+        .into_iter()
+        .filter(|(_, (count, planned, _))| *count >= MIN_LEGS_PER_CELL && *planned > 0)
+        .map(|((zone, band), (count, planned, actual))| {
+            let raw_ratio = actual as f64 / planned as f64;
+            TravelTimeFactor {
+                zone_lat: zone.lat,
+                zone_lng: zone.lng,
+                time_band: band.as_str().to_string(),
+                factor: (raw_ratio.clamp(TRAVEL_FACTOR_MIN, TRAVEL_FACTOR_MAX) * 100.0).round() / 100.0,
+                raw_ratio,
+                legs: count as i32,
+                updated_at: now,
+            }
+        })
+        .collect();
+    factors.sort_by_key(|f| (f.zone_lat, f.zone_lng, f.time_band.clone()));
+    factors
+}
+
+/// Correction applied to routing durations of one account
+#[derive(Debug, Clone, Default)]
+pub struct TravelTimeModel {
+    global: Option<f64>,
+    cells: HashMap<(ZoneCell, TimeBand), f64>,
+    /// Leg-weighted average of a zone's time bands
+    zones: HashMap<ZoneCell, f64>,
+}
+
+impl TravelTimeModel {
+    pub fn new(global: Option<f64>, factors: &[TravelTimeFactor]) -> Self {
+        let mut cells = HashMap::new();
+        let mut weighted: HashMap<ZoneCell, (f64, i32)> = HashMap::new();
+        for f in factors {
+            let Some(band) = TimeBand::parse(&f.time_band) else { continue };
+            cells.insert((f.cell(), band), f.factor);
+            let zone = weighted.entry(f.cell()).or_default();
+            zone.0 += f.factor * f.legs as f64;
+            zone.1 += f.legs;
+        }
+        let zones = weighted
+            .into_iter()
+            .filter(|(_, (_, legs))| *legs > 0)
+            .map(|(zone, (sum, legs))| (zone, sum / legs as f64))
+            .collect();
+
+        Self { global, cells, zones }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.global.is_none() && self.cells.is_empty()
+    }
+
+    pub fn zone_factor_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Factor of a leg; without a departure time the zone's average is used
+    pub fn factor(&self, from: Coordinates, to: Coordinates, departure: Option<NaiveTime>) -> f64 {
+        let zone = ZoneCell::of_leg(from, to);
+        departure
+            .and_then(|time| self.cells.get(&(zone, TimeBand::from_time(time))))
+            .or_else(|| self.zones.get(&zone))
+            .or(self.global.as_ref())
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    /// Scale every duration of `matrices`; locations are in matrix order
+    pub fn apply_to_matrix(&self, matrices: &mut DistanceTimeMatrices, locations: &[Coordinates]) {
+        if self.is_empty() {
+            return;
+        }
+        for (i, row) in matrices.durations.iter_mut().enumerate() {
+            for (j, duration) in row.iter_mut().enumerate() {
+                if let (Some(&from), Some(&to)) = (locations.get(i), locations.get(j)) {
+                    *duration = (*duration as f64 * self.factor(from, to, None)).round() as u64;
+                }
+            }
+        }
+    }
+
+    /// Rescale legs of a fixed sequence from the uncorrected `raw` durations
+    /// using the time band each leg departs in
+    pub fn apply_to_legs(
+        &self,
+        matrices: &mut DistanceTimeMatrices,
+        raw: &DistanceTimeMatrices,
+        locations: &[Coordinates],
+        legs: &[(usize, usize, NaiveTime)],
+    ) {
+        for &(from, to, departure) in legs {
+            let factor = self.factor(locations[from], locations[to], Some(departure));
+            matrices.durations[from][to] = (raw.durations[from][to] as f64 * factor).round() as u64;
+        }
+    }
+}
+
+/// Correction of the account; empty while correction is disabled
+pub async fn load_model(pool: &PgPool, user_id: Uuid) -> Result<TravelTimeModel> {
+    let Some(global) = queries::route_analysis::get_travel_time_factor(pool, user_id).await? else {
+        return Ok(TravelTimeModel::default());
+    };
+    let factors = queries::route_analysis::list_travel_time_factors(pool, user_id).await?;
+
+    Ok(TravelTimeModel::new(Some(global), &factors))
+}
+
+/// Store the correction backed by an analysis. Returns false when the
+/// analysis has too few legs to suggest one.
+pub async fn store(pool: &PgPool, user_id: Uuid, analysis: &RouteAnalysisResponse) -> Result<bool> {
+    let Some(global) = analysis.suggested_factor else {
+        return Ok(false);
+    };
+    let factors = learn(&analysis.legs);
+    queries::route_analysis::set_travel_time_factor(pool, user_id, Some(global)).await?;
+    queries::route_analysis::replace_travel_time_factors(pool, user_id, &factors).await?;
+
+    info!(
+        "Travel time correction for user {}: global {}, {} zone factors ({} capped)",
+        user_id,
+        global,
+        factors.len(),
+        factors.iter().filter(|f| f.capped()).count()
+    );
+    Ok(true)
+}
+
+/// Turn the correction off and forget the learned factors
+pub async fn clear(pool: &PgPool, user_id: Uuid) -> Result<()> {
+    queries::route_analysis::set_travel_time_factor(pool, user_id, None).await?;
+    queries::route_analysis::replace_travel_time_factors(pool, user_id, &[]).await?;
+    info!("Travel time correction removed for user {}", user_id);
+    Ok(())
+}
+
+/// Re-learn the correction of every account that has it enabled from
+/// their recent routes
+pub async fn relearn_all(pool: &PgPool) -> Result<usize> {
+    let today = Utc::now().date_naive();
+    let from = today - chrono::Duration::days(LEARNING_WINDOW_DAYS);
+
+    let mut updated = 0;
+    for user_id in queries::route_analysis::list_users_with_travel_correction(pool).await? {
+        let result = async {
+            let rows = queries::route_analysis::list_stop_actuals(pool, user_id, from, today, None).await?;
+            store(pool, user_id, &route_analysis::analyze(&rows)).await
+        }
+        .await;
+        match result {
+            Ok(true) => updated += 1,
+            // Too little recent data: keep what was learned before
+            Ok(false) => {}
+            Err(e) => error!("Failed to re-learn travel time correction for user {}: {}", user_id, e),
+        }
+    }
+
+    Ok(updated)
+}
+
+/// Background loop re-learning the corrections daily
+pub async fn run_scheduler(pool: PgPool) {
+    let mut ticker = tokio::time::interval(RELEARN_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        match relearn_all(&pool).await {
+            Ok(0) => {}
+            Ok(count) => info!("Re-learned travel time correction for {} accounts", count),
+            Err(e) => error!("Failed to re-learn travel time corrections: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    const BRNO: Coordinates = Coordinates { lat: 49.195, lng: 16.608 };
+    const BRNO_EAST: Coordinates = Coordinates { lat: 49.198, lng: 16.65 };
+    const ZLIN: Coordinates = Coordinates { lat: 49.226, lng: 17.667 };
+
+    fn leg(zone: ZoneCell, band: TimeBand, planned: i64, actual: i64) -> LegDeviation {
+        LegDeviation {
+            route_id: Uuid::nil(),
+            date: NaiveDate::from_ymd_opt(2025, 3, 10).unwrap(),
+            to_stop_order: 1,
+            planned_minutes: planned,
+            actual_minutes: actual,
+            distance_km: None,
+            band: None,
+            zone: Some(zone),
+            time_band: band,
+        }
+    }
+
+    fn at(hour: u32) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(hour, 0, 0)
+    }
+
+    #[test]
+    fn test_learn_needs_enough_legs_per_cell() {
+        let zone = ZoneCell::of_leg(BRNO, BRNO_EAST);
+        let mut legs: Vec<LegDeviation> = (0..MIN_LEGS_PER_CELL).map(|_| leg(zone, TimeBand::MorningPeak, 10, 13)).collect();
+        legs.extend((0..MIN_LEGS_PER_CELL - 1).map(|_| leg(zone, TimeBand::Midday, 10, 10)));
+
+        let factors = learn(&legs);
+
+        assert_eq!(factors.len(), 1);
+        assert_eq!(factors[0].time_band, "morning_peak");
+        assert_eq!(factors[0].factor, 1.3);
+        assert_eq!(factors[0].legs, MIN_LEGS_PER_CELL as i32);
+        assert!(!factors[0].capped());
+    }
+
+    #[test]
+    fn test_learn_caps_extreme_ratios() {
+        let zone = ZoneCell::of_leg(BRNO, BRNO_EAST);
+        let legs: Vec<LegDeviation> = (0..MIN_LEGS_PER_CELL).map(|_| leg(zone, TimeBand::AfternoonPeak, 10, 25)).collect();
+
+        let factors = learn(&legs);
+
+        assert_eq!(factors[0].factor, TRAVEL_FACTOR_MAX);
+        assert_eq!(factors[0].raw_ratio, 2.5);
+        assert!(factors[0].capped());
+    }
+
+    #[test]
+    fn test_model_prefers_most_specific_factor() {
+        let zone = ZoneCell::of_leg(BRNO, BRNO_EAST);
+        let mut legs: Vec<LegDeviation> = (0..8).map(|_| leg(zone, TimeBand::MorningPeak, 10, 14)).collect();
+        legs.extend((0..24).map(|_| leg(zone, TimeBand::Midday, 10, 10)));
+        let model = TravelTimeModel::new(Some(1.1), &learn(&legs));
+
+        assert_eq!(model.factor(BRNO, BRNO_EAST, at(7)), 1.4);
+        assert_eq!(model.factor(BRNO, BRNO_EAST, at(11)), 1.0);
+        // No factor for the evening: the zone's leg-weighted average
+        assert!((model.factor(BRNO, BRNO_EAST, at(20)) - 1.1).abs() < 1e-9);
+        // Outside any learned zone: the account-wide factor
+        assert_eq!(model.factor(BRNO, ZLIN, at(7)), 1.1);
+        assert_eq!(TravelTimeModel::default().factor(BRNO, ZLIN, at(7)), 1.0);
+    }
+
+    #[test]
+    fn test_apply_to_matrix_and_legs() {
+        let zone = ZoneCell::of_leg(BRNO, BRNO_EAST);
+        let legs: Vec<LegDeviation> = (0..8).map(|_| leg(zone, TimeBand::MorningPeak, 10, 15)).collect();
+        let model = TravelTimeModel::new(Some(1.2), &learn(&legs));
+        let locations = [BRNO, BRNO_EAST];
+        let raw = DistanceTimeMatrices {
+            distances: vec![vec![0, 3000], vec![3000, 0]],
+            durations: vec![vec![0, 600], vec![600, 0]],
+            size: 2,
+        };
+
+        let mut matrices = raw.clone();
+        model.apply_to_matrix(&mut matrices, &locations);
+        assert_eq!(matrices.durations, vec![vec![0, 900], vec![900, 0]]);
+
+        // Leaving at midday: no band factor, the zone average is the only one learned
+        model.apply_to_legs(&mut matrices, &raw, &locations, &[(0, 1, at(12).unwrap())]);
+        assert_eq!(matrices.durations[0][1], 900);
+        assert_eq!(matrices.distances, raw.distances);
+    }
+}
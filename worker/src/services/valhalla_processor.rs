@@ -14,12 +14,14 @@
 use std::sync::Arc;
 use anyhow::Result;
 use async_nats::Client;
-use async_nats::jetstream::{self, Context as JsContext};
+use async_nats::jetstream;
 use futures::StreamExt;
 use tracing::{info, warn, error};
 use uuid::Uuid;
 
 use crate::services::routing::{ValhallaClient, ValhallaConfig, RoutingService};
+use crate::subjects;
+use crate::transport::{JobMessage, JobQueue};
 use crate::types::{
     Coordinates,
     MatrixJobRequest, MatrixJobStatus, MatrixJobStatusUpdate,
@@ -31,25 +33,25 @@ use crate::types::{
 // Stream and consumer names
 const MATRIX_STREAM_NAME: &str = "SAZINKA_ROUTING_MATRIX_JOBS";
 const MATRIX_CONSUMER_NAME: &str = "matrix_workers";
-const MATRIX_SUBJECT: &str = "sazinka.jobs.valhalla.matrix";
-const MATRIX_STATUS_PREFIX: &str = "sazinka.job.valhalla.matrix.status";
+const MATRIX_SUBJECT: &str = subjects::jobs::VALHALLA_MATRIX;
+const MATRIX_STATUS_PREFIX: &str = subjects::job::VALHALLA_MATRIX_STATUS;
 
 const GEOMETRY_STREAM_NAME: &str = "SAZINKA_ROUTING_GEOMETRY_JOBS";
 const GEOMETRY_CONSUMER_NAME: &str = "geometry_workers";
-const GEOMETRY_SUBJECT: &str = "sazinka.jobs.valhalla.geometry";
-const GEOMETRY_STATUS_PREFIX: &str = "sazinka.job.valhalla.geometry.status";
+const GEOMETRY_SUBJECT: &str = subjects::jobs::VALHALLA_GEOMETRY;
+const GEOMETRY_STATUS_PREFIX: &str = subjects::job::VALHALLA_GEOMETRY_STATUS;
 
 /// Valhalla job processor with JetStream integration
 pub struct ValhallaProcessor {
     client: Client,
-    js: JsContext,
+    queue: JobQueue,
     valhalla: ValhallaClient,
 }
 
 impl ValhallaProcessor {
     /// Create a new Valhalla processor, initializing JetStream streams
     pub async fn new(client: Client, valhalla_url: &str) -> Result<Self> {
-        let js = jetstream::new(client.clone());
+        let queue = JobQueue::new(client.clone());
         
         // Create matrix stream
         let matrix_stream_config = jetstream::stream::Config {
@@ -60,7 +62,7 @@ impl ValhallaProcessor {
             retention: jetstream::stream::RetentionPolicy::WorkQueue,
             ..Default::default()
         };
-        js.get_or_create_stream(matrix_stream_config).await?;
+        queue.ensure_stream(matrix_stream_config).await?;
         info!("JetStream Valhalla matrix stream '{}' ready", MATRIX_STREAM_NAME);
         
         // Create geometry stream
@@ -72,7 +74,7 @@ impl ValhallaProcessor {
             retention: jetstream::stream::RetentionPolicy::WorkQueue,
             ..Default::default()
         };
-        js.get_or_create_stream(geometry_stream_config).await?;
+        queue.ensure_stream(geometry_stream_config).await?;
         info!("JetStream Valhalla geometry stream '{}' ready", GEOMETRY_STREAM_NAME);
         
         // Create Valhalla client
@@ -81,7 +83,7 @@ impl ValhallaProcessor {
         
         Ok(Self {
             client,
-            js,
+            queue,
             valhalla,
         })
     }
@@ -98,7 +100,7 @@ impl ValhallaProcessor {
         
         // Publish to JetStream
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish(MATRIX_SUBJECT, payload.into()).await?.await?;
+        self.queue.publish(MATRIX_SUBJECT, payload.into()).await?;
         
         info!("Matrix job {} submitted with {} locations", job_id, job.request.locations.len());
         
@@ -114,7 +116,7 @@ impl ValhallaProcessor {
     /// Publish a matrix job status update
     pub async fn publish_matrix_status(&self, job_id: Uuid, status: MatrixJobStatus) -> Result<()> {
         let update = MatrixJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", MATRIX_STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(MATRIX_STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         
         self.client.publish(subject, payload.into()).await?;
@@ -123,8 +125,6 @@ impl ValhallaProcessor {
     
     /// Start processing matrix jobs from the queue
     pub async fn start_matrix_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(MATRIX_STREAM_NAME).await?;
-        
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(MATRIX_CONSUMER_NAME.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
@@ -132,11 +132,9 @@ impl ValhallaProcessor {
             ..Default::default()
         };
         
-        let consumer = stream.get_or_create_consumer(MATRIX_CONSUMER_NAME, consumer_config).await?;
+        let mut messages = self.queue.consume(MATRIX_STREAM_NAME, consumer_config).await?;
         info!("JetStream Valhalla matrix consumer '{}' ready", MATRIX_CONSUMER_NAME);
         
-        let mut messages = consumer.messages().await?;
-        
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -157,7 +155,7 @@ impl ValhallaProcessor {
     }
     
     /// Process a single matrix job
-    async fn process_matrix_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_matrix_job(&self, msg: JobMessage) -> Result<()> {
         let job: QueuedMatrixJob = serde_json::from_slice(&msg.payload)?;
         let job_id = job.id;
         let locations = &job.request.locations;
@@ -196,7 +194,7 @@ impl ValhallaProcessor {
                 info!("Matrix job {} completed: {}x{} matrix", job_id, matrices.size, matrices.size);
             }
             Err(e) => {
-                let delivery_count = msg.info().map(|i| i.delivered).unwrap_or(0);
+                let delivery_count = msg.delivered();
                 warn!("Matrix job {} failed (attempt {}): {}", job_id, delivery_count, e);
                 
                 // Publish failed status
@@ -233,7 +231,7 @@ impl ValhallaProcessor {
         
         // Publish to JetStream
         let payload = serde_json::to_vec(&job)?;
-        self.js.publish(GEOMETRY_SUBJECT, payload.into()).await?.await?;
+        self.queue.publish(GEOMETRY_SUBJECT, payload.into()).await?;
         
         info!("Geometry job {} submitted with {} locations", job_id, job.request.locations.len());
         
@@ -249,7 +247,7 @@ impl ValhallaProcessor {
     /// Publish a geometry job status update
     pub async fn publish_geometry_status(&self, job_id: Uuid, status: GeometryJobStatus) -> Result<()> {
         let update = GeometryJobStatusUpdate::new(job_id, status);
-        let subject = format!("{}.{}", GEOMETRY_STATUS_PREFIX, job_id);
+        let subject = subjects::job_status(GEOMETRY_STATUS_PREFIX, job_id);
         let payload = serde_json::to_vec(&update)?;
         
         self.client.publish(subject, payload.into()).await?;
@@ -258,8 +256,6 @@ impl ValhallaProcessor {
     
     /// Start processing geometry jobs from the queue
     pub async fn start_geometry_processing(self: Arc<Self>) -> Result<()> {
-        let stream = self.js.get_stream(GEOMETRY_STREAM_NAME).await?;
-        
         let consumer_config = jetstream::consumer::pull::Config {
             durable_name: Some(GEOMETRY_CONSUMER_NAME.to_string()),
             ack_policy: jetstream::consumer::AckPolicy::Explicit,
@@ -267,11 +263,9 @@ impl ValhallaProcessor {
             ..Default::default()
         };
         
-        let consumer = stream.get_or_create_consumer(GEOMETRY_CONSUMER_NAME, consumer_config).await?;
+        let mut messages = self.queue.consume(GEOMETRY_STREAM_NAME, consumer_config).await?;
         info!("JetStream Valhalla geometry consumer '{}' ready", GEOMETRY_CONSUMER_NAME);
         
-        let mut messages = consumer.messages().await?;
-        
         while let Some(msg) = messages.next().await {
             match msg {
                 Ok(msg) => {
@@ -292,7 +286,7 @@ impl ValhallaProcessor {
     }
     
     /// Process a single geometry job
-    async fn process_geometry_job(&self, msg: jetstream::Message) -> Result<()> {
+    async fn process_geometry_job(&self, msg: JobMessage) -> Result<()> {
         let job: QueuedGeometryJob = serde_json::from_slice(&msg.payload)?;
         let job_id = job.id;
         let locations = &job.request.locations;
@@ -333,7 +327,7 @@ impl ValhallaProcessor {
                 info!("Geometry job {} completed", job_id);
             }
             Err(e) => {
-                let delivery_count = msg.info().map(|i| i.delivered).unwrap_or(0);
+                let delivery_count = msg.delivered();
                 warn!("Geometry job {} failed (attempt {}): {}", job_id, delivery_count, e);
                 
                 // Publish failed status
@@ -0,0 +1,287 @@
+//! Monthly VAT summary of billed work items
+//!
+//! Aggregates net amount, VAT and gross per (month, currency, VAT rate,
+//! payment method) across completed visits; amounts in different currencies
+//! are never summed together. VAT is rounded per work item, the way it is on
+//! the receipts handed to customers.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use rust_xlsxwriter::{Format, Workbook};
+use uuid::Uuid;
+
+use crate::types::currency::{currency_symbol, DEFAULT_CURRENCY};
+use crate::types::work_item::VisitWorkItem;
+use crate::types::VisitWithCustomer;
+
+/// Header row shared by the CSV and XLSX outputs
+pub const VAT_SUMMARY_HEADERS: &[&str] = &[
+    "month", "currency", "vat_rate", "payment_method", "item_count", "net", "vat", "gross",
+];
+
+/// One aggregated line of the summary (amounts in minor units)
+#[derive(Debug, Clone, PartialEq)]
+pub struct VatSummaryRow {
+    /// `YYYY-MM`
+    pub month: String,
+    /// ISO 4217 code
+    pub currency: String,
+    pub vat_rate: Option<i32>,
+    pub payment_method: Option<String>,
+    pub item_count: u32,
+    pub net_minor: i64,
+    pub vat_minor: i64,
+    pub gross_minor: i64,
+}
+
+impl VatSummaryRow {
+    /// Row cells in `VAT_SUMMARY_HEADERS` order
+    pub fn cells(&self) -> Vec<String> {
+        vec![
+            self.month.clone(),
+            self.currency.clone(),
+            self.vat_rate.map(|r| r.to_string()).unwrap_or_default(),
+            self.payment_method.clone().unwrap_or_default(),
+            self.item_count.to_string(),
+            format_minor(self.net_minor),
+            format_minor(self.vat_minor),
+            format_minor(self.gross_minor),
+        ]
+    }
+}
+
+/// VAT for one line, rounded half away from zero to whole minor units
+pub fn line_vat(net_minor: i64, vat_rate: Option<i32>) -> i64 {
+    let vat_hundredths = net_minor * vat_rate.unwrap_or(0) as i64;
+    (vat_hundredths + 50 * vat_hundredths.signum()) / 100
+}
+
+/// Format minor units as a decimal string ("1234.50")
+pub fn format_minor(minor: i64) -> String {
+    let sign = if minor < 0 { "-" } else { "" };
+    let abs = minor.abs();
+    format!("{}{}.{:02}", sign, abs / 100, abs % 100)
+}
+
+/// Aggregate billed work items of completed visits
+pub fn build_vat_summary(visits: &[VisitWithCustomer], work_items: &[VisitWorkItem]) -> Vec<VatSummaryRow> {
+    let months: std::collections::HashMap<Uuid, String> = visits
+        .iter()
+        .filter(|v| v.status == "completed")
+        .map(|v| (v.id, v.scheduled_date.format("%Y-%m").to_string()))
+        .collect();
+
+    type GroupKey = (String, String, Option<i32>, Option<String>);
+    let mut groups: BTreeMap<GroupKey, VatSummaryRow> = BTreeMap::new();
+    for wi in work_items {
+        let (Some(month), Some(net)) = (months.get(&wi.visit_id), wi.amount_minor) else {
+            continue;
+        };
+        let currency = wi.currency.clone().unwrap_or_else(|| DEFAULT_CURRENCY.to_string());
+        let key = (month.clone(), currency.clone(), wi.vat_rate, wi.payment_method.clone());
+        let row = groups.entry(key).or_insert_with(|| VatSummaryRow {
+            month: month.clone(),
+            currency,
+            vat_rate: wi.vat_rate,
+            payment_method: wi.payment_method.clone(),
+            item_count: 0,
+            net_minor: 0,
+            vat_minor: 0,
+            gross_minor: 0,
+        });
+        let vat = line_vat(net, wi.vat_rate);
+        row.item_count += 1;
+        row.net_minor += net;
+        row.vat_minor += vat;
+        row.gross_minor += net + vat;
+    }
+
+    groups.into_values().collect()
+}
+
+/// XLSX number format showing the currency symbol after the amount
+pub fn money_number_format(currency: &str) -> String {
+    format!("#,##0.00 \"{}\"", currency_symbol(currency))
+}
+
+/// Render the summary as an XLSX workbook
+pub fn render_vat_summary_xlsx(rows: &[VatSummaryRow]) -> Result<Vec<u8>> {
+    let mut workbook = Workbook::new();
+    let header = Format::new().set_bold();
+
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("VAT")?;
+    for (col, title) in VAT_SUMMARY_HEADERS.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *title, &header)?;
+    }
+
+    for (idx, row) in rows.iter().enumerate() {
+        let r = idx as u32 + 1;
+        let money = Format::new().set_num_format(money_number_format(&row.currency));
+        sheet.write_string(r, 0, &row.month)?;
+        sheet.write_string(r, 1, &row.currency)?;
+        if let Some(rate) = row.vat_rate {
+            sheet.write_number(r, 2, rate)?;
+        }
+        sheet.write_string(r, 3, row.payment_method.as_deref().unwrap_or_default())?;
+        sheet.write_number(r, 4, row.item_count)?;
+        sheet.write_number_with_format(r, 5, row.net_minor as f64 / 100.0, &money)?;
+        sheet.write_number_with_format(r, 6, row.vat_minor as f64 / 100.0, &money)?;
+        sheet.write_number_with_format(r, 7, row.gross_minor as f64 / 100.0, &money)?;
+    }
+    sheet.set_column_width(3, 16)?;
+    sheet.set_column_width(5, 14)?;
+    sheet.set_column_width(6, 14)?;
+    sheet.set_column_width(7, 14)?;
+
+    Ok(workbook.save_to_buffer()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::work_item::WorkType;
+    use chrono::Utc;
+
+    fn visit(status: &str, date: &str) -> VisitWithCustomer {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "userId": Uuid::nil(),
+            "customerId": Uuid::nil(),
+            "crewId": null,
+            "deviceId": null,
+            "scheduledDate": date,
+            "scheduledTimeStart": null,
+            "scheduledTimeEnd": null,
+            "status": status,
+            "visitType": "revision",
+            "actualArrival": null,
+            "actualDeparture": null,
+            "result": null,
+            "fieldNotes": null,
+            "requiresFollowUp": null,
+            "followUpReason": null,
+            "createdAt": Utc::now(),
+            "updatedAt": Utc::now(),
+            "customerName": null,
+            "customerStreet": null,
+            "customerCity": null,
+        }))
+        .unwrap()
+    }
+
+    fn item(visit_id: Uuid, amount: Option<i64>, rate: Option<i32>, method: &str) -> VisitWorkItem {
+        VisitWorkItem {
+            id: Uuid::new_v4(),
+            visit_id,
+            device_id: None,
+            revision_id: None,
+            crew_id: None,
+            work_type: WorkType::Revision,
+            duration_minutes: None,
+            result: None,
+            result_notes: None,
+            findings: None,
+            requires_follow_up: false,
+            follow_up_reason: None,
+            created_at: Utc::now(),
+            amount_minor: amount,
+            vat_rate: rate,
+            payment_method: Some(method.to_string()),
+            currency: amount.map(|_| "CZK".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_line_vat_rounds_per_line() {
+        assert_eq!(line_vat(100_000, Some(21)), 21_000);
+        assert_eq!(line_vat(1_234, Some(21)), 259); // 259.14
+        assert_eq!(line_vat(1_250, Some(12)), 150);
+        assert_eq!(line_vat(1_000, None), 0);
+    }
+
+    #[test]
+    fn test_line_vat_rounds_credits_away_from_zero() {
+        assert_eq!(line_vat(-150, Some(21)), -32); // -31.5
+        assert_eq!(line_vat(150, Some(21)), 32);
+        assert_eq!(line_vat(-1_234, Some(21)), -259); // -259.14
+    }
+
+    #[test]
+    fn test_format_minor() {
+        assert_eq!(format_minor(123_450), "1234.50");
+        assert_eq!(format_minor(5), "0.05");
+        assert_eq!(format_minor(-250), "-2.50");
+    }
+
+    #[test]
+    fn test_build_vat_summary_groups_by_month_rate_and_method() {
+        let march = visit("completed", "2026-03-10");
+        let march_b = visit("completed", "2026-03-25");
+        let april = visit("completed", "2026-04-02");
+        let planned = visit("planned", "2026-03-12");
+
+        let items = vec![
+            item(march.id, Some(100_000), Some(21), "cash"),
+            item(march_b.id, Some(50_000), Some(21), "cash"),
+            item(march.id, Some(20_000), Some(12), "card"),
+            item(april.id, Some(10_000), Some(21), "cash"),
+            item(planned.id, Some(99_999), Some(21), "cash"),
+            item(march.id, None, Some(21), "cash"),
+        ];
+
+        let rows = build_vat_summary(&[march, march_b, april, planned], &items);
+        assert_eq!(rows.len(), 3);
+
+        let cash_march = rows
+            .iter()
+            .find(|r| r.month == "2026-03" && r.vat_rate == Some(21))
+            .unwrap();
+        assert_eq!(cash_march.item_count, 2);
+        assert_eq!(cash_march.net_minor, 150_000);
+        assert_eq!(cash_march.vat_minor, 31_500);
+        assert_eq!(cash_march.gross_minor, 181_500);
+        assert_eq!(cash_march.cells()[1], "CZK");
+        assert_eq!(cash_march.cells()[5], "1500.00");
+
+        assert!(rows.iter().any(|r| r.month == "2026-04"));
+    }
+
+    #[test]
+    fn test_build_vat_summary_keeps_currencies_apart() {
+        let march = visit("completed", "2026-03-10");
+        let mut euro = item(march.id, Some(10_000), Some(21), "cash");
+        euro.currency = Some("EUR".to_string());
+        let items = vec![item(march.id, Some(100_000), Some(21), "cash"), euro];
+
+        let rows = build_vat_summary(&[march], &items);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].currency, "CZK");
+        assert_eq!(rows[0].net_minor, 100_000);
+        assert_eq!(rows[1].currency, "EUR");
+        assert_eq!(rows[1].net_minor, 10_000);
+    }
+
+    #[test]
+    fn test_money_number_format() {
+        assert_eq!(money_number_format("CZK"), "#,##0.00 \"Kč\"");
+        assert_eq!(money_number_format("EUR"), "#,##0.00 \"€\"");
+    }
+
+    #[test]
+    fn test_render_vat_summary_xlsx_is_zip() {
+        let rows = vec![VatSummaryRow {
+            month: "2026-03".into(),
+            currency: "EUR".into(),
+            vat_rate: Some(21),
+            payment_method: Some("cash".into()),
+            item_count: 1,
+            net_minor: 1000,
+            vat_minor: 210,
+            gross_minor: 1210,
+        }];
+        let bytes = render_vat_summary_xlsx(&rows).unwrap();
+        assert!(bytes.starts_with(b"PK"));
+    }
+}
@@ -0,0 +1,207 @@
+//! Visit duration tracking
+//!
+//! Splits the time a crew spends on a visit into travel (from its previous
+//! departure that day to the arrival) and on-site time (arrival to
+//! departure), recomputed whenever a progress event or a completion stamps
+//! the visit. On-site times of completed visits feed the learned service
+//! durations per visit type; travel and on-site minutes per crew make up the
+//! workload report used for payroll.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::types::report::{WorkloadReportResponse, WorkloadRow};
+use crate::types::visit_duration::{LearnedServiceDuration, VisitTimestamps, MAX_TRAVEL_MINUTES};
+
+/// Completed visits a visit type needs before its duration is learned
+pub const MIN_SAMPLES: usize = 5;
+/// Past visits the learned durations look at
+pub const LEARNING_WINDOW_DAYS: i64 = 180;
+/// On-site times outside this range are stamping mistakes, not samples
+const SAMPLE_RANGE_MINUTES: std::ops::RangeInclusive<i32> = 1..=480;
+
+fn minutes_between(from: DateTime<Utc>, to: DateTime<Utc>) -> Option<i64> {
+    let minutes = (to - from).num_seconds() as f64 / 60.0;
+    (minutes >= 0.0).then(|| minutes.round() as i64)
+}
+
+/// Travel and on-site minutes from a visit's progress timestamps. Travel is
+/// left empty for the day's first visit and after long breaks.
+pub fn split_durations(timestamps: &VisitTimestamps) -> (Option<i32>, Option<i32>) {
+    let Some(arrival) = timestamps.actual_arrival else {
+        return (None, None);
+    };
+    let travel = timestamps
+        .previous_departure
+        .and_then(|departure| minutes_between(departure, arrival))
+        .filter(|minutes| *minutes <= MAX_TRAVEL_MINUTES)
+        .map(|minutes| minutes as i32);
+    let on_site = timestamps
+        .actual_departure
+        .and_then(|departure| minutes_between(arrival, departure))
+        .map(|minutes| minutes as i32);
+    (travel, on_site)
+}
+
+/// Value at a fraction of sorted samples (nearest rank)
+fn percentile(sorted: &[i32], fraction: f64) -> i32 {
+    let rank = ((sorted.len() as f64 * fraction).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1]
+}
+
+/// Learned duration of one visit type, None with too few samples
+pub fn learn(visit_type: &str, samples: &[i32]) -> Option<LearnedServiceDuration> {
+    let mut sorted: Vec<i32> = samples.iter().copied().filter(|m| SAMPLE_RANGE_MINUTES.contains(m)).collect();
+    if sorted.len() < MIN_SAMPLES {
+        return None;
+    }
+    sorted.sort_unstable();
+    Some(LearnedServiceDuration {
+        visit_type: visit_type.to_string(),
+        samples: sorted.len(),
+        median_minutes: percentile(&sorted, 0.5),
+        p80_minutes: percentile(&sorted, 0.8),
+    })
+}
+
+/// Learned durations of all visit types with enough samples
+pub fn learn_all(samples: &[(String, i32)]) -> Vec<LearnedServiceDuration> {
+    let mut by_type: BTreeMap<&str, Vec<i32>> = BTreeMap::new();
+    for (visit_type, minutes) in samples {
+        by_type.entry(visit_type.as_str()).or_default().push(*minutes);
+    }
+    by_type.into_iter().filter_map(|(visit_type, minutes)| learn(visit_type, &minutes)).collect()
+}
+
+/// First day of the month of `to`
+pub fn default_from(to: NaiveDate) -> NaiveDate {
+    to.with_day(1).unwrap_or(to)
+}
+
+/// Workload report with a total row over all crews
+pub fn build_workload_report(from_date: NaiveDate, to_date: NaiveDate, crews: Vec<WorkloadRow>) -> WorkloadReportResponse {
+    let total = crews.iter().fold(WorkloadRow::default(), |total, row| WorkloadRow {
+        visits: total.visits + row.visits,
+        timed_visits: total.timed_visits + row.timed_visits,
+        travel_minutes: total.travel_minutes + row.travel_minutes,
+        on_site_minutes: total.on_site_minutes + row.on_site_minutes,
+        worked_minutes: total.worked_minutes + row.worked_minutes,
+        overridden_visits: total.overridden_visits + row.overridden_visits,
+        ..total
+    });
+    WorkloadReportResponse { from_date, to_date, crews, total }
+}
+
+/// Recompute a visit's durations from its progress timestamps, keeping
+/// overridden values
+pub async fn record(pool: &PgPool, user_id: Uuid, visit_id: Uuid) -> Result<()> {
+    let Some(timestamps) = queries::visit_duration::get_timestamps(pool, visit_id, user_id).await? else {
+        return Ok(());
+    };
+    let (travel, on_site) = split_durations(&timestamps);
+    queries::visit_duration::upsert_recorded(pool, visit_id, user_id, travel, on_site).await
+}
+
+/// Record durations without holding up the reply
+pub fn record_in_background(pool: &PgPool, user_id: Uuid, visit_id: Uuid) {
+    let pool = pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = record(&pool, user_id, visit_id).await {
+            warn!("Failed to record durations of visit {}: {}", visit_id, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 10, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_split_durations() {
+        let timestamps = VisitTimestamps {
+            actual_arrival: Some(at(9, 20)),
+            actual_departure: Some(at(10, 5)),
+            previous_departure: Some(at(8, 55)),
+        };
+        assert_eq!(split_durations(&timestamps), (Some(25), Some(45)));
+
+        // First visit of the day, still on site
+        let timestamps = VisitTimestamps { actual_arrival: Some(at(8, 0)), ..Default::default() };
+        assert_eq!(split_durations(&timestamps), (None, None));
+
+        // A lunch break is not travel
+        let timestamps = VisitTimestamps {
+            actual_arrival: Some(at(14, 0)),
+            actual_departure: Some(at(14, 30)),
+            previous_departure: Some(at(10, 0)),
+        };
+        assert_eq!(split_durations(&timestamps), (None, Some(30)));
+
+        // Departure stamped before arrival
+        let timestamps = VisitTimestamps {
+            actual_arrival: Some(at(9, 0)),
+            actual_departure: Some(at(8, 50)),
+            previous_departure: None,
+        };
+        assert_eq!(split_durations(&timestamps), (None, None));
+
+        assert_eq!(split_durations(&VisitTimestamps::default()), (None, None));
+    }
+
+    #[test]
+    fn test_learn() {
+        assert_eq!(learn("revision", &[30, 35, 40, 45]), None);
+        // Zero and all-day samples are dropped
+        assert_eq!(learn("revision", &[30, 35, 40, 45, 0, 900]), None);
+
+        let learned = learn("revision", &[50, 30, 35, 40, 45, 60, 0]).unwrap();
+        assert_eq!(learned.samples, 6);
+        assert_eq!(learned.median_minutes, 40);
+        assert_eq!(learned.p80_minutes, 50);
+    }
+
+    #[test]
+    fn test_learn_all_groups_by_type() {
+        let mut samples: Vec<(String, i32)> = (0..5).map(|i| ("revision".to_string(), 30 + i)).collect();
+        samples.extend((0..3).map(|i| ("repair".to_string(), 60 + i)));
+        let learned = learn_all(&samples);
+        assert_eq!(learned.len(), 1);
+        assert_eq!(learned[0].visit_type, "revision");
+        assert_eq!(learned[0].median_minutes, 32);
+    }
+
+    #[test]
+    fn test_build_workload_report_totals() {
+        let from = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 3, 31).unwrap();
+        assert_eq!(default_from(NaiveDate::from_ymd_opt(2026, 3, 17).unwrap()), from);
+
+        let row = |visits: i64, travel: i64, on_site: i64| WorkloadRow {
+            crew_id: Some(Uuid::new_v4()),
+            crew_name: Some("Crew".to_string()),
+            visits,
+            timed_visits: visits,
+            travel_minutes: travel,
+            on_site_minutes: on_site,
+            worked_minutes: travel + on_site,
+            overridden_visits: 1,
+        };
+        let report = build_workload_report(from, to, vec![row(4, 60, 180), row(2, 30, 90)]);
+        assert_eq!(report.crews.len(), 2);
+        assert_eq!(report.total.crew_id, None);
+        assert_eq!(report.total.visits, 6);
+        assert_eq!(report.total.worked_minutes, 360);
+        assert_eq!(report.total.overridden_visits, 2);
+    }
+}
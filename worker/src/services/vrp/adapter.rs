@@ -5,11 +5,15 @@ use serde_json::{json, Value};
 use vrp_pragmatic::format::problem::Matrix;
 
 use crate::services::routing::DistanceTimeMatrices;
-use super::{VrpProblem, StopTimeWindow};
+use super::{Load, VrpProblem, StopTimeWindow};
 
 pub const DEFAULT_PROFILE: &str = "car";
 pub const DEFAULT_VEHICLE_ID: &str = "vehicle_1";
 pub const DEFAULT_VEHICLE_TYPE: &str = "vehicle";
+/// Suffix of the extra job carrying the pickup of a stop that also has a delivery
+pub const PICKUP_JOB_SUFFIX: &str = "#pickup";
+/// Capacity used when the vehicle is not constrained (jobs carry no demand)
+const UNCONSTRAINED_CAPACITY: [u32; 1] = [1000];
 
 /// Compute the average travel time (in seconds) from all other locations to `target_index`.
 /// `target_index` is 0-based in the distance matrix (depot=0, stop[0]=1, etc.).
@@ -67,18 +71,20 @@ fn apply_arrival_buffer(
 /// `matrices`: if provided, used to estimate segment durations for buffer calculation.
 /// `buffer_percent`: percentage of segment duration to arrive early (0 = no buffer).
 /// `buffer_fixed_minutes`: fixed minutes to arrive early on top of percentage (0 = no fixed buffer).
+/// `capacity`: vehicle capacity; when set, stop demands become delivery/pickup jobs.
 pub fn build_pragmatic_problem_with_buffer(
     problem: &VrpProblem,
     date: NaiveDate,
     matrices: Option<&DistanceTimeMatrices>,
     buffer_percent: f64,
     buffer_fixed_minutes: f64,
+    capacity: Option<&Load>,
 ) -> Value {
     let jobs: Vec<Value> = problem
         .stops
         .iter()
         .enumerate()
-        .map(|(index, stop)| {
+        .flat_map(|(index, stop)| {
             let place = json!({
                 "location": { "index": index + 1 },
                 "duration": (stop.service_duration_minutes as i64) * 60,
@@ -102,15 +108,52 @@ pub fn build_pragmatic_problem_with_buffer(
                 None => place,
             };
 
-            json!({
-                "id": stop.id,
-                "services": [{
-                    "places": [place]
-                }]
-            })
+            let mut jobs = Vec::with_capacity(2);
+            let demand = &stop.demand;
+            if capacity.is_none() || demand.is_empty() {
+                jobs.push(json!({
+                    "id": stop.id,
+                    "services": [{ "places": [place] }]
+                }));
+            } else if demand.pickup.is_zero() {
+                jobs.push(json!({
+                    "id": stop.id,
+                    "deliveries": [{ "places": [place], "demand": demand.delivery.dimensions() }]
+                }));
+            } else if demand.delivery.is_zero() {
+                jobs.push(json!({
+                    "id": stop.id,
+                    "pickups": [{ "places": [place], "demand": demand.pickup.dimensions() }]
+                }));
+            } else {
+                // Delivery and pickup at the same visit: the pickup is a separate
+                // zero-duration job so both loads are tracked along the tour.
+                let mut pickup_place = place.clone();
+                pickup_place["duration"] = json!(0);
+                jobs.push(json!({
+                    "id": stop.id,
+                    "deliveries": [{ "places": [place], "demand": demand.delivery.dimensions() }]
+                }));
+                jobs.push(json!({
+                    "id": format!("{}{}", stop.id, PICKUP_JOB_SUFFIX),
+                    "pickups": [{ "places": [pickup_place], "demand": demand.pickup.dimensions() }]
+                }));
+            }
+            // Valued jobs are assigned first (maximize-value objective)
+            if stop.priority > 1 {
+                for job in &mut jobs {
+                    job["value"] = json!(stop.priority);
+                }
+            }
+            jobs
         })
         .collect();
 
+    let vehicle_capacity: Vec<u32> = match capacity {
+        Some(load) => load.dimensions().to_vec(),
+        None => UNCONSTRAINED_CAPACITY.to_vec(),
+    };
+
     json!({
         "plan": {
             "jobs": jobs
@@ -132,7 +175,7 @@ pub fn build_pragmatic_problem_with_buffer(
                     },
                     "end": {
                         "latest": format_rfc3339(date, problem.shift_end),
-                        "location": { "index": 0 }
+                        "location": { "index": problem.end_index() }
                     },
                     "breaks": if let Some(ref break_cfg) = problem.break_config {
                         vec![json!({
@@ -146,7 +189,7 @@ pub fn build_pragmatic_problem_with_buffer(
                         vec![]
                     }
                 }],
-                "capacity": [1000]
+                "capacity": vehicle_capacity
             }],
             "profiles": [{
                 "name": DEFAULT_PROFILE
@@ -206,7 +249,7 @@ mod tests {
 
     use crate::types::Coordinates;
     use crate::services::routing::DistanceTimeMatrices;
-    use super::super::{BreakConfig, Depot, VrpStop, VrpProblem};
+    use super::super::{BreakConfig, Depot, Load, StopDemand, VrpStop, VrpProblem, FIXED_STOP_PRIORITY};
 
     fn test_problem() -> VrpProblem {
         VrpProblem {
@@ -228,6 +271,7 @@ mod tests {
                         is_hard: true,
                     }),
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
                 VrpStop {
                     id: "stop-2".to_string(),
@@ -237,8 +281,10 @@ mod tests {
                     service_duration_minutes: 20,
                     time_window: None,
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
             ],
+            end_location: None,
             break_config: None,
         }
     }
@@ -248,7 +294,7 @@ mod tests {
         let date = NaiveDate::from_ymd_opt(2026, 1, 26).unwrap();
         let problem = test_problem();
 
-        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0);
+        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0, None);
 
         let jobs = json["plan"]["jobs"].as_array().unwrap();
         assert_eq!(jobs.len(), 2);
@@ -260,12 +306,37 @@ mod tests {
         assert_eq!(vehicle["shifts"][0]["end"]["location"]["index"], 0);
     }
 
+    #[test]
+    fn build_pragmatic_problem_ends_at_end_location() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 26).unwrap();
+        let mut problem = test_problem();
+        problem.end_location = Some(Coordinates { lat: 49.2, lng: 16.6 });
+
+        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0, None);
+
+        let shift = &json["fleet"]["vehicles"][0]["shifts"][0];
+        assert_eq!(shift["start"]["location"]["index"], 0);
+        assert_eq!(shift["end"]["location"]["index"], 3);
+    }
+
+    #[test]
+    fn build_pragmatic_problem_values_fixed_stops_only() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 26).unwrap();
+        let mut problem = test_problem();
+        problem.stops[1].priority = FIXED_STOP_PRIORITY;
+
+        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0, None);
+
+        assert!(json["plan"]["jobs"][0].get("value").is_none());
+        assert_eq!(json["plan"]["jobs"][1]["value"], 100);
+    }
+
     #[test]
     fn build_pragmatic_problem_encodes_service_duration_and_times() {
         let date = NaiveDate::from_ymd_opt(2026, 1, 26).unwrap();
         let problem = test_problem();
 
-        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0);
+        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0, None);
         let place = &json["plan"]["jobs"][0]["services"][0]["places"][0];
 
         assert_eq!(place["duration"], 1800);
@@ -280,13 +351,69 @@ mod tests {
         let date = NaiveDate::from_ymd_opt(2026, 1, 26).unwrap();
         let problem = test_problem();
 
-        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0);
+        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0, None);
         let parsed: Problem = serde_json::from_value(json).unwrap();
 
         assert_eq!(parsed.plan.jobs.len(), 2);
         assert_eq!(parsed.fleet.vehicles.len(), 1);
     }
 
+    #[test]
+    fn build_pragmatic_problem_without_capacity_ignores_demand() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 26).unwrap();
+        let mut problem = test_problem();
+        problem.stops[0].demand.delivery = Load::new(20, 10, 1);
+
+        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0, None);
+
+        assert!(json["plan"]["jobs"][0]["services"].is_array());
+        assert_eq!(json["fleet"]["vehicles"][0]["capacity"], json!([1000]));
+    }
+
+    #[test]
+    fn build_pragmatic_problem_with_capacity_encodes_demands() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 26).unwrap();
+        let mut problem = test_problem();
+        problem.stops[0].demand.delivery = Load::new(20, 10, 1);
+        problem.stops[1].demand.pickup = Load::new(5, 0, 2);
+        let capacity = Load::new(300, 1500, 30);
+
+        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0, Some(&capacity));
+
+        let jobs = json["plan"]["jobs"].as_array().unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0]["deliveries"][0]["demand"], json!([20, 10, 1]));
+        assert_eq!(jobs[1]["pickups"][0]["demand"], json!([5, 0, 2]));
+        assert_eq!(json["fleet"]["vehicles"][0]["capacity"], json!([300, 1500, 30]));
+
+        let parsed: Problem = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.plan.jobs.len(), 2);
+    }
+
+    #[test]
+    fn build_pragmatic_problem_splits_delivery_and_pickup_at_same_stop() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 26).unwrap();
+        let mut problem = test_problem();
+        problem.stops[0].priority = FIXED_STOP_PRIORITY;
+        problem.stops[0].demand = StopDemand {
+            delivery: Load::new(20, 10, 1),
+            pickup: Load::new(15, 10, 1),
+        };
+        let capacity = Load::new(300, 1500, 30);
+
+        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0, Some(&capacity));
+
+        let jobs = json["plan"]["jobs"].as_array().unwrap();
+        assert_eq!(jobs.len(), 3);
+        assert_eq!(jobs[0]["id"], "stop-1");
+        assert_eq!(jobs[0]["deliveries"][0]["places"][0]["duration"], 1800);
+        assert_eq!(jobs[1]["id"], "stop-1#pickup");
+        assert_eq!(jobs[1]["pickups"][0]["places"][0]["duration"], 0);
+        assert_eq!(jobs[1]["value"], 100);
+        // Stop without demand stays a plain service
+        assert!(jobs[2]["services"].is_array());
+    }
+
     #[test]
     fn build_pragmatic_matrix_flattens_row_major() {
         let matrices = DistanceTimeMatrices {
@@ -431,7 +558,7 @@ mod tests {
 
         // 10% buffer: avg to stop-1 = (3600+5400)/2=4500s, 10% = 450s = 7min30s
         // Original window: 10:00-12:00 → Shifted to 09:52:30-12:00
-        let json = build_pragmatic_problem_with_buffer(&problem, date, Some(&matrices), 10.0, 0.0);
+        let json = build_pragmatic_problem_with_buffer(&problem, date, Some(&matrices), 10.0, 0.0, None);
         let place = &json["plan"]["jobs"][0]["services"][0]["places"][0];
         let times = place["times"].as_array().unwrap();
         let start_str = times[0][0].as_str().unwrap();
@@ -463,7 +590,7 @@ mod tests {
         let problem = test_problem();
 
         // No buffer — equivalent to build_pragmatic_problem_with_buffer(..., None, 0.0, 0.0)
-        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0);
+        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0, None);
         let place = &json["plan"]["jobs"][0]["services"][0]["places"][0];
         let times = place["times"].as_array().unwrap();
         let start_str = times[0][0].as_str().unwrap();
@@ -497,6 +624,7 @@ mod tests {
                         is_hard: true,
                     }),
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
                 VrpStop {
                     id: "unscheduled-1".to_string(),
@@ -507,12 +635,14 @@ mod tests {
                     service_duration_minutes: 30,
                     time_window: None,
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
             ],
+            end_location: None,
             break_config: None,
         };
 
-        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0);
+        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0, None);
 
         // Scheduled customer: duration = 60min = 3600s, point window [08:00, 08:00]
         let place_scheduled = &json["plan"]["jobs"][0]["services"][0]["places"][0];
@@ -541,7 +671,7 @@ mod tests {
             duration_minutes: 45,
         });
 
-        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0);
+        let json = build_pragmatic_problem_with_buffer(&problem, date, None, 0.0, 0.0, None);
         let parsed: Problem = serde_json::from_value(json).unwrap();
 
         let breaks = parsed.fleet.vehicles[0].shifts[0]
@@ -1,5 +1,7 @@
 //! VRP Solver configuration
 
+use super::Load;
+
 /// Configuration for the VRP solver
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -13,6 +15,8 @@ pub struct SolverConfig {
     pub arrival_buffer_percent: f64,
     /// Fixed arrival buffer in minutes added on top of percentage buffer (default 0)
     pub arrival_buffer_fixed_minutes: f64,
+    /// Vehicle capacity; None = stop demands are not constrained
+    pub vehicle_capacity: Option<Load>,
 }
 
 impl Default for SolverConfig {
@@ -22,6 +26,7 @@ impl Default for SolverConfig {
             max_generations: 3000,
             arrival_buffer_percent: 10.0,
             arrival_buffer_fixed_minutes: 0.0,
+            vehicle_capacity: None,
         }
     }
 }
@@ -35,6 +40,7 @@ impl SolverConfig {
             max_generations,
             arrival_buffer_percent: 10.0,
             arrival_buffer_fixed_minutes: 0.0,
+            vehicle_capacity: None,
         }
     }
 
@@ -50,9 +56,16 @@ impl SolverConfig {
             max_generations,
             arrival_buffer_percent,
             arrival_buffer_fixed_minutes,
+            vehicle_capacity: None,
         }
     }
 
+    /// Limit the vehicle load to `capacity`
+    pub fn with_capacity(mut self, capacity: Load) -> Self {
+        self.vehicle_capacity = Some(capacity);
+        self
+    }
+
     /// Fast configuration for interactive use
     /// - Quick response time (~5 seconds)
     /// - Good enough for most cases
@@ -62,6 +75,7 @@ impl SolverConfig {
             max_generations: 500,
             arrival_buffer_percent: 10.0,
             arrival_buffer_fixed_minutes: 0.0,
+            vehicle_capacity: None,
         }
     }
 
@@ -74,6 +88,7 @@ impl SolverConfig {
             max_generations: 10000,
             arrival_buffer_percent: 10.0,
             arrival_buffer_fixed_minutes: 0.0,
+            vehicle_capacity: None,
         }
     }
 
@@ -86,6 +101,7 @@ impl SolverConfig {
             max_generations: 200,
             arrival_buffer_percent: 10.0,
             arrival_buffer_fixed_minutes: 0.0,
+            vehicle_capacity: None,
         }
     }
 }
@@ -143,4 +159,17 @@ mod tests {
         assert!((config.arrival_buffer_percent - 15.0).abs() < f64::EPSILON);
         assert!((config.arrival_buffer_fixed_minutes - 5.0).abs() < f64::EPSILON);
     }
+
+    #[test]
+    fn test_capacity_defaults_to_unconstrained() {
+        assert!(SolverConfig::default().vehicle_capacity.is_none());
+        assert!(SolverConfig::fast().vehicle_capacity.is_none());
+    }
+
+    #[test]
+    fn test_with_capacity_config() {
+        let config = SolverConfig::fast().with_capacity(Load::new(500, 2000, 40));
+        assert_eq!(config.vehicle_capacity, Some(Load::new(500, 2000, 40)));
+        assert_eq!(config.max_time_seconds, 5);
+    }
 }
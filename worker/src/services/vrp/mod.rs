@@ -8,12 +8,14 @@ mod solution;
 mod config;
 mod adapter;
 mod pragmatic;
+mod multiday;
 
-pub use problem::{VrpProblem, VrpStop, Depot, StopTimeWindow, BreakConfig};
+pub use problem::{VrpProblem, VrpStop, Depot, StopTimeWindow, BreakConfig, Load, StopDemand, FIXED_STOP_PRIORITY};
 pub use solution::{RouteSolution, PlannedStop, RouteWarning};
 pub use config::SolverConfig;
-pub use adapter::{build_pragmatic_problem_with_buffer, build_pragmatic_matrix, DEFAULT_PROFILE};
+pub use adapter::{build_pragmatic_problem_with_buffer, build_pragmatic_matrix, DEFAULT_PROFILE, PICKUP_JOB_SUFFIX};
 pub use pragmatic::solve_pragmatic;
+pub use multiday::{assign_days, planning_days, shift_capacity_minutes, DayStop, MAX_MULTIDAY_DAYS};
 
 use anyhow::Result;
 use chrono::{NaiveDate, NaiveTime, Timelike};
@@ -76,6 +78,9 @@ impl VrpSolver {
             Ok(Ok(Ok(mut solution))) => {
                 // solve_pragmatic succeeded within timeout
                 solution.algorithm = "vrp-pragmatic".to_string();
+                if let Some(capacity) = &self.config.vehicle_capacity {
+                    solution.check_capacity(problem, capacity);
+                }
                 solution.solve_time_ms = started_at.elapsed().as_millis() as u64;
                 
                 let mut final_log = Vec::new();
@@ -141,6 +146,10 @@ impl VrpSolver {
             warning_type: "SOLVER_FALLBACK".to_string(),
             message: serde_json::json!({"key": "planner:warning.solver_fallback"}).to_string(),
         });
+        // The heuristic ignores demands, so overloads are only reported
+        if let Some(capacity) = &self.config.vehicle_capacity {
+            solution.check_capacity(problem, capacity);
+        }
 
         info!(
             "VRP solved: {} stops, {:.1} km, score={}",
@@ -295,10 +304,10 @@ impl VrpSolver {
             prev_idx = stop_idx;
         }
 
-        // Add return to depot
+        // Add return to depot (or wherever the route ends)
         if !ordered_indices.is_empty() {
-            let return_distance = matrices.distance(prev_idx, 0);
-            let return_duration = matrices.duration(prev_idx, 0);
+            let return_distance = matrices.distance(prev_idx, problem.end_index());
+            let return_duration = matrices.duration(prev_idx, problem.end_index());
             total_distance += return_distance;
             total_duration += return_duration;
         }
@@ -366,6 +375,7 @@ mod tests {
             service_duration_minutes: 30,
             time_window: None,
             priority: 1,
+            demand: StopDemand::default(),
         }
     }
 
@@ -417,6 +427,7 @@ mod tests {
             stops: vec![],
             shift_start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
             shift_end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            end_location: None,
             break_config: None,
         };
 
@@ -443,6 +454,7 @@ mod tests {
             stops: vec![make_stop("Customer A", 50.1, 14.5)],
             shift_start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
             shift_end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            end_location: None,
             break_config: None,
         };
 
@@ -472,6 +484,7 @@ mod tests {
             ],
             shift_start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
             shift_end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            end_location: None,
             break_config: None,
         };
 
@@ -507,6 +520,7 @@ mod tests {
             ],
             shift_start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
             shift_end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            end_location: None,
             break_config: None,
         };
 
@@ -558,6 +572,7 @@ mod tests {
                     service_duration_minutes: 30,
                     time_window: None,
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
                 VrpStop {
                     id: "s2".to_string(),
@@ -567,8 +582,10 @@ mod tests {
                     service_duration_minutes: 30,
                     time_window: None,
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
             ],
+            end_location: None,
             break_config: None,
         };
 
@@ -618,8 +635,10 @@ mod tests {
                         is_hard: true,
                     }),
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
             ],
+            end_location: None,
             break_config: None,
         };
 
@@ -664,8 +683,10 @@ mod tests {
                         is_hard: true,
                     }),
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
             ],
+            end_location: None,
             break_config: None,
         };
 
@@ -704,6 +725,7 @@ mod tests {
                         is_hard: true,
                     }),
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
                 VrpStop {
                     id: "s2".to_string(),
@@ -717,8 +739,10 @@ mod tests {
                         is_hard: true,
                     }),
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
             ],
+            end_location: None,
             break_config: None,
         };
 
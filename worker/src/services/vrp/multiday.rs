@@ -0,0 +1,209 @@
+//! Multi-day planning: spreading a pool of stops over the days of a range
+//!
+//! Days are filled in order. A day first takes the stops due by that day
+//! (earliest due date first), then grows from there with the stops closest
+//! to what it already holds, up to its share of the remaining work and
+//! never beyond the shift. Work is estimated from service durations and
+//! straight-line travel; each day is then optimized on its own by the
+//! regular solver, and stops it cannot fit move on to the next day.
+
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+use uuid::Uuid;
+
+use crate::services::geo::travel_time_minutes;
+use crate::types::Coordinates;
+
+/// A stop to place on one of the days
+#[derive(Debug, Clone)]
+pub struct DayStop {
+    pub customer_id: Uuid,
+    pub coordinates: Coordinates,
+    /// Earliest due date of the stop's revisions
+    pub due_date: NaiveDate,
+    pub service_duration_minutes: u32,
+}
+
+/// Stops (indices into the input) per day, and the stops no day could take
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayAssignment {
+    pub days: Vec<Vec<usize>>,
+    pub unassigned: Vec<usize>,
+}
+
+/// Longest range a multi-day plan may span
+pub const MAX_MULTIDAY_DAYS: i64 = 31;
+
+/// Days of the range (inclusive) that get a route
+pub fn planning_days(start: NaiveDate, end: NaiveDate, include_weekends: bool) -> Vec<NaiveDate> {
+    start
+        .iter_days()
+        .take_while(|date| *date <= end)
+        .filter(|date| include_weekends || !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+        .collect()
+}
+
+/// Minutes of a shift available for stops and driving
+pub fn shift_capacity_minutes(shift_start: NaiveTime, shift_end: NaiveTime, break_minutes: u32) -> u32 {
+    let shift = (shift_end - shift_start).num_minutes() - break_minutes as i64;
+    shift.max(0) as u32
+}
+
+/// Estimated minutes a stop adds to a group of stops: its service plus the
+/// drive from the closest place the group visits (or the depot)
+fn added_minutes(depot: &Coordinates, day: &[usize], stops: &[DayStop], idx: usize) -> f64 {
+    let stop = &stops[idx];
+    let drive = day
+        .iter()
+        .map(|&i| travel_time_minutes(&stops[i].coordinates, &stop.coordinates))
+        .fold(travel_time_minutes(depot, &stop.coordinates), f64::min);
+    stop.service_duration_minutes as f64 + drive
+}
+
+/// Spread `stops` over `days` (in date order) with at most
+/// `capacity_minutes` of estimated work per day
+pub fn assign_days(depot: &Coordinates, stops: &[DayStop], days: &[NaiveDate], capacity_minutes: u32) -> DayAssignment {
+    let capacity = capacity_minutes as f64;
+    let mut remaining: Vec<usize> = (0..stops.len()).collect();
+    remaining.sort_by_key(|&i| (stops[i].due_date, i));
+    let mut assigned_days = Vec::with_capacity(days.len());
+
+    for (day_idx, &date) in days.iter().enumerate() {
+        // The day's share of the work still to place, so that stops due
+        // later are spread instead of piling up on the first days
+        let remaining_work: f64 = remaining
+            .iter()
+            .map(|&i| {
+                let others: Vec<usize> = remaining.iter().copied().filter(|&j| j != i).collect();
+                added_minutes(depot, &others, stops, i)
+            })
+            .sum();
+        let days_left = days.len() - day_idx;
+        let target = if days_left == 1 { capacity } else { (remaining_work / days_left as f64).min(capacity) };
+
+        let mut day: Vec<usize> = Vec::new();
+        let mut used = 0.0;
+
+        // Due by this day: take as many as the shift holds
+        let due_now: Vec<usize> = remaining.iter().copied().filter(|&i| stops[i].due_date <= date).collect();
+        for idx in due_now {
+            let extra = added_minutes(depot, &day, stops, idx);
+            if day.is_empty() || used + extra <= capacity {
+                day.push(idx);
+                used += extra;
+            }
+        }
+        remaining.retain(|i| !day.contains(i));
+
+        // Seed with the most urgent stop, then grow around what the day holds
+        if day.is_empty() {
+            if let Some(first) = remaining.first().copied() {
+                used += added_minutes(depot, &day, stops, first);
+                day.push(first);
+                remaining.remove(0);
+            }
+        }
+        loop {
+            let next = remaining
+                .iter()
+                .enumerate()
+                .map(|(pos, &i)| (pos, added_minutes(depot, &day, stops, i)))
+                .filter(|(_, extra)| used + extra <= capacity)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            // Stop once the day would end further past its share than short of it
+            let Some((pos, extra)) = next.filter(|(_, extra)| used + extra / 2.0 < target) else { break };
+            day.push(remaining.remove(pos));
+            used += extra;
+        }
+
+        assigned_days.push(day);
+    }
+
+    DayAssignment { days: assigned_days, unassigned: remaining }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 11, day).unwrap()
+    }
+
+    fn stop(n: u128, lat: f64, lng: f64, due: NaiveDate) -> DayStop {
+        DayStop {
+            customer_id: Uuid::from_u128(n),
+            coordinates: Coordinates { lat, lng },
+            due_date: due,
+            service_duration_minutes: 60,
+        }
+    }
+
+    fn brno() -> Coordinates {
+        Coordinates { lat: 49.1951, lng: 16.6068 }
+    }
+
+    #[test]
+    fn test_due_stops_go_first() {
+        let stops = vec![
+            stop(1, 49.20, 16.60, d(20)),
+            stop(2, 49.21, 16.61, d(2)),
+            stop(3, 49.19, 16.62, d(20)),
+        ];
+        let result = assign_days(&brno(), &stops, &[d(2), d(3), d(4)], 480);
+        assert!(result.days[0].contains(&1));
+        assert!(result.unassigned.is_empty());
+        // Spread, not piled onto the first day
+        assert!(result.days.iter().all(|day| !day.is_empty()));
+    }
+
+    #[test]
+    fn test_respects_shift_length() {
+        let stops: Vec<DayStop> = (0..10).map(|n| stop(n, 49.19 + n as f64 * 0.001, 16.60, d(30))).collect();
+        let result = assign_days(&brno(), &stops, &[d(2), d(3)], 240);
+        for day in &result.days {
+            assert!(day.len() <= 4, "{:?}", day);
+        }
+        assert_eq!(result.days.iter().map(Vec::len).sum::<usize>() + result.unassigned.len(), 10);
+        assert!(!result.unassigned.is_empty());
+    }
+
+    #[test]
+    fn test_groups_nearby_stops() {
+        // Two clusters: around Brno and around Jihlava
+        let stops = vec![
+            stop(1, 49.20, 16.60, d(30)),
+            stop(2, 49.40, 15.59, d(30)),
+            stop(3, 49.21, 16.61, d(30)),
+            stop(4, 49.41, 15.60, d(30)),
+        ];
+        let result = assign_days(&brno(), &stops, &[d(2), d(3)], 480);
+        let mut days: Vec<Vec<usize>> = result.days.into_iter().map(|mut day| { day.sort(); day }).collect();
+        days.sort();
+        assert_eq!(days, vec![vec![0, 2], vec![1, 3]]);
+    }
+
+    #[test]
+    fn test_planning_days() {
+        // 2026-11-06 is a Friday
+        assert_eq!(planning_days(d(6), d(9), false), vec![d(6), d(9)]);
+        assert_eq!(planning_days(d(6), d(9), true).len(), 4);
+        assert!(planning_days(d(9), d(6), true).is_empty());
+    }
+
+    #[test]
+    fn test_shift_capacity() {
+        let t = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        assert_eq!(shift_capacity_minutes(t(8), t(16), 30), 450);
+        assert_eq!(shift_capacity_minutes(t(16), t(8), 0), 0);
+    }
+
+    #[test]
+    fn test_empty_inputs() {
+        let result = assign_days(&brno(), &[], &[d(2)], 480);
+        assert_eq!(result.days, vec![Vec::<usize>::new()]);
+
+        let stops = vec![stop(1, 49.20, 16.60, d(2))];
+        let result = assign_days(&brno(), &stops, &[], 480);
+        assert_eq!(result.unassigned, vec![0]);
+    }
+}
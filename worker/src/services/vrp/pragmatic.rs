@@ -13,7 +13,7 @@ use vrp_pragmatic::format::solution::{PragmaticOutputType, Solution as Pragmatic
 
 use crate::services::routing::DistanceTimeMatrices;
 use super::{
-    build_pragmatic_matrix, build_pragmatic_problem_with_buffer, DEFAULT_PROFILE, PlannedStop,
+    build_pragmatic_matrix, build_pragmatic_problem_with_buffer, DEFAULT_PROFILE, PICKUP_JOB_SUFFIX, PlannedStop,
     RouteSolution, RouteWarning, SolverConfig, VrpProblem,
 };
 
@@ -33,6 +33,7 @@ pub fn solve_pragmatic(
         Some(matrices),
         config.arrival_buffer_percent,
         config.arrival_buffer_fixed_minutes,
+        config.vehicle_capacity.as_ref(),
     );
     let problem_format: Problem = serde_json::from_value(problem_json)
         .context("Failed to deserialize pragmatic problem")?;
@@ -147,6 +148,12 @@ fn map_solution(problem: &VrpProblem, solution: &PragmaticSolution) -> RouteSolu
                     continue;
                 }
 
+                if activity.job_id.ends_with(PICKUP_JOB_SUFFIX) {
+                    // Pickup half of a delivery+pickup visit; the visit itself
+                    // is represented by the delivery job.
+                    continue;
+                }
+
                 let stop_id = activity.job_id.as_str();
                 if let Some(definition) = stop_by_id.get(stop_id) {
                     // Use activity-level timing when available (a stop may
@@ -199,13 +206,17 @@ fn map_solution(problem: &VrpProblem, solution: &PragmaticSolution) -> RouteSolu
     let mut unassigned = Vec::new();
     if let Some(unassigned_jobs) = &solution.unassigned {
         for job in unassigned_jobs {
-            unassigned.push(job.job_id.clone());
-            
+            let job_id = job.job_id.strip_suffix(PICKUP_JOB_SUFFIX).unwrap_or(&job.job_id);
+            let visit_planned = planned_stops.iter().any(|p| p.stop_id == job_id);
+            if !visit_planned && !unassigned.iter().any(|id| id == job_id) {
+                unassigned.push(job_id.to_string());
+            }
+
             // Get customer name for better readability
             let customer_name = stop_by_id
-                .get(job.job_id.as_str())
+                .get(job_id)
                 .map(|s| s.customer_name.as_str())
-                .unwrap_or(&job.job_id);
+                .unwrap_or(job_id);
             
             // Extract reasons for this job
             let reasons: Vec<String> = job.reasons.iter().map(|r| {
@@ -303,7 +314,7 @@ mod tests {
     use uuid::Uuid;
 
     use crate::types::Coordinates;
-    use super::super::{BreakConfig, Depot, VrpStop};
+    use super::super::{BreakConfig, Depot, Load, StopDemand, VrpStop};
 
     fn test_problem() -> VrpProblem {
         VrpProblem {
@@ -319,6 +330,7 @@ mod tests {
                     service_duration_minutes: 20,
                     time_window: None,
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
                 VrpStop {
                     id: "stop-2".to_string(),
@@ -328,8 +340,10 @@ mod tests {
                     service_duration_minutes: 15,
                     time_window: None,
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
             ],
+            end_location: None,
             break_config: None,
         }
     }
@@ -362,6 +376,70 @@ mod tests {
         assert!(solution.unassigned.is_empty());
     }
 
+    #[test]
+    fn solve_pragmatic_respects_vehicle_capacity() {
+        let mut problem = test_problem();
+        problem.stops[0].demand.delivery = Load::new(60, 0, 1);
+        problem.stops[1].demand.delivery = Load::new(60, 0, 1);
+        let matrices = DistanceTimeMatrices {
+            distances: vec![
+                vec![0, 10000, 20000],
+                vec![10000, 0, 15000],
+                vec![20000, 15000, 0],
+            ],
+            durations: vec![
+                vec![0, 600, 1200],
+                vec![600, 0, 900],
+                vec![1200, 900, 0],
+            ],
+            size: 3,
+        };
+
+        let solution = solve_pragmatic(
+            &problem,
+            &matrices,
+            NaiveDate::from_ymd_opt(2026, 1, 26).unwrap(),
+            &SolverConfig::instant().with_capacity(Load::new(100, 100, 10)),
+        ).unwrap();
+
+        assert_eq!(solution.stops.len(), 1);
+        assert_eq!(solution.unassigned.len(), 1);
+    }
+
+    #[test]
+    fn solve_pragmatic_merges_pickup_half_into_visit() {
+        let mut problem = test_problem();
+        problem.stops[0].demand = StopDemand {
+            delivery: Load::new(10, 0, 1),
+            pickup: Load::new(10, 0, 1),
+        };
+        let matrices = DistanceTimeMatrices {
+            distances: vec![
+                vec![0, 10000, 20000],
+                vec![10000, 0, 15000],
+                vec![20000, 15000, 0],
+            ],
+            durations: vec![
+                vec![0, 600, 1200],
+                vec![600, 0, 900],
+                vec![1200, 900, 0],
+            ],
+            size: 3,
+        };
+
+        let solution = solve_pragmatic(
+            &problem,
+            &matrices,
+            NaiveDate::from_ymd_opt(2026, 1, 26).unwrap(),
+            &SolverConfig::instant().with_capacity(Load::new(100, 100, 10)),
+        ).unwrap();
+
+        assert_eq!(solution.stops.len(), 2);
+        assert!(solution.unassigned.is_empty());
+        assert!(solution.stops.iter().all(|s| !s.stop_id.ends_with(PICKUP_JOB_SUFFIX)));
+        assert!(solution.warnings.iter().all(|w| w.warning_type != "UNKNOWN_JOB"));
+    }
+
     #[test]
     fn solve_pragmatic_includes_service_duration_in_total() {
         // Problem with 2 stops, each with 30 min service duration = 1800 seconds each
@@ -378,6 +456,7 @@ mod tests {
                     service_duration_minutes: 30, // 30 min = 1800 seconds
                     time_window: None,
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
                 VrpStop {
                     id: "stop-2".to_string(),
@@ -387,8 +466,10 @@ mod tests {
                     service_duration_minutes: 30, // 30 min = 1800 seconds
                     time_window: None,
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
             ],
+            end_location: None,
             break_config: None,
         };
 
@@ -448,8 +529,10 @@ mod tests {
                     service_duration_minutes: 15, // Short: 15 min
                     time_window: None,
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
             ],
+            end_location: None,
             break_config: None,
         };
 
@@ -466,8 +549,10 @@ mod tests {
                     service_duration_minutes: 60, // Long: 60 min
                     time_window: None,
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
             ],
+            end_location: None,
             break_config: None,
         };
 
@@ -567,6 +652,7 @@ mod tests {
                         is_hard: true,
                     }),
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
                 VrpStop {
                     id: "stop-2".to_string(),
@@ -580,8 +666,10 @@ mod tests {
                         is_hard: true,
                     }),
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
             ],
+            end_location: None,
             break_config: Some(BreakConfig {
                 earliest_time: NaiveTime::from_hms_opt(11, 30, 0).unwrap(),
                 latest_time: NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
@@ -657,8 +745,10 @@ mod tests {
                         is_hard: true,
                     }),
                     priority: 1,
+                    demand: StopDemand::default(),
                 },
             ],
+            end_location: None,
             break_config: None,
         };
 
@@ -6,6 +6,10 @@ use uuid::Uuid;
 
 use crate::types::Coordinates;
 
+/// Priority of stops that must be kept in the plan (fixed appointments).
+/// Stops above the default priority of 1 are preferred over unassigning.
+pub const FIXED_STOP_PRIORITY: i32 = 100;
+
 /// VRP Problem definition
 #[derive(Debug, Clone)]
 pub struct VrpProblem {
@@ -17,10 +21,37 @@ pub struct VrpProblem {
     pub shift_start: NaiveTime,
     /// Working hours end
     pub shift_end: NaiveTime,
+    /// Where the route ends when it is not the depot (a route started
+    /// mid-day from the technician's position still returns home). Its
+    /// matrix index is `stops.len() + 1`.
+    pub end_location: Option<Coordinates>,
     /// Optional break configuration
     pub break_config: Option<BreakConfig>,
 }
 
+impl VrpProblem {
+    /// Matrix index of the location the route ends at
+    pub fn end_index(&self) -> usize {
+        if self.end_location.is_some() {
+            self.stops.len() + 1
+        } else {
+            0
+        }
+    }
+
+    /// Whether any stop carries a pickup or delivery demand
+    pub fn has_demand(&self) -> bool {
+        self.stops.iter().any(|s| !s.demand.is_empty())
+    }
+
+    /// Total load that has to leave the depot to serve all deliveries
+    pub fn total_delivery(&self) -> Load {
+        self.stops
+            .iter()
+            .fold(Load::default(), |acc, s| acc.saturating_add(s.demand.delivery))
+    }
+}
+
 /// Break configuration for VRP solver
 #[derive(Debug, Clone)]
 pub struct BreakConfig {
@@ -55,6 +86,82 @@ pub struct VrpStop {
     pub time_window: Option<StopTimeWindow>,
     /// Priority (higher = more important to visit)
     pub priority: i32,
+    /// Goods dropped off and picked up at this stop
+    pub demand: StopDemand,
+}
+
+/// Amount of goods in each capacity dimension.
+/// Used both for stop demand and for vehicle capacity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Load {
+    /// Weight in kilograms
+    pub weight_kg: u32,
+    /// Volume in litres
+    pub volume_liters: u32,
+    /// Number of pieces
+    pub count: u32,
+}
+
+impl Load {
+    pub fn new(weight_kg: u32, volume_liters: u32, count: u32) -> Self {
+        Self { weight_kg, volume_liters, count }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Dimensions in the order used by the pragmatic format: weight, volume, count
+    pub fn dimensions(&self) -> [u32; 3] {
+        [self.weight_kg, self.volume_liters, self.count]
+    }
+
+    pub fn saturating_add(self, other: Load) -> Load {
+        Load {
+            weight_kg: self.weight_kg.saturating_add(other.weight_kg),
+            volume_liters: self.volume_liters.saturating_add(other.volume_liters),
+            count: self.count.saturating_add(other.count),
+        }
+    }
+
+    pub fn saturating_sub(self, other: Load) -> Load {
+        Load {
+            weight_kg: self.weight_kg.saturating_sub(other.weight_kg),
+            volume_liters: self.volume_liters.saturating_sub(other.volume_liters),
+            count: self.count.saturating_sub(other.count),
+        }
+    }
+
+    /// Names of the dimensions in which this load exceeds `capacity`
+    pub fn exceeded_dimensions(&self, capacity: &Load) -> Vec<&'static str> {
+        let mut exceeded = Vec::new();
+        if self.weight_kg > capacity.weight_kg {
+            exceeded.push("weight");
+        }
+        if self.volume_liters > capacity.volume_liters {
+            exceeded.push("volume");
+        }
+        if self.count > capacity.count {
+            exceeded.push("count");
+        }
+        exceeded
+    }
+}
+
+/// Goods handled at a stop.
+/// Deliveries are loaded at the depot, pickups are carried back to it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StopDemand {
+    /// Goods dropped off at the stop (e.g. spare parts)
+    pub delivery: Load,
+    /// Goods collected at the stop (e.g. replaced parts)
+    pub pickup: Load,
+}
+
+impl StopDemand {
+    pub fn is_empty(&self) -> bool {
+        self.delivery.is_zero() && self.pickup.is_zero()
+    }
 }
 
 /// Time window for a stop
@@ -84,6 +191,7 @@ mod tests {
             stops: vec![],
             shift_start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
             shift_end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            end_location: None,
             break_config: None,
         };
 
@@ -102,6 +210,7 @@ mod tests {
             service_duration_minutes: 30,
             time_window: None,
             priority: 1,
+            demand: StopDemand::default(),
         };
 
         assert_eq!(stop.id, "stop-1");
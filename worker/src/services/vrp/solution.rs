@@ -1,9 +1,13 @@
 #![allow(dead_code)]
 //! VRP Solution types
 
+use std::collections::HashMap;
+
 use chrono::NaiveTime;
 use uuid::Uuid;
 
+use super::{Load, VrpProblem, VrpStop};
+
 /// Optimized route solution
 #[derive(Debug, Clone)]
 pub struct RouteSolution {
@@ -72,6 +76,67 @@ impl RouteSolution {
             unassigned: vec![],
         }
     }
+
+    /// Post-solve capacity validation.
+    /// The vehicle leaves the depot loaded with the deliveries of all planned stops,
+    /// unloads each delivery and loads each pickup along the route.
+    /// Emits a CAPACITY_EXCEEDED warning wherever the load exceeds `capacity`.
+    pub fn check_capacity(&mut self, problem: &VrpProblem, capacity: &Load) {
+        let stop_by_id: HashMap<&str, &VrpStop> =
+            problem.stops.iter().map(|s| (s.id.as_str(), s)).collect();
+        let route: Vec<&VrpStop> = self
+            .stops
+            .iter()
+            .filter_map(|p| stop_by_id.get(p.stop_id.as_str()).copied())
+            .collect();
+
+        let mut load = route
+            .iter()
+            .fold(Load::default(), |acc, s| acc.saturating_add(s.demand.delivery));
+        let exceeded = load.exceeded_dimensions(capacity);
+        if !exceeded.is_empty() {
+            self.warnings.push(capacity_warning(None, "depot", &load, capacity, &exceeded));
+        }
+
+        for stop in route {
+            load = load
+                .saturating_sub(stop.demand.delivery)
+                .saturating_add(stop.demand.pickup);
+            let exceeded = load.exceeded_dimensions(capacity);
+            if !exceeded.is_empty() {
+                self.warnings.push(capacity_warning(
+                    Some(stop),
+                    &stop.customer_name,
+                    &load,
+                    capacity,
+                    &exceeded,
+                ));
+            }
+        }
+    }
+}
+
+fn capacity_warning(
+    stop: Option<&VrpStop>,
+    name: &str,
+    load: &Load,
+    capacity: &Load,
+    exceeded: &[&str],
+) -> RouteWarning {
+    RouteWarning {
+        stop_id: stop.map(|s| s.id.clone()),
+        warning_type: "CAPACITY_EXCEEDED".to_string(),
+        message: serde_json::json!({"key": "planner:warning.capacity_exceeded", "params": {
+            "name": name,
+            "dimensions": exceeded.join(", "),
+            "weightKg": load.weight_kg,
+            "volumeLiters": load.volume_liters,
+            "count": load.count,
+            "capacityWeightKg": capacity.weight_kg,
+            "capacityVolumeLiters": capacity.volume_liters,
+            "capacityCount": capacity.count,
+        }}).to_string(),
+    }
 }
 
 #[cfg(test)]
@@ -113,4 +178,83 @@ mod tests {
 
         assert_eq!(warning.warning_type, "TIME_WINDOW");
     }
+
+    fn demand_stop(id: &str, delivery: Load, pickup: Load) -> VrpStop {
+        VrpStop {
+            id: id.to_string(),
+            customer_id: Uuid::new_v4(),
+            customer_name: id.to_string(),
+            coordinates: crate::types::Coordinates { lat: 50.0, lng: 14.0 },
+            service_duration_minutes: 30,
+            time_window: None,
+            priority: 1,
+            demand: super::super::StopDemand { delivery, pickup },
+        }
+    }
+
+    fn planned(ids: &[&str]) -> RouteSolution {
+        let mut solution = RouteSolution::empty();
+        for (i, id) in ids.iter().enumerate() {
+            solution.stops.push(PlannedStop {
+                stop_id: id.to_string(),
+                customer_id: Uuid::nil(),
+                customer_name: id.to_string(),
+                order: i as u32 + 1,
+                arrival_time: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+                departure_time: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+                waiting_time_minutes: 0,
+            });
+        }
+        solution
+    }
+
+    fn problem_with(stops: Vec<VrpStop>) -> VrpProblem {
+        VrpProblem {
+            depot: super::super::Depot { coordinates: crate::types::Coordinates { lat: 50.0, lng: 14.0 } },
+            stops,
+            shift_start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            shift_end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            end_location: None,
+            break_config: None,
+        }
+    }
+
+    #[test]
+    fn test_check_capacity_within_limits() {
+        let problem = problem_with(vec![
+            demand_stop("a", Load::new(40, 0, 2), Load::default()),
+            demand_stop("b", Load::new(40, 0, 2), Load::new(60, 0, 1)),
+        ]);
+        let mut solution = planned(&["a", "b"]);
+        solution.check_capacity(&problem, &Load::new(100, 100, 10));
+        assert!(solution.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_check_capacity_overloaded_at_depot() {
+        let problem = problem_with(vec![
+            demand_stop("a", Load::new(80, 0, 1), Load::default()),
+            demand_stop("b", Load::new(80, 0, 1), Load::default()),
+        ]);
+        let mut solution = planned(&["a", "b"]);
+        solution.check_capacity(&problem, &Load::new(100, 100, 10));
+        assert_eq!(solution.warnings.len(), 1);
+        assert_eq!(solution.warnings[0].warning_type, "CAPACITY_EXCEEDED");
+        assert!(solution.warnings[0].stop_id.is_none());
+        assert!(solution.warnings[0].message.contains("weight"));
+    }
+
+    #[test]
+    fn test_check_capacity_pickups_accumulate_along_route() {
+        let problem = problem_with(vec![
+            demand_stop("a", Load::default(), Load::new(0, 0, 6)),
+            demand_stop("b", Load::default(), Load::new(0, 0, 6)),
+            demand_stop("unplanned", Load::new(0, 0, 50), Load::default()),
+        ]);
+        let mut solution = planned(&["a", "b"]);
+        solution.check_capacity(&problem, &Load::new(100, 100, 10));
+        assert_eq!(solution.warnings.len(), 1);
+        assert_eq!(solution.warnings[0].stop_id.as_deref(), Some("b"));
+        assert!(solution.warnings[0].message.contains("count"));
+    }
 }
@@ -0,0 +1,166 @@
+//! Visit conditions
+//!
+//! When a visit is completed or cancelled, the day's weather at the customer
+//! is fetched from `WEATHER_API_URL` (an Open-Meteo compatible API, e.g.
+//! https://api.open-meteo.com) and stored with the crew's arrival delay in
+//! `visit_conditions`. Its forecast endpoint covers the past 92 and the next
+//! 16 days; outside that window, without the URL or without customer
+//! coordinates only the delay is stored.
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::http::{self, HttpService};
+use crate::types::weather::DailyWeather;
+
+const PAST_DAYS: i64 = 92;
+const FORECAST_DAYS: i64 = 16;
+const DAILY_FIELDS: &str =
+    "weather_code,temperature_2m_min,temperature_2m_max,precipitation_sum,snowfall_sum,wind_speed_10m_max";
+
+static API_URL: Lazy<Option<String>> = Lazy::new(|| {
+    std::env::var("WEATHER_API_URL")
+        .ok()
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+});
+
+/// Whether the API has the weather of `date` (as seen on `today`)
+fn in_range(date: NaiveDate, today: NaiveDate) -> bool {
+    date >= today - Duration::days(PAST_DAYS) && date < today + Duration::days(FORECAST_DAYS)
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastResponse {
+    daily: DailySeries,
+}
+
+/// Daily values, one array entry per day
+#[derive(Debug, Deserialize)]
+struct DailySeries {
+    #[serde(default)]
+    weather_code: Vec<Option<i32>>,
+    #[serde(default)]
+    temperature_2m_min: Vec<Option<f64>>,
+    #[serde(default)]
+    temperature_2m_max: Vec<Option<f64>>,
+    #[serde(default)]
+    precipitation_sum: Vec<Option<f64>>,
+    #[serde(default)]
+    snowfall_sum: Vec<Option<f64>>,
+    #[serde(default)]
+    wind_speed_10m_max: Vec<Option<f64>>,
+}
+
+fn first<T: Copy>(values: &[Option<T>]) -> Option<T> {
+    values.first().copied().flatten()
+}
+
+/// Weather of the single day of a forecast response
+fn parse_daily(body: &str) -> Result<DailyWeather> {
+    let daily = serde_json::from_str::<ForecastResponse>(body)?.daily;
+    Ok(DailyWeather {
+        weather_code: first(&daily.weather_code),
+        temperature_min_c: first(&daily.temperature_2m_min),
+        temperature_max_c: first(&daily.temperature_2m_max),
+        precipitation_mm: first(&daily.precipitation_sum),
+        snowfall_cm: first(&daily.snowfall_sum),
+        wind_max_kmh: first(&daily.wind_speed_10m_max),
+    })
+}
+
+/// Fetch the weather of a day at a place
+pub async fn fetch_daily(base_url: &str, lat: f64, lng: f64, date: NaiveDate) -> Result<DailyWeather> {
+    let url = format!(
+        "{}/v1/forecast?latitude={:.4}&longitude={:.4}&daily={}&timezone=Europe%2FPrague&start_date={}&end_date={}",
+        base_url, lat, lng, DAILY_FIELDS, date, date
+    );
+    let client = http::client(HttpService::Weather);
+    let response = http::send_idempotent(HttpService::Weather, || client.get(&url)).await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("weather API returned {}", response.status()));
+    }
+    parse_daily(&response.text().await?)
+}
+
+/// Record the conditions of a visit
+pub async fn record(pool: &PgPool, user_id: Uuid, visit_id: Uuid) -> Result<()> {
+    let Some((date, lat, lng)) = queries::visit_conditions::get_visit_location(pool, visit_id, user_id).await? else {
+        return Ok(());
+    };
+
+    let weather = match (API_URL.as_deref(), lat, lng) {
+        (Some(url), Some(lat), Some(lng)) if in_range(date, Utc::now().date_naive()) => {
+            match fetch_daily(url, lat, lng, date).await {
+                Ok(weather) => Some(weather),
+                Err(e) => {
+                    warn!("Failed to fetch weather for visit {}: {}", visit_id, e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    queries::visit_conditions::upsert_conditions(pool, visit_id, user_id, weather.as_ref()).await?;
+    debug!("Recorded conditions of visit {}", visit_id);
+    Ok(())
+}
+
+/// Record the conditions of a visit, in the background
+pub fn record_in_background(pool: &PgPool, user_id: Uuid, visit_id: Uuid) {
+    let pool = pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = record(&pool, user_id, visit_id).await {
+            warn!("Failed to record conditions of visit {}: {}", visit_id, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::weather::WeatherCondition;
+
+    #[test]
+    fn test_in_range() {
+        let today = NaiveDate::from_ymd_opt(2026, 1, 20).unwrap();
+        assert!(in_range(today, today));
+        assert!(in_range(today - Duration::days(92), today));
+        assert!(!in_range(today - Duration::days(93), today));
+        assert!(in_range(today + Duration::days(15), today));
+        assert!(!in_range(today + Duration::days(16), today));
+    }
+
+    #[test]
+    fn test_parse_daily() {
+        let body = r#"{
+            "latitude": 49.2, "longitude": 16.6,
+            "daily_units": {"temperature_2m_min": "°C"},
+            "daily": {
+                "time": ["2026-01-20"],
+                "weather_code": [73],
+                "temperature_2m_min": [-6.4],
+                "temperature_2m_max": [-1.2],
+                "precipitation_sum": [4.1],
+                "snowfall_sum": [2.8],
+                "wind_speed_10m_max": [null]
+            }
+        }"#;
+        let weather = parse_daily(body).unwrap();
+        assert_eq!(weather.condition(), Some(WeatherCondition::Snow));
+        assert_eq!(weather.temperature_min_c, Some(-6.4));
+        assert_eq!(weather.snowfall_cm, Some(2.8));
+        assert_eq!(weather.wind_max_kmh, None);
+
+        let empty = parse_daily(r#"{"daily": {"time": []}}"#).unwrap();
+        assert_eq!(empty, DailyWeather::default());
+        assert!(parse_daily(r#"{"error": true, "reason": "out of range"}"#).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+//! Weather outcome report
+//!
+//! Puts failure and cancellation rates and arrival delays of visits side by
+//! side per weather condition and per band of the day's lowest temperature,
+//! so owners can size winter scheduling buffers. The suggested buffer is the
+//! delay above the clear-weather baseline.
+
+use chrono::NaiveDate;
+
+use crate::types::report::{WeatherOutcomeCounts, WeatherOutcomeReportResponse, WeatherOutcomeRow};
+
+/// Conditions in report order
+const CONDITION_ORDER: [&str; 6] = ["clear", "cloudy", "fog", "rain", "snow", "storm"];
+/// Temperature bands in report order, coldest first
+const TEMPERATURE_BAND_ORDER: [&str; 4] = ["below_minus_5", "minus_5_to_0", "0_to_5", "above_5"];
+
+fn rate(part: i64, whole: i64) -> f64 {
+    if whole > 0 {
+        part as f64 / whole as f64
+    } else {
+        0.0
+    }
+}
+
+/// Mean delay in clear weather, or over all visits with a delay when no
+/// clear day was recorded (weighted by completed visits)
+fn baseline_delay(conditions: &[WeatherOutcomeCounts]) -> Option<f64> {
+    if let Some(clear) = conditions.iter().find(|c| c.bucket == "clear").and_then(|c| c.avg_arrival_delay_minutes) {
+        return Some(clear);
+    }
+    let (sum, weight) = conditions
+        .iter()
+        .filter_map(|c| c.avg_arrival_delay_minutes.map(|delay| (delay, c.completed.max(1) as f64)))
+        .fold((0.0, 0.0), |(sum, weight), (delay, w)| (sum + delay * w, weight + w));
+    (weight > 0.0).then(|| sum / weight)
+}
+
+fn rows(counts: &[WeatherOutcomeCounts], order: &[&str], baseline: Option<f64>) -> Vec<WeatherOutcomeRow> {
+    let mut rows: Vec<WeatherOutcomeRow> = counts
+        .iter()
+        .map(|c| WeatherOutcomeRow {
+            bucket: Some(c.bucket.clone()).filter(|b| !b.is_empty()),
+            visits: c.visits,
+            completed: c.completed,
+            failed: c.failed,
+            cancelled: c.cancelled,
+            failure_rate: rate(c.failed, c.completed),
+            cancellation_rate: rate(c.cancelled, c.visits),
+            avg_arrival_delay_minutes: c.avg_arrival_delay_minutes,
+            suggested_buffer_minutes: match (c.avg_arrival_delay_minutes, baseline) {
+                (Some(delay), Some(baseline)) => (delay - baseline).max(0.0).round() as i64,
+                _ => 0,
+            },
+        })
+        .collect();
+    let position = |row: &WeatherOutcomeRow| {
+        row.bucket
+            .as_deref()
+            .and_then(|b| order.iter().position(|o| *o == b))
+            .unwrap_or(order.len())
+    };
+    rows.sort_by_key(position);
+    rows
+}
+
+/// Report rows per condition and temperature band, unknown weather last
+pub fn build_report(
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    conditions: &[WeatherOutcomeCounts],
+    temperature_bands: &[WeatherOutcomeCounts],
+) -> WeatherOutcomeReportResponse {
+    let baseline = baseline_delay(conditions);
+    WeatherOutcomeReportResponse {
+        from_date,
+        to_date,
+        baseline_delay_minutes: baseline,
+        conditions: rows(conditions, &CONDITION_ORDER, baseline),
+        temperature_bands: rows(temperature_bands, &TEMPERATURE_BAND_ORDER, baseline),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(bucket: &str, visits: i64, failed: i64, cancelled: i64, delay: Option<f64>) -> WeatherOutcomeCounts {
+        WeatherOutcomeCounts {
+            bucket: bucket.to_string(),
+            visits,
+            completed: visits - cancelled,
+            failed,
+            cancelled,
+            avg_arrival_delay_minutes: delay,
+        }
+    }
+
+    #[test]
+    fn test_build_report_orders_rows_and_suggests_buffers() {
+        let from = NaiveDate::from_ymd_opt(2025, 11, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 2, 28).unwrap();
+        let report = build_report(
+            from,
+            to,
+            &[
+                counts("", 5, 0, 1, None),
+                counts("snow", 20, 4, 4, Some(23.6)),
+                counts("clear", 50, 2, 0, Some(4.0)),
+            ],
+            &[counts("above_5", 30, 1, 0, Some(3.0)), counts("below_minus_5", 10, 3, 5, Some(30.0))],
+        );
+
+        assert_eq!(report.baseline_delay_minutes, Some(4.0));
+        let buckets: Vec<Option<&str>> = report.conditions.iter().map(|r| r.bucket.as_deref()).collect();
+        assert_eq!(buckets, vec![Some("clear"), Some("snow"), None]);
+
+        let snow = &report.conditions[1];
+        assert_eq!(snow.suggested_buffer_minutes, 20);
+        assert_eq!(snow.failure_rate, 0.25);
+        assert_eq!(snow.cancellation_rate, 0.2);
+        assert_eq!(report.conditions[0].suggested_buffer_minutes, 0);
+        assert_eq!(report.conditions[2].suggested_buffer_minutes, 0);
+
+        let bands: Vec<Option<&str>> = report.temperature_bands.iter().map(|r| r.bucket.as_deref()).collect();
+        assert_eq!(bands, vec![Some("below_minus_5"), Some("above_5")]);
+        assert_eq!(report.temperature_bands[0].suggested_buffer_minutes, 26);
+        assert_eq!(report.temperature_bands[1].suggested_buffer_minutes, 0);
+    }
+
+    #[test]
+    fn test_baseline_without_clear_days() {
+        let baseline = baseline_delay(&[counts("rain", 10, 0, 0, Some(10.0)), counts("snow", 30, 0, 0, Some(20.0))]);
+        assert_eq!(baseline, Some(17.5));
+        assert_eq!(baseline_delay(&[counts("", 3, 0, 0, None)]), None);
+    }
+}
@@ -0,0 +1,282 @@
+//! Webhook delivery JetStream processor
+//!
+//! Entity changes are fanned out to the account's subscribed endpoints:
+//! every callback becomes a row in `webhook_deliveries` and a job on the
+//! `SAZINKA_WEBHOOK_JOBS` stream. The processor POSTs the rendered payload
+//! with an HMAC-SHA256 signature and leaves failed attempts unacked, so
+//! JetStream redelivers them along the backoff schedule until the last
+//! attempt marks the delivery as failed.
+//!
+//! ## Signature
+//! `X-Sazinka-Signature: t=<unix seconds>,v1=<hex HMAC-SHA256 of "<t>.<body>">`
+//! keyed with the endpoint's secret. Receivers should reject stale timestamps
+//! and dedupe on `X-Sazinka-Delivery`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_nats::jetstream;
+use async_nats::Client;
+use chrono::Utc;
+use futures::StreamExt;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::queries;
+use crate::services::automation;
+use crate::services::http::{self, HttpService};
+use crate::services::webhook_events;
+use crate::subjects;
+use crate::transport::{JobMessage, JobQueue};
+use crate::types::webhook::{QueuedWebhookDelivery, Webhook, WebhookPayloadFormat, WEBHOOK_DELIVERY_PENDING};
+
+// Stream and consumer names
+const STREAM_NAME: &str = "SAZINKA_WEBHOOK_JOBS";
+const CONSUMER_NAME: &str = "webhook_workers";
+const SUBJECT: &str = subjects::jobs::WEBHOOK;
+
+/// Attempts per delivery, the first one included
+const MAX_DELIVER: i64 = 6;
+
+/// Waits between attempts: 10 s, 1 min, 5 min, 30 min, 2 h
+const BACKOFF_SECS: [u64; 5] = [10, 60, 5 * 60, 30 * 60, 2 * 60 * 60];
+
+/// Longest endpoint error kept in the delivery log
+const MAX_ERROR_LEN: usize = 500;
+
+/// Generate an endpoint signing secret
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// Value of the `X-Sazinka-Signature` header for a body sent at `timestamp`
+pub fn signature_header(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("t={},v1={}", timestamp, hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Queue an event for every active endpoint of the account subscribed to it
+/// and run the account's automation rules for it.
+///
+/// Runs in the background so a slow database or queue never fails the
+/// mutation that caused the event.
+pub fn emit(client: &Client, pool: &PgPool, user_id: Uuid, event_type: &'static str, data: &impl Serialize) {
+    let data = match serde_json::to_value(data) {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Failed to serialize webhook event {}: {}", event_type, e);
+            return;
+        }
+    };
+    automation::on_event(client, pool, user_id, event_type, &data);
+    let queue = JobQueue::new(client.clone());
+    let pool = pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = enqueue_event(&queue, &pool, user_id, event_type, &data).await {
+            warn!("Failed to queue webhook event {} for user {}: {}", event_type, user_id, e);
+        }
+    });
+}
+
+/// Queue an event for the subscribed endpoints, returning how many were queued
+pub async fn enqueue_event(
+    queue: &JobQueue,
+    pool: &PgPool,
+    user_id: Uuid,
+    event_type: &str,
+    data: &serde_json::Value,
+) -> Result<usize> {
+    let webhooks = queries::webhook::list_subscribed(pool, user_id, event_type).await?;
+    let event_id = Uuid::new_v4();
+    for webhook in &webhooks {
+        enqueue_delivery(queue, pool, user_id, webhook, event_id, event_type, data).await?;
+    }
+    Ok(webhooks.len())
+}
+
+/// Log a delivery of an event to one endpoint and queue it, returning the delivery ID
+pub async fn enqueue_delivery(
+    queue: &JobQueue,
+    pool: &PgPool,
+    user_id: Uuid,
+    webhook: &Webhook,
+    event_id: Uuid,
+    event_type: &str,
+    data: &serde_json::Value,
+) -> Result<Uuid> {
+    let format = WebhookPayloadFormat::parse(&webhook.format);
+    let payload = webhook_events::render_event(format, event_id, event_type, Utc::now(), data);
+    let delivery_id = queries::webhook::insert_delivery(pool, webhook.id, user_id, event_id, event_type, &payload).await?;
+
+    let job = QueuedWebhookDelivery { delivery_id };
+    queue
+        .publish(format!("{}.deliver", SUBJECT), serde_json::to_vec(&job)?.into())
+        .await?;
+    Ok(delivery_id)
+}
+
+/// Webhook delivery processor
+pub struct WebhookProcessor {
+    queue: JobQueue,
+    pool: PgPool,
+}
+
+impl WebhookProcessor {
+    /// Create a new webhook processor, initializing the JetStream stream
+    pub async fn new(client: Client, pool: PgPool) -> Result<Self> {
+        let queue = JobQueue::new(client);
+
+        let stream_config = jetstream::stream::Config {
+            name: STREAM_NAME.to_string(),
+            subjects: vec![subjects::wildcard(SUBJECT)], // sazinka.jobs.webhook.*
+            max_messages: 100_000,
+            max_bytes: 100 * 1024 * 1024, // 100 MB
+            retention: jetstream::stream::RetentionPolicy::WorkQueue,
+            ..Default::default()
+        };
+        queue.ensure_stream(stream_config).await?;
+        info!("JetStream webhook stream '{}' ready", STREAM_NAME);
+
+        Ok(Self { queue, pool })
+    }
+
+    /// Start delivering queued webhooks
+    pub async fn start_processing(self: Arc<Self>) -> Result<()> {
+        let consumer_config = jetstream::consumer::pull::Config {
+            durable_name: Some(CONSUMER_NAME.to_string()),
+            ack_policy: jetstream::consumer::AckPolicy::Explicit,
+            max_deliver: MAX_DELIVER,
+            backoff: BACKOFF_SECS.iter().map(|secs| Duration::from_secs(*secs)).collect(),
+            filter_subject: subjects::tail_wildcard(SUBJECT),
+            ..Default::default()
+        };
+
+        let mut messages = self.queue.consume(STREAM_NAME, consumer_config).await?;
+        info!("JetStream webhook consumer '{}' ready", CONSUMER_NAME);
+
+        while let Some(msg) = messages.next().await {
+            match msg {
+                Ok(msg) => {
+                    let processor = Arc::clone(&self);
+                    tokio::spawn(async move {
+                        if let Err(e) = processor.process_delivery(msg).await {
+                            error!("Failed to process webhook delivery: {}", e);
+                        }
+                    });
+                }
+                Err(e) => {
+                    error!("Error receiving webhook message: {}", e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Attempt one delivery; failures stay unacked until the last attempt
+    async fn process_delivery(&self, msg: JobMessage) -> Result<()> {
+        let job: QueuedWebhookDelivery = match serde_json::from_slice(&msg.payload) {
+            Ok(job) => job,
+            Err(e) => {
+                msg.ack().await.ok();
+                return Err(anyhow!("invalid webhook job: {}", e));
+            }
+        };
+
+        let delivery = match queries::webhook::get_pending_delivery(&self.pool, job.delivery_id).await? {
+            Some(delivery) if delivery.status == WEBHOOK_DELIVERY_PENDING => delivery,
+            // Endpoint deleted or delivery already settled
+            _ => {
+                msg.ack().await?;
+                return Ok(());
+            }
+        };
+
+        let body = serde_json::to_vec(&delivery.payload)?;
+        let signature = signature_header(&delivery.secret, Utc::now().timestamp(), &body);
+        let result = http::client(HttpService::Webhook)
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-Sazinka-Event", &delivery.event_type)
+            .header("X-Sazinka-Delivery", delivery.id.to_string())
+            .header("X-Sazinka-Signature", signature)
+            .body(body)
+            .send()
+            .await;
+
+        let failure = match result {
+            Ok(response) if response.status().is_success() => {
+                queries::webhook::mark_delivered(&self.pool, delivery.id, response.status().as_u16() as i32).await?;
+                msg.ack().await?;
+                return Ok(());
+            }
+            Ok(response) => {
+                let status = response.status();
+                let text = response.text().await.unwrap_or_default();
+                (Some(status.as_u16() as i32), truncate(&format!("HTTP {}: {}", status, text.trim())))
+            }
+            Err(e) => (None, truncate(&e.to_string())),
+        };
+
+        let give_up = msg.delivered() >= MAX_DELIVER;
+        queries::webhook::record_failure(&self.pool, delivery.id, failure.0, &failure.1, give_up).await?;
+        if give_up {
+            warn!("Webhook delivery {} failed after {} attempts: {}", delivery.id, msg.delivered(), failure.1);
+            msg.ack().await?;
+        }
+        Ok(())
+    }
+}
+
+fn truncate(text: &str) -> String {
+    text.chars().take(MAX_ERROR_LEN).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_secret() {
+        let secret = generate_secret();
+        assert!(secret.starts_with("whsec_"));
+        assert_eq!(secret.len(), "whsec_".len() + 48);
+        assert_ne!(secret, generate_secret());
+    }
+
+    #[test]
+    fn test_signature_header() {
+        let header = signature_header("whsec_test", 1_700_000_000, br#"{"id":1}"#);
+        let (timestamp, signature) = header.split_once(",v1=").unwrap();
+        assert_eq!(timestamp, "t=1700000000");
+        assert_eq!(signature.len(), 64);
+
+        // Same input, same signature; any change breaks it
+        assert_eq!(header, signature_header("whsec_test", 1_700_000_000, br#"{"id":1}"#));
+        assert_ne!(header, signature_header("whsec_test", 1_700_000_001, br#"{"id":1}"#));
+        assert_ne!(header, signature_header("whsec_other", 1_700_000_000, br#"{"id":1}"#));
+    }
+
+    #[test]
+    fn test_backoff_fits_max_deliver() {
+        // JetStream rejects consumers with more backoff steps than redeliveries
+        assert!((BACKOFF_SECS.len() as i64) < MAX_DELIVER);
+    }
+
+    #[test]
+    fn test_truncate_error() {
+        assert_eq!(truncate("short"), "short");
+        assert_eq!(truncate(&"x".repeat(2 * MAX_ERROR_LEN)).len(), MAX_ERROR_LEN);
+    }
+}
@@ -0,0 +1,183 @@
+//! Outgoing event catalog and payload rendering
+//!
+//! Every event can be rendered either as our regular envelope or as a flat
+//! object that no-code tools map field by field without parsing nesting.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::types::webhook::{WebhookEventTypeInfo, WebhookPayloadFormat};
+
+/// Catalog of event types: (event type, entity, description)
+pub const EVENT_TYPES: &[(&str, &str, &str)] = &[
+    ("customer.created", "customer", "A customer was created"),
+    ("customer.updated", "customer", "Customer details changed"),
+    ("customer.deleted", "customer", "A customer was deleted"),
+    ("device.transferred", "device", "A device moved to another customer"),
+    ("revision.created", "revision", "A revision was created"),
+    ("revision.updated", "revision", "A revision was rescheduled or changed"),
+    ("revision.deleted", "revision", "A revision was deleted"),
+    ("visit.created", "visit", "A visit was planned"),
+    ("visit.updated", "visit", "A visit changed (status, time, result)"),
+    ("visit.deleted", "visit", "A visit was deleted"),
+    ("route.created", "route", "A route was saved"),
+    ("route.updated", "route", "A saved route changed"),
+    ("route.deleted", "route", "A route was deleted"),
+];
+
+/// Render an event body in the requested format
+pub fn render_event(
+    format: WebhookPayloadFormat,
+    event_id: Uuid,
+    event_type: &str,
+    occurred_at: DateTime<Utc>,
+    data: &Value,
+) -> Value {
+    match format {
+        WebhookPayloadFormat::Envelope => json!({
+            "id": event_id,
+            "type": event_type,
+            "occurredAt": occurred_at,
+            "data": data,
+        }),
+        WebhookPayloadFormat::Flat => {
+            let mut out = Map::new();
+            out.insert("eventId".to_string(), json!(event_id));
+            out.insert("eventType".to_string(), json!(event_type));
+            out.insert("occurredAt".to_string(), json!(occurred_at));
+            flatten_into(&mut out, "", data);
+            Value::Object(out)
+        }
+    }
+}
+
+/// Flatten nested objects into `parent_child` keys.
+///
+/// Arrays of scalars are kept as-is; arrays containing objects are
+/// serialized to a JSON string so the result stays single-level.
+fn flatten_into(out: &mut Map<String, Value>, prefix: &str, value: &Value) {
+    match value {
+        Value::Object(obj) => {
+            for (key, v) in obj {
+                let name = if prefix.is_empty() { key.clone() } else { format!("{}_{}", prefix, key) };
+                flatten_into(out, &name, v);
+            }
+        }
+        Value::Array(items) if items.iter().any(|i| i.is_object() || i.is_array()) => {
+            out.insert(prefix.to_string(), Value::String(value.to_string()));
+        }
+        _ if prefix.is_empty() => {
+            out.insert("value".to_string(), value.clone());
+        }
+        _ => {
+            out.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+/// Representative entity data for the catalog
+fn sample_entity(entity: &str) -> Value {
+    match entity {
+        "customer" => json!({
+            "id": "6f1c2b7e-0000-4000-8000-000000000001",
+            "type": "person",
+            "name": "Jan Novák",
+            "email": "jan.novak@example.cz",
+            "phone": "+420777123456",
+            "street": "Masarykova 12",
+            "city": "Brno",
+            "postalCode": "60200",
+            "country": "CZ",
+            "lat": 49.1951,
+            "lng": 16.6068,
+        }),
+        "device" => json!({
+            "id": "6f1c2b7e-0000-4000-8000-000000000007",
+            "deviceId": "6f1c2b7e-0000-4000-8000-000000000003",
+            "fromCustomerId": "6f1c2b7e-0000-4000-8000-000000000001",
+            "toCustomerId": "6f1c2b7e-0000-4000-8000-000000000008",
+            "movedRevisionIds": ["6f1c2b7e-0000-4000-8000-000000000002"],
+            "keptRevisionCount": 3,
+            "note": "Property sold",
+            "transferredAt": "2026-05-20T08:30:00Z",
+        }),
+        "revision" => json!({
+            "id": "6f1c2b7e-0000-4000-8000-000000000002",
+            "customerId": "6f1c2b7e-0000-4000-8000-000000000001",
+            "deviceId": "6f1c2b7e-0000-4000-8000-000000000003",
+            "status": "scheduled",
+            "dueDate": "2026-05-31",
+            "scheduledDate": "2026-05-20",
+        }),
+        "visit" => json!({
+            "id": "6f1c2b7e-0000-4000-8000-000000000004",
+            "customerId": "6f1c2b7e-0000-4000-8000-000000000001",
+            "scheduledDate": "2026-05-20",
+            "scheduledTimeStart": "09:00:00",
+            "status": "planned",
+            "customer": { "name": "Jan Novák", "city": "Brno" },
+        }),
+        "route" => json!({
+            "id": "6f1c2b7e-0000-4000-8000-000000000005",
+            "date": "2026-05-20",
+            "crewId": "6f1c2b7e-0000-4000-8000-000000000006",
+            "totalDistanceKm": 84.2,
+            "stopCount": 7,
+        }),
+        _ => json!({}),
+    }
+}
+
+/// Catalog entries with sample payloads in the given format
+pub fn event_catalog(format: WebhookPayloadFormat) -> Vec<WebhookEventTypeInfo> {
+    let sample_time = Utc.with_ymd_and_hms(2026, 5, 20, 8, 30, 0).unwrap();
+    EVENT_TYPES
+        .iter()
+        .map(|(event_type, entity, description)| WebhookEventTypeInfo {
+            event_type: event_type.to_string(),
+            entity: entity.to_string(),
+            description: description.to_string(),
+            sample_payload: render_event(format, Uuid::nil(), event_type, sample_time, &sample_entity(entity)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_envelope_nests_data() {
+        let event = render_event(WebhookPayloadFormat::Envelope, Uuid::nil(), "customer.created", Utc::now(), &json!({"name": "Jan"}));
+        assert_eq!(event["type"], "customer.created");
+        assert_eq!(event["data"]["name"], "Jan");
+    }
+
+    #[test]
+    fn test_render_flat_is_single_level() {
+        let data = json!({"id": "1", "customer": {"name": "Jan", "address": {"city": "Brno"}}, "tags": ["a", "b"]});
+        let event = render_event(WebhookPayloadFormat::Flat, Uuid::nil(), "visit.updated", Utc::now(), &data);
+        let obj = event.as_object().unwrap();
+        assert_eq!(obj["eventType"], "visit.updated");
+        assert_eq!(obj["id"], "1");
+        assert_eq!(obj["customer_name"], "Jan");
+        assert_eq!(obj["customer_address_city"], "Brno");
+        assert_eq!(obj["tags"], json!(["a", "b"]));
+        assert!(obj.values().all(|v| !v.is_object()));
+    }
+
+    #[test]
+    fn test_render_flat_stringifies_object_arrays() {
+        let data = json!({"items": [{"a": 1}]});
+        let event = render_event(WebhookPayloadFormat::Flat, Uuid::nil(), "route.updated", Utc::now(), &data);
+        assert_eq!(event["items"], json!("[{\"a\":1}]"));
+    }
+
+    #[test]
+    fn test_catalog_covers_all_event_types() {
+        let catalog = event_catalog(WebhookPayloadFormat::Flat);
+        assert_eq!(catalog.len(), EVENT_TYPES.len());
+        assert!(catalog.iter().all(|e| e.sample_payload["eventType"] == e.event_type.as_str()));
+    }
+}
@@ -0,0 +1,339 @@
+//! XLSX reading for the CSV imports
+//!
+//! Import files saved from Excel arrive as XLSX workbooks. The first
+//! worksheet is rewritten into our CSV layout (`;`, header row first), so it
+//! goes through the same parsing and header aliases as a CSV file. Cells
+//! with a date or time number format become `YYYY-MM-DD` / `HH:MM` text, the
+//! forms the importers parse; other cells keep their stored value.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+use crate::services::archive::read_limited;
+
+/// Relationship namespace of the `r:id` attribute of a workbook sheet
+const REL_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+/// Worksheet read when the workbook does not say which sheet comes first
+const DEFAULT_SHEET: &str = "xl/worksheets/sheet1.xml";
+/// Largest unpacked workbook part; a small file can inflate to far more
+const MAX_XLSX_ENTRY_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Whether an import file is an Excel workbook, decided by its name
+pub fn is_xlsx_filename(filename: &str) -> bool {
+    filename.trim().to_lowercase().ends_with(".xlsx")
+}
+
+/// How a numeric cell is displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberKind {
+    Plain,
+    Date,
+    DateTime,
+    Time,
+}
+
+/// Kind of a built-in number format id
+fn builtin_kind(id: u32) -> NumberKind {
+    match id {
+        14..=17 => NumberKind::Date,
+        22 => NumberKind::DateTime,
+        18..=21 | 45..=47 => NumberKind::Time,
+        _ => NumberKind::Plain,
+    }
+}
+
+/// Kind of a custom number format code such as `d.m.yyyy` or `hh:mm`
+fn format_code_kind(code: &str) -> NumberKind {
+    // Quoted literals, escaped characters and [colour]/[$-locale] sections
+    // do not describe the value
+    let mut stripped = String::new();
+    let mut chars = code.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                chars.by_ref().find(|&c| c == '"');
+            }
+            '[' => {
+                chars.by_ref().find(|&c| c == ']');
+            }
+            '\\' | '_' | '*' => {
+                chars.next();
+            }
+            c => stripped.push(c.to_ascii_lowercase()),
+        }
+    }
+    let has_date = stripped.contains('d') || stripped.contains('y');
+    let has_time = stripped.contains('h') || stripped.contains('s');
+    match (has_date, has_time) {
+        (true, true) => NumberKind::DateTime,
+        (true, false) => NumberKind::Date,
+        (false, true) => NumberKind::Time,
+        (false, false) => NumberKind::Plain,
+    }
+}
+
+/// Date of an Excel serial day number
+fn serial_to_datetime(serial: f64, date1904: bool) -> Option<NaiveDateTime> {
+    // 1899-12-30 absorbs Excel's fictitious 1900-02-29
+    let epoch = if date1904 {
+        NaiveDate::from_ymd_opt(1904, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(1899, 12, 30)?
+    };
+    if !serial.is_finite() || serial < 0.0 {
+        return None;
+    }
+    let seconds = (serial * 86_400.0).round() as i64;
+    epoch.and_hms_opt(0, 0, 0)?.checked_add_signed(Duration::seconds(seconds))
+}
+
+/// Text of a numeric cell in the given display kind
+fn format_number(value: &str, kind: NumberKind, date1904: bool) -> String {
+    let datetime = match kind {
+        NumberKind::Plain => None,
+        _ => value.parse::<f64>().ok().and_then(|v| serial_to_datetime(v, date1904)),
+    };
+    match (kind, datetime) {
+        (NumberKind::Date, Some(dt)) => dt.format("%Y-%m-%d").to_string(),
+        (NumberKind::DateTime, Some(dt)) => dt.format("%Y-%m-%d %H:%M").to_string(),
+        (NumberKind::Time, Some(dt)) => dt.format("%H:%M").to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Zero-based column of a cell reference such as `AB12`
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<u8> = reference.bytes().take_while(|b| b.is_ascii_alphabetic()).collect();
+    if letters.is_empty() {
+        return None;
+    }
+    letters
+        .iter()
+        .try_fold(0usize, |acc, b| acc.checked_mul(26)?.checked_add((b.to_ascii_uppercase() - b'A' + 1) as usize))
+        .map(|n| n - 1)
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Option<String>> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(anyhow!("import:xlsx_invalid: {}", e)),
+    };
+    read_part(&mut entry, MAX_XLSX_ENTRY_BYTES).map(Some)
+}
+
+/// Read a workbook part as text, refusing parts larger than `max_bytes`
+fn read_part(reader: impl Read, max_bytes: u64) -> Result<String> {
+    let Some(content) = read_limited(reader, max_bytes)? else {
+        return Err(anyhow!("import:xlsx_invalid: workbook part larger than {} bytes", max_bytes));
+    };
+    String::from_utf8(content).map_err(|e| anyhow!("import:xlsx_invalid: {}", e))
+}
+
+fn parse_xml(content: &str) -> Result<roxmltree::Document<'_>> {
+    roxmltree::Document::parse(content).map_err(|e| anyhow!("import:xlsx_invalid: {}", e))
+}
+
+/// Concatenated `<t>` text of a string item, without phonetic runs
+fn string_item_text(node: roxmltree::Node) -> String {
+    node.descendants()
+        .filter(|n| n.is_element() && n.tag_name().name() == "t")
+        .filter(|n| !n.ancestors().any(|a| a.tag_name().name() == "rPh"))
+        .filter_map(|n| n.text())
+        .collect()
+}
+
+/// Path of the first worksheet and whether the workbook uses the 1904 date system
+fn first_sheet(archive: &mut zip::ZipArchive<Cursor<&[u8]>>) -> Result<(String, bool)> {
+    let Some(workbook) = read_entry(archive, "xl/workbook.xml")? else {
+        return Err(anyhow!("import:xlsx_invalid: missing workbook"));
+    };
+    let doc = parse_xml(&workbook)?;
+    let date1904 = doc
+        .descendants()
+        .find(|n| n.has_tag_name("workbookPr"))
+        .and_then(|n| n.attribute("date1904"))
+        .is_some_and(|v| v == "1" || v == "true");
+    let rel_id = doc
+        .descendants()
+        .find(|n| n.has_tag_name("sheet"))
+        .and_then(|n| n.attribute((REL_NS, "id")))
+        .map(str::to_string);
+
+    let target = match (rel_id, read_entry(archive, "xl/_rels/workbook.xml.rels")?) {
+        (Some(rel_id), Some(rels)) => {
+            let rels = parse_xml(&rels)?;
+            rels.descendants()
+                .find(|n| n.has_tag_name("Relationship") && n.attribute("Id") == Some(rel_id.as_str()))
+                .and_then(|n| n.attribute("Target"))
+                .map(|t| match t.strip_prefix('/') {
+                    Some(absolute) => absolute.to_string(),
+                    None => format!("xl/{}", t),
+                })
+        }
+        _ => None,
+    };
+    Ok((target.unwrap_or_else(|| DEFAULT_SHEET.to_string()), date1904))
+}
+
+/// Number kind of every cell style index
+fn style_kinds(archive: &mut zip::ZipArchive<Cursor<&[u8]>>) -> Result<Vec<NumberKind>> {
+    let Some(styles) = read_entry(archive, "xl/styles.xml")? else {
+        return Ok(Vec::new());
+    };
+    let doc = parse_xml(&styles)?;
+    let custom: HashMap<u32, NumberKind> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("numFmt"))
+        .filter_map(|n| {
+            let id = n.attribute("numFmtId")?.parse().ok()?;
+            Some((id, format_code_kind(n.attribute("formatCode")?)))
+        })
+        .collect();
+    let kinds = doc
+        .descendants()
+        .find(|n| n.has_tag_name("cellXfs"))
+        .map(|xfs| {
+            xfs.children()
+                .filter(|n| n.has_tag_name("xf"))
+                .map(|xf| {
+                    let id = xf.attribute("numFmtId").and_then(|v| v.parse().ok()).unwrap_or(0);
+                    custom.get(&id).copied().unwrap_or_else(|| builtin_kind(id))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(kinds)
+}
+
+/// Rewrite the first worksheet of an XLSX workbook as `;`-separated CSV
+pub fn xlsx_to_csv(bytes: &[u8]) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| anyhow!("import:xlsx_invalid: {}", e))?;
+
+    let (sheet_path, date1904) = first_sheet(&mut archive)?;
+    let shared_strings: Vec<String> = match read_entry(&mut archive, "xl/sharedStrings.xml")? {
+        Some(content) => parse_xml(&content)?
+            .root_element()
+            .children()
+            .filter(|n| n.has_tag_name("si"))
+            .map(string_item_text)
+            .collect(),
+        None => Vec::new(),
+    };
+    let kinds = style_kinds(&mut archive)?;
+    let sheet = read_entry(&mut archive, &sheet_path)?.ok_or_else(|| anyhow!("import:xlsx_missing_sheet"))?;
+    let doc = parse_xml(&sheet)?;
+
+    let mut writer = csv::WriterBuilder::new().delimiter(b';').flexible(true).from_writer(Vec::new());
+    for row in doc.descendants().filter(|n| n.has_tag_name("row")) {
+        let mut values: Vec<String> = Vec::new();
+        for cell in row.children().filter(|n| n.has_tag_name("c")) {
+            let col = cell.attribute("r").and_then(column_index).unwrap_or(values.len());
+            let raw = cell.children().find(|n| n.has_tag_name("v")).and_then(|n| n.text()).unwrap_or("");
+            let value = match cell.attribute("t") {
+                Some("s") => raw.parse::<usize>().ok().and_then(|i| shared_strings.get(i)).cloned().unwrap_or_default(),
+                Some("inlineStr") => cell
+                    .children()
+                    .find(|n| n.has_tag_name("is"))
+                    .map(string_item_text)
+                    .unwrap_or_default(),
+                Some("b") => (if raw == "1" { "true" } else { "false" }).to_string(),
+                Some("e") => String::new(),
+                Some("str") => raw.to_string(),
+                _ => {
+                    let kind = cell
+                        .attribute("s")
+                        .and_then(|s| s.parse::<usize>().ok())
+                        .and_then(|s| kinds.get(s).copied())
+                        .unwrap_or(NumberKind::Plain);
+                    format_number(raw, kind, date1904)
+                }
+            };
+            if values.len() <= col {
+                values.resize(col + 1, String::new());
+            }
+            values[col] = value;
+        }
+        // Formatted but empty rows below the data are common in Excel files
+        if values.iter().all(|v| v.trim().is_empty()) {
+            continue;
+        }
+        writer.write_record(&values)?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| anyhow!("{}", e))?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+
+    #[test]
+    fn workbook_becomes_semicolon_csv() {
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        let date = Format::new().set_num_format("d.m.yyyy");
+        sheet.write_string(0, 0, "name").unwrap();
+        sheet.write_string(0, 1, "installation_date").unwrap();
+        sheet.write_string(0, 2, "revision_interval_months").unwrap();
+        sheet.write_string(0, 3, "notes").unwrap();
+        sheet.write_string(1, 0, "Novák; Jan").unwrap();
+        sheet
+            .write_datetime_with_format(1, 1, ExcelDateTime::from_ymd(2024, 3, 15).unwrap(), &date)
+            .unwrap();
+        sheet.write_number(1, 2, 12).unwrap();
+        sheet.write_boolean(1, 3, true).unwrap();
+        // A gap column and an empty formatted row are kept out of the way
+        sheet.write_string(2, 0, "Dvořák").unwrap();
+        sheet.write_number(2, 2, 24).unwrap();
+        sheet.write_string_with_format(4, 0, "", &date).unwrap();
+        let bytes = workbook.save_to_buffer().unwrap();
+
+        let csv = xlsx_to_csv(&bytes).unwrap();
+        assert_eq!(
+            csv,
+            "name;installation_date;revision_interval_months;notes\n\"Novák; Jan\";2024-03-15;12;true\nDvořák;;24\n"
+        );
+    }
+
+    #[test]
+    fn number_formats_are_classified() {
+        assert_eq!(builtin_kind(14), NumberKind::Date);
+        assert_eq!(builtin_kind(20), NumberKind::Time);
+        assert_eq!(builtin_kind(2), NumberKind::Plain);
+        assert_eq!(format_code_kind("d.m.yyyy h:mm"), NumberKind::DateTime);
+        assert_eq!(format_code_kind("[$-405]dd/mm/yy"), NumberKind::Date);
+        assert_eq!(format_code_kind("#,##0 \"Kč/hod\""), NumberKind::Plain);
+    }
+
+    #[test]
+    fn serials_convert_in_both_date_systems() {
+        assert_eq!(format_number("45366", NumberKind::Date, false), "2024-03-15");
+        assert_eq!(format_number("43904", NumberKind::Date, true), "2024-03-15");
+        assert_eq!(format_number("45366.5", NumberKind::DateTime, false), "2024-03-15 12:00");
+        assert_eq!(format_number("0.375", NumberKind::Time, false), "09:00");
+        assert_eq!(format_number("12.5", NumberKind::Plain, false), "12.5");
+    }
+
+    #[test]
+    fn column_references_and_filenames() {
+        assert_eq!(column_index("A1"), Some(0));
+        assert_eq!(column_index("AB12"), Some(27));
+        assert_eq!(column_index("12"), None);
+        assert!(is_xlsx_filename("Zákazníci.XLSX"));
+        assert!(!is_xlsx_filename("customers.csv"));
+        assert!(xlsx_to_csv(b"a;b").is_err());
+    }
+
+    #[test]
+    fn oversized_parts_are_rejected() {
+        assert_eq!(read_part(&b"<x/>"[..], 4).unwrap(), "<x/>");
+        let err = read_part(&b"<x></x>"[..], 4).unwrap_err();
+        assert!(err.to_string().starts_with("import:xlsx_invalid"));
+    }
+}
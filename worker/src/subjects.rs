@@ -0,0 +1,689 @@
+//! NATS subjects
+//!
+//! Every subject the worker subscribes or publishes to, so a typo is a
+//! compile error and a rename touches one place. Job status updates go to
+//! `{prefix}.{job_id}`; build them with [`job_status`] and match all jobs
+//! of a kind with [`wildcard`].
+
+use anyhow::{anyhow, Result};
+use async_nats::{Client, Subscriber};
+
+/// Prefix shared by all subjects
+pub const PREFIX: &str = "sazinka.";
+
+pub const EXPORT: &str = "sazinka.export";
+pub const HEALTH: &str = "sazinka.health";
+pub const PING: &str = "sazinka.ping";
+
+/// Worker-to-worker subjects. They live outside [`PREFIX`], so the gateway
+/// never forwards them from clients.
+pub mod internal {
+    /// Every worker replies with its telemetry counters of the period
+    pub const TELEMETRY_COLLECT: &str = "internal.telemetry.collect";
+}
+
+/// Status subject of one job, e.g. `sazinka.job.geocode.status.{job_id}`
+pub fn job_status(prefix: &str, job_id: impl std::fmt::Display) -> String {
+    format!("{}.{}", prefix, job_id)
+}
+
+/// Wildcard matching one more token, e.g. the status of every job of a kind
+pub fn wildcard(subject: &str) -> String {
+    format!("{}.*", subject)
+}
+
+/// Wildcard matching everything below `subject`
+pub fn tail_wildcard(subject: &str) -> String {
+    format!("{}.>", subject)
+}
+
+/// Whether `subject` matches `pattern`, where `*` stands for one token and
+/// a trailing `>` for one or more
+pub fn matches(pattern: &str, subject: &str) -> bool {
+    let mut tokens = subject.split('.');
+    for part in pattern.split('.') {
+        match (part, tokens.next()) {
+            (">", Some(_)) => return true,
+            ("*", Some(_)) => {}
+            (part, Some(token)) if part == token => {}
+            _ => return false,
+        }
+    }
+    tokens.next().is_none()
+}
+
+/// Whether a client-supplied subject is a concrete subject (no wildcards or
+/// whitespace) under [`PREFIX`]
+pub fn is_public(subject: &str) -> bool {
+    subject.starts_with(PREFIX)
+        && subject.split('.').all(|token| !token.is_empty() && token != "*" && token != ">")
+        && !subject.chars().any(char::is_whitespace)
+}
+
+/// Subscribe to several subjects at once, in order:
+///
+/// ```ignore
+/// let [get_sub, set_sub] = subscribe_all(&client, [coverage::GET, coverage::SET]).await?;
+/// ```
+pub async fn subscribe_all<const N: usize>(client: &Client, subjects: [&'static str; N]) -> Result<[Subscriber; N]> {
+    let mut subscribers = Vec::with_capacity(N);
+    for subject in subjects {
+        subscribers.push(client.subscribe(subject).await?);
+    }
+    subscribers
+        .try_into()
+        .map_err(|_| anyhow!("subscription count mismatch"))
+}
+
+pub mod account {
+    pub const DELETE: &str = "sazinka.account.delete";
+    pub const SUBSCRIPTION: &str = "sazinka.account.subscription";
+    pub const USAGE: &str = "sazinka.account.usage";
+}
+
+pub mod admin {
+    pub const ACCOUNT_TRANSFER_EXPORT: &str = "sazinka.admin.account.transfer.export";
+    pub const ACCOUNT_TRANSFER_IMPORT: &str = "sazinka.admin.account.transfer.import";
+    pub const ALERTS_CRASH: &str = "sazinka.admin.alerts.crash";
+    pub const AUDIT_LIST: &str = "sazinka.admin.audit.list";
+    pub const COUNTRIES_COVERAGE: &str = "sazinka.admin.countries.coverage";
+    pub const COUNTRIES_LIST: &str = "sazinka.admin.countries.list";
+    pub const COUNTRIES_SYNC: &str = "sazinka.admin.countries.sync";
+    pub const COUNTRIES_UPDATE: &str = "sazinka.admin.countries.update";
+    pub const CRASHES_LIST: &str = "sazinka.admin.crashes.list";
+    pub const CUSTOMER_REFERENCES: &str = "sazinka.admin.customer.references";
+    pub const DB_RESET: &str = "sazinka.admin.db.reset";
+    pub const DB_STATUS: &str = "sazinka.admin.db.status";
+    pub const GEOCODE_STATUS: &str = "sazinka.admin.geocode.status";
+    pub const JETSTREAM_STATUS: &str = "sazinka.admin.jetstream.status";
+    pub const LOGS: &str = "sazinka.admin.logs";
+    pub const LOGS_QUERY: &str = "sazinka.admin.logs.query";
+    pub const NOMINATIM_STATUS: &str = "sazinka.admin.nominatim.status";
+    pub const RECORDING_CLEAR: &str = "sazinka.admin.recording.clear";
+    pub const RECORDING_GET: &str = "sazinka.admin.recording.get";
+    pub const RECORDING_SET: &str = "sazinka.admin.recording.set";
+    pub const RESTART_ALL: &str = "sazinka.admin.restart.all";
+    pub const SUBSCRIPTION_GET: &str = "sazinka.admin.subscription.get";
+    pub const SUBSCRIPTION_SET: &str = "sazinka.admin.subscription.set";
+    pub const USERS_LIST: &str = "sazinka.admin.users.list";
+    pub const USERS_RESEND_VERIFICATION: &str = "sazinka.admin.users.resend_verification";
+    pub const USERS_RESET_PASSWORD: &str = "sazinka.admin.users.reset_password";
+    pub const USERS_SET_DISABLED: &str = "sazinka.admin.users.set_disabled";
+    pub const USERS_SET_PLAN: &str = "sazinka.admin.users.set_plan";
+    pub const USERS_SET_ROLE: &str = "sazinka.admin.users.set_role";
+    pub const VALHALLA_DIAGNOSE: &str = "sazinka.admin.valhalla.diagnose";
+    pub const VALHALLA_STATUS: &str = "sazinka.admin.valhalla.status";
+}
+
+pub mod analysis {
+    pub const ISOCHRONE: &str = "sazinka.analysis.isochrone";
+}
+
+pub mod automation {
+    pub const RULE_CREATE: &str = "sazinka.automation.rule.create";
+    pub const RULE_DELETE: &str = "sazinka.automation.rule.delete";
+    pub const RULE_LIST: &str = "sazinka.automation.rule.list";
+    pub const RULE_UPDATE: &str = "sazinka.automation.rule.update";
+    pub const RUN_LIST: &str = "sazinka.automation.run.list";
+}
+
+pub mod auth {
+    pub const DEV_VERIFY: &str = "sazinka.auth.dev.verify";
+    pub const EMAIL_RESEND: &str = "sazinka.auth.email.resend";
+    pub const EMAIL_VERIFY: &str = "sazinka.auth.email.verify";
+    pub const LOGIN: &str = "sazinka.auth.login";
+    pub const LOGINS_LIST: &str = "sazinka.auth.logins.list";
+    pub const PASSWORD_RESET: &str = "sazinka.auth.password.reset";
+    pub const REFRESH: &str = "sazinka.auth.refresh";
+    pub const REGISTER: &str = "sazinka.auth.register";
+    pub const REGISTER_START: &str = "sazinka.auth.register.start";
+    pub const VERIFY: &str = "sazinka.auth.verify";
+    pub const VERIFY_EMAIL: &str = "sazinka.auth.verify_email";
+    pub const WORKER_CREATE: &str = "sazinka.auth.worker.create";
+    pub const WORKER_DELETE: &str = "sazinka.auth.worker.delete";
+    pub const WORKER_LIST: &str = "sazinka.auth.worker.list";
+}
+
+pub mod board {
+    /// Feed of a user's dispatch board for a day, e.g.
+    /// `sazinka.board.{user_id}.2026-05-04`
+    pub fn feed(user_id: uuid::Uuid, date: chrono::NaiveDate) -> String {
+        format!("sazinka.board.{}.{}", user_id, date)
+    }
+
+    /// Pseudonymized feed of the same day for demo-mode sessions
+    pub fn demo_feed(user_id: uuid::Uuid, date: chrono::NaiveDate) -> String {
+        format!("{}.demo", feed(user_id, date))
+    }
+
+    pub const SNAPSHOT: &str = "sazinka.board.snapshot";
+}
+
+pub mod communication {
+    pub const CREATE: &str = "sazinka.communication.create";
+    pub const DELETE: &str = "sazinka.communication.delete";
+    pub const LIST: &str = "sazinka.communication.list";
+    pub const UPDATE: &str = "sazinka.communication.update";
+}
+
+pub mod countries {
+    pub const LIST: &str = "sazinka.countries.list";
+}
+
+pub mod coverage {
+    pub const GET: &str = "sazinka.coverage.get";
+    pub const REPORT: &str = "sazinka.coverage.report";
+    pub const SET: &str = "sazinka.coverage.set";
+}
+
+pub mod crew {
+    pub const CREATE: &str = "sazinka.crew.create";
+    pub const DELETE: &str = "sazinka.crew.delete";
+    pub const LIST: &str = "sazinka.crew.list";
+    pub const UPDATE: &str = "sazinka.crew.update";
+}
+
+pub mod crm_sync {
+    pub const CONNECTOR_CREATE: &str = "sazinka.crm_sync.connector.create";
+    pub const CONNECTOR_DELETE: &str = "sazinka.crm_sync.connector.delete";
+    pub const CONNECTOR_LIST: &str = "sazinka.crm_sync.connector.list";
+    pub const CONNECTOR_UPDATE: &str = "sazinka.crm_sync.connector.update";
+    pub const LOG_LIST: &str = "sazinka.crm_sync.log.list";
+    pub const RUN: &str = "sazinka.crm_sync.run";
+}
+
+pub mod customer {
+    pub const ABANDON: &str = "sazinka.customer.abandon";
+    pub const ANONYMIZE: &str = "sazinka.customer.anonymize";
+    pub const BULK_UPDATE: &str = "sazinka.customer.bulk_update";
+    pub const COLUMN_DISTINCT: &str = "sazinka.customer.column.distinct";
+    pub const CREATE: &str = "sazinka.customer.create";
+    pub const DELETE: &str = "sazinka.customer.delete";
+    pub const GET: &str = "sazinka.customer.get";
+    pub const HIERARCHY_GET: &str = "sazinka.customer.hierarchy.get";
+    pub const HIERARCHY_SET: &str = "sazinka.customer.hierarchy.set";
+    pub const HIERARCHY_SUMMARY: &str = "sazinka.customer.hierarchy.summary";
+    pub const LIST: &str = "sazinka.customer.list";
+    pub const LIST_EXTENDED: &str = "sazinka.customer.list.extended";
+    pub const RANDOM: &str = "sazinka.customer.random";
+    pub const RESTORE: &str = "sazinka.customer.restore";
+    pub const RISK_HISTORY: &str = "sazinka.customer.risk.history";
+    pub const RISK_INCIDENT_CREATE: &str = "sazinka.customer.risk.incident.create";
+    pub const RISK_INCIDENT_DELETE: &str = "sazinka.customer.risk.incident.delete";
+    pub const RISK_INCIDENT_LIST: &str = "sazinka.customer.risk.incident.list";
+    pub const RISK_SETTINGS_GET: &str = "sazinka.customer.risk.settings.get";
+    pub const RISK_SETTINGS_UPDATE: &str = "sazinka.customer.risk.settings.update";
+    pub const SITE_CREATE: &str = "sazinka.customer.site.create";
+    pub const SITE_DELETE: &str = "sazinka.customer.site.delete";
+    pub const SITE_LIST: &str = "sazinka.customer.site.list";
+    pub const SITE_UPDATE: &str = "sazinka.customer.site.update";
+    pub const SUMMARY: &str = "sazinka.customer.summary";
+    pub const UNABANDON: &str = "sazinka.customer.unabandon";
+    pub const UPDATE: &str = "sazinka.customer.update";
+}
+
+pub mod depot {
+    pub const CREATE: &str = "sazinka.depot.create";
+    pub const DELETE: &str = "sazinka.depot.delete";
+    pub const GEOCODE: &str = "sazinka.depot.geocode";
+    pub const LIST: &str = "sazinka.depot.list";
+    pub const UPDATE: &str = "sazinka.depot.update";
+}
+
+pub mod device {
+    pub const CREATE: &str = "sazinka.device.create";
+    pub const DELETE: &str = "sazinka.device.delete";
+    pub const GET: &str = "sazinka.device.get";
+    pub const LIST: &str = "sazinka.device.list";
+    pub const LOOKUP_BY_CODE: &str = "sazinka.device.lookup_by_code";
+    pub const QR: &str = "sazinka.device.qr";
+    pub const RESTORE: &str = "sazinka.device.restore";
+    pub const TRANSFER: &str = "sazinka.device.transfer";
+    pub const TRANSFERS: &str = "sazinka.device.transfers";
+    pub const UPDATE: &str = "sazinka.device.update";
+}
+
+pub mod device_type_config {
+    pub const CREATE: &str = "sazinka.device_type_config.create";
+    pub const GET: &str = "sazinka.device_type_config.get";
+    pub const LIST: &str = "sazinka.device_type_config.list";
+    pub const UPDATE: &str = "sazinka.device_type_config.update";
+}
+
+pub mod device_type_field {
+    pub const CREATE: &str = "sazinka.device_type_field.create";
+    pub const REORDER: &str = "sazinka.device_type_field.reorder";
+    pub const SET_ACTIVE: &str = "sazinka.device_type_field.set_active";
+    pub const UPDATE: &str = "sazinka.device_type_field.update";
+}
+
+pub mod calendar_feed {
+    pub const CREATE: &str = "sazinka.calendar_feed.create";
+    pub const LIST: &str = "sazinka.calendar_feed.list";
+    pub const REVOKE: &str = "sazinka.calendar_feed.revoke";
+}
+
+pub mod campaign {
+    pub const CREATE: &str = "sazinka.campaign.create";
+    pub const DELETE: &str = "sazinka.campaign.delete";
+    pub const LIST: &str = "sazinka.campaign.list";
+    pub const SENDS_LIST: &str = "sazinka.campaign.sends.list";
+    pub const UPDATE: &str = "sazinka.campaign.update";
+}
+
+pub mod catalog {
+    pub const CREATE: &str = "sazinka.catalog.create";
+    pub const LIST: &str = "sazinka.catalog.list";
+    pub const UPDATE: &str = "sazinka.catalog.update";
+}
+
+pub mod escalation {
+    pub const LOG_LIST: &str = "sazinka.escalation.log.list";
+    pub const RULE_CREATE: &str = "sazinka.escalation.rule.create";
+    pub const RULE_DELETE: &str = "sazinka.escalation.rule.delete";
+    pub const RULE_LIST: &str = "sazinka.escalation.rule.list";
+    pub const RULE_UPDATE: &str = "sazinka.escalation.rule.update";
+}
+
+pub mod events {
+    pub const SUBSCRIPTION_CHANGED: &str = "sazinka.events.subscription.changed";
+}
+
+pub mod export {
+    pub const DOWNLOAD: &str = "sazinka.export.download";
+    pub const SUBMIT: &str = "sazinka.export.submit";
+}
+
+pub mod geocode {
+    pub const ADDRESS_SUBMIT: &str = "sazinka.geocode.address.submit";
+    pub const INDEX_STATUS: &str = "sazinka.geocode.index.status";
+    pub const PENDING: &str = "sazinka.geocode.pending";
+    pub const RERUN: &str = "sazinka.geocode.rerun";
+    pub const REVERSE_SUBMIT: &str = "sazinka.geocode.reverse.submit";
+    pub const SUBMIT: &str = "sazinka.geocode.submit";
+}
+
+pub mod hooks {
+    pub const LIST: &str = "sazinka.hooks.list";
+    pub const REGISTER: &str = "sazinka.hooks.register";
+    pub const UNREGISTER: &str = "sazinka.hooks.unregister";
+}
+
+pub mod import {
+    pub const COMMUNICATION_SUBMIT: &str = "sazinka.import.communication.submit";
+    pub const CUSTOMER_SUBMIT: &str = "sazinka.import.customer.submit";
+    pub const CUSTOMER_REF_PREVIEW: &str = "sazinka.import.customer_ref.preview";
+    pub const DEVICE_SUBMIT: &str = "sazinka.import.device.submit";
+    pub const KML_SUBMIT: &str = "sazinka.import.kml.submit";
+    pub const REVISION_SUBMIT: &str = "sazinka.import.revision.submit";
+    pub const UPLOAD_BEGIN: &str = "sazinka.import.upload.begin";
+    pub const UPLOAD_CHUNK: &str = "sazinka.import.upload.chunk";
+    pub const UPLOAD_COMMIT: &str = "sazinka.import.upload.commit";
+    pub const VISIT_SUBMIT: &str = "sazinka.import.visit.submit";
+    pub const WORKLOG_SUBMIT: &str = "sazinka.import.worklog.submit";
+    pub const ZIP_SUBMIT: &str = "sazinka.import.zip.submit";
+}
+
+pub mod inbox {
+    pub const QUERY: &str = "sazinka.inbox.query";
+}
+
+pub mod inbox_state {
+    pub const GET: &str = "sazinka.inbox_state.get";
+    pub const SAVE: &str = "sazinka.inbox_state.save";
+}
+
+pub mod interval_adjustment {
+    pub const APPLY: &str = "sazinka.interval_adjustment.apply";
+    pub const LIST: &str = "sazinka.interval_adjustment.list";
+    pub const PREVIEW: &str = "sazinka.interval_adjustment.preview";
+}
+
+pub mod inventory {
+    pub const MATERIALS_LIST: &str = "sazinka.inventory.materials.list";
+    pub const MOVEMENTS_LIST: &str = "sazinka.inventory.movements.list";
+    pub const STOCK_LIST: &str = "sazinka.inventory.stock.list";
+    pub const STOCK_RESTOCK: &str = "sazinka.inventory.stock.restock";
+    pub const STOCK_SET: &str = "sazinka.inventory.stock.set";
+}
+
+pub mod job {
+    /// Status prefix of an import kind, e.g. "customer" or "worklog"
+    pub fn import_status(kind: &str) -> String {
+        format!("sazinka.job.import.{}.status", kind)
+    }
+
+    pub const EMAIL_STATUS: &str = "sazinka.job.email.status";
+    pub const EXPORT_STATUS: &str = "sazinka.job.export.status";
+    pub const GEOCODE_ADDRESS_STATUS: &str = "sazinka.job.geocode.address.status";
+    pub const GEOCODE_REVERSE_STATUS: &str = "sazinka.job.geocode.reverse.status";
+    pub const GEOCODE_STATUS: &str = "sazinka.job.geocode.status";
+    pub const IMPORT_COMMUNICATION_STATUS: &str = "sazinka.job.import.communication.status";
+    pub const IMPORT_CUSTOMER_STATUS: &str = "sazinka.job.import.customer.status";
+    pub const IMPORT_DEVICE_STATUS: &str = "sazinka.job.import.device.status";
+    pub const IMPORT_KML_STATUS: &str = "sazinka.job.import.kml.status";
+    pub const IMPORT_REVISION_STATUS: &str = "sazinka.job.import.revision.status";
+    pub const IMPORT_STATUS: &str = "sazinka.job.import.status";
+    pub const IMPORT_WORKLOG_STATUS: &str = "sazinka.job.import.worklog.status";
+    pub const IMPORT_ZIP_STATUS: &str = "sazinka.job.import.zip.status";
+    pub const INTERVAL_ADJUSTMENT_STATUS: &str = "sazinka.job.interval_adjustment.status";
+    pub const SMS_STATUS: &str = "sazinka.job.sms.status";
+    pub const STATUS: &str = "sazinka.job.status";
+    pub const VALHALLA_GEOMETRY_STATUS: &str = "sazinka.job.valhalla.geometry.status";
+    pub const VALHALLA_MATRIX_STATUS: &str = "sazinka.job.valhalla.matrix.status";
+}
+
+pub mod jobs {
+    pub const CANCEL: &str = "sazinka.jobs.cancel";
+    pub const EMAIL: &str = "sazinka.jobs.email";
+    pub const GEOCODE: &str = "sazinka.jobs.geocode";
+    pub const GEOCODE_ADDRESS: &str = "sazinka.jobs.geocode.address";
+    pub const GEOCODE_BULK: &str = "sazinka.jobs.geocode.bulk";
+    pub const GEOCODE_REVERSE: &str = "sazinka.jobs.geocode.reverse";
+    pub const HISTORY: &str = "sazinka.jobs.history";
+    pub const IMPORT: &str = "sazinka.jobs.import";
+    pub const LOST: &str = "sazinka.jobs.lost";
+    pub const RETRY: &str = "sazinka.jobs.retry";
+    pub const ROUTE: &str = "sazinka.jobs.route";
+    pub const SMS: &str = "sazinka.jobs.sms";
+    pub const VALHALLA_GEOMETRY: &str = "sazinka.jobs.valhalla.geometry";
+    pub const VALHALLA_MATRIX: &str = "sazinka.jobs.valhalla.matrix";
+    pub const WEBHOOK: &str = "sazinka.jobs.webhook";
+}
+
+pub mod lead {
+    pub const HISTORY: &str = "sazinka.lead.history";
+    pub const INTAKE_GET: &str = "sazinka.lead.intake.get";
+    pub const INTAKE_ROTATE: &str = "sazinka.lead.intake.rotate";
+    pub const INTAKE_UPDATE: &str = "sazinka.lead.intake.update";
+    pub const TRANSITION: &str = "sazinka.lead.transition";
+}
+
+pub mod map {
+    pub const SNAPSHOT_CUSTOMER: &str = "sazinka.map.snapshot.customer";
+    pub const SNAPSHOT_ROUTE: &str = "sazinka.map.snapshot.route";
+}
+
+pub mod note {
+    pub const AUDIT: &str = "sazinka.note.audit";
+    pub const CREATE: &str = "sazinka.note.create";
+    pub const DELETE: &str = "sazinka.note.delete";
+    pub const LIST: &str = "sazinka.note.list";
+    pub const UPDATE: &str = "sazinka.note.update";
+}
+
+pub mod notification {
+    pub const LIST: &str = "sazinka.notification.list";
+    pub const PREFERENCES_LIST: &str = "sazinka.notification.preferences.list";
+    pub const PREFERENCES_SET: &str = "sazinka.notification.preferences.set";
+    pub const READ: &str = "sazinka.notification.read";
+}
+
+pub mod onboarding {
+    pub const COMPLETE: &str = "sazinka.onboarding.complete";
+    pub const DEVICES: &str = "sazinka.onboarding.devices";
+    pub const PROFILE: &str = "sazinka.onboarding.profile";
+}
+
+pub mod org {
+    pub const GET: &str = "sazinka.org.get";
+    pub const INVITE_ACCEPT: &str = "sazinka.org.invite.accept";
+    pub const INVITE_CREATE: &str = "sazinka.org.invite.create";
+    pub const INVITE_LIST: &str = "sazinka.org.invite.list";
+    pub const INVITE_REVOKE: &str = "sazinka.org.invite.revoke";
+    pub const MEMBER_REMOVE: &str = "sazinka.org.member.remove";
+    pub const MEMBER_UPDATE: &str = "sazinka.org.member.update";
+    pub const UPDATE: &str = "sazinka.org.update";
+}
+
+pub mod planned_action {
+    pub const CANCEL: &str = "sazinka.planned_action.cancel";
+    pub const COMPLETE: &str = "sazinka.planned_action.complete";
+    pub const CREATE: &str = "sazinka.planned_action.create";
+    pub const GET: &str = "sazinka.planned_action.get";
+    pub const LIST: &str = "sazinka.planned_action.list";
+    pub const UPDATE: &str = "sazinka.planned_action.update";
+}
+
+pub mod portal {
+    pub const CALENDAR_FEED: &str = "sazinka.portal.calendar.feed";
+    pub const CERTIFICATE_VERIFY: &str = "sazinka.portal.certificate.verify";
+    pub const LEAD_SUBMIT: &str = "sazinka.portal.lead.submit";
+    pub const QUOTE_DECIDE: &str = "sazinka.portal.quote.decide";
+    pub const QUOTE_GET: &str = "sazinka.portal.quote.get";
+    pub const RESCHEDULE_GET: &str = "sazinka.portal.reschedule.get";
+    pub const RESCHEDULE_REQUEST: &str = "sazinka.portal.reschedule.request";
+}
+
+pub mod presence {
+    pub const HEARTBEAT: &str = "sazinka.presence.heartbeat";
+    pub const LEAVE: &str = "sazinka.presence.leave";
+    pub const LIST: &str = "sazinka.presence.list";
+}
+
+pub mod quality {
+    pub const REPORT: &str = "sazinka.quality.report";
+}
+
+pub mod quote {
+    pub const CREATE: &str = "sazinka.quote.create";
+    pub const DELETE: &str = "sazinka.quote.delete";
+    pub const GET: &str = "sazinka.quote.get";
+    pub const LIST: &str = "sazinka.quote.list";
+    pub const PDF: &str = "sazinka.quote.pdf";
+    pub const SEND: &str = "sazinka.quote.send";
+    pub const UPDATE: &str = "sazinka.quote.update";
+}
+
+pub mod report {
+    pub const ACQUISITION_SOURCES: &str = "sazinka.report.acquisition_sources";
+    pub const CAPACITY_FORECAST: &str = "sazinka.report.capacity_forecast";
+    pub const LEAD_FUNNEL: &str = "sazinka.report.lead_funnel";
+    pub const RESTOCK: &str = "sazinka.report.restock";
+    pub const WEATHER_OUTCOMES: &str = "sazinka.report.weather_outcomes";
+    pub const WORKLOAD: &str = "sazinka.report.workload";
+}
+
+pub mod reschedule {
+    pub const ACCEPT: &str = "sazinka.reschedule.accept";
+    pub const DECLINE: &str = "sazinka.reschedule.decline";
+    pub const LINK_CREATE: &str = "sazinka.reschedule.link.create";
+    pub const LIST: &str = "sazinka.reschedule.list";
+    pub const STATS: &str = "sazinka.reschedule.stats";
+}
+
+pub mod retention {
+    pub const POLICY_LIST: &str = "sazinka.retention.policy.list";
+    pub const POLICY_SET: &str = "sazinka.retention.policy.set";
+    pub const REPORT: &str = "sazinka.retention.report";
+}
+
+pub mod revision {
+    pub const COMPLETE: &str = "sazinka.revision.complete";
+    pub const CREATE: &str = "sazinka.revision.create";
+    pub const DELETE: &str = "sazinka.revision.delete";
+    pub const GET: &str = "sazinka.revision.get";
+    pub const LIST: &str = "sazinka.revision.list";
+    pub const NUMBERS_LIST: &str = "sazinka.revision.numbers.list";
+    pub const QUEUE: &str = "sazinka.revision.queue";
+    pub const REPORT_PDF: &str = "sazinka.revision.report_pdf";
+    pub const SCHEDULE: &str = "sazinka.revision.schedule";
+    pub const SNOOZE: &str = "sazinka.revision.snooze";
+    pub const STATS: &str = "sazinka.revision.stats";
+    pub const SUGGEST: &str = "sazinka.revision.suggest";
+    pub const UNSCHEDULE: &str = "sazinka.revision.unschedule";
+    pub const UPCOMING: &str = "sazinka.revision.upcoming";
+    pub const UPDATE: &str = "sazinka.revision.update";
+}
+
+pub mod role {
+    pub const ASSIGN: &str = "sazinka.role.assign";
+    pub const CREATE: &str = "sazinka.role.create";
+    pub const DELETE: &str = "sazinka.role.delete";
+    pub const GET: &str = "sazinka.role.get";
+    pub const LIST: &str = "sazinka.role.list";
+    pub const UNASSIGN: &str = "sazinka.role.unassign";
+    pub const UPDATE: &str = "sazinka.role.update";
+}
+
+pub mod route {
+    pub const ANALYSIS: &str = "sazinka.route.analysis";
+    pub const BREAK_SUGGEST: &str = "sazinka.route.break.suggest";
+    pub const DELETE: &str = "sazinka.route.delete";
+    pub const GET: &str = "sazinka.route.get";
+    pub const INSERTION_BATCH: &str = "sazinka.route.insertion.batch";
+    pub const INSERTION_CALCULATE: &str = "sazinka.route.insertion.calculate";
+    pub const INSERTION_MULTI_DAY: &str = "sazinka.route.insertion.multi_day";
+    pub const LIST: &str = "sazinka.route.list";
+    pub const LIST_FOR_DATE: &str = "sazinka.route.list_for_date";
+    pub const LOCK: &str = "sazinka.route.lock";
+    pub const PLAN: &str = "sazinka.route.plan";
+    pub const PLAN_FROM_HISTORY: &str = "sazinka.route.plan.from_history";
+    pub const PLAN_MULTIDAY: &str = "sazinka.route.plan.multiday";
+    pub const RECALCULATE: &str = "sazinka.route.recalculate";
+    pub const REOPTIMIZE_LIVE: &str = "sazinka.route.reoptimize.live";
+    pub const SAVE: &str = "sazinka.route.save";
+    pub const STOP_NOTE_UPDATE: &str = "sazinka.route.stop.note.update";
+    pub const SUBMIT: &str = "sazinka.route.submit";
+    pub const UNLOCK: &str = "sazinka.route.unlock";
+    pub const UPDATE: &str = "sazinka.route.update";
+}
+
+pub mod scoring {
+    pub const RULE_SET_ARCHIVE: &str = "sazinka.scoring.rule_set.archive";
+    pub const RULE_SET_CREATE: &str = "sazinka.scoring.rule_set.create";
+    pub const RULE_SET_DELETE: &str = "sazinka.scoring.rule_set.delete";
+    pub const RULE_SET_LIST: &str = "sazinka.scoring.rule_set.list";
+    pub const RULE_SET_RESTORE_DEFAULTS: &str = "sazinka.scoring.rule_set.restore_defaults";
+    pub const RULE_SET_SET_DEFAULT: &str = "sazinka.scoring.rule_set.set_default";
+    pub const RULE_SET_UPDATE: &str = "sazinka.scoring.rule_set.update";
+}
+
+pub mod settings {
+    pub const BREAK_UPDATE: &str = "sazinka.settings.break.update";
+    pub const BUSINESS_UPDATE: &str = "sazinka.settings.business.update";
+    pub const CUSTOMER_CODES_UPDATE: &str = "sazinka.settings.customer_codes.update";
+    pub const EMAIL_UPDATE: &str = "sazinka.settings.email.update";
+    pub const GET: &str = "sazinka.settings.get";
+    pub const NUMBERING_UPDATE: &str = "sazinka.settings.numbering.update";
+    pub const PREFERENCES_UPDATE: &str = "sazinka.settings.preferences.update";
+    pub const TELEMETRY_GET: &str = "sazinka.settings.telemetry.get";
+    pub const TELEMETRY_SET: &str = "sazinka.settings.telemetry.set";
+    pub const TEMPLATES_DELETE: &str = "sazinka.settings.templates.delete";
+    pub const TEMPLATES_LIST: &str = "sazinka.settings.templates.list";
+    pub const TEMPLATES_UPSERT: &str = "sazinka.settings.templates.upsert";
+    pub const WORK_UPDATE: &str = "sazinka.settings.work.update";
+}
+
+pub mod slots {
+    pub const CALENDAR: &str = "sazinka.slots.calendar";
+    pub const SUGGEST: &str = "sazinka.slots.suggest";
+    pub const SUGGEST_V2: &str = "sazinka.slots.suggest.v2";
+    pub const VALIDATE: &str = "sazinka.slots.validate";
+}
+
+pub mod subcontractor {
+    pub const ACCOUNT_CREATE: &str = "sazinka.subcontractor.account.create";
+    pub const CREATE: &str = "sazinka.subcontractor.create";
+    pub const DELETE: &str = "sazinka.subcontractor.delete";
+    pub const LIST: &str = "sazinka.subcontractor.list";
+    pub const MY_ROUTES: &str = "sazinka.subcontractor.my.routes";
+    pub const MY_VISITS: &str = "sazinka.subcontractor.my.visits";
+    pub const RATES_GET: &str = "sazinka.subcontractor.rates.get";
+    pub const RATES_SET: &str = "sazinka.subcontractor.rates.set";
+    pub const SETTLEMENT: &str = "sazinka.subcontractor.settlement";
+    pub const UPDATE: &str = "sazinka.subcontractor.update";
+}
+
+pub mod task {
+    pub const COMPLETE: &str = "sazinka.task.complete";
+    pub const CREATE: &str = "sazinka.task.create";
+    pub const GET: &str = "sazinka.task.get";
+    pub const LIST: &str = "sazinka.task.list";
+    pub const UPDATE: &str = "sazinka.task.update";
+}
+
+pub mod task_type {
+    pub const CREATE: &str = "sazinka.task_type.create";
+    pub const LIST: &str = "sazinka.task_type.list";
+    pub const UPDATE: &str = "sazinka.task_type.update";
+}
+
+pub mod user {
+    pub const ROLES_GET: &str = "sazinka.user.roles.get";
+    pub const ROLES_SET: &str = "sazinka.user.roles.set";
+}
+
+pub mod valhalla {
+    pub const GEOMETRY_SUBMIT: &str = "sazinka.valhalla.geometry.submit";
+    pub const MATRIX_SUBMIT: &str = "sazinka.valhalla.matrix.submit";
+}
+
+pub mod visit {
+    pub const COMPLETE: &str = "sazinka.visit.complete";
+    pub const CREATE: &str = "sazinka.visit.create";
+    pub const DELETE: &str = "sazinka.visit.delete";
+    pub const DURATION_GET: &str = "sazinka.visit.duration.get";
+    pub const DURATION_LEARNED: &str = "sazinka.visit.duration.learned";
+    pub const DURATION_SET: &str = "sazinka.visit.duration.set";
+    pub const GET: &str = "sazinka.visit.get";
+    pub const LIST: &str = "sazinka.visit.list";
+    pub const NOTES_HISTORY: &str = "sazinka.visit.notes.history";
+    pub const PROGRESS: &str = "sazinka.visit.progress";
+    pub const UPDATE: &str = "sazinka.visit.update";
+    pub const UPDATE_FIELD_NOTES: &str = "sazinka.visit.update_field_notes";
+}
+
+pub mod waitlist {
+    pub const JOIN: &str = "sazinka.waitlist.join";
+}
+
+pub mod webhook {
+    pub const CREATE: &str = "sazinka.webhook.create";
+    pub const DELETE: &str = "sazinka.webhook.delete";
+    pub const EVENT_TYPES: &str = "sazinka.webhook.event_types";
+    pub const LIST: &str = "sazinka.webhook.list";
+    pub const TEST: &str = "sazinka.webhook.test";
+}
+
+pub mod work_item {
+    pub const COMPLETE: &str = "sazinka.work_item.complete";
+    pub const CREATE: &str = "sazinka.work_item.create";
+    pub const GET: &str = "sazinka.work_item.get";
+    pub const LIST: &str = "sazinka.work_item.list";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("sazinka.jobs.sms.*", "sazinka.jobs.sms.reminder"));
+        assert!(!matches("sazinka.jobs.sms.*", "sazinka.jobs.sms"));
+        assert!(!matches("sazinka.jobs.sms.*", "sazinka.jobs.sms.reminder.extra"));
+        assert!(matches("sazinka.jobs.>", "sazinka.jobs.sms.reminder"));
+        assert!(!matches("sazinka.jobs.>", "sazinka.jobs"));
+        assert!(matches(route::GET, "sazinka.route.get"));
+        assert!(!matches(route::GET, "sazinka.route.get.x"));
+    }
+
+    #[test]
+    fn test_board_feed() {
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 5, 4).unwrap();
+        let feed = board::feed(uuid::Uuid::nil(), date);
+        assert_eq!(feed, "sazinka.board.00000000-0000-0000-0000-000000000000.2026-05-04");
+        assert!(is_public(&feed));
+        assert!(!matches(&feed, board::SNAPSHOT));
+        assert_eq!(board::demo_feed(uuid::Uuid::nil(), date), format!("{}.demo", feed));
+    }
+
+    #[test]
+    fn test_is_public() {
+        assert!(is_public(route::GET));
+        assert!(!is_public("_INBOX.abc"));
+        assert!(!is_public("sazinka.route.*"));
+        assert!(!is_public("sazinka..get"));
+        assert!(!is_public("sazinka.route get"));
+    }
+}
@@ -0,0 +1,417 @@
+//! Embedded message broker
+//!
+//! A minimal in-process server speaking the core NATS client protocol
+//! (CONNECT, PUB/HPUB, SUB/UNSUB, PING/PONG) on a loopback port, so handlers
+//! keep using a regular `async_nats::Client` in standalone mode. Supports
+//! wildcards, queue groups, `max_msgs` auto-unsubscribe and "no responders"
+//! replies; clustering, auth and JetStream are out of scope (jobs go through
+//! the local [`JobQueue`](super::queue::JobQueue) instead).
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use bytes::{Bytes, BytesMut};
+use parking_lot::RwLock;
+use rand::Rng;
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::subjects;
+
+/// Largest accepted payload, matching the production NATS config
+pub const MAX_PAYLOAD: usize = 8 * 1024 * 1024;
+/// Longest accepted protocol line
+const MAX_CONTROL_LINE: usize = 4096;
+/// Status message sent to a requester when nobody listens on the subject
+const NO_RESPONDERS_HEADER: &[u8] = b"NATS/1.0 503\r\n\r\n";
+
+/// The running broker
+pub struct Broker {
+    addr: SocketAddr,
+}
+
+impl Broker {
+    /// Start accepting clients on an ephemeral loopback port
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.context("Failed to bind embedded broker")?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(BrokerState::default());
+
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((socket, _)) => {
+                        let state = Arc::clone(&state);
+                        tokio::spawn(async move {
+                            if let Err(e) = serve_client(state, socket, addr).await {
+                                debug!("Embedded broker client closed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => warn!("Embedded broker accept failed: {}", e),
+                }
+            }
+        });
+
+        Ok(Self { addr })
+    }
+
+    /// URL clients connect to
+    pub fn url(&self) -> String {
+        format!("nats://{}", self.addr)
+    }
+}
+
+#[derive(Default)]
+struct BrokerState {
+    next_client: AtomicU64,
+    subscriptions: RwLock<Vec<Subscription>>,
+}
+
+struct Subscription {
+    client: u64,
+    sid: String,
+    subject: String,
+    queue: Option<String>,
+    /// Messages left before auto-unsubscribe
+    remaining: Option<u64>,
+    sender: mpsc::UnboundedSender<Bytes>,
+}
+
+/// Options a client sends with CONNECT
+#[derive(Debug, Default, Deserialize)]
+struct ConnectOptions {
+    #[serde(default)]
+    headers: bool,
+    #[serde(default)]
+    no_responders: bool,
+    #[serde(default)]
+    verbose: bool,
+}
+
+/// One client-to-server operation
+#[derive(Debug, PartialEq)]
+enum Op {
+    Connect(String),
+    Pub { subject: String, reply: Option<String>, header_len: usize, total_len: usize },
+    Sub { subject: String, queue: Option<String>, sid: String },
+    Unsub { sid: String, max: Option<u64> },
+    Ping,
+    Pong,
+}
+
+fn parse_op(line: &str) -> Result<Op> {
+    let (verb, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let args: Vec<&str> = rest.split_whitespace().collect();
+    let number = |arg: &str| arg.parse::<usize>().map_err(|_| anyhow!("invalid size '{}'", arg));
+    match (verb.to_ascii_uppercase().as_str(), args.as_slice()) {
+        ("CONNECT", _) => Ok(Op::Connect(rest.to_string())),
+        ("PUB", [subject, len]) => Ok(Op::Pub { subject: subject.to_string(), reply: None, header_len: 0, total_len: number(len)? }),
+        ("PUB", [subject, reply, len]) => Ok(Op::Pub {
+            subject: subject.to_string(),
+            reply: Some(reply.to_string()),
+            header_len: 0,
+            total_len: number(len)?,
+        }),
+        ("HPUB", [subject, header_len, total_len]) => Ok(Op::Pub {
+            subject: subject.to_string(),
+            reply: None,
+            header_len: number(header_len)?,
+            total_len: number(total_len)?,
+        }),
+        ("HPUB", [subject, reply, header_len, total_len]) => Ok(Op::Pub {
+            subject: subject.to_string(),
+            reply: Some(reply.to_string()),
+            header_len: number(header_len)?,
+            total_len: number(total_len)?,
+        }),
+        ("SUB", [subject, sid]) => Ok(Op::Sub { subject: subject.to_string(), queue: None, sid: sid.to_string() }),
+        ("SUB", [subject, queue, sid]) => Ok(Op::Sub {
+            subject: subject.to_string(),
+            queue: Some(queue.to_string()),
+            sid: sid.to_string(),
+        }),
+        ("UNSUB", [sid]) => Ok(Op::Unsub { sid: sid.to_string(), max: None }),
+        ("UNSUB", [sid, max]) => Ok(Op::Unsub { sid: sid.to_string(), max: Some(number(max)? as u64) }),
+        ("PING", []) => Ok(Op::Ping),
+        ("PONG", []) => Ok(Op::Pong),
+        _ => Err(anyhow!("unknown protocol operation '{}'", verb)),
+    }
+}
+
+/// Encode a MSG/HMSG frame for one subscriber
+fn message_frame(subject: &str, sid: &str, reply: Option<&str>, header_len: usize, body: &[u8]) -> Bytes {
+    let reply = reply.map(|r| format!(" {}", r)).unwrap_or_default();
+    let head = if header_len > 0 {
+        format!("HMSG {} {}{} {} {}\r\n", subject, sid, reply, header_len, body.len())
+    } else {
+        format!("MSG {} {}{} {}\r\n", subject, sid, reply, body.len())
+    };
+    let mut frame = BytesMut::with_capacity(head.len() + body.len() + 2);
+    frame.extend_from_slice(head.as_bytes());
+    frame.extend_from_slice(body);
+    frame.extend_from_slice(b"\r\n");
+    frame.freeze()
+}
+
+impl BrokerState {
+    /// Deliver a message; returns whether anyone received it
+    fn route(&self, subject: &str, reply: Option<&str>, header_len: usize, body: &[u8]) -> bool {
+        let mut delivered = false;
+        let mut queue_members: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut targets = Vec::new();
+
+        let subscriptions = self.subscriptions.read();
+        for (index, sub) in subscriptions.iter().enumerate() {
+            if !subjects::matches(&sub.subject, subject) {
+                continue;
+            }
+            match &sub.queue {
+                Some(queue) => queue_members.entry(queue.as_str()).or_default().push(index),
+                None => targets.push(index),
+            }
+        }
+        // One member of each queue group gets the message
+        for members in queue_members.values() {
+            targets.push(members[rand::thread_rng().gen_range(0..members.len())]);
+        }
+        let mut exhausted = Vec::new();
+        for index in targets {
+            let sub = &subscriptions[index];
+            let frame = message_frame(subject, &sub.sid, reply, header_len, body);
+            if sub.sender.send(frame).is_ok() {
+                delivered = true;
+            }
+            if sub.remaining == Some(1) {
+                exhausted.push((sub.client, sub.sid.clone()));
+            }
+        }
+        drop(subscriptions);
+
+        let mut subscriptions = self.subscriptions.write();
+        for sub in subscriptions.iter_mut() {
+            if subjects::matches(&sub.subject, subject) {
+                if let Some(remaining) = sub.remaining.as_mut() {
+                    *remaining = remaining.saturating_sub(1);
+                }
+            }
+        }
+        subscriptions.retain(|sub| !exhausted.contains(&(sub.client, sub.sid.clone())));
+        delivered
+    }
+
+    fn unsubscribe(&self, client: u64, sid: &str, max: Option<u64>) {
+        let mut subscriptions = self.subscriptions.write();
+        match max {
+            Some(max) if max > 0 => {
+                if let Some(sub) = subscriptions.iter_mut().find(|sub| sub.client == client && sub.sid == sid) {
+                    sub.remaining = Some(max);
+                }
+            }
+            _ => subscriptions.retain(|sub| !(sub.client == client && sub.sid == sid)),
+        }
+    }
+
+    fn drop_client(&self, client: u64) {
+        self.subscriptions.write().retain(|sub| sub.client != client);
+    }
+}
+
+async fn serve_client(state: Arc<BrokerState>, socket: TcpStream, addr: SocketAddr) -> Result<()> {
+    let client_id = state.next_client.fetch_add(1, Ordering::Relaxed) + 1;
+    let result = client_loop(&state, client_id, socket, addr).await;
+    state.drop_client(client_id);
+    result
+}
+
+async fn client_loop(state: &BrokerState, client_id: u64, socket: TcpStream, addr: SocketAddr) -> Result<()> {
+    let (read_half, mut write_half) = socket.into_split();
+    let (sender, mut outbox) = mpsc::unbounded_channel::<Bytes>();
+
+    let info = serde_json::json!({
+        "server_id": "sazinka-embedded",
+        "server_name": "sazinka-embedded",
+        "version": "2.10.0",
+        "proto": 1,
+        "host": addr.ip().to_string(),
+        "port": addr.port(),
+        "headers": true,
+        "max_payload": MAX_PAYLOAD,
+        "client_id": client_id,
+        "client_ip": "127.0.0.1",
+    });
+    sender.send(Bytes::from(format!("INFO {}\r\n", info)))?;
+
+    let writer = tokio::spawn(async move {
+        while let Some(frame) = outbox.recv().await {
+            if write_half.write_all(&frame).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut reader = BufReader::new(read_half);
+    let mut options = ConnectOptions::default();
+    let mut line = String::new();
+    let result = loop {
+        line.clear();
+        let read = (&mut reader).take(MAX_CONTROL_LINE as u64).read_line(&mut line).await?;
+        if read == 0 {
+            break Ok(());
+        }
+        if !line.ends_with('\n') {
+            let _ = sender.send(Bytes::from_static(b"-ERR 'Maximum Control Line Exceeded'\r\n"));
+            break Err(anyhow!("control line too long"));
+        }
+        let trimmed = line.trim_end();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let op = match parse_op(trimmed) {
+            Ok(op) => op,
+            Err(e) => {
+                let _ = sender.send(Bytes::from_static(b"-ERR 'Unknown Protocol Operation'\r\n"));
+                break Err(e);
+            }
+        };
+        match op {
+            Op::Connect(json) => {
+                options = serde_json::from_str(&json).unwrap_or_default();
+            }
+            Op::Ping => {
+                sender.send(Bytes::from_static(b"PONG\r\n"))?;
+            }
+            Op::Pong => {}
+            Op::Sub { subject, queue, sid } => {
+                state.subscriptions.write().push(Subscription {
+                    client: client_id,
+                    sid,
+                    subject,
+                    queue,
+                    remaining: None,
+                    sender: sender.clone(),
+                });
+            }
+            Op::Unsub { sid, max } => state.unsubscribe(client_id, &sid, max),
+            Op::Pub { subject, reply, header_len, total_len } => {
+                if total_len > MAX_PAYLOAD || header_len > total_len {
+                    let _ = sender.send(Bytes::from_static(b"-ERR 'Maximum Payload Violation'\r\n"));
+                    break Err(anyhow!("payload of {} bytes rejected", total_len));
+                }
+                let mut body = vec![0u8; total_len + 2];
+                reader.read_exact(&mut body).await?;
+                body.truncate(total_len);
+
+                let delivered = state.route(&subject, reply.as_deref(), header_len, &body);
+                if let (false, Some(reply)) = (delivered, reply.as_deref()) {
+                    if options.headers && options.no_responders {
+                        state.route(reply, None, NO_RESPONDERS_HEADER.len(), NO_RESPONDERS_HEADER);
+                    }
+                }
+            }
+        }
+        if options.verbose {
+            let _ = sender.send(Bytes::from_static(b"+OK\r\n"));
+        }
+    };
+
+    writer.abort();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_op() {
+        assert_eq!(
+            parse_op("PUB sazinka.ping _INBOX.1 5").unwrap(),
+            Op::Pub { subject: "sazinka.ping".into(), reply: Some("_INBOX.1".into()), header_len: 0, total_len: 5 }
+        );
+        assert_eq!(
+            parse_op("HPUB a.b 12 20").unwrap(),
+            Op::Pub { subject: "a.b".into(), reply: None, header_len: 12, total_len: 20 }
+        );
+        assert_eq!(
+            parse_op("SUB a.* workers 7").unwrap(),
+            Op::Sub { subject: "a.*".into(), queue: Some("workers".into()), sid: "7".into() }
+        );
+        assert_eq!(parse_op("UNSUB 7 1").unwrap(), Op::Unsub { sid: "7".into(), max: Some(1) });
+        assert_eq!(parse_op("ping").unwrap(), Op::Ping);
+        assert!(parse_op("PUB a.b").is_err());
+        assert!(parse_op("NOPE").is_err());
+    }
+
+    #[test]
+    fn test_message_frame() {
+        assert_eq!(&message_frame("a.b", "1", None, 0, b"hi")[..], b"MSG a.b 1 2\r\nhi\r\n");
+        assert_eq!(&message_frame("a.b", "1", Some("r"), 0, b"")[..], b"MSG a.b 1 r 0\r\n\r\n");
+        assert_eq!(&message_frame("a.b", "1", None, 2, b"hhbb")[..], b"HMSG a.b 1 2 4\r\nhhbb\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_request_reply_through_embedded_broker() {
+        let broker = Broker::start().await.unwrap();
+        let client = async_nats::connect(broker.url()).await.unwrap();
+
+        let mut sub = client.subscribe("sazinka.test.echo").await.unwrap();
+        let responder = client.clone();
+        tokio::spawn(async move {
+            while let Some(msg) = sub.next().await {
+                if let Some(reply) = msg.reply {
+                    responder.publish(reply, msg.payload).await.unwrap();
+                }
+            }
+        });
+        client.flush().await.unwrap();
+
+        let response = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.request("sazinka.test.echo", Bytes::from_static(b"hello")),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(response.payload, Bytes::from_static(b"hello"));
+
+        let missing = client.request("sazinka.test.nobody", Bytes::new()).await;
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_queue_group_delivers_once() {
+        let broker = Broker::start().await.unwrap();
+        let client = async_nats::connect(broker.url()).await.unwrap();
+
+        let mut first = client.queue_subscribe("sazinka.test.work", "workers".to_string()).await.unwrap();
+        let mut second = client.queue_subscribe("sazinka.test.work", "workers".to_string()).await.unwrap();
+        let mut watcher = client.subscribe("sazinka.test.*").await.unwrap();
+        client.flush().await.unwrap();
+
+        client.publish("sazinka.test.work", Bytes::from_static(b"job")).await.unwrap();
+        client.flush().await.unwrap();
+
+        let seen = tokio::time::timeout(Duration::from_secs(5), watcher.next()).await.unwrap().unwrap();
+        assert_eq!(seen.subject.as_str(), "sazinka.test.work");
+
+        let received = tokio::select! {
+            Some(_) = first.next() => 1,
+            Some(_) = second.next() => 2,
+        };
+        let other = match received {
+            1 => tokio::time::timeout(Duration::from_millis(100), second.next()).await,
+            _ => tokio::time::timeout(Duration::from_millis(100), first.next()).await,
+        };
+        assert!(other.is_err(), "queue group member {} was not the only receiver", received);
+    }
+}
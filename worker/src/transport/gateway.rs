@@ -0,0 +1,428 @@
+//! HTTP gateway of standalone mode
+//!
+//! Browsers cannot reach the embedded broker, so the worker serves the
+//! same request/reply API over HTTP:
+//!
+//! - `POST /api/{subject}` sends the JSON body as a request on `subject`
+//!   and answers with the handler's reply (503 when nothing handles the
+//!   subject, 504 on timeout)
+//! - `GET /api/events?subject={pattern}` streams messages published on a
+//!   subject or wildcard pattern (job status updates) as server-sent events
+//! - `GET /calendar/{token}.ics` serves a calendar feed as `text/calendar`
+//!   (`sazinka.portal.calendar.feed`) to calendar apps
+//! - `GET /health` for load balancers and uptime checks
+//! - `GET /healthz` and `GET /readyz`, liveness and readiness probes with
+//!   the dependency report of this process (`services::health`); 503 when
+//!   the worker is not live or not ready
+//!
+//! In NATS mode, browsers reach the API through the NATS server; calendar
+//! apps can only fetch plain HTTP, so [`spawn_feeds`] serves the calendar
+//! and health paths alone, and [`spawn_health`] only the health paths.
+//!
+//! Like the browser account of the NATS server, the gateway only reaches
+//! `sazinka.>` subjects. It stamps `clientIp` on every request with the
+//! address it saw, so public handlers can rate limit by IP.
+
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_nats::Client;
+use bytes::Bytes;
+use futures::StreamExt;
+use http_body_util::{combinators::BoxBody, BodyExt, Full, Limited, StreamBody};
+use hyper::body::{Frame, Incoming};
+use hyper::header::{HeaderMap, HeaderValue, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, CACHE_CONTROL, CONTENT_TYPE};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use super::broker::MAX_PAYLOAD;
+use crate::services::health;
+use crate::subjects;
+use crate::types::ErrorResponse;
+
+/// Longest a handler may take to answer (route optimization is the slowest)
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+const API_PREFIX: &str = "/api/";
+const EVENTS_PATH: &str = "/api/events";
+const CALENDAR_PREFIX: &str = "/calendar/";
+const CALENDAR_SUFFIX: &str = ".ics";
+/// Rendering a feed reads a few months of routes and visits
+const FEED_TIMEOUT: Duration = Duration::from_secs(30);
+
+type Body = BoxBody<Bytes, Infallible>;
+
+/// Shared state of the gateway's connections
+#[derive(Clone)]
+struct Gateway {
+    client: Client,
+    allowed_origin: HeaderValue,
+    /// Whether `/api/` is served; off for the feed-only gateway
+    serve_api: bool,
+    /// Whether calendar feeds are served; off for the health-only gateway
+    serve_feeds: bool,
+}
+
+/// Which probe a health path answers
+#[derive(Debug, Clone, Copy)]
+enum Probe {
+    Live,
+    Ready,
+}
+
+/// Start serving the gateway on `addr`
+pub async fn spawn(client: Client, addr: SocketAddr, allowed_origin: &str) -> Result<()> {
+    let gateway = Gateway {
+        client,
+        allowed_origin: HeaderValue::from_str(allowed_origin).context("APP_BASE_URL is not a valid origin")?,
+        serve_api: true,
+        serve_feeds: true,
+    };
+    serve(gateway, addr).await
+}
+
+/// Start serving only calendar feeds and the health check on `addr`
+pub async fn spawn_feeds(client: Client, addr: SocketAddr) -> Result<()> {
+    let gateway = Gateway {
+        client,
+        allowed_origin: HeaderValue::from_static("*"),
+        serve_api: false,
+        serve_feeds: true,
+    };
+    serve(gateway, addr).await
+}
+
+/// Start serving only the health paths on `addr`
+pub async fn spawn_health(client: Client, addr: SocketAddr) -> Result<()> {
+    let gateway = Gateway {
+        client,
+        allowed_origin: HeaderValue::from_static("*"),
+        serve_api: false,
+        serve_feeds: false,
+    };
+    serve(gateway, addr).await
+}
+
+async fn serve(gateway: Gateway, addr: SocketAddr) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind HTTP gateway to {}", addr))?;
+    let scope = match (gateway.serve_api, gateway.serve_feeds) {
+        (true, _) => "",
+        (false, true) => " (calendar feeds only)",
+        (false, false) => " (health only)",
+    };
+    info!("HTTP gateway listening on {}{}", addr, scope);
+
+    tokio::spawn(async move {
+        loop {
+            let (socket, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("HTTP gateway accept failed: {}", e);
+                    continue;
+                }
+            };
+            let gateway = gateway.clone();
+            tokio::spawn(async move {
+                let service = service_fn(move |request| {
+                    let gateway = gateway.clone();
+                    async move { Ok::<_, Infallible>(gateway.handle(request, peer.ip()).await) }
+                });
+                if let Err(e) = http1::Builder::new().serve_connection(TokioIo::new(socket), service).await {
+                    debug!("HTTP gateway connection closed: {}", e);
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+impl Gateway {
+    async fn handle(&self, request: Request<Incoming>, peer: IpAddr) -> Response<Body> {
+        let client_ip = client_ip(peer, request.headers());
+        let path = request.uri().path().to_string();
+        let mut response = match (request.method(), path.as_str()) {
+            (&Method::OPTIONS, _) => empty(StatusCode::NO_CONTENT),
+            (&Method::GET, "/health") => text(StatusCode::OK, "ok"),
+            (&Method::GET, "/healthz") => probe(Probe::Live).await,
+            (&Method::GET, "/readyz") => probe(Probe::Ready).await,
+            (&Method::GET, _) if self.serve_feeds && path.starts_with(CALENDAR_PREFIX) => {
+                self.calendar_feed(&path, client_ip).await
+            }
+            _ if !self.serve_api => error(StatusCode::NOT_FOUND, Uuid::nil(), "NOT_FOUND", "Unknown path"),
+            (&Method::GET, EVENTS_PATH) => self.events(request.uri().query()).await,
+            (&Method::POST, _) => match path.strip_prefix(API_PREFIX) {
+                Some(subject) => self.request(subject.to_string(), request.into_body(), client_ip).await,
+                None => error(StatusCode::NOT_FOUND, Uuid::nil(), "NOT_FOUND", "Unknown path"),
+            },
+            _ => error(StatusCode::NOT_FOUND, Uuid::nil(), "NOT_FOUND", "Unknown path"),
+        };
+
+        let headers = response.headers_mut();
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, self.allowed_origin.clone());
+        headers.insert(ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static("GET, POST, OPTIONS"));
+        headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, HeaderValue::from_static("content-type"));
+        response
+    }
+
+    /// Forward a request to its handler and return the reply
+    async fn request(&self, subject: String, body: Incoming, client_ip: IpAddr) -> Response<Body> {
+        if !subjects::is_public(&subject) {
+            return error(StatusCode::BAD_REQUEST, Uuid::nil(), "INVALID_REQUEST", "Invalid subject");
+        }
+        let payload = match Limited::new(body, MAX_PAYLOAD).collect().await {
+            Ok(collected) => collected.to_bytes(),
+            Err(_) => return error(StatusCode::PAYLOAD_TOO_LARGE, Uuid::nil(), "INVALID_REQUEST", "Request too large"),
+        };
+        let request_id = extract_request_id(&payload);
+        let payload = stamp_client_ip(payload, client_ip);
+
+        match tokio::time::timeout(REQUEST_TIMEOUT, self.client.request(subject.clone(), payload)).await {
+            Ok(Ok(reply)) => json(StatusCode::OK, reply.payload),
+            Ok(Err(e)) if e.kind() == async_nats::RequestErrorKind::NoResponders => {
+                error(StatusCode::SERVICE_UNAVAILABLE, request_id, "SERVICE_UNAVAILABLE", format!("No handler for {}", subject))
+            }
+            Ok(Err(e)) if e.kind() == async_nats::RequestErrorKind::TimedOut => {
+                error(StatusCode::GATEWAY_TIMEOUT, request_id, "TIMEOUT", "Request timed out")
+            }
+            Ok(Err(e)) => error(StatusCode::BAD_GATEWAY, request_id, "INTERNAL_ERROR", e.to_string()),
+            Err(_) => error(StatusCode::GATEWAY_TIMEOUT, request_id, "TIMEOUT", "Request timed out"),
+        }
+    }
+
+    /// Fetch a calendar feed for a calendar app
+    async fn calendar_feed(&self, path: &str, client_ip: IpAddr) -> Response<Body> {
+        let Some(token) = feed_token(path) else {
+            return text(StatusCode::NOT_FOUND, "Not found");
+        };
+        let request = serde_json::json!({
+            "id": Uuid::new_v4(),
+            "timestamp": chrono::Utc::now(),
+            "clientIp": client_ip.to_string(),
+            "payload": { "token": token },
+        });
+        let payload = Bytes::from(serde_json::to_vec(&request).unwrap_or_default());
+
+        let reply = match tokio::time::timeout(FEED_TIMEOUT, self.client.request(subjects::portal::CALENDAR_FEED, payload)).await {
+            Ok(Ok(reply)) => reply,
+            Ok(Err(e)) => {
+                warn!("Calendar feed request failed: {}", e);
+                return text(StatusCode::SERVICE_UNAVAILABLE, "Calendar feed unavailable");
+            }
+            Err(_) => return text(StatusCode::GATEWAY_TIMEOUT, "Calendar feed timed out"),
+        };
+        let reply: serde_json::Value = serde_json::from_slice(&reply.payload).unwrap_or_default();
+        if let Some(ics) = reply.pointer("/payload/ics").and_then(|ics| ics.as_str()) {
+            return Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, "text/calendar; charset=utf-8")
+                .header(CACHE_CONTROL, "private, max-age=300")
+                .body(Full::new(Bytes::from(ics.to_string())).boxed())
+                .expect("valid response");
+        }
+        match reply.pointer("/error/code").and_then(|code| code.as_str()) {
+            Some("INVALID_TOKEN") => text(StatusCode::NOT_FOUND, "Not found"),
+            Some("RATE_LIMITED") => text(StatusCode::TOO_MANY_REQUESTS, "Too many requests"),
+            _ => text(StatusCode::BAD_GATEWAY, "Calendar feed unavailable"),
+        }
+    }
+
+    /// Stream messages of a subject as server-sent events until the browser disconnects
+    async fn events(&self, query: Option<&str>) -> Response<Body> {
+        let Some(subject) = query.and_then(event_subject) else {
+            return error(StatusCode::BAD_REQUEST, Uuid::nil(), "INVALID_REQUEST", "Missing or invalid subject");
+        };
+        let subscriber = match self.client.subscribe(subject).await {
+            Ok(subscriber) => subscriber,
+            Err(e) => return error(StatusCode::BAD_GATEWAY, Uuid::nil(), "INTERNAL_ERROR", e.to_string()),
+        };
+
+        let frames = subscriber.map(|message| Ok::<_, Infallible>(Frame::data(sse_event(&message.payload))));
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "text/event-stream")
+            .header(CACHE_CONTROL, "no-cache")
+            .body(BodyExt::boxed(StreamBody::new(frames)))
+            .expect("valid response")
+    }
+}
+
+/// Subject requested by `?subject=...`; wildcards allowed, but only under `sazinka.`
+fn event_subject(query: &str) -> Option<String> {
+    let value = query.split('&').find_map(|pair| pair.strip_prefix("subject="))?;
+    let subject = urlencoding::decode(value).ok()?.into_owned();
+    let valid = subject.starts_with(subjects::PREFIX)
+        && subject.split('.').all(|token| !token.is_empty())
+        && !subject.chars().any(char::is_whitespace);
+    valid.then_some(subject)
+}
+
+/// One server-sent event; multi-line payloads become several data lines
+fn sse_event(payload: &[u8]) -> Bytes {
+    let text = String::from_utf8_lossy(payload);
+    let mut event = String::with_capacity(text.len() + 8);
+    for line in text.lines() {
+        event.push_str("data: ");
+        event.push_str(line);
+        event.push('\n');
+    }
+    event.push('\n');
+    Bytes::from(event)
+}
+
+/// Token of a `/calendar/{token}.ics` path; tokens are hex
+fn feed_token(path: &str) -> Option<&str> {
+    let token = path.strip_prefix(CALENDAR_PREFIX)?;
+    let token = token.strip_suffix(CALENDAR_SUFFIX).unwrap_or(token);
+    let valid = !token.is_empty() && token.len() <= 128 && token.chars().all(|c| c.is_ascii_hexdigit());
+    valid.then_some(token)
+}
+
+/// Address of the browser: the peer, or the last `X-Forwarded-For` hop when
+/// the peer is a reverse proxy on the same host or private network
+fn client_ip(peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+    let proxied = match peer {
+        IpAddr::V4(v4) => v4.is_loopback() || v4.is_private(),
+        IpAddr::V6(v6) => v6.is_loopback(),
+    };
+    if !proxied {
+        return peer;
+    }
+    headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|hop| hop.trim().parse().ok())
+        .unwrap_or(peer)
+}
+
+/// Overwrite `clientIp` of a JSON request so browsers cannot pick their own
+fn stamp_client_ip(payload: Bytes, client_ip: IpAddr) -> Bytes {
+    match serde_json::from_slice::<serde_json::Value>(&payload) {
+        Ok(serde_json::Value::Object(mut request)) => {
+            request.insert("clientIp".to_string(), serde_json::Value::String(client_ip.to_string()));
+            serde_json::to_vec(&request).map(Bytes::from).unwrap_or(payload)
+        }
+        _ => payload,
+    }
+}
+
+fn extract_request_id(payload: &[u8]) -> Uuid {
+    serde_json::from_slice::<serde_json::Value>(payload)
+        .ok()
+        .and_then(|v| v.get("id").and_then(|id| id.as_str()).and_then(|id| Uuid::parse_str(id).ok()))
+        .unwrap_or_else(Uuid::new_v4)
+}
+
+/// Answer a probe from this process's health report. A worker still
+/// starting is live but not ready.
+async fn probe(probe: Probe) -> Response<Body> {
+    let Some(report) = health::report().await else {
+        return match probe {
+            Probe::Live => text(StatusCode::OK, "starting"),
+            Probe::Ready => text(StatusCode::SERVICE_UNAVAILABLE, "starting"),
+        };
+    };
+    let passed = match probe {
+        Probe::Live => report.live,
+        Probe::Ready => report.ready,
+    };
+    let status = if passed { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    json(status, serde_json::to_vec(&report).unwrap_or_default().into())
+}
+
+fn json(status: StatusCode, body: Bytes) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Full::new(body).boxed())
+        .expect("valid response")
+}
+
+fn text(status: StatusCode, body: &'static str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "text/plain")
+        .body(Full::new(Bytes::from_static(body.as_bytes())).boxed())
+        .expect("valid response")
+}
+
+fn empty(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Full::new(Bytes::new()).boxed())
+        .expect("valid response")
+}
+
+fn error(status: StatusCode, request_id: Uuid, code: &str, message: impl Into<String>) -> Response<Body> {
+    let body = serde_json::to_vec(&ErrorResponse::new(request_id, code, message)).unwrap_or_default();
+    json(status, body.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_token() {
+        assert_eq!(feed_token("/calendar/abc123.ics"), Some("abc123"));
+        assert_eq!(feed_token("/calendar/abc123"), Some("abc123"));
+        assert_eq!(feed_token("/calendar/.ics"), None);
+        assert_eq!(feed_token("/calendar/../etc.ics"), None);
+        assert_eq!(feed_token("/api/abc123.ics"), None);
+    }
+
+    #[test]
+    fn test_event_subject() {
+        assert_eq!(
+            event_subject("subject=sazinka.job.geocode.status.%2A").as_deref(),
+            Some("sazinka.job.geocode.status.*")
+        );
+        assert_eq!(event_subject("x=1&subject=sazinka.job.>").as_deref(), Some("sazinka.job.>"));
+        assert!(event_subject("subject=_INBOX.>").is_none());
+        assert!(event_subject("subject=sazinka..x").is_none());
+        assert!(event_subject("other=1").is_none());
+    }
+
+    #[test]
+    fn test_sse_event() {
+        assert_eq!(&sse_event(b"{\"a\":1}")[..], b"data: {\"a\":1}\n\n");
+        assert_eq!(&sse_event(b"a\nb")[..], b"data: a\ndata: b\n\n");
+    }
+
+    #[test]
+    fn test_extract_request_id() {
+        let id = Uuid::new_v4();
+        assert_eq!(extract_request_id(format!("{{\"id\":\"{}\"}}", id).as_bytes()), id);
+        assert_ne!(extract_request_id(b"not json"), Uuid::nil());
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("10.0.0.9, 203.0.113.7"));
+        let proxy: IpAddr = "127.0.0.1".parse().unwrap();
+        let remote: IpAddr = "198.51.100.1".parse().unwrap();
+        assert_eq!(client_ip(proxy, &headers), "203.0.113.7".parse::<IpAddr>().unwrap());
+        // Only a local proxy is trusted to forward the address
+        assert_eq!(client_ip(remote, &headers), remote);
+        assert_eq!(client_ip(proxy, &HeaderMap::new()), proxy);
+    }
+
+    #[test]
+    fn test_stamp_client_ip() {
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let stamped = stamp_client_ip(Bytes::from_static(b"{\"id\":\"x\",\"clientIp\":\"1.2.3.4\"}"), ip);
+        let value: serde_json::Value = serde_json::from_slice(&stamped).unwrap();
+        assert_eq!(value["clientIp"], "203.0.113.7");
+        assert_eq!(&stamp_client_ip(Bytes::from_static(b"not json"), ip)[..], b"not json");
+    }
+}
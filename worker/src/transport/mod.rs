@@ -0,0 +1,96 @@
+//! Message transport
+//!
+//! The worker normally connects to a NATS server and queues jobs in
+//! JetStream. In standalone mode (`WORKER_MODE=standalone`), meant for
+//! small single-machine installs, it needs neither: an embedded broker
+//! carries the same request/reply traffic, jobs go through an in-process
+//! queue, and browsers talk to the worker over the HTTP gateway. Handlers
+//! are identical in both modes.
+
+pub mod broker;
+pub mod gateway;
+pub mod queue;
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use async_nats::Client;
+use tracing::info;
+
+use crate::config::Config;
+
+pub use queue::{JobQueue, JobMessage};
+
+/// How the worker exchanges messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransportMode {
+    /// External NATS server with JetStream
+    #[default]
+    Nats,
+    /// Embedded broker, in-process job queue and HTTP gateway
+    Standalone,
+}
+
+impl FromStr for TransportMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "nats" => Ok(TransportMode::Nats),
+            "standalone" => Ok(TransportMode::Standalone),
+            other => Err(anyhow!("Unknown WORKER_MODE '{}' (expected 'nats' or 'standalone')", other)),
+        }
+    }
+}
+
+/// Connect the worker's client for the configured mode. In standalone mode
+/// this starts the embedded broker, the local job queue and the gateway; in
+/// NATS mode a feed-only gateway when `CALENDAR_FEED_ADDR` is set and a
+/// health-only one when `HEALTH_ADDR` is set.
+pub async fn connect(config: &Config) -> Result<Client> {
+    JobQueue::install_stream_settings(config.jetstream.clone());
+    match config.transport_mode {
+        TransportMode::Nats => {
+            let client = match (std::env::var("NATS_USER"), std::env::var("NATS_PASSWORD")) {
+                (Ok(user), Ok(password)) if !user.is_empty() => {
+                    async_nats::ConnectOptions::new()
+                        .user_and_password(user, password)
+                        .connect(&config.nats_url)
+                        .await?
+                }
+                _ => async_nats::connect(&config.nats_url).await?,
+            };
+            info!("Connected to NATS at {}", config.nats_url);
+
+            if let Some(addr) = config.calendar_feed_addr {
+                gateway::spawn_feeds(client.clone(), addr).await?;
+            }
+            if let Some(addr) = config.health_addr {
+                gateway::spawn_health(client.clone(), addr).await?;
+            }
+            Ok(client)
+        }
+        TransportMode::Standalone => {
+            let broker = broker::Broker::start().await?;
+            JobQueue::install_local();
+            let client = async_nats::connect(broker.url()).await?;
+            info!("Standalone mode: embedded broker at {}, local job queue", broker.url());
+
+            gateway::spawn(client.clone(), config.gateway_addr, &config.app_base_url).await?;
+            Ok(client)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport_mode_parses() {
+        assert_eq!("".parse::<TransportMode>().unwrap(), TransportMode::Nats);
+        assert_eq!("NATS".parse::<TransportMode>().unwrap(), TransportMode::Nats);
+        assert_eq!(" standalone ".parse::<TransportMode>().unwrap(), TransportMode::Standalone);
+        assert!("embedded".parse::<TransportMode>().is_err());
+    }
+}
@@ -0,0 +1,715 @@
+#![allow(dead_code)]
+//! Job queues
+//!
+//! Processors publish and consume jobs through [`JobQueue`], which is
+//! JetStream when the worker runs against a NATS server and an in-process
+//! work queue ([`LocalQueue`]) in standalone mode. The local queue mirrors
+//! the JetStream features the processors rely on: work-queue retention,
+//! subject filters, explicit acks and redelivery after `ack_wait` (or the
+//! consumer's `backoff` schedule) up to `max_deliver` times. It is not persistent — jobs queued when the process
+//! stops are flagged as lost by the job backup reconciliation at startup.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_nats::jetstream::{self, consumer::pull, stream::RawMessageErrorKind};
+use async_nats::Client;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+use crate::config::JetStreamConfig;
+use crate::subjects;
+
+/// Redelivery delay of consumers that do not set `ack_wait` (JetStream default)
+const DEFAULT_ACK_WAIT: Duration = Duration::from_secs(30);
+
+/// The in-process queue, installed once when the worker starts in standalone mode
+static LOCAL_QUEUE: OnceCell<Arc<LocalQueue>> = OnceCell::new();
+
+/// Placement and limit overrides, installed once at startup
+static STREAM_SETTINGS: OnceCell<JetStreamConfig> = OnceCell::new();
+/// Effective limits of every stream this process ensured, for diagnostics
+static ENSURED_STREAMS: Lazy<Mutex<BTreeMap<String, StreamLimits>>> = Lazy::new(Default::default);
+
+/// Messages of a consumer, in delivery order
+pub type JobMessages = Pin<Box<dyn Stream<Item = Result<JobMessage>> + Send>>;
+
+/// Where jobs are queued
+#[derive(Clone)]
+pub enum JobQueue {
+    JetStream(Box<jetstream::Context>),
+    Local(Arc<LocalQueue>),
+}
+
+/// Size of a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueStreamState {
+    pub messages: u64,
+    pub bytes: u64,
+}
+
+/// Effective configuration of a stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamLimits {
+    pub max_messages: i64,
+    pub max_bytes: i64,
+    pub replicas: usize,
+    pub placement_cluster: Option<String>,
+    pub placement_tags: Vec<String>,
+}
+
+impl StreamLimits {
+    fn of(config: &jetstream::stream::Config) -> Self {
+        let placement = config.placement.clone().unwrap_or_default();
+        Self {
+            max_messages: config.max_messages,
+            max_bytes: config.max_bytes,
+            replicas: config.num_replicas.max(1),
+            placement_cluster: placement.cluster,
+            placement_tags: placement.tags,
+        }
+    }
+
+    /// Whether an existing stream must be updated to `wanted`. Placement
+    /// only counts when it is configured; the server may fill it in itself.
+    fn needs_update_to(&self, wanted: &StreamLimits) -> bool {
+        let placement_configured = wanted.placement_cluster.is_some() || !wanted.placement_tags.is_empty();
+        self.max_messages != wanted.max_messages
+            || self.max_bytes != wanted.max_bytes
+            || self.replicas != wanted.replicas
+            || (placement_configured
+                && (self.placement_cluster != wanted.placement_cluster || self.placement_tags != wanted.placement_tags))
+    }
+}
+
+/// Apply the configured overrides to a processor's stream config
+fn apply_stream_settings(settings: &JetStreamConfig, config: &mut jetstream::stream::Config) {
+    if let Some(limit) = settings.stream_limits.get(&config.name) {
+        if let Some(max_messages) = limit.max_messages {
+            config.max_messages = max_messages;
+        }
+        if let Some(max_bytes) = limit.max_bytes {
+            config.max_bytes = max_bytes;
+        }
+    }
+    if let Some(replicas) = settings.replicas {
+        config.num_replicas = replicas;
+    }
+    if settings.placement_cluster.is_some() || !settings.placement_tags.is_empty() {
+        config.placement = Some(jetstream::stream::Placement {
+            cluster: settings.placement_cluster.clone(),
+            tags: settings.placement_tags.clone(),
+        });
+    }
+}
+
+/// Effective limits of the streams ensured so far, by name
+pub fn ensured_streams() -> BTreeMap<String, StreamLimits> {
+    ENSURED_STREAMS.lock().clone()
+}
+
+/// Configured overrides naming a stream no processor has ensured (typos)
+pub fn unmatched_stream_overrides() -> Vec<String> {
+    let ensured = ENSURED_STREAMS.lock();
+    STREAM_SETTINGS
+        .get()
+        .map(|settings| {
+            settings
+                .stream_limits
+                .keys()
+                .filter(|name| !ensured.contains_key(*name))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl JobQueue {
+    /// The job queue of the running worker: the local queue in standalone
+    /// mode, JetStream over `client` otherwise
+    pub fn new(client: Client) -> Self {
+        match LOCAL_QUEUE.get() {
+            Some(local) => JobQueue::Local(Arc::clone(local)),
+            None => JobQueue::JetStream(Box::new(jetstream::new(client))),
+        }
+    }
+
+    /// Switch the process to the local queue. Must run before any processor starts.
+    pub fn install_local() -> Arc<LocalQueue> {
+        Arc::clone(LOCAL_QUEUE.get_or_init(|| Arc::new(LocalQueue::default())))
+    }
+
+    /// Install the placement and limit overrides. Must run before any processor starts.
+    pub fn install_stream_settings(settings: JetStreamConfig) {
+        if STREAM_SETTINGS.set(settings).is_err() {
+            warn!("JetStream stream settings already installed");
+        }
+    }
+
+    /// Create the stream unless it exists, with the configured overrides
+    /// applied. An existing JetStream stream whose limits or placement differ
+    /// is updated to match; a failed update is logged and the stream is used
+    /// as it is.
+    pub async fn ensure_stream(&self, mut config: jetstream::stream::Config) -> Result<()> {
+        if let Some(settings) = STREAM_SETTINGS.get() {
+            apply_stream_settings(settings, &mut config);
+        }
+        let wanted = StreamLimits::of(&config);
+        let effective = match self {
+            JobQueue::JetStream(js) => {
+                let stream = js.get_or_create_stream(config.clone()).await?;
+                let current = StreamLimits::of(&stream.cached_info().config);
+                if !current.needs_update_to(&wanted) {
+                    current
+                } else {
+                    match js.update_stream(&config).await {
+                        Ok(info) => {
+                            info!("Stream {} reconciled: {:?} -> {:?}", config.name, current, wanted);
+                            StreamLimits::of(&info.config)
+                        }
+                        Err(e) => {
+                            warn!("Failed to update stream {} to {:?}: {}", config.name, wanted, e);
+                            current
+                        }
+                    }
+                }
+            }
+            JobQueue::Local(local) => {
+                local.ensure_stream(&config);
+                wanted
+            }
+        };
+        ENSURED_STREAMS.lock().insert(config.name.clone(), effective);
+        Ok(())
+    }
+
+    /// Queue a job and wait until it is stored. Returns its stream sequence.
+    pub async fn publish(&self, subject: impl Into<String>, payload: Bytes) -> Result<u64> {
+        match self {
+            JobQueue::JetStream(js) => {
+                let ack = js.publish(subject.into(), payload).await?.await?;
+                Ok(ack.sequence)
+            }
+            JobQueue::Local(local) => local.publish(&subject.into(), payload),
+        }
+    }
+
+    /// Start delivering the stream's messages to a durable pull consumer
+    pub async fn consume(&self, stream: &str, config: pull::Config) -> Result<JobMessages> {
+        match self {
+            JobQueue::JetStream(js) => {
+                let name = config.durable_name.clone().unwrap_or_default();
+                let consumer = js.get_stream(stream).await?.get_or_create_consumer(&name, config).await?;
+                let messages = consumer.messages().await?;
+                Ok(messages
+                    .map(|message| message.map(JobMessage::from_jetstream).map_err(|e| anyhow!(e)))
+                    .boxed())
+            }
+            JobQueue::Local(local) => local.consume(stream, &config),
+        }
+    }
+
+    /// Whether the stream still holds the message (it is removed once acked)
+    pub async fn contains(&self, stream: &str, sequence: u64) -> Result<bool> {
+        match self {
+            JobQueue::JetStream(js) => {
+                let stream = js.get_stream_no_info(stream).await?;
+                match stream.get_raw_message(sequence).await {
+                    Ok(_) => Ok(true),
+                    Err(e) if e.kind() == RawMessageErrorKind::NoMessageFound => Ok(false),
+                    Err(e) => Err(anyhow!(e)),
+                }
+            }
+            JobQueue::Local(local) => Ok(local.contains(stream, sequence)),
+        }
+    }
+
+    /// Messages and bytes held by a stream
+    pub async fn stream_state(&self, stream: &str) -> Result<QueueStreamState> {
+        match self {
+            JobQueue::JetStream(js) => {
+                let info = js.get_stream(stream).await?.info().await?.clone();
+                Ok(QueueStreamState {
+                    messages: info.state.messages,
+                    bytes: info.state.bytes,
+                })
+            }
+            JobQueue::Local(local) => local.stream_state(stream).ok_or_else(|| anyhow!("stream not found")),
+        }
+    }
+
+    /// Messages waiting for a consumer
+    pub async fn consumer_pending(&self, stream: &str, consumer: &str) -> Result<u64> {
+        match self {
+            JobQueue::JetStream(js) => {
+                let mut consumer = js
+                    .get_stream(stream)
+                    .await?
+                    .get_consumer::<pull::Config>(consumer)
+                    .await
+                    .map_err(|e| anyhow!(e))?;
+                Ok(consumer.info().await?.num_pending)
+            }
+            JobQueue::Local(local) => local
+                .consumer_pending(stream, consumer)
+                .ok_or_else(|| anyhow!("consumer not found")),
+        }
+    }
+
+    /// Whether the queue can take jobs
+    pub async fn is_available(&self) -> bool {
+        match self {
+            JobQueue::JetStream(js) => js.query_account().await.is_ok(),
+            JobQueue::Local(_) => true,
+        }
+    }
+}
+
+/// A job delivered to a consumer
+pub struct JobMessage {
+    pub subject: String,
+    pub payload: Bytes,
+    delivered: i64,
+    acker: Acker,
+}
+
+enum Acker {
+    JetStream(Box<jetstream::Message>),
+    Local {
+        queue: Arc<LocalQueue>,
+        stream: String,
+        sequence: u64,
+    },
+}
+
+impl JobMessage {
+    fn from_jetstream(message: jetstream::Message) -> Self {
+        Self {
+            subject: message.subject.to_string(),
+            payload: message.payload.clone(),
+            delivered: message.info().map(|info| info.delivered).unwrap_or(0),
+            acker: Acker::JetStream(Box::new(message)),
+        }
+    }
+
+    /// How many times the job has been delivered, this delivery included
+    pub fn delivered(&self) -> i64 {
+        self.delivered
+    }
+
+    /// Confirm the job was handled; it will not be redelivered
+    pub async fn ack(&self) -> Result<()> {
+        match &self.acker {
+            Acker::JetStream(message) => message.ack().await.map_err(|e| anyhow!(e)),
+            Acker::Local { queue, stream, sequence } => {
+                queue.ack(stream, *sequence);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// In-process work queue used in standalone mode
+#[derive(Default)]
+pub struct LocalQueue {
+    streams: Mutex<HashMap<String, LocalStream>>,
+}
+
+struct LocalStream {
+    subjects: Vec<String>,
+    max_messages: i64,
+    max_bytes: i64,
+    last_sequence: u64,
+    ready: VecDeque<LocalMessage>,
+    in_flight: HashMap<u64, LocalMessage>,
+    /// Filter subject of each consumer
+    consumers: HashMap<String, String>,
+    notify: Arc<Notify>,
+}
+
+#[derive(Clone)]
+struct LocalMessage {
+    sequence: u64,
+    subject: String,
+    payload: Bytes,
+    delivered: i64,
+}
+
+impl LocalStream {
+    fn bytes(&self) -> u64 {
+        self.ready
+            .iter()
+            .chain(self.in_flight.values())
+            .map(|message| message.payload.len() as u64)
+            .sum()
+    }
+
+    fn len(&self) -> usize {
+        self.ready.len() + self.in_flight.len()
+    }
+
+    /// Drop the oldest waiting messages while the stream is over its limits
+    fn enforce_limits(&mut self) {
+        loop {
+            let over_messages = self.max_messages > 0 && self.len() as i64 > self.max_messages;
+            let over_bytes = self.max_bytes > 0 && self.bytes() as i64 > self.max_bytes;
+            if !(over_messages || over_bytes) {
+                return;
+            }
+            match self.ready.pop_front() {
+                Some(dropped) => warn!("Local queue full, discarding message {} on {}", dropped.sequence, dropped.subject),
+                None => return,
+            }
+        }
+    }
+
+    fn take_next(&mut self, filter: &str) -> Option<LocalMessage> {
+        let index = self
+            .ready
+            .iter()
+            .position(|message| filter.is_empty() || subjects::matches(filter, &message.subject))?;
+        let mut message = self.ready.remove(index)?;
+        message.delivered += 1;
+        self.in_flight.insert(message.sequence, message.clone());
+        Some(message)
+    }
+}
+
+impl LocalQueue {
+    fn ensure_stream(&self, config: &jetstream::stream::Config) {
+        self.streams.lock().entry(config.name.clone()).or_insert_with(|| LocalStream {
+            subjects: config.subjects.clone(),
+            max_messages: config.max_messages,
+            max_bytes: config.max_bytes,
+            last_sequence: 0,
+            ready: VecDeque::new(),
+            in_flight: HashMap::new(),
+            consumers: HashMap::new(),
+            notify: Arc::new(Notify::new()),
+        });
+    }
+
+    fn publish(&self, subject: &str, payload: Bytes) -> Result<u64> {
+        let mut streams = self.streams.lock();
+        let stream = streams
+            .values_mut()
+            .find(|stream| stream.subjects.iter().any(|pattern| subjects::matches(pattern, subject)))
+            .ok_or_else(|| anyhow!("no stream accepts subject {}", subject))?;
+
+        stream.last_sequence += 1;
+        let sequence = stream.last_sequence;
+        stream.ready.push_back(LocalMessage {
+            sequence,
+            subject: subject.to_string(),
+            payload,
+            delivered: 0,
+        });
+        stream.enforce_limits();
+        stream.notify.notify_waiters();
+        Ok(sequence)
+    }
+
+    fn consume(self: &Arc<Self>, stream_name: &str, config: &pull::Config) -> Result<JobMessages> {
+        let consumer = config.durable_name.clone().unwrap_or_default();
+        let notify = {
+            let mut streams = self.streams.lock();
+            let stream = streams
+                .get_mut(stream_name)
+                .ok_or_else(|| anyhow!("stream {} not found", stream_name))?;
+            stream.consumers.insert(consumer, config.filter_subject.clone());
+            Arc::clone(&stream.notify)
+        };
+
+        let state = Delivery {
+            queue: Arc::clone(self),
+            stream: stream_name.to_string(),
+            filter: config.filter_subject.clone(),
+            ack_wait: if config.ack_wait.is_zero() { DEFAULT_ACK_WAIT } else { config.ack_wait },
+            backoff: config.backoff.clone(),
+            max_deliver: config.max_deliver,
+            notify,
+        };
+        Ok(futures::stream::unfold(state, |state| async move {
+            let job = state.next().await;
+            Some((Ok(job), state))
+        })
+        .boxed())
+    }
+
+    fn ack(&self, stream: &str, sequence: u64) {
+        if let Some(stream) = self.streams.lock().get_mut(stream) {
+            stream.in_flight.remove(&sequence);
+        }
+    }
+
+    /// Put an unacked message back in line, unless it was acked meanwhile
+    /// or used up its deliveries
+    fn redeliver(&self, stream: &str, sequence: u64, delivered: i64, max_deliver: i64) {
+        let mut streams = self.streams.lock();
+        let Some(stream) = streams.get_mut(stream) else {
+            return;
+        };
+        if stream.in_flight.get(&sequence).map(|message| message.delivered) != Some(delivered) {
+            return;
+        }
+        let Some(message) = stream.in_flight.remove(&sequence) else {
+            return;
+        };
+        if max_deliver > 0 && message.delivered >= max_deliver {
+            warn!("Job {} on {} not acked after {} deliveries, dropping it", sequence, message.subject, message.delivered);
+            return;
+        }
+        stream.ready.push_front(message);
+        stream.notify.notify_waiters();
+    }
+
+    fn contains(&self, stream: &str, sequence: u64) -> bool {
+        self.streams.lock().get(stream).is_some_and(|stream| {
+            stream.in_flight.contains_key(&sequence) || stream.ready.iter().any(|message| message.sequence == sequence)
+        })
+    }
+
+    fn stream_state(&self, stream: &str) -> Option<QueueStreamState> {
+        self.streams.lock().get(stream).map(|stream| QueueStreamState {
+            messages: stream.len() as u64,
+            bytes: stream.bytes(),
+        })
+    }
+
+    fn consumer_pending(&self, stream: &str, consumer: &str) -> Option<u64> {
+        let streams = self.streams.lock();
+        let stream = streams.get(stream)?;
+        let filter = stream.consumers.get(consumer)?;
+        let pending = stream
+            .ready
+            .iter()
+            .filter(|message| filter.is_empty() || subjects::matches(filter, &message.subject))
+            .count();
+        Some(pending as u64)
+    }
+}
+
+/// State of one consumer's delivery loop
+struct Delivery {
+    queue: Arc<LocalQueue>,
+    stream: String,
+    filter: String,
+    ack_wait: Duration,
+    backoff: Vec<Duration>,
+    max_deliver: i64,
+    notify: Arc<Notify>,
+}
+
+/// Delay before the `delivered`-th delivery of a message is redelivered.
+///
+/// Like JetStream, a backoff schedule replaces `ack_wait`, and its last
+/// step repeats for deliveries beyond its length.
+fn redelivery_delay(ack_wait: Duration, backoff: &[Duration], delivered: i64) -> Duration {
+    let step = usize::try_from(delivered.max(1) - 1).unwrap_or(0);
+    backoff.get(step).or(backoff.last()).copied().unwrap_or(ack_wait)
+}
+
+impl Delivery {
+    async fn next(&self) -> JobMessage {
+        loop {
+            // Register for wakeups before looking, so a publish in between is not missed
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let message = self
+                .queue
+                .streams
+                .lock()
+                .get_mut(&self.stream)
+                .and_then(|stream| stream.take_next(&self.filter));
+            if let Some(message) = message {
+                self.schedule_redelivery(&message);
+                return JobMessage {
+                    subject: message.subject,
+                    payload: message.payload,
+                    delivered: message.delivered,
+                    acker: Acker::Local {
+                        queue: Arc::clone(&self.queue),
+                        stream: self.stream.clone(),
+                        sequence: message.sequence,
+                    },
+                };
+            }
+            notified.await;
+        }
+    }
+
+    fn schedule_redelivery(&self, message: &LocalMessage) {
+        let queue = Arc::clone(&self.queue);
+        let stream = self.stream.clone();
+        let (sequence, delivered, max_deliver) = (message.sequence, message.delivered, self.max_deliver);
+        let delay = redelivery_delay(self.ack_wait, &self.backoff, delivered);
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            queue.redeliver(&stream, sequence, delivered, max_deliver);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StreamLimitOverride;
+
+    #[test]
+    fn stream_settings_override_limits_and_placement() {
+        let mut settings = JetStreamConfig {
+            replicas: Some(3),
+            placement_tags: vec!["ssd".to_string()],
+            ..Default::default()
+        };
+        settings.stream_limits.insert(
+            "SAZINKA_EXPORT_JOBS".to_string(),
+            StreamLimitOverride { max_messages: None, max_bytes: Some(512 * 1024 * 1024) },
+        );
+        let mut config = jetstream::stream::Config {
+            name: "SAZINKA_EXPORT_JOBS".to_string(),
+            max_messages: 1000,
+            max_bytes: 200 * 1024 * 1024,
+            ..Default::default()
+        };
+        apply_stream_settings(&settings, &mut config);
+        let limits = StreamLimits::of(&config);
+        assert_eq!(limits.max_messages, 1000);
+        assert_eq!(limits.max_bytes, 512 * 1024 * 1024);
+        assert_eq!(limits.replicas, 3);
+        assert_eq!(limits.placement_tags, vec!["ssd".to_string()]);
+    }
+
+    #[test]
+    fn placement_filled_in_by_the_server_needs_no_update() {
+        let wanted = StreamLimits::of(&jetstream::stream::Config { max_messages: 10, ..Default::default() });
+        let mut current = wanted.clone();
+        current.placement_cluster = Some("c1".to_string());
+        assert!(!current.needs_update_to(&wanted));
+        current.max_messages = 5;
+        assert!(current.needs_update_to(&wanted));
+    }
+
+    fn local_queue() -> (Arc<LocalQueue>, JobQueue) {
+        let local = Arc::new(LocalQueue::default());
+        local.ensure_stream(&jetstream::stream::Config {
+            name: "TEST_JOBS".to_string(),
+            subjects: vec!["sazinka.jobs.test.>".to_string()],
+            max_messages: 3,
+            ..Default::default()
+        });
+        (Arc::clone(&local), JobQueue::Local(local))
+    }
+
+    fn consumer(filter: &str, ack_wait: Duration, max_deliver: i64) -> pull::Config {
+        pull::Config {
+            durable_name: Some("test_workers".to_string()),
+            filter_subject: filter.to_string(),
+            ack_wait,
+            max_deliver,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_consume_ack() {
+        let (_, queue) = local_queue();
+        let first = queue.publish("sazinka.jobs.test.a", Bytes::from_static(b"1")).await.unwrap();
+        let second = queue.publish("sazinka.jobs.test.b", Bytes::from_static(b"2")).await.unwrap();
+        assert_eq!((first, second), (1, 2));
+        assert!(queue.publish("sazinka.jobs.other", Bytes::new()).await.is_err());
+
+        let mut messages = queue
+            .consume("TEST_JOBS", consumer("sazinka.jobs.test.>", Duration::from_secs(30), 3))
+            .await
+            .unwrap();
+        assert_eq!(queue.consumer_pending("TEST_JOBS", "test_workers").await.unwrap(), 2);
+
+        let job = messages.next().await.unwrap().unwrap();
+        assert_eq!(job.payload, Bytes::from_static(b"1"));
+        assert_eq!(job.delivered(), 1);
+        assert!(queue.contains("TEST_JOBS", first).await.unwrap());
+        job.ack().await.unwrap();
+        assert!(!queue.contains("TEST_JOBS", first).await.unwrap());
+        assert_eq!(queue.stream_state("TEST_JOBS").await.unwrap().messages, 1);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_waits_for_publish() {
+        let (_, queue) = local_queue();
+        let mut messages = queue
+            .consume("TEST_JOBS", consumer("", Duration::from_secs(30), 0))
+            .await
+            .unwrap();
+
+        let publisher = queue.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            publisher.publish("sazinka.jobs.test.late", Bytes::from_static(b"late")).await.unwrap();
+        });
+
+        let job = tokio::time::timeout(Duration::from_secs(2), messages.next()).await.unwrap().unwrap().unwrap();
+        assert_eq!(job.subject, "sazinka.jobs.test.late");
+    }
+
+    #[tokio::test]
+    async fn test_filter_subject() {
+        let (_, queue) = local_queue();
+        queue.publish("sazinka.jobs.test.a", Bytes::from_static(b"a")).await.unwrap();
+        queue.publish("sazinka.jobs.test.b", Bytes::from_static(b"b")).await.unwrap();
+
+        let mut messages = queue
+            .consume("TEST_JOBS", consumer("sazinka.jobs.test.b", Duration::from_secs(30), 0))
+            .await
+            .unwrap();
+        let job = messages.next().await.unwrap().unwrap();
+        assert_eq!(job.payload, Bytes::from_static(b"b"));
+    }
+
+    #[tokio::test]
+    async fn test_unacked_job_is_redelivered_until_max_deliver() {
+        let (local, queue) = local_queue();
+        let sequence = queue.publish("sazinka.jobs.test.a", Bytes::from_static(b"a")).await.unwrap();
+        let mut messages = queue
+            .consume("TEST_JOBS", consumer("", Duration::from_millis(20), 2))
+            .await
+            .unwrap();
+
+        assert_eq!(messages.next().await.unwrap().unwrap().delivered(), 1);
+        let again = tokio::time::timeout(Duration::from_secs(2), messages.next()).await.unwrap().unwrap().unwrap();
+        assert_eq!(again.delivered(), 2);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(!local.contains("TEST_JOBS", sequence));
+    }
+
+    #[tokio::test]
+    async fn test_limits_discard_oldest_waiting() {
+        let (_, queue) = local_queue();
+        for n in 0..4 {
+            queue.publish(format!("sazinka.jobs.test.{}", n), Bytes::new()).await.unwrap();
+        }
+        assert_eq!(queue.stream_state("TEST_JOBS").await.unwrap().messages, 3);
+        assert!(!queue.contains("TEST_JOBS", 1).await.unwrap());
+        assert!(queue.contains("TEST_JOBS", 4).await.unwrap());
+    }
+
+    #[test]
+    fn test_redelivery_delay_follows_backoff() {
+        let ack_wait = Duration::from_secs(30);
+        let backoff = [Duration::from_secs(1), Duration::from_secs(10)];
+        assert_eq!(redelivery_delay(ack_wait, &[], 3), ack_wait);
+        assert_eq!(redelivery_delay(ack_wait, &backoff, 1), Duration::from_secs(1));
+        assert_eq!(redelivery_delay(ack_wait, &backoff, 2), Duration::from_secs(10));
+        assert_eq!(redelivery_delay(ack_wait, &backoff, 5), Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,124 @@
+#![allow(dead_code)]
+//! Account transfer between Sazinka instances
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Version of `transfer.json`, bumped on incompatible changes
+pub const TRANSFER_FORMAT_VERSION: u32 = 1;
+
+/// Transfer metadata stored as `transfer.json` next to the backup manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferInfo {
+    pub format: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Account owner on the source instance
+    pub owner_user_id: Uuid,
+    pub owner_email: String,
+}
+
+/// What to do with users whose email already exists on the target instance
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictPolicy {
+    /// Abort the import and list the conflicting emails
+    #[default]
+    Fail,
+    /// Keep the existing user: the owner merges into the existing account,
+    /// a worker's data is reassigned to the account owner
+    Skip,
+}
+
+impl std::str::FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fail" => Ok(ConflictPolicy::Fail),
+            "skip" => Ok(ConflictPolicy::Skip),
+            other => Err(format!("unknown conflict policy '{}' (expected fail or skip)", other)),
+        }
+    }
+}
+
+/// How a transfer archive is imported
+#[derive(Debug, Clone, Default)]
+pub struct TransferImportOptions {
+    /// Merge into this existing account instead of creating a new owner
+    pub into_user_id: Option<Uuid>,
+    pub on_conflict: ConflictPolicy,
+}
+
+/// Result of an import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferSummary {
+    /// Account owner on this instance
+    pub owner_user_id: Uuid,
+    /// Users not imported because their email already exists here
+    pub merged_users: Vec<String>,
+    pub tables: usize,
+    pub rows_inserted: u64,
+    /// Rows kept as they were because they conflict with existing data
+    pub rows_skipped: u64,
+}
+
+/// Request for sazinka.admin.account.transfer.export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountTransferExportRequest {
+    pub user_id: Uuid,
+    /// Base64 transfer key (32 bytes), shared with the target instance
+    pub key: String,
+}
+
+/// Response of sazinka.admin.account.transfer.export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountTransferExportResponse {
+    pub filename: String,
+    pub content_base64: String,
+    pub owner_email: String,
+    pub tables: usize,
+    pub rows: usize,
+}
+
+/// Request for sazinka.admin.account.transfer.import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountTransferImportRequest {
+    /// Committed chunked upload holding the archive
+    #[serde(default)]
+    pub upload_id: Option<Uuid>,
+    /// Archive inline, when no upload is referenced
+    #[serde(default)]
+    pub content_base64: String,
+    pub key: String,
+    #[serde(default)]
+    pub into_user_id: Option<Uuid>,
+    #[serde(default)]
+    pub on_conflict: ConflictPolicy,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflict_policy_parses() {
+        assert_eq!("fail".parse::<ConflictPolicy>(), Ok(ConflictPolicy::Fail));
+        assert_eq!("skip".parse::<ConflictPolicy>(), Ok(ConflictPolicy::Skip));
+        assert!("merge".parse::<ConflictPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_import_request_defaults() {
+        let request: AccountTransferImportRequest =
+            serde_json::from_str(r#"{"contentBase64": "AAAA", "key": "k"}"#).unwrap();
+        assert_eq!(request.on_conflict, ConflictPolicy::Fail);
+        assert!(request.upload_id.is_none());
+        assert!(request.into_user_id.is_none());
+    }
+}
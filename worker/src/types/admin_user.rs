@@ -0,0 +1,168 @@
+#![allow(dead_code)]
+//! Admin user management types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Roles an admin may assign
+pub const ASSIGNABLE_USER_ROLES: &[&str] = &["admin", "customer", "worker"];
+
+/// Audit log action names
+pub const AUDIT_USER_DISABLED: &str = "user.disabled";
+pub const AUDIT_USER_ENABLED: &str = "user.enabled";
+pub const AUDIT_PASSWORD_RESET_SENT: &str = "user.password_reset_sent";
+pub const AUDIT_ROLE_CHANGED: &str = "user.role_changed";
+pub const AUDIT_VERIFICATION_RESENT: &str = "user.verification_resent";
+pub const AUDIT_PLAN_CHANGED: &str = "user.plan_changed";
+pub const AUDIT_SUBSCRIPTION_CHANGED: &str = "user.subscription_changed";
+
+/// User row with activity and usage figures for the admin list
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminUserSummary {
+    pub id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub business_name: Option<String>,
+    pub role: String,
+    pub owner_id: Option<Uuid>,
+    #[sqlx(default)]
+    pub plan: String,
+    pub email_verified: bool,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    /// Latest of login, customer change and visit change
+    pub last_activity_at: Option<DateTime<Utc>>,
+    pub customer_count: i64,
+    pub visit_count_30d: i64,
+    pub worker_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to list users
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAdminUsersRequest {
+    /// Matches email, name or business name
+    pub search: Option<String>,
+    pub role: Option<String>,
+    pub include_disabled: Option<bool>,
+    /// Only verified (true) or only unverified (false) accounts
+    pub email_verified: Option<bool>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Paged user list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAdminUsersResponse {
+    pub items: Vec<AdminUserSummary>,
+    pub total: i64,
+}
+
+/// Request to disable or re-enable an account
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetUserDisabledRequest {
+    pub user_id: Uuid,
+    pub disabled: bool,
+    pub reason: Option<String>,
+}
+
+/// Request targeting a single user
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminUserIdRequest {
+    pub user_id: Uuid,
+}
+
+/// Request to change a user's role
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetUserRoleRequest {
+    pub user_id: Uuid,
+    pub role: String,
+}
+
+/// Request to move an account to another plan
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetUserPlanRequest {
+    pub user_id: Uuid,
+    pub plan: String,
+}
+
+/// Result of a user management action
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminUserActionResponse {
+    pub user_id: Uuid,
+    pub ok: bool,
+}
+
+/// One audited admin action
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminAuditEntry {
+    pub id: Uuid,
+    pub admin_id: Option<Uuid>,
+    pub admin_email: Option<String>,
+    pub action: String,
+    pub target_user_id: Option<Uuid>,
+    pub target_email: Option<String>,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to list the audit log
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAdminAuditRequest {
+    pub target_user_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// Audit log entries, newest first
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAdminAuditResponse {
+    pub items: Vec<AdminAuditEntry>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_users_request_defaults() {
+        let req: ListAdminUsersRequest = serde_json::from_str("{}").unwrap();
+        assert!(req.search.is_none());
+        assert!(req.include_disabled.is_none());
+        assert!(req.email_verified.is_none());
+    }
+
+    #[test]
+    fn test_list_users_request_email_verified_filter() {
+        let req: ListAdminUsersRequest = serde_json::from_str(r#"{"emailVerified":false}"#).unwrap();
+        assert_eq!(req.email_verified, Some(false));
+    }
+
+    #[test]
+    fn test_set_user_disabled_request_camel_case() {
+        let req: SetUserDisabledRequest = serde_json::from_str(
+            r#"{"userId":"00000000-0000-0000-0000-000000000001","disabled":true,"reason":"chargeback"}"#,
+        )
+        .unwrap();
+        assert!(req.disabled);
+        assert_eq!(req.reason.as_deref(), Some("chargeback"));
+    }
+
+    #[test]
+    fn test_assignable_roles() {
+        assert!(ASSIGNABLE_USER_ROLES.contains(&"worker"));
+        assert!(!ASSIGNABLE_USER_ROLES.contains(&"superuser"));
+    }
+}
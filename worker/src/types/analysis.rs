@@ -0,0 +1,103 @@
+#![allow(dead_code)]
+//! Service area analysis types
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Longest travel time Valhalla accepts for an isochrone contour
+pub const ISOCHRONE_MAX_MINUTES: u32 = 120;
+
+/// NATS: sazinka.analysis.isochrone
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IsochroneAnalysisRequest {
+    /// Depot to measure from (defaults to the primary depot)
+    pub depot_id: Option<Uuid>,
+    /// Maximum driving time from the depot
+    pub max_minutes: u32,
+    /// Locations of jobs being considered
+    #[serde(default)]
+    pub prospects: Vec<IsochroneProspect>,
+    /// Check existing customers as well
+    #[serde(default = "default_include_customers")]
+    pub include_customers: bool,
+}
+
+fn default_include_customers() -> bool { true }
+
+impl IsochroneAnalysisRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=ISOCHRONE_MAX_MINUTES).contains(&self.max_minutes) {
+            return Err(format!("maxMinutes must be between 1 and {}", ISOCHRONE_MAX_MINUTES));
+        }
+        if self.prospects.iter().any(|p| !(-90.0..=90.0).contains(&p.lat) || !(-180.0..=180.0).contains(&p.lng)) {
+            return Err("prospect coordinates are out of range".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Location of a job being considered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IsochroneProspect {
+    pub label: Option<String>,
+    pub lat: f64,
+    pub lng: f64,
+}
+
+/// Prospect with the result of the check
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IsochroneProspectResult {
+    pub label: Option<String>,
+    pub lat: f64,
+    pub lng: f64,
+    pub reachable: bool,
+}
+
+/// Geocoded customer considered by the analysis
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct LocatedCustomer {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub city: Option<String>,
+    pub lat: f64,
+    pub lng: f64,
+}
+
+/// Response for sazinka.analysis.isochrone
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IsochroneAnalysisResponse {
+    pub depot_id: Uuid,
+    pub depot_name: String,
+    pub max_minutes: u32,
+    /// Reachable area as GeoJSON polygon rings of [lng, lat]
+    pub polygon: Vec<Vec<[f64; 2]>>,
+    pub customers_inside: Vec<LocatedCustomer>,
+    pub customers_outside_count: usize,
+    pub prospects: Vec<IsochroneProspectResult>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_isochrone_request_defaults_and_validation() {
+        let req: IsochroneAnalysisRequest = serde_json::from_str(r#"{"maxMinutes": 45}"#).unwrap();
+        assert!(req.include_customers);
+        assert!(req.prospects.is_empty());
+        assert!(req.validate().is_ok());
+
+        let too_far: IsochroneAnalysisRequest = serde_json::from_str(r#"{"maxMinutes": 240}"#).unwrap();
+        assert!(too_far.validate().is_err());
+
+        let bad_point: IsochroneAnalysisRequest =
+            serde_json::from_str(r#"{"maxMinutes": 30, "prospects": [{"lat": 149.0, "lng": 16.0}]}"#).unwrap();
+        assert!(bad_point.validate().is_err());
+    }
+}
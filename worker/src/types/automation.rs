@@ -0,0 +1,385 @@
+#![allow(dead_code)]
+//! Automation rule types
+//!
+//! A rule is "when <trigger> AND <conditions> THEN <actions>". Conditions
+//! test fields of the event context by dotted path (`customer.email`,
+//! `revision.status`); actions may use the same paths as `{{placeholders}}`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::types::Json;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Revision open past its due date (found by the hourly scan, once per
+/// revision and rule)
+pub const AUTOMATION_TRIGGER_REVISION_OVERDUE: &str = "revision.overdue";
+
+/// Events a rule can be triggered by: entity changes (the webhook event
+/// catalog) and the overdue scan
+pub const AUTOMATION_TRIGGERS: &[&str] = &[
+    "customer.created",
+    "customer.updated",
+    "customer.deleted",
+    "device.transferred",
+    "revision.created",
+    "revision.updated",
+    "revision.deleted",
+    AUTOMATION_TRIGGER_REVISION_OVERDUE,
+    "visit.created",
+    "visit.updated",
+    "visit.deleted",
+    "route.created",
+    "route.updated",
+    "route.deleted",
+];
+
+pub const AUTOMATION_RUN_SUCCEEDED: &str = "succeeded";
+pub const AUTOMATION_RUN_FAILED: &str = "failed";
+/// Conditions did not hold (only logged by the overdue scan)
+pub const AUTOMATION_RUN_SKIPPED: &str = "skipped";
+
+pub const MAX_AUTOMATION_NAME_LEN: usize = 200;
+pub const MAX_AUTOMATION_CONDITIONS: usize = 20;
+pub const MAX_AUTOMATION_ACTIONS: usize = 10;
+/// Upper bound for `dueInDays` of a created task
+pub const MAX_TASK_DUE_IN_DAYS: i32 = 365;
+
+/// Entity of a trigger: `revision` for `revision.overdue`
+pub fn trigger_entity(trigger: &str) -> &str {
+    trigger.split('.').next().unwrap_or(trigger)
+}
+
+/// Comparison of a condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConditionOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    /// Substring (case-insensitive) or array element
+    Contains,
+    /// Field equals one of the values of an array
+    In,
+    /// Field is set and not an empty string
+    Exists,
+    Missing,
+}
+
+/// One condition; all conditions of a rule must hold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationCondition {
+    /// Dotted path into the event context, e.g. `customer.email`
+    pub field: String,
+    pub op: ConditionOp,
+    #[serde(default)]
+    pub value: Option<Value>,
+}
+
+/// Value at a dotted path; missing and null are both `None`
+pub fn lookup<'a>(context: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(context, |value, key| value.get(key))
+        .filter(|value| !value.is_null())
+}
+
+fn is_set(value: Option<&Value>) -> bool {
+    match value {
+        None => false,
+        Some(Value::String(s)) => !s.trim().is_empty(),
+        Some(_) => true,
+    }
+}
+
+/// Order two scalars: numbers by value, strings lexically (ISO dates sort right)
+fn compare(left: &Value, right: &Value) -> Option<std::cmp::Ordering> {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+impl AutomationCondition {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.field.is_empty() || self.field.split('.').any(str::is_empty) {
+            return Err(format!("invalid field '{}'", self.field));
+        }
+        match self.op {
+            ConditionOp::Exists | ConditionOp::Missing => Ok(()),
+            ConditionOp::In if !matches!(self.value, Some(Value::Array(_))) => {
+                Err(format!("condition on '{}' needs an array value", self.field))
+            }
+            _ if self.value.is_none() => Err(format!("condition on '{}' needs a value", self.field)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn matches(&self, context: &Value) -> bool {
+        let field = lookup(context, &self.field);
+        let value = self.value.as_ref().unwrap_or(&Value::Null);
+        match self.op {
+            ConditionOp::Exists => is_set(field),
+            ConditionOp::Missing => !is_set(field),
+            ConditionOp::Eq => field.unwrap_or(&Value::Null) == value,
+            ConditionOp::Ne => field.unwrap_or(&Value::Null) != value,
+            ConditionOp::Gt => field.and_then(|f| compare(f, value)).is_some_and(|o| o.is_gt()),
+            ConditionOp::Gte => field.and_then(|f| compare(f, value)).is_some_and(|o| o.is_ge()),
+            ConditionOp::Lt => field.and_then(|f| compare(f, value)).is_some_and(|o| o.is_lt()),
+            ConditionOp::Lte => field.and_then(|f| compare(f, value)).is_some_and(|o| o.is_le()),
+            ConditionOp::Contains => match (field, value) {
+                (Some(Value::String(f)), Value::String(v)) => f.to_lowercase().contains(&v.to_lowercase()),
+                (Some(Value::Array(items)), v) => items.contains(v),
+                _ => false,
+            },
+            ConditionOp::In => match value {
+                Value::Array(values) => field.is_some_and(|f| values.contains(f)),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Whether all conditions hold for the context
+pub fn conditions_match(conditions: &[AutomationCondition], context: &Value) -> bool {
+    conditions.iter().all(|condition| condition.matches(context))
+}
+
+/// What a rule does; texts may contain `{{customer.name}}`-style placeholders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AutomationAction {
+    /// E-mail the customer of the event
+    #[serde(rename_all = "camelCase")]
+    SendEmail { subject: String, body: String },
+    /// Task for the customer of the event
+    #[serde(rename_all = "camelCase")]
+    CreateTask {
+        task_type_id: Uuid,
+        #[serde(default)]
+        due_in_days: Option<i32>,
+        #[serde(default)]
+        note: Option<String>,
+    },
+    /// Notification in the account's notification center
+    #[serde(rename_all = "camelCase")]
+    Notify {
+        title: String,
+        #[serde(default)]
+        body: Option<String>,
+    },
+}
+
+impl AutomationAction {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            AutomationAction::SendEmail { subject, body } => {
+                if subject.trim().is_empty() || body.trim().is_empty() {
+                    return Err("sendEmail needs a subject and a body".to_string());
+                }
+            }
+            AutomationAction::CreateTask { due_in_days, .. } => {
+                if due_in_days.is_some_and(|d| !(0..=MAX_TASK_DUE_IN_DAYS).contains(&d)) {
+                    return Err(format!("dueInDays must be between 0 and {}", MAX_TASK_DUE_IN_DAYS));
+                }
+            }
+            AutomationAction::Notify { title, .. } => {
+                if title.trim().is_empty() {
+                    return Err("notify needs a title".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AutomationAction::SendEmail { .. } => "sendEmail",
+            AutomationAction::CreateTask { .. } => "createTask",
+            AutomationAction::Notify { .. } => "notify",
+        }
+    }
+}
+
+/// Shared validation for create/update payloads
+pub fn validate_rule_fields(
+    name: Option<&str>,
+    trigger: Option<&str>,
+    conditions: Option<&[AutomationCondition]>,
+    actions: Option<&[AutomationAction]>,
+) -> Result<(), String> {
+    if let Some(name) = name {
+        if name.trim().is_empty() || name.len() > MAX_AUTOMATION_NAME_LEN {
+            return Err(format!("name must have 1 to {} characters", MAX_AUTOMATION_NAME_LEN));
+        }
+    }
+    if let Some(trigger) = trigger {
+        if !AUTOMATION_TRIGGERS.contains(&trigger) {
+            return Err(format!("trigger must be one of: {}", AUTOMATION_TRIGGERS.join(", ")));
+        }
+    }
+    if let Some(conditions) = conditions {
+        if conditions.len() > MAX_AUTOMATION_CONDITIONS {
+            return Err(format!("at most {} conditions are allowed", MAX_AUTOMATION_CONDITIONS));
+        }
+        conditions.iter().try_for_each(AutomationCondition::validate)?;
+    }
+    if let Some(actions) = actions {
+        if actions.is_empty() || actions.len() > MAX_AUTOMATION_ACTIONS {
+            return Err(format!("a rule needs 1 to {} actions", MAX_AUTOMATION_ACTIONS));
+        }
+        actions.iter().try_for_each(AutomationAction::validate)?;
+    }
+    Ok(())
+}
+
+/// Automation rule of an account
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationRule {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub trigger: String,
+    pub conditions: Json<Vec<AutomationCondition>>,
+    pub actions: Json<Vec<AutomationAction>>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Execution log entry
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationRun {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    pub rule_name: String,
+    pub trigger: String,
+    pub entity_id: Option<Uuid>,
+    pub customer_id: Option<Uuid>,
+    pub status: String,
+    pub actions_run: i32,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================
+// Request / response types (NATS payloads)
+// ============================================================
+
+/// NATS: sazinka.automation.rule.create
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAutomationRuleRequest {
+    pub name: String,
+    pub trigger: String,
+    #[serde(default)]
+    pub conditions: Vec<AutomationCondition>,
+    pub actions: Vec<AutomationAction>,
+    pub enabled: Option<bool>,
+}
+
+/// NATS: sazinka.automation.rule.update (only provided fields change)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAutomationRuleRequest {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub trigger: Option<String>,
+    pub conditions: Option<Vec<AutomationCondition>>,
+    pub actions: Option<Vec<AutomationAction>>,
+    pub enabled: Option<bool>,
+}
+
+/// NATS: sazinka.automation.rule.delete
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationRuleIdRequest {
+    pub id: Uuid,
+}
+
+/// NATS: sazinka.automation.run.list
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAutomationRunsRequest {
+    pub rule_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// Response for sazinka.automation.rule.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAutomationRulesResponse {
+    pub rules: Vec<AutomationRule>,
+}
+
+/// Response for sazinka.automation.run.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAutomationRunsResponse {
+    pub runs: Vec<AutomationRun>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn condition(field: &str, op: ConditionOp, value: Option<Value>) -> AutomationCondition {
+        AutomationCondition { field: field.to_string(), op, value }
+    }
+
+    #[test]
+    fn test_conditions_match_context() {
+        let context = json!({
+            "revision": { "status": "scheduled", "dueDate": "2026-03-01", "durationMinutes": 45 },
+            "customer": { "email": "jan@example.cz", "phone": "", "tags": ["vip"] },
+        });
+
+        assert!(condition("customer.email", ConditionOp::Exists, None).matches(&context));
+        assert!(condition("customer.phone", ConditionOp::Missing, None).matches(&context));
+        assert!(condition("customer.street", ConditionOp::Missing, None).matches(&context));
+        assert!(condition("revision.status", ConditionOp::Eq, Some(json!("scheduled"))).matches(&context));
+        assert!(condition("revision.status", ConditionOp::In, Some(json!(["upcoming", "scheduled"]))).matches(&context));
+        assert!(condition("revision.dueDate", ConditionOp::Lt, Some(json!("2026-03-15"))).matches(&context));
+        assert!(condition("revision.durationMinutes", ConditionOp::Gte, Some(json!(45))).matches(&context));
+        assert!(!condition("revision.durationMinutes", ConditionOp::Gt, Some(json!("45"))).matches(&context));
+        assert!(condition("customer.email", ConditionOp::Contains, Some(json!("EXAMPLE"))).matches(&context));
+        assert!(condition("customer.tags", ConditionOp::Contains, Some(json!("vip"))).matches(&context));
+
+        let overdue_with_email = [
+            condition("revision.status", ConditionOp::Ne, Some(json!("completed"))),
+            condition("customer.email", ConditionOp::Exists, None),
+        ];
+        assert!(conditions_match(&overdue_with_email, &context));
+        assert!(!conditions_match(&overdue_with_email, &json!({ "revision": { "status": "scheduled" } })));
+    }
+
+    #[test]
+    fn test_rule_validation() {
+        let notify = AutomationAction::Notify { title: "Overdue".to_string(), body: None };
+        assert!(validate_rule_fields(Some("Overdue"), Some("revision.overdue"), Some(&[]), Some(&[notify])).is_ok());
+        assert!(validate_rule_fields(None, Some("revision.exploded"), None, None).is_err());
+        assert!(validate_rule_fields(Some(" "), None, None, None).is_err());
+        assert!(validate_rule_fields(None, None, None, Some(&[])).is_err());
+        assert!(validate_rule_fields(None, None, Some(&[condition("customer.email", ConditionOp::Eq, None)]), None).is_err());
+        assert!(validate_rule_fields(None, None, Some(&[condition("customer..email", ConditionOp::Exists, None)]), None).is_err());
+        let late_task = AutomationAction::CreateTask { task_type_id: Uuid::nil(), due_in_days: Some(400), note: None };
+        assert!(validate_rule_fields(None, None, None, Some(&[late_task])).is_err());
+    }
+
+    #[test]
+    fn test_action_parses() {
+        let action: AutomationAction =
+            serde_json::from_str(r#"{"type": "createTask", "taskTypeId": "00000000-0000-0000-0000-000000000000", "dueInDays": 7}"#)
+                .unwrap();
+        assert_eq!(action.kind(), "createTask");
+        assert_eq!(trigger_entity(AUTOMATION_TRIGGER_REVISION_OVERDUE), "revision");
+    }
+}
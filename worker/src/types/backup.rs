@@ -0,0 +1,92 @@
+#![allow(dead_code)]
+//! Encrypted data backup types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Version of the archive layout, bumped on incompatible changes
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// What a backup contains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum BackupScope {
+    /// Every table of the schema
+    Full,
+    /// Rows of one account (the owner, its workers and their data)
+    #[serde(rename_all = "camelCase")]
+    Account { user_id: Uuid },
+}
+
+impl BackupScope {
+    /// Part of the object name, e.g. `full` or `account-<uuid>`
+    pub fn label(&self) -> String {
+        match self {
+            BackupScope::Full => "full".to_string(),
+            BackupScope::Account { user_id } => format!("account-{}", user_id),
+        }
+    }
+}
+
+/// Table of contents stored as `manifest.json` in the archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub format: u32,
+    pub created_at: DateTime<Utc>,
+    pub scope: BackupScope,
+    /// Latest applied migration of the source database
+    pub schema_version: i64,
+    pub tables: Vec<BackupTableEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupTableEntry {
+    pub name: String,
+    pub rows: usize,
+}
+
+/// Backup object in the off-site storage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredBackup {
+    pub key: String,
+    pub created_at: DateTime<Utc>,
+    pub size: i64,
+}
+
+/// Column of a table, as the backup sees it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSchema {
+    pub name: String,
+    pub nullable: bool,
+}
+
+/// Single-column foreign key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKeySchema {
+    pub column: String,
+    pub parent_table: String,
+    pub parent_column: String,
+}
+
+/// Table of the database schema with what dump and restore need to know
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableSchema {
+    pub name: String,
+    /// Writable columns (generated columns excluded)
+    pub columns: Vec<ColumnSchema>,
+    pub primary_key: Vec<String>,
+    pub foreign_keys: Vec<ForeignKeySchema>,
+}
+
+impl TableSchema {
+    pub fn has_column(&self, name: &str) -> bool {
+        self.columns.iter().any(|c| c.name == name)
+    }
+
+    pub fn is_nullable(&self, name: &str) -> bool {
+        self.columns.iter().any(|c| c.name == name && c.nullable)
+    }
+}
@@ -0,0 +1,117 @@
+#![allow(dead_code)]
+//! Calendar (iCal) feed types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Longest feed name
+pub const MAX_CALENDAR_FEED_NAME_LEN: usize = 100;
+/// Active feeds an account may have
+pub const MAX_CALENDAR_FEEDS_PER_USER: i64 = 50;
+
+/// Feed subscription (the token is only shown when the feed is created)
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarFeed {
+    pub id: Uuid,
+    pub name: String,
+    /// Empty: every crew of the company
+    pub crew_ids: Vec<Uuid>,
+    pub include_capacity: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_fetched_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Feed resolved from a token, for rendering
+#[derive(Debug, Clone, FromRow)]
+pub struct ResolvedCalendarFeed {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub crew_ids: Vec<Uuid>,
+    pub include_capacity: bool,
+}
+
+/// NATS: sazinka.calendar_feed.create
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCalendarFeedRequest {
+    pub name: String,
+    /// Crews to include; empty or absent for the whole company
+    #[serde(default)]
+    pub crew_ids: Vec<Uuid>,
+    #[serde(default = "default_include_capacity")]
+    pub include_capacity: bool,
+}
+
+fn default_include_capacity() -> bool {
+    true
+}
+
+impl CreateCalendarFeedRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("name is required".to_string());
+        }
+        if name.chars().count() > MAX_CALENDAR_FEED_NAME_LEN {
+            return Err(format!("name must be at most {} characters", MAX_CALENDAR_FEED_NAME_LEN));
+        }
+        Ok(())
+    }
+}
+
+/// Response for sazinka.calendar_feed.create - the only time the URL is shown
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCalendarFeedResponse {
+    pub feed: CalendarFeed,
+    /// Subscription URL for calendar apps, secret token included
+    pub url: String,
+}
+
+/// Response for sazinka.calendar_feed.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCalendarFeedsResponse {
+    pub feeds: Vec<CalendarFeed>,
+}
+
+/// NATS: sazinka.calendar_feed.revoke
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokeCalendarFeedRequest {
+    pub id: Uuid,
+}
+
+/// Response for sazinka.portal.calendar.feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortalCalendarFeedResponse {
+    /// text/calendar body
+    pub ics: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_request_defaults() {
+        let req: CreateCalendarFeedRequest = serde_json::from_str(r#"{"name": "Office wall"}"#).unwrap();
+        assert!(req.crew_ids.is_empty());
+        assert!(req.include_capacity);
+        assert!(req.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_name() {
+        let mut req: CreateCalendarFeedRequest = serde_json::from_str(r#"{"name": "  "}"#).unwrap();
+        assert!(req.validate().is_err());
+        req.name = "x".repeat(MAX_CALENDAR_FEED_NAME_LEN + 1);
+        assert!(req.validate().is_err());
+    }
+}
@@ -0,0 +1,320 @@
+#![allow(dead_code)]
+//! Campaign trigger types
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Customers whose last completed revision is older than `months_since_revision`
+pub const CAMPAIGN_TRIGGER_LAST_REVISION_AGE: &str = "last_revision_age";
+/// Customers on the anniversary of becoming a customer
+pub const CAMPAIGN_TRIGGER_CUSTOMER_ANNIVERSARY: &str = "customer_anniversary";
+pub const CAMPAIGN_TRIGGER_TYPES: &[&str] = &[CAMPAIGN_TRIGGER_LAST_REVISION_AGE, CAMPAIGN_TRIGGER_CUSTOMER_ANNIVERSARY];
+
+pub const CAMPAIGN_CHANNEL_EMAIL: &str = "email";
+pub const CAMPAIGN_CHANNEL_SMS: &str = "sms";
+pub const CAMPAIGN_CHANNELS: &[&str] = &[CAMPAIGN_CHANNEL_EMAIL, CAMPAIGN_CHANNEL_SMS];
+
+pub const MAX_CAMPAIGN_NAME_LENGTH: usize = 200;
+/// Three concatenated SMS segments
+pub const MAX_CAMPAIGN_SMS_LENGTH: usize = 459;
+
+/// Campaign of an account, with its send statistics
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Campaign {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub trigger_type: String,
+    pub months_since_revision: Option<i32>,
+    pub active_from_month: Option<i32>,
+    pub active_to_month: Option<i32>,
+    pub channel: String,
+    pub subject: Option<String>,
+    pub body: String,
+    pub exclude_scheduled: bool,
+    pub exclude_contacted_days: Option<i32>,
+    pub repeat_after_days: Option<i32>,
+    pub enabled: bool,
+    pub last_run_on: Option<NaiveDate>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Messages queued
+    pub sent_count: i64,
+    /// Messages that could not be queued
+    pub failed_count: i64,
+    pub last_sent_at: Option<DateTime<Utc>>,
+}
+
+impl Campaign {
+    /// Whether the campaign runs in `month` (1-12). Without a range it runs
+    /// all year; a range such as 9–2 wraps the year end.
+    pub fn is_active_in(&self, month: u32) -> bool {
+        let month = month as i32;
+        match (self.active_from_month, self.active_to_month) {
+            (Some(from), Some(to)) if from <= to => (from..=to).contains(&month),
+            (Some(from), Some(to)) => month >= from || month <= to,
+            _ => true,
+        }
+    }
+}
+
+/// Customer a campaign newly matches
+#[derive(Debug, Clone, FromRow)]
+pub struct CampaignRecipient {
+    pub customer_id: Uuid,
+    pub customer_name: Option<String>,
+    /// E-mail or phone number, depending on the channel
+    pub contact: String,
+    pub last_revision_date: Option<NaiveDate>,
+}
+
+/// Campaign send log entry
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CampaignSend {
+    pub id: Uuid,
+    pub campaign_id: Uuid,
+    pub customer_id: Option<Uuid>,
+    pub customer_name: Option<String>,
+    pub channel: String,
+    pub recipient: String,
+    pub job_id: Option<Uuid>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Shared validation for create/update payloads
+pub fn validate_campaign_fields(
+    name: Option<&str>,
+    trigger_type: Option<&str>,
+    channel: Option<&str>,
+    months_since_revision: Option<i32>,
+    active_months: (Option<i32>, Option<i32>),
+    exclude_contacted_days: Option<i32>,
+    repeat_after_days: Option<i32>,
+) -> Result<(), String> {
+    if let Some(name) = name {
+        if name.trim().is_empty() || name.chars().count() > MAX_CAMPAIGN_NAME_LENGTH {
+            return Err(format!("name must be 1-{} characters", MAX_CAMPAIGN_NAME_LENGTH));
+        }
+    }
+    if let Some(trigger_type) = trigger_type {
+        if !CAMPAIGN_TRIGGER_TYPES.contains(&trigger_type) {
+            return Err(format!("triggerType must be one of: {}", CAMPAIGN_TRIGGER_TYPES.join(", ")));
+        }
+    }
+    if let Some(channel) = channel {
+        if !CAMPAIGN_CHANNELS.contains(&channel) {
+            return Err(format!("channel must be one of: {}", CAMPAIGN_CHANNELS.join(", ")));
+        }
+    }
+    if months_since_revision.is_some_and(|m| !(1..=120).contains(&m)) {
+        return Err("monthsSinceRevision must be between 1 and 120".to_string());
+    }
+    let (from, to) = active_months;
+    if from.is_some() != to.is_some() {
+        return Err("activeFromMonth and activeToMonth must be set together".to_string());
+    }
+    if from.into_iter().chain(to).any(|m| !(1..=12).contains(&m)) {
+        return Err("Active months must be between 1 and 12".to_string());
+    }
+    if exclude_contacted_days.is_some_and(|d| !(1..=365).contains(&d)) {
+        return Err("excludeContactedDays must be between 1 and 365".to_string());
+    }
+    if repeat_after_days.is_some_and(|d| !(1..=3650).contains(&d)) {
+        return Err("repeatAfterDays must be between 1 and 3650".to_string());
+    }
+    Ok(())
+}
+
+/// Message checks that depend on the channel
+pub fn validate_campaign_message(channel: &str, subject: Option<&str>, body: &str) -> Result<(), String> {
+    if body.trim().is_empty() {
+        return Err("body must not be empty".to_string());
+    }
+    if channel == CAMPAIGN_CHANNEL_EMAIL && subject.is_none_or(|s| s.trim().is_empty()) {
+        return Err("E-mail campaigns need a subject".to_string());
+    }
+    if channel == CAMPAIGN_CHANNEL_SMS && body.chars().count() > MAX_CAMPAIGN_SMS_LENGTH {
+        return Err(format!("SMS body is limited to {} characters", MAX_CAMPAIGN_SMS_LENGTH));
+    }
+    Ok(())
+}
+
+// ============================================================
+// Request / response types (NATS payloads)
+// ============================================================
+
+/// NATS: sazinka.campaign.create
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCampaignRequest {
+    pub name: String,
+    pub trigger_type: String,
+    pub months_since_revision: Option<i32>,
+    pub active_from_month: Option<i32>,
+    pub active_to_month: Option<i32>,
+    pub channel: String,
+    pub subject: Option<String>,
+    pub body: String,
+    pub exclude_scheduled: Option<bool>,
+    pub exclude_contacted_days: Option<i32>,
+    pub repeat_after_days: Option<i32>,
+    pub enabled: Option<bool>,
+}
+
+impl CreateCampaignRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_campaign_fields(
+            Some(&self.name),
+            Some(&self.trigger_type),
+            Some(&self.channel),
+            self.months_since_revision,
+            (self.active_from_month, self.active_to_month),
+            self.exclude_contacted_days,
+            self.repeat_after_days,
+        )?;
+        if self.trigger_type == CAMPAIGN_TRIGGER_LAST_REVISION_AGE && self.months_since_revision.is_none() {
+            return Err("monthsSinceRevision is required for this trigger".to_string());
+        }
+        validate_campaign_message(&self.channel, self.subject.as_deref(), &self.body)
+    }
+}
+
+/// NATS: sazinka.campaign.update
+///
+/// Trigger type and channel are fixed after creation; optional limits are
+/// replaced as given (null clears them).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCampaignRequest {
+    pub id: Uuid,
+    pub name: String,
+    pub months_since_revision: Option<i32>,
+    pub active_from_month: Option<i32>,
+    pub active_to_month: Option<i32>,
+    pub subject: Option<String>,
+    pub body: String,
+    pub exclude_scheduled: bool,
+    pub exclude_contacted_days: Option<i32>,
+    pub repeat_after_days: Option<i32>,
+    pub enabled: bool,
+}
+
+impl UpdateCampaignRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_campaign_fields(
+            Some(&self.name),
+            None,
+            None,
+            self.months_since_revision,
+            (self.active_from_month, self.active_to_month),
+            self.exclude_contacted_days,
+            self.repeat_after_days,
+        )
+    }
+}
+
+/// NATS: sazinka.campaign.delete
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CampaignIdRequest {
+    pub id: Uuid,
+}
+
+/// NATS: sazinka.campaign.sends.list
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCampaignSendsRequest {
+    pub campaign_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// Response for sazinka.campaign.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCampaignsResponse {
+    pub campaigns: Vec<Campaign>,
+}
+
+/// Response for sazinka.campaign.sends.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCampaignSendsResponse {
+    pub sends: Vec<CampaignSend>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_request() -> CreateCampaignRequest {
+        CreateCampaignRequest {
+            name: "Heating season checkup".to_string(),
+            trigger_type: CAMPAIGN_TRIGGER_LAST_REVISION_AGE.to_string(),
+            months_since_revision: Some(10),
+            active_from_month: Some(9),
+            active_to_month: Some(11),
+            channel: CAMPAIGN_CHANNEL_EMAIL.to_string(),
+            subject: Some("Time for a checkup".to_string()),
+            body: "Dear {{customerName}}, ...".to_string(),
+            exclude_scheduled: None,
+            exclude_contacted_days: Some(30),
+            repeat_after_days: Some(300),
+            enabled: None,
+        }
+    }
+
+    #[test]
+    fn test_create_request_validation() {
+        assert!(create_request().validate().is_ok());
+        assert!(CreateCampaignRequest { months_since_revision: None, ..create_request() }.validate().is_err());
+        assert!(CreateCampaignRequest { active_to_month: None, ..create_request() }.validate().is_err());
+        assert!(CreateCampaignRequest { active_from_month: Some(13), ..create_request() }.validate().is_err());
+        assert!(CreateCampaignRequest { channel: "fax".to_string(), ..create_request() }.validate().is_err());
+        assert!(CreateCampaignRequest { subject: None, ..create_request() }.validate().is_err());
+        assert!(CreateCampaignRequest {
+            channel: CAMPAIGN_CHANNEL_SMS.to_string(),
+            subject: None,
+            body: "x".repeat(MAX_CAMPAIGN_SMS_LENGTH + 1),
+            ..create_request()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_active_months_wrap_the_year_end() {
+        let campaign = |from, to| Campaign {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            name: String::new(),
+            trigger_type: CAMPAIGN_TRIGGER_CUSTOMER_ANNIVERSARY.to_string(),
+            months_since_revision: None,
+            active_from_month: from,
+            active_to_month: to,
+            channel: CAMPAIGN_CHANNEL_EMAIL.to_string(),
+            subject: None,
+            body: String::new(),
+            exclude_scheduled: true,
+            exclude_contacted_days: None,
+            repeat_after_days: None,
+            enabled: true,
+            last_run_on: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            sent_count: 0,
+            failed_count: 0,
+            last_sent_at: None,
+        };
+
+        assert!(campaign(None, None).is_active_in(7));
+        assert!(campaign(Some(9), Some(11)).is_active_in(10));
+        assert!(!campaign(Some(9), Some(11)).is_active_in(12));
+        assert!(campaign(Some(10), Some(2)).is_active_in(1));
+        assert!(!campaign(Some(10), Some(2)).is_active_in(5));
+    }
+}
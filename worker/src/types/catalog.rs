@@ -0,0 +1,127 @@
+#![allow(dead_code)]
+//! Service catalog types
+//!
+//! Priced services and materials an account offers; quote lines are picked
+//! from the catalog and keep a copy of the name and price.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::currency::{normalize_currency, SUPPORTED_CURRENCIES};
+use super::work_item::WorkType;
+
+pub const MAX_CATALOG_NAME_LENGTH: usize = 200;
+pub const MAX_CATALOG_UNIT_LENGTH: usize = 20;
+pub const DEFAULT_CATALOG_UNIT: &str = "ks";
+
+/// Catalog item
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogItem {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub unit: String,
+    /// Net price per unit in minor units of `currency`
+    pub unit_price_minor: i64,
+    /// VAT rate in percent
+    pub vat_rate: i32,
+    pub currency: String,
+    pub work_type: Option<WorkType>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// NATS: sazinka.catalog.create
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCatalogItemRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub unit: Option<String>,
+    pub unit_price_minor: i64,
+    pub vat_rate: i32,
+    /// Defaults to the account currency
+    pub currency: Option<String>,
+    pub work_type: Option<WorkType>,
+}
+
+/// NATS: sazinka.catalog.update
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCatalogItemRequest {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub unit: Option<String>,
+    pub unit_price_minor: i64,
+    pub vat_rate: i32,
+    pub currency: Option<String>,
+    pub work_type: Option<WorkType>,
+    pub is_active: bool,
+}
+
+/// NATS: sazinka.catalog.list
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCatalogItemsRequest {
+    /// Include deactivated items
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+/// Validate catalog item fields; returns the normalized unit and currency
+pub fn validate_catalog_item(
+    name: &str,
+    unit: Option<&str>,
+    unit_price_minor: i64,
+    vat_rate: i32,
+    currency: Option<&str>,
+    account_currency: &str,
+) -> Result<(String, String), String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_CATALOG_NAME_LENGTH {
+        return Err(format!("name must be 1-{} characters", MAX_CATALOG_NAME_LENGTH));
+    }
+    let unit = unit.map(str::trim).filter(|u| !u.is_empty()).unwrap_or(DEFAULT_CATALOG_UNIT);
+    if unit.chars().count() > MAX_CATALOG_UNIT_LENGTH {
+        return Err(format!("unit is limited to {} characters", MAX_CATALOG_UNIT_LENGTH));
+    }
+    if unit_price_minor < 0 {
+        return Err("unitPriceMinor must not be negative".to_string());
+    }
+    if !(0..=100).contains(&vat_rate) {
+        return Err("vatRate must be between 0 and 100".to_string());
+    }
+    let currency = match currency {
+        Some(code) => normalize_currency(code)
+            .ok_or_else(|| format!("currency must be one of: {}", SUPPORTED_CURRENCIES.join(", ")))?,
+        None => account_currency.to_string(),
+    };
+    Ok((unit.to_string(), currency))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_catalog_item() {
+        assert_eq!(
+            validate_catalog_item("Revize kotle", None, 150_000, 21, None, "CZK"),
+            Ok(("ks".to_string(), "CZK".to_string()))
+        );
+        assert_eq!(
+            validate_catalog_item("Práce", Some(" h "), 80_000, 21, Some("eur"), "CZK"),
+            Ok(("h".to_string(), "EUR".to_string()))
+        );
+        assert!(validate_catalog_item(" ", None, 100, 21, None, "CZK").is_err());
+        assert!(validate_catalog_item("x", None, -1, 21, None, "CZK").is_err());
+        assert!(validate_catalog_item("x", None, 100, 101, None, "CZK").is_err());
+        assert!(validate_catalog_item("x", None, 100, 21, Some("XXX"), "CZK").is_err());
+    }
+}
@@ -57,6 +57,53 @@ impl CommunicationDirection {
     }
 }
 
+/// Origin of a communication entry: written by a user, or automatically
+/// when a reminder goes out, a route is confirmed or a visit is completed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommunicationEntryType {
+    Manual,
+    ReminderEmail,
+    ReminderSms,
+    ConfirmationEmail,
+    ConfirmationSms,
+    CampaignEmail,
+    CampaignSms,
+    RouteConfirmed,
+    VisitCompleted,
+}
+
+impl CommunicationEntryType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::ReminderEmail => "reminder_email",
+            Self::ReminderSms => "reminder_sms",
+            Self::ConfirmationEmail => "confirmation_email",
+            Self::ConfirmationSms => "confirmation_sms",
+            Self::CampaignEmail => "campaign_email",
+            Self::CampaignSms => "campaign_sms",
+            Self::RouteConfirmed => "route_confirmed",
+            Self::VisitCompleted => "visit_completed",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "manual" => Some(Self::Manual),
+            "reminder_email" => Some(Self::ReminderEmail),
+            "reminder_sms" => Some(Self::ReminderSms),
+            "confirmation_email" => Some(Self::ConfirmationEmail),
+            "confirmation_sms" => Some(Self::ConfirmationSms),
+            "campaign_email" => Some(Self::CampaignEmail),
+            "campaign_sms" => Some(Self::CampaignSms),
+            "route_confirmed" => Some(Self::RouteConfirmed),
+            "visit_completed" => Some(Self::VisitCompleted),
+            _ => None,
+        }
+    }
+}
+
 /// Email delivery status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -88,11 +135,29 @@ pub struct Communication {
     
     pub email_status: Option<String>,
     pub duration_minutes: Option<i32>,
+
+    /// `CommunicationEntryType`; "manual" unless written by the system
+    pub entry_type: String,
+    /// Structured facts of an automatic entry (recipient, route, visit result, ...)
+    pub details: Option<serde_json::Value>,
     
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Entry written by the system into the customer's communication history
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutomaticCommunication {
+    pub customer_id: Uuid,
+    pub revision_id: Option<Uuid>,
+    pub entry_type: CommunicationEntryType,
+    /// `CommunicationType` of the channel used
+    pub comm_type: &'static str,
+    pub subject: Option<String>,
+    pub content: String,
+    pub details: serde_json::Value,
+}
+
 /// Request to create a communication
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -125,6 +190,9 @@ pub struct ListCommunicationsRequest {
     pub customer_id: Option<Uuid>,
     pub revision_id: Option<Uuid>,
     pub comm_type: Option<String>,
+    /// Only entries of these `CommunicationEntryType`s; empty means all
+    #[serde(default)]
+    pub entry_types: Vec<String>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
@@ -136,3 +204,55 @@ pub struct ListCommunicationsResponse {
     pub communications: Vec<Communication>,
     pub total: i64,
 }
+
+impl ListCommunicationsRequest {
+    /// Reject unknown entry types instead of silently matching nothing
+    pub fn validate(&self) -> Result<(), String> {
+        match self
+            .entry_types
+            .iter()
+            .find(|t| CommunicationEntryType::from_str(t).is_none())
+        {
+            Some(unknown) => Err(format!("Unknown entry type: {}", unknown)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_type_round_trips_through_str() {
+        for t in [
+            CommunicationEntryType::Manual,
+            CommunicationEntryType::ReminderEmail,
+            CommunicationEntryType::ReminderSms,
+            CommunicationEntryType::ConfirmationEmail,
+            CommunicationEntryType::ConfirmationSms,
+            CommunicationEntryType::CampaignEmail,
+            CommunicationEntryType::CampaignSms,
+            CommunicationEntryType::RouteConfirmed,
+            CommunicationEntryType::VisitCompleted,
+        ] {
+            assert_eq!(CommunicationEntryType::from_str(t.as_str()), Some(t));
+            assert_eq!(serde_json::to_value(t).unwrap(), t.as_str());
+        }
+    }
+
+    #[test]
+    fn list_request_defaults_to_all_entry_types() {
+        let req: ListCommunicationsRequest =
+            serde_json::from_str(r#"{"customerId":null}"#).unwrap();
+        assert!(req.entry_types.is_empty());
+        assert!(req.validate().is_ok());
+    }
+
+    #[test]
+    fn list_request_rejects_unknown_entry_type() {
+        let req: ListCommunicationsRequest =
+            serde_json::from_str(r#"{"entryTypes":["visit_completed","fax"]}"#).unwrap();
+        assert_eq!(req.validate().unwrap_err(), "Unknown entry type: fax");
+    }
+}
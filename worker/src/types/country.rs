@@ -79,6 +79,29 @@ pub struct CountrySyncResponse {
     pub updated: i32,
 }
 
+/// Customers whose country is unknown, unsupported or lacks map coverage,
+/// grouped by country (admin report).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryCoverageGap {
+    /// Country code as stored on the customers (upper-cased)
+    pub country: String,
+    /// None when the code is not in the `countries` table
+    pub name_en: Option<String>,
+    pub is_supported: Option<bool>,
+    pub has_map_coverage: Option<bool>,
+    pub customer_count: i64,
+    pub account_count: i64,
+    /// Customers without coordinates (cannot be routed)
+    pub ungeocoded_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryCoverageReportResponse {
+    pub items: Vec<CountryCoverageGap>,
+}
+
 // =============================================================================
 // API request types
 // =============================================================================
@@ -158,6 +181,25 @@ mod tests {
         assert!(req.valhalla_region.is_none());
     }
 
+    #[test]
+    fn coverage_gap_serializes_unknown_country() {
+        let gap = CountryCoverageGap {
+            country: "XX".into(),
+            name_en: None,
+            is_supported: None,
+            has_map_coverage: None,
+            customer_count: 3,
+            account_count: 1,
+            ungeocoded_count: 2,
+        };
+        let json = serde_json::to_value(&gap).unwrap();
+        assert_eq!(json["country"], "XX");
+        assert!(json["nameEn"].is_null());
+        assert!(json["isSupported"].is_null());
+        assert_eq!(json["customerCount"], 3);
+        assert_eq!(json["ungeocodedCount"], 2);
+    }
+
     #[test]
     fn country_sync_response_serializes() {
         let r = CountrySyncResponse { synced: 212, added: 210, updated: 2 };
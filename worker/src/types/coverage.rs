@@ -0,0 +1,222 @@
+#![allow(dead_code)]
+//! Service coverage by postal code
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Coverage is not checked
+pub const COVERAGE_MODE_OFF: &str = "off";
+/// Out-of-coverage customers are accepted with a warning
+pub const COVERAGE_MODE_WARN: &str = "warn";
+/// Out-of-coverage customers are refused
+pub const COVERAGE_MODE_REJECT: &str = "reject";
+pub const COVERAGE_MODES: &[&str] = &[COVERAGE_MODE_OFF, COVERAGE_MODE_WARN, COVERAGE_MODE_REJECT];
+
+pub const COVERAGE_SOURCE_CREATE: &str = "create";
+pub const COVERAGE_SOURCE_IMPORT: &str = "import";
+
+/// Longest accepted postal code pattern (after removing spaces)
+const MAX_PATTERN_LEN: usize = 10;
+
+/// Normalize a postal code for comparison ("602 00" → "60200")
+pub fn normalize_postal_code(postal_code: &str) -> String {
+    postal_code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Whether a normalized postal code matches one of the coverage patterns (prefix match)
+pub fn postal_code_covered(postal_code: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| postal_code.starts_with(pattern.as_str()))
+}
+
+/// Postal code prefix the account serves
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageArea {
+    pub id: Uuid,
+    pub pattern: String,
+    pub label: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Coverage configuration of an account
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageSettings {
+    pub mode: String,
+    pub areas: Vec<CoverageArea>,
+}
+
+/// Outcome of checking an address against the coverage list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverageVerdict {
+    Covered,
+    /// Outside the served area, accept with a warning
+    Warn,
+    /// Outside the served area, refuse
+    Reject,
+}
+
+impl CoverageSettings {
+    /// Check a postal code. Addresses without a postal code, and accounts
+    /// without a coverage list, are always considered covered.
+    pub fn verdict(&self, postal_code: Option<&str>) -> CoverageVerdict {
+        if self.mode == COVERAGE_MODE_OFF || self.areas.is_empty() {
+            return CoverageVerdict::Covered;
+        }
+        let postal_code = match postal_code.map(normalize_postal_code) {
+            Some(code) if !code.is_empty() => code,
+            _ => return CoverageVerdict::Covered,
+        };
+        let patterns: Vec<String> = self.areas.iter().map(|a| a.pattern.clone()).collect();
+        if postal_code_covered(&postal_code, &patterns) {
+            CoverageVerdict::Covered
+        } else if self.mode == COVERAGE_MODE_REJECT {
+            CoverageVerdict::Reject
+        } else {
+            CoverageVerdict::Warn
+        }
+    }
+}
+
+/// Coverage area in a set request
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageAreaInput {
+    pub pattern: String,
+    pub label: Option<String>,
+}
+
+/// NATS: sazinka.coverage.set — replaces the whole coverage list
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetCoverageRequest {
+    pub mode: String,
+    pub areas: Vec<CoverageAreaInput>,
+}
+
+impl SetCoverageRequest {
+    /// Validate the payload, returning normalized, deduplicated areas
+    pub fn validate(&self) -> Result<Vec<CoverageAreaInput>, String> {
+        if !COVERAGE_MODES.contains(&self.mode.as_str()) {
+            return Err(format!("mode must be one of: {}", COVERAGE_MODES.join(", ")));
+        }
+        let mut areas: Vec<CoverageAreaInput> = Vec::with_capacity(self.areas.len());
+        for area in &self.areas {
+            let pattern = normalize_postal_code(&area.pattern);
+            if pattern.is_empty() || pattern.len() > MAX_PATTERN_LEN {
+                return Err(format!("invalid postal code pattern: {}", area.pattern));
+            }
+            if !pattern.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(format!("invalid postal code pattern: {}", area.pattern));
+            }
+            if areas.iter().any(|a| a.pattern == pattern) {
+                continue;
+            }
+            let label = area.label.as_deref().map(str::trim).filter(|l| !l.is_empty()).map(String::from);
+            areas.push(CoverageAreaInput { pattern, label });
+        }
+        if self.mode != COVERAGE_MODE_OFF && areas.is_empty() {
+            return Err("at least one coverage area is required when coverage is enforced".to_string());
+        }
+        Ok(areas)
+    }
+}
+
+/// NATS: sazinka.coverage.report
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageReportRequest {
+    pub date_from: Option<NaiveDate>,
+    pub date_to: Option<NaiveDate>,
+}
+
+/// Demand from one postal code outside the served area
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageDemandRow {
+    pub postal_code: String,
+    pub city: Option<String>,
+    pub total: i64,
+    pub rejected: i64,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+/// Response for sazinka.coverage.report
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageReportResponse {
+    pub rows: Vec<CoverageDemandRow>,
+    pub total: i64,
+    pub rejected: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(mode: &str, patterns: &[&str]) -> CoverageSettings {
+        CoverageSettings {
+            mode: mode.to_string(),
+            areas: patterns
+                .iter()
+                .map(|p| CoverageArea {
+                    id: Uuid::nil(),
+                    pattern: p.to_string(),
+                    label: None,
+                    created_at: Utc::now(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_postal_code() {
+        assert_eq!(normalize_postal_code(" 602 00 "), "60200");
+        assert_eq!(normalize_postal_code("sw1a 1aa"), "SW1A1AA");
+    }
+
+    #[test]
+    fn test_verdict() {
+        let warn = settings(COVERAGE_MODE_WARN, &["602", "664"]);
+        assert_eq!(warn.verdict(Some("602 00")), CoverageVerdict::Covered);
+        assert_eq!(warn.verdict(Some("110 00")), CoverageVerdict::Warn);
+        assert_eq!(warn.verdict(None), CoverageVerdict::Covered);
+        assert_eq!(warn.verdict(Some("  ")), CoverageVerdict::Covered);
+
+        let reject = settings(COVERAGE_MODE_REJECT, &["602"]);
+        assert_eq!(reject.verdict(Some("110 00")), CoverageVerdict::Reject);
+
+        let off = settings(COVERAGE_MODE_OFF, &["602"]);
+        assert_eq!(off.verdict(Some("110 00")), CoverageVerdict::Covered);
+    }
+
+    #[test]
+    fn test_set_validation() {
+        let req = SetCoverageRequest {
+            mode: COVERAGE_MODE_REJECT.to_string(),
+            areas: vec![
+                CoverageAreaInput { pattern: "602 ".to_string(), label: Some(" Brno ".to_string()) },
+                CoverageAreaInput { pattern: "602".to_string(), label: None },
+            ],
+        };
+        let areas = req.validate().unwrap();
+        assert_eq!(areas.len(), 1);
+        assert_eq!(areas[0].pattern, "602");
+        assert_eq!(areas[0].label.as_deref(), Some("Brno"));
+
+        let empty = SetCoverageRequest { mode: COVERAGE_MODE_WARN.to_string(), areas: vec![] };
+        assert!(empty.validate().is_err());
+
+        let bad = SetCoverageRequest {
+            mode: COVERAGE_MODE_WARN.to_string(),
+            areas: vec![CoverageAreaInput { pattern: "60*".to_string(), label: None }],
+        };
+        assert!(bad.validate().is_err());
+    }
+}
@@ -0,0 +1,213 @@
+#![allow(dead_code)]
+//! CRM sync connector types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Default number of records sent per push request
+pub const DEFAULT_CRM_BATCH_SIZE: i32 = 50;
+/// Upper bound for batch size (matches the DB constraint)
+pub const MAX_CRM_BATCH_SIZE: i32 = 500;
+/// Default pull schedule
+pub const DEFAULT_CRM_PULL_INTERVAL_MINUTES: i32 = 60;
+
+/// Outbound REST connector configuration
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CrmConnector {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub base_url: String,
+    pub push_path: String,
+    pub pull_path: Option<String>,
+    pub auth_header_name: Option<String>,
+    /// Never echoed back to the client
+    #[serde(skip_serializing, default)]
+    pub auth_header_value: Option<String>,
+    /// Local field name → remote field name (JSON object of strings)
+    pub field_mapping: serde_json::Value,
+    pub batch_size: i32,
+    pub pull_interval_minutes: i32,
+    pub enabled: bool,
+    pub last_push_at: Option<DateTime<Utc>>,
+    /// Start of the last clean pull, sent as the `updatedSince` watermark
+    pub last_pull_at: Option<DateTime<Utc>>,
+    /// End of the last run, whatever its outcome (drives the schedule)
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A single sync run
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CrmSyncLogEntry {
+    pub id: Uuid,
+    pub connector_id: Uuid,
+    pub direction: String,
+    pub status: String,
+    pub items_total: i32,
+    pub items_succeeded: i32,
+    pub items_failed: i32,
+    pub conflicts: serde_json::Value,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+/// Field-level disagreement found while pulling remote updates.
+///
+/// The local value is kept; the conflict is reported for manual review.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrmSyncConflict {
+    pub entity_id: Uuid,
+    pub field: String,
+    pub local_value: serde_json::Value,
+    pub remote_value: serde_json::Value,
+}
+
+/// Outcome of one push or pull pass, persisted as a log entry
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrmSyncOutcome {
+    pub items_total: i32,
+    pub items_succeeded: i32,
+    pub items_failed: i32,
+    pub conflicts: Vec<CrmSyncConflict>,
+    pub error: Option<String>,
+}
+
+impl CrmSyncOutcome {
+    /// 'success', 'partial' or 'failed'
+    pub fn status(&self) -> &'static str {
+        if self.error.is_some() && self.items_succeeded == 0 {
+            "failed"
+        } else if self.error.is_some() || self.items_failed > 0 || !self.conflicts.is_empty() {
+            "partial"
+        } else {
+            "success"
+        }
+    }
+}
+
+// ============================================================
+// Request / response types (NATS payloads)
+// ============================================================
+
+/// NATS: sazinka.crm_sync.connector.create
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCrmConnectorRequest {
+    pub name: String,
+    pub base_url: String,
+    pub push_path: Option<String>,
+    pub pull_path: Option<String>,
+    pub auth_header_name: Option<String>,
+    pub auth_header_value: Option<String>,
+    #[serde(default)]
+    pub field_mapping: Option<serde_json::Value>,
+    pub batch_size: Option<i32>,
+    pub pull_interval_minutes: Option<i32>,
+    pub enabled: Option<bool>,
+}
+
+/// NATS: sazinka.crm_sync.connector.update
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCrmConnectorRequest {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub base_url: Option<String>,
+    pub push_path: Option<String>,
+    pub pull_path: Option<String>,
+    pub auth_header_name: Option<String>,
+    pub auth_header_value: Option<String>,
+    pub field_mapping: Option<serde_json::Value>,
+    pub batch_size: Option<i32>,
+    pub pull_interval_minutes: Option<i32>,
+    pub enabled: Option<bool>,
+}
+
+/// NATS: sazinka.crm_sync.connector.delete / sazinka.crm_sync.run
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrmConnectorIdRequest {
+    pub id: Uuid,
+}
+
+/// NATS: sazinka.crm_sync.log.list
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCrmSyncLogRequest {
+    pub connector_id: Uuid,
+    pub limit: Option<i64>,
+}
+
+/// Response for sazinka.crm_sync.connector.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCrmConnectorsResponse {
+    pub connectors: Vec<CrmConnector>,
+}
+
+/// Response for sazinka.crm_sync.log.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCrmSyncLogResponse {
+    pub entries: Vec<CrmSyncLogEntry>,
+}
+
+/// Response for sazinka.crm_sync.run
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunCrmSyncResponse {
+    pub push: CrmSyncOutcome,
+    pub pull: Option<CrmSyncOutcome>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_status() {
+        let mut outcome = CrmSyncOutcome { items_total: 2, items_succeeded: 2, ..Default::default() };
+        assert_eq!(outcome.status(), "success");
+
+        outcome.items_failed = 1;
+        assert_eq!(outcome.status(), "partial");
+
+        let failed = CrmSyncOutcome { error: Some("timeout".into()), ..Default::default() };
+        assert_eq!(failed.status(), "failed");
+    }
+
+    #[test]
+    fn test_connector_never_serializes_auth_value() {
+        let connector = CrmConnector {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            name: "Raynet".into(),
+            base_url: "https://api.example.com".into(),
+            push_path: "/sync".into(),
+            pull_path: None,
+            auth_header_name: Some("Authorization".into()),
+            auth_header_value: Some("Bearer secret".into()),
+            field_mapping: serde_json::json!({}),
+            batch_size: DEFAULT_CRM_BATCH_SIZE,
+            pull_interval_minutes: DEFAULT_CRM_PULL_INTERVAL_MINUTES,
+            enabled: true,
+            last_push_at: None,
+            last_pull_at: None,
+            last_run_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let json = serde_json::to_string(&connector).unwrap();
+        assert!(json.contains("authHeaderName"));
+        assert!(!json.contains("secret"));
+    }
+}
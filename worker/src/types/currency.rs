@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+//! Currencies of billed amounts
+
+/// Currency of accounts that never chose one
+pub const DEFAULT_CURRENCY: &str = "CZK";
+
+/// Currencies amounts can be billed in (all with two minor-unit digits)
+pub const SUPPORTED_CURRENCIES: &[&str] = &["CZK", "EUR", "PLN", "USD", "GBP"];
+
+/// Normalize an ISO 4217 code ("eur" → "EUR"); None when it is not supported
+pub fn normalize_currency(code: &str) -> Option<String> {
+    let code = code.trim().to_uppercase();
+    SUPPORTED_CURRENCIES.contains(&code.as_str()).then_some(code)
+}
+
+/// Symbol shown next to amounts in reports
+pub fn currency_symbol(code: &str) -> &str {
+    match code {
+        "CZK" => "Kč",
+        "EUR" => "€",
+        "PLN" => "zł",
+        "USD" => "$",
+        "GBP" => "£",
+        other => other,
+    }
+}
+
+/// Currency of a billed work item.
+///
+/// An explicit currency wins, then the currency the visit is already billed
+/// in, then the account currency. All billed items of one visit end up on a
+/// single invoice draft, so a currency differing from the visit's is refused.
+pub fn resolve_billing_currency(
+    requested: Option<&str>,
+    visit_currency: Option<&str>,
+    account_currency: &str,
+) -> Result<String, String> {
+    let currency = match requested {
+        Some(code) => normalize_currency(code)
+            .ok_or_else(|| format!("currency must be one of: {}", SUPPORTED_CURRENCIES.join(", ")))?,
+        None => visit_currency.unwrap_or(account_currency).to_string(),
+    };
+
+    match visit_currency {
+        Some(existing) if existing != currency => Err(format!(
+            "Visit is already billed in {}; work items of one visit cannot mix currencies",
+            existing
+        )),
+        _ => Ok(currency),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_currency() {
+        assert_eq!(normalize_currency(" eur ").as_deref(), Some("EUR"));
+        assert_eq!(normalize_currency("CZK").as_deref(), Some("CZK"));
+        assert_eq!(normalize_currency("XYZ"), None);
+    }
+
+    #[test]
+    fn test_resolve_billing_currency() {
+        assert_eq!(resolve_billing_currency(None, None, "CZK"), Ok("CZK".to_string()));
+        assert_eq!(resolve_billing_currency(None, Some("EUR"), "CZK"), Ok("EUR".to_string()));
+        assert_eq!(resolve_billing_currency(Some("eur"), Some("EUR"), "CZK"), Ok("EUR".to_string()));
+        assert!(resolve_billing_currency(Some("CZK"), Some("EUR"), "CZK").is_err());
+        assert!(resolve_billing_currency(Some("BTC"), None, "CZK").is_err());
+    }
+}
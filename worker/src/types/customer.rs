@@ -21,6 +21,42 @@ impl Default for CustomerType {
     }
 }
 
+/// Source of customers created by the web lead intake
+pub const ACQUISITION_SOURCE_WEB_FORM: &str = "web_form";
+/// Source of customers recommended by another customer
+pub const ACQUISITION_SOURCE_REFERRAL: &str = "referral";
+/// Where customers come from
+pub const ACQUISITION_SOURCES: &[&str] = &[ACQUISITION_SOURCE_WEB_FORM, "phone", "email", ACQUISITION_SOURCE_REFERRAL, "campaign", "other"];
+
+/// Normalize an acquisition source, accepting the Czech labels used in
+/// imported spreadsheets ("telefon", "doporučení", ...)
+pub fn normalize_acquisition_source(raw: &str) -> Option<&'static str> {
+    let value = raw.trim().to_lowercase().replace([' ', '-'], "_");
+    let source = match value.as_str() {
+        "web_form" | "web" | "webform" | "formular" | "formulář" | "webovy_formular" | "webový_formulář" => ACQUISITION_SOURCE_WEB_FORM,
+        "phone" | "telefon" | "tel" => "phone",
+        "email" | "e_mail" => "email",
+        "referral" | "doporuceni" | "doporučení" => ACQUISITION_SOURCE_REFERRAL,
+        "campaign" | "kampan" | "kampaň" => "campaign",
+        "other" | "jine" | "jiné" => "other",
+        _ => return None,
+    };
+    Some(source)
+}
+
+/// Check a requested source / referrer pair. A referrer is only kept for referrals.
+pub fn validate_acquisition_source(source: Option<&str>, referrer_customer_id: Option<Uuid>) -> Result<(), String> {
+    if let Some(source) = source.filter(|s| !s.is_empty()) {
+        if !ACQUISITION_SOURCES.contains(&source) {
+            return Err(format!("acquisitionSource must be one of: {}", ACQUISITION_SOURCES.join(", ")));
+        }
+    }
+    if referrer_customer_id.is_some() && source != Some(ACQUISITION_SOURCE_REFERRAL) {
+        return Err(format!("referrerCustomerId requires acquisitionSource \"{}\"", ACQUISITION_SOURCE_REFERRAL));
+    }
+    Ok(())
+}
+
 /// Customer entity
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 #[serde(rename_all = "camelCase")]
@@ -60,6 +96,45 @@ pub struct Customer {
     pub is_abandoned: bool,
     #[sqlx(default)]
     pub deleted_at: Option<DateTime<Utc>>,
+
+    /// Parent company when this customer is one of its branches
+    #[sqlx(default)]
+    pub parent_customer_id: Option<Uuid>,
+
+    /// Communication language; None uses the account default
+    #[sqlx(default)]
+    pub language: Option<String>,
+
+    /// Human-friendly code from the account's pattern, e.g. "CUS-2025-0001"
+    #[sqlx(default)]
+    pub customer_code: Option<String>,
+
+    /// One of `ACQUISITION_SOURCES`; None when unknown
+    #[sqlx(default)]
+    pub acquisition_source: Option<String>,
+    /// Customer who recommended this one (referrals only)
+    #[sqlx(default)]
+    pub referrer_customer_id: Option<Uuid>,
+    /// Lead state for customers that came in as leads (see `types::lead`)
+    #[sqlx(default)]
+    pub lead_status: Option<String>,
+    /// Why the lead was lost (lead_status "lost" only)
+    #[sqlx(default)]
+    pub lead_lost_reason: Option<String>,
+    #[sqlx(default)]
+    pub lead_status_changed_at: Option<DateTime<Utc>>,
+    /// Internal risk score (see `types::customer_risk`); only on customer.get
+    #[sqlx(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_score: Option<i32>,
+    #[sqlx(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_flag: Option<bool>,
+
+    /// Set on create when the address is outside the service coverage
+    #[sqlx(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage_warning: Option<String>,
 }
 
 /// Request to create a customer
@@ -82,6 +157,19 @@ pub struct CreateCustomerRequest {
     pub lat: Option<f64>,
     pub lng: Option<f64>,
     pub notes: Option<String>,
+    /// Create the customer as a branch of this parent company
+    #[serde(default)]
+    pub parent_customer_id: Option<Uuid>,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Explicit customer code; generated from the account pattern when empty
+    #[serde(default)]
+    pub customer_code: Option<String>,
+    #[serde(default)]
+    pub acquisition_source: Option<String>,
+    /// Recommending customer; only with the "referral" source
+    #[serde(default)]
+    pub referrer_customer_id: Option<Uuid>,
 }
 
 /// Request to update a customer
@@ -105,6 +193,101 @@ pub struct UpdateCustomerRequest {
     pub lat: Option<f64>,
     pub lng: Option<f64>,
     pub notes: Option<String>,
+    /// Communication language; an empty string resets it to the account default
+    #[serde(default)]
+    pub language: Option<String>,
+    /// An empty string clears the source; changing the source replaces the referrer
+    #[serde(default)]
+    pub acquisition_source: Option<String>,
+    #[serde(default)]
+    pub referrer_customer_id: Option<Uuid>,
+}
+
+/// Most customers one bulk update may change
+pub const MAX_BULK_UPDATE_CUSTOMERS: usize = 1000;
+
+pub const BULK_UPDATE_UPDATED: &str = "updated";
+/// Not a customer of the account, or anonymized / deleted
+pub const BULK_UPDATE_NOT_FOUND: &str = "not_found";
+
+/// Fields a bulk update sets on every listed customer; absent fields stay
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerBulkPatch {
+    #[serde(rename = "type")]
+    pub customer_type: Option<CustomerType>,
+    pub country: Option<String>,
+    /// Communication language; an empty string resets it to the account default
+    pub language: Option<String>,
+    /// An empty string clears the source; a new source clears the referrer
+    pub acquisition_source: Option<String>,
+}
+
+impl CustomerBulkPatch {
+    pub fn is_empty(&self) -> bool {
+        self.customer_type.is_none()
+            && self.country.is_none()
+            && self.language.is_none()
+            && self.acquisition_source.is_none()
+    }
+}
+
+/// Request to apply one patch to many customers
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkUpdateCustomersRequest {
+    pub ids: Vec<Uuid>,
+    pub patch: CustomerBulkPatch,
+}
+
+impl BulkUpdateCustomersRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ids.is_empty() || self.ids.len() > MAX_BULK_UPDATE_CUSTOMERS {
+            return Err(format!("ids must list 1 to {} customers", MAX_BULK_UPDATE_CUSTOMERS));
+        }
+        if self.patch.is_empty() {
+            return Err("patch changes nothing".to_string());
+        }
+        Ok(())
+    }
+
+    /// Requested IDs without repeats, in request order
+    pub fn unique_ids(&self) -> Vec<Uuid> {
+        let mut seen = std::collections::HashSet::new();
+        self.ids.iter().copied().filter(|id| seen.insert(*id)).collect()
+    }
+}
+
+/// Outcome for one requested customer
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkUpdateCustomerResult {
+    pub id: Uuid,
+    pub status: String,
+}
+
+/// Response of a bulk update
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkUpdateCustomersResponse {
+    pub updated: usize,
+    pub not_found: usize,
+    pub results: Vec<BulkUpdateCustomerResult>,
+}
+
+impl BulkUpdateCustomersResponse {
+    /// Report for `ids`, of which `updated` were changed
+    pub fn new(ids: &[Uuid], updated: &[Uuid]) -> Self {
+        let results: Vec<BulkUpdateCustomerResult> = ids
+            .iter()
+            .map(|id| BulkUpdateCustomerResult {
+                id: *id,
+                status: if updated.contains(id) { BULK_UPDATE_UPDATED } else { BULK_UPDATE_NOT_FOUND }.to_string(),
+            })
+            .collect();
+        let updated = results.iter().filter(|r| r.status == BULK_UPDATE_UPDATED).count();
+        Self { updated, not_found: results.len() - updated, results }
+    }
 }
 
 /// Coordinates
@@ -144,6 +327,17 @@ pub struct CustomerListItem {
     pub next_revision_date: Option<NaiveDate>,
     pub overdue_count: i64,
     pub never_serviced_count: i64,
+
+    // Hierarchy
+    #[sqlx(default)]
+    pub parent_customer_id: Option<Uuid>,
+    #[sqlx(default)]
+    pub parent_name: Option<String>,
+    #[sqlx(default)]
+    pub branch_count: i64,
+
+    #[sqlx(default)]
+    pub customer_code: Option<String>,
 }
 
 /// Single sort entry for server-side multi-column ordering.
@@ -228,6 +422,10 @@ pub struct ListCustomersRequest {
     pub next_revision_within_days: Option<i32>,
     /// Filter by customer type: "person", "company"
     pub customer_type: Option<String>,
+    /// Filter by hierarchy level: "parent", "branch", "standalone", "top"
+    pub hierarchy_level: Option<String>,
+    /// Only branches of this parent company
+    pub parent_customer_id: Option<Uuid>,
     /// Multi-level sort model sent from the frontend.
     /// When present and non-empty (after filtering invalid entries), takes
     /// precedence over the legacy sort_by/sort_order fields.
@@ -552,4 +750,49 @@ mod tests {
         assert!(json.contains("\"total\":2"));
         assert!(json.contains("\"hasMore\":false"));
     }
+
+    // ── Acquisition source ───────────────────────────────────────────────────
+
+    #[test]
+    fn acquisition_source_normalizes_czech_labels() {
+        assert_eq!(normalize_acquisition_source("Telefon"), Some("phone"));
+        assert_eq!(normalize_acquisition_source(" doporučení "), Some(ACQUISITION_SOURCE_REFERRAL));
+        assert_eq!(normalize_acquisition_source("Webový formulář"), Some("web_form"));
+        assert_eq!(normalize_acquisition_source("e-mail"), Some("email"));
+        assert_eq!(normalize_acquisition_source("billboard"), None);
+    }
+
+    #[test]
+    fn referrer_requires_referral_source() {
+        let referrer = Some(Uuid::new_v4());
+        assert!(validate_acquisition_source(Some("referral"), referrer).is_ok());
+        assert!(validate_acquisition_source(Some("phone"), None).is_ok());
+        assert!(validate_acquisition_source(Some(""), None).is_ok());
+        assert!(validate_acquisition_source(Some("phone"), referrer).is_err());
+        assert!(validate_acquisition_source(None, referrer).is_err());
+        assert!(validate_acquisition_source(Some("billboard"), None).is_err());
+    }
+
+    // ── Bulk update ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn bulk_update_request_validates_and_dedupes() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let patch = CustomerBulkPatch { country: Some("CZ".to_string()), ..Default::default() };
+        let request = BulkUpdateCustomersRequest { ids: vec![a, b, a], patch: patch.clone() };
+        assert!(request.validate().is_ok());
+        assert_eq!(request.unique_ids(), vec![a, b]);
+
+        assert!(BulkUpdateCustomersRequest { ids: vec![], patch }.validate().is_err());
+        assert!(BulkUpdateCustomersRequest { ids: vec![a], patch: CustomerBulkPatch::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn bulk_update_report_marks_missing_customers() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let report = BulkUpdateCustomersResponse::new(&[a, b], &[b]);
+        assert_eq!((report.updated, report.not_found), (1, 1));
+        assert_eq!(report.results[0], BulkUpdateCustomerResult { id: a, status: BULK_UPDATE_NOT_FOUND.to_string() });
+        assert_eq!(report.results[1].status, BULK_UPDATE_UPDATED);
+    }
 }
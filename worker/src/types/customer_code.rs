@@ -0,0 +1,155 @@
+#![allow(dead_code)]
+//! Customer code patterns
+//!
+//! A pattern is literal text with placeholders: `{YYYY}` / `{YY}` for the
+//! creation year and `{SEQ}` / `{SEQ:n}` for the sequence padded to n digits,
+//! e.g. "CUS-{YYYY}-{SEQ:4}" -> "CUS-2025-0001". Patterns with a year restart
+//! the sequence every year.
+
+/// Pattern of new accounts
+pub const DEFAULT_CUSTOMER_CODE_PATTERN: &str = "CUS-{YYYY}-{SEQ:4}";
+/// Period of a pattern without a year
+pub const CUSTOMER_CODE_PERIOD_NONE: i32 = 0;
+pub const MAX_PATTERN_LENGTH: usize = 40;
+const MAX_SEQ_PADDING: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Year4,
+    Year2,
+    Sequence(usize),
+}
+
+/// A parsed customer code pattern
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomerCodePattern {
+    parts: Vec<Part>,
+}
+
+impl CustomerCodePattern {
+    /// Parse and validate a pattern
+    pub fn parse(pattern: &str) -> Result<Self, &'static str> {
+        if pattern.chars().count() > MAX_PATTERN_LENGTH {
+            return Err("Pattern must be at most 40 characters");
+        }
+
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = pattern;
+        while let Some(c) = rest.chars().next() {
+            if c == '{' {
+                let end = rest.find('}').ok_or("Unclosed placeholder in pattern")?;
+                if !literal.is_empty() {
+                    parts.push(Part::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(parse_placeholder(&rest[1..end])?);
+                rest = &rest[end + 1..];
+                continue;
+            }
+            if c == '}' {
+                return Err("Unexpected '}' in pattern");
+            }
+            if !(c.is_alphanumeric() || matches!(c, '-' | '_' | '/' | '.')) {
+                return Err("Pattern may only contain letters, digits and - _ / .");
+            }
+            literal.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        match parts.iter().filter(|p| matches!(p, Part::Sequence(_))).count() {
+            0 => Err("Pattern must contain {SEQ}"),
+            1 => Ok(Self { parts }),
+            _ => Err("Pattern may contain {SEQ} only once"),
+        }
+    }
+
+    /// Whether the sequence restarts every year
+    pub fn has_year(&self) -> bool {
+        self.parts.iter().any(|p| matches!(p, Part::Year4 | Part::Year2))
+    }
+
+    /// Counter period of a code created in `year`
+    pub fn period_year(&self, year: i32) -> i32 {
+        if self.has_year() {
+            year
+        } else {
+            CUSTOMER_CODE_PERIOD_NONE
+        }
+    }
+
+    /// Render the code of a sequence number
+    pub fn format(&self, year: i32, sequence: i32) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Literal(text) => text.clone(),
+                Part::Year4 => format!("{:04}", year),
+                Part::Year2 => format!("{:02}", year.rem_euclid(100)),
+                Part::Sequence(width) => format!("{:0width$}", sequence, width = *width),
+            })
+            .collect()
+    }
+}
+
+fn parse_placeholder(token: &str) -> Result<Part, &'static str> {
+    match token {
+        "YYYY" => Ok(Part::Year4),
+        "YY" => Ok(Part::Year2),
+        "SEQ" => Ok(Part::Sequence(1)),
+        _ => {
+            let width = token
+                .strip_prefix("SEQ:")
+                .ok_or("Unknown placeholder; use {YYYY}, {YY} or {SEQ:n}")?
+                .parse::<usize>()
+                .map_err(|_| "Sequence padding must be a number")?;
+            if !(1..=MAX_SEQ_PADDING).contains(&width) {
+                return Err("Sequence padding must be between 1 and 10 digits");
+            }
+            Ok(Part::Sequence(width))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_pattern() {
+        let pattern = CustomerCodePattern::parse(DEFAULT_CUSTOMER_CODE_PATTERN).unwrap();
+        assert!(pattern.has_year());
+        assert_eq!(pattern.period_year(2025), 2025);
+        assert_eq!(pattern.format(2025, 1), "CUS-2025-0001");
+        assert_eq!(pattern.format(2025, 12345), "CUS-2025-12345");
+    }
+
+    #[test]
+    fn test_pattern_without_year() {
+        let pattern = CustomerCodePattern::parse("Z{SEQ:6}").unwrap();
+        assert!(!pattern.has_year());
+        assert_eq!(pattern.period_year(2025), CUSTOMER_CODE_PERIOD_NONE);
+        assert_eq!(pattern.format(2025, 42), "Z000042");
+    }
+
+    #[test]
+    fn test_short_year_and_plain_seq() {
+        let pattern = CustomerCodePattern::parse("{YY}/{SEQ}").unwrap();
+        assert_eq!(pattern.format(2007, 3), "07/3");
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(CustomerCodePattern::parse("CUS-{YYYY}").is_err());
+        assert!(CustomerCodePattern::parse("{SEQ}-{SEQ}").is_err());
+        assert!(CustomerCodePattern::parse("{SEQ:0}").is_err());
+        assert!(CustomerCodePattern::parse("{SEQ:11}").is_err());
+        assert!(CustomerCodePattern::parse("{MM}{SEQ}").is_err());
+        assert!(CustomerCodePattern::parse("{SEQ").is_err());
+        assert!(CustomerCodePattern::parse("A B{SEQ}").is_err());
+        assert!(CustomerCodePattern::parse(&format!("{}{{SEQ}}", "X".repeat(40))).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+#![allow(dead_code)]
+//! Customer hierarchy types (parent company and its branches)
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::customer::Customer;
+
+/// Hierarchy levels accepted by the customer list filter
+pub const HIERARCHY_LEVEL_PARENT: &str = "parent";
+pub const HIERARCHY_LEVEL_BRANCH: &str = "branch";
+pub const HIERARCHY_LEVEL_STANDALONE: &str = "standalone";
+/// Parents and standalone customers, i.e. everything that is not a branch
+pub const HIERARCHY_LEVEL_TOP: &str = "top";
+
+/// Request to attach a customer to a parent company, or detach it (null)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetCustomerParentRequest {
+    pub customer_id: Uuid,
+    pub parent_customer_id: Option<Uuid>,
+}
+
+/// Request for the group of a customer (any member may be given)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerHierarchyRequest {
+    pub customer_id: Uuid,
+}
+
+/// Branch row of a parent company
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerBranch {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub device_count: i64,
+}
+
+/// Parent company with its branches
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerHierarchyResponse {
+    pub parent: Customer,
+    pub branches: Vec<CustomerBranch>,
+}
+
+/// Figures of one group member for consolidated reporting
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct HierarchyMemberSummary {
+    pub customer_id: Uuid,
+    pub name: Option<String>,
+    pub city: Option<String>,
+    pub is_parent: bool,
+    pub device_count: i64,
+    pub revisions_overdue: i64,
+    pub revisions_due_30d: i64,
+    pub revisions_scheduled: i64,
+    pub visits_completed_12m: i64,
+}
+
+/// Group totals over all members
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HierarchyTotals {
+    pub branch_count: i64,
+    pub device_count: i64,
+    pub revisions_overdue: i64,
+    pub revisions_due_30d: i64,
+    pub revisions_scheduled: i64,
+    pub visits_completed_12m: i64,
+}
+
+impl HierarchyTotals {
+    /// Sum member figures; the parent is not counted as a branch
+    pub fn from_members(members: &[HierarchyMemberSummary]) -> Self {
+        members.iter().fold(Self::default(), |mut totals, m| {
+            if !m.is_parent {
+                totals.branch_count += 1;
+            }
+            totals.device_count += m.device_count;
+            totals.revisions_overdue += m.revisions_overdue;
+            totals.revisions_due_30d += m.revisions_due_30d;
+            totals.revisions_scheduled += m.revisions_scheduled;
+            totals.visits_completed_12m += m.visits_completed_12m;
+            totals
+        })
+    }
+}
+
+/// Consolidated report of a parent company and its branches
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HierarchySummaryResponse {
+    pub parent_customer_id: Uuid,
+    pub members: Vec<HierarchyMemberSummary>,
+    pub totals: HierarchyTotals,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(is_parent: bool, overdue: i64) -> HierarchyMemberSummary {
+        HierarchyMemberSummary {
+            customer_id: Uuid::new_v4(),
+            is_parent,
+            device_count: 2,
+            revisions_overdue: overdue,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_totals_sum_members_and_count_branches() {
+        let totals = HierarchyTotals::from_members(&[member(true, 1), member(false, 0), member(false, 3)]);
+        assert_eq!(totals.branch_count, 2);
+        assert_eq!(totals.device_count, 6);
+        assert_eq!(totals.revisions_overdue, 4);
+    }
+
+    #[test]
+    fn test_set_parent_request_accepts_null_to_detach() {
+        let req: SetCustomerParentRequest = serde_json::from_str(
+            r#"{"customerId":"00000000-0000-0000-0000-000000000001","parentCustomerId":null}"#,
+        )
+        .unwrap();
+        assert!(req.parent_customer_id.is_none());
+    }
+}
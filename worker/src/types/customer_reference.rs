@@ -0,0 +1,212 @@
+#![allow(dead_code)]
+//! Customer reference audit types
+//!
+//! Lists every table that points at a customer, so merges can move the rows
+//! and deletes can tell what would be lost. Keep `CustomerReferenceKind::ALL`
+//! in sync with new `customer_id` columns.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// What deleting the customer does to the referencing rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceEffect {
+    /// Part of the customer record, removed with it
+    Owned,
+    /// History removed by ON DELETE CASCADE
+    Deleted,
+    /// Rows stay but lose data (e.g. routes lose their stops)
+    Changed,
+    /// Reference cleared by ON DELETE SET NULL
+    Unlinked,
+    /// No foreign key; rows would point at a missing customer
+    Orphaned,
+}
+
+/// A kind of row referencing a customer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CustomerReferenceKind {
+    Sites,
+    Devices,
+    Revisions,
+    Visits,
+    WorkItems,
+    RouteStops,
+    Routes,
+    Communications,
+    PlannedActions,
+    Tasks,
+    RescheduleRequests,
+    Notes,
+    Branches,
+    Escalations,
+}
+
+impl CustomerReferenceKind {
+    pub const ALL: [CustomerReferenceKind; 14] = [
+        CustomerReferenceKind::Sites,
+        CustomerReferenceKind::Devices,
+        CustomerReferenceKind::Revisions,
+        CustomerReferenceKind::Visits,
+        CustomerReferenceKind::WorkItems,
+        CustomerReferenceKind::RouteStops,
+        CustomerReferenceKind::Routes,
+        CustomerReferenceKind::Communications,
+        CustomerReferenceKind::PlannedActions,
+        CustomerReferenceKind::Tasks,
+        CustomerReferenceKind::RescheduleRequests,
+        CustomerReferenceKind::Notes,
+        CustomerReferenceKind::Branches,
+        CustomerReferenceKind::Escalations,
+    ];
+
+    /// Table holding the referencing rows
+    pub fn table(self) -> &'static str {
+        match self {
+            CustomerReferenceKind::Sites => "customer_sites",
+            CustomerReferenceKind::Devices => "devices",
+            CustomerReferenceKind::Revisions => "revisions",
+            CustomerReferenceKind::Visits => "visits",
+            CustomerReferenceKind::WorkItems => "visit_work_items",
+            CustomerReferenceKind::RouteStops => "route_stops",
+            CustomerReferenceKind::Routes => "routes",
+            CustomerReferenceKind::Communications => "communications",
+            CustomerReferenceKind::PlannedActions => "planned_actions",
+            CustomerReferenceKind::Tasks => "tasks",
+            CustomerReferenceKind::RescheduleRequests => "reschedule_requests",
+            CustomerReferenceKind::Notes => "notes",
+            CustomerReferenceKind::Branches => "customers",
+            CustomerReferenceKind::Escalations => "escalation_log",
+        }
+    }
+
+    pub fn effect(self) -> ReferenceEffect {
+        match self {
+            CustomerReferenceKind::Sites => ReferenceEffect::Owned,
+            CustomerReferenceKind::Routes => ReferenceEffect::Changed,
+            CustomerReferenceKind::Notes => ReferenceEffect::Orphaned,
+            CustomerReferenceKind::Branches | CustomerReferenceKind::Escalations => ReferenceEffect::Unlinked,
+            _ => ReferenceEffect::Deleted,
+        }
+    }
+
+    /// Count query; `$1` is the customer id
+    pub fn count_sql(self) -> &'static str {
+        match self {
+            CustomerReferenceKind::Sites => "SELECT COUNT(*) FROM customer_sites WHERE customer_id = $1",
+            CustomerReferenceKind::Devices => "SELECT COUNT(*) FROM devices WHERE customer_id = $1",
+            CustomerReferenceKind::Revisions => "SELECT COUNT(*) FROM revisions WHERE customer_id = $1",
+            CustomerReferenceKind::Visits => "SELECT COUNT(*) FROM visits WHERE customer_id = $1",
+            CustomerReferenceKind::WorkItems => {
+                "SELECT COUNT(*) FROM visit_work_items w JOIN visits v ON v.id = w.visit_id WHERE v.customer_id = $1"
+            }
+            CustomerReferenceKind::RouteStops => "SELECT COUNT(*) FROM route_stops WHERE customer_id = $1",
+            CustomerReferenceKind::Routes => {
+                "SELECT COUNT(*) FROM routes r \
+                 WHERE EXISTS (SELECT 1 FROM route_stops s WHERE s.route_id = r.id AND s.customer_id = $1)"
+            }
+            CustomerReferenceKind::Communications => "SELECT COUNT(*) FROM communications WHERE customer_id = $1",
+            CustomerReferenceKind::PlannedActions => "SELECT COUNT(*) FROM planned_actions WHERE customer_id = $1",
+            CustomerReferenceKind::Tasks => "SELECT COUNT(*) FROM tasks WHERE customer_id = $1",
+            CustomerReferenceKind::RescheduleRequests => {
+                "SELECT COUNT(*) FROM reschedule_requests WHERE customer_id = $1"
+            }
+            CustomerReferenceKind::Notes => {
+                "SELECT COUNT(*) FROM notes WHERE entity_type = 'customer' AND entity_id = $1 AND deleted_at IS NULL"
+            }
+            CustomerReferenceKind::Branches => "SELECT COUNT(*) FROM customers WHERE parent_customer_id = $1",
+            CustomerReferenceKind::Escalations => "SELECT COUNT(*) FROM escalation_log WHERE customer_id = $1",
+        }
+    }
+}
+
+/// Request for sazinka.admin.customer.references
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerReferencesRequest {
+    pub customer_id: Uuid,
+}
+
+/// Rows of one kind referencing the customer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerReference {
+    pub kind: CustomerReferenceKind,
+    pub table: String,
+    pub count: i64,
+    pub effect: ReferenceEffect,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerReferenceReport {
+    pub customer_id: Uuid,
+    pub user_id: Uuid,
+    pub customer_name: Option<String>,
+    /// Kinds with at least one row
+    pub references: Vec<CustomerReference>,
+    pub total: i64,
+    /// Deleting would lose, change or orphan no other data
+    pub safe_to_delete: bool,
+}
+
+impl CustomerReferenceReport {
+    pub fn new(customer_id: Uuid, user_id: Uuid, customer_name: Option<String>, counts: Vec<(CustomerReferenceKind, i64)>) -> Self {
+        let references: Vec<CustomerReference> = counts
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(kind, count)| CustomerReference {
+                kind,
+                table: kind.table().to_string(),
+                count,
+                effect: kind.effect(),
+            })
+            .collect();
+        let total = references.iter().map(|r| r.count).sum();
+        let safe_to_delete = references
+            .iter()
+            .all(|r| matches!(r.effect, ReferenceEffect::Owned | ReferenceEffect::Unlinked));
+
+        Self {
+            customer_id,
+            user_id,
+            customer_name,
+            references,
+            total,
+            safe_to_delete,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_queries_target_their_table() {
+        for kind in CustomerReferenceKind::ALL {
+            assert!(kind.count_sql().contains(kind.table()), "{:?}", kind);
+            assert!(kind.count_sql().contains("$1"), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn test_report_safety() {
+        let id = Uuid::nil();
+        let only_sites = CustomerReferenceReport::new(
+            id,
+            id,
+            None,
+            vec![(CustomerReferenceKind::Sites, 1), (CustomerReferenceKind::Devices, 0), (CustomerReferenceKind::Branches, 2)],
+        );
+        assert!(only_sites.safe_to_delete);
+        assert_eq!(only_sites.references.len(), 2);
+        assert_eq!(only_sites.total, 3);
+
+        let with_history = CustomerReferenceReport::new(id, id, None, vec![(CustomerReferenceKind::Visits, 4)]);
+        assert!(!with_history.safe_to_delete);
+        assert_eq!(with_history.references[0].effect, ReferenceEffect::Deleted);
+    }
+}
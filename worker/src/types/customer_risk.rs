@@ -0,0 +1,272 @@
+#![allow(dead_code)]
+//! Customer risk scoring types
+//!
+//! The score (0-100) adds the account's weight per no-show and per recorded
+//! incident within the lookback window. Customers at or above the flag
+//! threshold are flagged; route stops carry only the flag.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+pub const RISK_INCIDENT_LATE_PAYMENT: &str = "late_payment";
+pub const RISK_INCIDENT_UNPAID: &str = "unpaid";
+pub const RISK_INCIDENT_DISPUTE: &str = "dispute";
+/// Crew could not get to the device (locked, refused entry)
+pub const RISK_INCIDENT_ACCESS_DENIED: &str = "access_denied";
+
+pub const RISK_INCIDENT_KINDS: &[&str] = &[
+    RISK_INCIDENT_LATE_PAYMENT,
+    RISK_INCIDENT_UNPAID,
+    RISK_INCIDENT_DISPUTE,
+    RISK_INCIDENT_ACCESS_DENIED,
+];
+
+/// Why a score was recomputed (audit log)
+pub const RISK_REASON_INCIDENT_ADDED: &str = "incident_added";
+pub const RISK_REASON_INCIDENT_REMOVED: &str = "incident_removed";
+pub const RISK_REASON_VISIT_COMPLETED: &str = "visit_completed";
+pub const RISK_REASON_SETTINGS_CHANGED: &str = "settings_changed";
+/// Occurrences left the lookback window (daily sweep)
+pub const RISK_REASON_AGED_OUT: &str = "aged_out";
+
+pub const MAX_RISK_SCORE: i32 = 100;
+pub const MAX_RISK_WEIGHT: i32 = 100;
+pub const MAX_RISK_LOOKBACK_MONTHS: i32 = 120;
+pub const MAX_RISK_NOTE_LEN: usize = 2000;
+
+/// Scoring weights of an account (per occurrence)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerRiskSettings {
+    pub no_show_weight: i32,
+    pub late_payment_weight: i32,
+    pub unpaid_weight: i32,
+    pub dispute_weight: i32,
+    pub access_denied_weight: i32,
+    pub lookback_months: i32,
+    pub flag_threshold: i32,
+}
+
+impl Default for CustomerRiskSettings {
+    /// Same as the column defaults of `customer_risk_settings`
+    fn default() -> Self {
+        Self {
+            no_show_weight: 15,
+            late_payment_weight: 10,
+            unpaid_weight: 30,
+            dispute_weight: 20,
+            access_denied_weight: 10,
+            lookback_months: 24,
+            flag_threshold: 40,
+        }
+    }
+}
+
+impl CustomerRiskSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [
+            ("noShowWeight", self.no_show_weight),
+            ("latePaymentWeight", self.late_payment_weight),
+            ("unpaidWeight", self.unpaid_weight),
+            ("disputeWeight", self.dispute_weight),
+            ("accessDeniedWeight", self.access_denied_weight),
+        ];
+        for (name, weight) in weights {
+            if !(0..=MAX_RISK_WEIGHT).contains(&weight) {
+                return Err(format!("{} must be between 0 and {}", name, MAX_RISK_WEIGHT));
+            }
+        }
+        if !(1..=MAX_RISK_LOOKBACK_MONTHS).contains(&self.lookback_months) {
+            return Err(format!("lookbackMonths must be between 1 and {}", MAX_RISK_LOOKBACK_MONTHS));
+        }
+        if !(1..=MAX_RISK_SCORE).contains(&self.flag_threshold) {
+            return Err(format!("flagThreshold must be between 1 and {}", MAX_RISK_SCORE));
+        }
+        Ok(())
+    }
+
+    /// Score for the occurrences within the lookback window
+    pub fn score(&self, counts: &RiskCounts) -> i32 {
+        let total = counts.no_shows * self.no_show_weight as i64
+            + counts.late_payments * self.late_payment_weight as i64
+            + counts.unpaid * self.unpaid_weight as i64
+            + counts.disputes * self.dispute_weight as i64
+            + counts.access_denied * self.access_denied_weight as i64;
+        total.clamp(0, MAX_RISK_SCORE as i64) as i32
+    }
+
+    pub fn is_flagged(&self, score: i32) -> bool {
+        score >= self.flag_threshold
+    }
+}
+
+/// Occurrences of a customer within the lookback window
+#[derive(Debug, Clone, Default, PartialEq, FromRow)]
+pub struct RiskCounts {
+    pub no_shows: i64,
+    pub late_payments: i64,
+    pub unpaid: i64,
+    pub disputes: i64,
+    pub access_denied: i64,
+}
+
+/// Recorded payment or access incident
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerRiskIncident {
+    pub id: Uuid,
+    pub customer_id: Uuid,
+    pub kind: String,
+    pub note: Option<String>,
+    pub occurred_on: NaiveDate,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Audit log entry of a score change
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerRiskScoreChange {
+    pub id: Uuid,
+    pub customer_id: Uuid,
+    pub customer_name: String,
+    pub old_score: i32,
+    pub new_score: i32,
+    pub flagged: bool,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Score of a customer after a recompute
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerRiskScore {
+    pub risk_score: i32,
+    pub risk_flag: bool,
+}
+
+// ============================================================
+// Request / response types (NATS payloads)
+// ============================================================
+
+/// NATS: sazinka.customer.risk.incident.create
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRiskIncidentRequest {
+    pub customer_id: Uuid,
+    pub kind: String,
+    pub note: Option<String>,
+    /// Defaults to today
+    pub occurred_on: Option<NaiveDate>,
+}
+
+impl CreateRiskIncidentRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if !RISK_INCIDENT_KINDS.contains(&self.kind.as_str()) {
+            return Err(format!("kind must be one of: {}", RISK_INCIDENT_KINDS.join(", ")));
+        }
+        if self.note.as_ref().is_some_and(|note| note.len() > MAX_RISK_NOTE_LEN) {
+            return Err(format!("note must have at most {} characters", MAX_RISK_NOTE_LEN));
+        }
+        if self.occurred_on.is_some_and(|date| date > Utc::now().date_naive()) {
+            return Err("occurredOn cannot be in the future".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// NATS: sazinka.customer.risk.incident.list
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListRiskIncidentsRequest {
+    pub customer_id: Uuid,
+}
+
+/// NATS: sazinka.customer.risk.incident.delete
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskIncidentIdRequest {
+    pub id: Uuid,
+}
+
+/// NATS: sazinka.customer.risk.history
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListRiskScoreChangesRequest {
+    pub customer_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// Response for sazinka.customer.risk.incident.create / delete
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskIncidentResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub incident: Option<CustomerRiskIncident>,
+    #[serde(flatten)]
+    pub score: CustomerRiskScore,
+}
+
+/// Response for sazinka.customer.risk.incident.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListRiskIncidentsResponse {
+    pub incidents: Vec<CustomerRiskIncident>,
+}
+
+/// Response for sazinka.customer.risk.history
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListRiskScoreChangesResponse {
+    pub changes: Vec<CustomerRiskScoreChange>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_adds_weights_and_clamps() {
+        let settings = CustomerRiskSettings::default();
+        assert_eq!(settings.score(&RiskCounts::default()), 0);
+
+        let counts = RiskCounts { no_shows: 1, late_payments: 2, ..Default::default() };
+        assert_eq!(settings.score(&counts), 35);
+        assert!(!settings.is_flagged(35));
+
+        let counts = RiskCounts { unpaid: 1, access_denied: 1, ..Default::default() };
+        assert_eq!(settings.score(&counts), 40);
+        assert!(settings.is_flagged(40));
+
+        let counts = RiskCounts { unpaid: 3, disputes: 2, ..Default::default() };
+        assert_eq!(settings.score(&counts), MAX_RISK_SCORE);
+
+        let ignore_no_shows = CustomerRiskSettings { no_show_weight: 0, ..Default::default() };
+        assert_eq!(ignore_no_shows.score(&RiskCounts { no_shows: 5, ..Default::default() }), 0);
+    }
+
+    #[test]
+    fn test_settings_validate() {
+        assert!(CustomerRiskSettings::default().validate().is_ok());
+        assert!(CustomerRiskSettings { dispute_weight: -1, ..Default::default() }.validate().is_err());
+        assert!(CustomerRiskSettings { unpaid_weight: 101, ..Default::default() }.validate().is_err());
+        assert!(CustomerRiskSettings { lookback_months: 0, ..Default::default() }.validate().is_err());
+        assert!(CustomerRiskSettings { flag_threshold: 0, ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_incident_validate() {
+        let request = |kind: &str, occurred_on: Option<NaiveDate>| CreateRiskIncidentRequest {
+            customer_id: Uuid::new_v4(),
+            kind: kind.to_string(),
+            note: None,
+            occurred_on,
+        };
+        assert!(request("unpaid", None).validate().is_ok());
+        assert!(request("late_payment", NaiveDate::from_ymd_opt(2025, 1, 31)).validate().is_ok());
+        assert!(request("rude", None).validate().is_err());
+        let tomorrow = Utc::now().date_naive().succ_opt();
+        assert!(request("dispute", tomorrow).validate().is_err());
+    }
+}
@@ -0,0 +1,282 @@
+#![allow(dead_code)]
+//! Customer address types (billing, service and mailing sites)
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::customer::Coordinates;
+
+/// Where a service location came from
+pub const LOCATION_SOURCE_DEVICE: &str = "device";
+pub const LOCATION_SOURCE_SITE: &str = "site";
+pub const LOCATION_SOURCE_SERVICE_ADDRESS: &str = "serviceAddress";
+
+/// Address types
+pub const ADDRESS_TYPE_BILLING: &str = "billing";
+pub const ADDRESS_TYPE_SERVICE: &str = "service";
+pub const ADDRESS_TYPE_MAILING: &str = "mailing";
+pub const ADDRESS_TYPES: &[&str] = &[ADDRESS_TYPE_BILLING, ADDRESS_TYPE_SERVICE, ADDRESS_TYPE_MAILING];
+
+/// An address of a customer. The primary billing address mirrors the
+/// address columns of the customer and is edited through the customer.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerSite {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub customer_id: Uuid,
+    pub name: String,
+    pub address_type: String,
+    pub is_primary: bool,
+    pub geocode_status: String,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CustomerSite {
+    /// Whether the address is kept in step with the customer row
+    pub fn is_customer_billing(&self) -> bool {
+        is_customer_billing(&self.address_type, self.is_primary)
+    }
+}
+
+/// The primary billing address belongs to the customer row
+pub fn is_customer_billing(address_type: &str, is_primary: bool) -> bool {
+    is_primary && address_type == ADDRESS_TYPE_BILLING
+}
+
+/// Request to create a site
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCustomerSiteRequest {
+    pub customer_id: Uuid,
+    pub name: String,
+    /// billing, service (default) or mailing
+    pub address_type: Option<String>,
+    pub is_primary: Option<bool>,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub notes: Option<String>,
+}
+
+/// Request to update a site
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCustomerSiteRequest {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub address_type: Option<String>,
+    pub is_primary: Option<bool>,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub notes: Option<String>,
+}
+
+/// Request to list the sites of a customer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCustomerSitesRequest {
+    pub customer_id: Uuid,
+}
+
+/// Request to delete a site
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerSiteIdRequest {
+    pub id: Uuid,
+}
+
+/// Candidate coordinates for a service stop, most specific first
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct ServiceLocationRow {
+    pub device_lat: Option<f64>,
+    pub device_lng: Option<f64>,
+    pub site_lat: Option<f64>,
+    pub site_lng: Option<f64>,
+    pub site_street: Option<String>,
+    pub site_city: Option<String>,
+    pub site_postal_code: Option<String>,
+}
+
+/// Resolved location of a service stop
+#[derive(Debug, Clone)]
+pub struct ServiceLocation {
+    pub coordinates: Coordinates,
+    pub source: &'static str,
+    /// Site address when the stop is at a site
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+}
+
+impl ServiceLocation {
+    /// Whether the site address should replace the customer's on the stop
+    pub fn has_address(&self) -> bool {
+        self.street.is_some() || self.city.is_some()
+    }
+}
+
+/// Both coordinates, if set and within range
+fn coordinates(lat: Option<f64>, lng: Option<f64>) -> Option<Coordinates> {
+    let (lat, lng) = (lat?, lng?);
+    validate_coordinates(lat, lng).ok()?;
+    Some(Coordinates { lat, lng })
+}
+
+/// Reject coordinates outside the valid range
+pub fn validate_coordinates(lat: f64, lng: f64) -> Result<(), &'static str> {
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+        return Err("Coordinates out of range");
+    }
+    Ok(())
+}
+
+/// Reject unknown address types
+pub fn validate_address_type(address_type: &str) -> Result<(), &'static str> {
+    if !ADDRESS_TYPES.contains(&address_type) {
+        return Err("Address type must be billing, service or mailing");
+    }
+    Ok(())
+}
+
+/// Optional coordinates must be set together and within range
+pub fn validate_optional_coordinates(lat: Option<f64>, lng: Option<f64>) -> Result<(), &'static str> {
+    match (lat, lng) {
+        (Some(lat), Some(lng)) => validate_coordinates(lat, lng),
+        (None, None) => Ok(()),
+        _ => Err("Latitude and longitude must be set together"),
+    }
+}
+
+/// Pick the stop location: device GPS override, then the device's site.
+/// None means the customer's own address applies.
+pub fn resolve_service_location(row: &ServiceLocationRow) -> Option<ServiceLocation> {
+    if let Some(coordinates) = coordinates(row.device_lat, row.device_lng) {
+        return Some(ServiceLocation {
+            coordinates,
+            source: LOCATION_SOURCE_DEVICE,
+            street: row.site_street.clone(),
+            city: row.site_city.clone(),
+            postal_code: row.site_postal_code.clone(),
+        });
+    }
+    coordinates(row.site_lat, row.site_lng).map(|coordinates| ServiceLocation {
+        coordinates,
+        source: LOCATION_SOURCE_SITE,
+        street: row.site_street.clone(),
+        city: row.site_city.clone(),
+        postal_code: row.site_postal_code.clone(),
+    })
+}
+
+/// Location of the customer's primary service address, if it is geocoded
+pub fn service_address_location(site: &CustomerSite) -> Option<ServiceLocation> {
+    coordinates(site.lat, site.lng).map(|coordinates| ServiceLocation {
+        coordinates,
+        source: LOCATION_SOURCE_SERVICE_ADDRESS,
+        street: site.street.clone(),
+        city: site.city.clone(),
+        postal_code: site.postal_code.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site_row() -> ServiceLocationRow {
+        ServiceLocationRow {
+            site_lat: Some(49.2),
+            site_lng: Some(16.6),
+            site_street: Some("Hala B".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_device_override_wins() {
+        let row = ServiceLocationRow { device_lat: Some(49.21), device_lng: Some(16.61), ..site_row() };
+        let location = resolve_service_location(&row).unwrap();
+        assert_eq!(location.source, LOCATION_SOURCE_DEVICE);
+        assert_eq!(location.coordinates.lat, 49.21);
+        // The site address still describes where the device is
+        assert_eq!(location.street.as_deref(), Some("Hala B"));
+    }
+
+    #[test]
+    fn test_site_used_without_override() {
+        let location = resolve_service_location(&site_row()).unwrap();
+        assert_eq!(location.source, LOCATION_SOURCE_SITE);
+        assert_eq!(location.coordinates.lng, 16.6);
+    }
+
+    #[test]
+    fn test_half_set_override_is_ignored() {
+        let row = ServiceLocationRow { device_lat: Some(49.21), ..site_row() };
+        assert_eq!(resolve_service_location(&row).unwrap().source, LOCATION_SOURCE_SITE);
+    }
+
+    #[test]
+    fn test_no_location_falls_back_to_customer() {
+        assert!(resolve_service_location(&ServiceLocationRow::default()).is_none());
+    }
+
+    #[test]
+    fn test_service_address_requires_coordinates() {
+        let mut site: CustomerSite = serde_json::from_value(serde_json::json!({
+            "id": Uuid::nil(), "userId": Uuid::nil(), "customerId": Uuid::nil(),
+            "name": "Provozovna", "addressType": "service", "isPrimary": true,
+            "geocodeStatus": "pending", "street": "Dlouhá 5", "city": "Brno",
+            "createdAt": "2026-01-01T00:00:00Z", "updatedAt": "2026-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        assert!(service_address_location(&site).is_none());
+
+        site.lat = Some(49.19);
+        site.lng = Some(16.6);
+        let location = service_address_location(&site).unwrap();
+        assert_eq!(location.source, LOCATION_SOURCE_SERVICE_ADDRESS);
+        assert_eq!(location.city.as_deref(), Some("Brno"));
+        assert!(!site.is_customer_billing());
+    }
+
+    #[test]
+    fn test_address_types() {
+        assert!(validate_address_type("mailing").is_ok());
+        assert!(validate_address_type("delivery").is_err());
+        assert!(is_customer_billing(ADDRESS_TYPE_BILLING, true));
+        assert!(!is_customer_billing(ADDRESS_TYPE_BILLING, false));
+    }
+
+    #[test]
+    fn test_validate_optional_coordinates_requires_pair() {
+        assert!(validate_optional_coordinates(None, None).is_ok());
+        assert!(validate_optional_coordinates(Some(49.2), Some(16.6)).is_ok());
+        assert!(validate_optional_coordinates(Some(49.2), None).is_err());
+        assert!(validate_optional_coordinates(None, Some(200.0)).is_err());
+    }
+
+    #[test]
+    fn test_validate_coordinates_range() {
+        assert!(validate_coordinates(50.08, 14.43).is_ok());
+        assert!(validate_coordinates(91.0, 14.43).is_err());
+        assert!(validate_coordinates(50.08, -181.0).is_err());
+    }
+}
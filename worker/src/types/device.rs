@@ -22,6 +22,14 @@ pub struct Device {
     pub revision_interval_months: i32,
     pub next_due_date: Option<NaiveDate>,
     pub notes: Option<String>,
+    /// Service site of the customer; None = the customer's own address
+    pub site_id: Option<Uuid>,
+    pub building: Option<String>,
+    pub floor: Option<String>,
+    pub room: Option<String>,
+    /// GPS override for devices the site coordinates don't pin down
+    pub location_lat: Option<f64>,
+    pub location_lng: Option<f64>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -66,6 +74,12 @@ pub struct CreateDeviceRequest {
     #[serde(default = "default_revision_interval")]
     pub revision_interval_months: i32,
     pub notes: Option<String>,
+    pub site_id: Option<Uuid>,
+    pub building: Option<String>,
+    pub floor: Option<String>,
+    pub room: Option<String>,
+    pub location_lat: Option<f64>,
+    pub location_lng: Option<f64>,
 }
 
 fn default_revision_interval() -> i32 {
@@ -85,6 +99,12 @@ pub struct UpdateDeviceRequest {
     pub installation_date: Option<NaiveDate>,
     pub revision_interval_months: Option<i32>,
     pub notes: Option<String>,
+    pub site_id: Option<Uuid>,
+    pub building: Option<String>,
+    pub floor: Option<String>,
+    pub room: Option<String>,
+    pub location_lat: Option<f64>,
+    pub location_lng: Option<f64>,
 }
 
 /// Request to list devices
@@ -101,6 +121,111 @@ pub struct DeviceIdRequest {
     pub id: Uuid,
 }
 
+/// Reply of sazinka.device.qr: the sticker of a device
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceCodeResponse {
+    pub device_id: Uuid,
+    /// Signed code, also accepted on its own by lookup_by_code
+    pub code: String,
+    /// URL encoded in the QR code
+    pub url: String,
+    pub svg: String,
+}
+
+/// NATS: sazinka.device.lookup_by_code - a scanned sticker (URL or code)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LookupDeviceByCodeRequest {
+    pub code: String,
+}
+
+/// Reply of sazinka.device.lookup_by_code
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceLookupResponse {
+    pub device: Device,
+    pub customer_name: Option<String>,
+    pub customer_street: Option<String>,
+    pub customer_city: Option<String>,
+    pub customer_postal_code: Option<String>,
+    /// Revisions of the device, newest due date first
+    pub revisions: Vec<crate::types::revision::Revision>,
+}
+
+/// Longest note on a device transfer
+pub const MAX_TRANSFER_NOTE_LENGTH: usize = 1000;
+
+/// NATS: sazinka.device.transfer - move a device to another customer
+/// (e.g. after a property sale). Completed and cancelled revisions stay with
+/// the original owner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferDeviceRequest {
+    pub device_id: Uuid,
+    pub to_customer_id: Uuid,
+    /// Move upcoming, scheduled and confirmed revisions (and their open
+    /// planned actions) along with the device
+    #[serde(default = "default_move_open_revisions")]
+    pub move_open_revisions: bool,
+    /// Service site of the new owner; None = the new owner's own address
+    pub site_id: Option<Uuid>,
+    pub note: Option<String>,
+}
+
+fn default_move_open_revisions() -> bool {
+    true
+}
+
+impl TransferDeviceRequest {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.note.as_deref().is_some_and(|n| n.chars().count() > MAX_TRANSFER_NOTE_LENGTH) {
+            return Err("Note is too long");
+        }
+        Ok(())
+    }
+}
+
+/// Audit record of a device changing owner
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceTransfer {
+    pub id: Uuid,
+    pub device_id: Uuid,
+    pub from_customer_id: Option<Uuid>,
+    pub to_customer_id: Option<Uuid>,
+    /// Open revisions that moved with the device
+    pub moved_revision_ids: Vec<Uuid>,
+    /// Revisions left with the original owner as history
+    pub kept_revision_count: i32,
+    pub note: Option<String>,
+    pub transferred_by: Option<Uuid>,
+    pub transferred_at: DateTime<Utc>,
+}
+
+/// Response of sazinka.device.transfer
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferDeviceResponse {
+    pub transfer: DeviceTransfer,
+    pub device: Device,
+    /// Moved revisions whose appointment was agreed with the previous owner
+    pub reschedule_needed: Vec<Uuid>,
+}
+
+/// NATS: sazinka.device.transfers - transfer history of a device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeviceTransfersRequest {
+    pub device_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeviceTransfersResponse {
+    pub transfers: Vec<DeviceTransfer>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,6 +245,12 @@ mod tests {
             revision_interval_months: 12,
             next_due_date: Some(NaiveDate::from_ymd_opt(2027, 1, 1).unwrap()),
             notes: None,
+            site_id: None,
+            building: None,
+            floor: None,
+            room: None,
+            location_lat: None,
+            location_lng: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -146,6 +277,12 @@ mod tests {
             revision_interval_months: 24,
             next_due_date: None,
             notes: None,
+            site_id: None,
+            building: None,
+            floor: None,
+            room: None,
+            location_lat: None,
+            location_lng: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -213,4 +350,29 @@ mod tests {
             assert_eq!(deserialized.as_str(), expected_str);
         }
     }
+
+    #[test]
+    fn test_transfer_request_moves_open_revisions_by_default() {
+        let json = r#"{
+            "deviceId": "123e4567-e89b-12d3-a456-426614174000",
+            "toCustomerId": "123e4567-e89b-12d3-a456-426614174001"
+        }"#;
+
+        let req: TransferDeviceRequest = serde_json::from_str(json).unwrap();
+        assert!(req.move_open_revisions);
+        assert!(req.site_id.is_none());
+        assert!(req.validate().is_ok());
+    }
+
+    #[test]
+    fn test_transfer_request_rejects_long_note() {
+        let req = TransferDeviceRequest {
+            device_id: Uuid::nil(),
+            to_customer_id: Uuid::nil(),
+            move_open_revisions: false,
+            site_id: None,
+            note: Some("x".repeat(MAX_TRANSFER_NOTE_LENGTH + 1)),
+        };
+        assert_eq!(req.validate(), Err("Note is too long"));
+    }
 }
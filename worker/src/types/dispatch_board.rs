@@ -0,0 +1,196 @@
+#![allow(dead_code)]
+//! Dispatch board feed types
+//!
+//! The office board loads a snapshot of a day and then applies deltas from
+//! the day's feed subject. A delta carries the whole new state of one route,
+//! so applying it is idempotent; its `version` tells a late delta from a
+//! newer snapshot (keep whichever version is higher).
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::VisitStatus;
+
+/// The route was created or changed; `route` is its new state
+pub const BOARD_DELTA_ROUTE: &str = "route";
+/// The route was deleted
+pub const BOARD_DELTA_ROUTE_REMOVED: &str = "route_removed";
+
+/// Route header as loaded from the database
+#[derive(Debug, Clone, FromRow)]
+pub struct BoardRouteRow {
+    pub id: Uuid,
+    pub date: NaiveDate,
+    pub crew_id: Option<Uuid>,
+    pub crew_name: Option<String>,
+    pub status: String,
+}
+
+/// One stop of a route with the progress of its visit
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardStop {
+    #[serde(skip)]
+    pub route_id: Uuid,
+    pub stop_id: Uuid,
+    pub stop_order: i32,
+    pub stop_type: String,
+    pub customer_id: Option<Uuid>,
+    pub customer_name: Option<String>,
+    pub address: Option<String>,
+    pub estimated_arrival: Option<NaiveTime>,
+    pub estimated_departure: Option<NaiveTime>,
+    pub visit_id: Option<Uuid>,
+    pub visit_status: Option<String>,
+    pub actual_arrival: Option<DateTime<Utc>>,
+    pub actual_departure: Option<DateTime<Utc>>,
+}
+
+/// A route on the board
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardRoute {
+    pub id: Uuid,
+    pub crew_id: Option<Uuid>,
+    pub crew_name: Option<String>,
+    pub status: String,
+    /// Customer stops and how many of them have a completed visit
+    pub customer_stops: usize,
+    pub completed_stops: usize,
+    /// Microseconds since the epoch when this state was read
+    pub version: i64,
+    pub stops: Vec<BoardStop>,
+}
+
+/// Attach the stops to their routes, keeping the order of both
+pub fn assemble_routes(rows: Vec<BoardRouteRow>, stops: Vec<BoardStop>, version: i64) -> Vec<BoardRoute> {
+    let mut by_route: HashMap<Uuid, Vec<BoardStop>> = HashMap::new();
+    for stop in stops {
+        by_route.entry(stop.route_id).or_default().push(stop);
+    }
+
+    rows.into_iter()
+        .map(|row| {
+            let stops = by_route.remove(&row.id).unwrap_or_default();
+            let customer_stops = stops.iter().filter(|s| s.customer_id.is_some()).count();
+            let completed_stops = stops
+                .iter()
+                .filter(|s| s.visit_status.as_deref() == Some(VisitStatus::Completed.as_str()))
+                .count();
+            BoardRoute {
+                id: row.id,
+                crew_id: row.crew_id,
+                crew_name: row.crew_name,
+                status: row.status,
+                customer_stops,
+                completed_stops,
+                version,
+                stops,
+            }
+        })
+        .collect()
+}
+
+/// NATS: sazinka.board.snapshot
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardSnapshotRequest {
+    pub date: NaiveDate,
+}
+
+/// Response for sazinka.board.snapshot
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardSnapshot {
+    pub date: NaiveDate,
+    /// Feed subject of the day; subscribe before requesting the snapshot
+    pub subject: String,
+    pub routes: Vec<BoardRoute>,
+}
+
+/// Message on the feed subject of a day
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardDelta {
+    pub date: NaiveDate,
+    /// `BOARD_DELTA_ROUTE` or `BOARD_DELTA_ROUTE_REMOVED`
+    pub kind: &'static str,
+    pub route_id: Uuid,
+    pub version: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<BoardRoute>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: Uuid) -> BoardRouteRow {
+        BoardRouteRow {
+            id,
+            date: NaiveDate::from_ymd_opt(2026, 5, 4).unwrap(),
+            crew_id: None,
+            crew_name: None,
+            status: "confirmed".to_string(),
+        }
+    }
+
+    fn stop(route_id: Uuid, order: i32, customer: bool, visit_status: Option<&str>) -> BoardStop {
+        BoardStop {
+            route_id,
+            stop_id: Uuid::new_v4(),
+            stop_order: order,
+            stop_type: if customer { "customer" } else { "break" }.to_string(),
+            customer_id: customer.then(Uuid::new_v4),
+            customer_name: None,
+            address: None,
+            estimated_arrival: None,
+            estimated_departure: None,
+            visit_id: None,
+            visit_status: visit_status.map(str::to_string),
+            actual_arrival: None,
+            actual_departure: None,
+        }
+    }
+
+    #[test]
+    fn test_assemble_routes_groups_stops_and_counts_progress() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let stops = vec![
+            stop(a, 1, true, Some("completed")),
+            stop(b, 1, true, Some("planned")),
+            stop(a, 2, false, None),
+            stop(a, 3, true, Some("in_progress")),
+        ];
+
+        let routes = assemble_routes(vec![row(a), row(b)], stops, 42);
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].id, a);
+        assert_eq!(routes[0].stops.iter().map(|s| s.stop_order).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!((routes[0].customer_stops, routes[0].completed_stops), (2, 1));
+        assert_eq!((routes[1].customer_stops, routes[1].completed_stops), (1, 0));
+        assert_eq!(routes[1].version, 42);
+
+        let empty = assemble_routes(vec![row(a)], Vec::new(), 1);
+        assert!(empty[0].stops.is_empty());
+    }
+
+    #[test]
+    fn test_delta_serialization() {
+        let delta = BoardDelta {
+            date: NaiveDate::from_ymd_opt(2026, 5, 4).unwrap(),
+            kind: BOARD_DELTA_ROUTE_REMOVED,
+            route_id: Uuid::nil(),
+            version: 7,
+            route: None,
+        };
+        let json = serde_json::to_value(&delta).unwrap();
+        assert_eq!(json["kind"], "route_removed");
+        assert_eq!(json["date"], "2026-05-04");
+        assert!(json.get("route").is_none());
+    }
+}
@@ -0,0 +1,154 @@
+#![allow(dead_code)]
+//! Deadline escalation types
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Revision still open more than `threshold_days` after its due date
+pub const ESCALATION_RULE_REVISION_OVERDUE: &str = "revision_overdue";
+/// Visit still planned more than `threshold_days` after its scheduled date
+pub const ESCALATION_RULE_VISIT_MISSED: &str = "visit_missed";
+pub const ESCALATION_RULE_TYPES: &[&str] = &[ESCALATION_RULE_REVISION_OVERDUE, ESCALATION_RULE_VISIT_MISSED];
+
+/// Upper bound for threshold_days (matches the DB constraint)
+pub const MAX_ESCALATION_THRESHOLD_DAYS: i32 = 365;
+
+/// Entity an escalation rule watches
+pub fn escalation_entity_type(rule_type: &str) -> &'static str {
+    if rule_type == ESCALATION_RULE_VISIT_MISSED {
+        "visit"
+    } else {
+        "revision"
+    }
+}
+
+/// Shared validation for create/update payloads
+pub fn validate_rule_fields(rule_type: Option<&str>, threshold_days: Option<i32>) -> Result<(), String> {
+    if let Some(rule_type) = rule_type {
+        if !ESCALATION_RULE_TYPES.contains(&rule_type) {
+            return Err(format!("ruleType must be one of: {}", ESCALATION_RULE_TYPES.join(", ")));
+        }
+    }
+    if let Some(days) = threshold_days {
+        if !(0..=MAX_ESCALATION_THRESHOLD_DAYS).contains(&days) {
+            return Err(format!("thresholdDays must be between 0 and {}", MAX_ESCALATION_THRESHOLD_DAYS));
+        }
+    }
+    Ok(())
+}
+
+/// Escalation rule of an account
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct EscalationRule {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub rule_type: String,
+    pub threshold_days: i32,
+    pub notify_email: bool,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Item newly escalated by a rule (logged, not yet notified)
+#[derive(Debug, Clone, FromRow)]
+pub struct EscalatedItem {
+    pub log_id: Uuid,
+    pub entity_id: Uuid,
+    pub customer_id: Option<Uuid>,
+    pub customer_name: Option<String>,
+    /// Due date of the revision or scheduled date of the visit
+    pub reference_date: NaiveDate,
+}
+
+/// Escalation log entry
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct EscalationLogEntry {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    pub rule_type: String,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub customer_id: Option<Uuid>,
+    pub customer_name: Option<String>,
+    pub notification_id: Option<Uuid>,
+    pub emailed_at: Option<DateTime<Utc>>,
+    pub email_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================
+// Request / response types (NATS payloads)
+// ============================================================
+
+/// NATS: sazinka.escalation.rule.create
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateEscalationRuleRequest {
+    pub rule_type: String,
+    pub threshold_days: i32,
+    pub notify_email: Option<bool>,
+    pub enabled: Option<bool>,
+}
+
+/// NATS: sazinka.escalation.rule.update
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateEscalationRuleRequest {
+    pub id: Uuid,
+    pub threshold_days: Option<i32>,
+    pub notify_email: Option<bool>,
+    pub enabled: Option<bool>,
+}
+
+/// NATS: sazinka.escalation.rule.delete
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EscalationRuleIdRequest {
+    pub id: Uuid,
+}
+
+/// NATS: sazinka.escalation.log.list
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListEscalationLogRequest {
+    pub rule_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// Response for sazinka.escalation.rule.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListEscalationRulesResponse {
+    pub rules: Vec<EscalationRule>,
+}
+
+/// Response for sazinka.escalation.log.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListEscalationLogResponse {
+    pub entries: Vec<EscalationLogEntry>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rule_fields() {
+        assert!(validate_rule_fields(Some(ESCALATION_RULE_REVISION_OVERDUE), Some(14)).is_ok());
+        assert!(validate_rule_fields(Some("customer_angry"), Some(1)).is_err());
+        assert!(validate_rule_fields(None, Some(-1)).is_err());
+        assert!(validate_rule_fields(None, Some(366)).is_err());
+    }
+
+    #[test]
+    fn test_entity_type_per_rule() {
+        assert_eq!(escalation_entity_type(ESCALATION_RULE_VISIT_MISSED), "visit");
+        assert_eq!(escalation_entity_type(ESCALATION_RULE_REVISION_OVERDUE), "revision");
+    }
+}
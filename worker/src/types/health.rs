@@ -0,0 +1,92 @@
+//! Worker health report types
+//!
+//! A report lists one check per dependency. Critical checks decide the
+//! probes: the worker is live while none of them is down and ready while
+//! all of them are ok. Valhalla and the geocoder have fallbacks (estimated
+//! travel times, the geocode queue retries), so their checks are reported
+//! but never fail a probe.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// State of one dependency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    /// Reachable but impaired (missing streams, open circuit breaker)
+    Degraded,
+    Down,
+    /// Not configured in this install
+    Skipped,
+}
+
+/// Result of checking one dependency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub critical: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub latency_ms: u64,
+}
+
+impl HealthCheck {
+    pub fn new(name: &str, critical: bool, status: CheckStatus, detail: Option<String>) -> Self {
+        Self { name: name.to_string(), status, critical, detail, latency_ms: 0 }
+    }
+}
+
+/// Response for sazinka.health
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    /// No critical check is down; restart the worker otherwise
+    pub live: bool,
+    /// All critical checks are ok; stop sending work otherwise
+    pub ready: bool,
+    pub checks: Vec<HealthCheck>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl HealthReport {
+    pub fn new(checks: Vec<HealthCheck>) -> Self {
+        let mut critical = checks.iter().filter(|c| c.critical);
+        let live = critical.clone().all(|c| c.status != CheckStatus::Down);
+        let ready = critical.all(|c| c.status == CheckStatus::Ok);
+        Self { live, ready, checks, checked_at: Utc::now() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(critical: bool, status: CheckStatus) -> HealthCheck {
+        HealthCheck::new("x", critical, status, None)
+    }
+
+    #[test]
+    fn test_report_probes_follow_critical_checks() {
+        let report = HealthReport::new(vec![check(true, CheckStatus::Ok), check(false, CheckStatus::Down)]);
+        assert!(report.live && report.ready);
+
+        let report = HealthReport::new(vec![check(true, CheckStatus::Degraded), check(true, CheckStatus::Ok)]);
+        assert!(report.live && !report.ready);
+
+        let report = HealthReport::new(vec![check(true, CheckStatus::Down), check(true, CheckStatus::Ok)]);
+        assert!(!report.live && !report.ready);
+    }
+
+    #[test]
+    fn test_report_serialization() {
+        let report = HealthReport::new(vec![HealthCheck::new("valhalla", false, CheckStatus::Skipped, None)]);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["checks"][0]["status"], "skipped");
+        assert_eq!(json["checks"][0]["latencyMs"], 0);
+        assert!(json["checks"][0].get("detail").is_none());
+        assert_eq!(json["ready"], true);
+    }
+}
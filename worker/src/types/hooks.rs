@@ -0,0 +1,207 @@
+#![allow(dead_code)]
+//! Extension hook types (external processes hooked into operations)
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+pub const BEFORE_ROUTE_SAVE: &str = "before.route.save";
+pub const AFTER_ROUTE_SAVE: &str = "after.route.save";
+pub const BEFORE_VISIT_COMPLETE: &str = "before.visit.complete";
+pub const AFTER_VISIT_COMPLETE: &str = "after.visit.complete";
+
+/// Operations a plugin can hook into. `before.*` hooks may veto or enrich
+/// the request, `after.*` hooks are notified of the result.
+pub const HOOK_POINTS: &[&str] = &[BEFORE_ROUTE_SAVE, AFTER_ROUTE_SAVE, BEFORE_VISIT_COMPLETE, AFTER_VISIT_COMPLETE];
+
+/// Answer time of a before hook when the plugin does not ask for another
+pub const DEFAULT_HOOK_TIMEOUT_MS: u64 = 1000;
+
+/// Longest answer time a plugin can ask for; the user is waiting
+pub const MAX_HOOK_TIMEOUT_MS: u64 = 5000;
+
+/// Longest plugin name accepted
+pub const MAX_PLUGIN_NAME_LEN: usize = 64;
+
+pub fn is_before_point(point: &str) -> bool {
+    point.starts_with("before.")
+}
+
+/// One hook of a plugin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookSpec {
+    /// One of `HOOK_POINTS`
+    pub point: String,
+    /// NATS subject the plugin listens on
+    pub subject: String,
+    /// Answer time of a before hook
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Veto the operation when the plugin does not answer in time
+    /// (by default it proceeds without the plugin)
+    #[serde(default)]
+    pub fail_closed: bool,
+}
+
+impl HookSpec {
+    pub fn validate(&self) -> Result<(), String> {
+        if !HOOK_POINTS.contains(&self.point.as_str()) {
+            return Err(format!("unknown hook point '{}' (expected one of {})", self.point, HOOK_POINTS.join(", ")));
+        }
+        if self.subject.trim().is_empty() || self.subject.contains(char::is_whitespace) {
+            return Err("subject must be a NATS subject without whitespace".to_string());
+        }
+        // A hook on a worker subject would call the worker back
+        if self.subject.starts_with("sazinka.") {
+            return Err("subject must not be in the sazinka.* namespace".to_string());
+        }
+        if self.timeout_ms.is_some_and(|t| t == 0 || t > MAX_HOOK_TIMEOUT_MS) {
+            return Err(format!("timeoutMs must be between 1 and {}", MAX_HOOK_TIMEOUT_MS));
+        }
+        Ok(())
+    }
+
+    pub fn timeout_ms(&self) -> u64 {
+        self.timeout_ms.unwrap_or(DEFAULT_HOOK_TIMEOUT_MS)
+    }
+}
+
+// ============================================================
+// Request / response types (NATS payloads)
+// ============================================================
+
+/// NATS: sazinka.hooks.register — replaces the plugin's hooks; plugins
+/// renew the registration before `ttlSeconds` runs out
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookRegisterRequest {
+    pub plugin: String,
+    pub hooks: Vec<HookSpec>,
+}
+
+impl HookRegisterRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_plugin_name(&self.plugin)?;
+        if self.hooks.is_empty() {
+            return Err("at least one hook is required".to_string());
+        }
+        self.hooks.iter().try_for_each(HookSpec::validate)
+    }
+}
+
+pub fn validate_plugin_name(plugin: &str) -> Result<(), String> {
+    if plugin.is_empty() || plugin.len() > MAX_PLUGIN_NAME_LEN {
+        return Err(format!("plugin must have 1 to {} characters", MAX_PLUGIN_NAME_LEN));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookRegisterResponse {
+    pub plugin: String,
+    pub registered: usize,
+    /// Registration expires unless renewed within this time
+    pub ttl_seconds: u64,
+}
+
+/// NATS: sazinka.hooks.unregister
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookUnregisterRequest {
+    pub plugin: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookUnregisterResponse {
+    pub removed: usize,
+}
+
+/// A live registration, as listed by sazinka.hooks.list
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookRegistration {
+    pub plugin: String,
+    pub point: String,
+    pub subject: String,
+    pub timeout_ms: u64,
+    pub fail_closed: bool,
+    pub expires_in_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookListResponse {
+    pub hooks: Vec<HookRegistration>,
+}
+
+// ============================================================
+// Worker ↔ plugin messages
+// ============================================================
+
+/// Sent to the plugin's subject
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookCall<'a> {
+    pub point: &'a str,
+    /// Account the operation runs in
+    pub user_id: Uuid,
+    /// Request of a before hook, result of an after hook
+    pub payload: &'a Value,
+}
+
+/// What a plugin does with a before hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HookAction {
+    Allow,
+    Veto,
+    /// Replace the request with `payload`
+    Enrich,
+}
+
+/// Plugin's answer to a before hook
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookReply {
+    pub action: HookAction,
+    /// Why the operation was vetoed, shown to the user
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// The whole request, changed
+    #[serde(default)]
+    pub payload: Option<Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(point: &str, subject: &str) -> HookSpec {
+        HookSpec { point: point.to_string(), subject: subject.to_string(), timeout_ms: None, fail_closed: false }
+    }
+
+    #[test]
+    fn test_hook_spec_validation() {
+        assert!(spec(BEFORE_ROUTE_SAVE, "plugins.rules.route_save").validate().is_ok());
+        assert!(spec("before.customer.delete", "plugins.rules").validate().is_err());
+        assert!(spec(AFTER_VISIT_COMPLETE, "").validate().is_err());
+        assert!(spec(AFTER_VISIT_COMPLETE, "sazinka.route.save").validate().is_err());
+        let slow = HookSpec { timeout_ms: Some(MAX_HOOK_TIMEOUT_MS + 1), ..spec(BEFORE_ROUTE_SAVE, "plugins.rules") };
+        assert!(slow.validate().is_err());
+        assert_eq!(spec(BEFORE_ROUTE_SAVE, "plugins.rules").timeout_ms(), DEFAULT_HOOK_TIMEOUT_MS);
+    }
+
+    #[test]
+    fn test_hook_reply_parses() {
+        let reply: HookReply = serde_json::from_str(r#"{"action": "veto", "reason": "no weekend work"}"#).unwrap();
+        assert_eq!(reply.action, HookAction::Veto);
+        assert_eq!(reply.reason.as_deref(), Some("no weekend work"));
+
+        let reply: HookReply = serde_json::from_str(r#"{"action": "allow"}"#).unwrap();
+        assert_eq!(reply.action, HookAction::Allow);
+        assert!(reply.payload.is_none());
+    }
+}
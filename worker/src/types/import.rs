@@ -26,6 +26,12 @@ pub enum ImportIssueCode {
     InvalidResult,
     DbError,
     ParseError,
+    /// Account plan limit reached
+    QuotaExceeded,
+    /// Address outside the account's service coverage
+    OutOfCoverage,
+    /// Characters that could not be decoded from the file's encoding
+    EncodingError,
     Unknown,
 }
 
@@ -55,6 +61,9 @@ pub struct ImportReport {
     pub imported_count: u32,
     pub updated_count: u32,
     pub skipped_count: u32,
+    /// Encoding the file was decoded from (e.g. "windows-1250")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
     pub issues: Vec<ImportIssue>,
 }
 
@@ -178,10 +187,135 @@ pub struct ImportWorkLogBatchRequest {
     pub entries: Vec<ImportWorkLogRequest>,
 }
 
+// =============================================================================
+// CUSTOMER REFERENCE PREVIEW
+// =============================================================================
+
+/// Key a customer reference was matched by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CustomerRefMatch {
+    /// Explicit "#id:<uuid>" (or exported "customer_uuid:<uuid>") reference
+    Id,
+    CustomerCode,
+    Ico,
+    Email,
+    Phone,
+}
+
+/// Request to preview how the customer_ref column of a CSV would resolve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerRefPreviewRequest {
+    #[serde(default)]
+    pub csv_content: String,
+    /// Raw file bytes; when present the worker detects the encoding itself
+    #[serde(default)]
+    pub csv_base64: Option<String>,
+    /// File sent through `sazinka.import.upload.*`; replaces the inline content
+    #[serde(default)]
+    pub upload_id: Option<Uuid>,
+    /// Name of the imported file; `*.xlsx` files are read as Excel workbooks
+    #[serde(default)]
+    pub filename: Option<String>,
+}
+
+/// Resolution of one CSV row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerRefPreviewRow {
+    /// 1-based line number in the file (header is line 1)
+    pub row_number: i32,
+    pub customer_ref: Option<String>,
+    pub customer_id: Option<Uuid>,
+    pub customer_name: Option<String>,
+    pub matched_by: Option<CustomerRefMatch>,
+}
+
+/// Resolution of every row before the import is submitted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerRefPreviewResponse {
+    pub rows: Vec<CustomerRefPreviewRow>,
+    pub total: u32,
+    pub matched: u32,
+    pub unmatched: u32,
+    /// Rows without a customer_ref value
+    pub missing: u32,
+}
+
+// =============================================================================
+// CHUNKED UPLOAD (files too large for one NATS message)
+// =============================================================================
+
+/// NATS: sazinka.import.upload.begin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportUploadBeginRequest {
+    pub filename: String,
+    /// Size of the whole file in bytes
+    pub total_size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportUploadBeginResponse {
+    pub upload_id: Uuid,
+    /// Largest chunk the worker accepts, in bytes before base64
+    pub max_chunk_size: usize,
+}
+
+/// NATS: sazinka.import.upload.chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportUploadChunkRequest {
+    pub upload_id: Uuid,
+    /// 0-based; chunks are sent in order, a repeated chunk is ignored
+    pub index: u32,
+    pub data_base64: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportUploadChunkResponse {
+    pub upload_id: Uuid,
+    /// Bytes stored so far
+    pub received: u64,
+}
+
+/// NATS: sazinka.import.upload.commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportUploadCommitRequest {
+    pub upload_id: Uuid,
+    /// Hex SHA-256 of the whole file, verified when present
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportUploadCommitResponse {
+    pub upload_id: Uuid,
+    pub size: u64,
+    pub sha256: String,
+}
+
 // =============================================================================
 // CUSTOMER IMPORT JOB (async background processing)
 // =============================================================================
 
+/// Application that produced the import file, see services::import_formats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceSystem {
+    /// Our own CSV layout
+    #[default]
+    Sazinka,
+    KominikSw,
+    Revizex,
+}
+
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
@@ -189,7 +323,17 @@ use uuid::Uuid;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CustomerImportJobRequest {
+    #[serde(default)]
     pub csv_content: String,
+    /// Raw file bytes; when present the worker detects the encoding itself
+    #[serde(default)]
+    pub csv_base64: Option<String>,
+    /// File sent through `sazinka.import.upload.*`; replaces the inline content
+    #[serde(default)]
+    pub upload_id: Option<Uuid>,
+    /// Export format of another application, mapped to ours before import
+    #[serde(default)]
+    pub source_system: SourceSystem,
     pub filename: String,
 }
 
@@ -259,6 +403,45 @@ impl QueuedCustomerImportJob {
     }
 }
 
+// =============================================================================
+// KML/KMZ CUSTOMER IMPORT JOB (async background processing)
+// =============================================================================
+
+/// Request to submit a KML/KMZ customer import job (map pins from Mapy.cz / My Maps).
+/// Progress is reported with `CustomerImportJobStatus`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KmlImportJobRequest {
+    /// Base64 encoded KML or KMZ content
+    #[serde(default)]
+    pub content_base64: String,
+    /// File sent through `sazinka.import.upload.*`; replaces the inline content
+    #[serde(default)]
+    pub upload_id: Option<Uuid>,
+    pub filename: String,
+}
+
+/// Queued KML import job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedKmlImportJob {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub submitted_at: DateTime<Utc>,
+    pub request: KmlImportJobRequest,
+}
+
+impl QueuedKmlImportJob {
+    pub fn new(user_id: Uuid, request: KmlImportJobRequest) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            submitted_at: Utc::now(),
+            request,
+        }
+    }
+}
+
 // =============================================================================
 // DEVICE IMPORT JOB (async background processing)
 // =============================================================================
@@ -267,7 +450,17 @@ impl QueuedCustomerImportJob {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeviceImportJobRequest {
+    #[serde(default)]
     pub csv_content: String,
+    /// Raw file bytes; when present the worker detects the encoding itself
+    #[serde(default)]
+    pub csv_base64: Option<String>,
+    /// File sent through `sazinka.import.upload.*`; replaces the inline content
+    #[serde(default)]
+    pub upload_id: Option<Uuid>,
+    /// Export format of another application, mapped to ours before import
+    #[serde(default)]
+    pub source_system: SourceSystem,
     pub filename: String,
 }
 
@@ -345,8 +538,54 @@ impl QueuedDeviceImportJob {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RevisionImportJobRequest {
+    #[serde(default)]
     pub csv_content: String,
+    /// Raw file bytes; when present the worker detects the encoding itself
+    #[serde(default)]
+    pub csv_base64: Option<String>,
+    /// File sent through `sazinka.import.upload.*`; replaces the inline content
+    #[serde(default)]
+    pub upload_id: Option<Uuid>,
     pub filename: String,
+    /// After the import, link completed revisions to visits on the same customer and date
+    #[serde(default)]
+    pub link_visits: bool,
+    /// When linking, create a completed visit for revisions without one
+    #[serde(default)]
+    pub create_missing_visits: bool,
+}
+
+/// How the linking pass handled one imported revision
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevisionLinkOutcome {
+    /// Linked to an existing visit
+    Linked,
+    /// Linked to a visit created for it
+    VisitCreated,
+    /// No visit on that customer and date
+    Unmatched,
+    /// Not completed, no date, or already linked
+    Skipped,
+}
+
+/// Result of the post-import linking pass
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionLinkSummary {
+    pub linked: u32,
+    pub visits_created: u32,
+    pub unmatched: u32,
+}
+
+impl RevisionLinkSummary {
+    pub fn record(&mut self, outcome: RevisionLinkOutcome) {
+        match outcome {
+            RevisionLinkOutcome::Linked => self.linked += 1,
+            RevisionLinkOutcome::VisitCreated => self.visits_created += 1,
+            RevisionLinkOutcome::Unmatched => self.unmatched += 1,
+            RevisionLinkOutcome::Skipped => {}
+        }
+    }
 }
 
 /// Status of a revision import job
@@ -360,7 +599,15 @@ pub enum RevisionImportJobStatus {
     #[serde(rename_all = "camelCase")]
     Importing { processed: u32, total: u32, succeeded: u32, failed: u32 },
     #[serde(rename_all = "camelCase")]
-    Completed { total: u32, succeeded: u32, failed: u32, report: ImportReport },
+    Completed {
+        total: u32,
+        succeeded: u32,
+        failed: u32,
+        report: ImportReport,
+        /// Set when the linking pass was requested
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        linking: Option<RevisionLinkSummary>,
+    },
     #[serde(rename_all = "camelCase")]
     Failed { error: String },
     #[serde(rename_all = "camelCase")]
@@ -423,7 +670,14 @@ impl QueuedRevisionImportJob {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CommunicationImportJobRequest {
+    #[serde(default)]
     pub csv_content: String,
+    /// Raw file bytes; when present the worker detects the encoding itself
+    #[serde(default)]
+    pub csv_base64: Option<String>,
+    /// File sent through `sazinka.import.upload.*`; replaces the inline content
+    #[serde(default)]
+    pub upload_id: Option<Uuid>,
     pub filename: String,
 }
 
@@ -501,7 +755,14 @@ impl QueuedCommunicationImportJob {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkLogImportJobRequest {
+    #[serde(default)]
     pub csv_content: String,
+    /// Raw file bytes; when present the worker detects the encoding itself
+    #[serde(default)]
+    pub csv_base64: Option<String>,
+    /// File sent through `sazinka.import.upload.*`; replaces the inline content
+    #[serde(default)]
+    pub upload_id: Option<Uuid>,
     pub filename: String,
 }
 
@@ -649,7 +910,11 @@ pub struct ZipImportFileInfo {
 #[serde(rename_all = "camelCase")]
 pub struct ZipImportJobRequest {
     /// Base64 encoded ZIP content
+    #[serde(default)]
     pub zip_content_base64: String,
+    /// File sent through `sazinka.import.upload.*`; replaces the inline content
+    #[serde(default)]
+    pub upload_id: Option<Uuid>,
     pub filename: String,
 }
 
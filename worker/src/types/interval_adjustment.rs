@@ -0,0 +1,218 @@
+#![allow(dead_code)]
+//! Bulk revision interval adjustment types
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Longest interval a regulation may set (matches the DB constraint)
+pub const MAX_INTERVAL_MONTHS: i32 = 240;
+/// Longest note describing the regulation change
+pub const MAX_REGULATION_NOTE_LENGTH: usize = 1000;
+/// Devices listed in a preview; the summary always covers all of them
+pub const MAX_PREVIEW_ITEMS: usize = 500;
+
+/// What an adjustment does to one device's pending revision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntervalAdjustmentAction {
+    /// The upcoming revision moves to the recalculated due date
+    Move,
+    /// The device had no open revision; an upcoming one is created
+    Create,
+    /// The upcoming revision is already due on the recalculated date
+    Unchanged,
+    /// The revision has an agreed appointment and is left for the user to review
+    KeepScheduled,
+    /// No completion, installation date or due date to count from; only the
+    /// interval changes
+    NoAnchor,
+}
+
+/// NATS: sazinka.interval_adjustment.preview - dry run of a regulation change,
+/// stored as a draft that can be applied later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewIntervalAdjustmentRequest {
+    pub device_type_config_id: Uuid,
+    pub new_interval_months: i32,
+    /// Only devices currently on this interval, leaving individually set
+    /// intervals alone; None = every device of the category
+    pub only_interval_months: Option<i32>,
+    /// Also make the new interval the category default for new devices
+    #[serde(default = "default_update_type_default")]
+    pub update_type_default: bool,
+    pub regulation_note: Option<String>,
+}
+
+fn default_update_type_default() -> bool {
+    true
+}
+
+impl PreviewIntervalAdjustmentRequest {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if !(1..=MAX_INTERVAL_MONTHS).contains(&self.new_interval_months) {
+            return Err("newIntervalMonths must be between 1 and 240");
+        }
+        if self.only_interval_months.is_some_and(|m| !(1..=MAX_INTERVAL_MONTHS).contains(&m)) {
+            return Err("onlyIntervalMonths must be between 1 and 240");
+        }
+        if self
+            .regulation_note
+            .as_deref()
+            .is_some_and(|n| n.chars().count() > MAX_REGULATION_NOTE_LENGTH)
+        {
+            return Err("Regulation note is too long");
+        }
+        Ok(())
+    }
+}
+
+/// What the plan of one device is computed from
+#[derive(Debug, Clone, FromRow)]
+pub struct IntervalAdjustmentSource {
+    pub device_id: Uuid,
+    pub customer_id: Uuid,
+    pub customer_name: Option<String>,
+    pub device_name: Option<String>,
+    pub revision_interval_months: i32,
+    pub installation_date: Option<NaiveDate>,
+    pub next_due_date: Option<NaiveDate>,
+    pub last_completed: Option<NaiveDate>,
+    /// Earliest upcoming, scheduled or confirmed revision
+    pub open_revision_id: Option<Uuid>,
+    pub open_status: Option<String>,
+    pub open_due_date: Option<NaiveDate>,
+}
+
+/// Planned change of one device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntervalAdjustmentItem {
+    pub device_id: Uuid,
+    pub customer_id: Uuid,
+    pub customer_name: Option<String>,
+    pub device_name: Option<String>,
+    pub old_interval_months: i32,
+    /// Date the new interval is counted from
+    pub anchor_date: Option<NaiveDate>,
+    pub revision_id: Option<Uuid>,
+    pub old_due_date: Option<NaiveDate>,
+    pub new_due_date: Option<NaiveDate>,
+    pub action: IntervalAdjustmentAction,
+}
+
+/// Device counts per action
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntervalAdjustmentSummary {
+    pub devices: i64,
+    pub moved: i64,
+    pub created: i64,
+    pub unchanged: i64,
+    pub kept_scheduled: i64,
+    pub no_anchor: i64,
+}
+
+impl IntervalAdjustmentSummary {
+    pub fn from_items(items: &[IntervalAdjustmentItem]) -> Self {
+        let mut summary = Self { devices: items.len() as i64, ..Self::default() };
+        for item in items {
+            match item.action {
+                IntervalAdjustmentAction::Move => summary.moved += 1,
+                IntervalAdjustmentAction::Create => summary.created += 1,
+                IntervalAdjustmentAction::Unchanged => summary.unchanged += 1,
+                IntervalAdjustmentAction::KeepScheduled => summary.kept_scheduled += 1,
+                IntervalAdjustmentAction::NoAnchor => summary.no_anchor += 1,
+            }
+        }
+        summary
+    }
+}
+
+/// Stored adjustment: draft after the preview, applied by a job
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct IntervalAdjustment {
+    pub id: Uuid,
+    pub device_type_config_id: Uuid,
+    pub new_interval_months: i32,
+    pub only_interval_months: Option<i32>,
+    pub update_type_default: bool,
+    pub regulation_note: Option<String>,
+    /// draft | running | applied | failed
+    pub status: String,
+    pub preview_summary: Json<IntervalAdjustmentSummary>,
+    pub applied_summary: Option<Json<IntervalAdjustmentSummary>>,
+    pub error: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewIntervalAdjustmentResponse {
+    pub adjustment: IntervalAdjustment,
+    /// First MAX_PREVIEW_ITEMS devices
+    pub items: Vec<IntervalAdjustmentItem>,
+    pub items_truncated: bool,
+}
+
+/// NATS: sazinka.interval_adjustment.apply - run a draft as a background job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyIntervalAdjustmentRequest {
+    pub id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyIntervalAdjustmentResponse {
+    /// Progress is published on sazinka.job.interval_adjustment.status.{jobId}
+    pub job_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListIntervalAdjustmentsResponse {
+    pub adjustments: Vec<IntervalAdjustment>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> PreviewIntervalAdjustmentRequest {
+        PreviewIntervalAdjustmentRequest {
+            device_type_config_id: Uuid::nil(),
+            new_interval_months: 24,
+            only_interval_months: Some(12),
+            update_type_default: true,
+            regulation_note: None,
+        }
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_intervals() {
+        assert!(request().validate().is_ok());
+        let mut req = request();
+        req.new_interval_months = 0;
+        assert!(req.validate().is_err());
+        let mut req = request();
+        req.only_interval_months = Some(241);
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn update_type_default_defaults_to_true() {
+        let req: PreviewIntervalAdjustmentRequest = serde_json::from_str(
+            r#"{"deviceTypeConfigId":"00000000-0000-0000-0000-000000000000","newIntervalMonths":6}"#,
+        )
+        .unwrap();
+        assert!(req.update_type_default);
+        assert_eq!(req.only_interval_months, None);
+    }
+}
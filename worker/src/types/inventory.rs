@@ -0,0 +1,187 @@
+#![allow(dead_code)]
+//! Spare parts inventory types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+pub const STOCK_REASON_RESTOCK: &str = "restock";
+pub const STOCK_REASON_CONSUMPTION: &str = "consumption";
+pub const STOCK_REASON_ADJUSTMENT: &str = "adjustment";
+
+pub const MAX_MATERIALS_PER_WORK_ITEM: usize = 50;
+
+/// Where parts are kept: a crew vehicle or a depot
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StockLocation {
+    pub crew_id: Option<Uuid>,
+    pub depot_id: Option<Uuid>,
+}
+
+impl StockLocation {
+    pub fn validate(&self) -> Result<(), String> {
+        match (self.crew_id, self.depot_id) {
+            (Some(_), None) | (None, Some(_)) => Ok(()),
+            _ => Err("Exactly one of crewId and depotId is required".to_string()),
+        }
+    }
+}
+
+/// Stock of one part at one location
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct StockLevel {
+    pub id: Uuid,
+    pub catalog_item_id: Uuid,
+    pub item_name: String,
+    pub unit: String,
+    pub crew_id: Option<Uuid>,
+    pub depot_id: Option<Uuid>,
+    /// Crew or depot name
+    pub location_name: String,
+    pub quantity: f64,
+    pub min_quantity: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Stock movement (positive change = stock in)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct StockMovement {
+    pub id: Uuid,
+    pub stock_level_id: Uuid,
+    pub change: f64,
+    pub reason: String,
+    pub work_item_id: Option<Uuid>,
+    pub note: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Material used on a work item
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkItemMaterial {
+    pub id: Uuid,
+    pub work_item_id: Uuid,
+    pub catalog_item_id: Uuid,
+    pub quantity: f64,
+    pub stock_level_id: Option<Uuid>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Material as entered on work item create/complete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkItemMaterialInput {
+    pub catalog_item_id: Uuid,
+    pub quantity: f64,
+}
+
+/// A stock level that dropped to its reorder point by a consumption
+#[derive(Debug, Clone, FromRow)]
+pub struct LowStockAlert {
+    pub stock_level_id: Uuid,
+    pub catalog_item_id: Uuid,
+    pub item_name: String,
+    pub unit: String,
+    pub location_name: String,
+    pub quantity: f64,
+    pub min_quantity: f64,
+}
+
+/// NATS: sazinka.inventory.stock.list
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListStockRequest {
+    pub crew_id: Option<Uuid>,
+    pub depot_id: Option<Uuid>,
+    pub catalog_item_id: Option<Uuid>,
+    /// Only levels at or below their reorder point
+    #[serde(default)]
+    pub low_only: bool,
+}
+
+/// NATS: sazinka.inventory.stock.restock - add delivered parts
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestockRequest {
+    pub catalog_item_id: Uuid,
+    #[serde(flatten)]
+    pub location: StockLocation,
+    pub quantity: f64,
+    pub note: Option<String>,
+}
+
+/// NATS: sazinka.inventory.stock.set - stocktake count and reorder point
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetStockRequest {
+    pub catalog_item_id: Uuid,
+    #[serde(flatten)]
+    pub location: StockLocation,
+    /// Counted quantity; the difference is logged as an adjustment
+    pub quantity: Option<f64>,
+    pub min_quantity: Option<f64>,
+    pub note: Option<String>,
+}
+
+/// NATS: sazinka.inventory.movements.list
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListMovementsRequest {
+    pub stock_level_id: Uuid,
+    pub limit: Option<i64>,
+}
+
+/// NATS: sazinka.inventory.materials.list
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListMaterialsRequest {
+    pub work_item_id: Uuid,
+}
+
+/// Validate materials entered on a work item
+pub fn validate_materials(materials: &[WorkItemMaterialInput]) -> Result<(), String> {
+    if materials.len() > MAX_MATERIALS_PER_WORK_ITEM {
+        return Err(format!("A work item takes at most {} materials", MAX_MATERIALS_PER_WORK_ITEM));
+    }
+    if materials.iter().any(|m| !m.quantity.is_finite() || m.quantity <= 0.0) {
+        return Err("Material quantity must be positive".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stock_location_needs_exactly_one() {
+        let id = Some(Uuid::nil());
+        assert!(StockLocation { crew_id: id, depot_id: None }.validate().is_ok());
+        assert!(StockLocation { crew_id: None, depot_id: id }.validate().is_ok());
+        assert!(StockLocation { crew_id: id, depot_id: id }.validate().is_err());
+        assert!(StockLocation::default().validate().is_err());
+    }
+
+    #[test]
+    fn test_restock_request_flattens_location() {
+        let id = Uuid::new_v4();
+        let json = format!(r#"{{"catalogItemId":"{id}","crewId":"{id}","quantity":5}}"#);
+        let request: RestockRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(request.location.crew_id, Some(id));
+        assert!(request.location.depot_id.is_none());
+    }
+
+    #[test]
+    fn test_validate_materials() {
+        let material = |quantity| WorkItemMaterialInput { catalog_item_id: Uuid::nil(), quantity };
+        assert!(validate_materials(&[material(1.0), material(0.5)]).is_ok());
+        assert!(validate_materials(&[material(0.0)]).is_err());
+        assert!(validate_materials(&[material(f64::NAN)]).is_err());
+    }
+}
@@ -5,6 +5,7 @@
 //! like route planning, batch geocoding, etc.
 
 use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
 use uuid::Uuid;
 
 use super::route::RoutePlanResponse;
@@ -266,6 +267,7 @@ mod tests {
         let request = GeocodeJobRequest {
             user_id: Uuid::nil(),
             customer_ids: vec![Uuid::nil()],
+            force: false,
         };
         
         let json = serde_json::to_string(&request).unwrap();
@@ -273,6 +275,35 @@ mod tests {
         assert!(json.contains("customerIds"));
     }
 
+    #[test]
+    fn test_geocode_rerun_request_validation() {
+        assert!(GeocodeRerunRequest::default().validate().is_err());
+
+        let failed = GeocodeRerunRequest { failed_only: true, ..Default::default() };
+        assert!(failed.validate().is_ok());
+
+        let range = GeocodeRerunRequest {
+            postal_code_from: Some("110 00".to_string()),
+            postal_code_to: Some("19999".to_string()),
+            ..Default::default()
+        };
+        assert!(range.validate().is_ok());
+        assert_eq!(range.postal_code_from().as_deref(), Some("11000"));
+
+        let reversed = GeocodeRerunRequest {
+            postal_code_from: Some("20000".to_string()),
+            postal_code_to: Some("19999".to_string()),
+            ..Default::default()
+        };
+        assert!(reversed.validate().is_err());
+
+        let letters = GeocodeRerunRequest { postal_code_from: Some("AB1".to_string()), ..Default::default() };
+        assert!(letters.validate().is_err());
+
+        let blank_city = GeocodeRerunRequest { city: Some("  ".to_string()), ..Default::default() };
+        assert!(blank_city.validate().is_err());
+    }
+
     #[test]
     fn test_geocode_job_status_processing_shows_progress() {
         let status = GeocodeJobStatus::Processing {
@@ -295,11 +326,13 @@ mod tests {
             succeeded: 95,
             failed: 5,
             failed_addresses: vec!["Bad Address 1".to_string()],
+            needs_review: 2,
         };
         
         let json = serde_json::to_string(&status).unwrap();
         assert!(json.contains("completed"));
         assert!(json.contains("failedAddresses"));
+        assert!(json.contains("\"needsReview\":2"));
     }
 
     #[test]
@@ -307,6 +340,7 @@ mod tests {
         let status = GeocodeAddressJobStatus::Completed {
             coordinates: Coordinates { lat: 50.0, lng: 14.0 },
             display_name: Some("Test".to_string()),
+            needs_review: false,
         };
 
         let json = serde_json::to_string(&status).unwrap();
@@ -499,6 +533,104 @@ pub struct GeocodeJobRequest {
     pub user_id: Uuid,
     /// Customer IDs to geocode (those without coordinates)
     pub customer_ids: Vec<Uuid>,
+    /// Geocode again even customers that already have coordinates
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Most customers one geocode re-run may queue
+pub const MAX_GEOCODE_RERUN_CUSTOMERS: i64 = 5_000;
+/// Customers listed in a re-run estimate
+pub const GEOCODE_RERUN_SAMPLE_SIZE: i64 = 20;
+
+/// Request for sazinka.geocode.rerun: re-geocode the customers matching a
+/// filter. Without `confirm` only the matching customers are counted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeocodeRerunRequest {
+    /// City, matched case-insensitively
+    #[serde(default)]
+    pub city: Option<String>,
+    /// Lowest postal code, inclusive
+    #[serde(default)]
+    pub postal_code_from: Option<String>,
+    /// Highest postal code, inclusive
+    #[serde(default)]
+    pub postal_code_to: Option<String>,
+    /// Only customers whose last geocoding failed
+    #[serde(default)]
+    pub failed_only: bool,
+    /// Only customers created (imported) on or after this date
+    #[serde(default)]
+    pub imported_after: Option<chrono::NaiveDate>,
+    /// Queue the job; otherwise only estimate its size
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+impl GeocodeRerunRequest {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let from = self.postal_code_from();
+        let to = self.postal_code_to();
+        for code in [&from, &to].into_iter().flatten() {
+            if code.is_empty() || !code.chars().all(|c| c.is_ascii_digit()) {
+                return Err("Postal codes must contain only digits");
+            }
+        }
+        if let (Some(from), Some(to)) = (&from, &to) {
+            if from.len() != to.len() || from > to {
+                return Err("Postal code range is invalid");
+            }
+        }
+        if self.city().is_none() && from.is_none() && to.is_none() && !self.failed_only && self.imported_after.is_none() {
+            return Err("At least one filter is required");
+        }
+        Ok(())
+    }
+
+    pub fn city(&self) -> Option<&str> {
+        self.city.as_deref().map(str::trim).filter(|c| !c.is_empty())
+    }
+
+    /// Lower bound with spaces removed ("110 00" -> "11000")
+    pub fn postal_code_from(&self) -> Option<String> {
+        self.postal_code_from.as_deref().map(compact_postal_code)
+    }
+
+    /// Upper bound with spaces removed
+    pub fn postal_code_to(&self) -> Option<String> {
+        self.postal_code_to.as_deref().map(compact_postal_code)
+    }
+}
+
+fn compact_postal_code(code: &str) -> String {
+    code.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// A customer a geocode re-run would queue
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct GeocodeRerunCandidate {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+    pub geocode_status: String,
+}
+
+/// Response of sazinka.geocode.rerun
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeocodeRerunResponse {
+    /// Customers matching the filter
+    pub matched: i64,
+    /// First matching customers
+    pub sample: Vec<GeocodeRerunCandidate>,
+    /// Most customers one re-run may queue
+    pub max_customers: i64,
+    /// Job of the queued re-run; None for an estimate
+    pub job_id: Option<Uuid>,
 }
 
 /// Status of a geocoding batch job
@@ -533,6 +665,9 @@ pub enum GeocodeJobStatus {
         failed: u32,
         /// List of addresses that failed (for display)
         failed_addresses: Vec<String>,
+        /// Geocoded outside the account's country, marked for review
+        #[serde(default)]
+        needs_review: u32,
     },
     /// Job failed entirely
     #[serde(rename_all = "camelCase")]
@@ -612,6 +747,9 @@ pub enum GeocodeAddressJobStatus {
     Completed {
         coordinates: Coordinates,
         display_name: Option<String>,
+        /// Result lies outside the account's country
+        #[serde(default)]
+        needs_review: bool,
     },
     #[serde(rename_all = "camelCase")]
     Failed { error: String },
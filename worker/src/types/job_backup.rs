@@ -0,0 +1,120 @@
+#![allow(dead_code)]
+//! Postgres backup of queued JetStream jobs
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Payloads above this size are not copied, only their metadata
+pub const MAX_BACKUP_PAYLOAD_BYTES: usize = 2 * 1024 * 1024;
+
+/// Backup row states
+pub const JOB_BACKUP_QUEUED: &str = "queued";
+pub const JOB_BACKUP_STARTED: &str = "started";
+pub const JOB_BACKUP_LOST: &str = "lost";
+
+/// Mirror of a submitted job
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct JobBackup {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub job_type: String,
+    pub stream: String,
+    pub subject: String,
+    pub stream_sequence: i64,
+    pub filename: Option<String>,
+    pub payload_bytes: i32,
+    /// Original message body; NULL when the upload was too large to keep
+    #[serde(skip)]
+    pub payload: Option<Vec<u8>>,
+    pub status: String,
+    pub submitted_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl JobBackup {
+    /// Whether the job can be republished from the backup alone
+    pub fn resubmittable(&self) -> bool {
+        self.payload.is_some()
+    }
+}
+
+/// Lost job as shown to the user
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LostJobInfo {
+    pub job_id: Uuid,
+    pub job_type: String,
+    pub filename: Option<String>,
+    pub payload_bytes: i32,
+    pub resubmittable: bool,
+    pub submitted_at: DateTime<Utc>,
+}
+
+impl From<JobBackup> for LostJobInfo {
+    fn from(backup: JobBackup) -> Self {
+        Self {
+            resubmittable: backup.resubmittable(),
+            job_id: backup.id,
+            job_type: backup.job_type,
+            filename: backup.filename,
+            payload_bytes: backup.payload_bytes,
+            submitted_at: backup.submitted_at,
+        }
+    }
+}
+
+/// Request to list lost jobs
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListLostJobsRequest {
+    pub limit: Option<i64>,
+}
+
+/// Lost jobs of the caller
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListLostJobsResponse {
+    pub jobs: Vec<LostJobInfo>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup(payload: Option<Vec<u8>>) -> JobBackup {
+        JobBackup {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            job_type: "import.customer".into(),
+            stream: crate::handlers::import::CUSTOMER_IMPORT_STREAM.into(),
+            subject: crate::subjects::import::CUSTOMER_SUBMIT.into(),
+            stream_sequence: 42,
+            filename: Some("zakaznici.csv".into()),
+            payload_bytes: 12,
+            payload,
+            status: JOB_BACKUP_LOST.into(),
+            submitted_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_lost_job_info_reports_resubmittable() {
+        let info = LostJobInfo::from(backup(Some(b"{}".to_vec())));
+        assert!(info.resubmittable);
+        assert_eq!(info.filename.as_deref(), Some("zakaznici.csv"));
+
+        let info = LostJobInfo::from(backup(None));
+        assert!(!info.resubmittable);
+    }
+
+    #[test]
+    fn test_job_backup_never_serializes_payload() {
+        let json = serde_json::to_value(backup(Some(b"secret csv".to_vec()))).unwrap();
+        assert!(json.get("payload").is_none());
+        assert_eq!(json["streamSequence"], 42);
+    }
+}
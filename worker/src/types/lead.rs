@@ -0,0 +1,288 @@
+#![allow(dead_code)]
+//! Web lead intake types
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Lead state of a customer created from a website form
+pub const LEAD_STATUS_NEW: &str = "new";
+pub const LEAD_STATUS_CONTACTED: &str = "contacted";
+pub const LEAD_STATUS_QUOTED: &str = "quoted";
+pub const LEAD_STATUS_WON: &str = "won";
+pub const LEAD_STATUS_LOST: &str = "lost";
+/// Pipeline stages in order; lost can be reached from any open stage
+pub const LEAD_STATUSES: &[&str] =
+    &[LEAD_STATUS_NEW, LEAD_STATUS_CONTACTED, LEAD_STATUS_QUOTED, LEAD_STATUS_WON, LEAD_STATUS_LOST];
+
+pub const MAX_LEAD_LOST_REASON_LENGTH: usize = 500;
+
+pub const CAPTCHA_PROVIDERS: &[&str] = &["turnstile", "hcaptcha", "recaptcha"];
+
+pub const MAX_LEAD_NAME_LENGTH: usize = 200;
+pub const MAX_LEAD_MESSAGE_LENGTH: usize = 4000;
+
+/// Token verification endpoint of a captcha provider
+pub fn captcha_verify_url(provider: &str) -> Option<&'static str> {
+    match provider {
+        "turnstile" => Some("https://challenges.cloudflare.com/turnstile/v0/siteverify"),
+        "hcaptcha" => Some("https://api.hcaptcha.com/siteverify"),
+        "recaptcha" => Some("https://www.google.com/recaptcha/api/siteverify"),
+        _ => None,
+    }
+}
+
+/// Check a lead moving from `from` (None = not a lead yet) to `to`.
+/// Open leads only move forward or to lost; won is final and a lost lead
+/// can only be reopened as new.
+pub fn validate_lead_transition(from: Option<&str>, to: &str) -> Result<(), String> {
+    let rank = |status: &str| LEAD_STATUSES.iter().position(|s| *s == status);
+    let Some(to_rank) = rank(to) else {
+        return Err(format!("status must be one of: {}", LEAD_STATUSES.join(", ")));
+    };
+    let allowed = match from {
+        None => to == LEAD_STATUS_NEW,
+        Some(LEAD_STATUS_WON) => false,
+        Some(LEAD_STATUS_LOST) => to == LEAD_STATUS_NEW,
+        Some(from) => to == LEAD_STATUS_LOST || rank(from).is_some_and(|from_rank| to_rank > from_rank),
+    };
+    if allowed {
+        Ok(())
+    } else {
+        Err(format!("A lead cannot move from {} to {}", from.unwrap_or("none"), to))
+    }
+}
+
+/// Intake settings of an account
+#[derive(Debug, Clone, FromRow)]
+pub struct LeadIntakeSettings {
+    pub user_id: Uuid,
+    pub intake_key: String,
+    pub enabled: bool,
+    pub captcha_provider: Option<String>,
+    pub captcha_secret: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Intake settings as shown to the owner; the captcha secret never leaves the worker
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeadIntakeSettingsResponse {
+    pub intake_key: String,
+    pub enabled: bool,
+    pub captcha_provider: Option<String>,
+    pub has_captcha_secret: bool,
+}
+
+impl From<LeadIntakeSettings> for LeadIntakeSettingsResponse {
+    fn from(settings: LeadIntakeSettings) -> Self {
+        Self {
+            intake_key: settings.intake_key,
+            enabled: settings.enabled,
+            captcha_provider: settings.captcha_provider,
+            has_captcha_secret: settings.captcha_secret.is_some_and(|s| !s.is_empty()),
+        }
+    }
+}
+
+/// NATS: sazinka.lead.intake.update
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateLeadIntakeRequest {
+    pub enabled: bool,
+    /// None turns captcha verification off
+    pub captcha_provider: Option<String>,
+    /// Omitted keeps the stored secret
+    pub captcha_secret: Option<String>,
+}
+
+impl UpdateLeadIntakeRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(provider) = self.captcha_provider.as_deref() {
+            if !CAPTCHA_PROVIDERS.contains(&provider) {
+                return Err(format!("captchaProvider must be one of: {}", CAPTCHA_PROVIDERS.join(", ")));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// NATS: sazinka.portal.lead.submit (public, no login)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitWebLeadRequest {
+    /// Intake key of the account
+    pub key: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+    pub message: Option<String>,
+    /// Date the visitor would like a visit on (booking forms)
+    pub preferred_date: Option<NaiveDate>,
+    /// Honeypot: hidden from people, filled in by bots
+    #[serde(default)]
+    pub website: Option<String>,
+    pub captcha_token: Option<String>,
+}
+
+impl SubmitWebLeadRequest {
+    /// Whether a bot filled in the hidden honeypot field
+    pub fn is_honeypot_filled(&self) -> bool {
+        self.website.as_deref().is_some_and(|w| !w.trim().is_empty())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_LEAD_NAME_LENGTH {
+            return Err(format!("name must be 1-{} characters", MAX_LEAD_NAME_LENGTH));
+        }
+        let email = self.email.as_deref().map(str::trim).filter(|e| !e.is_empty());
+        let phone = self.phone.as_deref().map(str::trim).filter(|p| !p.is_empty());
+        if email.is_none() && phone.is_none() {
+            return Err("An e-mail or phone number is required".to_string());
+        }
+        if email.is_some_and(|e| !e.contains('@') || e.contains(char::is_whitespace)) {
+            return Err("Invalid e-mail address".to_string());
+        }
+        if self.message.as_deref().is_some_and(|m| m.chars().count() > MAX_LEAD_MESSAGE_LENGTH) {
+            return Err(format!("message is limited to {} characters", MAX_LEAD_MESSAGE_LENGTH));
+        }
+        Ok(())
+    }
+}
+
+/// Reply to a website form. Spam gets the same reply as a real submission.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitWebLeadResponse {
+    pub received: bool,
+}
+
+/// NATS: sazinka.lead.transition
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransitionLeadRequest {
+    pub customer_id: Uuid,
+    pub status: String,
+    /// Required when the lead is lost
+    pub reason: Option<String>,
+}
+
+impl TransitionLeadRequest {
+    /// Trimmed reason; a lost lead must have one
+    pub fn validated_reason(&self) -> Result<Option<String>, String> {
+        let reason = self.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+        if self.status == LEAD_STATUS_LOST && reason.is_none() {
+            return Err("A reason is required when a lead is lost".to_string());
+        }
+        if reason.is_some_and(|r| r.chars().count() > MAX_LEAD_LOST_REASON_LENGTH) {
+            return Err(format!("reason is limited to {} characters", MAX_LEAD_LOST_REASON_LENGTH));
+        }
+        Ok(reason.map(String::from))
+    }
+}
+
+/// NATS: sazinka.lead.history
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeadHistoryRequest {
+    pub customer_id: Uuid,
+}
+
+/// One logged lead transition
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct LeadStatusChange {
+    pub id: Uuid,
+    pub customer_id: Uuid,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub reason: Option<String>,
+    pub changed_by: Option<Uuid>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission() -> SubmitWebLeadRequest {
+        SubmitWebLeadRequest {
+            key: "k".to_string(),
+            name: "Jan Novák".to_string(),
+            email: Some("jan@example.cz".to_string()),
+            message: Some("Prosím o revizi kotle".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_submission_validation() {
+        assert!(submission().validate().is_ok());
+        assert!(SubmitWebLeadRequest { name: " ".to_string(), ..submission() }.validate().is_err());
+        assert!(SubmitWebLeadRequest { email: None, ..submission() }.validate().is_err());
+        assert!(SubmitWebLeadRequest { email: None, phone: Some("602111222".to_string()), ..submission() }
+            .validate()
+            .is_ok());
+        assert!(SubmitWebLeadRequest { email: Some("not an email".to_string()), ..submission() }
+            .validate()
+            .is_err());
+        assert!(SubmitWebLeadRequest { message: Some("x".repeat(MAX_LEAD_MESSAGE_LENGTH + 1)), ..submission() }
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_lead_transitions() {
+        assert!(validate_lead_transition(None, LEAD_STATUS_NEW).is_ok());
+        assert!(validate_lead_transition(None, LEAD_STATUS_WON).is_err());
+        assert!(validate_lead_transition(Some(LEAD_STATUS_NEW), LEAD_STATUS_QUOTED).is_ok());
+        assert!(validate_lead_transition(Some(LEAD_STATUS_QUOTED), LEAD_STATUS_CONTACTED).is_err());
+        assert!(validate_lead_transition(Some(LEAD_STATUS_CONTACTED), LEAD_STATUS_LOST).is_ok());
+        assert!(validate_lead_transition(Some(LEAD_STATUS_LOST), LEAD_STATUS_NEW).is_ok());
+        assert!(validate_lead_transition(Some(LEAD_STATUS_LOST), LEAD_STATUS_WON).is_err());
+        assert!(validate_lead_transition(Some(LEAD_STATUS_WON), LEAD_STATUS_LOST).is_err());
+        assert!(validate_lead_transition(Some(LEAD_STATUS_NEW), LEAD_STATUS_NEW).is_err());
+        assert!(validate_lead_transition(Some(LEAD_STATUS_NEW), "archived").is_err());
+    }
+
+    #[test]
+    fn test_lost_requires_reason() {
+        let request = |status: &str, reason: Option<&str>| TransitionLeadRequest {
+            customer_id: Uuid::nil(),
+            status: status.to_string(),
+            reason: reason.map(String::from),
+        };
+        assert!(request(LEAD_STATUS_LOST, Some("  ")).validated_reason().is_err());
+        assert_eq!(request(LEAD_STATUS_LOST, Some(" Too expensive ")).validated_reason(), Ok(Some("Too expensive".to_string())));
+        assert_eq!(request(LEAD_STATUS_CONTACTED, None).validated_reason(), Ok(None));
+    }
+
+    #[test]
+    fn test_honeypot() {
+        assert!(!submission().is_honeypot_filled());
+        assert!(!SubmitWebLeadRequest { website: Some(" ".to_string()), ..submission() }.is_honeypot_filled());
+        assert!(SubmitWebLeadRequest { website: Some("http://spam".to_string()), ..submission() }.is_honeypot_filled());
+    }
+
+    #[test]
+    fn test_settings_response_hides_secret() {
+        let settings = LeadIntakeSettings {
+            user_id: Uuid::nil(),
+            intake_key: "abc".to_string(),
+            enabled: true,
+            captcha_provider: Some("turnstile".to_string()),
+            captcha_secret: Some("secret".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let json = serde_json::to_string(&LeadIntakeSettingsResponse::from(settings)).unwrap();
+        assert!(!json.contains("\"secret\""));
+        assert!(json.contains("\"hasCaptchaSecret\":true"));
+    }
+}
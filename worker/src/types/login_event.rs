@@ -0,0 +1,75 @@
+#![allow(dead_code)]
+//! Login history types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Why a login attempt was rejected
+pub const LOGIN_FAILURE_INVALID_PASSWORD: &str = "invalid_password";
+pub const LOGIN_FAILURE_LOCKED: &str = "locked";
+pub const LOGIN_FAILURE_DISABLED: &str = "disabled";
+
+/// Default number of history entries returned
+pub const DEFAULT_LOGIN_HISTORY_LIMIT: i64 = 20;
+/// Upper bound for the history page size
+pub const MAX_LOGIN_HISTORY_LIMIT: i64 = 100;
+
+/// One login attempt
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginEvent {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub success: bool,
+    pub failure_reason: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    /// First successful login from this user agent
+    pub new_device: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to list recent logins
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListLoginsRequest {
+    /// Admins may look at another user's history; ignored for everyone else
+    pub user_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+impl ListLoginsRequest {
+    pub fn effective_limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_LOGIN_HISTORY_LIMIT)
+            .clamp(1, MAX_LOGIN_HISTORY_LIMIT)
+    }
+}
+
+/// Recent logins, newest first
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListLoginsResponse {
+    pub items: Vec<LoginEvent>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_logins_request_default_limit() {
+        let req: ListLoginsRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(req.effective_limit(), DEFAULT_LOGIN_HISTORY_LIMIT);
+    }
+
+    #[test]
+    fn test_list_logins_request_limit_is_clamped() {
+        let req: ListLoginsRequest = serde_json::from_str(r#"{"limit":5000}"#).unwrap();
+        assert_eq!(req.effective_limit(), MAX_LOGIN_HISTORY_LIMIT);
+        let req: ListLoginsRequest = serde_json::from_str(r#"{"limit":0}"#).unwrap();
+        assert_eq!(req.effective_limit(), 1);
+    }
+}
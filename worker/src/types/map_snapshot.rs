@@ -0,0 +1,60 @@
+#![allow(dead_code)]
+//! Static map snapshot types
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const MAP_SNAPSHOT_DEFAULT_WIDTH: u32 = 800;
+pub const MAP_SNAPSHOT_DEFAULT_HEIGHT: u32 = 500;
+pub const MAP_SNAPSHOT_MIN_SIZE: u32 = 200;
+pub const MAP_SNAPSHOT_MAX_SIZE: u32 = 1600;
+
+/// Requested image size, defaulted and clamped to the supported range
+pub fn map_snapshot_size(width: Option<u32>, height: Option<u32>) -> (u32, u32) {
+    let clamp = |v: u32| v.clamp(MAP_SNAPSHOT_MIN_SIZE, MAP_SNAPSHOT_MAX_SIZE);
+    (
+        clamp(width.unwrap_or(MAP_SNAPSHOT_DEFAULT_WIDTH)),
+        clamp(height.unwrap_or(MAP_SNAPSHOT_DEFAULT_HEIGHT)),
+    )
+}
+
+/// NATS: sazinka.map.snapshot.route
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteMapSnapshotRequest {
+    pub route_id: Uuid,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// NATS: sazinka.map.snapshot.customer
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerMapSnapshotRequest {
+    pub customer_id: Uuid,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// PNG snapshot ready to embed in a printed document
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapSnapshotResponse {
+    /// `data:image/png;base64,...`
+    pub image_data_url: String,
+    pub width: u32,
+    pub height: u32,
+    /// Tile provider attribution to print with the image
+    pub attribution: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_snapshot_size() {
+        assert_eq!(map_snapshot_size(None, None), (800, 500));
+        assert_eq!(map_snapshot_size(Some(50), Some(5000)), (200, 1600));
+    }
+}
@@ -12,6 +12,16 @@ pub struct Request<T> {
     pub timestamp: DateTime<Utc>,
     #[serde(default)]
     pub token: Option<String>,  // JWT access token
+    /// Client IP address, when the sender provides it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    /// Client User-Agent, when the sender provides it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Session records screenshots; customer data is pseudonymized,
+    /// see services::demo_mode
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub demo_mode: bool,
     pub payload: T,
 }
 
@@ -22,6 +32,9 @@ impl<T> Request<T> {
             id: Uuid::new_v4(),
             timestamp: Utc::now(),
             token: Some(token),
+            client_ip: None,
+            user_agent: None,
+            demo_mode: false,
             payload,
         }
     }
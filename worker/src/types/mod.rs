@@ -1,53 +1,125 @@
 #![allow(unused_imports)]
 //! Type definitions
 
+pub mod account_transfer;
 pub mod action_target;
+pub mod admin_user;
+pub mod analysis;
+pub mod automation;
+pub mod backup;
+pub mod calendar_feed;
+pub mod campaign;
+pub mod catalog;
 pub mod communication;
 pub mod inbox;
 pub mod scoring;
 pub mod country;
+pub mod coverage;
+pub mod currency;
 pub mod customer;
+pub mod customer_code;
+pub mod customer_hierarchy;
+pub mod customer_risk;
+pub mod customer_reference;
+pub mod customer_site;
+pub mod dispatch_board;
 pub mod device;
 pub mod device_type_config;
+pub mod escalation;
+pub mod health;
+pub mod hooks;
 pub mod import;
+pub mod interval_adjustment;
+pub mod inventory;
 pub mod import_export_job;
 pub mod job;
+pub mod job_backup;
+pub mod lead;
+pub mod login_event;
+pub mod map_snapshot;
 pub mod messages;
 pub mod note;
+pub mod notification;
 pub mod notification_job;
+pub mod organization;
 pub mod planned_action;
+pub mod presence;
+pub mod quality;
+pub mod quota;
+pub mod quote;
+pub mod report;
+pub mod reschedule;
+pub mod retention;
 pub mod revision;
+pub mod revision_number;
 pub mod role;
 pub mod route;
+pub mod route_analysis;
+pub mod route_lock;
+pub mod routing_diagnostics;
 pub mod settings;
+pub mod subcontractor;
+pub mod subscription;
+pub mod template_translation;
 pub mod user;
 pub mod valhalla_job;
 pub mod crew;
+pub mod crm_sync;
 pub mod visit;
+pub mod visit_duration;
+pub mod weather;
+pub mod webhook;
 pub mod task;
+pub mod telemetry;
 pub mod work_item;
 
 pub use action_target::*;
+pub use admin_user::*;
+pub use analysis::*;
 pub use communication::*;
 pub use inbox::*;
 pub use scoring::*;
 pub use country::*;
+pub use coverage::*;
+pub use currency::*;
 pub use customer::*;
+pub use customer_code::*;
+pub use customer_hierarchy::*;
+pub use customer_reference::*;
+pub use customer_site::*;
 pub use device::*;
+pub use escalation::*;
 pub use import::*;
 pub use import_export_job::*;
+pub use interval_adjustment::*;
 pub use job::*;
+pub use job_backup::*;
+pub use login_event::*;
+pub use map_snapshot::*;
 pub use messages::*;
 pub use note::*;
+pub use notification::*;
 pub use notification_job::*;
 pub use planned_action::*;
+pub use quality::*;
+pub use quota::*;
+pub use report::*;
+pub use reschedule::*;
 pub use revision::*;
+pub use revision_number::*;
 pub use role::*;
 pub use route::*;
+pub use route_analysis::*;
+pub use route_lock::*;
 pub use settings::*;
+pub use subscription::*;
+pub use template_translation::*;
 pub use user::*;
 pub use valhalla_job::*;
 pub use crew::*;
+pub use crm_sync::*;
 pub use visit::*;
+pub use webhook::*;
 pub use task::*;
+pub use telemetry::*;
 pub use work_item::*;
\ No newline at end of file
@@ -0,0 +1,194 @@
+#![allow(dead_code)]
+//! Notification center types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Notification raised by an escalation rule
+pub const NOTIFICATION_KIND_ESCALATION: &str = "escalation";
+/// Notification about a submission from a website form
+pub const NOTIFICATION_KIND_LEAD: &str = "lead";
+/// Notification about a customer accepting or declining a quote
+pub const NOTIFICATION_KIND_QUOTE: &str = "quote";
+/// Notification about a part running low at a crew vehicle or depot
+pub const NOTIFICATION_KIND_LOW_STOCK: &str = "low_stock";
+/// Notification raised by an automation rule
+pub const NOTIFICATION_KIND_AUTOMATION: &str = "automation";
+
+/// In-app notification of an account
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub id: Uuid,
+    pub kind: String,
+    pub title: String,
+    pub body: Option<String>,
+    /// "revision", "visit", ... for deep links
+    pub entity_type: Option<String>,
+    pub entity_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+/// NATS: sazinka.notification.list
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListNotificationsRequest {
+    #[serde(default)]
+    pub unread_only: bool,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Response for sazinka.notification.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListNotificationsResponse {
+    pub items: Vec<Notification>,
+    pub unread_count: i64,
+}
+
+/// NATS: sazinka.notification.read - marks the given notifications, or all when `ids` is omitted
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkNotificationsReadRequest {
+    pub ids: Option<Vec<Uuid>>,
+}
+
+/// Response for sazinka.notification.read
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkNotificationsReadResponse {
+    pub updated: u64,
+    pub unread_count: i64,
+}
+
+// ============================================================
+// Subscription preferences
+// ============================================================
+
+/// Notification about a finished background job (import, export, route, ...)
+pub const NOTIFICATION_KIND_JOB: &str = "job";
+/// Summary of notifications held back for an hourly or daily digest
+pub const NOTIFICATION_KIND_DIGEST: &str = "digest";
+
+/// Imports and exports
+pub const NOTIFICATION_CATEGORY_IMPORT: &str = "import";
+/// Route planning jobs
+pub const NOTIFICATION_CATEGORY_ROUTE: &str = "route";
+/// Overdue revisions and other escalations
+pub const NOTIFICATION_CATEGORY_REMINDER: &str = "reminder";
+/// Everything else: leads, quotes, stock, geocoding
+pub const NOTIFICATION_CATEGORY_SYSTEM: &str = "system";
+
+pub const NOTIFICATION_CATEGORIES: &[&str] = &[
+    NOTIFICATION_CATEGORY_IMPORT,
+    NOTIFICATION_CATEGORY_ROUTE,
+    NOTIFICATION_CATEGORY_REMINDER,
+    NOTIFICATION_CATEGORY_SYSTEM,
+];
+
+pub const NOTIFICATION_DELIVERY_IMMEDIATE: &str = "immediate";
+pub const NOTIFICATION_DELIVERY_HOURLY: &str = "hourly";
+pub const NOTIFICATION_DELIVERY_DAILY: &str = "daily";
+
+pub const NOTIFICATION_DELIVERIES: &[&str] = &[
+    NOTIFICATION_DELIVERY_IMMEDIATE,
+    NOTIFICATION_DELIVERY_HOURLY,
+    NOTIFICATION_DELIVERY_DAILY,
+];
+
+/// How a user receives one category of notifications
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPreference {
+    pub category: String,
+    pub enabled: bool,
+    pub delivery: String,
+    /// True while the category has no stored preference
+    #[sqlx(default)]
+    pub is_default: bool,
+}
+
+impl NotificationPreference {
+    /// Every category is delivered immediately until the user says otherwise
+    pub fn default_for(category: &str) -> Self {
+        Self {
+            category: category.to_string(),
+            enabled: true,
+            delivery: NOTIFICATION_DELIVERY_IMMEDIATE.to_string(),
+            is_default: true,
+        }
+    }
+}
+
+/// Notification held back for a digest
+#[derive(Debug, Clone, FromRow)]
+pub struct NotificationDigestItem {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub category: String,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// NATS: sazinka.notification.preferences.set
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetNotificationPreferenceRequest {
+    pub category: String,
+    pub enabled: bool,
+    #[serde(default = "default_delivery")]
+    pub delivery: String,
+}
+
+fn default_delivery() -> String {
+    NOTIFICATION_DELIVERY_IMMEDIATE.to_string()
+}
+
+impl SetNotificationPreferenceRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if !NOTIFICATION_CATEGORIES.contains(&self.category.as_str()) {
+            return Err(format!("category must be one of: {}", NOTIFICATION_CATEGORIES.join(", ")));
+        }
+        if !NOTIFICATION_DELIVERIES.contains(&self.delivery.as_str()) {
+            return Err(format!("delivery must be one of: {}", NOTIFICATION_DELIVERIES.join(", ")));
+        }
+        Ok(())
+    }
+}
+
+/// Response for sazinka.notification.preferences.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPreferencesResponse {
+    /// One entry per category, defaults filled in
+    pub preferences: Vec<NotificationPreference>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(category: &str, delivery: &str) -> SetNotificationPreferenceRequest {
+        SetNotificationPreferenceRequest { category: category.into(), enabled: true, delivery: delivery.into() }
+    }
+
+    #[test]
+    fn test_validate_preference() {
+        assert!(request("import", "hourly").validate().is_ok());
+        assert!(request("reminder", "daily").validate().is_ok());
+        assert!(request("jobs", "hourly").validate().is_err());
+        assert!(request("route", "weekly").validate().is_err());
+    }
+
+    #[test]
+    fn test_delivery_defaults_to_immediate() {
+        let req: SetNotificationPreferenceRequest =
+            serde_json::from_str(r#"{"category": "route", "enabled": false}"#).unwrap();
+        assert_eq!(req.delivery, NOTIFICATION_DELIVERY_IMMEDIATE);
+        assert!(!req.enabled);
+    }
+}
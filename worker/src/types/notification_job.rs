@@ -112,6 +112,18 @@ pub struct CustomEmailRequest {
     pub body_text: Option<String>,
 }
 
+/// Request for a campaign email (already rendered for the customer)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CampaignEmailRequest {
+    pub campaign_id: Uuid,
+    pub customer_id: Uuid,
+    pub to: String,
+    pub subject: String,
+    pub body_html: String,
+    pub body_text: String,
+}
+
 /// Type of email job
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
@@ -125,6 +137,9 @@ pub enum EmailJobRequest {
     /// Custom email
     #[serde(rename_all = "camelCase")]
     Custom(CustomEmailRequest),
+    /// Campaign email
+    #[serde(rename_all = "camelCase")]
+    Campaign(CampaignEmailRequest),
 }
 
 impl EmailJobRequest {
@@ -133,6 +148,7 @@ impl EmailJobRequest {
             EmailJobRequest::RevisionReminder(_) => "revision_reminder",
             EmailJobRequest::AppointmentConfirmation(_) => "appointment_confirmation",
             EmailJobRequest::Custom(_) => "custom",
+            EmailJobRequest::Campaign(_) => "campaign",
         }
     }
 }
@@ -243,6 +259,16 @@ pub struct SmsConfirmationRequest {
     pub time_window: Option<String>,
 }
 
+/// Request for a campaign SMS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmsCampaignRequest {
+    pub campaign_id: Uuid,
+    pub customer_id: Uuid,
+    pub phone_number: String,
+    pub message: String,
+}
+
 /// Type of SMS job
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
@@ -253,6 +279,9 @@ pub enum SmsJobRequest {
     /// SMS confirmation
     #[serde(rename_all = "camelCase")]
     Confirmation(SmsConfirmationRequest),
+    /// Campaign SMS
+    #[serde(rename_all = "camelCase")]
+    Campaign(SmsCampaignRequest),
 }
 
 impl SmsJobRequest {
@@ -260,6 +289,7 @@ impl SmsJobRequest {
         match self {
             SmsJobRequest::Reminder(_) => "reminder",
             SmsJobRequest::Confirmation(_) => "confirmation",
+            SmsJobRequest::Campaign(_) => "campaign",
         }
     }
     
@@ -267,6 +297,7 @@ impl SmsJobRequest {
         match self {
             SmsJobRequest::Reminder(r) => &r.phone_number,
             SmsJobRequest::Confirmation(r) => &r.phone_number,
+            SmsJobRequest::Campaign(r) => &r.phone_number,
         }
     }
 }
@@ -0,0 +1,239 @@
+#![allow(dead_code)]
+//! Organization (team) types
+//!
+//! An organization is the team around one owner account. Its data stays keyed
+//! on the owner's user_id; members are worker logins of the owner, so they
+//! share it through `AuthInfo::data_user_id()`. Owners and admins manage the
+//! team, members only work with the shared data.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+pub const ORG_ROLE_OWNER: &str = "owner";
+pub const ORG_ROLE_ADMIN: &str = "admin";
+pub const ORG_ROLE_MEMBER: &str = "member";
+
+/// Roles an invitation or a role change can grant (there is one owner)
+pub const ASSIGNABLE_ORG_ROLES: &[&str] = &[ORG_ROLE_ADMIN, ORG_ROLE_MEMBER];
+
+/// How long an invitation link stays valid
+pub const INVITATION_TTL_DAYS: i64 = 7;
+pub const MAX_ORGANIZATION_NAME_LEN: usize = 255;
+
+/// Whether a membership role may manage the team
+pub fn can_manage(role: &str) -> bool {
+    role == ORG_ROLE_OWNER || role == ORG_ROLE_ADMIN
+}
+
+fn validate_assignable_role(role: &str) -> Result<(), String> {
+    if !ASSIGNABLE_ORG_ROLES.contains(&role) {
+        return Err(format!("role must be one of: {}", ASSIGNABLE_ORG_ROLES.join(", ")));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Organization {
+    pub id: Uuid,
+    pub owner_user_id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Organization and role of a login (token claims)
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct OrganizationMembership {
+    pub organization_id: Uuid,
+    pub role: String,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationMember {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub role: String,
+    pub joined_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+/// Pending invitation; the token is only ever sent by email
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationInvitation {
+    pub id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub invited_by: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Invitation looked up by its token
+#[derive(Debug, Clone, FromRow)]
+pub struct InvitationForAcceptance {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub owner_user_id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub owner_locale: String,
+}
+
+// ============================================================
+// Request / response types (NATS payloads)
+// ============================================================
+
+/// NATS: sazinka.org.update
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateOrganizationRequest {
+    pub name: String,
+}
+
+impl UpdateOrganizationRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() || name.len() > MAX_ORGANIZATION_NAME_LEN {
+            return Err(format!("name must have 1 to {} characters", MAX_ORGANIZATION_NAME_LEN));
+        }
+        Ok(())
+    }
+}
+
+/// NATS: sazinka.org.invite.create
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateInvitationRequest {
+    pub email: String,
+    #[serde(default = "default_invitation_role")]
+    pub role: String,
+}
+
+fn default_invitation_role() -> String {
+    ORG_ROLE_MEMBER.to_string()
+}
+
+impl CreateInvitationRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        let email = self.email.trim();
+        if email.len() > MAX_ORGANIZATION_NAME_LEN || !email.contains('@') {
+            return Err("email is not a valid address".to_string());
+        }
+        validate_assignable_role(&self.role)
+    }
+}
+
+/// NATS: sazinka.org.invite.revoke
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvitationIdRequest {
+    pub id: Uuid,
+}
+
+/// NATS: sazinka.org.invite.accept (public, authorized by the token)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptInvitationRequest {
+    pub token: String,
+    pub name: String,
+    pub password: String,
+}
+
+impl AcceptInvitationRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.token.trim().is_empty() {
+            return Err("token is required".to_string());
+        }
+        let name = self.name.trim();
+        if name.is_empty() || name.len() > MAX_ORGANIZATION_NAME_LEN {
+            return Err(format!("name must have 1 to {} characters", MAX_ORGANIZATION_NAME_LEN));
+        }
+        Ok(())
+    }
+}
+
+/// NATS: sazinka.org.member.update
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMemberRequest {
+    pub user_id: Uuid,
+    pub role: String,
+}
+
+impl UpdateMemberRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_assignable_role(&self.role)
+    }
+}
+
+/// NATS: sazinka.org.member.remove
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberIdRequest {
+    pub user_id: Uuid,
+}
+
+/// Response for sazinka.org.get / sazinka.org.update
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationResponse {
+    pub organization: Organization,
+    /// Role of the caller
+    pub role: String,
+    pub members: Vec<OrganizationMember>,
+    /// Pending invitations, for owners and admins only
+    pub invitations: Vec<OrganizationInvitation>,
+}
+
+/// Response for sazinka.org.invite.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListInvitationsResponse {
+    pub invitations: Vec<OrganizationInvitation>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_manage() {
+        assert!(can_manage(ORG_ROLE_OWNER));
+        assert!(can_manage(ORG_ROLE_ADMIN));
+        assert!(!can_manage(ORG_ROLE_MEMBER));
+        assert!(!can_manage("subcontractor"));
+    }
+
+    #[test]
+    fn test_invitation_validate() {
+        let request: CreateInvitationRequest = serde_json::from_str(r#"{"email": "jana@example.com"}"#).unwrap();
+        assert_eq!(request.role, ORG_ROLE_MEMBER);
+        assert!(request.validate().is_ok());
+
+        let request = |email: &str, role: &str| CreateInvitationRequest { email: email.to_string(), role: role.to_string() };
+        assert!(request("jana@example.com", "admin").validate().is_ok());
+        assert!(request("jana@example.com", "owner").validate().is_err());
+        assert!(request("jana", "member").validate().is_err());
+    }
+
+    #[test]
+    fn test_accept_and_member_validate() {
+        let accept = |token: &str, name: &str| AcceptInvitationRequest {
+            token: token.to_string(),
+            name: name.to_string(),
+            password: "Correct1horse".to_string(),
+        };
+        assert!(accept("abc", "Jana Nováková").validate().is_ok());
+        assert!(accept("abc", " ").validate().is_err());
+        assert!(accept(" ", "Jana").validate().is_err());
+
+        let update = |role: &str| UpdateMemberRequest { user_id: Uuid::new_v4(), role: role.to_string() };
+        assert!(update("admin").validate().is_ok());
+        assert!(update("owner").validate().is_err());
+    }
+}
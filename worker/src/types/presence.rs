@@ -0,0 +1,87 @@
+#![allow(dead_code)]
+//! Presence types (who is viewing or editing what)
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Entities the UI reports presence on
+pub const PRESENCE_ENTITY_TYPES: &[&str] = &["route", "customer", "visit", "revision", "device"];
+
+/// Longest client-generated session id accepted
+pub const MAX_PRESENCE_SESSION_ID_LEN: usize = 64;
+
+/// Someone else on the same entity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresenceViewer {
+    pub user_id: Uuid,
+    pub name: String,
+    /// Has unsaved changes open (edit form, route being rearranged)
+    pub editing: bool,
+    /// First heartbeat on the entity
+    pub since: DateTime<Utc>,
+}
+
+// ============================================================
+// Request / response types (NATS payloads)
+// ============================================================
+
+/// NATS: sazinka.presence.heartbeat
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresenceHeartbeatRequest {
+    /// Per browser tab, generated by the client
+    pub session_id: String,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    #[serde(default)]
+    pub editing: bool,
+}
+
+impl PresenceHeartbeatRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.session_id.is_empty() || self.session_id.len() > MAX_PRESENCE_SESSION_ID_LEN {
+            return Err(format!("sessionId must have 1 to {} characters", MAX_PRESENCE_SESSION_ID_LEN));
+        }
+        validate_entity_type(&self.entity_type)
+    }
+}
+
+/// NATS: sazinka.presence.leave
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresenceLeaveRequest {
+    pub session_id: String,
+    /// Entity left; every entity of the session when absent (tab closed)
+    #[serde(default)]
+    pub entity_type: Option<String>,
+    #[serde(default)]
+    pub entity_id: Option<Uuid>,
+}
+
+/// NATS: sazinka.presence.list
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresenceListRequest {
+    pub entity_type: String,
+    pub entity_id: Uuid,
+}
+
+/// Response for sazinka.presence.heartbeat and sazinka.presence.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresenceResponse {
+    /// Other sessions on the entity, editors first
+    pub viewers: Vec<PresenceViewer>,
+    /// Heartbeat interval the client should keep
+    pub heartbeat_seconds: u64,
+}
+
+pub fn validate_entity_type(entity_type: &str) -> Result<(), String> {
+    if PRESENCE_ENTITY_TYPES.contains(&entity_type) {
+        Ok(())
+    } else {
+        Err(format!("entityType must be one of: {}", PRESENCE_ENTITY_TYPES.join(", ")))
+    }
+}
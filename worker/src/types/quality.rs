@@ -0,0 +1,250 @@
+#![allow(dead_code)]
+//! Data quality report types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::subjects;
+
+pub const DEFAULT_ITEM_LIMIT: i64 = 50;
+pub const MAX_ITEM_LIMIT: i64 = 500;
+pub const DEFAULT_OVERDUE_GRACE_DAYS: i32 = 0;
+
+/// A data quality rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityRule {
+    /// Active customers that cannot be placed on the map
+    CustomerMissingCoordinates,
+    /// Devices without a revision interval
+    DeviceMissingInterval,
+    /// Overdue revisions of customers with neither phone nor email
+    OverdueWithoutContact,
+    /// Customer phone numbers not in international format
+    InvalidPhoneFormat,
+}
+
+impl QualityRule {
+    pub const ALL: [QualityRule; 4] = [
+        QualityRule::CustomerMissingCoordinates,
+        QualityRule::DeviceMissingInterval,
+        QualityRule::OverdueWithoutContact,
+        QualityRule::InvalidPhoneFormat,
+    ];
+
+    pub fn severity(self) -> QualitySeverity {
+        match self {
+            QualityRule::CustomerMissingCoordinates => QualitySeverity::Warning,
+            QualityRule::DeviceMissingInterval => QualitySeverity::Error,
+            QualityRule::OverdueWithoutContact => QualitySeverity::Error,
+            QualityRule::InvalidPhoneFormat => QualitySeverity::Warning,
+        }
+    }
+
+    /// How the problem can be repaired, when it can be done automatically
+    pub fn fix(self) -> Option<QualityFix> {
+        match self {
+            QualityRule::CustomerMissingCoordinates => Some(QualityFix {
+                action: "geocode".to_string(),
+                subject: Some(subjects::geocode::SUBMIT.to_string()),
+            }),
+            QualityRule::DeviceMissingInterval => Some(QualityFix {
+                action: "set_default_interval".to_string(),
+                subject: Some(subjects::device::UPDATE.to_string()),
+            }),
+            QualityRule::InvalidPhoneFormat => Some(QualityFix {
+                action: "normalize_phone".to_string(),
+                subject: Some(subjects::customer::UPDATE.to_string()),
+            }),
+            QualityRule::OverdueWithoutContact => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QualitySeverity {
+    Warning,
+    Error,
+}
+
+/// Automatic repair of a rule's findings; item suggestions carry the values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityFix {
+    pub action: String,
+    /// Subject that applies the fix to one item
+    pub subject: Option<String>,
+}
+
+/// Request for sazinka.quality.report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityReportRequest {
+    /// Rules to run; every rule when empty
+    #[serde(default)]
+    pub rules: Vec<QualityRule>,
+    /// Days past the due date before a revision counts as overdue
+    #[serde(default)]
+    pub overdue_grace_days: Option<i32>,
+    /// Items listed per rule
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+impl QualityReportRequest {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.overdue_grace_days.is_some_and(|days| !(0..=365).contains(&days)) {
+            return Err("overdueGraceDays must be between 0 and 365");
+        }
+        if self.limit.is_some_and(|limit| !(1..=MAX_ITEM_LIMIT).contains(&limit)) {
+            return Err("limit must be between 1 and 500");
+        }
+        Ok(())
+    }
+
+    /// Rules to run, in report order
+    pub fn effective_rules(&self) -> Vec<QualityRule> {
+        QualityRule::ALL
+            .into_iter()
+            .filter(|rule| self.rules.is_empty() || self.rules.contains(rule))
+            .collect()
+    }
+
+    pub fn item_limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_ITEM_LIMIT)
+    }
+
+    pub fn grace_days(&self) -> i32 {
+        self.overdue_grace_days.unwrap_or(DEFAULT_OVERDUE_GRACE_DAYS)
+    }
+}
+
+/// One record breaking a rule
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityItem {
+    /// Customer, device or revision, depending on the rule
+    pub entity_id: Uuid,
+    pub customer_id: Uuid,
+    pub customer_name: Option<String>,
+    /// The offending value or a short description
+    pub detail: Option<String>,
+    /// Value the fix would set, when one can be derived
+    #[sqlx(default)]
+    pub suggested_value: Option<String>,
+}
+
+/// Findings of one rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityFinding {
+    pub rule: QualityRule,
+    pub severity: QualitySeverity,
+    pub count: i64,
+    /// Up to `limit` of the offending records
+    pub items: Vec<QualityItem>,
+    pub fix: Option<QualityFix>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityReport {
+    pub generated_at: DateTime<Utc>,
+    pub findings: Vec<QualityFinding>,
+}
+
+/// Calling code used to complete 9-digit national numbers of an account's country
+pub fn calling_code(country: Option<&str>) -> &'static str {
+    match country.map(str::to_ascii_uppercase).as_deref() {
+        Some("SK") => "+421",
+        Some("AT") => "+43",
+        Some("DE") => "+49",
+        Some("PL") => "+48",
+        Some("HU") => "+36",
+        _ => "+420",
+    }
+}
+
+/// Whether a phone number is in international format (+ and 8–15 digits)
+pub fn is_international_phone(phone: &str) -> bool {
+    phone
+        .strip_prefix('+')
+        .is_some_and(|digits| (8..=15).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit()) && !digits.starts_with('0'))
+}
+
+/// International form of a phone number written in a local or sloppy format,
+/// using the account country's calling code for 9-digit national numbers
+pub fn normalize_phone(raw: &str, calling_code: &str) -> Option<String> {
+    let cleaned: String = raw.chars().filter(|c| !matches!(c, ' ' | '-' | '(' | ')' | '.' | '/')).collect();
+    let candidate = if let Some(rest) = cleaned.strip_prefix("00") {
+        format!("+{}", rest)
+    } else if cleaned.len() == 9 && cleaned.chars().all(|c| c.is_ascii_digit()) {
+        format!("{}{}", calling_code, cleaned)
+    } else {
+        cleaned
+    };
+    is_international_phone(&candidate).then_some(candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_international_phone() {
+        assert!(is_international_phone("+420602123456"));
+        assert!(!is_international_phone("602123456"));
+        assert!(!is_international_phone("+420 602 123 456"));
+        assert!(!is_international_phone("+0420602123456"));
+        assert!(!is_international_phone("+123"));
+    }
+
+    #[test]
+    fn test_normalize_phone() {
+        assert_eq!(normalize_phone("602 123 456", "+420").as_deref(), Some("+420602123456"));
+        assert_eq!(normalize_phone("00421-905-123-456", "+420").as_deref(), Some("+421905123456"));
+        assert_eq!(normalize_phone("+420 (602) 123 456", "+420").as_deref(), Some("+420602123456"));
+        assert_eq!(normalize_phone("volat po 17h", "+420"), None);
+        assert_eq!(normalize_phone("12345", "+420"), None);
+    }
+
+    #[test]
+    fn test_calling_code() {
+        assert_eq!(calling_code(Some("sk")), "+421");
+        assert_eq!(calling_code(None), "+420");
+    }
+
+    #[test]
+    fn test_effective_rules() {
+        let all = QualityReportRequest::default();
+        assert_eq!(all.effective_rules().len(), QualityRule::ALL.len());
+
+        let some = QualityReportRequest {
+            rules: vec![QualityRule::InvalidPhoneFormat, QualityRule::DeviceMissingInterval],
+            ..Default::default()
+        };
+        assert_eq!(
+            some.effective_rules(),
+            vec![QualityRule::DeviceMissingInterval, QualityRule::InvalidPhoneFormat]
+        );
+    }
+
+    #[test]
+    fn test_request_validation() {
+        assert!(QualityReportRequest::default().validate().is_ok());
+        let bad_limit = QualityReportRequest { limit: Some(0), ..Default::default() };
+        assert!(bad_limit.validate().is_err());
+        let bad_grace = QualityReportRequest { overdue_grace_days: Some(-1), ..Default::default() };
+        assert!(bad_grace.validate().is_err());
+    }
+
+    #[test]
+    fn test_rule_serialization() {
+        let json = serde_json::to_string(&QualityRule::OverdueWithoutContact).unwrap();
+        assert_eq!(json, "\"overdue_without_contact\"");
+        assert!(QualityRule::OverdueWithoutContact.fix().is_none());
+    }
+}
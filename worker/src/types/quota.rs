@@ -0,0 +1,128 @@
+#![allow(dead_code)]
+//! Account plans and usage quota types
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Account plans
+pub const PLAN_FREE: &str = "free";
+pub const PLAN_PRO: &str = "pro";
+pub const PLAN_BUSINESS: &str = "business";
+/// No limits; default for accounts created before plans existed
+pub const PLAN_UNLIMITED: &str = "unlimited";
+
+pub const ACCOUNT_PLANS: &[&str] = &[PLAN_FREE, PLAN_PRO, PLAN_BUSINESS, PLAN_UNLIMITED];
+
+/// Something an account's plan limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaMetric {
+    /// Customers on record at any time
+    Customers,
+    /// Route optimizations per calendar month
+    RoutePlans,
+    /// SMS messages per calendar month
+    SmsCredits,
+}
+
+impl QuotaMetric {
+    pub const ALL: [QuotaMetric; 3] = [QuotaMetric::Customers, QuotaMetric::RoutePlans, QuotaMetric::SmsCredits];
+
+    /// Key stored in `usage_counters.metric`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuotaMetric::Customers => "customers",
+            QuotaMetric::RoutePlans => "route_plans",
+            QuotaMetric::SmsCredits => "sms_credits",
+        }
+    }
+
+    /// Monthly metrics are counted per calendar month; the rest are live totals
+    pub fn is_monthly(&self) -> bool {
+        !matches!(self, QuotaMetric::Customers)
+    }
+}
+
+/// Limits of a plan (None = unlimited)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlanLimits {
+    pub customers: Option<i64>,
+    pub route_plans: Option<i64>,
+    pub sms_credits: Option<i64>,
+}
+
+impl PlanLimits {
+    /// Limits for a plan name. Unknown plans get the free tier.
+    pub fn for_plan(plan: &str) -> Self {
+        match plan {
+            PLAN_UNLIMITED => Self { customers: None, route_plans: None, sms_credits: None },
+            PLAN_BUSINESS => Self { customers: Some(10_000), route_plans: Some(3_000), sms_credits: Some(1_000) },
+            PLAN_PRO => Self { customers: Some(1_000), route_plans: Some(300), sms_credits: Some(200) },
+            _ => Self { customers: Some(50), route_plans: Some(30), sms_credits: Some(0) },
+        }
+    }
+
+    pub fn limit(&self, metric: QuotaMetric) -> Option<i64> {
+        match metric {
+            QuotaMetric::Customers => self.customers,
+            QuotaMetric::RoutePlans => self.route_plans,
+            QuotaMetric::SmsCredits => self.sms_credits,
+        }
+    }
+}
+
+/// Consumption of one metric
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageItem {
+    pub metric: QuotaMetric,
+    pub used: i64,
+    /// None = unlimited
+    pub limit: Option<i64>,
+    /// First day of the counted month, for monthly metrics
+    pub period_start: Option<NaiveDate>,
+}
+
+/// Response for sazinka.account.usage
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountUsageResponse {
+    pub plan: String,
+    pub items: Vec<UsageItem>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_plan_has_no_limits() {
+        let limits = PlanLimits::for_plan(PLAN_UNLIMITED);
+        for metric in QuotaMetric::ALL {
+            assert!(limits.limit(metric).is_none());
+        }
+    }
+
+    #[test]
+    fn test_unknown_plan_falls_back_to_free() {
+        assert_eq!(PlanLimits::for_plan("gold"), PlanLimits::for_plan(PLAN_FREE));
+    }
+
+    #[test]
+    fn test_plans_are_ordered() {
+        let free = PlanLimits::for_plan(PLAN_FREE);
+        let pro = PlanLimits::for_plan(PLAN_PRO);
+        let business = PlanLimits::for_plan(PLAN_BUSINESS);
+        for metric in QuotaMetric::ALL {
+            assert!(free.limit(metric) <= pro.limit(metric));
+            assert!(pro.limit(metric) <= business.limit(metric));
+        }
+    }
+
+    #[test]
+    fn test_metric_serializes_snake_case() {
+        assert_eq!(serde_json::to_string(&QuotaMetric::RoutePlans).unwrap(), "\"route_plans\"");
+        assert_eq!(QuotaMetric::SmsCredits.as_str(), "sms_credits");
+        assert!(!QuotaMetric::Customers.is_monthly());
+    }
+}
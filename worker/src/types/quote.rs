@@ -0,0 +1,305 @@
+#![allow(dead_code)]
+//! Quote (offer) types
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::work_item::WorkType;
+
+pub const QUOTE_STATUS_DRAFT: &str = "draft";
+pub const QUOTE_STATUS_SENT: &str = "sent";
+pub const QUOTE_STATUS_ACCEPTED: &str = "accepted";
+pub const QUOTE_STATUS_DECLINED: &str = "declined";
+pub const QUOTE_STATUS_EXPIRED: &str = "expired";
+
+pub const MAX_QUOTE_TITLE_LENGTH: usize = 200;
+pub const MAX_QUOTE_ITEMS: usize = 100;
+pub const MAX_DECLINE_REASON_LENGTH: usize = 1000;
+
+/// Quote header
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Quote {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub customer_id: Uuid,
+    pub quote_number: String,
+    pub title: String,
+    pub note: Option<String>,
+    pub currency: String,
+    pub valid_until: NaiveDate,
+    pub status: String,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub decline_reason: Option<String>,
+    /// Work order (planned action) created when the quote was accepted
+    pub planned_action_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Quote {
+    /// Status as the customer sees it: a sent quote past its validity is expired
+    pub fn effective_status(&self, today: NaiveDate) -> &str {
+        if self.status == QUOTE_STATUS_SENT && self.valid_until < today {
+            QUOTE_STATUS_EXPIRED
+        } else {
+            &self.status
+        }
+    }
+}
+
+/// Quote line
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteItem {
+    pub id: Uuid,
+    pub quote_id: Uuid,
+    pub position: i32,
+    pub catalog_item_id: Option<Uuid>,
+    pub name: String,
+    pub unit: String,
+    pub quantity: f64,
+    pub unit_price_minor: i64,
+    pub vat_rate: i32,
+    pub work_type: Option<WorkType>,
+}
+
+/// Quote line as entered; a catalog item fills in what is left out
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteItemInput {
+    pub catalog_item_id: Option<Uuid>,
+    pub name: Option<String>,
+    pub unit: Option<String>,
+    pub quantity: f64,
+    pub unit_price_minor: Option<i64>,
+    pub vat_rate: Option<i32>,
+    pub work_type: Option<WorkType>,
+}
+
+/// Quote line ready to store
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedQuoteItem {
+    pub catalog_item_id: Option<Uuid>,
+    pub name: String,
+    pub unit: String,
+    pub quantity: f64,
+    pub unit_price_minor: i64,
+    pub vat_rate: i32,
+    pub work_type: Option<WorkType>,
+}
+
+/// Totals of a quote
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteTotals {
+    pub net_minor: i64,
+    pub vat_minor: i64,
+    pub gross_minor: i64,
+}
+
+/// Quote with its lines and totals
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteDetail {
+    #[serde(flatten)]
+    pub quote: Quote,
+    pub items: Vec<QuoteItem>,
+    pub totals: QuoteTotals,
+}
+
+/// NATS: sazinka.quote.create
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateQuoteRequest {
+    pub customer_id: Uuid,
+    pub title: String,
+    pub note: Option<String>,
+    /// Defaults to 30 days from today
+    pub valid_until: Option<NaiveDate>,
+    pub items: Vec<QuoteItemInput>,
+}
+
+/// NATS: sazinka.quote.update (drafts only)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateQuoteRequest {
+    pub id: Uuid,
+    pub title: String,
+    pub note: Option<String>,
+    pub valid_until: NaiveDate,
+    pub items: Vec<QuoteItemInput>,
+}
+
+/// NATS: sazinka.quote.get / delete / send / pdf
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteIdRequest {
+    pub id: Uuid,
+}
+
+/// NATS: sazinka.quote.list
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListQuotesRequest {
+    pub customer_id: Option<Uuid>,
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Quote list row
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteListItem {
+    pub id: Uuid,
+    pub customer_id: Uuid,
+    pub customer_name: Option<String>,
+    pub quote_number: String,
+    pub title: String,
+    pub currency: String,
+    pub valid_until: NaiveDate,
+    pub status: String,
+    pub net_minor: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListQuotesResponse {
+    pub items: Vec<QuoteListItem>,
+    pub total: i64,
+}
+
+/// Reply of sazinka.quote.send
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteLinkResponse {
+    /// Acceptance link for the customer
+    pub url: String,
+    pub valid_until: NaiveDate,
+}
+
+/// Reply of sazinka.quote.pdf
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotePdfResponse {
+    pub filename: String,
+    /// Base64 encoded PDF
+    pub content_base64: String,
+}
+
+/// NATS: sazinka.portal.quote.get (public, no login)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortalQuoteRequest {
+    pub token: String,
+}
+
+/// NATS: sazinka.portal.quote.decide (public, no login)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortalQuoteDecisionRequest {
+    pub token: String,
+    pub accept: bool,
+    pub reason: Option<String>,
+}
+
+/// A quote as shown to the customer
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortalQuoteResponse {
+    pub quote_number: String,
+    pub title: String,
+    pub note: Option<String>,
+    pub company_name: String,
+    pub currency: String,
+    pub valid_until: NaiveDate,
+    pub status: String,
+    pub items: Vec<PortalQuoteItem>,
+    pub totals: QuoteTotals,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortalQuoteItem {
+    pub name: String,
+    pub unit: String,
+    pub quantity: f64,
+    pub unit_price_minor: i64,
+    pub vat_rate: i32,
+}
+
+impl From<&QuoteItem> for PortalQuoteItem {
+    fn from(item: &QuoteItem) -> Self {
+        Self {
+            name: item.name.clone(),
+            unit: item.unit.clone(),
+            quantity: item.quantity,
+            unit_price_minor: item.unit_price_minor,
+            vat_rate: item.vat_rate,
+        }
+    }
+}
+
+/// Validate the quote header fields shared by create and update
+pub fn validate_quote_header(title: &str, items: &[QuoteItemInput], valid_until: NaiveDate, today: NaiveDate) -> Result<(), String> {
+    let title = title.trim();
+    if title.is_empty() || title.chars().count() > MAX_QUOTE_TITLE_LENGTH {
+        return Err(format!("title must be 1-{} characters", MAX_QUOTE_TITLE_LENGTH));
+    }
+    if items.is_empty() || items.len() > MAX_QUOTE_ITEMS {
+        return Err(format!("A quote needs 1-{} items", MAX_QUOTE_ITEMS));
+    }
+    if valid_until < today {
+        return Err("validUntil must not be in the past".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(status: &str, valid_until: NaiveDate) -> Quote {
+        Quote {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            customer_id: Uuid::nil(),
+            quote_number: "N2026-0001".to_string(),
+            title: "Výměna kotle".to_string(),
+            note: None,
+            currency: "CZK".to_string(),
+            valid_until,
+            status: status.to_string(),
+            sent_at: None,
+            decided_at: None,
+            decline_reason: None,
+            planned_action_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_effective_status() {
+        let day = NaiveDate::from_ymd_opt(2026, 5, 10).unwrap();
+        let next = day.succ_opt().unwrap();
+        assert_eq!(quote(QUOTE_STATUS_SENT, day).effective_status(day), QUOTE_STATUS_SENT);
+        assert_eq!(quote(QUOTE_STATUS_SENT, day).effective_status(next), QUOTE_STATUS_EXPIRED);
+        assert_eq!(quote(QUOTE_STATUS_ACCEPTED, day).effective_status(next), QUOTE_STATUS_ACCEPTED);
+    }
+
+    #[test]
+    fn test_validate_quote_header() {
+        let today = NaiveDate::from_ymd_opt(2026, 5, 10).unwrap();
+        let items = vec![QuoteItemInput { quantity: 1.0, ..Default::default() }];
+        assert!(validate_quote_header("Nabídka", &items, today, today).is_ok());
+        assert!(validate_quote_header(" ", &items, today, today).is_err());
+        assert!(validate_quote_header("Nabídka", &[], today, today).is_err());
+        assert!(validate_quote_header("Nabídka", &items, today.pred_opt().unwrap(), today).is_err());
+    }
+}
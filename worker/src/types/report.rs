@@ -0,0 +1,342 @@
+#![allow(dead_code)]
+//! Reporting types (capacity planning, summaries)
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Request for the seasonal capacity forecast
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CapacityForecastRequest {
+    /// Start of the forecast (defaults to the Monday of the current week)
+    pub from_date: Option<NaiveDate>,
+    /// Number of weeks to project (defaults to 52, max 104)
+    pub weeks: Option<u32>,
+    /// Override for stops per crew per day (defaults to the user's max_revisions_per_day)
+    pub stops_per_day: Option<i32>,
+    /// Override for working days per week (defaults to 5)
+    pub working_days_per_week: Option<u32>,
+}
+
+/// Projection source for one device: when it is next due and how often it recurs
+#[derive(Debug, Clone, FromRow)]
+pub struct DueProjectionSource {
+    pub device_id: Uuid,
+    pub next_due_date: Option<NaiveDate>,
+    pub interval_months: Option<i32>,
+}
+
+/// One week of the capacity forecast
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CapacityForecastWeek {
+    /// Monday of the week
+    pub week_start: NaiveDate,
+    /// Revisions projected to fall due in this week
+    pub due_count: i64,
+    /// Overdue revisions carried into this week (only the first week)
+    pub backlog_count: i64,
+    /// Visits the crews can handle in this week
+    pub capacity: i64,
+    /// (due + backlog) / capacity × 100
+    pub utilization_percent: f64,
+    pub is_over_capacity: bool,
+}
+
+/// Response for the seasonal capacity forecast
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapacityForecastResponse {
+    pub weeks: Vec<CapacityForecastWeek>,
+    pub crew_count: i64,
+    pub stops_per_day: i32,
+    pub working_days_per_week: u32,
+    pub total_due: i64,
+    pub total_capacity: i64,
+    pub over_capacity_weeks: u32,
+}
+
+/// Request for the acquisition source report
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AcquisitionSourceReportRequest {
+    /// First day of the period (defaults to one year before `to_date`)
+    pub from_date: Option<NaiveDate>,
+    /// Last day of the period (defaults to today)
+    pub to_date: Option<NaiveDate>,
+}
+
+/// Customer and job counts of one source in the period ("" = unknown source)
+#[derive(Debug, Clone, FromRow)]
+pub struct AcquisitionSourceCounts {
+    pub source: String,
+    pub new_customers: i64,
+    pub customers_with_jobs: i64,
+    pub completed_visits: i64,
+}
+
+/// Billed revenue of one source in one currency ("" = unknown source)
+#[derive(Debug, Clone, FromRow)]
+pub struct AcquisitionSourceRevenue {
+    pub source: String,
+    pub currency: String,
+    pub amount_minor: i64,
+}
+
+/// Revenue in one currency; currencies are never summed together
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrencyAmount {
+    pub currency: String,
+    pub amount_minor: i64,
+}
+
+/// One acquisition source of the report
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AcquisitionSourceReportRow {
+    /// None for customers without a known source
+    pub source: Option<String>,
+    /// Customers created in the period
+    pub new_customers: i64,
+    /// Customers with a completed visit in the period
+    pub customers_with_jobs: i64,
+    pub completed_visits: i64,
+    /// Net amount of the work items billed on those visits
+    pub revenue: Vec<CurrencyAmount>,
+}
+
+/// Response for the acquisition source report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcquisitionSourceReportResponse {
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub sources: Vec<AcquisitionSourceReportRow>,
+}
+
+/// Request for the lead conversion funnel
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LeadFunnelRequest {
+    /// First day leads entered the pipeline (defaults to one year before `to_date`)
+    pub from_date: Option<NaiveDate>,
+    /// Last day leads entered the pipeline (defaults to today)
+    pub to_date: Option<NaiveDate>,
+}
+
+/// Funnel counts of one source ("" = unknown source)
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct LeadFunnelCounts {
+    pub source: String,
+    pub leads: i64,
+    pub contacted: i64,
+    pub quoted: i64,
+    pub won: i64,
+    pub lost: i64,
+}
+
+/// Funnel of one acquisition source, or of all leads
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LeadFunnelRow {
+    /// None for leads without a known source (and for the total)
+    pub source: Option<String>,
+    /// Leads that entered the pipeline in the period
+    pub leads: i64,
+    pub contacted: i64,
+    pub quoted: i64,
+    pub won: i64,
+    pub lost: i64,
+    /// Won leads / all leads, 0-1
+    pub conversion_rate: f64,
+}
+
+/// Response for the lead conversion funnel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeadFunnelResponse {
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub total: LeadFunnelRow,
+    pub sources: Vec<LeadFunnelRow>,
+}
+
+/// NATS: sazinka.report.restock
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestockReportRequest {
+    /// Limit to one crew vehicle
+    pub crew_id: Option<Uuid>,
+    /// Limit to one depot
+    pub depot_id: Option<Uuid>,
+    /// Consumption window the restock should cover (defaults to 30 days)
+    pub days: Option<i64>,
+}
+
+/// Stock level with its recent consumption
+#[derive(Debug, Clone, FromRow)]
+pub struct RestockCandidate {
+    pub catalog_item_id: Uuid,
+    pub item_name: String,
+    pub unit: String,
+    pub crew_id: Option<Uuid>,
+    pub depot_id: Option<Uuid>,
+    pub location_name: String,
+    pub quantity: f64,
+    pub min_quantity: f64,
+    /// Quantity consumed within the window
+    pub consumed: f64,
+}
+
+/// A part to bring to a location
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RestockRow {
+    pub catalog_item_id: Uuid,
+    pub item_name: String,
+    pub unit: String,
+    pub crew_id: Option<Uuid>,
+    pub depot_id: Option<Uuid>,
+    pub location_name: String,
+    pub quantity: f64,
+    pub min_quantity: f64,
+    pub consumed: f64,
+    /// Already at or below the reorder point
+    pub low: bool,
+    /// Whole units that cover the window's consumption above the reorder point
+    pub suggested_quantity: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestockReportResponse {
+    pub days: i64,
+    pub rows: Vec<RestockRow>,
+}
+
+/// NATS: sazinka.report.weather_outcomes
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WeatherOutcomeReportRequest {
+    /// First visit day (defaults to one year before `to_date`)
+    pub from_date: Option<NaiveDate>,
+    /// Last visit day (defaults to today)
+    pub to_date: Option<NaiveDate>,
+}
+
+/// Outcomes of the completed and cancelled visits of one bucket (a weather
+/// condition or a temperature band; "" = no weather recorded)
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct WeatherOutcomeCounts {
+    pub bucket: String,
+    pub visits: i64,
+    pub completed: i64,
+    /// Completed with result failed or customer_absent
+    pub failed: i64,
+    pub cancelled: i64,
+    pub avg_arrival_delay_minutes: Option<f64>,
+}
+
+/// Report row of one bucket
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WeatherOutcomeRow {
+    /// Condition (clear, cloudy, fog, rain, snow, storm) or temperature band
+    /// (below_minus_5, minus_5_to_0, 0_to_5, above_5); None = no weather recorded
+    pub bucket: Option<String>,
+    pub visits: i64,
+    pub completed: i64,
+    pub failed: i64,
+    pub cancelled: i64,
+    /// Failed / completed visits, 0-1
+    pub failure_rate: f64,
+    /// Cancelled / all visits, 0-1
+    pub cancellation_rate: f64,
+    /// Mean minutes the crew arrived after the planned stop time
+    pub avg_arrival_delay_minutes: Option<f64>,
+    /// Extra minutes per stop to plan in these conditions (delay above the
+    /// clear-weather baseline)
+    pub suggested_buffer_minutes: i64,
+}
+
+/// Response for sazinka.report.weather_outcomes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeatherOutcomeReportResponse {
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    /// Mean arrival delay in clear weather (all weather when no clear days)
+    pub baseline_delay_minutes: Option<f64>,
+    pub conditions: Vec<WeatherOutcomeRow>,
+    pub temperature_bands: Vec<WeatherOutcomeRow>,
+}
+
+/// NATS: sazinka.report.workload
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkloadReportRequest {
+    /// First visit day (defaults to the first day of `to_date`'s month)
+    pub from_date: Option<NaiveDate>,
+    /// Last visit day (defaults to today)
+    pub to_date: Option<NaiveDate>,
+}
+
+/// Completed visits and recorded minutes of one crew (None = no crew)
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkloadRow {
+    pub crew_id: Option<Uuid>,
+    pub crew_name: Option<String>,
+    pub visits: i64,
+    /// Visits with a recorded on-site time
+    pub timed_visits: i64,
+    pub travel_minutes: i64,
+    pub on_site_minutes: i64,
+    /// Travel plus on-site minutes, the base for payroll
+    pub worked_minutes: i64,
+    /// Visits with a hand-entered travel or on-site time
+    pub overridden_visits: i64,
+}
+
+/// Response for sazinka.report.workload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkloadReportResponse {
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub crews: Vec<WorkloadRow>,
+    /// All crews together (crew fields empty)
+    pub total: WorkloadRow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capacity_forecast_request_defaults() {
+        let request: CapacityForecastRequest = serde_json::from_str("{}").unwrap();
+        assert!(request.from_date.is_none());
+        assert!(request.weeks.is_none());
+        assert!(request.stops_per_day.is_none());
+    }
+
+    #[test]
+    fn test_capacity_forecast_week_serializes_camel_case() {
+        let week = CapacityForecastWeek {
+            week_start: NaiveDate::from_ymd_opt(2026, 10, 5).unwrap(),
+            due_count: 70,
+            backlog_count: 0,
+            capacity: 60,
+            utilization_percent: 116.7,
+            is_over_capacity: true,
+        };
+        let json = serde_json::to_string(&week).unwrap();
+        assert!(json.contains("\"weekStart\":\"2026-10-05\""));
+        assert!(json.contains("\"isOverCapacity\":true"));
+        assert!(json.contains("\"utilizationPercent\":116.7"));
+    }
+}
@@ -0,0 +1,234 @@
+#![allow(dead_code)]
+//! Customer reschedule request types
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Request states
+pub const RESCHEDULE_PENDING: &str = "pending";
+pub const RESCHEDULE_ACCEPTED: &str = "accepted";
+pub const RESCHEDULE_DECLINED: &str = "declined";
+
+pub const RESCHEDULE_STATES: &[&str] = &[RESCHEDULE_PENDING, RESCHEDULE_ACCEPTED, RESCHEDULE_DECLINED];
+
+/// How far ahead a customer may ask to move a visit
+pub const MAX_RESCHEDULE_HORIZON_DAYS: i64 = 180;
+/// Longest customer note kept
+pub const MAX_RESCHEDULE_NOTE_LEN: usize = 1000;
+
+/// Check a customer's requested date and preferred window
+pub fn validate_requested_slot(
+    requested_date: NaiveDate,
+    preferred_start: Option<NaiveTime>,
+    preferred_end: Option<NaiveTime>,
+    today: NaiveDate,
+) -> Result<(), &'static str> {
+    if requested_date < today {
+        return Err("Requested date is in the past");
+    }
+    if (requested_date - today).num_days() > MAX_RESCHEDULE_HORIZON_DAYS {
+        return Err("Requested date is too far ahead");
+    }
+    if let (Some(start), Some(end)) = (preferred_start, preferred_end) {
+        if end <= start {
+            return Err("Preferred time window must end after it starts");
+        }
+    }
+    Ok(())
+}
+
+/// Request to create a reschedule link for a visit
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRescheduleLinkRequest {
+    pub visit_id: Uuid,
+}
+
+/// Link to share with the end customer
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RescheduleLinkResponse {
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Portal request identified by the link token
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortalTokenRequest {
+    pub token: String,
+}
+
+/// Customer asks to move the visit
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitRescheduleRequest {
+    pub token: String,
+    pub requested_date: NaiveDate,
+    pub preferred_time_start: Option<NaiveTime>,
+    pub preferred_time_end: Option<NaiveTime>,
+    pub note: Option<String>,
+}
+
+/// A reschedule request
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct RescheduleRequest {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub visit_id: Uuid,
+    pub customer_id: Uuid,
+    #[sqlx(default)]
+    pub customer_name: Option<String>,
+    pub original_date: NaiveDate,
+    pub original_time_start: Option<NaiveTime>,
+    pub original_time_end: Option<NaiveTime>,
+    pub requested_date: NaiveDate,
+    pub preferred_time_start: Option<NaiveTime>,
+    pub preferred_time_end: Option<NaiveTime>,
+    pub customer_note: Option<String>,
+    /// Slot engine suggestions for the requested date, best first
+    pub suggested_slots: serde_json::Value,
+    pub status: String,
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub decision_note: Option<String>,
+    pub new_date: Option<NaiveDate>,
+    pub new_time_start: Option<NaiveTime>,
+    pub new_time_end: Option<NaiveTime>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What the end customer sees behind the link
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortalVisitResponse {
+    pub business_name: Option<String>,
+    pub scheduled_date: NaiveDate,
+    pub scheduled_time_start: Option<NaiveTime>,
+    pub scheduled_time_end: Option<NaiveTime>,
+    pub status: String,
+    /// Latest request for this visit, with its outcome
+    pub latest_request: Option<PortalRescheduleStatus>,
+}
+
+/// Outcome of a request as shown to the end customer
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortalRescheduleStatus {
+    pub requested_date: NaiveDate,
+    pub status: String,
+    pub new_date: Option<NaiveDate>,
+    pub new_time_start: Option<NaiveTime>,
+    pub new_time_end: Option<NaiveTime>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&RescheduleRequest> for PortalRescheduleStatus {
+    fn from(req: &RescheduleRequest) -> Self {
+        Self {
+            requested_date: req.requested_date,
+            status: req.status.clone(),
+            new_date: req.new_date,
+            new_time_start: req.new_time_start,
+            new_time_end: req.new_time_end,
+            created_at: req.created_at,
+        }
+    }
+}
+
+/// Request to list reschedule requests
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListRescheduleRequestsRequest {
+    pub status: Option<String>,
+    pub visit_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// Reschedule requests, newest first
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListRescheduleRequestsResponse {
+    pub items: Vec<RescheduleRequest>,
+}
+
+/// Dispatcher accepts a request. Date and times default to the requested
+/// date and the customer's preferred window.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptRescheduleRequest {
+    pub id: Uuid,
+    pub scheduled_date: Option<NaiveDate>,
+    pub scheduled_time_start: Option<NaiveTime>,
+    pub scheduled_time_end: Option<NaiveTime>,
+    pub crew_id: Option<Uuid>,
+    pub note: Option<String>,
+}
+
+/// Dispatcher declines a request
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeclineRescheduleRequest {
+    pub id: Uuid,
+    pub note: Option<String>,
+}
+
+/// Outcome counts for a period
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RescheduleStatsResponse {
+    pub pending: i64,
+    pub accepted: i64,
+    pub declined: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_validate_requested_slot_rejects_past() {
+        let today = date(2026, 3, 10);
+        assert!(validate_requested_slot(date(2026, 3, 9), None, None, today).is_err());
+        assert!(validate_requested_slot(today, None, None, today).is_ok());
+    }
+
+    #[test]
+    fn test_validate_requested_slot_horizon() {
+        let today = date(2026, 3, 10);
+        let limit = today + chrono::Duration::days(MAX_RESCHEDULE_HORIZON_DAYS);
+        assert!(validate_requested_slot(limit, None, None, today).is_ok());
+        assert!(validate_requested_slot(limit + chrono::Duration::days(1), None, None, today).is_err());
+    }
+
+    #[test]
+    fn test_validate_requested_slot_window() {
+        let today = date(2026, 3, 10);
+        assert!(validate_requested_slot(today, Some(time(9, 0)), Some(time(12, 0)), today).is_ok());
+        assert!(validate_requested_slot(today, Some(time(12, 0)), Some(time(9, 0)), today).is_err());
+        // An open-ended preference is fine
+        assert!(validate_requested_slot(today, Some(time(12, 0)), None, today).is_ok());
+    }
+
+    #[test]
+    fn test_submit_request_camel_case() {
+        let req: SubmitRescheduleRequest = serde_json::from_str(
+            r#"{"token":"abc","requestedDate":"2026-03-12","preferredTimeStart":"09:00:00","note":"after 9 please"}"#,
+        )
+        .unwrap();
+        assert_eq!(req.requested_date, date(2026, 3, 12));
+        assert_eq!(req.preferred_time_start, Some(time(9, 0)));
+        assert!(req.preferred_time_end.is_none());
+    }
+}
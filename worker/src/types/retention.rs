@@ -0,0 +1,172 @@
+#![allow(dead_code)]
+//! Document retention policy types
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Completed revisions (the inspection reports), counted from completion
+pub const RETENTION_ENTITY_REVISION: &str = "revision";
+/// Completed visits, counted from the visit date
+pub const RETENTION_ENTITY_VISIT: &str = "visit";
+/// Customer communication, counted from the contact date
+pub const RETENTION_ENTITY_COMMUNICATION: &str = "communication";
+pub const RETENTION_ENTITY_TYPES: &[&str] =
+    &[RETENTION_ENTITY_REVISION, RETENTION_ENTITY_VISIT, RETENTION_ENTITY_COMMUNICATION];
+
+/// Upper bound for retention_months (matches the DB constraint)
+pub const MAX_RETENTION_MONTHS: i32 = 1200;
+
+/// Period used when the account has not set one
+pub fn default_retention_months(entity_type: &str) -> i32 {
+    match entity_type {
+        RETENTION_ENTITY_REVISION => 120,
+        RETENTION_ENTITY_VISIT => 60,
+        _ => 36,
+    }
+}
+
+/// Shortest period an account may set; revision reports are legal
+/// documents and cannot be shortened below five years
+pub fn min_retention_months(entity_type: &str) -> i32 {
+    match entity_type {
+        RETENTION_ENTITY_REVISION => 60,
+        _ => 12,
+    }
+}
+
+/// Validation for policy.set payloads
+pub fn validate_policy(entity_type: &str, retention_months: i32) -> Result<(), String> {
+    if !RETENTION_ENTITY_TYPES.contains(&entity_type) {
+        return Err(format!("entityType must be one of: {}", RETENTION_ENTITY_TYPES.join(", ")));
+    }
+    let min = min_retention_months(entity_type);
+    if !(min..=MAX_RETENTION_MONTHS).contains(&retention_months) {
+        return Err(format!("retentionMonths must be between {} and {}", min, MAX_RETENTION_MONTHS));
+    }
+    Ok(())
+}
+
+/// Retention policy of an account for one entity type
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    pub entity_type: String,
+    pub retention_months: i32,
+    pub delete_after_expiry: bool,
+    /// False when the account set the policy itself
+    pub is_default: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl RetentionPolicy {
+    /// Built-in policy of an entity type
+    pub fn default_for(entity_type: &str) -> Self {
+        Self {
+            entity_type: entity_type.to_string(),
+            retention_months: default_retention_months(entity_type),
+            delete_after_expiry: false,
+            is_default: true,
+            updated_at: None,
+        }
+    }
+}
+
+/// Policy to apply in the sweep
+#[derive(Debug, Clone, FromRow)]
+pub struct ExpiringPolicy {
+    pub user_id: Uuid,
+    pub entity_type: String,
+    pub retention_months: i32,
+}
+
+/// Counts of one entity type for the retention report
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct RetentionCounts {
+    pub retained_count: i64,
+    pub expired_count: i64,
+    /// Reference date of the oldest record still inside its period
+    pub oldest_retained: Option<NaiveDate>,
+}
+
+/// Record removed by the retention sweep
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionDeletion {
+    pub id: Uuid,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub reference_date: NaiveDate,
+    pub retention_months: i32,
+    pub deleted_at: DateTime<Utc>,
+}
+
+// ============================================================
+// Request / response types (NATS payloads)
+// ============================================================
+
+/// NATS: sazinka.retention.policy.set
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetRetentionPolicyRequest {
+    pub entity_type: String,
+    pub retention_months: i32,
+    #[serde(default)]
+    pub delete_after_expiry: bool,
+}
+
+/// Response for sazinka.retention.policy.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListRetentionPoliciesResponse {
+    pub policies: Vec<RetentionPolicy>,
+}
+
+/// One entity type in the retention report
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionReportEntry {
+    pub policy: RetentionPolicy,
+    /// Records that cannot be deleted yet
+    pub retained_count: i64,
+    /// Records past their period (removed by the next sweep when
+    /// deleteAfterExpiry is on)
+    pub expired_count: i64,
+    /// When the oldest retained record leaves its period
+    pub next_expiry: Option<NaiveDate>,
+    pub deleted_last_year: i64,
+}
+
+/// Response for sazinka.retention.report
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionReportResponse {
+    pub generated_at: DateTime<Utc>,
+    pub entries: Vec<RetentionReportEntry>,
+    /// Latest sweep deletions, newest first
+    pub recent_deletions: Vec<RetentionDeletion>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_policy() {
+        assert!(validate_policy(RETENTION_ENTITY_REVISION, 120).is_ok());
+        assert!(validate_policy(RETENTION_ENTITY_REVISION, 24).is_err());
+        assert!(validate_policy(RETENTION_ENTITY_COMMUNICATION, 24).is_ok());
+        assert!(validate_policy(RETENTION_ENTITY_VISIT, 1201).is_err());
+        assert!(validate_policy("customer", 120).is_err());
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        for entity_type in RETENTION_ENTITY_TYPES {
+            let policy = RetentionPolicy::default_for(entity_type);
+            assert!(validate_policy(entity_type, policy.retention_months).is_ok());
+            assert!(!policy.delete_after_expiry);
+        }
+    }
+}
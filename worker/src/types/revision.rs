@@ -32,6 +32,9 @@ pub struct Revision {
     pub assigned_crew_id: Option<Uuid>,
     #[sqlx(default)]
     pub route_order: Option<i32>,
+    // Document number from the numbering series, assigned on completion
+    #[sqlx(default)]
+    pub document_number: Option<String>,
     // Device info (joined from devices table)
     #[sqlx(default)]
     pub device_name: Option<String>,
@@ -151,6 +154,9 @@ pub struct CompleteRevisionRequest {
 #[serde(rename_all = "camelCase")]
 pub struct ListRevisionsRequest {
     pub customer_id: Option<Uuid>,
+    /// With customer_id: also list revisions of the customer's branches
+    #[serde(default)]
+    pub include_branches: bool,
     pub device_id: Option<Uuid>,
     pub status: Option<String>,
     pub from_date: Option<NaiveDate>,
@@ -185,6 +191,15 @@ pub struct RevisionIdRequest {
     pub id: Uuid,
 }
 
+/// Reply of sazinka.revision.report_pdf
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionReportPdfResponse {
+    pub filename: String,
+    /// Base64 encoded PDF
+    pub content_base64: String,
+}
+
 /// Request to get suggested revisions for route planning
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -0,0 +1,198 @@
+#![allow(dead_code)]
+//! Revision document numbering types
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Period of a series that never resets
+pub const NUMBERING_PERIOD_NONE: i32 = 0;
+
+/// Format a document number, e.g. "REV-2025/0042" or "REV-0042" without a yearly reset
+pub fn format_document_number(prefix: &str, padding: i16, year: Option<i32>, sequence: i32) -> String {
+    let width = padding.max(1) as usize;
+    match year {
+        Some(year) => format!("{}{}/{:0width$}", prefix, year, sequence, width = width),
+        None => format!("{}{:0width$}", prefix, sequence, width = width),
+    }
+}
+
+/// Letters of verification codes; no 0/O, 1/I/L that are misread on paper
+const VERIFICATION_CODE_ALPHABET: &[u8] = b"23456789ABCDEFGHJKMNPQRSTUVWXYZ";
+const VERIFICATION_CODE_LENGTH: usize = 8;
+
+pub const CERTIFICATE_STATUS_VALID: &str = "valid";
+pub const CERTIFICATE_STATUS_EXPIRED: &str = "expired";
+pub const CERTIFICATE_STATUS_VOIDED: &str = "voided";
+
+/// Random verification code of a newly issued number
+pub fn generate_verification_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..VERIFICATION_CODE_LENGTH)
+        .map(|_| VERIFICATION_CODE_ALPHABET[rng.gen_range(0..VERIFICATION_CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+/// Code as printed on the report, e.g. "AB7K-M2QX"
+pub fn format_verification_code(code: &str) -> String {
+    let (head, tail) = code.split_at(code.len() / 2);
+    format!("{}-{}", head, tail)
+}
+
+/// Code as typed by a visitor: case, spaces and dashes don't matter
+pub fn normalize_verification_code(input: &str) -> String {
+    input
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Whether a typed code matches the stored one, compared in constant time
+pub fn verification_code_matches(stored: &str, typed: &str) -> bool {
+    let typed = normalize_verification_code(typed);
+    stored.len() == typed.len() && stored.bytes().zip(typed.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Status of a certificate shown on the verification page
+pub fn certificate_status(voided: bool, valid_until: Option<NaiveDate>, today: NaiveDate) -> &'static str {
+    if voided {
+        CERTIFICATE_STATUS_VOIDED
+    } else if valid_until.is_some_and(|until| until < today) {
+        CERTIFICATE_STATUS_EXPIRED
+    } else {
+        CERTIFICATE_STATUS_VALID
+    }
+}
+
+/// Sequences missing between 1 and the highest issued one
+pub fn find_sequence_gaps(sequences: &[i32]) -> Vec<i32> {
+    let Some(&max) = sequences.iter().max() else {
+        return Vec::new();
+    };
+    let issued: std::collections::HashSet<i32> = sequences.iter().copied().collect();
+    (1..max).filter(|seq| !issued.contains(seq)).collect()
+}
+
+/// A number in the register of issued document numbers
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuedDocumentNumber {
+    pub id: Uuid,
+    pub period_year: i32,
+    pub sequence: i32,
+    pub document_number: String,
+    pub revision_id: Option<Uuid>,
+    pub issued_at: DateTime<Utc>,
+    pub voided_at: Option<DateTime<Utc>>,
+    pub void_reason: Option<String>,
+}
+
+/// Issued number found by the verification page, with what it certifies
+#[derive(Debug, Clone, FromRow)]
+pub struct CertificateRecord {
+    pub user_id: Uuid,
+    pub document_number: String,
+    pub verification_code: String,
+    pub issued_at: DateTime<Utc>,
+    pub voided_at: Option<DateTime<Utc>>,
+    /// None once the revision was deleted
+    pub device_type: Option<String>,
+    pub result: Option<String>,
+    /// Issue date plus the device's revision interval
+    pub valid_until: Option<NaiveDate>,
+}
+
+/// NATS: sazinka.portal.certificate.verify (public, no login)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyCertificateRequest {
+    pub document_number: String,
+    pub verification_code: String,
+}
+
+/// What the verification page shows; deliberately no customer data
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyCertificateResponse {
+    pub document_number: String,
+    /// valid, expired or voided
+    pub status: String,
+    pub issued_on: NaiveDate,
+    pub valid_until: Option<NaiveDate>,
+    /// Label of the device type
+    pub device_type: Option<String>,
+    /// passed, failed or conditional
+    pub result: Option<String>,
+}
+
+/// Request for the register of one series period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDocumentNumbersRequest {
+    /// Calendar year, or 0 for a series that never resets
+    pub period_year: i32,
+}
+
+/// Register of a series period with any sequences missing from it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDocumentNumbersResponse {
+    pub items: Vec<IssuedDocumentNumber>,
+    pub voided_count: i64,
+    pub gaps: Vec<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_with_year() {
+        assert_eq!(format_document_number("REV-", 4, Some(2025), 42), "REV-2025/0042");
+    }
+
+    #[test]
+    fn test_format_without_year() {
+        assert_eq!(format_document_number("", 6, None, 7), "000007");
+    }
+
+    #[test]
+    fn test_format_longer_than_padding() {
+        assert_eq!(format_document_number("", 2, None, 1234), "1234");
+    }
+
+    #[test]
+    fn test_verification_code() {
+        let code = generate_verification_code();
+        assert_eq!(code.len(), VERIFICATION_CODE_LENGTH);
+        assert!(code.bytes().all(|b| VERIFICATION_CODE_ALPHABET.contains(&b)));
+        assert_ne!(code, generate_verification_code());
+
+        let printed = format_verification_code("AB7KM2QX");
+        assert_eq!(printed, "AB7K-M2QX");
+        assert!(verification_code_matches("AB7KM2QX", &printed));
+        assert!(verification_code_matches("AB7KM2QX", " ab7k m2qx "));
+        assert!(!verification_code_matches("AB7KM2QX", "AB7KM2Q"));
+        assert!(!verification_code_matches("AB7KM2QX", "AB7KM2QY"));
+    }
+
+    #[test]
+    fn test_certificate_status() {
+        let today = NaiveDate::from_ymd_opt(2026, 6, 1).unwrap();
+        let until = NaiveDate::from_ymd_opt(2026, 5, 31);
+        assert_eq!(certificate_status(false, None, today), CERTIFICATE_STATUS_VALID);
+        assert_eq!(certificate_status(false, NaiveDate::from_ymd_opt(2026, 6, 1), today), CERTIFICATE_STATUS_VALID);
+        assert_eq!(certificate_status(false, until, today), CERTIFICATE_STATUS_EXPIRED);
+        assert_eq!(certificate_status(true, None, today), CERTIFICATE_STATUS_VOIDED);
+    }
+
+    #[test]
+    fn test_gaps() {
+        assert_eq!(find_sequence_gaps(&[1, 2, 5, 3]), vec![4]);
+        assert!(find_sequence_gaps(&[]).is_empty());
+        assert!(find_sequence_gaps(&[1, 2, 3]).is_empty());
+    }
+}
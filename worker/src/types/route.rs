@@ -139,10 +139,59 @@ pub struct RoutePlanRequest {
     /// Fixed arrival buffer in minutes (default 0)
     #[serde(default)]
     pub arrival_buffer_fixed_minutes: f64,
+    /// Appointments already booked for a fixed time. The solver keeps them
+    /// and fits the candidates in `customer_ids` into the gaps.
+    #[serde(default)]
+    pub fixed_stops: Vec<FixedRouteStop>,
+    /// The shift starts no earlier than this (the rest of a day under way)
+    #[serde(default)]
+    pub not_before: Option<NaiveTime>,
+    /// Where the route ends, when it does not return to `start_location`
+    #[serde(default)]
+    pub end_location: Option<Coordinates>,
+    /// Plan no break, e.g. when it has already been taken today
+    #[serde(default)]
+    pub without_break: bool,
 }
 
 fn default_route_buffer_percent() -> f64 { 10.0 }
 
+/// A time-pinned appointment in a partial day plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixedRouteStop {
+    pub customer_id: Uuid,
+    /// Agreed arrival time
+    pub arrival_time: NaiveTime,
+    /// Length of the appointment; the default service duration if absent
+    #[serde(default)]
+    pub service_duration_minutes: Option<u32>,
+}
+
+impl RoutePlanRequest {
+    /// Candidate customers that are not already fixed appointments
+    pub fn floating_customer_ids(&self) -> Vec<Uuid> {
+        self.customer_ids
+            .iter()
+            .filter(|id| !self.fixed_stops.iter().any(|f| f.customer_id == **id))
+            .copied()
+            .collect()
+    }
+
+    /// Fixed appointments followed by the floating candidates
+    pub fn all_customer_ids(&self) -> Vec<Uuid> {
+        self.fixed_stops
+            .iter()
+            .map(|f| f.customer_id)
+            .chain(self.floating_customer_ids())
+            .collect()
+    }
+
+    pub fn fixed_stop(&self, customer_id: Uuid) -> Option<&FixedRouteStop> {
+        self.fixed_stops.iter().find(|f| f.customer_id == customer_id)
+    }
+}
+
 /// Working hours configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -160,6 +209,37 @@ impl Default for WorkingHours {
     }
 }
 
+/// Request to re-plan the rest of a saved route during the day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteReoptimizeLiveRequest {
+    pub route_id: Uuid,
+    /// Time the remaining stops are planned from
+    pub current_time: NaiveTime,
+    /// Technician's position; the last finished stop or the depot if absent
+    #[serde(default)]
+    pub current_position: Option<Coordinates>,
+    /// Route stops already done
+    #[serde(default)]
+    pub completed_stop_ids: Vec<Uuid>,
+    /// Route stops given up for today
+    #[serde(default)]
+    pub skipped_stop_ids: Vec<Uuid>,
+}
+
+/// Response of a live re-plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteReoptimizeLiveResponse {
+    pub route_id: Uuid,
+    /// Completed and skipped route stops, kept in their original order
+    pub locked_stop_ids: Vec<Uuid>,
+    /// Where the remaining stops are planned from
+    pub start_location: Coordinates,
+    /// Remaining stops from `start_location`, returning to the depot
+    pub plan: RoutePlanResponse,
+}
+
 /// Response from route planning
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -194,6 +274,105 @@ pub struct RoutePlanResponse {
     pub return_to_depot_duration_minutes: Option<i32>,
 }
 
+/// Request to propose routes mirroring the same week last year
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutePlanFromHistoryRequest {
+    /// Any day of the week to plan
+    pub date: NaiveDate,
+    /// Only routes of this crew
+    #[serde(default)]
+    pub crew_id: Option<Uuid>,
+}
+
+/// Last year's route mapped onto the planned week
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRouteProposal {
+    pub source_route_id: Uuid,
+    pub source_date: NaiveDate,
+    /// Same weekday in the planned week
+    pub date: NaiveDate,
+    pub crew_id: Option<Uuid>,
+    pub crew_name: Option<String>,
+    /// Customers of the old route whose revisions are due again
+    pub customer_ids: Vec<Uuid>,
+    /// Customers of the old route with nothing due, left out
+    pub not_due_customer_ids: Vec<Uuid>,
+    /// Solver result for the due customers; None when there was nothing to plan
+    pub plan: Option<RoutePlanResponse>,
+    /// Why no plan was made: NO_DUE_CUSTOMERS, NO_DEPOT or the solver error code
+    pub skipped_reason: Option<String>,
+}
+
+/// Proposed routes, nothing is saved until the dispatcher accepts them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutePlanFromHistoryResponse {
+    pub source_week_start: NaiveDate,
+    pub source_week_end: NaiveDate,
+    pub proposals: Vec<HistoryRouteProposal>,
+}
+
+/// Request to spread due revisions over the days of a range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutePlanMultidayRequest {
+    pub start_date: NaiveDate,
+    /// Inclusive
+    pub end_date: NaiveDate,
+    /// Crew whose shift hours and home depot apply
+    #[serde(default)]
+    pub crew_id: Option<Uuid>,
+    /// Revisions to plan; when empty, all unscheduled revisions due by
+    /// end_date
+    #[serde(default)]
+    pub revision_ids: Vec<Uuid>,
+    /// Start of every day; the crew's home depot or the primary depot if absent
+    #[serde(default)]
+    pub start_location: Option<Coordinates>,
+    #[serde(default)]
+    pub include_weekends: bool,
+}
+
+/// Unscheduled revision considered for multi-day planning
+#[derive(Debug, Clone, FromRow)]
+pub struct MultidayCandidate {
+    pub revision_id: Uuid,
+    pub customer_id: Uuid,
+    pub due_date: NaiveDate,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+}
+
+/// One day of a multi-day plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultidayRouteDay {
+    pub date: NaiveDate,
+    /// Customers planned on this day
+    pub customer_ids: Vec<Uuid>,
+    /// Revisions of those customers the day fulfils
+    pub revision_ids: Vec<Uuid>,
+    /// Customers planned after their revision's due date
+    pub late_customer_ids: Vec<Uuid>,
+    /// Solver result; None for days left empty
+    pub plan: Option<RoutePlanResponse>,
+    /// Why the day was not planned: the solver error code
+    pub skipped_reason: Option<String>,
+}
+
+/// Proposed routes for a date range, nothing is saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutePlanMultidayResponse {
+    pub days: Vec<MultidayRouteDay>,
+    /// Customers no day could take
+    pub unassigned_customer_ids: Vec<Uuid>,
+    /// Customers left out for missing coordinates
+    pub missing_coordinates_customer_ids: Vec<Uuid>,
+}
+
 /// A planned stop in the route
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -239,10 +418,209 @@ pub struct PlannedRouteStop {
     pub override_travel_duration_minutes: Option<i32>,
 }
 
+pub const MAX_STOP_NOTES_LENGTH: usize = 2000;
+pub const MAX_STOP_TASKS: usize = 30;
+pub const MAX_STOP_TASK_LENGTH: usize = 200;
+pub const MAX_BREAK_LOCATION_NAME_LENGTH: usize = 200;
+pub const MAX_BREAK_SUGGESTIONS: usize = 20;
+
+/// A checklist item of a route stop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteStopTask {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// Validate a stop's notes and checklist
+pub fn validate_stop_notes(notes: Option<&str>, tasks: &[RouteStopTask]) -> Result<(), &'static str> {
+    if notes.is_some_and(|n| n.chars().count() > MAX_STOP_NOTES_LENGTH) {
+        return Err("Stop notes must be at most 2000 characters");
+    }
+    if tasks.len() > MAX_STOP_TASKS {
+        return Err("A stop may have at most 30 tasks");
+    }
+    for task in tasks {
+        if task.text.trim().is_empty() {
+            return Err("Task text must not be empty");
+        }
+        if task.text.chars().count() > MAX_STOP_TASK_LENGTH {
+            return Err("Task text must be at most 200 characters");
+        }
+    }
+    Ok(())
+}
+
+/// Notes and checklist of one stop, for route.update and route.stop.note.update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRouteStopNotesRequest {
+    pub stop_id: Uuid,
+    /// New notes; unchanged when absent, cleared when empty
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// New checklist; unchanged when absent
+    #[serde(default)]
+    pub tasks: Option<Vec<RouteStopTask>>,
+}
+
+impl UpdateRouteStopNotesRequest {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        validate_stop_notes(self.notes.as_deref(), self.tasks.as_deref().unwrap_or_default())
+    }
+}
+
+/// A stop's notes and checklist after an update
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteStopNotes {
+    pub stop_id: Uuid,
+    pub notes: Option<String>,
+    pub tasks: sqlx::types::Json<Vec<RouteStopTask>>,
+}
+
+/// Kind of place suggested for a break
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BreakPlaceKind {
+    Restaurant,
+    Fuel,
+}
+
+impl BreakPlaceKind {
+    /// OSM `amenity` value of the kind
+    pub fn amenity(&self) -> &'static str {
+        match self {
+            BreakPlaceKind::Restaurant => "restaurant",
+            BreakPlaceKind::Fuel => "fuel",
+        }
+    }
+}
+
+/// Place a break is held at; a break without one is held at the previous stop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakLocation {
+    pub name: String,
+    pub kind: BreakPlaceKind,
+    pub lat: f64,
+    pub lng: f64,
+    pub address: Option<String>,
+}
+
+impl BreakLocation {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.name.trim().is_empty() || self.name.chars().count() > MAX_BREAK_LOCATION_NAME_LENGTH {
+            return Err("Break location name must have 1 to 200 characters");
+        }
+        if !(-90.0..=90.0).contains(&self.lat) || !(-180.0..=180.0).contains(&self.lng) {
+            return Err("Break location coordinates are out of range");
+        }
+        Ok(())
+    }
+}
+
+/// Request for sazinka.route.break.suggest: places for a break taken on the
+/// leg between two stops (or after the last one)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakSuggestRequest {
+    /// Stop the break follows
+    pub from: Coordinates,
+    /// Stop after the break; None when the break ends the route
+    pub to: Option<Coordinates>,
+    pub limit: Option<usize>,
+}
+
+impl BreakSuggestRequest {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let valid = |c: &Coordinates| (-90.0..=90.0).contains(&c.lat) && (-180.0..=180.0).contains(&c.lng);
+        if !valid(&self.from) || self.to.as_ref().is_some_and(|to| !valid(to)) {
+            return Err("Coordinates are out of range");
+        }
+        if self.limit.is_some_and(|limit| !(1..=MAX_BREAK_SUGGESTIONS).contains(&limit)) {
+            return Err("limit must be between 1 and 20");
+        }
+        Ok(())
+    }
+}
+
+/// A suggested break place
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakSuggestion {
+    #[serde(flatten)]
+    pub location: BreakLocation,
+    /// Extra straight-line distance compared to driving the leg directly
+    pub detour_km: f64,
+}
+
+/// Response of sazinka.route.break.suggest
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakSuggestResponse {
+    /// false when the user turned suggestions off; nothing is looked up then
+    pub enabled: bool,
+    pub suggestions: Vec<BreakSuggestion>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_plan_request_fixed_stops_take_precedence() {
+        let fixed = Uuid::new_v4();
+        let floating = Uuid::new_v4();
+        let request: RoutePlanRequest = serde_json::from_value(serde_json::json!({
+            "startLocation": { "lat": 50.0, "lng": 14.4 },
+            "customerIds": [floating, fixed],
+            "date": "2026-03-02",
+            "workingHours": null,
+            "crewId": null,
+            "fixedStops": [{ "customerId": fixed, "arrivalTime": "10:30:00", "serviceDurationMinutes": 45 }]
+        }))
+        .unwrap();
+
+        assert_eq!(request.floating_customer_ids(), vec![floating]);
+        assert_eq!(request.all_customer_ids(), vec![fixed, floating]);
+        assert_eq!(request.fixed_stop(fixed).and_then(|f| f.service_duration_minutes), Some(45));
+        assert!(request.fixed_stop(floating).is_none());
+    }
+
+    #[test]
+    fn test_reoptimize_live_request_defaults() {
+        let route_id = Uuid::new_v4();
+        let request: RouteReoptimizeLiveRequest = serde_json::from_value(serde_json::json!({
+            "routeId": route_id,
+            "currentTime": "13:15:00",
+            "completedStopIds": [route_id]
+        }))
+        .unwrap();
+
+        assert_eq!(request.current_time, NaiveTime::from_hms_opt(13, 15, 0).unwrap());
+        assert!(request.current_position.is_none());
+        assert_eq!(request.completed_stop_ids.len(), 1);
+        assert!(request.skipped_stop_ids.is_empty());
+    }
+
+    #[test]
+    fn test_validate_stop_notes() {
+        let task = |text: &str| RouteStopTask { text: text.to_string(), done: false };
+        assert!(validate_stop_notes(Some("bring 2 m flue liner"), &[task("check draft")]).is_ok());
+        assert!(validate_stop_notes(None, &[]).is_ok());
+        assert!(validate_stop_notes(Some(&"x".repeat(MAX_STOP_NOTES_LENGTH + 1)), &[]).is_err());
+        assert!(validate_stop_notes(None, &[task("  ")]).is_err());
+        assert!(validate_stop_notes(None, &vec![task("t"); MAX_STOP_TASKS + 1]).is_err());
+    }
+
+    #[test]
+    fn test_stop_task_done_defaults_to_false() {
+        let task: RouteStopTask = serde_json::from_str(r#"{"text":"check draft"}"#).unwrap();
+        assert!(!task.done);
+    }
+
     #[test]
     fn test_route_serializes_buffer_fields() {
         let route = Route {
@@ -316,4 +694,22 @@ mod tests {
         assert!(!json.contains("overrideServiceDurationMinutes"));
         assert!(!json.contains("overrideTravelDurationMinutes"));
     }
+
+    #[test]
+    fn test_break_location_validate_and_serialize() {
+        let location = BreakLocation {
+            name: "Restaurace U Lípy".to_string(),
+            kind: BreakPlaceKind::Restaurant,
+            lat: 49.2,
+            lng: 16.6,
+            address: None,
+        };
+        assert!(location.validate().is_ok());
+        assert!(BreakLocation { name: " ".to_string(), ..location.clone() }.validate().is_err());
+        assert!(BreakLocation { lat: 91.0, ..location.clone() }.validate().is_err());
+
+        let json = serde_json::to_value(BreakSuggestion { location, detour_km: 1.5 }).unwrap();
+        assert_eq!(json["kind"], "restaurant");
+        assert_eq!(json["detourKm"], 1.5);
+    }
 }
@@ -0,0 +1,288 @@
+#![allow(dead_code)]
+//! Actual-vs-planned route analysis types
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::Coordinates;
+
+/// Longest period one analysis may cover
+pub const ROUTE_ANALYSIS_MAX_DAYS: i64 = 366;
+/// Edge of a correction zone in degrees (about 11 × 7 km in CZ)
+pub const ZONE_SIZE_DEG: f64 = 0.1;
+/// Travel time correction factors are kept within this range
+pub const TRAVEL_FACTOR_MIN: f64 = 0.7;
+pub const TRAVEL_FACTOR_MAX: f64 = 1.5;
+
+/// NATS: sazinka.route.analysis
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteAnalysisRequest {
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    /// Only routes of this crew
+    pub crew_id: Option<Uuid>,
+    /// true stores the suggested correction factor and the zone factors
+    /// learned from the period for future planning, false removes them;
+    /// omitted leaves the stored correction as it is
+    pub apply_correction: Option<bool>,
+}
+
+impl RouteAnalysisRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.date_to < self.date_from {
+            return Err("dateTo must not be before dateFrom".to_string());
+        }
+        if (self.date_to - self.date_from).num_days() >= ROUTE_ANALYSIS_MAX_DAYS {
+            return Err(format!("period must be shorter than {} days", ROUTE_ANALYSIS_MAX_DAYS));
+        }
+        Ok(())
+    }
+}
+
+/// Route stop with its recorded arrival and departure (local time)
+#[derive(Debug, Clone, FromRow)]
+pub struct StopActualRow {
+    pub route_id: Uuid,
+    pub route_date: NaiveDate,
+    pub stop_order: i32,
+    pub stop_type: String,
+    pub customer_id: Option<Uuid>,
+    pub customer_name: Option<String>,
+    pub estimated_arrival: Option<NaiveTime>,
+    pub duration_from_previous_minutes: Option<i32>,
+    pub distance_from_previous_km: Option<f64>,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub actual_arrival: Option<NaiveDateTime>,
+    pub actual_departure: Option<NaiveDateTime>,
+}
+
+/// Length class of a leg; short legs are mostly within towns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LegBand {
+    /// Under 5 km
+    Short,
+    /// 5 – 25 km
+    Medium,
+    /// Over 25 km
+    Long,
+}
+
+impl LegBand {
+    pub fn from_km(km: f64) -> Self {
+        if km < 5.0 {
+            LegBand::Short
+        } else if km <= 25.0 {
+            LegBand::Medium
+        } else {
+            LegBand::Long
+        }
+    }
+}
+
+/// Part of the day a leg starts in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeBand {
+    /// 6:00 – 9:00
+    MorningPeak,
+    /// 9:00 – 15:00
+    Midday,
+    /// 15:00 – 18:00
+    AfternoonPeak,
+    OffPeak,
+}
+
+impl TimeBand {
+    pub fn from_time(time: NaiveTime) -> Self {
+        match time.hour() {
+            6..=8 => TimeBand::MorningPeak,
+            9..=14 => TimeBand::Midday,
+            15..=17 => TimeBand::AfternoonPeak,
+            _ => TimeBand::OffPeak,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeBand::MorningPeak => "morning_peak",
+            TimeBand::Midday => "midday",
+            TimeBand::AfternoonPeak => "afternoon_peak",
+            TimeBand::OffPeak => "off_peak",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "morning_peak" => Some(TimeBand::MorningPeak),
+            "midday" => Some(TimeBand::Midday),
+            "afternoon_peak" => Some(TimeBand::AfternoonPeak),
+            "off_peak" => Some(TimeBand::OffPeak),
+            _ => None,
+        }
+    }
+}
+
+/// Grid cell of a correction zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoneCell {
+    pub lat: i32,
+    pub lng: i32,
+}
+
+impl ZoneCell {
+    pub fn of(lat: f64, lng: f64) -> Self {
+        Self {
+            lat: (lat / ZONE_SIZE_DEG).floor() as i32,
+            lng: (lng / ZONE_SIZE_DEG).floor() as i32,
+        }
+    }
+
+    /// Zone a leg is attributed to: the one containing its midpoint
+    pub fn of_leg(from: Coordinates, to: Coordinates) -> Self {
+        Self::of((from.lat + to.lat) / 2.0, (from.lng + to.lng) / 2.0)
+    }
+}
+
+/// Learned travel time correction of one zone and time band
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TravelTimeFactor {
+    pub zone_lat: i32,
+    pub zone_lng: i32,
+    pub time_band: String,
+    /// Applied factor, `raw_ratio` limited to the allowed range
+    pub factor: f64,
+    /// Measured actual / planned driving time
+    pub raw_ratio: f64,
+    pub legs: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TravelTimeFactor {
+    pub fn cell(&self) -> ZoneCell {
+        ZoneCell { lat: self.zone_lat, lng: self.zone_lng }
+    }
+
+    /// The measured ratio was outside the allowed range
+    pub fn capped(&self) -> bool {
+        !(TRAVEL_FACTOR_MIN..=TRAVEL_FACTOR_MAX).contains(&self.raw_ratio)
+    }
+}
+
+/// Planned vs actual arrival at one stop
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopDeviation {
+    pub route_id: Uuid,
+    pub date: NaiveDate,
+    pub stop_order: i32,
+    pub customer_id: Option<Uuid>,
+    pub customer_name: Option<String>,
+    pub planned_arrival: NaiveTime,
+    pub actual_arrival: NaiveTime,
+    /// Positive when the crew arrived later than planned
+    pub delay_minutes: i64,
+}
+
+/// Planned vs actual driving time of one leg between two customer stops
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegDeviation {
+    pub route_id: Uuid,
+    pub date: NaiveDate,
+    /// Order of the stop the leg ends at
+    pub to_stop_order: i32,
+    pub planned_minutes: i64,
+    pub actual_minutes: i64,
+    pub distance_km: Option<f64>,
+    pub band: Option<LegBand>,
+    /// None when either end has no coordinates
+    pub zone: Option<ZoneCell>,
+    pub time_band: TimeBand,
+}
+
+/// Aggregated driving time bias of a set of legs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TravelBias {
+    /// None for the aggregate over all legs
+    pub band: Option<LegBand>,
+    pub legs: usize,
+    pub planned_minutes: i64,
+    pub actual_minutes: i64,
+    /// actual / planned; 1.12 = routing underestimates by 12 %
+    pub ratio: f64,
+}
+
+/// Response of sazinka.route.analysis
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteAnalysisResponse {
+    pub stops: Vec<StopDeviation>,
+    pub legs: Vec<LegDeviation>,
+    pub mean_delay_minutes: Option<f64>,
+    pub overall: Option<TravelBias>,
+    pub by_band: Vec<TravelBias>,
+    /// Correction factor backed by enough legs, if any
+    pub suggested_factor: Option<f64>,
+    /// Factor currently applied to route planning
+    pub applied_factor: Option<f64>,
+    /// Learned zone and time-of-day factors currently applied
+    pub zone_factors: Vec<TravelTimeFactor>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(from: &str, to: &str) -> RouteAnalysisRequest {
+        RouteAnalysisRequest {
+            date_from: from.parse().unwrap(),
+            date_to: to.parse().unwrap(),
+            crew_id: None,
+            apply_correction: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_period() {
+        assert!(request("2025-01-01", "2025-03-31").validate().is_ok());
+        assert!(request("2025-01-01", "2025-01-01").validate().is_ok());
+        assert!(request("2025-02-01", "2025-01-01").validate().is_err());
+        assert!(request("2024-01-01", "2025-06-01").validate().is_err());
+    }
+
+    #[test]
+    fn test_leg_band_from_km() {
+        assert_eq!(LegBand::from_km(1.2), LegBand::Short);
+        assert_eq!(LegBand::from_km(5.0), LegBand::Medium);
+        assert_eq!(LegBand::from_km(25.0), LegBand::Medium);
+        assert_eq!(LegBand::from_km(40.0), LegBand::Long);
+    }
+
+    #[test]
+    fn test_time_band_from_time() {
+        let at = |h: u32, m: u32| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert_eq!(TimeBand::from_time(at(7, 30)), TimeBand::MorningPeak);
+        assert_eq!(TimeBand::from_time(at(9, 0)), TimeBand::Midday);
+        assert_eq!(TimeBand::from_time(at(17, 59)), TimeBand::AfternoonPeak);
+        assert_eq!(TimeBand::from_time(at(5, 0)), TimeBand::OffPeak);
+        for band in [TimeBand::MorningPeak, TimeBand::Midday, TimeBand::AfternoonPeak, TimeBand::OffPeak] {
+            assert_eq!(TimeBand::parse(band.as_str()), Some(band));
+        }
+    }
+
+    #[test]
+    fn test_zone_cell_of_leg() {
+        let brno = Coordinates { lat: 49.195, lng: 16.608 };
+        assert_eq!(ZoneCell::of(brno.lat, brno.lng), ZoneCell { lat: 491, lng: 166 });
+        let west = Coordinates { lat: 49.15, lng: 16.55 };
+        assert_eq!(ZoneCell::of_leg(brno, west), ZoneCell { lat: 491, lng: 165 });
+    }
+}
@@ -0,0 +1,99 @@
+#![allow(dead_code)]
+//! Route edit lock types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+pub const DEFAULT_ROUTE_LOCK_TTL_SECONDS: u32 = 300;
+pub const MIN_ROUTE_LOCK_TTL_SECONDS: u32 = 30;
+pub const MAX_ROUTE_LOCK_TTL_SECONDS: u32 = 3600;
+
+/// Request for sazinka.route.lock (acquire or renew)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteLockRequest {
+    pub route_id: Uuid,
+    /// Lock lifetime; renew before it runs out
+    #[serde(default)]
+    pub ttl_seconds: Option<u32>,
+}
+
+impl RouteLockRequest {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self
+            .ttl_seconds
+            .is_some_and(|ttl| !(MIN_ROUTE_LOCK_TTL_SECONDS..=MAX_ROUTE_LOCK_TTL_SECONDS).contains(&ttl))
+        {
+            return Err("ttlSeconds must be between 30 and 3600");
+        }
+        Ok(())
+    }
+
+    pub fn ttl(&self) -> u32 {
+        self.ttl_seconds.unwrap_or(DEFAULT_ROUTE_LOCK_TTL_SECONDS)
+    }
+}
+
+/// Request for sazinka.route.unlock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteUnlockRequest {
+    pub route_id: Uuid,
+}
+
+/// An active edit lock
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteLock {
+    pub route_id: Uuid,
+    /// Dispatcher editing the route
+    pub holder_id: Uuid,
+    pub holder_name: Option<String>,
+    pub acquired_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl RouteLock {
+    /// Whether the lock keeps `user_id` from saving
+    pub fn blocks(&self, user_id: Uuid, now: DateTime<Utc>) -> bool {
+        self.holder_id != user_id && self.expires_at > now
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteUnlockResponse {
+    pub released: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_blocks_only_other_holders_until_expiry() {
+        let holder = Uuid::new_v4();
+        let now = Utc::now();
+        let lock = RouteLock {
+            route_id: Uuid::new_v4(),
+            holder_id: holder,
+            holder_name: None,
+            acquired_at: now,
+            expires_at: now + chrono::Duration::seconds(60),
+        };
+        assert!(!lock.blocks(holder, now));
+        assert!(lock.blocks(Uuid::new_v4(), now));
+        assert!(!lock.blocks(Uuid::new_v4(), now + chrono::Duration::seconds(61)));
+    }
+
+    #[test]
+    fn test_lock_request_ttl() {
+        let request = |ttl| RouteLockRequest { route_id: Uuid::new_v4(), ttl_seconds: ttl };
+        assert_eq!(request(None).ttl(), DEFAULT_ROUTE_LOCK_TTL_SECONDS);
+        assert!(request(Some(30)).validate().is_ok());
+        assert!(request(Some(29)).validate().is_err());
+        assert!(request(Some(3601)).validate().is_err());
+    }
+}
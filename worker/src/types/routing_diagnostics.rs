@@ -0,0 +1,112 @@
+#![allow(dead_code)]
+//! Routing data (Valhalla tile) diagnostics types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::services::geocoding::BoundingBox;
+
+/// Health of the routing data in one region
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RegionHealth {
+    /// Sample routes look plausible
+    Ok,
+    /// Sample points cannot be routed to at all (tiles missing or not built)
+    TilesMissing,
+    /// Routes exist but many legs are implausible (detours, impossible speeds)
+    LowQuality,
+}
+
+/// Why a diagnosis was run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosisTrigger {
+    /// Requested by an admin
+    Manual,
+    /// Unusual rate of matrix failures in a region
+    MatrixFailures,
+}
+
+/// Probe result of one grid cell of the diagnosed area
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionDiagnosis {
+    pub bounds: BoundingBox,
+    /// Sample points probed in the cell
+    pub samples: usize,
+    /// Probed legs from or to the cell's samples
+    pub legs: usize,
+    pub unreachable_legs: usize,
+    /// Legs that exist but failed the matrix sanity checks
+    pub suspicious_legs: usize,
+    pub health: RegionHealth,
+}
+
+/// Result of probing Valhalla across an area
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValhallaDiagnosis {
+    pub diagnosed_at: DateTime<Utc>,
+    pub trigger: DiagnosisTrigger,
+    /// Account whose customers were sampled (manual diagnoses)
+    pub user_id: Option<Uuid>,
+    /// Area covered by the samples
+    pub bounds: Option<BoundingBox>,
+    pub regions: Vec<RegionDiagnosis>,
+    /// Why the area could not be probed, if it could not
+    pub error: Option<String>,
+}
+
+impl ValhallaDiagnosis {
+    /// Regions with missing or low-quality routing data
+    pub fn problem_regions(&self) -> impl Iterator<Item = &RegionDiagnosis> {
+        self.regions.iter().filter(|r| r.health != RegionHealth::Ok)
+    }
+}
+
+/// Request for sazinka.admin.valhalla.diagnose
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValhallaDiagnoseRequest {
+    /// Account to sample; the caller's account when omitted
+    pub user_id: Option<Uuid>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnosis_serializes_to_camel_case() {
+        let diagnosis = ValhallaDiagnosis {
+            diagnosed_at: DateTime::from_timestamp(0, 0).unwrap(),
+            trigger: DiagnosisTrigger::MatrixFailures,
+            user_id: None,
+            bounds: Some(BoundingBox::new(49.0, 14.0, 50.0, 15.0)),
+            regions: vec![RegionDiagnosis {
+                bounds: BoundingBox::new(49.0, 14.0, 49.5, 14.5),
+                samples: 2,
+                legs: 12,
+                unreachable_legs: 12,
+                suspicious_legs: 0,
+                health: RegionHealth::TilesMissing,
+            }],
+            error: None,
+        };
+
+        let json = serde_json::to_value(&diagnosis).unwrap();
+        assert_eq!(json["trigger"], "matrixFailures");
+        assert_eq!(json["bounds"]["minLat"], 49.0);
+        assert_eq!(json["regions"][0]["health"], "tilesMissing");
+        assert_eq!(json["regions"][0]["unreachableLegs"], 12);
+        assert_eq!(diagnosis.problem_regions().count(), 1);
+    }
+
+    #[test]
+    fn test_diagnose_request_user_is_optional() {
+        let request: ValhallaDiagnoseRequest = serde_json::from_str("{}").unwrap();
+        assert!(request.user_id.is_none());
+    }
+}
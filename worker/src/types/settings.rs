@@ -95,6 +95,8 @@ pub struct BusinessInfo {
     pub country: Option<String>,
     /// Company-level locale for emails and external communication (e.g. "en", "cs").
     pub company_locale: String,
+    /// ISO 4217 currency of billed amounts (e.g. "CZK", "EUR").
+    pub currency: String,
 }
 
 /// Email template settings
@@ -139,6 +141,28 @@ pub struct BreakSettings {
     pub break_latest_time: String,    // "HH:MM"
     pub break_min_km: f64,
     pub break_max_km: f64,
+    /// Suggest restaurants and fuel stations near the route for breaks
+    pub break_location_suggestions: bool,
+}
+
+/// Numbering series of revision documents (inspection reports)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionNumberingSettings {
+    pub enabled: bool,
+    pub prefix: String,
+    /// Digits of the sequence part
+    pub padding: i16,
+    /// Restart the sequence every year and put the year in the number
+    pub yearly_reset: bool,
+}
+
+/// Pattern of generated customer codes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomerCodeSettings {
+    /// e.g. "CUS-{YYYY}-{SEQ:4}"; empty turns generation off
+    pub pattern: String,
 }
 
 /// Combined user settings response
@@ -151,6 +175,8 @@ pub struct UserSettings {
     pub depots: Vec<Depot>,
     pub preferences: UserPreferences,
     pub break_settings: BreakSettings,
+    pub revision_numbering: RevisionNumberingSettings,
+    pub customer_codes: CustomerCodeSettings,
 }
 
 /// Update work constraints request
@@ -181,6 +207,9 @@ pub struct UpdateBusinessInfoRequest {
     pub country: Option<String>,
     /// Company-level locale for emails and external communication (e.g. "en", "cs").
     pub company_locale: Option<String>,
+    /// ISO 4217 currency of billed amounts (e.g. "CZK", "EUR").
+    #[serde(default)]
+    pub currency: Option<String>,
 }
 
 /// Update email templates request
@@ -215,6 +244,52 @@ pub struct UpdateBreakSettingsRequest {
     pub break_latest_time: Option<String>,
     pub break_min_km: Option<f64>,
     pub break_max_km: Option<f64>,
+    pub break_location_suggestions: Option<bool>,
+}
+
+/// Update revision numbering request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRevisionNumberingRequest {
+    pub enabled: Option<bool>,
+    pub prefix: Option<String>,
+    pub padding: Option<i16>,
+    pub yearly_reset: Option<bool>,
+}
+
+impl UpdateRevisionNumberingRequest {
+    /// Check the series format before it is stored
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if let Some(padding) = self.padding {
+            if !(1..=10).contains(&padding) {
+                return Err("Padding must be between 1 and 10 digits");
+            }
+        }
+        if let Some(ref prefix) = self.prefix {
+            if prefix.trim().chars().count() > 20 {
+                return Err("Prefix must be at most 20 characters");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Update customer code pattern request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCustomerCodeRequest {
+    pub pattern: String,
+}
+
+impl UpdateCustomerCodeRequest {
+    /// Check the pattern before it is stored (empty is allowed)
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let pattern = self.pattern.trim();
+        if pattern.is_empty() {
+            return Ok(());
+        }
+        super::customer_code::CustomerCodePattern::parse(pattern).map(|_| ())
+    }
 }
 
 /// Delete account request (GDPR data excise)
@@ -283,6 +358,12 @@ pub struct UserWithSettings {
     pub break_latest_time: NaiveTime,
     pub break_min_km: f64,
     pub break_max_km: f64,
+    pub break_location_suggestions: bool,
+    pub revision_numbering_enabled: bool,
+    pub revision_number_prefix: String,
+    pub revision_number_padding: i16,
+    pub revision_number_yearly_reset: bool,
+    pub customer_code_pattern: String,
     /// BCP-47 locale code (e.g. "en", "cs", "en-GB"). Default: "en".
     pub locale: String,
     /// Last-used arrival buffer percentage for new routes.
@@ -291,6 +372,8 @@ pub struct UserWithSettings {
     pub last_arrival_buffer_fixed_minutes: f64,
     /// Company-level locale for emails and external communication. Default: "cs".
     pub company_locale: String,
+    /// ISO 4217 currency of billed amounts. Default: "CZK".
+    pub currency: String,
     /// When the confirmation email template was last manually edited.
     pub email_confirmation_edited_at: Option<DateTime<Utc>>,
     /// When the reminder email template was last manually edited.
@@ -328,6 +411,7 @@ impl UserWithSettings {
             postal_code: self.postal_code.clone(),
             country: self.country.clone(),
             company_locale: self.company_locale.clone(),
+            currency: self.currency.clone(),
         }
     }
 
@@ -430,6 +514,24 @@ impl UserWithSettings {
             break_latest_time: self.break_latest_time.format("%H:%M").to_string(),
             break_min_km: self.break_min_km,
             break_max_km: self.break_max_km,
+            break_location_suggestions: self.break_location_suggestions,
+        }
+    }
+
+    /// Convert to revision numbering settings
+    pub fn to_revision_numbering(&self) -> RevisionNumberingSettings {
+        RevisionNumberingSettings {
+            enabled: self.revision_numbering_enabled,
+            prefix: self.revision_number_prefix.clone(),
+            padding: self.revision_number_padding,
+            yearly_reset: self.revision_number_yearly_reset,
+        }
+    }
+
+    /// Convert to customer code settings
+    pub fn to_customer_codes(&self) -> CustomerCodeSettings {
+        CustomerCodeSettings {
+            pattern: self.customer_code_pattern.clone(),
         }
     }
 }
@@ -512,12 +614,39 @@ S pozdravom,
 {{phone}}
 {{email}}"#;
 
+/// Default reminder email template - German
+pub const DEFAULT_REMINDER_EMAIL_TEMPLATE_DE: &str = r#"Sehr geehrte Damen und Herren,
+
+wir möchten Sie daran erinnern, dass die regelmäßige Revision Ihres Geräts {{device_type}} bald fällig ist.
+
+Geplanter Termin: {{due_date}}
+
+Bitte kontaktieren Sie uns, um einen Termin zu vereinbaren.
+
+Mit freundlichen Grüßen
+{{business_name}}
+{{phone}}
+{{email}}"#;
+
+/// Default confirmation email template - German
+pub const DEFAULT_CONFIRMATION_EMAIL_TEMPLATE_DE: &str = r#"Sehr geehrte Damen und Herren,
+
+wir bestätigen Ihren Termin am {{date}} um {{time}}.
+
+Adresse: {{address}}
+
+Falls Sie den Termin ändern möchten, kontaktieren Sie uns bitte.
+
+Mit freundlichen Grüßen
+{{companyName}}"#;
+
 /// Return the default reminder email template for the given locale.
 pub fn default_reminder_template(locale: &str) -> &'static str {
     let lang = locale.split('-').next().unwrap_or(locale);
     match lang {
         "cs" => DEFAULT_REMINDER_EMAIL_TEMPLATE_CS,
         "sk" => DEFAULT_REMINDER_EMAIL_TEMPLATE_SK,
+        "de" => DEFAULT_REMINDER_EMAIL_TEMPLATE_DE,
         _ => DEFAULT_REMINDER_EMAIL_TEMPLATE_EN,
     }
 }
@@ -528,6 +657,7 @@ pub fn default_confirmation_template(locale: &str) -> &'static str {
     match lang {
         "cs" => DEFAULT_CONFIRMATION_EMAIL_TEMPLATE_CS,
         "sk" => DEFAULT_CONFIRMATION_EMAIL_TEMPLATE_SK,
+        "de" => DEFAULT_CONFIRMATION_EMAIL_TEMPLATE_DE,
         _ => DEFAULT_CONFIRMATION_EMAIL_TEMPLATE_EN,
     }
 }
@@ -538,6 +668,7 @@ pub fn default_reminder_subject(locale: &str) -> String {
     match lang {
         "cs" => "Připomínka termínu - {{customerName}}".to_string(),
         "sk" => "Pripomienka termínu - {{customerName}}".to_string(),
+        "de" => "Terminerinnerung - {{customerName}}".to_string(),
         _ => "Appointment reminder - {{customerName}}".to_string(),
     }
 }
@@ -548,6 +679,7 @@ pub fn default_confirmation_subject(locale: &str) -> String {
     match lang {
         "cs" => "Potvrzení termínu - {{customerName}}".to_string(),
         "sk" => "Potvrdenie termínu - {{customerName}}".to_string(),
+        "de" => "Terminbestätigung - {{customerName}}".to_string(),
         _ => "Appointment confirmation - {{customerName}}".to_string(),
     }
 }
@@ -574,4 +706,34 @@ mod tests {
         assert!((parsed.last_arrival_buffer_percent - 15.0).abs() < f64::EPSILON);
         assert!((parsed.last_arrival_buffer_fixed_minutes - 5.0).abs() < f64::EPSILON);
     }
+
+    #[test]
+    fn test_revision_numbering_request_validation() {
+        let mut req = UpdateRevisionNumberingRequest {
+            enabled: Some(true),
+            prefix: Some("REV-".to_string()),
+            padding: Some(4),
+            yearly_reset: None,
+        };
+        assert!(req.validate().is_ok());
+
+        req.padding = Some(0);
+        assert!(req.validate().is_err());
+
+        req.padding = None;
+        req.prefix = Some("X".repeat(21));
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_customer_code_request_validation() {
+        let mut req = UpdateCustomerCodeRequest { pattern: "CUS-{YYYY}-{SEQ:4}".to_string() };
+        assert!(req.validate().is_ok());
+
+        req.pattern = "  ".to_string();
+        assert!(req.validate().is_ok());
+
+        req.pattern = "CUS-{YYYY}".to_string();
+        assert!(req.validate().is_err());
+    }
 }
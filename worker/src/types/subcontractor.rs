@@ -0,0 +1,437 @@
+#![allow(dead_code)]
+//! Subcontractor types
+//!
+//! A subcontractor is an external company that owns crews of the account.
+//! Routes and visits of its crews are its assigned work; its login sees that
+//! work without prices or customer financial data (ICO/DIC, notes, risk).
+//! Completed assigned visits are settled at the subcontractor's rates.
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::currency::{normalize_currency, SUPPORTED_CURRENCIES};
+
+/// Role of subcontractor logins (`users.role`)
+pub const SUBCONTRACTOR_ROLE: &str = "subcontractor";
+
+/// Rate that applies to visit types without a rate of their own
+pub const DEFAULT_RATE_VISIT_TYPE: &str = "default";
+
+pub const MAX_SUBCONTRACTOR_NAME_LEN: usize = 255;
+pub const MAX_COMPANY_ID_LEN: usize = 20;
+pub const MAX_PHONE_LEN: usize = 20;
+pub const MAX_VISIT_TYPE_LEN: usize = 30;
+/// Longest period of a settlement or assigned-work listing
+pub const MAX_PERIOD_DAYS: i64 = 366;
+
+/// Subcontractor with the crews it owns
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Subcontractor {
+    pub id: Uuid,
+    pub name: String,
+    pub company_id: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub is_active: bool,
+    /// Login of the subcontractor, if one was created
+    pub account_user_id: Option<Uuid>,
+    pub crew_ids: Vec<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Agreed price of one visit of a type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SubcontractorRate {
+    pub visit_type: String,
+    pub amount_minor: i64,
+    pub currency: String,
+}
+
+/// Assigned route as seen by the subcontractor
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignedRoute {
+    pub id: Uuid,
+    pub date: NaiveDate,
+    pub crew_id: Uuid,
+    pub crew_name: String,
+    pub status: String,
+    pub total_distance_km: Option<f64>,
+    pub total_duration_minutes: Option<i32>,
+    #[sqlx(skip)]
+    pub stops: Vec<AssignedStop>,
+}
+
+/// Stop of an assigned route: where to go and whom to ask for, nothing billed
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignedStop {
+    #[serde(skip)]
+    pub route_id: Uuid,
+    pub stop_order: i32,
+    pub visit_id: Option<Uuid>,
+    pub customer_name: Option<String>,
+    pub contact_person: Option<String>,
+    pub phone: Option<String>,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub estimated_arrival: Option<NaiveTime>,
+    pub estimated_departure: Option<NaiveTime>,
+    pub status: Option<String>,
+}
+
+/// Assigned visit as seen by the subcontractor
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignedVisit {
+    pub id: Uuid,
+    pub crew_id: Uuid,
+    pub crew_name: String,
+    pub scheduled_date: NaiveDate,
+    pub scheduled_time_start: Option<NaiveTime>,
+    pub scheduled_time_end: Option<NaiveTime>,
+    pub status: String,
+    pub visit_type: Option<String>,
+    pub result: Option<String>,
+    pub customer_name: Option<String>,
+    pub contact_person: Option<String>,
+    pub phone: Option<String>,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+}
+
+/// Completed visit of a subcontractor's crew, before rates are applied
+#[derive(Debug, Clone, FromRow)]
+pub struct SettlementVisit {
+    pub visit_id: Uuid,
+    pub scheduled_date: NaiveDate,
+    pub visit_type: Option<String>,
+    pub crew_name: String,
+    pub customer_name: Option<String>,
+    pub city: Option<String>,
+    pub result: Option<String>,
+}
+
+/// Settled visit; no amount when neither its type nor a default is rated
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementLine {
+    pub visit_id: Uuid,
+    pub scheduled_date: NaiveDate,
+    pub visit_type: Option<String>,
+    pub crew_name: String,
+    pub customer_name: Option<String>,
+    pub city: Option<String>,
+    pub result: Option<String>,
+    pub amount_minor: Option<i64>,
+    pub currency: Option<String>,
+}
+
+/// Sum of the rated visits in one currency
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementTotal {
+    pub currency: String,
+    pub visits: i64,
+    pub amount_minor: i64,
+}
+
+// ============================================================
+// Request / response types (NATS payloads)
+// ============================================================
+
+fn validate_contact(
+    name: Option<&str>,
+    company_id: Option<&str>,
+    email: Option<&str>,
+    phone: Option<&str>,
+) -> Result<(), String> {
+    if let Some(name) = name {
+        if name.trim().is_empty() || name.len() > MAX_SUBCONTRACTOR_NAME_LEN {
+            return Err(format!("name must have 1 to {} characters", MAX_SUBCONTRACTOR_NAME_LEN));
+        }
+    }
+    if company_id.is_some_and(|id| id.len() > MAX_COMPANY_ID_LEN) {
+        return Err(format!("companyId must have at most {} characters", MAX_COMPANY_ID_LEN));
+    }
+    if email.is_some_and(|email| email.len() > MAX_SUBCONTRACTOR_NAME_LEN || !email.contains('@')) {
+        return Err("email is not a valid address".to_string());
+    }
+    if phone.is_some_and(|phone| phone.len() > MAX_PHONE_LEN) {
+        return Err(format!("phone must have at most {} characters", MAX_PHONE_LEN));
+    }
+    Ok(())
+}
+
+fn validate_period(from_date: NaiveDate, to_date: NaiveDate) -> Result<(), String> {
+    if to_date < from_date {
+        return Err("toDate must not be before fromDate".to_string());
+    }
+    if (to_date - from_date).num_days() >= MAX_PERIOD_DAYS {
+        return Err(format!("period must be at most {} days", MAX_PERIOD_DAYS));
+    }
+    Ok(())
+}
+
+/// NATS: sazinka.subcontractor.create
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSubcontractorRequest {
+    pub name: String,
+    pub company_id: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    /// Crews of the account to hand over to the subcontractor
+    #[serde(default)]
+    pub crew_ids: Vec<Uuid>,
+}
+
+impl CreateSubcontractorRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_contact(
+            Some(&self.name),
+            self.company_id.as_deref(),
+            self.email.as_deref(),
+            self.phone.as_deref(),
+        )
+    }
+}
+
+/// NATS: sazinka.subcontractor.update
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSubcontractorRequest {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub company_id: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub is_active: Option<bool>,
+    /// Replaces the crews of the subcontractor when given
+    pub crew_ids: Option<Vec<Uuid>>,
+}
+
+impl UpdateSubcontractorRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_contact(
+            self.name.as_deref(),
+            self.company_id.as_deref(),
+            self.email.as_deref(),
+            self.phone.as_deref(),
+        )
+    }
+}
+
+/// NATS: sazinka.subcontractor.delete / sazinka.subcontractor.rates.get
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubcontractorIdRequest {
+    pub id: Uuid,
+}
+
+/// NATS: sazinka.subcontractor.account.create
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSubcontractorAccountRequest {
+    pub subcontractor_id: Uuid,
+    pub email: String,
+    pub password: String,
+    pub name: String,
+}
+
+/// Rate of a rate card update; currency defaults to the account currency
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubcontractorRateInput {
+    pub visit_type: String,
+    pub amount_minor: i64,
+    pub currency: Option<String>,
+}
+
+/// NATS: sazinka.subcontractor.rates.set (replaces the rate card)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetSubcontractorRatesRequest {
+    pub subcontractor_id: Uuid,
+    pub rates: Vec<SubcontractorRateInput>,
+}
+
+impl SetSubcontractorRatesRequest {
+    /// Validated rates with normalized currencies
+    pub fn validate(&self, account_currency: &str) -> Result<Vec<SubcontractorRate>, String> {
+        let mut rates: Vec<SubcontractorRate> = Vec::with_capacity(self.rates.len());
+        for rate in &self.rates {
+            let visit_type = rate.visit_type.trim();
+            if visit_type.is_empty() || visit_type.len() > MAX_VISIT_TYPE_LEN {
+                return Err(format!("visitType must have 1 to {} characters", MAX_VISIT_TYPE_LEN));
+            }
+            if rates.iter().any(|r| r.visit_type == visit_type) {
+                return Err(format!("visitType {} is rated twice", visit_type));
+            }
+            if rate.amount_minor < 0 {
+                return Err("amountMinor must not be negative".to_string());
+            }
+            let currency = match rate.currency.as_deref() {
+                Some(code) => normalize_currency(code)
+                    .ok_or_else(|| format!("currency must be one of: {}", SUPPORTED_CURRENCIES.join(", ")))?,
+                None => account_currency.to_string(),
+            };
+            rates.push(SubcontractorRate {
+                visit_type: visit_type.to_string(),
+                amount_minor: rate.amount_minor,
+                currency,
+            });
+        }
+        Ok(rates)
+    }
+}
+
+/// NATS: sazinka.subcontractor.my.routes / sazinka.subcontractor.my.visits
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignedWorkRequest {
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+}
+
+impl AssignedWorkRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_period(self.from_date, self.to_date)
+    }
+}
+
+/// NATS: sazinka.subcontractor.settlement
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementRequest {
+    /// Required for the account owner; a subcontractor gets its own
+    pub subcontractor_id: Option<Uuid>,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+}
+
+impl SettlementRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_period(self.from_date, self.to_date)
+    }
+}
+
+/// Response for sazinka.subcontractor.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListSubcontractorsResponse {
+    pub items: Vec<Subcontractor>,
+}
+
+/// Response for sazinka.subcontractor.rates.get / set
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubcontractorRatesResponse {
+    pub subcontractor_id: Uuid,
+    pub rates: Vec<SubcontractorRate>,
+}
+
+/// Response for sazinka.subcontractor.my.routes
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignedRoutesResponse {
+    pub routes: Vec<AssignedRoute>,
+}
+
+/// Response for sazinka.subcontractor.my.visits
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignedVisitsResponse {
+    pub visits: Vec<AssignedVisit>,
+}
+
+/// Response for sazinka.subcontractor.settlement
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementResponse {
+    pub subcontractor_id: Uuid,
+    pub subcontractor_name: String,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub lines: Vec<SettlementLine>,
+    pub totals: Vec<SettlementTotal>,
+    /// Completed visits no rate applies to
+    pub unrated_visits: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(visit_type: &str, amount_minor: i64, currency: Option<&str>) -> SubcontractorRateInput {
+        SubcontractorRateInput {
+            visit_type: visit_type.to_string(),
+            amount_minor,
+            currency: currency.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_rates_validate() {
+        let request = |rates| SetSubcontractorRatesRequest { subcontractor_id: Uuid::new_v4(), rates };
+
+        let rates = request(vec![rate(" revision ", 80000, None), rate("default", 50000, Some("eur"))])
+            .validate("CZK")
+            .unwrap();
+        assert_eq!(rates[0], SubcontractorRate { visit_type: "revision".into(), amount_minor: 80000, currency: "CZK".into() });
+        assert_eq!(rates[1].currency, "EUR");
+
+        assert!(request(vec![]).validate("CZK").unwrap().is_empty());
+        assert!(request(vec![rate("revision", 1, None), rate("revision", 2, None)]).validate("CZK").is_err());
+        assert!(request(vec![rate("revision", -1, None)]).validate("CZK").is_err());
+        assert!(request(vec![rate("", 1, None)]).validate("CZK").is_err());
+        assert!(request(vec![rate("revision", 1, Some("XYZ"))]).validate("CZK").is_err());
+    }
+
+    #[test]
+    fn test_subcontractor_validate() {
+        let request = |name: &str, email: Option<&str>| CreateSubcontractorRequest {
+            name: name.to_string(),
+            company_id: Some("12345678".to_string()),
+            email: email.map(str::to_string),
+            phone: None,
+            crew_ids: vec![],
+        };
+        assert!(request("Revize Novák s.r.o.", Some("novak@example.com")).validate().is_ok());
+        assert!(request("  ", None).validate().is_err());
+        assert!(request("Novák", Some("novak")).validate().is_err());
+
+        let update = UpdateSubcontractorRequest {
+            id: Uuid::new_v4(),
+            name: None,
+            company_id: None,
+            email: None,
+            phone: Some("+420 777 123 456 789 000".to_string()),
+            is_active: None,
+            crew_ids: None,
+        };
+        assert!(update.validate().is_err());
+    }
+
+    #[test]
+    fn test_period_validate() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+        let request = |from_date, to_date| AssignedWorkRequest { from_date, to_date };
+        assert!(request(date(3, 1), date(3, 31)).validate().is_ok());
+        assert!(request(date(3, 1), date(3, 1)).validate().is_ok());
+        assert!(request(date(3, 2), date(3, 1)).validate().is_err());
+        let year_later = NaiveDate::from_ymd_opt(2027, 3, 2).unwrap();
+        assert!(request(date(3, 1), year_later).validate().is_err());
+    }
+}
@@ -0,0 +1,121 @@
+#![allow(dead_code)]
+//! Account subscription state types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::subjects;
+
+/// Subscription states
+pub const SUBSCRIPTION_TRIAL: &str = "trial";
+pub const SUBSCRIPTION_ACTIVE: &str = "active";
+/// Payment failed; the account stays readable until billing recovers
+pub const SUBSCRIPTION_PAST_DUE: &str = "past_due";
+pub const SUBSCRIPTION_CANCELLED: &str = "cancelled";
+
+pub const SUBSCRIPTION_STATES: &[&str] =
+    &[SUBSCRIPTION_TRIAL, SUBSCRIPTION_ACTIVE, SUBSCRIPTION_PAST_DUE, SUBSCRIPTION_CANCELLED];
+
+/// Core NATS subject carrying subscription transitions
+pub const SUBSCRIPTION_CHANGED_SUBJECT: &str = subjects::events::SUBSCRIPTION_CHANGED;
+/// Event type inside the envelope
+pub const SUBSCRIPTION_CHANGED_EVENT: &str = "subscription.changed";
+
+/// Whether accounts in this state may only read data
+pub fn is_read_only_state(status: &str) -> bool {
+    matches!(status, SUBSCRIPTION_PAST_DUE | SUBSCRIPTION_CANCELLED)
+}
+
+/// Subscription state of an account
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionInfo {
+    pub user_id: Uuid,
+    pub status: String,
+    pub changed_at: Option<DateTime<Utc>>,
+    pub billing_customer_ref: Option<String>,
+    #[sqlx(skip)]
+    pub read_only: bool,
+}
+
+impl SubscriptionInfo {
+    pub fn with_read_only(mut self) -> Self {
+        self.read_only = is_read_only_state(&self.status);
+        self
+    }
+}
+
+/// Request to set the subscription state of an account.
+///
+/// The account is addressed either by user id or by the billing system's
+/// customer reference; when both are given the reference is stored.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetSubscriptionRequest {
+    pub user_id: Option<Uuid>,
+    pub billing_customer_ref: Option<String>,
+    pub status: String,
+    /// Free text from the billing system (e.g. "invoice.payment_failed")
+    pub reason: Option<String>,
+}
+
+/// Request to read the subscription state of an account
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSubscriptionRequest {
+    pub user_id: Option<Uuid>,
+    pub billing_customer_ref: Option<String>,
+}
+
+/// Data of a `subscription.changed` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionChangedEvent {
+    pub user_id: Uuid,
+    pub from: String,
+    pub to: String,
+    pub billing_customer_ref: Option<String>,
+    pub reason: Option<String>,
+    pub read_only: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only_states() {
+        assert!(!is_read_only_state(SUBSCRIPTION_TRIAL));
+        assert!(!is_read_only_state(SUBSCRIPTION_ACTIVE));
+        assert!(is_read_only_state(SUBSCRIPTION_PAST_DUE));
+        assert!(is_read_only_state(SUBSCRIPTION_CANCELLED));
+    }
+
+    #[test]
+    fn test_set_subscription_request_by_billing_ref() {
+        let req: SetSubscriptionRequest = serde_json::from_str(
+            r#"{"billingCustomerRef":"cus_123","status":"past_due","reason":"invoice.payment_failed"}"#,
+        )
+        .unwrap();
+        assert!(req.user_id.is_none());
+        assert_eq!(req.billing_customer_ref.as_deref(), Some("cus_123"));
+        assert_eq!(req.status, SUBSCRIPTION_PAST_DUE);
+    }
+
+    #[test]
+    fn test_changed_event_camel_case() {
+        let event = SubscriptionChangedEvent {
+            user_id: Uuid::nil(),
+            from: SUBSCRIPTION_ACTIVE.to_string(),
+            to: SUBSCRIPTION_PAST_DUE.to_string(),
+            billing_customer_ref: None,
+            reason: None,
+            read_only: true,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["readOnly"], true);
+        assert_eq!(json["from"], "active");
+    }
+}
@@ -0,0 +1,24 @@
+#![allow(dead_code)]
+//! Telemetry opt-in types
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::telemetry::TelemetryPayload;
+
+/// Response for sazinka.settings.telemetry.get / set
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetrySettingsResponse {
+    pub enabled: bool,
+    /// Where data is sent; None when the deployment has telemetry disabled
+    pub endpoint: Option<String>,
+    /// Exactly what this account currently contributes to the next report
+    pub preview: TelemetryPayload,
+}
+
+/// NATS: sazinka.settings.telemetry.set
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTelemetryRequest {
+    pub enabled: bool,
+}
@@ -0,0 +1,107 @@
+#![allow(dead_code)]
+//! Language versions of customer-facing templates
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+pub const TEMPLATE_KIND_EMAIL_CONFIRMATION: &str = "email_confirmation";
+pub const TEMPLATE_KIND_EMAIL_REMINDER: &str = "email_reminder";
+pub const TEMPLATE_KIND_SMS_CONFIRMATION: &str = "sms_confirmation";
+pub const TEMPLATE_KIND_SMS_REMINDER: &str = "sms_reminder";
+pub const TEMPLATE_KINDS: &[&str] = &[
+    TEMPLATE_KIND_EMAIL_CONFIRMATION,
+    TEMPLATE_KIND_EMAIL_REMINDER,
+    TEMPLATE_KIND_SMS_CONFIRMATION,
+    TEMPLATE_KIND_SMS_REMINDER,
+];
+
+/// Languages customers can be addressed in
+pub const CUSTOMER_LANGUAGES: &[&str] = &["cs", "sk", "en", "de"];
+
+/// Normalize a language tag ("de-AT" → "de"); None when it is not supported
+pub fn normalize_language(tag: &str) -> Option<String> {
+    let lang = tag.trim().split(['-', '_']).next().unwrap_or("").to_lowercase();
+    CUSTOMER_LANGUAGES.contains(&lang.as_str()).then_some(lang)
+}
+
+/// Language version of a customer template
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateTranslation {
+    pub id: Uuid,
+    pub template_kind: String,
+    pub language: String,
+    pub subject: Option<String>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// NATS: sazinka.settings.templates.upsert
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertTemplateTranslationRequest {
+    pub template_kind: String,
+    pub language: String,
+    pub subject: Option<String>,
+    pub body: String,
+}
+
+impl UpsertTemplateTranslationRequest {
+    /// Validate the payload, returning the normalized language
+    pub fn validate(&self) -> Result<String, String> {
+        if !TEMPLATE_KINDS.contains(&self.template_kind.as_str()) {
+            return Err(format!("templateKind must be one of: {}", TEMPLATE_KINDS.join(", ")));
+        }
+        if self.body.trim().is_empty() {
+            return Err("body must not be empty".to_string());
+        }
+        normalize_language(&self.language)
+            .ok_or_else(|| format!("language must be one of: {}", CUSTOMER_LANGUAGES.join(", ")))
+    }
+}
+
+/// NATS: sazinka.settings.templates.delete
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteTemplateTranslationRequest {
+    pub template_kind: String,
+    pub language: String,
+}
+
+/// Response for sazinka.settings.templates.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTemplateTranslationsResponse {
+    pub translations: Vec<TemplateTranslation>,
+    pub languages: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_language() {
+        assert_eq!(normalize_language("de-AT").as_deref(), Some("de"));
+        assert_eq!(normalize_language(" CS ").as_deref(), Some("cs"));
+        assert_eq!(normalize_language("fr"), None);
+        assert_eq!(normalize_language(""), None);
+    }
+
+    #[test]
+    fn test_upsert_validation() {
+        let mut req = UpsertTemplateTranslationRequest {
+            template_kind: TEMPLATE_KIND_EMAIL_REMINDER.to_string(),
+            language: "de_DE".to_string(),
+            subject: None,
+            body: "Sehr geehrte Damen und Herren".to_string(),
+        };
+        assert_eq!(req.validate(), Ok("de".to_string()));
+
+        req.template_kind = "letter".to_string();
+        assert!(req.validate().is_err());
+    }
+}
@@ -54,6 +54,12 @@ pub struct User {
     /// BCP-47 locale code (e.g. "en", "cs", "en-GB"). Default: "en".
     pub locale: String,
     
+    // Admin management
+    #[sqlx(default)]
+    pub disabled_at: Option<DateTime<Utc>>,
+    #[sqlx(default)]
+    pub last_login_at: Option<DateTime<Utc>>,
+    
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -158,7 +158,7 @@ pub struct UpdateVisitRequest {
 }
 
 /// Request to complete a visit
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CompleteVisitRequest {
     pub id: Uuid,
@@ -0,0 +1,158 @@
+//! Visit duration types
+//!
+//! Progress events of the crew app stamp a visit's arrival ("arrived") and
+//! departure ("completed"); the time between them is the on-site time, the
+//! time since the crew's previous departure that day the travel time. Both
+//! can be overridden by hand.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+pub const PROGRESS_ARRIVED: &str = "arrived";
+pub const PROGRESS_COMPLETED: &str = "completed";
+
+/// Longer gaps before an arrival are breaks, not travel
+pub const MAX_TRAVEL_MINUTES: i64 = 180;
+/// Upper bound of a manual override (one working day)
+pub const MAX_OVERRIDE_MINUTES: i32 = 24 * 60;
+
+/// NATS: sazinka.visit.progress
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VisitProgressRequest {
+    pub visit_id: Uuid,
+    /// "arrived" or "completed"
+    pub event: String,
+    /// When it happened (defaults to now, for events queued offline)
+    pub at: Option<DateTime<Utc>>,
+}
+
+impl VisitProgressRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.event != PROGRESS_ARRIVED && self.event != PROGRESS_COMPLETED {
+            return Err(format!("event must be {} or {}", PROGRESS_ARRIVED, PROGRESS_COMPLETED));
+        }
+        Ok(())
+    }
+}
+
+/// NATS: sazinka.visit.duration.get
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VisitDurationRequest {
+    pub visit_id: Uuid,
+}
+
+/// NATS: sazinka.visit.duration.set
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetVisitDurationRequest {
+    pub visit_id: Uuid,
+    /// Override of the travel time (absent = unchanged)
+    pub travel_minutes: Option<i32>,
+    /// Override of the on-site time (absent = unchanged)
+    pub on_site_minutes: Option<i32>,
+    /// Drop both overrides and go back to the progress events
+    #[serde(default)]
+    pub reset: bool,
+}
+
+impl SetVisitDurationRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        for minutes in [self.travel_minutes, self.on_site_minutes].into_iter().flatten() {
+            if !(0..=MAX_OVERRIDE_MINUTES).contains(&minutes) {
+                return Err(format!("minutes must be between 0 and {}", MAX_OVERRIDE_MINUTES));
+            }
+        }
+        if self.reset && (self.travel_minutes.is_some() || self.on_site_minutes.is_some()) {
+            return Err("reset cannot be combined with minutes".to_string());
+        }
+        if !self.reset && self.travel_minutes.is_none() && self.on_site_minutes.is_none() {
+            return Err("travelMinutes, onSiteMinutes or reset is required".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Travel and on-site time of a visit
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct VisitDuration {
+    pub visit_id: Uuid,
+    pub travel_minutes: Option<i32>,
+    pub on_site_minutes: Option<i32>,
+    pub travel_overridden: bool,
+    pub on_site_overridden: bool,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Progress timestamps a visit's durations are derived from
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct VisitTimestamps {
+    pub actual_arrival: Option<DateTime<Utc>>,
+    pub actual_departure: Option<DateTime<Utc>>,
+    /// Latest departure of the same crew that day before this arrival
+    pub previous_departure: Option<DateTime<Utc>>,
+}
+
+/// NATS: sazinka.visit.duration.learned
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LearnedDurationsRequest {
+    /// Only this visit type
+    pub visit_type: Option<String>,
+}
+
+/// Service duration learned from the on-site times of completed visits
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LearnedServiceDuration {
+    pub visit_type: String,
+    pub samples: usize,
+    pub median_minutes: i32,
+    /// Covers four visits out of five; a safe planning value
+    pub p80_minutes: i32,
+}
+
+/// Response for sazinka.visit.duration.learned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LearnedDurationsResponse {
+    pub window_days: i64,
+    pub items: Vec<LearnedServiceDuration>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_validate() {
+        let request: VisitProgressRequest =
+            serde_json::from_str(&format!(r#"{{"visitId": "{}", "event": "arrived"}}"#, Uuid::new_v4())).unwrap();
+        assert!(request.at.is_none());
+        assert!(request.validate().is_ok());
+
+        let request = VisitProgressRequest { event: "started".to_string(), ..request };
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn test_set_duration_validate() {
+        let set = |travel: Option<i32>, on_site: Option<i32>, reset: bool| SetVisitDurationRequest {
+            visit_id: Uuid::new_v4(),
+            travel_minutes: travel,
+            on_site_minutes: on_site,
+            reset,
+        };
+        assert!(set(Some(15), None, false).validate().is_ok());
+        assert!(set(None, Some(45), false).validate().is_ok());
+        assert!(set(None, None, true).validate().is_ok());
+        assert!(set(None, None, false).validate().is_err());
+        assert!(set(Some(10), None, true).validate().is_err());
+        assert!(set(Some(-1), None, false).validate().is_err());
+        assert!(set(None, Some(MAX_OVERRIDE_MINUTES + 1), false).validate().is_err());
+    }
+}
@@ -0,0 +1,80 @@
+#![allow(dead_code)]
+//! Weather recorded per visit
+//!
+//! Daily weather at the customer on the day of the visit, as returned by an
+//! Open-Meteo compatible API (WMO weather codes).
+
+use serde::{Deserialize, Serialize};
+
+/// Coarse weather of a day, derived from its WMO weather code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherCondition {
+    Clear,
+    Cloudy,
+    Fog,
+    Rain,
+    Snow,
+    Storm,
+}
+
+impl WeatherCondition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Clear => "clear",
+            Self::Cloudy => "cloudy",
+            Self::Fog => "fog",
+            Self::Rain => "rain",
+            Self::Snow => "snow",
+            Self::Storm => "storm",
+        }
+    }
+
+    /// Condition of a WMO weather code; freezing drizzle and rain count as rain
+    pub fn from_wmo_code(code: i32) -> Option<Self> {
+        match code {
+            0 | 1 => Some(Self::Clear),
+            2 | 3 => Some(Self::Cloudy),
+            45 | 48 => Some(Self::Fog),
+            51..=67 | 80..=82 => Some(Self::Rain),
+            71..=77 | 85 | 86 => Some(Self::Snow),
+            95..=99 => Some(Self::Storm),
+            _ => None,
+        }
+    }
+}
+
+/// Weather of one day at one place
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DailyWeather {
+    pub weather_code: Option<i32>,
+    pub temperature_min_c: Option<f64>,
+    pub temperature_max_c: Option<f64>,
+    pub precipitation_mm: Option<f64>,
+    pub snowfall_cm: Option<f64>,
+    pub wind_max_kmh: Option<f64>,
+}
+
+impl DailyWeather {
+    pub fn condition(&self) -> Option<WeatherCondition> {
+        self.weather_code.and_then(WeatherCondition::from_wmo_code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_condition_from_wmo_code() {
+        assert_eq!(WeatherCondition::from_wmo_code(0), Some(WeatherCondition::Clear));
+        assert_eq!(WeatherCondition::from_wmo_code(3), Some(WeatherCondition::Cloudy));
+        assert_eq!(WeatherCondition::from_wmo_code(48), Some(WeatherCondition::Fog));
+        assert_eq!(WeatherCondition::from_wmo_code(66), Some(WeatherCondition::Rain));
+        assert_eq!(WeatherCondition::from_wmo_code(81), Some(WeatherCondition::Rain));
+        assert_eq!(WeatherCondition::from_wmo_code(75), Some(WeatherCondition::Snow));
+        assert_eq!(WeatherCondition::from_wmo_code(86), Some(WeatherCondition::Snow));
+        assert_eq!(WeatherCondition::from_wmo_code(99), Some(WeatherCondition::Storm));
+        assert_eq!(WeatherCondition::from_wmo_code(10), None);
+    }
+}
@@ -0,0 +1,217 @@
+#![allow(dead_code)]
+//! Webhook event types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Shape of an outgoing event body
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookPayloadFormat {
+    /// `{ id, type, occurredAt, data: { … } }` — nested entity under `data`
+    #[default]
+    Envelope,
+    /// Single-level object for no-code tools (Make.com, Zapier):
+    /// metadata and entity fields side by side, nested keys joined with `_`
+    Flat,
+}
+
+/// One entry of the event catalog
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEventTypeInfo {
+    pub event_type: String,
+    pub entity: String,
+    pub description: String,
+    pub sample_payload: serde_json::Value,
+}
+
+/// NATS: sazinka.webhook.event_types
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListWebhookEventTypesRequest {
+    /// Format used for the sample payloads (defaults to envelope)
+    #[serde(default)]
+    pub format: Option<WebhookPayloadFormat>,
+}
+
+/// Response for sazinka.webhook.event_types
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListWebhookEventTypesResponse {
+    pub format: WebhookPayloadFormat,
+    pub event_types: Vec<WebhookEventTypeInfo>,
+}
+
+impl WebhookPayloadFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WebhookPayloadFormat::Envelope => "envelope",
+            WebhookPayloadFormat::Flat => "flat",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "flat" => WebhookPayloadFormat::Flat,
+            _ => WebhookPayloadFormat::Envelope,
+        }
+    }
+}
+
+// ============================================================
+// Registered endpoints and deliveries
+// ============================================================
+
+pub const WEBHOOK_DELIVERY_PENDING: &str = "pending";
+pub const WEBHOOK_DELIVERY_DELIVERED: &str = "delivered";
+pub const WEBHOOK_DELIVERY_FAILED: &str = "failed";
+
+/// Event type of deliveries sent by sazinka.webhook.test
+pub const WEBHOOK_TEST_EVENT: &str = "webhook.test";
+
+/// Endpoints an account may register
+pub const MAX_WEBHOOKS_PER_USER: i64 = 20;
+const MAX_WEBHOOK_URL_LEN: usize = 2000;
+
+/// Registered endpoint (the secret is only returned when it is created)
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    #[serde(skip)]
+    pub secret: String,
+    /// Empty: every event
+    pub event_types: Vec<String>,
+    pub format: String,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    /// Outcome of the most recent delivery
+    #[sqlx(default)]
+    pub last_delivery_status: Option<String>,
+    #[sqlx(default)]
+    pub last_delivery_at: Option<DateTime<Utc>>,
+}
+
+/// Delivery queued for the delivery processor, with its endpoint
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingWebhookDelivery {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub url: String,
+    pub secret: String,
+}
+
+/// Job on the webhook delivery stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedWebhookDelivery {
+    pub delivery_id: Uuid,
+}
+
+/// NATS: sazinka.webhook.create
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Event types from sazinka.webhook.event_types; empty or absent for all
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    #[serde(default)]
+    pub format: WebhookPayloadFormat,
+    pub description: Option<String>,
+}
+
+impl CreateWebhookRequest {
+    /// `known_event_types` is the event catalog
+    pub fn validate(&self, known_event_types: &[&str]) -> Result<(), String> {
+        let url = self.url.trim();
+        if !url.starts_with("https://") || url.len() <= "https://".len() {
+            return Err("url must be an https:// address".to_string());
+        }
+        if url.len() > MAX_WEBHOOK_URL_LEN || url.chars().any(char::is_whitespace) {
+            return Err("url is not valid".to_string());
+        }
+        if let Some(unknown) = self.event_types.iter().find(|t| !known_event_types.contains(&t.as_str())) {
+            return Err(format!("unknown event type: {}", unknown));
+        }
+        Ok(())
+    }
+}
+
+/// Response for sazinka.webhook.create - the only time the secret is shown
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateWebhookResponse {
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+/// Response for sazinka.webhook.list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListWebhooksResponse {
+    pub webhooks: Vec<Webhook>,
+}
+
+/// NATS: sazinka.webhook.delete and sazinka.webhook.test
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookIdRequest {
+    pub id: Uuid,
+}
+
+/// Response for sazinka.webhook.test - the delivery is sent asynchronously
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestWebhookResponse {
+    pub delivery_id: Uuid,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KNOWN: &[&str] = &["customer.created", "visit.updated"];
+
+    fn request(url: &str, event_types: &[&str]) -> CreateWebhookRequest {
+        CreateWebhookRequest {
+            url: url.to_string(),
+            event_types: event_types.iter().map(|t| t.to_string()).collect(),
+            format: WebhookPayloadFormat::Envelope,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_webhook_url() {
+        assert!(request("https://hooks.example.com/sazinka", &[]).validate(KNOWN).is_ok());
+        assert!(request("http://hooks.example.com", &[]).validate(KNOWN).is_err());
+        assert!(request("https://", &[]).validate(KNOWN).is_err());
+        assert!(request("https://example.com/a b", &[]).validate(KNOWN).is_err());
+    }
+
+    #[test]
+    fn test_validate_webhook_event_types() {
+        assert!(request("https://example.com", &["visit.updated"]).validate(KNOWN).is_ok());
+        assert_eq!(
+            request("https://example.com", &["visit.exploded"]).validate(KNOWN),
+            Err("unknown event type: visit.exploded".to_string())
+        );
+    }
+
+    #[test]
+    fn test_create_request_defaults() {
+        let req: CreateWebhookRequest = serde_json::from_str(r#"{"url": "https://example.com"}"#).unwrap();
+        assert!(req.event_types.is_empty());
+        assert_eq!(req.format, WebhookPayloadFormat::Envelope);
+        assert_eq!(WebhookPayloadFormat::parse(req.format.as_str()), req.format);
+    }
+}
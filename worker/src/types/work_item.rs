@@ -10,6 +10,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use super::inventory::WorkItemMaterialInput;
+
 /// Work type enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[serde(rename_all = "snake_case")]
@@ -78,6 +80,16 @@ impl WorkResult {
             _ => None,
         }
     }
+
+    /// Work result recorded for a completed revision's result
+    pub fn from_revision_result(result: &str) -> Option<Self> {
+        match result {
+            "passed" => Some(WorkResult::Successful),
+            "conditional" => Some(WorkResult::Partial),
+            "failed" => Some(WorkResult::Failed),
+            _ => None,
+        }
+    }
 }
 
 /// Visit work item entity
@@ -97,6 +109,18 @@ pub struct VisitWorkItem {
     pub requires_follow_up: bool,
     pub follow_up_reason: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// Net amount in minor units of `currency`
+    #[sqlx(default)]
+    pub amount_minor: Option<i64>,
+    /// VAT rate in percent (0, 12, 21)
+    #[sqlx(default)]
+    pub vat_rate: Option<i32>,
+    /// 'cash', 'card', 'transfer' or 'other'
+    #[sqlx(default)]
+    pub payment_method: Option<String>,
+    /// ISO 4217 currency of the amount; set whenever the item is billed
+    #[sqlx(default)]
+    pub currency: Option<String>,
 }
 
 /// Request to create a work item
@@ -114,6 +138,18 @@ pub struct CreateWorkItemRequest {
     pub findings: Option<String>,
     pub requires_follow_up: Option<bool>,
     pub follow_up_reason: Option<String>,
+    #[serde(default)]
+    pub amount_minor: Option<i64>,
+    #[serde(default)]
+    pub vat_rate: Option<i32>,
+    #[serde(default)]
+    pub payment_method: Option<String>,
+    /// Defaults to the visit's billing currency, then the account currency
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// Catalog materials used; taken from the crew's stock on completion
+    #[serde(default)]
+    pub materials: Vec<WorkItemMaterialInput>,
 }
 
 /// Request to complete a work item
@@ -127,6 +163,18 @@ pub struct CompleteWorkItemRequest {
     pub findings: Option<String>,
     pub requires_follow_up: Option<bool>,
     pub follow_up_reason: Option<String>,
+    #[serde(default)]
+    pub amount_minor: Option<i64>,
+    #[serde(default)]
+    pub vat_rate: Option<i32>,
+    #[serde(default)]
+    pub payment_method: Option<String>,
+    /// Defaults to the visit's billing currency, then the account currency
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// Catalog materials used; taken from the crew's stock on completion
+    #[serde(default)]
+    pub materials: Vec<WorkItemMaterialInput>,
 }
 
 /// Request to list work items for a visit
@@ -278,6 +326,7 @@ mod tests {
         assert_eq!(req.result, WorkResult::Partial);
         assert_eq!(req.duration_minutes, Some(30));
         assert_eq!(req.requires_follow_up, Some(true));
+        assert!(req.amount_minor.is_none());
     }
 
     #[test]
@@ -296,6 +345,10 @@ mod tests {
             requires_follow_up: false,
             follow_up_reason: None,
             created_at: Utc::now(),
+            amount_minor: Some(150_000),
+            vat_rate: Some(21),
+            payment_method: Some("cash".to_string()),
+            currency: Some("CZK".to_string()),
         };
 
         let json = serde_json::to_string(&item).unwrap();
@@ -305,6 +358,14 @@ mod tests {
         assert!(json.contains("\"requiresFollowUp\":false"));
     }
 
+    #[test]
+    fn test_work_result_from_revision_result() {
+        assert_eq!(WorkResult::from_revision_result("passed"), Some(WorkResult::Successful));
+        assert_eq!(WorkResult::from_revision_result("conditional"), Some(WorkResult::Partial));
+        assert_eq!(WorkResult::from_revision_result("failed"), Some(WorkResult::Failed));
+        assert_eq!(WorkResult::from_revision_result("unknown"), None);
+    }
+
     #[test]
     fn test_list_work_items_request_deserialize() {
         let json = r#"{"visitId": "123e4567-e89b-12d3-a456-426614174000"}"#;